@@ -0,0 +1,234 @@
+//! Walks through the whole lifecycle of a `ComputeOffer` with the building blocks the
+//! `ProviderOfferingClient` wraps: the offer is built with a `ComputeOfferBuilder`, signed,
+//! validated the way the server validates it, submitted, then tracked with a
+//! `ComputeOfferTracker` until a requester bids on it and the proof resolving it lands on-chain.
+use alloy::dyn_abi::DynSolValue;
+use alloy::network::EthereumWallet;
+use alloy::primitives::{address, fixed_bytes, Bytes, FixedBytes, U256};
+use alloy::providers::ProviderBuilder;
+use alloy::signers::local::PrivateKeySigner;
+use alloy::signers::Signer;
+use alloy::sol_types::SolValue;
+use color_eyre::eyre::eyre;
+use color_eyre::Result;
+use dotenv::dotenv;
+use std::env;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+use taralli_client::api::submit::SubmitApiClient;
+use taralli_client::client::provider::control::{init_log_control, serve_log_filter};
+use taralli_client::client::provider::offering::sign_offer;
+use taralli_client::error::ClientError;
+use taralli_client::intent_builder::offer::ComputeOfferBuilder;
+use taralli_client::intent_builder::IntentBuilder;
+use taralli_client::resolver::{
+    offer::{ComputeOfferResolver, OfferResolveParams},
+    IntentResolver,
+};
+use taralli_client::tracker::{offer::ComputeOfferTracker, IntentAuctionTracker};
+use taralli_client::worker::ComputeWorker;
+use taralli_primitives::abi::universal_porchetta::VerifierDetails;
+use taralli_primitives::intents::ComputeIntent;
+use taralli_primitives::markets::{Network, SEPOLIA_UNIVERSAL_PORCHETTA_ADDRESS};
+use taralli_primitives::systems::sp1::{
+    Sp1Config, Sp1Mode, Sp1ProofParams, Sp1VerifierConstraints,
+};
+use taralli_primitives::systems::SystemId;
+use taralli_primitives::validation::offer::{
+    validate_offer_submission, ComputeOfferValidator, OfferValidationConfig,
+    OfferVerifierConstraints,
+};
+use taralli_primitives::validation::{BaseValidationConfig, IntentValidator};
+use taralli_worker::sp1::local::Sp1LocalProver;
+use taralli_worker::sp1::Sp1Worker;
+use url::Url;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // setup tracing for client execution, its filter being switchable while running
+    let log_filter = env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string());
+    let log_control = init_log_control(&log_filter)?;
+
+    // Load environment variables from the `.env` file
+    dotenv().ok();
+
+    // switch the log filter from a local endpoint, when configured
+    if let Ok(control_address) = env::var("CONTROL_ADDRESS") {
+        let control_address: SocketAddr = control_address.parse()?;
+        tokio::spawn(async move {
+            if let Err(e) = serve_log_filter(log_control, control_address).await {
+                tracing::error!("control endpoint failed: {e}");
+            }
+        });
+    }
+
+    let server_url = Url::parse(&env::var("SERVER_URL")?)?; // local server instance
+    let rpc_url = Url::parse(&env::var("RPC_URL")?)?; // testnet
+    let priv_key = &env::var("PROVIDER_PRIVATE_KEY")?; // private key
+
+    // system workload data
+    let sp1_program_path = Path::new("./contracts/test-proof-data/sp1/fibonacci-program");
+    // proof input(s)
+    let inputs = 1000u32;
+    // load elf binary
+    let elf = std::fs::read(sp1_program_path)?;
+
+    // proof commitment data
+    let reward_token_address = address!("b54061f59AcF94f86ee414C9a220aFFE8BbE6B35");
+    let reward_token_decimals = 18u8;
+    let reward_amount = U256::from(10); // 10 wei of tokens
+    let stake_token_address = address!("b54061f59AcF94f86ee414C9a220aFFE8BbE6B35");
+    let stake_token_decimals = 18u8;
+    let stake_amount = U256::from(1); // 1 wei of tokens
+    let proving_time = 60u32; // 1 min
+    let auction_length = 90u32; // 1.5 min
+
+    // SP1 sepolia groth16 verifier
+    let verifier_address = address!("E780809121774D06aD9B0EEeC620fF4B3913Ced1");
+    // verifyProof(bytes32 programVKey,bytes calldata publicValues,bytes calldata proofBytes)
+    let verify_function_selector: FixedBytes<4> = fixed_bytes!("41493c60");
+    // offset and length to extract inputs field
+    let inputs_offset = U256::from(0);
+    let inputs_length = U256::from(64);
+    // uses sha
+    let is_sha_commitment = true;
+
+    // network
+    let network = Network::Sepolia;
+    let market_address = SEPOLIA_UNIVERSAL_PORCHETTA_ADDRESS;
+
+    // signer
+    let signer = PrivateKeySigner::from_str(priv_key)?;
+
+    // build wallet for sending txs
+    let wallet = EthereumWallet::new(signer.clone());
+
+    // build rpc provider
+    let rpc_provider = ProviderBuilder::new()
+        .with_recommended_fillers()
+        .wallet(wallet)
+        .on_http(rpc_url);
+
+    // the checks the server runs on submitted offers
+    let validation_config = OfferValidationConfig {
+        base: BaseValidationConfig::default(),
+        minimum_allowed_stake: U256::from(1), // 1 wei of tokens
+        maximum_allowed_reward: U256::from(100000000000000000000u128), // 100 tokens
+    };
+    let verifier_constraints: OfferVerifierConstraints =
+        Sp1VerifierConstraints::for_network(network).into();
+
+    // system inputs
+    let proof_info = serde_json::to_value(Sp1ProofParams {
+        elf,
+        inputs: inputs.to_le_bytes().to_vec(),
+        config: Sp1Config {
+            mode: Sp1Mode::Groth16,
+        },
+    })?;
+
+    // load verification commitments
+    let public_inputs_commitment_preimage =
+        DynSolValue::Tuple(vec![DynSolValue::Bytes(inputs.to_le_bytes().to_vec())]);
+
+    // build proof commitment's verifier details
+    let verifier_details = VerifierDetails {
+        verifier: verifier_address,
+        selector: verify_function_selector,
+        isShaCommitment: is_sha_commitment,
+        inputsOffset: inputs_offset,
+        inputsLength: inputs_length,
+    };
+    // set extra_data = abi encoded verifier details
+    let extra_data = Bytes::from(VerifierDetails::abi_encode(&verifier_details));
+
+    // 1. build
+    let builder = ComputeOfferBuilder::new(
+        rpc_provider.clone(),
+        signer.address(),
+        market_address,
+        SystemId::Sp1,
+    )
+    .auction_length(auction_length)
+    .reward_token_address(reward_token_address)
+    .reward_token_decimals(reward_token_decimals)
+    .set_new_nonce()
+    .await?
+    .set_token_params(
+        reward_amount,
+        stake_token_address,
+        stake_token_decimals,
+        stake_amount,
+    )
+    .proving_time(proving_time)
+    .system(proof_info)
+    // hashed with the function declared by `isShaCommitment`
+    .set_verification_commitment_preimage(
+        &public_inputs_commitment_preimage.abi_encode(),
+        extra_data,
+    )?
+    .set_auction_timestamps_from_auction_length()
+    .await?;
+
+    // check the offer against the server's validation before signing it
+    builder
+        .validate_against(&validation_config, &verifier_constraints)
+        .await?;
+    let compute_offer = builder.build()?;
+
+    // 2. sign over the permit2 digest of the rpc provider's chain
+    let chain_id = builder.chain_id().await?;
+    let signed_offer = sign_offer(&signer, compute_offer, chain_id).await?;
+
+    // 3. validate the signed offer, signature included
+    ComputeOfferValidator::new(validation_config, verifier_constraints).validate(
+        &signed_offer,
+        signed_offer.proof_offer.startAuctionTimestamp,
+        &market_address,
+    )?;
+    let offer_id = signed_offer.compute_id();
+    println!("signed offer {offer_id}: {:?}", signed_offer.proof_offer);
+
+    // 4. start tracking the auction before submitting, so no bid is missed
+    let auction_tracker = ComputeOfferTracker::new(rpc_provider.clone(), market_address);
+    let auction = tokio::spawn(async move {
+        auction_tracker
+            .track_auction(offer_id, Duration::from_secs(u64::from(auction_length)))
+            .await
+    });
+
+    let response = SubmitApiClient::new(server_url)
+        .submit_intent(signed_offer.clone())
+        .await?;
+    if !response.status().is_success() {
+        return Err(ClientError::from_server_response(response).await.into());
+    }
+    tracing::info!("offer submitted, waiting for a bid");
+
+    // 5. once bid upon, prove and resolve the offer
+    let bid = auction
+        .await??
+        .ok_or_else(|| eyre!("no bid on offer {offer_id} before the auction ended"))?;
+    tracing::info!("offer bid upon: {:?}", bid);
+
+    let worker = Sp1Worker::new(Sp1LocalProver::new(false, sp1_sdk::SP1ProofMode::Groth16));
+    let work_result = worker.execute(&signed_offer).await?;
+    // a submission failing the market's commitment check would only waste the resolve gas
+    validate_offer_submission(&signed_offer.proof_offer, &work_result.opaque_submission)?;
+
+    let resolver = ComputeOfferResolver::new(rpc_provider.clone(), market_address);
+    resolver
+        .resolve_intent(offer_id, &OfferResolveParams::from(work_result))
+        .await?;
+
+    // 6. confirm the resolution from the market's events
+    let tracker = ComputeOfferTracker::new(rpc_provider.clone(), market_address);
+    let (resolve, tx_hash) = tracker
+        .track_resolve_with_tx_hash(offer_id, Duration::from_secs(u64::from(proving_time)))
+        .await?
+        .ok_or_else(|| eyre!("offer {offer_id} not resolved within its proving time"))?;
+    println!("offer resolved in transaction {tx_hash}: {resolve:?}");
+    Ok(())
+}
@@ -1,8 +1,7 @@
 use alloy::dyn_abi::DynSolValue;
 use alloy::network::EthereumWallet;
-use alloy::primitives::{address, fixed_bytes, Bytes, FixedBytes, B256, U256};
+use alloy::primitives::{address, fixed_bytes, Bytes, FixedBytes, U256};
 use alloy::providers::ProviderBuilder;
-use alloy::signers::k256::sha2::Sha256;
 use alloy::signers::local::PrivateKeySigner;
 use alloy::sol_types::SolValue;
 use color_eyre::Result;
@@ -10,9 +9,11 @@ use dotenv::dotenv;
 use sha3::Digest;
 use sp1_sdk::network::FulfillmentStrategy;
 use std::env;
+use std::net::SocketAddr;
 use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
+use taralli_client::client::provider::control::{init_log_control, serve_log_filter};
 use taralli_client::client::provider::offering::ProviderOfferingClient;
 use taralli_client::intent_builder::IntentBuilder;
 use taralli_primitives::abi::universal_porchetta::VerifierDetails;
@@ -23,22 +24,29 @@ use taralli_primitives::systems::sp1::{
 use taralli_primitives::systems::SystemId;
 use taralli_primitives::validation::offer::OfferValidationConfig;
 use taralli_primitives::validation::BaseValidationConfig;
-use taralli_worker::sp1::remote::Sp1RemoteProver;
+use taralli_worker::sp1::remote::{Sp1RemoteConfig, Sp1RemoteProver};
 use taralli_worker::sp1::Sp1Worker;
-use tracing::Level;
-use tracing_subscriber::EnvFilter;
 use url::Url;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // setup tracing for client execution
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .with_max_level(Level::INFO)
-        .init();
+    // setup tracing for client execution, its filter being switchable while running
+    let log_filter = env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string());
+    let log_control = init_log_control(&log_filter)?;
 
     // Load environment variables from the `.env` file
     dotenv().ok();
+
+    // switch the log filter from a local endpoint, when configured
+    if let Ok(control_address) = env::var("CONTROL_ADDRESS") {
+        let control_address: SocketAddr = control_address.parse()?;
+        tokio::spawn(async move {
+            if let Err(e) = serve_log_filter(log_control, control_address).await {
+                tracing::error!("control endpoint failed: {e}");
+            }
+        });
+    }
+
     let server_url = Url::parse(&env::var("SERVER_URL")?)?; // local server instance
     let rpc_url = Url::parse(&env::var("RPC_URL")?)?; // testnet
     let priv_key = &env::var("PROVIDER_PRIVATE_KEY")?; // private key
@@ -95,11 +103,9 @@ async fn main() -> Result<()> {
 
     // setup prover
     let sp1_prover = Sp1RemoteProver::new(
-        priv_key,
-        succint_rpc_url,
-        FulfillmentStrategy::Auction,
-        sp1_sdk::SP1ProofMode::Groth16,
-        true,
+        Sp1RemoteConfig::new(priv_key.as_str(), succint_rpc_url.as_str())
+            .with_fulfillment_strategy(FulfillmentStrategy::Auction)
+            .with_skip_simulation(true),
     );
     // setup sp1 compute worker
     let worker = Arc::new(Sp1Worker::new(sp1_prover));
@@ -139,9 +145,6 @@ async fn main() -> Result<()> {
     // load verification commitments
     let public_inputs_commitment_preimage =
         DynSolValue::Tuple(vec![DynSolValue::Bytes(inputs.to_le_bytes().to_vec())]);
-    let public_inputs_commitment_digest =
-        Sha256::digest(public_inputs_commitment_preimage.abi_encode());
-    let public_inputs_commitment = B256::from_slice(public_inputs_commitment_digest.as_slice());
 
     // build proof commitment's verifier details
     let verifier_details = VerifierDetails {
@@ -166,7 +169,11 @@ async fn main() -> Result<()> {
         )
         .proving_time(proving_time)
         .system(proof_info)
-        .set_verification_commitment_params(public_inputs_commitment, extra_data)
+        // hashed with the function declared by `isShaCommitment`
+        .set_verification_commitment_preimage(
+            &public_inputs_commitment_preimage.abi_encode(),
+            extra_data,
+        )?
         .set_auction_timestamps_from_auction_length()
         .await?
         .build()?; // convert ComputeOfferBuilder into ComputeOffer
@@ -0,0 +1,83 @@
+use alloy::network::EthereumWallet;
+use alloy::primitives::U256;
+use alloy::providers::ProviderBuilder;
+use alloy::signers::local::PrivateKeySigner;
+use color_eyre::Result;
+use dotenv::dotenv;
+use std::env;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use taralli_client::client::provider::control::{init_log_control, serve_log_filter};
+use taralli_client::client::requester::bidding::RequesterBiddingClient;
+use taralli_primitives::markets::SEPOLIA_UNIVERSAL_PORCHETTA_ADDRESS;
+use taralli_primitives::systems::SystemId;
+use taralli_primitives::validation::offer::{OfferValidationConfig, OfferVerifierConstraints};
+use taralli_primitives::validation::BaseValidationConfig;
+use url::Url;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // setup tracing for client execution, its filter being switchable while running
+    let log_filter = env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string());
+    let log_control = init_log_control(&log_filter)?;
+
+    // Load environment variables from the `.env` file
+    dotenv().ok();
+
+    // switch the log filter from a local endpoint, when configured
+    if let Ok(control_address) = env::var("CONTROL_ADDRESS") {
+        let control_address: SocketAddr = control_address.parse()?;
+        tokio::spawn(async move {
+            if let Err(e) = serve_log_filter(log_control, control_address).await {
+                tracing::error!("control endpoint failed: {e}");
+            }
+        });
+    }
+
+    let server_url = Url::parse(&env::var("SERVER_URL")?)?; // local server instance
+    let rpc_url = Url::parse(&env::var("RPC_URL")?)?; // testnet
+    let priv_key = &env::var("REQUESTER_PRIVATE_KEY")?; // private key
+
+    // build signer
+    let signer = PrivateKeySigner::from_str(priv_key)?;
+    // build wallet for sending txs
+    let wallet = EthereumWallet::new(signer.clone());
+    // build rpc provider
+    let rpc_provider = ProviderBuilder::new()
+        .with_recommended_fillers()
+        .wallet(wallet)
+        .on_http(rpc_url);
+
+    // validation config to check selected offers are correct
+    let validation_config = OfferValidationConfig {
+        base: BaseValidationConfig::default(),
+        minimum_allowed_stake: U256::from(1), // 1 wei of tokens
+        maximum_allowed_reward: U256::from(100000000000000000000u128), // 100 tokens
+    };
+
+    // instantiate requester bidding client
+    let requester = RequesterBiddingClient::new(
+        server_url,
+        rpc_provider,
+        signer,
+        SEPOLIA_UNIVERSAL_PORCHETTA_ADDRESS,
+        SystemId::Arkworks,
+        validation_config,
+        OfferVerifierConstraints::default(),
+    );
+
+    // find an active arkworks offer on the server
+    let offer = requester.search().await?;
+
+    tracing::info!("selected offer proof commitment: {:?}", offer.proof_offer);
+
+    // bid on the offer and wait for the provider to deliver the proof
+    let (resolve_event, opaque_submission) = requester.bid_and_await_result(offer).await?;
+
+    tracing::info!(
+        "offer resolved by {}, delivered submission: {}",
+        resolve_event.resolver,
+        opaque_submission
+    );
+    Ok(())
+}
@@ -1,7 +1,6 @@
 use alloy::dyn_abi::DynSolValue;
-use alloy::primitives::{address, fixed_bytes, Bytes, FixedBytes, B256, U256};
+use alloy::primitives::{address, fixed_bytes, Bytes, FixedBytes, U256};
 use alloy::providers::ProviderBuilder;
-use alloy::signers::k256::sha2::Sha256;
 use alloy::signers::local::PrivateKeySigner;
 use alloy::sol_types::SolValue;
 use color_eyre::Result;
@@ -11,11 +10,14 @@ use sha3::Digest;
 use std::env;
 use std::fs::File;
 use std::io::BufReader;
+use std::net::SocketAddr;
 use std::path::Path;
 use std::str::FromStr;
+use taralli_client::client::provider::control::{init_log_control, serve_log_filter};
 use taralli_client::client::requester::requesting::RequesterRequestingClient;
 use taralli_client::intent_builder::IntentBuilder;
 use taralli_primitives::abi::universal_bombetta::VerifierDetails;
+use taralli_primitives::commitment::{ARKWORKS_PUBLIC_INPUTS_OFFSET, ARKWORKS_PUBLIC_INPUT_LENGTH};
 use taralli_primitives::markets::SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS;
 use taralli_primitives::systems::arkworks::ArkworksProofParams;
 use taralli_primitives::systems::SystemId;
@@ -23,20 +25,27 @@ use taralli_primitives::validation::request::{
     RequestValidationConfig, RequestVerifierConstraints,
 };
 use taralli_primitives::validation::BaseValidationConfig;
-use tracing::Level;
-use tracing_subscriber::EnvFilter;
 use url::Url;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // setup tracing for client execution
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .with_max_level(Level::INFO)
-        .init();
+    // setup tracing for client execution, its filter being switchable while running
+    let log_filter = env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string());
+    let log_control = init_log_control(&log_filter)?;
 
     // Load environment variables from the `.env` file
     dotenv().ok();
+
+    // switch the log filter from a local endpoint, when configured
+    if let Ok(control_address) = env::var("CONTROL_ADDRESS") {
+        let control_address: SocketAddr = control_address.parse()?;
+        tokio::spawn(async move {
+            if let Err(e) = serve_log_filter(log_control, control_address).await {
+                tracing::error!("control endpoint failed: {e}");
+            }
+        });
+    }
+
     let server_url = Url::parse(&env::var("SERVER_URL")?)?; // local server instance
     let rpc_url = Url::parse(&env::var("RPC_URL")?)?; // testnet
     let priv_key = &env::var("REQUESTER_PRIVATE_KEY")?; // private key
@@ -72,9 +81,9 @@ async fn main() -> Result<()> {
     let verifier_address = address!("558D8D2f90c085A8Ed704084716F2797AAB26cC6");
     // verifyProof(uint256[2] calldata _pA,uint256[2][2] calldata _pB,uint256[2] calldata _pC,uint256[1] calldata _pubSignals)
     let verify_function_selector: FixedBytes<4> = fixed_bytes!("43753b4d");
-    // offset and length to extract inputs field
-    let inputs_offset = U256::from(256);
-    let inputs_length = U256::from(32);
+    // offset and length of the single public signal within the submission the worker formats
+    let inputs_offset = U256::from(ARKWORKS_PUBLIC_INPUTS_OFFSET);
+    let inputs_length = U256::from(ARKWORKS_PUBLIC_INPUT_LENGTH);
     // uses keccak
     let is_sha_commitment = false;
     // no partial commitments used
@@ -96,8 +105,11 @@ async fn main() -> Result<()> {
     let validation_config = RequestValidationConfig {
         base: BaseValidationConfig::default(),
         maximum_allowed_stake: 10000000000000000000, // 10 ether
+        ..Default::default()
     };
 
+    let verifier_constraints = RequestVerifierConstraints::default();
+
     // instantiate requester requesting client
     let requester = RequesterRequestingClient::new(
         server_url,
@@ -105,9 +117,13 @@ async fn main() -> Result<()> {
         signer,
         SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
         SystemId::Arkworks,
-        validation_config,
-        RequestVerifierConstraints::default(),
-    );
+        validation_config.clone(),
+        verifier_constraints.clone(),
+    )
+    .with_server_bounds_check(true);
+
+    // fail fast on a misconfigured rpc, market or server
+    requester.healthcheck().await.into_result()?;
 
     // set intent builder defaults
     let builder_default = requester
@@ -130,10 +146,6 @@ async fn main() -> Result<()> {
     let u256_public_input = U256::from_str(public_input_str)?;
     let public_inputs_commitment_preimage =
         DynSolValue::Tuple(vec![DynSolValue::Uint(u256_public_input, 256)]);
-    // sha256(abi.encode(imageId, proofInputHash))
-    let public_inputs_commitment_digest =
-        Sha256::digest(public_inputs_commitment_preimage.abi_encode());
-    let public_inputs_commitment = B256::from_slice(public_inputs_commitment_digest.as_slice());
 
     // build proof commitment's verifier details
     let verifier_details = VerifierDetails {
@@ -151,16 +163,25 @@ async fn main() -> Result<()> {
     let extra_data = Bytes::from(VerifierDetails::abi_encode(&verifier_details));
 
     // finish building compute request
-    let compute_request = builder
+    let builder = builder
         .set_new_nonce()
         .await?
         .set_token_params(minimum_stake, min_reward_amount, max_reward_amount)
         .proving_time(proving_time)
         .system(proof_info)
-        .set_verification_commitment_params(public_inputs_commitment, extra_data)
+        // hashed with the function declared by `isShaCommitment`
+        .set_verification_commitment_preimage(
+            &public_inputs_commitment_preimage.abi_encode(),
+            extra_data,
+        )?
         .set_auction_timestamps_from_auction_length()
-        .await?
-        .build()?; // convert ComputeRequestBuilder into ComputeRequest
+        .await?;
+
+    // check the request against the server's validation before signing it
+    builder
+        .validate_against(&validation_config, &verifier_constraints)
+        .await?;
+    let compute_request = builder.build()?; // convert ComputeRequestBuilder into ComputeRequest
 
     // sign built compute request
     let signed_request = requester.sign(compute_request.clone()).await?;
@@ -174,8 +195,12 @@ async fn main() -> Result<()> {
     );
 
     // submit and track ComputeRequest
-    requester
-        .submit_and_track(signed_request, u64::from(auction_length))
+    let outcome = requester
+        .submit_and_track(signed_request, u64::from(auction_length), true)
         .await?;
+
+    if let Some(payload) = outcome.payload {
+        tracing::info!("delivered proof: {:?}", payload.proof);
+    }
     Ok(())
 }
@@ -1,37 +1,47 @@
 use alloy::dyn_abi::DynSolValue;
-use alloy::primitives::{address, fixed_bytes, Bytes, FixedBytes, B256, U256};
+use alloy::primitives::{address, fixed_bytes, Bytes, FixedBytes, U256};
 use alloy::providers::ProviderBuilder;
-use alloy::signers::k256::sha2::Sha256;
 use alloy::signers::local::PrivateKeySigner;
 use alloy::sol_types::SolValue;
 use color_eyre::Result;
 use dotenv::dotenv;
 use sha3::Digest;
 use std::env;
+use std::net::SocketAddr;
 use std::path::Path;
 use std::str::FromStr;
+use taralli_client::client::provider::control::{init_log_control, serve_log_filter};
 use taralli_client::client::requester::requesting::RequesterRequestingClient;
 use taralli_client::intent_builder::IntentBuilder;
 use taralli_primitives::abi::universal_bombetta::VerifierDetails;
 use taralli_primitives::markets::{Network, SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS};
 use taralli_primitives::systems::risc0::{Risc0ProofParams, Risc0VerifierConstraints};
 use taralli_primitives::systems::SystemId;
-use taralli_primitives::validation::request::RequestValidationConfig;
+use taralli_primitives::validation::request::{
+    RequestValidationConfig, RequestVerifierConstraints,
+};
 use taralli_primitives::validation::BaseValidationConfig;
-use tracing::Level;
-use tracing_subscriber::EnvFilter;
 use url::Url;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // setup tracing for client execution
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .with_max_level(Level::INFO)
-        .init();
+    // setup tracing for client execution, its filter being switchable while running
+    let log_filter = env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string());
+    let log_control = init_log_control(&log_filter)?;
 
     // Load environment variables from the `.env` file
     dotenv().ok();
+
+    // switch the log filter from a local endpoint, when configured
+    if let Ok(control_address) = env::var("CONTROL_ADDRESS") {
+        let control_address: SocketAddr = control_address.parse()?;
+        tokio::spawn(async move {
+            if let Err(e) = serve_log_filter(log_control, control_address).await {
+                tracing::error!("control endpoint failed: {e}");
+            }
+        });
+    }
+
     let server_url = Url::parse(&env::var("SERVER_URL")?)?; // local server instance
     let rpc_url = Url::parse(&env::var("RPC_URL")?)?; // testnet
     let priv_key = &env::var("REQUESTER_PRIVATE_KEY")?; // private key
@@ -86,8 +96,12 @@ async fn main() -> Result<()> {
     let validation_config = RequestValidationConfig {
         base: BaseValidationConfig::default(),
         maximum_allowed_stake: 10000000000000000000, // 10 ether
+        ..Default::default()
     };
 
+    let verifier_constraints: RequestVerifierConstraints =
+        Risc0VerifierConstraints::for_network(network).into();
+
     // instantiate requester requesting client
     let requester = RequesterRequestingClient::new(
         server_url,
@@ -95,9 +109,13 @@ async fn main() -> Result<()> {
         signer,
         SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
         SystemId::Risc0,
-        validation_config,
-        Risc0VerifierConstraints::for_network(network).into(),
-    );
+        validation_config.clone(),
+        verifier_constraints.clone(),
+    )
+    .with_server_bounds_check(true);
+
+    // fail fast on a misconfigured rpc, market or server
+    requester.healthcheck().await.into_result()?;
 
     // set intent builder defaults
     let builder_default = requester
@@ -118,9 +136,6 @@ async fn main() -> Result<()> {
         DynSolValue::FixedBytes(risc0_image_id, 32),
         DynSolValue::Uint(proof_input, 256),
     ]);
-    let public_inputs_commitment_digest =
-        Sha256::digest(public_inputs_commitment_preimage.abi_encode());
-    let public_inputs_commitment = B256::from_slice(public_inputs_commitment_digest.as_slice());
 
     // build proof commitment's verifier details
     let verifier_details = VerifierDetails {
@@ -138,16 +153,25 @@ async fn main() -> Result<()> {
     let extra_data = Bytes::from(VerifierDetails::abi_encode(&verifier_details));
 
     // finish building compute request
-    let compute_request = builder
+    let builder = builder
         .set_new_nonce()
         .await?
         .set_token_params(minimum_stake, min_reward_amount, max_reward_amount)
         .proving_time(proving_time)
         .system(proof_info)
-        .set_verification_commitment_params(public_inputs_commitment, extra_data)
+        // hashed with the function declared by `isShaCommitment`
+        .set_verification_commitment_preimage(
+            &public_inputs_commitment_preimage.abi_encode(),
+            extra_data,
+        )?
         .set_auction_timestamps_from_auction_length()
-        .await?
-        .build()?; // convert ComputeRequestBuilder into ComputeRequest
+        .await?;
+
+    // check the request against the server's validation before signing it
+    builder
+        .validate_against(&validation_config, &verifier_constraints)
+        .await?;
+    let compute_request = builder.build()?; // convert ComputeRequestBuilder into ComputeRequest
 
     // sign built compute request
     let signed_request = requester.sign(compute_request.clone()).await?;
@@ -156,9 +180,13 @@ async fn main() -> Result<()> {
     requester.validate_request(&signed_request)?;
 
     // submit and track ComputeRequest
-    requester
-        .submit_and_track(signed_request, u64::from(auction_length))
+    let outcome = requester
+        .submit_and_track(signed_request, u64::from(auction_length), true)
         .await?;
 
+    if let Some(payload) = outcome.payload {
+        tracing::info!("delivered proof: {:?}", payload.proof);
+    }
+
     Ok(())
 }
@@ -5,27 +5,36 @@ use color_eyre::Result;
 use dotenv::dotenv;
 use std::collections::HashMap;
 use std::env;
+use std::net::SocketAddr;
 use std::str::FromStr;
+use taralli_client::client::provider::control::{init_log_control, serve_log_filter};
 use taralli_client::client::requester::searching::RequesterSearchingClient;
 use taralli_primitives::alloy::primitives::U256;
 use taralli_primitives::markets::SEPOLIA_UNIVERSAL_PORCHETTA_ADDRESS;
 use taralli_primitives::systems::SystemId;
 use taralli_primitives::validation::offer::{OfferValidationConfig, OfferVerifierConstraints};
 use taralli_primitives::validation::BaseValidationConfig;
-use tracing::Level;
-use tracing_subscriber::EnvFilter;
 use url::Url;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // setup tracing for client execution
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .with_max_level(Level::INFO)
-        .init();
+    // setup tracing for client execution, its filter being switchable while running
+    let log_filter = env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string());
+    let log_control = init_log_control(&log_filter)?;
 
     // Load environment variables from the `.env` file
     dotenv().ok();
+
+    // switch the log filter from a local endpoint, when configured
+    if let Ok(control_address) = env::var("CONTROL_ADDRESS") {
+        let control_address: SocketAddr = control_address.parse()?;
+        tokio::spawn(async move {
+            if let Err(e) = serve_log_filter(log_control, control_address).await {
+                tracing::error!("control endpoint failed: {e}");
+            }
+        });
+    }
+
     let server_url = Url::parse(&env::var("SERVER_URL")?)?; // local server instance
     let rpc_url = Url::parse(&env::var("RPC_URL")?)?; // testnet
     let priv_key = &env::var("REQUESTER_PRIVATE_KEY")?; // private key
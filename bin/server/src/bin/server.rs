@@ -2,7 +2,7 @@ use alloy::providers::ProviderBuilder;
 use axum::{
     http::{Response, StatusCode},
     response::IntoResponse,
-    routing::{get, post},
+    routing::get,
     Router,
 };
 use color_eyre::{eyre::Context, Result};
@@ -10,35 +10,44 @@ use dotenv::dotenv;
 use serde_json::json;
 use std::{str::FromStr, sync::Arc, time::Duration};
 use taralli_primitives::env::Environment;
+use taralli_primitives::systems::SYSTEMS;
+use taralli_primitives::validation::CommonValidationConfig;
 use taralli_server::{
+    clock::SystemClock,
     config::Config,
+    gc::IntentGc,
+    log_control::LogControl,
     postgres::Db,
-    routes::{
-        query::get_active_intents_by_id_handler,
-        submit::{submit_offer_handler, submit_request_handler},
-        subscribe::websocket_subscribe_handler,
-    },
+    router,
+    routes::admin::AdminState,
     state::{offer::OfferState, request::RequestState, BaseState},
     subscription_manager::SubscriptionManager,
 };
 use tokio::net::TcpListener;
 use tower_http::trace::TraceLayer;
 use tracing::info;
-use tracing_subscriber::EnvFilter;
 use url::Url;
 
 /// Taralli protocol server
 /// Handles:
 /// - submission of compute intents
 /// - subscriptions thorugh websocket streams of compute intents across a given set of system IDs.
-/// - storage of compute intents
+/// - storage of compute intents, archived or pruned once over for longer than their retention
+/// - advertisement of provider capabilities
+/// - registration of compression dictionaries shared by submitted systems
+/// - negotiation of resolution deadline extensions between requesters and winning providers
+/// - reporting of server status (subscribers, capabilities, validation bounds, markets)
 #[tokio::main]
 async fn main() -> Result<()> {
     color_eyre::install()?;
     dotenv().ok();
 
-    // Load configuration json
-    let config = Config::from_file("config.json").context("Failed to load config")?;
+    // Load configuration json, overridden by the TARALLI_SERVER__ environment variables
+    let config = Config::load("config.json", std::env::vars()).context("Failed to load config")?;
+    if std::env::args().any(|arg| arg == "--print-config") {
+        println!("{}", config.redacted_json()?);
+        return Ok(());
+    }
     // Load rpc url used by the server's rpc provider (sepolia currently)
 
     let url_opt = std::env::var("RPC_URL");
@@ -48,11 +57,10 @@ async fn main() -> Result<()> {
     };
     let rpc_url = Url::from_str(&rpc_url_string).context("Invalid RPC URL")?;
 
-    // setup tracing
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .with_max_level(config.log_level()?)
-        .init();
+    // setup tracing, its filter switchable at runtime through the admin routes
+    let log_level = config.log_level()?.to_string().to_lowercase();
+    let log_filter = std::env::var("RUST_LOG").unwrap_or(log_level);
+    let log_control = LogControl::init(&log_filter)?;
 
     // Get the validation configs from the server config
     let validation_configs = config.get_validation_configs();
@@ -62,7 +70,14 @@ async fn main() -> Result<()> {
 
     // setup subscription manager
     tracing::info!("Setting up subscription manager");
-    let subscription_manager: SubscriptionManager = Default::default();
+    let subscription_manager: Arc<SubscriptionManager> = Arc::new(
+        config
+            .subscription_buffer_size
+            .map(SubscriptionManager::new)
+            .unwrap_or_default()
+            .with_frame_compression(config.subscription_frame_compression)
+            .with_saturated_delivery(config.subscription_saturated_delivery),
+    );
 
     // initialize intent database
     tracing::info!("Setting up database");
@@ -71,28 +86,65 @@ async fn main() -> Result<()> {
     tracing::info!("Setting up state");
     let base_state = BaseState::new(
         rpc_provider.clone(),
-        config.markets,
+        config.markets().context("Failed to resolve markets")?,
         Duration::from_secs(u64::from(config.validation_timeout_seconds)),
         validation_configs,
+    )
+    .with_submission_limits(config.submission_limits.clone())
+    .with_clock_skew(config.clock_skew.clone());
+    let request_state = RequestState::new(base_state.clone(), subscription_manager.clone())
+        .with_intent_store(Arc::new(intent_db.clone()));
+    request_state.spawn_expiry_notifier();
+    // offers are broadcast over the same subscriptions as requests
+    let offer_state = OfferState::new(base_state, intent_db.clone())
+        .with_subscription_manager(subscription_manager.clone());
+
+    // archive requests and prune offers once they're over, offers being resolvable for as long
+    // as the longest proving time they're accepted with
+    let offer_resolve_window = SYSTEMS
+        .iter()
+        .map(|system_id| {
+            offer_state
+                .validation_configs()
+                .offer
+                .for_system(*system_id)
+                .maximum_proving_time()
+        })
+        .max()
+        .unwrap_or_default();
+    let gc = Arc::new(
+        IntentGc::new(config.gc.clone(), Arc::new(SystemClock))
+            .with_intent_store(Arc::new(intent_db.clone()))
+            .with_offer_db(intent_db, u64::from(offer_resolve_window))
+            .with_request_relays(request_state.key_exchange(), request_state.extensions())
+            .with_offer_inputs(offer_state.offer_inputs()),
     );
-    let request_state = RequestState::new(base_state.clone(), Arc::new(subscription_manager));
-    let offer_state = OfferState::new(base_state, intent_db);
+    tokio::spawn(gc.clone().run());
+    let request_state = request_state.with_gc(gc);
 
     tracing::info!("Setting up routers");
     // Create separate routers for each intent type
-    let request_routes = Router::new()
-        .route("/submit/request", post(submit_request_handler))
-        .route("/subscribe", get(websocket_subscribe_handler))
-        .with_state(request_state);
-    let offer_routes = Router::new()
-        .route("/submit/offer", post(submit_offer_handler))
-        .route("/query/:system_id", get(get_active_intents_by_id_handler))
-        .with_state(offer_state);
+    let request_routes = router::build(router::request_routes()).with_state(request_state);
+    let offer_routes = router::build(router::offer_routes(
+        config.submission_limits.max_body_size_bytes,
+    ))
+    .with_state(offer_state);
+
+    // admin routes are only served behind a configured key
+    let admin_routes = match &config.admin_api_key {
+        Some(api_key) => router::build(router::admin_routes()).with_state(AdminState::new(
+            api_key.as_str(),
+            log_control,
+            subscription_manager,
+        )),
+        None => Router::new(),
+    };
 
     tracing::info!("Merging routers");
     // Merge routers
     let app = request_routes
         .merge(offer_routes)
+        .merge(admin_routes)
         .layer(TraceLayer::new_for_http())
         .fallback(get(fallback));
 
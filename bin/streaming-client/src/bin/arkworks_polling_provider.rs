@@ -0,0 +1,87 @@
+use alloy::network::EthereumWallet;
+use alloy::providers::ProviderBuilder;
+use alloy::signers::local::PrivateKeySigner;
+use color_eyre::Result;
+use dotenv::dotenv;
+use std::env;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use taralli_client::client::provider::control::{init_log_control, serve_log_filter};
+use taralli_client::client::provider::searching::ProviderSearchingClient;
+use taralli_primitives::markets::SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS;
+use taralli_primitives::systems::SystemId;
+use taralli_primitives::validation::request::{
+    ComputeRequestValidator, RequestValidationConfig, RequestVerifierConstraints,
+};
+use taralli_primitives::validation::BaseValidationConfig;
+use taralli_worker::arkworks::ArkworksWorker;
+use url::Url;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // setup tracing for client execution, its filter being switchable while running
+    let log_filter = env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string());
+    let log_control = init_log_control(&log_filter)?;
+
+    // Load environment variables from the `.env` file
+    dotenv().ok();
+
+    // switch the log filter from a local endpoint, when configured
+    if let Ok(control_address) = env::var("CONTROL_ADDRESS") {
+        let control_address: SocketAddr = control_address.parse()?;
+        tokio::spawn(async move {
+            if let Err(e) = serve_log_filter(log_control, control_address).await {
+                tracing::error!("control endpoint failed: {e}");
+            }
+        });
+    }
+
+    let server_url = Url::parse(&env::var("SERVER_URL")?)?; // local server instance
+    let rpc_url = Url::parse(&env::var("RPC_URL")?)?; // testnet
+    let priv_key = &env::var("PROVIDER_PRIVATE_KEY")?; // provider private key
+
+    // build signer
+    let signer = PrivateKeySigner::from_str(priv_key)?;
+    // build wallet for sending txs
+    let wallet = EthereumWallet::new(signer.clone());
+    // build rpc provider
+    let rpc_provider = ProviderBuilder::new()
+        .with_recommended_fillers()
+        .wallet(wallet)
+        .on_http(rpc_url);
+
+    // validation config to check searched requests are correct
+    let validation_config = RequestValidationConfig {
+        base: BaseValidationConfig::default(),
+        maximum_allowed_stake: 10000000000000000000, // 10 ether
+        ..Default::default()
+    };
+
+    // arkworks verifier constraints
+    let verifier_constraints = RequestVerifierConstraints::default();
+
+    // validator
+    let validator = ComputeRequestValidator::new(validation_config.clone(), verifier_constraints);
+
+    // instantiate provider searching client
+    let provider_client = ProviderSearchingClient::new(
+        server_url,
+        rpc_provider,
+        signer.clone(),
+        SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+        SystemId::Arkworks,
+        validation_config,
+    )
+    .with_system_configuration(SystemId::Arkworks, ArkworksWorker::new(), validator)?;
+
+    // run provider client once
+    // Queries the server for the open arkworks requests, ranks the ones nobody bid on yet by
+    // their current reward, then bids upon, proves and resolves the best one passing analysis.
+    // Meant to be run periodically (e.g. by cron) where a long-lived websocket is impractical.
+    match provider_client.run_once().await? {
+        Some(request_id) => tracing::info!("request {request_id} resolved"),
+        None => tracing::info!("no open request to fulfill"),
+    }
+
+    Ok(())
+}
@@ -5,6 +5,7 @@ use color_eyre::Result;
 use dotenv::dotenv;
 use std::env;
 use std::str::FromStr;
+use taralli_client::client::provider::control::init_log_control;
 use taralli_client::client::provider::streaming::ProviderStreamingClient;
 use taralli_primitives::markets::SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS;
 use taralli_primitives::systems::SystemId;
@@ -13,17 +14,13 @@ use taralli_primitives::validation::request::{
 };
 use taralli_primitives::validation::BaseValidationConfig;
 use taralli_worker::arkworks::ArkworksWorker;
-use tracing::Level;
-use tracing_subscriber::EnvFilter;
 use url::Url;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // setup tracing for client execution
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .with_max_level(Level::INFO)
-        .init();
+    // setup tracing for client execution, its filter being switchable while running
+    let log_filter = env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string());
+    let log_control = init_log_control(&log_filter)?;
 
     // Load environment variables from the `.env` file
     dotenv().ok();
@@ -45,6 +42,7 @@ async fn main() -> Result<()> {
     let validation_config = RequestValidationConfig {
         base: BaseValidationConfig::default(),
         maximum_allowed_stake: 10000000000000000000, // 10 ether
+        ..Default::default()
     };
 
     // arkworks verifier constraints
@@ -54,7 +52,7 @@ async fn main() -> Result<()> {
     let validator = ComputeRequestValidator::new(validation_config.clone(), verifier_constraints);
 
     // instantiate provider streaming client
-    let provider_client = ProviderStreamingClient::new(
+    let mut provider_client = ProviderStreamingClient::new(
         server_url,
         rpc_provider,
         signer.clone(),
@@ -63,6 +61,15 @@ async fn main() -> Result<()> {
     )
     .with_system_configuration(SystemId::Arkworks, ArkworksWorker::new(), validator)?;
 
+    // switch the log filter and dump the run's state from a local endpoint, when configured
+    provider_client = provider_client.with_log_control(log_control);
+    if let Ok(control_address) = env::var("CONTROL_ADDRESS") {
+        provider_client = provider_client.with_control_endpoint(control_address.parse()?);
+    }
+
+    // fail fast on a misconfigured rpc, market, server or bidding account
+    provider_client.healthcheck().await.into_result()?;
+
     // run provider client
     // Subscribes to the server and receives back a ws stream or fails.
     // The client awaits the ws stream returned by the server to receive newly
@@ -6,7 +6,11 @@ use dotenv::dotenv;
 use risc0_zkvm::ProverOpts;
 use std::env;
 use std::str::FromStr;
+use std::sync::Arc;
+use taralli_client::client::provider::control::init_log_control;
 use taralli_client::client::provider::streaming::ProviderStreamingClient;
+use taralli_client::config::SubmissionConfig;
+use taralli_client::submitter::{PrivateSubmitter, PublicSubmitter};
 use taralli_primitives::markets::{Network, SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS};
 use taralli_primitives::systems::risc0::Risc0VerifierConstraints;
 use taralli_primitives::systems::SystemId;
@@ -14,17 +18,13 @@ use taralli_primitives::validation::request::{ComputeRequestValidator, RequestVa
 use taralli_primitives::validation::BaseValidationConfig;
 use taralli_worker::risc0::local::Risc0LocalProver;
 use taralli_worker::risc0::Risc0Worker;
-use tracing::Level;
-use tracing_subscriber::EnvFilter;
 use url::Url;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // setup tracing for client execution
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .with_max_level(Level::INFO)
-        .init();
+    // setup tracing for client execution, its filter being switchable while running
+    let log_filter = env::var("RUST_LOG").unwrap_or_else(|_| "info".to_string());
+    let log_control = init_log_control(&log_filter)?;
 
     // Load environment variables from the `.env` file
     dotenv().ok();
@@ -45,10 +45,17 @@ async fn main() -> Result<()> {
         .wallet(wallet)
         .on_http(rpc_url);
 
+    // optional private rpc (e.g. Flashbots Protect) to keep bids out of the public mempool
+    let submission_config = SubmissionConfig {
+        private_rpc_url: env::var("PRIVATE_RPC_URL").ok(),
+        ..Default::default()
+    };
+
     // validation config to check incoming requests from streams are correct
     let validation_config = RequestValidationConfig {
         base: BaseValidationConfig::default(),
         maximum_allowed_stake: 10000000000000000000, // 10 ether
+        ..Default::default()
     };
 
     // setup risc0 prover
@@ -60,15 +67,39 @@ async fn main() -> Result<()> {
     );
 
     // instantiate provider streaming client
-    let provider_client = ProviderStreamingClient::new(
+    let mut provider_client = ProviderStreamingClient::new(
         server_url,
-        rpc_provider,
+        rpc_provider.clone(),
         signer.clone(),
         SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
         validation_config,
     )
     .with_system_configuration(SystemId::Risc0, Risc0Worker::new(risc0_prover), validator)?;
 
+    // switch the log filter and dump the run's state from a local endpoint, when configured
+    provider_client = provider_client.with_log_control(log_control);
+    if let Ok(control_address) = env::var("CONTROL_ADDRESS") {
+        provider_client = provider_client.with_control_endpoint(control_address.parse()?);
+    }
+
+    // send bids/resolves privately, falling back to the public rpc if they aren't included in time
+    if let Some(private_rpc_url) = &submission_config.private_rpc_url {
+        // signed once through the public rpc provider, then broadcast as is on either path
+        let private_provider = ProviderBuilder::new().on_http(Url::parse(private_rpc_url)?);
+        provider_client = provider_client.with_transaction_submitter(Arc::new(
+            PrivateSubmitter::new(
+                Arc::new(rpc_provider.clone()),
+                Arc::new(PublicSubmitter::new(private_provider)),
+                Arc::new(PublicSubmitter::new(rpc_provider)),
+                submission_config.fallback_timeout(),
+            )
+            .with_receipt_timeout(submission_config.receipt_timeout()),
+        ));
+    }
+
+    // fail fast on a misconfigured rpc, market, server or bidding account
+    provider_client.healthcheck().await.into_result()?;
+
     // run provider client
     // Subscribes to the server and receives back a ws stream or fails.
     // The client awaits the ws stream returned by the server to receive newly
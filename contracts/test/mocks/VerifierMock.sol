@@ -0,0 +1,15 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.23;
+
+/// @notice Verifier accepting or rejecting every submission, whatever the selector called
+contract VerifierMock {
+    bool public immutable accepts;
+
+    constructor(bool accepts_) {
+        accepts = accepts_;
+    }
+
+    fallback() external {
+        require(accepts, "proof rejected");
+    }
+}
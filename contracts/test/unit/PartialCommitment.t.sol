@@ -0,0 +1,103 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.23;
+
+import "../BaseTest.sol";
+import "../mocks/VerifierMock.sol";
+import "src/libraries/BombettaTypes.sol";
+
+/// @notice Requests checking a partial commitment result are resolved by the provider only when the partial
+/// commitment it submits completes the requester's predetermined one into the result field of its submission,
+/// and the verifier accepts that submission.
+contract PartialCommitmentTest is BaseTest {
+    bytes32 constant PREDETERMINED_PARTIAL_COMMITMENT = bytes32(uint256(0x11));
+    bytes32 constant SUBMITTED_PARTIAL_COMMITMENT = bytes32(uint256(0x33));
+
+    VerifierMock acceptingVerifier;
+    VerifierMock rejectingVerifier;
+
+    function setUp() external {
+        _setUp();
+        acceptingVerifier = new VerifierMock(true);
+        rejectingVerifier = new VerifierMock(false);
+    }
+
+    function testResolveWithPartialCommitment() public {
+        (bytes32 requestId, uint256 rewardAmount) = _bidOnRequest(address(acceptingVerifier));
+        uint256 prestateBobTokenBalance = testToken.balanceOf(bob);
+        uint256 prestateBobBalance = bob.balance;
+
+        vm.prank(bob);
+        bool providerResolved = universalBombetta.resolve(requestId, _opaqueSubmission(), SUBMITTED_PARTIAL_COMMITMENT);
+
+        assertTrue(providerResolved);
+        assertEq(bob.balance - prestateBobBalance, 1 ether);
+        assertEq(testToken.balanceOf(bob) - prestateBobTokenBalance, rewardAmount);
+    }
+
+    function testResolveWithWrongPartialCommitmentIsSlashed() public {
+        (bytes32 requestId, uint256 rewardAmount) = _bidOnRequest(address(acceptingVerifier));
+        uint256 prestateAliceTokenBalance = testToken.balanceOf(alice);
+        uint256 prestateAliceBalance = alice.balance;
+
+        // the verifier would accept the submission, the partial commitment doesn't complete its result
+        vm.prank(bob);
+        bool providerResolved = universalBombetta.resolve(requestId, _opaqueSubmission(), bytes32(0));
+
+        assertFalse(providerResolved);
+        assertEq(alice.balance - prestateAliceBalance, 1 ether);
+        assertEq(testToken.balanceOf(alice) - prestateAliceTokenBalance, rewardAmount);
+    }
+
+    function testResolveRejectedByVerifierIsSlashed() public {
+        (bytes32 requestId, uint256 rewardAmount) = _bidOnRequest(address(rejectingVerifier));
+        uint256 prestateAliceTokenBalance = testToken.balanceOf(alice);
+        uint256 prestateAliceBalance = alice.balance;
+
+        // the partial commitment completes the result, the verifier rejects the proof
+        vm.prank(bob);
+        bool providerResolved = universalBombetta.resolve(requestId, _opaqueSubmission(), SUBMITTED_PARTIAL_COMMITMENT);
+
+        assertFalse(providerResolved);
+        assertEq(alice.balance - prestateAliceBalance, 1 ether);
+        assertEq(testToken.balanceOf(alice) - prestateAliceTokenBalance, rewardAmount);
+    }
+
+    /// @dev the inputs at offset 0, followed by the partial commitment result at offset 32
+    function _opaqueSubmission() internal pure returns (bytes memory) {
+        return abi.encode(uint256(33), PREDETERMINED_PARTIAL_COMMITMENT, SUBMITTED_PARTIAL_COMMITMENT);
+    }
+
+    function _bidOnRequest(address verifier) internal returns (bytes32 requestId, uint256 rewardAmount) {
+        UniversalBombetta.VerifierDetails memory verifierDetails = UniversalBombetta.VerifierDetails({
+            verifier: verifier,
+            selector: bytes4(keccak256("verify(uint256,bytes32,bytes32)")),
+            isShaCommitment: false,
+            inputsOffset: 0,
+            inputsLength: 32,
+            hasPartialCommitmentResultCheck: true,
+            submittedPartialCommitmentResultOffset: 32,
+            submittedPartialCommitmentResultLength: 64,
+            predeterminedPartialCommitment: PREDETERMINED_PARTIAL_COMMITMENT
+        });
+        ProofRequest memory request = ProofRequest({
+            signer: alice,
+            market: address(universalBombetta),
+            nonce: 0,
+            rewardToken: address(testToken),
+            maxRewardAmount: 1000 ether,
+            minRewardAmount: 0,
+            minimumStake: 1 ether,
+            startAuctionTimestamp: uint64(block.timestamp),
+            endAuctionTimestamp: uint64(block.timestamp + 1000),
+            provingTime: 1 days,
+            inputsCommitment: keccak256(abi.encode(uint256(33))),
+            extraData: abi.encode(verifierDetails)
+        });
+        bytes memory sig = _getBombettaSignature(address(universalBombetta), request, ALICE_PK);
+
+        vm.warp(block.timestamp + 10);
+        vm.prank(bob);
+        (, rewardAmount,) = universalBombetta.bid{value: 1 ether}(request, sig);
+        requestId = universalBombetta.computeRequestId(request, sig);
+    }
+}
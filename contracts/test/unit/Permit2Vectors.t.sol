@@ -0,0 +1,154 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.23;
+
+import "forge-std/Test.sol";
+import "src/UniversalBombetta.sol";
+import "src/UniversalPorchetta.sol";
+import "src/interfaces/IPermit2.sol";
+import "src/libraries/BombettaTypes.sol";
+import "src/libraries/PorchettaTypes.sol";
+
+/// Checks the markets hash and verify the permit2 test vectors generated by the rust client
+/// (`cargo run -p taralli-primitives --bin permit2_vectors`) the way the client signs them.
+contract Permit2VectorsTest is Test {
+    bytes32 constant EIP712_DOMAIN_TYPEHASH =
+        keccak256("EIP712Domain(string name,uint256 chainId,address verifyingContract)");
+
+    UniversalBombetta public universalBombetta;
+    UniversalPorchetta public universalPorchetta;
+
+    string vectorsJson;
+    bytes32 domainSeparator;
+    address signer;
+
+    function setUp() external {
+        // only pure hashing is exercised, no permit2 is needed
+        universalBombetta = new UniversalBombetta(IPermit2(address(0)));
+        universalPorchetta = new UniversalPorchetta(IPermit2(address(0)));
+
+        vectorsJson = vm.readFile("./test-proof-data/permit2/permit2_vectors.json");
+        assertEq(vm.parseJsonUint(vectorsJson, "$.version"), 1);
+        signer = vm.parseJsonAddress(vectorsJson, "$.signer");
+        assertEq(vm.addr(vm.parseJsonUint(vectorsJson, "$.signer_key")), signer);
+        domainSeparator = keccak256(
+            abi.encode(
+                EIP712_DOMAIN_TYPEHASH,
+                keccak256("Permit2"),
+                vm.parseJsonUint(vectorsJson, "$.chain_id"),
+                vm.parseJsonAddress(vectorsJson, "$.permit2")
+            )
+        );
+    }
+
+    function testProofRequestVectors() public view {
+        string[] memory names = abi.decode(vm.parseJson(vectorsJson, "$.requests[*].name"), (string[]));
+        assertGt(names.length, 0);
+        for (uint256 i = 0; i < names.length; i++) {
+            string memory path = string.concat("$.requests[", vm.toString(i), "]");
+            ProofRequest memory request = _parseProofRequest(string.concat(path, ".proof_request"));
+
+            bytes32 witness = universalBombetta.computeWitnessHash(request);
+            assertEq(witness, vm.parseJsonBytes32(vectorsJson, string.concat(path, ".witness")), names[i]);
+
+            bytes32 digest = _permitWitnessDigest(
+                request.rewardToken,
+                request.maxRewardAmount,
+                request.market,
+                request.nonce,
+                request.endAuctionTimestamp,
+                witness,
+                universalBombetta.FULL_PROOF_REQUEST_WITNESS_TYPE_STRING_STUB()
+            );
+            _assertSigned(path, digest, names[i]);
+        }
+    }
+
+    function testProofOfferVectors() public view {
+        string[] memory names = abi.decode(vm.parseJson(vectorsJson, "$.offers[*].name"), (string[]));
+        assertGt(names.length, 0);
+        for (uint256 i = 0; i < names.length; i++) {
+            string memory path = string.concat("$.offers[", vm.toString(i), "]");
+            ProofOffer memory offer = _parseProofOffer(string.concat(path, ".proof_offer"));
+
+            bytes32 witness = universalPorchetta.computeWitnessHash(offer);
+            assertEq(witness, vm.parseJsonBytes32(vectorsJson, string.concat(path, ".witness")), names[i]);
+
+            // the provider's permit covers its stake
+            bytes32 digest = _permitWitnessDigest(
+                offer.stakeToken,
+                offer.stakeAmount,
+                offer.market,
+                offer.nonce,
+                offer.endAuctionTimestamp,
+                witness,
+                universalPorchetta.FULL_PROOF_OFFER_WITNESS_TYPE_STRING_STUB()
+            );
+            _assertSigned(path, digest, names[i]);
+        }
+    }
+
+    /// digest of the PermitWitnessTransferFrom permit2 verifies in permitWitnessTransferFrom()
+    function _permitWitnessDigest(
+        address token,
+        uint256 amount,
+        address spender,
+        uint256 nonce,
+        uint256 deadline,
+        bytes32 witness,
+        string memory witnessTypeString
+    ) internal view returns (bytes32) {
+        bytes32 typeHash =
+            keccak256(abi.encodePacked(universalBombetta.PERMIT_TRANSFER_FROM_WITNESS_TYPEHASH_STUB(), witnessTypeString));
+        bytes32 tokenPermissionsHash =
+            keccak256(abi.encode(universalBombetta.TOKEN_PERMISSIONS_TYPEHASH(), token, amount));
+        bytes32 dataHash = keccak256(abi.encode(typeHash, tokenPermissionsHash, spender, nonce, deadline, witness));
+        return keccak256(abi.encodePacked("\x19\x01", domainSeparator, dataHash));
+    }
+
+    function _assertSigned(string memory path, bytes32 digest, string memory name) internal view {
+        assertEq(digest, vm.parseJsonBytes32(vectorsJson, string.concat(path, ".permit2_digest")), name);
+
+        bytes memory signature = vm.parseJsonBytes(vectorsJson, string.concat(path, ".signature"));
+        assertEq(signature.length, 65, name);
+        bytes32 r;
+        bytes32 s;
+        uint8 v;
+        assembly {
+            r := mload(add(signature, 0x20))
+            s := mload(add(signature, 0x40))
+            v := byte(0, mload(add(signature, 0x60)))
+        }
+        assertEq(ecrecover(digest, v, r, s), signer, name);
+    }
+
+    function _parseProofRequest(string memory path) internal view returns (ProofRequest memory request) {
+        request.signer = vm.parseJsonAddress(vectorsJson, string.concat(path, ".signer"));
+        request.market = vm.parseJsonAddress(vectorsJson, string.concat(path, ".market"));
+        request.nonce = vm.parseJsonUint(vectorsJson, string.concat(path, ".nonce"));
+        request.rewardToken = vm.parseJsonAddress(vectorsJson, string.concat(path, ".rewardToken"));
+        request.maxRewardAmount = vm.parseJsonUint(vectorsJson, string.concat(path, ".maxRewardAmount"));
+        request.minRewardAmount = vm.parseJsonUint(vectorsJson, string.concat(path, ".minRewardAmount"));
+        request.minimumStake = uint128(vm.parseJsonUint(vectorsJson, string.concat(path, ".minimumStake")));
+        request.startAuctionTimestamp =
+            uint64(vm.parseJsonUint(vectorsJson, string.concat(path, ".startAuctionTimestamp")));
+        request.endAuctionTimestamp = uint64(vm.parseJsonUint(vectorsJson, string.concat(path, ".endAuctionTimestamp")));
+        request.provingTime = uint32(vm.parseJsonUint(vectorsJson, string.concat(path, ".provingTime")));
+        request.inputsCommitment = vm.parseJsonBytes32(vectorsJson, string.concat(path, ".inputsCommitment"));
+        request.extraData = vm.parseJsonBytes(vectorsJson, string.concat(path, ".extraData"));
+    }
+
+    function _parseProofOffer(string memory path) internal view returns (ProofOffer memory offer) {
+        offer.signer = vm.parseJsonAddress(vectorsJson, string.concat(path, ".signer"));
+        offer.market = vm.parseJsonAddress(vectorsJson, string.concat(path, ".market"));
+        offer.nonce = vm.parseJsonUint(vectorsJson, string.concat(path, ".nonce"));
+        offer.rewardToken = vm.parseJsonAddress(vectorsJson, string.concat(path, ".rewardToken"));
+        offer.rewardAmount = vm.parseJsonUint(vectorsJson, string.concat(path, ".rewardAmount"));
+        offer.stakeToken = vm.parseJsonAddress(vectorsJson, string.concat(path, ".stakeToken"));
+        offer.stakeAmount = vm.parseJsonUint(vectorsJson, string.concat(path, ".stakeAmount"));
+        offer.startAuctionTimestamp = uint64(vm.parseJsonUint(vectorsJson, string.concat(path, ".startAuctionTimestamp")));
+        offer.endAuctionTimestamp = uint64(vm.parseJsonUint(vectorsJson, string.concat(path, ".endAuctionTimestamp")));
+        offer.provingTime = uint32(vm.parseJsonUint(vectorsJson, string.concat(path, ".provingTime")));
+        offer.inputsCommitment = vm.parseJsonBytes32(vectorsJson, string.concat(path, ".inputsCommitment"));
+        offer.extraData = vm.parseJsonBytes(vectorsJson, string.concat(path, ".extraData"));
+    }
+}
@@ -0,0 +1,77 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.23;
+
+import "../BaseTest.sol";
+import "../mocks/VerifierMock.sol";
+import "src/libraries/BombettaTypes.sol";
+
+/// @notice The deployed UniversalBombetta resolves requests against their leading verifier details only,
+/// so a submission formatted for an alternative appended to the extra data is checked by the leading
+/// verifier and slashed.
+contract VerifierAlternativesTest is BaseTest {
+    VerifierMock leadingVerifier;
+    VerifierMock alternativeVerifier;
+
+    function setUp() external {
+        _setUp();
+        leadingVerifier = new VerifierMock(false);
+        alternativeVerifier = new VerifierMock(true);
+    }
+
+    function testMarketDoesNotReadVerifierAlternatives() public view {
+        (bool advertised,) = address(universalBombetta).staticcall(abi.encodeWithSignature("readsVerifierAlternatives()"));
+        assertFalse(advertised);
+    }
+
+    function testAlternativeSubmissionIsSlashed() public {
+        UniversalBombetta.VerifierDetails memory leading = _verifierDetails(address(leadingVerifier));
+        UniversalBombetta.VerifierDetails memory alternative = _verifierDetails(address(alternativeVerifier));
+
+        ProofRequest memory request = ProofRequest({
+            signer: alice,
+            market: address(universalBombetta),
+            nonce: 0,
+            rewardToken: address(testToken),
+            maxRewardAmount: 1000 ether,
+            minRewardAmount: 0,
+            minimumStake: 1 ether,
+            startAuctionTimestamp: uint64(block.timestamp),
+            endAuctionTimestamp: uint64(block.timestamp + 1000),
+            provingTime: 1 days,
+            inputsCommitment: keccak256(abi.encode(uint256(33))),
+            // the alternative trails the leading details the market decodes
+            extraData: bytes.concat(abi.encode(leading), abi.encode(alternative))
+        });
+        bytes memory sig = _getBombettaSignature(address(universalBombetta), request, ALICE_PK);
+
+        vm.warp(block.timestamp + 10);
+        vm.prank(bob);
+        (, uint256 rewardAmount,) = universalBombetta.bid{value: 1 ether}(request, sig);
+        bytes32 requestId = universalBombetta.computeRequestId(request, sig);
+
+        uint256 prestateAliceTokenBalance = testToken.balanceOf(alice);
+        uint256 prestateAliceBalance = alice.balance;
+
+        // a submission the alternative verifier accepts is handed to the leading one instead
+        vm.prank(bob);
+        bool providerResolved = universalBombetta.resolve(requestId, abi.encode(uint256(33)), bytes32(0));
+
+        assertFalse(providerResolved);
+        assertEq(alice.balance - prestateAliceBalance, 1 ether);
+        assertEq(testToken.balanceOf(alice) - prestateAliceTokenBalance, rewardAmount);
+    }
+
+    function _verifierDetails(address verifier) internal pure returns (UniversalBombetta.VerifierDetails memory) {
+        return UniversalBombetta.VerifierDetails({
+            verifier: verifier,
+            selector: bytes4(keccak256("verify(uint256)")),
+            isShaCommitment: false,
+            inputsOffset: 0,
+            inputsLength: 32,
+            hasPartialCommitmentResultCheck: false,
+            submittedPartialCommitmentResultOffset: 0,
+            submittedPartialCommitmentResultLength: 0,
+            predeterminedPartialCommitment: bytes32(0)
+        });
+    }
+}
@@ -0,0 +1,76 @@
+//! Receive path of providers: every broadcast request is decoded from its frame and its system
+//! decompressed before the analyzer sees it
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use taralli_client::api::dictionary::DictionaryCache;
+use taralli_primitives::compression_utils::compression::compress_brotli;
+use taralli_primitives::compression_utils::frames::{
+    deflate_request_frame, inflate_request_frame_ref,
+};
+use taralli_primitives::compression_utils::intents::{
+    ComputeRequestCompressed, ComputeRequestCompressedRef,
+};
+use taralli_primitives::intents::envelope::{
+    decode_broadcast_envelope, BroadcastEnvelope, BroadcastMeta, IntentKind,
+};
+use url::Url;
+
+// fixtures are shared with the primitives benches
+#[path = "../../taralli-primitives/benches/support/mod.rs"]
+mod support;
+
+fn receive(c: &mut Criterion) {
+    let dictionaries = DictionaryCache::new(Url::parse("http://localhost:8000").unwrap());
+    for (name, system) in support::systems() {
+        let request = support::signed_request(system);
+        let compressed_request = ComputeRequestCompressed {
+            system_id: request.system_id,
+            system: compress_brotli(&serde_json::to_vec(&request.system).unwrap()).unwrap(),
+            proof_request: request.proof_request,
+            signature: request.signature,
+        };
+        let meta = BroadcastMeta {
+            sequence: 1,
+            received_at_ms: 1,
+        };
+        let envelope = bincode::serialize(&BroadcastEnvelope::new(
+            IntentKind::Request,
+            meta,
+            compressed_request.clone(),
+        ))
+        .unwrap();
+        let frame = deflate_request_frame(&compressed_request, meta).unwrap();
+
+        let mut group = c.benchmark_group(format!("receive/{name}"));
+        group.throughput(Throughput::Bytes(envelope.len() as u64));
+        group.bench_function("decode_envelope_owned", |b| {
+            b.iter(|| {
+                decode_broadcast_envelope::<ComputeRequestCompressed>(black_box(&envelope)).unwrap()
+            })
+        });
+        group.bench_function("decode_envelope_borrowed", |b| {
+            b.iter(|| {
+                decode_broadcast_envelope::<ComputeRequestCompressedRef>(black_box(&envelope))
+                    .unwrap()
+            })
+        });
+        group.bench_function("inflate_frame", |b| {
+            b.iter(|| inflate_request_frame_ref(black_box(&frame)).unwrap())
+        });
+        // systems compressed without a dictionary never reach the server
+        group.bench_function("decompress_system", |b| {
+            b.iter(|| {
+                support::block_on(
+                    dictionaries.decompress_system_payload(black_box(&compressed_request.system)),
+                )
+                .unwrap()
+            })
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, receive);
+criterion_main!(benches);
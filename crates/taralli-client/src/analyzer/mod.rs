@@ -4,6 +4,8 @@ use crate::error::Result;
 
 pub mod offer;
 pub mod request;
+pub mod reward;
+pub mod verifier;
 
 /// core analyzer trait
 #[async_trait]
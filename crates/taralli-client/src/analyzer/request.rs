@@ -1,11 +1,24 @@
+use std::collections::{HashMap, HashSet};
 use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
 
 use async_trait::async_trait;
 use taralli_primitives::alloy::{
-    network::Network, primitives::Address, providers::Provider, transports::Transport,
+    network::Network,
+    primitives::{Address, FixedBytes, U256},
+    providers::Provider,
+    transports::Transport,
 };
 use taralli_primitives::{
-    intents::request::ComputeRequest,
+    abi::{
+        extensions::{
+            decode_request_extensions, decode_request_verifier_alternatives, with_leading_verifier,
+            IVerifierAlternatives,
+        },
+        universal_bombetta::{ProofRequestVerifierDetails, UniversalBombetta::ProofRequest},
+    },
+    intents::{auction::RewardCurve, request::ComputeRequest},
+    markets::MarketFee,
     systems::SystemParams,
     validation::{
         registry::{ComputeRequestValidatorRegistry, ValidatorRegistry},
@@ -13,8 +26,11 @@ use taralli_primitives::{
     },
 };
 
-use crate::error::Result;
+use crate::error::{ClientError, Result};
+use crate::searcher::request::CostModel;
 
+use super::reward::RewardTokenPolicy;
+use super::verifier::{VerifierCheckConfig, VerifierChecker};
 use super::IntentAnalyzer;
 
 /// Analyzes a `ComputeRequest`'s validity and profitability
@@ -24,9 +40,21 @@ where
     P: Provider<T, N> + Clone + Send + Sync,
     N: Network + Clone + Send + Sync,
 {
-    _rpc_provider: P,
+    rpc_provider: P,
     pub market_address: Address,
     pub validator_registry: ComputeRequestValidatorRegistry,
+    pub verifier_checker: VerifierChecker<T, P, N>,
+    /// reward tokens accepted, any token is when unset
+    pub reward_tokens: Option<RewardTokenPolicy>,
+    /// cost the current reward of a request has to cover, in units of its reward token
+    pub cost_model: Option<Arc<dyn CostModel>>,
+    /// protocol fee the market takes out of rewards, the cost model being covered by the rest
+    pub market_fee: MarketFee,
+    /// verifiers the workers format submissions for, by address and selector, any verifier is
+    /// when unset
+    pub supported_verifiers: Option<HashSet<(Address, FixedBytes<4>)>>,
+    // whether each market resolves against the verifier alternative picked, as advertised
+    verifier_alternative_markets: Mutex<HashMap<Address, bool>>,
     phantom_data: PhantomData<(T, N)>,
 }
 
@@ -42,15 +70,222 @@ where
         validation_config: RequestValidationConfig,
     ) -> Self {
         Self {
-            _rpc_provider: rpc_provider,
+            verifier_checker: VerifierChecker::new(
+                rpc_provider.clone(),
+                VerifierCheckConfig::default(),
+            ),
+            rpc_provider,
             market_address,
             validator_registry: ComputeRequestValidatorRegistry::new(
                 validation_config.clone(),
                 RequestVerifierConstraints::default(),
             ),
+            reward_tokens: None,
+            cost_model: None,
+            market_fee: MarketFee::ZERO,
+            supported_verifiers: None,
+            verifier_alternative_markets: Mutex::new(HashMap::new()),
             phantom_data: PhantomData,
         }
     }
+
+    /// Configure the on-chain check of the verifiers named by analyzed requests
+    pub fn with_verifier_check(mut self, config: VerifierCheckConfig) -> Self {
+        self.verifier_checker.config = config;
+        self
+    }
+
+    /// Only accept requests rewarding in the policy's tokens
+    pub fn with_reward_tokens(mut self, reward_tokens: RewardTokenPolicy) -> Self {
+        self.reward_tokens = Some(reward_tokens);
+        self
+    }
+
+    /// Only accept requests whose current reward covers their cost under `cost_model`, see
+    /// `NormalizedCost` to price costs in USD across reward tokens
+    pub fn with_cost_model(mut self, cost_model: Arc<dyn CostModel>) -> Self {
+        self.cost_model = Some(cost_model);
+        self
+    }
+
+    /// Account for the protocol fee the market takes out of rewards, e.g. the one of
+    /// `MarketParams` once read from the deployments registry
+    pub fn with_market_fee(mut self, market_fee: MarketFee) -> Self {
+        self.market_fee = market_fee;
+        self
+    }
+
+    /// Only prove requests against the verifiers the workers format submissions for, picking the
+    /// first of a request's verifier alternatives among them
+    pub fn with_supported_verifiers(
+        mut self,
+        verifiers: impl IntoIterator<Item = (Address, FixedBytes<4>)>,
+    ) -> Self {
+        self.supported_verifiers = Some(verifiers.into_iter().collect());
+        self
+    }
+
+    /// Whether `market` resolves requests against the verifier alternative their provider
+    /// picked, skipping the `IVerifierAlternatives` check of the market
+    pub fn with_verifier_alternatives_market(
+        self,
+        market: Address,
+        reads_alternatives: bool,
+    ) -> Self {
+        self.verifier_alternative_markets
+            .lock()
+            .expect("verifier alternative markets lock poisoned")
+            .insert(market, reads_alternatives);
+        self
+    }
+
+    /// Whether `market` advertises it resolves requests against the verifier alternative their
+    /// provider picked. Markets not exposing `IVerifierAlternatives`, like the deployed
+    /// `UniversalBombetta`, resolve against the leading verifier details only. An rpc failure is
+    /// taken as no, without being remembered.
+    pub async fn market_reads_verifier_alternatives(&self, market: Address) -> bool {
+        if let Some(reads_alternatives) = self
+            .verifier_alternative_markets
+            .lock()
+            .expect("verifier alternative markets lock poisoned")
+            .get(&market)
+        {
+            return *reads_alternatives;
+        }
+        let reads_alternatives = match IVerifierAlternatives::new(market, self.rpc_provider.clone())
+            .readsVerifierAlternatives()
+            .call()
+            .await
+        {
+            Ok(advertised) => advertised._0,
+            Err(e) => match e.as_revert_data() {
+                // the market doesn't implement the capability
+                Some(_) => false,
+                None => {
+                    tracing::warn!(
+                        "checking whether market {} reads verifier alternatives failed: {}",
+                        market,
+                        e
+                    );
+                    return false;
+                }
+            },
+        };
+        self.verifier_alternative_markets
+            .lock()
+            .expect("verifier alternative markets lock poisoned")
+            .insert(market, reads_alternatives);
+        reads_alternatives
+    }
+
+    /// Select the verifier details the request is proven against: the first of its alternatives
+    /// the workers format submissions for and whose verifier supports its selector on-chain. On
+    /// markets not reading alternatives, only the leading verifier details are considered.
+    pub async fn select_verifier(
+        &self,
+        proof_request: &ProofRequest,
+    ) -> Result<ProofRequestVerifierDetails> {
+        let mut alternatives = decode_request_verifier_alternatives(&proof_request.extraData)
+            .map_err(|e| ClientError::IntentAnalysisError(e.to_string()))?;
+        if alternatives.len() > 1
+            && !self
+                .market_reads_verifier_alternatives(proof_request.market)
+                .await
+        {
+            alternatives.truncate(1);
+        }
+
+        let mut leading_rejection = None;
+        for verifier_details in alternatives {
+            let supported = self.supported_verifiers.as_ref().is_none_or(|verifiers| {
+                verifiers.contains(&(verifier_details.verifier, verifier_details.selector))
+            });
+            let checked = if supported {
+                self.verifier_checker
+                    .ensure_supported(verifier_details.verifier, verifier_details.selector)
+                    .await
+            } else {
+                Err(ClientError::IntentAnalysisError(format!(
+                    "no worker formats submissions for verifier {} with selector {}",
+                    verifier_details.verifier, verifier_details.selector
+                )))
+            };
+            match checked {
+                Ok(()) => return Ok(verifier_details),
+                Err(e) => {
+                    leading_rejection.get_or_insert(e);
+                }
+            }
+        }
+        Err(leading_rejection.expect("the leading verifier details are always decoded"))
+    }
+
+    /// The request as given to its worker: led by the selected verifier details when it carries
+    /// alternatives, so the submission is formatted against their offsets and hash function. It
+    /// stays led by its own verifier details on markets not reading alternatives.
+    pub async fn with_selected_verifier(
+        &self,
+        request: &ComputeRequest<SystemParams>,
+    ) -> Result<ComputeRequest<SystemParams>> {
+        let has_alternatives = decode_request_extensions(&request.proof_request.extraData)
+            .is_ok_and(|extensions| !extensions.verifier_alternatives.is_empty());
+        if !has_alternatives {
+            return Ok(request.clone());
+        }
+        let verifier_details = self.select_verifier(&request.proof_request).await?;
+        let mut selected = request.clone();
+        selected.proof_request.extraData =
+            with_leading_verifier(&request.proof_request.extraData, &verifier_details);
+        Ok(selected)
+    }
+
+    /// Check the request's reward token is accepted, given its reward at `latest_ts`
+    pub fn check_reward_token(&self, latest_ts: u64, proof_request: &ProofRequest) -> Result<()> {
+        let Some(reward_tokens) = &self.reward_tokens else {
+            return Ok(());
+        };
+        reward_tokens.check(
+            &proof_request.rewardToken,
+            reward_at(latest_ts, proof_request)?,
+        )?;
+        Ok(())
+    }
+
+    /// Check the request's reward token is accepted and its reward at `latest_ts`, net of the
+    /// market's fee, covers its cost
+    pub fn check_economics(
+        &self,
+        latest_ts: u64,
+        intent: &ComputeRequest<SystemParams>,
+    ) -> Result<()> {
+        self.check_reward_token(latest_ts, &intent.proof_request)?;
+        if let Some(cost_model) = &self.cost_model {
+            let reward = self
+                .market_fee
+                .net_reward(reward_at(latest_ts, &intent.proof_request)?);
+            let cost = cost_model.cost(intent);
+            if reward < cost {
+                return Err(ClientError::UnprofitableIntent { reward, cost });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Urgency the requester signaled through the extensions of the request's extra data, zero when
+/// not signaled or malformed. Urgent requests are proven first and bid upon right away.
+pub fn request_urgency(proof_request: &ProofRequest) -> u8 {
+    decode_request_extensions(&proof_request.extraData)
+        .ok()
+        .and_then(|extensions| extensions.urgency)
+        .unwrap_or(0)
+}
+
+/// Reward of a bid placed upon `proof_request` at `latest_ts`
+fn reward_at(latest_ts: u64, proof_request: &ProofRequest) -> Result<U256> {
+    Ok(RewardCurve::try_from(proof_request)
+        .map_err(|e| ClientError::IntentAnalysisError(e.to_string()))?
+        .reward_at(latest_ts))
 }
 
 #[async_trait]
@@ -67,7 +302,11 @@ where
         self.validator_registry
             .validate(intent, latest_ts, &self.market_address)?;
 
-        //// TODO: economic checks
+        // reward token and profitability checks, before any rpc request
+        self.check_economics(latest_ts, intent)?;
+
+        // make sure the resolve transaction can reach a verifier function
+        self.select_verifier(&intent.proof_request).await?;
 
         Ok(())
     }
@@ -0,0 +1,178 @@
+//! Reward tokens a provider accepts, and the normalization of rewards paid in different tokens
+//! into USD so their profitability can be compared.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use taralli_primitives::alloy::primitives::{
+    utils::{format_units, parse_units},
+    Address, U256,
+};
+use taralli_primitives::{intents::request::ComputeRequest, systems::SystemParams};
+
+use crate::error::{ClientError, Result};
+use crate::searcher::request::CostModel;
+
+/// USD price of one whole reward token, `None` when it isn't known
+pub type PriceSource = Arc<dyn Fn(Address) -> Option<f64> + Send + Sync>;
+
+/// Reward token accepted by a provider
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RewardTokenConfig {
+    pub address: Address,
+    pub decimals: u8,
+    /// smallest reward, in raw token units, accepted while the token has no price. Unpriced
+    /// tokens without it are rejected.
+    #[serde(default)]
+    pub min_unpriced_amount: Option<U256>,
+}
+
+/// How a reward in an accepted token is valued
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RewardValuation {
+    /// the token is priced at `usd_price` per whole token
+    Priced { usd_price: f64, decimals: u8 },
+    /// the token has no price, the reward met the token's minimum raw amount
+    Unpriced { min_amount: U256 },
+}
+
+/// Allowlist of the reward tokens a provider accepts, priced through an optional price source
+#[derive(Clone, Default)]
+pub struct RewardTokenPolicy {
+    tokens: HashMap<Address, RewardTokenConfig>,
+    price_source: Option<PriceSource>,
+}
+
+impl fmt::Debug for RewardTokenPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RewardTokenPolicy")
+            .field("tokens", &self.tokens)
+            .field(
+                "price_source",
+                &self.price_source.as_ref().map(|_| "<PriceSource>"),
+            )
+            .finish()
+    }
+}
+
+impl RewardTokenPolicy {
+    pub fn new(tokens: impl IntoIterator<Item = RewardTokenConfig>) -> Self {
+        Self {
+            tokens: tokens
+                .into_iter()
+                .map(|token| (token.address, token))
+                .collect(),
+            price_source: None,
+        }
+    }
+
+    /// Price the accepted tokens in USD with `price_source`
+    pub fn with_price_source(
+        mut self,
+        price_source: impl Fn(Address) -> Option<f64> + Send + Sync + 'static,
+    ) -> Self {
+        self.price_source = Some(Arc::new(price_source));
+        self
+    }
+
+    pub fn token(&self, address: &Address) -> Option<&RewardTokenConfig> {
+        self.tokens.get(address)
+    }
+
+    /// USD price of one whole `token`, `None` when it isn't accepted or has no price
+    pub fn usd_price(&self, token: &Address) -> Option<f64> {
+        self.tokens.get(token)?;
+        let price_source = self.price_source.as_ref()?;
+        let price = price_source(*token)?;
+        (price.is_finite() && price > 0.0).then_some(price)
+    }
+
+    /// Value in USD of `amount` raw units of `token`, `None` when it isn't accepted or has no price
+    pub fn usd_value(&self, token: &Address, amount: U256) -> Option<f64> {
+        let decimals = self.tokens.get(token)?.decimals;
+        let price = self.usd_price(token)?;
+        let whole_tokens: f64 = format_units(amount, decimals).ok()?.parse().ok()?;
+        Some(whole_tokens * price)
+    }
+
+    /// Raw units of `token` worth `usd`, `None` when it isn't accepted or has no price
+    pub fn raw_amount(&self, token: &Address, usd: f64) -> Option<U256> {
+        let decimals = self.tokens.get(token)?.decimals;
+        let whole_tokens = (usd / self.usd_price(token)?).max(0.0);
+        let amount = parse_units(
+            &format!("{:.*}", usize::from(decimals), whole_tokens),
+            decimals,
+        )
+        .map(|amount| amount.get_absolute())
+        .unwrap_or(U256::MAX);
+        Some(amount)
+    }
+
+    /// Check a reward of `amount` raw units of `token` is accepted, returning how it's valued
+    pub fn check(&self, token: &Address, amount: U256) -> Result<RewardValuation> {
+        let Some(config) = self.tokens.get(token) else {
+            return Err(ClientError::RewardTokenRejected {
+                token: *token,
+                reason: "not an accepted reward token".to_string(),
+            });
+        };
+        if let Some(usd_price) = self.usd_price(token) {
+            return Ok(RewardValuation::Priced {
+                usd_price,
+                decimals: config.decimals,
+            });
+        }
+        match config.min_unpriced_amount {
+            Some(min_amount) if amount >= min_amount => {
+                Ok(RewardValuation::Unpriced { min_amount })
+            }
+            Some(min_amount) => Err(ClientError::RewardTokenRejected {
+                token: *token,
+                reason: format!("token has no price, reward of {amount} below {min_amount}"),
+            }),
+            None => Err(ClientError::RewardTokenRejected {
+                token: *token,
+                reason: "token has no price".to_string(),
+            }),
+        }
+    }
+}
+
+/// Cost of a request in USD
+pub type UsdCost = Arc<dyn Fn(&ComputeRequest<SystemParams>) -> f64 + Send + Sync>;
+
+/// Cost model pricing requests in USD, converted into units of each request's reward token.
+/// Requests rewarding in unpriced tokens cost their token's minimum raw amount, and requests
+/// rewarding in tokens that aren't accepted can never be covered.
+pub struct NormalizedCost {
+    policy: RewardTokenPolicy,
+    usd_cost: UsdCost,
+}
+
+impl NormalizedCost {
+    pub fn new(
+        policy: RewardTokenPolicy,
+        usd_cost: impl Fn(&ComputeRequest<SystemParams>) -> f64 + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            policy,
+            usd_cost: Arc::new(usd_cost),
+        }
+    }
+}
+
+impl CostModel for NormalizedCost {
+    fn cost(&self, request: &ComputeRequest<SystemParams>) -> U256 {
+        let token = &request.proof_request.rewardToken;
+        match self.policy.raw_amount(token, (self.usd_cost)(request)) {
+            Some(amount) => amount,
+            None => self
+                .policy
+                .token(token)
+                .and_then(|config| config.min_unpriced_amount)
+                .unwrap_or(U256::MAX),
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use taralli_primitives::alloy::{
+    network::{Network, TransactionBuilder},
+    primitives::{Address, Bytes, FixedBytes},
+    providers::Provider,
+    transports::Transport,
+};
+
+use crate::api::retry::RetryPolicy;
+use crate::error::{ClientError, Result};
+use crate::rpc::retry_rpc;
+
+/// `PUSH1` and `PUSH32` opcodes, dispatchers push the selectors they compare the calldata's with
+/// the shortest push fitting them, e.g. `PUSH3` for a selector with a leading zero byte
+const PUSH1: u8 = 0x60;
+const PUSH32: u8 = 0x7f;
+/// zero bytes appended to the selector when probing it, enough for the static arguments of common
+/// verifier functions so their argument decoding doesn't revert before the function body runs
+const PROBE_ARGUMENTS_LENGTH: usize = 1024;
+
+/// Configures the on-chain check of the verifier named by an intent
+#[derive(Clone, Debug)]
+pub struct VerifierCheckConfig {
+    /// check the verifier before bidding, disable for latency sensitive setups
+    pub enabled: bool,
+    /// when the selector isn't found in the verifier's code (e.g. behind a proxy), dry-run it
+    pub probe_selector: bool,
+    /// how long the result of a check is reused for the same verifier and selector
+    pub cache_ttl: Duration,
+}
+
+impl Default for VerifierCheckConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            probe_selector: true,
+            cache_ttl: Duration::from_secs(300),
+        }
+    }
+}
+
+/// Outcome of checking a verifier
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerifierStatus {
+    /// code is deployed and the selector is (or wasn't probed for being) supported
+    Supported,
+    /// no code is deployed at the verifier address
+    NoCode,
+    /// the selector isn't in the verifier's code and probing it reverted without data, which both
+    /// a dispatcher not matching it and a function failing a reason-less check do
+    Inconclusive,
+}
+
+/// Checks that the verifier an intent names is deployed and supports the selector, so a provider
+/// doesn't prove a request whose resolve transaction can never succeed.
+pub struct VerifierChecker<T, P, N>
+where
+    T: Transport + Clone + Send + Sync,
+    P: Provider<T, N> + Clone + Send + Sync,
+    N: Network + Clone + Send + Sync,
+{
+    rpc_provider: P,
+    pub config: VerifierCheckConfig,
+    cache: Mutex<HashMap<(Address, FixedBytes<4>), (Instant, VerifierStatus)>>,
+    rpc_retry: RetryPolicy,
+    phantom_data: PhantomData<(T, N)>,
+}
+
+impl<T, P, N> VerifierChecker<T, P, N>
+where
+    T: Transport + Clone + Send + Sync,
+    P: Provider<T, N> + Clone + Send + Sync,
+    N: Network + Clone + Send + Sync,
+{
+    pub fn new(rpc_provider: P, config: VerifierCheckConfig) -> Self {
+        Self {
+            rpc_provider,
+            config,
+            cache: Mutex::new(HashMap::new()),
+            rpc_retry: RetryPolicy::default(),
+            phantom_data: PhantomData,
+        }
+    }
+
+    /// attempt the rpc calls checking verifiers again as `rpc_retry` allows when they fail
+    /// transiently. Probes reverting are answers, not failures, and aren't attempted again.
+    pub fn with_rpc_retry(mut self, rpc_retry: RetryPolicy) -> Self {
+        self.rpc_retry = rpc_retry;
+        self
+    }
+
+    /// Reject verifiers without code or not supporting the selector
+    pub async fn ensure_supported(&self, verifier: Address, selector: FixedBytes<4>) -> Result<()> {
+        match self.check(verifier, selector).await? {
+            VerifierStatus::Supported => Ok(()),
+            VerifierStatus::NoCode => Err(ClientError::IntentAnalysisError(format!(
+                "verifier {verifier} has no deployed code"
+            ))),
+            VerifierStatus::Inconclusive => Err(ClientError::IntentAnalysisError(format!(
+                "could not confirm verifier {verifier} supports selector {selector}"
+            ))),
+        }
+    }
+
+    /// Check the verifier, reusing results younger than the cache ttl. Rpc failures and
+    /// inconclusive results are not cached.
+    pub async fn check(
+        &self,
+        verifier: Address,
+        selector: FixedBytes<4>,
+    ) -> Result<VerifierStatus> {
+        if !self.config.enabled {
+            return Ok(VerifierStatus::Supported);
+        }
+        if let Some((checked_at, status)) = self
+            .cache
+            .lock()
+            .expect("verifier cache lock poisoned")
+            .get(&(verifier, selector))
+        {
+            if checked_at.elapsed() < self.config.cache_ttl {
+                return Ok(status.clone());
+            }
+        }
+
+        let status = self.fetch_status(verifier, selector).await?;
+        if status == VerifierStatus::Inconclusive {
+            return Ok(status);
+        }
+        self.cache
+            .lock()
+            .expect("verifier cache lock poisoned")
+            .insert((verifier, selector), (Instant::now(), status.clone()));
+        Ok(status)
+    }
+
+    async fn fetch_status(
+        &self,
+        verifier: Address,
+        selector: FixedBytes<4>,
+    ) -> Result<VerifierStatus> {
+        let rpc_provider = &self.rpc_provider;
+        let code = retry_rpc(&self.rpc_retry, || async move {
+            rpc_provider.get_code_at(verifier).await
+        })
+        .await?;
+        if code.is_empty() {
+            return Ok(VerifierStatus::NoCode);
+        }
+        if !self.config.probe_selector || dispatches_selector(&code, selector) {
+            return Ok(VerifierStatus::Supported);
+        }
+
+        // the selector may be dispatched by another contract (e.g. the implementation behind a
+        // proxy), dry-run it with dummy arguments
+        let calldata: Bytes = [selector.as_slice(), &[0u8; PROBE_ARGUMENTS_LENGTH]]
+            .concat()
+            .into();
+        let probe = &N::TransactionRequest::default()
+            .with_to(verifier)
+            .with_input(calldata);
+        retry_rpc(&self.rpc_retry, || async move {
+            match rpc_provider.call(probe).await {
+                Ok(_) => Ok(VerifierStatus::Supported),
+                Err(e) => match e.as_error_resp() {
+                    // reverting with a reason means the function ran and rejected the dummy
+                    // arguments, reverting without data may be either a dispatcher or the function
+                    // rejecting them
+                    Some(payload) => match payload.as_revert_data() {
+                        Some(reason) if !reason.is_empty() => {
+                            tracing::debug!("verifier {verifier} probe reverted with {reason}");
+                            Ok(VerifierStatus::Supported)
+                        }
+                        _ => Ok(VerifierStatus::Inconclusive),
+                    },
+                    None => Err(e),
+                },
+            }
+        })
+        .await
+    }
+}
+
+/// Whether the code pushes `selector` to compare the calldata selector against, walking the
+/// instructions so the data of other pushes isn't mistaken for one
+fn dispatches_selector(code: &[u8], selector: FixedBytes<4>) -> bool {
+    let significant = strip_leading_zeros(selector.as_slice());
+    let mut pc = 0;
+    while pc < code.len() {
+        let opcode = code[pc];
+        pc += 1;
+        if (PUSH1..=PUSH32).contains(&opcode) {
+            let length = usize::from(opcode - PUSH1) + 1;
+            let Some(data) = code.get(pc..pc + length) else {
+                return false;
+            };
+            if length <= selector.len()
+                && !significant.is_empty()
+                && strip_leading_zeros(data) == significant
+            {
+                return true;
+            }
+            pc += length;
+        }
+    }
+    false
+}
+
+fn strip_leading_zeros(bytes: &[u8]) -> &[u8] {
+    &bytes[bytes.iter().take_while(|byte| **byte == 0).count()..]
+}
@@ -0,0 +1,54 @@
+use reqwest::Client;
+use taralli_primitives::capabilities::SignedCapabilities;
+use url::Url;
+
+use crate::error::{ClientError, Result};
+
+/// Advertise a provider's capabilities to the protocol server
+pub struct CapabilitiesApiClient {
+    client: Client,
+    server_url: Url,
+}
+
+impl CapabilitiesApiClient {
+    #[must_use]
+    pub fn new(server_url: Url) -> Self {
+        Self {
+            client: Client::new(),
+            server_url,
+        }
+    }
+
+    /// Register the provider's capabilities
+    pub async fn register(&self, capabilities: &SignedCapabilities) -> Result<()> {
+        let url = self
+            .server_url
+            .join("/capabilities")
+            .map_err(|e| ClientError::ServerUrlParsingError(e.to_string()))?;
+        self.send(self.client.post(url).json(capabilities)).await
+    }
+
+    /// Refresh the provider's capabilities before the server drops them
+    pub async fn refresh(&self, capabilities: &SignedCapabilities) -> Result<()> {
+        let url = self
+            .server_url
+            .join(&format!(
+                "/capabilities/{}",
+                capabilities.capabilities.provider
+            ))
+            .map_err(|e| ClientError::ServerUrlParsingError(e.to_string()))?;
+        self.send(self.client.put(url).json(capabilities)).await
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<()> {
+        let response = request
+            .send()
+            .await
+            .map_err(|e| ClientError::ServerRequestError(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(ClientError::from_server_response(response).await);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+//! Deduplication of the intents received over a subscription.
+//!
+//! Servers may broadcast an intent more than once, e.g. after a restart or when a submission is
+//! retried, and a provider acting on both copies would bid twice. Intents are remembered by id
+//! until their auction ends, past which a copy can no longer be bid on anyway. They're ordered
+//! by the end of their auction, so expired ones are dropped without scanning the live ones.
+
+use std::collections::{BTreeMap, HashMap};
+
+use taralli_primitives::alloy::primitives::FixedBytes;
+
+/// Intents remembered by default, a few minutes of sustained traffic
+pub const DEFAULT_DEDUP_CAPACITY: usize = 4096;
+
+/// Ids of the intents received lately, bounded to `capacity` entries
+#[derive(Debug)]
+pub struct RecentIntents {
+    capacity: usize,
+    // key of each intent remembered in `by_expiry`
+    keys: HashMap<FixedBytes<32>, (u64, u64)>,
+    // ids by the end of their auction in unix seconds, then by the order they were remembered
+    // in, the first to expire evicted first when full
+    by_expiry: BTreeMap<(u64, u64), FixedBytes<32>>,
+    remembered: u64,
+}
+
+impl RecentIntents {
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            keys: HashMap::with_capacity(capacity),
+            by_expiry: BTreeMap::new(),
+            remembered: 0,
+        }
+    }
+
+    /// Remember an intent whose auction ends at `end_auction_timestamp`, as seen at `now` in unix
+    /// seconds. Returns false when it was seen before and its auction hasn't ended since.
+    pub fn insert(
+        &mut self,
+        intent_id: FixedBytes<32>,
+        end_auction_timestamp: u64,
+        now: u64,
+    ) -> bool {
+        if self.capacity == 0 {
+            return true;
+        }
+        if let Some(&(expiry, remembered)) = self.keys.get(&intent_id) {
+            if expiry > now {
+                return false;
+            }
+            // seen again after its auction ended, remembered anew
+            self.by_expiry.remove(&(expiry, remembered));
+            self.keys.remove(&intent_id);
+        }
+        self.remove_expired(now);
+        while self.keys.len() >= self.capacity {
+            if let Some((_, evicted)) = self.by_expiry.pop_first() {
+                self.keys.remove(&evicted);
+            }
+        }
+        self.remembered += 1;
+        let key = (end_auction_timestamp, self.remembered);
+        self.keys.insert(intent_id, key);
+        self.by_expiry.insert(key, intent_id);
+        true
+    }
+
+    /// Forget the intents whose auction ended by `now`
+    pub fn remove_expired(&mut self, now: u64) {
+        while let Some(entry) = self.by_expiry.first_entry() {
+            if entry.key().0 > now {
+                break;
+            }
+            self.keys.remove(&entry.remove());
+        }
+    }
+
+    /// Number of intents remembered
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
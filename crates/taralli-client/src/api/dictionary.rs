@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use reqwest::Client;
+use taralli_primitives::alloy::primitives::B256;
+use taralli_primitives::compression_utils::{
+    compression::{self, DecompressionBudget},
+    dictionary::{
+        decompress_brotli_with_dictionary_bounded, dictionary_hash, dictionary_reference,
+    },
+};
+use taralli_primitives::encryption::SystemPayload;
+use url::Url;
+
+use crate::error::{ClientError, Result};
+
+/// Compression dictionaries fetched from the protocol server by hash, kept for the systems
+/// compressed against them. Dictionaries are content-addressed, a cached one never goes stale.
+#[derive(Clone)]
+pub struct DictionaryCache {
+    client: Client,
+    server_url: Url,
+    dictionaries: Arc<Mutex<HashMap<B256, Arc<[u8]>>>>,
+}
+
+impl DictionaryCache {
+    #[must_use]
+    pub fn new(server_url: Url) -> Self {
+        Self {
+            client: Client::new(),
+            server_url,
+            dictionaries: Arc::default(),
+        }
+    }
+
+    /// Cache a dictionary known beforehand, e.g. the one this client compresses with
+    pub fn insert(&self, dictionary: impl Into<Arc<[u8]>>) -> B256 {
+        let dictionary = dictionary.into();
+        let hash = dictionary_hash(&dictionary);
+        self.dictionaries
+            .lock()
+            .expect("dictionary cache lock poisoned")
+            .insert(hash, dictionary);
+        hash
+    }
+
+    /// Dictionary of the given hash, fetched from the server on first use
+    pub async fn get(&self, hash: B256) -> Result<Arc<[u8]>> {
+        if let Some(dictionary) = self
+            .dictionaries
+            .lock()
+            .expect("dictionary cache lock poisoned")
+            .get(&hash)
+        {
+            return Ok(dictionary.clone());
+        }
+
+        let url = self
+            .server_url
+            .join(&format!("/dictionaries/{hash}"))
+            .map_err(|e| ClientError::ServerUrlParsingError(e.to_string()))?;
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| ClientError::ServerRequestError(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(ClientError::from_server_response(response).await);
+        }
+        let dictionary = response
+            .bytes()
+            .await
+            .map_err(|e| ClientError::ServerRequestError(e.to_string()))?;
+        // a server answering other bytes than asked for isn't trusted with the hash
+        if dictionary_hash(&dictionary) != hash {
+            return Err(ClientError::IntentDecompressionFailed(format!(
+                "server answered a dictionary not matching hash {hash}"
+            )));
+        }
+        tracing::debug!("compression dictionary {} fetched", hash);
+        let dictionary: Arc<[u8]> = dictionary.to_vec().into();
+        self.insert(dictionary.clone());
+        Ok(dictionary)
+    }
+
+    /// Decompress a system, fetching the dictionary it was compressed against if any
+    pub async fn decompress(&self, compressed_bytes: &[u8]) -> Result<Vec<u8>> {
+        let Some(hash) = dictionary_reference(compressed_bytes) else {
+            return Ok(compression::decompress_brotli(compressed_bytes).await?);
+        };
+        let dictionary = self.get(hash).await?;
+        Ok(decompress_brotli_with_dictionary_bounded(
+            compressed_bytes,
+            Some(&dictionary),
+            &DecompressionBudget::default(),
+        )
+        .await?)
+    }
+
+    /// Decompress a system payload, in the clear or encrypted, fetching the dictionary it was
+    /// compressed against if any
+    pub async fn decompress_system_payload(
+        &self,
+        compressed_bytes: &[u8],
+    ) -> Result<SystemPayload> {
+        let decompressed = self.decompress(compressed_bytes).await?;
+        serde_json::from_slice(&decompressed)
+            .map_err(|e| ClientError::IntentDecompressionFailed(e.to_string()))
+    }
+}
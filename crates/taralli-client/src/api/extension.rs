@@ -0,0 +1,133 @@
+use std::time::Duration;
+
+use reqwest::{Client, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+use taralli_primitives::alloy::primitives::B256;
+use taralli_primitives::extension::{ExtensionRequest, ExtensionResponse};
+use url::Url;
+
+use crate::error::{ClientError, Result};
+
+/// Negotiate a later resolution deadline of a won request through the protocol server
+pub struct ExtensionApiClient {
+    client: Client,
+    server_url: Url,
+}
+
+impl ExtensionApiClient {
+    #[must_use]
+    pub fn new(server_url: Url) -> Self {
+        Self {
+            client: Client::new(),
+            server_url,
+        }
+    }
+
+    /// Ask the requester of a won request for a later resolution deadline
+    pub async fn request_extension(
+        &self,
+        intent_id: B256,
+        extension_request: &ExtensionRequest,
+    ) -> Result<()> {
+        self.post(
+            &format!("/intents/{intent_id}/extension-request"),
+            extension_request,
+        )
+        .await
+    }
+
+    /// Extension request pending on one of the requester's requests, if any
+    pub async fn pending_extension_request(
+        &self,
+        intent_id: B256,
+    ) -> Result<Option<ExtensionRequest>> {
+        self.get(&format!("/intents/{intent_id}/extension-request"))
+            .await
+    }
+
+    /// Approve or decline the pending extension request of one of the requester's requests
+    pub async fn respond(
+        &self,
+        intent_id: B256,
+        extension_response: &ExtensionResponse,
+    ) -> Result<()> {
+        self.post(
+            &format!("/intents/{intent_id}/extension-response"),
+            extension_response,
+        )
+        .await
+    }
+
+    /// Requester's answer to the extension request of a won request, if any
+    pub async fn fetch_response(&self, intent_id: B256) -> Result<Option<ExtensionResponse>> {
+        self.get(&format!("/intents/{intent_id}/extension-response"))
+            .await
+    }
+
+    /// Poll the requester's answer to the extension request of a won request every
+    /// `poll_interval`, giving up after `timeout`
+    pub async fn await_response(
+        &self,
+        intent_id: B256,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<ExtensionResponse> {
+        tokio::time::timeout(timeout, async {
+            loop {
+                if let Some(extension_response) = self.fetch_response(intent_id).await? {
+                    return Ok(extension_response);
+                }
+                tokio::time::sleep(poll_interval).await;
+            }
+        })
+        .await
+        .map_err(|_| {
+            ClientError::ExtensionError(format!(
+                "no answer to the extension request of request {intent_id} within {}s",
+                timeout.as_secs()
+            ))
+        })?
+    }
+
+    async fn post<B: Serialize>(&self, path: &str, body: &B) -> Result<()> {
+        let url = self
+            .server_url
+            .join(path)
+            .map_err(|e| ClientError::ServerUrlParsingError(e.to_string()))?;
+        let response = self
+            .client
+            .post(url)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| ClientError::ServerRequestError(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(ClientError::from_server_response(response).await);
+        }
+        Ok(())
+    }
+
+    async fn get<R: DeserializeOwned>(&self, path: &str) -> Result<Option<R>> {
+        let url = self
+            .server_url
+            .join(path)
+            .map_err(|e| ClientError::ServerUrlParsingError(e.to_string()))?;
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| ClientError::ServerRequestError(e.to_string()))?;
+
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => response
+                .json::<R>()
+                .await
+                .map(Some)
+                .map_err(|e| ClientError::DeserializationError(e.to_string())),
+            _ => Err(ClientError::from_server_response(response).await),
+        }
+    }
+}
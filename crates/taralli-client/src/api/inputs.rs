@@ -0,0 +1,109 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::{Client, StatusCode};
+use taralli_primitives::alloy::{primitives::B256, signers::Signer};
+use taralli_primitives::offer_inputs::{InputsQuery, OfferInputs};
+use url::Url;
+
+use crate::error::{ClientError, Result};
+
+/// Relay the inputs of won offers through the protocol server
+pub struct OfferInputsApiClient {
+    client: Client,
+    server_url: Url,
+}
+
+impl OfferInputsApiClient {
+    #[must_use]
+    pub fn new(server_url: Url) -> Self {
+        Self {
+            client: Client::new(),
+            server_url,
+        }
+    }
+
+    /// Deliver the inputs of an offer won by the requester to its provider
+    pub async fn deliver_inputs(&self, offer_id: B256, offer_inputs: &OfferInputs) -> Result<()> {
+        let url = self.inputs_url(offer_id)?;
+        let response = self
+            .client
+            .post(url)
+            .json(offer_inputs)
+            .send()
+            .await
+            .map_err(|e| ClientError::ServerRequestError(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(ClientError::from_server_response(response).await);
+        }
+        Ok(())
+    }
+
+    /// Inputs delivered for an offer, if any. The server only serves them to the offer's
+    /// provider, the query being signed by `signer`.
+    pub async fn fetch_inputs<S: Signer>(
+        &self,
+        offer_id: B256,
+        signer: &S,
+    ) -> Result<Option<OfferInputs>> {
+        let url = self
+            .server_url
+            .join(&format!("/offers/{offer_id}/inputs/query"))
+            .map_err(|e| ClientError::ServerUrlParsingError(e.to_string()))?;
+        let issued_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time before unix epoch")
+            .as_secs();
+        let inputs_query = InputsQuery::sign(offer_id, issued_at, signer)
+            .await
+            .map_err(|e| ClientError::ServerRequestError(e.to_string()))?;
+        let response = self
+            .client
+            .post(url)
+            .json(&inputs_query)
+            .send()
+            .await
+            .map_err(|e| ClientError::ServerRequestError(e.to_string()))?;
+
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => response
+                .json::<OfferInputs>()
+                .await
+                .map(Some)
+                .map_err(|e| ClientError::DeserializationError(e.to_string())),
+            _ => Err(ClientError::from_server_response(response).await),
+        }
+    }
+
+    /// Poll the inputs delivered for an offer every `poll_interval`, giving up after `timeout`
+    pub async fn await_inputs<S: Signer>(
+        &self,
+        offer_id: B256,
+        signer: &S,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<OfferInputs> {
+        tokio::time::timeout(timeout, async {
+            loop {
+                if let Some(offer_inputs) = self.fetch_inputs(offer_id, signer).await? {
+                    return Ok(offer_inputs);
+                }
+                tokio::time::sleep(poll_interval).await;
+            }
+        })
+        .await
+        .map_err(|_| {
+            ClientError::OfferInputsError(format!(
+                "no inputs delivered for offer {offer_id} within {}s",
+                timeout.as_secs()
+            ))
+        })?
+    }
+
+    fn inputs_url(&self, offer_id: B256) -> Result<Url> {
+        self.server_url
+            .join(&format!("/offers/{offer_id}/inputs"))
+            .map_err(|e| ClientError::ServerUrlParsingError(e.to_string()))
+    }
+}
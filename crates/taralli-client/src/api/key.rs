@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+use reqwest::{Client, StatusCode};
+use serde::{de::DeserializeOwned, Serialize};
+use taralli_primitives::alloy::primitives::B256;
+use taralli_primitives::encryption::{KeyDelivery, KeyRequest};
+use url::Url;
+
+use crate::error::{ClientError, Result};
+
+/// Exchange the content key of an encrypted request through the protocol server
+pub struct KeyExchangeApiClient {
+    client: Client,
+    server_url: Url,
+}
+
+impl KeyExchangeApiClient {
+    #[must_use]
+    pub fn new(server_url: Url) -> Self {
+        Self {
+            client: Client::new(),
+            server_url,
+        }
+    }
+
+    /// Ask the requester of a won request for its content key
+    pub async fn request_key(&self, intent_id: B256, key_request: &KeyRequest) -> Result<()> {
+        self.post(&format!("/intents/{intent_id}/key/request"), key_request)
+            .await
+    }
+
+    /// Key request pending on one of the requester's requests, if any
+    pub async fn pending_key_request(&self, intent_id: B256) -> Result<Option<KeyRequest>> {
+        self.get(&format!("/intents/{intent_id}/key/request")).await
+    }
+
+    /// Answer the pending key request of one of the requester's requests
+    pub async fn deliver_key(&self, intent_id: B256, key_delivery: &KeyDelivery) -> Result<()> {
+        self.post(&format!("/intents/{intent_id}/key"), key_delivery)
+            .await
+    }
+
+    /// Key delivered for a won request, if any
+    pub async fn fetch_key(&self, intent_id: B256) -> Result<Option<KeyDelivery>> {
+        self.get(&format!("/intents/{intent_id}/key")).await
+    }
+
+    /// Poll the key delivered for a won request every `poll_interval`, giving up after `timeout`
+    pub async fn await_key(
+        &self,
+        intent_id: B256,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<KeyDelivery> {
+        tokio::time::timeout(timeout, async {
+            loop {
+                if let Some(key_delivery) = self.fetch_key(intent_id).await? {
+                    return Ok(key_delivery);
+                }
+                tokio::time::sleep(poll_interval).await;
+            }
+        })
+        .await
+        .map_err(|_| {
+            ClientError::KeyExchangeError(format!(
+                "no key delivered for request {intent_id} within {}s",
+                timeout.as_secs()
+            ))
+        })?
+    }
+
+    async fn post<B: Serialize>(&self, path: &str, body: &B) -> Result<()> {
+        let url = self
+            .server_url
+            .join(path)
+            .map_err(|e| ClientError::ServerUrlParsingError(e.to_string()))?;
+        let response = self
+            .client
+            .post(url)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| ClientError::ServerRequestError(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(ClientError::from_server_response(response).await);
+        }
+        Ok(())
+    }
+
+    async fn get<R: DeserializeOwned>(&self, path: &str) -> Result<Option<R>> {
+        let url = self
+            .server_url
+            .join(path)
+            .map_err(|e| ClientError::ServerUrlParsingError(e.to_string()))?;
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| ClientError::ServerRequestError(e.to_string()))?;
+
+        match response.status() {
+            StatusCode::NOT_FOUND => Ok(None),
+            status if status.is_success() => response
+                .json::<R>()
+                .await
+                .map(Some)
+                .map_err(|e| ClientError::DeserializationError(e.to_string())),
+            _ => Err(ClientError::from_server_response(response).await),
+        }
+    }
+}
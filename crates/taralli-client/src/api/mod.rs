@@ -1,5 +1,13 @@
 //! Api client utilities for taralli clients to interact with the protocol server
 
+pub mod capabilities;
+pub mod dedup;
+pub mod dictionary;
+pub mod extension;
+pub mod inputs;
+pub mod key;
 pub mod query;
+pub mod retry;
+pub mod status;
 pub mod submit;
 pub mod subscribe;
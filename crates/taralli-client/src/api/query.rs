@@ -1,22 +1,72 @@
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use reqwest::{
     header::{HeaderMap, HeaderValue},
     Client,
 };
-use taralli_primitives::compression_utils::db::StoredIntent;
+use serde::Deserialize;
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta::ProofRequest;
+use taralli_primitives::alloy::primitives::{Bytes, PrimitiveSignature, B256};
+use taralli_primitives::alloy::signers::Signer;
+use taralli_primitives::compression_utils::{db::StoredIntent, dictionary::dictionary_reference};
 use taralli_primitives::env::Environment;
+use taralli_primitives::request_metadata::MetadataQuery;
 use taralli_primitives::{
-    intents::offer::ComputeOffer,
+    intents::{
+        envelope::IntentMetadata, offer::ComputeOffer, request::ComputeRequest, ComputeIntent,
+    },
     systems::{SystemId, SystemParams},
 };
 use url::Url;
 
+use crate::api::dictionary::DictionaryCache;
 use crate::error::{ClientError, Result};
 
-/// Query `ComputeOffers` stored within the protocol server's intent db
+/// Request persisted by the server's intent store, as returned by the `/requests` route
+#[derive(Debug, Deserialize)]
+struct StoredRequestResponse {
+    system_id: SystemId,
+    /// brotli compressed `SystemParams`
+    system: Bytes,
+    /// json encoded `ProofRequest`
+    proof_commitment: Bytes,
+    signature: Bytes,
+}
+
+impl StoredRequestResponse {
+    async fn into_request(
+        self,
+        dictionaries: &DictionaryCache,
+    ) -> Result<ComputeRequest<SystemParams>> {
+        let system = dictionaries
+            .decompress(&self.system)
+            .await
+            .and_then(|system| {
+                serde_json::from_slice(&system)
+                    .map_err(|e| ClientError::IntentDecompressionFailed(e.to_string()))
+            })
+            .map_err(|e| ClientError::IntentParsingError(e.to_string()))?;
+        let proof_request: ProofRequest = serde_json::from_slice(&self.proof_commitment)
+            .map_err(|e| ClientError::IntentParsingError(e.to_string()))?;
+        let signature = PrimitiveSignature::try_from(&self.signature[..])
+            .map_err(|e| ClientError::IntentParsingError(e.to_string()))?;
+        Ok(ComputeRequest {
+            system_id: self.system_id,
+            system,
+            proof_request,
+            signature,
+        })
+    }
+}
+
+/// Query `ComputeOffers` and `ComputeRequests` stored within the protocol server's intent db
 pub struct QueryApiClient {
     _api_key: String,
     client: Client,
     server_url: Url,
+    // dictionaries of the systems compressed against one, fetched as they're referenced
+    dictionaries: DictionaryCache,
 }
 
 impl QueryApiClient {
@@ -37,6 +87,7 @@ impl QueryApiClient {
                 .default_headers(headers)
                 .build()
                 .expect("Failed to build reqwest client"),
+            dictionaries: DictionaryCache::new(server_url.clone()),
             server_url,
         }
     }
@@ -61,10 +112,7 @@ impl QueryApiClient {
 
         // Check if the response is successful
         if !response.status().is_success() {
-            return Err(ClientError::ServerRequestError(format!(
-                "Server returned error status: {}",
-                response.status()
-            )));
+            return Err(ClientError::from_server_response(response).await);
         }
 
         let response_text = response
@@ -102,21 +150,199 @@ impl QueryApiClient {
             return Ok(Vec::new());
         }
 
-        // Convert stored intents into ComputeOffers
-        let offers = stored_intents
-            .into_iter()
-            .map(|stored| {
-                let result = ComputeOffer::<SystemParams>::try_from(stored.clone());
-                if let Err(ref e) = result {
-                    tracing::error!("Failed to convert stored intent to offer: {}", e);
+        // Convert stored intents into ComputeOffers, the ones compressed against a dictionary
+        // decompressed with it
+        let mut offers = Vec::with_capacity(stored_intents.len());
+        for stored in stored_intents {
+            let result = match dictionary_reference(&stored.system) {
+                Some(_) => {
+                    let system = self.dictionaries.decompress(&stored.system).await?;
+                    stored.into_offer::<SystemParams>(&system)
                 }
-                result
-            })
-            .collect::<std::result::Result<Vec<_>, _>>()
-            .map_err(|e| ClientError::ServerRequestError(format!("Failed to parse offers: {e}")))?;
+                None => ComputeOffer::<SystemParams>::try_from(stored),
+            };
+            let offer = result.map_err(|e| {
+                tracing::error!("Failed to convert stored intent to offer: {}", e);
+                ClientError::ServerRequestError(format!("Failed to parse offers: {e}"))
+            })?;
+            offers.push(offer);
+        }
 
         tracing::info!("Successfully parsed {} offers", offers.len());
 
         Ok(offers)
     }
+
+    /// Query the requests of a system whose auction is still open at `active_at`. Requests that
+    /// fail to decode are logged and skipped.
+    pub async fn query_active_requests(
+        &self,
+        system_id: SystemId,
+        active_at: u64,
+    ) -> Result<Vec<ComputeRequest<SystemParams>>> {
+        self.query_requests(&[
+            ("system_id", system_id.as_str().to_string()),
+            ("active_at", active_at.to_string()),
+        ])
+        .await
+    }
+
+    /// Query the requests signed by `signer`, along with the metadata submitted with each, e.g.
+    /// for a requester to map its requests back to its own bookkeeping. Requests that fail to
+    /// decode are logged and skipped. `chain_id` is the chain the server validates intents for.
+    pub async fn query_signer_requests<S: Signer>(
+        &self,
+        signer: &S,
+        chain_id: u64,
+    ) -> Result<Vec<(ComputeRequest<SystemParams>, IntentMetadata)>> {
+        let requests = self
+            .query_requests(&[("signer", signer.address().to_string())])
+            .await?;
+        let mut metadata = self.query_request_metadata(signer, chain_id).await?;
+        Ok(requests
+            .into_iter()
+            .map(|request| {
+                let request_metadata = metadata.remove(&request.compute_id()).unwrap_or_default();
+                (request, request_metadata)
+            })
+            .collect())
+    }
+
+    /// Query the metadata submitted along with the requests signed by `signer`, by intent id.
+    /// The server only serves it to the requester, the query being signed by `signer` for the
+    /// chain `chain_id` the server validates intents for.
+    pub async fn query_request_metadata<S: Signer>(
+        &self,
+        signer: &S,
+        chain_id: u64,
+    ) -> Result<BTreeMap<B256, IntentMetadata>> {
+        let url = self
+            .server_url
+            .join("/requests/metadata")
+            .map_err(|e| ClientError::ServerUrlParsingError(e.to_string()))?;
+        let issued_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time before unix epoch")
+            .as_secs();
+        let metadata_query = MetadataQuery::sign(chain_id, issued_at, signer)
+            .await
+            .map_err(|e| ClientError::ServerRequestError(e.to_string()))?;
+
+        let response = self
+            .client
+            .post(url)
+            .json(&metadata_query)
+            .send()
+            .await
+            .map_err(|e| ClientError::ServerRequestError(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(ClientError::from_server_response(response).await);
+        }
+
+        let json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| ClientError::ServerRequestError(format!("Invalid JSON response: {e}")))?;
+        json.get("metadata")
+            .cloned()
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| {
+                ClientError::ServerRequestError(format!("Failed to parse request metadata: {e}"))
+            })?
+            .ok_or_else(|| {
+                ClientError::ServerRequestError(
+                    "Invalid response format: missing 'metadata' field".into(),
+                )
+            })
+    }
+
+    /// Query the `/requests` route with the given filters, following its pages to the last
+    async fn query_requests(
+        &self,
+        filters: &[(&str, String)],
+    ) -> Result<Vec<ComputeRequest<SystemParams>>> {
+        let mut stored_requests = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (page, next_cursor) = self.query_requests_page(filters, cursor).await?;
+            stored_requests.extend(page);
+            cursor = next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        let mut requests = Vec::with_capacity(stored_requests.len());
+        for stored in stored_requests {
+            match stored.into_request(&self.dictionaries).await {
+                Ok(request) => requests.push(request),
+                Err(e) => tracing::error!("Failed to convert stored request: {}", e),
+            }
+        }
+
+        tracing::info!("Successfully parsed {} requests", requests.len());
+
+        Ok(requests)
+    }
+
+    /// Query a page of the `/requests` route with the given filters, starting after `cursor`,
+    /// returning the cursor of the next page along with it
+    async fn query_requests_page(
+        &self,
+        filters: &[(&str, String)],
+        cursor: Option<B256>,
+    ) -> Result<(Vec<StoredRequestResponse>, Option<B256>)> {
+        let mut url = self
+            .server_url
+            .join("/requests")
+            .map_err(|e| ClientError::ServerUrlParsingError(e.to_string()))?;
+        url.query_pairs_mut().extend_pairs(filters);
+        if let Some(cursor) = cursor {
+            url.query_pairs_mut()
+                .append_pair("after", &cursor.to_string());
+        }
+
+        tracing::info!("Querying market requests at URL: {}", url);
+
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| ClientError::ServerRequestError(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(ClientError::from_server_response(response).await);
+        }
+
+        let json: serde_json::Value = response
+            .json()
+            .await
+            .map_err(|e| ClientError::ServerRequestError(format!("Invalid JSON response: {e}")))?;
+        let stored_requests: Vec<StoredRequestResponse> = json
+            .get("requests")
+            .cloned()
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| {
+                ClientError::ServerRequestError(format!("Failed to parse stored requests: {e}"))
+            })?
+            .ok_or_else(|| {
+                ClientError::ServerRequestError(
+                    "Invalid response format: missing 'requests' field".into(),
+                )
+            })?;
+        // absent from the responses of servers serving every request at once
+        let next_cursor = json
+            .get("next_cursor")
+            .cloned()
+            .map(serde_json::from_value::<Option<B256>>)
+            .transpose()
+            .map_err(|e| ClientError::ServerRequestError(format!("Invalid cursor: {e}")))?
+            .flatten();
+
+        Ok((stored_requests, next_cursor))
+    }
 }
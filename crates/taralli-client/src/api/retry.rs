@@ -0,0 +1,81 @@
+//! Retry policy for calls to the protocol server, also bounding the retries of rpc calls (see
+//! `rpc::retry_rpc`)
+
+use rand::Rng;
+use reqwest::{header::RETRY_AFTER, Response, StatusCode};
+use std::time::Duration;
+
+/// How failed calls to the protocol server are retried. Calls are retried on connection errors,
+/// timeouts, 5xx and 429 responses with exponential backoff and full jitter, until either
+/// `max_retries` is reached or the next attempt would start after `deadline`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// retries after the first attempt, 0 disables retrying
+    pub max_retries: u32,
+    /// upper bound of the backoff before the first retry
+    pub initial_backoff: Duration,
+    /// upper bound of the backoff between any two attempts
+    pub max_backoff: Duration,
+    /// total time budget for all attempts of a call
+    pub deadline: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(8),
+            deadline: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy making a single attempt
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Upper bound of the backoff before the given retry (starting at 0), doubling per retry
+    pub fn backoff_ceiling(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+
+    /// Backoff before the given retry (starting at 0), drawn uniformly up to its ceiling
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let ceiling = self.backoff_ceiling(retry);
+        if ceiling.is_zero() {
+            return ceiling;
+        }
+        rand::thread_rng().gen_range(Duration::ZERO..=ceiling)
+    }
+}
+
+/// Whether a response is a transient failure worth retrying
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// Whether a request error is a transient failure worth retrying
+pub fn is_retryable_error(error: &reqwest::Error) -> bool {
+    error.is_connect() || error.is_timeout()
+}
+
+/// Delay requested by the server through a `Retry-After` header given in seconds
+pub fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+}
@@ -0,0 +1,277 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use taralli_primitives::alloy::primitives::{Address, U256};
+use taralli_primitives::capabilities::CapabilitySummary;
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::systems::{SystemId, SystemIdMask, SystemParams};
+use taralli_primitives::validation::limits::ResourceLimits;
+use taralli_primitives::PrimitivesError;
+use url::Url;
+
+use crate::error::{ClientError, Result};
+
+/// Status reported by the protocol server's `/status` endpoint
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ServerStatus {
+    pub version: String,
+    pub systems: Vec<SystemStatus>,
+    pub active_subscriptions: usize,
+    pub validation: ValidationBounds,
+    pub markets: MarketAddresses,
+}
+
+/// A supported system along with the number of providers subscribed to it and the capabilities
+/// advertised for it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SystemStatus {
+    pub system_id: SystemId,
+    pub bit: SystemIdMask,
+    pub subscribers: usize,
+    #[serde(default)]
+    pub capabilities: CapabilitySummary,
+    /// absent from the status of servers predating per system bounds, in which case the bounds of
+    /// `ServerStatus::validation` apply
+    #[serde(default)]
+    pub validation: Option<SystemBounds>,
+}
+
+/// Time and stake bounds in effect for the intents of a single system
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SystemBounds {
+    pub request: SystemRequestBounds,
+    pub offer: SystemTimeBounds,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SystemTimeBounds {
+    pub minimum_proving_time: u32,
+    pub maximum_start_delay: u32,
+    pub minimum_auction_length: u32,
+    pub maximum_auction_length: u32,
+    /// absent from the status of servers predating it, which don't bound proving times
+    #[serde(default = "unbounded_proving_time")]
+    pub maximum_proving_time: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SystemRequestBounds {
+    #[serde(flatten)]
+    pub time: SystemTimeBounds,
+    pub maximum_allowed_stake: u128,
+}
+
+/// Validation bounds the server checks submitted intents against
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ValidationBounds {
+    pub request: RequestBounds,
+    pub offer: OfferBounds,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RequestBounds {
+    pub minimum_proving_time: u32,
+    pub maximum_start_delay: u32,
+    #[serde(default)]
+    pub minimum_auction_length: u32,
+    #[serde(default = "unbounded_auction_length")]
+    pub maximum_auction_length: u32,
+    #[serde(default = "unbounded_proving_time")]
+    pub maximum_proving_time: u32,
+    pub maximum_allowed_stake: u128,
+    pub supported_systems: Vec<SystemId>,
+    pub chain_id: u64,
+    /// absent from the status of servers predating them, in which case the default limits apply
+    #[serde(default)]
+    pub resource_limits: ResourceLimits,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OfferBounds {
+    pub minimum_proving_time: u32,
+    pub maximum_start_delay: u32,
+    #[serde(default)]
+    pub minimum_auction_length: u32,
+    #[serde(default = "unbounded_auction_length")]
+    pub maximum_auction_length: u32,
+    #[serde(default = "unbounded_proving_time")]
+    pub maximum_proving_time: u32,
+    pub maximum_allowed_reward: U256,
+    pub minimum_allowed_stake: U256,
+    pub supported_systems: Vec<SystemId>,
+    pub chain_id: u64,
+    #[serde(default)]
+    pub resource_limits: ResourceLimits,
+}
+
+fn unbounded_auction_length() -> u32 {
+    u32::MAX
+}
+
+fn unbounded_proving_time() -> u32 {
+    u32::MAX
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MarketAddresses {
+    pub universal_bombetta: Address,
+    pub universal_porchetta: Address,
+}
+
+impl ServerStatus {
+    /// Number of providers subscribed to the given system
+    #[must_use]
+    pub fn subscribers(&self, system_id: SystemId) -> usize {
+        self.systems
+            .iter()
+            .find(|system| system.system_id == system_id)
+            .map_or(0, |system| system.subscribers)
+    }
+
+    /// Capabilities advertised for the given system
+    #[must_use]
+    pub fn capabilities(&self, system_id: SystemId) -> CapabilitySummary {
+        self.systems
+            .iter()
+            .find(|system| system.system_id == system_id)
+            .map(|system| system.capabilities.clone())
+            .unwrap_or_default()
+    }
+
+    /// Bounds in effect for the intents of the given system, when the server reports them
+    #[must_use]
+    pub fn system_bounds(&self, system_id: SystemId) -> Option<&SystemBounds> {
+        self.systems
+            .iter()
+            .find(|system| system.system_id == system_id)
+            .and_then(|system| system.validation.as_ref())
+    }
+
+    /// Describe every advertised request bound the given request violates, given the current timestamp.
+    #[must_use]
+    pub fn request_bound_violations(
+        &self,
+        request: &ComputeRequest<SystemParams>,
+        latest_timestamp: u64,
+    ) -> Vec<String> {
+        let bounds = &self.validation.request;
+        let proof_request = &request.proof_request;
+        let mut violations = Vec::new();
+        // the bounds of the request's system when the server overrides them
+        let (time, maximum_allowed_stake) = match self.system_bounds(request.system_id) {
+            Some(system) => (
+                system.request.time.clone(),
+                system.request.maximum_allowed_stake,
+            ),
+            None => (
+                SystemTimeBounds {
+                    minimum_proving_time: bounds.minimum_proving_time,
+                    maximum_start_delay: bounds.maximum_start_delay,
+                    minimum_auction_length: bounds.minimum_auction_length,
+                    maximum_auction_length: bounds.maximum_auction_length,
+                    maximum_proving_time: bounds.maximum_proving_time,
+                },
+                bounds.maximum_allowed_stake,
+            ),
+        };
+
+        if !bounds.supported_systems.contains(&request.system_id) {
+            violations.push(format!(
+                "system {} is not supported by the server",
+                request.system_id.as_str()
+            ));
+        }
+        if proof_request.market != self.markets.universal_bombetta {
+            violations.push(format!(
+                "market {} does not match the server's market {}",
+                proof_request.market, self.markets.universal_bombetta
+            ));
+        }
+        if proof_request.provingTime < time.minimum_proving_time {
+            violations.push(format!(
+                "proving time {} is below the minimum of {}",
+                proof_request.provingTime, time.minimum_proving_time
+            ));
+        }
+        if proof_request.provingTime > time.maximum_proving_time {
+            violations.push(format!(
+                "proving time {} exceeds the maximum of {}",
+                proof_request.provingTime, time.maximum_proving_time
+            ));
+        }
+        let auction_length = proof_request
+            .endAuctionTimestamp
+            .saturating_sub(proof_request.startAuctionTimestamp);
+        if auction_length < u64::from(time.minimum_auction_length)
+            || auction_length > u64::from(time.maximum_auction_length)
+        {
+            violations.push(format!(
+                "auction length {auction_length}s is outside of [{}s, {}s]",
+                time.minimum_auction_length, time.maximum_auction_length
+            ));
+        }
+        if proof_request.startAuctionTimestamp
+            > latest_timestamp + u64::from(time.maximum_start_delay)
+        {
+            violations.push(format!(
+                "auction start {} exceeds the maximum start delay of {}s",
+                proof_request.startAuctionTimestamp, time.maximum_start_delay
+            ));
+        }
+        if proof_request.minimumStake > maximum_allowed_stake {
+            violations.push(format!(
+                "minimum stake {} exceeds the maximum allowed stake of {}",
+                proof_request.minimumStake, maximum_allowed_stake
+            ));
+        }
+        if let Err(e) = bounds
+            .resource_limits
+            .check(&request.system, proof_request.provingTime)
+        {
+            violations.push(match e {
+                PrimitivesError::ValidationError(reason) => reason,
+                e => e.to_string(),
+            });
+        }
+
+        violations
+    }
+}
+
+/// Fetch the protocol server's status
+pub struct StatusApiClient {
+    client: Client,
+    server_url: Url,
+}
+
+impl StatusApiClient {
+    #[must_use]
+    pub fn new(server_url: Url) -> Self {
+        Self {
+            client: Client::new(),
+            server_url,
+        }
+    }
+
+    pub async fn fetch_status(&self) -> Result<ServerStatus> {
+        let url = self
+            .server_url
+            .join("/status")
+            .map_err(|e| ClientError::ServerUrlParsingError(e.to_string()))?;
+
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| ClientError::ServerRequestError(e.to_string()))?;
+
+        if !response.status().is_success() {
+            return Err(ClientError::from_server_response(response).await);
+        }
+
+        response
+            .json::<ServerStatus>()
+            .await
+            .map_err(|e| ClientError::ServerRequestError(format!("Invalid status response: {e}")))
+    }
+}
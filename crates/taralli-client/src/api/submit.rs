@@ -1,14 +1,29 @@
+use std::sync::Arc;
+
 use reqwest::{
-    header::{HeaderMap, HeaderValue},
+    header::{HeaderMap, HeaderValue, CONTENT_TYPE},
     multipart::{Form, Part},
-    Client,
+    Client, StatusCode,
 };
+use serde::Deserialize;
 use serde_json::json;
 use taralli_primitives::{
-    compression_utils::compression::compress_brotli, env::Environment, intents::ComputeIntent,
+    alloy::primitives::B256,
+    compression_utils::{
+        compression::compress_brotli,
+        dictionary::{compress_brotli_with_dictionary, dictionary_hash},
+    },
+    env::Environment,
+    intents::envelope::{
+        IntentKind, IntentMetadata, SubmissionEnvelope, IDEMPOTENCY_KEY_HEADER,
+        INTENT_ENVELOPE_VERSION, INTENT_VERSION_HEADER,
+    },
+    intents::ComputeIntent,
 };
+use tokio::time::Instant;
 use url::Url;
 
+use crate::api::retry::{is_retryable_error, is_retryable_status, retry_after, RetryPolicy};
 use crate::error::{ClientError, Result};
 
 /// Submit compute intents to the protocol server
@@ -16,6 +31,27 @@ pub struct SubmitApiClient {
     _api_key: String,
     client: Client,
     server_url: Url,
+    retry_policy: RetryPolicy,
+    // dictionary systems are compressed against, registered with the server
+    dictionary: Option<Arc<[u8]>>,
+}
+
+/// Encoded parts of a submission, kept to rebuild the multipart form on retries
+struct SubmissionParts {
+    partial_intent_field_name: String,
+    partial_intent: String,
+    system_bytes: Vec<u8>,
+}
+
+impl SubmissionParts {
+    fn form(&self) -> Form {
+        Form::new()
+            .part(
+                self.partial_intent_field_name.clone(),
+                Part::text(self.partial_intent.clone()),
+            )
+            .part("system_bytes", Part::bytes(self.system_bytes.clone()))
+    }
 }
 
 impl SubmitApiClient {
@@ -24,6 +60,10 @@ impl SubmitApiClient {
         let mut headers = HeaderMap::new();
         headers.insert("Content-Type", HeaderValue::from_static("application/json"));
         headers.insert("Content-Encoding", HeaderValue::from_static("br"));
+        headers.insert(
+            INTENT_VERSION_HEADER,
+            HeaderValue::from(INTENT_ENVELOPE_VERSION),
+        );
 
         let mut api_key = String::new();
         if Environment::from_env_var() == Environment::Production {
@@ -37,12 +77,75 @@ impl SubmitApiClient {
                 .build()
                 .expect("Failed to build reqwest client"),
             server_url,
+            retry_policy: RetryPolicy::default(),
+            dictionary: None,
+        }
+    }
+
+    /// Set how failed submissions are retried
+    #[must_use]
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
+    /// Compress the systems of submitted intents against `dictionary`, e.g. the
+    /// `system_dictionary` of a base intent sharing their guest program. The dictionary has to be
+    /// registered with `register_dictionary`, submissions falling back to plain compression when
+    /// the server doesn't know it.
+    #[must_use]
+    pub fn with_dictionary(mut self, dictionary: impl Into<Arc<[u8]>>) -> Self {
+        self.dictionary = Some(dictionary.into());
+        self
+    }
+
+    /// Register a compression dictionary with the server, returning its hash
+    pub async fn register_dictionary(&self, dictionary: &[u8]) -> Result<B256> {
+        let url = self
+            .server_url
+            .join("/dictionaries")
+            .map_err(|e| ClientError::ServerUrlParsingError(e.to_string()))?;
+        let response = self
+            .client
+            .post(url)
+            .header(CONTENT_TYPE, "application/octet-stream")
+            .body(dictionary.to_vec())
+            .send()
+            .await
+            .map_err(|e| ClientError::ServerRequestError(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(ClientError::from_server_response(response).await);
+        }
+        #[derive(Deserialize)]
+        struct Registered {
+            hash: B256,
+        }
+        let registered: Registered = response
+            .json()
+            .await
+            .map_err(|e| ClientError::ServerRequestError(e.to_string()))?;
+        if registered.hash != dictionary_hash(dictionary) {
+            return Err(ClientError::ServerRequestError(format!(
+                "server registered the dictionary as {}",
+                registered.hash
+            )));
         }
+        Ok(registered.hash)
     }
 
-    /// Returns Multipart intent Form with two parts: `System` as a `application/octet-stream` and remaining
-    /// fields as `application/json`.
-    fn build_multipart<I: ComputeIntent>(&self, intent: I) -> Result<Form> {
+    /// Returns the parts of a Multipart intent Form: `System` as a `application/octet-stream` and remaining
+    /// fields as `application/json`, wrapped within a versioned `IntentEnvelope` along with
+    /// `metadata`. The system is compressed against `dictionary` when given.
+    fn build_multipart<I: ComputeIntent>(
+        &self,
+        intent: &I,
+        metadata: &IntentMetadata,
+        dictionary: Option<&[u8]>,
+    ) -> Result<SubmissionParts> {
         let proof_commitment_string = format!("proof_{}", intent.type_string());
 
         let partial_intent = json!({
@@ -51,24 +154,47 @@ impl SubmitApiClient {
             "signature": intent.signature(),
         });
 
-        let partial_intent_string = serde_json::to_string(&partial_intent)
-            .map_err(|e| ClientError::IntentSubmissionFailed(e.to_string()))?;
-        let partial_intent_part = Part::text(partial_intent_string);
+        let kind = IntentKind::try_from(intent.type_string().as_str())?;
+        let partial_intent_string = serde_json::to_string(&SubmissionEnvelope::new(
+            kind,
+            partial_intent,
+            metadata.clone(),
+        ))
+        .map_err(|e| ClientError::IntentSubmissionFailed(e.to_string()))?;
         let partial_intent_field_name = format!("partial_{}", intent.type_string());
 
         let system_as_bytes = serde_json::to_vec(&intent.system())
             .map_err(|e| ClientError::IntentSubmissionFailed(e.to_string()))?;
-        let compressed = compress_brotli(&system_as_bytes)?;
-        let compressed_part = Part::bytes(compressed);
-
-        let form = Form::new()
-            .part(partial_intent_field_name, partial_intent_part)
-            .part("system_bytes", compressed_part);
+        let compressed = match dictionary {
+            Some(dictionary) => compress_brotli_with_dictionary(&system_as_bytes, dictionary)?,
+            None => compress_brotli(&system_as_bytes)?,
+        };
 
-        Ok(form)
+        Ok(SubmissionParts {
+            partial_intent_field_name,
+            partial_intent: partial_intent_string,
+            system_bytes: compressed,
+        })
     }
 
+    /// Submit an intent, retrying transient failures according to the retry policy. Every attempt
+    /// carries the same idempotency key, so the server never broadcasts or stores an intent twice.
+    /// Once retries are exhausted the last failed response is returned.
+    /// An intent compressed against a dictionary the server doesn't know is submitted again with
+    /// its system compressed on its own.
     pub async fn submit_intent<I: ComputeIntent>(&self, intent: I) -> Result<reqwest::Response> {
+        self.submit_intent_with_metadata(intent, &IntentMetadata::new())
+            .await
+    }
+
+    /// Submit an intent as `submit_intent` does, along with `metadata` for the submitter's own
+    /// bookkeeping. The metadata isn't signed, the server stores it with the intent and serves it
+    /// back to queries but never broadcasts it.
+    pub async fn submit_intent_with_metadata<I: ComputeIntent>(
+        &self,
+        intent: I,
+        metadata: &IntentMetadata,
+    ) -> Result<reqwest::Response> {
         let endpoint = format!("/submit/{}", intent.type_string());
 
         let url = self
@@ -76,16 +202,64 @@ impl SubmitApiClient {
             .join(&endpoint)
             .map_err(|e| ClientError::ServerUrlParsingError(e.to_string()))?;
 
-        let payload = self.build_multipart(intent)?;
+        let parts = self.build_multipart(&intent, metadata, self.dictionary.as_deref())?;
+        let idempotency_key = hex::encode(rand::random::<[u8; 16]>());
+        let response = self.send_parts(&url, &parts, &idempotency_key).await?;
+        // submission routes only answer not found for unknown dictionaries
+        if self.dictionary.is_some() && response.status() == StatusCode::NOT_FOUND {
+            tracing::warn!("server doesn't know the compression dictionary, compressing without");
+            let parts = self.build_multipart(&intent, metadata, None)?;
+            return self.send_parts(&url, &parts, &idempotency_key).await;
+        }
+        Ok(response)
+    }
+
+    /// Post the submission parts, retrying transient failures according to the retry policy
+    async fn send_parts(
+        &self,
+        url: &Url,
+        parts: &SubmissionParts,
+        idempotency_key: &str,
+    ) -> Result<reqwest::Response> {
+        let deadline = Instant::now() + self.retry_policy.deadline;
+        let mut retry = 0;
 
-        let response = self
-            .client
-            .post(url)
-            .multipart(payload)
-            .send()
-            .await
-            .map_err(|e| ClientError::ServerRequestError(e.to_string()))?;
+        loop {
+            let result = self
+                .client
+                .post(url.clone())
+                .header(IDEMPOTENCY_KEY_HEADER, idempotency_key)
+                .multipart(parts.form())
+                .send()
+                .await;
 
-        Ok(response)
+            let backoff = match &result {
+                // a conflict means an earlier attempt with the key is still being processed
+                Ok(response)
+                    if is_retryable_status(response.status())
+                        || response.status() == StatusCode::CONFLICT =>
+                {
+                    retry_after(response).unwrap_or_else(|| self.retry_policy.backoff(retry))
+                }
+                Err(e) if is_retryable_error(e) => self.retry_policy.backoff(retry),
+                _ => return result.map_err(|e| ClientError::ServerRequestError(e.to_string())),
+            };
+
+            let resume_at = Instant::now() + backoff;
+            if retry >= self.retry_policy.max_retries || resume_at > deadline {
+                return result.map_err(|e| ClientError::ServerRequestError(e.to_string()));
+            }
+            tracing::warn!(
+                "submission attempt {} failed ({}), retrying in {:?}",
+                retry + 1,
+                match &result {
+                    Ok(response) => response.status().to_string(),
+                    Err(e) => e.to_string(),
+                },
+                backoff
+            );
+            tokio::time::sleep_until(resume_at).await;
+            retry += 1;
+        }
     }
 }
@@ -1,14 +1,36 @@
-use std::{pin::Pin, time::Duration};
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
+use base64::prelude::{Engine, BASE64_STANDARD};
 use futures::{
     stream::{SplitSink, SplitStream},
     SinkExt, Stream, StreamExt,
 };
+use serde::Deserialize;
 use taralli_primitives::{
-    compression_utils::{compression, intents::ComputeRequestCompressed},
+    alloy::primitives::{Address, FixedBytes},
+    compression_utils::{
+        frames::{
+            inflate_request_frame_ref, is_deflated_frame, DEFLATE_FRAME_COMPRESSION,
+            FRAME_COMPRESSION_HEADER,
+        },
+        intents::{CompressedIntent, ComputeOfferCompressed},
+    },
+    encryption::{EncryptedSystemParams, SystemPayload},
     env::Environment,
-    intents::request::ComputeRequest,
-    systems::{SystemIdMask, SystemParams},
+    intents::envelope::{
+        unix_time_ms, BroadcastMeta, IntentKind, SubscriptionMask, INTENT_ENVELOPE_VERSION,
+        INTENT_VERSION_HEADER, LEGACY_INTENT_VERSION, SSE_CONTROL_EVENT, SSE_INTENT_EVENT,
+    },
+    intents::{offer::ComputeOffer, request::ComputeRequest, ComputeIntent},
+    systems::{SystemId, SystemIdMask, SystemParams},
 };
 use tokio::{net::TcpStream, signal, time::timeout};
 use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
@@ -19,18 +41,110 @@ use tungstenite::{
 };
 use url::Url;
 
+use crate::api::dedup::{RecentIntents, DEFAULT_DEDUP_CAPACITY};
+use crate::api::dictionary::DictionaryCache;
 use crate::error::{ClientError, Result};
 
 // type alias for stream of compute requests returned by the protocol server
 pub type ComputeRequestStream =
     Pin<Box<dyn Stream<Item = Result<ComputeRequest<SystemParams>>> + Send>>;
 
-/// Subscribe over websocket stream to broadcasts as new `ComputeRequest`'s are submitted to
-/// the protocol server
+// type alias for stream of compute requests and control notifications returned by the protocol server
+pub type SubscriptionStream = Pin<Box<dyn Stream<Item = Result<StreamItem>> + Send>>;
+
+/// Item received over a market subscription
+#[derive(Debug)]
+pub enum StreamItem {
+    /// A newly submitted `ComputeRequest`, along with its delivery unless the server predates
+    /// broadcast metadata
+    Request(ComputeRequest<SystemParams>, Option<Delivery>),
+    /// A newly submitted `ComputeRequest` whose system params are encrypted, only the provider
+    /// winning its auction gets the key to decrypt them.
+    EncryptedRequest(ComputeRequest<EncryptedSystemParams>, Option<Delivery>),
+    /// A newly submitted `ComputeOffer`, only received by clients subscribed to offers
+    Offer(ComputeOffer<SystemParams>, Option<Delivery>),
+    /// The subscription fell behind and the server skipped this many broadcast messages.
+    /// Missed intents have to be backfilled through the query api.
+    Lagged(u64),
+    /// The auction of a broadcast request ended, it can no longer be bid on.
+    Expired {
+        intent_id: FixedBytes<32>,
+        system_id: SystemId,
+    },
+    Saturated {
+        sequence: Option<u64>,
+    },
+}
+
+/// How a broadcast intent reached this client
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Delivery {
+    /// sequence number the server stamped the intent with, increasing across all systems
+    pub sequence: u64,
+    /// unix time in milliseconds at which the server received the intent's submission
+    pub server_received_at_ms: u64,
+    /// unix time in milliseconds at which this client received the broadcast
+    pub received_at_ms: u64,
+}
+
+impl Delivery {
+    #[must_use]
+    pub fn new(meta: BroadcastMeta, received_at_ms: u64) -> Self {
+        Self {
+            sequence: meta.sequence,
+            server_received_at_ms: meta.received_at_ms,
+            received_at_ms,
+        }
+    }
+
+    /// Time from the server receiving the submission to this client receiving its broadcast.
+    /// Zero when this client's clock runs behind the server's.
+    #[must_use]
+    pub fn delay(&self) -> Duration {
+        Duration::from_millis(
+            self.received_at_ms
+                .saturating_sub(self.server_received_at_ms),
+        )
+    }
+}
+
+/// Control frames the server sends as JSON text messages
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ControlFrame {
+    Lagged {
+        skipped: u64,
+    },
+    Expired {
+        intent_id: FixedBytes<32>,
+        system_id: SystemId,
+    },
+}
+
+/// Subscribe over websocket stream to broadcasts as new `ComputeRequest`'s, and optionally
+/// `ComputeOffer`'s, are submitted to the protocol server, or over server-sent events where
+/// websockets are blocked
+#[derive(Clone)]
 pub struct SubscribeApiClient {
     server_url: Url,
     api_key: String,
     pub subscribed_to: SystemIdMask,
+    /// systems whose offers are received over the same subscription, none by default
+    pub offers_subscribed_to: SystemIdMask,
+    /// buffer size requested to the server, bounded by the server's broadcast buffer capacity
+    pub buffer_size: Option<usize>,
+    /// ask the server for deflated frames, which it may decline
+    pub frame_compression: bool,
+    /// provider address sent to the server, which skips or flags requests while the provider's
+    /// advertised capabilities report no free slot for them
+    pub provider: Option<Address>,
+    /// subscribe over server-sent events when the websocket upgrade fails
+    pub sse_fallback: bool,
+    /// number of intents remembered to drop the ones broadcast again, zero disables deduplication
+    pub dedup_capacity: usize,
+    duplicates: Arc<AtomicU64>,
+    // dictionaries of the systems compressed against one, fetched as they're referenced
+    dictionaries: DictionaryCache,
 }
 
 impl SubscribeApiClient {
@@ -43,11 +157,82 @@ impl SubscribeApiClient {
 
         Self {
             api_key,
+            dictionaries: DictionaryCache::new(server_url.clone()),
             server_url,
             subscribed_to: subscribe_to,
+            offers_subscribed_to: 0,
+            buffer_size: None,
+            frame_compression: false,
+            provider: None,
+            sse_fallback: false,
+            dedup_capacity: DEFAULT_DEDUP_CAPACITY,
+            duplicates: Arc::new(AtomicU64::new(0)),
         }
     }
 
+    /// Ask the server to keep at most `buffer_size` unsent messages for this subscription
+    /// before skipping the oldest ones.
+    #[must_use]
+    pub fn with_buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = Some(buffer_size);
+        self
+    }
+
+    /// Receive the offers of the systems of `mask` along with the requests, as `StreamItem::Offer`.
+    /// Servers predating offer broadcasts only send requests.
+    #[must_use]
+    pub fn with_offers(mut self, mask: SystemIdMask) -> Self {
+        self.offers_subscribed_to = mask;
+        self
+    }
+
+    /// Ask the server to send requests as deflated frames, keeping their Brotli compressed system
+    /// apart so it isn't compressed twice. Worth it for high request volumes with large extra data.
+    #[must_use]
+    pub fn with_frame_compression(mut self, enabled: bool) -> Self {
+        self.frame_compression = enabled;
+        self
+    }
+
+    /// Identify the subscription as the one of `provider`, whose capabilities are advertised to the
+    /// server, so it doesn't get requests while the provider has no free slot for them.
+    #[must_use]
+    pub fn with_provider(mut self, provider: Address) -> Self {
+        self.provider = Some(provider);
+        self
+    }
+
+    /// Fall back to server-sent events when the websocket upgrade fails, e.g. behind proxies
+    /// blocking websockets.
+    #[must_use]
+    pub fn with_sse_fallback(mut self, enabled: bool) -> Self {
+        self.sse_fallback = enabled;
+        self
+    }
+
+    /// Remember up to `capacity` intents to drop the ones the server broadcasts again until
+    /// their auction ends, zero disables deduplication.
+    #[must_use]
+    pub fn with_dedup_capacity(mut self, capacity: usize) -> Self {
+        self.dedup_capacity = capacity;
+        self
+    }
+
+    /// Url of the server subscribed to
+    pub fn server_url(&self) -> &Url {
+        &self.server_url
+    }
+
+    /// Number of intents broadcast again and dropped, across the client's subscriptions
+    pub fn duplicates_dropped(&self) -> u64 {
+        self.duplicates.load(Ordering::Relaxed)
+    }
+
+    /// Compression dictionaries fetched to decompress the received systems
+    pub fn dictionaries(&self) -> &DictionaryCache {
+        &self.dictionaries
+    }
+
     pub fn set_system_id_mask(&mut self, mask: u8) {
         self.subscribed_to |= mask;
     }
@@ -57,7 +242,7 @@ impl SubscribeApiClient {
     /// * `listener` - The listener object associated to the websocket stream.
     /// * `shutdown_receiver` - The receiving side of IPC communication linking WebSocket streams.
     /// # Returns
-    /// * A stream of requests.
+    /// * A stream of requests, lag and expiry notifications.
     /// # Errors
     /// * If the WebSocket stream is unavailable
     /// * If the WebSocket stream isn't properly serialized
@@ -68,12 +253,13 @@ impl SubscribeApiClient {
     async fn get_stream_with_shutdown(
         listener: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
         shutdown_receiver: tokio::sync::oneshot::Receiver<()>,
-    ) -> Result<ComputeRequestStream> {
+        dictionaries: DictionaryCache,
+    ) -> Result<SubscriptionStream> {
         // Start a `stream::unfold`, which keeps passing listener and shutdown_receiver to next iterations,
-        // whilst yielding `Request<ProvingSystemParams>` at the end of each iteration.
+        // whilst yielding a `StreamItem` at the end of each iteration.
         let parsed_stream = futures::stream::unfold(
-            (listener, shutdown_receiver),
-            |(mut listener, mut shutdown_receiver)| async move {
+            (listener, shutdown_receiver, dictionaries),
+            |(mut listener, mut shutdown_receiver, dictionaries)| async move {
                 loop {
                     // We will asynchronously wait for a websocket message or a shutdown signal.
                     tokio::select! {
@@ -83,41 +269,17 @@ impl SubscribeApiClient {
                                 // This is the only case that yields action from us.
                                 // We expect the server to send us serialized, Brotli-compressed, binary messages.
                                 Some(Ok(Message::Binary(bytes))) => {
-                                    // First we deserialize the data sent via the WebSocket.
-                                    let request_compressed: ComputeRequestCompressed = match bincode::deserialize(&bytes) {
-                                        Ok(rc) => rc,
-                                        Err(e) => {
-                                            let err = Err(ClientError::IntentParsingError(
-                                                format!("Failed to deserialize WebSocket data: {e:?}")
-                                            ));
-                                            // Yield an error item but continue the stream
-                                            return Some((err, (listener, shutdown_receiver)));
-                                        }
-                                    };
-
-                                    // Then, we need to decompress the system information.
-                                    let system = match compression::decompress_system(
-                                        request_compressed.system
-                                    ).await {
-                                        Ok(decompressed) => decompressed,
-                                        Err(e) => {
-                                            let err = Err(ClientError::IntentParsingError(
-                                                format!("Failed to decompress system information: {e}")
-                                            ));
-                                            return Some((err, (listener, shutdown_receiver)));
-                                        }
-                                    };
-
-                                    // Create the final Compute Request which will be received.
-                                    let request = ComputeRequest::<SystemParams> {
-                                        system_id: request_compressed.system_id,
-                                        system,
-                                        proof_request: request_compressed.proof_request,
-                                        signature: request_compressed.signature,
-                                    };
-
-                                    // Yield a successful `Ok(...)` item, continuing the stream
-                                    return Some((Ok(request), (listener, shutdown_receiver)));
+                                    let item =
+                                        decode_intent(&bytes, unix_time_ms(), &dictionaries).await;
+                                    // Yield the item, errors included, continuing the stream
+                                    return Some((item, (listener, shutdown_receiver, dictionaries)));
+                                }
+                                // Control frames are sent as text, e.g. lag notifications.
+                                Some(Ok(Message::Text(text))) => {
+                                    match decode_control(text.as_str()) {
+                                        Some(item) => return Some((Ok(item), (listener, shutdown_receiver, dictionaries))),
+                                        None => continue,
+                                    }
                                 }
                                 Some(Ok(Message::Close(cf))) => {
                                     tracing::info!("WebSocket closed by server: {:?}", cf);
@@ -153,12 +315,191 @@ impl SubscribeApiClient {
         Ok(Box::pin(parsed_stream))
     }
 
+    /// Subscribe to the markets of the client's system mask, receiving only `ComputeRequest`s.
+    /// Lag and expiry notifications as well as encrypted requests and offers are dropped, use `subscribe` to
+    /// handle them.
     pub async fn subscribe_to_markets(&self) -> Result<ComputeRequestStream> {
-        let mut url = self
-            .server_url
-            .join(format!("/subscribe?subscribed_to={}", self.subscribed_to).as_str())
-            .map_err(|e| ClientError::ServerSubscriptionError(e.to_string()))?;
+        let stream = self.subscribe().await?.filter_map(|item| async move {
+            match item {
+                Ok(StreamItem::Request(request, _)) => Some(Ok(request)),
+                Ok(
+                    StreamItem::EncryptedRequest(..)
+                    | StreamItem::Offer(..)
+                    | StreamItem::Lagged(_)
+                    | StreamItem::Expired { .. },
+                ) => None,
+                Err(e) => Some(Err(e)),
+            }
+        });
+        Ok(Box::pin(stream))
+    }
+
+    /// Subscribe to the markets of the client's system mask, receiving `ComputeRequest`s along with
+    /// notifications of the requests skipped whenever the subscription falls behind and of the
+    /// requests whose auction ended.
+    /// The subscription goes over a websocket, falling back to server-sent events when the upgrade
+    /// fails if enabled.
+    /// Intents the server broadcasts again are dropped until their auction ends.
+    pub async fn subscribe(&self) -> Result<SubscriptionStream> {
+        let stream = match self.subscribe_websocket().await {
+            Err(e) if self.sse_fallback => {
+                tracing::warn!("{e}, falling back to server-sent events");
+                self.subscribe_sse().await
+            }
+            result => result,
+        }?;
+        Ok(self.deduplicated(stream))
+    }
+
+    /// Subscribe as `subscribe` does, to the systems of `mask` instead of the client's mask, e.g.
+    /// to narrow down the subscription of a running client
+    pub async fn subscribe_to(&self, mask: SystemIdMask) -> Result<SubscriptionStream> {
+        let mut api = self.clone();
+        api.subscribed_to = mask;
+        api.subscribe().await
+    }
+
+    /// Drop the intents of `stream` seen before whose auction hasn't ended, counting them
+    fn deduplicated(&self, stream: SubscriptionStream) -> SubscriptionStream {
+        if self.dedup_capacity == 0 {
+            return stream;
+        }
+        let mut recent = RecentIntents::new(self.dedup_capacity);
+        let duplicates = self.duplicates.clone();
+        Box::pin(stream.filter(move |item| {
+            let seen = match item {
+                Ok(StreamItem::Request(request, _)) => Some((
+                    request.compute_id(),
+                    request.proof_request.endAuctionTimestamp,
+                )),
+                Ok(StreamItem::EncryptedRequest(request, _)) => Some((
+                    request.compute_id(),
+                    request.proof_request.endAuctionTimestamp,
+                )),
+                Ok(StreamItem::Offer(offer, _)) => {
+                    Some((offer.compute_id(), offer.proof_offer.endAuctionTimestamp))
+                }
+                _ => None,
+            };
+            let keep = match seen {
+                Some((intent_id, end_auction_timestamp)) => {
+                    let first =
+                        recent.insert(intent_id, end_auction_timestamp, unix_time_ms() / 1000);
+                    if !first {
+                        duplicates.fetch_add(1, Ordering::Relaxed);
+                        tracing::debug!("Dropping intent {} broadcast again", intent_id);
+                    }
+                    first
+                }
+                None => true,
+            };
+            futures::future::ready(keep)
+        }))
+    }
+
+    /// Subscribe over server-sent events, receiving the same items as over a websocket.
+    pub async fn subscribe_sse(&self) -> Result<SubscriptionStream> {
+        let url = self.subscribe_url("/subscribe/sse")?;
+        tracing::info!("Connecting to event stream: {url}");
+
+        let mut request = reqwest::Client::new()
+            .get(url)
+            .header("x-api-key", self.api_key.clone())
+            .header(INTENT_VERSION_HEADER, INTENT_ENVELOPE_VERSION)
+            .header(reqwest::header::ACCEPT, "text/event-stream");
+        if self.frame_compression {
+            request = request.header(FRAME_COMPRESSION_HEADER, DEFLATE_FRAME_COMPRESSION);
+        }
+        let response = request.send().await.map_err(|e| {
+            ClientError::ServerSubscriptionError(format!("Event stream connect error: {e}"))
+        })?;
+        if !response.status().is_success() {
+            return Err(ClientError::ServerSubscriptionError(format!(
+                "Event stream connect error: {}",
+                response.status()
+            )));
+        }
+        if self.frame_compression && response.headers().get(FRAME_COMPRESSION_HEADER).is_none() {
+            tracing::info!("Server declined frame compression, receiving uncompressed frames");
+        }
 
+        // Events are parsed as chunks arrive, the ones completed by a chunk are yielded in order.
+        let parsed_stream = futures::stream::unfold(
+            (
+                Box::pin(response.bytes_stream()),
+                SseParser::default(),
+                VecDeque::<SseEvent>::new(),
+                self.dictionaries.clone(),
+            ),
+            |(mut body, mut parser, mut events, dictionaries)| async move {
+                loop {
+                    if let Some(event) = events.pop_front() {
+                        let item = match event.event.as_str() {
+                            SSE_INTENT_EVENT => {
+                                let received_at_ms = unix_time_ms();
+                                match BASE64_STANDARD.decode(event.data.trim()) {
+                                    Ok(bytes) => {
+                                        decode_intent(&bytes, received_at_ms, &dictionaries).await
+                                    }
+                                    Err(e) => Err(ClientError::IntentParsingError(format!(
+                                        "Failed to decode event data: {e}"
+                                    ))),
+                                }
+                            }
+                            SSE_CONTROL_EVENT => match decode_control(&event.data) {
+                                Some(item) => Ok(item),
+                                None => continue,
+                            },
+                            other => {
+                                tracing::info!("Ignoring unexpected event: {other}");
+                                continue;
+                            }
+                        };
+                        return Some((item, (body, parser, events, dictionaries)));
+                    }
+                    match body.next().await {
+                        Some(Ok(chunk)) => events.extend(parser.push(&chunk)),
+                        Some(Err(e)) => {
+                            tracing::error!("Event stream error: {:?}", e);
+                            return None;
+                        }
+                        None => {
+                            tracing::info!("Event stream ended.");
+                            return None;
+                        }
+                    }
+                }
+            },
+        );
+
+        Ok(Box::pin(parsed_stream))
+    }
+
+    /// Url of the subscription route at `path`, with the client's subscription arguments
+    fn subscribe_url(&self, path: &str) -> Result<Url> {
+        let mut path = format!("{path}?subscribed_to={}", self.subscribed_to);
+        // only sent when subscribing to offers, so requests only subscriptions work with any server
+        if self.offers_subscribed_to != 0 {
+            let mask = SubscriptionMask {
+                requests: self.subscribed_to,
+                offers: self.offers_subscribed_to,
+            };
+            path.push_str(&format!("&subscription_mask={}", mask.encode()));
+        }
+        if let Some(buffer_size) = self.buffer_size {
+            path.push_str(&format!("&buffer_size={buffer_size}"));
+        }
+        if let Some(provider) = self.provider {
+            path.push_str(&format!("&provider={provider}"));
+        }
+        self.server_url
+            .join(path.as_str())
+            .map_err(|e| ClientError::ServerSubscriptionError(e.to_string()))
+    }
+
+    /// Subscribe over a websocket.
+    pub async fn subscribe_websocket(&self) -> Result<SubscriptionStream> {
+        let mut url = self.subscribe_url("/subscribe")?;
         let scheme = url.scheme().to_string();
 
         let new_scheme = match scheme.as_str() {
@@ -172,7 +513,7 @@ impl SubscribeApiClient {
 
         tracing::info!("Connecting to WebSocket: {url}");
 
-        let request = tungstenite::http::Request::builder()
+        let mut request = tungstenite::http::Request::builder()
             .uri(url.as_str())
             .header(
                 "Host",
@@ -181,18 +522,24 @@ impl SubscribeApiClient {
                 })?,
             )
             .header("x-api-key", self.api_key.clone())
+            .header(INTENT_VERSION_HEADER, INTENT_ENVELOPE_VERSION)
             .header("Sec-WebSocket-Key", generate_key())
             .header("Sec-WebSocket-Version", "13")
             .header("Connection", "Upgrade")
-            .header("Upgrade", "websocket")
-            .body(())
-            .map_err(|e| {
-                ClientError::ServerSubscriptionError(format!("ComputeRequest build error: {e}"))
-            })?;
+            .header("Upgrade", "websocket");
+        if self.frame_compression {
+            request = request.header(FRAME_COMPRESSION_HEADER, DEFLATE_FRAME_COMPRESSION);
+        }
+        let request = request.body(()).map_err(|e| {
+            ClientError::ServerSubscriptionError(format!("ComputeRequest build error: {e}"))
+        })?;
 
-        let (ws_stream, _resp) = connect_async(request).await.map_err(|e| {
+        let (ws_stream, response) = connect_async(request).await.map_err(|e| {
             ClientError::ServerSubscriptionError(format!("WebSocket connect error: {e}"))
         })?;
+        if self.frame_compression && response.headers().get(FRAME_COMPRESSION_HEADER).is_none() {
+            tracing::info!("Server declined frame compression, receiving uncompressed frames");
+        }
 
         // Split the websocket since we're only receiving data on this client side.
         // Therefore, sender side will have the purpose of sending packets to close the connection.
@@ -211,7 +558,12 @@ impl SubscribeApiClient {
         ));
 
         // Create a stream that processes messages until shutdown is received
-        let parsed_stream = Self::get_stream_with_shutdown(ws_listener, shutdown_receiver).await?;
+        let parsed_stream = Self::get_stream_with_shutdown(
+            ws_listener,
+            shutdown_receiver,
+            self.dictionaries.clone(),
+        )
+        .await?;
 
         let wrapped_stream = CleanupStream {
             inner: parsed_stream,
@@ -278,15 +630,202 @@ impl SubscribeApiClient {
     }
 }
 
-/// Wrapper around the `ComputeRequestStream` type.
+/// Decode a broadcast intent, deflated or enveloped as advertised to the server, or a bare request
+/// when the server predates the envelope. The broadcast metadata is returned unless the server
+/// predates it. The compressed system of requests is borrowed from the received message rather
+/// than copied.
+fn decode_broadcast(bytes: &[u8]) -> Result<(CompressedIntent<'_>, Option<BroadcastMeta>)> {
+    if is_deflated_frame(bytes) {
+        let (envelope, meta) = inflate_request_frame_ref(bytes)?;
+        if envelope.v > INTENT_ENVELOPE_VERSION || envelope.kind != IntentKind::Request {
+            return Err(ClientError::IntentParsingError(format!(
+                "unexpected {:?} envelope of version {}",
+                envelope.kind, envelope.v
+            )));
+        }
+        return Ok((CompressedIntent::Request(envelope.payload), meta));
+    }
+    let decoded = match CompressedIntent::decode_broadcast(bytes) {
+        Ok((envelope, meta))
+            if envelope.v != LEGACY_INTENT_VERSION && envelope.v <= INTENT_ENVELOPE_VERSION =>
+        {
+            Ok((envelope.payload, meta))
+        }
+        _ => bincode::deserialize(bytes).map(|request| (CompressedIntent::Request(request), None)),
+    };
+    decoded.map_err(|e| ClientError::DeserializationError(e.to_string()))
+}
+
+/// Decode a broadcast intent received at `received_at_ms` into a stream item
+async fn decode_intent(
+    bytes: &[u8],
+    received_at_ms: u64,
+    dictionaries: &DictionaryCache,
+) -> Result<StreamItem> {
+    // First we deserialize the broadcast data.
+    let (intent_compressed, meta) = decode_broadcast(bytes).map_err(|e| {
+        ClientError::IntentParsingError(format!("Failed to deserialize broadcast data: {e:?}"))
+    })?;
+    let delivery = meta.map(|meta| Delivery::new(meta, received_at_ms));
+    let request_compressed = match intent_compressed {
+        CompressedIntent::Request(request_compressed) => request_compressed,
+        CompressedIntent::Offer(offer_compressed) => {
+            return decode_offer(offer_compressed, delivery, dictionaries).await
+        }
+    };
+
+    // Then, we need to decompress the system information, which the requester may have encrypted
+    // or compressed against a dictionary.
+    let payload = dictionaries
+        .decompress_system_payload(&request_compressed.system)
+        .await
+        .map_err(|e| {
+            ClientError::IntentParsingError(format!("Failed to decompress system information: {e}"))
+        })?;
+
+    // Create the final Compute Request which will be received.
+    Ok(match payload {
+        SystemPayload::Plain(system) => StreamItem::Request(
+            ComputeRequest {
+                system_id: request_compressed.system_id,
+                system,
+                proof_request: request_compressed.proof_request,
+                signature: request_compressed.signature,
+            },
+            delivery,
+        ),
+        SystemPayload::Encrypted(system) => StreamItem::EncryptedRequest(
+            ComputeRequest {
+                system_id: request_compressed.system_id,
+                system,
+                proof_request: request_compressed.proof_request,
+                signature: request_compressed.signature,
+            },
+            delivery,
+        ),
+    })
+}
+
+/// Decompress the system of a broadcast offer into a stream item, offers are never encrypted
+async fn decode_offer(
+    offer_compressed: ComputeOfferCompressed,
+    delivery: Option<Delivery>,
+    dictionaries: &DictionaryCache,
+) -> Result<StreamItem> {
+    let payload = dictionaries
+        .decompress_system_payload(&offer_compressed.system)
+        .await
+        .map_err(|e| {
+            ClientError::IntentParsingError(format!("Failed to decompress system information: {e}"))
+        })?;
+    match payload {
+        SystemPayload::Plain(system) => Ok(StreamItem::Offer(
+            ComputeOffer {
+                system_id: offer_compressed.system_id,
+                system,
+                proof_offer: offer_compressed.proof_offer,
+                signature: offer_compressed.signature,
+            },
+            delivery,
+        )),
+        SystemPayload::Encrypted(_) => Err(ClientError::IntentParsingError(
+            "offers can't carry encrypted system params".to_string(),
+        )),
+    }
+}
+
+/// Decode a JSON control frame, `None` for frames this client doesn't know
+fn decode_control(text: &str) -> Option<StreamItem> {
+    match serde_json::from_str::<ControlFrame>(text) {
+        Ok(ControlFrame::Lagged { skipped }) => {
+            tracing::warn!("Subscription lagged, server skipped {} message(s)", skipped);
+            Some(StreamItem::Lagged(skipped))
+        }
+        Ok(ControlFrame::Expired {
+            intent_id,
+            system_id,
+        }) => {
+            tracing::debug!("Auction of request {} ended", intent_id);
+            Some(StreamItem::Expired {
+                intent_id,
+                system_id,
+            })
+        }
+        Ok(ControlFrame::Saturated { sequence }) => {
+            tracing::debug!(
+                "Request {:?} sent while the provider has no free slot for it",
+                sequence
+            );
+            None
+        }
+        Err(e) => {
+            tracing::info!("Ignoring unexpected control frame: {} ({})", text, e);
+            None
+        }
+    }
+}
+
+/// Event of a server-sent events stream
+#[derive(Debug, Default)]
+struct SseEvent {
+    event: String,
+    data: String,
+}
+
+/// Incremental parser of a `text/event-stream` body, fed chunks as they arrive
+#[derive(Default)]
+struct SseParser {
+    buffer: Vec<u8>,
+    event: SseEvent,
+    has_data: bool,
+}
+
+impl SseParser {
+    /// Consume a chunk of the body, returning the events it completed. Comments, such as the
+    /// server's keepalives, are skipped.
+    fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\r', '\n']);
+            if line.is_empty() {
+                // a blank line dispatches the event
+                let event = std::mem::take(&mut self.event);
+                if std::mem::take(&mut self.has_data) {
+                    events.push(event);
+                }
+                continue;
+            }
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "event" => self.event.event = value.to_string(),
+                "data" => {
+                    if self.has_data {
+                        self.event.data.push('\n');
+                    }
+                    self.event.data.push_str(value);
+                    self.has_data = true;
+                }
+                // comments and fields we don't use
+                _ => {}
+            }
+        }
+        events
+    }
+}
+
+/// Wrapper around the `SubscriptionStream` type.
 /// The intent here is to implement a custom `Drop` so we can set the closing of WebSocket conns.
 pub struct CleanupStream {
-    inner: ComputeRequestStream,
+    inner: SubscriptionStream,
     cleanup_sender: Option<tokio::sync::oneshot::Sender<()>>,
 }
 
 impl Stream for CleanupStream {
-    type Item = Result<ComputeRequest<SystemParams>>;
+    type Item = Result<StreamItem>;
 
     fn poll_next(
         mut self: Pin<&mut Self>,
@@ -0,0 +1,240 @@
+//! Registry of the programs and circuits a provider knows, so the requests it receives can be
+//! told apart by name rather than by megabytes of bytes.
+//!
+//! Artifacts (the ELF of risc0 and sp1 systems, the r1cs and witness generator wasm of arkworks
+//! circuits) are keyed by the sha256 of their bytes and carry a label and metadata assigned by
+//! the provider. A system is identified once every one of its artifacts is registered, under the
+//! label of its ELF or r1cs. In strict mode, only systems whose artifacts are all trusted are
+//! processed. The registry is read from a JSON file and updated at runtime through its shared
+//! handle, runtime updates aren't written back to the file.
+//!
+//! Checked systems are counted by label, and the label is recorded on the intent span they're
+//! checked within, so every event of the intent carries it.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use taralli_primitives::alloy::primitives::B256;
+use taralli_primitives::commitment::CommitmentHasher;
+use taralli_primitives::logging::ARTIFACT_FIELD;
+use taralli_primitives::systems::SystemParams;
+
+use crate::error::{ClientError, Result};
+
+/// label reported for systems whose artifacts aren't all registered
+pub const UNKNOWN_ARTIFACT: &str = "unknown artifact";
+
+/// Metadata of a registered artifact
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactInfo {
+    pub label: String,
+    /// seconds a proof of the artifact usually takes, informational
+    #[serde(default)]
+    pub expected_proving_seconds: Option<u64>,
+    /// whether the artifact is proven in strict mode
+    #[serde(default)]
+    pub trusted: bool,
+}
+
+/// Artifacts of a registry and its mode, as read from its file
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactRegistryConfig {
+    /// drop the requests whose artifacts aren't all registered and trusted
+    #[serde(default)]
+    pub strict: bool,
+    /// artifacts by the sha256 of their bytes
+    #[serde(default)]
+    pub artifacts: BTreeMap<B256, ArtifactInfo>,
+}
+
+/// What a registry tells about the artifacts of a system
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ArtifactMatch {
+    /// every artifact is registered, the info being the one of the ELF or r1cs
+    Known(ArtifactInfo),
+    /// digests of the artifacts that aren't registered
+    Unknown { digests: Vec<B256> },
+}
+
+impl ArtifactMatch {
+    /// Label the system is reported under
+    #[must_use]
+    pub fn label(&self) -> &str {
+        match self {
+            ArtifactMatch::Known(info) => &info.label,
+            ArtifactMatch::Unknown { .. } => UNKNOWN_ARTIFACT,
+        }
+    }
+
+    fn trusted(&self) -> bool {
+        matches!(
+            self,
+            ArtifactMatch::Known(ArtifactInfo { trusted: true, .. })
+        )
+    }
+}
+
+impl fmt::Display for ArtifactMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArtifactMatch::Known(info) => write!(f, "{}", info.label),
+            ArtifactMatch::Unknown { digests } => {
+                let digests: Vec<String> = digests.iter().map(ToString::to_string).collect();
+                write!(f, "{UNKNOWN_ARTIFACT} ({})", digests.join(", "))
+            }
+        }
+    }
+}
+
+/// Number of systems identified since the registry was created
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ArtifactStats {
+    /// systems identified, by label
+    pub matched: BTreeMap<String, u64>,
+    /// systems with artifacts that aren't registered
+    pub unknown: u64,
+    /// systems dropped in strict mode, by label
+    pub rejected: BTreeMap<String, u64>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    config: ArtifactRegistryConfig,
+    stats: ArtifactStats,
+}
+
+/// Artifacts known to a provider, shared with the running client to be updated at runtime
+#[derive(Debug, Default)]
+pub struct ArtifactRegistry {
+    state: Mutex<State>,
+}
+
+impl ArtifactRegistry {
+    pub fn new(config: ArtifactRegistryConfig) -> Self {
+        Self {
+            state: Mutex::new(State {
+                config,
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Registry read from the JSON file at `path`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| ClientError::ConfigError(format!("reading {}: {e}", path.display())))?;
+        let config = serde_json::from_str(&json)
+            .map_err(|e| ClientError::ConfigError(format!("parsing {}: {e}", path.display())))?;
+        Ok(Self::new(config))
+    }
+
+    /// Register the artifact whose sha256 is `digest`, replacing its previous info
+    pub fn register(&self, digest: B256, info: ArtifactInfo) {
+        self.state
+            .lock()
+            .unwrap()
+            .config
+            .artifacts
+            .insert(digest, info);
+    }
+
+    /// Register the artifact made of `bytes`, returning its digest
+    pub fn register_bytes(&self, bytes: &[u8], info: ArtifactInfo) -> B256 {
+        let digest = artifact_digest(bytes);
+        self.register(digest, info);
+        digest
+    }
+
+    /// Forget the artifact whose sha256 is `digest`, returning its info
+    pub fn remove(&self, digest: B256) -> Option<ArtifactInfo> {
+        self.state.lock().unwrap().config.artifacts.remove(&digest)
+    }
+
+    /// Turn strict mode on or off
+    pub fn set_strict(&self, strict: bool) {
+        self.state.lock().unwrap().config.strict = strict;
+    }
+
+    /// Identify the artifacts of `system`, counting the match
+    pub fn identify(&self, system: &SystemParams) -> ArtifactMatch {
+        let mut state = self.state.lock().unwrap();
+        let artifact_match = identify(&state.config.artifacts, system);
+        match &artifact_match {
+            ArtifactMatch::Known(info) => {
+                *state.stats.matched.entry(info.label.clone()).or_default() += 1;
+            }
+            ArtifactMatch::Unknown { .. } => state.stats.unknown += 1,
+        }
+        artifact_match
+    }
+
+    /// Identify the artifacts of `system`, recording their label on the current intent span, and
+    /// fail with `ClientError::IntentVetoed` when strict mode is on and they aren't all
+    /// registered and trusted
+    pub fn check(&self, system: &SystemParams) -> Result<ArtifactMatch> {
+        let artifact_match = self.identify(system);
+        tracing::Span::current().record(ARTIFACT_FIELD, artifact_match.label());
+        let mut state = self.state.lock().unwrap();
+        if state.config.strict && !artifact_match.trusted() {
+            *state
+                .stats
+                .rejected
+                .entry(artifact_match.label().to_string())
+                .or_default() += 1;
+            return Err(ClientError::IntentVetoed(format!(
+                "strict artifact registry doesn't trust {artifact_match}"
+            )));
+        }
+        Ok(artifact_match)
+    }
+
+    /// Current artifacts and mode
+    pub fn config(&self) -> ArtifactRegistryConfig {
+        self.state.lock().unwrap().config.clone()
+    }
+
+    /// Counters of the systems identified
+    pub fn stats(&self) -> ArtifactStats {
+        self.state.lock().unwrap().stats.clone()
+    }
+}
+
+/// Digest artifacts are registered under, the sha256 of their bytes
+#[must_use]
+pub fn artifact_digest(bytes: &[u8]) -> B256 {
+    CommitmentHasher::Sha256.hash(bytes)
+}
+
+/// Digests of the artifacts of `system`, its ELF or r1cs first
+#[must_use]
+pub fn system_artifacts(system: &SystemParams) -> Vec<B256> {
+    match system {
+        SystemParams::Risc0(params) => vec![artifact_digest(&params.elf)],
+        SystemParams::Sp1(params) => vec![artifact_digest(&params.elf)],
+        SystemParams::Arkworks(params) => {
+            vec![artifact_digest(&params.r1cs), artifact_digest(&params.wasm)]
+        }
+    }
+}
+
+fn identify(artifacts: &BTreeMap<B256, ArtifactInfo>, system: &SystemParams) -> ArtifactMatch {
+    let digests = system_artifacts(system);
+    let unknown: Vec<B256> = digests
+        .iter()
+        .filter(|digest| !artifacts.contains_key(digest))
+        .copied()
+        .collect();
+    if !unknown.is_empty() {
+        return ArtifactMatch::Unknown { digests: unknown };
+    }
+    // a trusted circuit paired with an untrusted witness generator isn't trusted
+    let trusted = digests.iter().all(|digest| artifacts[digest].trusted);
+    ArtifactMatch::Known(ArtifactInfo {
+        trusted,
+        ..artifacts[&digests[0]].clone()
+    })
+}
@@ -0,0 +1,260 @@
+//! Auditing of resolved requests: the proof a provider delivered on-chain is pulled back from the
+//! resolve transaction and verified again locally, flagging markets and verifiers that accepted a
+//! proof which doesn't hold.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use taralli_primitives::alloy::{
+    consensus::Transaction,
+    network::Network,
+    primitives::{Address, B256},
+    providers::Provider,
+    sol_types::SolCall,
+    transports::Transport,
+};
+use taralli_primitives::{
+    abi::{
+        universal_bombetta::UniversalBombetta::{self, ProofRequest, UniversalBombettaInstance},
+        verifier_details::decode_verifier_details,
+    },
+    intents::request::{compute_request_id, ComputeRequest},
+    systems::{SystemId, SystemParams},
+    validation::request::validate_request_submission,
+};
+
+use crate::api::retry::RetryPolicy;
+use crate::error::{ClientError, Result};
+use crate::rpc::retry_rpc;
+use crate::tracker::payload::DecodedProof;
+use crate::tracker::request::ComputeRequestTracker;
+
+/// Outcome of verifying a resolved proof locally
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProofVerdict {
+    Valid,
+    /// The proof doesn't verify, the reason is given
+    Invalid(String),
+    /// The proof couldn't be checked locally, the reason is given
+    Unverifiable(String),
+}
+
+/// Local verification of the proofs of one system
+#[async_trait]
+pub trait ProofVerifier: Send + Sync {
+    /// Verify a proof decoded from a resolve transaction. `system` holds the params the request
+    /// was submitted with, when they are known.
+    async fn verify(
+        &self,
+        proof: &DecodedProof,
+        system: Option<&SystemParams>,
+    ) -> Result<ProofVerdict>;
+}
+
+/// Audit of the resolution of a single request
+#[derive(Clone, Debug)]
+pub struct AuditReport {
+    pub intent_id: B256,
+    pub system_id: SystemId,
+    /// Provider which bid upon and resolved the request
+    pub provider: Address,
+    pub resolve_tx_hash: B256,
+    /// Verifier contract the market called, `None` when the request's extra data doesn't decode
+    pub verifier: Option<Address>,
+    /// Request as it was bid upon on-chain
+    pub proof_request: ProofRequest,
+    /// Outcome of the market's commitment checks, replayed against the submission
+    pub commitment: std::result::Result<(), String>,
+    pub proof: ProofVerdict,
+}
+
+impl AuditReport {
+    /// Whether the market accepted a resolution that fails the checks of the audit
+    pub fn accepted_bad_proof(&self) -> bool {
+        self.commitment.is_err() || matches!(self.proof, ProofVerdict::Invalid(_))
+    }
+}
+
+/// Client auditing resolved `ComputeRequest`s from an rpc endpoint
+pub struct AuditClient<T, P, N> {
+    rpc_provider: P,
+    market_address: Address,
+    tracker: ComputeRequestTracker<T, P, N>,
+    from_block: u64,
+    verifiers: HashMap<SystemId, Arc<dyn ProofVerifier>>,
+    rpc_retry: RetryPolicy,
+    phantom_data: PhantomData<(T, N)>,
+}
+
+impl<T, P, N> AuditClient<T, P, N>
+where
+    T: Transport + Clone + Send + Sync,
+    P: Provider<T, N> + Clone + Send + Sync,
+    N: Network + Clone + Send + Sync,
+{
+    pub fn new(rpc_provider: P, market_address: Address) -> Self {
+        Self {
+            tracker: ComputeRequestTracker::new(rpc_provider.clone(), market_address),
+            rpc_provider,
+            market_address,
+            from_block: 0,
+            verifiers: HashMap::new(),
+            rpc_retry: RetryPolicy::default(),
+            phantom_data: PhantomData,
+        }
+    }
+
+    /// Only search the market's events from `from_block` onwards
+    pub fn with_from_block(mut self, from_block: u64) -> Self {
+        self.from_block = from_block;
+        self
+    }
+
+    /// Verify the proofs of `system_id` with `verifier`, proofs of systems without a verifier
+    /// are reported as unverifiable
+    pub fn with_verifier(mut self, system_id: SystemId, verifier: Arc<dyn ProofVerifier>) -> Self {
+        self.verifiers.insert(system_id, verifier);
+        self
+    }
+
+    /// attempt the rpc calls reading the market again as `rpc_retry` allows when they fail
+    /// transiently
+    pub fn with_rpc_retry(mut self, rpc_retry: RetryPolicy) -> Self {
+        self.tracker = self.tracker.with_rpc_retry(rpc_retry.clone());
+        self.rpc_retry = rpc_retry;
+        self
+    }
+
+    /// Audit the resolution of the request `intent_id` of the system `system_id`
+    pub async fn audit_request(&self, intent_id: B256, system_id: SystemId) -> Result<AuditReport> {
+        self.audit(intent_id, system_id, None).await
+    }
+
+    /// Audit the resolution of a request as stored by the server, whose system params are passed
+    /// on to the verifier
+    pub async fn audit_stored_request(
+        &self,
+        request: &ComputeRequest<SystemParams>,
+    ) -> Result<AuditReport> {
+        let intent_id = compute_request_id(&request.proof_request, &request.signature);
+        self.audit(intent_id, request.system_id, Some(&request.system))
+            .await
+    }
+
+    async fn audit(
+        &self,
+        intent_id: B256,
+        system_id: SystemId,
+        system: Option<&SystemParams>,
+    ) -> Result<AuditReport> {
+        let (proof_request, provider) = self.fetch_bid_request(intent_id).await?;
+        let (resolve_event, resolve_tx_hash) = self.fetch_resolve(intent_id).await?;
+        let payload = self
+            .tracker
+            .fetch_resolution_payload(intent_id, &resolve_event, resolve_tx_hash, None)
+            .await?;
+
+        let commitment = validate_request_submission(
+            &proof_request,
+            &payload.opaque_submission,
+            payload.submitted_partial_commitment.unwrap_or_default(),
+        )
+        .map_err(|e| e.to_string());
+
+        let proof = match DecodedProof::decode(system_id, &payload.opaque_submission) {
+            Ok(proof) => self.verify(system_id, &proof, system).await,
+            Err(e) => ProofVerdict::Invalid(e.to_string()),
+        };
+
+        Ok(AuditReport {
+            intent_id,
+            system_id,
+            provider,
+            resolve_tx_hash,
+            verifier: decode_verifier_details(&proof_request.extraData)
+                .ok()
+                .map(|details| details.verifier),
+            proof_request,
+            commitment,
+            proof,
+        })
+    }
+
+    async fn verify(
+        &self,
+        system_id: SystemId,
+        proof: &DecodedProof,
+        system: Option<&SystemParams>,
+    ) -> ProofVerdict {
+        let Some(verifier) = self.verifiers.get(&system_id) else {
+            return ProofVerdict::Unverifiable(format!(
+                "no local verifier for {} proofs",
+                system_id.as_str()
+            ));
+        };
+        verifier
+            .verify(proof, system)
+            .await
+            .unwrap_or_else(|e| ProofVerdict::Unverifiable(e.to_string()))
+    }
+
+    /// Fetch the request as it was bid upon, from the calldata of the bid transaction, alongside
+    /// the bidding provider
+    async fn fetch_bid_request(&self, intent_id: B256) -> Result<(ProofRequest, Address)> {
+        let market_contract =
+            UniversalBombettaInstance::new(self.market_address, self.rpc_provider.clone());
+        let (bid, log) = market_contract
+            .Bid_filter()
+            .topic2(intent_id)
+            .from_block(self.from_block)
+            .query()
+            .await
+            .map_err(|e| ClientError::EventFilterError(e.to_string()))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                ClientError::TrackIntentError(format!("request {intent_id} was never bid upon"))
+            })?;
+
+        let bid_tx_hash = log.transaction_hash.ok_or_else(|| {
+            ClientError::LogParseError(format!("bid log of request {intent_id} has no tx hash"))
+        })?;
+        // a node lagging behind the one the log came from answers with none, attempted again too
+        let rpc_provider = &self.rpc_provider;
+        let bid_tx = retry_rpc(&self.rpc_retry, || async move {
+            rpc_provider
+                .get_transaction_by_hash(bid_tx_hash)
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("bid transaction {bid_tx_hash} not found"))
+        })
+        .await?;
+        let bid_call = UniversalBombetta::bidCall::abi_decode(bid_tx.input(), true)
+            .map_err(|e| ClientError::LogParseError(format!("invalid bid calldata: {e}")))?;
+
+        Ok((bid_call.request, bid.provider))
+    }
+
+    async fn fetch_resolve(&self, intent_id: B256) -> Result<(UniversalBombetta::Resolve, B256)> {
+        let market_contract =
+            UniversalBombettaInstance::new(self.market_address, self.rpc_provider.clone());
+        let (resolve, log) = market_contract
+            .Resolve_filter()
+            .topic2(intent_id)
+            .from_block(self.from_block)
+            .query()
+            .await
+            .map_err(|e| ClientError::EventFilterError(e.to_string()))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                ClientError::TrackIntentError(format!("request {intent_id} was not resolved"))
+            })?;
+
+        let resolve_tx_hash = log.transaction_hash.ok_or_else(|| {
+            ClientError::LogParseError(format!("resolve log of request {intent_id} has no tx hash"))
+        })?;
+        Ok((resolve, resolve_tx_hash))
+    }
+}
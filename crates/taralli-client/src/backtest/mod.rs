@@ -0,0 +1,145 @@
+//! Offline evaluation of bidding strategies. Intent traffic is recorded into a `Trace`, a JSONL
+//! file with one broadcast request per line, which the `BacktestSimulator` replays through an
+//! analyzer, cost model and bid policy using the production reward curve and bid timing.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use taralli_primitives::alloy::primitives::{Address, FixedBytes, U256};
+use taralli_primitives::intents::{request::ComputeRequest, ComputeIntent};
+use taralli_primitives::systems::SystemParams;
+
+use crate::error::{ClientError, Result};
+
+pub mod recorder;
+pub mod simulator;
+
+/// Winning bid of a request as observed from the market's `Bid` events
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObservedBid {
+    /// timestamp of the block including the bid
+    pub timestamp: u64,
+    pub reward_amount: U256,
+    pub provider: Address,
+}
+
+/// Request broadcast by the protocol server and the bid that won its auction, if any
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TraceEntry {
+    /// timestamp at which the request was first seen, the earliest a provider could react to it
+    pub observed_at: u64,
+    pub request: ComputeRequest<SystemParams>,
+    #[serde(default)]
+    pub winning_bid: Option<ObservedBid>,
+}
+
+impl TraceEntry {
+    pub fn request_id(&self) -> FixedBytes<32> {
+        self.request.compute_id()
+    }
+}
+
+/// Recorded requests, ordered by the time they were observed
+#[derive(Clone, Debug, Default)]
+pub struct Trace {
+    // keyed by observation time, then by order of insertion
+    entries: BTreeMap<(u64, u64), TraceEntry>,
+    // key of the entry of each recorded request
+    keys: HashMap<FixedBytes<32>, (u64, u64)>,
+    inserted: u64,
+}
+
+impl Trace {
+    /// Parse a trace from JSONL, one `TraceEntry` per non empty line
+    pub fn from_jsonl(jsonl: &str) -> Result<Self> {
+        let mut trace = Self::default();
+        for (index, line) in jsonl.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = serde_json::from_str(line).map_err(|e| {
+                ClientError::DeserializationError(format!("trace line {}: {e}", index + 1))
+            })?;
+            trace.insert(entry);
+        }
+        Ok(trace)
+    }
+
+    pub fn to_jsonl(&self) -> Result<String> {
+        let mut jsonl = String::new();
+        for entry in self.entries() {
+            let line = serde_json::to_string(entry)
+                .map_err(|e| ClientError::BacktestError(e.to_string()))?;
+            jsonl.push_str(&line);
+            jsonl.push('\n');
+        }
+        Ok(jsonl)
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let jsonl = std::fs::read_to_string(path.as_ref()).map_err(|e| {
+            ClientError::BacktestError(format!("reading {}: {e}", path.as_ref().display()))
+        })?;
+        Self::from_jsonl(&jsonl)
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        std::fs::write(path.as_ref(), self.to_jsonl()?).map_err(|e| {
+            ClientError::BacktestError(format!("writing {}: {e}", path.as_ref().display()))
+        })
+    }
+
+    /// Add an entry, merging it with a recorded entry of the same request: the earliest
+    /// observation is kept along with any known winning bid.
+    pub fn insert(&mut self, entry: TraceEntry) {
+        let request_id = entry.request_id();
+        let (key, entry) = match self.keys.get(&request_id) {
+            Some(&(observed_at, inserted)) => {
+                let mut recorded = self
+                    .entries
+                    .remove(&(observed_at, inserted))
+                    .expect("keyed requests are recorded");
+                recorded.observed_at = observed_at.min(entry.observed_at);
+                if recorded.winning_bid.is_none() {
+                    recorded.winning_bid = entry.winning_bid;
+                }
+                ((recorded.observed_at, inserted), recorded)
+            }
+            None => {
+                self.inserted += 1;
+                ((entry.observed_at, self.inserted), entry)
+            }
+        };
+        self.keys.insert(request_id, key);
+        self.entries.insert(key, entry);
+    }
+
+    /// Set the winning bid of a recorded request, returning whether the request is recorded
+    pub fn record_winning_bid(&mut self, request_id: FixedBytes<32>, bid: ObservedBid) -> bool {
+        match self
+            .keys
+            .get(&request_id)
+            .and_then(|key| self.entries.get_mut(key))
+        {
+            Some(recorded) => {
+                recorded.winning_bid = Some(bid);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Recorded requests, in the order they were observed
+    pub fn entries(&self) -> impl Iterator<Item = &TraceEntry> {
+        self.entries.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta::UniversalBombettaInstance;
+use taralli_primitives::alloy::{
+    consensus::BlockHeader,
+    eips::{BlockId, BlockNumberOrTag},
+    network::{BlockResponse, BlockTransactionsKind, Network},
+    primitives::Address,
+    providers::Provider,
+    transports::Transport,
+};
+use taralli_primitives::systems::SystemId;
+use url::Url;
+
+use crate::api::query::QueryApiClient;
+use crate::api::retry::RetryPolicy;
+use crate::error::{ClientError, Result};
+use crate::rpc::retry_rpc;
+
+use super::{ObservedBid, Trace, TraceEntry};
+
+/// Populates traces from live traffic: the open requests stored by the protocol server and the
+/// `Bid` events of the market contract they were auctioned in.
+pub struct TraceRecorder<T, P, N> {
+    api_client: QueryApiClient,
+    rpc_provider: P,
+    market_address: Address,
+    system_id: SystemId,
+    rpc_retry: RetryPolicy,
+    phantom_data: PhantomData<(T, N)>,
+}
+
+impl<T, P, N> TraceRecorder<T, P, N>
+where
+    T: Transport + Clone,
+    P: Provider<T, N> + Clone,
+    N: Network + Clone,
+{
+    pub fn new(
+        server_url: Url,
+        rpc_provider: P,
+        market_address: Address,
+        system_id: SystemId,
+    ) -> Self {
+        Self {
+            api_client: QueryApiClient::new(server_url),
+            rpc_provider,
+            market_address,
+            system_id,
+            rpc_retry: RetryPolicy::default(),
+            phantom_data: PhantomData,
+        }
+    }
+
+    /// attempt the rpc calls fetching bid block timestamps again as `rpc_retry` allows when they
+    /// fail transiently
+    pub fn with_rpc_retry(mut self, rpc_retry: RetryPolicy) -> Self {
+        self.rpc_retry = rpc_retry;
+        self
+    }
+
+    /// Record the requests of the market whose auction is open at `active_at`, returning how many
+    /// were not already recorded. Polling this regularly keeps each request's earliest sighting.
+    pub async fn record_active_requests(&self, trace: &mut Trace, active_at: u64) -> Result<usize> {
+        let recorded = trace.len();
+        self.api_client
+            .query_active_requests(self.system_id, active_at)
+            .await?
+            .into_iter()
+            .filter(|request| request.proof_request.market == self.market_address)
+            .for_each(|request| {
+                trace.insert(TraceEntry {
+                    observed_at: active_at,
+                    request,
+                    winning_bid: None,
+                })
+            });
+        Ok(trace.len() - recorded)
+    }
+
+    /// Attach the bids emitted by the market within the given blocks to the recorded requests
+    /// they won, returning how many recorded requests got a winning bid.
+    pub async fn record_winning_bids(
+        &self,
+        trace: &mut Trace,
+        from_block: u64,
+        to_block: BlockNumberOrTag,
+    ) -> Result<usize> {
+        let market_contract =
+            UniversalBombettaInstance::new(self.market_address, self.rpc_provider.clone());
+        let bids = market_contract
+            .Bid_filter()
+            .from_block(from_block)
+            .to_block(to_block)
+            .query()
+            .await
+            .map_err(|e| ClientError::EventFilterError(e.to_string()))?;
+
+        // bids of the same block share its timestamp
+        let mut block_timestamps: HashMap<u64, u64> = HashMap::new();
+        let mut recorded = 0;
+        for (bid, log) in bids {
+            let timestamp = match (log.block_timestamp, log.block_number) {
+                (Some(timestamp), _) => timestamp,
+                (None, Some(number)) => match block_timestamps.get(&number) {
+                    Some(timestamp) => *timestamp,
+                    None => {
+                        let timestamp = self.block_timestamp(number).await?;
+                        block_timestamps.insert(number, timestamp);
+                        timestamp
+                    }
+                },
+                (None, None) => {
+                    tracing::warn!("skipping pending bid on request {}", bid.requestId);
+                    continue;
+                }
+            };
+            let observed = ObservedBid {
+                timestamp,
+                reward_amount: bid.rewardAmount,
+                provider: bid.provider,
+            };
+            if trace.record_winning_bid(bid.requestId, observed) {
+                recorded += 1;
+            }
+        }
+        Ok(recorded)
+    }
+
+    async fn block_timestamp(&self, number: u64) -> Result<u64> {
+        let rpc_provider = &self.rpc_provider;
+        retry_rpc(&self.rpc_retry, || async move {
+            rpc_provider
+                .get_block(BlockId::number(number), BlockTransactionsKind::Hashes)
+                .await
+                .map_err(|e| e.to_string())?
+                .map(|block| block.header().timestamp())
+                .ok_or_else(|| format!("block {number} not found"))
+        })
+        .await
+    }
+}
@@ -0,0 +1,234 @@
+use std::sync::Arc;
+
+use taralli_primitives::alloy::primitives::{FixedBytes, U256};
+use taralli_primitives::intents::{auction::RewardCurve, request::ComputeRequest};
+use taralli_primitives::systems::SystemParams;
+
+use crate::analyzer::IntentAnalyzer;
+use crate::bidder::request::target_bid_timestamp;
+use crate::searcher::request::{CostModel, FixedCost};
+
+use super::{Trace, TraceEntry};
+
+/// Reward a provider waits for before bidding on a request
+pub trait BidPolicy: Send + Sync {
+    /// Target reward of a bid on `request` costing `cost` to fulfill, `None` to pass on it
+    fn target_amount(&self, request: &ComputeRequest<SystemParams>, cost: U256) -> Option<U256>;
+}
+
+/// Bid as soon as the reward covers both the request's minimum reward and the cost. Without a
+/// cost this is what the provider clients do, bidding for the minimum reward right away.
+pub struct BidImmediately;
+
+impl BidPolicy for BidImmediately {
+    fn target_amount(&self, request: &ComputeRequest<SystemParams>, cost: U256) -> Option<U256> {
+        let target = request.proof_request.minRewardAmount.max(cost);
+        (target <= request.proof_request.maxRewardAmount).then_some(target)
+    }
+}
+
+/// Wait for the reward to exceed the cost by `margin_bps` basis points of the cost
+pub struct TargetMargin {
+    pub margin_bps: u64,
+}
+
+impl BidPolicy for TargetMargin {
+    fn target_amount(&self, request: &ComputeRequest<SystemParams>, cost: U256) -> Option<U256> {
+        let margin = cost * U256::from(self.margin_bps) / U256::from(10_000);
+        let target = request.proof_request.minRewardAmount.max(cost + margin);
+        (target <= request.proof_request.maxRewardAmount).then_some(target)
+    }
+}
+
+/// What the simulated provider got out of a recorded request
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SimulatedOutcome {
+    /// the analyzer rejected the request or the policy passed on it
+    Skipped { reason: String },
+    /// the simulated bid landed first
+    Won {
+        bid_timestamp: u64,
+        reward: U256,
+        cost: U256,
+    },
+    /// the recorded winning bid landed at or before the simulated one
+    Lost {
+        bid_timestamp: u64,
+        winning_timestamp: u64,
+    },
+}
+
+/// Simulated outcome of a recorded request
+#[derive(Clone, Debug)]
+pub struct RequestOutcome {
+    pub request_id: FixedBytes<32>,
+    pub outcome: SimulatedOutcome,
+    /// the request was not won although winning it, at the reward its recorded winner got or at
+    /// its maximum reward when nobody bid, would have covered its cost
+    pub missed_opportunity: bool,
+}
+
+/// Aggregated results of a backtest
+#[derive(Clone, Debug, Default)]
+pub struct BacktestReport {
+    pub outcomes: Vec<RequestOutcome>,
+}
+
+impl BacktestReport {
+    pub fn requests(&self) -> usize {
+        self.outcomes.len()
+    }
+
+    pub fn bids(&self) -> usize {
+        self.wins() + self.losses()
+    }
+
+    pub fn wins(&self) -> usize {
+        self.count(|outcome| matches!(outcome, SimulatedOutcome::Won { .. }))
+    }
+
+    pub fn losses(&self) -> usize {
+        self.count(|outcome| matches!(outcome, SimulatedOutcome::Lost { .. }))
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.count(|outcome| matches!(outcome, SimulatedOutcome::Skipped { .. }))
+    }
+
+    /// Share of the simulated bids that won their auction
+    pub fn win_rate(&self) -> f64 {
+        match self.bids() {
+            0 => 0.0,
+            bids => self.wins() as f64 / bids as f64,
+        }
+    }
+
+    /// Sum of the rewards minus costs of the won requests
+    pub fn expected_profit(&self) -> U256 {
+        self.outcomes
+            .iter()
+            .filter_map(|request| match request.outcome {
+                SimulatedOutcome::Won { reward, cost, .. } => Some(reward.saturating_sub(cost)),
+                _ => None,
+            })
+            .fold(U256::ZERO, |total, profit| total + profit)
+    }
+
+    pub fn missed_opportunities(&self) -> usize {
+        self.outcomes
+            .iter()
+            .filter(|request| request.missed_opportunity)
+            .count()
+    }
+
+    fn count(&self, predicate: impl Fn(&SimulatedOutcome) -> bool) -> usize {
+        self.outcomes
+            .iter()
+            .filter(|request| predicate(&request.outcome))
+            .count()
+    }
+}
+
+/// Replays a `Trace` as a provider would have experienced it: each request is analyzed when it was
+/// observed, priced with the cost model, and bid upon at the timestamp the production bidder would
+/// target for the policy's reward. A bid wins when it lands before the recorded winning bid.
+pub struct BacktestSimulator<A> {
+    analyzer: A,
+    cost_model: Arc<dyn CostModel>,
+    policy: Arc<dyn BidPolicy>,
+    /// seconds between observing a request and a bid decided on it landing on chain
+    reaction_time: u64,
+}
+
+impl<A> BacktestSimulator<A>
+where
+    A: IntentAnalyzer<Intent = ComputeRequest<SystemParams>> + Sync,
+{
+    pub fn new(analyzer: A, policy: Arc<dyn BidPolicy>) -> Self {
+        Self {
+            analyzer,
+            cost_model: Arc::new(FixedCost(U256::ZERO)),
+            policy,
+            reaction_time: 0,
+        }
+    }
+
+    pub fn with_cost_model(mut self, cost_model: Arc<dyn CostModel>) -> Self {
+        self.cost_model = cost_model;
+        self
+    }
+
+    pub fn with_reaction_time(mut self, reaction_time: u64) -> Self {
+        self.reaction_time = reaction_time;
+        self
+    }
+
+    pub async fn run(&self, trace: &Trace) -> BacktestReport {
+        let mut report = BacktestReport::default();
+        for entry in trace.entries() {
+            report.outcomes.push(self.simulate(entry).await);
+        }
+        report
+    }
+
+    async fn simulate(&self, entry: &TraceEntry) -> RequestOutcome {
+        let request = &entry.request;
+        let cost = self.cost_model.cost(request);
+        let decided_at = entry.observed_at + self.reaction_time;
+
+        let outcome = match self.bid(decided_at, request, cost).await {
+            Err(reason) => SimulatedOutcome::Skipped { reason },
+            Ok((bid_timestamp, reward)) => match &entry.winning_bid {
+                Some(winning_bid) if winning_bid.timestamp <= bid_timestamp => {
+                    SimulatedOutcome::Lost {
+                        bid_timestamp,
+                        winning_timestamp: winning_bid.timestamp,
+                    }
+                }
+                _ => SimulatedOutcome::Won {
+                    bid_timestamp,
+                    reward,
+                    cost,
+                },
+            },
+        };
+
+        let missed_opportunity = !matches!(outcome, SimulatedOutcome::Won { .. }) && {
+            let available_reward = entry
+                .winning_bid
+                .as_ref()
+                .map_or(request.proof_request.maxRewardAmount, |winning_bid| {
+                    winning_bid.reward_amount
+                });
+            available_reward >= cost
+        };
+
+        RequestOutcome {
+            request_id: entry.request_id(),
+            outcome,
+            missed_opportunity,
+        }
+    }
+
+    /// Timestamp and reward of the bid the policy would place on `request`
+    async fn bid(
+        &self,
+        decided_at: u64,
+        request: &ComputeRequest<SystemParams>,
+        cost: U256,
+    ) -> core::result::Result<(u64, U256), String> {
+        self.analyzer
+            .analyze(decided_at, request)
+            .await
+            .map_err(|e| e.to_string())?;
+        let target_amount = self
+            .policy
+            .target_amount(request, cost)
+            .ok_or_else(|| format!("no target reward covering a cost of {cost}"))?;
+        let reward_curve =
+            RewardCurve::try_from(&request.proof_request).map_err(|e| e.to_string())?;
+        let bid_timestamp = target_bid_timestamp(&request.proof_request, decided_at, target_amount)
+            .map_err(|e| e.to_string())?;
+        Ok((bid_timestamp, reward_curve.reward_at(bid_timestamp)))
+    }
+}
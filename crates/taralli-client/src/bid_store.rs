@@ -0,0 +1,214 @@
+//! Bids a provider attempted, so a request replayed after a restart (e.g. through the server's
+//! backfill) isn't bid upon twice.
+//!
+//! A bid attempt is recorded before its transaction is sent. When a request already attempted is
+//! received again, the market tells what came of the attempt: a bid of the provider's own is
+//! resumed straight from proving, a bid of anyone else drops the request, and a request still open
+//! is bid upon again. Requests never attempted are bid upon without asking the market.
+//!
+//! A request is processed, from its bid to its resolve, under a claim on the store, so a copy of
+//! it received meanwhile, e.g. by another client sharing the store, isn't resumed from its won
+//! bid and proven and resolved twice.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use taralli_primitives::alloy::primitives::{Address, B256};
+
+use crate::error::{ClientError, Result};
+use crate::market_state::AuctionState;
+use crate::persist::persist;
+
+/// seconds attempts are kept for, beyond which their request can no longer be resolved
+pub const DEFAULT_RETENTION_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+/// How far a bid attempt got
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BidStage {
+    /// bid transaction about to be sent, whether it landed is unknown
+    Attempted,
+    /// bid included, the request being proved
+    Won,
+    /// request resolved
+    Resolved,
+}
+
+/// Bid attempt on a request, as persisted
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BidAttempt {
+    pub intent_id: B256,
+    pub stage: BidStage,
+    /// unix timestamp the stage was reached at
+    pub updated_at: u64,
+}
+
+/// What to do with a request received again, see `replay_decision`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReplayDecision {
+    /// bid upon the request, no bid of it being known
+    Bid,
+    /// the provider's own bid won the request, prove and resolve it without bidding again
+    Resume,
+    /// the request was bid upon by someone else, resolved or expired
+    Drop(String),
+}
+
+/// Decide what to do with a request whose previous bid attempt is `attempt`, the market telling
+/// `state` for it. `provider` is the address the provider bids from.
+pub fn replay_decision(
+    attempt: Option<&BidAttempt>,
+    state: AuctionState,
+    provider: Address,
+) -> ReplayDecision {
+    if attempt.is_some_and(|attempt| attempt.stage == BidStage::Resolved) {
+        return ReplayDecision::Drop("already resolved by the provider".to_string());
+    }
+    match state {
+        AuctionState::Open => ReplayDecision::Bid,
+        AuctionState::Bid { prover, .. } if prover == provider => ReplayDecision::Resume,
+        AuctionState::Bid { prover, .. } => {
+            ReplayDecision::Drop(format!("already bid upon by {prover}"))
+        }
+        AuctionState::Resolved => ReplayDecision::Drop("already resolved".to_string()),
+        AuctionState::Expired => ReplayDecision::Drop("auction expired".to_string()),
+    }
+}
+
+/// Bid attempts of a provider, persisted to a JSON file when one is given
+#[derive(Debug, Default)]
+pub struct BidStore {
+    attempts: Mutex<HashMap<B256, BidAttempt>>,
+    path: Option<PathBuf>,
+    // held while writing the file, writes persisting the attempts as of when they start
+    persisting: tokio::sync::Mutex<()>,
+    // requests being processed
+    claimed: Mutex<HashSet<B256>>,
+}
+
+/// Claim on the processing of a request, released when dropped
+#[derive(Debug)]
+pub struct BidClaim<'a> {
+    store: &'a BidStore,
+    intent_id: B256,
+}
+
+impl Drop for BidClaim<'_> {
+    fn drop(&mut self) {
+        self.store
+            .claimed
+            .lock()
+            .expect("bid store lock poisoned")
+            .remove(&self.intent_id);
+    }
+}
+
+impl BidStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store persisted to `path`, starting from the attempts it holds if it exists. Attempts
+    /// older than `DEFAULT_RETENTION_SECONDS` are dropped.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let persisted: Vec<BidAttempt> = match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| {
+                ClientError::ConfigError(format!("parsing {}: {e}", path.display()))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(ClientError::ConfigError(format!(
+                    "reading {}: {e}",
+                    path.display()
+                )))
+            }
+        };
+        let retained_from = unix_now().saturating_sub(DEFAULT_RETENTION_SECONDS);
+        let attempts = persisted
+            .into_iter()
+            .filter(|attempt| attempt.updated_at >= retained_from)
+            .map(|attempt| (attempt.intent_id, attempt))
+            .collect();
+        Ok(Self {
+            attempts: Mutex::new(attempts),
+            path: Some(path.to_path_buf()),
+            ..Default::default()
+        })
+    }
+
+    /// Bid attempt recorded on `intent_id`, if any
+    pub fn attempt(&self, intent_id: B256) -> Option<BidAttempt> {
+        self.attempts.lock().unwrap().get(&intent_id).copied()
+    }
+
+    /// Record a bid on `intent_id` about to be sent
+    pub async fn record_attempt(&self, intent_id: B256) {
+        self.set_stage(intent_id, BidStage::Attempted).await;
+    }
+
+    /// Record the bid on `intent_id` as won
+    pub async fn record_won(&self, intent_id: B256) {
+        self.set_stage(intent_id, BidStage::Won).await;
+    }
+
+    /// Record the request `intent_id` as resolved
+    pub async fn record_resolved(&self, intent_id: B256) {
+        self.set_stage(intent_id, BidStage::Resolved).await;
+    }
+
+    /// Claim the processing of request `intent_id`, `None` while it's already claimed
+    pub fn claim(&self, intent_id: B256) -> Option<BidClaim<'_>> {
+        let claimed = self
+            .claimed
+            .lock()
+            .expect("bid store lock poisoned")
+            .insert(intent_id);
+        if !claimed {
+            return None;
+        }
+        Some(BidClaim {
+            store: self,
+            intent_id,
+        })
+    }
+
+    async fn set_stage(&self, intent_id: B256, stage: BidStage) {
+        self.attempts.lock().unwrap().insert(
+            intent_id,
+            BidAttempt {
+                intent_id,
+                stage,
+                updated_at: unix_now(),
+            },
+        );
+
+        let Some(path) = &self.path else {
+            return;
+        };
+        // the last write to start persists the latest attempts, whatever order stages land in
+        let _persisting = self.persisting.lock().await;
+        let json = {
+            let attempts = self.attempts.lock().unwrap();
+            let mut snapshot: Vec<&BidAttempt> = attempts.values().collect();
+            snapshot.sort_by_key(|attempt| attempt.updated_at);
+            serde_json::to_vec(&snapshot)
+        };
+        let written = match json {
+            Ok(json) => persist(path.clone(), json).await.map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        if let Err(e) = written {
+            tracing::warn!("failed to persist bid store to {}: {e}", path.display());
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
@@ -1,6 +1,11 @@
+use crate::api::retry::RetryPolicy;
+use crate::confirmations::Confirmations;
 use crate::error::{ClientError, Result};
+use crate::market_state::MarketStateClient;
+use crate::submitter::{submit_transaction, TransactionSubmitter};
 use async_trait::async_trait;
 use std::marker::PhantomData;
+use std::sync::Arc;
 use taralli_primitives::abi::universal_porchetta::UniversalPorchetta::{
     ProofOffer, UniversalPorchettaInstance,
 };
@@ -14,9 +19,18 @@ use super::IntentBidder;
 
 /// Bid on a `ComputeOffer`
 #[derive(Clone)]
-pub struct ComputeOfferBidder<T, P, N> {
+pub struct ComputeOfferBidder<T, P, N>
+where
+    N: Network,
+{
     rpc_provider: P,
     market_address: Address,
+    // sends the transactions instead of `rpc_provider` when set
+    submitter: Option<Arc<dyn TransactionSubmitter<N>>>,
+    // depth bid transactions are confirmed at before their receipt is returned
+    confirmations: Confirmations,
+    // attempts of the rpc calls preparing bids failing transiently
+    rpc_retry: RetryPolicy,
     phantom_data: PhantomData<(T, N)>,
 }
 
@@ -32,9 +46,32 @@ where
         Self {
             rpc_provider,
             market_address,
+            submitter: None,
+            confirmations: Confirmations::default(),
+            rpc_retry: RetryPolicy::default(),
             phantom_data: PhantomData,
         }
     }
+
+    /// send bid transactions through the given submitter (e.g. a private rpc) instead of the rpc provider
+    pub fn with_submitter(mut self, submitter: Arc<dyn TransactionSubmitter<N>>) -> Self {
+        self.submitter = Some(submitter);
+        self
+    }
+
+    /// wait for bid transactions to be confirmed at the given depth, failing with
+    /// `ClientError::TransactionReorged` when they're reorged out meanwhile
+    pub fn with_confirmations(mut self, confirmations: Confirmations) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    /// attempt the rpc calls preparing bids again as `rpc_retry` allows when they fail
+    /// transiently. Bid transactions themselves are sent once.
+    pub fn with_rpc_retry(mut self, rpc_retry: RetryPolicy) -> Self {
+        self.rpc_retry = rpc_retry;
+        self
+    }
 }
 
 #[async_trait]
@@ -74,35 +111,34 @@ where
 
         tracing::info!("bidder: check timestamps done");
 
-        let active_offer_return = market_contract
-            .activeProofOfferData(intent_id)
-            .call()
-            .await
-            .map_err(|e| ClientError::TransactionSetupError(e.to_string()))?;
-
-        if active_offer_return.provider != Address::ZERO {
-            return Err(ClientError::TransactionSetupError(
-                "Another Bid has already submitted for this Auction".into(),
-            ));
+        if MarketStateClient::new(self.rpc_provider.clone(), self.market_address)
+            .with_rpc_retry(self.rpc_retry.clone())
+            .offer_state(
+                intent_id,
+                intent_proof_commitment.endAuctionTimestamp,
+                latest_ts,
+            )
+            .await?
+            .is_bid()
+        {
+            return Err(ClientError::AuctionAlreadyBid { intent_id });
         }
 
-        tracing::info!(
-            "bidder: requester address = {}",
-            active_offer_return.requester
-        );
-
-        let receipt = market_contract
+        let bid_transaction = market_contract
             .bid(
                 intent_proof_commitment.clone(),
                 Bytes::from(signature.as_bytes()),
             )
-            .send()
-            .await
-            .map_err(|e| ClientError::TransactionError(e.to_string()))?
-            .get_receipt()
-            .await
-            .map_err(|e| ClientError::TransactionFailure(e.to_string()))?;
+            .into_transaction_request();
+        let receipt = submit_transaction::<T, P, N>(
+            &self.rpc_provider,
+            self.submitter.as_ref(),
+            bid_transaction,
+        )
+        .await?;
 
-        Ok(receipt)
+        self.confirmations
+            .confirm_receipt::<T, P, N>(&self.rpc_provider, receipt)
+            .await
     }
 }
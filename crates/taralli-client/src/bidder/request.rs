@@ -1,30 +1,76 @@
+use crate::analyzer::request::request_urgency;
+use crate::api::retry::RetryPolicy;
+use crate::clock::ChainClock;
+use crate::confirmations::Confirmations;
 use crate::error::{ClientError, Result};
+use crate::market_state::{AuctionState, MarketStateClient};
+use crate::rpc::retry_rpc;
+use crate::submitter::{submit_transaction, TransactionSubmitter};
 use async_trait::async_trait;
 use std::marker::PhantomData;
+use std::sync::Arc;
 use taralli_primitives::abi::universal_bombetta::UniversalBombetta::{
-    ProofRequest, UniversalBombettaInstance,
+    self, ProofRequest, UniversalBombettaInstance,
 };
 use taralli_primitives::alloy::network::Network;
 use taralli_primitives::alloy::network::ReceiptResponse;
+use taralli_primitives::alloy::network::TransactionBuilder;
 use taralli_primitives::alloy::primitives::FixedBytes;
 use taralli_primitives::alloy::primitives::{Address, Bytes, PrimitiveSignature, U256};
 use taralli_primitives::alloy::providers::Provider;
 use taralli_primitives::alloy::transports::Transport;
-use tokio::time::{sleep, Duration};
+use taralli_primitives::intents::auction::RewardCurve;
 
 use super::IntentBidder;
 
+/// Seconds before the end of an auction the wait for a bid gives up at, a bid sent later being
+/// unlikely to be included before the auction ends
+pub const BID_INCLUSION_MARGIN_SECS: u64 = 2;
+
 /// Bid on a `ComputeRequest`
 #[derive(Clone)]
-pub struct ComputeRequestBidder<T, P, N> {
+pub struct ComputeRequestBidder<T, P, N>
+where
+    N: Network,
+{
     rpc_provider: P,
     market_address: Address,
+    // sends the transactions instead of `rpc_provider` when set
+    submitter: Option<Arc<dyn TransactionSubmitter<N>>>,
+    // depth bid transactions are confirmed at before their receipt is returned
+    confirmations: Confirmations,
+    // chain time bids wait for
+    clock: ChainClock<T, P, N>,
+    // attempts of the rpc calls preparing bids failing transiently
+    rpc_retry: RetryPolicy,
     phantom_data: PhantomData<(T, N)>,
 }
 
 #[derive(Clone)]
 pub struct ComputeRequestBidParams {
+    /// least reward the bid is sent for, the reservation price when sniping
     pub target_amount: U256,
+    pub mode: BidMode,
+}
+
+/// When a bid is sent once the reward reaches the target amount
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BidMode {
+    /// bid as soon as the reward reaches the target amount
+    #[default]
+    Immediate,
+    /// bid as late as the auction allows, collecting the highest reward without revealing any
+    /// interest in the request before then
+    Snipe(SnipeParams),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SnipeParams {
+    /// seconds before the end of the auction the bid is sent at, leaving room for it to be
+    /// included. The proving deadline counts from the bid, so it isn't shortened by the wait.
+    pub safety_window_secs: u64,
+    /// wei per gas added to the estimated priority fee, so the bid lands ahead of competing ones
+    pub priority_fee_bump: u128,
 }
 
 impl<T, P, N> ComputeRequestBidder<T, P, N>
@@ -35,11 +81,187 @@ where
 {
     pub fn new(rpc_provider: P, market_address: Address) -> Self {
         Self {
+            clock: ChainClock::new(rpc_provider.clone()),
             rpc_provider,
             market_address,
+            submitter: None,
+            confirmations: Confirmations::default(),
+            rpc_retry: RetryPolicy::default(),
             phantom_data: PhantomData,
         }
     }
+
+    /// wait for bid timestamps as told by `clock`, e.g. the one shared by the client's components
+    pub fn with_clock(mut self, clock: ChainClock<T, P, N>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// send bid transactions through the given submitter (e.g. a private rpc) instead of the rpc provider
+    pub fn with_submitter(mut self, submitter: Arc<dyn TransactionSubmitter<N>>) -> Self {
+        self.submitter = Some(submitter);
+        self
+    }
+
+    /// wait for bid transactions to be confirmed at the given depth, failing with
+    /// `ClientError::TransactionReorged` when they're reorged out meanwhile
+    pub fn with_confirmations(mut self, confirmations: Confirmations) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    /// attempt the rpc calls preparing bids again as `rpc_retry` allows when they fail
+    /// transiently. Bid transactions themselves are sent once.
+    pub fn with_rpc_retry(mut self, rpc_retry: RetryPolicy) -> Self {
+        self.rpc_retry = rpc_retry;
+        self
+    }
+
+    /// Wait until the chain time reaches the timestamp a bid on `proof_request`, decided upon at
+    /// `latest_ts`, is sent at given `bid_params`, returning that timestamp. Fails with
+    /// `ClientError::WaitDeadlinePassed` once the chain time gets within
+    /// `BID_INCLUSION_MARGIN_SECS` of the end of the auction first.
+    pub async fn wait_for_bid_timestamp(
+        &self,
+        latest_ts: u64,
+        proof_request: &ProofRequest,
+        bid_params: &ComputeRequestBidParams,
+    ) -> Result<u64> {
+        // the requester pays for latency on urgent requests, sniping them would defeat it
+        let urgency = request_urgency(proof_request);
+        if urgency > 0 && matches!(bid_params.mode, BidMode::Snipe(_)) {
+            tracing::info!("bidder: request of urgency {} bid upon right away", urgency);
+        }
+        let bid_timestamp = match &bid_params.mode {
+            BidMode::Snipe(_) if urgency > 0 => {
+                target_bid_timestamp(proof_request, latest_ts, bid_params.target_amount)?
+            }
+            BidMode::Immediate => {
+                target_bid_timestamp(proof_request, latest_ts, bid_params.target_amount)?
+            }
+            BidMode::Snipe(snipe_params) => snipe_bid_timestamp(
+                proof_request,
+                latest_ts,
+                bid_params.target_amount,
+                snipe_params,
+            )?,
+        };
+        tracing::info!("bidder: check timestamps done");
+
+        // wait ideal number of seconds to get +/- the target_amount
+        if bid_timestamp > latest_ts {
+            tracing::info!(
+                "bidder: waiting {} seconds for ideal amount",
+                bid_timestamp - latest_ts
+            );
+            let deadline = proof_request
+                .endAuctionTimestamp
+                .saturating_sub(BID_INCLUSION_MARGIN_SECS);
+            self.clock.sleep_until(bid_timestamp, deadline).await?;
+        }
+        Ok(bid_timestamp)
+    }
+
+    /// `Bid` event the market emitted for `intent_id` in the block of `receipt`, telling the
+    /// reward and stake the bid actually got
+    pub async fn bid_event(
+        &self,
+        intent_id: FixedBytes<32>,
+        receipt: &N::ReceiptResponse,
+    ) -> Result<UniversalBombetta::Bid> {
+        let block_number = receipt.block_number().ok_or_else(|| {
+            ClientError::EventFilterError(format!(
+                "bid transaction {} is pending",
+                receipt.transaction_hash()
+            ))
+        })?;
+        let market_contract =
+            &UniversalBombettaInstance::new(self.market_address, self.rpc_provider.clone());
+        retry_rpc(&self.rpc_retry, || async move {
+            market_contract
+                .Bid_filter()
+                .topic2(intent_id)
+                .from_block(block_number)
+                .to_block(block_number)
+                .query()
+                .await
+        })
+        .await?
+        .into_iter()
+        .map(|(bid, _log)| bid)
+        .next()
+        .ok_or_else(|| {
+            ClientError::EventFilterError(format!(
+                "no bid event for intent {intent_id} in block {block_number}"
+            ))
+        })
+    }
+}
+
+/// Timestamp at which a bid on `proof_request`, decided upon at `latest_ts`, gets at least
+/// `target_amount`: right away when the current reward already covers it, otherwise once the
+/// reward curve reaches it.
+pub fn target_bid_timestamp(
+    proof_request: &ProofRequest,
+    latest_ts: u64,
+    target_amount: U256,
+) -> Result<u64> {
+    // check auction has started
+    if latest_ts < proof_request.startAuctionTimestamp {
+        return Err(ClientError::TransactionSetupError(
+            "Auction has not started based on current block ts".into(),
+        ));
+    }
+
+    // check that the deadline is not passed
+    if latest_ts > proof_request.endAuctionTimestamp {
+        return Err(ClientError::TransactionSetupError(
+            "Auction has expired".into(),
+        ));
+    }
+
+    let reward_curve = RewardCurve::try_from(proof_request)
+        .map_err(|e| ClientError::TransactionSetupError(e.to_string()))?;
+    let current_estimated_amount = reward_curve.reward_at(latest_ts);
+    tracing::debug!(
+        "bidder: current_estimated_amount: {}",
+        current_estimated_amount
+    );
+
+    if current_estimated_amount >= target_amount {
+        return Ok(latest_ts);
+    }
+    // a fixed reward never rises to the target
+    if reward_curve.is_fixed_price() {
+        return Err(ClientError::TransactionSetupError(
+            "Target amount is out of bounds".into(),
+        ));
+    }
+    reward_curve
+        .timestamp_for(target_amount)
+        .ok_or_else(|| ClientError::TransactionSetupError("Target amount is out of bounds".into()))
+}
+
+/// Timestamp at which a snipe bid on `proof_request`, decided upon at `latest_ts`, is sent: the
+/// start of the safety window before the end of the auction, provided the reward reached
+/// `reservation_price` by then.
+pub fn snipe_bid_timestamp(
+    proof_request: &ProofRequest,
+    latest_ts: u64,
+    reservation_price: U256,
+    snipe_params: &SnipeParams,
+) -> Result<u64> {
+    let earliest_ts = target_bid_timestamp(proof_request, latest_ts, reservation_price)?;
+    let latest_safe_ts = proof_request
+        .endAuctionTimestamp
+        .saturating_sub(snipe_params.safety_window_secs);
+    if earliest_ts > latest_safe_ts {
+        return Err(ClientError::TransactionSetupError(format!(
+            "Reservation price is only reached at {}, past the safety window starting at {}",
+            earliest_ts, latest_safe_ts
+        )));
+    }
+    Ok(latest_safe_ts)
 }
 
 #[async_trait]
@@ -63,134 +285,73 @@ where
         let market_contract =
             UniversalBombettaInstance::new(self.market_address, self.rpc_provider.clone());
 
-        // check auction has started
-        if latest_ts < intent_proof_commitment.startAuctionTimestamp {
-            return Err(ClientError::TransactionSetupError(
-                "Auction has not started based on current block ts".into(),
-            ));
-        }
+        let bid_timestamp = self
+            .wait_for_bid_timestamp(latest_ts, &intent_proof_commitment, &bid_params)
+            .await?;
 
-        // check that the deadline is not passed
-        if latest_ts > intent_proof_commitment.endAuctionTimestamp {
-            return Err(ClientError::TransactionSetupError(
-                "Auction has expired".into(),
-            ));
-        }
-
-        tracing::info!("bidder: check timestamps done");
-
-        // auction is active, calculate target timestamp from target_amount
-        let current_estimated_amount = calculate_current_reward(
-            latest_ts,
-            intent_proof_commitment.startAuctionTimestamp,
-            intent_proof_commitment.endAuctionTimestamp,
-            intent_proof_commitment.minRewardAmount,
-            intent_proof_commitment.maxRewardAmount,
-        );
-
-        tracing::info!(
-            "bidder: current_estimated_amount: {}",
-            current_estimated_amount
-        );
-
-        if current_estimated_amount < bid_params.target_amount {
-            // wait ideal number of seconds to get +/- the target_amount, then send bid
-            let target_timestamp = calculate_target_timestamp(
-                bid_params.target_amount,
-                intent_proof_commitment.startAuctionTimestamp,
+        // check the request wasn't bid upon while waiting, right before sending the bid
+        match MarketStateClient::new(self.rpc_provider.clone(), self.market_address)
+            .with_rpc_retry(self.rpc_retry.clone())
+            .request_state(
+                intent_id,
                 intent_proof_commitment.endAuctionTimestamp,
-                intent_proof_commitment.minRewardAmount,
-                intent_proof_commitment.maxRewardAmount,
-            )?;
-            let wait_time = target_timestamp - latest_ts;
-            tracing::info!("bidder: waiting {} seconds for ideal amount", wait_time);
-            // Wait for `wait_time` seconds
-            sleep(Duration::from_secs(wait_time)).await;
-        }
-
-        tracing::info!("bidder: calculate target ts for target amount");
-
-        let active_request_return = market_contract
-            .activeProofRequestData(intent_id)
-            .call()
-            .await
-            .map_err(|e| ClientError::TransactionSetupError(e.to_string()))?;
-
-        if active_request_return.requester != Address::ZERO {
-            return Err(ClientError::TransactionSetupError(
-                "Another Bid has already submitted for this Auction".into(),
-            ));
+                bid_timestamp,
+            )
+            .await?
+        {
+            AuctionState::Open => {}
+            AuctionState::Expired => {
+                return Err(ClientError::TransactionSetupError(
+                    "Auction has expired".into(),
+                ))
+            }
+            AuctionState::Bid { .. } | AuctionState::Resolved => {
+                return Err(ClientError::AuctionAlreadyBid { intent_id })
+            }
         }
 
-        let receipt = market_contract
+        let mut bid_transaction = market_contract
             .bid(
                 intent_proof_commitment.clone(),
                 Bytes::from(signature.as_bytes()),
             )
             .value(U256::from(intent_proof_commitment.minimumStake))
-            .send()
-            .await
-            .map_err(|e| ClientError::TransactionError(e.to_string()))?
-            .get_receipt()
-            .await
-            .map_err(|e| ClientError::TransactionFailure(e.to_string()))?;
+            .into_transaction_request();
+        if let BidMode::Snipe(snipe_params) = &bid_params.mode {
+            if snipe_params.priority_fee_bump > 0 {
+                let rpc_provider = &self.rpc_provider;
+                let fees = retry_rpc(&self.rpc_retry, || async move {
+                    rpc_provider.estimate_eip1559_fees(None).await
+                })
+                .await?;
+                bid_transaction.set_max_priority_fee_per_gas(
+                    fees.max_priority_fee_per_gas
+                        .saturating_add(snipe_params.priority_fee_bump),
+                );
+                bid_transaction.set_max_fee_per_gas(
+                    fees.max_fee_per_gas
+                        .saturating_add(snipe_params.priority_fee_bump),
+                );
+            }
+        }
+        let receipt = submit_transaction::<T, P, N>(
+            &self.rpc_provider,
+            self.submitter.as_ref(),
+            bid_transaction,
+        )
+        .await?;
 
         tracing::info!("bid txs receipt: {:?}", receipt);
 
         // Check if the transaction was reverted
         if !receipt.status() {
-            return Err(ClientError::TransactionFailure(
-                "Transaction reverted on-chain".into(),
-            ));
+            return Err(ClientError::TransactionReverted {
+                tx_hash: receipt.transaction_hash(),
+            });
         }
 
-        Ok(receipt)
-    }
-}
-
-fn calculate_current_reward(
-    current_timestamp: u64,
-    start_timestamp: u64,
-    end_timestamp: u64,
-    min_reward: U256,
-    max_reward: U256,
-) -> U256 {
-    if current_timestamp == start_timestamp {
-        return max_reward; // At auction start, return maxRewardAmount
-    }
-    if current_timestamp == end_timestamp {
-        return min_reward; // At auction end, return minRewardAmount
-    }
-
-    // calculate factor to decrease by to get estimated current amount
-    let elapsed_time = U256::from(current_timestamp - start_timestamp);
-    let total_duration = U256::from(end_timestamp - start_timestamp);
-    let reward_range = max_reward - min_reward;
-    let decrease_amount = (elapsed_time * reward_range) / total_duration;
-
-    max_reward - decrease_amount
-}
-
-fn calculate_target_timestamp(
-    target_amount: U256,
-    start_timestamp: u64,
-    end_timestamp: u64,
-    min_reward: U256,
-    max_reward: U256,
-) -> Result<u64> {
-    // Ensure target_amount is within min_reward and max_reward
-    if target_amount < min_reward || target_amount > max_reward {
-        return Err(ClientError::TransactionSetupError(
-            "Target amount is out of bounds".into(),
-        ));
+        self.confirmations
+            .confirm_receipt::<T, P, N>(&self.rpc_provider, receipt)
+            .await
     }
-
-    let total_duration = U256::from(end_timestamp - start_timestamp);
-    let elapsed_time = total_duration * (target_amount - min_reward) / (max_reward - min_reward);
-    let target_timestamp = U256::from(start_timestamp) + elapsed_time;
-
-    // Convert U256 back to u64
-    target_timestamp.try_into().map_err(|e| {
-        ClientError::TransactionSetupError(format!("Failed to convert target timestamp: {e}"))
-    })
 }
@@ -0,0 +1,302 @@
+//! Connectivity checks run before a client starts working, so a misconfigured RPC url, market
+//! address or server url is reported up front rather than deep inside the first bid or
+//! submission.
+//!
+//! Every check runs concurrently and is timed. A failed check is hard when the client can't work
+//! without it, the report only failing as a whole on hard failures.
+
+use std::fmt;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use taralli_primitives::alloy::{
+    network::Network,
+    primitives::{utils::format_ether, U256},
+    providers::Provider,
+    signers::Signer,
+    transports::Transport,
+};
+use taralli_primitives::systems::SystemId;
+
+use crate::api::status::StatusApiClient;
+use crate::error::{ClientError, Result};
+
+use super::BaseClient;
+
+/// Balance below which the signer's account is considered unfunded, 0.001 ether
+pub const DEFAULT_DUST_THRESHOLD: U256 = U256::from_limbs([1_000_000_000_000_000, 0, 0, 0]);
+/// Time each check gets before failing
+pub const DEFAULT_HEALTHCHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What the checks expect to find
+#[derive(Clone, Debug)]
+pub struct HealthcheckConfig {
+    /// chain id the rpc and the server have to be on
+    pub chain_id: u64,
+    /// systems the server has to support
+    pub systems: Vec<SystemId>,
+    /// balance the signer's account needs
+    pub dust_threshold: U256,
+    /// whether an account below the dust threshold is a hard failure, e.g. for providers staking
+    /// on their bids, rather than a warning
+    pub funds_required: bool,
+    pub timeout: Duration,
+}
+
+impl HealthcheckConfig {
+    pub fn new(chain_id: u64, systems: Vec<SystemId>) -> Self {
+        Self {
+            chain_id,
+            systems,
+            dust_threshold: DEFAULT_DUST_THRESHOLD,
+            funds_required: true,
+            timeout: DEFAULT_HEALTHCHECK_TIMEOUT,
+        }
+    }
+
+    pub fn with_dust_threshold(mut self, dust_threshold: U256) -> Self {
+        self.dust_threshold = dust_threshold;
+        self
+    }
+
+    pub fn with_funds_required(mut self, funds_required: bool) -> Self {
+        self.funds_required = funds_required;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// A single startup check
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Healthcheck {
+    /// the rpc answers, on the expected chain
+    RpcChain,
+    /// the market contract has code at the configured address
+    MarketCode,
+    /// the server's `/status` answers, on the expected chain and supporting the client's systems
+    ServerStatus,
+    /// the signer's account holds more than the dust threshold
+    SignerFunds,
+}
+
+impl Healthcheck {
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Healthcheck::RpcChain => "rpc chain",
+            Healthcheck::MarketCode => "market code",
+            Healthcheck::ServerStatus => "server status",
+            Healthcheck::SignerFunds => "signer funds",
+        }
+    }
+}
+
+/// Outcome of a single check
+#[derive(Clone, Debug)]
+pub struct HealthcheckResult {
+    pub check: Healthcheck,
+    pub passed: bool,
+    /// whether the client can't work when the check fails
+    pub hard: bool,
+    /// what was found, or why the check failed
+    pub detail: String,
+    pub latency: Duration,
+}
+
+impl fmt::Display for HealthcheckResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match (self.passed, self.hard) {
+            (true, _) => "ok",
+            (false, true) => "FAILED",
+            (false, false) => "warning",
+        };
+        write!(
+            f,
+            "{}: {status} in {}ms, {}",
+            self.check.as_str(),
+            self.latency.as_millis(),
+            self.detail
+        )
+    }
+}
+
+/// Outcome of every check, in the order of `Healthcheck`
+#[derive(Clone, Debug)]
+pub struct HealthReport {
+    pub checks: Vec<HealthcheckResult>,
+}
+
+impl HealthReport {
+    /// Checks that failed, hard or not
+    pub fn failures(&self) -> impl Iterator<Item = &HealthcheckResult> {
+        self.checks.iter().filter(|result| !result.passed)
+    }
+
+    /// Whether no hard check failed
+    #[must_use]
+    pub fn is_healthy(&self) -> bool {
+        self.failures().all(|result| !result.hard)
+    }
+
+    /// The report, or `ClientError::HealthcheckFailed` listing the hard failures
+    pub fn into_result(self) -> Result<Self> {
+        if self.is_healthy() {
+            return Ok(self);
+        }
+        Err(ClientError::HealthcheckFailed(
+            self.failures()
+                .filter(|result| result.hard)
+                .map(|result| format!("{}: {}", result.check.as_str(), result.detail))
+                .collect(),
+        ))
+    }
+}
+
+impl fmt::Display for HealthReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for result in &self.checks {
+            writeln!(f, "{result}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<T, P, N, S> BaseClient<T, P, N, S>
+where
+    T: Transport + Clone,
+    P: Provider<T, N> + Clone,
+    N: Network + Clone,
+    S: Signer + Clone,
+{
+    /// Check the rpc, the market, the server behind `status_api` and the signer's funds
+    /// concurrently against `config`
+    pub async fn healthcheck(
+        &self,
+        status_api: &StatusApiClient,
+        config: &HealthcheckConfig,
+    ) -> HealthReport {
+        let (rpc_chain, market_code, server_status, signer_funds) = tokio::join!(
+            timed(Healthcheck::RpcChain, true, config.timeout, async {
+                let chain_id = self
+                    .rpc_provider
+                    .get_chain_id()
+                    .await
+                    .map_err(|e| format!("rpc unreachable: {e}"))?;
+                if chain_id == config.chain_id {
+                    Ok(format!("chain id {chain_id}"))
+                } else {
+                    Err(format!(
+                        "rpc is on chain {chain_id}, expected chain {}",
+                        config.chain_id
+                    ))
+                }
+            }),
+            timed(Healthcheck::MarketCode, true, config.timeout, async {
+                let code = self
+                    .rpc_provider
+                    .get_code_at(self.market_address)
+                    .await
+                    .map_err(|e| format!("rpc unreachable: {e}"))?;
+                if code.is_empty() {
+                    Err(format!("no contract at market {}", self.market_address))
+                } else {
+                    Ok(format!(
+                        "{} bytes of code at market {}",
+                        code.len(),
+                        self.market_address
+                    ))
+                }
+            }),
+            timed(Healthcheck::ServerStatus, true, config.timeout, async {
+                let status = status_api
+                    .fetch_status()
+                    .await
+                    .map_err(|e| format!("server unreachable: {e}"))?;
+                if status.validation.request.chain_id != config.chain_id {
+                    return Err(format!(
+                        "server validates intents of chain {}, expected chain {}",
+                        status.validation.request.chain_id, config.chain_id
+                    ));
+                }
+                let missing: Vec<&str> = config
+                    .systems
+                    .iter()
+                    .filter(|system_id| {
+                        !status
+                            .systems
+                            .iter()
+                            .any(|system| system.system_id == **system_id)
+                    })
+                    .map(SystemId::as_str)
+                    .collect();
+                if missing.is_empty() {
+                    Ok(format!("server {} up", status.version))
+                } else {
+                    Err(format!("server doesn't support {}", missing.join(", ")))
+                }
+            }),
+            timed(
+                Healthcheck::SignerFunds,
+                config.funds_required,
+                config.timeout,
+                async {
+                    let account = self.signer.address();
+                    let balance = self
+                        .rpc_provider
+                        .get_balance(account)
+                        .await
+                        .map_err(|e| format!("rpc unreachable: {e}"))?;
+                    if balance > config.dust_threshold {
+                        Ok(format!("{account} holds {} ether", format_ether(balance)))
+                    } else {
+                        Err(format!(
+                            "{account} holds {} ether, no more than the dust threshold of {} ether",
+                            format_ether(balance),
+                            format_ether(config.dust_threshold)
+                        ))
+                    }
+                }
+            ),
+        );
+
+        let report = HealthReport {
+            checks: vec![rpc_chain, market_code, server_status, signer_funds],
+        };
+        for result in &report.checks {
+            if result.passed {
+                tracing::info!("healthcheck {result}");
+            } else {
+                tracing::warn!("healthcheck {result}");
+            }
+        }
+        report
+    }
+}
+
+/// Run `check` within `timeout`, timing it
+async fn timed(
+    check: Healthcheck,
+    hard: bool,
+    timeout: Duration,
+    run: impl Future<Output = std::result::Result<String, String>>,
+) -> HealthcheckResult {
+    let started = Instant::now();
+    let outcome = tokio::time::timeout(timeout, run)
+        .await
+        .unwrap_or_else(|_| Err(format!("timed out after {}s", timeout.as_secs())));
+    let (passed, detail) = match outcome {
+        Ok(detail) => (true, detail),
+        Err(detail) => (false, detail),
+    };
+    HealthcheckResult {
+        check,
+        passed,
+        hard,
+        detail,
+        latency: started.elapsed(),
+    }
+}
@@ -3,6 +3,9 @@
 use std::marker::PhantomData;
 use taralli_primitives::alloy::primitives::Address;
 
+use crate::clock::ChainClock;
+
+pub mod healthcheck;
 pub mod provider;
 pub mod requester;
 
@@ -10,17 +13,25 @@ pub mod requester;
 pub struct BaseClient<T, P, N, S> {
     rpc_provider: P,
     signer: S,
-    _market_address: Address,
+    market_address: Address,
+    // chain time the client schedules against, shared with its components
+    clock: ChainClock<T, P, N>,
     phantom: PhantomData<(T, N)>,
 }
 
-impl<T, P, N, S> BaseClient<T, P, N, S> {
+impl<T, P: Clone, N, S> BaseClient<T, P, N, S> {
     pub fn new(rpc_provider: P, signer: S, market_address: Address) -> Self {
         Self {
+            clock: ChainClock::new(rpc_provider.clone()),
             rpc_provider,
             signer,
-            _market_address: market_address,
+            market_address,
             phantom: PhantomData,
         }
     }
+
+    /// Chain time the client schedules against
+    pub fn clock(&self) -> &ChainClock<T, P, N> {
+        &self.clock
+    }
 }
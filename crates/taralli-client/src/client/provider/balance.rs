@@ -0,0 +1,243 @@
+//! Monitoring of the balance a provider stakes on its bids. Bids send the request's minimum stake
+//! as value, so a bid is skipped when the balance can't cover the stake and the bid's gas, and
+//! listeners are warned once the balance drops below a threshold, e.g. to page an operator or to
+//! top the balance up.
+
+use std::future::Future;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_util::future::BoxFuture;
+use futures_util::FutureExt;
+use taralli_primitives::alloy::{
+    network::Network,
+    primitives::{Address, FixedBytes, U256},
+    providers::Provider,
+    transports::Transport,
+};
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use crate::api::retry::RetryPolicy;
+use crate::error::{ClientError, Result};
+use crate::rpc::retry_rpc;
+
+/// gas a bid transaction is assumed to use when none is configured
+pub const DEFAULT_BID_GAS_LIMIT: u64 = 300_000;
+/// how often the balance is polled between bids
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(60);
+/// time after a top-up before a balance still below the threshold is topped up again, the
+/// top-up's transfer taking a while to land
+pub const DEFAULT_TOP_UP_COOLDOWN: Duration = Duration::from_secs(10 * 60);
+
+/// Listener of the balance monitor's events. Every method defaults to a no-op.
+#[async_trait]
+pub trait BalanceHook: Send + Sync {
+    /// a bid was skipped, the balance doesn't cover its stake and gas
+    async fn on_insufficient_funds(
+        &self,
+        _intent_id: FixedBytes<32>,
+        _balance: U256,
+        _required: U256,
+    ) {
+    }
+
+    /// the balance dropped below the threshold, called again only once it went back above it
+    async fn on_low_balance(&self, _balance: U256, _threshold: U256) {}
+}
+
+type TopUp = Box<dyn Fn(U256) -> BoxFuture<'static, Result<()>> + Send + Sync>;
+
+/// Checks the balance of the account bids are sent from
+pub struct BalanceMonitor<T, P, N> {
+    rpc_provider: P,
+    account: Address,
+    low_balance_threshold: U256,
+    bid_gas_limit: u64,
+    buffer: U256,
+    poll_interval: Duration,
+    rpc_retry: RetryPolicy,
+    hooks: Vec<Arc<dyn BalanceHook>>,
+    top_up: Option<TopUp>,
+    top_up_cooldown: Duration,
+    // set while the balance is below the threshold, so listeners are warned once per drop
+    below_threshold: AtomicBool,
+    // held while the top-up runs, a balance still low during it doesn't start another one
+    topping_up: Mutex<()>,
+    // when the last top-up since the balance dropped below the threshold started
+    last_top_up: std::sync::Mutex<Option<Instant>>,
+    phantom: PhantomData<(T, N)>,
+}
+
+impl<T, P, N> BalanceMonitor<T, P, N>
+where
+    T: Transport + Clone,
+    P: Provider<T, N> + Clone,
+    N: Network + Clone,
+{
+    /// Monitor the balance of `account`, the address bid transactions are sent from
+    pub fn new(rpc_provider: P, account: Address, low_balance_threshold: U256) -> Self {
+        Self {
+            rpc_provider,
+            account,
+            low_balance_threshold,
+            bid_gas_limit: DEFAULT_BID_GAS_LIMIT,
+            buffer: U256::ZERO,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            rpc_retry: RetryPolicy::default(),
+            hooks: Vec::new(),
+            top_up: None,
+            top_up_cooldown: DEFAULT_TOP_UP_COOLDOWN,
+            below_threshold: AtomicBool::new(false),
+            topping_up: Mutex::new(()),
+            last_top_up: std::sync::Mutex::new(None),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Gas a bid transaction is assumed to use, priced at the current gas price
+    pub fn with_bid_gas_limit(mut self, bid_gas_limit: u64) -> Self {
+        self.bid_gas_limit = bid_gas_limit;
+        self
+    }
+
+    /// Wei kept on top of a bid's stake and gas, e.g. for the resolve transaction
+    pub fn with_buffer(mut self, buffer: U256) -> Self {
+        self.buffer = buffer;
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// attempt the rpc calls reading the balance and gas price again as `rpc_retry` allows when
+    /// they fail transiently
+    pub fn with_rpc_retry(mut self, rpc_retry: RetryPolicy) -> Self {
+        self.rpc_retry = rpc_retry;
+        self
+    }
+
+    /// Register a listener of the monitor's events, listeners are called in registration order
+    pub fn with_hook(mut self, hook: Arc<dyn BalanceHook>) -> Self {
+        self.hooks.push(hook);
+        self
+    }
+
+    /// Top the balance up with `top_up` once it is found below the threshold, and again while it
+    /// stays below it once the top-up cooldown passed. It's given the balance found and its
+    /// failures are logged.
+    pub fn with_top_up<F, Fut>(mut self, top_up: F) -> Self
+    where
+        F: Fn(U256) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.top_up = Some(Box::new(move |balance| top_up(balance).boxed()));
+        self
+    }
+
+    /// Wait `top_up_cooldown` after a top-up before topping a balance still below the threshold
+    /// up again
+    pub fn with_top_up_cooldown(mut self, top_up_cooldown: Duration) -> Self {
+        self.top_up_cooldown = top_up_cooldown;
+        self
+    }
+
+    pub async fn balance(&self) -> Result<U256> {
+        let (rpc_provider, account) = (&self.rpc_provider, self.account);
+        retry_rpc(&self.rpc_retry, || async move {
+            rpc_provider.get_balance(account).await
+        })
+        .await
+    }
+
+    /// Balance a bid staking `stake` needs: the stake, the bid's gas at the current gas price
+    /// and the buffer
+    pub async fn required_for_bid(&self, stake: U256) -> Result<U256> {
+        let rpc_provider = &self.rpc_provider;
+        let gas_price = retry_rpc(&self.rpc_retry, || async move {
+            rpc_provider.get_gas_price().await
+        })
+        .await?;
+        Ok(stake
+            .saturating_add(U256::from(gas_price).saturating_mul(U256::from(self.bid_gas_limit)))
+            .saturating_add(self.buffer))
+    }
+
+    /// Check the balance covers a bid on `intent_id` staking `stake`, failing with
+    /// `ClientError::InsufficientFunds` when it doesn't so the bid is skipped
+    pub async fn ensure_covers_bid(&self, intent_id: FixedBytes<32>, stake: U256) -> Result<()> {
+        let balance = self.balance().await?;
+        let required = self.required_for_bid(stake).await?;
+        self.observe(balance).await;
+
+        if balance < required {
+            for hook in &self.hooks {
+                hook.on_insufficient_funds(intent_id, balance, required)
+                    .await;
+            }
+            return Err(ClientError::InsufficientFunds {
+                intent_id,
+                balance,
+                required,
+            });
+        }
+        Ok(())
+    }
+
+    /// Poll the balance forever, warning listeners when it drops below the threshold. Failed
+    /// polls are logged.
+    pub async fn run(&self) {
+        loop {
+            match self.balance().await {
+                Ok(balance) => self.observe(balance).await,
+                Err(e) => tracing::warn!("failed to poll the balance of {}: {}", self.account, e),
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Warn listeners when `balance` is below the threshold and wasn't at the last observation,
+    /// and top it up unless it was within the cooldown
+    async fn observe(&self, balance: U256) {
+        if balance >= self.low_balance_threshold {
+            self.below_threshold.store(false, Ordering::Relaxed);
+            // the next drop is topped up right away
+            *self.last_top_up.lock().expect("top-up lock poisoned") = None;
+            return;
+        }
+
+        if !self.below_threshold.swap(true, Ordering::Relaxed) {
+            tracing::warn!(
+                "balance of {} dropped to {}, below the threshold of {}",
+                self.account,
+                balance,
+                self.low_balance_threshold
+            );
+            for hook in &self.hooks {
+                hook.on_low_balance(balance, self.low_balance_threshold)
+                    .await;
+            }
+        }
+
+        if let Some(top_up) = &self.top_up {
+            let Ok(_topping_up) = self.topping_up.try_lock() else {
+                return;
+            };
+            {
+                let mut last_top_up = self.last_top_up.lock().expect("top-up lock poisoned");
+                if last_top_up.is_some_and(|started| started.elapsed() < self.top_up_cooldown) {
+                    return;
+                }
+                *last_top_up = Some(Instant::now());
+            }
+            if let Err(e) = top_up(balance).await {
+                tracing::error!("failed to top up the balance of {}: {}", self.account, e);
+            }
+        }
+    }
+}
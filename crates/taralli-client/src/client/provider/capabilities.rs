@@ -0,0 +1,122 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use taralli_primitives::alloy::{primitives::Address, signers::Signer};
+use taralli_primitives::capabilities::{
+    ProviderCapabilities, SignedCapabilities, SystemCapability,
+};
+use tokio::sync::Notify;
+use url::Url;
+
+use crate::api::capabilities::CapabilitiesApiClient;
+use crate::error::{ClientError, Result};
+
+/// how often the capabilities are refreshed when no job starts or finishes, well within the
+/// server's ttl
+const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Keeps a provider's capabilities advertised to the protocol server, refreshing them on a
+/// heartbeat and whenever a job starts or finishes
+pub struct CapabilityAdvertiser<S> {
+    api: CapabilitiesApiClient,
+    signer: S,
+    systems: Vec<SystemCapability>,
+    max_concurrent_jobs: u32,
+    active_jobs: AtomicU32,
+    jobs_changed: Notify,
+    heartbeat_interval: Duration,
+}
+
+impl<S: Signer> CapabilityAdvertiser<S> {
+    pub fn new(
+        server_url: Url,
+        signer: S,
+        systems: Vec<SystemCapability>,
+        max_concurrent_jobs: u32,
+    ) -> Self {
+        Self {
+            api: CapabilitiesApiClient::new(server_url),
+            signer,
+            systems,
+            max_concurrent_jobs,
+            active_jobs: AtomicU32::new(0),
+            jobs_changed: Notify::new(),
+            heartbeat_interval: DEFAULT_HEARTBEAT_INTERVAL,
+        }
+    }
+
+    pub fn with_heartbeat_interval(mut self, heartbeat_interval: Duration) -> Self {
+        self.heartbeat_interval = heartbeat_interval;
+        self
+    }
+
+    /// Address of the provider the capabilities are advertised for
+    pub fn provider(&self) -> Address {
+        self.signer.address()
+    }
+
+    pub fn active_jobs(&self) -> u32 {
+        self.active_jobs.load(Ordering::Relaxed)
+    }
+
+    /// Count a job as active until the returned guard is dropped
+    pub fn track_job(&self) -> JobGuard<'_, S> {
+        self.active_jobs.fetch_add(1, Ordering::Relaxed);
+        self.jobs_changed.notify_one();
+        JobGuard { advertiser: self }
+    }
+
+    /// Current capabilities, signed by the provider
+    pub async fn signed_capabilities(&self) -> Result<SignedCapabilities> {
+        let capabilities = ProviderCapabilities {
+            provider: self.signer.address(),
+            systems: self.systems.clone(),
+            max_concurrent_jobs: self.max_concurrent_jobs,
+            active_jobs: self.active_jobs(),
+            issued_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("system time before unix epoch")
+                .as_secs(),
+        };
+        SignedCapabilities::sign(capabilities, &self.signer)
+            .await
+            .map_err(|e| ClientError::ServerRequestError(e.to_string()))
+    }
+
+    pub async fn register(&self) -> Result<()> {
+        self.api.register(&self.signed_capabilities().await?).await
+    }
+
+    pub async fn refresh(&self) -> Result<()> {
+        self.api.refresh(&self.signed_capabilities().await?).await
+    }
+
+    /// Register the capabilities, then refresh them forever. Failures are logged, the next
+    /// refresh registers the capabilities again.
+    pub async fn run(&self) {
+        if let Err(e) = self.register().await {
+            tracing::warn!("failed to register capabilities: {}", e);
+        }
+        loop {
+            tokio::select! {
+                _ = tokio::time::sleep(self.heartbeat_interval) => {}
+                _ = self.jobs_changed.notified() => {}
+            }
+            if let Err(e) = self.refresh().await {
+                tracing::warn!("failed to refresh capabilities: {}", e);
+            }
+        }
+    }
+}
+
+/// Job counted by a `CapabilityAdvertiser` while it lives
+pub struct JobGuard<'a, S> {
+    advertiser: &'a CapabilityAdvertiser<S>,
+}
+
+impl<S> Drop for JobGuard<'_, S> {
+    fn drop(&mut self) {
+        self.advertiser.active_jobs.fetch_sub(1, Ordering::Relaxed);
+        self.advertiser.jobs_changed.notify_one();
+    }
+}
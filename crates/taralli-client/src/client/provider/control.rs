@@ -0,0 +1,681 @@
+//! Control of a running provider: pausing the intake of new intents, shutting down once the work
+//! in flight completes or a grace period expires, and reporting the intents left unresolved.
+//!
+//! Intents are tracked through their `IntentStage` by an `InFlight` guard. Once shutdown is
+//! requested no new intent is taken nor bid upon, proofs in flight get the grace period to
+//! complete and completed proofs are still resolved. Intents bid upon but not resolved are
+//! abandoned and listed in the `RunReport`, along with their proof when it was computed so they
+//! can be resolved manually.
+//!
+//! The handle also changes the log filter of the process while it runs, when given a
+//! `LogControl` such as the reload handle of its subscriber's `EnvFilter`, and dumps the intents
+//! in flight to the logs. Both are reached from outside the process through a small local HTTP
+//! endpoint:
+//! - `GET /log-filter` reports the filter in effect
+//! - `PUT /log-filter`, with a `{"filter": ...}` body, replaces it
+//! - `POST /dump-state` logs the state of the run
+//!
+//! Processes without a run, such as requesters, serve the log filter routes alone through
+//! `serve_log_filter`.
+//!
+//! Given the `WorkerManager` of the provider, the handle swaps the worker of a system without
+//! dropping the subscription: executions already started keep the worker they started with,
+//! the next ones get the new worker. Disabling a system removes its worker and narrows the
+//! subscription, which the provider reconnects with. Both are recorded as `RunEvent`s.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Deserialize;
+use serde_json::json;
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta::ProofRequest;
+use taralli_primitives::alloy::primitives::FixedBytes;
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::logging::STATE_TARGET;
+use taralli_primitives::systems::{SystemId, SystemIdMask, SystemParams};
+use tokio::net::TcpListener;
+use tokio::sync::watch;
+use tokio::time::Instant;
+use tracing_subscriber::{
+    fmt, layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Registry,
+};
+
+use crate::error::{ClientError, Result};
+use crate::worker::{SharedWorker, WorkResult, WorkerManager};
+
+type Request = ComputeRequest<SystemParams>;
+
+/// Log filter of a running process, as changed through `RunControl::set_log_filter`
+pub trait LogFilter: Send + Sync {
+    /// Filter in effect
+    fn log_filter(&self) -> String;
+
+    /// Replace the filter. An invalid filter leaves the one in effect.
+    fn set_log_filter(&self, filter: &str) -> Result<()>;
+}
+
+/// Shared handle to the log filter of the process
+pub type LogControl = Arc<dyn LogFilter>;
+
+/// The reload handle of a subscriber's `EnvFilter`, keeping the `STATE_TARGET` events at info
+/// whatever the filter
+impl<S: 'static> LogFilter for reload::Handle<EnvFilter, S> {
+    fn log_filter(&self) -> String {
+        self.with_current(ToString::to_string)
+            .unwrap_or_else(|e| format!("unavailable: {e}"))
+    }
+
+    fn set_log_filter(&self, filter: &str) -> Result<()> {
+        let env_filter = EnvFilter::try_new(format!("{filter},{STATE_TARGET}=info"))
+            .map_err(|e| ClientError::ConfigError(format!("invalid log filter {filter:?}: {e}")))?;
+        self.reload(env_filter)
+            .map_err(|e| ClientError::ConfigError(format!("reloading log filter: {e}")))?;
+        tracing::info!(target: STATE_TARGET, "log filter set to {filter}");
+        Ok(())
+    }
+}
+
+/// Install a global fmt subscriber filtered by `filter` (`RUST_LOG` syntax), returning the handle
+/// changing it
+pub fn init_log_control(filter: &str) -> Result<LogControl> {
+    let env_filter = EnvFilter::try_new(format!("{filter},{STATE_TARGET}=info"))
+        .map_err(|e| ClientError::ConfigError(format!("invalid log filter {filter:?}: {e}")))?;
+    let (layer, handle) = reload::Layer::<EnvFilter, Registry>::new(env_filter);
+    tracing_subscriber::registry()
+        .with(layer)
+        .with(fmt::layer())
+        .try_init()
+        .map_err(|e| ClientError::ConfigError(format!("installing subscriber: {e}")))?;
+    Ok(Arc::new(handle))
+}
+
+/// Routes of the control endpoint changing the log filter through `log_control`
+pub fn log_filter_router(log_control: LogControl) -> Router {
+    Router::new()
+        .route("/log-filter", get(get_log_filter).put(set_log_filter))
+        .with_state(log_control)
+}
+
+/// Serve the log filter routes on `listen_address` until it fails, the address being meant to
+/// stay local
+pub async fn serve_log_filter(log_control: LogControl, listen_address: SocketAddr) -> Result<()> {
+    let listener = TcpListener::bind(listen_address)
+        .await
+        .map_err(|e| ClientError::ControlEndpointError(e.to_string()))?;
+    axum::serve(listener, log_filter_router(log_control))
+        .await
+        .map_err(|e| ClientError::ControlEndpointError(e.to_string()))
+}
+
+/// Whether a running provider takes new intents
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunState {
+    Running,
+    /// new intents are left unread, the server skips them once the subscription buffer is full
+    Paused,
+    /// no new intent is taken, the run ends once the intents in flight are done or abandoned
+    ShuttingDown,
+}
+
+/// Step of its fulfillment an intent in flight is at
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IntentStage {
+    /// received, being analyzed and bid upon
+    Queued,
+    /// bid upon, waiting for its proof
+    Proving,
+    /// proven, waiting for the resolve transaction
+    AwaitingResolve,
+}
+
+/// Number of intents at each stage of their fulfillment
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RunStatus {
+    pub state: RunState,
+    pub queued: usize,
+    pub proving: usize,
+    pub awaiting_resolve: usize,
+    /// intents resolved since the run started
+    pub completed: usize,
+}
+
+/// Intent bid upon but not resolved
+#[derive(Clone, Debug)]
+pub struct AbandonedIntent {
+    pub intent_id: FixedBytes<32>,
+    pub system_id: SystemId,
+    pub proof_request: ProofRequest,
+    /// stage the intent was abandoned at
+    pub stage: IntentStage,
+    pub reason: String,
+    /// proof computed for the intent, set when it was abandoned while awaiting its resolve
+    pub work_result: Option<WorkResult>,
+}
+
+/// Change made to a running provider through its control
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RunEvent {
+    /// the worker of the system was replaced, `old` being unset when it had none
+    WorkerReplaced {
+        system_id: SystemId,
+        old: Option<String>,
+        new: String,
+    },
+    /// the system's worker was removed and the subscription narrowed down
+    SystemDisabled { system_id: SystemId },
+}
+
+/// Outcome of a run, returned once it ended
+#[derive(Clone, Debug, Default)]
+pub struct RunReport {
+    /// number of intents resolved
+    pub completed: usize,
+    pub abandoned: Vec<AbandonedIntent>,
+}
+
+struct Tracked {
+    system_id: SystemId,
+    proof_request: ProofRequest,
+    stage: IntentStage,
+    work_result: Option<WorkResult>,
+}
+
+#[derive(Default)]
+struct Ledger {
+    in_flight: HashMap<FixedBytes<32>, Tracked>,
+    completed: usize,
+    abandoned: Vec<AbandonedIntent>,
+    events: Vec<RunEvent>,
+}
+
+struct Inner {
+    state: watch::Sender<RunState>,
+    // set along with `RunState::ShuttingDown`
+    grace_deadline: Mutex<Option<Instant>>,
+    ledger: Mutex<Ledger>,
+    report: watch::Sender<Option<RunReport>>,
+    log_control: Option<LogControl>,
+    worker_manager: Option<WorkerManager<Request>>,
+    // systems left out of the subscription
+    disabled_systems: watch::Sender<SystemIdMask>,
+}
+
+/// Handle of a running provider, cloned freely. The run has to keep being polled for pausing and
+/// shutting down to take effect.
+#[derive(Clone)]
+pub struct RunControl {
+    inner: Arc<Inner>,
+}
+
+impl Default for RunControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RunControl {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_log_control(None)
+    }
+
+    /// Control also changing the log filter through `log_control`, when set
+    #[must_use]
+    pub fn with_log_control(log_control: Option<LogControl>) -> Self {
+        Self::for_workers(log_control, None)
+    }
+
+    /// Control also replacing the workers of `worker_manager` and disabling its systems, when set
+    #[must_use]
+    pub fn for_workers(
+        log_control: Option<LogControl>,
+        worker_manager: Option<WorkerManager<Request>>,
+    ) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                state: watch::channel(RunState::Running).0,
+                grace_deadline: Mutex::new(None),
+                ledger: Mutex::new(Ledger::default()),
+                report: watch::channel(None).0,
+                log_control,
+                worker_manager,
+                disabled_systems: watch::channel(0).0,
+            }),
+        }
+    }
+
+    /// Replace the worker of `system_id`, e.g. with one running a new prover version. Executions
+    /// already started keep the worker they started with, the next ones get `worker`. A disabled
+    /// system is subscribed to again.
+    pub fn replace_worker(&self, system_id: SystemId, worker: SharedWorker<Request>) -> Result<()> {
+        let worker_manager = self.worker_manager()?;
+        let new = worker.describe();
+        let old = worker_manager
+            .set_worker(system_id, worker)
+            .map(|old| old.describe());
+        tracing::info!(
+            "worker of system {:?} replaced: {} -> {}",
+            system_id,
+            old.as_deref().unwrap_or("none"),
+            new
+        );
+        self.inner.disabled_systems.send_if_modified(|disabled| {
+            let modified = *disabled & system_id.as_bit() != 0;
+            *disabled &= !system_id.as_bit();
+            modified
+        });
+        self.record(RunEvent::WorkerReplaced {
+            system_id,
+            old,
+            new,
+        });
+        Ok(())
+    }
+
+    /// Stop taking intents of `system_id`: its worker is removed, executions already started run
+    /// to completion, and the provider subscribes again without the system
+    pub fn disable_system(&self, system_id: SystemId) -> Result<()> {
+        self.worker_manager()?.remove_worker(system_id);
+        tracing::info!("system {:?} disabled", system_id);
+        self.inner.disabled_systems.send_if_modified(|disabled| {
+            let modified = *disabled & system_id.as_bit() == 0;
+            *disabled |= system_id.as_bit();
+            modified
+        });
+        self.record(RunEvent::SystemDisabled { system_id });
+        Ok(())
+    }
+
+    /// Mask of the systems disabled while running
+    #[must_use]
+    pub fn disabled_systems(&self) -> SystemIdMask {
+        *self.inner.disabled_systems.borrow()
+    }
+
+    /// Receiver notified as systems are disabled or enabled again
+    #[must_use]
+    pub fn watch_disabled_systems(&self) -> watch::Receiver<SystemIdMask> {
+        self.inner.disabled_systems.subscribe()
+    }
+
+    /// Changes made to the run through the control, oldest first
+    #[must_use]
+    pub fn events(&self) -> Vec<RunEvent> {
+        self.inner
+            .ledger
+            .lock()
+            .expect("run ledger lock poisoned")
+            .events
+            .clone()
+    }
+
+    fn worker_manager(&self) -> Result<&WorkerManager<Request>> {
+        self.inner.worker_manager.as_ref().ok_or_else(|| {
+            ClientError::ConfigError("the run has no worker manager to change".to_string())
+        })
+    }
+
+    fn record(&self, event: RunEvent) {
+        self.inner
+            .ledger
+            .lock()
+            .expect("run ledger lock poisoned")
+            .events
+            .push(event);
+    }
+
+    /// Replace the log filter of the process, e.g. `info,taralli_client::bidder=debug`
+    pub fn set_log_filter(&self, filter: impl Into<String>) -> Result<()> {
+        self.inner
+            .log_control
+            .as_ref()
+            .ok_or_else(|| {
+                ClientError::ConfigError("the run has no log control to set".to_string())
+            })?
+            .set_log_filter(&filter.into())
+    }
+
+    /// Filter in effect, unset when the run has no log control
+    #[must_use]
+    pub fn log_filter(&self) -> Option<String> {
+        self.inner
+            .log_control
+            .as_ref()
+            .map(|log_control| log_control.log_filter())
+    }
+
+    /// Log the state of the run and its intents in flight at info, whatever the log filter
+    pub fn dump_state(&self) {
+        let status = self.status();
+        tracing::info!(
+            target: STATE_TARGET,
+            "run {:?}: {} queued, {} proving, {} awaiting resolve, {} completed, {} abandoned",
+            status.state,
+            status.queued,
+            status.proving,
+            status.awaiting_resolve,
+            status.completed,
+            self.inner.ledger.lock().expect("run ledger lock poisoned").abandoned.len()
+        );
+        if let Some(log_control) = &self.inner.log_control {
+            tracing::info!(target: STATE_TARGET, "log filter: {}", log_control.log_filter());
+        }
+        let ledger = self.inner.ledger.lock().expect("run ledger lock poisoned");
+        for event in &ledger.events {
+            tracing::info!(target: STATE_TARGET, "{:?}", event);
+        }
+        let mut in_flight: Vec<_> = ledger.in_flight.iter().collect();
+        in_flight.sort_by_key(|(intent_id, tracked)| (tracked.stage, **intent_id));
+        for (intent_id, tracked) in in_flight {
+            tracing::info!(
+                target: STATE_TARGET,
+                "intent {intent_id} of system {:?}: {:?}, auction ends at {}",
+                tracked.system_id,
+                tracked.stage,
+                tracked.proof_request.endAuctionTimestamp
+            );
+        }
+    }
+
+    /// Routes of the control endpoint of the run, the log filter ones only when it has a log
+    /// control
+    pub fn router(&self) -> Router {
+        let router = Router::new()
+            .route("/dump-state", post(dump_state))
+            .with_state(self.clone());
+        match &self.inner.log_control {
+            Some(log_control) => router.merge(log_filter_router(log_control.clone())),
+            None => router,
+        }
+    }
+
+    /// Serve the control endpoint of the run on `listener` until it fails
+    pub async fn serve(&self, listener: TcpListener) -> Result<()> {
+        axum::serve(listener, self.router())
+            .await
+            .map_err(|e| ClientError::ControlEndpointError(e.to_string()))
+    }
+
+    /// Stop taking new intents until resumed, the intents in flight keep being processed
+    pub fn pause(&self) {
+        self.inner.state.send_if_modified(|state| {
+            let modified = *state == RunState::Running;
+            if modified {
+                *state = RunState::Paused;
+            }
+            modified
+        });
+    }
+
+    pub fn resume(&self) {
+        self.inner.state.send_if_modified(|state| {
+            let modified = *state == RunState::Paused;
+            if modified {
+                *state = RunState::Running;
+            }
+            modified
+        });
+    }
+
+    /// Stop taking new intents and give the proofs in flight `grace` to complete, returning the
+    /// report of the run once it ended. Completed proofs are resolved even past the grace period.
+    /// Requesting shutdown again doesn't extend the first grace period.
+    pub async fn shutdown(&self, grace: Duration) -> RunReport {
+        {
+            let mut grace_deadline = self
+                .inner
+                .grace_deadline
+                .lock()
+                .expect("grace deadline lock poisoned");
+            grace_deadline.get_or_insert_with(|| Instant::now() + grace);
+        }
+        self.inner.state.send_replace(RunState::ShuttingDown);
+
+        let mut report = self.inner.report.subscribe();
+        let report = report
+            .wait_for(Option::is_some)
+            .await
+            .expect("report sender lives as long as the control");
+        (*report).clone().expect("waited for a report")
+    }
+
+    #[must_use]
+    pub fn state(&self) -> RunState {
+        *self.inner.state.borrow()
+    }
+
+    #[must_use]
+    pub fn status(&self) -> RunStatus {
+        let ledger = self.inner.ledger.lock().expect("run ledger lock poisoned");
+        let at_stage = |stage: IntentStage| {
+            ledger
+                .in_flight
+                .values()
+                .filter(|tracked| tracked.stage == stage)
+                .count()
+        };
+        RunStatus {
+            state: self.state(),
+            queued: at_stage(IntentStage::Queued),
+            proving: at_stage(IntentStage::Proving),
+            awaiting_resolve: at_stage(IntentStage::AwaitingResolve),
+            completed: ledger.completed,
+        }
+    }
+
+    /// Wait until new intents can be taken, returning false once shutdown is requested
+    pub async fn accepting(&self) -> bool {
+        let mut state = self.inner.state.subscribe();
+        let state = state
+            .wait_for(|state| *state != RunState::Paused)
+            .await
+            .expect("state sender lives as long as the control");
+        *state == RunState::Running
+    }
+
+    /// Resolve once the state is no longer `Running`
+    pub async fn interrupted(&self) {
+        let mut state = self.inner.state.subscribe();
+        let _ = state.wait_for(|state| *state != RunState::Running).await;
+    }
+
+    /// Run `work` until it completes or the grace period of a requested shutdown expires, in
+    /// which case `None` is returned
+    pub async fn within_grace<F: Future>(&self, work: F) -> Option<F::Output> {
+        tokio::select! {
+            output = work => Some(output),
+            () = self.grace_expired() => None,
+        }
+    }
+
+    async fn grace_expired(&self) {
+        let mut state = self.inner.state.subscribe();
+        let _ = state
+            .wait_for(|state| *state == RunState::ShuttingDown)
+            .await;
+        let deadline = *self
+            .inner
+            .grace_deadline
+            .lock()
+            .expect("grace deadline lock poisoned");
+        if let Some(deadline) = deadline {
+            tokio::time::sleep_until(deadline).await;
+        }
+    }
+
+    /// Track an intent from its reception, until the returned guard completes or abandons it
+    pub fn track(
+        &self,
+        intent_id: FixedBytes<32>,
+        system_id: SystemId,
+        proof_request: ProofRequest,
+    ) -> InFlight {
+        self.inner
+            .ledger
+            .lock()
+            .expect("run ledger lock poisoned")
+            .in_flight
+            .insert(
+                intent_id,
+                Tracked {
+                    system_id,
+                    proof_request,
+                    stage: IntentStage::Queued,
+                    work_result: None,
+                },
+            );
+        InFlight {
+            control: self.clone(),
+            intent_id,
+            finished: false,
+        }
+    }
+
+    /// Publish the report of the run, intents still in flight are abandoned
+    pub fn finish(&self) -> RunReport {
+        let mut ledger = self.inner.ledger.lock().expect("run ledger lock poisoned");
+        let in_flight: Vec<_> = ledger.in_flight.drain().collect();
+        for (intent_id, tracked) in in_flight {
+            if tracked.stage > IntentStage::Queued {
+                ledger
+                    .abandoned
+                    .push(abandoned(intent_id, tracked, "run ended".to_string()));
+            }
+        }
+        let report = RunReport {
+            completed: ledger.completed,
+            abandoned: ledger.abandoned.clone(),
+        };
+        self.inner.report.send_replace(Some(report.clone()));
+        report
+    }
+}
+
+fn abandoned(intent_id: FixedBytes<32>, tracked: Tracked, reason: String) -> AbandonedIntent {
+    AbandonedIntent {
+        intent_id,
+        system_id: tracked.system_id,
+        proof_request: tracked.proof_request,
+        stage: tracked.stage,
+        reason,
+        work_result: tracked.work_result,
+    }
+}
+
+/// Intent tracked by a `RunControl`. Dropping the guard without completing or abandoning the
+/// intent abandons it when it was bid upon, and forgets it otherwise.
+pub struct InFlight {
+    control: RunControl,
+    intent_id: FixedBytes<32>,
+    finished: bool,
+}
+
+impl InFlight {
+    /// The intent was bid upon, its proof is being computed
+    pub fn proving(&self) {
+        self.update(|tracked| tracked.stage = IntentStage::Proving);
+    }
+
+    /// The intent was proven with `work_result`, its resolve is being sent
+    pub fn awaiting_resolve(&self, work_result: &WorkResult) {
+        self.update(|tracked| {
+            tracked.stage = IntentStage::AwaitingResolve;
+            tracked.work_result = Some(work_result.clone());
+        });
+    }
+
+    /// The intent was resolved
+    pub fn complete(mut self) {
+        self.finished = true;
+        let mut ledger = self
+            .control
+            .inner
+            .ledger
+            .lock()
+            .expect("run ledger lock poisoned");
+        ledger.in_flight.remove(&self.intent_id);
+        ledger.completed += 1;
+    }
+
+    /// Give up on the intent. It's only reported when it was bid upon.
+    pub fn abandon(mut self, reason: impl Into<String>) {
+        self.finished = true;
+        self.remove(reason.into());
+    }
+
+    fn update(&self, update: impl FnOnce(&mut Tracked)) {
+        if let Some(tracked) = self
+            .control
+            .inner
+            .ledger
+            .lock()
+            .unwrap()
+            .in_flight
+            .get_mut(&self.intent_id)
+        {
+            update(tracked);
+        }
+    }
+
+    fn remove(&self, reason: String) {
+        let mut ledger = self
+            .control
+            .inner
+            .ledger
+            .lock()
+            .expect("run ledger lock poisoned");
+        if let Some(tracked) = ledger.in_flight.remove(&self.intent_id) {
+            if tracked.stage > IntentStage::Queued {
+                ledger
+                    .abandoned
+                    .push(abandoned(self.intent_id, tracked, reason));
+            }
+        }
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.remove("processing was cancelled".to_string());
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct NewLogFilter {
+    filter: String,
+}
+
+async fn get_log_filter(State(log_control): State<LogControl>) -> Json<serde_json::Value> {
+    Json(json!({ "filter": log_control.log_filter() }))
+}
+
+async fn set_log_filter(
+    State(log_control): State<LogControl>,
+    Json(new_filter): Json<NewLogFilter>,
+) -> Response {
+    match log_control.set_log_filter(&new_filter.filter) {
+        Ok(()) => Json(json!({ "filter": log_control.log_filter() })).into_response(),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": e.to_string() })),
+        )
+            .into_response(),
+    }
+}
+
+async fn dump_state(State(control): State<RunControl>) -> StatusCode {
+    control.dump_state();
+    StatusCode::NO_CONTENT
+}
@@ -1,2 +1,7 @@
+pub mod balance;
+pub mod capabilities;
+pub mod control;
 pub mod offering;
+pub mod review;
+pub mod searching;
 pub mod streaming;
@@ -1,28 +1,38 @@
 use std::sync::Arc;
 use std::time::Duration;
-use taralli_primitives::alloy::primitives::Address;
+use taralli_primitives::abi::verifier_details::{decode_offer_verifier_details, DecodeMode};
+use taralli_primitives::alloy::primitives::{Address, B256};
 use taralli_primitives::alloy::signers::Signer;
 use taralli_primitives::alloy::{network::Network, providers::Provider, transports::Transport};
 use taralli_primitives::intents::offer::ComputeOffer;
 use taralli_primitives::intents::ComputeIntent;
+use taralli_primitives::offer_inputs::with_offer_inputs;
 use taralli_primitives::systems::{SystemId, SystemParams};
 use taralli_primitives::validation::offer::{
-    ComputeOfferValidator, OfferValidationConfig, OfferVerifierConstraints,
+    validate_offer_inputs_commitment, validate_offer_submission, ComputeOfferValidator,
+    OfferValidationConfig, OfferVerifierConstraints,
 };
 use taralli_primitives::validation::IntentValidator;
 use url::Url;
 
+use crate::api::inputs::OfferInputsApiClient;
 use crate::api::submit::SubmitApiClient;
 use crate::client::BaseClient;
+use crate::confirmations::Confirmations;
 use crate::error::{ClientError, Result};
 use crate::resolver::IntentResolver;
-use crate::tracker::IntentAuctionTracker;
+use crate::submitter::TransactionSubmitter;
+use crate::tracker::{IntentAuctionTracker, TrackerConfig};
 use crate::worker::{ComputeWorker, WorkResult};
 use crate::{
-    intent_builder::offer::ComputeOfferBuilder, resolver::offer::ComputeOfferResolver,
+    intent_builder::offer::ComputeOfferBuilder,
+    resolver::offer::{ComputeOfferResolver, OfferResolveParams},
     tracker::offer::ComputeOfferTracker,
 };
 
+/// how often the server is polled for the inputs of a won offer
+const INPUTS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
 /// Client that submits signed compute offerings to the protocol server, tracks their auction status,
 /// compute's the offered compute workload assuming it is bid upon, and then resolves the compute offer
 /// within the market contract.
@@ -39,6 +49,10 @@ where
     pub tracker: ComputeOfferTracker<T, P, N>,
     pub worker: Arc<dyn ComputeWorker<ComputeOffer<SystemParams>>>,
     pub resolver: ComputeOfferResolver<T, P, N>,
+    inputs_api: OfferInputsApiClient,
+    // time the requester of a won offer gets to deliver its inputs, when the offer leaves them
+    // to the requester
+    inputs_timeout: Option<Duration>,
 }
 
 impl<T, P, N, S> ProviderOfferingClient<T, P, N, S>
@@ -62,6 +76,7 @@ where
         Self {
             base: BaseClient::new(rpc_provider.clone(), signer.clone(), market_address),
             api: SubmitApiClient::new(server_url.clone()),
+            inputs_api: OfferInputsApiClient::new(server_url.clone()),
             validator: ComputeOfferValidator::new(validation_config, verifier_constraints),
             builder: ComputeOfferBuilder::new(
                 rpc_provider.clone(),
@@ -72,9 +87,40 @@ where
             tracker: ComputeOfferTracker::new(rpc_provider.clone(), market_address),
             worker,
             resolver: ComputeOfferResolver::new(rpc_provider, market_address),
+            inputs_timeout: None,
         }
     }
 
+    /// Prove won offers over the inputs their requester delivers through the server rather than
+    /// the inputs of the offered system, giving the requester up to `inputs_timeout` (capped by
+    /// the proving time) to deliver them
+    pub fn with_inputs_delivery(mut self, inputs_timeout: Duration) -> Self {
+        self.inputs_timeout = Some(inputs_timeout);
+        self
+    }
+
+    /// send resolve transactions through the given submitter (e.g. a private rpc)
+    pub fn with_transaction_submitter(
+        mut self,
+        submitter: Arc<dyn TransactionSubmitter<N>>,
+    ) -> Self {
+        self.resolver = self.resolver.with_submitter(submitter);
+        self
+    }
+
+    /// wait for the tracked bid and the resolve transaction to be confirmed at the given depth
+    pub fn with_confirmations(mut self, confirmations: Confirmations) -> Self {
+        self.tracker = self.tracker.with_confirmations(confirmations.clone());
+        self.resolver = self.resolver.with_confirmations(confirmations);
+        self
+    }
+
+    /// poll the market alongside the event watch while tracking, see `TrackerConfig`
+    pub fn with_tracker_config(mut self, tracker_config: TrackerConfig) -> Self {
+        self.tracker = self.tracker.with_tracker_config(tracker_config);
+        self
+    }
+
     /// sign the inputted proof offer and submit it to the taralli server.
     /// then start tracking the offer auction on-chain.
     pub async fn submit_and_track(
@@ -108,61 +154,100 @@ where
 
         // track the offer's auction
         if !response.status().is_success() {
-            // Parse the error response
-            let error_body = response.json::<serde_json::Value>().await.map_err(|e| {
-                ClientError::ServerRequestError(format!("Failed to parse error response: {e}"))
-            })?;
-
-            return Err(ClientError::IntentSubmissionFailed(format!(
-                "Server validation failed: {}",
-                error_body["error"].as_str().unwrap_or("Unknown error")
-            )));
+            return Err(ClientError::from_server_response(response).await);
         }
 
         tracing::info!("Offer submitted successfully, waiting for auction result");
 
         // Wait for auction result
-        let _auction_result = auction_tracker
+        let bid = auction_tracker
             .await
             .map_err(|e| ClientError::TrackIntentError(e.to_string()))?
             .ok_or(ClientError::AuctionTimeoutError())?;
 
+        let offer = match self.inputs_timeout {
+            Some(inputs_timeout) => {
+                let inputs_timeout =
+                    inputs_timeout.min(Duration::from_secs(offer.proof_offer.provingTime.into()));
+                self.receive_inputs(offer_id, offer, bid.signer, inputs_timeout)
+                    .await
+                    .inspect_err(|e| {
+                        tracing::warn!("offer {} abandoned without its inputs: {}", offer_id, e)
+                    })?
+            }
+            None => offer,
+        };
+
         tracing::info!("Auction completed, starting compute worker");
 
         // Execute worker
-        let work_result: WorkResult = self
-            .worker
-            .execute(&offer)
-            .await
-            .map_err(|e| ClientError::WorkerError(e.to_string()))?;
+        let work_result: WorkResult = self.worker.execute(&offer).await?;
 
         tracing::info!("Compute worker execution completed, resolving");
 
+        // a submission failing the market's commitment check would only waste the resolve gas
+        validate_offer_submission(&offer.proof_offer, &work_result.opaque_submission)
+            .map_err(|e| ClientError::WorkerError(e.to_string()))?;
+
         self.resolver
-            .resolve_intent(offer_id, work_result.opaque_submission)
+            .resolve_intent(offer_id, &OfferResolveParams::from(work_result))
             .await
-            .map_err(|e| ClientError::TransactionFailure(format!("resolver failed: {e}")))?;
+            .inspect_err(|e| tracing::error!("resolver failed: {}", e))?;
 
         tracing::info!("Compute offer resolved");
         Ok(())
     }
 
-    pub async fn sign(
+    /// Await the inputs the requester of a won offer delivers, only handing them to the worker
+    /// once they are signed by the bidder and match the offer's inputs commitment
+    pub async fn receive_inputs(
         &self,
-        mut offer: ComputeOffer<SystemParams>,
+        offer_id: B256,
+        offer: ComputeOffer<SystemParams>,
+        bidder: Address,
+        inputs_timeout: Duration,
     ) -> Result<ComputeOffer<SystemParams>> {
-        // build permit2 digest
-        let permit2_digest = offer.compute_permit2_digest();
-        // sign permit2 digest
-        let signature = self
-            .base
-            .signer
-            .sign_hash(&permit2_digest)
+        let offer_inputs = self
+            .inputs_api
+            .await_inputs(
+                offer_id,
+                &self.base.signer,
+                INPUTS_POLL_INTERVAL,
+                inputs_timeout,
+            )
+            .await?;
+        let signer = offer_inputs
+            .signer(offer_id)
+            .map_err(|e| ClientError::OfferInputsError(e.to_string()))?;
+        if signer != bidder {
+            return Err(ClientError::OfferInputsError(format!(
+                "inputs of offer {offer_id} signed by {signer}, not by its bidder {bidder}"
+            )));
+        }
+        let inputs = offer_inputs
+            .decompress(&Default::default())
             .await
-            .map_err(|e| ClientError::IntentSigningError(e.to_string()))?;
-        // load signature into proof request
-        offer.signature = signature;
-        Ok(offer)
+            .map_err(|e| ClientError::OfferInputsError(e.to_string()))?;
+        validate_offer_inputs_commitment(&offer.proof_offer, &inputs)
+            .map_err(|e| ClientError::OfferInputsError(e.to_string()))?;
+        let system = with_offer_inputs(&offer.system, &inputs)
+            .map_err(|e| ClientError::OfferInputsError(e.to_string()))?;
+        tracing::info!(
+            "{} bytes of inputs received for offer {}",
+            inputs.len(),
+            offer_id
+        );
+
+        Ok(ComputeOffer { system, ..offer })
+    }
+
+    pub async fn sign(
+        &self,
+        offer: ComputeOffer<SystemParams>,
+    ) -> Result<ComputeOffer<SystemParams>> {
+        // build permit2 digest for the chain of the rpc provider
+        let chain_id = self.builder.chain_id().await?;
+        sign_offer(&self.base.signer, offer, chain_id).await
     }
 
     pub fn validate_offer(&self, offer: &ComputeOffer<SystemParams>) -> Result<()> {
@@ -175,3 +260,25 @@ where
         Ok(())
     }
 }
+
+/// Sign a proof offer over its permit2 digest for the given chain. The digest commits to the
+/// `ProofOffer` witness under the Porchetta type hashes, with the stake as the permitted transfer,
+/// so the signature is what `UniversalPorchetta.bid()` recovers the offer signer from.
+pub async fn sign_offer<S: Signer>(
+    signer: &S,
+    mut offer: ComputeOffer<SystemParams>,
+    chain_id: u64,
+) -> Result<ComputeOffer<SystemParams>> {
+    // refuse to sign an intent whose verifier details can't be decoded exactly
+    decode_offer_verifier_details(&offer.proof_offer.extraData, DecodeMode::Strict)
+        .map_err(|e| ClientError::ValidationError(e.to_string()))?;
+    let permit2_digest = offer.compute_permit2_digest(chain_id);
+    // sign permit2 digest
+    let signature = signer
+        .sign_hash(&permit2_digest)
+        .await
+        .map_err(|e| ClientError::IntentSigningError(e.to_string()))?;
+    // load signature into proof offer
+    offer.signature = signature;
+    Ok(offer)
+}
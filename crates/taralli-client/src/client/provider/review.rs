@@ -0,0 +1,272 @@
+//! Manual review of the requests a provider bids upon.
+//!
+//! In `BiddingMode::Manual`, analyzed requests are parked in a `ReviewQueue` instead of being bid
+//! upon right away. An operator, or an external policy service, decides on each through a small
+//! local HTTP endpoint:
+//! - `GET /pending` lists the requests awaiting review
+//! - `POST /pending/{request_id}/approve` resumes the bid, proof and resolve of the request
+//! - `POST /pending/{request_id}/reject`, with an optional `{"reason": ...}` body, drops it
+//!
+//! Requests left pending expire `expiry_margin_secs` before their auction ends, as a bid sent
+//! later might not land before the auction closes.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use taralli_primitives::alloy::primitives::FixedBytes;
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::systems::SystemParams;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+use crate::error::{ClientError, Result};
+
+/// Seconds before the end of its auction a request left pending expires, about a block
+pub const DEFAULT_EXPIRY_MARGIN_SECS: u64 = 12;
+
+fn default_expiry_margin_secs() -> u64 {
+    DEFAULT_EXPIRY_MARGIN_SECS
+}
+
+/// Whether a provider bids on the requests passing its analysis on its own
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "mode")]
+pub enum BiddingMode {
+    #[default]
+    Automatic,
+    /// park requests in a `ReviewQueue` until approved
+    Manual(ReviewConfig),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewConfig {
+    /// address the review endpoint listens on, meant to stay local
+    pub listen_address: SocketAddr,
+    #[serde(default = "default_expiry_margin_secs")]
+    pub expiry_margin_secs: u64,
+}
+
+impl ReviewConfig {
+    #[must_use]
+    pub fn expiry_margin(&self) -> Duration {
+        Duration::from_secs(self.expiry_margin_secs)
+    }
+}
+
+/// Request awaiting review, as listed by the review endpoint
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PendingRequest {
+    pub request_id: FixedBytes<32>,
+    pub request: ComputeRequest<SystemParams>,
+    /// seconds left before the request expires
+    pub expires_in_secs: u64,
+}
+
+#[derive(Deserialize)]
+struct Rejection {
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+type Approved = (FixedBytes<32>, ComputeRequest<SystemParams>);
+
+struct Parked {
+    request: ComputeRequest<SystemParams>,
+    expires_at: Instant,
+}
+
+struct Inner {
+    expiry_margin: Duration,
+    pending: Mutex<HashMap<FixedBytes<32>, Parked>>,
+    approved_sender: mpsc::UnboundedSender<Approved>,
+    approved_receiver: tokio::sync::Mutex<mpsc::UnboundedReceiver<Approved>>,
+}
+
+/// Requests awaiting review before being bid upon, cloned freely
+#[derive(Clone)]
+pub struct ReviewQueue {
+    inner: Arc<Inner>,
+}
+
+impl ReviewQueue {
+    #[must_use]
+    pub fn new(expiry_margin: Duration) -> Self {
+        let (approved_sender, approved_receiver) = mpsc::unbounded_channel();
+        Self {
+            inner: Arc::new(Inner {
+                expiry_margin,
+                pending: Mutex::new(HashMap::new()),
+                approved_sender,
+                approved_receiver: tokio::sync::Mutex::new(approved_receiver),
+            }),
+        }
+    }
+
+    /// Park a request for review, decided upon at `latest_ts`. Returns false when its auction
+    /// ends too soon to leave any time for the review.
+    pub fn park(
+        &self,
+        request_id: FixedBytes<32>,
+        request: ComputeRequest<SystemParams>,
+        latest_ts: u64,
+    ) -> bool {
+        let expires_in = request
+            .proof_request
+            .endAuctionTimestamp
+            .saturating_sub(self.inner.expiry_margin.as_secs())
+            .saturating_sub(latest_ts);
+        if expires_in == 0 {
+            return false;
+        }
+        self.inner.pending.lock().unwrap().insert(
+            request_id,
+            Parked {
+                request,
+                expires_at: Instant::now() + Duration::from_secs(expires_in),
+            },
+        );
+        true
+    }
+
+    /// Requests awaiting review, the ones expiring first first
+    #[must_use]
+    pub fn pending(&self) -> Vec<PendingRequest> {
+        let mut pending = self.inner.pending.lock().unwrap();
+        remove_expired(&mut pending);
+        let now = Instant::now();
+        let mut listed: Vec<_> = pending
+            .iter()
+            .map(|(request_id, parked)| PendingRequest {
+                request_id: *request_id,
+                request: parked.request.clone(),
+                expires_in_secs: parked.expires_at.saturating_duration_since(now).as_secs(),
+            })
+            .collect();
+        listed.sort_by_key(|pending| pending.expires_in_secs);
+        listed
+    }
+
+    /// Hand a pending request over to the bidder. Returns false when the request isn't pending,
+    /// e.g. it expired or was already decided upon.
+    pub fn approve(&self, request_id: FixedBytes<32>) -> bool {
+        let parked = {
+            let mut pending = self.inner.pending.lock().unwrap();
+            remove_expired(&mut pending);
+            pending.remove(&request_id)
+        };
+        let Some(parked) = parked else {
+            return false;
+        };
+        tracing::info!("request {} approved", request_id);
+        // the receiver lives as long as the sender, both being held by the queue
+        let _ = self
+            .inner
+            .approved_sender
+            .send((request_id, parked.request));
+        true
+    }
+
+    /// Drop a pending request without bidding. Returns false when the request isn't pending.
+    pub fn reject(&self, request_id: FixedBytes<32>, reason: Option<&str>) -> bool {
+        let parked = {
+            let mut pending = self.inner.pending.lock().unwrap();
+            remove_expired(&mut pending);
+            pending.remove(&request_id)
+        };
+        if parked.is_none() {
+            return false;
+        }
+        tracing::info!(
+            "request {} rejected: {}",
+            request_id,
+            reason.unwrap_or("no reason given")
+        );
+        true
+    }
+
+    /// Wait for the next approved request
+    pub async fn next_approved(&self) -> (FixedBytes<32>, ComputeRequest<SystemParams>) {
+        self.inner
+            .approved_receiver
+            .lock()
+            .await
+            .recv()
+            .await
+            .expect("approved sender lives as long as the queue")
+    }
+
+    /// Routes of the review endpoint
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/pending", get(list_pending))
+            .route("/pending/:request_id/approve", post(approve))
+            .route("/pending/:request_id/reject", post(reject))
+            .with_state(self.clone())
+    }
+
+    /// Serve the review endpoint on `listener` until it fails
+    pub async fn serve(&self, listener: TcpListener) -> Result<()> {
+        axum::serve(listener, self.router())
+            .await
+            .map_err(|e| ClientError::ReviewEndpointError(e.to_string()))
+    }
+}
+
+fn remove_expired(pending: &mut HashMap<FixedBytes<32>, Parked>) {
+    let now = Instant::now();
+    pending.retain(|request_id, parked| {
+        let live = parked.expires_at > now;
+        if !live {
+            tracing::info!("request {} expired before being reviewed", request_id);
+        }
+        live
+    });
+}
+
+async fn list_pending(State(queue): State<ReviewQueue>) -> Json<Vec<PendingRequest>> {
+    Json(queue.pending())
+}
+
+async fn approve(
+    State(queue): State<ReviewQueue>,
+    Path(request_id): Path<FixedBytes<32>>,
+) -> Response {
+    decided(request_id, queue.approve(request_id), "approved")
+}
+
+async fn reject(
+    State(queue): State<ReviewQueue>,
+    Path(request_id): Path<FixedBytes<32>>,
+    rejection: Option<Json<Rejection>>,
+) -> Response {
+    let reason = rejection.and_then(|Json(rejection)| rejection.reason);
+    decided(
+        request_id,
+        queue.reject(request_id, reason.as_deref()),
+        "rejected",
+    )
+}
+
+fn decided(request_id: FixedBytes<32>, was_pending: bool, decision: &str) -> Response {
+    if was_pending {
+        Json(json!({ "request_id": request_id, "decision": decision })).into_response()
+    } else {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({ "error": format!("request {request_id} is not pending review") })),
+        )
+            .into_response()
+    }
+}
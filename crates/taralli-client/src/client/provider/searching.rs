@@ -0,0 +1,284 @@
+use std::{collections::HashMap, sync::Arc, time::Instant};
+
+use taralli_primitives::alloy::{
+    network::Network,
+    primitives::{Address, FixedBytes, U256},
+    providers::Provider,
+    signers::Signer,
+    transports::Transport,
+};
+use taralli_primitives::{
+    intents::request::ComputeRequest,
+    logging::intent_span,
+    markets::MarketFee,
+    systems::{SystemId, SystemParams},
+    validation::{
+        registry::ValidatorRegistry,
+        request::{validate_request_submission, ComputeRequestValidator, RequestValidationConfig},
+    },
+};
+use tracing::Instrument;
+use url::Url;
+
+use crate::error::{ClientError, Result};
+use crate::{
+    analyzer::{request::ComputeRequestAnalyzer, IntentAnalyzer},
+    bidder::{
+        request::{BidMode, ComputeRequestBidParams, ComputeRequestBidder},
+        IntentBidder,
+    },
+    client::{provider::balance::BalanceMonitor, BaseClient},
+    market_state::MarketStateClient,
+    proving_stats::{workload_size, ProvingStats},
+    resolver::{request::ComputeRequestResolver, IntentResolver},
+    searcher::request::{ComputeRequestSearcher, CostModel},
+    work_cache::WorkCache,
+    worker::{ComputeWorker, WorkResult, WorkerManager},
+};
+
+/// Client that fulfills `ComputeRequests` by polling the protocol server for the open requests of
+/// a system instead of holding a websocket subscription, e.g. when run periodically by a
+/// scheduler. Each run fulfills at most one request: the best ranked one passing analysis is bid
+/// upon, proven and resolved within the market contract.
+pub struct ProviderSearchingClient<T, P, N, S>
+where
+    T: Transport + Clone,
+    P: Provider<T, N> + Clone,
+    N: Network + Clone,
+{
+    base: BaseClient<T, P, N, S>,
+    searcher: ComputeRequestSearcher<T, P, N>,
+    analyzer: ComputeRequestAnalyzer<T, P, N>,
+    bidder: ComputeRequestBidder<T, P, N>,
+    worker_manager: WorkerManager<ComputeRequest<SystemParams>>,
+    resolver: ComputeRequestResolver<T, P, N>,
+    // skips bids the bidding account can't cover when set
+    balance_monitor: Option<BalanceMonitor<T, P, N>>,
+    // records proving durations and skips requests not provable in time when set
+    proving_stats: Option<Arc<ProvingStats>>,
+}
+
+impl<T, P, N, S> ProviderSearchingClient<T, P, N, S>
+where
+    T: Transport + Clone,
+    P: Provider<T, N> + Clone,
+    N: Network + Clone,
+    S: Signer + Clone,
+{
+    pub fn new(
+        server_url: Url,
+        rpc_provider: P,
+        signer: S,
+        market_address: Address,
+        system_id: SystemId,
+        validation_config: RequestValidationConfig,
+    ) -> Self {
+        let base = BaseClient::new(rpc_provider.clone(), signer.clone(), market_address);
+        Self {
+            searcher: ComputeRequestSearcher::new(
+                server_url,
+                rpc_provider.clone(),
+                market_address,
+                system_id,
+            )
+            .with_clock(base.clock().clone()),
+            analyzer: ComputeRequestAnalyzer::new(
+                rpc_provider.clone(),
+                market_address,
+                validation_config,
+            ),
+            bidder: ComputeRequestBidder::new(rpc_provider.clone(), market_address)
+                .with_clock(base.clock().clone()),
+            worker_manager: WorkerManager::new(HashMap::new()),
+            resolver: ComputeRequestResolver::new(rpc_provider, market_address),
+            balance_monitor: None,
+            proving_stats: None,
+            base,
+        }
+    }
+
+    /// Rank searched requests by their current reward minus the cost given by `cost_model`
+    pub fn with_cost_model(mut self, cost_model: Arc<dyn CostModel>) -> Self {
+        self.searcher = self.searcher.with_cost_model(cost_model);
+        self
+    }
+
+    /// Account for the protocol fee the market takes out of rewards, both when ranking and
+    /// analyzing requests
+    pub fn with_market_fee(mut self, market_fee: MarketFee) -> Self {
+        self.searcher = self.searcher.with_market_fee(market_fee);
+        self.analyzer = self.analyzer.with_market_fee(market_fee);
+        self
+    }
+
+    /// Reuse the results cached in `work_cache` for workloads already proved instead of proving
+    /// them again
+    pub fn with_work_cache(mut self, work_cache: Arc<WorkCache>) -> Self {
+        self.worker_manager = self.worker_manager.with_cache(work_cache);
+        self
+    }
+
+    /// Check the bidding account's balance covers the bid before sending it
+    pub fn with_balance_monitor(mut self, balance_monitor: BalanceMonitor<T, P, N>) -> Self {
+        self.balance_monitor = Some(balance_monitor);
+        self
+    }
+
+    /// Record how long each proof takes in `proving_stats`, and skip requests whose proving time
+    /// is shorter than the estimate once it's made from enough samples
+    pub fn with_proving_stats(mut self, proving_stats: Arc<ProvingStats>) -> Self {
+        self.proving_stats = Some(proving_stats);
+        self
+    }
+
+    /// Register the worker and validator of the searched system
+    pub fn with_system_configuration<
+        W: ComputeWorker<ComputeRequest<SystemParams>> + Send + Sync + 'static,
+    >(
+        mut self,
+        system_id: SystemId,
+        worker: W,
+        validator: ComputeRequestValidator,
+    ) -> Result<Self> {
+        self.worker_manager.set_worker(system_id, Arc::new(worker));
+        self.analyzer
+            .validator_registry
+            .register(system_id, validator);
+        Ok(self)
+    }
+
+    /// Search, analyze, bid, prove and resolve a single request, returning its id or `None` when
+    /// no open request is worth bidding on.
+    pub async fn run_once(&self) -> Result<Option<FixedBytes<32>>> {
+        let latest_ts = self.base.clock.now().await?;
+        let candidates = self.searcher.candidates(latest_ts).await?;
+        tracing::info!("{} candidate request(s) found", candidates.len());
+
+        for candidate in candidates {
+            let span = intent_span(
+                candidate.request_id,
+                candidate.request.system_id,
+                candidate.request.proof_request.market,
+            );
+            let selected =
+                async {
+                    if let Err(e) = self.analyzer.analyze(latest_ts, &candidate.request).await {
+                        tracing::info!("skipping request {}: {}", candidate.request_id, e);
+                        return Ok(false);
+                    }
+                    if let Err(e) = self
+                        .worker_manager
+                        .ensure_partial_commitment(&candidate.request)
+                    {
+                        tracing::info!("skipping request {}: {}", candidate.request_id, e);
+                        return Ok(false);
+                    }
+                    if let Some(Err(e)) = self.proving_stats.as_ref().map(|proving_stats| {
+                        proving_stats.ensure_provable_in_time(&candidate.request)
+                    }) {
+                        tracing::info!("skipping request {}: {}", candidate.request_id, e);
+                        return Ok(false);
+                    }
+                    tracing::info!(
+                        "request {} selected, current reward {} for a cost of {}",
+                        candidate.request_id,
+                        candidate.current_reward,
+                        candidate.cost
+                    );
+                    self.fulfill(latest_ts, candidate.request_id, &candidate.request)
+                        .await?;
+                    Ok::<_, ClientError>(true)
+                }
+                .instrument(span)
+                .await?;
+            if selected {
+                return Ok(Some(candidate.request_id));
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn fulfill(
+        &self,
+        latest_ts: u64,
+        request_id: FixedBytes<32>,
+        request: &ComputeRequest<SystemParams>,
+    ) -> Result<()> {
+        if let Some(balance_monitor) = &self.balance_monitor {
+            balance_monitor
+                .ensure_covers_bid(request_id, U256::from(request.proof_request.minimumStake))
+                .await?;
+        }
+
+        // bid right away, the request is already open to every other provider
+        let bid_params = ComputeRequestBidParams {
+            target_amount: request.proof_request.minRewardAmount,
+            mode: BidMode::Immediate,
+        };
+        self.bidder
+            .submit_bid(
+                latest_ts,
+                request_id,
+                bid_params,
+                request.proof_request.clone(),
+                request.signature,
+            )
+            .await
+            .inspect_err(|e| tracing::error!("bid txs failed: {}", e))?;
+        tracing::info!("bid transaction submitted successfully");
+
+        // the proving window opened with the bid's block, not once the receipt came back
+        let resolution_deadline =
+            MarketStateClient::new(self.base.rpc_provider.clone(), self.base.market_address)
+                .request_resolution_deadline(request_id)
+                .await?
+                .ok_or_else(|| {
+                    ClientError::TrackIntentError(format!(
+                        "request {request_id} has no winning bid"
+                    ))
+                })?;
+        let bid_timestamp =
+            resolution_deadline.saturating_sub(request.proof_request.provingTime.into());
+        let now = self.base.clock.now().await?;
+        let started_at = Instant::now();
+        let work_result = self
+            .worker_manager
+            .execute_after_bid(request, bid_timestamp, now)
+            .await;
+        if let Some(proving_stats) = &self.proving_stats {
+            let size_hint = workload_size(&request.system);
+            match &work_result {
+                Ok(_) => {
+                    proving_stats
+                        .record(request.system_id, size_hint, started_at.elapsed())
+                        .await
+                }
+                Err(ClientError::WorkerTimeout { .. }) => {
+                    proving_stats
+                        .record_timeout(request.system_id, size_hint, started_at.elapsed())
+                        .await
+                }
+                Err(_) => {}
+            }
+        }
+        let work_result: WorkResult = work_result?;
+        tracing::info!("worker executed");
+
+        // a submission failing the market's commitment checks would only waste the resolve gas
+        validate_request_submission(
+            &request.proof_request,
+            &work_result.opaque_submission,
+            work_result.partial_commitment,
+        )
+        .map_err(|e| ClientError::WorkerError(e.to_string()))?;
+
+        self.resolver
+            .resolve_intent(request_id, &work_result)
+            .await
+            .inspect_err(|e| tracing::error!("resolve txs failed: {}", e))?;
+        tracing::info!("resolve transaction submitted");
+
+        Ok(())
+    }
+}
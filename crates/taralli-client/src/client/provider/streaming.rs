@@ -1,34 +1,91 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    future::Future,
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use futures_util::StreamExt;
 use taralli_primitives::alloy::{
-    consensus::BlockHeader,
-    eips::{BlockId, BlockNumberOrTag::Latest},
-    network::{BlockResponse, BlockTransactionsKind, Network},
-    primitives::{Address, FixedBytes},
+    network::{Ethereum, Network, ReceiptResponse},
+    primitives::{Address, FixedBytes, PrimitiveSignature, U256},
     providers::Provider,
-    signers::Signer,
-    transports::Transport,
+    signers::{local::PrivateKeySigner, Signer},
+    transports::{Client, Http, Transport},
 };
 use taralli_primitives::{
+    abi::universal_bombetta::UniversalBombetta::ProofRequest,
+    encryption::{EncryptedSystemParams, KeyExchangeKeypair, KeyRequest},
+    extension::{ExtensionRequest, ExtensionResponse},
     intents::{request::ComputeRequest, ComputeIntent},
-    systems::{SystemId, SystemParams},
+    logging::intent_span,
+    markets::MarketFee,
+    systems::{System, SystemId, SystemParams, SYSTEMS},
     validation::{
         registry::ValidatorRegistry,
-        request::{ComputeRequestValidator, RequestValidationConfig},
+        request::{validate_request_submission, ComputeRequestValidator, RequestValidationConfig},
+        IntentValidator,
     },
 };
+use tracing::Instrument;
 
 use url::Url;
 
 use crate::error::{ClientError, Result};
 use crate::{
-    analyzer::{request::ComputeRequestAnalyzer, IntentAnalyzer},
-    bidder::{request::ComputeRequestBidParams, request::ComputeRequestBidder, IntentBidder},
+    analyzer::{
+        request::ComputeRequestAnalyzer, reward::RewardTokenPolicy, verifier::VerifierCheckConfig,
+        IntentAnalyzer,
+    },
+    artifacts::ArtifactRegistry,
+    bid_store::{replay_decision, BidClaim, BidStore, ReplayDecision},
+    bidder::{
+        request::{BidMode, ComputeRequestBidParams, ComputeRequestBidder},
+        IntentBidder,
+    },
+    clock::ChainClock,
+    confirmations::Confirmations,
+    env::{EnvConfig, WalletProvider, PROVIDER_PRIVATE_KEY_VAR},
+    hooks::{BidDecision, HookRegistry, ProviderHook},
+    market_state::MarketStateClient,
+    metrics::DeliveryMetrics,
+    pnl::{GasCost, PnlLedger},
+    proving_stats::{workload_size, ProvingStats},
     resolver::{request::ComputeRequestResolver, IntentResolver},
+    searcher::request::CostModel,
+    signer_filter::SignerFilter,
+    submitter::TransactionSubmitter,
+    work_cache::WorkCache,
     worker::{ComputeWorker, WorkResult, WorkerManager},
 };
-use crate::{api::subscribe::SubscribeApiClient, client::BaseClient};
+use crate::{
+    api::{
+        extension::ExtensionApiClient,
+        key::KeyExchangeApiClient,
+        status::StatusApiClient,
+        subscribe::{Delivery, StreamItem, SubscribeApiClient},
+    },
+    client::{
+        healthcheck::{HealthReport, HealthcheckConfig},
+        provider::{
+            balance::BalanceMonitor,
+            capabilities::CapabilityAdvertiser,
+            control::{InFlight, LogControl, RunControl, RunReport, RunState},
+            review::{BiddingMode, ReviewQueue},
+        },
+        BaseClient,
+    },
+};
+
+/// how often the server is polled for the key of a won encrypted request
+const KEY_POLL_INTERVAL: Duration = Duration::from_secs(2);
+/// default time the requester gets to deliver the key of a won encrypted request
+const DEFAULT_KEY_EXCHANGE_TIMEOUT: Duration = Duration::from_secs(120);
+/// how often the server is polled for the requester's answer to an extension request
+const EXTENSION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// times a reorged out bid or resolve transaction is sent again
+const MAX_REORG_RETRIES: u32 = 2;
 
 /// Client that fulfills `ComputeRequests` by subscribing to the protocol server over websocket
 /// stream to receive newly submitted `ComputeRequests` at the given system IDs they subscribed to.
@@ -46,6 +103,51 @@ where
     bidder: ComputeRequestBidder<T, P, N>,
     worker_manager: WorkerManager<ComputeRequest<SystemParams>>,
     resolver: ComputeRequestResolver<T, P, N>,
+    hooks: HookRegistry<ComputeRequest<SystemParams>>,
+    key_api: KeyExchangeApiClient,
+    extension_api: ExtensionApiClient,
+    // validators of the public part of encrypted requests, per system
+    encrypted_validators: HashMap<SystemId, ComputeRequestValidator>,
+    key_exchange_timeout: Duration,
+    // advertises the provider's capabilities to the server when set
+    advertiser: Option<CapabilityAdvertiser<S>>,
+    // skips bids the bidding account can't cover when set
+    balance_monitor: Option<BalanceMonitor<T, P, N>>,
+    delivery_metrics: Arc<DeliveryMetrics>,
+    // skips requests whose proving time is shorter than the historical estimate when set
+    proving_stats: Option<Arc<ProvingStats>>,
+    bidding_mode: BiddingMode,
+    // parks analyzed requests until approved, set in `BiddingMode::Manual`
+    review_queue: Option<ReviewQueue>,
+    // records the reward and gas of the requests won when set
+    pnl_ledger: Option<Arc<PnlLedger>>,
+    // drops the requests of filtered out signers before analysis when set
+    signer_filter: Option<Arc<SignerFilter>>,
+    // labels the artifacts of incoming requests, dropping untrusted ones in strict mode, when set
+    artifact_registry: Option<Arc<ArtifactRegistry>>,
+    // handed to the run control to change the log filter while running when set
+    log_control: Option<LogControl>,
+    // address the control endpoint of the run listens on when set
+    control_address: Option<SocketAddr>,
+    // records bid attempts, and the address they're sent from, so replayed requests aren't bid
+    // upon twice when set
+    bid_store: Option<(Arc<BidStore>, Address)>,
+}
+
+impl ProviderStreamingClient<Http<Client>, WalletProvider, Ethereum, PrivateKeySigner> {
+    /// Client configured from the environment variables documented in `crate::env`, the
+    /// provider's key being read from `PROVIDER_PRIVATE_KEY` or `PRIVATE_KEY`. Systems are
+    /// configured on the returned client as with `new`.
+    pub fn from_env() -> Result<Self> {
+        let config = EnvConfig::from_env(PROVIDER_PRIVATE_KEY_VAR)?;
+        Ok(Self::new(
+            config.server_url.clone(),
+            config.rpc_provider(),
+            config.signer.clone(),
+            config.market_address,
+            config.request_validation_config(),
+        ))
+    }
 }
 
 impl<T, P, N, S> ProviderStreamingClient<T, P, N, S>
@@ -62,18 +164,310 @@ where
         market_address: Address,
         validation_config: RequestValidationConfig,
     ) -> Self {
+        let base = BaseClient::new(rpc_provider.clone(), signer.clone(), market_address);
+        let bidder = ComputeRequestBidder::new(rpc_provider.clone(), market_address)
+            .with_clock(base.clock().clone());
         Self {
-            base: BaseClient::new(rpc_provider.clone(), signer.clone(), market_address),
+            base,
             api: SubscribeApiClient::new(server_url.clone(), 0u8),
+            key_api: KeyExchangeApiClient::new(server_url.clone()),
+            extension_api: ExtensionApiClient::new(server_url.clone()),
             analyzer: ComputeRequestAnalyzer::new(
                 rpc_provider.clone(),
                 market_address,
                 validation_config,
             ),
-            bidder: ComputeRequestBidder::new(rpc_provider.clone(), market_address),
+            bidder,
             worker_manager: WorkerManager::new(HashMap::new()),
             resolver: ComputeRequestResolver::new(rpc_provider, market_address),
+            hooks: HookRegistry::default(),
+            encrypted_validators: HashMap::new(),
+            key_exchange_timeout: DEFAULT_KEY_EXCHANGE_TIMEOUT,
+            advertiser: None,
+            balance_monitor: None,
+            delivery_metrics: Arc::new(DeliveryMetrics::default()),
+            proving_stats: None,
+            bidding_mode: BiddingMode::default(),
+            review_queue: None,
+            pnl_ledger: None,
+            signer_filter: None,
+            artifact_registry: None,
+            log_control: None,
+            control_address: None,
+            bid_store: None,
+        }
+    }
+
+    /// Check the rpc, the market, the server and the bidding account before running, see
+    /// `client::healthcheck`. Bids stake from the signer's account, so an unfunded one is a hard
+    /// failure. Call `into_result` on the report to abort on hard failures.
+    pub async fn healthcheck(&self) -> HealthReport {
+        let systems = SYSTEMS
+            .iter()
+            .filter(|system_id| system_id.as_bit() & self.api.subscribed_to != 0)
+            .copied()
+            .collect();
+        let config = HealthcheckConfig::new(
+            self.analyzer
+                .validator_registry
+                .default_config()
+                .base
+                .chain_id,
+            systems,
+        );
+        self.base
+            .healthcheck(
+                &StatusApiClient::new(self.api.server_url().clone()),
+                &config,
+            )
+            .await
+    }
+
+    /// Delivery delay of the requests received while running
+    pub fn delivery_metrics(&self) -> Arc<DeliveryMetrics> {
+        self.delivery_metrics.clone()
+    }
+
+    /// send bid and resolve transactions through the given submitter (e.g. a private rpc),
+    /// keeping other providers from seeing the bids in the public mempool
+    pub fn with_transaction_submitter(
+        mut self,
+        submitter: Arc<dyn TransactionSubmitter<N>>,
+    ) -> Self {
+        self.bidder = self.bidder.with_submitter(submitter.clone());
+        self.resolver = self.resolver.with_submitter(submitter);
+        self
+    }
+
+    /// wait for bid and resolve transactions to be confirmed at the given depth. Bids reorged
+    /// out meanwhile are placed again while the auction is open, reorged resolves are sent again.
+    pub fn with_confirmations(mut self, confirmations: Confirmations) -> Self {
+        self.bidder = self.bidder.with_confirmations(confirmations.clone());
+        self.resolver = self.resolver.with_confirmations(confirmations);
+        self
+    }
+
+    /// Schedule against `clock` instead of the latest block timestamp of the rpc provider, e.g.
+    /// a `ChainClock::manual` one in tests. The bidder shares it.
+    pub fn with_clock(mut self, clock: ChainClock<T, P, N>) -> Self {
+        self.bidder = self.bidder.with_clock(clock.clone());
+        self.base.clock = clock;
+        self
+    }
+
+    /// Only prove requests against the verifiers the workers format submissions for, by address
+    /// and selector, picking the first of a request's verifier alternatives among them
+    pub fn with_supported_verifiers(
+        mut self,
+        verifiers: impl IntoIterator<Item = (Address, FixedBytes<4>)>,
+    ) -> Self {
+        self.analyzer = self.analyzer.with_supported_verifiers(verifiers);
+        self
+    }
+
+    /// Configure the on-chain check of the verifier named by incoming requests, e.g. disabling it
+    /// for latency sensitive setups
+    pub fn with_verifier_check(mut self, config: VerifierCheckConfig) -> Self {
+        self.analyzer = self.analyzer.with_verifier_check(config);
+        self
+    }
+
+    /// Only bid on requests rewarding in the policy's tokens
+    pub fn with_reward_tokens(mut self, reward_tokens: RewardTokenPolicy) -> Self {
+        self.analyzer = self.analyzer.with_reward_tokens(reward_tokens);
+        self
+    }
+
+    /// Only bid on requests whose current reward covers their cost under `cost_model`
+    pub fn with_cost_model(mut self, cost_model: Arc<dyn CostModel>) -> Self {
+        self.analyzer = self.analyzer.with_cost_model(cost_model);
+        self
+    }
+
+    /// Account for the protocol fee the market takes out of rewards when checking their cost
+    pub fn with_market_fee(mut self, market_fee: MarketFee) -> Self {
+        self.analyzer = self.analyzer.with_market_fee(market_fee);
+        self
+    }
+
+    /// Let the `RunControl` of `run_with_control` change the log filter through `log_control`
+    pub fn with_log_control(mut self, log_control: LogControl) -> Self {
+        self.log_control = Some(log_control);
+        self
+    }
+
+    /// Serve the control endpoint of the run on `listen_address`, changing the log filter and
+    /// dumping the run's state from outside the process. The address is meant to stay local.
+    pub fn with_control_endpoint(mut self, listen_address: SocketAddr) -> Self {
+        self.control_address = Some(listen_address);
+        self
+    }
+
+    /// Reuse the results cached in `work_cache` for workloads already proved instead of proving
+    /// them again
+    pub fn with_work_cache(mut self, work_cache: Arc<WorkCache>) -> Self {
+        self.worker_manager = self.worker_manager.with_cache(work_cache);
+        self
+    }
+
+    /// Record how long each proof takes in `proving_stats`, and skip requests whose proving time
+    /// is shorter than the estimate once it's made from enough samples
+    pub fn with_proving_stats(mut self, proving_stats: Arc<ProvingStats>) -> Self {
+        self.proving_stats = Some(proving_stats);
+        self
+    }
+
+    /// In `BiddingMode::Manual`, park the requests passing analysis until approved through the
+    /// review endpoint, served while running, or the queue returned by `review_queue`
+    pub fn with_bidding_mode(mut self, bidding_mode: BiddingMode) -> Self {
+        self.review_queue = match &bidding_mode {
+            BiddingMode::Automatic => None,
+            BiddingMode::Manual(config) => Some(ReviewQueue::new(config.expiry_margin())),
+        };
+        self.bidding_mode = bidding_mode;
+        self
+    }
+
+    /// Queue of the requests awaiting review, set in `BiddingMode::Manual`
+    pub fn review_queue(&self) -> Option<ReviewQueue> {
+        self.review_queue.clone()
+    }
+
+    /// Record the reward, stake and gas of each request won to the given ledger, reporting the
+    /// provider's profit and loss
+    pub fn with_pnl_ledger(mut self, pnl_ledger: Arc<PnlLedger>) -> Self {
+        self.pnl_ledger = Some(pnl_ledger);
+        self
+    }
+
+    /// Drop the requests of the signers `signer_filter` filters out as soon as they're received,
+    /// and report to it the worker failures of each signer. The filter can be updated while
+    /// running through the shared handle.
+    pub fn with_signer_filter(mut self, signer_filter: Arc<SignerFilter>) -> Self {
+        self.signer_filter = Some(signer_filter);
+        self
+    }
+
+    /// Record bid attempts to `bid_store` and check the ones of requests received again, e.g.
+    /// replayed after a restart, against the market before bidding, see `crate::bid_store`.
+    /// `bidder_address` is the address bids are sent from, the signer's.
+    pub fn with_bid_store(mut self, bid_store: Arc<BidStore>, bidder_address: Address) -> Self {
+        self.bid_store = Some((bid_store, bidder_address));
+        self
+    }
+
+    /// Label the artifacts of the requests received with the ones of `artifact_registry`, and in
+    /// its strict mode drop the requests whose artifacts aren't trusted before analysis. The
+    /// registry can be updated while running through the shared handle.
+    pub fn with_artifact_registry(mut self, artifact_registry: Arc<ArtifactRegistry>) -> Self {
+        self.artifact_registry = Some(artifact_registry);
+        self
+    }
+
+    /// Register a hook called while processing requests, hooks run in registration order
+    pub fn with_hook(mut self, hook: Arc<dyn ProviderHook<ComputeRequest<SystemParams>>>) -> Self {
+        self.hooks.register(hook);
+        self
+    }
+
+    /// Register a hook given `timeout` per callback instead of `DEFAULT_HOOK_TIMEOUT`
+    pub fn with_hook_timeout(
+        mut self,
+        hook: Arc<dyn ProviderHook<ComputeRequest<SystemParams>>>,
+        timeout: Duration,
+    ) -> Self {
+        self.hooks.register_with_timeout(hook, timeout);
+        self
+    }
+
+    /// Let the bid proceed when a `pre_bid` hook times out, rather than vetoing it
+    pub fn with_hook_timeouts_ignored(mut self) -> Self {
+        self.hooks.set_veto_on_timeout(false);
+        self
+    }
+
+    /// Time the requester of a won encrypted request gets to deliver its key before the request
+    /// is abandoned
+    pub fn with_key_exchange_timeout(mut self, key_exchange_timeout: Duration) -> Self {
+        self.key_exchange_timeout = key_exchange_timeout;
+        self
+    }
+
+    /// Advertise the provider's capabilities to the server while running, counting the requests
+    /// being fulfilled as active jobs. The subscription is identified as the provider's, so the
+    /// server holds back requests while the provider has no free slot for them.
+    pub fn with_capability_advertiser(mut self, advertiser: CapabilityAdvertiser<S>) -> Self {
+        self.api = self.api.with_provider(advertiser.provider());
+        self.advertiser = Some(advertiser);
+        self
+    }
+
+    /// Check the bidding account's balance covers each bid before sending it, and poll it while
+    /// running to warn the monitor's listeners when it runs low
+    pub fn with_balance_monitor(mut self, balance_monitor: BalanceMonitor<T, P, N>) -> Self {
+        self.balance_monitor = Some(balance_monitor);
+        self
+    }
+
+    /// ask the server for deflated request frames, see `SubscribeApiClient::with_frame_compression`
+    pub fn with_frame_compression(mut self, enabled: bool) -> Self {
+        self.api = self.api.with_frame_compression(enabled);
+        self
+    }
+
+    /// subscribe over server-sent events when the websocket upgrade fails, see
+    /// `SubscribeApiClient::with_sse_fallback`
+    pub fn with_sse_fallback(mut self, enabled: bool) -> Self {
+        self.api = self.api.with_sse_fallback(enabled);
+        self
+    }
+
+    /// remember up to `capacity` intents to drop the ones broadcast again, see
+    /// `SubscribeApiClient::with_dedup_capacity`
+    pub fn with_dedup_capacity(mut self, capacity: usize) -> Self {
+        self.api = self.api.with_dedup_capacity(capacity);
+        self
+    }
+
+    /// Number of requests the server broadcast again and the client dropped
+    pub fn duplicates_dropped(&self) -> u64 {
+        self.api.duplicates_dropped()
+    }
+
+    /// Ask the requester of won request `request_id` to push its resolution deadline back to
+    /// `new_deadline`, waiting up to `timeout` for the answer. An approval carries the
+    /// requester's signature over the new deadline, which only a market supporting extensions
+    /// enforces: on the deployed markets the request still defaults at its original deadline.
+    pub async fn request_extension(
+        &self,
+        request_id: FixedBytes<32>,
+        new_deadline: u64,
+        reason: impl Into<String>,
+        timeout: Duration,
+    ) -> Result<ExtensionResponse> {
+        let extension_request =
+            ExtensionRequest::sign(request_id, new_deadline, reason.into(), &self.base.signer)
+                .await
+                .map_err(|e| ClientError::ExtensionError(e.to_string()))?;
+        self.extension_api
+            .request_extension(request_id, &extension_request)
+            .await?;
+        tracing::info!(
+            "extension of request {} to {} requested",
+            request_id,
+            new_deadline
+        );
+        let extension_response = self
+            .extension_api
+            .await_response(request_id, EXTENSION_POLL_INTERVAL, timeout)
+            .await?;
+        if extension_response.new_deadline != new_deadline {
+            return Err(ClientError::ExtensionError(format!(
+                "answer is for the deadline {}, requested {new_deadline}",
+                extension_response.new_deadline
+            )));
         }
+        Ok(extension_response)
     }
 
     /// Register a system configuration with the client for a specific system
@@ -93,11 +487,11 @@ where
         self.api.set_system_id_mask(updated_mask);
 
         // set compute worker for the system
-        self.worker_manager
-            .workers
-            .insert(system_id, Arc::new(worker));
+        self.worker_manager.set_worker(system_id, Arc::new(worker));
 
         // set analyzer/validator for the system
+        self.encrypted_validators
+            .insert(system_id, validator.clone());
         self.analyzer
             .validator_registry
             .register(system_id, validator);
@@ -105,98 +499,884 @@ where
     }
 
     pub async fn run(&self) -> Result<()> {
+        self.run_with_control().1.await.map(|_| ())
+    }
+
+    /// Run the client along with a handle to pause it, shut it down and follow the intents in
+    /// flight. The returned future has to keep being polled for the handle to take effect, it
+    /// resolves to the report of the run once shut down.
+    pub fn run_with_control(
+        &self,
+    ) -> (
+        RunControl,
+        impl Future<Output = Result<RunReport>> + Send + '_,
+    ) {
+        let control =
+            RunControl::for_workers(self.log_control.clone(), Some(self.worker_manager.clone()));
+        let handle = control.clone();
+        (handle, async move { self.run_controlled(&control).await })
+    }
+
+    async fn run_controlled(&self, control: &RunControl) -> Result<RunReport> {
+        // the report is published however the run ends, a failed subscription included, so a
+        // shutdown waiting on it returns
+        let finish = FinishOnDrop::new(control);
+
         // subscribe to all markets included within the client's system mask
         let mut stream = self
             .api
-            .subscribe_to_markets()
+            .subscribe()
             .await
             .map_err(|e| ClientError::ServerRequestError(e.to_string()))?;
         tracing::info!("subscribed to markets, waiting for incoming requests");
 
-        while let Some(result) = stream.next().await {
-            match result {
-                Ok(request) => {
-                    let request_id = request.compute_id();
-                    tracing::info!(
-                        "Incoming request - proving system id: {:?}, proof request: {:?}, request ID: {:?}",
-                        request.system_id,
-                        request.proof_request,
-                        request_id
-                    );
-                    if let Err(e) = self.process_request(request_id, request).await {
-                        tracing::error!("Failed to process proof request: {:?}", e);
+        // bound before running so a taken address fails the run right away
+        let review_listener = match &self.bidding_mode {
+            BiddingMode::Automatic => None,
+            BiddingMode::Manual(config) => Some(
+                tokio::net::TcpListener::bind(config.listen_address)
+                    .await
+                    .map_err(|e| ClientError::ReviewEndpointError(e.to_string()))?,
+            ),
+        };
+        let control_listener = match self.control_address {
+            Some(listen_address) => Some(
+                tokio::net::TcpListener::bind(listen_address)
+                    .await
+                    .map_err(|e| ClientError::ControlEndpointError(e.to_string()))?,
+            ),
+            None => None,
+        };
+        let serve_control = async {
+            match control_listener {
+                Some(listener) => {
+                    if let Err(e) = control.serve(listener).await {
+                        tracing::error!("control endpoint failed: {}", e);
+                    }
+                }
+                None => std::future::pending().await,
+            }
+        };
+        let serve_review = async {
+            match (&self.review_queue, review_listener) {
+                (Some(review_queue), Some(listener)) => {
+                    if let Err(e) = review_queue.serve(listener).await {
+                        tracing::error!("review endpoint failed: {}", e);
+                    }
+                }
+                _ => std::future::pending().await,
+            }
+        };
+
+        // keep the advertised capabilities alive alongside the stream
+        let advertise = async {
+            match &self.advertiser {
+                Some(advertiser) => advertiser.run().await,
+                None => std::future::pending().await,
+            }
+        };
+        let monitor_balance = async {
+            match &self.balance_monitor {
+                Some(balance_monitor) => balance_monitor.run().await,
+                None => std::future::pending().await,
+            }
+        };
+        let mut disabled_systems = control.watch_disabled_systems();
+        let process = async {
+            while control.accepting().await {
+                let result = tokio::select! {
+                    result = stream.next() => match result {
+                        Some(result) => result,
+                        None => break,
+                    },
+                    (request_id, request) = self.next_approved() => {
+                        let span = intent_span(request_id, request.system_id, request.proof_request.market);
+                        if let Err(e) = self
+                            .process_approved_request(request_id, request, control)
+                            .instrument(span.clone())
+                            .await
+                        {
+                            span.in_scope(|| {
+                                tracing::error!("Failed to process approved request {request_id}: {:?}", e);
+                            });
+                        }
+                        continue;
+                    }
+                    // paused or shutting down, the state is checked again before reading on
+                    () = control.interrupted() => continue,
+                    Ok(()) = disabled_systems.changed() => {
+                        let mask = self.api.subscribed_to & !*disabled_systems.borrow();
+                        tracing::info!("subscribing again to system mask {mask:#010b}");
+                        match self.api.subscribe_to(mask).await {
+                            Ok(narrowed) => stream = narrowed,
+                            Err(e) => tracing::error!("Failed to subscribe again: {:?}", e),
+                        }
+                        continue;
+                    }
+                };
+                match result {
+                    Ok(StreamItem::Lagged(skipped)) => {
+                        tracing::warn!(
+                            "subscription lagged behind, {} broadcast request(s) were missed",
+                            skipped
+                        );
+                        continue;
+                    }
+                    Ok(StreamItem::Expired { intent_id, .. }) => {
+                        tracing::info!("auction of request {intent_id} ended");
+                        continue;
+                    }
+                    // providers bid on requests, offers only reach clients subscribing to them
+                    Ok(StreamItem::Offer(offer, _)) => {
+                        tracing::debug!("ignoring offer {}", offer.compute_id());
+                        continue;
+                    }
+                    Ok(StreamItem::Request(request, delivery)) => {
+                        let request_id = request.compute_id();
+                        let span = intent_span(
+                            request_id,
+                            request.system_id,
+                            request.proof_request.market,
+                        );
+                        async {
+                            self.record_delivery(request_id, delivery);
+                            if !self.admits_signer(request_id, request.proof_request.signer)
+                                || !self.admits_artifact(request_id, &request.system)
+                            {
+                                return;
+                            }
+                            tracing::info!(
+                                "Incoming request - proving system id: {:?}, proof request: {:?}, request ID: {:?}",
+                                request.system_id,
+                                request.proof_request,
+                                request_id
+                            );
+                            match self.process_request(request_id, request, control).await {
+                                Ok(()) => {}
+                                Err(ClientError::IntentVetoed(reason)) => {
+                                    tracing::info!("request {request_id} dropped: {reason}");
+                                }
+                                Err(e @ ClientError::InsufficientFunds { .. }) => {
+                                    tracing::warn!("bid on request {request_id} skipped: {e}");
+                                }
+                                Err(e) if e.is_transient() => {
+                                    tracing::warn!("request {request_id} left after rpc retries ran out: {e}");
+                                }
+                                Err(e) => tracing::error!("Failed to process proof request: {:?}", e),
+                            }
+                        }
+                        .instrument(span)
+                        .await;
+                    }
+                    Ok(StreamItem::EncryptedRequest(request, delivery)) => {
+                        let request_id = request.compute_id();
+                        let span = intent_span(
+                            request_id,
+                            request.system_id,
+                            request.proof_request.market,
+                        );
+                        async {
+                            self.record_delivery(request_id, delivery);
+                            if !self.admits_signer(request_id, request.proof_request.signer) {
+                                return;
+                            }
+                            tracing::info!(
+                                "Incoming encrypted request - proving system id: {:?}, proof request: {:?}, request ID: {:?}",
+                                request.system_id,
+                                request.proof_request,
+                                request_id
+                            );
+                            if let Err(e) = self
+                                .process_encrypted_request(request_id, request, control)
+                                .await
+                            {
+                                tracing::error!("Failed to process encrypted proof request: {:?}", e);
+                            }
+                        }
+                        .instrument(span)
+                        .await;
                     }
+                    Err(e) => tracing::error!("Error receiving event: {:?}", e),
                 }
-                Err(e) => tracing::error!("Error receiving event: {:?}", e),
+                tracing::info!("request processed");
             }
-            tracing::info!("request processed");
+        };
+        tokio::select! {
+            _ = process => {}
+            _ = advertise => {}
+            _ = monitor_balance => {}
+            _ = serve_review => {}
+            _ = serve_control => {}
         }
 
-        Ok(())
+        // hits on cached results are only persisted along with the next result cached
+        if let Some(work_cache) = &self.worker_manager.cache {
+            work_cache.flush().await;
+        }
+        let report = finish.finish();
+        if !report.abandoned.is_empty() {
+            tracing::warn!(
+                "run ended with {} request(s) bid upon but not resolved",
+                report.abandoned.len()
+            );
+        }
+        Ok(report)
+    }
+
+    fn record_delivery(&self, request_id: FixedBytes<32>, delivery: Option<Delivery>) {
+        let Some(delivery) = delivery else {
+            return;
+        };
+        self.delivery_metrics.record(&delivery);
+        tracing::info!(
+            "request {} (sequence {}) received at {}, {:?} after the server received it",
+            request_id,
+            delivery.sequence,
+            delivery.received_at_ms,
+            delivery.delay()
+        );
+    }
+
+    /// Whether the signer filter, if any, lets the request of `signer` through
+    fn admits_signer(&self, request_id: FixedBytes<32>, signer: Address) -> bool {
+        let Some(signer_filter) = &self.signer_filter else {
+            return true;
+        };
+        match signer_filter.check(signer) {
+            Ok(()) => true,
+            Err(rejection) => {
+                tracing::info!("request {request_id} dropped: {rejection}");
+                false
+            }
+        }
+    }
+
+    /// Whether the artifact registry, if any, lets the request proving `system` through, its
+    /// artifact's label being recorded on the request's span
+    fn admits_artifact(&self, request_id: FixedBytes<32>, system: &SystemParams) -> bool {
+        let Some(artifact_registry) = &self.artifact_registry else {
+            return true;
+        };
+        match artifact_registry.check(system) {
+            Ok(artifact) => {
+                tracing::info!("request {request_id} proves {artifact}");
+                true
+            }
+            Err(e) => {
+                tracing::info!("request {request_id} dropped: {e}");
+                false
+            }
+        }
     }
 
     async fn process_request(
         &self,
         request_id: FixedBytes<32>,
         request: ComputeRequest<SystemParams>,
+        control: &RunControl,
     ) -> Result<()> {
-        // Fetch latest block timestamp
-        // TODO: remove this call from the request processing work flow, instead passing it in as input from another external process
-        let current_ts = self
-            .base
-            .rpc_provider
-            .get_block(BlockId::Number(Latest), BlockTransactionsKind::Hashes)
-            .await
-            .map_err(|e| ClientError::RpcRequestError(e.to_string()))?
-            .ok_or_else(|| ClientError::RpcRequestError("Block header not found".to_string()))?
-            .header()
-            .timestamp();
+        let _claim = self.claim(request_id)?;
+        let in_flight = control.track(request_id, request.system_id, request.proof_request.clone());
+        self.hooks.on_intent_received(request_id, &request);
 
+        // TODO: remove this call from the request processing work flow, instead passing it in as input from another external process
+        let current_ts = self.latest_timestamp().await?;
         tracing::info!("latest block timesetamp fetched: {}", current_ts);
 
+        match self
+            .replay_decision(request_id, &request.proof_request, current_ts)
+            .await?
+        {
+            ReplayDecision::Bid => {}
+            ReplayDecision::Resume => {
+                tracing::info!("request {request_id} already won, resuming from proving");
+                in_flight.proving();
+                return self.fulfill(request_id, request, in_flight, control).await;
+            }
+            ReplayDecision::Drop(reason) => return Err(ClientError::IntentVetoed(reason)),
+        }
+
         // analyze the validity and profitability of the request
         self.analyzer
             .analyze(current_ts, &request)
             .await
             .map_err(|e| ClientError::IntentAnalysisError(e.to_string()))?;
+        self.ensure_provable_in_time(&request)?;
+        // checked against the verifier alternative the request would be proven against
+        self.worker_manager
+            .ensure_partial_commitment(&self.analyzer.with_selected_verifier(&request).await?)?;
         tracing::info!("analysis done");
 
-        // for now hard code minimum value since analysis is incomplete
-        let bid_params = ComputeRequestBidParams {
-            target_amount: request.proof_request.minRewardAmount,
-        };
+        if let BidDecision::Veto(reason) = self.hooks.pre_bid(request_id, &request).await {
+            return Err(ClientError::IntentVetoed(format!(
+                "vetoed by hook: {reason}"
+            )));
+        }
+
+        if let Some(review_queue) = &self.review_queue {
+            if !review_queue.park(request_id, request, current_ts) {
+                return Err(ClientError::IntentVetoed(
+                    "auction ends before the request could be reviewed".to_string(),
+                ));
+            }
+            tracing::info!("request {request_id} parked for review");
+            return Ok(());
+        }
+
+        self.bid_and_fulfill(current_ts, request_id, request, in_flight, control)
+            .await
+    }
+
+    /// Bid on a request approved through the review queue, analyzing it again as the auction
+    /// moved on while it was reviewed
+    async fn process_approved_request(
+        &self,
+        request_id: FixedBytes<32>,
+        request: ComputeRequest<SystemParams>,
+        control: &RunControl,
+    ) -> Result<()> {
+        let _claim = self.claim(request_id)?;
+        let in_flight = control.track(request_id, request.system_id, request.proof_request.clone());
+        let current_ts = self.latest_timestamp().await?;
+        self.analyzer
+            .analyze(current_ts, &request)
+            .await
+            .map_err(|e| ClientError::IntentAnalysisError(e.to_string()))?;
+
+        self.bid_and_fulfill(current_ts, request_id, request, in_flight, control)
+            .await
+    }
+
+    async fn bid_and_fulfill(
+        &self,
+        current_ts: u64,
+        request_id: FixedBytes<32>,
+        request: ComputeRequest<SystemParams>,
+        in_flight: InFlight,
+        control: &RunControl,
+    ) -> Result<()> {
+        self.ensure_covers_bid(request_id, &request.proof_request)
+            .await?;
+        ensure_running(control)?;
+        self.ensure_system_enabled(request.system_id)?;
 
         // Submit a bid for the request
-        self.bidder
-            .submit_bid(
+        let bid_receipt = self
+            .bid(
                 current_ts,
                 request_id,
-                bid_params,
-                request.proof_request.clone(),
+                &request.proof_request,
                 request.signature,
             )
-            .await
-            .map_err(|e| ClientError::TransactionFailure(format!("bid txs failed: {e}")))?;
+            .await?;
 
         tracing::info!("bid transaction submitted successfully");
+        self.record_bid(request_id, request.system_id, &bid_receipt)
+            .await;
+        in_flight.proving();
+
+        self.fulfill(request_id, request, in_flight, control).await
+    }
 
-        // Execute worker
-        let work_result: WorkResult = self
-            .worker_manager
-            .execute(&request)
+    /// Wait for the next request approved through the review queue, never resolving outside of
+    /// `BiddingMode::Manual`
+    async fn next_approved(&self) -> (FixedBytes<32>, ComputeRequest<SystemParams>) {
+        match &self.review_queue {
+            Some(review_queue) => review_queue.next_approved().await,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Bid on a request whose system params are encrypted, then wait for its requester to deliver
+    /// the key before running the worker. The key request is signed by the client's signer, which
+    /// has to be the address bidding. Hooks only see the request once it is decrypted, so they
+    /// cannot veto the bid.
+    async fn process_encrypted_request(
+        &self,
+        request_id: FixedBytes<32>,
+        request: ComputeRequest<EncryptedSystemParams>,
+        control: &RunControl,
+    ) -> Result<()> {
+        let _claim = self.claim(request_id)?;
+        let in_flight = control.track(request_id, request.system_id, request.proof_request.clone());
+        let current_ts = self.latest_timestamp().await?;
+        let resume = match self
+            .replay_decision(request_id, &request.proof_request, current_ts)
+            .await?
+        {
+            ReplayDecision::Bid => false,
+            ReplayDecision::Resume => true,
+            ReplayDecision::Drop(reason) => return Err(ClientError::IntentVetoed(reason)),
+        };
+        if resume {
+            tracing::info!("request {request_id} already won, requesting key");
+        } else {
+            self.bid_encrypted(current_ts, request_id, &request, control)
+                .await?;
+        }
+        in_flight.proving();
+
+        let request = match self.decrypt_won_request(request_id, request, control).await {
+            Ok(request) => request,
+            Err(e) => {
+                self.record_default(request_id, &e);
+                in_flight.abandon(e.to_string());
+                return Err(e);
+            }
+        };
+        self.hooks.on_intent_received(request_id, &request);
+
+        self.fulfill(request_id, request, in_flight, control).await
+    }
+
+    /// Analyze the public part of an encrypted request and bid on it
+    async fn bid_encrypted(
+        &self,
+        current_ts: u64,
+        request_id: FixedBytes<32>,
+        request: &ComputeRequest<EncryptedSystemParams>,
+        control: &RunControl,
+    ) -> Result<()> {
+        // only the public part of the request can be analyzed before winning it
+        let validator = self
+            .encrypted_validators
+            .get(&request.system_id)
+            .ok_or_else(|| {
+                ClientError::IntentAnalysisError(format!(
+                    "No validator for system ID: {:?}",
+                    request.system_id
+                ))
+            })?;
+        validator
+            .validate(request, current_ts, &self.analyzer.market_address)
+            .map_err(|e| ClientError::IntentAnalysisError(e.to_string()))?;
+        self.analyzer
+            .check_reward_token(current_ts, &request.proof_request)
+            .map_err(|e| ClientError::IntentAnalysisError(e.to_string()))?;
+        self.analyzer
+            .select_verifier(&request.proof_request)
+            .await?;
+        tracing::info!("analysis of encrypted request done");
+
+        self.ensure_covers_bid(request_id, &request.proof_request)
+            .await?;
+        ensure_running(control)?;
+        self.ensure_system_enabled(request.system_id)?;
+        let bid_receipt = self
+            .bid(
+                current_ts,
+                request_id,
+                &request.proof_request,
+                request.signature,
+            )
+            .await?;
+        tracing::info!("bid transaction submitted successfully, requesting key");
+        self.record_bid(request_id, request.system_id, &bid_receipt)
+            .await;
+        Ok(())
+    }
+
+    /// Ask the requester of a won encrypted request for its key and decrypt the request with it,
+    /// giving up once the grace period of a requested shutdown expires
+    async fn decrypt_won_request(
+        &self,
+        request_id: FixedBytes<32>,
+        request: ComputeRequest<EncryptedSystemParams>,
+        control: &RunControl,
+    ) -> Result<ComputeRequest<SystemParams>> {
+        // ask the requester for the key, wrapped for a keypair only used for this request
+        let keypair = KeyExchangeKeypair::random();
+        let key_request = KeyRequest::sign(request_id, keypair.public_key(), &self.base.signer)
             .await
-            .map_err(|e| ClientError::WorkerError(e.to_string()))?;
+            .map_err(|e| ClientError::KeyExchangeError(e.to_string()))?;
+        self.key_api.request_key(request_id, &key_request).await?;
+        let key_delivery = control
+            .within_grace(self.key_api.await_key(
+                request_id,
+                KEY_POLL_INTERVAL,
+                self.key_exchange_timeout,
+            ))
+            .await
+            .ok_or_else(|| {
+                ClientError::KeyExchangeError(
+                    "shutdown grace period expired awaiting the key".to_string(),
+                )
+            })??;
 
-        tracing::info!("worker executed");
+        let content_key = keypair
+            .unwrap_key(
+                request_id,
+                &request.system.encapsulation_key,
+                &key_delivery.wrapped_key,
+            )
+            .map_err(|e| ClientError::KeyExchangeError(e.to_string()))?;
+        let system = request
+            .system
+            .decrypt(&content_key, request_id)
+            .map_err(|e| ClientError::KeyExchangeError(e.to_string()))?;
+        if system.system_id() != request.system_id {
+            return Err(ClientError::KeyExchangeError(
+                "decrypted system does not match system id".to_string(),
+            ));
+        }
+        tracing::info!("encrypted request decrypted");
+
+        Ok(ComputeRequest {
+            system_id: request.system_id,
+            system,
+            proof_request: request.proof_request,
+            signature: request.signature,
+        })
+    }
+
+    /// Run the worker on a won request and resolve it, the request is abandoned when either fails
+    async fn fulfill(
+        &self,
+        request_id: FixedBytes<32>,
+        request: ComputeRequest<SystemParams>,
+        in_flight: InFlight,
+        control: &RunControl,
+    ) -> Result<()> {
+        match self
+            .prove_and_resolve(request_id, request, &in_flight, control)
+            .await
+        {
+            Ok(resolve_receipt) => {
+                if let Some((bid_store, _)) = &self.bid_store {
+                    bid_store.record_resolved(request_id).await;
+                }
+                if let Some(pnl_ledger) = &self.pnl_ledger {
+                    pnl_ledger.record_resolve(request_id, GasCost::from_receipt(&resolve_receipt));
+                }
+                in_flight.complete();
+                Ok(())
+            }
+            Err(e) => {
+                self.record_default(request_id, &e);
+                in_flight.abandon(e.to_string());
+                Err(e)
+            }
+        }
+    }
 
-        // Resolve request
-        self.resolver
-            .resolve_intent(request_id, work_result.opaque_submission)
+    /// Run the worker within the grace period of a requested shutdown, then resolve the request,
+    /// returning the receipt of the resolve
+    async fn prove_and_resolve(
+        &self,
+        request_id: FixedBytes<32>,
+        request: ComputeRequest<SystemParams>,
+        in_flight: &InFlight,
+        control: &RunControl,
+    ) -> Result<N::ReceiptResponse> {
+        let _job = self
+            .advertiser
+            .as_ref()
+            .map(CapabilityAdvertiser::track_job);
+
+        // Execute worker, against the verifier alternative selected when the request carries some
+        let work_request = self.analyzer.with_selected_verifier(&request).await?;
+        let (bid_timestamp, now) = self
+            .bid_timestamp(request_id, &request.proof_request)
+            .await?;
+        let started_at = Instant::now();
+        let work_result = control
+            .within_grace(
+                self.worker_manager
+                    .execute_after_bid(&work_request, bid_timestamp, now),
+            )
             .await
-            .map_err(|e| ClientError::TransactionFailure(format!("resolve txs failed: {e}")))?;
+            .ok_or_else(|| {
+                ClientError::ProofTimeout("shutdown grace period expired while proving".to_string())
+            })?;
+        if let (Err(ClientError::WorkerTimeout { .. }), Some(proving_stats)) =
+            (&work_result, &self.proving_stats)
+        {
+            proving_stats
+                .record_timeout(
+                    request.system_id,
+                    workload_size(&request.system),
+                    started_at.elapsed(),
+                )
+                .await;
+        }
+        let work_result: WorkResult =
+            self.record_worker_outcome(request.proof_request.signer, work_result)?;
+        if let Some(proving_stats) = &self.proving_stats {
+            proving_stats
+                .record(
+                    request.system_id,
+                    workload_size(&request.system),
+                    started_at.elapsed(),
+                )
+                .await;
+        }
+
+        tracing::info!("worker executed");
+        self.hooks.post_proof(request_id, &request, &work_result);
+
+        // a submission failing the market's commitment checks would only waste the resolve gas
+        validate_request_submission(
+            &work_request.proof_request,
+            &work_result.opaque_submission,
+            work_result.partial_commitment,
+        )
+        .map_err(|e| ClientError::WorkerError(e.to_string()))?;
+        in_flight.awaiting_resolve(&work_result);
+
+        // Resolve request, sending the resolve again if it's reorged out
+        let mut reorgs = 0;
+        let receipt = loop {
+            match self.resolver.resolve_intent(request_id, &work_result).await {
+                Err(ClientError::TransactionReorged { tx_hash }) if reorgs < MAX_REORG_RETRIES => {
+                    reorgs += 1;
+                    tracing::warn!(
+                        "resolve transaction {} reorged out, resolving again",
+                        tx_hash
+                    );
+                }
+                result => {
+                    break result.inspect_err(|e| tracing::error!("resolve txs failed: {}", e))?
+                }
+            }
+        };
 
         tracing::info!("resolve transaction submitted");
+        self.hooks
+            .post_resolve(request_id, &request, receipt.transaction_hash());
+
+        Ok(receipt)
+    }
+
+    /// Record a won request to the pnl ledger, along with the reward and stake its bid got as
+    /// emitted by the market. Failing to read them only costs the entry, not the request.
+    async fn record_bid(
+        &self,
+        request_id: FixedBytes<32>,
+        system_id: SystemId,
+        bid_receipt: &N::ReceiptResponse,
+    ) {
+        let Some(pnl_ledger) = &self.pnl_ledger else {
+            return;
+        };
+        match self.bidder.bid_event(request_id, bid_receipt).await {
+            Ok(bid_event) => {
+                pnl_ledger.record_bid(system_id, &bid_event, GasCost::from_receipt(bid_receipt))
+            }
+            Err(e) => tracing::warn!("failed to record bid on request {request_id}: {e}"),
+        }
+    }
+
+    /// Timestamp of the bid that won a request, as recorded by the market through its resolution
+    /// deadline, along with the current chain time. Resumed requests were bid upon long before
+    /// proving starts, so the proving window isn't counted from now.
+    async fn bid_timestamp(
+        &self,
+        request_id: FixedBytes<32>,
+        proof_request: &ProofRequest,
+    ) -> Result<(u64, u64)> {
+        let now = self.latest_timestamp().await?;
+        let resolution_deadline =
+            MarketStateClient::new(self.base.rpc_provider.clone(), self.base.market_address)
+                .request_resolution_deadline(request_id)
+                .await?
+                .ok_or_else(|| {
+                    ClientError::TrackIntentError(format!(
+                        "request {request_id} has no winning bid"
+                    ))
+                })?;
+        let bid_timestamp = resolution_deadline.saturating_sub(proof_request.provingTime.into());
+        Ok((bid_timestamp, now))
+    }
+
+    /// Report the worker's outcome on a request of `signer` to the signer filter, if any. Only
+    /// `ClientError::WorkerError`s count as failures, as the other errors don't come from the
+    /// request's inputs.
+    fn record_worker_outcome(
+        &self,
+        signer: Address,
+        work_result: Result<WorkResult>,
+    ) -> Result<WorkResult> {
+        if let Some(signer_filter) = &self.signer_filter {
+            match &work_result {
+                Ok(_) => signer_filter.record_success(signer),
+                Err(ClientError::WorkerError(_)) => {
+                    signer_filter.record_failure(signer);
+                }
+                Err(_) => {}
+            }
+        }
+        work_result
+    }
+
+    /// Record a won request given up on to the pnl ledger
+    fn record_default(&self, request_id: FixedBytes<32>, error: &ClientError) {
+        if let Some(pnl_ledger) = &self.pnl_ledger {
+            pnl_ledger.record_default(request_id, error.to_string());
+        }
+    }
+
+    /// Bid on a request, bidding again while the auction is open if the bid is reorged out,
+    /// returning the receipt of the bid
+    async fn bid(
+        &self,
+        mut current_ts: u64,
+        request_id: FixedBytes<32>,
+        proof_request: &ProofRequest,
+        signature: PrimitiveSignature,
+    ) -> Result<N::ReceiptResponse> {
+        if let Some((bid_store, _)) = &self.bid_store {
+            bid_store.record_attempt(request_id).await;
+        }
+        let mut reorgs = 0;
+        loop {
+            // for now hard code minimum value since analysis is incomplete
+            let bid_params = ComputeRequestBidParams {
+                target_amount: proof_request.minRewardAmount,
+                mode: BidMode::Immediate,
+            };
+            match self
+                .bidder
+                .submit_bid(
+                    current_ts,
+                    request_id,
+                    bid_params,
+                    proof_request.clone(),
+                    signature,
+                )
+                .await
+            {
+                Err(ClientError::TransactionReorged { tx_hash }) if reorgs < MAX_REORG_RETRIES => {
+                    reorgs += 1;
+                    tracing::warn!("bid transaction {} reorged out, bidding again", tx_hash);
+                    current_ts = self.latest_timestamp().await?;
+                }
+                result => {
+                    let receipt =
+                        result.inspect_err(|e| tracing::error!("bid txs failed: {}", e))?;
+                    if let Some((bid_store, _)) = &self.bid_store {
+                        bid_store.record_won(request_id).await;
+                    }
+                    return Ok(receipt);
+                }
+            }
+        }
+    }
 
+    /// What to do with a request whose bid was already attempted, e.g. before a restart, as told
+    /// by the market. Requests never attempted, or received without a bid store, are bid upon.
+    async fn replay_decision(
+        &self,
+        request_id: FixedBytes<32>,
+        proof_request: &ProofRequest,
+        current_ts: u64,
+    ) -> Result<ReplayDecision> {
+        let Some((bid_store, bidder_address)) = &self.bid_store else {
+            return Ok(ReplayDecision::Bid);
+        };
+        let Some(attempt) = bid_store.attempt(request_id) else {
+            return Ok(ReplayDecision::Bid);
+        };
+        let state =
+            MarketStateClient::new(self.base.rpc_provider.clone(), self.base.market_address)
+                .request_state(request_id, proof_request.endAuctionTimestamp, current_ts)
+                .await?;
+        let decision = replay_decision(Some(&attempt), state, *bidder_address);
+        tracing::info!(
+            "request {request_id} received again after a {:?} bid attempt: {:?}",
+            attempt.stage,
+            decision
+        );
+        Ok(decision)
+    }
+
+    /// Claim the processing of a request on the bid store, if any, failing with
+    /// `ClientError::IntentVetoed` while a copy of it is processed
+    fn claim(&self, request_id: FixedBytes<32>) -> Result<Option<BidClaim<'_>>> {
+        let Some((bid_store, _)) = &self.bid_store else {
+            return Ok(None);
+        };
+        bid_store.claim(request_id).map(Some).ok_or_else(|| {
+            ClientError::IntentVetoed(format!("request {request_id} is already being processed"))
+        })
+    }
+
+    /// Drop requests of a system disabled since they were received, whose worker was removed
+    fn ensure_system_enabled(&self, system_id: SystemId) -> Result<()> {
+        if self.worker_manager.worker(system_id).is_none() {
+            return Err(ClientError::IntentVetoed(format!(
+                "no worker set for system {system_id:?}"
+            )));
+        }
         Ok(())
     }
+
+    /// Fail with `ClientError::IntentAnalysisError` when the proving stats expect the request to
+    /// take longer to prove than its proving time
+    fn ensure_provable_in_time(&self, request: &ComputeRequest<SystemParams>) -> Result<()> {
+        match &self.proving_stats {
+            Some(proving_stats) => proving_stats.ensure_provable_in_time(request),
+            None => Ok(()),
+        }
+    }
+
+    /// Fail with `ClientError::InsufficientFunds` when the balance monitor finds the bidding
+    /// account can't cover the bid
+    async fn ensure_covers_bid(
+        &self,
+        request_id: FixedBytes<32>,
+        proof_request: &ProofRequest,
+    ) -> Result<()> {
+        match &self.balance_monitor {
+            Some(balance_monitor) => {
+                balance_monitor
+                    .ensure_covers_bid(request_id, U256::from(proof_request.minimumStake))
+                    .await
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Latest block timestamp as told by the clock shared with the bidder, only fetched once
+    /// the cached one gets stale
+    async fn latest_timestamp(&self) -> Result<u64> {
+        self.base.clock.now().await
+    }
+}
+
+/// Fail with `ClientError::IntentVetoed` once shutdown is requested, so no new bid is sent
+fn ensure_running(control: &RunControl) -> Result<()> {
+    if control.state() == RunState::ShuttingDown {
+        return Err(ClientError::IntentVetoed(
+            "provider shutting down".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Publishes the report of a run when dropped, unless it was already finished
+struct FinishOnDrop<'a> {
+    control: &'a RunControl,
+    finished: bool,
+}
+
+impl<'a> FinishOnDrop<'a> {
+    fn new(control: &'a RunControl) -> Self {
+        Self {
+            control,
+            finished: false,
+        }
+    }
+
+    fn finish(mut self) -> RunReport {
+        self.finished = true;
+        self.control.finish()
+    }
+}
+
+impl Drop for FinishOnDrop<'_> {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.control.finish();
+        }
+    }
 }
@@ -0,0 +1,291 @@
+//! Funding of the reward a requester pays through permit2. Markets pull a request's reward with a
+//! signature transfer when a provider bids: permit2 spends the requester's ERC20 allowance to it,
+//! out of the requester's balance, consuming the request's permit2 nonce, and only until the
+//! permit's deadline, the end of the request's auction. A request failing any of those can't be
+//! bid on, so requesters check them before submitting, and can have the ERC20 approval of permit2
+//! sent for them.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use taralli_primitives::abi::erc20::IERC20::IERC20Instance;
+use taralli_primitives::abi::permit2::Permit2::Permit2Instance;
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta::ProofRequest;
+use taralli_primitives::alloy::{
+    network::{Network, ReceiptResponse},
+    primitives::{Address, U256},
+    providers::Provider,
+    transports::Transport,
+};
+use taralli_primitives::utils::PERMIT2_ADDRESS;
+
+use crate::api::retry::RetryPolicy;
+use crate::clock::ChainClock;
+use crate::error::{ClientError, Result};
+use crate::rpc::retry_rpc;
+use crate::submitter::{submit_transaction, TransactionSubmitter};
+
+const U256_ONE: U256 = U256::from_limbs([1, 0, 0, 0]);
+const U256_256: U256 = U256::from_limbs([256, 0, 0, 0]);
+
+/// What the requester does about a reward its market can't pull through permit2
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AllowanceCheck {
+    /// log a warning and submit the request anyway
+    Warn,
+    /// fail with `ClientError::Permit2AllowanceInsufficient` before submitting
+    Fail,
+    /// send the ERC20 approval of permit2 the request needs before submitting, see
+    /// `Permit2AllowanceChecker::ensure_permit2_approval`
+    Approve,
+}
+
+/// State of the requester's reward token and permit2 nonce a request's bid depends on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RewardFunding {
+    /// ERC20 allowance the requester granted permit2
+    pub permit2_allowance: U256,
+    /// reward token balance of the requester
+    pub balance: U256,
+    /// whether the request's permit2 nonce was already spent
+    pub nonce_used: bool,
+    /// chain time the funding was read at
+    pub now: u64,
+}
+
+/// Way the funding of a request's reward falls short of it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AllowanceShortfall {
+    /// the ERC20 allowance of permit2 is below the request's maximum reward
+    Insufficient { amount: U256, required: U256 },
+    /// the requester's balance is below the request's maximum reward
+    Balance { balance: U256, required: U256 },
+    /// the request's permit2 nonce was already spent
+    NonceUsed { nonce: U256 },
+    /// the permit's deadline, the end of the request's auction, has passed
+    Expired { deadline: u64, now: u64 },
+}
+
+impl AllowanceShortfall {
+    /// Whether approving permit2 makes up for the shortfall
+    pub fn is_approvable(&self) -> bool {
+        matches!(self, Self::Insufficient { .. })
+    }
+}
+
+impl fmt::Display for AllowanceShortfall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Insufficient { amount, required } => write!(
+                f,
+                "permit2 allowance of {amount} is below the maximum reward of {required}"
+            ),
+            Self::Balance { balance, required } => write!(
+                f,
+                "balance of {balance} is below the maximum reward of {required}"
+            ),
+            Self::NonceUsed { nonce } => write!(f, "permit2 nonce {nonce} was already used"),
+            Self::Expired { deadline, now } => write!(
+                f,
+                "permit deadline {deadline}, the end of the auction, passed at {now}"
+            ),
+        }
+    }
+}
+
+/// Ways `funding` falls short of `request`, none when its market can pull the reward
+#[must_use]
+pub fn funding_shortfalls(
+    funding: &RewardFunding,
+    request: &ProofRequest,
+) -> Vec<AllowanceShortfall> {
+    let mut shortfalls = Vec::new();
+    if funding.permit2_allowance < request.maxRewardAmount {
+        shortfalls.push(AllowanceShortfall::Insufficient {
+            amount: funding.permit2_allowance,
+            required: request.maxRewardAmount,
+        });
+    }
+    if funding.balance < request.maxRewardAmount {
+        shortfalls.push(AllowanceShortfall::Balance {
+            balance: funding.balance,
+            required: request.maxRewardAmount,
+        });
+    }
+    if funding.nonce_used {
+        shortfalls.push(AllowanceShortfall::NonceUsed {
+            nonce: request.nonce,
+        });
+    }
+    if funding.now > request.endAuctionTimestamp {
+        shortfalls.push(AllowanceShortfall::Expired {
+            deadline: request.endAuctionTimestamp,
+            now: funding.now,
+        });
+    }
+    shortfalls
+}
+
+/// Reads the reward funding of a requester's requests and approves permit2
+#[derive(Clone)]
+pub struct Permit2AllowanceChecker<T, P, N> {
+    rpc_provider: P,
+    owner: Address,
+    clock: ChainClock<T, P, N>,
+    rpc_retry: RetryPolicy,
+    phantom_data: PhantomData<(T, N)>,
+}
+
+impl<T, P, N> Permit2AllowanceChecker<T, P, N>
+where
+    T: Transport + Clone,
+    P: Provider<T, N> + Clone,
+    N: Network + Clone,
+{
+    /// Checker of the rewards funded by `owner`, the requests' signer
+    pub fn new(rpc_provider: P, owner: Address) -> Self {
+        Self {
+            clock: ChainClock::new(rpc_provider.clone()),
+            rpc_provider,
+            owner,
+            rpc_retry: RetryPolicy::default(),
+            phantom_data: PhantomData,
+        }
+    }
+
+    /// compare the permit deadlines against `clock` rather than one of its own
+    pub fn with_clock(mut self, clock: ChainClock<T, P, N>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// attempt the funding reads again as `rpc_retry` allows when they fail transiently
+    pub fn with_rpc_retry(mut self, rpc_retry: RetryPolicy) -> Self {
+        self.rpc_retry = rpc_retry;
+        self
+    }
+
+    /// ERC20 allowance of `token` the owner granted permit2
+    pub async fn permit2_allowance(&self, token: Address) -> Result<U256> {
+        let erc20 = &IERC20Instance::new(token, self.rpc_provider.clone());
+        let allowance = retry_rpc(&self.rpc_retry, || async move {
+            erc20.allowance(self.owner, PERMIT2_ADDRESS).call().await
+        })
+        .await?;
+        Ok(allowance._0)
+    }
+
+    /// Balance of `token` of the owner
+    pub async fn balance(&self, token: Address) -> Result<U256> {
+        let erc20 = &IERC20Instance::new(token, self.rpc_provider.clone());
+        let balance = retry_rpc(&self.rpc_retry, || async move {
+            erc20.balanceOf(self.owner).call().await
+        })
+        .await?;
+        Ok(balance._0)
+    }
+
+    /// Whether the owner already spent the permit2 `nonce`
+    pub async fn is_nonce_used(&self, nonce: U256) -> Result<bool> {
+        let permit2 = &Permit2Instance::new(PERMIT2_ADDRESS, self.rpc_provider.clone());
+        let bitmap = retry_rpc(&self.rpc_retry, || async move {
+            permit2
+                .nonceBitmap(self.owner, nonce / U256_256)
+                .call()
+                .await
+        })
+        .await?
+        ._0;
+        let bit = (nonce % U256_256).to::<usize>();
+        Ok(bitmap & (U256_ONE << bit) != U256::ZERO)
+    }
+
+    /// Funding of the request's reward as of the current chain time
+    pub async fn funding(&self, request: &ProofRequest) -> Result<RewardFunding> {
+        Ok(RewardFunding {
+            permit2_allowance: self.permit2_allowance(request.rewardToken).await?,
+            balance: self.balance(request.rewardToken).await?,
+            nonce_used: self.is_nonce_used(request.nonce).await?,
+            now: self.clock.now().await?,
+        })
+    }
+
+    /// Ways the funding of the request's reward falls short of it
+    pub async fn check(&self, request: &ProofRequest) -> Result<Vec<AllowanceShortfall>> {
+        let funding = self.funding(request).await?;
+        Ok(funding_shortfalls(&funding, request))
+    }
+
+    /// Like `check`, failing with `ClientError::Permit2AllowanceInsufficient` on any shortfall
+    pub async fn ensure_allowance(&self, request: &ProofRequest) -> Result<()> {
+        let shortfalls = self.check(request).await?;
+        if shortfalls.is_empty() {
+            return Ok(());
+        }
+        Err(insufficient(request, &shortfalls))
+    }
+
+    /// ERC20 `approve` transaction granting permit2 the request's maximum reward of its reward
+    /// token on top of `permit2_allowance`, the allowance left to the requests already submitted
+    pub fn approval_transaction(
+        &self,
+        request: &ProofRequest,
+        permit2_allowance: U256,
+    ) -> N::TransactionRequest {
+        IERC20Instance::new(request.rewardToken, self.rpc_provider.clone())
+            .approve(
+                PERMIT2_ADDRESS,
+                permit2_allowance.saturating_add(request.maxRewardAmount),
+            )
+            .from(self.owner)
+            .into_transaction_request()
+    }
+
+    /// Send the approval of `approval_transaction` through `submitter`, or the rpc provider
+    /// when unset, unless the current allowance already covers the request. Fails with
+    /// `ClientError::Permit2AllowanceInsufficient` when a shortfall an approval doesn't make up
+    /// for remains. Returns the receipt of the approval when one was sent.
+    pub async fn ensure_permit2_approval(
+        &self,
+        request: &ProofRequest,
+        submitter: Option<&Arc<dyn TransactionSubmitter<N>>>,
+    ) -> Result<Option<N::ReceiptResponse>> {
+        let funding = self.funding(request).await?;
+        let shortfalls = funding_shortfalls(&funding, request);
+        if shortfalls.is_empty() {
+            return Ok(None);
+        }
+        if !shortfalls.iter().all(AllowanceShortfall::is_approvable) {
+            return Err(insufficient(request, &shortfalls));
+        }
+        tracing::info!(
+            "approving {} more of {} to permit2: {}",
+            request.maxRewardAmount,
+            request.rewardToken,
+            shortfalls[0]
+        );
+        let receipt = submit_transaction(
+            &self.rpc_provider,
+            submitter,
+            self.approval_transaction(request, funding.permit2_allowance),
+        )
+        .await?;
+        if !receipt.status() {
+            return Err(ClientError::TransactionReverted {
+                tx_hash: receipt.transaction_hash(),
+            });
+        }
+        Ok(Some(receipt))
+    }
+}
+
+fn insufficient(request: &ProofRequest, shortfalls: &[AllowanceShortfall]) -> ClientError {
+    ClientError::Permit2AllowanceInsufficient {
+        token: request.rewardToken,
+        spender: request.market,
+        shortfalls: shortfalls.iter().map(ToString::to_string).collect(),
+    }
+}
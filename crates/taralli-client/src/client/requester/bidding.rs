@@ -0,0 +1,175 @@
+use std::time::Duration;
+
+use taralli_primitives::abi::universal_porchetta::UniversalPorchetta;
+use taralli_primitives::alloy::primitives::{Address, Bytes};
+use taralli_primitives::alloy::signers::Signer;
+use taralli_primitives::alloy::{network::Network, providers::Provider, transports::Transport};
+use taralli_primitives::intents::offer::ComputeOffer;
+use taralli_primitives::intents::ComputeIntent;
+use taralli_primitives::offer_inputs::OfferInputs;
+use taralli_primitives::systems::{SystemId, SystemParams};
+use taralli_primitives::validation::offer::{
+    validate_offer_inputs_commitment, ComputeOfferValidator, OfferValidationConfig,
+    OfferVerifierConstraints,
+};
+use taralli_primitives::validation::IntentValidator;
+use url::Url;
+
+use crate::api::inputs::OfferInputsApiClient;
+use crate::bidder::offer::{ComputeOfferBidParams, ComputeOfferBidder};
+use crate::bidder::IntentBidder;
+use crate::error::{ClientError, Result};
+use crate::searcher::{offer::ComputeOfferSearcher, IntentSearcher};
+use crate::tracker::offer::ComputeOfferTracker;
+
+use crate::client::BaseClient;
+
+/// Client for requesters buying compute from the offer market. Offers are sourced from the
+/// server, validated, bid upon (locking the requester's reward tokens within the market) and
+/// then tracked until the provider resolves them, returning the delivered opaque submission.
+/// Offers leaving their inputs to the requester get them delivered through the server once bid.
+pub struct RequesterBiddingClient<T, P, N, S>
+where
+    T: Transport + Clone,
+    P: Provider<T, N> + Clone,
+    N: Network + Clone,
+{
+    pub base: BaseClient<T, P, N, S>,
+    pub searcher: ComputeOfferSearcher,
+    pub validator: ComputeOfferValidator,
+    pub bidder: ComputeOfferBidder<T, P, N>,
+    pub tracker: ComputeOfferTracker<T, P, N>,
+    inputs_api: OfferInputsApiClient,
+    market_address: Address,
+}
+
+impl<T, P, N, S> RequesterBiddingClient<T, P, N, S>
+where
+    T: Transport + Clone + Send + Sync,
+    P: Provider<T, N> + Clone + Send + Sync,
+    N: Network + Clone + Send + Sync,
+    S: Signer + Clone,
+{
+    pub fn new(
+        server_url: Url,
+        rpc_provider: P,
+        signer: S,
+        market_address: Address,
+        system_id: SystemId,
+        validation_config: OfferValidationConfig,
+        verifier_constraints: OfferVerifierConstraints,
+    ) -> Self {
+        Self {
+            base: BaseClient::new(rpc_provider.clone(), signer, market_address),
+            searcher: ComputeOfferSearcher::new(server_url.clone(), system_id, market_address),
+            validator: ComputeOfferValidator::new(validation_config, verifier_constraints),
+            bidder: ComputeOfferBidder::new(rpc_provider.clone(), market_address),
+            tracker: ComputeOfferTracker::new(rpc_provider, market_address),
+            inputs_api: OfferInputsApiClient::new(server_url),
+            market_address,
+        }
+    }
+
+    /// Search the server for an active offer matching the configured system id.
+    pub async fn search(&self) -> Result<ComputeOffer<SystemParams>> {
+        self.searcher.search().await
+    }
+
+    /// Search for an offer and then bid on it, awaiting the delivered result.
+    pub async fn search_and_bid(&self) -> Result<(UniversalPorchetta::Resolve, Bytes)> {
+        let offer = self.search().await?;
+        self.bid_and_await_result(offer).await
+    }
+
+    /// Validate the given offer, bid upon it and track it until resolution. Returns the
+    /// resolve event alongside the opaque submission pulled from the resolve transaction.
+    pub async fn bid_and_await_result(
+        &self,
+        offer: ComputeOffer<SystemParams>,
+    ) -> Result<(UniversalPorchetta::Resolve, Bytes)> {
+        self.bid_and_track(offer, None).await
+    }
+
+    /// Bid upon the given offer like `bid_and_await_result`, delivering `inputs` to its provider
+    /// once the bid lands. The inputs are checked against the offer's inputs commitment before
+    /// any tokens are locked.
+    pub async fn bid_with_inputs_and_await_result(
+        &self,
+        offer: ComputeOffer<SystemParams>,
+        inputs: &[u8],
+    ) -> Result<(UniversalPorchetta::Resolve, Bytes)> {
+        self.bid_and_track(offer, Some(inputs)).await
+    }
+
+    async fn bid_and_track(
+        &self,
+        offer: ComputeOffer<SystemParams>,
+        inputs: Option<&[u8]>,
+    ) -> Result<(UniversalPorchetta::Resolve, Bytes)> {
+        let offer_id = offer.compute_id();
+
+        // current chain time
+        let current_ts = self.base.clock.now().await?;
+
+        // validate the offer before locking any tokens
+        self.validator
+            .validate(&offer, current_ts, &self.market_address)
+            .map_err(|e| ClientError::ValidationError(e.to_string()))?;
+        if let Some(inputs) = inputs {
+            validate_offer_inputs_commitment(&offer.proof_offer, inputs)
+                .map_err(|e| ClientError::OfferInputsError(e.to_string()))?;
+        }
+        tracing::info!("offer {} validated, bidding", offer_id);
+
+        // compute resolve deadline timestamp
+        let resolve_deadline_ts =
+            offer.proof_offer.endAuctionTimestamp + u64::from(offer.proof_offer.provingTime);
+
+        // submit a bid for the offer
+        self.bidder
+            .submit_bid(
+                current_ts,
+                offer_id,
+                ComputeOfferBidParams {},
+                offer.proof_offer.clone(),
+                offer.signature,
+            )
+            .await
+            .inspect_err(|e| tracing::error!("bid txs failed: {}", e))?;
+
+        // hand the inputs over to the provider now that the market records the bid
+        if let Some(inputs) = inputs {
+            let offer_inputs = OfferInputs::sign(offer_id, inputs, &self.base.signer)
+                .await
+                .map_err(|e| ClientError::OfferInputsError(e.to_string()))?;
+            self.inputs_api
+                .deliver_inputs(offer_id, &offer_inputs)
+                .await
+                .inspect_err(|e| tracing::error!("inputs delivery failed: {}", e))?;
+            tracing::info!("inputs of offer {} delivered", offer_id);
+        }
+
+        tracing::info!("bid submitted, tracking resolution of offer {}", offer_id);
+
+        // track the resolution up until the resolve deadline
+        let resolve_timeout = Duration::from_secs(resolve_deadline_ts.saturating_sub(current_ts));
+        let (resolve_event, resolve_tx_hash) = self
+            .tracker
+            .track_resolve_with_tx_hash(offer_id, resolve_timeout)
+            .await?
+            .ok_or_else(|| {
+                ClientError::TrackIntentError(format!(
+                    "offer {offer_id} was not resolved before its deadline"
+                ))
+            })?;
+
+        // pull the delivered result from the resolve transaction's calldata
+        let opaque_submission = self
+            .tracker
+            .fetch_opaque_submission(resolve_tx_hash)
+            .await?;
+
+        tracing::info!("Compute offer resolved");
+        Ok((resolve_event, opaque_submission))
+    }
+}
@@ -0,0 +1,47 @@
+//! Requester side of the deadline extension negotiation: deciding whether the provider that won
+//! a request gets more time to resolve it than the request's proving time.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta;
+use taralli_primitives::alloy::primitives::B256;
+use taralli_primitives::extension::ExtensionRequest;
+
+/// Decides the extension requests of the providers that won the requester's requests. Approved
+/// extensions keep the requester tracking the request until the new deadline instead of counting
+/// it as defaulted at the original one.
+#[async_trait]
+pub trait ExtensionPolicy: Send + Sync {
+    /// Whether to extend the deadline of request `request_id`, won by `bid`, from
+    /// `current_deadline` to the one proposed by `extension_request`
+    async fn approve(
+        &self,
+        request_id: B256,
+        bid: &UniversalBombetta::Bid,
+        extension_request: &ExtensionRequest,
+        current_deadline: u64,
+    ) -> bool;
+}
+
+/// Approves extensions pushing the deadline back by at most `max_extension`
+#[derive(Clone, Copy, Debug)]
+pub struct MaxExtensionPolicy {
+    pub max_extension: Duration,
+}
+
+#[async_trait]
+impl ExtensionPolicy for MaxExtensionPolicy {
+    async fn approve(
+        &self,
+        _request_id: B256,
+        _bid: &UniversalBombetta::Bid,
+        extension_request: &ExtensionRequest,
+        current_deadline: u64,
+    ) -> bool {
+        extension_request
+            .new_deadline
+            .saturating_sub(current_deadline)
+            <= self.max_extension.as_secs()
+    }
+}
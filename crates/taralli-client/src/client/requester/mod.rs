@@ -1,2 +1,6 @@
+pub mod allowance;
+pub mod bidding;
+pub mod extension;
 pub mod requesting;
+pub mod resubmission;
 pub mod searching;
@@ -1,8 +1,17 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use taralli_primitives::alloy::primitives::Address;
-use taralli_primitives::alloy::signers::Signer;
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta;
+use taralli_primitives::abi::verifier_details::{decode_request_verifier_details, DecodeMode};
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::{Address, FixedBytes, U256};
+use taralli_primitives::alloy::signers::{local::PrivateKeySigner, Signer};
+use taralli_primitives::alloy::transports::{Client, Http};
 use taralli_primitives::alloy::{network::Network, providers::Provider, transports::Transport};
+use taralli_primitives::encryption::{EncryptedSystemParams, KeyDelivery, SystemKeys};
+use taralli_primitives::extension::ExtensionResponse;
+use taralli_primitives::intents::envelope::{unix_time_ms, IntentMetadata};
 use taralli_primitives::intents::request::ComputeRequest;
 use taralli_primitives::intents::ComputeIntent;
 use taralli_primitives::systems::{SystemId, SystemParams};
@@ -12,15 +21,49 @@ use taralli_primitives::validation::request::{
 use taralli_primitives::validation::IntentValidator;
 use url::Url;
 
+use crate::api::extension::ExtensionApiClient;
+use crate::api::key::KeyExchangeApiClient;
+use crate::api::status::StatusApiClient;
 use crate::api::submit::SubmitApiClient;
+use crate::client::healthcheck::{HealthReport, HealthcheckConfig};
+use crate::confirmations::Confirmations;
+use crate::env::{EnvConfig, WalletProvider, REQUESTER_PRIVATE_KEY_VAR};
 use crate::error::{ClientError, Result};
-use crate::tracker::{IntentAuctionTracker, IntentResolveTracker};
+use crate::tracker::multi::{MultiTracker, TrackedIntent};
+use crate::tracker::payload::ResolutionPayload;
+use crate::tracker::{IntentAuctionTracker, TrackerConfig};
 use crate::{
     intent_builder::request::ComputeRequestBuilder, tracker::request::ComputeRequestTracker,
 };
 
 use crate::client::BaseClient;
 
+use super::allowance::{AllowanceCheck, Permit2AllowanceChecker};
+use super::extension::ExtensionPolicy;
+use super::resubmission::ResubmissionPolicy;
+
+/// Interval the server is polled at for extension requests unless configured otherwise
+pub const DEFAULT_EXTENSION_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Resolve event of a tracked request alongside the hash of the transaction that emitted it
+type Resolution = Option<(UniversalBombetta::Resolve, FixedBytes<32>)>;
+
+/// Outcome of a tracked `ComputeRequest`
+#[derive(Debug)]
+pub struct RequestOutcome {
+    /// Winning bid of the request's auction
+    pub bid_event: UniversalBombetta::Bid,
+    /// Resolve event, if the request was resolved before its deadline
+    pub resolve_event: Option<UniversalBombetta::Resolve>,
+    /// Proof delivered within the resolve transaction, if it was requested and resolved
+    pub payload: Option<ResolutionPayload>,
+    /// Latest deadline extension approved to the provider, the request only counting as
+    /// defaulted once the extended deadline passed
+    pub extension: Option<ExtensionResponse>,
+    /// Metadata the request was submitted with
+    pub metadata: IntentMetadata,
+}
+
 /// Client that submits signed `ComputeRequest` to the protocol server, tracks their auction status
 /// and then tracks their resolution status to see if the requested compute workload was fulfilled.
 pub struct RequesterRequestingClient<T, P, N, S>
@@ -31,9 +74,42 @@ where
 {
     pub base: BaseClient<T, P, N, S>,
     pub api: SubmitApiClient,
+    pub status_api: StatusApiClient,
+    pub key_api: KeyExchangeApiClient,
+    pub extension_api: ExtensionApiClient,
     pub validator: ComputeRequestValidator,
     pub builder: ComputeRequestBuilder<T, P, N>,
     pub tracker: ComputeRequestTracker<T, P, N>,
+    // system of the requests submitted, checked against the server's by `healthcheck`
+    system_id: SystemId,
+    check_server_bounds: bool,
+    // checks the permit2 allowance of each request's reward token before submitting it when set
+    allowance_check: Option<AllowanceCheck>,
+    // answers the extension requests of winning providers when set
+    extension_policy: Option<Arc<dyn ExtensionPolicy>>,
+    extension_poll_interval: Duration,
+    // resubmits requests whose auction ends without a bid when set
+    resubmission_policy: Option<ResubmissionPolicy>,
+    // tracks the submitted requests through shared event filters rather than their own when set
+    multi_tracker: Option<Arc<MultiTracker>>,
+}
+
+impl RequesterRequestingClient<Http<Client>, WalletProvider, Ethereum, PrivateKeySigner> {
+    /// Client requesting proofs of `system_id`, configured from the environment variables
+    /// documented in `crate::env`, the requester's key being read from `REQUESTER_PRIVATE_KEY` or
+    /// `PRIVATE_KEY`
+    pub fn from_env(system_id: SystemId) -> Result<Self> {
+        let config = EnvConfig::from_env(REQUESTER_PRIVATE_KEY_VAR)?;
+        Ok(Self::new(
+            config.server_url.clone(),
+            config.rpc_provider(),
+            config.signer.clone(),
+            config.market_address,
+            system_id,
+            config.request_validation_config(),
+            RequestVerifierConstraints::default(),
+        ))
+    }
 }
 
 impl<T, P, N, S> RequesterRequestingClient<T, P, N, S>
@@ -52,92 +128,548 @@ where
         validation_config: RequestValidationConfig,
         verifier_constraints: RequestVerifierConstraints,
     ) -> Self {
+        let base = BaseClient::new(rpc_provider.clone(), signer.clone(), market_address);
         Self {
-            base: BaseClient::new(rpc_provider.clone(), signer.clone(), market_address),
-            api: SubmitApiClient::new(server_url),
+            api: SubmitApiClient::new(server_url.clone()),
+            key_api: KeyExchangeApiClient::new(server_url.clone()),
+            extension_api: ExtensionApiClient::new(server_url.clone()),
+            status_api: StatusApiClient::new(server_url),
             validator: ComputeRequestValidator::new(validation_config, verifier_constraints),
             builder: ComputeRequestBuilder::new(
                 rpc_provider.clone(),
                 signer.address(),
                 market_address,
                 system_id,
-            ),
+            )
+            .with_clock(base.clock().clone()),
             tracker: ComputeRequestTracker::new(rpc_provider, market_address),
+            base,
+            system_id,
+            check_server_bounds: false,
+            allowance_check: None,
+            extension_policy: None,
+            extension_poll_interval: DEFAULT_EXTENSION_POLL_INTERVAL,
+            resubmission_policy: None,
+            multi_tracker: None,
         }
     }
 
+    /// Check the rpc, the market, the server and the requester's account before submitting, see
+    /// `client::healthcheck`. Requests are paid through permit2 rather than sent from the
+    /// account, so an unfunded one is only a warning. Call `into_result` on the report to abort
+    /// on hard failures.
+    pub async fn healthcheck(&self) -> HealthReport {
+        let validation_config =
+            IntentValidator::<ComputeRequest<SystemParams>>::validation_config(&self.validator);
+        let config = HealthcheckConfig::new(validation_config.base.chain_id, vec![self.system_id])
+            .with_funds_required(false);
+        self.base.healthcheck(&self.status_api, &config).await
+    }
+
+    /// Before submitting, warn about requests violating the validation bounds advertised
+    /// by the server's `/status` endpoint.
+    pub fn with_server_bounds_check(mut self, check_server_bounds: bool) -> Self {
+        self.check_server_bounds = check_server_bounds;
+        self
+    }
+
+    /// Before submitting, check the market can pull the request's reward through permit2: the
+    /// requester's ERC20 allowance of permit2 and balance cover its maximum reward, its permit2
+    /// nonce is unused and its auction hasn't ended, warning, failing or sending the approval of
+    /// permit2 as `allowance_check` says when it can't.
+    pub fn with_allowance_check(mut self, allowance_check: AllowanceCheck) -> Self {
+        self.allowance_check = Some(allowance_check);
+        self
+    }
+
+    /// only count bids and resolutions once the transactions emitting them are confirmed at the
+    /// given depth, so a reorg can't make a request look resolved when it isn't
+    pub fn with_confirmations(mut self, confirmations: Confirmations) -> Self {
+        self.tracker = self.tracker.with_confirmations(confirmations);
+        self
+    }
+
+    /// poll the market alongside the event watch while tracking, see `TrackerConfig`
+    pub fn with_tracker_config(mut self, tracker_config: TrackerConfig) -> Self {
+        self.tracker = self.tracker.with_tracker_config(tracker_config);
+        self
+    }
+
+    /// answer the extension requests of the providers winning the submitted requests with
+    /// `policy`, polling the server for them every `poll_interval` while tracking resolutions
+    pub fn with_extension_policy(
+        mut self,
+        policy: Arc<dyn ExtensionPolicy>,
+        poll_interval: Duration,
+    ) -> Self {
+        self.extension_policy = Some(policy);
+        self.extension_poll_interval = poll_interval;
+        self
+    }
+
+    /// resubmit the requests whose auction ends without a bid as `policy` allows, see
+    /// `ResubmissionPolicy`
+    pub fn with_resubmission_policy(mut self, policy: ResubmissionPolicy) -> Self {
+        self.resubmission_policy = Some(policy);
+        self
+    }
+
+    /// track the submitted requests by registering them on `multi_tracker`, whose bid and resolve
+    /// filters are shared by every request in flight, rather than installing filters of their
+    /// own, see `tracker::multi`. The market isn't polled then, regardless of the tracker config.
+    pub fn with_multi_tracker(mut self, multi_tracker: Arc<MultiTracker>) -> Self {
+        self.multi_tracker = Some(multi_tracker);
+        self
+    }
+
     /// sign the inputted proof request and submit it to the taralli server.
-    /// then start tracking the request auction and resolution on-chain.
+    /// then start tracking the request auction and resolution on-chain. When `fetch_payload`
+    /// is set, the proof delivered by the provider is pulled from the resolve transaction.
+    /// With a resubmission policy set, a request whose auction ends without a bid is rebuilt
+    /// with a bumped reward and submitted again, the outcome being the one of the request bid on.
     pub async fn submit_and_track(
         &self,
         request: ComputeRequest<SystemParams>,
         auction_time_length: u64,
-    ) -> Result<()> {
+        fetch_payload: bool,
+    ) -> Result<RequestOutcome> {
+        self.submit_and_track_with_metadata(
+            request,
+            auction_time_length,
+            fetch_payload,
+            IntentMetadata::new(),
+        )
+        .await
+    }
+
+    /// `submit_and_track` submitting the request along with `metadata`, e.g. the requester's own
+    /// job id. The metadata isn't signed nor broadcast to providers, the server serves it back to
+    /// the requester's queries and the outcome carries it. Resubmitted requests keep it.
+    pub async fn submit_and_track_with_metadata(
+        &self,
+        request: ComputeRequest<SystemParams>,
+        auction_time_length: u64,
+        fetch_payload: bool,
+        metadata: IntentMetadata,
+    ) -> Result<RequestOutcome> {
+        let metadata = &metadata;
+        let Some(policy) = &self.resubmission_policy else {
+            return self
+                .submit_and_track_once(request, auction_time_length, fetch_payload, metadata)
+                .await;
+        };
+        policy
+            .run(
+                request,
+                |request| {
+                    self.submit_and_track_once(
+                        request,
+                        auction_time_length,
+                        fetch_payload,
+                        metadata,
+                    )
+                },
+                |request, max_reward_amount| self.rebuild_request(request, max_reward_amount),
+                |request| self.validate_request(request),
+            )
+            .await
+    }
+
+    /// Rebuild a request whose auction ended without a bid for resubmission: a nonce other than
+    /// its own, an auction of the same length starting at the latest block, `max_reward_amount`
+    /// as maximum reward, signed again
+    pub async fn rebuild_request(
+        &self,
+        mut request: ComputeRequest<SystemParams>,
+        max_reward_amount: U256,
+    ) -> Result<ComputeRequest<SystemParams>> {
+        let mut builder = self.builder.clone();
+        // the expired request's nonce is still unused, so is one of the next two
+        request.proof_request.nonce = builder
+            .base
+            .get_new_nonces(2)
+            .await?
+            .into_iter()
+            .find(|nonce| *nonce != request.proof_request.nonce)
+            .ok_or_else(|| ClientError::GetNonceError("no unused nonce found".to_string()))?;
+        let auction_length = request
+            .proof_request
+            .endAuctionTimestamp
+            .saturating_sub(request.proof_request.startAuctionTimestamp);
+        let start_auction_timestamp = builder.base.latest_timestamp().await?;
+        request.proof_request.startAuctionTimestamp = start_auction_timestamp;
+        request.proof_request.endAuctionTimestamp = start_auction_timestamp + auction_length;
+        request.proof_request.maxRewardAmount = max_reward_amount;
+        self.sign(request).await
+    }
+
+    /// Submit a signed request and track it once, see `submit_and_track_with_metadata`
+    async fn submit_and_track_once(
+        &self,
+        request: ComputeRequest<SystemParams>,
+        auction_time_length: u64,
+        fetch_payload: bool,
+        metadata: &IntentMetadata,
+    ) -> Result<RequestOutcome> {
+        if let Some(allowance_check) = self.allowance_check {
+            self.check_allowance(&request, allowance_check).await?;
+        }
+
         // compute request id
         let request_id = request.compute_id();
+        let system_id = request.system_id;
 
         // compute resolve deadline timestamp
         let resolve_deadline = request.proof_request.endAuctionTimestamp
             + u64::from(request.proof_request.provingTime);
 
         // setup tracking
-        let auction_tracker = self
-            .tracker
-            .track_auction(request_id, Duration::from_secs(auction_time_length));
-        let resolution_tracker = self
-            .tracker
-            .track_resolve(request_id, Duration::from_secs(resolve_deadline));
+        let tracked = self.register(request_id)?;
+        let auction_tracker = self.watch_auction(
+            request_id,
+            Duration::from_secs(auction_time_length),
+            tracked.as_ref(),
+        );
+        let resolution_tracker = self.watch_resolve(
+            request_id,
+            Duration::from_secs(
+                resolve_deadline.saturating_sub(request.proof_request.startAuctionTimestamp),
+            ),
+            tracked.as_ref(),
+        );
 
         tracing::info!(
             "tracking setup for request ID: {}. submitting to server",
             request_id
         );
 
+        if self.check_server_bounds {
+            self.warn_server_bound_violations(&request).await;
+        }
+
         // submit signed request to server
         let response = self
             .api
-            .submit_intent(request)
+            .submit_intent_with_metadata(request, metadata)
             .await
             .map_err(|e| ClientError::ServerRequestError(e.to_string()))?;
 
         if !response.status().is_success() {
-            // Get the response text instead of trying to parse JSON directly
-            let error_text = response
-                .text()
-                .await
-                .unwrap_or_else(|_| "Unknown error".to_string());
-
-            // Try to parse as JSON if possible
-            let error_message = match serde_json::from_str::<serde_json::Value>(&error_text) {
-                Ok(json) => {
-                    if let Some(error) = json.get("error").and_then(|e| e.as_str()) {
-                        format!("Server validation failed: {error}")
-                    } else {
-                        format!("Server returned error: {error_text}")
-                    }
-                }
-                Err(_) => format!("Server returned error: {error_text}"),
-            };
-
-            return Err(ClientError::IntentSubmissionFailed(error_message));
+            return Err(ClientError::from_server_response(response).await);
         }
 
         tracing::info!("Request submitted successfully, waiting for auction result");
 
         // Wait for auction result
-        let _auction_result = auction_tracker
+        let bid_event = auction_tracker
             .await
             .map_err(|e| ClientError::TrackIntentError(e.to_string()))?
             .ok_or(ClientError::AuctionTimeoutError())?;
 
         tracing::info!("Auction completed, waiting for resolution");
 
-        // Wait for resolution
-        let _resolution_result = resolution_tracker
-            .await
-            .map_err(|e| ClientError::TrackIntentError(e.to_string()))?;
+        // Wait for resolution, answering the provider's extension requests meanwhile
+        let (resolution_result, extension) = self
+            .await_resolution(
+                request_id,
+                &bid_event,
+                resolution_tracker,
+                resolve_deadline,
+                tracked.as_ref(),
+            )
+            .await?;
+
+        let payload = match (&resolution_result, fetch_payload) {
+            (Some((resolve_event, resolve_tx_hash)), true) => Some(
+                self.tracker
+                    .fetch_resolution_payload(
+                        request_id,
+                        resolve_event,
+                        *resolve_tx_hash,
+                        Some(system_id),
+                    )
+                    .await?,
+            ),
+            _ => None,
+        };
 
         tracing::info!("Tracking complete");
+        Ok(RequestOutcome {
+            bid_event,
+            resolve_event: resolution_result.map(|(resolve_event, _)| resolve_event),
+            payload,
+            extension,
+            metadata: metadata.clone(),
+        })
+    }
+
+    /// Track the resolution of request `request_id`, won by `bid_event`, until the unix
+    /// timestamp `resolve_deadline`, answering the provider's extension requests meanwhile when
+    /// an extension policy is set. Returns the resolve event with the hash of its transaction,
+    /// and the latest extension approved.
+    pub async fn track_resolution(
+        &self,
+        request_id: FixedBytes<32>,
+        bid_event: &UniversalBombetta::Bid,
+        resolve_deadline: u64,
+    ) -> Result<(Resolution, Option<ExtensionResponse>)> {
+        let tracked = self.register(request_id)?;
+        let resolution_tracker = self.watch_resolve(
+            request_id,
+            Duration::from_secs(resolve_deadline.saturating_sub(unix_time_ms() / 1000)),
+            tracked.as_ref(),
+        );
+        self.await_resolution(
+            request_id,
+            bid_event,
+            resolution_tracker,
+            resolve_deadline,
+            tracked.as_ref(),
+        )
+        .await
+    }
+
+    /// Register request `request_id` on the multi tracker when set, see `with_multi_tracker`
+    fn register(&self, request_id: FixedBytes<32>) -> Result<Option<TrackedIntent>> {
+        self.multi_tracker
+            .as_ref()
+            .map(|multi_tracker| multi_tracker.register(request_id))
+            .transpose()
+    }
+
+    /// Track the auction of request `request_id` through its handle on the multi tracker when
+    /// registered on one, through filters of its own otherwise
+    async fn watch_auction(
+        &self,
+        request_id: FixedBytes<32>,
+        timeout: Duration,
+        tracked: Option<&TrackedIntent>,
+    ) -> Result<Option<UniversalBombetta::Bid>> {
+        match tracked {
+            Some(tracked) => tracked.track_auction(timeout).await,
+            None => self.tracker.track_auction(request_id, timeout).await,
+        }
+    }
+
+    /// Track the resolution of request `request_id` like `watch_auction` tracks its auction
+    async fn watch_resolve(
+        &self,
+        request_id: FixedBytes<32>,
+        timeout: Duration,
+        tracked: Option<&TrackedIntent>,
+    ) -> Result<Resolution> {
+        match tracked {
+            Some(tracked) => tracked.track_resolve_with_tx_hash(timeout).await,
+            None => {
+                self.tracker
+                    .track_resolve_with_tx_hash(request_id, timeout)
+                    .await
+            }
+        }
+    }
+
+    /// Approve or decline extending the deadline of request `request_id` to `new_deadline`, as
+    /// asked by the provider that won it
+    pub async fn respond_to_extension(
+        &self,
+        request_id: FixedBytes<32>,
+        new_deadline: u64,
+        approve: bool,
+    ) -> Result<ExtensionResponse> {
+        let extension_response = if approve {
+            ExtensionResponse::approve(request_id, new_deadline, &self.base.signer).await
+        } else {
+            ExtensionResponse::decline(request_id, new_deadline, &self.base.signer).await
+        }
+        .map_err(|e| ClientError::ExtensionError(e.to_string()))?;
+        self.extension_api
+            .respond(request_id, &extension_response)
+            .await?;
+        tracing::info!(
+            "extension of request {} to {} {}",
+            request_id,
+            new_deadline,
+            if approve { "approved" } else { "declined" }
+        );
+        Ok(extension_response)
+    }
+
+    /// Await `resolution_tracker`, answering extension requests meanwhile. Once the tracker ends
+    /// without a resolution, tracking goes on until the latest approved deadline rather than
+    /// counting the request as defaulted.
+    async fn await_resolution(
+        &self,
+        request_id: FixedBytes<32>,
+        bid_event: &UniversalBombetta::Bid,
+        resolution_tracker: impl Future<Output = Result<Resolution>>,
+        resolve_deadline: u64,
+        tracked: Option<&TrackedIntent>,
+    ) -> Result<(Resolution, Option<ExtensionResponse>)> {
+        let Some(policy) = &self.extension_policy else {
+            let resolution = resolution_tracker
+                .await
+                .map_err(|e| ClientError::TrackIntentError(e.to_string()))?;
+            return Ok((resolution, None));
+        };
+
+        let approved = Mutex::new(None);
+        let answering = self.answer_extension_requests(
+            request_id,
+            bid_event,
+            resolve_deadline,
+            policy.as_ref(),
+            &approved,
+        );
+        tokio::pin!(answering);
+        let mut deadline = resolve_deadline;
+        let mut resolution = tokio::select! {
+            resolution = resolution_tracker => resolution,
+            () = &mut answering => unreachable!("answering extension requests never ends"),
+        };
+        loop {
+            let resolved = resolution.map_err(|e| ClientError::TrackIntentError(e.to_string()))?;
+            let extension = approved.lock().unwrap().clone();
+            match extension {
+                Some(extension) if resolved.is_none() && extension.new_deadline > deadline => {
+                    tracing::info!(
+                        "request {} not resolved by {}, tracking on until the extended deadline {}",
+                        request_id,
+                        deadline,
+                        extension.new_deadline
+                    );
+                    deadline = extension.new_deadline;
+                    let resolution_tracker = self.watch_resolve(
+                        request_id,
+                        Duration::from_secs(deadline.saturating_sub(unix_time_ms() / 1000)),
+                        tracked,
+                    );
+                    resolution = tokio::select! {
+                        resolution = resolution_tracker => resolution,
+                        () = &mut answering => unreachable!("answering extension requests never ends"),
+                    };
+                }
+                extension => return Ok((resolved, extension)),
+            }
+        }
+    }
+
+    /// Answer the extension requests posted by the provider of request `request_id` with
+    /// `policy`, polling the server until dropped. Approved extensions are kept in `approved`.
+    async fn answer_extension_requests(
+        &self,
+        request_id: FixedBytes<32>,
+        bid_event: &UniversalBombetta::Bid,
+        resolve_deadline: u64,
+        policy: &dyn ExtensionPolicy,
+        approved: &Mutex<Option<ExtensionResponse>>,
+    ) {
+        let mut answered = None;
+        loop {
+            match self
+                .extension_api
+                .pending_extension_request(request_id)
+                .await
+            {
+                Ok(Some(extension_request)) if answered.as_ref() != Some(&extension_request) => {
+                    let current_deadline = approved
+                        .lock()
+                        .unwrap()
+                        .as_ref()
+                        .map_or(resolve_deadline, |extension: &ExtensionResponse| {
+                            extension.new_deadline
+                        });
+                    // the server only relays requests of the provider, checked again regardless
+                    let approve = extension_request
+                        .signer(request_id)
+                        .is_ok_and(|signer| signer == bid_event.provider)
+                        && extension_request.new_deadline > current_deadline
+                        && policy
+                            .approve(request_id, bid_event, &extension_request, current_deadline)
+                            .await;
+                    match self
+                        .respond_to_extension(request_id, extension_request.new_deadline, approve)
+                        .await
+                    {
+                        Ok(extension_response) => {
+                            if approve {
+                                *approved.lock().unwrap() = Some(extension_response);
+                            }
+                            answered = Some(extension_request);
+                        }
+                        Err(e) => tracing::warn!(
+                            "answering the extension request of request {request_id} failed: {e}"
+                        ),
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(
+                    "polling the extension requests of request {request_id} failed: {e}"
+                ),
+            }
+            tokio::time::sleep(self.extension_poll_interval).await;
+        }
+    }
+
+    /// Log a warning for each server validation bound the request violates. Failing to fetch
+    /// the server status is not fatal, the server still validates the request on submission.
+    async fn warn_server_bound_violations(&self, request: &ComputeRequest<SystemParams>) {
+        let status = match self.status_api.fetch_status().await {
+            Ok(status) => status,
+            Err(e) => {
+                tracing::warn!(
+                    "failed to fetch server status, skipping bounds check: {}",
+                    e
+                );
+                return;
+            }
+        };
+
+        let latest_timestamp = match self.base.clock.now().await {
+            Ok(timestamp) => timestamp,
+            Err(e) => {
+                tracing::warn!("failed to read chain time, skipping bounds check: {}", e);
+                return;
+            }
+        };
+
+        for violation in status.request_bound_violations(request, latest_timestamp) {
+            tracing::warn!("request violates server bounds: {}", violation);
+        }
+        if status.subscribers(request.system_id) == 0 {
+            tracing::warn!(
+                "no providers are subscribed to system {}",
+                request.system_id.as_str()
+            );
+        }
+    }
+
+    /// Check the permit2 allowance covering `request`, see `with_allowance_check`
+    async fn check_allowance(
+        &self,
+        request: &ComputeRequest<SystemParams>,
+        allowance_check: AllowanceCheck,
+    ) -> Result<()> {
+        let checker = Permit2AllowanceChecker::new(
+            self.base.rpc_provider.clone(),
+            self.base.signer.address(),
+        )
+        .with_clock(self.base.clock.clone());
+        match allowance_check {
+            AllowanceCheck::Warn => {
+                for shortfall in checker.check(&request.proof_request).await? {
+                    tracing::warn!("request may not be settled: {}", shortfall);
+                }
+            }
+            AllowanceCheck::Fail => checker.ensure_allowance(&request.proof_request).await?,
+            AllowanceCheck::Approve => {
+                if let Some(receipt) = checker
+                    .ensure_permit2_approval(&request.proof_request, None)
+                    .await?
+                {
+                    tracing::info!("permit2 approval sent: {:?}", receipt);
+                }
+            }
+        }
         Ok(())
     }
 
@@ -145,8 +677,12 @@ where
         &self,
         mut request: ComputeRequest<SystemParams>,
     ) -> Result<ComputeRequest<SystemParams>> {
-        // build permit2 digest
-        let permit2_digest = request.compute_permit2_digest();
+        // refuse to sign an intent whose verifier details can't be decoded exactly
+        decode_request_verifier_details(&request.proof_request.extraData, DecodeMode::Strict)
+            .map_err(|e| ClientError::ValidationError(e.to_string()))?;
+        // build permit2 digest for the chain of the rpc provider
+        let chain_id = self.builder.chain_id().await?;
+        let permit2_digest = request.compute_permit2_digest(chain_id);
 
         // sign permit2 digest
         let signature = self
@@ -160,6 +696,58 @@ where
         Ok(request)
     }
 
+    /// Encrypt the system params of a signed request, so only the provider winning its auction
+    /// can read them. The returned keys are needed to serve the key with `serve_key`.
+    pub fn encrypt_request(
+        &self,
+        request: ComputeRequest<SystemParams>,
+    ) -> Result<(ComputeRequest<EncryptedSystemParams>, SystemKeys)> {
+        let (system, keys) = EncryptedSystemParams::encrypt(&request.system, request.compute_id())?;
+        Ok((
+            ComputeRequest {
+                system_id: request.system_id,
+                system,
+                proof_request: request.proof_request,
+                signature: request.signature,
+            },
+            keys,
+        ))
+    }
+
+    /// Wait for the provider that won an encrypted request to ask for its key, then deliver the
+    /// content key wrapped for it. Gives up after `timeout`, e.g. the request's proving time.
+    pub async fn serve_key(
+        &self,
+        request_id: FixedBytes<32>,
+        keys: &SystemKeys,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<()> {
+        let key_request = tokio::time::timeout(timeout, async {
+            loop {
+                if let Some(key_request) = self.key_api.pending_key_request(request_id).await? {
+                    return Ok(key_request);
+                }
+                tokio::time::sleep(poll_interval).await;
+            }
+        })
+        .await
+        .map_err(|_| {
+            ClientError::KeyExchangeError(format!(
+                "no key requested for request {request_id} within {}s",
+                timeout.as_secs()
+            ))
+        })??;
+
+        let wrapped_key = keys.wrap_for(request_id, &key_request.provider_key)?;
+        let key_delivery = KeyDelivery::sign(request_id, wrapped_key, &self.base.signer)
+            .await
+            .map_err(|e| ClientError::KeyExchangeError(e.to_string()))?;
+        self.key_api.deliver_key(request_id, &key_delivery).await?;
+        tracing::info!("key of request {} delivered", request_id);
+        Ok(())
+    }
+
     pub fn validate_request(&self, request: &ComputeRequest<SystemParams>) -> Result<()> {
         // validate a request built by the requester client
         self.validator.validate(
@@ -0,0 +1,175 @@
+//! Resubmission of requests whose auction ended without a bid: rebuilt with a fresh nonce and
+//! auction, their maximum reward bumped to attract providers, re-signed and submitted again.
+
+use std::future::Future;
+
+use taralli_primitives::alloy::primitives::{B256, U256};
+use taralli_primitives::intents::envelope::unix_time_ms;
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::intents::ComputeIntent;
+use taralli_primitives::systems::SystemParams;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::error::{ClientError, Result};
+
+/// Why a request whose auction ended without a bid isn't resubmitted
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ResubmissionStop {
+    /// the policy's resubmissions were all used
+    AttemptsExhausted,
+    /// the policy's deadline passed
+    DeadlinePassed,
+    /// the rebuilt request, reward bumped, failed validation
+    ValidationFailed(String),
+}
+
+/// Progress of the resubmissions of a request
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ResubmissionEvent {
+    /// the auction of `previous_id` ended without a bid, resubmitted as `request_id`
+    Resubmitted {
+        /// resubmission number, starting at 1
+        attempt: u32,
+        previous_id: B256,
+        request_id: B256,
+        max_reward_amount: U256,
+    },
+    /// the auction of `request_id` ended without a bid and it isn't resubmitted
+    Stopped {
+        /// resubmissions made before stopping
+        attempts: u32,
+        request_id: B256,
+        reason: ResubmissionStop,
+    },
+}
+
+/// How requests whose auction ends without a bid are resubmitted
+#[derive(Clone, Debug)]
+pub struct ResubmissionPolicy {
+    /// resubmissions made at most after the first submission
+    pub max_attempts: u32,
+    /// percentage the maximum reward grows by at each resubmission
+    pub reward_bump_percent: u32,
+    /// maximum reward the bumps never go past
+    pub max_reward_amount: U256,
+    /// unix timestamp after which requests aren't resubmitted anymore
+    pub deadline: Option<u64>,
+    // receives an event for each resubmission and for the stop of resubmissions
+    events: Option<UnboundedSender<ResubmissionEvent>>,
+}
+
+impl ResubmissionPolicy {
+    pub fn new(max_attempts: u32, reward_bump_percent: u32, max_reward_amount: U256) -> Self {
+        Self {
+            max_attempts,
+            reward_bump_percent,
+            max_reward_amount,
+            deadline: None,
+            events: None,
+        }
+    }
+
+    /// stop resubmitting once the unix timestamp `deadline` passed
+    pub fn with_deadline(mut self, deadline: u64) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// send an event to `events` for each resubmission and once resubmissions stop
+    pub fn with_events(mut self, events: UnboundedSender<ResubmissionEvent>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Maximum reward of the resubmission of a request offering `max_reward_amount`, bumped by
+    /// the policy's percentage and capped at its maximum. A request offering more than the cap
+    /// keeps its reward.
+    pub fn escalate(&self, max_reward_amount: U256) -> U256 {
+        let bumped = max_reward_amount.saturating_mul(U256::from(100 + self.reward_bump_percent))
+            / U256::from(100);
+        bumped.min(self.max_reward_amount).max(max_reward_amount)
+    }
+
+    /// Submit `request` with `submit`, resubmitting it as rebuilt by `rebuild` with an escalated
+    /// maximum reward each time its auction ends without a bid, until a submission gets a bid,
+    /// fails otherwise, or the policy stops resubmissions. Rebuilt requests failing `validate`
+    /// aren't submitted. Ends with `AuctionTimeoutError` when resubmissions stop.
+    pub async fn run<O, Submit, SubmitFut, Rebuild, RebuildFut>(
+        &self,
+        mut request: ComputeRequest<SystemParams>,
+        mut submit: Submit,
+        mut rebuild: Rebuild,
+        validate: impl Fn(&ComputeRequest<SystemParams>) -> Result<()>,
+    ) -> Result<O>
+    where
+        Submit: FnMut(ComputeRequest<SystemParams>) -> SubmitFut,
+        SubmitFut: Future<Output = Result<O>>,
+        Rebuild: FnMut(ComputeRequest<SystemParams>, U256) -> RebuildFut,
+        RebuildFut: Future<Output = Result<ComputeRequest<SystemParams>>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match submit(request.clone()).await {
+                Err(ClientError::AuctionTimeoutError()) => {}
+                result => return result,
+            }
+            let previous_id = request.compute_id();
+
+            if attempt >= self.max_attempts {
+                return Err(self.stop(attempt, previous_id, ResubmissionStop::AttemptsExhausted));
+            }
+            if self
+                .deadline
+                .is_some_and(|deadline| unix_time_ms() / 1000 >= deadline)
+            {
+                return Err(self.stop(attempt, previous_id, ResubmissionStop::DeadlinePassed));
+            }
+            let max_reward_amount = self.escalate(request.proof_request.maxRewardAmount);
+            let rebuilt = rebuild(request, max_reward_amount).await?;
+            if let Err(e) = validate(&rebuilt) {
+                self.stop(
+                    attempt,
+                    previous_id,
+                    ResubmissionStop::ValidationFailed(e.to_string()),
+                );
+                return Err(e);
+            }
+
+            attempt += 1;
+            request = rebuilt;
+            tracing::info!(
+                "auction of request {} ended without a bid, resubmitting as {} with a maximum reward of {}",
+                previous_id,
+                request.compute_id(),
+                max_reward_amount
+            );
+            self.emit(ResubmissionEvent::Resubmitted {
+                attempt,
+                previous_id,
+                request_id: request.compute_id(),
+                max_reward_amount,
+            });
+        }
+    }
+
+    fn stop(&self, attempts: u32, request_id: B256, reason: ResubmissionStop) -> ClientError {
+        tracing::warn!(
+            "auction of request {} ended without a bid, not resubmitting: {:?}",
+            request_id,
+            reason
+        );
+        self.emit(ResubmissionEvent::Stopped {
+            attempts,
+            request_id,
+            reason,
+        });
+        ClientError::AuctionTimeoutError()
+    }
+
+    fn emit(&self, event: ResubmissionEvent) {
+        if let Some(events) = &self.events {
+            // a dropped receiver only means nobody listens anymore
+            let _ = events.send(event);
+        }
+    }
+}
@@ -1,9 +1,5 @@
 use std::time::Duration;
 
-use taralli_primitives::alloy::consensus::BlockHeader;
-use taralli_primitives::alloy::eips::BlockId;
-use taralli_primitives::alloy::eips::BlockNumberOrTag::Latest;
-use taralli_primitives::alloy::network::{BlockResponse, BlockTransactionsKind};
 use taralli_primitives::alloy::primitives::Address;
 use taralli_primitives::alloy::signers::Signer;
 use taralli_primitives::alloy::{network::Network, providers::Provider, transports::Transport};
@@ -80,17 +76,8 @@ where
             offer.proof_offer
         );
 
-        // Fetch latest block timestamp
-        // TODO: remove this call from the request processing work flow, instead passing it in as input from another external process
-        let current_ts = self
-            .base
-            .rpc_provider
-            .get_block(BlockId::Number(Latest), BlockTransactionsKind::Hashes)
-            .await
-            .map_err(|e| ClientError::RpcRequestError(e.to_string()))?
-            .ok_or_else(|| ClientError::RpcRequestError("Block header not found".to_string()))?
-            .header()
-            .timestamp();
+        // current chain time
+        let current_ts = self.base.clock.now().await?;
 
         // compute resolve deadline timestamp
         let resolve_deadline_ts =
@@ -113,7 +100,7 @@ where
                 offer.signature,
             )
             .await
-            .map_err(|e| ClientError::TransactionFailure(format!("bid txs failed: {e}")))?;
+            .inspect_err(|e| tracing::error!("bid txs failed: {}", e))?;
 
         tracing::info!("bid transaction submitted successfully, tracking resolution of the offer");
 
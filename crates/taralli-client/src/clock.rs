@@ -0,0 +1,223 @@
+//! Chain time, as told by the timestamp of the latest block.
+//!
+//! Auctions, proving deadlines and validation bounds are all expressed in block timestamps, which
+//! drift away from the local wall clock on chains with irregular block times. Scheduling against
+//! the wall clock then bids too early or too late, so every wait and deadline is computed against
+//! a `ChainClock` instead.
+//!
+//! The latest block timestamp is cached and extrapolated with the local monotonic clock until it's
+//! older than the clock's max staleness, so the components sharing a clock don't each fetch the
+//! latest block for every intent. A clock built on a `ManualClock` is driven by hand instead, e.g.
+//! to test the wait logic deterministically.
+
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use taralli_primitives::alloy::{
+    consensus::BlockHeader,
+    eips::{BlockId, BlockNumberOrTag::Latest},
+    network::{BlockResponse, BlockTransactionsKind, Network},
+    providers::Provider,
+    transports::Transport,
+};
+use tokio::sync::watch;
+
+use crate::api::retry::RetryPolicy;
+use crate::error::{ClientError, Result};
+use crate::rpc::retry_rpc;
+
+/// Age past which the cached block timestamp is fetched again
+pub const DEFAULT_MAX_STALENESS: Duration = Duration::from_secs(2);
+
+/// Time source of a `ChainClock`
+#[derive(Clone)]
+enum ClockSource<P> {
+    Rpc(P),
+    Manual(ManualClock),
+}
+
+/// Latest block timestamp fetched, along with when it was
+#[derive(Clone, Copy, Debug)]
+struct ClockReading {
+    block_timestamp: u64,
+    fetched_at: Instant,
+    /// block timestamp minus the local wall clock when fetched, in seconds
+    drift: i64,
+}
+
+/// Chain time shared by the components of a client. Clones share their cached reading.
+#[derive(Clone)]
+pub struct ChainClock<T, P, N> {
+    source: ClockSource<P>,
+    pub max_staleness: Duration,
+    rpc_retry: RetryPolicy,
+    reading: Arc<Mutex<Option<ClockReading>>>,
+    phantom: PhantomData<(T, N)>,
+}
+
+impl<T, P, N> ChainClock<T, P, N> {
+    /// Clock reading the latest block timestamp of `rpc_provider`
+    pub fn new(rpc_provider: P) -> Self {
+        Self::from_source(ClockSource::Rpc(rpc_provider))
+    }
+
+    /// Clock telling the time of `manual_clock`, without any rpc request
+    pub fn manual(manual_clock: ManualClock) -> Self {
+        Self::from_source(ClockSource::Manual(manual_clock))
+    }
+
+    fn from_source(source: ClockSource<P>) -> Self {
+        Self {
+            source,
+            max_staleness: DEFAULT_MAX_STALENESS,
+            rpc_retry: RetryPolicy::default(),
+            reading: Arc::new(Mutex::new(None)),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Fetch the latest block timestamp again once the cached one is older than `max_staleness`
+    pub fn with_max_staleness(mut self, max_staleness: Duration) -> Self {
+        self.max_staleness = max_staleness;
+        self
+    }
+
+    /// attempt the rpc calls fetching the latest block again as `rpc_retry` allows when they fail
+    /// transiently
+    pub fn with_rpc_retry(mut self, rpc_retry: RetryPolicy) -> Self {
+        self.rpc_retry = rpc_retry;
+        self
+    }
+
+    /// Chain time minus the local wall clock as of the last fetch, in seconds, `None` before the
+    /// first one. Positive when blocks are timestamped ahead of the local clock.
+    pub fn drift(&self) -> Option<i64> {
+        self.reading
+            .lock()
+            .expect("clock lock poisoned")
+            .map(|reading| reading.drift)
+    }
+}
+
+impl<T, P, N> ChainClock<T, P, N>
+where
+    T: Transport + Clone,
+    P: Provider<T, N> + Clone,
+    N: Network + Clone,
+{
+    /// Current chain time: the cached latest block timestamp plus the time elapsed since it was
+    /// fetched, fetched again when older than `max_staleness`
+    pub async fn now(&self) -> Result<u64> {
+        if let ClockSource::Manual(manual_clock) = &self.source {
+            return Ok(manual_clock.now());
+        }
+        let cached = *self.reading.lock().expect("clock lock poisoned");
+        match cached {
+            Some(reading) if reading.fetched_at.elapsed() < self.max_staleness => {
+                Ok(reading.block_timestamp + reading.fetched_at.elapsed().as_secs())
+            }
+            _ => self.refresh().await,
+        }
+    }
+
+    /// Fetch the latest block timestamp, whatever the age of the cached one
+    pub async fn refresh(&self) -> Result<u64> {
+        let rpc_provider = match &self.source {
+            ClockSource::Rpc(rpc_provider) => rpc_provider,
+            ClockSource::Manual(manual_clock) => return Ok(manual_clock.now()),
+        };
+        // a node behind on the latest block answers with none, which is attempted again too
+        let block_timestamp = retry_rpc(&self.rpc_retry, || async move {
+            rpc_provider
+                .get_block(BlockId::Number(Latest), BlockTransactionsKind::Hashes)
+                .await
+                .map_err(|e| e.to_string())?
+                .map(|block| block.header().timestamp())
+                .ok_or_else(|| "block header not found".to_string())
+        })
+        .await?;
+        let wall_clock = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        *self.reading.lock().expect("clock lock poisoned") = Some(ClockReading {
+            block_timestamp,
+            fetched_at: Instant::now(),
+            drift: block_timestamp as i64 - wall_clock as i64,
+        });
+        Ok(block_timestamp)
+    }
+
+    /// Wait until the chain time reaches `timestamp`, returning the chain time then, or fail with
+    /// `ClientError::WaitDeadlinePassed` once it reaches `deadline` first. Waits are estimated
+    /// with the local clock and checked against the chain, so slow blocks extend them. Each one
+    /// covers at most half of the time left before the deadline, so blocks coming faster than the
+    /// local clock are caught up with before the wait runs past it.
+    pub async fn sleep_until(&self, timestamp: u64, deadline: u64) -> Result<u64> {
+        let passed = |chain_time| ClientError::WaitDeadlinePassed {
+            timestamp,
+            deadline,
+            chain_time,
+        };
+        if let ClockSource::Manual(manual_clock) = &self.source {
+            let now = manual_clock.sleep_until(timestamp.min(deadline)).await;
+            return if timestamp <= now && now < deadline {
+                Ok(now)
+            } else {
+                Err(passed(now))
+            };
+        }
+        loop {
+            let now = self.now().await?;
+            if now >= deadline {
+                return Err(passed(now));
+            }
+            if now >= timestamp {
+                return Ok(now);
+            }
+            let wait = (timestamp - now).min((deadline - now).div_ceil(2));
+            tokio::time::sleep(Duration::from_secs(wait)).await;
+        }
+    }
+}
+
+/// Time set by hand, waking the waits it reaches. Clones share their time.
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    time: Arc<watch::Sender<u64>>,
+}
+
+impl ManualClock {
+    pub fn new(timestamp: u64) -> Self {
+        Self {
+            time: Arc::new(watch::Sender::new(timestamp)),
+        }
+    }
+
+    pub fn now(&self) -> u64 {
+        *self.time.borrow()
+    }
+
+    /// Set the time, which never goes backwards
+    pub fn set(&self, timestamp: u64) {
+        self.time.send_if_modified(|time| {
+            let modified = timestamp > *time;
+            *time = (*time).max(timestamp);
+            modified
+        });
+    }
+
+    pub fn advance(&self, seconds: u64) {
+        self.set(self.now() + seconds);
+    }
+
+    /// Wait until the time is set to `timestamp` or later, returning the time then
+    pub async fn sleep_until(&self, timestamp: u64) -> u64 {
+        let mut receiver = self.time.subscribe();
+        let reached = receiver
+            .wait_for(|time| *time >= timestamp)
+            .await
+            .map(|time| *time);
+        reached.expect("the clock outlives its waits")
+    }
+}
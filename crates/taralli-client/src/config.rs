@@ -1,6 +1,6 @@
 //! Client Configurations
 
-use std::{collections::HashMap, fmt, sync::Arc};
+use std::{collections::HashMap, fmt, sync::Arc, time::Duration};
 
 use serde::{Deserialize, Serialize};
 use taralli_primitives::{
@@ -9,7 +9,15 @@ use taralli_primitives::{
     validation::{offer::OfferValidationConfig, request::RequestValidationConfig},
 };
 
-use crate::worker::{ComputeWorker, WorkerManager};
+use crate::analyzer::reward::RewardTokenConfig;
+use crate::artifacts::ArtifactRegistry;
+use crate::bid_store::BidStore;
+use crate::client::provider::review::BiddingMode;
+use crate::confirmations::Confirmations;
+use crate::error::{ClientError, Result};
+use crate::submitter::DEFAULT_RECEIPT_TIMEOUT;
+use crate::work_cache::{WorkCache, WorkCacheConfig};
+use crate::worker::{ComputeWorker, WorkerManager, DEFAULT_RESOLVE_MARGIN_SECONDS};
 
 #[derive(Clone)]
 pub struct ClientValidationConfigs {
@@ -23,6 +31,45 @@ pub struct BidderConfig {
     pub max_bid_attempts: u32,
 }
 
+/// Transaction submission settings for bidders and resolvers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmissionConfig {
+    /// private rpc (e.g. Flashbots Protect) only used to send transactions, reads keep going
+    /// through the client's rpc provider
+    pub private_rpc_url: Option<String>,
+    /// seconds to wait for a private submission to be included before submitting publicly
+    pub fallback_timeout_seconds: u64,
+    /// seconds to wait for a transaction to be included on either path before giving up on it
+    #[serde(default = "default_receipt_timeout_seconds")]
+    pub receipt_timeout_seconds: u64,
+}
+
+fn default_receipt_timeout_seconds() -> u64 {
+    DEFAULT_RECEIPT_TIMEOUT.as_secs()
+}
+
+impl Default for SubmissionConfig {
+    fn default() -> Self {
+        Self {
+            private_rpc_url: None,
+            fallback_timeout_seconds: 24, // 2 blocks
+            receipt_timeout_seconds: default_receipt_timeout_seconds(),
+        }
+    }
+}
+
+impl SubmissionConfig {
+    #[must_use]
+    pub fn fallback_timeout(&self) -> Duration {
+        Duration::from_secs(self.fallback_timeout_seconds)
+    }
+
+    #[must_use]
+    pub fn receipt_timeout(&self) -> Duration {
+        Duration::from_secs(self.receipt_timeout_seconds)
+    }
+}
+
 /// Serializable provider configs (for loading from files)
 #[derive(Clone, Debug, Deserialize)]
 pub struct ProviderOfferingConfigFile {
@@ -34,6 +81,43 @@ pub struct ProviderOfferingConfigFile {
 pub struct ProviderStreamingConfigFile {
     pub supported_systems: Vec<SystemId>,
     pub validation_config: RequestValidationConfig,
+    /// ask the server for deflated request frames
+    #[serde(default)]
+    pub frame_compression: bool,
+    /// subscribe over server-sent events when websocket upgrades are blocked
+    #[serde(default)]
+    pub sse_fallback: bool,
+    /// reward tokens accepted, any token is when unset
+    #[serde(default)]
+    pub reward_tokens: Option<Vec<RewardTokenConfig>>,
+    /// confirmation depth of bid and resolve transactions
+    #[serde(default)]
+    pub confirmations: Confirmations,
+    /// whether requests are bid upon automatically or once approved through the review endpoint
+    #[serde(default)]
+    pub bidding_mode: BiddingMode,
+    /// reuse the results of workloads already proved, disabled when unset
+    #[serde(default)]
+    pub work_cache: Option<WorkCacheConfig>,
+    /// JSON file the work cache is persisted to, kept in memory when unset
+    #[serde(default)]
+    pub work_cache_path: Option<String>,
+    /// seconds of each proving window kept for the resolve transaction, workers being cancelled
+    /// once the rest runs out
+    #[serde(default = "default_resolve_margin_seconds")]
+    pub resolve_margin_seconds: u64,
+    /// JSON file of the artifacts the provider knows, see `crate::artifacts`, requests are not
+    /// identified when unset
+    #[serde(default)]
+    pub artifact_registry_path: Option<String>,
+    /// JSON file the provider's bid attempts are persisted to, see `crate::bid_store`, requests
+    /// replayed after a restart are not checked against the market when unset
+    #[serde(default)]
+    pub bid_store_path: Option<String>,
+}
+
+fn default_resolve_margin_seconds() -> u64 {
+    DEFAULT_RESOLVE_MARGIN_SECONDS
 }
 
 /// Runtime provider client configs (with workers)
@@ -48,6 +132,13 @@ pub struct ProviderOfferingConfig<I: ComputeIntent> {
 pub struct ProviderStreamingConfig<I: ComputeIntent> {
     pub worker_manager: Arc<WorkerManager<I>>,
     pub validation_config: RequestValidationConfig,
+    pub frame_compression: bool,
+    pub sse_fallback: bool,
+    pub reward_tokens: Option<Vec<RewardTokenConfig>>,
+    pub confirmations: Confirmations,
+    pub bidding_mode: BiddingMode,
+    pub artifact_registry: Option<Arc<ArtifactRegistry>>,
+    pub bid_store: Option<Arc<BidStore>>,
 }
 
 /// provider config Debug impls
@@ -65,6 +156,13 @@ impl<I: ComputeIntent> fmt::Debug for ProviderStreamingConfig<I> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("ProviderStreamingConfig")
             .field("validation_config", &self.validation_config)
+            .field("frame_compression", &self.frame_compression)
+            .field("sse_fallback", &self.sse_fallback)
+            .field("reward_tokens", &self.reward_tokens)
+            .field("confirmations", &self.confirmations)
+            .field("bidding_mode", &self.bidding_mode)
+            .field("artifact_registry", &self.artifact_registry)
+            .field("bid_store", &self.bid_store)
             .field("worker_manager", &"<WorkerManager>")
             .finish()
     }
@@ -85,21 +183,60 @@ impl ProviderOfferingConfigFile {
 }
 
 impl ProviderStreamingConfigFile {
+    /// Load the config from a file whose format is given by its extension, e.g. `provider.toml`
+    pub fn from_file(path: &str) -> Result<Self> {
+        ::config::Config::builder()
+            .add_source(::config::File::with_name(path))
+            .build()
+            .and_then(::config::Config::try_deserialize)
+            .map_err(|e| ClientError::ConfigError(e.to_string()))
+    }
+
+    /// Build the runtime config, loading the persisted work cache, the artifact registry and the
+    /// bid store when they're configured. Fails when `worker_factory` has no worker for one of
+    /// the supported systems, e.g. one whose feature isn't compiled in.
     pub fn to_runtime_config<I: ComputeIntent>(
         &self,
-        worker_factory: impl Fn(SystemId) -> Arc<dyn ComputeWorker<I> + Send + Sync>,
-    ) -> ProviderStreamingConfig<I> {
+        worker_factory: impl Fn(SystemId) -> Result<Arc<dyn ComputeWorker<I> + Send + Sync>>,
+    ) -> Result<ProviderStreamingConfig<I>> {
         let mut workers = HashMap::new();
         for system_id in &self.supported_systems {
-            workers.insert(*system_id, worker_factory(*system_id));
+            let worker = worker_factory(*system_id)?;
+            workers.insert(*system_id, worker);
         }
 
-        let worker_manager = Arc::new(WorkerManager::new(workers));
+        let mut worker_manager = WorkerManager::new(workers)
+            .with_resolve_margin(Duration::from_secs(self.resolve_margin_seconds));
+        if let Some(cache_config) = &self.work_cache {
+            let cache = match &self.work_cache_path {
+                Some(path) => WorkCache::load(cache_config.clone(), path)?,
+                None => WorkCache::new(cache_config.clone()),
+            };
+            worker_manager = worker_manager.with_cache(Arc::new(cache));
+        }
+        let worker_manager = Arc::new(worker_manager);
+        let artifact_registry = self
+            .artifact_registry_path
+            .as_ref()
+            .map(|path| ArtifactRegistry::load(path).map(Arc::new))
+            .transpose()?;
+        let bid_store = self
+            .bid_store_path
+            .as_ref()
+            .map(|path| BidStore::load(path).map(Arc::new))
+            .transpose()?;
 
-        ProviderStreamingConfig {
+        Ok(ProviderStreamingConfig {
             worker_manager,
             validation_config: self.validation_config.clone(),
-        }
+            frame_compression: self.frame_compression,
+            sse_fallback: self.sse_fallback,
+            reward_tokens: self.reward_tokens.clone(),
+            confirmations: self.confirmations.clone(),
+            bidding_mode: self.bidding_mode.clone(),
+            artifact_registry,
+            bid_store,
+        })
     }
 }
 
@@ -114,6 +251,9 @@ pub struct RequesterSearcherConfig {
 pub struct RequesterRequestingConfig {
     pub system_id: SystemId,
     pub validation_config: RequestValidationConfig,
+    /// confirmation depth of the tracked bid and resolve events
+    #[serde(default)]
+    pub confirmations: Confirmations,
 }
 
 #[derive(Clone, Debug, Deserialize)]
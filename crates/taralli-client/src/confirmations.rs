@@ -0,0 +1,176 @@
+//! Confirmation depth of the transactions bidders, resolvers and trackers rely on.
+//!
+//! A transaction included in the latest block can still be dropped by a reorg, taking its bid or
+//! resolve event with it. With a depth of N, a transaction only counts once its block is N blocks
+//! deep, and its receipt is fetched again at that point to make sure it's still part of the chain.
+//! The default depth of 1 trusts the first receipt, without any extra rpc call.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use taralli_primitives::alloy::network::{Network, ReceiptResponse};
+use taralli_primitives::alloy::primitives::B256;
+use taralli_primitives::alloy::providers::Provider;
+use taralli_primitives::alloy::rpc::types::Log;
+use taralli_primitives::alloy::transports::Transport;
+use tokio::time::sleep;
+
+use crate::api::retry::RetryPolicy;
+use crate::error::{ClientError, Result};
+use crate::rpc::retry_rpc;
+
+/// Depth transactions are trusted at unless configured otherwise, their inclusion block
+pub const DEFAULT_CONFIRMATIONS: u64 = 1;
+/// How often the chain head is polled while waiting for confirmations
+pub const DEFAULT_CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+fn default_confirmations() -> u64 {
+    DEFAULT_CONFIRMATIONS
+}
+
+fn default_poll_interval_ms() -> u64 {
+    DEFAULT_CONFIRMATION_POLL_INTERVAL.as_millis() as u64
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Confirmations {
+    /// blocks a transaction's block has to be deep before it's trusted, counting its own block
+    #[serde(default = "default_confirmations")]
+    pub depth: u64,
+    /// milliseconds between two polls of the chain head
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+}
+
+impl Default for Confirmations {
+    fn default() -> Self {
+        Self {
+            depth: DEFAULT_CONFIRMATIONS,
+            poll_interval_ms: default_poll_interval_ms(),
+        }
+    }
+}
+
+impl Confirmations {
+    pub fn new(depth: u64) -> Self {
+        Self {
+            depth,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval_ms = poll_interval.as_millis() as u64;
+        self
+    }
+
+    #[must_use]
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms)
+    }
+
+    /// Whether transactions are trusted as soon as they're included
+    #[must_use]
+    pub fn is_immediate(&self) -> bool {
+        self.depth <= 1
+    }
+
+    /// Wait for the transaction `tx_hash` to be `depth` blocks deep, returning its receipt as of
+    /// then. `None` when the transaction is no longer part of the chain, i.e. it was reorged out.
+    /// The polls are attempted again as the default `RetryPolicy` allows when they fail
+    /// transiently, the confirmation settings being config values rather than a client component.
+    pub async fn wait_for<T, P, N>(
+        &self,
+        rpc_provider: &P,
+        tx_hash: B256,
+    ) -> Result<Option<N::ReceiptResponse>>
+    where
+        T: Transport + Clone,
+        P: Provider<T, N>,
+        N: Network,
+    {
+        let rpc_retry = RetryPolicy::default();
+        loop {
+            let Some(receipt) = retry_rpc(&rpc_retry, || async move {
+                rpc_provider.get_transaction_receipt(tx_hash).await
+            })
+            .await?
+            else {
+                return Ok(None);
+            };
+            // receipts of pending transactions have no block yet
+            if let Some(block_number) = receipt.block_number() {
+                let head = retry_rpc(&rpc_retry, || async move {
+                    rpc_provider.get_block_number().await
+                })
+                .await?;
+                if head.saturating_add(1) >= block_number.saturating_add(self.depth) {
+                    return Ok(Some(receipt));
+                }
+            }
+            sleep(self.poll_interval()).await;
+        }
+    }
+
+    /// Receipt of a transaction just sent, once confirmed. Fails with
+    /// `ClientError::TransactionReorged` when the transaction was reorged out meanwhile, and with
+    /// `ClientError::TransactionReverted` when it was included again but reverted this time.
+    pub async fn confirm_receipt<T, P, N>(
+        &self,
+        rpc_provider: &P,
+        receipt: N::ReceiptResponse,
+    ) -> Result<N::ReceiptResponse>
+    where
+        T: Transport + Clone,
+        P: Provider<T, N>,
+        N: Network,
+    {
+        if self.is_immediate() {
+            return Ok(receipt);
+        }
+        let tx_hash = receipt.transaction_hash();
+        let receipt = self
+            .wait_for(rpc_provider, tx_hash)
+            .await?
+            .ok_or(ClientError::TransactionReorged { tx_hash })?;
+        if !receipt.status() {
+            return Err(ClientError::TransactionReverted { tx_hash });
+        }
+        Ok(receipt)
+    }
+
+    /// Whether the transaction emitting a tracked event is still part of the chain, and
+    /// succeeded, once confirmed. Events are trusted when that can't be checked, as the event
+    /// stream wouldn't yield them again.
+    pub(crate) async fn confirm_log<T, P, N>(&self, rpc_provider: &P, log: &Log) -> bool
+    where
+        T: Transport + Clone,
+        P: Provider<T, N>,
+        N: Network,
+    {
+        if self.is_immediate() {
+            return true;
+        }
+        let Some(tx_hash) = log.transaction_hash else {
+            return true;
+        };
+        match self.wait_for::<T, P, N>(rpc_provider, tx_hash).await {
+            Ok(Some(receipt)) if receipt.status() => true,
+            Ok(_) => {
+                tracing::warn!(
+                    "transaction {} emitting the tracked event was reorged out, watching on",
+                    tx_hash
+                );
+                false
+            }
+            Err(e) => {
+                tracing::error!(
+                    "couldn't confirm transaction {} emitting the tracked event: {}",
+                    tx_hash,
+                    e
+                );
+                true
+            }
+        }
+    }
+}
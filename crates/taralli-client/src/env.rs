@@ -0,0 +1,202 @@
+//! Client configuration read from environment variables, for standing up a client in one call.
+//!
+//! Variables read:
+//! - `RPC_URL`: rpc of the chain the markets are deployed on
+//! - `SERVER_URL`: protocol server
+//! - `PRIVATE_KEY`: key of the account signing intents and transactions, the role specific
+//!   `REQUESTER_PRIVATE_KEY` or `PROVIDER_PRIVATE_KEY` taking precedence when set
+//! - `CHAIN_ID` (optional): chain the intents are signed for, sepolia by default
+//! - `MARKET_ADDRESS` (optional): market overriding the one deployed on `CHAIN_ID`
+//! - `DEPLOYMENTS_FILE` (optional): JSON file of deployments added to the built-in ones
+//! - `MAXIMUM_ALLOWED_STAKE` (optional): maximum stake of the requests validated, in wei
+
+use std::str::FromStr;
+
+use taralli_primitives::alloy::network::{Ethereum, EthereumWallet};
+use taralli_primitives::alloy::primitives::Address;
+use taralli_primitives::alloy::providers::fillers::{
+    BlobGasFiller, ChainIdFiller, FillProvider, GasFiller, JoinFill, NonceFiller, WalletFiller,
+};
+use taralli_primitives::alloy::providers::{Identity, ProviderBuilder, RootProvider};
+use taralli_primitives::alloy::signers::local::PrivateKeySigner;
+use taralli_primitives::alloy::transports::{Client, Http};
+use taralli_primitives::deployments::DeploymentRegistry;
+use taralli_primitives::markets::SEPOLIA_CHAIN_ID;
+use taralli_primitives::validation::request::RequestValidationConfig;
+use taralli_primitives::validation::BaseValidationConfig;
+use url::Url;
+
+use crate::error::{ClientError, Result};
+
+pub const RPC_URL_VAR: &str = "RPC_URL";
+pub const SERVER_URL_VAR: &str = "SERVER_URL";
+pub const PRIVATE_KEY_VAR: &str = "PRIVATE_KEY";
+pub const REQUESTER_PRIVATE_KEY_VAR: &str = "REQUESTER_PRIVATE_KEY";
+pub const PROVIDER_PRIVATE_KEY_VAR: &str = "PROVIDER_PRIVATE_KEY";
+pub const CHAIN_ID_VAR: &str = "CHAIN_ID";
+pub const MARKET_ADDRESS_VAR: &str = "MARKET_ADDRESS";
+pub const DEPLOYMENTS_FILE_VAR: &str = "DEPLOYMENTS_FILE";
+pub const MAXIMUM_ALLOWED_STAKE_VAR: &str = "MAXIMUM_ALLOWED_STAKE";
+
+/// Maximum stake of the requests validated when `MAXIMUM_ALLOWED_STAKE` isn't set, 10 ether
+pub const DEFAULT_MAXIMUM_ALLOWED_STAKE: u128 = 10_000_000_000_000_000_000;
+
+/// Rpc provider signing transactions with the configured key, with the recommended fillers
+pub type WalletProvider = FillProvider<
+    JoinFill<
+        JoinFill<
+            Identity,
+            JoinFill<GasFiller, JoinFill<BlobGasFiller, JoinFill<NonceFiller, ChainIdFiller>>>,
+        >,
+        WalletFiller<EthereumWallet>,
+    >,
+    RootProvider<Http<Client>>,
+    Http<Client>,
+    Ethereum,
+>;
+
+/// Configuration of a client read from the environment
+#[derive(Debug, Clone)]
+pub struct EnvConfig {
+    pub rpc_url: Url,
+    pub server_url: Url,
+    pub signer: PrivateKeySigner,
+    pub chain_id: u64,
+    /// `MARKET_ADDRESS` when set, the universal bombetta deployed on `chain_id` otherwise
+    pub market_address: Address,
+    pub maximum_allowed_stake: u128,
+}
+
+impl EnvConfig {
+    /// Read the configuration from the process environment, the private key from
+    /// `role_private_key_var` when set and `PRIVATE_KEY` otherwise
+    pub fn from_env(role_private_key_var: &str) -> Result<Self> {
+        Self::from_lookup(role_private_key_var, |name| std::env::var(name).ok())
+    }
+
+    /// Read the configuration from the variables `lookup` returns. Every missing or invalid
+    /// variable is reported at once in a `ClientError::EnvironmentError`.
+    pub fn from_lookup(
+        role_private_key_var: &str,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        let mut errors = Vec::new();
+        let mut required = |name: &str| {
+            let value = lookup(name).filter(|value| !value.is_empty());
+            if value.is_none() {
+                errors.push(format!("{name} is not set"));
+            }
+            value
+        };
+        let rpc_url = required(RPC_URL_VAR);
+        let server_url = required(SERVER_URL_VAR);
+        let (private_key_var, private_key) = match [role_private_key_var, PRIVATE_KEY_VAR]
+            .into_iter()
+            .find_map(|name| Some(name).zip(lookup(name).filter(|value| !value.is_empty())))
+        {
+            Some((name, private_key)) => (name, Some(private_key)),
+            None => {
+                errors.push(format!(
+                    "neither {role_private_key_var} nor {PRIVATE_KEY_VAR} is set"
+                ));
+                (PRIVATE_KEY_VAR, None)
+            }
+        };
+
+        let rpc_url = rpc_url.and_then(|value| parse(&mut errors, RPC_URL_VAR, &value));
+        let server_url = server_url.and_then(|value| parse(&mut errors, SERVER_URL_VAR, &value));
+        let signer = private_key.and_then(|value| {
+            PrivateKeySigner::from_str(&value)
+                .map_err(|_| errors.push(format!("{private_key_var} is not a valid private key")))
+                .ok()
+        });
+        let chain_id = match lookup(CHAIN_ID_VAR) {
+            Some(value) => parse(&mut errors, CHAIN_ID_VAR, &value),
+            None => Some(SEPOLIA_CHAIN_ID),
+        };
+        let maximum_allowed_stake = match lookup(MAXIMUM_ALLOWED_STAKE_VAR) {
+            Some(value) => parse(&mut errors, MAXIMUM_ALLOWED_STAKE_VAR, &value),
+            None => Some(DEFAULT_MAXIMUM_ALLOWED_STAKE),
+        };
+        let market_address = match lookup(MARKET_ADDRESS_VAR) {
+            Some(value) => parse(&mut errors, MARKET_ADDRESS_VAR, &value),
+            // the deployment is only looked up when the market isn't set
+            None => chain_id.and_then(|chain_id| {
+                let registry = match lookup(DEPLOYMENTS_FILE_VAR) {
+                    Some(path) => DeploymentRegistry::builtin().with_overrides_file(path),
+                    None => Ok(DeploymentRegistry::builtin()),
+                };
+                registry
+                    .and_then(|registry| {
+                        registry
+                            .get(chain_id)
+                            .map(|deployment| deployment.universal_bombetta)
+                    })
+                    .map_err(|e| {
+                        errors.push(format!(
+                            "{MARKET_ADDRESS_VAR} is not set and no market was found: {e}"
+                        ))
+                    })
+                    .ok()
+            }),
+        };
+
+        match (
+            rpc_url,
+            server_url,
+            signer,
+            chain_id,
+            market_address,
+            maximum_allowed_stake,
+        ) {
+            (
+                Some(rpc_url),
+                Some(server_url),
+                Some(signer),
+                Some(chain_id),
+                Some(market_address),
+                Some(maximum_allowed_stake),
+            ) if errors.is_empty() => Ok(Self {
+                rpc_url,
+                server_url,
+                signer,
+                chain_id,
+                market_address,
+                maximum_allowed_stake,
+            }),
+            _ => Err(ClientError::EnvironmentError(errors)),
+        }
+    }
+
+    /// Rpc provider signing transactions with the configured key
+    pub fn rpc_provider(&self) -> WalletProvider {
+        ProviderBuilder::new()
+            .with_recommended_fillers()
+            .wallet(EthereumWallet::new(self.signer.clone()))
+            .on_http(self.rpc_url.clone())
+    }
+
+    /// Default validation config of requests, for the configured chain and maximum stake
+    #[must_use]
+    pub fn request_validation_config(&self) -> RequestValidationConfig {
+        RequestValidationConfig {
+            base: BaseValidationConfig {
+                chain_id: self.chain_id,
+                ..Default::default()
+            },
+            maximum_allowed_stake: self.maximum_allowed_stake,
+            ..Default::default()
+        }
+    }
+}
+
+/// Parse the value of the variable `name`, recording why when it's invalid
+fn parse<T: FromStr>(errors: &mut Vec<String>, name: &str, value: &str) -> Option<T>
+where
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse()
+        .map_err(|e| errors.push(format!("{name} is invalid: {e}")))
+        .ok()
+}
@@ -1,3 +1,8 @@
+use crate::rpc::RpcErrorKind;
+use taralli_primitives::alloy::primitives::{Address, B256, U256};
+use taralli_primitives::error::ErrorCode;
+use taralli_primitives::systems::SystemId;
+use taralli_primitives::validation::violation::Violation;
 use taralli_primitives::PrimitivesError;
 use thiserror::Error;
 
@@ -5,6 +10,10 @@ use thiserror::Error;
 pub enum ClientError {
     #[error("Config error: {0}")]
     ConfigError(String),
+    #[error("Invalid environment: {}", .0.join("; "))]
+    EnvironmentError(Vec<String>),
+    #[error("Startup checks failed: {}", .0.join("; "))]
+    HealthcheckFailed(Vec<String>),
     #[error("Failed to setup bid: {0}")]
     TransactionSetupError(String),
     #[error("Failed to setup event filter: {0}")]
@@ -15,12 +24,29 @@ pub enum ClientError {
     ServerSubscriptionError(String),
     #[error("Failed intent analysis: {0}")]
     IntentAnalysisError(String),
+    #[error("Intent vetoed by pre-bid hook: {0}")]
+    IntentVetoed(String),
+    #[error("Reward token {token} rejected: {reason}")]
+    RewardTokenRejected { token: Address, reason: String },
+    #[error("Reward of {reward} doesn't cover the cost of {cost}")]
+    UnprofitableIntent { reward: U256, cost: U256 },
     #[error("Failed deserialization: {0}")]
     DeserializationError(String),
     #[error("Worker failed with error: {0}")]
     WorkerError(String),
+    #[error("Remote prover quota exhausted: {0}")]
+    ProverQuotaExhausted(String),
+    #[error("Remote proof not fulfilled in time: {0}")]
+    ProofTimeout(String),
+    #[error("Worker of system {system_id:?} cancelled after exceeding its {budget_secs}s proving budget")]
+    WorkerTimeout {
+        system_id: SystemId,
+        budget_secs: u64,
+    },
     #[error("Client builder error: {0}")]
     BuilderError(String),
+    #[error("Intent template error: {0}")]
+    TemplateError(String),
     #[error("Failed to submit intent: {0}")]
     IntentSubmissionFailed(String),
     #[error("Query failed, no offers available for system: {0}")]
@@ -33,34 +59,217 @@ pub enum ClientError {
     TransactionError(String),
     #[error("Transaction failed: {0}")]
     TransactionFailure(String),
+    #[error("Transaction {tx_hash} reverted on-chain")]
+    TransactionReverted { tx_hash: B256 },
+    #[error(
+        "Transaction {tx_hash} was reorged out before reaching the configured confirmation depth"
+    )]
+    TransactionReorged { tx_hash: B256 },
+    #[error("Auction of intent {intent_id} already has a bid")]
+    AuctionAlreadyBid { intent_id: B256 },
+    #[error("Balance of {balance} wei doesn't cover the {required} wei a bid on intent {intent_id} needs")]
+    InsufficientFunds {
+        intent_id: B256,
+        balance: U256,
+        required: U256,
+    },
     #[error("Failed to parse logs: {0}")]
     LogParseError(String),
     #[error("Failed server request: {0}")]
     ServerRequestError(String),
+    #[error("Server rejected the request with status {status}: {message}")]
+    ServerRejected {
+        status: u16,
+        /// `None` when the server answered without a code, or with one unknown to this client
+        code: Option<ErrorCode>,
+        message: String,
+        /// every failed check of a submission rejected by validation, empty otherwise
+        violations: Vec<Violation>,
+    },
+    /// unclassified rpc failure, the client's own rpc calls fail with `RpcFailed` instead
     #[error("Failed rpc request: {0}")]
     RpcRequestError(String),
+    #[error("Failed rpc request ({kind}): {message}")]
+    RpcFailed { kind: RpcErrorKind, message: String },
     #[error("Failed intent signing: {0}")]
     IntentSigningError(String),
     #[error("Failed to parse server url: {0}")]
     ServerUrlParsingError(String),
     #[error("Failed to get permit2 nonce: {0}")]
     GetNonceError(String),
+    #[error("Permit2 nonce {nonce} of {signer} was already used")]
+    NonceAlreadyUsed { signer: Address, nonce: U256 },
+    #[error("Reward of {token} can't be pulled by {spender} through permit2: {}", .shortfalls.join("; "))]
+    Permit2AllowanceInsufficient {
+        token: Address,
+        spender: Address,
+        shortfalls: Vec<String>,
+    },
     #[error("Failed to find unused permit2 nonce for configured account")]
     FindUnusedNonceError(),
     #[error("Failed to set timestamps for intent, auction length is 0")]
     SetAuctionTimestampsError(),
+    #[error(
+        "Auction ended at {end_auction_timestamp}, before its release at chain time {chain_time}"
+    )]
+    ReleaseWindowPassed {
+        end_auction_timestamp: u64,
+        chain_time: u64,
+    },
+    #[error("Chain time {chain_time} reached the deadline {deadline} of a wait for {timestamp}")]
+    WaitDeadlinePassed {
+        timestamp: u64,
+        deadline: u64,
+        chain_time: u64,
+    },
     #[error("Auction timed out with no Bids")]
     AuctionTimeoutError(),
     #[error("Validation error: {0}")]
     ValidationError(String),
+    #[error("Validation failed: {}", .0.join("; "))]
+    ValidationViolations(Vec<String>),
     #[error("Primitives error: {0}")]
     PrimitivesError(#[from] PrimitivesError),
     #[error("API key error: {0}")]
     ApiKeyError(String),
     #[error("Invalid client mode: {0}")]
     InvalidMode(String),
+    #[error("Key exchange error: {0}")]
+    KeyExchangeError(String),
+    #[error("Offer inputs error: {0}")]
+    OfferInputsError(String),
+    #[error("Extension error: {0}")]
+    ExtensionError(String),
+    #[error("Backtest error: {0}")]
+    BacktestError(String),
+    #[error("Review endpoint error: {0}")]
+    ReviewEndpointError(String),
+    #[error("Control endpoint error: {0}")]
+    ControlEndpointError(String),
     #[error("Provider search is not implemented, error")]
     ProviderSearchingUnimplemented,
 }
 
 pub type Result<T> = core::result::Result<T, ClientError>;
+
+impl ClientError {
+    /// Stable machine readable code of the error, unique to its variant
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            ClientError::ConfigError(_) => "config",
+            ClientError::EnvironmentError(_) => "environment",
+            ClientError::HealthcheckFailed(_) => "healthcheck_failed",
+            ClientError::TransactionSetupError(_) => "transaction_setup",
+            ClientError::EventFilterError(_) => "event_filter",
+            ClientError::IntentParsingError(_) => "intent_parsing",
+            ClientError::ServerSubscriptionError(_) => "server_subscription",
+            ClientError::IntentAnalysisError(_) => "intent_analysis",
+            ClientError::IntentVetoed(_) => "intent_vetoed",
+            ClientError::RewardTokenRejected { .. } => "reward_token_rejected",
+            ClientError::UnprofitableIntent { .. } => "unprofitable_intent",
+            ClientError::DeserializationError(_) => "deserialization",
+            ClientError::WorkerError(_) => "worker",
+            ClientError::ProverQuotaExhausted(_) => "prover_quota_exhausted",
+            ClientError::ProofTimeout(_) => "proof_timeout",
+            ClientError::WorkerTimeout { .. } => "worker_timeout",
+            ClientError::BuilderError(_) => "builder",
+            ClientError::TemplateError(_) => "template",
+            ClientError::IntentSubmissionFailed(_) => "intent_submission_failed",
+            ClientError::NoOffersAvailable(_) => "no_offers_available",
+            ClientError::IntentDecompressionFailed(_) => "intent_decompression_failed",
+            ClientError::TrackIntentError(_) => "track_intent",
+            ClientError::TransactionError(_) => "transaction",
+            ClientError::TransactionFailure(_) => "transaction_failure",
+            ClientError::TransactionReverted { .. } => "transaction_reverted",
+            ClientError::TransactionReorged { .. } => "transaction_reorged",
+            ClientError::AuctionAlreadyBid { .. } => "auction_already_bid",
+            ClientError::InsufficientFunds { .. } => "insufficient_funds",
+            ClientError::LogParseError(_) => "log_parse",
+            ClientError::ServerRequestError(_) => "server_request",
+            ClientError::ServerRejected { .. } => "server_rejected",
+            ClientError::RpcRequestError(_) => "rpc_request",
+            ClientError::RpcFailed { .. } => "rpc_failed",
+            ClientError::IntentSigningError(_) => "intent_signing",
+            ClientError::ServerUrlParsingError(_) => "server_url_parsing",
+            ClientError::GetNonceError(_) => "get_nonce",
+            ClientError::NonceAlreadyUsed { .. } => "nonce_already_used",
+            ClientError::Permit2AllowanceInsufficient { .. } => "permit2_allowance_insufficient",
+            ClientError::FindUnusedNonceError() => "find_unused_nonce",
+            ClientError::SetAuctionTimestampsError() => "set_auction_timestamps",
+            ClientError::ReleaseWindowPassed { .. } => "release_window_passed",
+            ClientError::WaitDeadlinePassed { .. } => "wait_deadline_passed",
+            ClientError::AuctionTimeoutError() => "auction_timeout",
+            ClientError::ValidationError(_) => "validation",
+            ClientError::ValidationViolations(_) => "validation_violations",
+            ClientError::PrimitivesError(_) => "primitives",
+            ClientError::ApiKeyError(_) => "api_key",
+            ClientError::InvalidMode(_) => "invalid_mode",
+            ClientError::KeyExchangeError(_) => "key_exchange",
+            ClientError::OfferInputsError(_) => "offer_inputs",
+            ClientError::ExtensionError(_) => "extension",
+            ClientError::BacktestError(_) => "backtest",
+            ClientError::ReviewEndpointError(_) => "review_endpoint",
+            ClientError::ControlEndpointError(_) => "control_endpoint",
+            ClientError::ProviderSearchingUnimplemented => "provider_searching_unimplemented",
+        }
+    }
+
+    /// Kind of the failed rpc call behind the error, if it is one
+    pub fn rpc_error_kind(&self) -> Option<RpcErrorKind> {
+        match self {
+            ClientError::RpcFailed { kind, .. } => Some(*kind),
+            _ => None,
+        }
+    }
+
+    /// Whether the error is an rpc failure that may pass once attempted again, so the intent it
+    /// failed can be requeued rather than dropped
+    pub fn is_transient(&self) -> bool {
+        self.rpc_error_kind()
+            .is_some_and(|kind| kind.is_retryable())
+    }
+
+    /// Code the server rejected the request with, if it did
+    pub fn server_code(&self) -> Option<ErrorCode> {
+        match self {
+            ClientError::ServerRejected { code, .. } => *code,
+            _ => None,
+        }
+    }
+
+    /// Failed checks the server rejected a submission for, if it did
+    pub fn violations(&self) -> &[Violation] {
+        match self {
+            ClientError::ServerRejected { violations, .. } => violations,
+            _ => &[],
+        }
+    }
+
+    /// Error of a server response with a non-success status, keeping the code and violations of
+    /// its JSON error body. Bodies that aren't one are kept as the message.
+    pub async fn from_server_response(response: reqwest::Response) -> Self {
+        let status = response.status().as_u16();
+        let text = response.text().await.unwrap_or_default();
+        let body = serde_json::from_str::<serde_json::Value>(&text).ok();
+        let message = body
+            .as_ref()
+            .and_then(|body| body["error"].as_str())
+            .map(str::to_string)
+            .unwrap_or(text);
+        let code = body
+            .as_ref()
+            .and_then(|body| body["code"].as_str())
+            .and_then(ErrorCode::parse);
+        let violations = body
+            .as_ref()
+            .and_then(|body| body.get("violations"))
+            .and_then(|violations| serde_json::from_value(violations.clone()).ok())
+            .unwrap_or_default();
+        ClientError::ServerRejected {
+            status,
+            code,
+            message,
+            violations,
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! Callbacks running custom logic at points of a provider's intent processing, e.g. consulting a
+//! capacity planner before bidding or notifying an accounting service after a resolve.
+
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_util::FutureExt;
+use taralli_primitives::alloy::primitives::{FixedBytes, TxHash};
+use tokio::task::JoinHandle;
+use tracing::Instrument;
+
+use crate::worker::WorkResult;
+
+/// time a hook gets to run a callback before it is given up on
+pub const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of a `pre_bid` hook
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BidDecision {
+    Proceed,
+    /// drop the intent without bidding, with the reason logged
+    Veto(String),
+}
+
+/// Hook called while a provider processes an intent. Hooks only get shared references, so they
+/// can't alter the intent or the client's state. Every method defaults to a no-op.
+#[async_trait]
+pub trait ProviderHook<I: Send + Sync>: Send + Sync {
+    /// an intent arrived, before it is analyzed
+    async fn on_intent_received(&self, _intent_id: FixedBytes<32>, _intent: &I) {}
+
+    /// the intent passed analysis, returning a veto drops it before bidding
+    async fn pre_bid(&self, _intent_id: FixedBytes<32>, _intent: &I) -> BidDecision {
+        BidDecision::Proceed
+    }
+
+    /// the worker produced the proof, before it is resolved
+    async fn post_proof(&self, _intent_id: FixedBytes<32>, _intent: &I, _result: &WorkResult) {}
+
+    /// the resolve transaction was included
+    async fn post_resolve(&self, _intent_id: FixedBytes<32>, _intent: &I, _resolve_tx: TxHash) {}
+}
+
+/// Hooks of a provider client, called in registration order. A panicking hook is logged and
+/// doesn't take the client down, in `pre_bid` it counts as a veto. Each hook gets a timeout per
+/// callback, a `pre_bid` timing out counts as a veto unless configured otherwise.
+///
+/// The callbacks only observing the intent (`on_intent_received`, `post_proof`, `post_resolve`)
+/// run on a spawned task, so a slow hook doesn't hold up bidding or resolving. The returned
+/// handle completes once every hook ran.
+pub struct HookRegistry<I: Send + Sync> {
+    hooks: Vec<RegisteredHook<I>>,
+    veto_on_timeout: bool,
+}
+
+struct RegisteredHook<I: Send + Sync> {
+    hook: Arc<dyn ProviderHook<I>>,
+    timeout: Duration,
+}
+
+impl<I: Send + Sync> Clone for RegisteredHook<I> {
+    fn clone(&self) -> Self {
+        Self {
+            hook: self.hook.clone(),
+            timeout: self.timeout,
+        }
+    }
+}
+
+impl<I: Send + Sync> Default for HookRegistry<I> {
+    fn default() -> Self {
+        Self {
+            hooks: Vec::new(),
+            veto_on_timeout: true,
+        }
+    }
+}
+
+impl<I: Clone + Send + Sync + 'static> HookRegistry<I> {
+    /// Register a hook given `DEFAULT_HOOK_TIMEOUT` per callback
+    pub fn register(&mut self, hook: Arc<dyn ProviderHook<I>>) {
+        self.register_with_timeout(hook, DEFAULT_HOOK_TIMEOUT);
+    }
+
+    /// Register a hook given `timeout` per callback
+    pub fn register_with_timeout(&mut self, hook: Arc<dyn ProviderHook<I>>, timeout: Duration) {
+        self.hooks.push(RegisteredHook { hook, timeout });
+    }
+
+    /// Whether a `pre_bid` timing out vetoes the bid (the default) or lets the next hook decide
+    pub fn set_veto_on_timeout(&mut self, veto_on_timeout: bool) {
+        self.veto_on_timeout = veto_on_timeout;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    pub fn on_intent_received(&self, intent_id: FixedBytes<32>, intent: &I) -> JoinHandle<()> {
+        let (hooks, intent) = (self.hooks.clone(), intent.clone());
+        tokio::spawn(
+            async move {
+                for RegisteredHook { hook, timeout } in &hooks {
+                    let call = hook.on_intent_received(intent_id, &intent);
+                    observe("on_intent_received", intent_id, *timeout, call).await;
+                }
+            }
+            .in_current_span(),
+        )
+    }
+
+    /// First veto wins, the remaining hooks aren't called
+    pub async fn pre_bid(&self, intent_id: FixedBytes<32>, intent: &I) -> BidDecision {
+        for RegisteredHook { hook, timeout } in &self.hooks {
+            let call = AssertUnwindSafe(hook.pre_bid(intent_id, intent)).catch_unwind();
+            let decision = match tokio::time::timeout(*timeout, call).await {
+                Ok(Ok(decision)) => decision,
+                Ok(Err(_)) => BidDecision::Veto("pre_bid hook panicked".to_string()),
+                Err(_) if self.veto_on_timeout => {
+                    BidDecision::Veto(format!("pre_bid hook timed out after {timeout:?}"))
+                }
+                Err(_) => {
+                    tracing::warn!(
+                        "pre_bid hook timed out after {timeout:?} for intent {intent_id}, ignored"
+                    );
+                    continue;
+                }
+            };
+            if let BidDecision::Veto(_) = decision {
+                return decision;
+            }
+        }
+        BidDecision::Proceed
+    }
+
+    pub fn post_proof(
+        &self,
+        intent_id: FixedBytes<32>,
+        intent: &I,
+        result: &WorkResult,
+    ) -> JoinHandle<()> {
+        let (hooks, intent, result) = (self.hooks.clone(), intent.clone(), result.clone());
+        tokio::spawn(
+            async move {
+                for RegisteredHook { hook, timeout } in &hooks {
+                    let call = hook.post_proof(intent_id, &intent, &result);
+                    observe("post_proof", intent_id, *timeout, call).await;
+                }
+            }
+            .in_current_span(),
+        )
+    }
+
+    pub fn post_resolve(
+        &self,
+        intent_id: FixedBytes<32>,
+        intent: &I,
+        resolve_tx: TxHash,
+    ) -> JoinHandle<()> {
+        let (hooks, intent) = (self.hooks.clone(), intent.clone());
+        tokio::spawn(
+            async move {
+                for RegisteredHook { hook, timeout } in &hooks {
+                    let call = hook.post_resolve(intent_id, &intent, resolve_tx);
+                    observe("post_resolve", intent_id, *timeout, call).await;
+                }
+            }
+            .in_current_span(),
+        )
+    }
+}
+
+/// Run an observing callback, logging it panicking or timing out
+async fn observe(
+    callback: &str,
+    intent_id: FixedBytes<32>,
+    timeout: Duration,
+    call: impl Future<Output = ()>,
+) {
+    match tokio::time::timeout(timeout, AssertUnwindSafe(call).catch_unwind()).await {
+        Ok(Ok(())) => {}
+        Ok(Err(_)) => tracing::error!("{callback} hook panicked for intent {intent_id}"),
+        Err(_) => {
+            tracing::warn!("{callback} hook timed out after {timeout:?} for intent {intent_id}")
+        }
+    }
+}
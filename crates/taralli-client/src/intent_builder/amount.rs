@@ -0,0 +1,76 @@
+//! Conversion of token amounts between human units, e.g. `1.5` tokens, and the raw units of a
+//! token with a given number of decimals that intents carry.
+
+use taralli_primitives::alloy::primitives::U256;
+
+use crate::error::{ClientError, Result};
+
+/// Raw units of a token with `decimals` decimals worth `amount` whole tokens.
+///
+/// `amount` is taken at its shortest decimal representation, so `0.1` is exactly one tenth of a
+/// token. NaN, infinite and negative amounts are rejected, as are amounts with more fractional
+/// digits than the token has decimals and amounts not fitting a U256 once scaled.
+pub fn parse_decimal_amount(amount: f64, decimals: u8) -> Result<U256> {
+    if !amount.is_finite() {
+        return Err(ClientError::BuilderError(format!(
+            "token amount {amount} isn't a finite number"
+        )));
+    }
+    if amount < 0.0 {
+        return Err(ClientError::BuilderError(format!(
+            "token amount {amount} is negative"
+        )));
+    }
+    // Display of f64 is the shortest representation parsing back to the same value, without
+    // exponent. abs() drops the sign of -0.0.
+    let repr = amount.abs().to_string();
+    let (integer, fraction) = repr.split_once('.').unwrap_or((&repr, ""));
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > usize::from(decimals) {
+        return Err(ClientError::BuilderError(format!(
+            "token amount {repr} has more than the token's {decimals} decimals"
+        )));
+    }
+
+    let overflow = || {
+        ClientError::BuilderError(format!(
+            "token amount {repr} with {decimals} decimals overflows a U256"
+        ))
+    };
+    let scale = U256::from(10u8)
+        .checked_pow(U256::from(decimals))
+        .ok_or_else(overflow)?;
+    let integer = U256::from_str_radix(integer, 10).map_err(|_| overflow())?;
+    let fraction = if fraction.is_empty() {
+        U256::ZERO
+    } else {
+        let fraction_scale =
+            U256::from(10u8).pow(U256::from(usize::from(decimals) - fraction.len()));
+        U256::from_str_radix(fraction, 10)
+            .map_err(|_| overflow())?
+            .checked_mul(fraction_scale)
+            .ok_or_else(overflow)?
+    };
+    integer
+        .checked_mul(scale)
+        .and_then(|scaled| scaled.checked_add(fraction))
+        .ok_or_else(overflow)
+}
+
+/// Decimal representation of `raw` units of a token with `decimals` decimals, without trailing
+/// zeros, e.g. `1.5` for 1500000 units of a 6 decimals token.
+pub fn format_amount(raw: U256, decimals: u8) -> String {
+    let digits = raw.to_string();
+    let decimals = usize::from(decimals);
+    if decimals == 0 {
+        return digits;
+    }
+    let padded = format!("{digits:0>width$}", width = decimals + 1);
+    let (integer, fraction) = padded.split_at(padded.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{integer}.{fraction}")
+    }
+}
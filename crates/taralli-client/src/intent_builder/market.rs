@@ -0,0 +1,234 @@
+//! Parameters of a market contract read from chain, used to seed the intent builders with the
+//! values the market will check instead of relying on them being supplied by hand.
+//!
+//! The markets only expose the permit2 contract they settle through (a constructor argument) and
+//! the witness type hash their intents are signed with, so these are checked against the ones the
+//! client signs with before anything is built. They don't expose the protocol fee they take on
+//! rewards either, which is read from the deployments registry instead.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use taralli_primitives::abi::{
+    universal_bombetta::UniversalBombetta::UniversalBombettaInstance,
+    universal_porchetta::UniversalPorchetta::UniversalPorchettaInstance,
+};
+use taralli_primitives::alloy::{
+    network::Network,
+    primitives::{Address, B256},
+    providers::Provider,
+    transports::Transport,
+};
+use taralli_primitives::deployments::DeploymentRegistry;
+use taralli_primitives::intents::{
+    offer::PROOF_OFFER_WITNESS_TYPE_HASH, request::PROOF_REQUEST_WITNESS_TYPE_HASH,
+};
+use taralli_primitives::markets::MarketFee;
+use taralli_primitives::utils::PERMIT2_ADDRESS;
+
+use crate::api::retry::RetryPolicy;
+use crate::error::{ClientError, Result};
+use crate::rpc::{classify_rpc_error, retry_rpc, RpcErrorKind};
+
+/// how long fetched market params are reused by default
+pub const DEFAULT_MARKET_PARAMS_TTL: Duration = Duration::from_secs(600);
+
+/// Kind of intents a market settles
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarketKind {
+    /// `UniversalBombetta`, settling `ComputeRequest`s
+    Request,
+    /// `UniversalPorchetta`, settling `ComputeOffer`s
+    Offer,
+}
+
+/// Public parameters of a deployed market
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MarketParams {
+    pub market_address: Address,
+    pub kind: MarketKind,
+    /// chain the market is deployed on
+    pub chain_id: u64,
+    /// permit2 contract the market transfers rewards and stakes through
+    pub permit2: Address,
+    /// type hash of the witness the market's intents are signed with
+    pub witness_type_hash: B256,
+    /// protocol fee the market takes out of the rewards it pays
+    pub fee: MarketFee,
+}
+
+impl MarketParams {
+    /// Read the parameters of the market at `market_address`, telling request and offer markets
+    /// apart by the witness type hash getter they expose. The fee is left at zero, see
+    /// `with_registry_fee`.
+    pub async fn fetch<T, P, N>(rpc_provider: &P, market_address: Address) -> Result<Self>
+    where
+        T: Transport + Clone + Send + Sync,
+        P: Provider<T, N> + Clone + Send + Sync,
+        N: Network + Clone + Send + Sync,
+    {
+        Self::fetch_with_rpc_retry(rpc_provider, market_address, &RetryPolicy::default()).await
+    }
+
+    /// `fetch`, attempting the rpc calls again as `rpc_retry` allows when they fail transiently.
+    /// A getter reverting tells the market kind apart and isn't attempted again.
+    pub async fn fetch_with_rpc_retry<T, P, N>(
+        rpc_provider: &P,
+        market_address: Address,
+        rpc_retry: &RetryPolicy,
+    ) -> Result<Self>
+    where
+        T: Transport + Clone + Send + Sync,
+        P: Provider<T, N> + Clone + Send + Sync,
+        N: Network + Clone + Send + Sync,
+    {
+        let chain_id = retry_rpc(
+            rpc_retry,
+            || async move { rpc_provider.get_chain_id().await },
+        )
+        .await?;
+
+        let bombetta = &UniversalBombettaInstance::new(market_address, rpc_provider.clone());
+        let porchetta = &UniversalPorchettaInstance::new(market_address, rpc_provider.clone());
+        let request_hash = retry_rpc(rpc_retry, || async move {
+            match bombetta.PROOF_REQUEST_WITNESS_TYPE_HASH().call().await {
+                Ok(hash) => Ok(Some(hash._0)),
+                Err(e) if reverted(&e) => Ok(None),
+                Err(e) => Err(e),
+            }
+        })
+        .await?;
+        let (kind, witness_type_hash) = match request_hash {
+            Some(hash) => (MarketKind::Request, hash),
+            None => {
+                let offer_hash = retry_rpc(rpc_retry, || async move {
+                    match porchetta.PROOF_OFFER_WITNESS_TYPE_HASH().call().await {
+                        Ok(hash) => Ok(Some(hash._0)),
+                        Err(e) if reverted(&e) => Ok(None),
+                        Err(e) => Err(e),
+                    }
+                })
+                .await?;
+                let hash = offer_hash.ok_or_else(|| {
+                    ClientError::BuilderError(format!(
+                        "{market_address} is neither a request nor an offer market"
+                    ))
+                })?;
+                (MarketKind::Offer, hash)
+            }
+        };
+        // both markets share the permit2 getter
+        let permit2 = retry_rpc(rpc_retry, || async move { bombetta.PERMIT2().call().await })
+            .await?
+            ._0;
+
+        Ok(Self {
+            market_address,
+            kind,
+            chain_id,
+            permit2,
+            witness_type_hash,
+            fee: MarketFee::ZERO,
+        })
+    }
+
+    /// Set the fee of the market to the one of its deployment in `deployments`, failing when
+    /// the registry has no deployment of the market's chain including it
+    pub fn with_registry_fee(mut self, deployments: &DeploymentRegistry) -> Result<Self> {
+        self.fee = deployments
+            .get(self.chain_id)?
+            .market_fee(self.market_address)
+            .ok_or_else(|| {
+                ClientError::BuilderError(format!(
+                    "market {} isn't part of the deployment of chain {}",
+                    self.market_address, self.chain_id
+                ))
+            })?;
+        Ok(self)
+    }
+
+    /// Check the market settles intents of `kind` signed the way this client signs them
+    pub fn ensure_compatible(&self, kind: MarketKind) -> Result<()> {
+        if self.kind != kind {
+            return Err(ClientError::BuilderError(format!(
+                "market {} settles {:?} intents, not {kind:?} intents",
+                self.market_address, self.kind
+            )));
+        }
+        if self.permit2 != PERMIT2_ADDRESS {
+            return Err(ClientError::BuilderError(format!(
+                "market {} uses permit2 at {}, intents are signed for {PERMIT2_ADDRESS}",
+                self.market_address, self.permit2
+            )));
+        }
+        let expected = match kind {
+            MarketKind::Request => *PROOF_REQUEST_WITNESS_TYPE_HASH,
+            MarketKind::Offer => *PROOF_OFFER_WITNESS_TYPE_HASH,
+        };
+        if self.witness_type_hash != expected {
+            return Err(ClientError::BuilderError(format!(
+                "market {} expects witness type hash {}, intents are signed with {expected}",
+                self.market_address, self.witness_type_hash
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Whether a getter call failed by reverting, i.e. the contract doesn't expose it
+fn reverted(error: &impl std::fmt::Display) -> bool {
+    classify_rpc_error(&error.to_string()) == RpcErrorKind::Revert
+}
+
+/// Market params fetched per market address, reused until they are older than the ttl
+pub struct MarketParamsCache {
+    entries: Mutex<HashMap<Address, (Instant, MarketParams)>>,
+    ttl: Duration,
+}
+
+impl Default for MarketParamsCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_MARKET_PARAMS_TTL)
+    }
+}
+
+impl MarketParamsCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+        }
+    }
+
+    /// Params of the market, fetched if they aren't cached or are older than the ttl. Rpc
+    /// failures are not cached.
+    pub async fn get<T, P, N>(
+        &self,
+        rpc_provider: &P,
+        market_address: Address,
+    ) -> Result<MarketParams>
+    where
+        T: Transport + Clone + Send + Sync,
+        P: Provider<T, N> + Clone + Send + Sync,
+        N: Network + Clone + Send + Sync,
+    {
+        if let Some((fetched_at, params)) = self
+            .entries
+            .lock()
+            .expect("market params cache lock poisoned")
+            .get(&market_address)
+        {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(params.clone());
+            }
+        }
+
+        let params = MarketParams::fetch(rpc_provider, market_address).await?;
+        self.entries
+            .lock()
+            .expect("market params cache lock poisoned")
+            .insert(market_address, (Instant::now(), params.clone()));
+        Ok(params)
+    }
+}
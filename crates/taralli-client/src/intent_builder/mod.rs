@@ -1,22 +1,26 @@
+pub mod amount;
+pub mod market;
 pub mod offer;
+pub mod release;
 pub mod request;
+pub mod sharded;
+pub mod template;
 
 use serde_json::Value;
+use std::sync::{Arc, OnceLock};
 use taralli_primitives::alloy::primitives::{Address, Bytes, PrimitiveSignature, B256, U256};
-use taralli_primitives::alloy::{
-    consensus::BlockHeader,
-    eips::BlockId,
-    network::{BlockResponse, BlockTransactionsKind, Network},
-    providers::Provider,
-    transports::Transport,
-};
+use taralli_primitives::alloy::{network::Network, providers::Provider, transports::Transport};
 use taralli_primitives::systems::SystemId;
-use taralli_primitives::systems::SystemParams;
+use taralli_primitives::systems::{SystemInputs, SystemParams};
 
 use crate::{
+    api::retry::RetryPolicy,
+    clock::ChainClock,
     error::{ClientError, Result},
     nonce_manager::Permit2NonceManager,
+    rpc::retry_rpc,
 };
+use market::MarketParams;
 
 /// core builder trait
 pub trait IntentBuilder {
@@ -44,6 +48,11 @@ where
     permit2_nonce_manager: Permit2NonceManager<T, P, N>,
     signer_address: Address,
     auction_length: u32,
+    // chain id of the rpc provider, fetched once and shared across builder clones
+    chain_id: Arc<OnceLock<u64>>,
+    // chain time auction timestamps are set from
+    clock: ChainClock<T, P, N>,
+    rpc_retry: RetryPolicy,
     // general proof commitment params
     pub market_address: Address,
     pub nonce: U256,
@@ -57,7 +66,8 @@ where
     // general system params
     system_id: SystemId,
     system: serde_json::Value,
-    pub inputs: Vec<u8>,
+    // inputs set apart from the rest of the system params, overriding `system["inputs"]`
+    inputs: Option<SystemInputs>,
 }
 
 impl<T, P, N> BaseIntentBuilder<T, P, N>
@@ -76,10 +86,13 @@ where
         let permit2_nonce_manager = Permit2NonceManager::new(rpc_provider.clone(), signer_address);
 
         Self {
+            clock: ChainClock::new(rpc_provider.clone()),
             rpc_provider,
             permit2_nonce_manager,
+            rpc_retry: RetryPolicy::default(),
             signer_address,
             auction_length: 0u32,
+            chain_id: Arc::new(OnceLock::new()),
             market_address,
             nonce: U256::ZERO,
             reward_token_address: Address::ZERO,
@@ -91,8 +104,46 @@ where
             extra_data: Bytes::from(""),
             system_id,
             system: Value::Null,
-            inputs: vec![],
+            inputs: None,
+        }
+    }
+
+    /// chain id the built intents are signed for, fetched from the rpc provider on first use
+    pub async fn chain_id(&self) -> Result<u64> {
+        if let Some(chain_id) = self.chain_id.get() {
+            return Ok(*chain_id);
         }
+        let rpc_provider = &self.rpc_provider;
+        let chain_id = retry_rpc(&self.rpc_retry, || async move {
+            rpc_provider.get_chain_id().await
+        })
+        .await?;
+        Ok(*self.chain_id.get_or_init(|| chain_id))
+    }
+
+    /// return the `IntentBuilder` targeting the market described by `params`, with the chain id
+    /// it is deployed on. Fails if the market doesn't settle intents signed the way this client
+    /// signs them.
+    pub fn with_market_defaults(mut self, params: &MarketParams) -> Result<Self> {
+        params.ensure_compatible(params.kind)?;
+        self.market_address = params.market_address;
+        self.chain_id = Arc::new(OnceLock::from(params.chain_id));
+        Ok(self)
+    }
+
+    /// return the `IntentBuilder` reading the chain time from `clock`, e.g. the one of the client
+    /// it builds intents for
+    pub fn with_clock(mut self, clock: ChainClock<T, P, N>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// attempt the rpc calls fetching the chain id and nonces again as `rpc_retry` allows when
+    /// they fail transiently. The clock is left as is, being shared with the client.
+    pub fn with_rpc_retry(mut self, rpc_retry: RetryPolicy) -> Self {
+        self.permit2_nonce_manager = self.permit2_nonce_manager.with_rpc_retry(rpc_retry.clone());
+        self.rpc_retry = rpc_retry;
+        self
     }
 
     /// return the `RequestBuilder` with the added permit2 nonce
@@ -105,6 +156,14 @@ where
         Ok(self)
     }
 
+    /// fetch `count` distinct unused permit2 nonces for intents signed together
+    pub async fn get_new_nonces(&mut self, count: usize) -> Result<Vec<U256>> {
+        self.permit2_nonce_manager
+            .get_nonces(count)
+            .await
+            .map_err(|e| ClientError::GetNonceError(e.to_string()))
+    }
+
     /// return the `RequestBuilder` with the added auction timestamps based on auction length
     /// and the current latest block timestamp
     pub async fn set_auction_timestamps_from_auction_length(mut self) -> Result<Self> {
@@ -125,19 +184,26 @@ where
         &self,
         auction_length: u32,
     ) -> Result<(u64, u64)> {
-        let latest_block = self
-            .rpc_provider
-            .get_block(BlockId::latest(), BlockTransactionsKind::Hashes)
-            .await
-            .map_err(|e| ClientError::RpcRequestError(e.to_string()))?
-            .ok_or_else(|| ClientError::RpcRequestError("Latest block not found".to_string()))?;
-
-        let start_auction_timestamp = latest_block.header().timestamp();
+        let start_auction_timestamp = self.latest_timestamp().await?;
         let end_auction_timestamp = start_auction_timestamp + u64::from(auction_length);
 
         Ok((start_auction_timestamp, end_auction_timestamp))
     }
 
+    /// timestamp of the latest block, as told by the builder's clock
+    pub async fn latest_timestamp(&self) -> Result<u64> {
+        self.clock.now().await
+    }
+
+    /// fail with every violation found, if any
+    fn ensure_no_violations(violations: Vec<String>) -> Result<()> {
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(ClientError::ValidationViolations(violations))
+        }
+    }
+
     /// return the `IntentBuilder` with the added auction time parameters
     pub fn set_time_params(
         mut self,
@@ -162,8 +228,24 @@ where
         self
     }
 
+    /// build the system params, rejecting params whose shape doesn't match the system id
     pub fn build_system(&self) -> Result<SystemParams> {
-        SystemParams::try_from((&self.system_id, self.system.to_string().into_bytes()))
+        let mut system = self.system.clone();
+        if let Some(inputs) = self.inputs.clone() {
+            let inputs = inputs
+                .into_params_value(self.system_id)
+                .map_err(|e| ClientError::BuilderError(e.to_string()))?;
+            if system.is_null() {
+                system = Value::Object(serde_json::Map::new());
+            }
+            system
+                .as_object_mut()
+                .ok_or_else(|| {
+                    ClientError::BuilderError("system params must be a JSON object".to_string())
+                })?
+                .insert("inputs".to_string(), inputs);
+        }
+        SystemParams::try_from((&self.system_id, system.to_string().into_bytes()))
             .map_err(|e| ClientError::BuilderError(e.to_string()))
     }
 
@@ -229,8 +311,18 @@ where
         self
     }
 
-    pub fn inputs(mut self, inputs: Vec<u8>) -> Self {
-        self.inputs = inputs;
+    pub fn inputs(mut self, inputs: SystemInputs) -> Self {
+        self.inputs = Some(inputs);
         self
     }
+
+    /// inputs of systems proving programs, e.g. abi encoded risc0 guest inputs
+    pub fn inputs_bytes(self, inputs: Vec<u8>) -> Self {
+        self.inputs(SystemInputs::Bytes(inputs))
+    }
+
+    /// inputs of systems proving circuits, e.g. the arkworks circom signals object
+    pub fn inputs_json(self, inputs: Value) -> Self {
+        self.inputs(SystemInputs::Json(inputs))
+    }
 }
@@ -1,4 +1,5 @@
 use serde_json::Value;
+use std::sync::{Arc, OnceLock};
 use taralli_primitives::abi::universal_porchetta::UniversalPorchetta::ProofOffer;
 use taralli_primitives::alloy::{
     network::Network,
@@ -6,11 +7,20 @@ use taralli_primitives::alloy::{
     providers::Provider,
     transports::Transport,
 };
+use taralli_primitives::commitment::CommitmentHasher;
+use taralli_primitives::deployments::DeploymentRegistry;
 use taralli_primitives::intents::offer::ComputeOffer;
-use taralli_primitives::systems::{SystemId, SystemParams};
+use taralli_primitives::systems::{SystemId, SystemInputs, SystemParams};
+use taralli_primitives::validation::offer::{
+    ComputeOfferValidator, OfferValidationConfig, OfferVerifierConstraints,
+};
+use taralli_primitives::validation::IntentValidator;
 
+use super::amount::parse_decimal_amount;
+use super::market::{MarketKind, MarketParams};
 use super::{BaseIntentBuilder, IntentBuilder};
-use crate::error::Result;
+use crate::clock::ChainClock;
+use crate::error::{ClientError, Result};
 use crate::nonce_manager::Permit2NonceManager;
 
 /// Intent builder for `ComputeOffers`
@@ -44,10 +54,12 @@ where
         // build permit2 nonce manager
         let permit2_nonce_manager = Permit2NonceManager::new(rpc_provider.clone(), signer_address);
         let base = BaseIntentBuilder {
+            clock: ChainClock::new(rpc_provider.clone()),
             rpc_provider,
             permit2_nonce_manager,
             signer_address,
             auction_length: 0u32,
+            chain_id: Arc::new(OnceLock::new()),
             market_address,
             nonce: U256::ZERO,
             reward_token_address: Address::ZERO,
@@ -59,7 +71,7 @@ where
             extra_data: Bytes::from(""),
             system_id,
             system: Value::Null,
-            inputs: vec![],
+            inputs: None,
         };
         Self {
             base,
@@ -70,6 +82,47 @@ where
         }
     }
 
+    /// build a `ComputeOfferBuilder` targeting the offer market deployed on `chain_id`, which
+    /// `market_address` still overrides
+    pub fn for_chain(
+        rpc_provider: P,
+        signer_address: Address,
+        chain_id: u64,
+        system_id: SystemId,
+        deployments: &DeploymentRegistry,
+    ) -> Result<Self> {
+        let market_address = deployments.get(chain_id)?.universal_porchetta;
+        let mut builder = Self::new(rpc_provider, signer_address, market_address, system_id);
+        builder.base.chain_id = Arc::new(OnceLock::from(chain_id));
+        Ok(builder)
+    }
+
+    pub async fn chain_id(&self) -> Result<u64> {
+        self.base.chain_id().await
+    }
+
+    /// Run the validation the server runs on submitted offers, the signature check aside, on the
+    /// offer built from the builder's current state at the latest block timestamp. Fails with
+    /// every violation found, so an offer the server would reject isn't signed.
+    pub async fn validate_against(
+        &self,
+        config: &OfferValidationConfig,
+        constraints: &OfferVerifierConstraints,
+    ) -> Result<()> {
+        let offer = self.build()?;
+        let latest_timestamp = self.base.latest_timestamp().await?;
+        let violations = ComputeOfferValidator::new(config.clone(), constraints.clone())
+            .unsigned_violations(&offer, latest_timestamp, &self.base.market_address);
+        BaseIntentBuilder::<T, P, N>::ensure_no_violations(violations)
+    }
+
+    /// target the offer market described by `params`, rejecting other markets
+    pub fn with_market_defaults(mut self, params: &MarketParams) -> Result<Self> {
+        params.ensure_compatible(MarketKind::Offer)?;
+        self.base = self.base.with_market_defaults(params)?;
+        Ok(self)
+    }
+
     pub async fn set_new_nonce(mut self) -> Result<Self> {
         self.base = self.base.set_new_nonce().await?;
         Ok(self)
@@ -106,6 +159,18 @@ where
         self
     }
 
+    /// Commit to the inputs `preimage` with the hash function declared by the verifier details
+    /// abi encoded in `extra_data`
+    pub fn set_verification_commitment_preimage(
+        self,
+        inputs_preimage: &[u8],
+        extra_data: Bytes,
+    ) -> Result<Self> {
+        let hasher = CommitmentHasher::from_offer_extra_data(&extra_data)
+            .map_err(|e| ClientError::BuilderError(e.to_string()))?;
+        Ok(self.set_verification_commitment_params(hasher.hash(inputs_preimage), extra_data))
+    }
+
     pub fn auction_length(mut self, auction_length: u32) -> Self {
         self.base = self.base.auction_length(auction_length);
         self
@@ -161,11 +226,21 @@ where
         self
     }
 
-    pub fn inputs(mut self, inputs: Vec<u8>) -> Self {
+    pub fn inputs(mut self, inputs: SystemInputs) -> Self {
         self.base = self.base.inputs(inputs);
         self
     }
 
+    pub fn inputs_bytes(mut self, inputs: Vec<u8>) -> Self {
+        self.base = self.base.inputs_bytes(inputs);
+        self
+    }
+
+    pub fn inputs_json(mut self, inputs: Value) -> Self {
+        self.base = self.base.inputs_json(inputs);
+        self
+    }
+
     /// return the builder with added reward/stake parameters
     pub fn set_token_params(
         mut self,
@@ -186,6 +261,13 @@ where
         self
     }
 
+    /// set the reward in whole reward tokens, e.g. `1.5`, converted to raw units with the reward
+    /// token's decimals, which must be set beforehand
+    pub fn reward_amount_decimal(mut self, reward_amount: f64) -> Result<Self> {
+        self.reward_amount = parse_decimal_amount(reward_amount, self.base.reward_token_decimals)?;
+        Ok(self)
+    }
+
     pub fn stake_token_address(mut self, stake_token_address: Address) -> Self {
         self.stake_token_address = stake_token_address;
         self
@@ -200,6 +282,13 @@ where
         self.stake_amount = stake_amount;
         self
     }
+
+    /// set the stake in whole stake tokens, converted to raw units with the stake token's
+    /// decimals, which must be set beforehand
+    pub fn stake_amount_decimal(mut self, stake_amount: f64) -> Result<Self> {
+        self.stake_amount = parse_decimal_amount(stake_amount, self.stake_token_decimals)?;
+        Ok(self)
+    }
 }
 
 impl<T, P, N> IntentBuilder for ComputeOfferBuilder<T, P, N>
@@ -0,0 +1,243 @@
+//! Requests signed ahead of their release, e.g. on an air-gapped host holding the key, and
+//! submitted later from another host.
+//!
+//! The signing host builds an `UnsignedRequest` with an explicit nonce and absolute auction
+//! timestamps, without any rpc request, and exports it as JSON along with the permit2 typed data
+//! for the signer to review. Once signed, the request is handed to the submission host, whose
+//! `ReleaseFinalizer` checks it can still be settled before it is submitted: its nonce unused and
+//! its auction window within the time bounds the server validates against at the current chain
+//! time.
+
+use std::marker::PhantomData;
+
+use serde::{Deserialize, Serialize};
+use taralli_primitives::alloy::{
+    dyn_abi::TypedData,
+    network::Network,
+    primitives::{PrimitiveSignature, B256},
+    providers::Provider,
+    signers::Signer,
+    transports::Transport,
+};
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::intents::ComputeIntent;
+use taralli_primitives::systems::SystemParams;
+use taralli_primitives::validation::request::validate_request_signature;
+use taralli_primitives::validation::{validate_commitment_time_constraints, BaseValidationConfig};
+
+use super::MOCK_SIGNATURE_BYTES;
+use crate::clock::ChainClock;
+use crate::error::{ClientError, Result};
+use crate::nonce_manager::Permit2NonceManager;
+
+/// Version of the `UnsignedRequest` JSON format
+pub const UNSIGNED_REQUEST_VERSION: u32 = 1;
+
+/// Request built to be signed elsewhere, carrying a placeholder signature along with the chain it
+/// is signed for and the permit2 typed data describing it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UnsignedRequest {
+    pub version: u32,
+    pub chain_id: u64,
+    pub request: ComputeRequest<SystemParams>,
+    /// `eth_signTypedData_v4` typed data of the request, for the signer to review or sign. Its
+    /// signing hash is the digest the market verifies.
+    pub typed_data: TypedData,
+}
+
+impl UnsignedRequest {
+    pub fn new(mut request: ComputeRequest<SystemParams>, chain_id: u64) -> Self {
+        request.signature = PrimitiveSignature::try_from(&MOCK_SIGNATURE_BYTES[..])
+            .expect("Unreachable: Mock Signature try from failure");
+        let typed_data = request.permit2_typed_data(chain_id);
+        Self {
+            version: UNSIGNED_REQUEST_VERSION,
+            chain_id,
+            request,
+            typed_data,
+        }
+    }
+
+    /// Permit2 digest the request must be signed over
+    #[must_use]
+    pub fn digest(&self) -> B256 {
+        self.request.compute_permit2_digest(self.chain_id)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|e| ClientError::BuilderError(e.to_string()))
+    }
+
+    /// Read an unsigned request exported by `to_json`, rejecting other versions of the format and
+    /// typed data that doesn't describe the request
+    pub fn from_json(json: &str) -> Result<Self> {
+        let unsigned: Self = serde_json::from_str(json)
+            .map_err(|e| ClientError::DeserializationError(e.to_string()))?;
+        if unsigned.version != UNSIGNED_REQUEST_VERSION {
+            return Err(ClientError::DeserializationError(format!(
+                "unsigned request format version {} unsupported, expected {UNSIGNED_REQUEST_VERSION}",
+                unsigned.version
+            )));
+        }
+        let typed_data_hash = |typed_data: &TypedData| {
+            typed_data
+                .eip712_signing_hash()
+                .map_err(|e| ClientError::DeserializationError(e.to_string()))
+        };
+        let expected = unsigned.request.permit2_typed_data(unsigned.chain_id);
+        if typed_data_hash(&unsigned.typed_data)? != typed_data_hash(&expected)? {
+            return Err(ClientError::DeserializationError(
+                "typed data doesn't match the request".to_string(),
+            ));
+        }
+        Ok(unsigned)
+    }
+
+    /// Sign the request with `signer`, which must be the request's signer
+    pub async fn sign<S: Signer>(self, signer: &S) -> Result<ComputeRequest<SystemParams>> {
+        if signer.address() != self.request.proof_request.signer {
+            return Err(ClientError::IntentSigningError(format!(
+                "request is signed by {}, not {}",
+                self.request.proof_request.signer,
+                signer.address()
+            )));
+        }
+        let signature = signer
+            .sign_hash(&self.digest())
+            .await
+            .map_err(|e| ClientError::IntentSigningError(e.to_string()))?;
+        self.with_signature(signature)
+    }
+
+    /// Attach a signature made elsewhere over `digest`, rejecting it unless it recovers to the
+    /// request's signer
+    pub fn with_signature(
+        mut self,
+        signature: PrimitiveSignature,
+    ) -> Result<ComputeRequest<SystemParams>> {
+        validate_request_signature(&self.request.proof_request, &signature, self.chain_id)?;
+        self.request.signature = signature;
+        Ok(self.request)
+    }
+}
+
+/// What to do with a signed request whose auction is over by the time it is released
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResignPolicy {
+    /// reject the request
+    #[default]
+    Reject,
+    /// move the auction to start at the current chain time, keeping its length and nonce, and
+    /// hand the request back to be signed again
+    ShiftWindow,
+}
+
+/// Signed request ready to be submitted, or moved to a new auction window and needing a new
+/// signature first
+#[derive(Clone, Debug)]
+pub enum Release {
+    Ready(ComputeRequest<SystemParams>),
+    Resign(UnsignedRequest),
+}
+
+/// Checks signed requests against the chain right before their submission
+pub struct ReleaseFinalizer<T, P, N> {
+    rpc_provider: P,
+    chain_id: u64,
+    clock: ChainClock<T, P, N>,
+    pub resign_policy: ResignPolicy,
+    /// time bounds released requests are checked against, the ones of the server they are
+    /// submitted to
+    pub validation_config: BaseValidationConfig,
+    phantom: PhantomData<(T, N)>,
+}
+
+impl<T, P, N> ReleaseFinalizer<T, P, N>
+where
+    T: Transport + Clone + Send + Sync,
+    P: Provider<T, N> + Clone + Send + Sync,
+    N: Network + Clone + Send + Sync,
+{
+    /// Finalizer of requests signed for `chain_id`, reading the chain through `rpc_provider`
+    pub fn new(rpc_provider: P, chain_id: u64) -> Self {
+        Self {
+            clock: ChainClock::new(rpc_provider.clone()),
+            rpc_provider,
+            chain_id,
+            resign_policy: ResignPolicy::default(),
+            validation_config: BaseValidationConfig::default(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Read the chain time from `clock`, e.g. the one of the client submitting the requests
+    pub fn with_clock(mut self, clock: ChainClock<T, P, N>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_resign_policy(mut self, resign_policy: ResignPolicy) -> Self {
+        self.resign_policy = resign_policy;
+        self
+    }
+
+    /// Check released requests against the time bounds of `validation_config`
+    pub fn with_validation_config(mut self, validation_config: BaseValidationConfig) -> Self {
+        self.validation_config = validation_config;
+        self
+    }
+
+    /// Check `request` can still be settled: signed for the finalizer's chain, its nonce unused
+    /// and its auction window within the validation config's time bounds at the current chain
+    /// time. A request whose auction is over is rejected, or handed back with a shifted window
+    /// when the resign policy allows it. A request released too long before its auction starts is
+    /// rejected whatever the policy, its signature still holding once the auction is near.
+    pub async fn finalize_and_validate(
+        &self,
+        request: ComputeRequest<SystemParams>,
+    ) -> Result<Release> {
+        validate_request_signature(&request.proof_request, &request.signature, self.chain_id)?;
+
+        let signer = request.proof_request.signer;
+        let nonce = request.proof_request.nonce;
+        let nonce_manager = Permit2NonceManager::new(self.rpc_provider.clone(), signer);
+        if nonce_manager.is_nonce_used(nonce).await? {
+            return Err(ClientError::NonceAlreadyUsed { signer, nonce });
+        }
+
+        let chain_time = self.clock.now().await?;
+        let end_auction_timestamp = request.proof_request.endAuctionTimestamp;
+        let validation_config = self.validation_config.for_system(request.system_id());
+        if chain_time < end_auction_timestamp {
+            validate_commitment_time_constraints(
+                &request.proof_request,
+                chain_time,
+                &validation_config,
+            )?;
+            return Ok(Release::Ready(request));
+        }
+        match self.resign_policy {
+            ResignPolicy::Reject => Err(ClientError::ReleaseWindowPassed {
+                end_auction_timestamp,
+                chain_time,
+            }),
+            ResignPolicy::ShiftWindow => {
+                let mut shifted = request;
+                let auction_length = end_auction_timestamp
+                    .saturating_sub(shifted.proof_request.startAuctionTimestamp);
+                shifted.proof_request.startAuctionTimestamp = chain_time;
+                shifted.proof_request.endAuctionTimestamp = chain_time + auction_length;
+                validate_commitment_time_constraints(
+                    &shifted.proof_request,
+                    chain_time,
+                    &validation_config,
+                )?;
+                Ok(Release::Resign(UnsignedRequest::new(
+                    shifted,
+                    self.chain_id,
+                )))
+            }
+        }
+    }
+}
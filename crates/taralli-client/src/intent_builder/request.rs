@@ -1,5 +1,9 @@
 use serde_json::Value;
-use taralli_primitives::abi::universal_bombetta::UniversalBombetta::ProofRequest;
+use std::sync::{Arc, OnceLock};
+use taralli_primitives::abi::extensions::{decode_request_extensions, with_request_extensions};
+use taralli_primitives::abi::universal_bombetta::{
+    ProofRequestVerifierDetails, UniversalBombetta::ProofRequest,
+};
 use taralli_primitives::alloy::{
     network::Network,
     primitives::U256,
@@ -7,12 +11,25 @@ use taralli_primitives::alloy::{
     providers::Provider,
     transports::Transport,
 };
+use taralli_primitives::commitment::CommitmentHasher;
+use taralli_primitives::deployments::DeploymentRegistry;
 use taralli_primitives::intents::request::ComputeRequest;
-use taralli_primitives::systems::{SystemId, SystemParams};
+use taralli_primitives::markets::MarketFee;
+use taralli_primitives::systems::{SystemId, SystemInputs, SystemParams};
+use taralli_primitives::validation::request::{
+    ComputeRequestValidator, RequestValidationConfig, RequestVerifierConstraints,
+};
+use taralli_primitives::validation::IntentValidator;
 
+use super::amount::parse_decimal_amount;
+use super::market::{MarketKind, MarketParams};
+use super::release::UnsignedRequest;
+use super::template::{IntentTemplate, RequestTemplateParams};
 use super::{BaseIntentBuilder, IntentBuilder};
-use crate::error::Result;
+use crate::clock::ChainClock;
+use crate::error::{ClientError, Result};
 use crate::nonce_manager::Permit2NonceManager;
+use crate::oracle::{RewardStrategy, RewardSuggestion};
 
 /// Intent builder for `ComputeRequests`
 #[derive(Clone)]
@@ -27,6 +44,13 @@ where
     pub max_reward_amount: U256,
     pub min_reward_amount: U256,
     pub minimum_stake: u128,
+    // urgency signaled through the extensions of `extra_data` when set
+    pub urgency: Option<u8>,
+    // verifier details the request can also be verified with, signaled through the extensions of
+    // `extra_data` after the leading ones
+    pub verifier_alternatives: Vec<ProofRequestVerifierDetails>,
+    // protocol fee the market takes out of the rewards, accounted for by the net reward setters
+    pub market_fee: MarketFee,
 }
 
 impl<T, P, N> ComputeRequestBuilder<T, P, N>
@@ -44,10 +68,12 @@ where
         // build permit2 nonce manager
         let permit2_nonce_manager = Permit2NonceManager::new(rpc_provider.clone(), signer_address);
         let base = BaseIntentBuilder {
+            clock: ChainClock::new(rpc_provider.clone()),
             rpc_provider,
             permit2_nonce_manager,
             signer_address,
             auction_length: 0u32,
+            chain_id: Arc::new(OnceLock::new()),
             market_address,
             nonce: U256::ZERO,
             reward_token_address: Address::ZERO,
@@ -59,16 +85,136 @@ where
             extra_data: Bytes::from(""),
             system_id,
             system: Value::Null,
-            inputs: vec![],
+            inputs: None,
         };
         Self {
             base,
             max_reward_amount: U256::ZERO,
             min_reward_amount: U256::ZERO,
             minimum_stake: 0u128,
+            urgency: None,
+            verifier_alternatives: Vec::new(),
+            market_fee: MarketFee::ZERO,
         }
     }
 
+    /// build a `ComputeRequestBuilder` with all parameters of the template set, leaving the
+    /// nonce, auction timestamps and system to be set per request
+    pub fn from_template(
+        rpc_provider: P,
+        signer_address: Address,
+        template: &IntentTemplate,
+    ) -> Result<Self> {
+        template.validate()?;
+        let request_params = template.request.as_ref().ok_or_else(|| {
+            ClientError::TemplateError("template is missing request parameters".into())
+        })?;
+
+        Ok(Self::new(
+            rpc_provider,
+            signer_address,
+            template.market_address,
+            template.system_id,
+        )
+        .auction_length(template.auction_length)
+        .reward_token_address(template.reward_token_address)
+        .reward_token_decimals(template.reward_token_decimals)
+        .proving_time(template.proving_time)
+        .extra_data(template.extra_data.clone())
+        .set_token_params(
+            request_params.minimum_stake,
+            request_params.min_reward_amount,
+            request_params.max_reward_amount,
+        ))
+    }
+
+    /// capture the builder's current parameters as a template
+    #[must_use]
+    pub fn to_template(&self) -> IntentTemplate {
+        IntentTemplate {
+            system_id: self.base.system_id,
+            market_address: self.base.market_address,
+            auction_length: self.base.auction_length,
+            reward_token_address: self.base.reward_token_address,
+            reward_token_decimals: self.base.reward_token_decimals,
+            proving_time: self.base.proving_time,
+            extra_data: self.extra_data_with_extensions(),
+            request: Some(RequestTemplateParams {
+                max_reward_amount: self.max_reward_amount,
+                min_reward_amount: self.min_reward_amount,
+                minimum_stake: self.minimum_stake,
+            }),
+            overrides: Default::default(),
+        }
+    }
+
+    /// build a `ComputeRequestBuilder` targeting the request market deployed on `chain_id`, which
+    /// `market_address` still overrides
+    pub fn for_chain(
+        rpc_provider: P,
+        signer_address: Address,
+        chain_id: u64,
+        system_id: SystemId,
+        deployments: &DeploymentRegistry,
+    ) -> Result<Self> {
+        let market_address = deployments.get(chain_id)?.universal_bombetta;
+        let mut builder = Self::new(rpc_provider, signer_address, market_address, system_id);
+        builder.base.chain_id = Arc::new(OnceLock::from(chain_id));
+        Ok(builder)
+    }
+
+    pub async fn chain_id(&self) -> Result<u64> {
+        self.base.chain_id().await
+    }
+
+    /// Run the validation the server runs on submitted requests, the signature check aside, on the
+    /// request built from the builder's current state at the latest block timestamp. Fails with
+    /// every violation found, so a request the server would reject isn't signed.
+    pub async fn validate_against(
+        &self,
+        config: &RequestValidationConfig,
+        constraints: &RequestVerifierConstraints,
+    ) -> Result<()> {
+        let request = self.build()?;
+        let latest_timestamp = self.base.latest_timestamp().await?;
+        let violations = ComputeRequestValidator::new(config.clone(), constraints.clone())
+            .unsigned_violations(&request, latest_timestamp, &self.base.market_address);
+        BaseIntentBuilder::<T, P, N>::ensure_no_violations(violations)
+    }
+
+    /// Build the request to be signed elsewhere and submitted later, without any rpc request. The
+    /// nonce and the absolute auction timestamps must be set beforehand, and the chain id known
+    /// offline, e.g. from `for_chain` or `with_market_defaults`.
+    pub fn build_unsigned_with_placeholders(&self) -> Result<UnsignedRequest> {
+        let chain_id = *self.base.chain_id.get().ok_or_else(|| {
+            ClientError::BuilderError(
+                "chain id must be known to build the request offline".to_string(),
+            )
+        })?;
+        if self.base.start_auction_timestamp == 0
+            || self.base.end_auction_timestamp <= self.base.start_auction_timestamp
+        {
+            return Err(ClientError::BuilderError(
+                "auction timestamps must be set to build the request offline".to_string(),
+            ));
+        }
+        Ok(UnsignedRequest::new(self.build()?, chain_id))
+    }
+
+    /// target the request market described by `params`, rejecting other markets
+    pub fn with_market_defaults(mut self, params: &MarketParams) -> Result<Self> {
+        params.ensure_compatible(MarketKind::Request)?;
+        self.base = self.base.with_market_defaults(params)?;
+        self.market_fee = params.fee;
+        Ok(self)
+    }
+
+    /// account for the protocol fee the market takes out of rewards
+    pub fn market_fee(mut self, market_fee: MarketFee) -> Self {
+        self.market_fee = market_fee;
+        self
+    }
+
     pub async fn set_new_nonce(mut self) -> Result<Self> {
         self.base = self.base.set_new_nonce().await?;
         Ok(self)
@@ -105,6 +251,18 @@ where
         self
     }
 
+    /// Commit to the inputs `preimage` with the hash function declared by the verifier details
+    /// abi encoded in `extra_data`
+    pub fn set_verification_commitment_preimage(
+        self,
+        inputs_preimage: &[u8],
+        extra_data: Bytes,
+    ) -> Result<Self> {
+        let hasher = CommitmentHasher::from_request_extra_data(&extra_data)
+            .map_err(|e| ClientError::BuilderError(e.to_string()))?;
+        Ok(self.set_verification_commitment_params(hasher.hash(inputs_preimage), extra_data))
+    }
+
     pub fn auction_length(mut self, auction_length: u32) -> Self {
         self.base = self.base.auction_length(auction_length);
         self
@@ -160,11 +318,21 @@ where
         self
     }
 
-    pub fn inputs(mut self, inputs: Vec<u8>) -> Self {
+    pub fn inputs(mut self, inputs: SystemInputs) -> Self {
         self.base = self.base.inputs(inputs);
         self
     }
 
+    pub fn inputs_bytes(mut self, inputs: Vec<u8>) -> Self {
+        self.base = self.base.inputs_bytes(inputs);
+        self
+    }
+
+    pub fn inputs_json(mut self, inputs: Value) -> Self {
+        self.base = self.base.inputs_json(inputs);
+        self
+    }
+
     /// return the builder with added reward/stake parameters
     pub fn set_token_params(
         mut self,
@@ -188,10 +356,91 @@ where
         self
     }
 
+    /// set the min and max rewards so providers are paid at least `min_net` and `max_net` once the
+    /// market's fee is taken
+    pub fn set_net_reward_amounts(mut self, min_net: U256, max_net: U256) -> Self {
+        self.min_reward_amount = self.market_fee.gross_reward(min_net);
+        self.max_reward_amount = self.market_fee.gross_reward(max_net);
+        self
+    }
+
+    /// part of the maximum reward paid to the provider once the market's fee is taken
+    pub fn max_net_reward(&self) -> U256 {
+        self.market_fee.net_reward(self.max_reward_amount)
+    }
+
+    /// set the min and max rewards in whole reward tokens, e.g. `1.5`, converted to raw units
+    /// with the reward token's decimals, which must be set beforehand
+    pub fn set_reward_amounts_decimal(mut self, min: f64, max: f64) -> Result<Self> {
+        let decimals = self.base.reward_token_decimals;
+        self.min_reward_amount = parse_decimal_amount(min, decimals)?;
+        self.max_reward_amount = parse_decimal_amount(max, decimals)?;
+        Ok(self)
+    }
+
+    /// pay exactly `reward_amount` to the first provider to bid, without price discovery. The
+    /// request carries no separate flag, equal min and max rewards mark it as fixed price.
+    pub fn fixed_reward(mut self, reward_amount: U256) -> Self {
+        self.min_reward_amount = reward_amount;
+        self.max_reward_amount = reward_amount;
+        self
+    }
+
+    /// set the min and max rewards from a `PriceOracle` suggestion, priced with `strategy`
+    pub fn set_reward_from_suggestion(
+        mut self,
+        suggestion: &RewardSuggestion,
+        strategy: RewardStrategy,
+    ) -> Self {
+        (self.min_reward_amount, self.max_reward_amount) = suggestion.reward_range(strategy);
+        self
+    }
+
+    pub fn is_fixed_reward(&self) -> bool {
+        self.min_reward_amount == self.max_reward_amount
+    }
+
+    /// Schedule the request's auction against `clock`, e.g. the clock of the client submitting it
+    pub fn with_clock(mut self, clock: ChainClock<T, P, N>) -> Self {
+        self.base = self.base.with_clock(clock);
+        self
+    }
+
     pub fn minimum_stake(mut self, stake_amount: u128) -> Self {
         self.minimum_stake = stake_amount;
         self
     }
+
+    /// signal the request's urgency to providers, from 0 up to `MAX_URGENCY`. The level is
+    /// appended to the extra data as an extension, which the markets ignore.
+    pub fn urgency(mut self, level: u8) -> Self {
+        self.urgency = Some(level);
+        self
+    }
+
+    /// add verifier details the request can also be verified with, after the ones leading the
+    /// extra data and the alternatives added before, so providers can prove it against whichever
+    /// they support. Alternatives are appended to the extra data as extensions.
+    pub fn verifier_alternative(mut self, verifier_details: ProofRequestVerifierDetails) -> Self {
+        self.verifier_alternatives.push(verifier_details);
+        self
+    }
+
+    /// extra data along with the urgency and verifier alternative extensions when set, the other
+    /// extensions being kept
+    fn extra_data_with_extensions(&self) -> Bytes {
+        if self.urgency.is_none() && self.verifier_alternatives.is_empty() {
+            return self.base.extra_data.clone();
+        }
+        let mut extensions = decode_request_extensions(&self.base.extra_data).unwrap_or_default();
+        if let Some(urgency) = self.urgency {
+            extensions.urgency = Some(urgency);
+        }
+        extensions
+            .verifier_alternatives
+            .extend(self.verifier_alternatives.iter().cloned());
+        with_request_extensions(&self.base.extra_data, &extensions)
+    }
 }
 
 impl<T, P, N> IntentBuilder for ComputeRequestBuilder<T, P, N>
@@ -220,7 +469,7 @@ where
                 endAuctionTimestamp: self.base.end_auction_timestamp,
                 provingTime: self.base.proving_time,
                 inputsCommitment: self.base.inputs_commitment,
-                extraData: self.base.extra_data.clone(),
+                extraData: self.extra_data_with_extensions(),
             },
             signature: BaseIntentBuilder::<T, P, N>::create_dummy_signature(),
         })
@@ -0,0 +1,200 @@
+use std::sync::Arc;
+
+use taralli_primitives::abi::extensions::{with_request_extensions, Extensions};
+use taralli_primitives::abi::verifier_details::{DecodeMode, REQUEST_VERIFIER_DETAILS_LENGTH};
+use taralli_primitives::alloy::{
+    network::Network,
+    primitives::{B256, U256},
+    providers::Provider,
+    signers::Signer,
+    transports::Transport,
+};
+use taralli_primitives::commitment::CommitmentHasher;
+use taralli_primitives::intents::{request::ComputeRequest, ComputeIntent};
+use taralli_primitives::systems::{SystemInputs, SystemParams};
+
+use super::request::ComputeRequestBuilder;
+use super::IntentBuilder;
+use crate::error::{ClientError, Result};
+
+pub use taralli_primitives::abi::extensions::ShardTag;
+
+/// Inputs of each shard, given the shard index
+pub type ShardInputs = Arc<dyn Fn(usize) -> SystemInputs + Send + Sync>;
+/// Inputs commitment preimage of each shard, given the shard index
+pub type ShardCommitmentPreimage = Arc<dyn Fn(usize) -> Vec<u8> + Send + Sync>;
+
+/// Splits one embarrassingly parallel job into `shard_count` linked `ComputeRequest`s. Each shard
+/// gets its own inputs and nonce, an even share of the builder's rewards and an auction starting
+/// `auction_stagger` seconds after the previous shard's, so the shards don't compete with each
+/// other for the same providers.
+pub struct ShardedRequestBuilder<T, P, N>
+where
+    T: Transport + Clone + Send + Sync,
+    P: Provider<T, N> + Clone + Send + Sync,
+    N: Network + Clone + Send + Sync,
+{
+    /// builder of the whole job, its rewards are the totals split across shards
+    pub builder: ComputeRequestBuilder<T, P, N>,
+    pub shard_count: usize,
+    pub job_id: B256,
+    pub auction_stagger: u64,
+    shard_inputs: Option<ShardInputs>,
+    // per shard inputs commitments, every shard keeps the builder's commitment when unset
+    shard_commitment_preimage: Option<ShardCommitmentPreimage>,
+    // nonces fetched for the shards, consecutive from the builder's nonce when unset
+    nonces: Option<Vec<U256>>,
+}
+
+impl<T, P, N> ShardedRequestBuilder<T, P, N>
+where
+    T: Transport + Clone + Send + Sync,
+    P: Provider<T, N> + Clone + Send + Sync,
+    N: Network + Clone + Send + Sync,
+{
+    pub fn new(builder: ComputeRequestBuilder<T, P, N>, shard_count: usize) -> Self {
+        Self {
+            builder,
+            shard_count,
+            job_id: B256::from(rand::random::<[u8; 32]>()),
+            auction_stagger: 0,
+            shard_inputs: None,
+            shard_commitment_preimage: None,
+            nonces: None,
+        }
+    }
+
+    pub fn shard_inputs(
+        mut self,
+        shard_inputs: impl Fn(usize) -> SystemInputs + Send + Sync + 'static,
+    ) -> Self {
+        self.shard_inputs = Some(Arc::new(shard_inputs));
+        self
+    }
+
+    /// commit each shard to its own preimage, hashed as declared by the builder's verifier details
+    pub fn shard_commitment_preimage(
+        mut self,
+        shard_commitment_preimage: impl Fn(usize) -> Vec<u8> + Send + Sync + 'static,
+    ) -> Self {
+        self.shard_commitment_preimage = Some(Arc::new(shard_commitment_preimage));
+        self
+    }
+
+    pub fn job_id(mut self, job_id: B256) -> Self {
+        self.job_id = job_id;
+        self
+    }
+
+    pub fn auction_stagger(mut self, seconds: u64) -> Self {
+        self.auction_stagger = seconds;
+        self
+    }
+
+    /// return the builder with an unused permit2 nonce fetched for every shard
+    pub async fn set_new_nonces(mut self) -> Result<Self> {
+        self.nonces = Some(self.builder.base.get_new_nonces(self.shard_count).await?);
+        Ok(self)
+    }
+
+    /// Build the unsigned shards of the job
+    pub fn build_shards(&self) -> Result<Vec<ComputeRequest<SystemParams>>> {
+        if self.shard_count == 0 {
+            return Err(ClientError::BuilderError(
+                "shard count must be at least 1".to_string(),
+            ));
+        }
+        let shard_inputs = self
+            .shard_inputs
+            .as_ref()
+            .ok_or_else(|| ClientError::BuilderError("shard inputs are not set".to_string()))?;
+        let extra_data = &self.builder.base.extra_data;
+        if extra_data.len() < REQUEST_VERIFIER_DETAILS_LENGTH {
+            return Err(ClientError::BuilderError(format!(
+                "extra data must start with the {REQUEST_VERIFIER_DETAILS_LENGTH} byte verifier details to be tagged, got {} bytes",
+                extra_data.len()
+            )));
+        }
+        let extensions = Extensions::decode(
+            &extra_data[REQUEST_VERIFIER_DETAILS_LENGTH..],
+            DecodeMode::Strict,
+        )
+        .map_err(|e| ClientError::BuilderError(e.to_string()))?;
+        let hasher = CommitmentHasher::from_request_extra_data(&self.builder.base.extra_data)
+            .map_err(|e| ClientError::BuilderError(e.to_string()))?;
+        let nonces = match &self.nonces {
+            Some(nonces) if nonces.len() == self.shard_count => nonces.clone(),
+            Some(nonces) => {
+                return Err(ClientError::BuilderError(format!(
+                    "{} nonces fetched for {} shards",
+                    nonces.len(),
+                    self.shard_count
+                )))
+            }
+            None => (0..self.shard_count)
+                .map(|index| self.builder.base.nonce + U256::from(index))
+                .collect(),
+        };
+
+        let min_rewards = split_evenly(self.builder.min_reward_amount, self.shard_count);
+        let max_rewards = split_evenly(self.builder.max_reward_amount, self.shard_count);
+        (0..self.shard_count)
+            .map(|index| {
+                let stagger = self.auction_stagger * index as u64;
+                let extra_data = with_request_extensions(
+                    extra_data,
+                    &Extensions {
+                        shard: Some(ShardTag {
+                            job_id: self.job_id,
+                            index,
+                            count: self.shard_count,
+                        }),
+                        ..extensions.clone()
+                    },
+                );
+                let inputs_commitment = match &self.shard_commitment_preimage {
+                    Some(preimage) => hasher.hash(&preimage(index)),
+                    None => self.builder.base.inputs_commitment,
+                };
+                self.builder
+                    .clone()
+                    .set_verification_commitment_params(inputs_commitment, extra_data)
+                    .nonce(nonces[index])
+                    .min_reward_amount(min_rewards[index])
+                    .max_reward_amount(max_rewards[index])
+                    .start_auction_timestamp(self.builder.base.start_auction_timestamp + stagger)
+                    .end_auction_timestamp(self.builder.base.end_auction_timestamp + stagger)
+                    .inputs(shard_inputs(index))
+                    .build()
+            })
+            .collect()
+    }
+
+    /// Build the shards of the job and sign each of them for the builder's chain
+    pub async fn build_all<S: Signer>(
+        &self,
+        signer: &S,
+    ) -> Result<Vec<ComputeRequest<SystemParams>>> {
+        let chain_id = self.builder.chain_id().await?;
+        let mut shards = self.build_shards()?;
+        for shard in &mut shards {
+            shard.signature = signer
+                .sign_hash(&shard.compute_permit2_digest(chain_id))
+                .await
+                .map_err(|e| ClientError::IntentSigningError(e.to_string()))?;
+        }
+        Ok(shards)
+    }
+}
+
+/// Split `total` into `parts` amounts differing by at most 1 and summing to `total`
+fn split_evenly(total: U256, parts: usize) -> Vec<U256> {
+    let parts_u256 = U256::from(parts);
+    let share = total / parts_u256;
+    let remainder: usize = (total % parts_u256)
+        .try_into()
+        .expect("remainder is below the number of parts");
+    (0..parts)
+        .map(|index| share + U256::from(u8::from(index < remainder)))
+        .collect()
+}
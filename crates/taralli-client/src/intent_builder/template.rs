@@ -0,0 +1,195 @@
+//! Reusable presets of intent builder parameters.
+//!
+//! A template captures everything about an intent that usually stays the same across
+//! submissions (market, token, amounts, auction length, verifier details), leaving out the
+//! nonce, the auction timestamps and the system inputs which are set per intent.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use taralli_primitives::abi::verifier_details::{decode_request_verifier_details, DecodeMode};
+use taralli_primitives::alloy::primitives::{Address, Bytes, U256};
+use taralli_primitives::systems::SystemId;
+
+use crate::error::{ClientError, Result};
+
+/// Serializable preset of intent builder parameters
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntentTemplate {
+    pub system_id: SystemId,
+    pub market_address: Address,
+    pub auction_length: u32,
+    pub reward_token_address: Address,
+    pub reward_token_decimals: u8,
+    pub proving_time: u32,
+    /// abi encoded verifier details
+    pub extra_data: Bytes,
+    /// `ComputeRequest` specific parameters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request: Option<RequestTemplateParams>,
+    /// named sets of fields replacing the ones above, see `IntentTemplate::with_override`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub overrides: BTreeMap<String, TemplateOverride>,
+}
+
+/// `ComputeRequest` specific template parameters
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequestTemplateParams {
+    pub max_reward_amount: U256,
+    pub min_reward_amount: U256,
+    pub minimum_stake: u128,
+}
+
+/// Fields of a template replaced when a named override is selected
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TemplateOverride {
+    pub market_address: Option<Address>,
+    pub auction_length: Option<u32>,
+    pub reward_token_address: Option<Address>,
+    pub reward_token_decimals: Option<u8>,
+    pub proving_time: Option<u32>,
+    pub extra_data: Option<Bytes>,
+    pub max_reward_amount: Option<U256>,
+    pub min_reward_amount: Option<U256>,
+    pub minimum_stake: Option<u128>,
+}
+
+impl IntentTemplate {
+    /// Parse and validate a template from its json representation
+    pub fn from_json_str(json: &str) -> Result<Self> {
+        let raw: serde_json::Value =
+            serde_json::from_str(json).map_err(|e| ClientError::TemplateError(e.to_string()))?;
+        validate_address_checksums(&raw)?;
+
+        let template: Self =
+            serde_json::from_value(raw).map_err(|e| ClientError::TemplateError(e.to_string()))?;
+        template.validate()?;
+        Ok(template)
+    }
+
+    /// Load and validate a template from a json file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let json = std::fs::read_to_string(path.as_ref()).map_err(|e| {
+            ClientError::TemplateError(format!("failed to read {}: {e}", path.as_ref().display()))
+        })?;
+        Self::from_json_str(&json)
+    }
+
+    /// Save the template as a json file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| ClientError::TemplateError(e.to_string()))?;
+        std::fs::write(path.as_ref(), json).map_err(|e| {
+            ClientError::TemplateError(format!("failed to write {}: {e}", path.as_ref().display()))
+        })
+    }
+
+    /// Check the template describes an intent that can be built
+    pub fn validate(&self) -> Result<()> {
+        if self.auction_length == 0 {
+            return Err(ClientError::TemplateError(
+                "auction length must be greater than 0".into(),
+            ));
+        }
+        if self.proving_time == 0 {
+            return Err(ClientError::TemplateError(
+                "proving time must be greater than 0".into(),
+            ));
+        }
+        if self.reward_token_address == Address::ZERO {
+            return Err(ClientError::TemplateError(
+                "reward token address is not set".into(),
+            ));
+        }
+        decode_request_verifier_details(&self.extra_data, DecodeMode::Strict)
+            .map_err(|e| ClientError::TemplateError(e.to_string()))?;
+        if let Some(request) = &self.request {
+            if request.min_reward_amount > request.max_reward_amount {
+                return Err(ClientError::TemplateError(
+                    "minimum reward amount exceeds maximum reward amount".into(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Return a copy of the template with the fields of the named override applied
+    pub fn with_override(&self, name: &str) -> Result<Self> {
+        let template_override = self
+            .overrides
+            .get(name)
+            .ok_or_else(|| ClientError::TemplateError(format!("unknown override: {name}")))?;
+
+        let mut template = self.clone();
+        if let Some(market_address) = template_override.market_address {
+            template.market_address = market_address;
+        }
+        if let Some(auction_length) = template_override.auction_length {
+            template.auction_length = auction_length;
+        }
+        if let Some(reward_token_address) = template_override.reward_token_address {
+            template.reward_token_address = reward_token_address;
+        }
+        if let Some(reward_token_decimals) = template_override.reward_token_decimals {
+            template.reward_token_decimals = reward_token_decimals;
+        }
+        if let Some(proving_time) = template_override.proving_time {
+            template.proving_time = proving_time;
+        }
+        if let Some(extra_data) = &template_override.extra_data {
+            template.extra_data = extra_data.clone();
+        }
+        let overrides_request_params = template_override.max_reward_amount.is_some()
+            || template_override.min_reward_amount.is_some()
+            || template_override.minimum_stake.is_some();
+        if let Some(request) = template.request.as_mut() {
+            if let Some(max_reward_amount) = template_override.max_reward_amount {
+                request.max_reward_amount = max_reward_amount;
+            }
+            if let Some(min_reward_amount) = template_override.min_reward_amount {
+                request.min_reward_amount = min_reward_amount;
+            }
+            if let Some(minimum_stake) = template_override.minimum_stake {
+                request.minimum_stake = minimum_stake;
+            }
+        } else if overrides_request_params {
+            return Err(ClientError::TemplateError(format!(
+                "override {name} sets request parameters on a template without them"
+            )));
+        }
+
+        template.validate()?;
+        Ok(template)
+    }
+}
+
+/// Mixed case addresses must carry a valid EIP-55 checksum, all lower/upper case ones are
+/// accepted as is.
+fn validate_address_checksums(value: &serde_json::Value) -> Result<()> {
+    match value {
+        serde_json::Value::Object(fields) => {
+            for (name, field) in fields {
+                if let (true, Some(address)) = (name.ends_with("address"), field.as_str()) {
+                    validate_address_checksum(name, address)?;
+                } else {
+                    validate_address_checksums(field)?;
+                }
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_address_checksum(name: &str, address: &str) -> Result<()> {
+    let hex = address.strip_prefix("0x").unwrap_or(address);
+    let is_mixed_case =
+        hex.chars().any(|c| c.is_ascii_lowercase()) && hex.chars().any(|c| c.is_ascii_uppercase());
+    if is_mixed_case {
+        Address::parse_checksummed(address, None)
+            .map_err(|e| ClientError::TemplateError(format!("invalid {name} checksum: {e}")))?;
+    }
+    Ok(())
+}
@@ -3,13 +3,31 @@
 
 pub mod analyzer;
 pub mod api;
+pub mod artifacts;
+pub mod audit;
+pub mod backtest;
+pub mod bid_store;
 pub mod bidder;
 pub mod client;
+pub mod clock;
 pub mod config;
+pub mod confirmations;
+pub mod env;
 pub mod error;
+pub mod hooks;
 pub mod intent_builder;
+pub mod market_state;
+pub mod metrics;
 pub mod nonce_manager;
+pub mod oracle;
+pub mod persist;
+pub mod pnl;
+pub mod proving_stats;
 pub mod resolver;
+pub mod rpc;
 pub mod searcher;
+pub mod signer_filter;
+pub mod submitter;
 pub mod tracker;
+pub mod work_cache;
 pub mod worker;
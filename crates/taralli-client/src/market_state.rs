@@ -0,0 +1,224 @@
+//! Auction state of intents as recorded by their market: whether they're still open to bids, who
+//! bid on them at what price, and whether they were resolved.
+
+use std::marker::PhantomData;
+
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta::UniversalBombettaInstance;
+use taralli_primitives::abi::universal_porchetta::UniversalPorchetta::UniversalPorchettaInstance;
+use taralli_primitives::alloy::{
+    network::Network,
+    primitives::{Address, B256, U256},
+    providers::Provider,
+    transports::Transport,
+};
+
+use crate::api::retry::RetryPolicy;
+use crate::error::Result;
+use crate::rpc::retry_rpc;
+
+/// Auction state of an intent
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuctionState {
+    /// not bid upon, the auction still running
+    Open,
+    /// bid upon and not resolved yet
+    Bid {
+        /// address that placed the bid, the requester when bidding on an offer
+        bidder: Address,
+        /// address obligated to resolve the intent
+        prover: Address,
+        /// reward paid to the prover on resolution
+        amount: U256,
+        /// block the bid was included in, `None` when its event isn't found in the looked up logs
+        at_block: Option<u64>,
+    },
+    /// bid upon and resolved, whether by a proof or by slashing the prover
+    Resolved,
+    /// not bid upon before the end of the auction
+    Expired,
+}
+
+impl AuctionState {
+    pub fn is_open(&self) -> bool {
+        matches!(self, Self::Open)
+    }
+
+    /// Whether the intent was bid upon, resolved or not
+    pub fn is_bid(&self) -> bool {
+        matches!(self, Self::Bid { .. } | Self::Resolved)
+    }
+}
+
+/// Reads the auction state of intents from their market. Intents not bid upon leave no trace in
+/// the market, their state being derived from the end of their auction instead. Bids stay in
+/// the market's state once resolved, resolutions being found through the market's logs.
+#[derive(Clone, Debug)]
+pub struct MarketStateClient<T, P, N> {
+    rpc_provider: P,
+    market_address: Address,
+    // first block the market's logs are looked up from
+    from_block: u64,
+    // attempts of the rpc calls reading the market failing transiently
+    rpc_retry: RetryPolicy,
+    phantom_data: PhantomData<(T, N)>,
+}
+
+impl<T, P, N> MarketStateClient<T, P, N>
+where
+    T: Transport + Clone,
+    P: Provider<T, N> + Clone,
+    N: Network,
+{
+    pub fn new(rpc_provider: P, market_address: Address) -> Self {
+        Self {
+            rpc_provider,
+            market_address,
+            from_block: 0,
+            rpc_retry: RetryPolicy::default(),
+            phantom_data: PhantomData,
+        }
+    }
+
+    /// look up the market's bid and resolve events from `from_block` on, e.g. the market's
+    /// deployment block, instead of from genesis
+    pub fn with_from_block(mut self, from_block: u64) -> Self {
+        self.from_block = from_block;
+        self
+    }
+
+    /// attempt the rpc calls reading the market again as `rpc_retry` allows when they fail
+    /// transiently
+    pub fn with_rpc_retry(mut self, rpc_retry: RetryPolicy) -> Self {
+        self.rpc_retry = rpc_retry;
+        self
+    }
+
+    /// State of the auction of request `intent_id` ending at `end_auction_timestamp`, at time
+    /// `timestamp`
+    pub async fn request_state(
+        &self,
+        intent_id: B256,
+        end_auction_timestamp: u64,
+        timestamp: u64,
+    ) -> Result<AuctionState> {
+        let market_contract =
+            &UniversalBombettaInstance::new(self.market_address, self.rpc_provider.clone());
+        let active = retry_rpc(&self.rpc_retry, || async move {
+            market_contract
+                .activeProofRequestData(intent_id)
+                .call()
+                .await
+        })
+        .await?;
+        if active.requester == Address::ZERO {
+            return Ok(unbid_state(end_auction_timestamp, timestamp));
+        }
+
+        let resolves = retry_rpc(&self.rpc_retry, || async move {
+            market_contract
+                .Resolve_filter()
+                .topic2(intent_id)
+                .from_block(self.from_block)
+                .query()
+                .await
+        })
+        .await?;
+        if !resolves.is_empty() {
+            return Ok(AuctionState::Resolved);
+        }
+        let at_block = retry_rpc(&self.rpc_retry, || async move {
+            market_contract
+                .Bid_filter()
+                .topic2(intent_id)
+                .from_block(self.from_block)
+                .query()
+                .await
+        })
+        .await?
+        .into_iter()
+        .find_map(|(_, log)| log.block_number);
+        Ok(AuctionState::Bid {
+            bidder: active.provider,
+            prover: active.provider,
+            amount: active.rewardAmount,
+            at_block,
+        })
+    }
+
+    /// Resolution deadline of request `intent_id`, its bid's timestamp plus its proving time,
+    /// `None` when it wasn't bid upon
+    pub async fn request_resolution_deadline(&self, intent_id: B256) -> Result<Option<u64>> {
+        let market_contract =
+            &UniversalBombettaInstance::new(self.market_address, self.rpc_provider.clone());
+        let active = retry_rpc(&self.rpc_retry, || async move {
+            market_contract
+                .activeProofRequestData(intent_id)
+                .call()
+                .await
+        })
+        .await?;
+        if active.requester == Address::ZERO {
+            return Ok(None);
+        }
+        Ok(Some(
+            u64::try_from(active.resolutionDeadline).unwrap_or(u64::MAX),
+        ))
+    }
+
+    /// State of the auction of offer `intent_id` ending at `end_auction_timestamp`, at time
+    /// `timestamp`
+    pub async fn offer_state(
+        &self,
+        intent_id: B256,
+        end_auction_timestamp: u64,
+        timestamp: u64,
+    ) -> Result<AuctionState> {
+        let market_contract =
+            &UniversalPorchettaInstance::new(self.market_address, self.rpc_provider.clone());
+        let active = retry_rpc(&self.rpc_retry, || async move {
+            market_contract.activeProofOfferData(intent_id).call().await
+        })
+        .await?;
+        if active.requester == Address::ZERO {
+            return Ok(unbid_state(end_auction_timestamp, timestamp));
+        }
+
+        let resolves = retry_rpc(&self.rpc_retry, || async move {
+            market_contract
+                .Resolve_filter()
+                .topic2(intent_id)
+                .from_block(self.from_block)
+                .query()
+                .await
+        })
+        .await?;
+        if !resolves.is_empty() {
+            return Ok(AuctionState::Resolved);
+        }
+        let at_block = retry_rpc(&self.rpc_retry, || async move {
+            market_contract
+                .Bid_filter()
+                .topic2(intent_id)
+                .from_block(self.from_block)
+                .query()
+                .await
+        })
+        .await?
+        .into_iter()
+        .find_map(|(_, log)| log.block_number);
+        Ok(AuctionState::Bid {
+            bidder: active.requester,
+            prover: active.provider,
+            amount: active.rewardAmount,
+            at_block,
+        })
+    }
+}
+
+fn unbid_state(end_auction_timestamp: u64, timestamp: u64) -> AuctionState {
+    if timestamp > end_auction_timestamp {
+        AuctionState::Expired
+    } else {
+        AuctionState::Open
+    }
+}
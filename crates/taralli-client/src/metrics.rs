@@ -0,0 +1,55 @@
+//! Metrics collected by the clients while running, read through the accessors of the client
+//! collecting them.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::api::subscribe::Delivery;
+
+/// Delivery delay of the requests broadcast by the server, from the server receiving their
+/// submission to the provider receiving their broadcast. Requests broadcast by servers predating
+/// the broadcast metadata aren't counted.
+#[derive(Debug, Default)]
+pub struct DeliveryMetrics {
+    delivered: AtomicU64,
+    total_delay_ms: AtomicU64,
+    max_delay_ms: AtomicU64,
+    last_delay_ms: AtomicU64,
+    last_sequence: AtomicU64,
+}
+
+impl DeliveryMetrics {
+    pub fn record(&self, delivery: &Delivery) {
+        let delay_ms = delivery.delay().as_millis() as u64;
+        self.total_delay_ms.fetch_add(delay_ms, Ordering::Relaxed);
+        self.max_delay_ms.fetch_max(delay_ms, Ordering::Relaxed);
+        self.last_delay_ms.store(delay_ms, Ordering::Relaxed);
+        self.last_sequence
+            .store(delivery.sequence, Ordering::Relaxed);
+        self.delivered.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Number of requests recorded
+    pub fn delivered(&self) -> u64 {
+        self.delivered.load(Ordering::Relaxed)
+    }
+
+    pub fn last_delay(&self) -> Duration {
+        Duration::from_millis(self.last_delay_ms.load(Ordering::Relaxed))
+    }
+
+    pub fn max_delay(&self) -> Duration {
+        Duration::from_millis(self.max_delay_ms.load(Ordering::Relaxed))
+    }
+
+    /// Mean delay of the requests recorded, zero before any is
+    pub fn mean_delay(&self) -> Duration {
+        let total_delay_ms = self.total_delay_ms.load(Ordering::Relaxed);
+        Duration::from_millis(total_delay_ms.checked_div(self.delivered()).unwrap_or(0))
+    }
+
+    /// Sequence number of the last request recorded, `None` before any is
+    pub fn last_sequence(&self) -> Option<u64> {
+        (self.delivered() > 0).then(|| self.last_sequence.load(Ordering::Relaxed))
+    }
+}
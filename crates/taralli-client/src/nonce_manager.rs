@@ -8,7 +8,9 @@ use taralli_primitives::alloy::{
 };
 use taralli_primitives::utils::PERMIT2_ADDRESS;
 
+use crate::api::retry::RetryPolicy;
 use crate::error::{ClientError, Result};
+use crate::rpc::retry_rpc;
 
 const U256_ONE: U256 = U256::from_limbs([1, 0, 0, 0]);
 const U256_256: U256 = U256::from_limbs([256, 0, 0, 0]);
@@ -19,6 +21,7 @@ pub struct Permit2NonceManager<T, P, N> {
     provider: P,
     signer_address: Address,
     nonce_cache: Option<(U256, U256)>,
+    rpc_retry: RetryPolicy,
     _phantom: PhantomData<(T, N)>,
 }
 
@@ -33,10 +36,18 @@ where
             provider,
             nonce_cache: None,
             signer_address,
+            rpc_retry: RetryPolicy::default(),
             _phantom: PhantomData,
         }
     }
 
+    /// attempt the rpc calls reading the nonce bitmaps again as `rpc_retry` allows when they fail
+    /// transiently
+    pub fn with_rpc_retry(mut self, rpc_retry: RetryPolicy) -> Self {
+        self.rpc_retry = rpc_retry;
+        self
+    }
+
     pub async fn get_nonce(&mut self) -> Result<U256> {
         if let Some(nonce_cache) = self.nonce_cache {
             if let Ok(nonce) = self.find_unused_nonce(nonce_cache.0, nonce_cache.1) {
@@ -51,6 +62,37 @@ where
         Ok(nonce)
     }
 
+    /// Get `count` distinct unused nonces, e.g. to sign a set of intents submitted together
+    pub async fn get_nonces(&mut self, count: usize) -> Result<Vec<U256>> {
+        let permit2 = Permit2Instance::new(PERMIT2_ADDRESS, self.provider.clone());
+        let mut nonces = Vec::with_capacity(count);
+        let mut word_pos = U256::ZERO;
+        while nonces.len() < count {
+            let bitmap = self
+                .nonce_bitmap(&permit2, self.signer_address, word_pos)
+                .await?;
+            nonces.extend(
+                (0..256)
+                    .filter(|i| bitmap & (U256_ONE << i) == U256::ZERO)
+                    .map(|i| word_pos * U256_256 + U256::from(i))
+                    .take(count - nonces.len()),
+            );
+            word_pos += U256_ONE;
+        }
+        Ok(nonces)
+    }
+
+    /// Whether `nonce` was already spent by the signer, e.g. by an intent signed ahead of time and
+    /// submitted meanwhile
+    pub async fn is_nonce_used(&self, nonce: U256) -> Result<bool> {
+        let permit2 = Permit2Instance::new(PERMIT2_ADDRESS, self.provider.clone());
+        let bitmap = self
+            .nonce_bitmap(&permit2, self.signer_address, nonce / U256_256)
+            .await?;
+        let bit = (nonce % U256_256).to::<usize>();
+        Ok(bitmap & (U256_ONE << bit) != U256::ZERO)
+    }
+
     async fn fetch_next_word(
         &self,
         signer: Address,
@@ -58,12 +100,7 @@ where
     ) -> Result<(U256, U256)> {
         let mut word_pos = U256::ZERO;
         loop {
-            let bitmap = permit2
-                .nonceBitmap(signer, word_pos)
-                .call()
-                .await
-                .map_err(|e| ClientError::RpcRequestError(e.to_string()))?
-                ._0;
+            let bitmap = self.nonce_bitmap(&permit2, signer, word_pos).await?;
             if bitmap != U256::MAX {
                 return Ok((word_pos, bitmap));
             }
@@ -71,6 +108,19 @@ where
         }
     }
 
+    async fn nonce_bitmap(
+        &self,
+        permit2: &Permit2Instance<T, P, N>,
+        signer: Address,
+        word_pos: U256,
+    ) -> Result<U256> {
+        let bitmap = retry_rpc(&self.rpc_retry, || async move {
+            permit2.nonceBitmap(signer, word_pos).call().await
+        })
+        .await?;
+        Ok(bitmap._0)
+    }
+
     fn find_unused_nonce(&self, word_pos: U256, bitmap: U256) -> Result<U256> {
         for i in 0..256 {
             if bitmap & (U256_ONE << i) == U256::ZERO {
@@ -0,0 +1,200 @@
+//! Reward suggestions for requesters, derived from the prices recent auctions cleared at.
+//!
+//! `Bid` events carry the reward a request cleared at but neither its system nor its proving
+//! time, so clearing prices are ingested from `Trace`s, whose `TraceRecorder` joins the requests
+//! stored by the protocol server with the market's `Bid` events, or recorded one by one.
+
+use std::collections::{HashMap, VecDeque};
+
+use taralli_primitives::alloy::primitives::U256;
+use taralli_primitives::systems::SystemId;
+
+use crate::backtest::Trace;
+
+/// number of clearing prices kept per system when none is configured
+pub const DEFAULT_MAX_SAMPLES: usize = 256;
+/// basis points in one
+const BPS: u64 = 10_000;
+/// factor within which a request's proving time is comparable to the one suggested for
+const PROVING_TIME_TOLERANCE: u32 = 2;
+
+/// Reward a request cleared at
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClearingPrice {
+    /// timestamp of the block including the winning bid
+    pub timestamp: u64,
+    pub reward_amount: U256,
+    /// proving time of the request, in seconds
+    pub proving_time: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RewardPercentile {
+    P25,
+    P50,
+    P75,
+}
+
+/// Percentiles of the rewards recent requests of a system and proving time cleared at. Rewards
+/// are the ones requesters paid, the market's fee included, so they set requests' rewards as is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RewardSuggestion {
+    pub p25: U256,
+    pub p50: U256,
+    pub p75: U256,
+    /// number of clearing prices the percentiles were computed from
+    pub samples: usize,
+}
+
+impl RewardSuggestion {
+    #[must_use]
+    pub fn at(&self, percentile: RewardPercentile) -> U256 {
+        match percentile {
+            RewardPercentile::P25 => self.p25,
+            RewardPercentile::P50 => self.p50,
+            RewardPercentile::P75 => self.p75,
+        }
+    }
+
+    /// Min and max rewards of a request priced with `strategy`
+    #[must_use]
+    pub fn reward_range(&self, strategy: RewardStrategy) -> (U256, U256) {
+        match strategy {
+            RewardStrategy::Fixed(percentile) => (self.at(percentile), self.at(percentile)),
+            RewardStrategy::Band {
+                percentile,
+                spread_bps,
+            } => {
+                let reward = self.at(percentile);
+                let spread = reward.saturating_mul(U256::from(spread_bps)) / U256::from(BPS);
+                (reward.saturating_sub(spread), reward.saturating_add(spread))
+            }
+            RewardStrategy::Interquartile => (self.p25, self.p75),
+        }
+    }
+}
+
+/// How a request's min and max rewards are set from a `RewardSuggestion`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RewardStrategy {
+    /// pay the percentile as a fixed reward
+    Fixed(RewardPercentile),
+    /// auction the reward within `spread_bps` basis points below and above the percentile
+    Band {
+        percentile: RewardPercentile,
+        spread_bps: u64,
+    },
+    /// auction the reward from the 25th up to the 75th percentile
+    Interquartile,
+}
+
+/// Recent clearing prices per system
+#[derive(Clone, Debug)]
+pub struct PriceOracle {
+    history: HashMap<SystemId, VecDeque<ClearingPrice>>,
+    max_samples: usize,
+}
+
+impl Default for PriceOracle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PriceOracle {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            history: HashMap::new(),
+            max_samples: DEFAULT_MAX_SAMPLES,
+        }
+    }
+
+    /// Keep at most `max_samples` clearing prices per system, dropping the oldest ones
+    #[must_use]
+    pub fn with_max_samples(mut self, max_samples: usize) -> Self {
+        self.max_samples = max_samples.max(1);
+        self
+    }
+
+    /// Record the price a request of `system_id` cleared at, kept ordered by timestamp
+    pub fn record(&mut self, system_id: SystemId, price: ClearingPrice) {
+        let history = self.history.entry(system_id).or_default();
+        let position = history.partition_point(|recorded| recorded.timestamp <= price.timestamp);
+        history.insert(position, price);
+        while history.len() > self.max_samples {
+            history.pop_front();
+        }
+    }
+
+    /// Record the clearing price of every request of the trace that got a winning bid,
+    /// returning how many were recorded
+    pub fn ingest_trace(&mut self, trace: &Trace) -> usize {
+        let mut recorded = 0;
+        for entry in &trace.entries {
+            let Some(bid) = &entry.winning_bid else {
+                continue;
+            };
+            self.record(
+                entry.request.system_id,
+                ClearingPrice {
+                    timestamp: bid.timestamp,
+                    reward_amount: bid.reward_amount,
+                    proving_time: entry.request.proof_request.provingTime,
+                },
+            );
+            recorded += 1;
+        }
+        recorded
+    }
+
+    /// Number of clearing prices recorded for `system_id`
+    #[must_use]
+    pub fn samples(&self, system_id: SystemId) -> usize {
+        self.history.get(&system_id).map_or(0, VecDeque::len)
+    }
+
+    /// Suggest rewards for a request of `system_id` proven within `proving_time` seconds, from
+    /// the prices requests of a comparable proving time cleared at, or of any proving time when
+    /// none is comparable. Percentiles are taken by nearest rank. `None` when no price was
+    /// recorded for the system.
+    #[must_use]
+    pub fn suggest_reward(
+        &self,
+        system_id: SystemId,
+        proving_time: u32,
+    ) -> Option<RewardSuggestion> {
+        let history = self
+            .history
+            .get(&system_id)
+            .filter(|history| !history.is_empty())?;
+        let mut rewards: Vec<U256> = history
+            .iter()
+            .filter(|price| is_comparable(price.proving_time, proving_time))
+            .map(|price| price.reward_amount)
+            .collect();
+        if rewards.is_empty() {
+            rewards = history.iter().map(|price| price.reward_amount).collect();
+        }
+        rewards.sort_unstable();
+        Some(RewardSuggestion {
+            p25: nearest_rank(&rewards, 25),
+            p50: nearest_rank(&rewards, 50),
+            p75: nearest_rank(&rewards, 75),
+            samples: rewards.len(),
+        })
+    }
+}
+
+/// Whether a request of `proving_time` is priced like one of `target` seconds, i.e. within
+/// `PROVING_TIME_TOLERANCE` times of it
+fn is_comparable(proving_time: u32, target: u32) -> bool {
+    proving_time.saturating_mul(PROVING_TIME_TOLERANCE) >= target
+        && proving_time <= target.saturating_mul(PROVING_TIME_TOLERANCE)
+}
+
+/// Smallest value of the sorted, non empty `values` at or above `percent` percent of them
+fn nearest_rank(values: &[U256], percent: usize) -> U256 {
+    let rank = (percent * values.len()).div_ceil(100).max(1);
+    values[rank - 1]
+}
@@ -0,0 +1,27 @@
+//! Persistence of the local state files of clients. Files are replaced atomically: written to a
+//! temporary file synced to disk, then renamed over the previous one, so a crash mid-write leaves
+//! either the previous contents or the new ones. Writes from async code run on the blocking pool
+//! instead of the executor.
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Replace the contents of `path` with `contents` atomically
+pub fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_name);
+    {
+        let mut file = std::fs::File::create(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+    }
+    std::fs::rename(&tmp_path, path)
+}
+
+/// `write_atomically` on the blocking pool
+pub async fn persist(path: PathBuf, contents: Vec<u8>) -> io::Result<()> {
+    tokio::task::spawn_blocking(move || write_atomically(&path, &contents))
+        .await
+        .map_err(io::Error::other)?
+}
@@ -0,0 +1,282 @@
+//! Profit and loss of the requests a provider bids upon, for reporting what it earned net of gas.
+//!
+//! Each request won is recorded with the reward and stake of its winning bid along with the gas
+//! of its bid and resolve transactions, read from their receipts. Requests the provider fails to
+//! resolve after bidding are recorded as defaults, their stake being slashed by the market.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta;
+use taralli_primitives::alloy::network::ReceiptResponse;
+use taralli_primitives::alloy::primitives::{Address, B256, U256};
+use taralli_primitives::intents::envelope::unix_time_ms;
+use taralli_primitives::systems::SystemId;
+
+use crate::error::{ClientError, Result};
+
+/// Gas a transaction cost, as read from its receipt
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GasCost {
+    pub tx_hash: B256,
+    pub gas_used: u64,
+    /// price paid per unit of gas, in wei
+    pub effective_gas_price: u128,
+}
+
+impl GasCost {
+    pub fn from_receipt<R: ReceiptResponse>(receipt: &R) -> Self {
+        Self {
+            tx_hash: receipt.transaction_hash(),
+            gas_used: receipt.gas_used(),
+            effective_gas_price: receipt.effective_gas_price(),
+        }
+    }
+
+    /// Fee paid for the transaction, in wei
+    pub fn fee(&self) -> U256 {
+        U256::from(self.gas_used) * U256::from(self.effective_gas_price)
+    }
+}
+
+/// What became of a request after it was won
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "outcome")]
+pub enum IntentOutcome {
+    /// bid upon, not resolved yet
+    Pending,
+    Resolved {
+        resolve_gas: GasCost,
+    },
+    /// not resolved by the provider, the reason is given
+    Defaulted {
+        reason: String,
+    },
+}
+
+/// Request won by the provider, as persisted
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PnlEntry {
+    pub intent_id: B256,
+    pub system_id: SystemId,
+    pub reward_token: Address,
+    /// reward of the winning bid, earned once resolved
+    pub reward_amount: U256,
+    /// eth staked by the winning bid, slashed on default
+    pub stake: U256,
+    /// unix time in seconds the bid was recorded at
+    pub won_at: u64,
+    pub bid_gas: GasCost,
+    #[serde(flatten)]
+    pub outcome: IntentOutcome,
+}
+
+impl PnlEntry {
+    /// Gas fees paid for the request's transactions, in wei
+    pub fn gas_spent(&self) -> U256 {
+        match &self.outcome {
+            IntentOutcome::Resolved { resolve_gas } => self.bid_gas.fee() + resolve_gas.fee(),
+            _ => self.bid_gas.fee(),
+        }
+    }
+}
+
+/// Profit and loss of the requests of one system won over a period
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SystemPnl {
+    pub intents_won: u64,
+    pub intents_resolved: u64,
+    /// rewards earned by resolving, per reward token
+    pub gross_rewards: HashMap<Address, U256>,
+    /// gas fees paid for bids and resolves, in wei
+    pub gas_spent: U256,
+    pub defaults: u64,
+    /// stake lost to defaults, in wei
+    pub stake_slashed: U256,
+}
+
+/// Profit and loss per system of the requests won over a period
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PnlReport {
+    /// unix time in seconds the period starts at, inclusive
+    pub from: u64,
+    /// unix time in seconds the period ends at, exclusive
+    pub to: u64,
+    pub systems: HashMap<SystemId, SystemPnl>,
+}
+
+impl PnlReport {
+    pub fn to_json(&self) -> String {
+        // keys are system ids and addresses, both serialized as strings
+        serde_json::to_string_pretty(self).expect("pnl reports serialize to json")
+    }
+
+    /// One row per system and reward token, gas and defaults being reported on each row of
+    /// their system
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "system_id,intents_won,intents_resolved,reward_token,gross_reward,gas_spent_wei,defaults,stake_slashed_wei\n",
+        );
+        let mut systems: Vec<_> = self.systems.iter().collect();
+        systems.sort_by_key(|(system_id, _)| system_id.as_bit());
+        for (system_id, pnl) in systems {
+            let mut rewards: Vec<_> = pnl.gross_rewards.iter().collect();
+            rewards.sort();
+            let rewards = if rewards.is_empty() {
+                vec![(None, U256::ZERO)]
+            } else {
+                rewards
+                    .into_iter()
+                    .map(|(token, amount)| (Some(*token), *amount))
+                    .collect()
+            };
+            for (token, amount) in rewards {
+                let _ = writeln!(
+                    csv,
+                    "{},{},{},{},{},{},{},{}",
+                    system_id.as_str(),
+                    pnl.intents_won,
+                    pnl.intents_resolved,
+                    token.map(|token| token.to_string()).unwrap_or_default(),
+                    amount,
+                    pnl.gas_spent,
+                    pnl.defaults,
+                    pnl.stake_slashed
+                );
+            }
+        }
+        csv
+    }
+}
+
+/// Requests won by a provider, persisted to a JSON file when one is given
+#[derive(Debug, Default)]
+pub struct PnlLedger {
+    entries: Mutex<Vec<PnlEntry>>,
+    path: Option<PathBuf>,
+}
+
+impl PnlLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ledger persisted to `path`, starting from the entries it holds if it exists
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let entries: Vec<PnlEntry> = match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| {
+                ClientError::ConfigError(format!("parsing {}: {e}", path.display()))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(ClientError::ConfigError(format!(
+                    "reading {}: {e}",
+                    path.display()
+                )))
+            }
+        };
+        Ok(Self {
+            entries: Mutex::new(entries),
+            path: Some(path.to_path_buf()),
+        })
+    }
+
+    /// Record a request won by the bid of `bid_event`, whose transaction cost `bid_gas`
+    pub fn record_bid(
+        &self,
+        system_id: SystemId,
+        bid_event: &UniversalBombetta::Bid,
+        bid_gas: GasCost,
+    ) {
+        let intent_id = bid_event.requestId;
+        self.update(|entries| {
+            entries.retain(|entry| entry.intent_id != intent_id);
+            entries.push(PnlEntry {
+                intent_id,
+                system_id,
+                reward_token: bid_event.rewardToken,
+                reward_amount: bid_event.rewardAmount,
+                stake: bid_event.ethStake,
+                won_at: unix_time_ms() / 1000,
+                bid_gas,
+                outcome: IntentOutcome::Pending,
+            });
+        });
+    }
+
+    /// Record the resolve of a won request
+    pub fn record_resolve(&self, intent_id: B256, resolve_gas: GasCost) {
+        self.set_outcome(intent_id, IntentOutcome::Resolved { resolve_gas });
+    }
+
+    /// Record a won request the provider gave up on
+    pub fn record_default(&self, intent_id: B256, reason: String) {
+        self.set_outcome(intent_id, IntentOutcome::Defaulted { reason });
+    }
+
+    /// Requests recorded, in the order they were won
+    pub fn entries(&self) -> Vec<PnlEntry> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    /// Summary per system of the requests won within `period`, in unix seconds
+    pub fn report(&self, period: Range<u64>) -> PnlReport {
+        let mut systems: HashMap<SystemId, SystemPnl> = HashMap::new();
+        for entry in self.entries.lock().unwrap().iter() {
+            if !period.contains(&entry.won_at) {
+                continue;
+            }
+            let pnl = systems.entry(entry.system_id).or_default();
+            pnl.intents_won += 1;
+            pnl.gas_spent += entry.gas_spent();
+            match &entry.outcome {
+                IntentOutcome::Pending => {}
+                IntentOutcome::Resolved { .. } => {
+                    pnl.intents_resolved += 1;
+                    *pnl.gross_rewards.entry(entry.reward_token).or_default() +=
+                        entry.reward_amount;
+                }
+                IntentOutcome::Defaulted { .. } => {
+                    pnl.defaults += 1;
+                    pnl.stake_slashed += entry.stake;
+                }
+            }
+        }
+        PnlReport {
+            from: period.start,
+            to: period.end,
+            systems,
+        }
+    }
+
+    fn set_outcome(&self, intent_id: B256, outcome: IntentOutcome) {
+        self.update(|entries| {
+            match entries
+                .iter_mut()
+                .find(|entry| entry.intent_id == intent_id)
+            {
+                Some(entry) => entry.outcome = outcome,
+                None => tracing::warn!("no bid recorded for request {}", intent_id),
+            }
+        });
+    }
+
+    fn update(&self, update: impl FnOnce(&mut Vec<PnlEntry>)) {
+        let mut entries = self.entries.lock().unwrap();
+        update(&mut entries);
+
+        if let Some(path) = &self.path {
+            let written = serde_json::to_string(&*entries)
+                .map_err(|e| e.to_string())
+                .and_then(|json| std::fs::write(path, json).map_err(|e| e.to_string()));
+            if let Err(e) = written {
+                tracing::warn!("failed to persist pnl ledger to {}: {e}", path.display());
+            }
+        }
+    }
+}
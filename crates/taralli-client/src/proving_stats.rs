@@ -0,0 +1,280 @@
+//! Proving durations observed by a provider, refining the estimate of how long a request of a
+//! given system and workload size takes to prove.
+//!
+//! Durations are averaged per system and workload size bucket, each bucket covering sizes up to
+//! twice the previous one. Until a bucket holds samples, estimates fall back to the proving time
+//! rates of the configured `ResourceLimits`.
+//!
+//! Providers turn requests down when the estimate exceeds their proving time before bidding on
+//! them, and price requests by their estimated proving time through `usd_cost`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::systems::{arkworks::R1csHeader, SystemId, SystemParams};
+use taralli_primitives::validation::limits::ResourceLimits;
+
+use crate::error::{ClientError, Result};
+use crate::persist::persist;
+
+const MIB: u64 = 1024 * 1024;
+const MILLION: u64 = 1_000_000;
+
+/// Workload size of a system, the size hint estimates are made for: bytes of ELF and inputs for
+/// the systems proving a program's execution, constraints for the ones proving a circuit (bytes
+/// of r1cs when its header can't be parsed)
+pub fn workload_size(system: &SystemParams) -> u64 {
+    match system {
+        SystemParams::Risc0(params) => (params.elf.len() + params.inputs.len()) as u64,
+        SystemParams::Sp1(params) => (params.elf.len() + params.inputs.len()) as u64,
+        SystemParams::Arkworks(params) => R1csHeader::parse(&params.r1cs)
+            .map_or(params.r1cs.len() as u64, |header| {
+                u64::from(header.constraints)
+            }),
+    }
+}
+
+/// Bucket of the sizes in `[2^(bucket - 1), 2^bucket)`, zero sized workloads have their own
+fn bucket(size_hint: u64) -> u32 {
+    u64::BITS - size_hint.leading_zeros()
+}
+
+#[derive(Clone, Debug)]
+pub struct ProvingStatsConfig {
+    /// weight of a new duration in the average, between 0 and 1
+    pub smoothing: f64,
+    /// samples a bucket needs before its average is trusted as is
+    pub min_samples: u32,
+    /// factor applied to the average of buckets with fewer than `min_samples` samples
+    pub low_sample_multiplier: f64,
+    /// proving time rates estimates fall back to for buckets without samples
+    pub fallback: ResourceLimits,
+}
+
+impl Default for ProvingStatsConfig {
+    fn default() -> Self {
+        Self {
+            smoothing: 0.2,
+            min_samples: 5,
+            low_sample_multiplier: 2.0,
+            fallback: ResourceLimits::default(),
+        }
+    }
+}
+
+/// Average proving duration of a bucket, as persisted
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProvingRecord {
+    pub system_id: SystemId,
+    pub bucket: u32,
+    pub mean_seconds: f64,
+    pub samples: u32,
+    /// executions cancelled for exceeding their proving budget, also counted in `samples`
+    #[serde(default)]
+    pub timeouts: u32,
+}
+
+/// Proving durations per system and workload size bucket, persisted to a JSON file when one is
+/// given
+#[derive(Debug)]
+pub struct ProvingStats {
+    config: ProvingStatsConfig,
+    records: Mutex<HashMap<(SystemId, u32), ProvingRecord>>,
+    path: Option<PathBuf>,
+    // held while the records are persisted, so writes land in the order records were taken in
+    persisting: tokio::sync::Mutex<()>,
+}
+
+impl ProvingStats {
+    pub fn new(config: ProvingStatsConfig) -> Self {
+        Self {
+            config,
+            records: Mutex::new(HashMap::new()),
+            path: None,
+            persisting: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Stats persisted to `path`, starting from the records it holds if it exists
+    pub fn load(config: ProvingStatsConfig, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let records: Vec<ProvingRecord> = match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| {
+                ClientError::ConfigError(format!("parsing {}: {e}", path.display()))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(ClientError::ConfigError(format!(
+                    "reading {}: {e}",
+                    path.display()
+                )))
+            }
+        };
+        Ok(Self {
+            config,
+            records: Mutex::new(
+                records
+                    .into_iter()
+                    .map(|record| ((record.system_id, record.bucket), record))
+                    .collect(),
+            ),
+            path: Some(path.to_path_buf()),
+            persisting: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    /// Record the duration a workload of `size_hint` took to prove
+    pub async fn record(&self, system_id: SystemId, size_hint: u64, duration: Duration) {
+        self.add_sample(system_id, size_hint, duration, false).await;
+    }
+
+    /// Record a workload of `size_hint` cancelled after `budget` without being proved. The budget
+    /// is recorded as its duration, a lower bound of what proving it takes, so estimates of
+    /// similar workloads grow past it.
+    pub async fn record_timeout(&self, system_id: SystemId, size_hint: u64, budget: Duration) {
+        self.add_sample(system_id, size_hint, budget, true).await;
+    }
+
+    /// Number of executions cancelled for exceeding their proving budget, for workloads of the
+    /// bucket of `size_hint`
+    pub fn timeouts(&self, system_id: SystemId, size_hint: u64) -> u32 {
+        self.records
+            .lock()
+            .unwrap()
+            .get(&(system_id, bucket(size_hint)))
+            .map_or(0, |record| record.timeouts)
+    }
+
+    async fn add_sample(
+        &self,
+        system_id: SystemId,
+        size_hint: u64,
+        duration: Duration,
+        timeout: bool,
+    ) {
+        {
+            let mut records = self.records.lock().unwrap();
+            let bucket = bucket(size_hint);
+            let seconds = duration.as_secs_f64();
+            let record = records
+                .entry((system_id, bucket))
+                .and_modify(|record| {
+                    record.mean_seconds += self.config.smoothing * (seconds - record.mean_seconds);
+                    record.samples = record.samples.saturating_add(1);
+                })
+                .or_insert(ProvingRecord {
+                    system_id,
+                    bucket,
+                    mean_seconds: seconds,
+                    samples: 1,
+                    timeouts: 0,
+                });
+            if timeout {
+                record.timeouts = record.timeouts.saturating_add(1);
+            }
+        }
+
+        let Some(path) = &self.path else {
+            return;
+        };
+        // the last write to start persists the latest records, whatever order samples land in
+        let _persisting = self.persisting.lock().await;
+        let json = {
+            let records = self.records.lock().unwrap();
+            let mut snapshot: Vec<&ProvingRecord> = records.values().collect();
+            snapshot.sort_by_key(|record| (record.system_id.as_bit(), record.bucket));
+            serde_json::to_vec(&snapshot)
+        };
+        let written = match json {
+            Ok(json) => persist(path.clone(), json).await.map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        if let Err(e) = written {
+            tracing::warn!("failed to persist proving stats to {}: {e}", path.display());
+        }
+    }
+
+    /// Number of durations recorded for workloads of the bucket of `size_hint`
+    pub fn samples(&self, system_id: SystemId, size_hint: u64) -> u32 {
+        self.records
+            .lock()
+            .unwrap()
+            .get(&(system_id, bucket(size_hint)))
+            .map_or(0, |record| record.samples)
+    }
+
+    /// Whether estimates of workloads of `size_hint` are made from enough recorded durations
+    pub fn has_enough_samples(&self, system_id: SystemId, size_hint: u64) -> bool {
+        self.samples(system_id, size_hint) >= self.config.min_samples
+    }
+
+    /// Expected duration of proving a workload of `size_hint`. Averages of buckets with fewer
+    /// than `min_samples` samples are scaled up by `low_sample_multiplier`, buckets without any
+    /// fall back to the configured proving time rates.
+    pub fn estimate(&self, system_id: SystemId, size_hint: u64) -> Duration {
+        let records = self.records.lock().unwrap();
+        let Some(record) = records.get(&(system_id, bucket(size_hint))) else {
+            return self.fallback(system_id, size_hint);
+        };
+        let seconds = if record.samples >= self.config.min_samples {
+            record.mean_seconds
+        } else {
+            record.mean_seconds * self.config.low_sample_multiplier
+        };
+        Duration::from_secs_f64(seconds.max(0.0))
+    }
+
+    /// Fail with `ClientError::IntentAnalysisError` when `request` is expected to take longer to
+    /// prove than its proving time. Estimates made from too few samples aren't trusted to turn
+    /// requests down.
+    pub fn ensure_provable_in_time(&self, request: &ComputeRequest<SystemParams>) -> Result<()> {
+        let size_hint = workload_size(&request.system);
+        if !self.has_enough_samples(request.system_id, size_hint) {
+            return Ok(());
+        }
+        let estimate = self.estimate(request.system_id, size_hint);
+        let proving_time = Duration::from_secs(request.proof_request.provingTime.into());
+        if estimate > proving_time {
+            return Err(ClientError::IntentAnalysisError(format!(
+                "estimated proving time of {}s exceeds the request's {}s",
+                estimate.as_secs(),
+                proving_time.as_secs()
+            )));
+        }
+        Ok(())
+    }
+
+    /// USD cost of requests priced at `usd_per_proving_second` of their estimated proving time,
+    /// e.g. the cost of a `NormalizedCost`
+    pub fn usd_cost(
+        self: Arc<Self>,
+        usd_per_proving_second: f64,
+    ) -> impl Fn(&ComputeRequest<SystemParams>) -> f64 + Send + Sync + 'static {
+        move |request| {
+            let size_hint = workload_size(&request.system);
+            self.estimate(request.system_id, size_hint).as_secs_f64() * usd_per_proving_second
+        }
+    }
+
+    /// Proving time the configured rates require for a workload of `size_hint`
+    fn fallback(&self, system_id: SystemId, size_hint: u64) -> Duration {
+        let limits = &self.config.fallback;
+        let seconds = match system_id {
+            SystemId::Risc0 => {
+                size_hint.div_ceil(MIB) * u64::from(limits.risc0.proving_seconds_per_mib)
+            }
+            SystemId::Sp1 => {
+                size_hint.div_ceil(MIB) * u64::from(limits.sp1.proving_seconds_per_mib)
+            }
+            SystemId::Arkworks => {
+                size_hint.div_ceil(MILLION)
+                    * u64::from(limits.arkworks.proving_seconds_per_million_constraints)
+            }
+        };
+        Duration::from_secs(seconds)
+    }
+}
@@ -1,6 +1,6 @@
 use crate::error::Result;
 use async_trait::async_trait;
-use taralli_primitives::alloy::primitives::{Bytes, FixedBytes};
+use taralli_primitives::alloy::primitives::FixedBytes;
 
 use taralli_primitives::alloy::network::Network;
 
@@ -11,9 +11,16 @@ pub mod request;
 #[async_trait]
 pub trait IntentResolver<N: Network> {
     type Intent;
+    /// Arguments of the market's resolve besides the intent id, built from the result of the
+    /// worker: the `WorkResult` itself on markets taking its partial commitment
+    type ResolveParams: Send + Sync;
+
+    /// Resolve `intent_id` with `params`. Every intent is resolved in its own transaction:
+    /// neither market exposes a batch resolve, and both only accept resolves sent by the bidding
+    /// provider, which rules out routing them through a multicall contract.
     async fn resolve_intent(
         &self,
         intent_id: FixedBytes<32>,
-        opaque_submission: Bytes,
+        params: &Self::ResolveParams,
     ) -> Result<N::ReceiptResponse>;
 }
@@ -1,6 +1,8 @@
 use std::marker::PhantomData;
+use std::sync::Arc;
 
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use taralli_primitives::abi::universal_porchetta::UniversalPorchetta::UniversalPorchettaInstance;
 use taralli_primitives::alloy::network::Network;
 use taralli_primitives::alloy::primitives::{Address, Bytes, FixedBytes};
@@ -9,10 +11,39 @@ use taralli_primitives::alloy::transports::Transport;
 use taralli_primitives::intents::offer::ComputeOffer;
 use taralli_primitives::systems::SystemParams;
 
+use crate::confirmations::Confirmations;
 use crate::error::{ClientError, Result};
+use crate::submitter::{submit_transaction, TransactionSubmitter};
+use crate::worker::WorkResult;
 
 use super::IntentResolver;
 
+/// Arguments of `UniversalPorchetta.resolve(offerId, opaqueSubmission)` besides the offer id.
+/// The market checks the submission against the inputs commitment and verifier details it stored
+/// from the offer when it was bid upon, so neither the bidding requester's signature nor an
+/// attestation of the inputs they delivered is part of the calldata. Offers check no partial
+/// commitment either.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OfferResolveParams {
+    pub opaque_submission: Bytes,
+}
+
+impl From<WorkResult> for OfferResolveParams {
+    fn from(work_result: WorkResult) -> Self {
+        Self {
+            opaque_submission: work_result.opaque_submission,
+        }
+    }
+}
+
+impl From<&WorkResult> for OfferResolveParams {
+    fn from(work_result: &WorkResult) -> Self {
+        Self {
+            opaque_submission: work_result.opaque_submission.clone(),
+        }
+    }
+}
+
 /// Resolver for `ComputeOffers`
 pub struct ComputeOfferResolver<T, P, N>
 where
@@ -22,6 +53,10 @@ where
 {
     rpc_provider: P,
     market_address: Address,
+    // sends the transactions instead of `rpc_provider` when set
+    submitter: Option<Arc<dyn TransactionSubmitter<N>>>,
+    // depth resolve transactions are confirmed at before their receipt is returned
+    confirmations: Confirmations,
     phantom_data: PhantomData<(T, N)>,
 }
 
@@ -35,9 +70,24 @@ where
         Self {
             rpc_provider,
             market_address,
+            submitter: None,
+            confirmations: Confirmations::default(),
             phantom_data: PhantomData,
         }
     }
+
+    /// send resolve transactions through the given submitter (e.g. a private rpc) instead of the rpc provider
+    pub fn with_submitter(mut self, submitter: Arc<dyn TransactionSubmitter<N>>) -> Self {
+        self.submitter = Some(submitter);
+        self
+    }
+
+    /// wait for resolve transactions to be confirmed at the given depth, failing with
+    /// `ClientError::TransactionReorged` when they're reorged out meanwhile
+    pub fn with_confirmations(mut self, confirmations: Confirmations) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
 }
 
 #[async_trait]
@@ -48,30 +98,33 @@ where
     N: Network + Clone,
 {
     type Intent = ComputeOffer<SystemParams>;
+    type ResolveParams = OfferResolveParams;
 
     async fn resolve_intent(
         &self,
         intent_id: FixedBytes<32>,
-        opaque_submission: Bytes,
+        params: &OfferResolveParams,
     ) -> Result<N::ReceiptResponse> {
         tracing::info!("resolving intent");
 
         let market_contract =
             UniversalPorchettaInstance::new(self.market_address, self.rpc_provider.clone());
 
-        let call_return = market_contract
-            .resolve(intent_id, opaque_submission)
-            .send()
-            .await
-            .map_err(|e| ClientError::TransactionError(e.to_string()))?;
+        let resolve_transaction = market_contract
+            .resolve(intent_id, params.opaque_submission.clone())
+            .into_transaction_request();
 
-        let receipt = call_return
-            .get_receipt()
-            .await
-            .map_err(|e| ClientError::TransactionFailure(e.to_string()))?;
+        let receipt = submit_transaction::<T, P, N>(
+            &self.rpc_provider,
+            self.submitter.as_ref(),
+            resolve_transaction,
+        )
+        .await?;
 
         tracing::info!("resolve txs receipt: {:?}", receipt);
 
-        Ok(receipt)
+        self.confirmations
+            .confirm_receipt::<T, P, N>(&self.rpc_provider, receipt)
+            .await
     }
 }
@@ -1,19 +1,24 @@
 use std::marker::PhantomData;
+use std::sync::Arc;
 
 use async_trait::async_trait;
 use taralli_primitives::abi::universal_bombetta::UniversalBombetta::UniversalBombettaInstance;
 use taralli_primitives::alloy::network::Network;
-use taralli_primitives::alloy::primitives::{Address, Bytes, FixedBytes, B256};
+use taralli_primitives::alloy::primitives::{Address, FixedBytes};
 use taralli_primitives::alloy::providers::Provider;
 use taralli_primitives::alloy::transports::Transport;
 use taralli_primitives::intents::request::ComputeRequest;
 use taralli_primitives::systems::SystemParams;
 
-use crate::error::{ClientError, Result};
+use crate::confirmations::Confirmations;
+use crate::error::Result;
+use crate::submitter::{submit_transaction, TransactionSubmitter};
+use crate::worker::WorkResult;
 
 use super::IntentResolver;
 
-/// Resolver for `ComputeRequests`
+/// Resolver for `ComputeRequests`. The market only accepts resolves sent by the provider that bid
+/// (`msg.sender == provider`), so resolves go through the same signer as the bids.
 pub struct ComputeRequestResolver<T, P, N>
 where
     T: Transport + Clone,
@@ -22,6 +27,10 @@ where
 {
     rpc_provider: P,
     market_address: Address,
+    // sends the transactions instead of `rpc_provider` when set
+    submitter: Option<Arc<dyn TransactionSubmitter<N>>>,
+    // depth resolve transactions are confirmed at before their receipt is returned
+    confirmations: Confirmations,
     phantom_data: PhantomData<(T, N)>,
 }
 
@@ -35,9 +44,24 @@ where
         Self {
             rpc_provider,
             market_address,
+            submitter: None,
+            confirmations: Confirmations::default(),
             phantom_data: PhantomData,
         }
     }
+
+    /// send resolve transactions through the given submitter (e.g. a private rpc) instead of the rpc provider
+    pub fn with_submitter(mut self, submitter: Arc<dyn TransactionSubmitter<N>>) -> Self {
+        self.submitter = Some(submitter);
+        self
+    }
+
+    /// wait for resolve transactions to be confirmed at the given depth, failing with
+    /// `ClientError::TransactionReorged` when they're reorged out meanwhile
+    pub fn with_confirmations(mut self, confirmations: Confirmations) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
 }
 
 #[async_trait]
@@ -48,30 +72,39 @@ where
     N: Network + Clone,
 {
     type Intent = ComputeRequest<SystemParams>;
+    /// `UniversalBombetta.resolve` takes the opaque submission and the submitted partial
+    /// commitment, both fields of the work result
+    type ResolveParams = WorkResult;
 
     async fn resolve_intent(
         &self,
         intent_id: FixedBytes<32>,
-        opaque_submission: Bytes,
+        work_result: &WorkResult,
     ) -> Result<N::ReceiptResponse> {
         tracing::info!("resolving intent");
 
         let market_contract =
             UniversalBombettaInstance::new(self.market_address, self.rpc_provider.clone());
 
-        let call_return = market_contract
-            .resolve(intent_id, opaque_submission, B256::ZERO)
-            .send()
-            .await
-            .map_err(|e| ClientError::TransactionError(e.to_string()))?;
+        let resolve_transaction = market_contract
+            .resolve(
+                intent_id,
+                work_result.opaque_submission.clone(),
+                work_result.partial_commitment,
+            )
+            .into_transaction_request();
 
-        let receipt = call_return
-            .get_receipt()
-            .await
-            .map_err(|e| ClientError::TransactionFailure(e.to_string()))?;
+        let receipt = submit_transaction::<T, P, N>(
+            &self.rpc_provider,
+            self.submitter.as_ref(),
+            resolve_transaction,
+        )
+        .await?;
 
         tracing::info!("resolve txs receipt: {:?}", receipt);
 
-        Ok(receipt)
+        self.confirmations
+            .confirm_receipt::<T, P, N>(&self.rpc_provider, receipt)
+            .await
     }
 }
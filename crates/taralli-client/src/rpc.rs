@@ -0,0 +1,180 @@
+//! Classification and retrying of failed rpc calls.
+//!
+//! Failures are classified from the error alloy reports, its json-rpc error code or http status
+//! when it carries one, its message otherwise. Only transient and rate limited failures are
+//! retried, a missing method or a reverted call failing the same way on every attempt.
+
+use std::fmt;
+use std::future::Future;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::retry::RetryPolicy;
+use crate::error::{ClientError, Result};
+
+/// Kind of a failed rpc call
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RpcErrorKind {
+    /// network failure or an rpc node struggling (connection reset, timeout, 5xx), worth retrying
+    Transient,
+    /// the rpc throttled the call, worth retrying after backing off
+    RateLimited,
+    /// the rpc doesn't serve the call (unknown method, invalid params, malformed response)
+    Method,
+    /// the call reverted, as it will on every attempt against the same state
+    Revert,
+}
+
+impl RpcErrorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Transient => "transient",
+            Self::RateLimited => "rate_limited",
+            Self::Method => "method",
+            Self::Revert => "revert",
+        }
+    }
+
+    /// Whether a call failing this way may succeed when attempted again
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, Self::Transient | Self::RateLimited)
+    }
+
+    /// Kind of a json-rpc error code, `None` for the codes rpcs return for any kind of failure
+    pub fn from_code(code: i64) -> Option<Self> {
+        match code {
+            // execution reverted
+            3 => Some(Self::Revert),
+            // limit exceeded
+            -32005 => Some(Self::RateLimited),
+            // parse error, invalid request, method not found, invalid params, unsupported method
+            -32700 | -32600 | -32601 | -32602 | -32004 => Some(Self::Method),
+            // internal error
+            -32603 => Some(Self::Transient),
+            _ => None,
+        }
+    }
+
+    /// Kind of an http status an rpc answered with, `None` for successful ones
+    pub fn from_http_status(status: u16) -> Option<Self> {
+        match status {
+            429 => Some(Self::RateLimited),
+            500..=599 | 408 => Some(Self::Transient),
+            400..=499 => Some(Self::Method),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RpcErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+const REVERT_PATTERNS: &[&str] = &["execution reverted", "revert"];
+const RATE_LIMIT_PATTERNS: &[&str] = &[
+    "rate limit",
+    "too many requests",
+    "limit exceeded",
+    "exceeded the quota",
+    "capacity exceeded",
+];
+const METHOD_PATTERNS: &[&str] = &[
+    "method not found",
+    "does not exist/is not available",
+    "not supported",
+    "unsupported",
+    "invalid params",
+    "invalid argument",
+    "deserialization error",
+];
+
+/// Kind of a failed rpc call from the error alloy reports for it, e.g.
+/// `server returned an error response: error code -32601: the method eth_foo does not exist`
+pub fn classify_rpc_error(message: &str) -> RpcErrorKind {
+    let message = message.to_lowercase();
+    let contains_any = |patterns: &[&str]| patterns.iter().any(|pattern| message.contains(pattern));
+
+    if contains_any(REVERT_PATTERNS) {
+        return RpcErrorKind::Revert;
+    }
+    if let Some(kind) = number_after(&message, "error code ").and_then(RpcErrorKind::from_code) {
+        return kind;
+    }
+    if let Some(kind) = number_after(&message, "http error ")
+        .and_then(|status| u16::try_from(status).ok())
+        .and_then(RpcErrorKind::from_http_status)
+    {
+        return kind;
+    }
+    if contains_any(RATE_LIMIT_PATTERNS) {
+        return RpcErrorKind::RateLimited;
+    }
+    if contains_any(METHOD_PATTERNS) {
+        return RpcErrorKind::Method;
+    }
+    // connection resets, timeouts, nodes behind on the requested block and anything unknown,
+    // which is given the bounded retries
+    RpcErrorKind::Transient
+}
+
+/// Integer following `prefix` in `message`
+fn number_after(message: &str, prefix: &str) -> Option<i64> {
+    let rest = &message[message.find(prefix)? + prefix.len()..];
+    let end = rest
+        .char_indices()
+        .find(|(i, c)| !(c.is_ascii_digit() || (*i == 0 && *c == '-')))
+        .map_or(rest.len(), |(i, _)| i);
+    rest[..end].parse().ok()
+}
+
+impl ClientError {
+    /// Error of a failed rpc call, classified
+    pub fn rpc(error: impl fmt::Display) -> Self {
+        let message = error.to_string();
+        ClientError::RpcFailed {
+            kind: classify_rpc_error(&message),
+            message,
+        }
+    }
+}
+
+/// Run the rpc call made by `call`, attempting it again on transient and rate limited failures as
+/// `policy` allows. Rate limited calls back off for the whole backoff ceiling rather than a
+/// jittered part of it. Fails with `ClientError::RpcFailed` carrying the kind of the last failure.
+pub async fn retry_rpc<T, E, F, Fut>(policy: &RetryPolicy, mut call: F) -> Result<T>
+where
+    E: fmt::Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = core::result::Result<T, E>>,
+{
+    let started = Instant::now();
+    let mut retry = 0;
+    loop {
+        let error = match call().await {
+            Ok(value) => return Ok(value),
+            Err(e) => ClientError::rpc(e),
+        };
+        let Some(kind) = error.rpc_error_kind().filter(RpcErrorKind::is_retryable) else {
+            return Err(error);
+        };
+        let backoff = match kind {
+            RpcErrorKind::RateLimited => policy.backoff_ceiling(retry),
+            _ => policy.backoff(retry),
+        };
+        if retry >= policy.max_retries || started.elapsed() + backoff > policy.deadline {
+            return Err(error);
+        }
+        tracing::warn!(
+            "rpc call failed ({}), attempting again in {:?}: {}",
+            kind,
+            backoff,
+            error
+        );
+        tokio::time::sleep(backoff).await;
+        retry += 1;
+    }
+}
@@ -3,6 +3,7 @@ use async_trait::async_trait;
 use crate::error::Result;
 
 pub mod offer;
+pub mod request;
 
 /// core searcher trait used across all compute intent markets
 #[async_trait]
@@ -0,0 +1,226 @@
+use std::cmp::Reverse;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use taralli_primitives::alloy::{
+    network::Network,
+    primitives::{Address, FixedBytes, U256},
+    providers::Provider,
+    transports::Transport,
+};
+use taralli_primitives::{
+    intents::{auction::RewardCurve, request::ComputeRequest, ComputeIntent},
+    markets::MarketFee,
+    systems::{SystemId, SystemParams},
+};
+use url::Url;
+
+use crate::analyzer::request::request_urgency;
+use crate::api::query::QueryApiClient;
+use crate::clock::ChainClock;
+use crate::error::{ClientError, Result};
+use crate::market_state::MarketStateClient;
+
+use super::IntentSearcher;
+
+/// Cost of fulfilling a request, in units of its reward token
+pub trait CostModel: Send + Sync {
+    fn cost(&self, request: &ComputeRequest<SystemParams>) -> U256;
+}
+
+/// Same cost for every request
+pub struct FixedCost(pub U256);
+
+impl CostModel for FixedCost {
+    fn cost(&self, _request: &ComputeRequest<SystemParams>) -> U256 {
+        self.0
+    }
+}
+
+/// Request found by a search, priced at the searched timestamp
+#[derive(Clone, Debug)]
+pub struct RequestCandidate {
+    pub request_id: FixedBytes<32>,
+    pub request: ComputeRequest<SystemParams>,
+    /// reward of a bid placed at the searched timestamp, net of the market's fee
+    pub current_reward: U256,
+    pub cost: U256,
+    /// urgency signaled by the requester
+    pub urgency: u8,
+}
+
+impl RequestCandidate {
+    pub fn margin(&self) -> U256 {
+        self.current_reward.saturating_sub(self.cost)
+    }
+}
+
+/// Keep the requests whose auction is open at `latest_ts` and whose current reward covers their
+/// cost, most urgent first and then best margin first
+pub fn rank_requests(
+    requests: Vec<ComputeRequest<SystemParams>>,
+    latest_ts: u64,
+    cost_model: &dyn CostModel,
+) -> Vec<RequestCandidate> {
+    rank_requests_net_of_fee(requests, latest_ts, cost_model, MarketFee::ZERO)
+}
+
+/// Same as `rank_requests` for a market taking `market_fee` out of rewards, the cost being
+/// covered by the rewards net of the fee
+pub fn rank_requests_net_of_fee(
+    requests: Vec<ComputeRequest<SystemParams>>,
+    latest_ts: u64,
+    cost_model: &dyn CostModel,
+    market_fee: MarketFee,
+) -> Vec<RequestCandidate> {
+    let mut candidates: Vec<RequestCandidate> = requests
+        .into_iter()
+        .filter(|request| {
+            request.proof_request.startAuctionTimestamp <= latest_ts
+                && latest_ts <= request.proof_request.endAuctionTimestamp
+        })
+        .filter_map(|request| {
+            let current_reward = market_fee.net_reward(
+                RewardCurve::try_from(&request.proof_request)
+                    .ok()?
+                    .reward_at(latest_ts),
+            );
+            let cost = cost_model.cost(&request);
+            (current_reward >= cost).then(|| RequestCandidate {
+                request_id: request.compute_id(),
+                urgency: request_urgency(&request.proof_request),
+                request,
+                current_reward,
+                cost,
+            })
+        })
+        .collect();
+    candidates.sort_by_key(|candidate| Reverse((candidate.urgency, candidate.margin())));
+    candidates
+}
+
+/// Searcher for `ComputeRequests`, pulling the open requests stored by the server instead of
+/// streaming them. Requests only reach the chain once bid upon, so the market contract is only
+/// used to drop the ones another provider already bid on.
+pub struct ComputeRequestSearcher<T, P, N> {
+    api_client: QueryApiClient,
+    rpc_provider: P,
+    market_address: Address,
+    system_id: SystemId,
+    cost_model: Arc<dyn CostModel>,
+    market_fee: MarketFee,
+    // chain time the auctions of searched requests are priced at
+    clock: ChainClock<T, P, N>,
+    phantom_data: PhantomData<(T, N)>,
+}
+
+impl<T, P, N> ComputeRequestSearcher<T, P, N>
+where
+    T: Transport + Clone,
+    P: Provider<T, N> + Clone,
+    N: Network + Clone,
+{
+    pub fn new(
+        server_url: Url,
+        rpc_provider: P,
+        market_address: Address,
+        system_id: SystemId,
+    ) -> Self {
+        Self {
+            api_client: QueryApiClient::new(server_url),
+            clock: ChainClock::new(rpc_provider.clone()),
+            rpc_provider,
+            market_address,
+            system_id,
+            cost_model: Arc::new(FixedCost(U256::ZERO)),
+            market_fee: MarketFee::ZERO,
+            phantom_data: PhantomData,
+        }
+    }
+
+    /// Rank candidates against the given cost model instead of considering them free to prove
+    pub fn with_cost_model(mut self, cost_model: Arc<dyn CostModel>) -> Self {
+        self.cost_model = cost_model;
+        self
+    }
+
+    /// Rank candidates by their rewards net of the protocol fee the market takes
+    pub fn with_market_fee(mut self, market_fee: MarketFee) -> Self {
+        self.market_fee = market_fee;
+        self
+    }
+
+    /// Price candidates at the chain time told by `clock`, e.g. the one of the client searching
+    pub fn with_clock(mut self, clock: ChainClock<T, P, N>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub async fn latest_timestamp(&self) -> Result<u64> {
+        self.clock.now().await
+    }
+
+    /// Open and unbid requests of the searcher's market, best margin first
+    pub async fn candidates(&self, latest_ts: u64) -> Result<Vec<RequestCandidate>> {
+        let requests: Vec<_> = self
+            .api_client
+            .query_active_requests(self.system_id, latest_ts)
+            .await?
+            .into_iter()
+            .filter(|request| request.proof_request.market == self.market_address)
+            .collect();
+        tracing::info!(
+            "SEARCHER: {} open requests found from query",
+            requests.len()
+        );
+
+        let market_state = MarketStateClient::new(self.rpc_provider.clone(), self.market_address);
+        let mut candidates = Vec::new();
+        for candidate in rank_requests_net_of_fee(
+            requests,
+            latest_ts,
+            self.cost_model.as_ref(),
+            self.market_fee,
+        ) {
+            let state = market_state
+                .request_state(
+                    candidate.request_id,
+                    candidate.request.proof_request.endAuctionTimestamp,
+                    latest_ts,
+                )
+                .await?;
+            if state.is_open() {
+                candidates.push(candidate);
+            }
+        }
+        Ok(candidates)
+    }
+}
+
+#[async_trait]
+impl<T, P, N> IntentSearcher for ComputeRequestSearcher<T, P, N>
+where
+    T: Transport + Clone,
+    P: Provider<T, N> + Clone,
+    N: Network + Clone,
+{
+    type Intent = ComputeRequest<SystemParams>;
+
+    async fn search(&self) -> Result<Self::Intent> {
+        let latest_ts = self.latest_timestamp().await?;
+        let best = self
+            .candidates(latest_ts)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| ClientError::ServerRequestError("No requests available".into()))?;
+
+        tracing::info!(
+            "SEARCHER: request selected with request ID: {}",
+            best.request_id
+        );
+
+        Ok(best.request)
+    }
+}
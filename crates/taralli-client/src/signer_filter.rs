@@ -0,0 +1,300 @@
+//! Filtering of the intents a provider receives by the address that signed them, e.g. to stop
+//! analyzing the requests of a requester whose inputs keep crashing the prover.
+//!
+//! Signers are checked against an allowlist, every signer being allowed while it's empty, and a
+//! denylist whose entries are either permanent or bans expiring at a unix time. Signers whose
+//! requests fail the worker a configured number of times in a row are banned automatically. The
+//! lists are persisted to a JSON file when one is given, the counters of filtered intents aren't.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use taralli_primitives::alloy::primitives::Address;
+use taralli_primitives::intents::envelope::unix_time_ms;
+
+use crate::error::{ClientError, Result};
+
+#[derive(Clone, Debug)]
+pub struct SignerFilterConfig {
+    /// consecutive worker failures after which a signer is banned, signers are never banned
+    /// automatically when unset
+    pub auto_ban_after: Option<u32>,
+    /// how long automatic bans last
+    pub auto_ban_duration: Duration,
+}
+
+impl Default for SignerFilterConfig {
+    fn default() -> Self {
+        Self {
+            auto_ban_after: None,
+            auto_ban_duration: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+/// Denylist entry of a signer
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DenyEntry {
+    /// unix time in seconds the ban ends at, the signer is denied for good when unset
+    pub expires_at: Option<u64>,
+    pub reason: String,
+}
+
+/// Lists a signer filter checks signers against, as persisted
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignerLists {
+    #[serde(default)]
+    pub allowlist: BTreeSet<Address>,
+    #[serde(default)]
+    pub denylist: BTreeMap<Address, DenyEntry>,
+}
+
+/// Why the intents of a signer are dropped
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignerRejection {
+    /// the signer is on the denylist for good
+    Denied { reason: String },
+    /// the signer is banned until `expires_at`, in unix seconds
+    Banned { expires_at: u64, reason: String },
+    /// the allowlist is set and doesn't hold the signer
+    NotAllowlisted,
+}
+
+impl fmt::Display for SignerRejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignerRejection::Denied { reason } => write!(f, "signer denied: {reason}"),
+            SignerRejection::Banned { expires_at, reason } => {
+                write!(f, "signer banned until {expires_at}: {reason}")
+            }
+            SignerRejection::NotAllowlisted => write!(f, "signer not on the allowlist"),
+        }
+    }
+}
+
+/// Number of intents filtered out since the filter was created, by reason
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SignerFilterStats {
+    pub denied: u64,
+    pub banned: u64,
+    pub not_allowlisted: u64,
+    /// signers banned after failing the worker
+    pub auto_bans: u64,
+}
+
+impl SignerFilterStats {
+    /// Intents filtered out for any reason
+    pub fn filtered(&self) -> u64 {
+        self.denied + self.banned + self.not_allowlisted
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    lists: SignerLists,
+    consecutive_failures: HashMap<Address, u32>,
+    stats: SignerFilterStats,
+}
+
+/// Allowlist and denylist of the signers whose intents a provider processes, shared with the
+/// running client to be updated at runtime
+#[derive(Debug)]
+pub struct SignerFilter {
+    config: SignerFilterConfig,
+    state: Mutex<State>,
+    path: Option<PathBuf>,
+}
+
+impl SignerFilter {
+    pub fn new(config: SignerFilterConfig) -> Self {
+        Self::with_lists(config, SignerLists::default())
+    }
+
+    /// Filter starting from the given lists, not persisted
+    pub fn with_lists(config: SignerFilterConfig, lists: SignerLists) -> Self {
+        Self {
+            config,
+            state: Mutex::new(State {
+                lists,
+                ..Default::default()
+            }),
+            path: None,
+        }
+    }
+
+    /// Filter persisted to `path`, starting from the lists it holds if it exists
+    pub fn load(config: SignerFilterConfig, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let lists: SignerLists = match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| {
+                ClientError::ConfigError(format!("parsing {}: {e}", path.display()))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => SignerLists::default(),
+            Err(e) => {
+                return Err(ClientError::ConfigError(format!(
+                    "reading {}: {e}",
+                    path.display()
+                )))
+            }
+        };
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            ..Self::with_lists(config, lists)
+        })
+    }
+
+    /// Check whether the intents of `signer` are processed, counting the ones filtered out.
+    /// Expired bans are lifted along the way.
+    pub fn check(&self, signer: Address) -> std::result::Result<(), SignerRejection> {
+        let now = unix_time_ms() / 1000;
+        let mut state = self.state.lock().unwrap();
+        let rejection = match state.lists.denylist.get(&signer).cloned() {
+            Some(DenyEntry {
+                expires_at: None,
+                reason,
+            }) => Some(SignerRejection::Denied { reason }),
+            Some(DenyEntry {
+                expires_at: Some(expires_at),
+                reason,
+            }) if expires_at > now => Some(SignerRejection::Banned { expires_at, reason }),
+            Some(_) => {
+                state.lists.denylist.remove(&signer);
+                self.persist(&state.lists);
+                None
+            }
+            None => None,
+        };
+        let rejection = rejection.or_else(|| {
+            (!state.lists.allowlist.is_empty() && !state.lists.allowlist.contains(&signer))
+                .then_some(SignerRejection::NotAllowlisted)
+        });
+        match &rejection {
+            Some(SignerRejection::Denied { .. }) => state.stats.denied += 1,
+            Some(SignerRejection::Banned { .. }) => state.stats.banned += 1,
+            Some(SignerRejection::NotAllowlisted) => state.stats.not_allowlisted += 1,
+            None => {}
+        }
+        rejection.map_or(Ok(()), Err)
+    }
+
+    /// Add `signer` to the allowlist, from then on only allowlisted signers are processed
+    pub fn allow(&self, signer: Address) {
+        self.update(|lists| {
+            lists.allowlist.insert(signer);
+        });
+    }
+
+    /// Remove `signer` from the allowlist, every signer is processed again once it's empty
+    pub fn disallow(&self, signer: Address) {
+        self.update(|lists| {
+            lists.allowlist.remove(&signer);
+        });
+    }
+
+    /// Deny `signer` for good
+    pub fn deny(&self, signer: Address, reason: impl Into<String>) {
+        self.deny_until(signer, None, reason.into());
+    }
+
+    /// Deny `signer` for `duration`
+    pub fn ban(&self, signer: Address, duration: Duration, reason: impl Into<String>) {
+        self.ban_until(signer, unix_time_ms() / 1000 + duration.as_secs(), reason);
+    }
+
+    /// Deny `signer` until the unix time `expires_at`, in seconds
+    pub fn ban_until(&self, signer: Address, expires_at: u64, reason: impl Into<String>) {
+        self.deny_until(signer, Some(expires_at), reason.into());
+    }
+
+    /// Remove `signer` from the denylist, lifting its ban
+    pub fn undeny(&self, signer: Address) {
+        self.update(|lists| {
+            lists.denylist.remove(&signer);
+        });
+        self.state
+            .lock()
+            .unwrap()
+            .consecutive_failures
+            .remove(&signer);
+    }
+
+    /// Record that the worker failed on an intent of `signer`, banning it for the configured
+    /// duration once it failed `auto_ban_after` times in a row. Returns whether it was banned.
+    pub fn record_failure(&self, signer: Address) -> bool {
+        let Some(auto_ban_after) = self.config.auto_ban_after else {
+            return false;
+        };
+        let mut state = self.state.lock().unwrap();
+        let failures = state.consecutive_failures.entry(signer).or_default();
+        *failures += 1;
+        if *failures < auto_ban_after {
+            return false;
+        }
+        let failures = *failures;
+        state.consecutive_failures.remove(&signer);
+        state.stats.auto_bans += 1;
+        state.lists.denylist.insert(
+            signer,
+            DenyEntry {
+                expires_at: Some(unix_time_ms() / 1000 + self.config.auto_ban_duration.as_secs()),
+                reason: format!("worker failed on {failures} consecutive intents"),
+            },
+        );
+        self.persist(&state.lists);
+        tracing::warn!(
+            "signer {} banned for {}s after {} consecutive worker failures",
+            signer,
+            self.config.auto_ban_duration.as_secs(),
+            failures
+        );
+        true
+    }
+
+    /// Record that the worker succeeded on an intent of `signer`, resetting its failures
+    pub fn record_success(&self, signer: Address) {
+        self.state
+            .lock()
+            .unwrap()
+            .consecutive_failures
+            .remove(&signer);
+    }
+
+    /// Current allowlist and denylist
+    pub fn lists(&self) -> SignerLists {
+        self.state.lock().unwrap().lists.clone()
+    }
+
+    /// Counters of the intents filtered out
+    pub fn stats(&self) -> SignerFilterStats {
+        self.state.lock().unwrap().stats
+    }
+
+    fn deny_until(&self, signer: Address, expires_at: Option<u64>, reason: String) {
+        self.update(|lists| {
+            lists
+                .denylist
+                .insert(signer, DenyEntry { expires_at, reason });
+        });
+    }
+
+    fn update(&self, update: impl FnOnce(&mut SignerLists)) {
+        let mut state = self.state.lock().unwrap();
+        update(&mut state.lists);
+        self.persist(&state.lists);
+    }
+
+    fn persist(&self, lists: &SignerLists) {
+        if let Some(path) = &self.path {
+            let written = serde_json::to_string(lists)
+                .map_err(|e| e.to_string())
+                .and_then(|json| std::fs::write(path, json).map_err(|e| e.to_string()));
+            if let Err(e) = written {
+                tracing::warn!("failed to persist signer lists to {}: {e}", path.display());
+            }
+        }
+    }
+}
@@ -0,0 +1,318 @@
+//! Submission of the transactions sent by bidders and resolvers.
+//!
+//! By default transactions are sent through the client's rpc provider (the public mempool).
+//! Bids are a race though, so a `PrivateSubmitter` can be used to send them through a private
+//! rpc (e.g. Flashbots Protect) instead, falling back to the public path when the private
+//! submission doesn't land in time.
+//!
+//! The private submitter signs the transaction once and broadcasts the same raw transaction on
+//! both paths, so a fallback can't land a second transaction with another nonce: whichever path
+//! includes it, there is a single hash to watch for.
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use taralli_primitives::alloy::eips::Encodable2718;
+use taralli_primitives::alloy::network::Network;
+use taralli_primitives::alloy::primitives::{keccak256, Bytes, B256};
+use taralli_primitives::alloy::providers::fillers::{FillProvider, TxFiller};
+use taralli_primitives::alloy::providers::{Provider, SendableTx};
+use taralli_primitives::alloy::transports::Transport;
+use tokio::time::Instant;
+
+use crate::error::{ClientError, Result};
+
+/// time to wait for a sent transaction to be included before giving up on it
+pub const DEFAULT_RECEIPT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// interval between two receipt lookups of a privately submitted transaction
+pub const DEFAULT_RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Send a transaction and wait for its receipt
+#[async_trait]
+pub trait TransactionSubmitter<N: Network>: Send + Sync {
+    async fn send_and_wait_receipt(
+        &self,
+        transaction: N::TransactionRequest,
+    ) -> Result<N::ReceiptResponse>;
+}
+
+/// Fill and sign a transaction without sending it
+#[async_trait]
+pub trait TransactionSigner<N: Network>: Send + Sync {
+    /// Hash and raw encoding of the signed transaction
+    async fn sign_transaction(&self, transaction: N::TransactionRequest) -> Result<(B256, Bytes)>;
+}
+
+/// Broadcast signed transactions and look up their receipts
+#[async_trait]
+pub trait RawTransactionSender<N: Network>: Send + Sync {
+    /// Broadcast the signed transaction `raw`, returning its hash
+    async fn send_raw_transaction(&self, raw: &Bytes) -> Result<B256>;
+
+    /// Receipt of the transaction `transaction_hash`, once included
+    async fn transaction_receipt(
+        &self,
+        transaction_hash: B256,
+    ) -> Result<Option<N::ReceiptResponse>>;
+}
+
+/// A provider with a wallet filler signs the transactions it fills
+#[async_trait]
+impl<F, P, T, N> TransactionSigner<N> for FillProvider<F, P, T, N>
+where
+    F: TxFiller<N>,
+    P: Provider<T, N>,
+    T: Transport + Clone,
+    N: Network,
+{
+    async fn sign_transaction(&self, transaction: N::TransactionRequest) -> Result<(B256, Bytes)> {
+        let envelope = match self
+            .fill(transaction)
+            .await
+            .map_err(|e| ClientError::TransactionSetupError(e.to_string()))?
+        {
+            SendableTx::Envelope(envelope) => envelope,
+            SendableTx::Builder(_) => {
+                return Err(ClientError::TransactionSetupError(
+                    "the provider has no wallet to sign transactions with".to_string(),
+                ))
+            }
+        };
+        let raw = Bytes::from(envelope.encoded_2718());
+        Ok((keccak256(&raw), raw))
+    }
+}
+
+/// Submits transactions through an rpc provider, which fills and signs them. Also broadcasts
+/// transactions signed elsewhere, as an endpoint of a `PrivateSubmitter`.
+#[derive(Clone)]
+pub struct PublicSubmitter<T, P, N> {
+    rpc_provider: P,
+    receipt_timeout: Duration,
+    phantom_data: PhantomData<(T, N)>,
+}
+
+impl<T, P, N> PublicSubmitter<T, P, N>
+where
+    T: Transport + Clone,
+    P: Provider<T, N> + Clone,
+    N: Network + Clone,
+{
+    pub fn new(rpc_provider: P) -> Self {
+        Self {
+            rpc_provider,
+            receipt_timeout: DEFAULT_RECEIPT_TIMEOUT,
+            phantom_data: PhantomData,
+        }
+    }
+
+    /// Give up on transactions not included within `receipt_timeout`
+    pub fn with_receipt_timeout(mut self, receipt_timeout: Duration) -> Self {
+        self.receipt_timeout = receipt_timeout;
+        self
+    }
+}
+
+#[async_trait]
+impl<T, P, N> TransactionSubmitter<N> for PublicSubmitter<T, P, N>
+where
+    T: Transport + Clone,
+    P: Provider<T, N> + Clone,
+    N: Network + Clone,
+{
+    async fn send_and_wait_receipt(
+        &self,
+        transaction: N::TransactionRequest,
+    ) -> Result<N::ReceiptResponse> {
+        send_through_provider(&self.rpc_provider, transaction, self.receipt_timeout).await
+    }
+}
+
+#[async_trait]
+impl<T, P, N> RawTransactionSender<N> for PublicSubmitter<T, P, N>
+where
+    T: Transport + Clone,
+    P: Provider<T, N> + Clone,
+    N: Network + Clone,
+{
+    async fn send_raw_transaction(&self, raw: &Bytes) -> Result<B256> {
+        let pending = self
+            .rpc_provider
+            .send_raw_transaction(raw)
+            .await
+            .map_err(|e| ClientError::TransactionError(e.to_string()))?;
+        Ok(*pending.tx_hash())
+    }
+
+    async fn transaction_receipt(
+        &self,
+        transaction_hash: B256,
+    ) -> Result<Option<N::ReceiptResponse>> {
+        self.rpc_provider
+            .get_transaction_receipt(transaction_hash)
+            .await
+            .map_err(|e| ClientError::TransactionFailure(e.to_string()))
+    }
+}
+
+/// Submits transactions privately first, then rebroadcasts the same signed transaction publicly
+/// if the private submission fails or isn't included within `fallback_timeout`. The receipt is
+/// looked up on both paths until `receipt_timeout`.
+#[derive(Clone)]
+pub struct PrivateSubmitter<N: Network> {
+    signer: Arc<dyn TransactionSigner<N>>,
+    private: Arc<dyn RawTransactionSender<N>>,
+    public: Arc<dyn RawTransactionSender<N>>,
+    fallback_timeout: Duration,
+    receipt_timeout: Duration,
+    poll_interval: Duration,
+}
+
+impl<N: Network> PrivateSubmitter<N> {
+    pub fn new(
+        signer: Arc<dyn TransactionSigner<N>>,
+        private: Arc<dyn RawTransactionSender<N>>,
+        public: Arc<dyn RawTransactionSender<N>>,
+        fallback_timeout: Duration,
+    ) -> Self {
+        Self {
+            signer,
+            private,
+            public,
+            fallback_timeout,
+            receipt_timeout: DEFAULT_RECEIPT_TIMEOUT,
+            poll_interval: DEFAULT_RECEIPT_POLL_INTERVAL,
+        }
+    }
+
+    /// Give up on transactions not included within `receipt_timeout` of being signed
+    pub fn with_receipt_timeout(mut self, receipt_timeout: Duration) -> Self {
+        self.receipt_timeout = receipt_timeout;
+        self
+    }
+
+    /// Look up the receipt of submitted transactions every `poll_interval`
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Receipt of `transaction_hash` from whichever path reports it first, unless none does
+    /// before `until`
+    async fn watch_receipt(
+        &self,
+        transaction_hash: B256,
+        until: Instant,
+    ) -> Option<N::ReceiptResponse> {
+        loop {
+            for (path, sender) in [("private", &self.private), ("public", &self.public)] {
+                match sender.transaction_receipt(transaction_hash).await {
+                    Ok(Some(receipt)) => return Some(receipt),
+                    Ok(None) => {}
+                    Err(e) => tracing::debug!(
+                        "looking up the receipt of {} through the {} rpc failed: {}",
+                        transaction_hash,
+                        path,
+                        e
+                    ),
+                }
+            }
+            if Instant::now() + self.poll_interval > until {
+                return None;
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+#[async_trait]
+impl<N: Network> TransactionSubmitter<N> for PrivateSubmitter<N> {
+    async fn send_and_wait_receipt(
+        &self,
+        transaction: N::TransactionRequest,
+    ) -> Result<N::ReceiptResponse> {
+        let (transaction_hash, raw) = self.signer.sign_transaction(transaction).await?;
+        let start = Instant::now();
+        let deadline = start + self.receipt_timeout;
+
+        match self.private.send_raw_transaction(&raw).await {
+            Ok(_) => {
+                let fallback_at = deadline.min(start + self.fallback_timeout);
+                if let Some(receipt) = self.watch_receipt(transaction_hash, fallback_at).await {
+                    return Ok(receipt);
+                }
+                tracing::warn!(
+                    "private submission of {} not included after {:?}, rebroadcasting publicly",
+                    transaction_hash,
+                    self.fallback_timeout
+                );
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "private submission of {} failed, broadcasting publicly: {}",
+                    transaction_hash,
+                    e
+                );
+            }
+        }
+
+        // the transaction may already be known to the public mempool, or included, so a
+        // rejected rebroadcast isn't a failure
+        if let Err(e) = self.public.send_raw_transaction(&raw).await {
+            tracing::warn!("public broadcast of {} failed: {}", transaction_hash, e);
+        }
+        self.watch_receipt(transaction_hash, deadline)
+            .await
+            .ok_or_else(|| {
+                ClientError::TransactionFailure(format!(
+                    "transaction {} not included after {:?}",
+                    transaction_hash, self.receipt_timeout
+                ))
+            })
+    }
+}
+
+/// Send a transaction through the given submitter, or through the rpc provider if none is set
+pub(crate) async fn submit_transaction<T, P, N>(
+    rpc_provider: &P,
+    submitter: Option<&Arc<dyn TransactionSubmitter<N>>>,
+    transaction: N::TransactionRequest,
+) -> Result<N::ReceiptResponse>
+where
+    T: Transport + Clone,
+    P: Provider<T, N> + Clone,
+    N: Network + Clone,
+{
+    match submitter {
+        Some(submitter) => submitter.send_and_wait_receipt(transaction).await,
+        None => send_through_provider(rpc_provider, transaction, DEFAULT_RECEIPT_TIMEOUT).await,
+    }
+}
+
+async fn send_through_provider<T, P, N>(
+    rpc_provider: &P,
+    transaction: N::TransactionRequest,
+    receipt_timeout: Duration,
+) -> Result<N::ReceiptResponse>
+where
+    T: Transport + Clone,
+    P: Provider<T, N> + Clone,
+    N: Network + Clone,
+{
+    let pending = rpc_provider
+        .send_transaction(transaction)
+        .await
+        .map_err(|e| ClientError::TransactionError(e.to_string()))?;
+    let transaction_hash = *pending.tx_hash();
+    tokio::time::timeout(receipt_timeout, pending.get_receipt())
+        .await
+        .map_err(|_| {
+            ClientError::TransactionFailure(format!(
+                "transaction {transaction_hash} not included after {receipt_timeout:?}"
+            ))
+        })?
+        .map_err(|e| ClientError::TransactionFailure(e.to_string()))
+}
@@ -1,11 +1,68 @@
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
 use std::time::Duration;
-use taralli_primitives::alloy::primitives::FixedBytes;
+use taralli_primitives::alloy::{
+    eips::BlockId, network::Network, primitives::FixedBytes, providers::Provider,
+    transports::Transport,
+};
 
+use crate::api::retry::RetryPolicy;
+use crate::confirmations::Confirmations;
 use crate::error::Result;
+use crate::rpc::retry_rpc;
 
+pub mod multi;
 pub mod offer;
+pub mod payload;
 pub mod request;
+pub mod sharded;
+
+/// Interval the market is polled at unless configured otherwise
+pub const DEFAULT_TRACKER_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+fn default_poll_interval_ms() -> u64 {
+    DEFAULT_TRACKER_POLL_INTERVAL.as_millis() as u64
+}
+
+/// How trackers follow an intent besides watching the market's events through a log filter,
+/// which some rpc providers support poorly or drop while reconnecting
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackerConfig {
+    /// poll the market alongside the event watch: its state for bids, falling back to an event
+    /// synthesized from it when the bid event can't be found, and its logs for resolves, as the
+    /// markets don't clear the state of resolved intents
+    #[serde(default)]
+    pub poll_market: bool,
+    /// milliseconds between two polls of the market
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+}
+
+impl Default for TrackerConfig {
+    fn default() -> Self {
+        Self {
+            poll_market: false,
+            poll_interval_ms: default_poll_interval_ms(),
+        }
+    }
+}
+
+impl TrackerConfig {
+    /// Poll the market every `poll_interval` alongside the event watch
+    #[must_use]
+    pub fn polling(poll_interval: Duration) -> Self {
+        Self {
+            poll_market: true,
+            poll_interval_ms: poll_interval.as_millis() as u64,
+        }
+    }
+
+    #[must_use]
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms)
+    }
+}
 
 #[async_trait]
 pub trait IntentAuctionTracker {
@@ -28,3 +85,67 @@ pub trait IntentResolveTracker {
         timeout: Duration,
     ) -> Result<Option<Self::ResolveEvent>>;
 }
+
+/// First event found by either the event watch or the poll of the market, `None` once both
+/// ended without one
+pub(crate) async fn first_event<E>(
+    watch: impl Future<Output = Option<E>>,
+    poll: impl Future<Output = Option<E>>,
+) -> Option<E> {
+    tokio::select! {
+        Some(event) = watch => Some(event),
+        Some(event) = poll => Some(event),
+        else => None,
+    }
+}
+
+/// Poll `find` every `poll_interval` until it finds an event, starting right away. Failed polls
+/// are logged and retried.
+pub(crate) async fn poll_until_found<E, F, Fut>(poll_interval: Duration, mut find: F) -> Option<E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Option<E>>>,
+{
+    loop {
+        match find().await {
+            Ok(Some(event)) => return Some(event),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("polling the market failed: {e}"),
+        }
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+/// Latest block number, which polled logs are searched from when tracking starts
+pub(crate) async fn current_block<T, P, N>(rpc_provider: &P, rpc_retry: &RetryPolicy) -> Result<u64>
+where
+    T: Transport + Clone,
+    P: Provider<T, N>,
+    N: Network,
+{
+    retry_rpc(rpc_retry, || async move {
+        rpc_provider.get_block_number().await
+    })
+    .await
+}
+
+/// Block the market's state is read at, `confirmations` deep so that state written by a
+/// transaction reorged out meanwhile isn't trusted
+pub(crate) async fn confirmed_block<T, P, N>(
+    rpc_provider: &P,
+    confirmations: &Confirmations,
+    rpc_retry: &RetryPolicy,
+) -> Result<BlockId>
+where
+    T: Transport + Clone,
+    P: Provider<T, N>,
+    N: Network,
+{
+    if confirmations.is_immediate() {
+        return Ok(BlockId::latest());
+    }
+    let head = current_block::<T, P, N>(rpc_provider, rpc_retry).await?;
+    Ok(BlockId::number(
+        head.saturating_sub(confirmations.depth.saturating_sub(1)),
+    ))
+}
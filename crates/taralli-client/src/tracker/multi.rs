@@ -0,0 +1,387 @@
+//! Tracking of many requests through a single event loop. Each `ComputeRequestTracker` call
+//! installs log filters of its own on the rpc, which run out around a few dozen requests in
+//! flight. A `MultiTracker` watches the market through one bid and one resolve filter covering
+//! every request, handing each event to the watcher of the request it is about. Requests are
+//! registered before being submitted, and deregistered once resolved or given up on. The filters
+//! are installed again when the event stream ends, e.g. once the rpc dropped them, the tracker
+//! giving up on every request only when they can't be.
+
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_util::{Stream, StreamExt};
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta::{
+    self, UniversalBombettaInstance,
+};
+use taralli_primitives::alloy::{
+    network::Network, primitives::B256, providers::Provider, transports::Transport,
+};
+use tokio::sync::{broadcast, watch};
+use tokio::task::JoinHandle;
+
+use crate::api::retry::RetryPolicy;
+use crate::error::{ClientError, Result};
+
+use super::request::ComputeRequestTracker;
+
+/// Events of the consolidated stream buffered per subscriber before the slowest skips some
+const EVENT_BUFFER: usize = 1024;
+/// Logs confirmed at once by the market's event source
+const CONFIRMATION_CONCURRENCY: usize = 64;
+
+/// Bid or resolve event of the market about a tracked request
+#[derive(Clone, Debug)]
+pub enum TrackingEvent {
+    Bid(UniversalBombetta::Bid),
+    /// resolve event alongside the hash of the transaction that emitted it
+    Resolve {
+        resolve_event: UniversalBombetta::Resolve,
+        tx_hash: B256,
+    },
+}
+
+impl TrackingEvent {
+    /// Id of the request the event is about
+    #[must_use]
+    pub fn intent_id(&self) -> B256 {
+        match self {
+            Self::Bid(bid_event) => bid_event.requestId,
+            Self::Resolve { resolve_event, .. } => resolve_event.requestId,
+        }
+    }
+}
+
+pub type TrackingEventStream = Pin<Box<dyn Stream<Item = TrackingEvent> + Send>>;
+
+/// Source of the confirmed bid and resolve events of a market, across every request
+#[async_trait]
+pub trait MarketEventSource {
+    async fn events(&self) -> Result<TrackingEventStream>;
+}
+
+#[async_trait]
+impl<T, P, N> MarketEventSource for ComputeRequestTracker<T, P, N>
+where
+    T: Transport + Clone + Send + Sync + 'static,
+    P: Provider<T, N> + Clone + Send + Sync + 'static,
+    N: Network + Clone + Send + Sync + 'static,
+{
+    /// Watch the market through one bid filter and one resolve filter, unfiltered by request,
+    /// yielding events once confirmed at the tracker's depth
+    async fn events(&self) -> Result<TrackingEventStream> {
+        let market_contract =
+            UniversalBombettaInstance::new(self.market_address, self.rpc_provider.clone());
+        let bids = market_contract
+            .Bid_filter()
+            .watch()
+            .await
+            .map_err(|e| ClientError::TrackIntentError(e.to_string()))?
+            .into_stream()
+            .map(|log_result| {
+                log_result.map(|(bid_event, log)| (TrackingEvent::Bid(bid_event), log))
+            });
+        let resolves = market_contract
+            .Resolve_filter()
+            .watch()
+            .await
+            .map_err(|e| ClientError::TrackIntentError(e.to_string()))?
+            .into_stream()
+            .map(|log_result| {
+                log_result.map(|(resolve_event, log)| {
+                    (
+                        TrackingEvent::Resolve {
+                            resolve_event,
+                            // checked before the event is confirmed
+                            tx_hash: log.transaction_hash.unwrap_or_default(),
+                        },
+                        log,
+                    )
+                })
+            });
+
+        let rpc_provider = self.rpc_provider.clone();
+        let confirmations = self.confirmations.clone();
+        let events = futures_util::stream::select(bids, resolves)
+            .map(move |log_result| {
+                let rpc_provider = rpc_provider.clone();
+                let confirmations = confirmations.clone();
+                async move {
+                    let (event, log) = match log_result {
+                        Ok(event) => event,
+                        Err(e) => {
+                            tracing::error!("Error processing log: {:?}", e);
+                            return None;
+                        }
+                    };
+                    if log.transaction_hash.is_none() {
+                        tracing::error!("market log is missing its transaction hash");
+                        return None;
+                    }
+                    confirmations
+                        .confirm_log::<T, P, N>(&rpc_provider, &log)
+                        .await
+                        .then_some(event)
+                }
+            })
+            // confirmed concurrently, yielded in the order the market emitted them
+            .buffered(CONFIRMATION_CONCURRENCY)
+            .filter_map(|event| async move { event });
+        Ok(Box::pin(events))
+    }
+}
+
+/// Events seen so far of a tracked request
+#[derive(Clone, Debug, Default)]
+struct IntentEvents {
+    bid_event: Option<UniversalBombetta::Bid>,
+    resolution: Option<(UniversalBombetta::Resolve, B256)>,
+}
+
+type Watchers = Arc<Mutex<HashMap<B256, watch::Sender<IntentEvents>>>>;
+
+/// Tracks any number of requests through a single stream of the market's events, see the module
+/// documentation. Unlike `ComputeRequestTracker` the market isn't polled, a gap in the event
+/// stream (e.g. while its filters are installed again) is only recovered from by tracking the
+/// requests affected on their own.
+pub struct MultiTracker {
+    watchers: Watchers,
+    events: broadcast::Sender<(B256, TrackingEvent)>,
+    // cleared under the watchers lock once the event loop gave up, so no request is registered
+    // on a tracker no event reaches anymore
+    alive: Arc<AtomicBool>,
+    event_loop: JoinHandle<()>,
+}
+
+impl MultiTracker {
+    /// Start the event loop, demultiplexing the events of `source` to the registered requests
+    pub async fn start(source: impl MarketEventSource + Send + Sync + 'static) -> Result<Self> {
+        Self::start_with_restart_policy(source, RetryPolicy::default()).await
+    }
+
+    /// Same as `start`, the filters being installed again up to `restart_policy.max_retries` times
+    /// in a row once the event stream ends, backing off between attempts. The count is reset by
+    /// every event received, the policy's deadline isn't used.
+    pub async fn start_with_restart_policy(
+        source: impl MarketEventSource + Send + Sync + 'static,
+        restart_policy: RetryPolicy,
+    ) -> Result<Self> {
+        let mut market_events = source.events().await?;
+        let watchers = Watchers::default();
+        let (events, _) = broadcast::channel(EVENT_BUFFER);
+        let alive = Arc::new(AtomicBool::new(true));
+
+        let event_loop = tokio::spawn({
+            let watchers = watchers.clone();
+            let events = events.clone();
+            let alive = alive.clone();
+            async move {
+                let mut restarts = 0;
+                loop {
+                    while let Some(event) = market_events.next().await {
+                        restarts = 0;
+                        dispatch(&watchers, &events, event);
+                    }
+                    match restart(&source, &restart_policy, &mut restarts).await {
+                        Some(restarted) => market_events = restarted,
+                        None => break,
+                    }
+                }
+                tracing::error!("market event stream ended, tracked requests are given up on");
+                let mut watchers = watchers.lock().unwrap();
+                alive.store(false, Ordering::SeqCst);
+                watchers.clear();
+            }
+        });
+
+        Ok(Self {
+            watchers,
+            events,
+            alive,
+            event_loop,
+        })
+    }
+
+    /// Whether the market's events still reach the tracker, requests can't be registered anymore
+    /// once they don't
+    pub fn is_alive(&self) -> bool {
+        self.alive.load(Ordering::SeqCst)
+    }
+
+    /// Start tracking request `intent_id`, before submitting it so none of its events is missed.
+    /// The request is deregistered once resolved, or once every handle to it is dropped. Fails
+    /// with `ClientError::TrackIntentError` once the tracker gave up on the market's events.
+    pub fn register(&self, intent_id: B256) -> Result<TrackedIntent> {
+        let mut watchers = self.watchers.lock().unwrap();
+        if !self.is_alive() {
+            return Err(ClientError::TrackIntentError(format!(
+                "market event stream ended, request {intent_id} can't be tracked"
+            )));
+        }
+        let receiver = watchers
+            .entry(intent_id)
+            .or_insert_with(|| watch::channel(IntentEvents::default()).0)
+            .subscribe();
+        Ok(TrackedIntent {
+            intent_id,
+            receiver,
+            watchers: self.watchers.clone(),
+            alive: self.alive.clone(),
+        })
+    }
+
+    /// Stop tracking request `intent_id`, its handles failing to await events not seen yet
+    pub fn deregister(&self, intent_id: B256) {
+        self.watchers.lock().unwrap().remove(&intent_id);
+    }
+
+    /// Number of requests being tracked
+    pub fn tracked(&self) -> usize {
+        self.watchers.lock().unwrap().len()
+    }
+
+    /// Consolidated stream of the events of the tracked requests as they're seen from now on. A
+    /// subscriber falling more than `EVENT_BUFFER` events behind skips the oldest.
+    pub fn events(&self) -> impl Stream<Item = (B256, TrackingEvent)> + Send + 'static {
+        futures_util::stream::unfold(self.events.subscribe(), |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        tracing::warn!("tracking event stream lagged, {skipped} events skipped");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+}
+
+impl Drop for MultiTracker {
+    fn drop(&mut self) {
+        self.event_loop.abort();
+        // the handles left fail rather than wait for events no loop delivers
+        let mut watchers = self.watchers.lock().unwrap();
+        self.alive.store(false, Ordering::SeqCst);
+        watchers.clear();
+    }
+}
+
+/// Hand `event` to the watcher of the request it is about, when registered, and to the
+/// consolidated stream
+fn dispatch(
+    watchers: &Watchers,
+    events: &broadcast::Sender<(B256, TrackingEvent)>,
+    event: TrackingEvent,
+) {
+    let intent_id = event.intent_id();
+    let mut watchers = watchers.lock().unwrap();
+    let Some(watcher) = watchers.get(&intent_id) else {
+        return;
+    };
+    watcher.send_modify(|seen| match &event {
+        TrackingEvent::Bid(bid_event) => {
+            seen.bid_event.get_or_insert_with(|| bid_event.clone());
+        }
+        TrackingEvent::Resolve {
+            resolve_event,
+            tx_hash,
+        } => seen.resolution = Some((resolve_event.clone(), *tx_hash)),
+    });
+    // a resolved request is done with, its handles keep the events seen
+    if matches!(event, TrackingEvent::Resolve { .. }) {
+        watchers.remove(&intent_id);
+    }
+    // no subscriber is no error
+    let _ = events.send((intent_id, event));
+}
+
+/// Install the filters of `source` again, `None` once `restarts` reached the policy's retries
+async fn restart(
+    source: &(impl MarketEventSource + Sync),
+    restart_policy: &RetryPolicy,
+    restarts: &mut u32,
+) -> Option<TrackingEventStream> {
+    while *restarts < restart_policy.max_retries {
+        tokio::time::sleep(restart_policy.backoff(*restarts)).await;
+        *restarts += 1;
+        match source.events().await {
+            Ok(events) => {
+                tracing::warn!(
+                    "market event stream ended, filters installed again, events emitted meanwhile are missed"
+                );
+                return Some(events);
+            }
+            Err(e) => tracing::warn!("failed to install the market event filters again: {e}"),
+        }
+    }
+    None
+}
+
+/// Handle awaiting the events of a request registered on a `MultiTracker`, mirroring
+/// `ComputeRequestTracker`'s auction and resolve tracking
+pub struct TrackedIntent {
+    intent_id: B256,
+    receiver: watch::Receiver<IntentEvents>,
+    watchers: Watchers,
+    alive: Arc<AtomicBool>,
+}
+
+impl TrackedIntent {
+    #[must_use]
+    pub fn intent_id(&self) -> B256 {
+        self.intent_id
+    }
+
+    /// Bid won the request's auction with, `None` when none was seen within `timeout`
+    pub async fn track_auction(&self, timeout: Duration) -> Result<Option<UniversalBombetta::Bid>> {
+        self.wait_for(timeout, |seen| seen.bid_event.clone()).await
+    }
+
+    /// Resolve event of the request alongside the hash of the transaction that emitted it,
+    /// `None` when none was seen within `timeout`
+    pub async fn track_resolve_with_tx_hash(
+        &self,
+        timeout: Duration,
+    ) -> Result<Option<(UniversalBombetta::Resolve, B256)>> {
+        self.wait_for(timeout, |seen| seen.resolution.clone()).await
+    }
+
+    async fn wait_for<E>(
+        &self,
+        timeout: Duration,
+        event: impl Fn(&IntentEvents) -> Option<E>,
+    ) -> Result<Option<E>> {
+        let mut receiver = self.receiver.clone();
+        match tokio::time::timeout(timeout, receiver.wait_for(|seen| event(seen).is_some())).await {
+            Ok(Ok(seen)) => Ok(event(&seen)),
+            Ok(Err(_)) if !self.alive.load(Ordering::SeqCst) => {
+                Err(ClientError::TrackIntentError(format!(
+                    "market event stream ended while tracking request {}",
+                    self.intent_id
+                )))
+            }
+            Ok(Err(_)) => Err(ClientError::TrackIntentError(format!(
+                "request {} is no longer tracked",
+                self.intent_id
+            ))),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+impl Drop for TrackedIntent {
+    fn drop(&mut self) {
+        let mut watchers = self.watchers.lock().unwrap();
+        // this handle's receiver being the last one, the request is given up on
+        let last = watchers.get(&self.intent_id).is_some_and(|watcher| {
+            watcher.receiver_count() <= 1 && watcher.subscribe().same_channel(&self.receiver)
+        });
+        if last {
+            watchers.remove(&self.intent_id);
+        }
+    }
+}
@@ -0,0 +1,189 @@
+//! Decoding of the proof payload delivered on-chain when an intent is resolved.
+
+use taralli_primitives::abi::{
+    universal_bombetta::UniversalBombetta, universal_porchetta::UniversalPorchetta,
+};
+use taralli_primitives::alloy::dyn_abi::{DynSolType, DynSolValue};
+use taralli_primitives::alloy::primitives::{Bytes, B256, U256};
+use taralli_primitives::alloy::sol_types::SolCall;
+use taralli_primitives::systems::SystemId;
+
+use crate::error::{ClientError, Result};
+
+/// Proof data delivered by a provider when resolving an intent
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolutionPayload {
+    /// Id of the resolved intent
+    pub intent_id: B256,
+    /// Raw opaque submission passed to the verifier
+    pub opaque_submission: Bytes,
+    /// Partial commitment submitted alongside the proof (only present for `ComputeRequest`s)
+    pub submitted_partial_commitment: Option<B256>,
+    /// Per-system decoded proof, when the system of the intent is known
+    pub proof: Option<DecodedProof>,
+}
+
+/// Opaque submissions decoded using the layouts the workers format them with
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodedProof {
+    Arkworks {
+        p_a: Vec<U256>,
+        p_b: Vec<Vec<U256>>,
+        p_c: Vec<U256>,
+        pub_signals: Vec<U256>,
+    },
+    Risc0 {
+        seal: Bytes,
+        image_id: B256,
+        journal_digest: B256,
+    },
+    Sp1 {
+        vkey: B256,
+        public_values: Bytes,
+        proof_bytes: Bytes,
+    },
+}
+
+impl DecodedProof {
+    /// Decode an opaque submission of the given system
+    pub fn decode(system_id: SystemId, opaque_submission: &[u8]) -> Result<Self> {
+        let uint_array = || DynSolType::Array(Box::new(DynSolType::Uint(256)));
+
+        let layout = match system_id {
+            // (uint256[] pA, uint256[][] pB, uint256[] pC, uint256[] pubSignals)
+            SystemId::Arkworks => DynSolType::Tuple(vec![
+                uint_array(),
+                DynSolType::Array(Box::new(uint_array())),
+                uint_array(),
+                uint_array(),
+            ]),
+            // (bytes seal, bytes32 imageId, bytes32 journalDigest)
+            SystemId::Risc0 => DynSolType::Tuple(vec![
+                DynSolType::Bytes,
+                DynSolType::FixedBytes(32),
+                DynSolType::FixedBytes(32),
+            ]),
+            // (bytes32 vkey, bytes publicValues, bytes proofBytes)
+            SystemId::Sp1 => DynSolType::Tuple(vec![
+                DynSolType::FixedBytes(32),
+                DynSolType::Bytes,
+                DynSolType::Bytes,
+            ]),
+        };
+
+        let decoded = layout.abi_decode(opaque_submission).map_err(|e| {
+            ClientError::LogParseError(format!(
+                "failed to decode {} opaque submission: {e}",
+                system_id.as_str()
+            ))
+        })?;
+
+        let fields = match decoded {
+            DynSolValue::Tuple(fields) => fields,
+            _ => {
+                return Err(ClientError::LogParseError(
+                    "opaque submission is not a tuple".into(),
+                ))
+            }
+        };
+
+        match (system_id, fields.as_slice()) {
+            (SystemId::Arkworks, [p_a, p_b, p_c, pub_signals]) => Ok(Self::Arkworks {
+                p_a: uint_values(p_a)?,
+                p_b: p_b
+                    .as_array()
+                    .ok_or_else(|| unexpected_field("pB"))?
+                    .iter()
+                    .map(uint_values)
+                    .collect::<Result<_>>()?,
+                p_c: uint_values(p_c)?,
+                pub_signals: uint_values(pub_signals)?,
+            }),
+            (SystemId::Risc0, [seal, image_id, journal_digest]) => Ok(Self::Risc0 {
+                seal: bytes_value(seal)?,
+                image_id: word_value(image_id)?,
+                journal_digest: word_value(journal_digest)?,
+            }),
+            (SystemId::Sp1, [vkey, public_values, proof_bytes]) => Ok(Self::Sp1 {
+                vkey: word_value(vkey)?,
+                public_values: bytes_value(public_values)?,
+                proof_bytes: bytes_value(proof_bytes)?,
+            }),
+            _ => Err(unexpected_field("tuple length")),
+        }
+    }
+}
+
+/// Decode the calldata of a `UniversalBombetta.resolve(...)` transaction
+pub fn decode_request_resolve_calldata(
+    calldata: &[u8],
+    system_id: Option<SystemId>,
+) -> Result<ResolutionPayload> {
+    let resolve_call = UniversalBombetta::resolveCall::abi_decode(calldata, true).map_err(|e| {
+        ClientError::LogParseError(format!("failed to decode resolve calldata: {e}"))
+    })?;
+
+    let proof = system_id
+        .map(|id| DecodedProof::decode(id, &resolve_call.opaqueSubmission))
+        .transpose()?;
+
+    Ok(ResolutionPayload {
+        intent_id: resolve_call.requestId,
+        opaque_submission: resolve_call.opaqueSubmission,
+        submitted_partial_commitment: Some(resolve_call.submittedPartialCommitment),
+        proof,
+    })
+}
+
+/// Decode the calldata of a `UniversalPorchetta.resolve(...)` transaction
+pub fn decode_offer_resolve_calldata(
+    calldata: &[u8],
+    system_id: Option<SystemId>,
+) -> Result<ResolutionPayload> {
+    let resolve_call =
+        UniversalPorchetta::resolveCall::abi_decode(calldata, true).map_err(|e| {
+            ClientError::LogParseError(format!("failed to decode resolve calldata: {e}"))
+        })?;
+
+    let proof = system_id
+        .map(|id| DecodedProof::decode(id, &resolve_call.opaqueSubmission))
+        .transpose()?;
+
+    Ok(ResolutionPayload {
+        intent_id: resolve_call.offerId,
+        opaque_submission: resolve_call.opaqueSubmission,
+        submitted_partial_commitment: None,
+        proof,
+    })
+}
+
+fn unexpected_field(field: &str) -> ClientError {
+    ClientError::LogParseError(format!("unexpected opaque submission field: {field}"))
+}
+
+fn uint_values(value: &DynSolValue) -> Result<Vec<U256>> {
+    value
+        .as_array()
+        .ok_or_else(|| unexpected_field("uint256[]"))?
+        .iter()
+        .map(|v| {
+            v.as_uint()
+                .map(|(v, _)| v)
+                .ok_or_else(|| unexpected_field("uint256"))
+        })
+        .collect()
+}
+
+fn bytes_value(value: &DynSolValue) -> Result<Bytes> {
+    value
+        .as_bytes()
+        .map(Bytes::copy_from_slice)
+        .ok_or_else(|| unexpected_field("bytes"))
+}
+
+fn word_value(value: &DynSolValue) -> Result<B256> {
+    value
+        .as_fixed_bytes()
+        .map(|(word, _)| B256::from_slice(word))
+        .ok_or_else(|| unexpected_field("bytes32"))
+}
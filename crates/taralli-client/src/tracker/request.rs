@@ -3,6 +3,7 @@ use futures_util::StreamExt;
 use std::marker::PhantomData;
 use std::time::Duration;
 use taralli_primitives::alloy::{
+    consensus::Transaction,
     network::Network,
     primitives::{Address, B256},
     providers::Provider,
@@ -11,17 +12,28 @@ use taralli_primitives::alloy::{
 use taralli_primitives::{
     abi::universal_bombetta::UniversalBombetta::{self, UniversalBombettaInstance},
     intents::request::ComputeRequest,
-    systems::SystemParams,
+    systems::{SystemId, SystemParams},
 };
 
+use crate::api::retry::RetryPolicy;
+use crate::confirmations::Confirmations;
 use crate::error::{ClientError, Result};
+use crate::rpc::retry_rpc;
 
-use super::{IntentAuctionTracker, IntentResolveTracker};
+use super::payload::{decode_request_resolve_calldata, ResolutionPayload};
+use super::{
+    confirmed_block, current_block, first_event, poll_until_found, IntentAuctionTracker,
+    IntentResolveTracker, TrackerConfig,
+};
 
 /// `ComputeRequest` tracker for both auctions and resolutons
 pub struct ComputeRequestTracker<T, P, N> {
-    rpc_provider: P,
-    market_address: Address,
+    pub(super) rpc_provider: P,
+    pub(super) market_address: Address,
+    // depth events are yielded at, once the transaction emitting them is confirmed
+    pub(super) confirmations: Confirmations,
+    tracker_config: TrackerConfig,
+    rpc_retry: RetryPolicy,
     phantom_data: PhantomData<(T, N)>,
 }
 
@@ -35,45 +47,172 @@ where
         Self {
             rpc_provider,
             market_address,
+            confirmations: Confirmations::default(),
+            tracker_config: TrackerConfig::default(),
+            rpc_retry: RetryPolicy::default(),
             phantom_data: PhantomData,
         }
     }
-}
 
-#[async_trait]
-impl<T, P, N> IntentAuctionTracker for ComputeRequestTracker<T, P, N>
-where
-    T: Transport + Clone + Send + Sync,
-    P: Provider<T, N> + Clone + Send + Sync,
-    N: Network + Clone + Send + Sync,
-{
-    type Intent = ComputeRequest<SystemParams>;
-    type BidEvent = UniversalBombetta::Bid;
+    /// only yield events once the transaction emitting them is confirmed at the given depth,
+    /// watching on for another event when it was reorged out meanwhile
+    pub fn with_confirmations(mut self, confirmations: Confirmations) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
 
-    /// Start tracking auction events for a request
-    async fn track_auction(
+    /// poll the market alongside the event watch as configured, see `TrackerConfig`
+    pub fn with_tracker_config(mut self, tracker_config: TrackerConfig) -> Self {
+        self.tracker_config = tracker_config;
+        self
+    }
+
+    /// attempt the rpc calls reading the market again as `rpc_retry` allows when they fail
+    /// transiently
+    pub fn with_rpc_retry(mut self, rpc_retry: RetryPolicy) -> Self {
+        self.rpc_retry = rpc_retry;
+        self
+    }
+
+    /// Bid placed on a request as recorded in the market's state at the confirmation depth,
+    /// `None` while it isn't bid upon. The market's bid event emitted from `from_block` on is
+    /// returned when found, otherwise an event is synthesized from the state, whose `ethStake`
+    /// is the stake the provider sent rather than the request's minimum.
+    pub async fn bid_from_state(
+        &self,
+        intent_id: B256,
+        from_block: u64,
+    ) -> Result<Option<UniversalBombetta::Bid>> {
+        let market_contract =
+            &UniversalBombettaInstance::new(self.market_address, self.rpc_provider.clone());
+        let block =
+            confirmed_block::<T, P, N>(&self.rpc_provider, &self.confirmations, &self.rpc_retry)
+                .await?;
+        let active = retry_rpc(&self.rpc_retry, || async move {
+            market_contract
+                .activeProofRequestData(intent_id)
+                .block(block)
+                .call()
+                .await
+        })
+        .await?;
+        if active.provider == Address::ZERO {
+            return Ok(None);
+        }
+
+        // reconcile with the bid event when the rpc serves it
+        match market_contract
+            .Bid_filter()
+            .topic2(intent_id)
+            .from_block(from_block)
+            .query()
+            .await
+        {
+            Ok(logs) => {
+                if let Some((bid_event, _)) = logs.into_iter().next() {
+                    return Ok(Some(bid_event));
+                }
+            }
+            Err(e) => tracing::warn!("looking up the bid event of request {intent_id} failed: {e}"),
+        }
+        tracing::info!("bid on request {intent_id} found in the market's state");
+        Ok(Some(UniversalBombetta::Bid {
+            signer: active.requester,
+            requestId: intent_id,
+            rewardToken: active.rewardToken,
+            rewardAmount: active.rewardAmount,
+            ethStake: active.providerStake,
+            provider: active.provider,
+        }))
+    }
+
+    /// Resolve event of a request emitted from `from_block` on, once confirmed, along with the
+    /// hash of the transaction that emitted it. Read through `eth_getLogs`, which unlike log
+    /// filters doesn't depend on the rpc keeping state.
+    pub async fn resolve_from_logs(
+        &self,
+        intent_id: B256,
+        from_block: u64,
+    ) -> Result<Option<(UniversalBombetta::Resolve, B256)>> {
+        let market_contract =
+            &UniversalBombettaInstance::new(self.market_address, self.rpc_provider.clone());
+        let logs = retry_rpc(&self.rpc_retry, || async move {
+            market_contract
+                .Resolve_filter()
+                .topic2(intent_id)
+                .from_block(from_block)
+                .query()
+                .await
+        })
+        .await?;
+        for (resolve_event, log) in logs {
+            let Some(tx_hash) = log.transaction_hash else {
+                continue;
+            };
+            if self
+                .confirmations
+                .confirm_log::<T, P, N>(&self.rpc_provider, &log)
+                .await
+            {
+                return Ok(Some((resolve_event, tx_hash)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Block the market's logs are polled from, `None` when polling is disabled
+    async fn polling_start(&self) -> Result<Option<u64>> {
+        if !self.tracker_config.poll_market {
+            return Ok(None);
+        }
+        current_block::<T, P, N>(&self.rpc_provider, &self.rpc_retry)
+            .await
+            .map(Some)
+    }
+
+    /// Track the resolution of a request, returning the resolve event alongside the hash of
+    /// the transaction that emitted it.
+    pub async fn track_resolve_with_tx_hash(
         &self,
         intent_id: B256,
         timeout: Duration,
-    ) -> Result<Option<Self::BidEvent>> {
+    ) -> Result<Option<(UniversalBombetta::Resolve, B256)>> {
         let market_contract =
             UniversalBombettaInstance::new(self.market_address, self.rpc_provider.clone());
 
-        let bid_filter = market_contract.Bid_filter().topic2(intent_id);
-
-        let event_poller = bid_filter
+        let from_block = self.polling_start().await?;
+        let resolve_stream = match market_contract
+            .Resolve_filter()
+            .topic2(intent_id)
             .watch()
             .await
-            .map_err(|e| ClientError::TrackIntentError(e.to_string()))?;
-
-        let mut bid_stream = event_poller.into_stream();
+        {
+            Ok(event_poller) => Some(event_poller.into_stream()),
+            Err(e) if from_block.is_some() => {
+                tracing::warn!("watching resolve events failed, polling the market only: {e}");
+                None
+            }
+            Err(e) => return Err(ClientError::TrackIntentError(e.to_string())),
+        };
 
-        let result = tokio::time::timeout(timeout, async move {
-            while let Some(log_result) = bid_stream.next().await {
+        let watch = async move {
+            let mut resolve_stream = resolve_stream?;
+            while let Some(log_result) = resolve_stream.next().await {
                 match log_result {
-                    Ok((bid_event, _)) => {
-                        tracing::info!("Bid event found: {:?}", bid_event);
-                        return Some(bid_event);
+                    Ok((resolve_event, log)) => {
+                        tracing::info!("Resolve event found: {:?}", resolve_event);
+                        let Some(tx_hash) = log.transaction_hash else {
+                            tracing::error!("Resolve log is missing its transaction hash");
+                            continue;
+                        };
+                        if !self
+                            .confirmations
+                            .confirm_log::<T, P, N>(&self.rpc_provider, &log)
+                            .await
+                        {
+                            continue;
+                        }
+                        return Some((resolve_event, tx_hash));
                     }
                     Err(e) => {
                         tracing::error!("Error processing log: {:?}", e);
@@ -81,52 +220,106 @@ where
                 }
             }
             None
-        })
-        .await;
+        };
+        let poll = async move {
+            let from_block = from_block?;
+            poll_until_found(self.tracker_config.poll_interval(), || {
+                self.resolve_from_logs(intent_id, from_block)
+            })
+            .await
+        };
+        let result = tokio::time::timeout(timeout, first_event(watch, poll)).await;
 
         if let Ok(event) = result {
             Ok(event)
         } else {
-            tracing::info!("Auction watching timed out");
+            tracing::info!("Resolve watching timed out");
             Ok(None)
         }
     }
+
+    /// Pull the resolve transaction of a tracked resolve event and decode the proof delivered
+    /// within its calldata. The per-system proof is decoded when `system_id` is known.
+    pub async fn fetch_resolution_payload(
+        &self,
+        intent_id: B256,
+        resolve_event: &UniversalBombetta::Resolve,
+        resolve_tx_hash: B256,
+        system_id: Option<SystemId>,
+    ) -> Result<ResolutionPayload> {
+        if resolve_event.requestId != intent_id {
+            return Err(ClientError::TrackIntentError(format!(
+                "resolve event is for request {}, expected {intent_id}",
+                resolve_event.requestId
+            )));
+        }
+
+        // a node lagging behind the one the event came from answers with none, attempted again
+        let rpc_provider = &self.rpc_provider;
+        let resolve_tx = retry_rpc(&self.rpc_retry, || async move {
+            rpc_provider
+                .get_transaction_by_hash(resolve_tx_hash)
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or_else(|| format!("resolve transaction {resolve_tx_hash} not found"))
+        })
+        .await?;
+
+        let payload = decode_request_resolve_calldata(resolve_tx.input(), system_id)?;
+
+        if payload.intent_id != intent_id {
+            return Err(ClientError::TrackIntentError(format!(
+                "transaction {resolve_tx_hash} did not resolve request {intent_id}"
+            )));
+        }
+
+        Ok(payload)
+    }
 }
 
 #[async_trait]
-impl<T, P, N> IntentResolveTracker for ComputeRequestTracker<T, P, N>
+impl<T, P, N> IntentAuctionTracker for ComputeRequestTracker<T, P, N>
 where
     T: Transport + Clone + Send + Sync,
     P: Provider<T, N> + Clone + Send + Sync,
     N: Network + Clone + Send + Sync,
 {
     type Intent = ComputeRequest<SystemParams>;
-    type ResolveEvent = UniversalBombetta::Resolve;
+    type BidEvent = UniversalBombetta::Bid;
 
     /// Start tracking auction events for a request
-    async fn track_resolve(
+    async fn track_auction(
         &self,
         intent_id: B256,
         timeout: Duration,
-    ) -> Result<Option<Self::ResolveEvent>> {
+    ) -> Result<Option<Self::BidEvent>> {
         let market_contract =
             UniversalBombettaInstance::new(self.market_address, self.rpc_provider.clone());
 
-        let resolve_filter = market_contract.Resolve_filter().topic2(intent_id);
-
-        let event_poller = resolve_filter
-            .watch()
-            .await
-            .map_err(|e| ClientError::TrackIntentError(e.to_string()))?;
-
-        let mut resolve_stream = event_poller.into_stream();
+        let from_block = self.polling_start().await?;
+        let bid_stream = match market_contract.Bid_filter().topic2(intent_id).watch().await {
+            Ok(event_poller) => Some(event_poller.into_stream()),
+            Err(e) if from_block.is_some() => {
+                tracing::warn!("watching bid events failed, polling the market only: {e}");
+                None
+            }
+            Err(e) => return Err(ClientError::TrackIntentError(e.to_string())),
+        };
 
-        let result = tokio::time::timeout(timeout, async move {
-            while let Some(log_result) = resolve_stream.next().await {
+        let watch = async move {
+            let mut bid_stream = bid_stream?;
+            while let Some(log_result) = bid_stream.next().await {
                 match log_result {
-                    Ok((resolve_event, _)) => {
-                        tracing::info!("Resolve event found: {:?}", resolve_event);
-                        return Some(resolve_event);
+                    Ok((bid_event, log)) => {
+                        tracing::info!("Bid event found: {:?}", bid_event);
+                        if !self
+                            .confirmations
+                            .confirm_log::<T, P, N>(&self.rpc_provider, &log)
+                            .await
+                        {
+                            continue;
+                        }
+                        return Some(bid_event);
                     }
                     Err(e) => {
                         tracing::error!("Error processing log: {:?}", e);
@@ -134,8 +327,15 @@ where
                 }
             }
             None
-        })
-        .await;
+        };
+        let poll = async move {
+            let from_block = from_block?;
+            poll_until_found(self.tracker_config.poll_interval(), || {
+                self.bid_from_state(intent_id, from_block)
+            })
+            .await
+        };
+        let result = tokio::time::timeout(timeout, first_event(watch, poll)).await;
 
         if let Ok(event) = result {
             Ok(event)
@@ -145,3 +345,26 @@ where
         }
     }
 }
+
+#[async_trait]
+impl<T, P, N> IntentResolveTracker for ComputeRequestTracker<T, P, N>
+where
+    T: Transport + Clone + Send + Sync,
+    P: Provider<T, N> + Clone + Send + Sync,
+    N: Network + Clone + Send + Sync,
+{
+    type Intent = ComputeRequest<SystemParams>;
+    type ResolveEvent = UniversalBombetta::Resolve;
+
+    /// Start tracking resolve events for a request
+    async fn track_resolve(
+        &self,
+        intent_id: B256,
+        timeout: Duration,
+    ) -> Result<Option<Self::ResolveEvent>> {
+        Ok(self
+            .track_resolve_with_tx_hash(intent_id, timeout)
+            .await?
+            .map(|(resolve_event, _)| resolve_event))
+    }
+}
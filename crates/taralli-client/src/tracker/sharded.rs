@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use futures_util::future::join_all;
+use taralli_primitives::alloy::{
+    network::Network, primitives::Address, primitives::B256, providers::Provider,
+    transports::Transport,
+};
+use taralli_primitives::intents::{request::ComputeRequest, ComputeIntent};
+use taralli_primitives::systems::SystemParams;
+
+use super::request::ComputeRequestTracker;
+use super::{IntentAuctionTracker, IntentResolveTracker};
+
+/// How the auction and resolution of one shard ended
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShardStatus {
+    /// a provider won the shard and resolved it
+    Resolved,
+    /// no provider bid before the auction ended
+    NoBid,
+    /// a provider won the shard but didn't resolve it before its deadline
+    Unresolved,
+    /// tracking the shard failed
+    Failed(String),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShardOutcome {
+    pub index: usize,
+    pub request_id: B256,
+    pub status: ShardStatus,
+}
+
+/// Outcome of all the shards of a job
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShardedOutcome {
+    /// every shard was resolved
+    Complete { shards: Vec<ShardOutcome> },
+    /// some shards were not resolved, their indexes are listed in `failed`
+    Partial {
+        shards: Vec<ShardOutcome>,
+        failed: Vec<usize>,
+    },
+}
+
+impl ShardedOutcome {
+    /// Aggregate the outcomes of each shard, ordered by shard index
+    pub fn aggregate(mut shards: Vec<ShardOutcome>) -> Self {
+        shards.sort_by_key(|shard| shard.index);
+        let failed: Vec<usize> = shards
+            .iter()
+            .filter(|shard| shard.status != ShardStatus::Resolved)
+            .map(|shard| shard.index)
+            .collect();
+        if failed.is_empty() {
+            Self::Complete { shards }
+        } else {
+            Self::Partial { shards, failed }
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        matches!(self, Self::Complete { .. })
+    }
+
+    pub fn shards(&self) -> &[ShardOutcome] {
+        match self {
+            Self::Complete { shards } | Self::Partial { shards, .. } => shards,
+        }
+    }
+
+    /// Indexes of the shards that were not resolved
+    pub fn failed_shards(&self) -> &[usize] {
+        match self {
+            Self::Complete { .. } => &[],
+            Self::Partial { failed, .. } => failed,
+        }
+    }
+}
+
+/// Tracks the auctions and resolutions of the shards of a job built by `ShardedRequestBuilder`
+pub struct ShardedRequestTracker<T, P, N> {
+    tracker: ComputeRequestTracker<T, P, N>,
+}
+
+impl<T, P, N> ShardedRequestTracker<T, P, N>
+where
+    T: Transport + Clone + Send + Sync,
+    P: Provider<T, N> + Clone + Send + Sync,
+    N: Network + Clone + Send + Sync,
+{
+    pub fn new(rpc_provider: P, market_address: Address) -> Self {
+        Self {
+            tracker: ComputeRequestTracker::new(rpc_provider, market_address),
+        }
+    }
+
+    /// Track every shard until it is resolved or its deadline passes. Start tracking before
+    /// submitting the shards so no bid or resolve event is missed.
+    pub async fn track_all(&self, shards: &[ComputeRequest<SystemParams>]) -> ShardedOutcome {
+        let outcomes = join_all(
+            shards
+                .iter()
+                .enumerate()
+                .map(|(index, shard)| self.track_shard(index, shard)),
+        )
+        .await;
+        ShardedOutcome::aggregate(outcomes)
+    }
+
+    async fn track_shard(
+        &self,
+        index: usize,
+        shard: &ComputeRequest<SystemParams>,
+    ) -> ShardOutcome {
+        let request_id = shard.compute_id();
+        let proof_request = &shard.proof_request;
+        let auction_length = proof_request
+            .endAuctionTimestamp
+            .saturating_sub(proof_request.startAuctionTimestamp);
+        let resolve_deadline =
+            proof_request.endAuctionTimestamp + u64::from(proof_request.provingTime);
+
+        let (auction, resolution) = tokio::join!(
+            self.tracker
+                .track_auction(request_id, Duration::from_secs(auction_length)),
+            self.tracker.track_resolve(
+                request_id,
+                Duration::from_secs(
+                    resolve_deadline.saturating_sub(proof_request.startAuctionTimestamp)
+                ),
+            ),
+        );
+        let status = match (auction, resolution) {
+            (Err(e), _) | (_, Err(e)) => ShardStatus::Failed(e.to_string()),
+            (Ok(_), Ok(Some(_))) => ShardStatus::Resolved,
+            (Ok(None), Ok(None)) => ShardStatus::NoBid,
+            (Ok(Some(_)), Ok(None)) => ShardStatus::Unresolved,
+        };
+        ShardOutcome {
+            index,
+            request_id,
+            status,
+        }
+    }
+}
@@ -0,0 +1,293 @@
+//! Results of the workloads a provider already proved, reused when an identical workload is
+//! requested again (e.g. a request resubmitted after its auction expired without a bid).
+//!
+//! Results are keyed by system, inputs commitment and a hash of the full system params, so a hit
+//! is only possible for the exact same program and inputs. Systems whose proofs embed randomness
+//! a market may reject once replayed can be left out of the cache.
+//!
+//! The file is rewritten, atomically and off the executor, when a result is cached. Hits only
+//! update the recency of results in memory, written along with the next result cached or on
+//! `flush`, so a run of hits doesn't rewrite the file each time.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use taralli_primitives::alloy::primitives::{keccak256, B256};
+use taralli_primitives::intents::{CommonProofCommitment, ComputeIntent};
+use taralli_primitives::systems::SystemId;
+
+use crate::error::{ClientError, Result};
+use crate::persist::persist;
+use crate::worker::WorkResult;
+
+/// number of results kept when none is configured
+pub const DEFAULT_MAX_ENTRIES: usize = 64;
+/// seconds a result is reused for when none is configured
+pub const DEFAULT_MAX_AGE_SECONDS: u64 = 24 * 60 * 60;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkCacheConfig {
+    /// results kept, the least recently used ones are evicted beyond it
+    #[serde(default = "default_max_entries")]
+    pub max_entries: usize,
+    /// seconds after which a result is proved again instead of reused
+    #[serde(default = "default_max_age_seconds")]
+    pub max_age_seconds: u64,
+    /// systems whose results are never reused, e.g. ones embedding randomness in their proofs
+    #[serde(default)]
+    pub uncached_systems: Vec<SystemId>,
+}
+
+fn default_max_entries() -> usize {
+    DEFAULT_MAX_ENTRIES
+}
+
+fn default_max_age_seconds() -> u64 {
+    DEFAULT_MAX_AGE_SECONDS
+}
+
+impl Default for WorkCacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: DEFAULT_MAX_ENTRIES,
+            max_age_seconds: DEFAULT_MAX_AGE_SECONDS,
+            uncached_systems: Vec::new(),
+        }
+    }
+}
+
+impl WorkCacheConfig {
+    #[must_use]
+    pub fn max_age(&self) -> Duration {
+        Duration::from_secs(self.max_age_seconds)
+    }
+}
+
+/// Workload a result was proved for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct WorkKey {
+    pub system_id: SystemId,
+    pub inputs_commitment: B256,
+    /// keccak256 of the JSON encoded system params
+    pub params_hash: B256,
+}
+
+impl WorkKey {
+    /// Key of the workload of `intent`, `None` when its system can't be encoded
+    pub fn of<I: ComputeIntent>(intent: &I) -> Option<Self> {
+        let params = serde_json::to_vec(intent.system()).ok()?;
+        Some(Self {
+            system_id: intent.system_id(),
+            inputs_commitment: intent.proof_commitment().inputs_commitment(),
+            params_hash: keccak256(params),
+        })
+    }
+}
+
+/// Cached result, as persisted
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkRecord {
+    pub key: WorkKey,
+    pub result: WorkResult,
+    /// unix timestamp the result was proved at
+    pub proved_at: u64,
+    /// unix timestamp the result was last reused at, or proved at if never
+    pub last_used: u64,
+}
+
+/// Bounded least recently used cache of work results, persisted to a JSON file when one is given
+#[derive(Debug)]
+pub struct WorkCache {
+    config: WorkCacheConfig,
+    records: Mutex<CacheRecords>,
+    path: Option<PathBuf>,
+    // held while the records are persisted, so writes land in the order records were taken in
+    persisting: tokio::sync::Mutex<()>,
+}
+
+#[derive(Debug, Default)]
+struct CacheRecords {
+    entries: HashMap<WorkKey, WorkRecord>,
+    /// use order of the entries, incremented on each insertion and hit so recency is strict
+    /// even within the same second
+    uses: HashMap<WorkKey, u64>,
+    clock: u64,
+    /// changed since last persisted
+    dirty: bool,
+}
+
+impl CacheRecords {
+    fn touch(&mut self, key: WorkKey) {
+        self.clock += 1;
+        self.uses.insert(key, self.clock);
+    }
+
+    fn remove(&mut self, key: &WorkKey) {
+        self.entries.remove(key);
+        self.uses.remove(key);
+    }
+}
+
+impl WorkCache {
+    pub fn new(config: WorkCacheConfig) -> Self {
+        Self {
+            config,
+            records: Mutex::new(CacheRecords::default()),
+            path: None,
+            persisting: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Cache persisted to `path`, starting from the results it holds if it exists
+    pub fn load(config: WorkCacheConfig, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut persisted: Vec<WorkRecord> = match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| {
+                ClientError::ConfigError(format!("parsing {}: {e}", path.display()))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(ClientError::ConfigError(format!(
+                    "reading {}: {e}",
+                    path.display()
+                )))
+            }
+        };
+
+        let cache = Self {
+            config,
+            records: Mutex::new(CacheRecords::default()),
+            path: Some(path.to_path_buf()),
+            persisting: tokio::sync::Mutex::new(()),
+        };
+        persisted.sort_by_key(|record| record.last_used);
+        {
+            let mut records = cache.records.lock().unwrap();
+            for record in persisted {
+                records.touch(record.key);
+                records.entries.insert(record.key, record);
+            }
+            cache.evict(&mut records);
+        }
+        Ok(cache)
+    }
+
+    /// Number of results cached
+    pub fn len(&self) -> usize {
+        self.records.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether results of `system_id` are reused
+    pub fn caches(&self, system_id: SystemId) -> bool {
+        !self.config.uncached_systems.contains(&system_id)
+    }
+
+    /// Result previously proved for the workload of `intent`, if it is still fresh. Stale
+    /// results are dropped. Neither is persisted until the next result is cached or the cache
+    /// flushed.
+    pub fn get<I: ComputeIntent>(&self, intent: &I) -> Option<WorkResult> {
+        if !self.caches(intent.system_id()) {
+            return None;
+        }
+        let key = WorkKey::of(intent)?;
+        let now = unix_now();
+        let mut records = self.records.lock().unwrap();
+        let record = records.entries.get_mut(&key)?;
+        if now.saturating_sub(record.proved_at) > self.config.max_age_seconds {
+            records.remove(&key);
+            records.dirty = true;
+            return None;
+        }
+        record.last_used = now;
+        let result = record.result.clone();
+        records.touch(key);
+        records.dirty = true;
+        Some(result)
+    }
+
+    /// Cache the result proved for the workload of `intent`, evicting the least recently used
+    /// results beyond `max_entries`, and persist the cache
+    pub async fn insert<I: ComputeIntent>(&self, intent: &I, result: &WorkResult) {
+        if !self.caches(intent.system_id()) {
+            return;
+        }
+        let Some(key) = WorkKey::of(intent) else {
+            return;
+        };
+        let now = unix_now();
+        {
+            let mut records = self.records.lock().unwrap();
+            records.entries.insert(
+                key,
+                WorkRecord {
+                    key,
+                    result: result.clone(),
+                    proved_at: now,
+                    last_used: now,
+                },
+            );
+            records.touch(key);
+            self.evict(&mut records);
+            records.dirty = true;
+        }
+        self.persist().await;
+    }
+
+    /// Persist the hits and stale results dropped since the cache was last persisted, if any
+    pub async fn flush(&self) {
+        self.persist().await;
+    }
+
+    fn evict(&self, records: &mut CacheRecords) {
+        while records.entries.len() > self.config.max_entries {
+            let Some(oldest) = records
+                .uses
+                .iter()
+                .min_by_key(|(_, used)| **used)
+                .map(|(key, _)| *key)
+            else {
+                break;
+            };
+            records.remove(&oldest);
+        }
+    }
+
+    async fn persist(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        // the last write to start persists the latest records, whatever order changes land in
+        let _persisting = self.persisting.lock().await;
+        let json = {
+            let mut records = self.records.lock().unwrap();
+            if !records.dirty {
+                return;
+            }
+            records.dirty = false;
+            let mut snapshot: Vec<&WorkRecord> = records.entries.values().collect();
+            snapshot.sort_by_key(|record| records.uses.get(&record.key));
+            serde_json::to_vec(&snapshot)
+        };
+        let written = match json {
+            Ok(json) => persist(path.clone(), json).await.map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        if let Err(e) = written {
+            self.records.lock().unwrap().dirty = true;
+            tracing::warn!("failed to persist work cache to {}: {e}", path.display());
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
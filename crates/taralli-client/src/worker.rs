@@ -1,14 +1,22 @@
 use crate::error::{ClientError, Result};
+use crate::work_cache::WorkCache;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use taralli_primitives::alloy::primitives::{Bytes, FixedBytes};
-use taralli_primitives::intents::ComputeIntent;
+use taralli_primitives::intents::{CommonProofCommitment, ComputeIntent};
 use taralli_primitives::systems::SystemId;
+use tokio_util::sync::CancellationToken;
+
+/// seconds of an intent's proving window kept for its resolve transaction to land, when none is
+/// configured
+pub const DEFAULT_RESOLVE_MARGIN_SECONDS: u64 = 30;
 
 /// Output type of a compute worker that can be used by an intent
 /// resolver to resolve a compute intent.
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct WorkResult {
     pub opaque_submission: Bytes,
     pub partial_commitment: FixedBytes<32>,
@@ -20,13 +28,45 @@ pub struct WorkResult {
 #[async_trait]
 pub trait ComputeWorker<I: ComputeIntent>: Send + Sync {
     async fn execute(&self, intent: &I) -> Result<WorkResult>;
+
+    /// Execute within `budget`, what is left of the intent's proving window, aborting once
+    /// `cancel` is cancelled. The execution is dropped when its budget runs out either way,
+    /// workers running subprocesses or remote sessions override this to bound and stop them too.
+    async fn execute_cancellable(
+        &self,
+        intent: &I,
+        budget: Duration,
+        cancel: CancellationToken,
+    ) -> Result<WorkResult> {
+        let _ = (budget, cancel);
+        self.execute(intent).await
+    }
+
+    /// Description of the worker, recorded when it's replaced on a running provider
+    fn describe(&self) -> String {
+        std::any::type_name::<Self>().to_string()
+    }
+
+    /// Whether the worker computes the partial commitment intents checking a partial commitment
+    /// result have to be resolved with. Providers don't bid on those intents otherwise.
+    fn produces_partial_commitment(&self) -> bool {
+        false
+    }
 }
 
+/// Worker of a system, shared by the executions running it
+pub type SharedWorker<I> = Arc<dyn ComputeWorker<I> + Send + Sync>;
+
 /// manager type allowing clients to handle multiple compute workers organized
-/// by system ID to provide compute for many systems simultaneously
+/// by system ID to provide compute for many systems simultaneously, optionally
+/// reusing the results of workloads already proved. Workers are cancelled once
+/// the proving window of their intent, less the resolve margin, runs out.
+/// Clones share their workers, so a worker replaced through one reaches the others.
 #[derive(Clone)]
 pub struct WorkerManager<I: ComputeIntent> {
-    pub workers: HashMap<SystemId, Arc<dyn ComputeWorker<I> + Send + Sync>>,
+    workers: Arc<RwLock<HashMap<SystemId, SharedWorker<I>>>>,
+    pub cache: Option<Arc<WorkCache>>,
+    pub resolve_margin: Duration,
 }
 
 impl<I: ComputeIntent> WorkerManager<I> {
@@ -34,17 +74,136 @@ impl<I: ComputeIntent> WorkerManager<I> {
     pub fn new(
         workers: HashMap<SystemId, Arc<dyn ComputeWorker<I> + Send + Sync + 'static>>,
     ) -> Self {
-        Self { workers }
+        Self {
+            workers: Arc::new(RwLock::new(workers)),
+            cache: None,
+            resolve_margin: Duration::from_secs(DEFAULT_RESOLVE_MARGIN_SECONDS),
+        }
     }
 
+    /// Reuse the results cached in `cache` instead of executing identical workloads again
+    #[must_use]
+    pub fn with_cache(mut self, cache: Arc<WorkCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Keep `resolve_margin` of each proving window for the resolve transaction
+    #[must_use]
+    pub fn with_resolve_margin(mut self, resolve_margin: Duration) -> Self {
+        self.resolve_margin = resolve_margin;
+        self
+    }
+
+    /// Worker currently set for `system_id`
+    pub fn worker(&self, system_id: SystemId) -> Option<SharedWorker<I>> {
+        self.workers.read().unwrap().get(&system_id).cloned()
+    }
+
+    /// Set the worker of `system_id`, returning the one it replaces. Executions already started
+    /// keep the worker they started with.
+    pub fn set_worker(
+        &self,
+        system_id: SystemId,
+        worker: SharedWorker<I>,
+    ) -> Option<SharedWorker<I>> {
+        self.workers.write().unwrap().insert(system_id, worker)
+    }
+
+    /// Remove the worker of `system_id`, executions already started run to completion
+    pub fn remove_worker(&self, system_id: SystemId) -> Option<SharedWorker<I>> {
+        self.workers.write().unwrap().remove(&system_id)
+    }
+
+    /// Fail with `ClientError::IntentAnalysisError` when `intent` checks a partial commitment
+    /// result on resolve that the worker of its system can't produce
+    pub fn ensure_partial_commitment(&self, intent: &I) -> Result<()> {
+        if intent
+            .proof_commitment()
+            .partial_commitment_check()
+            .is_none()
+        {
+            return Ok(());
+        }
+        let system_id = I::system_id(intent);
+        match self.worker(system_id) {
+            Some(worker) if worker.produces_partial_commitment() => Ok(()),
+            _ => Err(ClientError::IntentAnalysisError(format!(
+                "intent checks a partial commitment result the worker of system {:?} can't produce",
+                system_id
+            ))),
+        }
+    }
+
+    /// Time the worker of `intent` is given at `now`: what is left of the proving window its bid
+    /// at `bid_timestamp` opened, less the resolve margin
+    pub fn proving_budget(&self, intent: &I, bid_timestamp: u64, now: u64) -> Duration {
+        let proving_deadline =
+            bid_timestamp.saturating_add(intent.proof_commitment().proving_time().into());
+        Duration::from_secs(proving_deadline.saturating_sub(now))
+            .saturating_sub(self.resolve_margin)
+    }
+
+    /// Execute the worker of `intent` as if its bid landed now, e.g. right after bidding
     pub async fn execute(&self, intent: &I) -> Result<WorkResult> {
-        let worker = self.workers.get(&I::system_id(intent)).ok_or_else(|| {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("system time before unix epoch")
+            .as_secs();
+        self.execute_after_bid(intent, now, now).await
+    }
+
+    /// Execute the worker of `intent` won by a bid at `bid_timestamp`, within the proving budget
+    /// left at `now`
+    pub async fn execute_after_bid(
+        &self,
+        intent: &I,
+        bid_timestamp: u64,
+        now: u64,
+    ) -> Result<WorkResult> {
+        self.execute_within(intent, self.proving_budget(intent, bid_timestamp, now))
+            .await
+    }
+
+    /// Execute the worker of `intent`, cancelling it with `ClientError::WorkerTimeout` once
+    /// `budget` runs out. The worker is told to stop before its execution is dropped, so it can
+    /// stop what it runs outside of it.
+    pub async fn execute_within(&self, intent: &I, budget: Duration) -> Result<WorkResult> {
+        let system_id = I::system_id(intent);
+        // cloned out of the map, a worker replaced meanwhile doesn't affect this execution
+        let worker = self.worker(system_id).ok_or_else(|| {
             ClientError::WorkerError(format!(
                 "worker not set for proving system id: {:?}",
-                I::system_id(intent)
+                system_id
             ))
         })?;
 
-        worker.execute(intent).await
+        if let Some(result) = self.cache.as_ref().and_then(|cache| cache.get(intent)) {
+            tracing::info!("reusing cached work result of system {:?}", system_id);
+            return Ok(result);
+        }
+
+        let cancel = CancellationToken::new();
+        // cancelled however the execution ends, the manager's future being dropped included
+        let _cancel_on_drop = cancel.clone().drop_guard();
+        let execution = worker.execute_cancellable(intent, budget, cancel.clone());
+        tokio::pin!(execution);
+        let result = tokio::select! {
+            result = &mut execution => result?,
+            _ = tokio::time::sleep(budget) => {
+                // cancelled before the execution is dropped on return
+                cancel.cancel();
+                let e = ClientError::WorkerTimeout {
+                    system_id,
+                    budget_secs: budget.as_secs(),
+                };
+                tracing::warn!("{e}");
+                return Err(e);
+            }
+        };
+        if let Some(cache) = &self.cache {
+            cache.insert(intent, &result).await;
+        }
+        Ok(result)
     }
 }
@@ -0,0 +1,124 @@
+use taralli_client::intent_builder::amount::{format_amount, parse_decimal_amount};
+use taralli_client::intent_builder::offer::ComputeOfferBuilder;
+use taralli_client::intent_builder::request::ComputeRequestBuilder;
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::{Address, U256};
+use taralli_primitives::alloy::providers::{ProviderBuilder, RootProvider};
+use taralli_primitives::alloy::transports::http::{Client, Http};
+use taralli_primitives::systems::SystemId;
+
+type StubProvider = RootProvider<Http<Client>>;
+
+/// Provider of a node never reached, the builders only contacting it when building
+fn provider() -> StubProvider {
+    ProviderBuilder::new().on_http("http://127.0.0.1:1".parse().unwrap())
+}
+
+#[test]
+/// Ensures whole token amounts are scaled by the decimals of 6, 8 and 18 decimals tokens.
+fn should_scale_amounts_by_token_decimals() {
+    assert_eq!(
+        parse_decimal_amount(1.5, 6).unwrap(),
+        U256::from(1_500_000u64)
+    );
+    assert_eq!(
+        parse_decimal_amount(0.00000001, 8).unwrap(),
+        U256::from(1u64)
+    );
+    assert_eq!(
+        parse_decimal_amount(100.0, 18).unwrap(),
+        U256::from(100u128 * 10u128.pow(18))
+    );
+    assert_eq!(parse_decimal_amount(0.0, 18).unwrap(), U256::ZERO);
+    assert_eq!(parse_decimal_amount(-0.0, 6).unwrap(), U256::ZERO);
+    assert_eq!(parse_decimal_amount(42.0, 0).unwrap(), U256::from(42u64));
+}
+
+#[test]
+/// Ensures amounts are taken at their shortest decimal representation, so values without an
+/// exact binary representation don't round to a neighbouring raw amount.
+fn should_not_round_inexact_floats() {
+    assert_eq!(
+        parse_decimal_amount(0.1, 18).unwrap(),
+        U256::from(10u128.pow(17))
+    );
+    assert_eq!(
+        parse_decimal_amount(0.3, 6).unwrap(),
+        U256::from(300_000u64)
+    );
+    assert_eq!(
+        parse_decimal_amount(1.000001, 6).unwrap(),
+        U256::from(1_000_001u64)
+    );
+}
+
+#[test]
+/// Ensures amounts with more fractional digits than the token's decimals are rejected rather
+/// than truncated.
+fn should_reject_precision_loss() {
+    for (amount, decimals) in [(1.0000001, 6), (0.000000001, 8), (0.5, 0)] {
+        let err = parse_decimal_amount(amount, decimals).unwrap_err();
+        assert!(err.to_string().contains("decimals"), "{err}");
+    }
+}
+
+#[test]
+/// Ensures NaN, infinite and negative amounts are rejected.
+fn should_reject_invalid_amounts() {
+    for amount in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, -1.0, -0.5] {
+        assert!(parse_decimal_amount(amount, 18).is_err(), "{amount}");
+    }
+}
+
+#[test]
+/// Ensures amounts not fitting a U256 once scaled are rejected instead of wrapping.
+fn should_reject_amounts_overflowing_u256() {
+    // U256::MAX is about 1.16e77
+    assert!(parse_decimal_amount(1e50, 18).is_ok());
+    let err = parse_decimal_amount(1e50, 36).unwrap_err();
+    assert!(err.to_string().contains("overflows"), "{err}");
+    assert!(parse_decimal_amount(1e78, 0).is_err());
+    assert!(parse_decimal_amount(f64::MAX, 0).is_err());
+    assert!(parse_decimal_amount(1.0, 78).is_err());
+}
+
+#[test]
+/// Ensures raw amounts are formatted back into the decimal amounts they were parsed from.
+fn should_format_raw_amounts() {
+    assert_eq!(format_amount(U256::from(1_500_000u64), 6), "1.5");
+    assert_eq!(format_amount(U256::from(1u64), 8), "0.00000001");
+    assert_eq!(format_amount(U256::from(10u128.pow(18)), 18), "1");
+    assert_eq!(format_amount(U256::ZERO, 18), "0");
+    assert_eq!(format_amount(U256::from(42u64), 0), "42");
+    for (amount, decimals) in [(0.1, 18), (123.456789, 6), (0.00000042, 8)] {
+        let raw = parse_decimal_amount(amount, decimals).unwrap();
+        assert_eq!(format_amount(raw, decimals), amount.to_string());
+    }
+}
+
+#[test]
+/// Ensures the builders convert decimal amounts with the decimals of the token they pay in.
+fn should_set_builder_amounts_from_decimals() {
+    let request_builder: ComputeRequestBuilder<Http<Client>, StubProvider, Ethereum> =
+        ComputeRequestBuilder::new(provider(), Address::ZERO, Address::ZERO, SystemId::Risc0)
+            .reward_token_decimals(6)
+            .set_reward_amounts_decimal(0.5, 2.25)
+            .unwrap();
+    assert_eq!(request_builder.min_reward_amount, U256::from(500_000u64));
+    assert_eq!(request_builder.max_reward_amount, U256::from(2_250_000u64));
+    assert!(request_builder
+        .set_reward_amounts_decimal(0.0000001, 1.0)
+        .is_err());
+
+    let offer_builder: ComputeOfferBuilder<Http<Client>, StubProvider, Ethereum> =
+        ComputeOfferBuilder::new(provider(), Address::ZERO, Address::ZERO, SystemId::Risc0)
+            .reward_token_decimals(8)
+            .stake_token_decimals(18)
+            .reward_amount_decimal(0.25)
+            .unwrap()
+            .stake_amount_decimal(0.1)
+            .unwrap();
+    assert_eq!(offer_builder.reward_amount, U256::from(25_000_000u64));
+    assert_eq!(offer_builder.stake_amount, U256::from(10u128.pow(17)));
+    assert!(offer_builder.stake_amount_decimal(f64::NAN).is_err());
+}
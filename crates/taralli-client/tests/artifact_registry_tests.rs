@@ -0,0 +1,192 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use taralli_client::artifacts::{
+    artifact_digest, ArtifactInfo, ArtifactMatch, ArtifactRegistry, ArtifactRegistryConfig,
+    UNKNOWN_ARTIFACT,
+};
+use taralli_primitives::alloy::primitives::{Address, B256};
+use taralli_primitives::logging::intent_span;
+use taralli_primitives::systems::risc0::Risc0ProofParams;
+use taralli_primitives::systems::{SystemId, SystemParams};
+use tracing_subscriber::{fmt, layer::SubscriberExt, Registry};
+
+fn read_fixture(path: &str) -> Vec<u8> {
+    std::fs::read(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../contracts/test-proof-data")
+            .join(path),
+    )
+    .unwrap()
+}
+
+fn risc0_system(elf: Vec<u8>) -> SystemParams {
+    SystemParams::Risc0(Risc0ProofParams {
+        elf,
+        inputs: vec![0; 32],
+    })
+}
+
+fn is_even(trusted: bool) -> ArtifactInfo {
+    ArtifactInfo {
+        label: "is-even".to_string(),
+        expected_proving_seconds: Some(120),
+        trusted,
+    }
+}
+
+#[test]
+/// Ensures a registered ELF is reported under its label, and any other one as unknown with its
+/// digest, each match being counted.
+fn should_label_registered_artifacts() {
+    let registry = ArtifactRegistry::default();
+    let elf = read_fixture("risc0/is-even");
+    let digest = registry.register_bytes(&elf, is_even(false));
+    assert_eq!(digest, artifact_digest(&elf));
+
+    let known = registry.identify(&risc0_system(elf.clone()));
+    assert_eq!(known, ArtifactMatch::Known(is_even(false)));
+    assert_eq!(known.label(), "is-even");
+
+    let mut other_elf = elf;
+    other_elf.push(0);
+    let unknown = registry.identify(&risc0_system(other_elf.clone()));
+    assert_eq!(
+        unknown,
+        ArtifactMatch::Unknown {
+            digests: vec![artifact_digest(&other_elf)]
+        }
+    );
+    assert_eq!(unknown.label(), UNKNOWN_ARTIFACT);
+
+    let stats = registry.stats();
+    assert_eq!(stats.matched.get("is-even"), Some(&1));
+    assert_eq!(stats.unknown, 1);
+    assert!(stats.rejected.is_empty());
+
+    // artifacts removed at runtime are no longer known
+    assert_eq!(registry.remove(digest), Some(is_even(false)));
+    assert_eq!(
+        registry.identify(&risc0_system(read_fixture("risc0/is-even"))),
+        ArtifactMatch::Unknown {
+            digests: vec![digest]
+        }
+    );
+}
+
+#[test]
+/// Ensures strict mode only lets trusted artifacts through, unknown and untrusted ones being
+/// vetoed, while the requests of every artifact go through otherwise.
+fn should_reject_untrusted_artifacts_in_strict_mode() {
+    let elf = read_fixture("risc0/is-even");
+    let registry = ArtifactRegistry::new(ArtifactRegistryConfig {
+        strict: true,
+        artifacts: [(artifact_digest(&elf), is_even(true))].into(),
+    });
+
+    assert_eq!(
+        registry.check(&risc0_system(elf.clone())).unwrap().label(),
+        "is-even"
+    );
+    let err = registry.check(&risc0_system(vec![1, 2, 3])).unwrap_err();
+    assert_eq!(err.error_code(), "intent_vetoed");
+    assert!(err.to_string().contains(UNKNOWN_ARTIFACT), "{err}");
+
+    registry.register_bytes(&elf, is_even(false));
+    assert!(registry.check(&risc0_system(elf.clone())).is_err());
+    let rejected = registry.stats().rejected;
+    assert_eq!(rejected.get(UNKNOWN_ARTIFACT), Some(&1));
+    assert_eq!(rejected.get("is-even"), Some(&1));
+
+    registry.set_strict(false);
+    assert!(registry.check(&risc0_system(elf)).is_ok());
+    assert!(registry.check(&risc0_system(vec![1, 2, 3])).is_ok());
+}
+
+#[test]
+/// Ensures the events logged for an intent once its artifacts were checked carry their label,
+/// whether the intent goes through or is dropped.
+fn should_label_intent_events_with_artifact() {
+    let logged = Arc::new(Mutex::new(Vec::new()));
+    let writer = logged.clone();
+    let subscriber = Registry::default().with(
+        fmt::layer()
+            .with_ansi(false)
+            .with_writer(move || LogWriter(writer.clone())),
+    );
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let elf = read_fixture("risc0/is-even");
+    let registry = ArtifactRegistry::new(ArtifactRegistryConfig {
+        strict: true,
+        artifacts: [(artifact_digest(&elf), is_even(true))].into(),
+    });
+    for (intent, elf) in [(1, elf), (2, vec![1, 2, 3])] {
+        intent_span(B256::repeat_byte(intent), SystemId::Risc0, Address::ZERO).in_scope(|| {
+            let admitted = registry.check(&risc0_system(elf)).is_ok();
+            tracing::info!("intent {intent} admitted: {admitted}");
+        });
+    }
+
+    let logged = String::from_utf8(logged.lock().unwrap().clone()).unwrap();
+    let line = |message: &str| {
+        logged
+            .lines()
+            .find(|line| line.contains(message))
+            .unwrap_or_else(|| panic!("{message} not logged: {logged}"))
+    };
+    let admitted = line("intent 1 admitted: true");
+    assert!(admitted.contains("artifact=is-even"), "{admitted}");
+    let dropped = line("intent 2 admitted: false");
+    assert!(
+        dropped.contains(&format!("artifact={UNKNOWN_ARTIFACT}")),
+        "{dropped}"
+    );
+}
+
+#[test]
+/// Ensures a registry is read from its JSON file, artifacts keyed by their hex digest.
+fn should_load_registry_file() {
+    let elf = read_fixture("risc0/is-even");
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("artifacts.json");
+    std::fs::write(
+        &path,
+        serde_json::json!({
+            "strict": true,
+            "artifacts": {
+                artifact_digest(&elf).to_string(): { "label": "is-even", "trusted": true }
+            }
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let registry = ArtifactRegistry::load(&path).unwrap();
+    assert!(registry.config().strict);
+    assert_eq!(
+        registry.check(&risc0_system(elf)).unwrap(),
+        ArtifactMatch::Known(ArtifactInfo {
+            label: "is-even".to_string(),
+            expected_proving_seconds: None,
+            trusted: true,
+        })
+    );
+
+    let err = ArtifactRegistry::load(dir.path().join("missing.json")).unwrap_err();
+    assert_eq!(err.error_code(), "config");
+}
+
+/// Writer appending the log lines to a shared buffer
+struct LogWriter(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
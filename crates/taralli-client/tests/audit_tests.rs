@@ -0,0 +1,351 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use taralli_client::audit::{AuditClient, ProofVerdict, ProofVerifier};
+use taralli_client::error::{ClientError, Result};
+use taralli_client::tracker::payload::DecodedProof;
+use taralli_primitives::abi::universal_bombetta::{UniversalBombetta, VerifierDetails};
+use taralli_primitives::alloy::dyn_abi::DynSolValue;
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::{
+    address, fixed_bytes, keccak256, Address, Bytes, LogData, B256, U256,
+};
+use taralli_primitives::alloy::providers::RootProvider;
+use taralli_primitives::alloy::sol_types::{SolCall, SolEvent, SolValue};
+use taralli_primitives::alloy::transports::http::{Client, Http};
+use taralli_primitives::intents::request::{compute_request_id, ComputeRequest};
+use taralli_primitives::systems::{SystemId, SystemParams};
+
+pub mod common;
+use common::fixtures::request_fixture;
+use common::rpc::{method_not_found, start_stub_node, stub_provider};
+
+const MARKET: Address = address!("1111111111111111111111111111111111111111");
+const VERIFIER: Address = address!("AC292cF957Dd5BA174cdA13b05C16aFC71700327");
+const REQUESTER: Address = address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+const PROVIDER: Address = address!("7777777777777777777777777777777777777777");
+const BID_TX_HASH: B256 = B256::repeat_byte(0xb1);
+const RESOLVE_TX_HASH: B256 = B256::repeat_byte(0xe5);
+const IMAGE_ID: B256 = B256::repeat_byte(0x0a);
+const JOURNAL_DIGEST: B256 = B256::repeat_byte(0x0d);
+const VALID_SEAL: [u8; 4] = [1, 2, 3, 4];
+
+type StubAuditClient = AuditClient<Http<Client>, RootProvider<Http<Client>>, Ethereum>;
+
+/// Stub JSON-RPC node serving the logs and transactions of a single bid and, if any, its resolve
+struct StubNode {
+    url: reqwest::Url,
+}
+
+impl StubNode {
+    async fn start(request: &ComputeRequest<SystemParams>, resolve_input: Option<Bytes>) -> Self {
+        let intent_id = compute_request_id(&request.proof_request, &request.signature);
+
+        let bid_input: Bytes = UniversalBombetta::bidCall {
+            request: request.proof_request.clone(),
+            signature: request.signature.as_bytes().into(),
+        }
+        .abi_encode()
+        .into();
+        let bid_log = log_json(
+            UniversalBombetta::Bid {
+                signer: REQUESTER,
+                requestId: intent_id,
+                rewardToken: request.proof_request.rewardToken,
+                rewardAmount: U256::from(150),
+                ethStake: U256::ZERO,
+                provider: PROVIDER,
+            }
+            .encode_log_data(),
+            BID_TX_HASH,
+        );
+        let resolve_log = log_json(
+            UniversalBombetta::Resolve {
+                signer: REQUESTER,
+                requestId: intent_id,
+                resolver: PROVIDER,
+            }
+            .encode_log_data(),
+            RESOLVE_TX_HASH,
+        );
+
+        let mut transactions = HashMap::from([(BID_TX_HASH, bid_input)]);
+        let resolved = resolve_input.is_some();
+        if let Some(resolve_input) = resolve_input {
+            transactions.insert(RESOLVE_TX_HASH, resolve_input);
+        }
+
+        let url = start_stub_node(move |request| match request["method"].as_str().unwrap() {
+            "eth_getLogs" => {
+                let topic0 = &request["params"][0]["topics"][0];
+                let topic0 = topic0.as_str().or_else(|| topic0[0].as_str()).unwrap();
+                let logs = if topic0 == UniversalBombetta::Bid::SIGNATURE_HASH.to_string() {
+                    vec![bid_log.clone()]
+                } else if resolved {
+                    vec![resolve_log.clone()]
+                } else {
+                    vec![]
+                };
+                json!({ "result": logs })
+            }
+            "eth_getTransactionByHash" => {
+                let hash: B256 = request["params"][0].as_str().unwrap().parse().unwrap();
+                let tx = transactions
+                    .get(&hash)
+                    .map(|input| transaction_json(hash, input));
+                json!({ "result": tx })
+            }
+            _ => method_not_found(),
+        })
+        .await;
+
+        Self { url }
+    }
+
+    fn audit_client(&self) -> StubAuditClient {
+        AuditClient::new(stub_provider(self.url.clone()), MARKET)
+    }
+}
+
+fn log_json(log: LogData, tx_hash: B256) -> Value {
+    json!({
+        "address": MARKET,
+        "topics": log.topics(),
+        "data": log.data,
+        "blockHash": B256::repeat_byte(0xbb),
+        "blockNumber": "0x1",
+        "transactionHash": tx_hash,
+        "transactionIndex": "0x0",
+        "logIndex": "0x0",
+        "removed": false,
+    })
+}
+
+fn transaction_json(hash: B256, input: &Bytes) -> Value {
+    json!({
+        "type": "0x0",
+        "hash": hash,
+        "chainId": "0x1",
+        "nonce": "0x0",
+        "gasPrice": "0x1",
+        "gas": "0x100000",
+        "to": MARKET,
+        "value": "0x0",
+        "input": input,
+        "v": "0x25",
+        "r": "0x1",
+        "s": "0x1",
+        "from": PROVIDER,
+        "blockHash": B256::repeat_byte(0xbb),
+        "blockNumber": "0x1",
+        "transactionIndex": "0x0",
+    })
+}
+
+/// Risc0 request committing to the keccak hash of `IMAGE_ID` and `JOURNAL_DIGEST`, located at
+/// offset 64 of the opaque submission
+fn audited_request() -> ComputeRequest<SystemParams> {
+    let extra_data = VerifierDetails {
+        verifier: VERIFIER,
+        selector: fixed_bytes!("ab750e75"),
+        isShaCommitment: false,
+        inputsOffset: U256::from(64),
+        inputsLength: U256::from(64),
+        hasPartialCommitmentResultCheck: false,
+        submittedPartialCommitmentResultOffset: U256::ZERO,
+        submittedPartialCommitmentResultLength: U256::ZERO,
+        predeterminedPartialCommitment: B256::ZERO,
+    }
+    .abi_encode();
+
+    let mut request = request_fixture();
+    request.proof_request.signer = REQUESTER;
+    request.proof_request.market = MARKET;
+    request.proof_request.rewardToken = address!("2222222222222222222222222222222222222222");
+    request.proof_request.inputsCommitment =
+        keccak256([IMAGE_ID.as_slice(), JOURNAL_DIGEST.as_slice()].concat());
+    request.proof_request.extraData = extra_data.into();
+    request
+}
+
+/// Calldata of the resolve transaction of `request`, submitting a risc0 proof laid out as the
+/// risc0 worker formats it
+fn resolve_input(
+    request: &ComputeRequest<SystemParams>,
+    seal: &[u8],
+    journal_digest: B256,
+) -> Bytes {
+    let opaque_submission = DynSolValue::Tuple(vec![
+        DynSolValue::Bytes(seal.to_vec()),
+        DynSolValue::FixedBytes(IMAGE_ID, 32),
+        DynSolValue::FixedBytes(journal_digest, 32),
+    ])
+    .abi_encode();
+    UniversalBombetta::resolveCall {
+        requestId: compute_request_id(&request.proof_request, &request.signature),
+        opaqueSubmission: opaque_submission.into(),
+        submittedPartialCommitment: B256::ZERO,
+    }
+    .abi_encode()
+    .into()
+}
+
+/// Verifier accepting the risc0 proofs sealed with `VALID_SEAL`, and recording whether it was
+/// given the request's system params
+#[derive(Default)]
+struct SealVerifier {
+    given_system: Mutex<Vec<bool>>,
+}
+
+#[async_trait]
+impl ProofVerifier for SealVerifier {
+    async fn verify(
+        &self,
+        proof: &DecodedProof,
+        system: Option<&SystemParams>,
+    ) -> Result<ProofVerdict> {
+        self.given_system.lock().unwrap().push(system.is_some());
+        match proof {
+            DecodedProof::Risc0 { seal, .. } if seal.as_ref() == VALID_SEAL => {
+                Ok(ProofVerdict::Valid)
+            }
+            DecodedProof::Risc0 { .. } => Ok(ProofVerdict::Invalid("bad seal".to_string())),
+            _ => Err(ClientError::WorkerError("Expected a Risc0 proof".into())),
+        }
+    }
+}
+
+#[tokio::test]
+/// Ensures a sound resolution passes both the replayed commitment checks and local verification,
+/// with the verifier given the system params of the stored request.
+async fn should_audit_valid_resolution() {
+    let request = audited_request();
+    let node = StubNode::start(
+        &request,
+        Some(resolve_input(&request, &VALID_SEAL, JOURNAL_DIGEST)),
+    )
+    .await;
+    let verifier = Arc::new(SealVerifier::default());
+
+    let report = node
+        .audit_client()
+        .with_verifier(SystemId::Risc0, verifier.clone())
+        .audit_stored_request(&request)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        report.intent_id,
+        compute_request_id(&request.proof_request, &request.signature)
+    );
+    assert_eq!(report.system_id, SystemId::Risc0);
+    assert_eq!(report.provider, PROVIDER);
+    assert_eq!(report.resolve_tx_hash, RESOLVE_TX_HASH);
+    assert_eq!(report.verifier, Some(VERIFIER));
+    assert_eq!(
+        report.proof_request.inputsCommitment,
+        request.proof_request.inputsCommitment
+    );
+    assert_eq!(report.commitment, Ok(()));
+    assert_eq!(report.proof, ProofVerdict::Valid);
+    assert!(!report.accepted_bad_proof());
+    assert_eq!(*verifier.given_system.lock().unwrap(), vec![true]);
+}
+
+#[tokio::test]
+/// Ensures a resolution the market shouldn't have accepted is flagged, whether its submission
+/// breaks the inputs commitment or carries a proof that doesn't verify.
+async fn should_flag_accepted_bad_proofs() {
+    let request = audited_request();
+    let intent_id = compute_request_id(&request.proof_request, &request.signature);
+
+    let tampered_journal = StubNode::start(
+        &request,
+        Some(resolve_input(
+            &request,
+            &VALID_SEAL,
+            B256::repeat_byte(0xff),
+        )),
+    )
+    .await;
+    let report = tampered_journal
+        .audit_client()
+        .with_verifier(SystemId::Risc0, Arc::new(SealVerifier::default()))
+        .audit_request(intent_id, SystemId::Risc0)
+        .await
+        .unwrap();
+    let reason = report.commitment.clone().unwrap_err();
+    assert!(reason.contains("inputs commitment"), "{reason}");
+    assert!(report.accepted_bad_proof());
+
+    let bad_seal = StubNode::start(
+        &request,
+        Some(resolve_input(&request, &[9, 9], JOURNAL_DIGEST)),
+    )
+    .await;
+    let verifier = Arc::new(SealVerifier::default());
+    let report = bad_seal
+        .audit_client()
+        .with_verifier(SystemId::Risc0, verifier.clone())
+        .audit_request(intent_id, SystemId::Risc0)
+        .await
+        .unwrap();
+    assert_eq!(report.commitment, Ok(()));
+    assert_eq!(report.proof, ProofVerdict::Invalid("bad seal".to_string()));
+    assert!(report.accepted_bad_proof());
+    // without the stored request, the verifier has no system params
+    assert_eq!(*verifier.given_system.lock().unwrap(), vec![false]);
+}
+
+#[tokio::test]
+/// Ensures proofs of systems without a local verifier are reported as unverifiable, and that
+/// submissions not decoding for their system are reported as invalid.
+async fn should_report_unverifiable_systems() {
+    let request = audited_request();
+    let intent_id = compute_request_id(&request.proof_request, &request.signature);
+    let node = StubNode::start(
+        &request,
+        Some(resolve_input(&request, &VALID_SEAL, JOURNAL_DIGEST)),
+    )
+    .await;
+
+    let report = node
+        .audit_client()
+        .audit_request(intent_id, SystemId::Risc0)
+        .await
+        .unwrap();
+    assert_eq!(report.commitment, Ok(()));
+    assert_eq!(
+        report.proof,
+        ProofVerdict::Unverifiable("no local verifier for risc0 proofs".to_string())
+    );
+    assert!(!report.accepted_bad_proof());
+
+    // a risc0 submission doesn't follow the arkworks layout
+    let report = node
+        .audit_client()
+        .audit_request(intent_id, SystemId::Arkworks)
+        .await
+        .unwrap();
+    assert!(
+        matches!(report.proof, ProofVerdict::Invalid(ref reason) if reason.contains("arkworks")),
+        "{:?}",
+        report.proof
+    );
+}
+
+#[tokio::test]
+/// Ensures auditing a request that was bid upon but never resolved fails.
+async fn should_fail_auditing_unresolved_request() {
+    let request = audited_request();
+    let node = StubNode::start(&request, None).await;
+
+    match node.audit_client().audit_stored_request(&request).await {
+        Err(ClientError::TrackIntentError(reason)) => {
+            assert!(reason.contains("was not resolved"), "{reason}")
+        }
+        other => panic!("expected an unresolved request error, got {other:?}"),
+    }
+}
@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use taralli_client::analyzer::IntentAnalyzer;
+use taralli_client::backtest::simulator::{
+    BacktestSimulator, BidImmediately, BidPolicy, SimulatedOutcome, TargetMargin,
+};
+use taralli_client::backtest::{Trace, TraceEntry};
+use taralli_client::error::{ClientError, Result};
+use taralli_client::searcher::request::FixedCost;
+use taralli_primitives::alloy::primitives::U256;
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::systems::SystemParams;
+
+/// four requests auctioned from 1000 to 1100, observed at 1000:
+/// 1. reward rising from 300 to 1300, won by a bid at 1030 for 600
+/// 2. fixed reward of 500, never bid upon
+/// 3. reward rising from 100 to 2100, won by a bid at 1080 for 1700
+/// 4. same curve as 1 with a 10 second proving time, won by a bid at 1050 for 800
+const TRACE: &str = include_str!("fixtures/backtest_trace.jsonl");
+
+/// Rejects requests leaving less than `min_proving_time` seconds to prove
+struct ProvingTimeAnalyzer {
+    min_proving_time: u32,
+}
+
+#[async_trait]
+impl IntentAnalyzer for ProvingTimeAnalyzer {
+    type Intent = ComputeRequest<SystemParams>;
+
+    async fn analyze(&self, _latest_ts: u64, intent: &Self::Intent) -> Result<()> {
+        if intent.proof_request.provingTime < self.min_proving_time {
+            return Err(ClientError::IntentAnalysisError(
+                "proving time too short".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Entry of the request observed `index`-th
+fn entry(trace: &Trace, index: usize) -> &TraceEntry {
+    trace.entries().nth(index).unwrap()
+}
+
+fn simulator(policy: Arc<dyn BidPolicy>) -> BacktestSimulator<ProvingTimeAnalyzer> {
+    BacktestSimulator::new(
+        ProvingTimeAnalyzer {
+            min_proving_time: 30,
+        },
+        policy,
+    )
+    .with_cost_model(Arc::new(FixedCost(U256::from(200))))
+    .with_reaction_time(2)
+}
+
+#[test]
+/// Ensures traces round trip through JSONL, merging repeated sightings of a request.
+fn should_round_trip_trace() {
+    let trace = Trace::from_jsonl(TRACE).unwrap();
+    assert_eq!(trace.len(), 4);
+
+    let mut reparsed = Trace::from_jsonl(&trace.to_jsonl().unwrap()).unwrap();
+    assert_eq!(reparsed.len(), 4);
+    assert_eq!(
+        entry(&reparsed, 2).request_id(),
+        entry(&trace, 2).request_id()
+    );
+    assert_eq!(
+        entry(&reparsed, 2).winning_bid,
+        entry(&trace, 2).winning_bid
+    );
+
+    // a later sighting neither duplicates the request nor delays it
+    let mut later = entry(&trace, 1).clone();
+    later.observed_at = 1050;
+    reparsed.insert(later);
+    assert_eq!(reparsed.len(), 4);
+    assert_eq!(entry(&reparsed, 1).observed_at, 1000);
+
+    // an earlier one moves it ahead of the requests observed in between
+    let mut earlier = entry(&trace, 2).clone();
+    earlier.observed_at = 0;
+    reparsed.insert(earlier);
+    assert_eq!(reparsed.len(), 4);
+    assert_eq!(
+        entry(&reparsed, 0).request_id(),
+        entry(&trace, 2).request_id()
+    );
+    assert!(reparsed
+        .entries()
+        .zip(reparsed.entries().skip(1))
+        .all(|(first, second)| first.observed_at <= second.observed_at));
+
+    let err = Trace::from_jsonl("{}\n").unwrap_err();
+    assert!(
+        err.to_string().contains("trace line 1"),
+        "unexpected error: {err}"
+    );
+}
+
+#[tokio::test]
+/// Ensures two strategies replayed over the same trace are compared on win rate, profit and
+/// missed opportunities.
+async fn should_compare_bidding_strategies() {
+    let trace = Trace::from_jsonl(TRACE).unwrap();
+
+    // bids once the minimum reward and the cost are covered: 1002, 1002 and 1005
+    let immediate = simulator(Arc::new(BidImmediately)).run(&trace).await;
+    assert_eq!(immediate.requests(), 4);
+    assert_eq!(immediate.wins(), 3);
+    assert_eq!(immediate.losses(), 0);
+    assert_eq!(immediate.skipped(), 1);
+    assert_eq!(immediate.win_rate(), 1.0);
+    // (320 - 200) + (500 - 200) + (200 - 200)
+    assert_eq!(immediate.expected_profit(), U256::from(420));
+    assert_eq!(immediate.missed_opportunities(), 1);
+    assert_eq!(
+        immediate.outcomes[0].outcome,
+        SimulatedOutcome::Won {
+            bid_timestamp: 1002,
+            reward: U256::from(320),
+            cost: U256::from(200),
+        }
+    );
+
+    // waits for a reward of 600, reached at 1030 and 1025, out of reach of the fixed reward
+    let patient = simulator(Arc::new(TargetMargin { margin_bps: 20_000 }))
+        .run(&trace)
+        .await;
+    assert_eq!(patient.wins(), 1);
+    assert_eq!(patient.losses(), 1);
+    assert_eq!(patient.skipped(), 2);
+    assert_eq!(patient.win_rate(), 0.5);
+    assert_eq!(patient.expected_profit(), U256::from(400));
+    assert_eq!(patient.missed_opportunities(), 3);
+    assert_eq!(
+        patient.outcomes[0].outcome,
+        SimulatedOutcome::Lost {
+            bid_timestamp: 1030,
+            winning_timestamp: 1030,
+        }
+    );
+
+    assert!(immediate.expected_profit() > patient.expected_profit());
+}
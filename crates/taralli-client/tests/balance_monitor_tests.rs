@@ -0,0 +1,237 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::json;
+use taralli_client::client::provider::balance::{BalanceHook, BalanceMonitor};
+use taralli_client::error::ClientError;
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::{address, Address, FixedBytes, B256, U256};
+use taralli_primitives::alloy::providers::RootProvider;
+use taralli_primitives::alloy::transports::http::{Client, Http};
+
+pub mod common;
+use common::rpc::{method_not_found, start_stub_node, stub_provider};
+
+const BIDDER: Address = address!("7777777777777777777777777777777777777777");
+const GAS_PRICE: u64 = 1_000_000_000;
+const BID_GAS_LIMIT: u64 = 100_000;
+const ETHER: u64 = 1_000_000_000_000_000_000;
+
+type StubMonitor = BalanceMonitor<Http<Client>, RootProvider<Http<Client>>, Ethereum>;
+
+/// Stub JSON-RPC node answering `eth_getBalance` with a balance the test can change, and
+/// `eth_gasPrice` with `GAS_PRICE`
+struct StubNode {
+    url: reqwest::Url,
+    balance: Arc<Mutex<U256>>,
+}
+
+impl StubNode {
+    async fn start(balance: U256) -> Self {
+        let balance = Arc::new(Mutex::new(balance));
+
+        let answered = balance.clone();
+        let url = start_stub_node(move |request| match request["method"].as_str().unwrap() {
+            "eth_getBalance" => {
+                json!({ "result": format!("{:#x}", *answered.lock().unwrap()) })
+            }
+            "eth_gasPrice" => json!({ "result": format!("{GAS_PRICE:#x}") }),
+            _ => method_not_found(),
+        })
+        .await;
+
+        Self { url, balance }
+    }
+
+    fn set_balance(&self, balance: U256) {
+        *self.balance.lock().unwrap() = balance;
+    }
+
+    fn monitor(&self, low_balance_threshold: U256) -> StubMonitor {
+        BalanceMonitor::new(
+            stub_provider(self.url.clone()),
+            BIDDER,
+            low_balance_threshold,
+        )
+        .with_bid_gas_limit(BID_GAS_LIMIT)
+    }
+}
+
+fn ether(amount: u64) -> U256 {
+    U256::from(amount) * U256::from(ETHER)
+}
+
+/// Hook recording the events it is called with
+#[derive(Default)]
+struct RecordingHook {
+    insufficient_funds: Mutex<Vec<(FixedBytes<32>, U256, U256)>>,
+    low_balances: Mutex<Vec<U256>>,
+}
+
+#[async_trait]
+impl BalanceHook for RecordingHook {
+    async fn on_insufficient_funds(
+        &self,
+        intent_id: FixedBytes<32>,
+        balance: U256,
+        required: U256,
+    ) {
+        self.insufficient_funds
+            .lock()
+            .unwrap()
+            .push((intent_id, balance, required));
+    }
+
+    async fn on_low_balance(&self, balance: U256, _threshold: U256) {
+        self.low_balances.lock().unwrap().push(balance);
+    }
+}
+
+#[tokio::test]
+/// Ensures a bid whose stake and gas exceed the balance is skipped with the shortfall reported,
+/// while one the balance covers goes through.
+async fn should_skip_bid_not_covered_by_balance() {
+    let node = StubNode::start(ether(1)).await;
+    let hook = Arc::new(RecordingHook::default());
+    let monitor = node
+        .monitor(U256::ZERO)
+        .with_buffer(U256::from(1_000))
+        .with_hook(hook.clone());
+    let intent_id = B256::repeat_byte(1);
+    let required = ether(1) + U256::from(GAS_PRICE * BID_GAS_LIMIT) + U256::from(1_000);
+
+    let err = monitor
+        .ensure_covers_bid(intent_id, ether(1))
+        .await
+        .unwrap_err();
+    match err {
+        ClientError::InsufficientFunds {
+            intent_id: skipped,
+            balance,
+            required: reported,
+        } => {
+            assert_eq!(skipped, intent_id);
+            assert_eq!(balance, ether(1));
+            assert_eq!(reported, required);
+        }
+        err => panic!("unexpected error: {err}"),
+    }
+    assert_eq!(
+        *hook.insufficient_funds.lock().unwrap(),
+        vec![(intent_id, ether(1), required)]
+    );
+
+    node.set_balance(ether(2));
+    monitor
+        .ensure_covers_bid(intent_id, ether(1))
+        .await
+        .unwrap();
+    assert_eq!(hook.insufficient_funds.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+/// Ensures listeners are warned once per drop below the threshold and the top-up is invoked with
+/// the balance found.
+async fn should_warn_and_top_up_below_threshold() {
+    let node = Arc::new(StubNode::start(ether(1)).await);
+    let hook = Arc::new(RecordingHook::default());
+    let top_ups = Arc::new(AtomicU32::new(0));
+
+    let topped_up = top_ups.clone();
+    let topped_up_node = node.clone();
+    let monitor = node
+        .monitor(ether(5))
+        .with_hook(hook.clone())
+        .with_top_up(move |balance| {
+            let topped_up = topped_up.clone();
+            let node = topped_up_node.clone();
+            async move {
+                assert_eq!(balance, ether(1));
+                topped_up.fetch_add(1, Ordering::Relaxed);
+                node.set_balance(ether(10));
+                Ok(())
+            }
+        });
+
+    let intent_id = B256::repeat_byte(1);
+    monitor
+        .ensure_covers_bid(intent_id, U256::from(1))
+        .await
+        .unwrap();
+    assert_eq!(*hook.low_balances.lock().unwrap(), vec![ether(1)]);
+    assert_eq!(top_ups.load(Ordering::Relaxed), 1);
+
+    // topped up above the threshold, nothing to report
+    monitor
+        .ensure_covers_bid(intent_id, U256::from(1))
+        .await
+        .unwrap();
+    assert_eq!(hook.low_balances.lock().unwrap().len(), 1);
+    assert_eq!(top_ups.load(Ordering::Relaxed), 1);
+
+    // a later drop is reported again
+    node.set_balance(ether(1));
+    monitor
+        .ensure_covers_bid(intent_id, U256::from(1))
+        .await
+        .unwrap();
+    assert_eq!(hook.low_balances.lock().unwrap().len(), 2);
+    assert_eq!(top_ups.load(Ordering::Relaxed), 2);
+}
+
+#[tokio::test]
+/// Ensures a balance staying below the threshold is topped up once, and again only after the
+/// cooldown, rather than on every observation while the top-up's transfer lands.
+async fn should_top_up_once_per_cooldown() {
+    let node = Arc::new(StubNode::start(ether(1)).await);
+    let hook = Arc::new(RecordingHook::default());
+    let top_ups = Arc::new(AtomicU32::new(0));
+
+    let topped_up = top_ups.clone();
+    let monitor = node
+        .monitor(ether(5))
+        .with_hook(hook.clone())
+        .with_top_up_cooldown(Duration::from_millis(200))
+        .with_top_up(move |_| {
+            let topped_up = topped_up.clone();
+            // the transfer hasn't landed by the next observations
+            async move {
+                topped_up.fetch_add(1, Ordering::Relaxed);
+                Ok(())
+            }
+        });
+
+    let intent_id = B256::repeat_byte(1);
+    for _ in 0..5 {
+        monitor
+            .ensure_covers_bid(intent_id, U256::from(1))
+            .await
+            .unwrap();
+    }
+    assert_eq!(top_ups.load(Ordering::Relaxed), 1);
+    assert_eq!(hook.low_balances.lock().unwrap().len(), 1);
+
+    // still low once the cooldown passed
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    monitor
+        .ensure_covers_bid(intent_id, U256::from(1))
+        .await
+        .unwrap();
+    assert_eq!(top_ups.load(Ordering::Relaxed), 2);
+    assert_eq!(hook.low_balances.lock().unwrap().len(), 1);
+
+    // back above the threshold, the next drop is topped up right away
+    node.set_balance(ether(10));
+    monitor
+        .ensure_covers_bid(intent_id, U256::from(1))
+        .await
+        .unwrap();
+    node.set_balance(ether(1));
+    monitor
+        .ensure_covers_bid(intent_id, U256::from(1))
+        .await
+        .unwrap();
+    assert_eq!(top_ups.load(Ordering::Relaxed), 3);
+}
@@ -0,0 +1,259 @@
+use serde_json::{json, Value};
+use taralli_client::bid_store::{replay_decision, BidAttempt, BidStage, BidStore, ReplayDecision};
+use taralli_client::market_state::{AuctionState, MarketStateClient};
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::{Address, Bytes, B256, U256};
+use taralli_primitives::alloy::sol_types::SolValue;
+use taralli_primitives::alloy::transports::http::{Client, Http};
+use taralli_primitives::alloy::utils::hex;
+
+pub mod common;
+use common::rpc::{start_stub_node, stub_provider, StubProvider};
+
+const MARKET: Address = Address::repeat_byte(0x0c);
+const REQUESTER: Address = Address::repeat_byte(0x0a);
+const PROVIDER: Address = Address::repeat_byte(0x0b);
+const OTHER_PROVIDER: Address = Address::repeat_byte(0x0e);
+const INTENT_ID: B256 = B256::repeat_byte(0x11);
+const END_AUCTION: u64 = 1_000;
+
+type StateClient = MarketStateClient<Http<Client>, StubProvider, Ethereum>;
+
+/// Stub JSON-RPC node of a market whose active data of every request was bid upon by `provider`,
+/// unbid for the zero address, none of them being resolved
+async fn start_node(provider: Address) -> StateClient {
+    let requester = if provider == Address::ZERO {
+        Address::ZERO
+    } else {
+        REQUESTER
+    };
+    let active = active_request_data(requester, provider);
+    let url = start_stub_node(move |request| {
+        let result = match request["method"].as_str().unwrap() {
+            "eth_chainId" => json!("0x1"),
+            "eth_call" => active.clone(),
+            "eth_getLogs" => json!([]),
+            method => panic!("unexpected method {method}"),
+        };
+        json!({ "result": result })
+    })
+    .await;
+    MarketStateClient::new(stub_provider(url), MARKET)
+}
+
+/// `activeProofRequestData` of a request bid upon by `provider`
+fn active_request_data(requester: Address, provider: Address) -> Value {
+    json!(hex::encode_prefixed(
+        (
+            requester,
+            provider,
+            U256::from(2_000_000_000u64),
+            Address::repeat_byte(0x0d),
+            U256::from(1000),
+            U256::from(7),
+            B256::ZERO,
+            Bytes::new(),
+        )
+            .abi_encode_params()
+    ))
+}
+
+fn store_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!(
+        "taralli-bid-store-{name}-{}.json",
+        std::process::id()
+    ))
+}
+
+fn attempt(stage: BidStage) -> BidAttempt {
+    BidAttempt {
+        intent_id: INTENT_ID,
+        stage,
+        updated_at: 0,
+    }
+}
+
+#[test]
+/// Ensures replayed requests are bid upon only while open, resumed when won by the provider and
+/// dropped otherwise.
+fn should_decide_replays_from_market_state() {
+    let bid_by = |prover| AuctionState::Bid {
+        bidder: prover,
+        prover,
+        amount: U256::from(1000),
+        at_block: Some(12),
+    };
+    let attempted = attempt(BidStage::Attempted);
+    let won = attempt(BidStage::Won);
+    let resolved = attempt(BidStage::Resolved);
+
+    // bid never landed before the restart, the auction still open
+    assert_eq!(
+        replay_decision(Some(&attempted), AuctionState::Open, PROVIDER),
+        ReplayDecision::Bid
+    );
+    assert_eq!(
+        replay_decision(None, AuctionState::Open, PROVIDER),
+        ReplayDecision::Bid
+    );
+    // bid landed, whether its receipt was recorded before the restart or not
+    for previous in [&attempted, &won] {
+        assert_eq!(
+            replay_decision(Some(previous), bid_by(PROVIDER), PROVIDER),
+            ReplayDecision::Resume
+        );
+        assert!(matches!(
+            replay_decision(Some(previous), bid_by(OTHER_PROVIDER), PROVIDER),
+            ReplayDecision::Drop(_)
+        ));
+        for state in [AuctionState::Resolved, AuctionState::Expired] {
+            assert!(matches!(
+                replay_decision(Some(previous), state, PROVIDER),
+                ReplayDecision::Drop(_)
+            ));
+        }
+    }
+    // resolved by the provider, whatever the market still holds
+    assert!(matches!(
+        replay_decision(Some(&resolved), bid_by(PROVIDER), PROVIDER),
+        ReplayDecision::Drop(_)
+    ));
+}
+
+#[tokio::test]
+/// Ensures bid attempts survive a restart, each stage being persisted as it's reached.
+async fn should_persist_bid_stages_across_restarts() {
+    let path = store_path("stages");
+    let _ = std::fs::remove_file(&path);
+
+    let store = BidStore::load(&path).unwrap();
+    assert_eq!(store.attempt(INTENT_ID), None);
+    store.record_attempt(INTENT_ID).await;
+    drop(store);
+
+    let store = BidStore::load(&path).unwrap();
+    assert_eq!(store.attempt(INTENT_ID).unwrap().stage, BidStage::Attempted);
+    store.record_won(INTENT_ID).await;
+    drop(store);
+
+    let store = BidStore::load(&path).unwrap();
+    assert_eq!(store.attempt(INTENT_ID).unwrap().stage, BidStage::Won);
+    store.record_resolved(INTENT_ID).await;
+    drop(store);
+
+    let store = BidStore::load(&path).unwrap();
+    assert_eq!(store.attempt(INTENT_ID).unwrap().stage, BidStage::Resolved);
+    assert_eq!(store.attempt(B256::repeat_byte(0x22)), None);
+
+    // attempts past the retention are dropped on load
+    std::fs::write(
+        &path,
+        serde_json::to_string(&[attempt(BidStage::Won)]).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(BidStore::load(&path).unwrap().attempt(INTENT_ID), None);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+/// Ensures a request replayed after a restart between bid and resolve is resumed when the market
+/// recorded the provider's bid, dropped when it recorded someone else's and bid upon again when
+/// the bid never landed.
+async fn should_resume_own_bid_after_restart() {
+    let path = store_path("restart");
+    let _ = std::fs::remove_file(&path);
+    // bid sent, then the process crashed before its receipt came back
+    BidStore::load(&path)
+        .unwrap()
+        .record_attempt(INTENT_ID)
+        .await;
+
+    let store = BidStore::load(&path).unwrap();
+    let previous = store.attempt(INTENT_ID);
+    assert!(previous.is_some());
+    for (bidder, expected) in [
+        (PROVIDER, Some(ReplayDecision::Resume)),
+        (OTHER_PROVIDER, None),
+        (Address::ZERO, Some(ReplayDecision::Bid)),
+    ] {
+        let state = start_node(bidder)
+            .await
+            .request_state(INTENT_ID, END_AUCTION, END_AUCTION - 10)
+            .await
+            .unwrap();
+        let decision = replay_decision(previous.as_ref(), state, PROVIDER);
+        match expected {
+            Some(expected) => assert_eq!(decision, expected),
+            None => assert!(matches!(decision, ReplayDecision::Drop(_)), "{decision:?}"),
+        }
+    }
+
+    // once resolved, a request replayed again is dropped without bidding
+    store.record_resolved(INTENT_ID).await;
+    let store = BidStore::load(&path).unwrap();
+    let state = start_node(PROVIDER)
+        .await
+        .request_state(INTENT_ID, END_AUCTION, END_AUCTION - 10)
+        .await
+        .unwrap();
+    assert!(matches!(
+        replay_decision(store.attempt(INTENT_ID).as_ref(), state, PROVIDER),
+        ReplayDecision::Drop(_)
+    ));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+/// Ensures a provider stopped right after reaching any stage of a bid resumes from it: a bid
+/// attempted or won is proven without bidding again, a request resolved is dropped. Stages are
+/// written whole, no temporary file being left behind.
+async fn should_resume_from_every_stage() {
+    let stages = [BidStage::Attempted, BidStage::Won, BidStage::Resolved];
+    for (reached, stage) in stages.into_iter().enumerate() {
+        let path = store_path(&format!("resume-{reached}"));
+        let _ = std::fs::remove_file(&path);
+        let store = BidStore::load(&path).unwrap();
+        for stage in &stages[..=reached] {
+            match stage {
+                BidStage::Attempted => store.record_attempt(INTENT_ID).await,
+                BidStage::Won => store.record_won(INTENT_ID).await,
+                BidStage::Resolved => store.record_resolved(INTENT_ID).await,
+            }
+        }
+        // stopped there
+        drop(store);
+
+        let mut tmp_path = path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        assert!(!std::path::Path::new(&tmp_path).exists());
+        let store = BidStore::load(&path).unwrap();
+        let previous = store.attempt(INTENT_ID).unwrap();
+        assert_eq!(previous.stage, stage);
+        let state = start_node(PROVIDER)
+            .await
+            .request_state(INTENT_ID, END_AUCTION, END_AUCTION - 10)
+            .await
+            .unwrap();
+        let decision = replay_decision(Some(&previous), state, PROVIDER);
+        match stage {
+            BidStage::Attempted | BidStage::Won => assert_eq!(decision, ReplayDecision::Resume),
+            BidStage::Resolved => {
+                assert!(matches!(decision, ReplayDecision::Drop(_)), "{decision:?}")
+            }
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
+
+#[test]
+/// Ensures a request is processed once at a time, a copy received while it's being proven not
+/// being resumed from its won bid.
+fn should_process_one_copy_of_a_request_at_a_time() {
+    let store = BidStore::new();
+    let claim = store.claim(INTENT_ID).unwrap();
+    assert!(store.claim(INTENT_ID).is_none());
+    // other requests are processed meanwhile
+    assert!(store.claim(B256::repeat_byte(0x22)).is_some());
+    drop(claim);
+    assert!(store.claim(INTENT_ID).is_some());
+}
@@ -0,0 +1,221 @@
+use serde_json::{json, Value};
+use taralli_client::error::ClientError;
+use taralli_client::intent_builder::offer::ComputeOfferBuilder;
+use taralli_client::intent_builder::request::ComputeRequestBuilder;
+use taralli_primitives::abi::universal_bombetta::VerifierDetails;
+use taralli_primitives::abi::universal_porchetta::ProofOfferVerifierDetails;
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::{address, fixed_bytes, Address, B256, U256};
+use taralli_primitives::alloy::sol_types::SolValue;
+use taralli_primitives::alloy::transports::http::{Client, Http};
+use taralli_primitives::markets::{
+    SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS, SEPOLIA_UNIVERSAL_PORCHETTA_ADDRESS,
+};
+use taralli_primitives::systems::SystemId;
+use taralli_primitives::validation::offer::{OfferValidationConfig, OfferVerifierConstraints};
+use taralli_primitives::validation::request::{
+    RequestValidationConfig, RequestVerifierConstraints,
+};
+use taralli_primitives::validation::BaseValidationConfig;
+
+pub mod common;
+use common::rpc::{block_json, method_not_found, start_stub_node, stub_provider, StubProvider};
+
+const SIGNER_ADDRESS: Address = address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+const VERIFIER: Address = address!("AC292cF957Dd5BA174cdA13b05C16aFC71700327");
+const LATEST_TIMESTAMP: u64 = 1_000;
+
+/// Start a stub JSON-RPC node whose latest block has the timestamp `LATEST_TIMESTAMP`
+async fn start_block_node() -> reqwest::Url {
+    start_stub_node(|request| match request["method"].as_str().unwrap() {
+        "eth_getBlockByNumber" => json!({ "result": block_json(LATEST_TIMESTAMP) }),
+        _ => method_not_found(),
+    })
+    .await
+}
+
+fn risc0_system() -> Value {
+    json!({ "elf": [1, 2, 3], "inputs": vec![0u8; 32] })
+}
+
+/// Request builder passing the default validation, at `LATEST_TIMESTAMP`
+fn request_builder(
+    url: reqwest::Url,
+) -> ComputeRequestBuilder<Http<Client>, StubProvider, Ethereum> {
+    let extra_data = VerifierDetails {
+        verifier: VERIFIER,
+        selector: fixed_bytes!("ab750e75"),
+        isShaCommitment: false,
+        inputsOffset: U256::from(32),
+        inputsLength: U256::from(64),
+        hasPartialCommitmentResultCheck: false,
+        submittedPartialCommitmentResultOffset: U256::ZERO,
+        submittedPartialCommitmentResultLength: U256::ZERO,
+        predeterminedPartialCommitment: B256::ZERO,
+    }
+    .abi_encode();
+
+    ComputeRequestBuilder::new(
+        stub_provider(url),
+        SIGNER_ADDRESS,
+        SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+        SystemId::Risc0,
+    )
+    .system(risc0_system())
+    .nonce(U256::from(7))
+    .set_time_params(LATEST_TIMESTAMP, LATEST_TIMESTAMP + 60, 120)
+    .set_token_params(1, U256::from(10), U256::from(1001))
+    .set_verification_commitment_params(B256::ZERO, extra_data.into())
+}
+
+fn request_config() -> RequestValidationConfig {
+    RequestValidationConfig {
+        base: BaseValidationConfig::default(),
+        maximum_allowed_stake: 1_000,
+        ..Default::default()
+    }
+}
+
+fn violations(result: taralli_client::error::Result<()>) -> Vec<String> {
+    match result {
+        Err(ClientError::ValidationViolations(violations)) => violations,
+        other => panic!("expected validation violations, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+/// Ensures a request passing every check is let through unsigned.
+async fn should_accept_valid_unsigned_request() {
+    let url = start_block_node().await;
+    request_builder(url)
+        .validate_against(&request_config(), &RequestVerifierConstraints::default())
+        .await
+        .expect("request should pass validation");
+}
+
+#[tokio::test]
+/// Ensures every violation of a request is reported at once rather than the first one only.
+async fn should_aggregate_request_violations() {
+    let url = start_block_node().await;
+    let builder = request_builder(url)
+        // below the minimum proving time
+        .proving_time(10)
+        // above the maximum allowed stake
+        .minimum_stake(1_001);
+    let config = RequestValidationConfig {
+        base: BaseValidationConfig {
+            supported_systems: vec![SystemId::Sp1],
+            ..Default::default()
+        },
+        maximum_allowed_stake: 1_000,
+        ..Default::default()
+    };
+    let constraints = RequestVerifierConstraints {
+        verifier: Some(address!("0000000000000000000000000000000000000042")),
+        ..Default::default()
+    };
+
+    let violations = violations(builder.validate_against(&config, &constraints).await);
+    assert_eq!(
+        violations,
+        vec![
+            "unsupported system",
+            "proving time too low",
+            "eth stake amount invalid",
+            "verifier address does not match constraints",
+        ]
+    );
+}
+
+#[tokio::test]
+/// Ensures the auction window is checked against the latest block of the rpc provider.
+async fn should_check_auction_window_against_latest_block() {
+    let url = start_block_node().await;
+    // the auction already ended at the latest block
+    let builder =
+        request_builder(url).set_time_params(LATEST_TIMESTAMP - 120, LATEST_TIMESTAMP - 60, 120);
+
+    let violations = violations(
+        builder
+            .validate_against(&request_config(), &RequestVerifierConstraints::default())
+            .await,
+    );
+    assert_eq!(violations, vec!["invalid timestamp"]);
+}
+
+#[tokio::test]
+/// Ensures requests are accepted up to the maximum auction length and proving time, and turned
+/// down one second past either, as the server would.
+async fn should_bound_auction_length_and_proving_time() {
+    let url = start_block_node().await;
+    let base = BaseValidationConfig::default();
+    let (max_auction, max_proving) = (base.maximum_auction_length, base.maximum_proving_time);
+    let validate = |auction_length: u32, proving_time: u32| {
+        let builder = request_builder(url.clone()).set_time_params(
+            LATEST_TIMESTAMP,
+            LATEST_TIMESTAMP + u64::from(auction_length),
+            proving_time,
+        );
+        async move {
+            builder
+                .validate_against(&request_config(), &RequestVerifierConstraints::default())
+                .await
+        }
+    };
+
+    validate(max_auction, max_proving)
+        .await
+        .expect("request at the maximum bounds should pass validation");
+    assert_eq!(
+        violations(validate(max_auction, max_proving + 1).await),
+        vec!["proving time too high"]
+    );
+    assert_eq!(
+        violations(validate(max_auction + 1, max_proving).await),
+        vec![format!(
+            "auction length {}s is outside of [{}s, {max_auction}s]",
+            max_auction + 1,
+            base.minimum_auction_length
+        )]
+    );
+}
+
+#[tokio::test]
+/// Ensures offers are validated with the offer validator, reporting every violation.
+async fn should_aggregate_offer_violations() {
+    let url = start_block_node().await;
+    let extra_data = ProofOfferVerifierDetails {
+        verifier: VERIFIER,
+        selector: fixed_bytes!("ab750e75"),
+        isShaCommitment: false,
+        inputsOffset: U256::ZERO,
+        inputsLength: U256::ZERO,
+    }
+    .abi_encode();
+    let builder = ComputeOfferBuilder::new(
+        stub_provider(url),
+        SIGNER_ADDRESS,
+        SEPOLIA_UNIVERSAL_PORCHETTA_ADDRESS,
+        SystemId::Risc0,
+    )
+    .system(risc0_system())
+    .set_time_params(LATEST_TIMESTAMP, LATEST_TIMESTAMP + 60, 10)
+    // a reward above the maximum allowed
+    .set_token_params(U256::from(2_000), Address::ZERO, 18, U256::from(10))
+    .set_verification_commitment_params(B256::ZERO, extra_data.into());
+    let config = OfferValidationConfig {
+        base: BaseValidationConfig::default(),
+        maximum_allowed_reward: U256::from(1_000),
+        minimum_allowed_stake: U256::from(10),
+    };
+
+    let violations = violations(
+        builder
+            .validate_against(&config, &OfferVerifierConstraints::default())
+            .await,
+    );
+    assert_eq!(
+        violations,
+        vec!["proving time too low", "token reward amount invalid"]
+    );
+}
@@ -0,0 +1,220 @@
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::json;
+use taralli_client::api::retry::RetryPolicy;
+use taralli_client::bidder::request::{
+    target_bid_timestamp, BidMode, ComputeRequestBidParams, ComputeRequestBidder,
+    BID_INCLUSION_MARGIN_SECS,
+};
+use taralli_client::clock::{ChainClock, ManualClock};
+use taralli_client::error::ClientError;
+use taralli_client::rpc::RpcErrorKind;
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta::ProofRequest;
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::{Address, B256, U256};
+use taralli_primitives::alloy::transports::http::{Client, Http};
+use url::Url;
+
+pub mod common;
+use common::rpc::{
+    block_json, method_not_found, start_stub_node, stub_provider, unreachable_url, StubProvider,
+};
+
+type StubClock = ChainClock<Http<Client>, StubProvider, Ethereum>;
+
+/// Stub JSON-RPC node whose latest block has the timestamp held by `timestamp`, counting the
+/// blocks fetched in `fetches`
+async fn start_clock_node(timestamp: Arc<AtomicU64>, fetches: Arc<AtomicUsize>) -> Url {
+    start_stub_node(move |request| match request["method"].as_str().unwrap() {
+        "eth_getBlockByNumber" => {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            json!({ "result": block_json(timestamp.load(Ordering::SeqCst)) })
+        }
+        _ => method_not_found(),
+    })
+    .await
+}
+
+fn proof_request_fixture(min_reward: u64, max_reward: u64, start: u64, end: u64) -> ProofRequest {
+    ProofRequest {
+        signer: Address::ZERO,
+        market: Address::ZERO,
+        nonce: U256::ZERO,
+        rewardToken: Address::ZERO,
+        maxRewardAmount: U256::from(max_reward),
+        minRewardAmount: U256::from(min_reward),
+        minimumStake: 1,
+        startAuctionTimestamp: start,
+        endAuctionTimestamp: end,
+        provingTime: 30,
+        inputsCommitment: B256::ZERO,
+        extraData: vec![].into(),
+    }
+}
+
+#[tokio::test]
+/// Ensures the latest block timestamp is cached until stale, shared by clones of the clock and
+/// fetched again on refresh.
+async fn should_refresh_stale_block_timestamp() {
+    let timestamp = Arc::new(AtomicU64::new(1_000));
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let url = start_clock_node(timestamp.clone(), fetches.clone()).await;
+    let provider = stub_provider(url);
+
+    let clock: StubClock =
+        ChainClock::new(provider.clone()).with_max_staleness(Duration::from_secs(3_600));
+    assert_eq!(clock.drift(), None);
+    assert_eq!(clock.now().await.unwrap(), 1_000);
+    assert_eq!(fetches.load(Ordering::SeqCst), 1);
+    // blocks stamped long before the local clock
+    assert!(clock.drift().unwrap() < 0);
+
+    // a new block isn't seen until the cached timestamp is stale, by any clone of the clock
+    timestamp.store(2_000, Ordering::SeqCst);
+    assert!(clock.now().await.unwrap() < 2_000);
+    assert!(clock.clone().now().await.unwrap() < 2_000);
+    assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+    assert_eq!(clock.refresh().await.unwrap(), 2_000);
+    assert_eq!(fetches.load(Ordering::SeqCst), 2);
+    assert!(clock.now().await.unwrap() >= 2_000);
+    assert_eq!(fetches.load(Ordering::SeqCst), 2);
+
+    // an always stale cache fetches the latest block every time
+    let clock: StubClock = ChainClock::new(provider).with_max_staleness(Duration::ZERO);
+    timestamp.store(3_000, Ordering::SeqCst);
+    assert_eq!(clock.now().await.unwrap(), 3_000);
+    timestamp.store(3_012, Ordering::SeqCst);
+    assert_eq!(clock.now().await.unwrap(), 3_012);
+    assert_eq!(fetches.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+/// Ensures a node not serving the latest block yet is asked again, and that the failure is
+/// classified once the retries run out.
+async fn should_retry_missing_latest_block() {
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let counted = fetches.clone();
+    let url = start_stub_node(move |request| match request["method"].as_str().unwrap() {
+        "eth_getBlockByNumber" => match counted.fetch_add(1, Ordering::SeqCst) {
+            0 => json!({ "result": null }),
+            _ => json!({ "result": block_json(1_000) }),
+        },
+        _ => method_not_found(),
+    })
+    .await;
+    let rpc_retry = RetryPolicy {
+        max_retries: 1,
+        initial_backoff: Duration::from_millis(10),
+        ..Default::default()
+    };
+
+    let clock: StubClock = ChainClock::new(stub_provider(url)).with_rpc_retry(rpc_retry.clone());
+    assert_eq!(clock.refresh().await.unwrap(), 1_000);
+    assert_eq!(fetches.load(Ordering::SeqCst), 2);
+
+    let clock: StubClock =
+        ChainClock::new(stub_provider(unreachable_url().await)).with_rpc_retry(rpc_retry);
+    let err = clock.refresh().await.unwrap_err();
+    assert_eq!(err.rpc_error_kind(), Some(RpcErrorKind::Transient), "{err}");
+}
+
+#[tokio::test]
+/// Ensures the bidder waits for the bid timestamp against its clock, without any rpc request:
+/// the wait stays pending while the clock is short of it and completes once the clock reaches it.
+async fn should_wait_for_bid_timestamp_on_manual_clock() {
+    let manual = ManualClock::new(1_000);
+    let provider: StubProvider = stub_provider(unreachable_url().await);
+    let bidder: ComputeRequestBidder<Http<Client>, StubProvider, Ethereum> =
+        ComputeRequestBidder::new(provider, Address::ZERO)
+            .with_clock(ChainClock::manual(manual.clone()));
+
+    // reward rising from 100 to 500, the target amount of 300 is reached mid auction
+    let proof_request = proof_request_fixture(100, 500, 1_000, 1_100);
+    let bid_params = ComputeRequestBidParams {
+        target_amount: U256::from(300),
+        mode: BidMode::Immediate,
+    };
+    let bid_timestamp =
+        target_bid_timestamp(&proof_request, 1_000, bid_params.target_amount).unwrap();
+    assert!(bid_timestamp > 1_001);
+
+    let mut wait = tokio::spawn(async move {
+        bidder
+            .wait_for_bid_timestamp(1_000, &proof_request, &bid_params)
+            .await
+    });
+
+    manual.set(bid_timestamp - 1);
+    assert!(tokio::time::timeout(Duration::from_millis(100), &mut wait)
+        .await
+        .is_err());
+
+    manual.advance(1);
+    let waited = tokio::time::timeout(Duration::from_secs(5), wait)
+        .await
+        .expect("wait not woken by the clock")
+        .unwrap()
+        .unwrap();
+    assert_eq!(waited, bid_timestamp);
+    assert_eq!(manual.now(), bid_timestamp);
+}
+
+#[tokio::test]
+/// Ensures a manual clock never goes backwards.
+async fn should_keep_manual_clock_monotonic() {
+    let manual = ManualClock::new(1_000);
+    let clock: StubClock = ChainClock::manual(manual.clone());
+    manual.set(900);
+    assert_eq!(clock.now().await.unwrap(), 1_000);
+    manual.advance(5);
+    assert_eq!(clock.now().await.unwrap(), 1_005);
+    assert_eq!(clock.sleep_until(1_002, u64::MAX).await.unwrap(), 1_005);
+}
+
+#[tokio::test]
+/// Ensures the wait for a bid gives up once the chain time gets within the inclusion margin of
+/// the end of the auction before the bid timestamp, e.g. when blocks jump ahead.
+async fn should_give_up_bid_wait_before_auction_end() {
+    let manual = ManualClock::new(1_000);
+    let provider: StubProvider = stub_provider(unreachable_url().await);
+    let bidder: ComputeRequestBidder<Http<Client>, StubProvider, Ethereum> =
+        ComputeRequestBidder::new(provider, Address::ZERO)
+            .with_clock(ChainClock::manual(manual.clone()));
+
+    let proof_request = proof_request_fixture(100, 500, 1_000, 1_100);
+    let bid_params = ComputeRequestBidParams {
+        target_amount: U256::from(300),
+        mode: BidMode::Immediate,
+    };
+    let bid_timestamp =
+        target_bid_timestamp(&proof_request, 1_000, bid_params.target_amount).unwrap();
+    let wait = tokio::spawn(async move {
+        bidder
+            .wait_for_bid_timestamp(1_000, &proof_request, &bid_params)
+            .await
+    });
+
+    // a block stamped within the margin of the end, past the bid timestamp
+    let deadline = 1_100 - BID_INCLUSION_MARGIN_SECS;
+    manual.set(deadline);
+    let err = tokio::time::timeout(Duration::from_secs(5), wait)
+        .await
+        .expect("wait not woken by the clock")
+        .unwrap()
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ClientError::WaitDeadlinePassed { timestamp, deadline: passed, chain_time }
+                if timestamp == bid_timestamp && passed == deadline && chain_time == deadline
+        ),
+        "{err}"
+    );
+
+    // a bid timestamp within the margin is never waited for
+    let clock: StubClock = ChainClock::manual(ManualClock::new(1_000));
+    assert!(clock.sleep_until(1_099, deadline).await.is_err());
+}
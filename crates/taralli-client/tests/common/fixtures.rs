@@ -0,0 +1,88 @@
+use rstest::*;
+use serde_json::{json, Value};
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta::ProofRequest;
+use taralli_primitives::alloy::primitives::{Address, PrimitiveSignature, B256, U256};
+use taralli_primitives::alloy::rpc::types::TransactionReceipt;
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::systems::risc0::Risc0ProofParams;
+use taralli_primitives::systems::{SystemId, SystemParams};
+
+#[fixture]
+/// Risc0 request of 32 bytes of inputs, auctioned from 100 to 200 and proved within 60 seconds,
+/// which tests adjust to their case
+pub fn request_fixture() -> ComputeRequest<SystemParams> {
+    ComputeRequest {
+        system_id: SystemId::Risc0,
+        system: SystemParams::try_from((
+            &SystemId::Risc0,
+            serde_json::to_vec(&Risc0ProofParams {
+                elf: vec![1, 2, 3],
+                inputs: vec![4; 32],
+            })
+            .unwrap(),
+        ))
+        .unwrap(),
+        proof_request: ProofRequest {
+            signer: Address::ZERO,
+            market: Address::ZERO,
+            nonce: U256::from(1),
+            rewardToken: Address::ZERO,
+            maxRewardAmount: U256::from(200),
+            minRewardAmount: U256::from(100),
+            minimumStake: 0,
+            startAuctionTimestamp: 100,
+            endAuctionTimestamp: 200,
+            provingTime: 60,
+            inputsCommitment: B256::repeat_byte(4),
+            extraData: vec![].into(),
+        },
+        signature: PrimitiveSignature::new(U256::from(1), U256::from(2), false),
+    }
+}
+
+/// `request_fixture` with the given nonce, and inputs of 32 `inputs` bytes committed to as such
+pub fn request_with_inputs(nonce: u64, inputs: u8) -> ComputeRequest<SystemParams> {
+    let mut request = request_fixture();
+    request.system = SystemParams::try_from((
+        &SystemId::Risc0,
+        serde_json::to_vec(&Risc0ProofParams {
+            elf: vec![1, 2, 3],
+            inputs: vec![inputs; 32],
+        })
+        .unwrap(),
+    ))
+    .unwrap();
+    request.proof_request.nonce = U256::from(nonce);
+    request.proof_request.inputsCommitment = B256::repeat_byte(inputs);
+    request
+}
+
+/// Receipt of transaction `transaction_hash` as served by `eth_getTransactionReceipt`, included
+/// in `block_number` and succeeding as `status` says
+pub fn receipt_json(transaction_hash: B256, status: bool, block_number: u64) -> Value {
+    json!({
+        "type": "0x2",
+        "status": if status { "0x1" } else { "0x0" },
+        "cumulativeGasUsed": "0x5208",
+        "logs": [],
+        "logsBloom": format!("0x{}", "00".repeat(256)),
+        "transactionHash": transaction_hash,
+        "transactionIndex": "0x0",
+        "blockHash": B256::repeat_byte(0x01),
+        "blockNumber": format!("{block_number:#x}"),
+        "gasUsed": "0x5208",
+        "effectiveGasPrice": "0x1",
+        "from": "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266",
+        "to": "0x6209431b6c8f38471dc65564be2fd08298705bbd",
+        "contractAddress": null
+    })
+}
+
+#[fixture]
+/// Receipt of a succeeding transaction `transaction_hash` included in block 1
+pub fn receipt_fixture(
+    #[default(B256::repeat_byte(0xaa))] transaction_hash: B256,
+) -> TransactionReceipt {
+    serde_json::from_value(receipt_json(transaction_hash, true, 1))
+        .expect("Couldn't build receipt fixture")
+}
@@ -0,0 +1,191 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use taralli_client::confirmations::Confirmations;
+use taralli_client::error::ClientError;
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::B256;
+use taralli_primitives::alloy::rpc::types::TransactionReceipt;
+
+pub mod common;
+use common::fixtures::receipt_json;
+use common::rpc::{method_not_found, start_stub_node, stub_provider, StubProvider};
+
+const TX_HASH: B256 = B256::repeat_byte(0x42);
+const INCLUSION_BLOCK: u64 = 10;
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// What happens to the transaction once the chain reaches a block
+#[derive(Clone, Copy)]
+enum Reorg {
+    /// the transaction is dropped
+    Drop,
+    /// the transaction is included again but reverts
+    Revert,
+}
+
+struct ChainState {
+    head: u64,
+    receipt: Option<Value>,
+    reorg_at: Option<(u64, Reorg)>,
+    calls: u32,
+}
+
+/// Stub JSON-RPC node whose chain advances one block per `eth_blockNumber` call, answering
+/// `eth_getTransactionReceipt` for `TX_HASH` until a reorg drops or reverts it
+struct StubNode {
+    url: reqwest::Url,
+    state: Arc<Mutex<ChainState>>,
+}
+
+impl StubNode {
+    async fn start(reorg_at: Option<(u64, Reorg)>) -> Self {
+        let state = Arc::new(Mutex::new(ChainState {
+            head: INCLUSION_BLOCK,
+            receipt: Some(receipt_json(TX_HASH, true, INCLUSION_BLOCK)),
+            reorg_at,
+            calls: 0,
+        }));
+
+        let chain = state.clone();
+        let url = start_stub_node(move |request| {
+            let mut chain = chain.lock().unwrap();
+            chain.calls += 1;
+            match request["method"].as_str().unwrap() {
+                "eth_blockNumber" => {
+                    chain.head += 1;
+                    if let Some((block, reorg)) = chain.reorg_at {
+                        if chain.head >= block {
+                            chain.receipt = match reorg {
+                                Reorg::Drop => None,
+                                Reorg::Revert => {
+                                    Some(receipt_json(TX_HASH, false, INCLUSION_BLOCK))
+                                }
+                            };
+                        }
+                    }
+                    json!({ "result": format!("{:#x}", chain.head) })
+                }
+                "eth_getTransactionReceipt" => {
+                    json!({ "result": chain.receipt.clone().unwrap_or(Value::Null) })
+                }
+                _ => method_not_found(),
+            }
+        })
+        .await;
+
+        Self { url, state }
+    }
+
+    fn provider(&self) -> StubProvider {
+        stub_provider(self.url.clone())
+    }
+
+    fn head(&self) -> u64 {
+        self.state.lock().unwrap().head
+    }
+
+    fn calls(&self) -> u32 {
+        self.state.lock().unwrap().calls
+    }
+}
+
+fn confirmations(depth: u64) -> Confirmations {
+    Confirmations::new(depth).with_poll_interval(POLL_INTERVAL)
+}
+
+fn included_receipt() -> TransactionReceipt {
+    serde_json::from_value(receipt_json(TX_HASH, true, INCLUSION_BLOCK)).unwrap()
+}
+
+#[tokio::test]
+/// Ensures receipts are trusted as is at the default depth, without asking the node again.
+async fn should_trust_receipts_at_default_depth() {
+    let node = StubNode::start(Some((INCLUSION_BLOCK + 1, Reorg::Drop))).await;
+
+    let receipt = Confirmations::default()
+        .confirm_receipt::<_, _, Ethereum>(&node.provider(), included_receipt())
+        .await
+        .unwrap();
+    assert_eq!(receipt.transaction_hash, TX_HASH);
+    assert_eq!(node.calls(), 0);
+}
+
+#[tokio::test]
+/// Ensures receipts are only returned once their block is as deep as configured.
+async fn should_wait_for_confirmation_depth() {
+    let node = StubNode::start(None).await;
+
+    let receipt = confirmations(5)
+        .confirm_receipt::<_, _, Ethereum>(&node.provider(), included_receipt())
+        .await
+        .unwrap();
+    assert_eq!(receipt.transaction_hash, TX_HASH);
+    // the inclusion block counts as the first confirmation
+    assert_eq!(node.head(), INCLUSION_BLOCK + 4);
+}
+
+#[tokio::test]
+/// Ensures transactions dropped by a reorg before reaching the confirmation depth are reported,
+/// so the bidder or resolver sending them can send them again.
+async fn should_report_reorged_out_transaction() {
+    let node = StubNode::start(Some((INCLUSION_BLOCK + 2, Reorg::Drop))).await;
+
+    let err = confirmations(5)
+        .confirm_receipt::<_, _, Ethereum>(&node.provider(), included_receipt())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, ClientError::TransactionReorged { tx_hash } if tx_hash == TX_HASH),
+        "{err}"
+    );
+    assert_eq!(err.error_code(), "transaction_reorged");
+    assert!(node.head() < INCLUSION_BLOCK + 4);
+}
+
+#[tokio::test]
+/// Ensures transactions included again after a reorg, but reverting this time, are reported as
+/// reverted.
+async fn should_report_transaction_reverted_after_reorg() {
+    let node = StubNode::start(Some((INCLUSION_BLOCK + 2, Reorg::Revert))).await;
+
+    let err = confirmations(3)
+        .confirm_receipt::<_, _, Ethereum>(&node.provider(), included_receipt())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, ClientError::TransactionReverted { tx_hash } if tx_hash == TX_HASH),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+/// Ensures waiting on a transaction reports it gone once the node no longer knows of it, rather
+/// than polling until the depth is reached.
+async fn should_report_dropped_transaction_as_gone() {
+    let node = StubNode::start(Some((INCLUSION_BLOCK + 1, Reorg::Drop))).await;
+
+    let receipt = confirmations(100)
+        .wait_for::<_, _, Ethereum>(&node.provider(), TX_HASH)
+        .await
+        .unwrap();
+    assert!(receipt.is_none());
+    assert_eq!(node.head(), INCLUSION_BLOCK + 1);
+}
+
+#[test]
+/// Ensures the confirmation depth defaults to trusting the inclusion block when left out of a
+/// client config.
+fn should_default_confirmations_in_config() {
+    let confirmations: Confirmations = serde_json::from_value(json!({})).unwrap();
+    assert_eq!(confirmations, Confirmations::default());
+    assert!(confirmations.is_immediate());
+
+    let confirmations: Confirmations = serde_json::from_value(json!({ "depth": 12 })).unwrap();
+    assert_eq!(confirmations.depth, 12);
+    assert_eq!(
+        confirmations.poll_interval(),
+        Confirmations::default().poll_interval()
+    );
+}
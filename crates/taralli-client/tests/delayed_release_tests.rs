@@ -0,0 +1,232 @@
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use taralli_client::clock::{ChainClock, ManualClock};
+use taralli_client::error::ClientError;
+use taralli_client::intent_builder::release::{
+    Release, ReleaseFinalizer, ResignPolicy, UnsignedRequest,
+};
+use taralli_client::intent_builder::request::ComputeRequestBuilder;
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::{address, U256};
+use taralli_primitives::alloy::signers::{local::PrivateKeySigner, Signer};
+use taralli_primitives::alloy::transports::http::{Client, Http};
+use taralli_primitives::deployments::DeploymentRegistry;
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::intents::ComputeIntent;
+use taralli_primitives::markets::SEPOLIA_CHAIN_ID;
+use taralli_primitives::systems::{SystemId, SystemParams};
+use taralli_primitives::validation::request::validate_request_signature;
+use taralli_primitives::validation::violation::ValidationRule;
+use taralli_primitives::PrimitivesError;
+
+pub mod common;
+use common::rpc::{method_not_found, start_stub_node, stub_provider, StubProvider};
+
+const DUMMY_PRIV_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+const NONCE: u64 = 7;
+const START_AUCTION_TIMESTAMP: u64 = 10_000;
+const AUCTION_LENGTH: u64 = 60;
+
+/// Start a stub JSON-RPC node answering every `eth_call`, i.e. permit2 `nonceBitmap`, with the
+/// first word of nonces set to `bitmap`
+async fn start_bitmap_node(bitmap: Arc<AtomicU64>) -> StubProvider {
+    let url = start_stub_node(move |request| match request["method"].as_str().unwrap() {
+        "eth_call" => json!({
+            "result": format!("0x{:064x}", bitmap.load(Ordering::Relaxed))
+        }),
+        _ => method_not_found(),
+    })
+    .await;
+
+    stub_provider(url)
+}
+
+fn signer() -> PrivateKeySigner {
+    PrivateKeySigner::from_str(DUMMY_PRIV_KEY).unwrap()
+}
+
+/// Request builder for sepolia, its nonce and auction set ahead of time
+fn request_builder(
+    rpc_provider: StubProvider,
+) -> ComputeRequestBuilder<Http<Client>, StubProvider, Ethereum> {
+    ComputeRequestBuilder::for_chain(
+        rpc_provider,
+        signer().address(),
+        SEPOLIA_CHAIN_ID,
+        SystemId::Risc0,
+        &DeploymentRegistry::builtin(),
+    )
+    .unwrap()
+    .system(json!({ "elf": [1, 2, 3], "inputs": vec![0u8; 32] }))
+    .nonce(U256::from(NONCE))
+    .set_time_params(
+        START_AUCTION_TIMESTAMP,
+        START_AUCTION_TIMESTAMP + AUCTION_LENGTH,
+        120,
+    )
+    .set_token_params(1, U256::from(10), U256::from(100))
+}
+
+/// Request signed offline, through its exported JSON
+async fn signed_offline(rpc_provider: StubProvider) -> ComputeRequest<SystemParams> {
+    let json = request_builder(rpc_provider)
+        .build_unsigned_with_placeholders()
+        .unwrap()
+        .to_json()
+        .unwrap();
+    UnsignedRequest::from_json(&json)
+        .unwrap()
+        .sign(&signer())
+        .await
+        .unwrap()
+}
+
+fn finalizer(
+    rpc_provider: StubProvider,
+    clock: &ManualClock,
+) -> ReleaseFinalizer<Http<Client>, StubProvider, Ethereum> {
+    ReleaseFinalizer::new(rpc_provider, SEPOLIA_CHAIN_ID)
+        .with_clock(ChainClock::manual(clock.clone()))
+}
+
+#[tokio::test]
+/// Ensures a request built and exported offline is signed from its JSON alone and released as
+/// signed while its auction is ahead of the chain.
+async fn should_sign_exported_request_and_release_it() {
+    let bitmap = Arc::new(AtomicU64::new(0));
+    let rpc_provider = start_bitmap_node(bitmap.clone()).await;
+
+    let unsigned = request_builder(rpc_provider.clone())
+        .build_unsigned_with_placeholders()
+        .unwrap();
+    assert_eq!(unsigned.chain_id, SEPOLIA_CHAIN_ID);
+    assert_eq!(
+        unsigned.typed_data.primary_type,
+        "PermitWitnessTransferFrom"
+    );
+    let json = unsigned.to_json().unwrap();
+
+    // typed data edited apart from the request
+    let mut tampered: Value = serde_json::from_str(&json).unwrap();
+    tampered["typed_data"]["message"]["permitted"]["amount"] = json!("1000000");
+    let err = UnsignedRequest::from_json(&tampered.to_string()).unwrap_err();
+    assert!(matches!(err, ClientError::DeserializationError(_)), "{err}");
+
+    // signed by another key than the request's signer
+    let other = PrivateKeySigner::random();
+    let err = UnsignedRequest::from_json(&json)
+        .unwrap()
+        .sign(&other)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ClientError::IntentSigningError(_)), "{err}");
+    let signature = other.sign_hash(&unsigned.digest()).await.unwrap();
+    assert!(unsigned.clone().with_signature(signature).is_err());
+
+    let signed = UnsignedRequest::from_json(&json)
+        .unwrap()
+        .sign(&signer())
+        .await
+        .unwrap();
+    validate_request_signature(&signed.proof_request, &signed.signature, SEPOLIA_CHAIN_ID).unwrap();
+
+    let clock = ManualClock::new(START_AUCTION_TIMESTAMP - 60);
+    let released = finalizer(rpc_provider.clone(), &clock)
+        .finalize_and_validate(signed.clone())
+        .await
+        .unwrap();
+    let Release::Ready(released) = released else {
+        panic!("expected the signed request to be released as is");
+    };
+    assert_eq!(released.signature, signed.signature);
+    assert_eq!(released.compute_id(), signed.compute_id());
+
+    // the chain id can't be fetched offline
+    let err = ComputeRequestBuilder::new(
+        rpc_provider,
+        signer().address(),
+        address!("2222222222222222222222222222222222222222"),
+        SystemId::Risc0,
+    )
+    .build_unsigned_with_placeholders()
+    .unwrap_err();
+    assert!(matches!(err, ClientError::BuilderError(_)), "{err}");
+}
+
+#[tokio::test]
+/// Ensures a request is rejected once its nonce is used or its auction is over at release, and
+/// only handed back to be signed again for a shifted window when the resign policy allows it.
+async fn should_reject_stale_release() {
+    let bitmap = Arc::new(AtomicU64::new(0));
+    let rpc_provider = start_bitmap_node(bitmap.clone()).await;
+    let signed = signed_offline(rpc_provider.clone()).await;
+    let end_auction_timestamp = START_AUCTION_TIMESTAMP + AUCTION_LENGTH;
+    let clock = ManualClock::new(end_auction_timestamp);
+
+    let err = finalizer(rpc_provider.clone(), &clock)
+        .finalize_and_validate(signed.clone())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ClientError::ReleaseWindowPassed { end_auction_timestamp: end, chain_time }
+                if end == end_auction_timestamp && chain_time == end_auction_timestamp
+        ),
+        "{err}"
+    );
+
+    // released too long before its auction starts for the server to accept it
+    let early = ManualClock::new(START_AUCTION_TIMESTAMP - 600);
+    let err = finalizer(rpc_provider.clone(), &early)
+        .with_resign_policy(ResignPolicy::ShiftWindow)
+        .finalize_and_validate(signed.clone())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            &err,
+            ClientError::PrimitivesError(PrimitivesError::ValidationViolation(violation))
+                if violation.rule == ValidationRule::TimestampWindow
+        ),
+        "{err}"
+    );
+
+    // moved to a window starting now, signed again and released
+    clock.advance(30);
+    let shifting =
+        finalizer(rpc_provider.clone(), &clock).with_resign_policy(ResignPolicy::ShiftWindow);
+    let Release::Resign(unsigned) = shifting
+        .finalize_and_validate(signed.clone())
+        .await
+        .unwrap()
+    else {
+        panic!("expected the request to be handed back to be signed again");
+    };
+    let now = clock.now();
+    assert_eq!(unsigned.request.proof_request.startAuctionTimestamp, now);
+    assert_eq!(
+        unsigned.request.proof_request.endAuctionTimestamp,
+        now + AUCTION_LENGTH
+    );
+    assert_eq!(unsigned.request.proof_request.nonce, U256::from(NONCE));
+    let resigned = unsigned.sign(&signer()).await.unwrap();
+    assert!(matches!(
+        shifting
+            .finalize_and_validate(resigned.clone())
+            .await
+            .unwrap(),
+        Release::Ready(_)
+    ));
+
+    // submitted meanwhile, whatever the policy
+    bitmap.store(1 << NONCE, Ordering::Relaxed);
+    let err = shifting.finalize_and_validate(resigned).await.unwrap_err();
+    assert!(
+        matches!(err, ClientError::NonceAlreadyUsed { nonce, .. } if nonce == U256::from(NONCE)),
+        "{err}"
+    );
+}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use taralli_client::client::provider::streaming::ProviderStreamingClient;
+use taralli_client::client::requester::requesting::RequesterRequestingClient;
+use taralli_client::env::{
+    EnvConfig, CHAIN_ID_VAR, DEFAULT_MAXIMUM_ALLOWED_STAKE, DEPLOYMENTS_FILE_VAR,
+    MARKET_ADDRESS_VAR, MAXIMUM_ALLOWED_STAKE_VAR, PRIVATE_KEY_VAR, PROVIDER_PRIVATE_KEY_VAR,
+    REQUESTER_PRIVATE_KEY_VAR, RPC_URL_VAR, SERVER_URL_VAR,
+};
+use taralli_client::error::ClientError;
+use taralli_primitives::alloy::primitives::{address, Address};
+use taralli_primitives::markets::{SEPOLIA_CHAIN_ID, SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS};
+use taralli_primitives::systems::SystemId;
+
+const ANVIL_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+const ANVIL_ADDRESS: Address = address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+const SECOND_ANVIL_KEY: &str = "0x59c6995e998f97a5a0044966f0945389dc9e86dae88c7a8412f4603b6b78690d";
+const SECOND_ANVIL_ADDRESS: Address = address!("70997970C51812dc3A010C7d01b50e0d17dc79C8");
+
+const ALL_VARS: [&str; 9] = [
+    RPC_URL_VAR,
+    SERVER_URL_VAR,
+    PRIVATE_KEY_VAR,
+    REQUESTER_PRIVATE_KEY_VAR,
+    PROVIDER_PRIVATE_KEY_VAR,
+    CHAIN_ID_VAR,
+    MARKET_ADDRESS_VAR,
+    DEPLOYMENTS_FILE_VAR,
+    MAXIMUM_ALLOWED_STAKE_VAR,
+];
+
+/// Tests share the process environment, so the ones changing it run one at a time
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+/// Environment holding only the given client variables until dropped, when the previous values
+/// are restored
+struct ScopedEnv {
+    previous: Vec<(&'static str, Option<String>)>,
+}
+
+impl ScopedEnv {
+    fn new(vars: &[(&'static str, &str)]) -> Self {
+        let previous = ALL_VARS
+            .iter()
+            .map(|name| (*name, std::env::var(name).ok()))
+            .collect();
+        for name in ALL_VARS {
+            std::env::remove_var(name);
+        }
+        for (name, value) in vars {
+            std::env::set_var(name, value);
+        }
+        Self { previous }
+    }
+}
+
+impl Drop for ScopedEnv {
+    fn drop(&mut self) {
+        for (name, value) in &self.previous {
+            match value {
+                Some(value) => std::env::set_var(name, value),
+                None => std::env::remove_var(name),
+            }
+        }
+    }
+}
+
+fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars: HashMap<String, String> = vars
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+    move |name| vars.get(name).cloned()
+}
+
+fn environment_errors(result: Result<EnvConfig, ClientError>) -> Vec<String> {
+    match result {
+        Err(ClientError::EnvironmentError(errors)) => errors,
+        other => panic!("expected an environment error, got {other:?}"),
+    }
+}
+
+#[test]
+/// Ensures both clients are built from the process environment, the market defaulting to the
+/// sepolia deployment when neither `CHAIN_ID` nor `MARKET_ADDRESS` are set.
+fn should_build_clients_from_env() {
+    let _lock = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let _env = ScopedEnv::new(&[
+        (RPC_URL_VAR, "http://localhost:8545"),
+        (SERVER_URL_VAR, "http://localhost:8000"),
+        (PRIVATE_KEY_VAR, ANVIL_KEY),
+    ]);
+
+    let config = EnvConfig::from_env(REQUESTER_PRIVATE_KEY_VAR).unwrap();
+    assert_eq!(config.rpc_url.as_str(), "http://localhost:8545/");
+    assert_eq!(config.server_url.as_str(), "http://localhost:8000/");
+    assert_eq!(config.signer.address(), ANVIL_ADDRESS);
+    assert_eq!(config.chain_id, SEPOLIA_CHAIN_ID);
+    assert_eq!(config.market_address, SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS);
+    assert_eq!(config.maximum_allowed_stake, DEFAULT_MAXIMUM_ALLOWED_STAKE);
+    let validation_config = config.request_validation_config();
+    assert_eq!(validation_config.base.chain_id, SEPOLIA_CHAIN_ID);
+    assert_eq!(
+        validation_config.maximum_allowed_stake,
+        DEFAULT_MAXIMUM_ALLOWED_STAKE
+    );
+
+    assert!(ProviderStreamingClient::from_env().is_ok());
+    assert!(RequesterRequestingClient::from_env(SystemId::Risc0).is_ok());
+}
+
+#[test]
+/// Ensures every missing or invalid variable of the process environment is reported at once.
+fn should_report_every_invalid_variable_from_env() {
+    let _lock = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let _env = ScopedEnv::new(&[
+        (RPC_URL_VAR, "not a url"),
+        (PROVIDER_PRIVATE_KEY_VAR, "0x1234"),
+        (CHAIN_ID_VAR, "sepolia"),
+    ]);
+
+    let errors = match ProviderStreamingClient::from_env() {
+        Err(ClientError::EnvironmentError(errors)) => errors,
+        Err(e) => panic!("expected an environment error, got {e:?}"),
+        Ok(_) => panic!("expected an environment error"),
+    };
+    assert_eq!(errors.len(), 4, "{errors:?}");
+    for name in [
+        RPC_URL_VAR,
+        SERVER_URL_VAR,
+        PROVIDER_PRIVATE_KEY_VAR,
+        CHAIN_ID_VAR,
+    ] {
+        assert!(
+            errors.iter().any(|error| error.starts_with(name)),
+            "{name} missing from {errors:?}"
+        );
+    }
+    // the unparseable chain id is reported, not the market it leaves unresolved
+    assert!(!errors
+        .iter()
+        .any(|error| error.starts_with(MARKET_ADDRESS_VAR)));
+
+    let message = ClientError::EnvironmentError(errors).to_string();
+    assert!(message.starts_with("Invalid environment: "));
+}
+
+#[test]
+/// Ensures the role specific key takes precedence over `PRIVATE_KEY`, and that the overrides of
+/// the market, chain and stake are applied.
+fn should_apply_overrides() {
+    let base = [
+        (RPC_URL_VAR, "http://localhost:8545"),
+        (SERVER_URL_VAR, "http://localhost:8000"),
+        (PRIVATE_KEY_VAR, ANVIL_KEY),
+    ];
+
+    let mut vars = base.to_vec();
+    vars.push((REQUESTER_PRIVATE_KEY_VAR, SECOND_ANVIL_KEY));
+    let requester = EnvConfig::from_lookup(REQUESTER_PRIVATE_KEY_VAR, lookup(&vars)).unwrap();
+    assert_eq!(requester.signer.address(), SECOND_ANVIL_ADDRESS);
+    let provider = EnvConfig::from_lookup(PROVIDER_PRIVATE_KEY_VAR, lookup(&vars)).unwrap();
+    assert_eq!(provider.signer.address(), ANVIL_ADDRESS);
+
+    let market = Address::repeat_byte(0x42);
+    let mut vars = base.to_vec();
+    let market_string = market.to_string();
+    vars.extend([
+        (CHAIN_ID_VAR, "31337"),
+        (MARKET_ADDRESS_VAR, market_string.as_str()),
+        (MAXIMUM_ALLOWED_STAKE_VAR, "1000"),
+    ]);
+    let config = EnvConfig::from_lookup(PROVIDER_PRIVATE_KEY_VAR, lookup(&vars)).unwrap();
+    assert_eq!(config.chain_id, 31337);
+    assert_eq!(config.market_address, market);
+    assert_eq!(config.maximum_allowed_stake, 1000);
+    assert_eq!(config.request_validation_config().base.chain_id, 31337);
+}
+
+#[test]
+/// Ensures a chain without a known deployment needs `MARKET_ADDRESS`, and that missing keys name
+/// both variables the key can be read from.
+fn should_report_unresolved_market_and_missing_key() {
+    let vars = [
+        (RPC_URL_VAR, "http://localhost:8545"),
+        (SERVER_URL_VAR, "http://localhost:8000"),
+        (CHAIN_ID_VAR, "31337"),
+    ];
+
+    let errors = environment_errors(EnvConfig::from_lookup(
+        REQUESTER_PRIVATE_KEY_VAR,
+        lookup(&vars),
+    ));
+    assert_eq!(errors.len(), 2, "{errors:?}");
+    assert!(errors
+        .iter()
+        .any(|error| error.contains(REQUESTER_PRIVATE_KEY_VAR) && error.contains(PRIVATE_KEY_VAR)));
+    assert!(errors
+        .iter()
+        .any(|error| error.starts_with(MARKET_ADDRESS_VAR)));
+
+    // an empty variable counts as unset
+    let mut vars = vars.to_vec();
+    vars.push((PRIVATE_KEY_VAR, ""));
+    let errors = environment_errors(EnvConfig::from_lookup(
+        REQUESTER_PRIVATE_KEY_VAR,
+        lookup(&vars),
+    ));
+    assert_eq!(errors.len(), 2, "{errors:?}");
+}
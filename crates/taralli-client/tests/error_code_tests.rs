@@ -0,0 +1,208 @@
+use std::collections::HashSet;
+
+use taralli_client::error::ClientError;
+use taralli_client::rpc::RpcErrorKind;
+use taralli_primitives::alloy::primitives::{Address, B256, U256};
+use taralli_primitives::error::ErrorCode;
+use taralli_primitives::systems::SystemId;
+use taralli_primitives::PrimitivesError;
+
+/// One error of every variant. The match fails to compile once a variant is added, so the new
+/// variant can't be left out of the list.
+fn every_variant() -> Vec<ClientError> {
+    let errors = vec![
+        ClientError::ConfigError(String::new()),
+        ClientError::EnvironmentError(Vec::new()),
+        ClientError::HealthcheckFailed(Vec::new()),
+        ClientError::TransactionSetupError(String::new()),
+        ClientError::EventFilterError(String::new()),
+        ClientError::IntentParsingError(String::new()),
+        ClientError::ServerSubscriptionError(String::new()),
+        ClientError::IntentAnalysisError(String::new()),
+        ClientError::IntentVetoed(String::new()),
+        ClientError::RewardTokenRejected {
+            token: Address::ZERO,
+            reason: String::new(),
+        },
+        ClientError::UnprofitableIntent {
+            reward: U256::ZERO,
+            cost: U256::ZERO,
+        },
+        ClientError::DeserializationError(String::new()),
+        ClientError::WorkerError(String::new()),
+        ClientError::ProverQuotaExhausted(String::new()),
+        ClientError::ProofTimeout(String::new()),
+        ClientError::WorkerTimeout {
+            system_id: SystemId::Risc0,
+            budget_secs: 0,
+        },
+        ClientError::BuilderError(String::new()),
+        ClientError::TemplateError(String::new()),
+        ClientError::IntentSubmissionFailed(String::new()),
+        ClientError::NoOffersAvailable(String::new()),
+        ClientError::IntentDecompressionFailed(String::new()),
+        ClientError::TrackIntentError(String::new()),
+        ClientError::TransactionError(String::new()),
+        ClientError::TransactionFailure(String::new()),
+        ClientError::TransactionReverted {
+            tx_hash: B256::ZERO,
+        },
+        ClientError::TransactionReorged {
+            tx_hash: B256::ZERO,
+        },
+        ClientError::AuctionAlreadyBid {
+            intent_id: B256::ZERO,
+        },
+        ClientError::InsufficientFunds {
+            intent_id: B256::ZERO,
+            balance: U256::ZERO,
+            required: U256::ZERO,
+        },
+        ClientError::LogParseError(String::new()),
+        ClientError::ServerRequestError(String::new()),
+        ClientError::ServerRejected {
+            status: 400,
+            code: Some(ErrorCode::ValidationFailed),
+            message: String::new(),
+            violations: Vec::new(),
+        },
+        ClientError::RpcRequestError(String::new()),
+        ClientError::RpcFailed {
+            kind: RpcErrorKind::Transient,
+            message: String::new(),
+        },
+        ClientError::IntentSigningError(String::new()),
+        ClientError::ServerUrlParsingError(String::new()),
+        ClientError::GetNonceError(String::new()),
+        ClientError::NonceAlreadyUsed {
+            signer: Address::ZERO,
+            nonce: U256::ZERO,
+        },
+        ClientError::Permit2AllowanceInsufficient {
+            token: Address::ZERO,
+            spender: Address::ZERO,
+            shortfalls: Vec::new(),
+        },
+        ClientError::FindUnusedNonceError(),
+        ClientError::SetAuctionTimestampsError(),
+        ClientError::ReleaseWindowPassed {
+            end_auction_timestamp: 0,
+            chain_time: 0,
+        },
+        ClientError::WaitDeadlinePassed {
+            timestamp: 0,
+            deadline: 0,
+            chain_time: 0,
+        },
+        ClientError::AuctionTimeoutError(),
+        ClientError::ValidationError(String::new()),
+        ClientError::ValidationViolations(Vec::new()),
+        ClientError::PrimitivesError(PrimitivesError::ConfigError(String::new())),
+        ClientError::ApiKeyError(String::new()),
+        ClientError::InvalidMode(String::new()),
+        ClientError::KeyExchangeError(String::new()),
+        ClientError::OfferInputsError(String::new()),
+        ClientError::ExtensionError(String::new()),
+        ClientError::BacktestError(String::new()),
+        ClientError::ReviewEndpointError(String::new()),
+        ClientError::ControlEndpointError(String::new()),
+        ClientError::ProviderSearchingUnimplemented,
+    ];
+    for error in &errors {
+        match error {
+            ClientError::ConfigError(_)
+            | ClientError::EnvironmentError(_)
+            | ClientError::HealthcheckFailed(_)
+            | ClientError::TransactionSetupError(_)
+            | ClientError::EventFilterError(_)
+            | ClientError::IntentParsingError(_)
+            | ClientError::ServerSubscriptionError(_)
+            | ClientError::IntentAnalysisError(_)
+            | ClientError::IntentVetoed(_)
+            | ClientError::RewardTokenRejected { .. }
+            | ClientError::UnprofitableIntent { .. }
+            | ClientError::DeserializationError(_)
+            | ClientError::WorkerError(_)
+            | ClientError::ProverQuotaExhausted(_)
+            | ClientError::ProofTimeout(_)
+            | ClientError::WorkerTimeout { .. }
+            | ClientError::BuilderError(_)
+            | ClientError::TemplateError(_)
+            | ClientError::IntentSubmissionFailed(_)
+            | ClientError::NoOffersAvailable(_)
+            | ClientError::IntentDecompressionFailed(_)
+            | ClientError::TrackIntentError(_)
+            | ClientError::TransactionError(_)
+            | ClientError::TransactionFailure(_)
+            | ClientError::TransactionReverted { .. }
+            | ClientError::TransactionReorged { .. }
+            | ClientError::AuctionAlreadyBid { .. }
+            | ClientError::InsufficientFunds { .. }
+            | ClientError::LogParseError(_)
+            | ClientError::ServerRequestError(_)
+            | ClientError::ServerRejected { .. }
+            | ClientError::RpcRequestError(_)
+            | ClientError::RpcFailed { .. }
+            | ClientError::IntentSigningError(_)
+            | ClientError::ServerUrlParsingError(_)
+            | ClientError::GetNonceError(_)
+            | ClientError::NonceAlreadyUsed { .. }
+            | ClientError::Permit2AllowanceInsufficient { .. }
+            | ClientError::FindUnusedNonceError()
+            | ClientError::SetAuctionTimestampsError()
+            | ClientError::ReleaseWindowPassed { .. }
+            | ClientError::WaitDeadlinePassed { .. }
+            | ClientError::AuctionTimeoutError()
+            | ClientError::ValidationError(_)
+            | ClientError::ValidationViolations(_)
+            | ClientError::PrimitivesError(_)
+            | ClientError::ApiKeyError(_)
+            | ClientError::InvalidMode(_)
+            | ClientError::KeyExchangeError(_)
+            | ClientError::OfferInputsError(_)
+            | ClientError::ExtensionError(_)
+            | ClientError::BacktestError(_)
+            | ClientError::ReviewEndpointError(_)
+            | ClientError::ControlEndpointError(_)
+            | ClientError::ProviderSearchingUnimplemented => {}
+        }
+    }
+    errors
+}
+
+#[test]
+/// Ensures each error code is used by exactly one variant.
+fn should_map_each_code_to_one_variant() {
+    let errors = every_variant();
+    let codes: HashSet<&str> = errors.iter().map(ClientError::error_code).collect();
+    assert_eq!(codes.len(), errors.len());
+    assert!(codes.iter().all(|code| !code.is_empty()));
+}
+
+#[test]
+/// Ensures only server rejections carry a server code.
+fn should_expose_server_code_of_rejections_only() {
+    for error in every_variant() {
+        match error {
+            ClientError::ServerRejected { .. } => {
+                assert_eq!(error.server_code(), Some(ErrorCode::ValidationFailed))
+            }
+            error => assert_eq!(error.server_code(), None),
+        }
+    }
+}
+
+#[test]
+/// Ensures the server codes are parsed back from their wire form, and unknown ones are ignored.
+fn should_parse_server_codes() {
+    let codes: HashSet<&str> = ErrorCode::ALL.iter().map(ErrorCode::as_str).collect();
+    assert_eq!(codes.len(), ErrorCode::ALL.len());
+    for code in ErrorCode::ALL {
+        assert_eq!(ErrorCode::parse(code.as_str()), Some(code));
+        assert_eq!(
+            serde_json::to_value(code).unwrap(),
+            serde_json::json!(code.as_str())
+        );
+    }
+    assert_eq!(ErrorCode::parse("added_by_a_newer_server"), None);
+}
@@ -0,0 +1,242 @@
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use taralli_client::api::status::{
+    MarketAddresses, OfferBounds, RequestBounds, ServerStatus, StatusApiClient, SystemStatus,
+    ValidationBounds,
+};
+use taralli_client::client::healthcheck::{Healthcheck, HealthcheckConfig};
+use taralli_client::client::requester::requesting::RequesterRequestingClient;
+use taralli_client::client::BaseClient;
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::{address, Address, U256};
+use taralli_primitives::alloy::providers::{ProviderBuilder, RootProvider};
+use taralli_primitives::alloy::signers::local::PrivateKeySigner;
+use taralli_primitives::alloy::transports::http::{Client, Http};
+use taralli_primitives::markets::SEPOLIA_CHAIN_ID;
+use taralli_primitives::systems::SystemId;
+use taralli_primitives::validation::request::{
+    RequestValidationConfig, RequestVerifierConstraints,
+};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use url::Url;
+
+pub mod common;
+use common::rpc::unreachable_url;
+
+const MARKET: Address = address!("2222222222222222222222222222222222222222");
+const ETHER: u64 = 1_000_000_000_000_000_000;
+
+type StubRequester =
+    RequesterRequestingClient<Http<Client>, RootProvider<Http<Client>>, Ethereum, PrivateKeySigner>;
+type StubBase = BaseClient<Http<Client>, RootProvider<Http<Client>>, Ethereum, PrivateKeySigner>;
+
+/// Stub of both a JSON-RPC node on `chain_id` holding code at every address and `balance` in
+/// every account, and a protocol server whose `/status` supports risc0 on `chain_id`
+async fn start_stub(chain_id: u64, balance: U256) -> Url {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap())
+        .parse()
+        .unwrap();
+    let status = serde_json::to_string(&server_status(chain_id)).unwrap();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let (head, body) = read_request(&mut stream).await;
+            let body = if head.starts_with("GET /status") {
+                status.clone()
+            } else {
+                let request: Value = serde_json::from_slice(&body).unwrap();
+                let mut response = match request["method"].as_str().unwrap() {
+                    "eth_chainId" => json!({ "result": format!("{chain_id:#x}") }),
+                    "eth_getCode" => json!({ "result": "0x6080604052" }),
+                    "eth_getBalance" => json!({ "result": format!("{balance:#x}") }),
+                    _ => json!({ "error": { "code": -32601, "message": "method not found" } }),
+                };
+                response["jsonrpc"] = json!("2.0");
+                response["id"] = request["id"].clone();
+                response.to_string()
+            };
+            let http = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(http.as_bytes()).await.unwrap();
+            stream.shutdown().await.unwrap();
+        }
+    });
+    url
+}
+
+/// Read a whole request, returning its head and body
+async fn read_request(stream: &mut TcpStream) -> (String, Vec<u8>) {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 4096];
+    let header_end = loop {
+        let read = stream.read(&mut buffer).await.unwrap();
+        request.extend_from_slice(&buffer[..read]);
+        if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&request[..header_end]).to_string();
+    let content_length: usize = head
+        .to_lowercase()
+        .lines()
+        .find_map(|line| line.strip_prefix("content-length: ").map(str::to_string))
+        .map_or(0, |length| length.trim().parse().unwrap());
+    while request.len() < header_end + content_length {
+        let read = stream.read(&mut buffer).await.unwrap();
+        request.extend_from_slice(&buffer[..read]);
+    }
+    (head, request[header_end..].to_vec())
+}
+
+fn server_status(chain_id: u64) -> ServerStatus {
+    ServerStatus {
+        version: "test".to_string(),
+        systems: vec![SystemStatus {
+            system_id: SystemId::Risc0,
+            bit: SystemId::Risc0.as_bit(),
+            subscribers: 1,
+            capabilities: Default::default(),
+            validation: None,
+        }],
+        active_subscriptions: 1,
+        validation: ValidationBounds {
+            request: RequestBounds {
+                minimum_proving_time: 0,
+                maximum_start_delay: 300,
+                minimum_auction_length: 0,
+                maximum_auction_length: 3_600,
+                maximum_proving_time: 86_400,
+                maximum_allowed_stake: 1,
+                supported_systems: vec![SystemId::Risc0],
+                chain_id,
+                resource_limits: Default::default(),
+            },
+            offer: OfferBounds {
+                minimum_proving_time: 0,
+                maximum_start_delay: 300,
+                minimum_auction_length: 0,
+                maximum_auction_length: 3_600,
+                maximum_proving_time: 86_400,
+                maximum_allowed_reward: U256::from(1),
+                minimum_allowed_stake: U256::ZERO,
+                supported_systems: vec![SystemId::Risc0],
+                chain_id,
+                resource_limits: Default::default(),
+            },
+        },
+        markets: MarketAddresses {
+            universal_bombetta: MARKET,
+            universal_porchetta: MARKET,
+        },
+    }
+}
+
+fn requester(server_url: Url, rpc_url: Url, system_id: SystemId) -> StubRequester {
+    RequesterRequestingClient::new(
+        server_url,
+        ProviderBuilder::new().on_http(rpc_url),
+        PrivateKeySigner::random(),
+        MARKET,
+        system_id,
+        RequestValidationConfig::default(),
+        RequestVerifierConstraints::default(),
+    )
+}
+
+#[tokio::test]
+/// Ensures every check passes against a reachable rpc and server on the expected chain, each
+/// being timed.
+async fn should_pass_against_healthy_stack() {
+    let url = start_stub(SEPOLIA_CHAIN_ID, U256::from(ETHER)).await;
+
+    let report = requester(url.clone(), url, SystemId::Risc0)
+        .healthcheck()
+        .await;
+    assert!(report.is_healthy(), "{report}");
+    assert_eq!(report.failures().count(), 0, "{report}");
+    assert_eq!(
+        report
+            .checks
+            .iter()
+            .map(|result| result.check)
+            .collect::<Vec<_>>(),
+        vec![
+            Healthcheck::RpcChain,
+            Healthcheck::MarketCode,
+            Healthcheck::ServerStatus,
+            Healthcheck::SignerFunds,
+        ]
+    );
+    assert!(report.into_result().is_ok());
+}
+
+#[tokio::test]
+/// Ensures a wrong chain id and an unreachable server are both reported at once, the checks that
+/// don't depend on them still passing.
+async fn should_aggregate_wrong_chain_and_unreachable_server() {
+    let rpc_url = start_stub(1, U256::from(ETHER)).await;
+    let server_url = unreachable_url().await;
+
+    let report = requester(server_url, rpc_url, SystemId::Risc0)
+        .healthcheck()
+        .await;
+    assert!(!report.is_healthy(), "{report}");
+    let failed: Vec<Healthcheck> = report.failures().map(|result| result.check).collect();
+    assert_eq!(
+        failed,
+        vec![Healthcheck::RpcChain, Healthcheck::ServerStatus],
+        "{report}"
+    );
+
+    let err = report.into_result().unwrap_err();
+    assert_eq!(err.error_code(), "healthcheck_failed");
+    let message = err.to_string();
+    assert!(message.contains("rpc is on chain 1"), "{message}");
+    assert!(message.contains("server unreachable"), "{message}");
+}
+
+#[tokio::test]
+/// Ensures systems the server doesn't support fail the server check.
+async fn should_fail_on_unsupported_system() {
+    let url = start_stub(SEPOLIA_CHAIN_ID, U256::from(ETHER)).await;
+
+    let report = requester(url.clone(), url, SystemId::Sp1)
+        .healthcheck()
+        .await;
+    let failed: Vec<_> = report.failures().collect();
+    assert_eq!(failed.len(), 1, "{report}");
+    assert_eq!(failed[0].check, Healthcheck::ServerStatus);
+    assert!(failed[0].detail.contains("sp1"), "{report}");
+}
+
+#[tokio::test]
+/// Ensures an account holding dust only warns requesters, but fails clients that need funds.
+async fn should_fail_unfunded_accounts_only_when_funds_are_required() {
+    let url = start_stub(SEPOLIA_CHAIN_ID, U256::from(1)).await;
+
+    let report = requester(url.clone(), url.clone(), SystemId::Risc0)
+        .healthcheck()
+        .await;
+    assert!(report.is_healthy(), "{report}");
+    let warnings: Vec<_> = report.failures().collect();
+    assert_eq!(warnings.len(), 1, "{report}");
+    assert_eq!(warnings[0].check, Healthcheck::SignerFunds);
+    assert!(!warnings[0].hard);
+
+    let base: StubBase = BaseClient::new(
+        ProviderBuilder::new().on_http(url.clone()),
+        PrivateKeySigner::random(),
+        MARKET,
+    );
+    let config = HealthcheckConfig::new(SEPOLIA_CHAIN_ID, vec![SystemId::Risc0])
+        .with_timeout(Duration::from_secs(5));
+    let report = base.healthcheck(&StatusApiClient::new(url), &config).await;
+    assert!(!report.is_healthy(), "{report}");
+    assert!(report.into_result().is_err());
+}
@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use taralli_client::error::Result;
+use taralli_client::worker::{ComputeWorker, WorkResult, WorkerManager};
+use taralli_primitives::alloy::primitives::{Address, Bytes, B256};
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::intents::ComputeIntent;
+use taralli_primitives::logging::intent_span;
+use taralli_primitives::systems::{SystemId, SystemParams};
+use tracing::Instrument;
+use tracing_subscriber::{fmt, layer::SubscriberExt, Registry};
+
+pub mod common;
+use common::fixtures::request_with_inputs;
+
+const MARKET: Address = Address::repeat_byte(9);
+
+type Request = ComputeRequest<SystemParams>;
+
+/// Worker logging before and after yielding, so concurrent executions interleave
+struct YieldingWorker;
+
+#[async_trait]
+impl ComputeWorker<Request> for YieldingWorker {
+    async fn execute(&self, intent: &Request) -> Result<WorkResult> {
+        let inputs = intent.proof_request.inputsCommitment;
+        tracing::info!("proving inputs {inputs}");
+        tokio::task::yield_now().await;
+        tracing::info!("proved inputs {inputs}");
+        Ok(WorkResult {
+            opaque_submission: Bytes::from(vec![7; 8]),
+            partial_commitment: inputs,
+        })
+    }
+}
+
+#[tokio::test]
+/// Ensures the events logged while proving interleaved intents each carry the id of the intent
+/// they were logged for.
+async fn should_tag_interleaved_intents_with_their_id() {
+    let logged = Arc::new(Mutex::new(Vec::new()));
+    let writer = logged.clone();
+    let subscriber = Registry::default().with(
+        fmt::layer()
+            .with_ansi(false)
+            .with_writer(move || LogWriter(writer.clone())),
+    );
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let manager = WorkerManager::new(HashMap::from([(
+        SystemId::Risc0,
+        Arc::new(YieldingWorker) as Arc<dyn ComputeWorker<Request> + Send + Sync>,
+    )]));
+    let requests = [request_with_inputs(1, 1), request_with_inputs(2, 2)].map(|mut request| {
+        request.proof_request.market = MARKET;
+        request
+    });
+    let [first, second] = requests.each_ref().map(|request| {
+        intent_span(
+            request.compute_id(),
+            request.system_id,
+            request.proof_request.market,
+        )
+    });
+    let (first, second) = tokio::join!(
+        manager.execute(&requests[0]).instrument(first),
+        manager.execute(&requests[1]).instrument(second)
+    );
+    first.unwrap();
+    second.unwrap();
+
+    let logged = String::from_utf8(logged.lock().unwrap().clone()).unwrap();
+    let lines: Vec<&str> = logged.lines().collect();
+    let position = |message: &str| {
+        lines
+            .iter()
+            .position(|line| line.contains(message))
+            .unwrap_or_else(|| panic!("{message} not logged: {logged}"))
+    };
+    // both intents were in flight at once
+    let proving = format!("proving inputs {}", B256::repeat_byte(2));
+    let proved = format!("proved inputs {}", B256::repeat_byte(1));
+    assert!(position(&proving) < position(&proved), "{logged}");
+
+    for (request, other) in [(&requests[0], &requests[1]), (&requests[1], &requests[0])] {
+        let inputs = request.proof_request.inputsCommitment;
+        for message in [
+            format!("proving inputs {inputs}"),
+            format!("proved inputs {inputs}"),
+        ] {
+            let line = lines[position(&message)];
+            assert!(
+                line.contains(&format!("intent_id={}", request.compute_id())),
+                "{line}"
+            );
+            assert!(!line.contains(&other.compute_id().to_string()), "{line}");
+            assert!(line.contains("system_id=Risc0"), "{line}");
+            assert!(line.contains(&format!("market={MARKET}")), "{line}");
+        }
+    }
+}
+
+/// Writer appending the log lines to a shared buffer
+struct LogWriter(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,261 @@
+use taralli_client::intent_builder::request::ComputeRequestBuilder;
+use taralli_client::intent_builder::template::{
+    IntentTemplate, RequestTemplateParams, TemplateOverride,
+};
+use taralli_client::intent_builder::IntentBuilder;
+use taralli_primitives::abi::universal_bombetta::VerifierDetails;
+use taralli_primitives::alloy::primitives::{address, fixed_bytes, Address, Bytes, B256, U256};
+use taralli_primitives::alloy::providers::{ProviderBuilder, RootProvider};
+use taralli_primitives::alloy::sol_types::SolValue;
+use taralli_primitives::alloy::transports::http::{Client, Http};
+use taralli_primitives::deployments::DeploymentRegistry;
+use taralli_primitives::intents::auction::RewardCurve;
+use taralli_primitives::markets::{SEPOLIA_CHAIN_ID, SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS};
+use taralli_primitives::systems::{System, SystemId, SystemInputs};
+
+const SIGNER_ADDRESS: Address = address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+const REWARD_TOKEN_ADDRESS: Address = address!("b54061f59AcF94f86ee414C9a220aFFE8BbE6B35");
+
+fn rpc_provider() -> RootProvider<Http<Client>> {
+    ProviderBuilder::new().on_http("http://localhost:8545".parse().unwrap())
+}
+
+fn verifier_details_fixture() -> Bytes {
+    Bytes::from(
+        VerifierDetails {
+            verifier: address!("AC292cF957Dd5BA174cdA13b05C16aFC71700327"),
+            selector: fixed_bytes!("ab750e75"),
+            isShaCommitment: true,
+            inputsOffset: U256::from(32),
+            inputsLength: U256::from(64),
+            hasPartialCommitmentResultCheck: false,
+            submittedPartialCommitmentResultOffset: U256::ZERO,
+            submittedPartialCommitmentResultLength: U256::ZERO,
+            predeterminedPartialCommitment: B256::ZERO,
+        }
+        .abi_encode(),
+    )
+}
+
+fn template_fixture() -> IntentTemplate {
+    IntentTemplate {
+        system_id: SystemId::Risc0,
+        market_address: SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+        auction_length: 60,
+        reward_token_address: REWARD_TOKEN_ADDRESS,
+        reward_token_decimals: 18,
+        proving_time: 60,
+        extra_data: verifier_details_fixture(),
+        request: Some(RequestTemplateParams {
+            max_reward_amount: U256::from(100e18),
+            min_reward_amount: U256::from(10),
+            minimum_stake: 1,
+        }),
+        overrides: [(
+            "urgent".to_string(),
+            TemplateOverride {
+                auction_length: Some(15),
+                max_reward_amount: Some(U256::from(200e18)),
+                ..Default::default()
+            },
+        )]
+        .into_iter()
+        .collect(),
+    }
+}
+
+#[test]
+/// Ensures templates survive a json round trip, including through a file.
+fn should_round_trip_template_serde() {
+    let template = template_fixture();
+
+    let json = serde_json::to_string(&template).unwrap();
+    assert_eq!(IntentTemplate::from_json_str(&json).unwrap(), template);
+
+    let path = std::env::temp_dir().join(format!("intent_template_{}.json", std::process::id()));
+    template.save(&path).unwrap();
+    let loaded = IntentTemplate::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, template);
+}
+
+#[test]
+/// Ensures a builder hydrated from a template builds the same `ProofRequest` as one
+/// configured call by call.
+fn should_build_same_request_from_template() {
+    let template = template_fixture();
+    let inputs_commitment = B256::repeat_byte(0x42);
+    let system = serde_json::json!({ "elf": [1, 2, 3], "inputs": vec![4u8; 32] });
+
+    let manual = ComputeRequestBuilder::new(
+        rpc_provider(),
+        SIGNER_ADDRESS,
+        SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+        SystemId::Risc0,
+    )
+    .auction_length(60)
+    .reward_token_address(REWARD_TOKEN_ADDRESS)
+    .reward_token_decimals(18)
+    .proving_time(60)
+    .extra_data(verifier_details_fixture())
+    .set_token_params(1, U256::from(10), U256::from(100e18));
+    let hydrated =
+        ComputeRequestBuilder::from_template(rpc_provider(), SIGNER_ADDRESS, &template).unwrap();
+
+    let finish = |builder: ComputeRequestBuilder<_, _, _>| {
+        builder
+            .nonce(U256::from(7))
+            .start_auction_timestamp(1_000)
+            .end_auction_timestamp(1_060)
+            .system(system.clone())
+            .set_verification_commitment_params(inputs_commitment, verifier_details_fixture())
+            .build()
+            .unwrap()
+    };
+    let manual_request = finish(manual.clone());
+    let hydrated_request = finish(hydrated.clone());
+
+    // `ProofRequest` has no `PartialEq`, compare its abi encoding instead
+    assert_eq!(
+        hydrated_request.proof_request.abi_encode(),
+        manual_request.proof_request.abi_encode()
+    );
+    assert_eq!(hydrated_request.system_id, manual_request.system_id);
+
+    // capturing the builder again gives back the template, minus its overrides
+    let mut captured = hydrated.to_template();
+    captured.overrides = template.overrides.clone();
+    assert_eq!(captured, template);
+    assert_eq!(manual.to_template().request, template.request);
+}
+
+#[test]
+/// Ensures named overrides only replace the fields they set.
+fn should_apply_named_override() {
+    let template = template_fixture();
+    let urgent = template.with_override("urgent").unwrap();
+
+    assert_eq!(urgent.auction_length, 15);
+    assert_eq!(
+        urgent.request.as_ref().unwrap().max_reward_amount,
+        U256::from(200e18)
+    );
+    assert_eq!(urgent.proving_time, template.proving_time);
+    assert_eq!(urgent.reward_token_address, template.reward_token_address);
+    assert!(template.with_override("missing").is_err());
+}
+
+#[test]
+/// Ensures templates with undecodable verifier details or bad address checksums are
+/// rejected on load.
+fn should_reject_invalid_templates() {
+    let mut template = template_fixture();
+    template.extra_data = Bytes::from(vec![1u8, 2, 3]);
+    let json = serde_json::to_string(&template).unwrap();
+    assert!(IntentTemplate::from_json_str(&json).is_err());
+
+    let mut json = serde_json::to_value(template_fixture()).unwrap();
+    // flip the case of a single character, breaking the EIP-55 checksum
+    json["reward_token_address"] =
+        serde_json::Value::String("0xB54061f59AcF94f86ee414C9a220aFFE8BbE6B35".to_string());
+    assert!(IntentTemplate::from_json_str(&json.to_string()).is_err());
+
+    // lower case addresses carry no checksum and are accepted
+    json["reward_token_address"] =
+        serde_json::Value::String("0xb54061f59acf94f86ee414c9a220affe8bbe6b35".to_string());
+    assert!(IntentTemplate::from_json_str(&json.to_string()).is_ok());
+}
+
+#[test]
+/// Ensures a fixed reward sets both reward bounds and builds a fixed price request.
+fn should_build_fixed_reward_request() {
+    let builder = ComputeRequestBuilder::new(
+        rpc_provider(),
+        SIGNER_ADDRESS,
+        SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+        SystemId::Risc0,
+    )
+    .fixed_reward(U256::from(5e18));
+    assert!(builder.is_fixed_reward());
+
+    let request = builder
+        .start_auction_timestamp(1_000)
+        .end_auction_timestamp(1_060)
+        .system(serde_json::json!({ "elf": [1, 2, 3], "inputs": vec![4u8; 32] }))
+        .build()
+        .unwrap();
+
+    assert_eq!(request.proof_request.minRewardAmount, U256::from(5e18));
+    assert_eq!(request.proof_request.maxRewardAmount, U256::from(5e18));
+    assert!(RewardCurve::try_from(&request.proof_request)
+        .unwrap()
+        .is_fixed_price());
+}
+
+#[test]
+/// Ensures typed inputs are merged into the system params and misshaped ones fail the build.
+fn should_build_request_with_typed_inputs() {
+    let builder = ComputeRequestBuilder::new(
+        rpc_provider(),
+        SIGNER_ADDRESS,
+        SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+        SystemId::Risc0,
+    )
+    .system(serde_json::json!({ "elf": [1, 2, 3] }));
+
+    let request = builder.clone().inputs_bytes(vec![4; 32]).build().unwrap();
+    assert_eq!(request.system.inputs(), SystemInputs::Bytes(vec![4; 32]));
+
+    let err = builder
+        .clone()
+        .inputs_json(serde_json::json!({ "in": "1" }))
+        .build()
+        .unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("risc0 inputs must be bytes, got JSON"),
+        "unexpected error: {err}"
+    );
+
+    // not abi encoded
+    assert!(builder.inputs_bytes(vec![4, 5, 6]).build().is_err());
+}
+
+#[tokio::test]
+/// Ensures builders made for a chain target its deployed market, without asking the rpc for the
+/// chain id, and keep accepting an explicit market address.
+async fn should_build_request_for_chain() {
+    let deployments = DeploymentRegistry::builtin();
+    let builder = ComputeRequestBuilder::for_chain(
+        rpc_provider(),
+        SIGNER_ADDRESS,
+        SEPOLIA_CHAIN_ID,
+        SystemId::Risc0,
+        &deployments,
+    )
+    .unwrap();
+    assert_eq!(
+        builder.base.market_address,
+        SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS
+    );
+    // the rpc of `rpc_provider` isn't running
+    assert_eq!(builder.chain_id().await.unwrap(), SEPOLIA_CHAIN_ID);
+
+    let builder = builder.market_address(REWARD_TOKEN_ADDRESS);
+    assert_eq!(builder.base.market_address, REWARD_TOKEN_ADDRESS);
+
+    let err = ComputeRequestBuilder::for_chain(
+        rpc_provider(),
+        SIGNER_ADDRESS,
+        1,
+        SystemId::Risc0,
+        &deployments,
+    )
+    .err()
+    .unwrap();
+    assert!(
+        err.to_string()
+            .contains(&format!("known chains: {SEPOLIA_CHAIN_ID}")),
+        "{err}"
+    );
+}
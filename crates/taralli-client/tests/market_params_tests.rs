@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::json;
+use taralli_client::intent_builder::market::{MarketKind, MarketParams, MarketParamsCache};
+use taralli_client::intent_builder::offer::ComputeOfferBuilder;
+use taralli_client::intent_builder::request::ComputeRequestBuilder;
+use taralli_primitives::abi::{
+    universal_bombetta::UniversalBombetta, universal_porchetta::UniversalPorchetta,
+};
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::{address, Address, B256, U256};
+use taralli_primitives::alloy::sol_types::{SolCall, SolValue};
+use taralli_primitives::alloy::transports::http::{Client, Http};
+use taralli_primitives::alloy::utils::hex;
+use taralli_primitives::deployments::{Deployment, DeploymentRegistry};
+use taralli_primitives::intents::{
+    offer::PROOF_OFFER_WITNESS_TYPE_HASH, request::PROOF_REQUEST_WITNESS_TYPE_HASH,
+};
+use taralli_primitives::markets::MarketFee;
+use taralli_primitives::systems::SystemId;
+use taralli_primitives::utils::PERMIT2_ADDRESS;
+
+pub mod common;
+use common::rpc::{method_not_found, start_stub_node, stub_provider, StubProvider};
+
+const MARKET: Address = address!("5555555555555555555555555555555555555555");
+const CHAIN_ID: u64 = 31_337;
+
+/// Stub JSON-RPC node of a chain with a single market deployed, answering the market getters whose
+/// selectors are in `getters` and reverting on the others, recording the methods it is called with
+struct StubNode {
+    url: reqwest::Url,
+    methods: Arc<Mutex<Vec<String>>>,
+}
+
+impl StubNode {
+    async fn start(getters: HashMap<[u8; 4], Vec<u8>>) -> Self {
+        let methods = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = methods.clone();
+        let url = start_stub_node(move |request| {
+            let method = request["method"].as_str().unwrap().to_string();
+            recorded.lock().unwrap().push(method.clone());
+
+            match method.as_str() {
+                "eth_chainId" => json!({ "result": format!("{CHAIN_ID:#x}") }),
+                "eth_call" => {
+                    let call = &request["params"][0];
+                    let input = call["input"].as_str().or(call["data"].as_str()).unwrap();
+                    let input = hex::decode(input).unwrap();
+                    let selector: [u8; 4] = input[..4].try_into().unwrap();
+                    match getters.get(&selector) {
+                        Some(output) => json!({ "result": hex::encode_prefixed(output) }),
+                        None => {
+                            json!({ "error": { "code": 3, "message": "execution reverted" } })
+                        }
+                    }
+                }
+                _ => method_not_found(),
+            }
+        })
+        .await;
+
+        Self { url, methods }
+    }
+
+    /// Node with a `UniversalBombetta` deployed with `permit2` as constructor argument
+    async fn bombetta(permit2: Address) -> Self {
+        Self::start(HashMap::from([
+            (
+                UniversalBombetta::PERMIT2Call::SELECTOR,
+                permit2.abi_encode(),
+            ),
+            (
+                UniversalBombetta::PROOF_REQUEST_WITNESS_TYPE_HASHCall::SELECTOR,
+                PROOF_REQUEST_WITNESS_TYPE_HASH.abi_encode(),
+            ),
+        ]))
+        .await
+    }
+
+    fn methods(&self) -> Vec<String> {
+        self.methods.lock().unwrap().clone()
+    }
+
+    fn provider(&self) -> StubProvider {
+        stub_provider(self.url.clone())
+    }
+}
+
+#[tokio::test]
+/// Ensures the params fetched from a request market match its constructor arguments and seed the
+/// request builder, which then doesn't query the chain id again.
+async fn should_seed_request_builder_from_market() {
+    let node = StubNode::bombetta(PERMIT2_ADDRESS).await;
+    let provider = node.provider();
+
+    let params = MarketParams::fetch(&provider, MARKET).await.unwrap();
+    assert_eq!(
+        params,
+        MarketParams {
+            market_address: MARKET,
+            kind: MarketKind::Request,
+            chain_id: CHAIN_ID,
+            permit2: PERMIT2_ADDRESS,
+            witness_type_hash: *PROOF_REQUEST_WITNESS_TYPE_HASH,
+            fee: MarketFee::ZERO,
+        }
+    );
+    let fetched = node.methods().len();
+
+    let builder: ComputeRequestBuilder<Http<Client>, StubProvider, Ethereum> =
+        ComputeRequestBuilder::new(
+            provider.clone(),
+            Address::ZERO,
+            Address::ZERO,
+            SystemId::Risc0,
+        )
+        .with_market_defaults(&params)
+        .unwrap();
+    assert_eq!(builder.base.market_address, MARKET);
+    assert_eq!(builder.chain_id().await.unwrap(), CHAIN_ID);
+    assert_eq!(node.methods().len(), fetched);
+
+    // a request market can't back an offer builder
+    let err = ComputeOfferBuilder::new(provider, Address::ZERO, Address::ZERO, SystemId::Risc0)
+        .with_market_defaults(&params)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("not Offer intents"), "{err}");
+}
+
+#[tokio::test]
+/// Ensures offer markets are told apart from request markets by their witness type hash getter.
+async fn should_detect_offer_market() {
+    let node = StubNode::start(HashMap::from([
+        (
+            UniversalPorchetta::PERMIT2Call::SELECTOR,
+            PERMIT2_ADDRESS.abi_encode(),
+        ),
+        (
+            UniversalPorchetta::PROOF_OFFER_WITNESS_TYPE_HASHCall::SELECTOR,
+            PROOF_OFFER_WITNESS_TYPE_HASH.abi_encode(),
+        ),
+    ]))
+    .await;
+
+    let params = MarketParams::fetch(&node.provider(), MARKET).await.unwrap();
+    assert_eq!(params.kind, MarketKind::Offer);
+    assert!(params.ensure_compatible(MarketKind::Offer).is_ok());
+
+    // neither getter answers on a contract that isn't a market
+    let node = StubNode::start(HashMap::new()).await;
+    let err = MarketParams::fetch(&node.provider(), MARKET)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("neither"), "{err}");
+}
+
+#[tokio::test]
+/// Ensures markets settling through another permit2 or expecting another witness are rejected
+/// before anything is signed for them.
+async fn should_reject_incompatible_market() {
+    let other_permit2 = address!("6666666666666666666666666666666666666666");
+    let node = StubNode::bombetta(other_permit2).await;
+    let provider = node.provider();
+
+    let params = MarketParams::fetch(&provider, MARKET).await.unwrap();
+    assert_eq!(params.permit2, other_permit2);
+    let err = ComputeRequestBuilder::new(provider, Address::ZERO, MARKET, SystemId::Risc0)
+        .with_market_defaults(&params)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("uses permit2"), "{err}");
+
+    let params = MarketParams {
+        permit2: PERMIT2_ADDRESS,
+        witness_type_hash: B256::ZERO,
+        ..params
+    };
+    let err = params.ensure_compatible(MarketKind::Request).unwrap_err();
+    assert!(err.to_string().contains("witness type hash"), "{err}");
+}
+
+#[tokio::test]
+/// Ensures params are reused within the cache ttl and refetched after it.
+async fn should_cache_params_per_market() {
+    let node = StubNode::bombetta(PERMIT2_ADDRESS).await;
+    let provider = node.provider();
+    let cache = MarketParamsCache::new(Duration::from_millis(200));
+
+    let params = cache.get(&provider, MARKET).await.unwrap();
+    let fetched = node.methods().len();
+    assert_eq!(cache.get(&provider, MARKET).await.unwrap(), params);
+    assert_eq!(node.methods().len(), fetched);
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    cache.get(&provider, MARKET).await.unwrap();
+    assert_eq!(node.methods().len(), 2 * fetched);
+}
+
+#[tokio::test]
+/// Ensures the fee of a market is read from its deployment and carried by the request builder,
+/// whose net rewards are grossed up by it.
+async fn should_read_market_fee_from_registry() {
+    let node = StubNode::bombetta(PERMIT2_ADDRESS).await;
+    let provider = node.provider();
+    let deployment = Deployment {
+        universal_bombetta: MARKET,
+        universal_porchetta: Address::ZERO,
+        universal_bombetta_fee: MarketFee::from_bps(250),
+        universal_porchetta_fee: MarketFee::ZERO,
+        permit2: PERMIT2_ADDRESS,
+        verifiers: HashMap::new(),
+    };
+
+    let params = MarketParams::fetch(&provider, MARKET).await.unwrap();
+    let err = params
+        .clone()
+        .with_registry_fee(&DeploymentRegistry::builtin())
+        .unwrap_err();
+    assert!(err.to_string().contains(&CHAIN_ID.to_string()), "{err}");
+    let params = params
+        .with_registry_fee(&DeploymentRegistry::builtin().with_deployment(CHAIN_ID, deployment))
+        .unwrap();
+    assert_eq!(params.fee, MarketFee::from_bps(250));
+    assert_eq!(params.fee.to_string(), "2.50%");
+
+    let builder: ComputeRequestBuilder<Http<Client>, StubProvider, Ethereum> =
+        ComputeRequestBuilder::new(provider, Address::ZERO, Address::ZERO, SystemId::Risc0)
+            .with_market_defaults(&params)
+            .unwrap()
+            .set_net_reward_amounts(U256::from(975), U256::from(1_950));
+    assert_eq!(builder.min_reward_amount, U256::from(999));
+    assert_eq!(builder.max_reward_amount, U256::from(1_999));
+    assert_eq!(builder.max_net_reward(), U256::from(1_950));
+}
@@ -0,0 +1,223 @@
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use taralli_client::market_state::{AuctionState, MarketStateClient};
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta;
+use taralli_primitives::abi::universal_porchetta::UniversalPorchetta;
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::{Address, Bytes, B256, U256};
+use taralli_primitives::alloy::sol_types::{SolEvent, SolValue};
+use taralli_primitives::alloy::transports::http::{Client, Http};
+use taralli_primitives::alloy::utils::hex;
+
+pub mod common;
+use common::rpc::{start_stub_node, stub_provider, StubProvider};
+
+const MARKET: Address = Address::repeat_byte(0x0c);
+const REQUESTER: Address = Address::repeat_byte(0x0a);
+const PROVIDER: Address = Address::repeat_byte(0x0b);
+const REWARD_TOKEN: Address = Address::repeat_byte(0x0d);
+const INTENT_ID: B256 = B256::repeat_byte(0x11);
+const BID_BLOCK: u64 = 12;
+const END_AUCTION: u64 = 1_000;
+
+type StateClient = MarketStateClient<Http<Client>, StubProvider, Ethereum>;
+
+/// Stub JSON-RPC node of a market whose active data of every intent is `active`, serving `bid`
+/// and `resolve` as the logs of the market's bid and resolve events
+async fn start_node(active: Value, bid: Option<Value>, resolve: Option<Value>) -> StateClient {
+    let logs = Arc::new((bid, resolve));
+    let url = start_stub_node(move |request| {
+        let params = &request["params"];
+        let result = match request["method"].as_str().unwrap() {
+            "eth_chainId" => json!("0x1"),
+            "eth_call" => active.clone(),
+            "eth_getLogs" => {
+                let (bid, resolve) = &*logs;
+                let topic = &params[0]["topics"][0];
+                let log = if *topic == json!(UniversalBombetta::Bid::SIGNATURE_HASH)
+                    || *topic == json!(UniversalPorchetta::Bid::SIGNATURE_HASH)
+                {
+                    bid
+                } else {
+                    // both markets' resolve events share their signature
+                    assert_eq!(*topic, json!(UniversalBombetta::Resolve::SIGNATURE_HASH));
+                    resolve
+                };
+                json!(log.iter().collect::<Vec<_>>())
+            }
+            method => panic!("unexpected method {method}"),
+        };
+        json!({ "result": result })
+    })
+    .await;
+    MarketStateClient::new(stub_provider(url), MARKET)
+}
+
+fn hex_json(bytes: impl AsRef<[u8]>) -> Value {
+    json!(hex::encode_prefixed(bytes))
+}
+
+/// `activeProofRequestData` of a request bid upon by `provider`, unbid for the zero address
+fn active_request_data(requester: Address, provider: Address) -> Value {
+    hex_json(
+        (
+            requester,
+            provider,
+            U256::from(2_000_000_000u64),
+            REWARD_TOKEN,
+            U256::from(1000),
+            U256::from(7),
+            B256::ZERO,
+            Bytes::new(),
+        )
+            .abi_encode_params(),
+    )
+}
+
+/// Log of `event` as served by `eth_getLogs`
+fn log_json(event: &impl SolEvent, block: u64) -> Value {
+    let data = event.encode_log_data();
+    json!({
+        "address": MARKET,
+        "topics": data.topics(),
+        "data": hex_json(&data.data),
+        "blockHash": B256::repeat_byte(0x01),
+        "blockNumber": format!("{block:#x}"),
+        "transactionHash": B256::repeat_byte(0x42),
+        "transactionIndex": "0x0",
+        "logIndex": "0x0",
+        "removed": false
+    })
+}
+
+fn bid_log() -> Value {
+    log_json(
+        &UniversalBombetta::Bid {
+            signer: REQUESTER,
+            requestId: INTENT_ID,
+            rewardToken: REWARD_TOKEN,
+            rewardAmount: U256::from(1000),
+            ethStake: U256::from(7),
+            provider: PROVIDER,
+        },
+        BID_BLOCK,
+    )
+}
+
+fn resolve_log() -> Value {
+    log_json(
+        &UniversalBombetta::Resolve {
+            signer: REQUESTER,
+            requestId: INTENT_ID,
+            resolver: PROVIDER,
+        },
+        BID_BLOCK + 5,
+    )
+}
+
+#[tokio::test]
+/// Ensures a request absent from the market is open until the end of its auction, expired after.
+async fn should_derive_unbid_state_from_auction_end() {
+    let client = start_node(
+        active_request_data(Address::ZERO, Address::ZERO),
+        None,
+        None,
+    )
+    .await;
+
+    let state = client
+        .request_state(INTENT_ID, END_AUCTION, END_AUCTION)
+        .await
+        .unwrap();
+    assert_eq!(state, AuctionState::Open);
+    assert!(state.is_open());
+
+    let state = client
+        .request_state(INTENT_ID, END_AUCTION, END_AUCTION + 1)
+        .await
+        .unwrap();
+    assert_eq!(state, AuctionState::Expired);
+    assert!(!state.is_bid());
+}
+
+#[tokio::test]
+/// Ensures a bid request reports its prover, reward and the block of its bid event, the block
+/// being unknown when the event isn't served.
+async fn should_report_bid_state() {
+    let client = start_node(
+        active_request_data(REQUESTER, PROVIDER),
+        Some(bid_log()),
+        None,
+    )
+    .await;
+    let state = client
+        .request_state(INTENT_ID, END_AUCTION, END_AUCTION + 1)
+        .await
+        .unwrap();
+    assert_eq!(
+        state,
+        AuctionState::Bid {
+            bidder: PROVIDER,
+            prover: PROVIDER,
+            amount: U256::from(1000),
+            at_block: Some(BID_BLOCK),
+        }
+    );
+    assert!(state.is_bid());
+
+    let client = start_node(active_request_data(REQUESTER, PROVIDER), None, None).await;
+    let state = client
+        .request_state(INTENT_ID, END_AUCTION, 0)
+        .await
+        .unwrap();
+    assert!(matches!(state, AuctionState::Bid { at_block: None, .. }));
+}
+
+#[tokio::test]
+/// Ensures a bid request whose resolve event is served is reported resolved, its bid remaining in
+/// the market's state.
+async fn should_report_resolved_state() {
+    let client = start_node(
+        active_request_data(REQUESTER, PROVIDER),
+        Some(bid_log()),
+        Some(resolve_log()),
+    )
+    .await;
+    let state = client
+        .request_state(INTENT_ID, END_AUCTION, END_AUCTION + 1)
+        .await
+        .unwrap();
+    assert_eq!(state, AuctionState::Resolved);
+    assert!(state.is_bid());
+}
+
+#[tokio::test]
+/// Ensures an offer's bidder is its requester while its prover is the offering provider.
+async fn should_report_offer_bidder_and_prover() {
+    let active_offer_data = hex_json(
+        (
+            PROVIDER,
+            REQUESTER,
+            U256::from(2_000_000_000u64),
+            REWARD_TOKEN,
+            U256::from(500),
+            REWARD_TOKEN,
+            U256::from(9),
+            B256::ZERO,
+            Bytes::new(),
+        )
+            .abi_encode_params(),
+    );
+    let client = start_node(active_offer_data, None, None).await;
+    let state = client.offer_state(INTENT_ID, END_AUCTION, 0).await.unwrap();
+    assert_eq!(
+        state,
+        AuctionState::Bid {
+            bidder: REQUESTER,
+            prover: PROVIDER,
+            amount: U256::from(500),
+            at_block: None,
+        }
+    );
+}
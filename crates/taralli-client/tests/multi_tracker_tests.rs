@@ -0,0 +1,235 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use taralli_client::api::retry::RetryPolicy;
+use taralli_client::error::{ClientError, Result};
+use taralli_client::tracker::multi::{
+    MarketEventSource, MultiTracker, TrackingEvent, TrackingEventStream,
+};
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta;
+use taralli_primitives::alloy::primitives::{Address, B256, U256};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::UnboundedReceiverStream;
+
+const REQUESTER: Address = Address::repeat_byte(0x0a);
+const REWARD_TOKEN: Address = Address::repeat_byte(0x0d);
+const INTENTS: u8 = 100;
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Mocked market whose events are sent through a channel per installation of its filters,
+/// failing to install them once every channel was handed out
+struct ChannelSource {
+    receivers: Mutex<VecDeque<mpsc::UnboundedReceiver<TrackingEvent>>>,
+}
+
+#[async_trait]
+impl MarketEventSource for ChannelSource {
+    async fn events(&self) -> Result<TrackingEventStream> {
+        let receiver = self
+            .receivers
+            .lock()
+            .unwrap()
+            .pop_front()
+            .ok_or_else(|| ClientError::TrackIntentError("filter limit reached".to_string()))?;
+        Ok(Box::pin(UnboundedReceiverStream::new(receiver)))
+    }
+}
+
+/// Tracker over a market whose filters can be installed `installs` times, along with the sender
+/// of the events of each installation
+async fn start_tracker_with_installs(
+    installs: usize,
+) -> (MultiTracker, Vec<mpsc::UnboundedSender<TrackingEvent>>) {
+    let (senders, receivers): (Vec<_>, VecDeque<_>) =
+        (0..installs).map(|_| mpsc::unbounded_channel()).unzip();
+    let source = ChannelSource {
+        receivers: Mutex::new(receivers),
+    };
+    let restart_policy = RetryPolicy {
+        max_retries: 2,
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(10),
+        deadline: TIMEOUT,
+    };
+    let tracker = MultiTracker::start_with_restart_policy(source, restart_policy)
+        .await
+        .unwrap();
+    (tracker, senders)
+}
+
+async fn start_tracker() -> (MultiTracker, mpsc::UnboundedSender<TrackingEvent>) {
+    let (tracker, mut senders) = start_tracker_with_installs(1).await;
+    (tracker, senders.remove(0))
+}
+
+fn intent_id(index: u8) -> B256 {
+    B256::repeat_byte(index)
+}
+
+fn provider(index: u8) -> Address {
+    Address::repeat_byte(index)
+}
+
+fn resolve_tx_hash(index: u8) -> B256 {
+    B256::left_padding_from(&[0xee, index])
+}
+
+fn bid(index: u8) -> TrackingEvent {
+    TrackingEvent::Bid(UniversalBombetta::Bid {
+        signer: REQUESTER,
+        requestId: intent_id(index),
+        rewardToken: REWARD_TOKEN,
+        rewardAmount: U256::from(index),
+        ethStake: U256::ZERO,
+        provider: provider(index),
+    })
+}
+
+fn resolve(index: u8) -> TrackingEvent {
+    TrackingEvent::Resolve {
+        resolve_event: UniversalBombetta::Resolve {
+            signer: REQUESTER,
+            requestId: intent_id(index),
+            resolver: provider(index),
+        },
+        tx_hash: resolve_tx_hash(index),
+    }
+}
+
+#[tokio::test]
+/// Ensures the events of 100 requests sharing one event stream reach each request's handle and
+/// the consolidated stream, events of unregistered requests being dropped, and resolved requests
+/// no longer being tracked.
+async fn should_demux_events_to_tracked_intents() {
+    let (tracker, market) = start_tracker().await;
+    let handles: Vec<_> = (1..=INTENTS)
+        .map(|index| tracker.register(intent_id(index)).unwrap())
+        .collect();
+    assert_eq!(tracker.tracked(), usize::from(INTENTS));
+    let consolidated = tracker.events();
+
+    // bids in reverse order, each request resolved right after the next one was bid on
+    for index in (1..=INTENTS).rev() {
+        market.send(bid(index)).unwrap();
+        if index < INTENTS {
+            market.send(resolve(index + 1)).unwrap();
+        }
+        // unregistered
+        market.send(bid(index + INTENTS)).unwrap();
+    }
+    market.send(resolve(1)).unwrap();
+
+    for (index, handle) in (1..=INTENTS).zip(&handles) {
+        let bid_event = handle.track_auction(TIMEOUT).await.unwrap().unwrap();
+        assert_eq!(bid_event.requestId, intent_id(index));
+        assert_eq!(bid_event.provider, provider(index));
+        assert_eq!(bid_event.rewardAmount, U256::from(index));
+
+        let (resolve_event, tx_hash) = handle
+            .track_resolve_with_tx_hash(TIMEOUT)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(resolve_event.requestId, intent_id(index));
+        assert_eq!(resolve_event.resolver, provider(index));
+        assert_eq!(tx_hash, resolve_tx_hash(index));
+    }
+    assert_eq!(tracker.tracked(), 0);
+
+    let events: Vec<_> = tokio::time::timeout(
+        TIMEOUT,
+        consolidated.take(2 * usize::from(INTENTS)).collect(),
+    )
+    .await
+    .unwrap();
+    let mut per_intent: HashMap<B256, Vec<TrackingEvent>> = HashMap::new();
+    for (intent_id, event) in events {
+        assert_eq!(event.intent_id(), intent_id);
+        per_intent.entry(intent_id).or_default().push(event);
+    }
+    assert_eq!(per_intent.len(), usize::from(INTENTS));
+    for index in 1..=INTENTS {
+        let events = &per_intent[&intent_id(index)];
+        assert!(matches!(
+            events.as_slice(),
+            [TrackingEvent::Bid(_), TrackingEvent::Resolve { .. }]
+        ));
+    }
+
+    // resolved requests keep their events
+    assert!(handles[0].track_auction(TIMEOUT).await.unwrap().is_some());
+}
+
+#[tokio::test]
+/// Ensures requests are deregistered once every handle to them is dropped or they're deregistered
+/// explicitly, awaiting handles timing out without an event and failing once no longer tracked.
+async fn should_clean_up_intents_given_up_on() {
+    let (tracker, market) = start_tracker().await;
+
+    let handles: Vec<_> = (1..=INTENTS)
+        .map(|index| tracker.register(intent_id(index)).unwrap())
+        .collect();
+    let second = tracker.register(intent_id(1)).unwrap();
+    assert_eq!(tracker.tracked(), usize::from(INTENTS));
+
+    // auctions ending without a bid
+    assert!(handles[1]
+        .track_auction(Duration::from_millis(50))
+        .await
+        .unwrap()
+        .is_none());
+    drop(handles);
+    // still awaited through another handle
+    assert_eq!(tracker.tracked(), 1);
+
+    market.send(bid(1)).unwrap();
+    assert!(second.track_auction(TIMEOUT).await.unwrap().is_some());
+    tracker.deregister(intent_id(1));
+    assert_eq!(tracker.tracked(), 0);
+    let err = second
+        .track_resolve_with_tx_hash(TIMEOUT)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ClientError::TrackIntentError(_)), "{err}");
+    drop(second);
+
+    // the market's event stream ending
+    let handle = tracker.register(intent_id(2)).unwrap();
+    drop(market);
+    let err = handle.track_auction(TIMEOUT).await.unwrap_err();
+    assert!(matches!(err, ClientError::TrackIntentError(_)), "{err}");
+    assert_eq!(tracker.tracked(), 0);
+}
+
+#[tokio::test]
+/// Ensures the filters are installed again once the market's event stream ends, the requests
+/// registered meanwhile still getting their events, and that once they can't be, pending waits
+/// and later registrations fail rather than wait for events no loop delivers.
+async fn should_fail_registering_after_event_stream_ends() {
+    let (tracker, mut markets) = start_tracker_with_installs(2).await;
+    let handle = tracker.register(intent_id(1)).unwrap();
+
+    // the first filters dropped by the rpc
+    drop(markets.remove(0));
+    markets[0].send(bid(1)).unwrap();
+    assert!(handle.track_auction(TIMEOUT).await.unwrap().is_some());
+    assert!(tracker.is_alive());
+
+    // the filters can't be installed again
+    let pending = tracker.register(intent_id(2)).unwrap();
+    drop(markets);
+    let err = pending.track_auction(TIMEOUT).await.unwrap_err();
+    assert!(
+        matches!(&err, ClientError::TrackIntentError(msg) if msg.contains("event stream ended")),
+        "{err}"
+    );
+    assert!(!tracker.is_alive());
+    assert_eq!(tracker.tracked(), 0);
+
+    let err = tracker.register(intent_id(3)).err().unwrap();
+    assert!(matches!(err, ClientError::TrackIntentError(_)), "{err}");
+    assert_eq!(tracker.tracked(), 0);
+}
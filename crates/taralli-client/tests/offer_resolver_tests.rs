@@ -0,0 +1,83 @@
+//! Calldata of porchetta resolves. The rust tests have no anvil harness to deploy the markets on,
+//! the market executing this calldata end to end is covered by the foundry tests instead
+//! (`contracts/test/unit/UniversalPorchetta.t.sol`), so these check the resolver sends exactly the
+//! call those tests make.
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use taralli_client::error::Result;
+use taralli_client::resolver::offer::{ComputeOfferResolver, OfferResolveParams};
+use taralli_client::resolver::IntentResolver;
+use taralli_client::submitter::TransactionSubmitter;
+use taralli_client::worker::WorkResult;
+use taralli_primitives::abi::universal_porchetta::UniversalPorchetta;
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::{Address, Bytes, TxKind, B256};
+use taralli_primitives::alloy::providers::{ProviderBuilder, RootProvider};
+use taralli_primitives::alloy::rpc::types::{TransactionReceipt, TransactionRequest};
+use taralli_primitives::alloy::sol_types::SolCall;
+use taralli_primitives::alloy::transports::http::{Client, Http};
+
+pub mod common;
+use common::fixtures::receipt_fixture;
+
+type StubProvider = RootProvider<Http<Client>>;
+
+const MARKET: Address = Address::repeat_byte(0x0c);
+const OFFER_ID: B256 = B256::repeat_byte(0x11);
+
+/// Stub submitter recording the transactions it sends
+#[derive(Default)]
+struct RecordingSubmitter {
+    transactions: Mutex<Vec<TransactionRequest>>,
+}
+
+#[async_trait]
+impl TransactionSubmitter<Ethereum> for RecordingSubmitter {
+    async fn send_and_wait_receipt(
+        &self,
+        transaction: TransactionRequest,
+    ) -> Result<TransactionReceipt> {
+        self.transactions.lock().unwrap().push(transaction);
+        Ok(receipt_fixture(B256::repeat_byte(0xaa)))
+    }
+}
+
+fn resolver(
+    submitter: Arc<RecordingSubmitter>,
+) -> ComputeOfferResolver<Http<Client>, StubProvider, Ethereum> {
+    // never reached, the resolves going through the recording submitter
+    let provider = ProviderBuilder::new().on_http("http://127.0.0.1:1".parse().unwrap());
+    ComputeOfferResolver::new(provider, MARKET).with_submitter(submitter)
+}
+
+#[tokio::test]
+/// Ensures the params of an offer resolve are sent to the market as
+/// `resolve(offerId, opaqueSubmission)`, the worker's partial commitment being left out.
+async fn should_resolve_offer_with_params_calldata() {
+    let work_result = WorkResult {
+        opaque_submission: Bytes::from(vec![0xab; 96]),
+        partial_commitment: B256::repeat_byte(0x33),
+    };
+    let params = OfferResolveParams::from(&work_result);
+    assert_eq!(params, OfferResolveParams::from(work_result.clone()));
+
+    let submitter = Arc::new(RecordingSubmitter::default());
+    resolver(submitter.clone())
+        .resolve_intent(OFFER_ID, &params)
+        .await
+        .unwrap();
+
+    let transaction = submitter.transactions.lock().unwrap().pop().unwrap();
+    assert_eq!(transaction.to, Some(TxKind::Call(MARKET)));
+    let expected = UniversalPorchetta::resolveCall {
+        offerId: OFFER_ID,
+        opaqueSubmission: work_result.opaque_submission,
+    }
+    .abi_encode();
+    assert_eq!(
+        transaction.input.input().cloned().unwrap_or_default(),
+        Bytes::from(expected)
+    );
+}
@@ -0,0 +1,118 @@
+use std::str::FromStr;
+
+use taralli_client::client::provider::offering::sign_offer;
+use taralli_primitives::abi::universal_porchetta::{
+    ProofOfferVerifierDetails, UniversalPorchetta::ProofOffer,
+};
+use taralli_primitives::alloy::primitives::{
+    address, fixed_bytes, Address, PrimitiveSignature, B256, U256,
+};
+use taralli_primitives::alloy::signers::{local::PrivateKeySigner, Signer};
+use taralli_primitives::alloy::sol_types::SolValue;
+use taralli_primitives::intents::offer::ComputeOffer;
+use taralli_primitives::markets::SEPOLIA_CHAIN_ID;
+use taralli_primitives::systems::risc0::Risc0ProofParams;
+use taralli_primitives::systems::{SystemId, SystemParams};
+use taralli_primitives::validation::offer::validate_offer_signature;
+
+const DUMMY_PRIV_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+const OTHER: Address = address!("9999999999999999999999999999999999999999");
+
+fn offer_fixture(signer: Address) -> ComputeOffer<SystemParams> {
+    ComputeOffer {
+        system_id: SystemId::Risc0,
+        system: SystemParams::try_from((
+            &SystemId::Risc0,
+            serde_json::to_vec(&Risc0ProofParams {
+                elf: vec![1, 2, 3],
+                inputs: vec![4; 32],
+            })
+            .unwrap(),
+        ))
+        .unwrap(),
+        proof_offer: ProofOffer {
+            signer,
+            market: address!("2222222222222222222222222222222222222222"),
+            nonce: U256::from(7),
+            rewardToken: address!("3333333333333333333333333333333333333333"),
+            rewardAmount: U256::from(1000),
+            stakeToken: address!("5555555555555555555555555555555555555555"),
+            stakeAmount: U256::from(100),
+            startAuctionTimestamp: 1_700_000_000,
+            endAuctionTimestamp: 1_700_000_060,
+            provingTime: 30,
+            inputsCommitment: B256::repeat_byte(0x44),
+            extraData: ProofOfferVerifierDetails {
+                verifier: address!("4444444444444444444444444444444444444444"),
+                selector: fixed_bytes!("deadbeef"),
+                isShaCommitment: false,
+                inputsOffset: U256::ZERO,
+                inputsLength: U256::from(96),
+            }
+            .abi_encode()
+            .into(),
+        },
+        signature: PrimitiveSignature::new(U256::from(1), U256::from(2), false),
+    }
+}
+
+async fn signed_offer_fixture() -> ComputeOffer<SystemParams> {
+    let signer = PrivateKeySigner::from_str(DUMMY_PRIV_KEY).unwrap();
+    sign_offer(&signer, offer_fixture(signer.address()), SEPOLIA_CHAIN_ID)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+/// Ensures a signed offer passes signature validation on the chain it was signed for only.
+async fn should_validate_signed_offer() {
+    let offer = signed_offer_fixture().await;
+
+    assert!(
+        validate_offer_signature(&offer.proof_offer, &offer.signature, SEPOLIA_CHAIN_ID).is_ok()
+    );
+    assert!(validate_offer_signature(&offer.proof_offer, &offer.signature, 1).is_err());
+}
+
+#[tokio::test]
+/// Ensures tampering with any ProofOffer field invalidates the signature.
+async fn should_reject_tampered_offer() {
+    let offer = signed_offer_fixture().await;
+    let tampers: Vec<(&str, fn(&mut ProofOffer))> = vec![
+        ("signer", |o| o.signer = OTHER),
+        ("market", |o| o.market = OTHER),
+        ("nonce", |o| o.nonce += U256::from(1)),
+        ("rewardToken", |o| o.rewardToken = OTHER),
+        ("rewardAmount", |o| o.rewardAmount += U256::from(1)),
+        ("stakeToken", |o| o.stakeToken = OTHER),
+        ("stakeAmount", |o| o.stakeAmount += U256::from(1)),
+        ("startAuctionTimestamp", |o| o.startAuctionTimestamp += 1),
+        ("endAuctionTimestamp", |o| o.endAuctionTimestamp += 1),
+        ("provingTime", |o| o.provingTime += 1),
+        ("inputsCommitment", |o| {
+            o.inputsCommitment = B256::repeat_byte(0x45)
+        }),
+        ("extraData", |o| {
+            o.extraData = [o.extraData.to_vec(), vec![0]].concat().into()
+        }),
+    ];
+
+    for (field, tamper) in tampers {
+        let mut proof_offer = offer.proof_offer.clone();
+        tamper(&mut proof_offer);
+        assert!(
+            validate_offer_signature(&proof_offer, &offer.signature, SEPOLIA_CHAIN_ID).is_err(),
+            "tampered {field} still validates"
+        );
+    }
+}
+
+#[tokio::test]
+/// Ensures offers whose verifier details don't decode are not signed.
+async fn should_refuse_to_sign_undecodable_verifier_details() {
+    let signer = PrivateKeySigner::from_str(DUMMY_PRIV_KEY).unwrap();
+    let mut offer = offer_fixture(signer.address());
+    offer.proof_offer.extraData = vec![0u8; 31].into();
+
+    assert!(sign_offer(&signer, offer, SEPOLIA_CHAIN_ID).await.is_err());
+}
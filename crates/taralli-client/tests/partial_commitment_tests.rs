@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use taralli_client::error::{ClientError, Result};
+use taralli_client::resolver::offer::{ComputeOfferResolver, OfferResolveParams};
+use taralli_client::resolver::request::ComputeRequestResolver;
+use taralli_client::resolver::IntentResolver;
+use taralli_client::submitter::TransactionSubmitter;
+use taralli_client::worker::{ComputeWorker, WorkResult, WorkerManager};
+use taralli_primitives::abi::universal_bombetta::{ProofRequestVerifierDetails, UniversalBombetta};
+use taralli_primitives::abi::universal_porchetta::UniversalPorchetta;
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::{fixed_bytes, Address, Bytes, B256, U256};
+use taralli_primitives::alloy::providers::{ProviderBuilder, RootProvider};
+use taralli_primitives::alloy::rpc::types::{TransactionReceipt, TransactionRequest};
+use taralli_primitives::alloy::sol_types::{SolCall, SolValue};
+use taralli_primitives::alloy::transports::http::{Client, Http};
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::systems::{SystemId, SystemParams};
+
+pub mod common;
+use common::fixtures::{receipt_fixture, request_fixture};
+
+type Request = ComputeRequest<SystemParams>;
+type StubProvider = RootProvider<Http<Client>>;
+
+const MARKET: Address = Address::repeat_byte(0x0c);
+const INTENT_ID: B256 = B256::repeat_byte(0x11);
+const PARTIAL_COMMITMENT: B256 = B256::repeat_byte(0x33);
+
+/// Stub submitter recording the calldata of the transactions it sends
+#[derive(Default)]
+struct RecordingSubmitter {
+    calldata: Mutex<Vec<Bytes>>,
+}
+
+#[async_trait]
+impl TransactionSubmitter<Ethereum> for RecordingSubmitter {
+    async fn send_and_wait_receipt(
+        &self,
+        transaction: TransactionRequest,
+    ) -> Result<TransactionReceipt> {
+        let calldata = transaction.input.input().cloned().unwrap_or_default();
+        self.calldata.lock().unwrap().push(calldata);
+        Ok(receipt_fixture(B256::repeat_byte(0xaa)))
+    }
+}
+
+/// Worker proving nothing, only telling whether it produces partial commitments
+struct StubWorker {
+    produces_partial_commitment: bool,
+}
+
+#[async_trait]
+impl ComputeWorker<Request> for StubWorker {
+    async fn execute(&self, _intent: &Request) -> Result<WorkResult> {
+        unreachable!("only the partial commitment support is checked")
+    }
+
+    fn produces_partial_commitment(&self) -> bool {
+        self.produces_partial_commitment
+    }
+}
+
+fn provider() -> StubProvider {
+    // never reached, the resolves going through the recording submitter
+    ProviderBuilder::new().on_http("http://127.0.0.1:1".parse().unwrap())
+}
+
+fn work_result() -> WorkResult {
+    WorkResult {
+        opaque_submission: Bytes::from(vec![7u8; 96]),
+        partial_commitment: PARTIAL_COMMITMENT,
+    }
+}
+
+fn worker_manager(produces_partial_commitment: bool) -> WorkerManager<Request> {
+    WorkerManager::new(HashMap::from([(
+        SystemId::Risc0,
+        Arc::new(StubWorker {
+            produces_partial_commitment,
+        }) as Arc<dyn ComputeWorker<Request> + Send + Sync>,
+    )]))
+}
+
+fn committing_request(has_partial_commitment_result_check: bool) -> Request {
+    let verifier_details = ProofRequestVerifierDetails {
+        verifier: Address::repeat_byte(0x0f),
+        selector: fixed_bytes!("deadbeef"),
+        isShaCommitment: true,
+        inputsOffset: U256::from(32),
+        inputsLength: U256::from(64),
+        hasPartialCommitmentResultCheck: has_partial_commitment_result_check,
+        submittedPartialCommitmentResultOffset: U256::from(32),
+        submittedPartialCommitmentResultLength: U256::from(64),
+        predeterminedPartialCommitment: B256::repeat_byte(0x22),
+    };
+    let mut request = request_fixture();
+    request.proof_request.market = MARKET;
+    request.proof_request.inputsCommitment = B256::repeat_byte(0x44);
+    request.proof_request.extraData = verifier_details.abi_encode().into();
+    request
+}
+
+#[tokio::test]
+/// Ensures request resolves carry the worker's partial commitment in the market's calldata,
+/// offer resolves having none to carry.
+async fn should_resolve_with_partial_commitment() {
+    let submitter = Arc::new(RecordingSubmitter::default());
+    let resolver: ComputeRequestResolver<Http<Client>, StubProvider, Ethereum> =
+        ComputeRequestResolver::new(provider(), MARKET).with_submitter(submitter.clone());
+    resolver
+        .resolve_intent(INTENT_ID, &work_result())
+        .await
+        .unwrap();
+
+    let calldata = submitter.calldata.lock().unwrap().pop().unwrap();
+    let resolve = UniversalBombetta::resolveCall::abi_decode(&calldata, true).unwrap();
+    assert_eq!(resolve.requestId, INTENT_ID);
+    assert_eq!(resolve.opaqueSubmission, work_result().opaque_submission);
+    assert_eq!(resolve.submittedPartialCommitment, PARTIAL_COMMITMENT);
+
+    let resolver: ComputeOfferResolver<Http<Client>, StubProvider, Ethereum> =
+        ComputeOfferResolver::new(provider(), MARKET).with_submitter(submitter.clone());
+    resolver
+        .resolve_intent(INTENT_ID, &OfferResolveParams::from(work_result()))
+        .await
+        .unwrap();
+
+    let calldata = submitter.calldata.lock().unwrap().pop().unwrap();
+    let resolve = UniversalPorchetta::resolveCall::abi_decode(&calldata, true).unwrap();
+    assert_eq!(resolve.offerId, INTENT_ID);
+    assert_eq!(resolve.opaqueSubmission, work_result().opaque_submission);
+}
+
+#[test]
+/// Ensures requests checking a partial commitment result are only taken by providers whose worker
+/// produces partial commitments, requests without the check by any.
+fn should_refuse_partial_commitment_requests_without_capable_worker() {
+    let checked = committing_request(true);
+    let unchecked = committing_request(false);
+
+    worker_manager(true)
+        .ensure_partial_commitment(&checked)
+        .unwrap();
+    let err = worker_manager(false)
+        .ensure_partial_commitment(&checked)
+        .unwrap_err();
+    assert!(
+        matches!(err, ClientError::IntentAnalysisError(ref reason) if reason.contains("partial commitment")),
+        "{err}"
+    );
+    // no worker for the system at all
+    WorkerManager::<Request>::new(HashMap::new())
+        .ensure_partial_commitment(&checked)
+        .unwrap_err();
+
+    worker_manager(false)
+        .ensure_partial_commitment(&unchecked)
+        .unwrap();
+}
@@ -0,0 +1,247 @@
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde_json::json;
+use taralli_client::client::requester::allowance::{
+    AllowanceShortfall, Permit2AllowanceChecker, RewardFunding,
+};
+use taralli_client::error::{ClientError, Result};
+use taralli_client::submitter::TransactionSubmitter;
+use taralli_primitives::abi::erc20::IERC20;
+use taralli_primitives::abi::permit2::Permit2;
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta::ProofRequest;
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::{address, Address, Bytes, TxKind, B256, U256};
+use taralli_primitives::alloy::rpc::types::{TransactionReceipt, TransactionRequest};
+use taralli_primitives::alloy::sol_types::{SolCall, SolValue};
+use taralli_primitives::alloy::utils::hex;
+use taralli_primitives::utils::PERMIT2_ADDRESS;
+
+pub mod common;
+use common::rpc::{block_json, method_not_found, start_stub_node, stub_provider, StubProvider};
+
+const REQUESTER: Address = address!("f39fd6e51aad88f6f4ce6ab8827279cfffb92266");
+const MARKET: Address = address!("1111111111111111111111111111111111111111");
+const TOKEN: Address = address!("2222222222222222222222222222222222222222");
+const MAX_REWARD: u64 = 1_000;
+const NONCE: u64 = 259;
+const END_AUCTION_TIMESTAMP: u64 = 10_060;
+
+/// Start a stub JSON-RPC node answering the ERC20 `allowance` and `balanceOf`, permit2
+/// `nonceBitmap` and latest block calls from `funding`
+async fn start_funding_node(funding: Arc<Mutex<RewardFunding>>) -> StubProvider {
+    let url = start_stub_node(move |request| {
+        let funding = *funding.lock().unwrap();
+        match request["method"].as_str().unwrap() {
+            "eth_getBlockByNumber" => json!({ "result": block_json(funding.now) }),
+            "eth_call" => {
+                let call = &request["params"][0];
+                let input = call["input"].as_str().or(call["data"].as_str()).unwrap();
+                let input = hex::decode(input).unwrap();
+                let selector: [u8; 4] = input[..4].try_into().unwrap();
+                let output = match selector {
+                    IERC20::allowanceCall::SELECTOR => funding.permit2_allowance,
+                    IERC20::balanceOfCall::SELECTOR => funding.balance,
+                    Permit2::nonceBitmapCall::SELECTOR if funding.nonce_used => {
+                        U256::from(1) << (NONCE % 256) as usize
+                    }
+                    Permit2::nonceBitmapCall::SELECTOR => U256::ZERO,
+                    _ => return json!({ "error": { "code": 3, "message": "execution reverted" } }),
+                };
+                json!({ "result": Bytes::from(output.abi_encode()) })
+            }
+            _ => method_not_found(),
+        }
+    })
+    .await;
+
+    stub_provider(url)
+}
+
+fn proof_request() -> ProofRequest {
+    ProofRequest {
+        signer: REQUESTER,
+        market: MARKET,
+        nonce: U256::from(NONCE),
+        rewardToken: TOKEN,
+        maxRewardAmount: U256::from(MAX_REWARD),
+        minRewardAmount: U256::from(10),
+        minimumStake: 0,
+        startAuctionTimestamp: END_AUCTION_TIMESTAMP - 60,
+        endAuctionTimestamp: END_AUCTION_TIMESTAMP,
+        provingTime: 120,
+        inputsCommitment: B256::ZERO,
+        extraData: Bytes::new(),
+    }
+}
+
+/// Funding covering the request, read during its auction
+fn funded() -> RewardFunding {
+    RewardFunding {
+        permit2_allowance: U256::from(MAX_REWARD),
+        balance: U256::from(MAX_REWARD),
+        nonce_used: false,
+        now: END_AUCTION_TIMESTAMP - 30,
+    }
+}
+
+/// Stub submitter recording the transactions sent through it, all landing
+#[derive(Default)]
+struct RecordingSubmitter {
+    transactions: Mutex<Vec<TransactionRequest>>,
+}
+
+#[async_trait]
+impl TransactionSubmitter<Ethereum> for RecordingSubmitter {
+    async fn send_and_wait_receipt(
+        &self,
+        transaction: TransactionRequest,
+    ) -> Result<TransactionReceipt> {
+        self.transactions.lock().unwrap().push(transaction);
+        Ok(serde_json::from_value(json!({
+            "type": "0x2",
+            "status": "0x1",
+            "cumulativeGasUsed": "0x5208",
+            "logs": [],
+            "logsBloom": format!("0x{}", "00".repeat(256)),
+            "transactionHash": B256::repeat_byte(0xaa),
+            "transactionIndex": "0x0",
+            "blockHash": B256::repeat_byte(0x01),
+            "blockNumber": "0x1",
+            "gasUsed": "0x5208",
+            "effectiveGasPrice": "0x1",
+            "from": REQUESTER,
+            "to": TOKEN,
+            "contractAddress": null
+        }))
+        .unwrap())
+    }
+}
+
+#[tokio::test]
+/// Ensures a request is only reported fundable when the ERC20 allowance of permit2 and the balance
+/// cover its maximum reward, its permit2 nonce is unused and its auction hasn't ended.
+async fn should_check_funding_against_request() {
+    let record = Arc::new(Mutex::new(funded()));
+    let checker = Permit2AllowanceChecker::new(start_funding_node(record.clone()).await, REQUESTER);
+    let request = proof_request();
+
+    // healthy
+    assert_eq!(checker.funding(&request).await.unwrap(), funded());
+    assert!(checker.check(&request).await.unwrap().is_empty());
+    checker.ensure_allowance(&request).await.unwrap();
+
+    // permit2 allowed less than the maximum reward
+    *record.lock().unwrap() = RewardFunding {
+        permit2_allowance: U256::from(MAX_REWARD - 1),
+        ..funded()
+    };
+    assert_eq!(
+        checker.check(&request).await.unwrap(),
+        vec![AllowanceShortfall::Insufficient {
+            amount: U256::from(MAX_REWARD - 1),
+            required: U256::from(MAX_REWARD),
+        }]
+    );
+    let err = checker.ensure_allowance(&request).await.unwrap_err();
+    assert!(
+        matches!(
+            &err,
+            ClientError::Permit2AllowanceInsufficient { token, spender, shortfalls }
+                if *token == TOKEN && *spender == MARKET && shortfalls.len() == 1
+        ),
+        "{err}"
+    );
+
+    // balance below the maximum reward
+    *record.lock().unwrap() = RewardFunding {
+        balance: U256::from(1),
+        ..funded()
+    };
+    assert_eq!(
+        checker.check(&request).await.unwrap(),
+        vec![AllowanceShortfall::Balance {
+            balance: U256::from(1),
+            required: U256::from(MAX_REWARD),
+        }]
+    );
+
+    // nonce spent
+    *record.lock().unwrap() = RewardFunding {
+        nonce_used: true,
+        ..funded()
+    };
+    assert_eq!(
+        checker.check(&request).await.unwrap(),
+        vec![AllowanceShortfall::NonceUsed {
+            nonce: U256::from(NONCE)
+        }]
+    );
+
+    // the permit expires with the auction
+    *record.lock().unwrap() = RewardFunding {
+        now: END_AUCTION_TIMESTAMP + 1,
+        ..funded()
+    };
+    assert_eq!(
+        checker.check(&request).await.unwrap(),
+        vec![AllowanceShortfall::Expired {
+            deadline: END_AUCTION_TIMESTAMP,
+            now: END_AUCTION_TIMESTAMP + 1,
+        }]
+    );
+    assert!(checker.ensure_allowance(&request).await.is_err());
+}
+
+#[tokio::test]
+/// Ensures the ERC20 approval of permit2 is only sent when the allowance alone falls short, adding
+/// the request's maximum reward to the allowance left, and never for a shortfall it can't make up.
+async fn should_approve_permit2_through_erc20() {
+    let record = Arc::new(Mutex::new(funded()));
+    let checker = Permit2AllowanceChecker::new(start_funding_node(record.clone()).await, REQUESTER);
+    let recording = Arc::new(RecordingSubmitter::default());
+    let submitter: Arc<dyn TransactionSubmitter<Ethereum>> = recording.clone();
+    let request = proof_request();
+
+    let receipt = checker
+        .ensure_permit2_approval(&request, Some(&submitter))
+        .await
+        .unwrap();
+    assert!(receipt.is_none());
+    assert!(recording.transactions.lock().unwrap().is_empty());
+
+    // an approval doesn't fund an empty balance
+    *record.lock().unwrap() = RewardFunding {
+        permit2_allowance: U256::ZERO,
+        balance: U256::ZERO,
+        ..funded()
+    };
+    let err = checker
+        .ensure_permit2_approval(&request, Some(&submitter))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ClientError::Permit2AllowanceInsufficient { .. }
+    ));
+    assert!(recording.transactions.lock().unwrap().is_empty());
+
+    *record.lock().unwrap() = RewardFunding {
+        permit2_allowance: U256::from(400),
+        ..funded()
+    };
+    let receipt = checker
+        .ensure_permit2_approval(&request, Some(&submitter))
+        .await
+        .unwrap();
+    assert!(receipt.is_some());
+
+    let transactions = recording.transactions.lock().unwrap();
+    assert_eq!(transactions.len(), 1);
+    let approval = &transactions[0];
+    assert_eq!(approval.to, Some(TxKind::Call(TOKEN)));
+    assert_eq!(approval.from, Some(REQUESTER));
+    let call = IERC20::approveCall::abi_decode(approval.input.input().unwrap(), true).unwrap();
+    assert_eq!(call.spender, PERMIT2_ADDRESS);
+    assert_eq!(call.amount, U256::from(400 + MAX_REWARD));
+}
@@ -0,0 +1,116 @@
+use serde_json::json;
+use taralli_client::pnl::{GasCost, IntentOutcome, PnlLedger};
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta;
+use taralli_primitives::alloy::primitives::{address, Address, B256, U256};
+use taralli_primitives::alloy::rpc::types::TransactionReceipt;
+use taralli_primitives::systems::SystemId;
+
+const REWARD_TOKEN: Address = address!("1c7D4B196Cb0C7B01d743Fbc6116a902379C7238");
+const PROVIDER: Address = address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+
+/// Receipt of a successful transaction, as returned by the rpc
+fn receipt(tx_byte: u8, gas_used: u64, effective_gas_price: u128) -> TransactionReceipt {
+    serde_json::from_value(json!({
+        "type": "0x2",
+        "status": "0x1",
+        "transactionHash": B256::repeat_byte(tx_byte),
+        "transactionIndex": "0x0",
+        "blockHash": B256::repeat_byte(0xbb),
+        "blockNumber": "0x10",
+        "from": PROVIDER,
+        "to": Address::ZERO,
+        "contractAddress": null,
+        "gasUsed": format!("{gas_used:#x}"),
+        "effectiveGasPrice": format!("{effective_gas_price:#x}"),
+        "cumulativeGasUsed": format!("{gas_used:#x}"),
+        "logs": [],
+        "logsBloom": format!("0x{}", "00".repeat(256)),
+    }))
+    .unwrap()
+}
+
+fn bid_event(intent_byte: u8, reward_amount: u64, stake: u64) -> UniversalBombetta::Bid {
+    UniversalBombetta::Bid {
+        signer: Address::ZERO,
+        requestId: B256::repeat_byte(intent_byte),
+        rewardToken: REWARD_TOKEN,
+        rewardAmount: U256::from(reward_amount),
+        ethStake: U256::from(stake),
+        provider: PROVIDER,
+    }
+}
+
+#[test]
+/// Ensures the report of a resolved and a defaulted request matches the rewards of their bids
+/// and the gas of their receipts.
+fn should_report_rewards_net_of_gas() {
+    let path = std::env::temp_dir().join(format!("taralli-pnl-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let ledger = PnlLedger::load(&path).unwrap();
+
+    let resolved_bid = receipt(1, 120_000, 2_000_000_000);
+    let resolve = receipt(2, 300_000, 3_000_000_000);
+    let defaulted_bid = receipt(3, 110_000, 1_000_000_000);
+
+    ledger.record_bid(
+        SystemId::Risc0,
+        &bid_event(0xa1, 500, 10),
+        GasCost::from_receipt(&resolved_bid),
+    );
+    ledger.record_bid(
+        SystemId::Sp1,
+        &bid_event(0xa2, 700, 20),
+        GasCost::from_receipt(&defaulted_bid),
+    );
+    ledger.record_resolve(B256::repeat_byte(0xa1), GasCost::from_receipt(&resolve));
+    ledger.record_default(B256::repeat_byte(0xa2), "worker failed".to_string());
+
+    let entries = ledger.entries();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].bid_gas.tx_hash, B256::repeat_byte(1));
+    assert_eq!(entries[0].bid_gas.gas_used, 120_000);
+    assert_eq!(
+        entries[1].outcome,
+        IntentOutcome::Defaulted {
+            reason: "worker failed".to_string()
+        }
+    );
+
+    let report = ledger.report(0..u64::MAX);
+    let risc0 = &report.systems[&SystemId::Risc0];
+    assert_eq!(risc0.intents_won, 1);
+    assert_eq!(risc0.intents_resolved, 1);
+    assert_eq!(risc0.gross_rewards[&REWARD_TOKEN], U256::from(500));
+    assert_eq!(
+        risc0.gas_spent,
+        U256::from(120_000u64 * 2_000_000_000 + 300_000u64 * 3_000_000_000)
+    );
+    assert_eq!(risc0.defaults, 0);
+
+    let sp1 = &report.systems[&SystemId::Sp1];
+    assert_eq!(sp1.intents_won, 1);
+    assert!(sp1.gross_rewards.is_empty());
+    assert_eq!(sp1.gas_spent, U256::from(110_000u64 * 1_000_000_000));
+    assert_eq!(sp1.defaults, 1);
+    assert_eq!(sp1.stake_slashed, U256::from(20));
+
+    // requests won outside of the period aren't reported
+    assert!(ledger.report(0..1).systems.is_empty());
+
+    // the ledger is persisted
+    let reloaded = PnlLedger::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(reloaded.report(0..u64::MAX), report);
+
+    let csv = report.to_csv();
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(
+        rows[1],
+        format!("risc0,1,1,{REWARD_TOKEN},500,1140000000000000,0,0")
+    );
+    assert_eq!(rows[2], "sp1,1,0,,0,110000000000000,1,20");
+
+    let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+    assert_eq!(json["systems"]["Sp1"]["defaults"], 1);
+}
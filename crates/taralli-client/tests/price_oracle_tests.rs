@@ -0,0 +1,155 @@
+use taralli_client::backtest::Trace;
+use taralli_client::intent_builder::request::ComputeRequestBuilder;
+use taralli_client::oracle::{
+    ClearingPrice, PriceOracle, RewardPercentile, RewardStrategy, RewardSuggestion,
+};
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::{Address, U256};
+use taralli_primitives::alloy::providers::{ProviderBuilder, RootProvider};
+use taralli_primitives::alloy::transports::http::{Client, Http};
+use taralli_primitives::systems::SystemId;
+
+/// the backtest trace, whose Risc0 requests were won at 600 and 1700 with a 60 second proving
+/// time and at 800 with a 10 second proving time
+const TRACE: &str = include_str!("fixtures/backtest_trace.jsonl");
+
+fn price(timestamp: u64, reward_amount: u64, proving_time: u32) -> ClearingPrice {
+    ClearingPrice {
+        timestamp,
+        reward_amount: U256::from(reward_amount),
+        proving_time,
+    }
+}
+
+fn suggestion(p25: u64, p50: u64, p75: u64, samples: usize) -> RewardSuggestion {
+    RewardSuggestion {
+        p25: U256::from(p25),
+        p50: U256::from(p50),
+        p75: U256::from(p75),
+        samples,
+    }
+}
+
+#[test]
+/// Ensures percentiles are taken by nearest rank, whatever order the prices were recorded in.
+fn should_suggest_percentiles_of_clearing_prices() {
+    let mut oracle = PriceOracle::new();
+    for (timestamp, reward) in [700, 100, 1000, 400, 200, 900, 300, 600, 500, 800]
+        .into_iter()
+        .enumerate()
+    {
+        oracle.record(SystemId::Risc0, price(timestamp as u64, reward, 60));
+    }
+
+    assert_eq!(
+        oracle.suggest_reward(SystemId::Risc0, 60),
+        Some(suggestion(300, 500, 800, 10))
+    );
+
+    // a single price is every percentile
+    let mut oracle = PriceOracle::new();
+    oracle.record(SystemId::Sp1, price(1, 250, 60));
+    assert_eq!(
+        oracle.suggest_reward(SystemId::Sp1, 60),
+        Some(suggestion(250, 250, 250, 1))
+    );
+}
+
+#[test]
+/// Ensures systems without history get no suggestion rather than a made up one.
+fn should_not_suggest_without_history() {
+    let mut oracle = PriceOracle::new();
+    assert_eq!(oracle.suggest_reward(SystemId::Risc0, 60), None);
+
+    oracle.record(SystemId::Risc0, price(1, 500, 60));
+    assert_eq!(oracle.suggest_reward(SystemId::Sp1, 60), None);
+    assert_eq!(oracle.samples(SystemId::Sp1), 0);
+
+    // an empty trace leaves the oracle without history
+    let mut oracle = PriceOracle::new();
+    assert_eq!(oracle.ingest_trace(&Trace::default()), 0);
+    assert_eq!(oracle.suggest_reward(SystemId::Risc0, 60), None);
+}
+
+#[test]
+/// Ensures suggestions only use requests of a comparable proving time when there are some, and
+/// only the most recent prices are kept.
+fn should_suggest_from_comparable_recent_prices() {
+    let mut oracle = PriceOracle::new().with_max_samples(4);
+    oracle.record(SystemId::Risc0, price(1, 5_000, 10));
+    oracle.record(SystemId::Risc0, price(2, 100, 60));
+    oracle.record(SystemId::Risc0, price(3, 200, 60));
+    oracle.record(SystemId::Risc0, price(4, 300, 100));
+
+    // the 10 second request isn't comparable to a 60 second one
+    assert_eq!(
+        oracle.suggest_reward(SystemId::Risc0, 60),
+        Some(suggestion(100, 200, 300, 3))
+    );
+    // nothing is comparable to an hour, every price is used
+    assert_eq!(
+        oracle.suggest_reward(SystemId::Risc0, 3_600),
+        Some(suggestion(100, 200, 300, 4))
+    );
+
+    // the oldest price is dropped
+    oracle.record(SystemId::Risc0, price(5, 400, 60));
+    assert_eq!(oracle.samples(SystemId::Risc0), 4);
+    assert_eq!(
+        oracle.suggest_reward(SystemId::Risc0, 3_600),
+        Some(suggestion(200, 300, 400, 4))
+    );
+}
+
+#[test]
+/// Ensures the winning bids of a recorded trace are ingested along with their request's proving
+/// time, and requests never bid upon are left out.
+fn should_ingest_trace() {
+    let trace = Trace::from_jsonl(TRACE).unwrap();
+    let mut oracle = PriceOracle::new();
+
+    assert_eq!(oracle.ingest_trace(&trace), 3);
+    assert_eq!(oracle.samples(SystemId::Risc0), 3);
+    assert_eq!(
+        oracle.suggest_reward(SystemId::Risc0, 60),
+        Some(suggestion(600, 600, 1700, 2))
+    );
+    assert_eq!(
+        oracle.suggest_reward(SystemId::Risc0, 10),
+        Some(suggestion(800, 800, 800, 1))
+    );
+}
+
+#[test]
+/// Ensures the request builder's rewards are set around the percentile the strategy picks.
+fn should_set_builder_rewards_from_suggestion() {
+    let builder: ComputeRequestBuilder<Http<Client>, RootProvider<Http<Client>>, Ethereum> =
+        ComputeRequestBuilder::new(
+            ProviderBuilder::new().on_http("http://localhost:8545".parse().unwrap()),
+            Address::ZERO,
+            Address::ZERO,
+            SystemId::Risc0,
+        );
+    let suggestion = suggestion(300, 500, 800, 10);
+
+    let fixed = builder
+        .clone()
+        .set_reward_from_suggestion(&suggestion, RewardStrategy::Fixed(RewardPercentile::P75));
+    assert!(fixed.is_fixed_reward());
+    assert_eq!(fixed.max_reward_amount, U256::from(800));
+
+    let band = builder.clone().set_reward_from_suggestion(
+        &suggestion,
+        RewardStrategy::Band {
+            percentile: RewardPercentile::P50,
+            spread_bps: 2_000,
+        },
+    );
+    assert_eq!(band.min_reward_amount, U256::from(400));
+    assert_eq!(band.max_reward_amount, U256::from(600));
+
+    let interquartile =
+        builder.set_reward_from_suggestion(&suggestion, RewardStrategy::Interquartile);
+    assert_eq!(interquartile.min_reward_amount, U256::from(300));
+    assert_eq!(interquartile.max_reward_amount, U256::from(800));
+}
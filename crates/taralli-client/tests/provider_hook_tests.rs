@@ -0,0 +1,237 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use taralli_client::hooks::{BidDecision, HookRegistry, ProviderHook};
+use taralli_client::worker::WorkResult;
+use taralli_primitives::alloy::primitives::{Bytes, FixedBytes, TxHash, B256};
+
+const VETOED: B256 = B256::repeat_byte(0xaa);
+const ACCEPTED: B256 = B256::repeat_byte(0xbb);
+
+const HOOK_TIMEOUT: Duration = Duration::from_millis(50);
+
+/// Stand-in for the intents a provider processes
+#[derive(Clone)]
+struct Intent;
+
+/// Hook recording its calls as `<name>:<callback>`, vetoing `VETOED` before bidding
+struct RecordingHook {
+    name: &'static str,
+    calls: Arc<Mutex<Vec<String>>>,
+}
+
+impl RecordingHook {
+    fn record(&self, callback: &str) {
+        self.calls
+            .lock()
+            .unwrap()
+            .push(format!("{}:{callback}", self.name));
+    }
+}
+
+#[async_trait]
+impl ProviderHook<Intent> for RecordingHook {
+    async fn on_intent_received(&self, _intent_id: FixedBytes<32>, _intent: &Intent) {
+        self.record("received");
+    }
+
+    async fn pre_bid(&self, intent_id: FixedBytes<32>, _intent: &Intent) -> BidDecision {
+        self.record("pre_bid");
+        if intent_id == VETOED {
+            BidDecision::Veto(format!("{} is over capacity", self.name))
+        } else {
+            BidDecision::Proceed
+        }
+    }
+
+    async fn post_proof(&self, _intent_id: FixedBytes<32>, _intent: &Intent, _result: &WorkResult) {
+        self.record("post_proof");
+    }
+
+    async fn post_resolve(
+        &self,
+        _intent_id: FixedBytes<32>,
+        _intent: &Intent,
+        _resolve_tx: TxHash,
+    ) {
+        self.record("post_resolve");
+    }
+}
+
+/// Hook panicking in every callback
+struct PanickingHook;
+
+#[async_trait]
+impl ProviderHook<Intent> for PanickingHook {
+    async fn on_intent_received(&self, _intent_id: FixedBytes<32>, _intent: &Intent) {
+        panic!("hook bug");
+    }
+
+    async fn pre_bid(&self, _intent_id: FixedBytes<32>, _intent: &Intent) -> BidDecision {
+        panic!("hook bug");
+    }
+}
+
+/// Hook outliving its timeout in every callback
+struct StalledHook;
+
+#[async_trait]
+impl ProviderHook<Intent> for StalledHook {
+    async fn on_intent_received(&self, _intent_id: FixedBytes<32>, _intent: &Intent) {
+        tokio::time::sleep(Duration::from_secs(3600)).await;
+    }
+
+    async fn pre_bid(&self, _intent_id: FixedBytes<32>, _intent: &Intent) -> BidDecision {
+        tokio::time::sleep(Duration::from_secs(3600)).await;
+        BidDecision::Proceed
+    }
+}
+
+fn stalled_registry(calls: &Arc<Mutex<Vec<String>>>) -> HookRegistry<Intent> {
+    let mut registry = HookRegistry::default();
+    registry.register_with_timeout(Arc::new(StalledHook), HOOK_TIMEOUT);
+    registry.register(Arc::new(RecordingHook {
+        name: "accounting",
+        calls: calls.clone(),
+    }));
+    registry
+}
+
+fn registry(calls: &Arc<Mutex<Vec<String>>>) -> HookRegistry<Intent> {
+    let mut registry = HookRegistry::default();
+    for name in ["planner", "accounting"] {
+        registry.register(Arc::new(RecordingHook {
+            name,
+            calls: calls.clone(),
+        }));
+    }
+    registry
+}
+
+#[tokio::test]
+/// Ensures the first veto drops the intent before the later hooks are asked.
+async fn should_stop_at_first_veto() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let registry = registry(&calls);
+
+    registry.on_intent_received(VETOED, &Intent).await.unwrap();
+    let decision = registry.pre_bid(VETOED, &Intent).await;
+
+    assert_eq!(
+        decision,
+        BidDecision::Veto("planner is over capacity".to_string())
+    );
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec!["planner:received", "accounting:received", "planner:pre_bid"]
+    );
+}
+
+#[tokio::test]
+/// Ensures every hook sees each step of an accepted intent in registration order.
+async fn should_call_hooks_in_order() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let registry = registry(&calls);
+    let work_result = WorkResult {
+        opaque_submission: Bytes::new(),
+        partial_commitment: FixedBytes::ZERO,
+    };
+
+    registry
+        .on_intent_received(ACCEPTED, &Intent)
+        .await
+        .unwrap();
+    assert_eq!(
+        registry.pre_bid(ACCEPTED, &Intent).await,
+        BidDecision::Proceed
+    );
+    registry
+        .post_proof(ACCEPTED, &Intent, &work_result)
+        .await
+        .unwrap();
+    registry
+        .post_resolve(ACCEPTED, &Intent, TxHash::repeat_byte(0x01))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec![
+            "planner:received",
+            "accounting:received",
+            "planner:pre_bid",
+            "accounting:pre_bid",
+            "planner:post_proof",
+            "accounting:post_proof",
+            "planner:post_resolve",
+            "accounting:post_resolve",
+        ]
+    );
+}
+
+#[tokio::test]
+/// Ensures a panicking hook doesn't unwind into the client and vetoes the bid.
+async fn should_contain_panicking_hook() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let mut registry = HookRegistry::default();
+    registry.register(Arc::new(PanickingHook));
+    registry.register(Arc::new(RecordingHook {
+        name: "accounting",
+        calls: calls.clone(),
+    }));
+
+    registry
+        .on_intent_received(ACCEPTED, &Intent)
+        .await
+        .unwrap();
+    let decision = registry.pre_bid(ACCEPTED, &Intent).await;
+
+    assert!(matches!(decision, BidDecision::Veto(_)));
+    assert_eq!(*calls.lock().unwrap(), vec!["accounting:received"]);
+}
+
+#[tokio::test]
+/// Ensures a stalled hook is given up on after its timeout, vetoing the bid by default.
+async fn should_veto_on_stalled_pre_bid() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let registry = stalled_registry(&calls);
+
+    let decision =
+        tokio::time::timeout(Duration::from_secs(5), registry.pre_bid(ACCEPTED, &Intent))
+            .await
+            .expect("pre_bid hook wasn't timed out");
+
+    assert!(matches!(decision, BidDecision::Veto(_)));
+    assert!(calls.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+/// Ensures a stalled hook lets the later hooks decide when timeouts aren't vetoes.
+async fn should_ignore_stalled_pre_bid_when_configured() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let mut registry = stalled_registry(&calls);
+    registry.set_veto_on_timeout(false);
+
+    let decision = registry.pre_bid(ACCEPTED, &Intent).await;
+
+    assert_eq!(decision, BidDecision::Proceed);
+    assert_eq!(*calls.lock().unwrap(), vec!["accounting:pre_bid"]);
+}
+
+#[tokio::test]
+/// Ensures observing hooks run off the caller's task and a stalled one doesn't hold up the
+/// later ones past its timeout.
+async fn should_spawn_observing_hooks() {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let registry = stalled_registry(&calls);
+
+    let handle = registry.on_intent_received(ACCEPTED, &Intent);
+    assert!(calls.lock().unwrap().is_empty());
+
+    tokio::time::timeout(Duration::from_secs(5), handle)
+        .await
+        .expect("on_intent_received hook wasn't timed out")
+        .unwrap();
+    assert_eq!(*calls.lock().unwrap(), vec!["accounting:received"]);
+}
@@ -0,0 +1,183 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use taralli_client::proving_stats::{workload_size, ProvingStats, ProvingStatsConfig};
+use taralli_primitives::systems::risc0::Risc0ProofParams;
+use taralli_primitives::systems::{SystemId, SystemParams};
+use taralli_primitives::validation::limits::ResourceLimits;
+
+pub mod common;
+use common::fixtures::request_fixture;
+
+const MIB: u64 = 1024 * 1024;
+
+#[tokio::test]
+/// Ensures the estimate converges towards the durations recorded for a workload size, without
+/// affecting other sizes or systems.
+async fn should_converge_towards_recorded_durations() {
+    let stats = ProvingStats::new(ProvingStatsConfig::default());
+
+    for _ in 0..50 {
+        stats
+            .record(SystemId::Risc0, 3 * MIB, Duration::from_secs(40))
+            .await;
+    }
+    let estimate = stats.estimate(SystemId::Risc0, 3 * MIB).as_secs_f64();
+    assert!((estimate - 40.0).abs() < 0.01, "{estimate}");
+    assert!(stats.has_enough_samples(SystemId::Risc0, 3 * MIB));
+    // same bucket
+    assert_eq!(
+        stats.estimate(SystemId::Risc0, 2 * MIB + 1),
+        stats.estimate(SystemId::Risc0, 3 * MIB)
+    );
+
+    // proving getting slower moves the estimate along
+    for _ in 0..50 {
+        stats
+            .record(SystemId::Risc0, 3 * MIB, Duration::from_secs(80))
+            .await;
+    }
+    let estimate = stats.estimate(SystemId::Risc0, 3 * MIB).as_secs_f64();
+    assert!((estimate - 80.0).abs() < 0.01, "{estimate}");
+
+    assert_eq!(stats.samples(SystemId::Risc0, 16 * MIB), 0);
+    assert_eq!(stats.samples(SystemId::Sp1, 3 * MIB), 0);
+}
+
+#[test]
+/// Ensures estimates of workloads without recorded durations fall back to the proving time rates
+/// of the configured resource limits.
+fn should_fall_back_to_resource_limits_on_cold_start() {
+    let mut fallback = ResourceLimits::default();
+    fallback.risc0.proving_seconds_per_mib = 5;
+    fallback.arkworks.proving_seconds_per_million_constraints = 7;
+    let stats = ProvingStats::new(ProvingStatsConfig {
+        fallback,
+        ..Default::default()
+    });
+
+    assert_eq!(
+        stats.estimate(SystemId::Risc0, 3 * MIB),
+        Duration::from_secs(15)
+    );
+    assert_eq!(
+        stats.estimate(SystemId::Risc0, 3 * MIB + 1),
+        Duration::from_secs(20)
+    );
+    assert_eq!(
+        stats.estimate(SystemId::Arkworks, 2_500_000),
+        Duration::from_secs(21)
+    );
+    assert!(!stats.has_enough_samples(SystemId::Risc0, 3 * MIB));
+}
+
+#[tokio::test]
+/// Ensures estimates made from fewer samples than required are scaled by the low sample
+/// multiplier.
+async fn should_scale_estimates_with_few_samples() {
+    let stats = ProvingStats::new(ProvingStatsConfig {
+        min_samples: 3,
+        low_sample_multiplier: 1.5,
+        ..Default::default()
+    });
+
+    stats
+        .record(SystemId::Sp1, MIB, Duration::from_secs(10))
+        .await;
+    stats
+        .record(SystemId::Sp1, MIB, Duration::from_secs(10))
+        .await;
+    assert_eq!(stats.estimate(SystemId::Sp1, MIB), Duration::from_secs(15));
+    assert!(!stats.has_enough_samples(SystemId::Sp1, MIB));
+
+    stats
+        .record(SystemId::Sp1, MIB, Duration::from_secs(10))
+        .await;
+    assert_eq!(stats.estimate(SystemId::Sp1, MIB), Duration::from_secs(10));
+    assert!(stats.has_enough_samples(SystemId::Sp1, MIB));
+}
+
+#[tokio::test]
+/// Ensures recorded durations are persisted and loaded back across restarts.
+async fn should_persist_recorded_durations() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("proving_stats.json");
+
+    let stats = ProvingStats::load(ProvingStatsConfig::default(), &path).unwrap();
+    for seconds in [20, 30, 40] {
+        stats
+            .record(SystemId::Risc0, MIB, Duration::from_secs(seconds))
+            .await;
+    }
+    stats
+        .record(SystemId::Arkworks, 1_000_000, Duration::from_secs(5))
+        .await;
+
+    let reloaded = ProvingStats::load(ProvingStatsConfig::default(), &path).unwrap();
+    for (system_id, size_hint) in [(SystemId::Risc0, MIB), (SystemId::Arkworks, 1_000_000)] {
+        assert_eq!(
+            reloaded.estimate(system_id, size_hint),
+            stats.estimate(system_id, size_hint)
+        );
+        assert_eq!(
+            reloaded.samples(system_id, size_hint),
+            stats.samples(system_id, size_hint)
+        );
+    }
+
+    // written through a temporary file renamed over the previous records
+    assert!(!dir.path().join("proving_stats.json.tmp").exists());
+
+    std::fs::write(&path, "not json").unwrap();
+    assert!(ProvingStats::load(ProvingStatsConfig::default(), &path).is_err());
+}
+
+#[tokio::test]
+/// Ensures requests are turned down once enough samples expect them to outlast their proving
+/// time, and priced by their estimated proving time.
+async fn should_check_and_price_requests_by_estimate() {
+    let stats = Arc::new(ProvingStats::new(ProvingStatsConfig {
+        min_samples: 2,
+        ..Default::default()
+    }));
+    let request = request_fixture();
+    let size_hint = workload_size(&request.system);
+    let proving_time = u64::from(request.proof_request.provingTime);
+
+    // a single slow sample isn't trusted to turn the request down
+    stats
+        .record(
+            SystemId::Risc0,
+            size_hint,
+            Duration::from_secs(2 * proving_time),
+        )
+        .await;
+    assert!(stats.ensure_provable_in_time(&request).is_ok());
+    stats
+        .record(
+            SystemId::Risc0,
+            size_hint,
+            Duration::from_secs(2 * proving_time),
+        )
+        .await;
+    let err = stats.ensure_provable_in_time(&request).unwrap_err();
+    assert!(err.to_string().contains("exceeds"), "{err}");
+
+    let usd_cost = stats.clone().usd_cost(0.5);
+    assert_eq!(usd_cost(&request), proving_time as f64);
+}
+
+#[test]
+/// Ensures the workload size of programs covers both the ELF and the inputs.
+fn should_size_programs_by_elf_and_inputs() {
+    let system = SystemParams::try_from((
+        &SystemId::Risc0,
+        serde_json::to_vec(&Risc0ProofParams {
+            elf: vec![1; 100],
+            inputs: vec![2; 28],
+        })
+        .unwrap(),
+    ))
+    .unwrap();
+    assert_eq!(workload_size(&system), 128);
+}
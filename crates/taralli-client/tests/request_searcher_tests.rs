@@ -0,0 +1,161 @@
+use std::sync::{Arc, Mutex};
+
+use serde_json::json;
+use taralli_client::api::query::QueryApiClient;
+use taralli_client::searcher::request::{rank_requests, CostModel, FixedCost};
+use taralli_primitives::abi::extensions::{with_request_extensions, Extensions};
+use taralli_primitives::abi::verifier_details::REQUEST_VERIFIER_DETAILS_LENGTH;
+use taralli_primitives::alloy::primitives::{Bytes, B256, U256};
+use taralli_primitives::compression_utils::compression::compress_brotli;
+use taralli_primitives::intents::{request::ComputeRequest, ComputeIntent};
+use taralli_primitives::systems::{SystemId, SystemParams};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use url::Url;
+
+pub mod common;
+use common::fixtures::request_with_inputs;
+
+/// Cost proportional to the request's minimum reward
+struct HalfMinimumReward;
+
+impl CostModel for HalfMinimumReward {
+    fn cost(&self, request: &ComputeRequest<SystemParams>) -> U256 {
+        request.proof_request.minRewardAmount / U256::from(2)
+    }
+}
+
+/// request auctioned from 100 to 200, its reward rising from `min_reward` to `max_reward`
+fn rewarded_request(nonce: u64, min_reward: u64, max_reward: u64) -> ComputeRequest<SystemParams> {
+    let mut request = request_with_inputs(nonce, 4);
+    request.proof_request.minRewardAmount = U256::from(min_reward);
+    request.proof_request.maxRewardAmount = U256::from(max_reward);
+    request
+}
+
+#[test]
+/// Ensures candidates are ranked by current reward minus cost, dropping unprofitable requests.
+fn should_rank_requests_by_margin() {
+    let requests = vec![
+        rewarded_request(1, 100, 100),
+        rewarded_request(2, 10, 1000),
+        rewarded_request(3, 300, 300),
+    ];
+
+    // halfway through the auction request 2 pays 505
+    let ranked = rank_requests(requests.clone(), 150, &FixedCost(U256::from(200)));
+    let nonces: Vec<_> = ranked
+        .iter()
+        .map(|candidate| candidate.request.proof_request.nonce)
+        .collect();
+    assert_eq!(nonces, vec![U256::from(2), U256::from(3)]);
+    assert_eq!(ranked[0].current_reward, U256::from(505));
+    assert_eq!(ranked[0].margin(), U256::from(305));
+    assert_eq!(ranked[0].request_id, requests[1].compute_id());
+
+    let ranked = rank_requests(requests, 150, &HalfMinimumReward);
+    assert_eq!(ranked[0].margin(), U256::from(500));
+    assert_eq!(ranked.len(), 3);
+}
+
+#[test]
+/// Ensures requests whose auction hasn't started or has ended are not candidates.
+fn should_drop_requests_outside_their_auction() {
+    let requests = vec![rewarded_request(1, 100, 100)];
+
+    assert!(rank_requests(requests.clone(), 99, &FixedCost(U256::ZERO)).is_empty());
+    assert_eq!(
+        rank_requests(requests.clone(), 200, &FixedCost(U256::ZERO)).len(),
+        1
+    );
+    assert!(rank_requests(requests, 201, &FixedCost(U256::ZERO)).is_empty());
+}
+
+#[test]
+/// Ensures a request signaling urgency is ranked ahead of requests with a better margin.
+fn should_rank_urgent_requests_first() {
+    let mut urgent = rewarded_request(1, 100, 100);
+    urgent.proof_request.extraData = with_request_extensions(
+        &[0u8; REQUEST_VERIFIER_DETAILS_LENGTH],
+        &Extensions {
+            urgency: Some(1),
+            ..Default::default()
+        },
+    );
+    let requests = vec![rewarded_request(2, 300, 300), urgent];
+
+    let ranked = rank_requests(requests, 150, &FixedCost(U256::ZERO));
+    let nonces: Vec<_> = ranked
+        .iter()
+        .map(|candidate| candidate.request.proof_request.nonce)
+        .collect();
+    assert_eq!(nonces, vec![U256::from(1), U256::from(2)]);
+    assert_eq!(ranked[0].urgency, 1);
+    assert_eq!(ranked[1].urgency, 0);
+}
+
+#[tokio::test]
+/// Ensures stored requests returned by the server are decoded, skipping malformed ones.
+async fn should_query_active_requests() {
+    let request = rewarded_request(7, 10, 20);
+    let body = json!({
+        "requests": [
+            {
+                "intent_id": request.compute_id(),
+                "system_id": request.system_id,
+                "signer": request.proof_request.signer,
+                "market": request.proof_request.market,
+                "start_auction_timestamp": 100,
+                "end_auction_timestamp": 200,
+                "status": "broadcast",
+                "system": Bytes::from(
+                    compress_brotli(&serde_json::to_vec(&request.system).unwrap()).unwrap()
+                ),
+                "proof_commitment": Bytes::from(serde_json::to_vec(&request.proof_request).unwrap()),
+                "signature": Bytes::from(request.signature.as_bytes().to_vec()),
+            },
+            {
+                "intent_id": B256::ZERO,
+                "system_id": request.system_id,
+                "system": "0x010203",
+                "proof_commitment": "0x",
+                "signature": "0x",
+            }
+        ],
+        "next_cursor": null
+    })
+    .to_string();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+    let request_line = Arc::new(Mutex::new(String::new()));
+    let recorded = request_line.clone();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buffer = vec![0u8; 4096];
+        let read = stream.read(&mut buffer).await.unwrap();
+        let head = String::from_utf8_lossy(&buffer[..read]).to_string();
+        *recorded.lock().unwrap() = head.lines().next().unwrap_or_default().to_string();
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).await.unwrap();
+        stream.shutdown().await.unwrap();
+    });
+
+    let requests = QueryApiClient::new(url)
+        .query_active_requests(SystemId::Risc0, 150)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        request_line.lock().unwrap().as_str(),
+        "GET /requests?system_id=risc0&active_at=150 HTTP/1.1"
+    );
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].compute_id(), request.compute_id());
+    assert_eq!(requests[0].proof_request.nonce, U256::from(7));
+    assert_eq!(requests[0].system_id, SystemId::Risc0);
+}
@@ -0,0 +1,224 @@
+use std::sync::Mutex;
+
+use taralli_client::client::requester::resubmission::{
+    ResubmissionEvent, ResubmissionPolicy, ResubmissionStop,
+};
+use taralli_client::error::{ClientError, Result};
+use taralli_primitives::alloy::primitives::{B256, U256};
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::intents::ComputeIntent;
+use taralli_primitives::systems::SystemParams;
+use tokio::sync::mpsc;
+
+pub mod common;
+use common::fixtures::request_fixture;
+
+const AUCTION_LENGTH: u64 = 100;
+
+/// Market whose auctions end without a bid until the `bid_on`th submission, if any, recording
+/// the submitted requests
+struct StubMarket {
+    bid_on: Option<usize>,
+    submitted: Mutex<Vec<ComputeRequest<SystemParams>>>,
+}
+
+impl StubMarket {
+    fn rejecting_bids() -> Self {
+        Self {
+            bid_on: None,
+            submitted: Mutex::new(Vec::new()),
+        }
+    }
+
+    async fn submit(&self, request: ComputeRequest<SystemParams>) -> Result<B256> {
+        let mut submitted = self.submitted.lock().unwrap();
+        let request_id = request.compute_id();
+        submitted.push(request);
+        if self.bid_on == Some(submitted.len()) {
+            Ok(request_id)
+        } else {
+            Err(ClientError::AuctionTimeoutError())
+        }
+    }
+
+    fn max_rewards(&self) -> Vec<u64> {
+        self.submitted
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|request| request.proof_request.maxRewardAmount.to::<u64>())
+            .collect()
+    }
+}
+
+/// Rebuild a request as the requester client does, with the next nonce and a later auction
+async fn rebuild(
+    mut request: ComputeRequest<SystemParams>,
+    max_reward_amount: U256,
+) -> Result<ComputeRequest<SystemParams>> {
+    request.proof_request.nonce += U256::from(1);
+    request.proof_request.startAuctionTimestamp += AUCTION_LENGTH;
+    request.proof_request.endAuctionTimestamp += AUCTION_LENGTH;
+    request.proof_request.maxRewardAmount = max_reward_amount;
+    Ok(request)
+}
+
+fn drain(events: &mut mpsc::UnboundedReceiver<ResubmissionEvent>) -> Vec<ResubmissionEvent> {
+    let mut drained = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        drained.push(event);
+    }
+    drained
+}
+
+/// request never bid upon, rewarding up to 100
+fn unbid_request() -> ComputeRequest<SystemParams> {
+    let mut request = request_fixture();
+    request.proof_request.maxRewardAmount = U256::from(100);
+    request.proof_request.minRewardAmount = U256::from(50);
+    request.proof_request.startAuctionTimestamp = 1_000;
+    request.proof_request.endAuctionTimestamp = 1_000 + AUCTION_LENGTH;
+    request
+}
+
+#[test]
+/// Ensures rewards grow by the bump percentage until capped, never dropping below the current one.
+fn should_escalate_reward_up_to_cap() {
+    let policy = ResubmissionPolicy::new(5, 25, U256::from(180));
+    assert_eq!(policy.escalate(U256::from(100)), U256::from(125));
+    assert_eq!(policy.escalate(U256::from(156)), U256::from(180));
+    assert_eq!(policy.escalate(U256::from(200)), U256::from(200));
+    assert_eq!(policy.escalate(U256::MAX), U256::MAX);
+}
+
+#[tokio::test]
+/// Ensures a request never bid upon is resubmitted with escalating rewards until the attempts
+/// are exhausted, an event being emitted for each resubmission and for the stop.
+async fn should_resubmit_until_attempts_exhausted() {
+    let (sender, mut events) = mpsc::unbounded_channel();
+    let policy = ResubmissionPolicy::new(4, 25, U256::from(180)).with_events(sender);
+    let market = StubMarket::rejecting_bids();
+
+    let err = policy
+        .run(
+            unbid_request(),
+            |request| market.submit(request),
+            rebuild,
+            |_| Ok(()),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ClientError::AuctionTimeoutError()), "{err}");
+    assert_eq!(market.max_rewards(), [100, 125, 156, 180, 180]);
+
+    let submitted = market.submitted.lock().unwrap().clone();
+    let events = drain(&mut events);
+    assert_eq!(events.len(), 5);
+    for (attempt, (event, pair)) in events.iter().zip(submitted.windows(2)).enumerate() {
+        assert_eq!(
+            *event,
+            ResubmissionEvent::Resubmitted {
+                attempt: attempt as u32 + 1,
+                previous_id: pair[0].compute_id(),
+                request_id: pair[1].compute_id(),
+                max_reward_amount: pair[1].proof_request.maxRewardAmount,
+            }
+        );
+    }
+    assert_eq!(
+        events[4],
+        ResubmissionEvent::Stopped {
+            attempts: 4,
+            request_id: submitted[4].compute_id(),
+            reason: ResubmissionStop::AttemptsExhausted,
+        }
+    );
+}
+
+#[tokio::test]
+/// Ensures resubmissions end with the submission bid upon.
+async fn should_stop_resubmitting_once_bid() {
+    let policy = ResubmissionPolicy::new(4, 50, U256::from(1_000));
+    let market = StubMarket {
+        bid_on: Some(3),
+        submitted: Mutex::new(Vec::new()),
+    };
+
+    let bid_request = policy
+        .run(
+            unbid_request(),
+            |request| market.submit(request),
+            rebuild,
+            |_| Ok(()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(market.max_rewards(), [100, 150, 225]);
+    assert_eq!(
+        bid_request,
+        market.submitted.lock().unwrap()[2].compute_id()
+    );
+}
+
+#[tokio::test]
+/// Ensures a rebuilt request failing validation once its reward is bumped isn't submitted.
+async fn should_not_resubmit_invalid_request() {
+    let (sender, mut events) = mpsc::unbounded_channel();
+    let policy = ResubmissionPolicy::new(4, 25, U256::from(1_000)).with_events(sender);
+    let market = StubMarket::rejecting_bids();
+
+    let err = policy
+        .run(
+            unbid_request(),
+            |request| market.submit(request),
+            rebuild,
+            |request| {
+                if request.proof_request.maxRewardAmount > U256::from(150) {
+                    return Err(ClientError::ValidationError("reward above budget".into()));
+                }
+                Ok(())
+            },
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ClientError::ValidationError(_)), "{err}");
+    assert_eq!(market.max_rewards(), [100, 125]);
+    assert!(matches!(
+        drain(&mut events).last(),
+        Some(ResubmissionEvent::Stopped {
+            attempts: 1,
+            reason: ResubmissionStop::ValidationFailed(_),
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+/// Ensures nothing is resubmitted once the policy's deadline passed.
+async fn should_not_resubmit_past_deadline() {
+    let (sender, mut events) = mpsc::unbounded_channel();
+    let policy = ResubmissionPolicy::new(4, 25, U256::from(1_000))
+        .with_deadline(1)
+        .with_events(sender);
+    let market = StubMarket::rejecting_bids();
+
+    let err = policy
+        .run(
+            unbid_request(),
+            |request| market.submit(request),
+            rebuild,
+            |_| Ok(()),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ClientError::AuctionTimeoutError()), "{err}");
+    assert_eq!(market.max_rewards(), [100]);
+    assert!(matches!(
+        drain(&mut events).as_slice(),
+        [ResubmissionEvent::Stopped {
+            attempts: 0,
+            reason: ResubmissionStop::DeadlinePassed,
+            ..
+        }]
+    ));
+}
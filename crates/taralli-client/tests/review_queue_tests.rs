@@ -0,0 +1,177 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use taralli_client::client::provider::review::{
+    BiddingMode, PendingRequest, ReviewConfig, ReviewQueue, DEFAULT_EXPIRY_MARGIN_SECS,
+};
+use taralli_primitives::alloy::primitives::{FixedBytes, U256};
+use taralli_primitives::intents::{request::ComputeRequest, ComputeIntent};
+use taralli_primitives::systems::SystemParams;
+use tokio::net::TcpListener;
+
+pub mod common;
+use common::fixtures::request_with_inputs;
+
+const EXPIRY_MARGIN: Duration = Duration::from_secs(10);
+const END_AUCTION_TIMESTAMP: u64 = 200;
+
+/// Park the request of `nonce` decided upon at `latest_ts`, returning its id
+fn park(queue: &ReviewQueue, nonce: u64, latest_ts: u64) -> FixedBytes<32> {
+    let request = queued_request(nonce);
+    let request_id = request.compute_id();
+    assert!(queue.park(request_id, request, latest_ts));
+    request_id
+}
+
+/// Serve the review endpoint of `queue`, returning its url
+async fn serve(queue: &ReviewQueue) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let queue = queue.clone();
+    tokio::spawn(async move { queue.serve(listener).await });
+    url
+}
+
+/// Stand-in for the bidder of a provider in manual mode, recording the requests it's handed
+fn spawn_bidder(queue: &ReviewQueue) -> Arc<Mutex<Vec<FixedBytes<32>>>> {
+    let bid_upon = Arc::new(Mutex::new(Vec::new()));
+    let queue = queue.clone();
+    let recorded = bid_upon.clone();
+    tokio::spawn(async move {
+        loop {
+            let (request_id, _request) = queue.next_approved().await;
+            recorded.lock().unwrap().push(request_id);
+        }
+    });
+    bid_upon
+}
+
+/// request auctioned from 100 to `END_AUCTION_TIMESTAMP`
+fn queued_request(nonce: u64) -> ComputeRequest<SystemParams> {
+    let mut request = request_with_inputs(nonce, 4);
+    request.proof_request.maxRewardAmount = U256::from(1_000);
+    request.proof_request.endAuctionTimestamp = END_AUCTION_TIMESTAMP;
+    request
+}
+
+#[tokio::test]
+/// Ensures approved requests reach the bidder and rejected ones never do, with decisions made
+/// through the review endpoint.
+async fn should_only_bid_on_approved_requests() {
+    let queue = ReviewQueue::new(EXPIRY_MARGIN);
+    let url = serve(&queue).await;
+    let bid_upon = spawn_bidder(&queue);
+    let http = reqwest::Client::new();
+
+    let approved = park(&queue, 1, 100);
+    let rejected = park(&queue, 2, 100);
+    let left_pending = park(&queue, 3, 100);
+
+    let pending: Vec<PendingRequest> = http
+        .get(format!("{url}/pending"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(pending.len(), 3);
+    assert!(pending
+        .iter()
+        .all(|pending| pending.expires_in_secs <= 90 && pending.expires_in_secs >= 85));
+
+    let response = http
+        .post(format!("{url}/pending/{approved}/approve"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["decision"], "approved");
+
+    let response = http
+        .post(format!("{url}/pending/{rejected}/reject"))
+        .json(&json!({ "reason": "reward token not worth the risk" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["decision"], "rejected");
+
+    // decisions are final
+    for decision in ["approve", "reject"] {
+        let response = http
+            .post(format!("{url}/pending/{rejected}/{decision}"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+    }
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(*bid_upon.lock().unwrap(), vec![approved]);
+
+    let pending: Vec<PendingRequest> = http
+        .get(format!("{url}/pending"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].request_id, left_pending);
+    assert_eq!(pending[0].request.proof_request.nonce, U256::from(3));
+}
+
+#[tokio::test]
+/// Ensures requests left pending expire ahead of the end of their auction, and requests decided
+/// upon too late to leave any time for review aren't parked.
+async fn should_expire_requests_before_auction_ends() {
+    let queue = ReviewQueue::new(EXPIRY_MARGIN);
+    let bid_upon = spawn_bidder(&queue);
+
+    // a second left before the expiry margin starts
+    let expiring = park(
+        &queue,
+        1,
+        END_AUCTION_TIMESTAMP - EXPIRY_MARGIN.as_secs() - 1,
+    );
+    assert_eq!(queue.pending().len(), 1);
+
+    tokio::time::sleep(Duration::from_millis(1_100)).await;
+    assert!(queue.pending().is_empty());
+    assert!(!queue.approve(expiring));
+
+    let late = queued_request(2);
+    assert!(!queue.park(
+        late.compute_id(),
+        late,
+        END_AUCTION_TIMESTAMP - EXPIRY_MARGIN.as_secs()
+    ));
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(bid_upon.lock().unwrap().is_empty());
+}
+
+#[test]
+/// Ensures providers bid automatically unless configured for manual review.
+fn should_configure_bidding_mode() {
+    let mode: BiddingMode = serde_json::from_value(json!({ "mode": "automatic" })).unwrap();
+    assert_eq!(mode, BiddingMode::default());
+
+    let mode: BiddingMode = serde_json::from_value(json!({
+        "mode": "manual",
+        "listen_address": "127.0.0.1:7070",
+    }))
+    .unwrap();
+    assert_eq!(
+        mode,
+        BiddingMode::Manual(ReviewConfig {
+            listen_address: "127.0.0.1:7070".parse().unwrap(),
+            expiry_margin_secs: DEFAULT_EXPIRY_MARGIN_SECS,
+        })
+    );
+}
@@ -0,0 +1,301 @@
+use std::io::Write;
+use std::sync::Arc;
+
+use taralli_client::analyzer::request::ComputeRequestAnalyzer;
+use taralli_client::analyzer::reward::{
+    NormalizedCost, RewardTokenConfig, RewardTokenPolicy, RewardValuation,
+};
+use taralli_client::config::ProviderStreamingConfigFile;
+use taralli_client::error::ClientError;
+use taralli_client::searcher::request::{rank_requests, rank_requests_net_of_fee, CostModel};
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::{address, Address, U256};
+use taralli_primitives::alloy::providers::{ProviderBuilder, RootProvider};
+use taralli_primitives::alloy::transports::http::{Client, Http};
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::markets::MarketFee;
+use taralli_primitives::systems::SystemParams;
+
+pub mod common;
+use common::fixtures::request_fixture;
+
+const USDC: Address = address!("1c7D4B196Cb0C7B01d743Fbc6116a902379C7238");
+const WETH: Address = address!("fFf9976782d46CC05630D1f6eBAb18b2324d6B14");
+const MEME: Address = address!("00000000000000000000000000000000000000aa");
+const SCAM: Address = address!("00000000000000000000000000000000000000bb");
+
+type StubAnalyzer = ComputeRequestAnalyzer<Http<Client>, RootProvider<Http<Client>>, Ethereum>;
+
+/// USDC and WETH priced at 1 and 2000 USD, MEME accepted unpriced from 1000 raw units
+fn policy() -> RewardTokenPolicy {
+    RewardTokenPolicy::new([
+        RewardTokenConfig {
+            address: USDC,
+            decimals: 6,
+            min_unpriced_amount: None,
+        },
+        RewardTokenConfig {
+            address: WETH,
+            decimals: 18,
+            min_unpriced_amount: None,
+        },
+        RewardTokenConfig {
+            address: MEME,
+            decimals: 18,
+            min_unpriced_amount: Some(U256::from(1_000)),
+        },
+    ])
+    .with_price_source(|token| match token {
+        USDC => Some(1.0),
+        WETH => Some(2_000.0),
+        _ => None,
+    })
+}
+
+fn analyzer() -> StubAnalyzer {
+    ComputeRequestAnalyzer::new(
+        ProviderBuilder::new().on_http("http://localhost:8545".parse().unwrap()),
+        Address::ZERO,
+        Default::default(),
+    )
+}
+
+/// Costs 10 USD to prove any request
+fn ten_dollars() -> Arc<dyn CostModel> {
+    Arc::new(NormalizedCost::new(policy(), |_| 10.0))
+}
+
+/// Request rewarding a fixed `reward` of `token`, auctioned from 100 to 200
+fn rewarded_request(token: Address, reward: U256) -> ComputeRequest<SystemParams> {
+    let mut request = request_fixture();
+    request.proof_request.rewardToken = token;
+    request.proof_request.maxRewardAmount = reward;
+    request.proof_request.minRewardAmount = reward;
+    request
+}
+
+#[test]
+/// Ensures rewards in allowlisted tokens are normalized into USD with their own decimals.
+fn should_normalize_allowlisted_rewards() {
+    let policy = policy();
+
+    assert_eq!(
+        policy.check(&USDC, U256::from(1)).unwrap(),
+        RewardValuation::Priced {
+            usd_price: 1.0,
+            decimals: 6
+        }
+    );
+    assert_eq!(policy.usd_value(&USDC, U256::from(12_500_000)), Some(12.5));
+    assert_eq!(
+        policy.usd_value(&WETH, U256::from(5_000_000_000_000_000u64)),
+        Some(10.0)
+    );
+    assert_eq!(policy.raw_amount(&USDC, 10.0), Some(U256::from(10_000_000)));
+    assert_eq!(
+        policy.raw_amount(&WETH, 10.0),
+        Some(U256::from(5_000_000_000_000_000u64))
+    );
+    assert_eq!(policy.usd_value(&SCAM, U256::from(1)), None);
+}
+
+#[test]
+/// Ensures requests rewarding in tokens missing from the allowlist are rejected.
+fn should_reject_unlisted_reward_tokens() {
+    let analyzer = analyzer().with_reward_tokens(policy());
+
+    let err = analyzer
+        .check_economics(150, &rewarded_request(SCAM, U256::MAX))
+        .unwrap_err();
+    assert!(
+        matches!(err, ClientError::RewardTokenRejected { token, .. } if token == SCAM),
+        "{err}"
+    );
+    assert_eq!(err.error_code(), "reward_token_rejected");
+}
+
+#[test]
+/// Ensures unpriced tokens are only accepted from their minimum raw amount, and rejected when
+/// they have none.
+fn should_accept_unpriced_tokens_from_their_minimum() {
+    let analyzer = analyzer().with_reward_tokens(policy());
+
+    analyzer
+        .check_economics(150, &rewarded_request(MEME, U256::from(1_000)))
+        .unwrap();
+    assert!(matches!(
+        analyzer.check_economics(150, &rewarded_request(MEME, U256::from(999))),
+        Err(ClientError::RewardTokenRejected { token, .. }) if token == MEME
+    ));
+
+    // USDC without a price source is unpriced, and has no minimum
+    let unpriced =
+        analyzer().with_reward_tokens(RewardTokenPolicy::new(policy().token(&USDC).cloned()));
+    assert!(matches!(
+        unpriced.check_economics(150, &rewarded_request(USDC, U256::MAX)),
+        Err(ClientError::RewardTokenRejected { token, .. }) if token == USDC
+    ));
+    assert!(unpriced
+        .check_reward_token(150, &rewarded_request(USDC, U256::MAX).proof_request)
+        .is_err());
+}
+
+#[test]
+/// Ensures the cost model converts a USD cost into each request's reward token, so rewards in
+/// tokens of different prices and decimals are compared in the same unit.
+fn should_compare_rewards_against_normalized_cost() {
+    let analyzer = analyzer()
+        .with_reward_tokens(policy())
+        .with_cost_model(ten_dollars());
+
+    // 12 USDC and 0.006 WETH cover 10 USD
+    analyzer
+        .check_economics(150, &rewarded_request(USDC, U256::from(12_000_000)))
+        .unwrap();
+    analyzer
+        .check_economics(
+            150,
+            &rewarded_request(WETH, U256::from(6_000_000_000_000_000u64)),
+        )
+        .unwrap();
+
+    // 8 USDC doesn't
+    let err = analyzer
+        .check_economics(150, &rewarded_request(USDC, U256::from(8_000_000)))
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ClientError::UnprofitableIntent { reward, cost }
+                if reward == U256::from(8_000_000) && cost == U256::from(10_000_000)
+        ),
+        "{err}"
+    );
+
+    // unpriced tokens cost their minimum, unlisted ones can't be covered
+    let cost_model = ten_dollars();
+    assert_eq!(
+        cost_model.cost(&rewarded_request(MEME, U256::ZERO)),
+        U256::from(1_000)
+    );
+    assert_eq!(
+        cost_model.cost(&rewarded_request(SCAM, U256::ZERO)),
+        U256::MAX
+    );
+
+    // ranked by margin in raw units, unlisted tokens are dropped
+    let ranked = rank_requests(
+        vec![
+            rewarded_request(USDC, U256::from(12_000_000)),
+            rewarded_request(USDC, U256::from(8_000_000)),
+            rewarded_request(SCAM, U256::from(u64::MAX)),
+        ],
+        150,
+        cost_model.as_ref(),
+    );
+    assert_eq!(ranked.len(), 1);
+    assert_eq!(ranked[0].margin(), U256::from(2_000_000));
+}
+
+#[test]
+/// Ensures the market's fee shifts the reward accepted up to the one covering the cost once the
+/// fee is taken, and requests are ranked by their margin net of the fee.
+fn should_cover_cost_net_of_market_fee() {
+    let analyzer = analyzer()
+        .with_reward_tokens(policy())
+        .with_cost_model(ten_dollars());
+    let fee = MarketFee::from_bps(500);
+
+    // 10.5 USDC covers 10 USD without fee, not once 5% of it is taken
+    analyzer
+        .check_economics(150, &rewarded_request(USDC, U256::from(10_500_000)))
+        .unwrap();
+    let analyzer = analyzer.with_market_fee(fee);
+    let err = analyzer
+        .check_economics(150, &rewarded_request(USDC, U256::from(10_500_000)))
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ClientError::UnprofitableIntent { reward, cost }
+                if reward == U256::from(9_975_000) && cost == U256::from(10_000_000)
+        ),
+        "{err}"
+    );
+
+    // the threshold moves to the smallest reward netting the cost
+    let threshold = fee.gross_reward(U256::from(10_000_000));
+    assert_eq!(threshold, U256::from(10_526_315));
+    analyzer
+        .check_economics(150, &rewarded_request(USDC, threshold))
+        .unwrap();
+    assert!(matches!(
+        analyzer.check_economics(150, &rewarded_request(USDC, threshold - U256::from(1))),
+        Err(ClientError::UnprofitableIntent { .. })
+    ));
+
+    let requests = vec![
+        rewarded_request(USDC, U256::from(12_000_000)),
+        rewarded_request(USDC, U256::from(10_500_000)),
+    ];
+    assert_eq!(
+        rank_requests(requests.clone(), 150, ten_dollars().as_ref()).len(),
+        2
+    );
+    let ranked = rank_requests_net_of_fee(requests, 150, ten_dollars().as_ref(), fee);
+    assert_eq!(ranked.len(), 1);
+    assert_eq!(ranked[0].current_reward, U256::from(11_400_000));
+    assert_eq!(ranked[0].margin(), U256::from(1_400_000));
+}
+
+#[test]
+/// Ensures the reward token allowlist is loaded from the provider's TOML config file.
+fn should_load_reward_tokens_from_toml() {
+    let mut file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    write!(
+        file,
+        r#"
+supported_systems = ["risc0"]
+
+[validation_config]
+maximum_allowed_stake = 1000
+
+[validation_config.base]
+minimum_proving_time = 30
+maximum_start_delay = 300
+supported_systems = ["risc0"]
+
+[[reward_tokens]]
+address = "{USDC}"
+decimals = 6
+
+[[reward_tokens]]
+address = "{MEME}"
+decimals = 18
+min_unpriced_amount = "1000"
+"#
+    )
+    .unwrap();
+
+    let config = ProviderStreamingConfigFile::from_file(file.path().to_str().unwrap()).unwrap();
+    let reward_tokens = config.reward_tokens.expect("reward tokens not loaded");
+    assert_eq!(
+        reward_tokens,
+        vec![
+            RewardTokenConfig {
+                address: USDC,
+                decimals: 6,
+                min_unpriced_amount: None,
+            },
+            RewardTokenConfig {
+                address: MEME,
+                decimals: 18,
+                min_unpriced_amount: Some(U256::from(1_000)),
+            },
+        ]
+    );
+
+    let policy = RewardTokenPolicy::new(reward_tokens);
+    assert!(policy.check(&MEME, U256::from(1_000)).is_ok());
+    assert!(policy.check(&USDC, U256::from(1_000)).is_err());
+}
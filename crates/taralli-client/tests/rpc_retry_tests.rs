@@ -0,0 +1,222 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use taralli_client::api::retry::RetryPolicy;
+use taralli_client::error::ClientError;
+use taralli_client::rpc::{classify_rpc_error, retry_rpc, RpcErrorKind};
+use taralli_primitives::alloy::providers::{Provider, ProviderBuilder};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+fn no_backoff() -> RetryPolicy {
+    RetryPolicy {
+        max_retries: 3,
+        initial_backoff: Duration::ZERO,
+        max_backoff: Duration::ZERO,
+        deadline: Duration::from_secs(30),
+    }
+}
+
+#[test]
+/// Ensures the errors alloy reports are classified from their json-rpc code or http status, their
+/// message otherwise.
+fn should_classify_rpc_errors() {
+    for (message, kind) in [
+        (
+            "server returned an error response: error code -32601: the method eth_foo does not exist/is not available",
+            RpcErrorKind::Method,
+        ),
+        (
+            "server returned an error response: error code -32602: invalid argument 0: hex string has length 3",
+            RpcErrorKind::Method,
+        ),
+        (
+            "server returned an error response: error code 3: execution reverted: AuctionEnded, data: \"0x\"",
+            RpcErrorKind::Revert,
+        ),
+        (
+            "HTTP error 429 with body: {\"error\":\"too many requests\"}",
+            RpcErrorKind::RateLimited,
+        ),
+        (
+            "server returned an error response: error code -32005: limit exceeded",
+            RpcErrorKind::RateLimited,
+        ),
+        (
+            "HTTP error 503 with body: service unavailable",
+            RpcErrorKind::Transient,
+        ),
+        (
+            "server returned an error response: error code -32000: header not found",
+            RpcErrorKind::Transient,
+        ),
+        (
+            "error sending request for url (http://127.0.0.1:8545/): connection reset by peer",
+            RpcErrorKind::Transient,
+        ),
+        (
+            "deserialization error: missing field `number` at line 1 column 20",
+            RpcErrorKind::Method,
+        ),
+    ] {
+        assert_eq!(classify_rpc_error(message), kind, "{message}");
+    }
+}
+
+#[tokio::test]
+/// Ensures only transient and rate limited failures are attempted again, as many times as the
+/// policy allows, and the kind of the last failure is surfaced.
+async fn should_retry_transient_kinds_only() {
+    for (message, kind, attempts) in [
+        ("connection reset by peer", RpcErrorKind::Transient, 4),
+        ("HTTP error 429 with body: ", RpcErrorKind::RateLimited, 4),
+        (
+            "error code -32601: the method eth_foo does not exist/is not available",
+            RpcErrorKind::Method,
+            1,
+        ),
+        ("error code 3: execution reverted", RpcErrorKind::Revert, 1),
+    ] {
+        let calls = &AtomicU32::new(0);
+        let err = retry_rpc(&no_backoff(), || async move {
+            calls.fetch_add(1, Ordering::Relaxed);
+            Err::<(), _>(message)
+        })
+        .await
+        .unwrap_err();
+
+        assert_eq!(calls.load(Ordering::Relaxed), attempts, "{message}");
+        assert!(
+            matches!(err, ClientError::RpcFailed { kind: failed, .. } if failed == kind),
+            "{err}"
+        );
+        assert_eq!(err.is_transient(), kind.is_retryable());
+    }
+
+    // succeeding before running out of retries
+    let calls = &AtomicU32::new(0);
+    let value = retry_rpc(&no_backoff(), || async move {
+        match calls.fetch_add(1, Ordering::Relaxed) {
+            0 | 1 => Err("operation timed out"),
+            _ => Ok(7),
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(value, 7);
+    assert_eq!(calls.load(Ordering::Relaxed), 3);
+
+    // retries disabled
+    let calls = &AtomicU32::new(0);
+    retry_rpc(
+        &RetryPolicy {
+            max_retries: 0,
+            ..no_backoff()
+        },
+        || async move {
+            calls.fetch_add(1, Ordering::Relaxed);
+            Err::<(), _>("connection reset by peer")
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(calls.load(Ordering::Relaxed), 1);
+}
+
+/// Stub rpc answering the `n`th request with `responses[n]` as status and json-rpc result or
+/// error, the last response repeating, counting the requests it receives
+async fn stub_rpc(responses: Vec<(u16, &'static str)>) -> (String, Arc<AtomicU32>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicU32::new(0));
+
+    let received = requests.clone();
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let received = received.clone();
+            let responses = responses.clone();
+            tokio::spawn(async move {
+                while let Some(id) = read_request_id(&mut stream).await {
+                    let n = received.fetch_add(1, Ordering::Relaxed) as usize;
+                    let (status, payload) = responses[n.min(responses.len() - 1)];
+                    let body = if status == 200 {
+                        format!("{{\"jsonrpc\":\"2.0\",\"id\":{id},{payload}}}")
+                    } else {
+                        payload.to_string()
+                    };
+                    let response = format!(
+                        "HTTP/1.1 {status} STUB\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{body}",
+                        body.len()
+                    );
+                    if stream.write_all(response.as_bytes()).await.is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+    });
+
+    (url, requests)
+}
+
+/// Read a whole json-rpc request, returning its id, `None` once the connection is closed
+async fn read_request_id(stream: &mut TcpStream) -> Option<u64> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        if let Some(headers_end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            let headers = String::from_utf8_lossy(&buffer[..headers_end]).to_lowercase();
+            let content_length: usize = headers
+                .lines()
+                .find_map(|line| line.strip_prefix("content-length:"))
+                .map_or(0, |length| length.trim().parse().unwrap());
+            let body_start = headers_end + 4;
+            if buffer.len() >= body_start + content_length {
+                let body: serde_json::Value =
+                    serde_json::from_slice(&buffer[body_start..body_start + content_length])
+                        .unwrap();
+                return body["id"].as_u64();
+            }
+        }
+        let read = stream.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+}
+
+#[tokio::test]
+/// Ensures rpc failures reported by alloy itself are classified and retried as their kind allows.
+async fn should_retry_provider_calls_by_kind() {
+    let (url, requests) = stub_rpc(vec![
+        (503, "service unavailable"),
+        (429, "too many requests"),
+        (200, "\"result\":\"0x10\""),
+    ])
+    .await;
+    let provider = &ProviderBuilder::new().on_http(url.parse().unwrap());
+    let block = retry_rpc(&no_backoff(), || async move {
+        provider.get_block_number().await
+    })
+    .await
+    .unwrap();
+    assert_eq!(block, 16);
+    assert_eq!(requests.load(Ordering::Relaxed), 3);
+
+    let (url, requests) = stub_rpc(vec![(
+        200,
+        "\"error\":{\"code\":-32601,\"message\":\"the method eth_blockNumber does not exist/is not available\"}",
+    )])
+    .await;
+    let provider = &ProviderBuilder::new().on_http(url.parse().unwrap());
+    let err = retry_rpc(&no_backoff(), || async move {
+        provider.get_block_number().await
+    })
+    .await
+    .unwrap_err();
+    assert_eq!(err.rpc_error_kind(), Some(RpcErrorKind::Method), "{err}");
+    assert_eq!(requests.load(Ordering::Relaxed), 1);
+}
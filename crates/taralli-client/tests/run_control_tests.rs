@@ -0,0 +1,322 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use taralli_client::client::provider::control::{
+    IntentStage, LogFilter, RunControl, RunReport, RunState,
+};
+use taralli_client::client::provider::streaming::ProviderStreamingClient;
+use taralli_client::worker::WorkResult;
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta::ProofRequest;
+use taralli_primitives::alloy::primitives::{Address, Bytes, FixedBytes, B256, U256};
+use taralli_primitives::alloy::providers::ProviderBuilder;
+use taralli_primitives::alloy::signers::local::PrivateKeySigner;
+use taralli_primitives::systems::SystemId;
+use tokio::net::TcpListener;
+use tracing_subscriber::{fmt, layer::SubscriberExt, reload, EnvFilter, Registry};
+
+pub mod common;
+use common::rpc::unreachable_url;
+
+fn proof_request_fixture(nonce: u64) -> ProofRequest {
+    ProofRequest {
+        signer: Address::ZERO,
+        market: Address::ZERO,
+        nonce: U256::from(nonce),
+        rewardToken: Address::ZERO,
+        maxRewardAmount: U256::from(200),
+        minRewardAmount: U256::from(100),
+        minimumStake: 0,
+        startAuctionTimestamp: 100,
+        endAuctionTimestamp: 200,
+        provingTime: 60,
+        inputsCommitment: B256::ZERO,
+        extraData: vec![].into(),
+    }
+}
+
+fn work_result_fixture() -> WorkResult {
+    WorkResult {
+        opaque_submission: Bytes::from(vec![1, 2, 3]),
+        partial_commitment: FixedBytes::ZERO,
+    }
+}
+
+/// Stand in for the run loop: take no new intent once shutdown is requested, then publish the
+/// report once the intents in flight are done
+async fn run_until_shutdown(control: RunControl, in_flight: tokio::task::JoinHandle<()>) {
+    while control.accepting().await {
+        control.interrupted().await;
+    }
+    in_flight.await.unwrap();
+    control.finish();
+}
+
+#[tokio::test]
+/// Ensures pausing stops the intake of intents until resumed, and shutting down ends it.
+async fn should_pause_and_resume_intake() {
+    let control = RunControl::new();
+    assert_eq!(control.state(), RunState::Running);
+    assert!(control.accepting().await);
+
+    control.pause();
+    assert_eq!(control.status().state, RunState::Paused);
+    assert!(
+        tokio::time::timeout(Duration::from_millis(100), control.accepting())
+            .await
+            .is_err(),
+        "a paused run shouldn't take intents"
+    );
+    tokio::time::timeout(Duration::from_millis(100), control.interrupted())
+        .await
+        .expect("pausing should interrupt the run");
+
+    let accepting = tokio::spawn({
+        let control = control.clone();
+        async move { control.accepting().await }
+    });
+    control.resume();
+    assert!(accepting.await.unwrap());
+
+    // pausing once shutdown is requested doesn't take the run out of it
+    let shutdown = tokio::spawn({
+        let control = control.clone();
+        async move { control.shutdown(Duration::from_millis(10)).await }
+    });
+    while control.state() != RunState::ShuttingDown {
+        tokio::task::yield_now().await;
+    }
+    control.pause();
+    assert_eq!(control.state(), RunState::ShuttingDown);
+    assert!(!control.accepting().await);
+    control.finish();
+    shutdown.await.unwrap();
+}
+
+#[tokio::test]
+/// Ensures proofs completing within the grace period are still resolved and counted once
+/// shutdown is requested.
+async fn should_complete_in_flight_work_within_grace() {
+    let control = RunControl::new();
+    let intent_id = FixedBytes::from([1; 32]);
+    let in_flight = control.track(intent_id, SystemId::Risc0, proof_request_fixture(1));
+    in_flight.proving();
+    assert_eq!(control.status().proving, 1);
+
+    let work = tokio::spawn({
+        let control = control.clone();
+        async move {
+            let work_result = control
+                .within_grace(async {
+                    tokio::time::sleep(Duration::from_millis(50)).await;
+                    work_result_fixture()
+                })
+                .await
+                .expect("proof should complete within the grace period");
+            in_flight.awaiting_resolve(&work_result);
+            assert_eq!(control.status().awaiting_resolve, 1);
+            in_flight.complete();
+        }
+    });
+    let run = tokio::spawn(run_until_shutdown(control.clone(), work));
+
+    let report: RunReport = control.shutdown(Duration::from_secs(5)).await;
+    run.await.unwrap();
+    assert_eq!(report.completed, 1);
+    assert!(report.abandoned.is_empty(), "{report:?}");
+    assert_eq!(control.status().completed, 1);
+}
+
+#[tokio::test]
+/// Ensures proofs outliving the grace period are abandoned and listed in the report at the stage
+/// they were at, while intents never bid upon are left out.
+async fn should_report_work_abandoned_past_grace() {
+    let control = RunControl::new();
+    let proving_id = FixedBytes::from([1; 32]);
+    let proven_id = FixedBytes::from([2; 32]);
+    let queued_id = FixedBytes::from([3; 32]);
+
+    let proving = control.track(proving_id, SystemId::Risc0, proof_request_fixture(1));
+    proving.proving();
+    let proven = control.track(proven_id, SystemId::Sp1, proof_request_fixture(2));
+    proven.proving();
+    proven.awaiting_resolve(&work_result_fixture());
+    let queued = control.track(queued_id, SystemId::Risc0, proof_request_fixture(3));
+
+    let work = tokio::spawn({
+        let control = control.clone();
+        async move {
+            let proof = control
+                .within_grace(tokio::time::sleep(Duration::from_secs(60)))
+                .await;
+            assert!(proof.is_none(), "proof shouldn't outlive the grace period");
+            proving.abandon("shutdown grace period expired while proving");
+            drop(queued);
+        }
+    });
+    // the proven intent is still awaiting its resolve when the run ends
+    let run = tokio::spawn(run_until_shutdown(control.clone(), work));
+
+    let report = control.shutdown(Duration::from_millis(50)).await;
+    run.await.unwrap();
+    drop(proven);
+
+    assert_eq!(report.completed, 0);
+    assert_eq!(report.abandoned.len(), 2, "{report:?}");
+    let abandoned_proving = report
+        .abandoned
+        .iter()
+        .find(|intent| intent.intent_id == proving_id)
+        .expect("proving intent should be reported");
+    assert_eq!(abandoned_proving.stage, IntentStage::Proving);
+    assert_eq!(
+        abandoned_proving.reason,
+        "shutdown grace period expired while proving"
+    );
+    assert!(abandoned_proving.work_result.is_none());
+
+    let abandoned_proven = report
+        .abandoned
+        .iter()
+        .find(|intent| intent.intent_id == proven_id)
+        .expect("proven intent should be reported");
+    assert_eq!(abandoned_proven.stage, IntentStage::AwaitingResolve);
+    assert_eq!(abandoned_proven.system_id, SystemId::Sp1);
+    assert_eq!(abandoned_proven.reason, "run ended");
+    assert_eq!(
+        abandoned_proven
+            .work_result
+            .as_ref()
+            .map(|work_result| work_result.opaque_submission.clone()),
+        Some(work_result_fixture().opaque_submission)
+    );
+    assert!(report
+        .abandoned
+        .iter()
+        .all(|intent| intent.intent_id != queued_id));
+}
+
+#[tokio::test]
+/// Ensures a run failing to subscribe still publishes its report, so shutting it down returns
+/// rather than waiting on it forever.
+async fn should_finish_run_failing_to_subscribe() {
+    let client = ProviderStreamingClient::new(
+        unreachable_url().await,
+        ProviderBuilder::new().on_http(unreachable_url().await),
+        PrivateKeySigner::random(),
+        Address::ZERO,
+        Default::default(),
+    );
+    let (control, run) = client.run_with_control();
+    assert!(run.await.is_err());
+
+    let report = tokio::time::timeout(Duration::from_secs(1), control.shutdown(Duration::ZERO))
+        .await
+        .expect("report of the failed run was published");
+    assert_eq!(report.completed, 0);
+    assert!(report.abandoned.is_empty());
+}
+
+#[test]
+/// Ensures the control sets the log filter through the log control it was given, and dumps the
+/// intents in flight at info even when the filter only keeps errors.
+fn should_set_log_filter_and_dump_state() {
+    assert!(RunControl::new().set_log_filter("debug").is_err());
+
+    let logged = Arc::new(Mutex::new(Vec::new()));
+    let (filter, log_control) = reload::Layer::<_, Registry>::new(EnvFilter::new("info"));
+    let writer = logged.clone();
+    let subscriber = Registry::default().with(filter).with(
+        fmt::layer()
+            .with_ansi(false)
+            .with_writer(move || LogWriter(writer.clone())),
+    );
+
+    tracing::subscriber::with_default(subscriber, || {
+        let control = RunControl::with_log_control(Some(Arc::new(log_control.clone())));
+        let in_flight = control.track(
+            B256::repeat_byte(1),
+            SystemId::Risc0,
+            proof_request_fixture(1),
+        );
+        in_flight.proving();
+
+        control.set_log_filter("error").unwrap();
+        assert!(control.set_log_filter("bidder=loud").is_err());
+        assert!(log_control.log_filter().contains("error"));
+        tracing::info!("filtered out");
+        control.dump_state();
+
+        let logged = String::from_utf8(logged.lock().unwrap().clone()).unwrap();
+        assert!(!logged.contains("filtered out"), "{logged}");
+        assert!(logged.contains("0 queued, 1 proving"), "{logged}");
+        assert!(
+            logged.contains(&B256::repeat_byte(1).to_string()),
+            "{logged}"
+        );
+        assert!(logged.contains("log filter: error"), "{logged}");
+        in_flight.complete();
+    });
+}
+
+#[tokio::test]
+/// Ensures the control endpoint of a run reports and replaces the log filter, turning down an
+/// invalid one, and dumps the run's state on request.
+async fn should_serve_log_filter_and_state_dumps() {
+    let (_filter, log_control) = reload::Layer::<_, Registry>::new(EnvFilter::new("info"));
+    let control = RunControl::with_log_control(Some(Arc::new(log_control.clone())));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let served = control.clone();
+    tokio::spawn(async move { served.serve(listener).await });
+    let http = reqwest::Client::new();
+
+    let response = http
+        .put(format!("{url}/log-filter"))
+        .json(&json!({ "filter": "warn,taralli_client::bidder=debug" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 200);
+    assert!(log_control
+        .log_filter()
+        .contains("taralli_client::bidder=debug"));
+
+    let response = http
+        .put(format!("{url}/log-filter"))
+        .json(&json!({ "filter": "bidder=loud" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), 400);
+    let body: Value = http
+        .get(format!("{url}/log-filter"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(body["filter"]
+        .as_str()
+        .unwrap()
+        .contains("taralli_client::bidder=debug"));
+    assert_eq!(control.log_filter(), Some(log_control.log_filter()));
+
+    let response = http.post(format!("{url}/dump-state")).send().await.unwrap();
+    assert_eq!(response.status(), 204);
+}
+
+/// Writer appending the log lines to a shared buffer
+struct LogWriter(Arc<Mutex<Vec<u8>>>);
+
+impl std::io::Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
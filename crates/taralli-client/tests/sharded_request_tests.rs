@@ -0,0 +1,198 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use taralli_client::intent_builder::request::ComputeRequestBuilder;
+use taralli_client::intent_builder::sharded::{ShardTag, ShardedRequestBuilder};
+use taralli_client::tracker::sharded::{ShardOutcome, ShardStatus, ShardedOutcome};
+use taralli_primitives::abi::extensions::decode_request_extensions;
+use taralli_primitives::abi::universal_bombetta::VerifierDetails;
+use taralli_primitives::abi::verifier_details::{decode_request_verifier_details, DecodeMode};
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::{address, fixed_bytes, keccak256, Address, B256, U256};
+use taralli_primitives::alloy::providers::{ProviderBuilder, RootProvider};
+use taralli_primitives::alloy::signers::{local::PrivateKeySigner, Signer};
+use taralli_primitives::alloy::sol_types::SolValue;
+use taralli_primitives::alloy::transports::http::{Client, Http};
+use taralli_primitives::intents::ComputeIntent;
+use taralli_primitives::markets::SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS;
+use taralli_primitives::systems::{System, SystemId, SystemInputs};
+use taralli_primitives::validation::request::{
+    validate_request, RequestValidationConfig, RequestVerifierConstraints,
+};
+
+const SIGNER_ADDRESS: Address = address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+const SIGNER_PRIV_KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+fn builder_fixture() -> ComputeRequestBuilder<Http<Client>, RootProvider<Http<Client>>, Ethereum> {
+    let extra_data = VerifierDetails {
+        verifier: address!("AC292cF957Dd5BA174cdA13b05C16aFC71700327"),
+        selector: fixed_bytes!("ab750e75"),
+        isShaCommitment: false,
+        inputsOffset: U256::from(32),
+        inputsLength: U256::from(64),
+        hasPartialCommitmentResultCheck: false,
+        submittedPartialCommitmentResultOffset: U256::ZERO,
+        submittedPartialCommitmentResultLength: U256::ZERO,
+        predeterminedPartialCommitment: B256::ZERO,
+    }
+    .abi_encode();
+
+    ComputeRequestBuilder::new(
+        ProviderBuilder::new().on_http("http://localhost:8545".parse().unwrap()),
+        SIGNER_ADDRESS,
+        SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+        SystemId::Risc0,
+    )
+    .system(serde_json::json!({ "elf": [1, 2, 3] }))
+    .nonce(U256::from(7))
+    .set_time_params(1_000, 1_060, 120)
+    .set_token_params(1, U256::from(10), U256::from(1001))
+    .set_verification_commitment_params(B256::ZERO, extra_data.into())
+}
+
+#[test]
+/// Ensures a job splits into shards with unique nonces, rewards summing to the configured totals,
+/// staggered auctions, their own inputs and commitments, and a tag linking them together.
+fn should_split_job_into_linked_shards() {
+    let job_id = B256::repeat_byte(0x42);
+    let shards = ShardedRequestBuilder::new(builder_fixture(), 4)
+        .job_id(job_id)
+        .auction_stagger(30)
+        .shard_inputs(|index| SystemInputs::Bytes(vec![index as u8; 32]))
+        .shard_commitment_preimage(|index| vec![index as u8; 32])
+        .build_shards()
+        .unwrap();
+    assert_eq!(shards.len(), 4);
+
+    let nonces: HashSet<U256> = shards
+        .iter()
+        .map(|shard| shard.proof_request.nonce)
+        .collect();
+    assert_eq!(nonces.len(), 4);
+
+    let (min_total, max_total) =
+        shards
+            .iter()
+            .fold((U256::ZERO, U256::ZERO), |(min, max), shard| {
+                (
+                    min + shard.proof_request.minRewardAmount,
+                    max + shard.proof_request.maxRewardAmount,
+                )
+            });
+    assert_eq!(min_total, U256::from(10));
+    assert_eq!(max_total, U256::from(1001));
+    // shares differ by at most one unit
+    assert_eq!(shards[0].proof_request.maxRewardAmount, U256::from(251));
+    assert_eq!(shards[3].proof_request.maxRewardAmount, U256::from(250));
+
+    for (index, shard) in shards.iter().enumerate() {
+        let proof_request = &shard.proof_request;
+        assert_eq!(
+            proof_request.startAuctionTimestamp,
+            1_000 + 30 * index as u64
+        );
+        assert_eq!(proof_request.endAuctionTimestamp, 1_060 + 30 * index as u64);
+        assert_eq!(
+            shard.system.inputs(),
+            SystemInputs::Bytes(vec![index as u8; 32])
+        );
+        assert_eq!(
+            proof_request.inputsCommitment,
+            keccak256(vec![index as u8; 32])
+        );
+        assert_eq!(
+            ShardTag::from_extra_data(&proof_request.extraData),
+            Some(ShardTag {
+                job_id,
+                index,
+                count: 4,
+            })
+        );
+    }
+}
+
+#[tokio::test]
+/// Ensures signed shards pass validation and strict decoding, their tag round-tripping through the
+/// extensions along with the builder's own.
+async fn should_validate_sharded_requests() {
+    let signer = PrivateKeySigner::from_str(SIGNER_PRIV_KEY).unwrap();
+    let validation_config = RequestValidationConfig {
+        maximum_allowed_stake: u128::MAX,
+        ..Default::default()
+    };
+    let mut shards = ShardedRequestBuilder::new(builder_fixture().urgency(2), 3)
+        .job_id(B256::repeat_byte(0x42))
+        .shard_inputs(|index| SystemInputs::Bytes(vec![index as u8; 32]))
+        .build_shards()
+        .unwrap();
+
+    for (index, shard) in shards.iter_mut().enumerate() {
+        shard.signature = signer
+            .sign_hash(&shard.compute_permit2_digest(validation_config.base.chain_id))
+            .await
+            .unwrap();
+        validate_request(
+            shard,
+            &validation_config,
+            &RequestVerifierConstraints::default(),
+        )
+        .unwrap();
+        let extra_data = &shard.proof_request.extraData;
+        decode_request_verifier_details(extra_data, DecodeMode::Strict).unwrap();
+
+        let extensions = decode_request_extensions(extra_data).unwrap();
+        assert_eq!(extensions.urgency, Some(2));
+        assert_eq!(
+            extensions.shard,
+            Some(ShardTag {
+                job_id: B256::repeat_byte(0x42),
+                index,
+                count: 3,
+            })
+        );
+    }
+}
+
+#[test]
+/// Ensures a job can't be split without shard inputs or into zero shards.
+fn should_reject_incomplete_sharding() {
+    let err = ShardedRequestBuilder::new(builder_fixture(), 2)
+        .build_shards()
+        .unwrap_err();
+    assert!(err.to_string().contains("shard inputs"), "{err}");
+
+    let err = ShardedRequestBuilder::new(builder_fixture(), 0)
+        .shard_inputs(|_| SystemInputs::Bytes(vec![0; 32]))
+        .build_shards()
+        .unwrap_err();
+    assert!(err.to_string().contains("at least 1"), "{err}");
+}
+
+#[test]
+/// Ensures shard outcomes aggregate into a complete outcome only when every shard resolved,
+/// listing the shards that didn't otherwise.
+fn should_aggregate_mixed_shard_outcomes() {
+    let outcome = |index: usize, status: ShardStatus| ShardOutcome {
+        index,
+        request_id: B256::repeat_byte(index as u8),
+        status,
+    };
+
+    let complete = ShardedOutcome::aggregate(vec![
+        outcome(1, ShardStatus::Resolved),
+        outcome(0, ShardStatus::Resolved),
+    ]);
+    assert!(complete.is_complete());
+    assert!(complete.failed_shards().is_empty());
+    assert_eq!(complete.shards()[0].index, 0);
+
+    let partial = ShardedOutcome::aggregate(vec![
+        outcome(3, ShardStatus::Failed("rpc down".into())),
+        outcome(0, ShardStatus::Resolved),
+        outcome(2, ShardStatus::Unresolved),
+        outcome(1, ShardStatus::NoBid),
+    ]);
+    assert!(!partial.is_complete());
+    assert_eq!(partial.failed_shards(), &[1, 2, 3]);
+    assert_eq!(partial.shards().len(), 4);
+}
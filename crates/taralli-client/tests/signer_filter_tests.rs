@@ -0,0 +1,136 @@
+use std::time::Duration;
+
+use taralli_client::signer_filter::{
+    SignerFilter, SignerFilterConfig, SignerFilterStats, SignerRejection,
+};
+use taralli_primitives::alloy::primitives::Address;
+use taralli_primitives::intents::envelope::unix_time_ms;
+
+fn signer(byte: u8) -> Address {
+    Address::repeat_byte(byte)
+}
+
+fn auto_banning(after: u32) -> SignerFilterConfig {
+    SignerFilterConfig {
+        auto_ban_after: Some(after),
+        auto_ban_duration: Duration::from_secs(3600),
+    }
+}
+
+#[test]
+/// Ensures denied and banned signers are filtered out and counted, bans being lifted once expired
+/// or removed.
+fn should_filter_denied_signers() {
+    let filter = SignerFilter::new(SignerFilterConfig::default());
+    assert_eq!(filter.check(signer(1)), Ok(()));
+
+    filter.deny(signer(1), "inputs crash the prover");
+    assert_eq!(
+        filter.check(signer(1)),
+        Err(SignerRejection::Denied {
+            reason: "inputs crash the prover".to_string()
+        })
+    );
+    assert_eq!(filter.check(signer(2)), Ok(()));
+
+    filter.ban(signer(2), Duration::from_secs(60), "spam");
+    assert!(matches!(
+        filter.check(signer(2)),
+        Err(SignerRejection::Banned { .. })
+    ));
+    // a ban in the past no longer applies and is dropped from the denylist
+    filter.ban_until(signer(3), unix_time_ms() / 1000 - 1, "expired");
+    assert_eq!(filter.check(signer(3)), Ok(()));
+    assert!(!filter.lists().denylist.contains_key(&signer(3)));
+
+    filter.undeny(signer(1));
+    assert_eq!(filter.check(signer(1)), Ok(()));
+
+    assert_eq!(
+        filter.stats(),
+        SignerFilterStats {
+            denied: 1,
+            banned: 1,
+            not_allowlisted: 0,
+            auto_bans: 0,
+        }
+    );
+}
+
+#[test]
+/// Ensures only allowlisted signers are processed once the allowlist is set, and every signer
+/// again once it's emptied.
+fn should_only_admit_allowlisted_signers() {
+    let filter = SignerFilter::new(SignerFilterConfig::default());
+    filter.allow(signer(1));
+    assert_eq!(filter.check(signer(1)), Ok(()));
+    assert_eq!(
+        filter.check(signer(2)),
+        Err(SignerRejection::NotAllowlisted)
+    );
+
+    // the denylist takes precedence over the allowlist
+    filter.deny(signer(1), "denied");
+    assert!(matches!(
+        filter.check(signer(1)),
+        Err(SignerRejection::Denied { .. })
+    ));
+
+    filter.disallow(signer(1));
+    assert_eq!(filter.check(signer(2)), Ok(()));
+    assert_eq!(filter.stats().filtered(), 2);
+}
+
+#[test]
+/// Ensures signers are banned after the configured number of consecutive worker failures, a
+/// success resetting the count.
+fn should_auto_ban_after_consecutive_failures() {
+    let filter = SignerFilter::new(auto_banning(3));
+
+    assert!(!filter.record_failure(signer(1)));
+    assert!(!filter.record_failure(signer(1)));
+    filter.record_success(signer(1));
+    assert!(!filter.record_failure(signer(1)));
+    assert!(!filter.record_failure(signer(1)));
+    assert_eq!(filter.check(signer(1)), Ok(()));
+
+    assert!(filter.record_failure(signer(1)));
+    let Err(SignerRejection::Banned { expires_at, .. }) = filter.check(signer(1)) else {
+        panic!("signer should be banned");
+    };
+    assert!(expires_at > unix_time_ms() / 1000 + 3500);
+    assert_eq!(filter.stats().auto_bans, 1);
+    // failures of other signers are counted separately
+    assert_eq!(filter.check(signer(2)), Ok(()));
+
+    // without a configured count, failures never ban
+    let filter = SignerFilter::new(SignerFilterConfig::default());
+    for _ in 0..10 {
+        assert!(!filter.record_failure(signer(1)));
+    }
+    assert_eq!(filter.check(signer(1)), Ok(()));
+}
+
+#[test]
+/// Ensures the lists, including automatic bans, are persisted and loaded back.
+fn should_persist_signer_lists() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("signers.json");
+
+    let filter = SignerFilter::load(auto_banning(1), &path).unwrap();
+    filter.allow(signer(1));
+    filter.deny(signer(2), "denied");
+    filter.record_failure(signer(3));
+    let lists = filter.lists();
+    assert_eq!(lists.denylist.len(), 2);
+
+    let loaded = SignerFilter::load(auto_banning(1), &path).unwrap();
+    assert_eq!(loaded.lists(), lists);
+    assert!(matches!(
+        loaded.check(signer(3)),
+        Err(SignerRejection::Banned { .. })
+    ));
+
+    std::fs::write(&path, "not json").unwrap();
+    assert!(SignerFilter::load(auto_banning(1), &path).is_err());
+}
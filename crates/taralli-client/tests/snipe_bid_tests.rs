@@ -0,0 +1,171 @@
+use taralli_client::bidder::request::{snipe_bid_timestamp, target_bid_timestamp, SnipeParams};
+use taralli_client::error::ClientError;
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta::ProofRequest;
+use taralli_primitives::alloy::primitives::{Address, B256, U256};
+use taralli_primitives::intents::auction::RewardCurve;
+
+fn proof_request_fixture(min_reward: u64, max_reward: u64, start: u64, end: u64) -> ProofRequest {
+    ProofRequest {
+        signer: Address::ZERO,
+        market: Address::ZERO,
+        nonce: U256::ZERO,
+        rewardToken: Address::ZERO,
+        maxRewardAmount: U256::from(max_reward),
+        minRewardAmount: U256::from(min_reward),
+        minimumStake: 1,
+        startAuctionTimestamp: start,
+        endAuctionTimestamp: end,
+        provingTime: 30,
+        inputsCommitment: B256::ZERO,
+        extraData: vec![].into(),
+    }
+}
+
+fn snipe(safety_window_secs: u64) -> SnipeParams {
+    SnipeParams {
+        safety_window_secs,
+        priority_fee_bump: 1_000_000_000,
+    }
+}
+
+#[test]
+/// Ensures snipe bids are sent as the safety window starts, once the reward has reached the
+/// reservation price.
+fn should_snipe_at_start_of_safety_window() {
+    // (auction, decided at, reservation price, safety window, snipe timestamp)
+    let auctions = [
+        // reward rising from 100 to 500, the reservation price is reached long before the window
+        (
+            proof_request_fixture(100, 500, 1_000, 1_100),
+            1_000,
+            200,
+            10,
+            1_090,
+        ),
+        // decided upon mid auction
+        (
+            proof_request_fixture(100, 500, 1_000, 1_100),
+            1_060,
+            200,
+            10,
+            1_090,
+        ),
+        // reservation price reached exactly as the window starts
+        (
+            proof_request_fixture(100, 500, 1_000, 1_100),
+            1_000,
+            460,
+            10,
+            1_090,
+        ),
+        // reservation price below the minimum reward
+        (
+            proof_request_fixture(300, 1_300, 1_000, 1_100),
+            1_000,
+            100,
+            30,
+            1_070,
+        ),
+        // fixed reward, nothing to gain by waiting but nothing revealed either
+        (
+            proof_request_fixture(500, 500, 1_000, 1_060),
+            1_000,
+            500,
+            5,
+            1_055,
+        ),
+        // no safety window, bid at the very end of the auction
+        (
+            proof_request_fixture(100, 2_100, 1_000, 1_100),
+            1_000,
+            1_700,
+            0,
+            1_100,
+        ),
+    ];
+
+    for (proof_request, latest_ts, reservation_price, safety_window_secs, expected) in auctions {
+        let reservation_price = U256::from(reservation_price);
+        let snipe_ts = snipe_bid_timestamp(
+            &proof_request,
+            latest_ts,
+            reservation_price,
+            &snipe(safety_window_secs),
+        )
+        .unwrap();
+        assert_eq!(snipe_ts, expected);
+
+        // never later than the safety window allows
+        assert!(snipe_ts + safety_window_secs <= proof_request.endAuctionTimestamp);
+        // never earlier than the reservation price allows, nor than the target strategy would bid
+        let curve = RewardCurve::try_from(&proof_request).unwrap();
+        assert!(curve.reward_at(snipe_ts) >= reservation_price);
+        assert!(
+            snipe_ts >= target_bid_timestamp(&proof_request, latest_ts, reservation_price).unwrap()
+        );
+    }
+}
+
+#[test]
+/// Ensures auctions whose reward doesn't reach the reservation price before the safety window
+/// starts aren't sniped.
+fn should_not_snipe_past_safety_window() {
+    // (auction, decided at, reservation price, safety window)
+    let auctions = [
+        // reservation price only reached at 1_095, within the safety window
+        (
+            proof_request_fixture(100, 500, 1_000, 1_100),
+            1_000,
+            480,
+            10,
+        ),
+        // reservation price above the maximum reward
+        (
+            proof_request_fixture(100, 500, 1_000, 1_100),
+            1_000,
+            501,
+            10,
+        ),
+        // fixed reward below the reservation price
+        (proof_request_fixture(500, 500, 1_000, 1_060), 1_000, 501, 5),
+        // decided upon once the safety window started
+        (
+            proof_request_fixture(100, 500, 1_000, 1_100),
+            1_095,
+            200,
+            10,
+        ),
+        // safety window longer than the auction
+        (
+            proof_request_fixture(100, 500, 1_000, 1_100),
+            1_000,
+            100,
+            200,
+        ),
+    ];
+
+    for (proof_request, latest_ts, reservation_price, safety_window_secs) in auctions {
+        let err = snipe_bid_timestamp(
+            &proof_request,
+            latest_ts,
+            U256::from(reservation_price),
+            &snipe(safety_window_secs),
+        )
+        .unwrap_err();
+        assert!(
+            matches!(err, ClientError::TransactionSetupError(_)),
+            "{err}"
+        );
+    }
+}
+
+#[test]
+/// Ensures auctions that haven't started or already ended aren't sniped.
+fn should_not_snipe_outside_auction() {
+    let proof_request = proof_request_fixture(100, 500, 1_000, 1_100);
+    for latest_ts in [999, 1_101] {
+        assert!(
+            snipe_bid_timestamp(&proof_request, latest_ts, U256::from(100), &snipe(10)).is_err()
+        );
+    }
+}
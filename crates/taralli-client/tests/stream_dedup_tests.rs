@@ -0,0 +1,148 @@
+use axum::{http::header, routing::get, Router};
+use base64::prelude::{Engine, BASE64_STANDARD};
+use futures::StreamExt;
+use taralli_client::api::dedup::RecentIntents;
+use taralli_client::api::subscribe::{StreamItem, SubscribeApiClient};
+use taralli_primitives::alloy::primitives::{FixedBytes, U256};
+use taralli_primitives::compression_utils::{compression, intents::ComputeRequestCompressed};
+use taralli_primitives::intents::envelope::{
+    unix_time_ms, BroadcastEnvelope, BroadcastMeta, IntentKind, SSE_INTENT_EVENT,
+};
+use taralli_primitives::intents::{request::ComputeRequest, ComputeIntent};
+use taralli_primitives::systems::{SystemId, SystemParams};
+use tokio::net::TcpListener;
+use url::Url;
+
+pub mod common;
+use common::fixtures::request_with_inputs;
+
+/// Server-sent event broadcasting `request` as the server does
+fn intent_event(request: &ComputeRequest<SystemParams>, sequence: u64) -> String {
+    let compressed = ComputeRequestCompressed {
+        system_id: request.system_id,
+        system: compression::compress_brotli(&serde_json::to_vec(&request.system).unwrap())
+            .unwrap(),
+        proof_request: request.proof_request.clone(),
+        signature: request.signature,
+    };
+    let envelope = BroadcastEnvelope::new(
+        IntentKind::Request,
+        BroadcastMeta {
+            sequence,
+            received_at_ms: unix_time_ms(),
+        },
+        compressed,
+    );
+    let bytes = bincode::serialize(&envelope).unwrap();
+    format!(
+        "event: {SSE_INTENT_EVENT}\ndata: {}\n\n",
+        BASE64_STANDARD.encode(bytes)
+    )
+}
+
+/// Serve `body` as the event stream of every subscription, the websocket route being missing so
+/// clients fall back to server-sent events. Returns the server's url.
+async fn serve_events(body: String) -> Url {
+    let app = Router::new().route(
+        "/subscribe/sse",
+        get(move || {
+            let body = body.clone();
+            async move { ([(header::CONTENT_TYPE, "text/event-stream")], body) }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    url.parse().unwrap()
+}
+
+/// Ids of the requests yielded by a subscription of `client`, until the stream ends
+async fn received(client: &SubscribeApiClient) -> Vec<FixedBytes<32>> {
+    let stream = client.subscribe().await.unwrap();
+    stream
+        .filter_map(|item| async move {
+            match item.unwrap() {
+                StreamItem::Request(request, _) => Some(request.compute_id()),
+                _ => None,
+            }
+        })
+        .collect()
+        .await
+}
+
+/// request whose auction is still running
+fn live_request(nonce: u64) -> ComputeRequest<SystemParams> {
+    let now = unix_time_ms() / 1000;
+    let mut request = request_with_inputs(nonce, 4);
+    request.proof_request.maxRewardAmount = U256::from(1_000);
+    request.proof_request.startAuctionTimestamp = now;
+    request.proof_request.endAuctionTimestamp = now + 600;
+    request
+}
+
+#[tokio::test]
+/// Ensures a request broadcast twice is yielded once, and counted as a duplicate.
+async fn should_drop_rebroadcast_requests() {
+    let first = live_request(1);
+    let second = live_request(2);
+    let body = [
+        intent_event(&first, 1),
+        intent_event(&first, 2),
+        intent_event(&second, 3),
+    ]
+    .concat();
+
+    let client =
+        SubscribeApiClient::new(serve_events(body.clone()).await, SystemId::Risc0.as_bit())
+            .with_sse_fallback(true);
+    assert_eq!(
+        received(&client).await,
+        vec![first.compute_id(), second.compute_id()]
+    );
+    assert_eq!(client.duplicates_dropped(), 1);
+
+    // without deduplication each broadcast is yielded
+    let client = SubscribeApiClient::new(serve_events(body).await, SystemId::Risc0.as_bit())
+        .with_sse_fallback(true)
+        .with_dedup_capacity(0);
+    assert_eq!(received(&client).await.len(), 3);
+    assert_eq!(client.duplicates_dropped(), 0);
+}
+
+#[test]
+/// Ensures intents are forgotten once their auction ended, and the first to expire once full,
+/// so memory stays bounded under sustained traffic.
+fn should_bound_recent_intents() {
+    let mut recent = RecentIntents::new(3);
+    let id = |byte: u8| FixedBytes::<32>::repeat_byte(byte);
+
+    assert!(recent.insert(id(1), 100, 10));
+    assert!(!recent.insert(id(1), 100, 50));
+    // seen again once its auction ended, it can't be bid on and is let through
+    assert!(recent.insert(id(1), 100, 100));
+
+    for byte in 2..=20 {
+        assert!(recent.insert(id(byte), 1_000, 100));
+        assert!(recent.len() <= 3);
+    }
+    // the oldest were evicted, the latest still dropped
+    assert!(!recent.insert(id(20), 1_000, 100));
+    assert!(recent.insert(id(2), 1_000, 100));
+
+    // expired intents go before live ones are evicted
+    let mut recent = RecentIntents::new(2);
+    assert!(recent.insert(id(1), 50, 10));
+    assert!(recent.insert(id(2), 1_000, 10));
+    assert!(recent.insert(id(3), 1_000, 60));
+    assert!(!recent.insert(id(2), 1_000, 60));
+    recent.remove_expired(1_000);
+    assert!(recent.is_empty());
+
+    // when full of live intents, the one whose auction ends first is evicted
+    let mut recent = RecentIntents::new(2);
+    assert!(recent.insert(id(1), 2_000, 10));
+    assert!(recent.insert(id(2), 1_000, 10));
+    assert!(recent.insert(id(3), 3_000, 10));
+    assert!(!recent.insert(id(1), 2_000, 10));
+    assert!(recent.insert(id(2), 1_000, 10));
+}
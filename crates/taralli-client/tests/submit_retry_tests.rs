@@ -0,0 +1,186 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use taralli_client::api::retry::RetryPolicy;
+use taralli_client::api::submit::SubmitApiClient;
+use taralli_primitives::intents::envelope::IDEMPOTENCY_KEY_HEADER;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use url::Url;
+
+pub mod common;
+use common::fixtures::request_fixture;
+
+/// Stub server answering the first `failures` requests with `failure` and the rest with 200,
+/// recording the idempotency key of every request it receives
+struct StubServer {
+    url: Url,
+    idempotency_keys: Arc<Mutex<Vec<String>>>,
+}
+
+impl StubServer {
+    async fn start(failures: usize, failure: &'static str) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        let idempotency_keys = Arc::new(Mutex::new(Vec::new()));
+
+        let keys = idempotency_keys.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let key = read_request(&mut stream).await;
+                let attempt = {
+                    let mut keys = keys.lock().unwrap();
+                    keys.push(key);
+                    keys.len()
+                };
+                let response = if attempt <= failures {
+                    failure
+                } else {
+                    "HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\n{}"
+                };
+                stream.write_all(response.as_bytes()).await.unwrap();
+                stream.shutdown().await.unwrap();
+            }
+        });
+
+        Self {
+            url,
+            idempotency_keys,
+        }
+    }
+
+    fn idempotency_keys(&self) -> Vec<String> {
+        self.idempotency_keys.lock().unwrap().clone()
+    }
+}
+
+/// Read a whole request, returning its idempotency key
+async fn read_request(stream: &mut TcpStream) -> String {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 4096];
+    let header_end = loop {
+        let read = stream.read(&mut buffer).await.unwrap();
+        request.extend_from_slice(&buffer[..read]);
+        if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+    };
+
+    let headers = String::from_utf8_lossy(&request[..header_end]).to_lowercase();
+    let header_value = |name: &str| {
+        headers
+            .lines()
+            .find_map(|line| line.strip_prefix(&format!("{name}: ")))
+            .map(|value| value.trim().to_string())
+    };
+    let content_length: usize = header_value("content-length")
+        .expect("request without content-length")
+        .parse()
+        .unwrap();
+    while request.len() < header_end + content_length {
+        let read = stream.read(&mut buffer).await.unwrap();
+        request.extend_from_slice(&buffer[..read]);
+    }
+
+    header_value(IDEMPOTENCY_KEY_HEADER).expect("request without idempotency key")
+}
+
+fn policy(max_retries: u32, backoff: Duration) -> RetryPolicy {
+    RetryPolicy {
+        max_retries,
+        initial_backoff: backoff,
+        max_backoff: backoff * 4,
+        deadline: Duration::from_secs(10),
+    }
+}
+
+#[tokio::test]
+/// Ensures 5xx responses are retried with the same idempotency key until the server succeeds.
+async fn should_retry_server_errors_with_same_idempotency_key() {
+    let server = StubServer::start(
+        2,
+        "HTTP/1.1 502 Bad Gateway\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+    )
+    .await;
+    let client = SubmitApiClient::new(server.url.clone())
+        .with_retry_policy(policy(3, Duration::from_millis(40)));
+
+    let started = Instant::now();
+    let response = client.submit_intent(request_fixture()).await.unwrap();
+
+    assert_eq!(response.status(), 200);
+    let keys = server.idempotency_keys();
+    assert_eq!(keys.len(), 3);
+    assert!(keys.iter().all(|key| key == &keys[0]));
+    // full jitter backoffs of at most 40ms and 80ms
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
+
+#[tokio::test]
+/// Ensures 429 responses wait for the server's Retry-After before retrying.
+async fn should_honor_retry_after() {
+    let server = StubServer::start(
+        1,
+        "HTTP/1.1 429 Too Many Requests\r\nretry-after: 1\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+    )
+    .await;
+    let client = SubmitApiClient::new(server.url.clone())
+        .with_retry_policy(policy(3, Duration::from_millis(10)));
+
+    let started = Instant::now();
+    let response = client.submit_intent(request_fixture()).await.unwrap();
+
+    assert_eq!(response.status(), 200);
+    assert_eq!(server.idempotency_keys().len(), 2);
+    assert!(started.elapsed() >= Duration::from_secs(1));
+}
+
+#[tokio::test]
+/// Ensures the last failed response is returned once retries are exhausted.
+async fn should_stop_after_max_retries() {
+    let server = StubServer::start(
+        usize::MAX,
+        "HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+    )
+    .await;
+    let client = SubmitApiClient::new(server.url.clone())
+        .with_retry_policy(policy(2, Duration::from_millis(10)));
+
+    let response = client.submit_intent(request_fixture()).await.unwrap();
+
+    assert_eq!(response.status(), 503);
+    assert_eq!(server.idempotency_keys().len(), 3);
+}
+
+#[tokio::test]
+/// Ensures client errors are not retried.
+async fn should_not_retry_client_errors() {
+    let server = StubServer::start(
+        usize::MAX,
+        "HTTP/1.1 400 Bad Request\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+    )
+    .await;
+    let client = SubmitApiClient::new(server.url.clone())
+        .with_retry_policy(policy(3, Duration::from_millis(10)));
+
+    let response = client.submit_intent(request_fixture()).await.unwrap();
+
+    assert_eq!(response.status(), 400);
+    assert_eq!(server.idempotency_keys().len(), 1);
+}
+
+#[test]
+/// Ensures backoffs double per retry, stay within their ceiling and are capped by max_backoff.
+fn should_bound_backoff() {
+    let policy = policy(10, Duration::from_millis(100));
+
+    assert_eq!(policy.backoff_ceiling(0), Duration::from_millis(100));
+    assert_eq!(policy.backoff_ceiling(1), Duration::from_millis(200));
+    assert_eq!(policy.backoff_ceiling(2), Duration::from_millis(400));
+    assert_eq!(policy.backoff_ceiling(5), Duration::from_millis(400));
+    assert_eq!(policy.backoff_ceiling(u32::MAX), Duration::from_millis(400));
+    for retry in 0..10 {
+        assert!(policy.backoff(retry) <= policy.backoff_ceiling(retry));
+    }
+}
@@ -0,0 +1,178 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use taralli_client::error::{ClientError, Result};
+use taralli_client::submitter::{
+    PrivateSubmitter, RawTransactionSender, TransactionSigner, TransactionSubmitter,
+};
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::{Bytes, B256};
+use taralli_primitives::alloy::rpc::types::{TransactionReceipt, TransactionRequest};
+
+pub mod common;
+use common::fixtures::receipt_fixture;
+
+const TRANSACTION_HASH: B256 = B256::repeat_byte(0xaa);
+const RAW_TRANSACTION: [u8; 4] = [2, 1, 1, 1];
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Stub signer counting the transactions it signs
+#[derive(Default)]
+struct StubSigner {
+    signed: AtomicUsize,
+}
+
+#[async_trait]
+impl TransactionSigner<Ethereum> for StubSigner {
+    async fn sign_transaction(&self, _transaction: TransactionRequest) -> Result<(B256, Bytes)> {
+        self.signed.fetch_add(1, Ordering::SeqCst);
+        Ok((TRANSACTION_HASH, Bytes::from(RAW_TRANSACTION)))
+    }
+}
+
+/// Stub rpc recording the raw transactions broadcast through it. Transactions it `includes`
+/// land on the shared `chain`, whose receipts every rpc reports.
+struct StubRpc {
+    accepts: bool,
+    includes: bool,
+    chain: Arc<AtomicBool>,
+    broadcast: Mutex<Vec<Bytes>>,
+}
+
+impl StubRpc {
+    fn new(chain: &Arc<AtomicBool>, accepts: bool, includes: bool) -> Arc<Self> {
+        Arc::new(Self {
+            accepts,
+            includes,
+            chain: chain.clone(),
+            broadcast: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn broadcast(&self) -> Vec<Bytes> {
+        self.broadcast.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl RawTransactionSender<Ethereum> for StubRpc {
+    async fn send_raw_transaction(&self, raw: &Bytes) -> Result<B256> {
+        self.broadcast.lock().unwrap().push(raw.clone());
+        if !self.accepts {
+            return Err(ClientError::TransactionError("rejected".into()));
+        }
+        if self.includes {
+            self.chain.store(true, Ordering::SeqCst);
+        }
+        Ok(TRANSACTION_HASH)
+    }
+
+    async fn transaction_receipt(
+        &self,
+        transaction_hash: B256,
+    ) -> Result<Option<TransactionReceipt>> {
+        Ok(self
+            .chain
+            .load(Ordering::SeqCst)
+            .then(|| receipt_fixture(transaction_hash)))
+    }
+}
+
+fn submitter(
+    signer: &Arc<StubSigner>,
+    private: &Arc<StubRpc>,
+    public: &Arc<StubRpc>,
+    fallback_timeout: Duration,
+) -> PrivateSubmitter<Ethereum> {
+    PrivateSubmitter::new(
+        signer.clone(),
+        private.clone(),
+        public.clone(),
+        fallback_timeout,
+    )
+    .with_receipt_timeout(Duration::from_millis(500))
+    .with_poll_interval(POLL_INTERVAL)
+}
+
+#[tokio::test]
+/// Ensures included private submissions never reach the public path.
+async fn should_submit_privately() {
+    let chain = Arc::new(AtomicBool::new(false));
+    let signer = Arc::new(StubSigner::default());
+    let private = StubRpc::new(&chain, true, true);
+    let public = StubRpc::new(&chain, true, true);
+
+    let receipt = submitter(&signer, &private, &public, Duration::from_millis(100))
+        .send_and_wait_receipt(TransactionRequest::default())
+        .await
+        .unwrap();
+
+    assert_eq!(receipt.transaction_hash, TRANSACTION_HASH);
+    assert_eq!(private.broadcast(), vec![Bytes::from(RAW_TRANSACTION)]);
+    assert!(public.broadcast().is_empty());
+}
+
+#[tokio::test]
+/// Ensures the transaction signed for the private rpc is rebroadcast as is publicly once it
+/// isn't included in time, rather than signed again with another nonce.
+async fn should_rebroadcast_same_transaction_when_private_submission_never_lands() {
+    let chain = Arc::new(AtomicBool::new(false));
+    let signer = Arc::new(StubSigner::default());
+    let private = StubRpc::new(&chain, true, false);
+    let public = StubRpc::new(&chain, true, true);
+
+    let receipt = submitter(&signer, &private, &public, Duration::from_millis(100))
+        .send_and_wait_receipt(TransactionRequest::default())
+        .await
+        .unwrap();
+
+    assert_eq!(receipt.transaction_hash, TRANSACTION_HASH);
+    assert_eq!(signer.signed.load(Ordering::SeqCst), 1);
+    assert_eq!(private.broadcast(), vec![Bytes::from(RAW_TRANSACTION)]);
+    assert_eq!(public.broadcast(), private.broadcast());
+}
+
+#[tokio::test]
+/// Ensures the public path is used right away when the private rpc rejects the transaction.
+async fn should_fall_back_when_private_submission_fails() {
+    let chain = Arc::new(AtomicBool::new(false));
+    let signer = Arc::new(StubSigner::default());
+    let private = StubRpc::new(&chain, false, false);
+    let public = StubRpc::new(&chain, true, true);
+
+    let receipt = tokio::time::timeout(
+        Duration::from_secs(5),
+        submitter(&signer, &private, &public, Duration::from_secs(3600))
+            .send_and_wait_receipt(TransactionRequest::default()),
+    )
+    .await
+    .expect("Fallback didn't kick in")
+    .unwrap();
+
+    assert_eq!(receipt.transaction_hash, TRANSACTION_HASH);
+    assert_eq!(public.broadcast(), vec![Bytes::from(RAW_TRANSACTION)]);
+}
+
+#[tokio::test]
+/// Ensures a transaction included on neither path is given up on once the receipt timeout
+/// expires, after being rebroadcast publicly.
+async fn should_time_out_when_never_included() {
+    let chain = Arc::new(AtomicBool::new(false));
+    let signer = Arc::new(StubSigner::default());
+    let private = StubRpc::new(&chain, true, false);
+    let public = StubRpc::new(&chain, true, false);
+
+    let err = tokio::time::timeout(
+        Duration::from_secs(5),
+        submitter(&signer, &private, &public, Duration::from_millis(100))
+            .send_and_wait_receipt(TransactionRequest::default()),
+    )
+    .await
+    .expect("Receipt wait didn't time out")
+    .unwrap_err();
+
+    assert!(matches!(err, ClientError::TransactionFailure(_)), "{err}");
+    assert_eq!(public.broadcast(), vec![Bytes::from(RAW_TRANSACTION)]);
+}
@@ -0,0 +1,245 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use taralli_client::tracker::offer::ComputeOfferTracker;
+use taralli_client::tracker::request::ComputeRequestTracker;
+use taralli_client::tracker::{IntentAuctionTracker, TrackerConfig};
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta;
+use taralli_primitives::abi::universal_porchetta::UniversalPorchetta;
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::{Address, Bytes, B256, U256};
+use taralli_primitives::alloy::sol_types::{SolEvent, SolValue};
+use taralli_primitives::alloy::transports::http::{Client, Http};
+use taralli_primitives::alloy::utils::hex;
+
+pub mod common;
+use common::rpc::{method_not_found, start_stub_node, stub_provider, StubProvider};
+
+const MARKET: Address = Address::repeat_byte(0x0c);
+const REQUESTER: Address = Address::repeat_byte(0x0a);
+const PROVIDER: Address = Address::repeat_byte(0x0b);
+const REWARD_TOKEN: Address = Address::repeat_byte(0x0d);
+const INTENT_ID: B256 = B256::repeat_byte(0x11);
+const RESOLVE_TX_HASH: B256 = B256::repeat_byte(0x42);
+const HEAD: u64 = 16;
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+const TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Answer of the stub node to a method it doesn't handle itself, given the request's params
+type Handler = Arc<dyn Fn(&str, &Value) -> Option<Value> + Send + Sync>;
+
+/// Stub JSON-RPC node whose log filters never deliver any log, answering other methods through
+/// `handler`
+async fn start_node(handler: Handler) -> StubProvider {
+    let url = start_stub_node(move |request| {
+        let method = request["method"].as_str().unwrap();
+        let result = match method {
+            "eth_blockNumber" => Some(json!(format!("{HEAD:#x}"))),
+            "eth_chainId" => Some(json!("0x1")),
+            "eth_newFilter" => Some(json!("0x1")),
+            "eth_getFilterChanges" => Some(json!([])),
+            "eth_uninstallFilter" => Some(json!(true)),
+            _ => handler(method, &request["params"]),
+        };
+        match result {
+            Some(result) => json!({ "result": result }),
+            None => method_not_found(),
+        }
+    })
+    .await;
+    stub_provider(url)
+}
+
+fn hex_json(bytes: impl AsRef<[u8]>) -> Value {
+    json!(hex::encode_prefixed(bytes))
+}
+
+/// `activeProofRequestData` of a request bid upon by `provider`
+fn active_request_data(provider: Address) -> Value {
+    hex_json(
+        (
+            REQUESTER,
+            provider,
+            U256::from(2_000_000_000u64),
+            REWARD_TOKEN,
+            U256::from(1000),
+            U256::from(7),
+            B256::ZERO,
+            Bytes::new(),
+        )
+            .abi_encode_params(),
+    )
+}
+
+/// Log of `event` as served by `eth_getLogs`
+fn log_json(event: &impl SolEvent, tx_hash: B256) -> Value {
+    let data = event.encode_log_data();
+    json!({
+        "address": MARKET,
+        "topics": data.topics(),
+        "data": hex_json(&data.data),
+        "blockHash": B256::repeat_byte(0x01),
+        "blockNumber": format!("{HEAD:#x}"),
+        "transactionHash": tx_hash,
+        "transactionIndex": "0x0",
+        "logIndex": "0x0",
+        "removed": false
+    })
+}
+
+/// Whether `eth_getLogs` was called for the logs of `E`
+fn queries<E: SolEvent>(params: &Value) -> bool {
+    params[0]["topics"][0] == json!(E::SIGNATURE_HASH)
+}
+
+fn request_tracker(
+    rpc_provider: StubProvider,
+    tracker_config: TrackerConfig,
+) -> ComputeRequestTracker<Http<Client>, StubProvider, Ethereum> {
+    ComputeRequestTracker::new(rpc_provider, MARKET).with_tracker_config(tracker_config)
+}
+
+#[tokio::test]
+/// Ensures a bid whose event is never delivered by the log filter is still reported from the
+/// market's state, the stake being the one the provider sent.
+async fn should_report_bid_from_market_state() {
+    let rpc_provider = start_node(Arc::new(|method, _| match method {
+        "eth_call" => Some(active_request_data(PROVIDER)),
+        "eth_getLogs" => Some(json!([])),
+        _ => None,
+    }))
+    .await;
+
+    let bid = request_tracker(rpc_provider, TrackerConfig::polling(POLL_INTERVAL))
+        .track_auction(INTENT_ID, TIMEOUT)
+        .await
+        .unwrap()
+        .expect("bid not reported");
+    assert_eq!(bid.requestId, INTENT_ID);
+    assert_eq!(bid.signer, REQUESTER);
+    assert_eq!(bid.provider, PROVIDER);
+    assert_eq!(bid.rewardToken, REWARD_TOKEN);
+    assert_eq!(bid.rewardAmount, U256::from(1000));
+    assert_eq!(bid.ethStake, U256::from(7));
+}
+
+#[tokio::test]
+/// Ensures the bid event is returned over the state when `eth_getLogs` serves it, and that a
+/// request not bid upon yet isn't reported.
+async fn should_reconcile_bid_with_logged_event() {
+    let logged = UniversalBombetta::Bid {
+        signer: REQUESTER,
+        requestId: INTENT_ID,
+        rewardToken: REWARD_TOKEN,
+        rewardAmount: U256::from(1000),
+        ethStake: U256::from(5),
+        provider: PROVIDER,
+    };
+    let log = log_json(&logged, B256::repeat_byte(0x43));
+    let rpc_provider = start_node(Arc::new(move |method, params| match method {
+        "eth_call" => Some(active_request_data(PROVIDER)),
+        "eth_getLogs" if queries::<UniversalBombetta::Bid>(params) => Some(json!([log.clone()])),
+        _ => None,
+    }))
+    .await;
+    let tracker = request_tracker(rpc_provider, TrackerConfig::polling(POLL_INTERVAL));
+    let bid = tracker.bid_from_state(INTENT_ID, 0).await.unwrap().unwrap();
+    assert_eq!(bid.ethStake, U256::from(5));
+
+    let rpc_provider = start_node(Arc::new(|method, _| match method {
+        "eth_call" => Some(active_request_data(Address::ZERO)),
+        _ => None,
+    }))
+    .await;
+    let tracker = request_tracker(rpc_provider, TrackerConfig::polling(POLL_INTERVAL));
+    assert!(tracker
+        .bid_from_state(INTENT_ID, 0)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+/// Ensures a resolve missed by the log filter is found through `eth_getLogs`, along with the
+/// hash of the transaction that emitted it.
+async fn should_report_resolve_from_polled_logs() {
+    let resolve = UniversalBombetta::Resolve {
+        signer: REQUESTER,
+        requestId: INTENT_ID,
+        resolver: PROVIDER,
+    };
+    let log = log_json(&resolve, RESOLVE_TX_HASH);
+    let rpc_provider = start_node(Arc::new(move |method, params| match method {
+        "eth_getLogs" if queries::<UniversalBombetta::Resolve>(params) => {
+            Some(json!([log.clone()]))
+        }
+        "eth_getLogs" => Some(json!([])),
+        _ => None,
+    }))
+    .await;
+
+    let (resolve_event, tx_hash) =
+        request_tracker(rpc_provider, TrackerConfig::polling(POLL_INTERVAL))
+            .track_resolve_with_tx_hash(INTENT_ID, TIMEOUT)
+            .await
+            .unwrap()
+            .expect("resolve not reported");
+    assert_eq!(resolve_event.requestId, INTENT_ID);
+    assert_eq!(resolve_event.resolver, PROVIDER);
+    assert_eq!(tx_hash, RESOLVE_TX_HASH);
+}
+
+#[tokio::test]
+/// Ensures the market isn't polled unless configured to, the tracker then relying on the log
+/// filter alone.
+async fn should_not_poll_market_by_default() {
+    let rpc_provider = start_node(Arc::new(|method, _| match method {
+        "eth_call" => panic!("market state polled while polling is disabled"),
+        _ => None,
+    }))
+    .await;
+
+    let bid = request_tracker(rpc_provider, TrackerConfig::default())
+        .track_auction(INTENT_ID, Duration::from_millis(500))
+        .await
+        .unwrap();
+    assert!(bid.is_none());
+}
+
+#[tokio::test]
+/// Ensures an offer's bid missed by the log filter is reported from the market's state.
+async fn should_report_offer_bid_from_market_state() {
+    let rpc_provider = start_node(Arc::new(|method, _| match method {
+        "eth_call" => Some(hex_json(
+            (
+                PROVIDER,
+                REQUESTER,
+                U256::from(2_000_000_000u64),
+                REWARD_TOKEN,
+                U256::from(1000),
+                REWARD_TOKEN,
+                U256::from(9),
+                B256::ZERO,
+                Bytes::new(),
+            )
+                .abi_encode_params(),
+        )),
+        "eth_getLogs" => Some(json!([])),
+        _ => None,
+    }))
+    .await;
+
+    let tracker: ComputeOfferTracker<Http<Client>, StubProvider, Ethereum> =
+        ComputeOfferTracker::new(rpc_provider, MARKET)
+            .with_tracker_config(TrackerConfig::polling(POLL_INTERVAL));
+    let bid: UniversalPorchetta::Bid = tracker
+        .track_auction(INTENT_ID, TIMEOUT)
+        .await
+        .unwrap()
+        .expect("bid not reported");
+    assert_eq!(bid.offerId, INTENT_ID);
+    assert_eq!(bid.signer, PROVIDER);
+    assert_eq!(bid.requester, REQUESTER);
+    assert_eq!(bid.stakeAmount, U256::from(9));
+}
@@ -0,0 +1,200 @@
+use serde_json::json;
+use taralli_client::analyzer::request::ComputeRequestAnalyzer;
+use taralli_client::analyzer::verifier::VerifierCheckConfig;
+use taralli_client::error::ClientError;
+use taralli_primitives::abi::extensions::{
+    decode_request_extensions, decode_request_verifier_alternatives, with_request_extensions,
+    Extensions,
+};
+use taralli_primitives::abi::universal_bombetta::ProofRequestVerifierDetails;
+use taralli_primitives::abi::verifier_details::{
+    decode_verifier_details, REQUEST_VERIFIER_DETAILS_LENGTH,
+};
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::{
+    address, fixed_bytes, Address, FixedBytes, B256, U256,
+};
+use taralli_primitives::alloy::providers::{ProviderBuilder, RootProvider};
+use taralli_primitives::alloy::sol_types::SolValue;
+use taralli_primitives::alloy::transports::http::{Client, Http};
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::systems::SystemParams;
+
+pub mod common;
+use common::fixtures::request_fixture;
+use common::rpc::{method_not_found, start_stub_node};
+
+const OLD_VERIFIER: Address = address!("1111111111111111111111111111111111111111");
+const NEW_VERIFIER: Address = address!("2222222222222222222222222222222222222222");
+const SELECTOR: FixedBytes<4> = fixed_bytes!("deadbeef");
+
+type StubAnalyzer = ComputeRequestAnalyzer<Http<Client>, RootProvider<Http<Client>>, Ethereum>;
+
+/// Verifier details of `verifier`, reading inputs at `inputs_offset`
+fn verifier_details(verifier: Address, inputs_offset: u64) -> ProofRequestVerifierDetails {
+    ProofRequestVerifierDetails {
+        verifier,
+        selector: SELECTOR,
+        isShaCommitment: verifier == OLD_VERIFIER,
+        inputsOffset: U256::from(inputs_offset),
+        inputsLength: U256::from(64),
+        hasPartialCommitmentResultCheck: false,
+        submittedPartialCommitmentResultOffset: U256::ZERO,
+        submittedPartialCommitmentResultLength: U256::ZERO,
+        predeterminedPartialCommitment: B256::ZERO,
+    }
+}
+
+/// Request verified by the old verifier, or alternatively by the new one
+fn request_with_alternative() -> ComputeRequest<SystemParams> {
+    let extra_data = with_request_extensions(
+        &verifier_details(OLD_VERIFIER, 32).abi_encode(),
+        &Extensions {
+            urgency: Some(1),
+            verifier_alternatives: vec![verifier_details(NEW_VERIFIER, 96)],
+            ..Default::default()
+        },
+    );
+    request_with_extra_data(extra_data.to_vec())
+}
+
+/// Analyzer whose workers format submissions for `verifiers` only, without checking them on-chain,
+/// on a market reading verifier alternatives
+fn analyzer(verifiers: &[Address]) -> StubAnalyzer {
+    analyzer_at("http://localhost:8545".parse().unwrap(), verifiers)
+        .with_verifier_alternatives_market(Address::ZERO, true)
+}
+
+/// Analyzer whose workers format submissions for `verifiers` only, reading the market through the
+/// rpc at `rpc_url`
+fn analyzer_at(rpc_url: reqwest::Url, verifiers: &[Address]) -> StubAnalyzer {
+    ComputeRequestAnalyzer::new(
+        ProviderBuilder::new().on_http(rpc_url),
+        Address::ZERO,
+        Default::default(),
+    )
+    .with_verifier_check(VerifierCheckConfig {
+        enabled: false,
+        ..Default::default()
+    })
+    .with_supported_verifiers(verifiers.iter().map(|verifier| (*verifier, SELECTOR)))
+}
+
+fn request_with_extra_data(extra_data: Vec<u8>) -> ComputeRequest<SystemParams> {
+    let mut request = request_fixture();
+    request.proof_request.minRewardAmount = U256::from(100);
+    request.proof_request.maxRewardAmount = U256::from(100);
+    request.proof_request.extraData = extra_data.into();
+    request
+}
+
+#[tokio::test]
+/// Ensures the analyzer selects the second alternative when its workers only format for it, and
+/// the worker is given the request led by it so offsets and hash flag match.
+async fn should_select_second_alternative() {
+    let request = request_with_alternative();
+    let analyzer = analyzer(&[NEW_VERIFIER]);
+
+    let selected = analyzer
+        .select_verifier(&request.proof_request)
+        .await
+        .unwrap();
+    assert_eq!(selected, verifier_details(NEW_VERIFIER, 96));
+
+    let work_request = analyzer.with_selected_verifier(&request).await.unwrap();
+    let leading = decode_verifier_details(&work_request.proof_request.extraData).unwrap();
+    assert_eq!(leading.verifier, NEW_VERIFIER);
+    assert_eq!(leading.inputsOffset, U256::from(96));
+    assert!(!leading.isShaCommitment);
+    // the extensions are kept, and only the worker's copy changes
+    assert_eq!(
+        work_request.proof_request.extraData[REQUEST_VERIFIER_DETAILS_LENGTH..],
+        request.proof_request.extraData[REQUEST_VERIFIER_DETAILS_LENGTH..]
+    );
+    assert_eq!(
+        decode_request_extensions(&work_request.proof_request.extraData)
+            .unwrap()
+            .urgency,
+        Some(1)
+    );
+    assert_eq!(
+        decode_request_verifier_alternatives(&request.proof_request.extraData).unwrap()[0].verifier,
+        OLD_VERIFIER
+    );
+}
+
+#[tokio::test]
+/// Ensures the leading verifier details are preferred when supported, and requests without
+/// alternatives are given to the worker as they are.
+async fn should_prefer_leading_verifier() {
+    let request = request_with_alternative();
+    let analyzer = analyzer(&[OLD_VERIFIER, NEW_VERIFIER]);
+    assert_eq!(
+        analyzer
+            .select_verifier(&request.proof_request)
+            .await
+            .unwrap()
+            .verifier,
+        OLD_VERIFIER
+    );
+    let work_request = analyzer.with_selected_verifier(&request).await.unwrap();
+    assert_eq!(
+        work_request.proof_request.extraData,
+        request.proof_request.extraData
+    );
+
+    let single = request_with_extra_data(verifier_details(OLD_VERIFIER, 32).abi_encode());
+    let work_request = analyzer.with_selected_verifier(&single).await.unwrap();
+    assert_eq!(
+        work_request.proof_request.extraData,
+        single.proof_request.extraData
+    );
+}
+
+#[tokio::test]
+/// Ensures requests none of whose alternatives are supported are rejected, reporting the leading
+/// one.
+async fn should_reject_unsupported_alternatives() {
+    let request = request_with_alternative();
+    match analyzer(&[]).select_verifier(&request.proof_request).await {
+        Err(ClientError::IntentAnalysisError(message)) => {
+            assert!(message.contains(&OLD_VERIFIER.to_string()), "{message}")
+        }
+        other => panic!("expected the request to be rejected, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+/// Ensures alternatives are never picked on a market not advertising it reads them, like the
+/// deployed `UniversalBombetta` which resolves against the leading verifier details only.
+async fn should_ignore_alternatives_on_market_not_reading_them() {
+    // the market reverts the capability check, not implementing it
+    let rpc_url = start_stub_node(|request| match request["method"].as_str() {
+        Some("eth_call") => json!({
+            "error": { "code": 3, "message": "execution reverted", "data": "0x" }
+        }),
+        _ => method_not_found(),
+    })
+    .await;
+    let request = request_with_alternative();
+
+    let analyzer = analyzer_at(rpc_url.clone(), &[NEW_VERIFIER]);
+    assert!(
+        !analyzer
+            .market_reads_verifier_alternatives(Address::ZERO)
+            .await
+    );
+    match analyzer.select_verifier(&request.proof_request).await {
+        Err(ClientError::IntentAnalysisError(message)) => {
+            assert!(message.contains(&OLD_VERIFIER.to_string()), "{message}")
+        }
+        other => panic!("expected the request to be rejected, got {other:?}"),
+    }
+
+    let analyzer = analyzer_at(rpc_url, &[OLD_VERIFIER, NEW_VERIFIER]);
+    let work_request = analyzer.with_selected_verifier(&request).await.unwrap();
+    assert_eq!(
+        work_request.proof_request.extraData,
+        request.proof_request.extraData
+    );
+}
@@ -0,0 +1,199 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use taralli_client::analyzer::verifier::{VerifierCheckConfig, VerifierChecker, VerifierStatus};
+use taralli_primitives::alloy::network::Ethereum;
+use taralli_primitives::alloy::primitives::{address, fixed_bytes, Address, FixedBytes};
+use taralli_primitives::alloy::providers::RootProvider;
+use taralli_primitives::alloy::transports::http::{Client, Http};
+
+pub mod common;
+use common::rpc::{method_not_found, start_stub_node, stub_provider};
+
+const VERIFIER: Address = address!("4444444444444444444444444444444444444444");
+const SELECTOR: FixedBytes<4> = fixed_bytes!("deadbeef");
+
+type StubChecker = VerifierChecker<Http<Client>, RootProvider<Http<Client>>, Ethereum>;
+
+/// Stub JSON-RPC node answering `eth_getCode` with `code` and `eth_call` with `call`, recording
+/// the methods it is called with
+struct StubNode {
+    url: reqwest::Url,
+    methods: Arc<Mutex<Vec<String>>>,
+}
+
+impl StubNode {
+    async fn start(code: &'static str, call: Value) -> Self {
+        let methods = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = methods.clone();
+        let url = start_stub_node(move |request| {
+            let method = request["method"].as_str().unwrap().to_string();
+            recorded.lock().unwrap().push(method.clone());
+            match method.as_str() {
+                "eth_getCode" => json!({ "result": code }),
+                "eth_call" => call.clone(),
+                _ => method_not_found(),
+            }
+        })
+        .await;
+
+        Self { url, methods }
+    }
+
+    fn methods(&self) -> Vec<String> {
+        self.methods.lock().unwrap().clone()
+    }
+
+    fn checker(&self, config: VerifierCheckConfig) -> StubChecker {
+        VerifierChecker::new(stub_provider(self.url.clone()), config)
+    }
+}
+
+fn reverted(data: Option<&str>) -> Value {
+    let mut error = json!({ "code": 3, "message": "execution reverted" });
+    if let Some(data) = data {
+        error["data"] = json!(data);
+    }
+    json!({ "error": error })
+}
+
+#[tokio::test]
+/// Ensures verifiers without deployed code are rejected.
+async fn should_reject_verifier_without_code() {
+    let node = StubNode::start("0x", json!({ "result": "0x" })).await;
+
+    let status = node
+        .checker(VerifierCheckConfig::default())
+        .check(VERIFIER, SELECTOR)
+        .await
+        .unwrap();
+
+    assert_eq!(status, VerifierStatus::NoCode);
+    assert_eq!(node.methods(), vec!["eth_getCode"]);
+}
+
+#[tokio::test]
+/// Ensures a selector dispatched by the verifier's code is accepted without a dry-run.
+async fn should_accept_selector_in_code() {
+    // PUSH1 0xe0 ... PUSH4 0xdeadbeef EQ
+    let node = StubNode::start("0x60e063deadbeef14", reverted(None)).await;
+
+    let status = node
+        .checker(VerifierCheckConfig::default())
+        .check(VERIFIER, SELECTOR)
+        .await
+        .unwrap();
+
+    assert_eq!(status, VerifierStatus::Supported);
+    assert_eq!(node.methods(), vec!["eth_getCode"]);
+}
+
+#[tokio::test]
+/// Ensures a dry-run reverting with a reason (here a custom error) counts as the function existing.
+async fn should_accept_selector_reverting_with_reason() {
+    let node = StubNode::start("0x6080604052", reverted(Some("0x8baa579f"))).await;
+
+    let status = node
+        .checker(VerifierCheckConfig::default())
+        .check(VERIFIER, SELECTOR)
+        .await
+        .unwrap();
+
+    assert_eq!(status, VerifierStatus::Supported);
+    assert_eq!(node.methods(), vec!["eth_getCode", "eth_call"]);
+}
+
+#[tokio::test]
+/// Ensures a selector with leading zero bytes, pushed by a shorter push than `PUSH4`, is found in
+/// the verifier's code.
+async fn should_accept_selector_pushed_short() {
+    // PUSH1 0xe0 ... PUSH3 0x00beef EQ
+    let node = StubNode::start("0x60e0620000beef14", reverted(None)).await;
+
+    let status = node
+        .checker(VerifierCheckConfig::default())
+        .check(VERIFIER, fixed_bytes!("0000beef"))
+        .await
+        .unwrap();
+
+    assert_eq!(status, VerifierStatus::Supported);
+    assert_eq!(node.methods(), vec!["eth_getCode"]);
+}
+
+#[tokio::test]
+/// Ensures the selector's bytes within the data of a wider push don't count as dispatching it.
+async fn should_ignore_selector_in_push_data() {
+    // PUSH8 0x63deadbeef000000 POP
+    let node = StubNode::start("0x6763deadbeef00000050", json!({ "result": "0x" })).await;
+
+    let status = node
+        .checker(VerifierCheckConfig::default())
+        .check(VERIFIER, SELECTOR)
+        .await
+        .unwrap();
+
+    assert_eq!(status, VerifierStatus::Supported);
+    assert_eq!(node.methods(), vec!["eth_getCode", "eth_call"]);
+}
+
+#[tokio::test]
+/// Ensures a dry-run reverting without data is inconclusive, rejected but checked again next time.
+async fn should_not_cache_inconclusive_probe() {
+    let node = StubNode::start("0x6080604052", reverted(None)).await;
+    let checker = node.checker(VerifierCheckConfig::default());
+
+    let err = checker
+        .ensure_supported(VERIFIER, SELECTOR)
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("could not confirm verifier"),
+        "unexpected error: {err}"
+    );
+
+    let status = checker.check(VERIFIER, SELECTOR).await.unwrap();
+    assert_eq!(status, VerifierStatus::Inconclusive);
+    assert_eq!(
+        node.methods(),
+        vec!["eth_getCode", "eth_call", "eth_getCode", "eth_call"]
+    );
+}
+
+#[tokio::test]
+/// Ensures results are reused within the cache ttl and refetched after it.
+async fn should_cache_results_per_verifier() {
+    let node = StubNode::start("0x", json!({ "result": "0x" })).await;
+    let checker = node.checker(VerifierCheckConfig {
+        cache_ttl: Duration::from_millis(200),
+        ..Default::default()
+    });
+
+    checker.check(VERIFIER, SELECTOR).await.unwrap();
+    checker.check(VERIFIER, SELECTOR).await.unwrap();
+    assert_eq!(node.methods().len(), 1);
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    checker.check(VERIFIER, SELECTOR).await.unwrap();
+    assert_eq!(node.methods().len(), 2);
+}
+
+#[tokio::test]
+/// Ensures a disabled check makes no rpc calls.
+async fn should_skip_disabled_check() {
+    let node = StubNode::start("0x", json!({ "result": "0x" })).await;
+
+    let status = node
+        .checker(VerifierCheckConfig {
+            enabled: false,
+            ..Default::default()
+        })
+        .check(VERIFIER, SELECTOR)
+        .await
+        .unwrap();
+
+    assert_eq!(status, VerifierStatus::Supported);
+    assert!(node.methods().is_empty());
+}
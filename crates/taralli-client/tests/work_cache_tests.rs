@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use taralli_client::error::Result;
+use taralli_client::work_cache::{WorkCache, WorkCacheConfig, WorkKey};
+use taralli_client::worker::{ComputeWorker, WorkResult, WorkerManager};
+use taralli_primitives::alloy::primitives::{Bytes, B256};
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::systems::{SystemId, SystemParams};
+
+pub mod common;
+use common::fixtures::request_with_inputs;
+
+type Request = ComputeRequest<SystemParams>;
+
+/// Worker counting its executions, proving every workload into its inputs commitment
+#[derive(Default)]
+struct CountingWorker {
+    executions: AtomicUsize,
+}
+
+#[async_trait]
+impl ComputeWorker<Request> for CountingWorker {
+    async fn execute(&self, intent: &Request) -> Result<WorkResult> {
+        self.executions.fetch_add(1, Ordering::SeqCst);
+        Ok(WorkResult {
+            opaque_submission: Bytes::from(vec![7; 8]),
+            partial_commitment: intent.proof_request.inputsCommitment,
+        })
+    }
+}
+
+fn worker_manager(worker: Arc<CountingWorker>, cache: Option<WorkCache>) -> WorkerManager<Request> {
+    let manager = WorkerManager::new(HashMap::from([(
+        SystemId::Risc0,
+        worker as Arc<dyn ComputeWorker<Request> + Send + Sync>,
+    )]));
+    match cache {
+        Some(cache) => manager.with_cache(Arc::new(cache)),
+        None => manager,
+    }
+}
+
+#[tokio::test]
+/// Ensures a request resubmitted with the same workload is resolved from the cached result
+/// without executing the worker again.
+async fn should_reuse_result_of_identical_workload() {
+    let worker = Arc::new(CountingWorker::default());
+    let manager = worker_manager(
+        worker.clone(),
+        Some(WorkCache::new(WorkCacheConfig::default())),
+    );
+
+    let first = manager.execute(&request_with_inputs(1, 4)).await.unwrap();
+    let resubmitted = manager.execute(&request_with_inputs(2, 4)).await.unwrap();
+    assert_eq!(resubmitted, first);
+    assert_eq!(worker.executions.load(Ordering::SeqCst), 1);
+
+    // other inputs are proved
+    manager.execute(&request_with_inputs(3, 5)).await.unwrap();
+    assert_eq!(worker.executions.load(Ordering::SeqCst), 2);
+
+    // without a cache every workload is
+    let uncached = worker_manager(worker.clone(), None);
+    uncached.execute(&request_with_inputs(1, 4)).await.unwrap();
+    uncached.execute(&request_with_inputs(2, 4)).await.unwrap();
+    assert_eq!(worker.executions.load(Ordering::SeqCst), 4);
+}
+
+#[tokio::test]
+/// Ensures systems left out of the cache and stale results are proved again.
+async fn should_prove_uncached_systems_and_stale_results() {
+    let worker = Arc::new(CountingWorker::default());
+    let manager = worker_manager(
+        worker.clone(),
+        Some(WorkCache::new(WorkCacheConfig {
+            uncached_systems: vec![SystemId::Risc0],
+            ..Default::default()
+        })),
+    );
+    manager.execute(&request_with_inputs(1, 4)).await.unwrap();
+    manager.execute(&request_with_inputs(2, 4)).await.unwrap();
+    assert_eq!(worker.executions.load(Ordering::SeqCst), 2);
+    assert!(manager.cache.as_ref().unwrap().is_empty());
+
+    let cache = WorkCache::new(WorkCacheConfig::default());
+    let request = request_with_inputs(1, 4);
+    let result = WorkResult {
+        opaque_submission: Bytes::from(vec![1]),
+        partial_commitment: B256::ZERO,
+    };
+    cache.insert(&request, &result).await;
+    assert_eq!(cache.get(&request), Some(result.clone()));
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("work_cache.json");
+    let stale = format!(
+        r#"[{{"key":{},"result":{},"proved_at":0,"last_used":0}}]"#,
+        serde_json::to_string(&WorkKey::of(&request).unwrap()).unwrap(),
+        serde_json::to_string(&result).unwrap(),
+    );
+    std::fs::write(&path, stale).unwrap();
+    let cache = WorkCache::load(WorkCacheConfig::default(), &path).unwrap();
+    assert_eq!(cache.len(), 1);
+    assert_eq!(cache.get(&request), None);
+    assert!(cache.is_empty());
+}
+
+#[tokio::test]
+/// Ensures the least recently used results are evicted beyond the configured size and the cache
+/// is restored from the file it is persisted to.
+async fn should_evict_least_recently_used_and_persist() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("work_cache.json");
+    let config = WorkCacheConfig {
+        max_entries: 2,
+        ..Default::default()
+    };
+    let worker = Arc::new(CountingWorker::default());
+    let manager = worker_manager(
+        worker.clone(),
+        Some(WorkCache::load(config.clone(), &path).unwrap()),
+    );
+
+    manager.execute(&request_with_inputs(1, 1)).await.unwrap();
+    manager.execute(&request_with_inputs(2, 2)).await.unwrap();
+    // reusing the first result makes the second the least recently used
+    manager.execute(&request_with_inputs(3, 1)).await.unwrap();
+    manager.execute(&request_with_inputs(4, 3)).await.unwrap();
+    assert_eq!(worker.executions.load(Ordering::SeqCst), 3);
+    assert_eq!(manager.cache.as_ref().unwrap().len(), 2);
+
+    let restored = WorkCache::load(config, &path).unwrap();
+    assert!(restored.get(&request_with_inputs(5, 1)).is_some());
+    assert!(restored.get(&request_with_inputs(6, 3)).is_some());
+    assert!(restored.get(&request_with_inputs(7, 2)).is_none());
+}
+
+#[tokio::test]
+/// Ensures hits on cached results don't rewrite the cache file, their recency being persisted
+/// along with the next result cached or once flushed.
+async fn should_batch_persisting_hits() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("work_cache.json");
+    let cache = WorkCache::load(WorkCacheConfig::default(), &path).unwrap();
+    let request = request_with_inputs(1, 4);
+    let result = WorkResult {
+        opaque_submission: Bytes::from(vec![1]),
+        partial_commitment: B256::ZERO,
+    };
+    cache.insert(&request, &result).await;
+    assert!(path.exists());
+
+    std::fs::remove_file(&path).unwrap();
+    for _ in 0..3 {
+        assert_eq!(cache.get(&request), Some(result.clone()));
+    }
+    assert!(!path.exists());
+
+    cache.flush().await;
+    let restored = WorkCache::load(WorkCacheConfig::default(), &path).unwrap();
+    assert_eq!(restored.get(&request), Some(result));
+
+    // nothing changed since, flushing again doesn't write
+    std::fs::remove_file(&path).unwrap();
+    cache.flush().await;
+    assert!(!path.exists());
+}
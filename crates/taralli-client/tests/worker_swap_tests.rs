@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use taralli_client::client::provider::control::{RunControl, RunEvent};
+use taralli_client::error::Result;
+use taralli_client::worker::{ComputeWorker, SharedWorker, WorkResult, WorkerManager};
+use taralli_primitives::alloy::primitives::{Bytes, B256};
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::systems::{SystemId, SystemParams};
+use tokio::sync::Notify;
+
+pub mod common;
+use common::fixtures::request_fixture;
+
+type Request = ComputeRequest<SystemParams>;
+
+/// Worker proving every workload into its `version`, once `release` is notified when set
+struct VersionedWorker {
+    version: u8,
+    release: Option<Arc<Notify>>,
+}
+
+#[async_trait]
+impl ComputeWorker<Request> for VersionedWorker {
+    async fn execute(&self, _intent: &Request) -> Result<WorkResult> {
+        if let Some(release) = &self.release {
+            release.notified().await;
+        }
+        Ok(WorkResult {
+            opaque_submission: Bytes::from(vec![self.version]),
+            partial_commitment: B256::ZERO,
+        })
+    }
+
+    fn describe(&self) -> String {
+        format!("risc0 prover v{}", self.version)
+    }
+}
+
+fn worker(version: u8, release: Option<Arc<Notify>>) -> SharedWorker<Request> {
+    Arc::new(VersionedWorker { version, release })
+}
+
+fn slow_request() -> Request {
+    let mut request = request_fixture();
+    request.proof_request.provingTime = 600;
+    request
+}
+
+#[tokio::test]
+/// Ensures a worker replaced through the run control only serves the executions started after
+/// the swap, the one in flight completing with the worker it started with.
+async fn should_route_new_executions_to_replaced_worker() {
+    let release = Arc::new(Notify::new());
+    let manager = WorkerManager::new(HashMap::from([(
+        SystemId::Risc0,
+        worker(1, Some(release.clone())),
+    )]));
+    let control = RunControl::for_workers(None, Some(manager.clone()));
+
+    // started on the first worker, which holds it until released
+    let in_flight = tokio::spawn({
+        let manager = manager.clone();
+        async move { manager.execute(&slow_request()).await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    control
+        .replace_worker(SystemId::Risc0, worker(2, None))
+        .unwrap();
+    let dispatched = manager.execute(&slow_request()).await.unwrap();
+    assert_eq!(dispatched.opaque_submission, Bytes::from(vec![2]));
+    assert!(!in_flight.is_finished());
+
+    release.notify_one();
+    let completed = in_flight.await.unwrap().unwrap();
+    assert_eq!(completed.opaque_submission, Bytes::from(vec![1]));
+
+    assert_eq!(
+        control.events(),
+        vec![RunEvent::WorkerReplaced {
+            system_id: SystemId::Risc0,
+            old: Some("risc0 prover v1".to_string()),
+            new: "risc0 prover v2".to_string(),
+        }]
+    );
+}
+
+#[tokio::test]
+/// Ensures disabling a system removes its worker and narrows the subscription, and replacing its
+/// worker afterwards subscribes to it again.
+async fn should_disable_and_enable_system_again() {
+    let manager = WorkerManager::new(HashMap::from([
+        (SystemId::Risc0, worker(1, None)),
+        (SystemId::Sp1, worker(1, None)),
+    ]));
+    let control = RunControl::for_workers(None, Some(manager.clone()));
+    let mut disabled_systems = control.watch_disabled_systems();
+
+    control.disable_system(SystemId::Risc0).unwrap();
+    tokio::time::timeout(Duration::from_secs(1), disabled_systems.changed())
+        .await
+        .expect("subscription not narrowed")
+        .unwrap();
+    assert_eq!(*disabled_systems.borrow(), SystemId::Risc0.as_bit());
+    assert!(manager.worker(SystemId::Risc0).is_none());
+    assert!(manager.worker(SystemId::Sp1).is_some());
+    assert!(manager.execute(&slow_request()).await.is_err());
+
+    control
+        .replace_worker(SystemId::Risc0, worker(2, None))
+        .unwrap();
+    assert_eq!(control.disabled_systems(), 0);
+    assert_eq!(
+        control.events(),
+        vec![
+            RunEvent::SystemDisabled {
+                system_id: SystemId::Risc0
+            },
+            RunEvent::WorkerReplaced {
+                system_id: SystemId::Risc0,
+                old: None,
+                new: "risc0 prover v2".to_string(),
+            },
+        ]
+    );
+
+    // a control without the provider's workers can't change them
+    assert!(RunControl::new().disable_system(SystemId::Sp1).is_err());
+}
@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use taralli_client::error::{ClientError, Result};
+use taralli_client::proving_stats::{ProvingStats, ProvingStatsConfig};
+use taralli_client::worker::{ComputeWorker, WorkResult, WorkerManager};
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::systems::{SystemId, SystemParams};
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+
+pub mod common;
+use common::fixtures::request_fixture;
+
+type Request = ComputeRequest<SystemParams>;
+
+/// Worker never completing, holding a slot of `slots` while it runs and flagging `cancelled`
+/// from a task of its own once cancelled, as a worker running a subprocess would kill it
+struct HangingWorker {
+    slots: Arc<Semaphore>,
+    cancelled: Arc<AtomicBool>,
+}
+
+#[async_trait]
+impl ComputeWorker<Request> for HangingWorker {
+    async fn execute(&self, intent: &Request) -> Result<WorkResult> {
+        self.execute_cancellable(intent, Duration::MAX, CancellationToken::new())
+            .await
+    }
+
+    async fn execute_cancellable(
+        &self,
+        _intent: &Request,
+        _budget: Duration,
+        cancel: CancellationToken,
+    ) -> Result<WorkResult> {
+        let _slot = self.slots.clone().acquire_owned().await.unwrap();
+        let cancelled = self.cancelled.clone();
+        tokio::spawn(async move {
+            cancel.cancelled().await;
+            cancelled.store(true, Ordering::SeqCst);
+        });
+        std::future::pending().await
+    }
+}
+
+/// Worker never completing, flagging whether it was cancelled by the time its execution drops
+struct DropObservingWorker {
+    cancelled_on_drop: Arc<AtomicBool>,
+}
+
+/// Held by an execution, recording the state of its cancellation when dropped with it
+struct CancellationOnDrop {
+    cancel: CancellationToken,
+    cancelled_on_drop: Arc<AtomicBool>,
+}
+
+impl Drop for CancellationOnDrop {
+    fn drop(&mut self) {
+        self.cancelled_on_drop
+            .store(self.cancel.is_cancelled(), Ordering::SeqCst);
+    }
+}
+
+#[async_trait]
+impl ComputeWorker<Request> for DropObservingWorker {
+    async fn execute(&self, intent: &Request) -> Result<WorkResult> {
+        self.execute_cancellable(intent, Duration::MAX, CancellationToken::new())
+            .await
+    }
+
+    async fn execute_cancellable(
+        &self,
+        _intent: &Request,
+        _budget: Duration,
+        cancel: CancellationToken,
+    ) -> Result<WorkResult> {
+        let _on_drop = CancellationOnDrop {
+            cancel,
+            cancelled_on_drop: self.cancelled_on_drop.clone(),
+        };
+        std::future::pending().await
+    }
+}
+
+fn worker_manager(worker: impl ComputeWorker<Request> + 'static) -> WorkerManager<Request> {
+    WorkerManager::new(HashMap::from([(
+        SystemId::Risc0,
+        Arc::new(worker) as Arc<dyn ComputeWorker<Request> + Send + Sync>,
+    )]))
+}
+
+fn request_proved_within(proving_time: u32) -> Request {
+    let mut request = request_fixture();
+    request.proof_request.provingTime = proving_time;
+    request
+}
+
+#[tokio::test]
+/// Ensures a worker that never completes is cancelled once its budget runs out, its slot being
+/// released and the timeout surfacing as an error.
+async fn should_cancel_hanging_worker_and_reclaim_slot() {
+    let slots = Arc::new(Semaphore::new(1));
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let manager = worker_manager(HangingWorker {
+        slots: slots.clone(),
+        cancelled: cancelled.clone(),
+    });
+
+    let err = manager
+        .execute_within(&request_proved_within(60), Duration::from_millis(50))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ClientError::WorkerTimeout {
+                system_id: SystemId::Risc0,
+                budget_secs: 0,
+            }
+        ),
+        "{err}"
+    );
+    assert_eq!(err.error_code(), "worker_timeout");
+    assert_eq!(slots.available_permits(), 1);
+
+    // the worker was told to clean up what it runs
+    tokio::time::timeout(Duration::from_secs(1), async {
+        while !cancelled.load(Ordering::SeqCst) {
+            tokio::task::yield_now().await;
+        }
+    })
+    .await
+    .expect("worker not cancelled");
+
+    // the slot is taken again by the next execution
+    let err = manager
+        .execute_within(&request_proved_within(60), Duration::from_millis(10))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ClientError::WorkerTimeout { .. }), "{err}");
+    assert_eq!(slots.available_permits(), 1);
+}
+
+#[tokio::test]
+/// Ensures a timed out worker is told to stop before its execution is dropped, so what it runs
+/// outside of it is stopped too.
+async fn should_cancel_worker_before_dropping_execution() {
+    let cancelled_on_drop = Arc::new(AtomicBool::new(false));
+    let manager = worker_manager(DropObservingWorker {
+        cancelled_on_drop: cancelled_on_drop.clone(),
+    });
+    let err = manager
+        .execute_within(&request_proved_within(60), Duration::from_millis(10))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ClientError::WorkerTimeout { .. }), "{err}");
+    assert!(cancelled_on_drop.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+/// Ensures workers are given what is left of the proving window their bid opened, less the
+/// resolve margin.
+async fn should_budget_proving_window_less_resolve_margin() {
+    let manager = worker_manager(HangingWorker {
+        slots: Arc::new(Semaphore::new(1)),
+        cancelled: Arc::new(AtomicBool::new(false)),
+    });
+    assert_eq!(
+        manager.proving_budget(&request_proved_within(60), 1_000, 1_000),
+        Duration::from_secs(30)
+    );
+    // proving starting after the bid, e.g. resumed after a restart, gets what is left
+    assert_eq!(
+        manager.proving_budget(&request_proved_within(60), 1_000, 1_020),
+        Duration::from_secs(10)
+    );
+    assert_eq!(
+        manager.proving_budget(&request_proved_within(60), 1_000, 1_100),
+        Duration::ZERO
+    );
+
+    let manager = manager.with_resolve_margin(Duration::from_secs(59));
+    assert_eq!(
+        manager.proving_budget(&request_proved_within(60), 1_000, 1_000),
+        Duration::from_secs(1)
+    );
+    // a window shorter than the margin leaves no time at all
+    assert_eq!(
+        manager.proving_budget(&request_proved_within(30), 1_000, 1_000),
+        Duration::ZERO
+    );
+    let err = manager
+        .execute(&request_proved_within(30))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ClientError::WorkerTimeout { .. }), "{err}");
+}
+
+#[tokio::test]
+/// Ensures timed out executions are counted and raise the estimate of similar workloads.
+async fn should_record_timeouts_in_proving_stats() {
+    let stats = ProvingStats::new(ProvingStatsConfig {
+        min_samples: 1,
+        ..Default::default()
+    });
+    stats
+        .record(SystemId::Risc0, 1024, Duration::from_secs(10))
+        .await;
+    stats
+        .record_timeout(SystemId::Risc0, 1024, Duration::from_secs(60))
+        .await;
+    assert_eq!(stats.timeouts(SystemId::Risc0, 1024), 1);
+    assert_eq!(stats.samples(SystemId::Risc0, 1024), 2);
+    assert!(stats.estimate(SystemId::Risc0, 1024) > Duration::from_secs(10));
+    assert_eq!(stats.timeouts(SystemId::Sp1, 1024), 0);
+}
@@ -0,0 +1,41 @@
+//! Compression and decompression of system params by every codec of `support::codecs`
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+mod support;
+
+fn compression(c: &mut Criterion) {
+    for (name, system) in support::systems() {
+        let payload = serde_json::to_vec(&system).expect("couldn't serialize system");
+        let mut group = c.benchmark_group(format!("compression/{name}"));
+        group.throughput(Throughput::Bytes(payload.len() as u64));
+        // the arkworks circuit takes seconds to compress at the highest levels
+        group.sample_size(10);
+
+        for codec in support::codecs() {
+            let compressed = codec.compress(&payload).expect("compression failed");
+            eprintln!(
+                "{name} {}: {} -> {} bytes ({:.1}%)",
+                codec.name(),
+                payload.len(),
+                compressed.len(),
+                100.0 * compressed.len() as f64 / payload.len() as f64
+            );
+
+            group.bench_with_input(
+                BenchmarkId::new("compress", codec.name()),
+                &payload,
+                |b, payload| b.iter(|| codec.compress(payload).unwrap()),
+            );
+            group.bench_with_input(
+                BenchmarkId::new("decompress", codec.name()),
+                &compressed,
+                |b, compressed| b.iter(|| codec.decompress(compressed).unwrap()),
+            );
+        }
+        group.finish();
+    }
+}
+
+criterion_group!(benches, compression);
+criterion_main!(benches);
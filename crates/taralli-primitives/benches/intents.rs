@@ -0,0 +1,60 @@
+//! Encoding, digest and validation of compute requests, as run on every submission and broadcast
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use taralli_primitives::compression_utils::compression::compress_brotli;
+use taralli_primitives::compression_utils::intents::ComputeRequestCompressed;
+use taralli_primitives::intents::ComputeIntent;
+use taralli_primitives::markets::SEPOLIA_CHAIN_ID;
+use taralli_primitives::validation::request::{
+    validate_request, RequestValidationConfig, RequestVerifierConstraints,
+};
+
+mod support;
+
+fn bincode(c: &mut Criterion) {
+    for (name, system) in support::systems() {
+        let request = support::signed_request(system);
+        let compressed_request = ComputeRequestCompressed {
+            system_id: request.system_id,
+            system: compress_brotli(&serde_json::to_vec(&request.system).unwrap()).unwrap(),
+            proof_request: request.proof_request,
+            signature: request.signature,
+        };
+        let encoded = bincode::serialize(&compressed_request).unwrap();
+
+        let mut group = c.benchmark_group(format!("bincode/{name}"));
+        group.throughput(Throughput::Bytes(encoded.len() as u64));
+        group.bench_function("serialize", |b| {
+            b.iter(|| bincode::serialize(black_box(&compressed_request)).unwrap())
+        });
+        group.bench_function("deserialize", |b| {
+            b.iter(|| {
+                bincode::deserialize::<ComputeRequestCompressed>(black_box(&encoded)).unwrap()
+            })
+        });
+        group.finish();
+    }
+}
+
+fn permit2_digest(c: &mut Criterion) {
+    let request = support::signed_request(support::sp1_system());
+    c.bench_function("permit2_digest/request", |b| {
+        b.iter(|| black_box(&request).compute_permit2_digest(SEPOLIA_CHAIN_ID))
+    });
+}
+
+fn validation(c: &mut Criterion) {
+    let request = support::signed_request(support::sp1_system());
+    let config = RequestValidationConfig::default();
+    let constraints = RequestVerifierConstraints::default();
+    validate_request(&request, &config, &constraints).expect("fixture fails validation");
+
+    c.bench_function("validate_request/sp1", |b| {
+        b.iter(|| validate_request(black_box(&request), &config, &constraints).unwrap())
+    });
+}
+
+criterion_group!(benches, bincode, permit2_digest, validation);
+criterion_main!(benches);
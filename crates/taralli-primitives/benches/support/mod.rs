@@ -0,0 +1,161 @@
+//! Fixtures and codecs shared by the benches. Systems are read from `contracts/test-proof-data`,
+//! the sp1 fibonacci program standing in for small systems and the arkworks sha256 circuit for
+//! large ones. Only the dependencies shared with taralli-client are used, so its benches run on
+//! the same fixtures.
+
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use taralli_primitives::abi::universal_bombetta::{
+    ProofRequestVerifierDetails, UniversalBombetta::ProofRequest,
+};
+use taralli_primitives::alloy::primitives::{address, Address, PrimitiveSignature, B256, U256};
+use taralli_primitives::alloy::signers::{local::PrivateKeySigner, Signer};
+use taralli_primitives::alloy::sol_types::SolValue;
+use taralli_primitives::compression_utils::compression::{
+    compress_brotli_with_params, decompress_brotli, BrotliParams,
+};
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::intents::ComputeIntent;
+use taralli_primitives::markets::SEPOLIA_CHAIN_ID;
+use taralli_primitives::systems::arkworks::ArkworksProofParams;
+use taralli_primitives::systems::sp1::{Sp1Config, Sp1Mode, Sp1ProofParams};
+use taralli_primitives::systems::{System, SystemParams};
+use taralli_primitives::Result;
+
+/// market the request fixtures reference
+pub const MARKET: Address = address!("4242424242424242424242424242424242424242");
+/// timestamp the request fixtures are validated at
+pub const LATEST_TIMESTAMP: u64 = 1_000;
+
+/// Codec a system payload is sent with. Benches run every codec of `codecs`, so a codec added
+/// there is measured against the current ones on the same inputs.
+pub trait Codec {
+    /// name the codec's results are reported under
+    fn name(&self) -> String;
+    fn compress(&self, payload: &[u8]) -> Result<Vec<u8>>;
+    fn decompress(&self, compressed: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Brotli at the given level, within the default window
+pub struct Brotli {
+    pub level: u32,
+}
+
+impl Codec for Brotli {
+    fn name(&self) -> String {
+        format!("brotli-{}", self.level)
+    }
+
+    fn compress(&self, payload: &[u8]) -> Result<Vec<u8>> {
+        compress_brotli_with_params(
+            &payload,
+            &BrotliParams {
+                level: self.level,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn decompress(&self, compressed: &[u8]) -> Result<Vec<u8>> {
+        block_on(decompress_brotli(compressed))
+    }
+}
+
+/// Codecs compared by the benches: the fastest and smallest Brotli levels around the default
+pub fn codecs() -> Vec<Box<dyn Codec>> {
+    [1, 4, BrotliParams::default().level, 11]
+        .into_iter()
+        .map(|level| Box::new(Brotli { level }) as Box<dyn Codec>)
+        .collect()
+}
+
+/// runtime driving the async decompression, built once so it isn't part of the measurements
+static RUNTIME: LazyLock<tokio::runtime::Runtime> = LazyLock::new(|| {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("failed to build runtime")
+});
+
+pub fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    RUNTIME.block_on(future)
+}
+
+fn test_proof_data(path: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../../contracts/test-proof-data")
+        .join(path)
+}
+
+fn read(path: &str) -> Vec<u8> {
+    let path = test_proof_data(path);
+    std::fs::read(&path).unwrap_or_else(|e| panic!("couldn't read {}: {e}", path.display()))
+}
+
+/// Small system: the sp1 fibonacci program
+pub fn sp1_system() -> SystemParams {
+    SystemParams::Sp1(Sp1ProofParams {
+        config: Sp1Config {
+            mode: Sp1Mode::Groth16,
+        },
+        elf: read("sp1/fibonacci-program"),
+        inputs: U256::from(20).abi_encode(),
+    })
+}
+
+/// Large system: the arkworks sha256 circuit
+pub fn arkworks_system() -> SystemParams {
+    SystemParams::Arkworks(ArkworksProofParams {
+        r1cs: read("groth16/sha/sha256_test512.r1cs"),
+        wasm: read("groth16/sha/sha256_test512_js/sha256_test512.wasm"),
+        inputs: serde_json::from_slice(&read("groth16/sha/input.json"))
+            .expect("couldn't parse circuit inputs"),
+    })
+}
+
+/// Named systems the benches run against
+pub fn systems() -> Vec<(&'static str, SystemParams)> {
+    vec![("sp1", sp1_system()), ("arkworks", arkworks_system())]
+}
+
+/// Request of `system` passing `validate_request` at `LATEST_TIMESTAMP`, signed on sepolia
+pub fn signed_request(system: SystemParams) -> ComputeRequest<SystemParams> {
+    let signer = PrivateKeySigner::from_bytes(&B256::repeat_byte(1)).expect("invalid key");
+    let mut request = ComputeRequest {
+        system_id: system.system_id(),
+        system,
+        proof_request: ProofRequest {
+            signer: signer.address(),
+            market: MARKET,
+            nonce: U256::ZERO,
+            rewardToken: Address::ZERO,
+            maxRewardAmount: U256::from(100),
+            minRewardAmount: U256::from(10),
+            minimumStake: 0,
+            startAuctionTimestamp: LATEST_TIMESTAMP,
+            endAuctionTimestamp: LATEST_TIMESTAMP + 100,
+            provingTime: 60,
+            inputsCommitment: B256::ZERO,
+            extraData: ProofRequestVerifierDetails {
+                verifier: Address::ZERO,
+                selector: Default::default(),
+                isShaCommitment: true,
+                inputsOffset: U256::ZERO,
+                inputsLength: U256::from(32),
+                hasPartialCommitmentResultCheck: false,
+                submittedPartialCommitmentResultOffset: U256::ZERO,
+                submittedPartialCommitmentResultLength: U256::ZERO,
+                predeterminedPartialCommitment: B256::ZERO,
+            }
+            .abi_encode()
+            .into(),
+        },
+        signature: PrimitiveSignature::new(U256::from(1), U256::from(2), false),
+    };
+    request.signature =
+        block_on(signer.sign_hash(&request.compute_permit2_digest(SEPOLIA_CHAIN_ID)))
+            .expect("failed to sign request");
+    request
+}
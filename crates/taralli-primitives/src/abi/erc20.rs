@@ -0,0 +1,11 @@
+use alloy::sol;
+
+sol! {
+    /// ERC20 functions read and sent by requesters paying rewards through permit2
+    #[sol(rpc)]
+    interface IERC20 {
+        function allowance(address owner, address spender) external view returns (uint256);
+        function balanceOf(address account) external view returns (uint256);
+        function approve(address spender, uint256 amount) external returns (bool);
+    }
+}
@@ -0,0 +1,264 @@
+//! Extensions appended to an intent's `extraData` after its abi encoded `VerifierDetails`.
+//!
+//! Each extension is a tag byte, a length byte and as many value bytes. The markets only decode
+//! the `VerifierDetails` at the start of `extraData`, so extensions are covered by the intent's
+//! signature without changing how it's resolved, and intents without any decode as before.
+//!
+//! Known extensions:
+//! - `URGENCY_TAG`: a single byte urgency level, from 0 up to `MAX_URGENCY`, which providers may
+//!   use to prioritize the request over others paying the same
+//! - `VERIFIER_ALTERNATIVE_TAG`: a `VerifierDetails` the request can also be verified with, e.g.
+//!   a newer version of its verifier. The value is a version byte followed by the fields of the
+//!   struct packed, as abi encoding them wouldn't fit the length byte. The extension repeats once
+//!   per alternative, in order of preference after the leading `VerifierDetails`.
+//! - `SHARD_TAG`: the `ShardTag` linking the shards of a job split by a requester, its job id
+//!   followed by the shard's index and the job's shard count as 8 byte big endian integers.
+//!
+//! The markets resolve against the leading `VerifierDetails`, a provider picking an alternative
+//! needs a market reading it from the extension, see `with_leading_verifier`. Such a market
+//! advertises it through `IVerifierAlternatives`, the deployed `UniversalBombetta` doesn't: it
+//! only ever calls the leading verifier, so alternatives are informational there and validation
+//! holds the leading verifier details to the constraints regardless of them.
+
+use alloy::primitives::{Address, Bytes, FixedBytes, B256, U256};
+use alloy::sol;
+use alloy::sol_types::SolValue;
+
+use super::universal_bombetta::ProofRequestVerifierDetails;
+use super::verifier_details::{
+    decode_verifier_details, DecodeError, DecodeMode, REQUEST_VERIFIER_DETAILS_LENGTH,
+};
+
+/// Tag of the urgency extension
+pub const URGENCY_TAG: u8 = 0x01;
+/// Highest urgency level an intent can signal
+pub const MAX_URGENCY: u8 = 3;
+/// Tag of the verifier alternative extension
+pub const VERIFIER_ALTERNATIVE_TAG: u8 = 0x02;
+/// Version of the packed encoding of verifier alternatives
+pub const VERIFIER_ALTERNATIVE_VERSION: u8 = 1;
+/// Length of a verifier alternative's value: its version byte and packed fields
+const VERIFIER_ALTERNATIVE_LENGTH: usize = 1 + 20 + 4 + 1 + 32 + 32 + 1 + 32 + 32 + 32;
+/// Tag of the shard extension
+pub const SHARD_TAG: u8 = 0x03;
+/// Length of a shard tag's value: its job id, index and count
+const SHARD_TAG_LENGTH: usize = 32 + 8 + 8;
+
+sol! {
+    /// Capability of the markets resolving requests against the verifier alternative their
+    /// provider picked rather than the leading verifier details
+    #[sol(rpc)]
+    interface IVerifierAlternatives {
+        function readsVerifierAlternatives() external view returns (bool);
+    }
+}
+
+/// Tag of every shard of a job, linking the shards together off-chain
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShardTag {
+    /// identifier shared by all the shards of a job
+    pub job_id: B256,
+    pub index: usize,
+    pub count: usize,
+}
+
+impl ShardTag {
+    /// Read the shard tag of a request's `extraData` extensions, if it was built as a shard
+    pub fn from_extra_data(extra_data: &[u8]) -> Option<Self> {
+        decode_request_extensions(extra_data).ok()?.shard
+    }
+}
+
+/// Extensions carried by an intent's `extraData`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Extensions {
+    /// urgency level signaled by the requester, `None` when not signaled
+    pub urgency: Option<u8>,
+    /// verifier details the request can also be verified with, after the leading ones
+    pub verifier_alternatives: Vec<ProofRequestVerifierDetails>,
+    /// shard of a job the request is, `None` when not built as a shard
+    pub shard: Option<ShardTag>,
+}
+
+impl Extensions {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.urgency.is_none() && self.verifier_alternatives.is_empty() && self.shard.is_none()
+    }
+
+    /// Encode the extensions set, nothing when none is
+    #[must_use]
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoded = Vec::new();
+        if let Some(urgency) = self.urgency {
+            encoded.extend_from_slice(&[URGENCY_TAG, 1, urgency]);
+        }
+        for alternative in &self.verifier_alternatives {
+            encoded
+                .extend_from_slice(&[VERIFIER_ALTERNATIVE_TAG, VERIFIER_ALTERNATIVE_LENGTH as u8]);
+            encoded.extend_from_slice(&encode_verifier_alternative(alternative));
+        }
+        if let Some(shard) = &self.shard {
+            encoded.extend_from_slice(&[SHARD_TAG, SHARD_TAG_LENGTH as u8]);
+            encoded.extend_from_slice(shard.job_id.as_slice());
+            encoded.extend_from_slice(&(shard.index as u64).to_be_bytes());
+            encoded.extend_from_slice(&(shard.count as u64).to_be_bytes());
+        }
+        encoded
+    }
+
+    /// Decode the extensions following the `VerifierDetails`. Unknown extensions are skipped by
+    /// the lenient decoder, e.g. ones added by a newer version, and rejected by the strict one.
+    pub fn decode(encoded: &[u8], mode: DecodeMode) -> Result<Self, DecodeError> {
+        let mut extensions = Self::default();
+        let mut offset = 0;
+        while offset < encoded.len() {
+            let invalid = |reason| DecodeError::InvalidExtension { offset, reason };
+            let Some(&[tag, length]) = encoded.get(offset..offset + 2) else {
+                return Err(invalid("extension header is incomplete"));
+            };
+            let value = encoded
+                .get(offset + 2..offset + 2 + usize::from(length))
+                .ok_or_else(|| invalid("extension value is incomplete"))?;
+            match tag {
+                URGENCY_TAG => match value {
+                    [urgency] => extensions.urgency = Some(*urgency),
+                    _ => return Err(invalid("urgency is not a single byte")),
+                },
+                VERIFIER_ALTERNATIVE_TAG => match value.first() {
+                    Some(&VERIFIER_ALTERNATIVE_VERSION) => extensions
+                        .verifier_alternatives
+                        .push(decode_verifier_alternative(value).map_err(invalid)?),
+                    // alternatives encoded by a newer version
+                    Some(_) if mode == DecodeMode::Lenient => {}
+                    _ => return Err(invalid("unknown verifier alternative version")),
+                },
+                SHARD_TAG => {
+                    extensions.shard = Some(decode_shard_tag(value).map_err(invalid)?);
+                }
+                _ if mode == DecodeMode::Strict => return Err(invalid("unknown extension")),
+                _ => {}
+            }
+            offset += 2 + usize::from(length);
+        }
+        Ok(extensions)
+    }
+}
+
+/// Decode the extensions of a `ComputeRequest`'s `extraData`, skipping unknown ones
+pub fn decode_request_extensions(extra_data: &[u8]) -> Result<Extensions, DecodeError> {
+    match extra_data.get(REQUEST_VERIFIER_DETAILS_LENGTH..) {
+        Some(encoded) => Extensions::decode(encoded, DecodeMode::Lenient),
+        None => Ok(Extensions::default()),
+    }
+}
+
+/// `extraData` of a `ComputeRequest` with its extensions replaced by `extensions`
+#[must_use]
+pub fn with_request_extensions(extra_data: &[u8], extensions: &Extensions) -> Bytes {
+    let verifier_details = &extra_data[..extra_data.len().min(REQUEST_VERIFIER_DETAILS_LENGTH)];
+    [verifier_details, &extensions.encode()].concat().into()
+}
+
+/// Verifier details a `ComputeRequest` can be verified with: the leading ones of its `extraData`,
+/// followed by the alternatives of its extensions in order of preference
+pub fn decode_request_verifier_alternatives(
+    extra_data: &[u8],
+) -> Result<Vec<ProofRequestVerifierDetails>, DecodeError> {
+    let mut alternatives = vec![decode_verifier_details(extra_data)?];
+    alternatives.extend(decode_request_extensions(extra_data)?.verifier_alternatives);
+    Ok(alternatives)
+}
+
+/// `extraData` of a `ComputeRequest` led by `verifier_details`, its extensions being kept. This
+/// is the `extraData` a worker formats its submission against once an alternative is picked, the
+/// signed `extraData` staying the one resolved. Only meaningful on a market advertising
+/// `IVerifierAlternatives`, the others resolving against the leading verifier details.
+#[must_use]
+pub fn with_leading_verifier(
+    extra_data: &[u8],
+    verifier_details: &ProofRequestVerifierDetails,
+) -> Bytes {
+    let extensions = extra_data
+        .get(REQUEST_VERIFIER_DETAILS_LENGTH..)
+        .unwrap_or_default();
+    [verifier_details.abi_encode().as_slice(), extensions]
+        .concat()
+        .into()
+}
+
+fn encode_verifier_alternative(alternative: &ProofRequestVerifierDetails) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(VERIFIER_ALTERNATIVE_LENGTH);
+    encoded.push(VERIFIER_ALTERNATIVE_VERSION);
+    encoded.extend_from_slice(alternative.verifier.as_slice());
+    encoded.extend_from_slice(alternative.selector.as_slice());
+    encoded.push(u8::from(alternative.isShaCommitment));
+    encoded.extend_from_slice(&alternative.inputsOffset.to_be_bytes::<32>());
+    encoded.extend_from_slice(&alternative.inputsLength.to_be_bytes::<32>());
+    encoded.push(u8::from(alternative.hasPartialCommitmentResultCheck));
+    encoded.extend_from_slice(
+        &alternative
+            .submittedPartialCommitmentResultOffset
+            .to_be_bytes::<32>(),
+    );
+    encoded.extend_from_slice(
+        &alternative
+            .submittedPartialCommitmentResultLength
+            .to_be_bytes::<32>(),
+    );
+    encoded.extend_from_slice(alternative.predeterminedPartialCommitment.as_slice());
+    encoded
+}
+
+/// Decode a shard tag's value
+fn decode_shard_tag(value: &[u8]) -> Result<ShardTag, &'static str> {
+    if value.len() != SHARD_TAG_LENGTH {
+        return Err("shard tag is not of its length");
+    }
+    let (job_id, rest) = value.split_at(32);
+    let (index, count) = rest.split_at(8);
+    let integer = |bytes: &[u8]| {
+        usize::try_from(u64::from_be_bytes(bytes.try_into().expect("8 bytes")))
+            .map_err(|_| "shard integer overflows")
+    };
+    let shard = ShardTag {
+        job_id: B256::from_slice(job_id),
+        index: integer(index)?,
+        count: integer(count)?,
+    };
+    if shard.index >= shard.count {
+        return Err("shard index is not below the shard count");
+    }
+    Ok(shard)
+}
+
+/// Decode a verifier alternative's value, version byte included
+fn decode_verifier_alternative(value: &[u8]) -> Result<ProofRequestVerifierDetails, &'static str> {
+    if value.len() != VERIFIER_ALTERNATIVE_LENGTH {
+        return Err("verifier alternative is not of its version's length");
+    }
+    let (verifier, rest) = value[1..].split_at(20);
+    let (selector, rest) = rest.split_at(4);
+    let (is_sha_commitment, rest) = rest.split_at(1);
+    let (inputs_offset, rest) = rest.split_at(32);
+    let (inputs_length, rest) = rest.split_at(32);
+    let (has_partial_commitment_result_check, rest) = rest.split_at(1);
+    let (result_offset, rest) = rest.split_at(32);
+    let (result_length, predetermined_partial_commitment) = rest.split_at(32);
+    let flag = |byte: &[u8]| match byte {
+        [0] => Ok(false),
+        [1] => Ok(true),
+        _ => Err("verifier alternative flag is neither 0 nor 1"),
+    };
+    Ok(ProofRequestVerifierDetails {
+        verifier: Address::from_slice(verifier),
+        selector: FixedBytes::from_slice(selector),
+        isShaCommitment: flag(is_sha_commitment)?,
+        inputsOffset: U256::from_be_slice(inputs_offset),
+        inputsLength: U256::from_be_slice(inputs_length),
+        hasPartialCommitmentResultCheck: flag(has_partial_commitment_result_check)?,
+        submittedPartialCommitmentResultOffset: U256::from_be_slice(result_offset),
+        submittedPartialCommitmentResultLength: U256::from_be_slice(result_length),
+        predeterminedPartialCommitment: B256::from_slice(predetermined_partial_commitment),
+    })
+}
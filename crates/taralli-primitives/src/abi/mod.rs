@@ -1,5 +1,8 @@
 //! This module contains all solidity contract abi's used across the Taralli protocol
 
+pub mod erc20;
+pub mod extensions;
 pub mod permit2;
 pub mod universal_bombetta;
 pub mod universal_porchetta;
+pub mod verifier_details;
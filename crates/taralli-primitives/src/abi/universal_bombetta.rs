@@ -10,7 +10,7 @@ sol! {
 
 // UniversalBombetta.VerifierDetails
 sol! {
-    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    #[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
     struct VerifierDetails {
         address verifier;
         bytes4 selector;
@@ -0,0 +1,189 @@
+//! Decoding of the `VerifierDetails` abi encoded within an intent's `extraData`.
+//!
+//! Every field of both `VerifierDetails` structs is a static type, so their encoding is a fixed
+//! sequence of 32 byte words. Checking each word against the type of its field lets decoding
+//! errors point at the offending field instead of failing the whole struct.
+
+use alloy::hex;
+use alloy::sol_types::SolValue;
+use thiserror::Error;
+
+use super::{
+    extensions::Extensions, universal_bombetta::ProofRequestVerifierDetails,
+    universal_porchetta::ProofOfferVerifierDetails,
+};
+
+const WORD_SIZE: usize = 32;
+
+/// Length of the encoded `VerifierDetails` of a `ComputeRequest`, extensions start past it
+pub const REQUEST_VERIFIER_DETAILS_LENGTH: usize =
+    REQUEST_VERIFIER_DETAILS_LAYOUT.len() * WORD_SIZE;
+
+/// Maximum `extraData` length accepted when trailing bytes are allowed
+pub const MAX_EXTRA_DATA_LENGTH: usize = 1024;
+
+/// Error decoding `VerifierDetails` from an intent's `extraData`
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DecodeError {
+    #[error("extraData is {actual} bytes, expected at least {minimum} (field `{field}` at offset {offset} is incomplete)")]
+    TooShort {
+        actual: usize,
+        minimum: usize,
+        field: &'static str,
+        offset: usize,
+    },
+    #[error("extraData is {actual} bytes, exceeding the maximum of {maximum}")]
+    TooLong { actual: usize, maximum: usize },
+    #[error("extraData has {trailing} trailing byte(s) after the verifier details: 0x{snippet}")]
+    TrailingBytes { trailing: usize, snippet: String },
+    #[error("invalid `{field}` at offset {offset}: {reason} (word: 0x{snippet})")]
+    InvalidField {
+        field: &'static str,
+        offset: usize,
+        reason: &'static str,
+        snippet: String,
+    },
+    #[error("invalid extension at offset {offset} past the verifier details: {reason}")]
+    InvalidExtension { offset: usize, reason: &'static str },
+    #[error("failed to decode VerifierDetails: {0}")]
+    Abi(String),
+}
+
+/// Whether bytes following the encoded struct are accepted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeMode {
+    /// trailing bytes are ignored, as long as `extraData` stays within `MAX_EXTRA_DATA_LENGTH`
+    Lenient,
+    /// `extraData` must be exactly the encoded struct, followed by well formed known extensions
+    Strict,
+}
+
+#[derive(Clone, Copy)]
+enum WordKind {
+    Address,
+    Bytes4,
+    Bool,
+    Uint256,
+    Bytes32,
+}
+
+const REQUEST_VERIFIER_DETAILS_LAYOUT: [(&str, WordKind); 9] = [
+    ("verifier", WordKind::Address),
+    ("selector", WordKind::Bytes4),
+    ("isShaCommitment", WordKind::Bool),
+    ("inputsOffset", WordKind::Uint256),
+    ("inputsLength", WordKind::Uint256),
+    ("hasPartialCommitmentResultCheck", WordKind::Bool),
+    ("submittedPartialCommitmentResultOffset", WordKind::Uint256),
+    ("submittedPartialCommitmentResultLength", WordKind::Uint256),
+    ("predeterminedPartialCommitment", WordKind::Bytes32),
+];
+
+const OFFER_VERIFIER_DETAILS_LAYOUT: [(&str, WordKind); 5] = [
+    ("verifier", WordKind::Address),
+    ("selector", WordKind::Bytes4),
+    ("isShaCommitment", WordKind::Bool),
+    ("inputsOffset", WordKind::Uint256),
+    ("inputsLength", WordKind::Uint256),
+];
+
+/// Decode the `VerifierDetails` of a `ComputeRequest`, ignoring trailing bytes
+pub fn decode_verifier_details(
+    extra_data: &[u8],
+) -> Result<ProofRequestVerifierDetails, DecodeError> {
+    decode_request_verifier_details(extra_data, DecodeMode::Lenient)
+}
+
+/// Decode the `VerifierDetails` of a `ComputeRequest`
+pub fn decode_request_verifier_details(
+    extra_data: &[u8],
+    mode: DecodeMode,
+) -> Result<ProofRequestVerifierDetails, DecodeError> {
+    let encoded = check_layout(extra_data, &REQUEST_VERIFIER_DETAILS_LAYOUT, mode)?;
+    ProofRequestVerifierDetails::abi_decode(encoded, true)
+        .map_err(|e| DecodeError::Abi(e.to_string()))
+}
+
+/// Decode the `VerifierDetails` of a `ComputeOffer`
+pub fn decode_offer_verifier_details(
+    extra_data: &[u8],
+    mode: DecodeMode,
+) -> Result<ProofOfferVerifierDetails, DecodeError> {
+    let encoded = check_layout(extra_data, &OFFER_VERIFIER_DETAILS_LAYOUT, mode)?;
+    ProofOfferVerifierDetails::abi_decode(encoded, true)
+        .map_err(|e| DecodeError::Abi(e.to_string()))
+}
+
+/// Check the length of `extra_data` and each word against its field's type, returning the
+/// bytes of the encoded struct.
+fn check_layout<'a>(
+    extra_data: &'a [u8],
+    layout: &[(&'static str, WordKind)],
+    mode: DecodeMode,
+) -> Result<&'a [u8], DecodeError> {
+    let encoded_length = layout.len() * WORD_SIZE;
+
+    if extra_data.len() < encoded_length {
+        let incomplete = extra_data.len() / WORD_SIZE;
+        return Err(DecodeError::TooShort {
+            actual: extra_data.len(),
+            minimum: encoded_length,
+            field: layout[incomplete].0,
+            offset: incomplete * WORD_SIZE,
+        });
+    }
+    match mode {
+        DecodeMode::Strict
+            if extra_data.len() > encoded_length
+                && Extensions::decode(&extra_data[encoded_length..], DecodeMode::Strict)
+                    .is_err() =>
+        {
+            return Err(DecodeError::TrailingBytes {
+                trailing: extra_data.len() - encoded_length,
+                snippet: snippet(&extra_data[encoded_length..]),
+            });
+        }
+        DecodeMode::Lenient if extra_data.len() > MAX_EXTRA_DATA_LENGTH => {
+            return Err(DecodeError::TooLong {
+                actual: extra_data.len(),
+                maximum: MAX_EXTRA_DATA_LENGTH,
+            });
+        }
+        _ => {}
+    }
+
+    for (index, (field, kind)) in layout.iter().enumerate() {
+        let offset = index * WORD_SIZE;
+        let word = &extra_data[offset..offset + WORD_SIZE];
+        let invalid = |reason| DecodeError::InvalidField {
+            field,
+            offset,
+            reason,
+            snippet: snippet(word),
+        };
+        match kind {
+            WordKind::Address if word[..12].iter().any(|b| *b != 0) => {
+                return Err(invalid("address is not left padded with zeros"));
+            }
+            WordKind::Bytes4 if word[4..].iter().any(|b| *b != 0) => {
+                return Err(invalid("bytes4 is not right padded with zeros"));
+            }
+            WordKind::Bool if word[..31].iter().any(|b| *b != 0) || word[31] > 1 => {
+                return Err(invalid("bool is neither 0 nor 1"));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(&extra_data[..encoded_length])
+}
+
+/// hex of the first bytes of a region, enough to identify it
+fn snippet(bytes: &[u8]) -> String {
+    const SNIPPET_LENGTH: usize = WORD_SIZE;
+    if bytes.len() > SNIPPET_LENGTH {
+        format!("{}..", hex::encode(&bytes[..SNIPPET_LENGTH]))
+    } else {
+        hex::encode(bytes)
+    }
+}
@@ -0,0 +1,36 @@
+//! Regenerate the permit2 test vectors shared with the Solidity tests.
+//!
+//! Usage: `cargo run -p taralli-primitives --bin permit2_vectors -- [path] [random vectors]`,
+//! writing to the stored file of the repository by default.
+
+use std::path::PathBuf;
+
+use taralli_primitives::permit2_vectors::{
+    Permit2Vectors, DEFAULT_RANDOM_VECTORS, PERMIT2_VECTORS_PATH,
+};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let path = args.next().map(PathBuf::from).unwrap_or_else(|| {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../..")
+            .join(PERMIT2_VECTORS_PATH)
+    });
+    let random = match args.next() {
+        Some(random) => random.parse()?,
+        None => DEFAULT_RANDOM_VECTORS,
+    };
+
+    let vectors = Permit2Vectors::generate(random)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(&path, vectors.to_json()?)?;
+    println!(
+        "wrote {} request and {} offer vectors to {}",
+        vectors.requests.len(),
+        vectors.offers.len(),
+        path.display()
+    );
+    Ok(())
+}
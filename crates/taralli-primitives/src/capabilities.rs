@@ -0,0 +1,161 @@
+//! Capabilities providers advertise to the protocol server: the systems they prove, how fast,
+//! for how much and how many jobs at once. Requesters read them back through the server's status
+//! to tell whether any online provider takes their requests before submitting them.
+//!
+//! Records are signed by the provider address and carry the time they were issued, so only the
+//! provider can update its own record and an older record can't replace a newer one.
+
+use alloy::primitives::{keccak256, Address, PrimitiveSignature, B256, U256};
+use alloy::signers::Signer;
+use alloy::sol_types::SolValue;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{PrimitivesError, Result};
+use crate::systems::SystemId;
+
+/// domain separating capability signatures from other signatures of the provider address
+const CAPABILITIES_DOMAIN: &[u8] = b"taralli-provider-capabilities";
+
+/// What a provider accepts for one system
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SystemCapability {
+    pub system_id: SystemId,
+    /// seconds the provider typically needs to prove a request of this system
+    pub typical_proving_time: u32,
+    /// lowest reward the provider bids for, in base units of the reward token
+    pub minimum_reward: U256,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderCapabilities {
+    pub provider: Address,
+    pub systems: Vec<SystemCapability>,
+    pub max_concurrent_jobs: u32,
+    /// jobs the provider is currently working on
+    pub active_jobs: u32,
+    /// unix timestamp at which the record was issued
+    pub issued_at: u64,
+}
+
+impl ProviderCapabilities {
+    pub fn system(&self, system_id: SystemId) -> Option<&SystemCapability> {
+        self.systems
+            .iter()
+            .find(|capability| capability.system_id == system_id)
+    }
+
+    /// Whether the provider takes a request of `system_id` giving `proving_time` seconds to prove
+    /// and paying up to `max_reward`
+    pub fn accepts(&self, system_id: SystemId, proving_time: u32, max_reward: U256) -> bool {
+        self.system(system_id).is_some_and(|capability| {
+            capability.typical_proving_time <= proving_time
+                && capability.minimum_reward <= max_reward
+        })
+    }
+
+    /// Jobs the provider takes on top of the ones it works on
+    pub fn free_slots(&self) -> u32 {
+        self.max_concurrent_jobs.saturating_sub(self.active_jobs)
+    }
+
+    /// Hash signed by the provider address
+    pub fn digest(&self) -> B256 {
+        let systems: Vec<(u8, u32, U256)> = self
+            .systems
+            .iter()
+            .map(|capability| {
+                (
+                    capability.system_id.as_bit(),
+                    capability.typical_proving_time,
+                    capability.minimum_reward,
+                )
+            })
+            .collect();
+        let encoded = (
+            self.provider,
+            systems,
+            self.max_concurrent_jobs,
+            self.active_jobs,
+            self.issued_at,
+        )
+            .abi_encode_params();
+        keccak256([CAPABILITIES_DOMAIN, &encoded].concat())
+    }
+}
+
+/// Capabilities along with the provider's signature over them
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedCapabilities {
+    pub capabilities: ProviderCapabilities,
+    pub signature: PrimitiveSignature,
+}
+
+impl SignedCapabilities {
+    pub async fn sign<S: Signer>(capabilities: ProviderCapabilities, signer: &S) -> Result<Self> {
+        let signature = signer
+            .sign_hash(&capabilities.digest())
+            .await
+            .map_err(|e| PrimitivesError::SignatureError(e.to_string()))?;
+        Ok(Self {
+            capabilities,
+            signature,
+        })
+    }
+
+    /// Check the record was signed by the provider it describes
+    pub fn verify(&self) -> Result<()> {
+        let signer = self
+            .signature
+            .recover_address_from_prehash(&self.capabilities.digest())
+            .map_err(|e| PrimitivesError::SignatureError(e.to_string()))?;
+        if signer != self.capabilities.provider {
+            return Err(PrimitivesError::SignatureError(format!(
+                "capabilities of {} signed by {signer}",
+                self.capabilities.provider
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Capabilities of the live providers of one system, as aggregated by the server
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilitySummary {
+    /// providers that advertised the system
+    pub registered_providers: usize,
+    /// jobs the registered providers can take on top of the ones they work on
+    pub available_slots: u64,
+    /// shortest typical proving time of the registered providers
+    pub fastest_proving_time: Option<u32>,
+    /// lowest minimum reward of the registered providers
+    pub lowest_minimum_reward: Option<U256>,
+}
+
+impl CapabilitySummary {
+    pub fn aggregate<'a>(
+        system_id: SystemId,
+        providers: impl IntoIterator<Item = &'a ProviderCapabilities>,
+    ) -> Self {
+        providers
+            .into_iter()
+            .filter_map(|provider| Some((provider, provider.system(system_id)?)))
+            .fold(Self::default(), |summary, (provider, capability)| Self {
+                registered_providers: summary.registered_providers + 1,
+                available_slots: summary.available_slots + u64::from(provider.free_slots()),
+                fastest_proving_time: Some(
+                    summary
+                        .fastest_proving_time
+                        .map_or(capability.typical_proving_time, |fastest| {
+                            fastest.min(capability.typical_proving_time)
+                        }),
+                ),
+                lowest_minimum_reward: Some(
+                    summary
+                        .lowest_minimum_reward
+                        .map_or(capability.minimum_reward, |lowest| {
+                            lowest.min(capability.minimum_reward)
+                        }),
+                ),
+            })
+    }
+}
@@ -0,0 +1,309 @@
+//! Hashing of the commitments checked by the markets when an intent is resolved. The hash function
+//! is picked by the intent's `VerifierDetails.isShaCommitment`, so every commitment of an intent
+//! has to be computed through the `CommitmentHasher` derived from its verifier details.
+
+use std::fmt;
+use std::ops::Range;
+
+use alloy::primitives::{keccak256, Bytes, B256, U256};
+use alloy::signers::k256::sha2::{Digest, Sha256};
+
+use crate::abi::universal_bombetta::ProofRequestVerifierDetails;
+use crate::abi::verifier_details::{
+    decode_offer_verifier_details, decode_verifier_details, DecodeMode,
+};
+use crate::intents::CommonProofCommitment;
+use crate::systems::SystemId;
+use crate::{PrimitivesError, Result};
+
+/// Hash function of an intent's commitments
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommitmentHasher {
+    Keccak256,
+    Sha256,
+}
+
+impl CommitmentHasher {
+    #[must_use]
+    pub fn from_is_sha_commitment(is_sha_commitment: bool) -> Self {
+        if is_sha_commitment {
+            Self::Sha256
+        } else {
+            Self::Keccak256
+        }
+    }
+
+    /// Hasher of a `ComputeRequest`, from its abi encoded verifier details
+    pub fn from_request_extra_data(extra_data: &Bytes) -> Result<Self> {
+        decode_verifier_details(extra_data)
+            .map(|details| Self::from_is_sha_commitment(details.isShaCommitment))
+            .map_err(|e| PrimitivesError::CommitmentError(e.to_string()))
+    }
+
+    /// Hasher of a `ComputeOffer`, from its abi encoded verifier details
+    pub fn from_offer_extra_data(extra_data: &Bytes) -> Result<Self> {
+        decode_offer_verifier_details(extra_data, DecodeMode::Lenient)
+            .map(|details| Self::from_is_sha_commitment(details.isShaCommitment))
+            .map_err(|e| PrimitivesError::CommitmentError(e.to_string()))
+    }
+
+    #[must_use]
+    pub fn hash(&self, preimage: &[u8]) -> B256 {
+        match self {
+            Self::Keccak256 => keccak256(preimage),
+            Self::Sha256 => B256::from_slice(Sha256::digest(preimage).as_slice()),
+        }
+    }
+
+    /// Hash of `predeterminedPartialCommitment ++ submittedPartialCommitment`, which has to match
+    /// the hash of the result field of the opaque submission when the request checks it
+    #[must_use]
+    pub fn partial_commitment_result(
+        &self,
+        predetermined_partial_commitment: B256,
+        submitted_partial_commitment: B256,
+    ) -> B256 {
+        self.hash(
+            &[
+                predetermined_partial_commitment.as_slice(),
+                submitted_partial_commitment.as_slice(),
+            ]
+            .concat(),
+        )
+    }
+}
+
+impl From<&ProofRequestVerifierDetails> for CommitmentHasher {
+    fn from(verifier_details: &ProofRequestVerifierDetails) -> Self {
+        Self::from_is_sha_commitment(verifier_details.isShaCommitment)
+    }
+}
+
+impl fmt::Display for CommitmentHasher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Keccak256 => write!(f, "keccak256"),
+            Self::Sha256 => write!(f, "sha256"),
+        }
+    }
+}
+
+/// Partial commitment result check declared by a request's verifier details. On resolve the market
+/// checks the hash of the submission's result field is the hash of the predetermined partial
+/// commitment followed by the partial commitment submitted along with the submission.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PartialCommitmentCheck {
+    pub hasher: CommitmentHasher,
+    pub result_offset: U256,
+    pub result_length: U256,
+    pub predetermined_partial_commitment: B256,
+}
+
+impl PartialCommitmentCheck {
+    /// Check declared by `verifier_details`, if they declare one
+    #[must_use]
+    pub fn from_verifier_details(verifier_details: &ProofRequestVerifierDetails) -> Option<Self> {
+        verifier_details
+            .hasPartialCommitmentResultCheck
+            .then(|| Self {
+                hasher: CommitmentHasher::from(verifier_details),
+                result_offset: verifier_details.submittedPartialCommitmentResultOffset,
+                result_length: verifier_details.submittedPartialCommitmentResultLength,
+                predetermined_partial_commitment: verifier_details.predeterminedPartialCommitment,
+            })
+    }
+
+    /// Partial commitment to submit along with `opaque_submission` for the check to pass. Short
+    /// of a hash collision, its result field has to hold the predetermined partial commitment
+    /// followed by the submitted one.
+    pub fn submitted_partial_commitment(&self, opaque_submission: &[u8]) -> Result<B256> {
+        let result = submission_field(opaque_submission, self.result_offset, self.result_length)?;
+        match result.split_at_checked(32) {
+            Some((predetermined, submitted))
+                if predetermined == self.predetermined_partial_commitment.as_slice()
+                    && submitted.len() == 32 =>
+            {
+                Ok(B256::from_slice(submitted))
+            }
+            _ => Err(PrimitivesError::CommitmentError(format!(
+                "result field at offset {} of length {} doesn't hold the predetermined partial commitment {} followed by a 32 byte one",
+                self.result_offset, self.result_length, self.predetermined_partial_commitment
+            ))),
+        }
+    }
+
+    /// Run the check the market runs on resolve
+    pub fn verify(
+        &self,
+        opaque_submission: &[u8],
+        submitted_partial_commitment: B256,
+    ) -> Result<()> {
+        let result = submission_field(opaque_submission, self.result_offset, self.result_length)?;
+        if self.hasher.hash(result)
+            != self.hasher.partial_commitment_result(
+                self.predetermined_partial_commitment,
+                submitted_partial_commitment,
+            )
+        {
+            return Err(PrimitivesError::CommitmentError(format!(
+                "submitted partial commitment result does not match the {} hash of the predetermined and submitted partial commitments",
+                self.hasher
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Partial commitment to resolve an intent with `opaque_submission`: the one its partial
+/// commitment result check takes, zero when it declares none
+pub fn submitted_partial_commitment<C: CommonProofCommitment>(
+    proof_commitment: &C,
+    opaque_submission: &[u8],
+) -> Result<B256> {
+    match proof_commitment.partial_commitment_check() {
+        Some(check) => check.submitted_partial_commitment(opaque_submission),
+        None => Ok(B256::ZERO),
+    }
+}
+
+/// Check `inputs_commitment` is the hash of `preimage` under the hasher declared by the
+/// verifier details, pointing out commitments hashed with the other function.
+pub fn check_inputs_commitment(
+    hasher: CommitmentHasher,
+    inputs_commitment: B256,
+    preimage: &[u8],
+) -> Result<()> {
+    if hasher.hash(preimage) == inputs_commitment {
+        return Ok(());
+    }
+    let other = match hasher {
+        CommitmentHasher::Keccak256 => CommitmentHasher::Sha256,
+        CommitmentHasher::Sha256 => CommitmentHasher::Keccak256,
+    };
+    if other.hash(preimage) == inputs_commitment {
+        Err(PrimitivesError::CommitmentError(format!(
+            "inputs commitment is the {other} hash of the inputs, but the verifier details declare {hasher}"
+        )))
+    } else {
+        Err(PrimitivesError::CommitmentError(format!(
+            "inputs commitment does not match the {hasher} hash of the inputs"
+        )))
+    }
+}
+
+/// Slice `offset..offset + length` of an opaque submission, as extracted by the markets
+pub fn submission_field(opaque_submission: &[u8], offset: U256, length: U256) -> Result<&[u8]> {
+    let out_of_bounds = || {
+        PrimitivesError::CommitmentError(format!(
+            "field at offset {offset} of length {length} exceeds the {} byte opaque submission",
+            opaque_submission.len()
+        ))
+    };
+    let start = usize::try_from(offset).map_err(|_| out_of_bounds())?;
+    let len = usize::try_from(length).map_err(|_| out_of_bounds())?;
+    let end = start.checked_add(len).ok_or_else(out_of_bounds)?;
+    opaque_submission.get(start..end).ok_or_else(out_of_bounds)
+}
+
+/// Offset in an arkworks opaque submission of its first public signal. The proof points `pA`,
+/// `pB` and `pC` have a fixed size, so the `pubSignals` elements always start past their encoding
+/// and the length word of `pubSignals`, whatever their number.
+pub const ARKWORKS_PUBLIC_INPUTS_OFFSET: usize = 640;
+/// Length of each public signal in an arkworks opaque submission
+pub const ARKWORKS_PUBLIC_INPUT_LENGTH: usize = 32;
+
+/// Range of an opaque submission holding the public inputs of its proof, in the layout the
+/// workers format each system with:
+/// * risc0 `(bytes seal, bytes32 imageId, bytes32 journalDigest)`: `imageId` and `journalDigest`
+/// * sp1 `(bytes32 vkey, bytes publicValues, bytes proofBytes)`: the contents of `publicValues`
+/// * arkworks `(uint256[] pA, uint256[][] pB, uint256[] pC, uint256[] pubSignals)`: the
+///   elements of `pubSignals`
+pub fn public_inputs_range(system_id: SystemId, opaque_submission: &[u8]) -> Result<Range<usize>> {
+    const WORD: usize = 32;
+    let malformed = |reason: &str| {
+        PrimitivesError::CommitmentError(format!(
+            "malformed {} opaque submission of {} bytes: {reason}",
+            system_id.as_str(),
+            opaque_submission.len()
+        ))
+    };
+    let word = |offset: usize| -> Result<usize> {
+        let word = offset
+            .checked_add(WORD)
+            .and_then(|end| opaque_submission.get(offset..end))
+            .ok_or_else(|| malformed("truncated head"))?;
+        usize::try_from(U256::from_be_slice(word)).map_err(|_| malformed("oversized word"))
+    };
+    // dynamic fields are encoded as their length followed by their contents
+    let dynamic_field = |head_index: usize, element_size: usize| -> Result<Range<usize>> {
+        let offset = word(head_index * WORD)?;
+        let length = word(offset)?
+            .checked_mul(element_size)
+            .ok_or_else(|| malformed("oversized field"))?;
+        let start = offset + WORD;
+        let end = start
+            .checked_add(length)
+            .filter(|end| *end <= opaque_submission.len())
+            .ok_or_else(|| malformed("field exceeds the submission"))?;
+        Ok(start..end)
+    };
+
+    match system_id {
+        SystemId::Risc0 if opaque_submission.len() >= 3 * WORD => Ok(WORD..3 * WORD),
+        SystemId::Risc0 => Err(malformed("truncated head")),
+        SystemId::Sp1 => dynamic_field(1, 1),
+        SystemId::Arkworks => dynamic_field(3, WORD).and_then(|range| {
+            if range.start == ARKWORKS_PUBLIC_INPUTS_OFFSET {
+                Ok(range)
+            } else {
+                Err(malformed("public signals not past fixed size proof points"))
+            }
+        }),
+    }
+}
+
+/// Check the inputs the verifier details of an intent point at lie within the public inputs of
+/// its opaque submission, and that those are the public inputs the proof was produced with.
+/// Inputs declared anywhere else would be checked against the commitment by the market, failing
+/// only once the resolve is sent.
+pub fn check_public_inputs_layout(
+    system_id: SystemId,
+    opaque_submission: &[u8],
+    extra_data: &[u8],
+    expected_public_inputs: &[u8],
+) -> Result<()> {
+    // the request verifier details start with the fields of the offer ones
+    let verifier_details = decode_offer_verifier_details(extra_data, DecodeMode::Lenient)
+        .map_err(|e| PrimitivesError::CommitmentError(e.to_string()))?;
+    if verifier_details.inputsLength.is_zero() {
+        return Ok(());
+    }
+
+    let range = public_inputs_range(system_id, opaque_submission)?;
+    let public_inputs = &opaque_submission[range.clone()];
+    if public_inputs != expected_public_inputs {
+        return Err(PrimitivesError::CommitmentError(format!(
+            "{} opaque submission holds {} bytes of public inputs at {range:?} not matching the {} bytes of the proof",
+            system_id.as_str(),
+            public_inputs.len(),
+            expected_public_inputs.len()
+        )));
+    }
+
+    let declared = submission_field(
+        opaque_submission,
+        verifier_details.inputsOffset,
+        verifier_details.inputsLength,
+    )?;
+    // the field is in bounds, so its offset fits
+    let start = verifier_details.inputsOffset.to::<usize>();
+    if start < range.start || start + declared.len() > range.end {
+        return Err(PrimitivesError::CommitmentError(format!(
+            "inputs at offset {} of length {} fall outside the {} public inputs at {range:?} of the opaque submission",
+            verifier_details.inputsOffset,
+            verifier_details.inputsLength,
+            system_id.as_str()
+        )));
+    }
+    Ok(())
+}
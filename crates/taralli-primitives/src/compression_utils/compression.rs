@@ -1,13 +1,57 @@
 use std::io::Write;
+use std::time::Duration;
 
 use async_compression::tokio::bufread::BrotliDecoder;
 use tokio::io::AsyncReadExt;
 
 use crate::{
+    encryption::SystemPayload,
     error::{PrimitivesError, Result},
     systems::SystemParams,
 };
 
+/// Brotli encoder settings
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BrotliParams {
+    /// internal buffer size, 0 picks the encoder's default
+    pub buffer_size: usize,
+    /// compression level, from 0 (fastest) to 11 (smallest)
+    pub level: u32,
+    /// base two logarithm of the window size
+    pub window_size: u32,
+}
+
+impl Default for BrotliParams {
+    fn default() -> Self {
+        Self {
+            buffer_size: 0,
+            level: 7,
+            window_size: 24,
+        }
+    }
+}
+
+impl BrotliParams {
+    /// Settings read from the `BROTLI_BUFFER_SIZE`, `BROTLI_COMPRESSION_LEVEL` and
+    /// `BROTLI_WINDOW_SIZE` environment variables, defaulting the unset or invalid ones
+    #[must_use]
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        Self {
+            buffer_size: env_or("BROTLI_BUFFER_SIZE", default.buffer_size),
+            level: env_or("BROTLI_COMPRESSION_LEVEL", default.level),
+            window_size: env_or("BROTLI_WINDOW_SIZE", default.window_size),
+        }
+    }
+}
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
 /// Compresses the bytes payload using Brotli compression
 /// and returns the compressed payload as a byte vector
 /// # Arguments
@@ -16,28 +60,31 @@ use crate::{
 /// * A byte vector containing the compressed payload
 /// # Details
 /// The compression level, buffer size, and window size are configurable
-/// via the environment variables.
+/// via the environment variables, see `BrotliParams::from_env`.
 /// Furthermore, we chose to instantiate a new compressor for each intent
 /// if the need to submit multiple intent concurrently arises.
 pub fn compress_brotli<T>(payload: &T) -> Result<Vec<u8>>
 where
     T: AsRef<[u8]>,
 {
-    // We opt for some default values that may be reasonable for the general use case.
+    compress_brotli_with_params(payload, &BrotliParams::from_env())
+}
+
+/// Compresses the bytes payload using Brotli compression with the given encoder settings
+/// # Arguments
+/// * `payload` - The payload to be compressed
+/// * `params` - The encoder settings
+/// # Returns
+/// * A byte vector containing the compressed payload
+pub fn compress_brotli_with_params<T>(payload: &T, params: &BrotliParams) -> Result<Vec<u8>>
+where
+    T: AsRef<[u8]>,
+{
     let mut brotli_encoder = brotli::CompressorWriter::new(
         Vec::new(),
-        std::env::var("BROTLI_BUFFER_SIZE")
-            .unwrap_or_else(|_| "0".to_string())
-            .parse::<usize>()
-            .unwrap_or(0),
-        std::env::var("BROTLI_COMPRESSION_LEVEL")
-            .unwrap_or_else(|_| "7".to_string())
-            .parse::<u32>()
-            .unwrap_or(7),
-        std::env::var("BROTLI_WINDOW_SIZE")
-            .unwrap_or_else(|_| "24".to_string())
-            .parse::<u32>()
-            .unwrap_or(24),
+        params.buffer_size,
+        params.level,
+        params.window_size,
     );
     brotli_encoder
         .write_all(payload.as_ref())
@@ -50,7 +97,7 @@ where
 /// * `compressed_bytes` - The Brotli-compressed byte vector
 /// # Returns
 /// * A byte vector containing the decompressed data
-pub async fn decompress_system(compressed_bytes: Vec<u8>) -> Result<SystemParams> {
+pub async fn decompress_system(compressed_bytes: impl AsRef<[u8]>) -> Result<SystemParams> {
     let decompressed = decompress_brotli(compressed_bytes).await?;
     let params = serde_json::from_slice(&decompressed)
         .map_err(|e| PrimitivesError::DecompressionError(e.to_string()))?;
@@ -62,8 +109,8 @@ pub async fn decompress_system(compressed_bytes: Vec<u8>) -> Result<SystemParams
 /// * `compressed_bytes` - The Brotli-compressed byte vector
 /// # Returns
 /// * A byte vector containing the decompressed data
-pub async fn decompress_brotli(compressed_bytes: Vec<u8>) -> Result<Vec<u8>> {
-    let mut decoder = BrotliDecoder::new(tokio::io::BufReader::new(&compressed_bytes[..]));
+pub async fn decompress_brotli(compressed_bytes: impl AsRef<[u8]>) -> Result<Vec<u8>> {
+    let mut decoder = BrotliDecoder::new(tokio::io::BufReader::new(compressed_bytes.as_ref()));
     let mut decompressed = Vec::new();
     decoder
         .read_to_end(&mut decompressed)
@@ -71,3 +118,92 @@ pub async fn decompress_brotli(compressed_bytes: Vec<u8>) -> Result<Vec<u8>> {
         .map_err(|e| PrimitivesError::DecompressionError(e.to_string()))?;
     Ok(decompressed)
 }
+
+/// Upper bounds applied when decompressing untrusted payloads
+#[derive(Clone, Debug)]
+pub struct DecompressionBudget {
+    /// maximum number of decompressed bytes
+    pub max_output_bytes: usize,
+    /// maximum wall time spent decompressing
+    pub max_duration: Duration,
+}
+
+impl Default for DecompressionBudget {
+    fn default() -> Self {
+        Self {
+            max_output_bytes: 64 * 1024 * 1024, // 64 MiB
+            max_duration: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Decompress a Brotli-compressed byte vector into system parameters, in the clear or encrypted
+/// # Arguments
+/// * `compressed_bytes` - The Brotli-compressed byte vector
+/// # Returns
+/// * The decompressed system payload
+pub async fn decompress_system_payload(
+    compressed_bytes: impl AsRef<[u8]>,
+) -> Result<SystemPayload> {
+    let decompressed = decompress_brotli(compressed_bytes).await?;
+    let payload = serde_json::from_slice(&decompressed)
+        .map_err(|e| PrimitivesError::DecompressionError(e.to_string()))?;
+    Ok(payload)
+}
+
+/// Decompress a Brotli-compressed byte vector into system parameters, in the clear or encrypted,
+/// within the given budget
+/// # Arguments
+/// * `compressed_bytes` - The Brotli-compressed byte vector
+/// * `budget` - The output size and time limits of the decompression
+/// # Returns
+/// * The decompressed system payload
+pub async fn decompress_system_bounded(
+    compressed_bytes: &[u8],
+    budget: &DecompressionBudget,
+) -> Result<SystemPayload> {
+    let decompressed = decompress_brotli_bounded(compressed_bytes, budget).await?;
+    let params = serde_json::from_slice(&decompressed)
+        .map_err(|e| PrimitivesError::DecompressionError(e.to_string()))?;
+    Ok(params)
+}
+
+/// Incrementally decompress a Brotli-compressed byte slice, aborting as soon as the output
+/// grows past `budget.max_output_bytes` or decompression runs longer than `budget.max_duration`.
+/// # Arguments
+/// * `compressed_bytes` - The Brotli-compressed byte slice
+/// * `budget` - The output size and time limits of the decompression
+/// # Returns
+/// * A byte vector containing the decompressed data
+pub async fn decompress_brotli_bounded(
+    compressed_bytes: &[u8],
+    budget: &DecompressionBudget,
+) -> Result<Vec<u8>> {
+    let decompression = async {
+        let mut decoder = BrotliDecoder::new(tokio::io::BufReader::new(compressed_bytes));
+        let mut decompressed = Vec::new();
+        let mut chunk = [0u8; 16 * 1024];
+        loop {
+            let read = decoder
+                .read(&mut chunk)
+                .await
+                .map_err(|e| PrimitivesError::DecompressionError(e.to_string()))?;
+            if read == 0 {
+                return Ok(decompressed);
+            }
+            if decompressed.len() + read > budget.max_output_bytes {
+                return Err(PrimitivesError::DecompressionLimitExceeded(
+                    budget.max_output_bytes,
+                ));
+            }
+            decompressed.extend_from_slice(&chunk[..read]);
+            // the decoder reads from memory and never pends on its own, yield so the
+            // timeout below gets a chance to fire
+            tokio::task::yield_now().await;
+        }
+    };
+
+    tokio::time::timeout(budget.max_duration, decompression)
+        .await
+        .map_err(|_| PrimitivesError::DecompressionTimeout(budget.max_duration.as_secs()))?
+}
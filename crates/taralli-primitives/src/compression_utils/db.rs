@@ -131,26 +131,44 @@ where
             PrimitivesError::DbDeserializeError(format!("Failed to decompress system bytes: {e}"))
         })?;
 
+        stored.into_offer(&decompressed_bytes)
+    }
+}
+
+impl StoredIntent {
+    /// Convert a stored offer into a `ComputeOffer` given its decompressed system, e.g. when
+    /// decompressed against the dictionary it references
+    pub fn into_offer<S: System>(self, decompressed_system: &[u8]) -> Result<ComputeOffer<S>>
+    where
+        S: for<'de> serde::Deserialize<'de>,
+    {
         // Parse the decompressed system data
-        let system = serde_json::from_slice::<S>(&decompressed_bytes).map_err(|e| {
+        let system = serde_json::from_slice::<S>(decompressed_system).map_err(|e| {
             PrimitivesError::DbDeserializeError(format!(
                 "Failed to deserialize decompressed system: {e}"
             ))
         })?;
 
         // Parse the proof_commitment from binary data
-        let proof_offer = serde_json::from_slice(&stored.proof_commitment).map_err(|e| {
+        let proof_offer = serde_json::from_slice(&self.proof_commitment).map_err(|e| {
             PrimitivesError::DbDeserializeError(format!(
                 "Failed to deserialize proof_commitment: {e}"
             ))
         })?;
 
         // Convert system_id string to SystemId
-        let system_id = SystemId::try_from(stored.system_id.as_str())
+        let system_id = SystemId::try_from(self.system_id.as_str())
             .map_err(|e| PrimitivesError::DbDeserializeError(format!("Invalid system_id: {e}")))?;
+        if system.system_id() != system_id {
+            return Err(PrimitivesError::DbDeserializeError(format!(
+                "system params of {} stored for system {}",
+                system.system_id().as_str(),
+                system_id.as_str()
+            )));
+        }
 
         // Convert signature bytes to Signature type
-        let signature = PrimitiveSignature::try_from(stored.signature.as_slice())
+        let signature = PrimitiveSignature::try_from(self.signature.as_slice())
             .map_err(|e| PrimitivesError::DbDeserializeError(format!("Invalid signature: {e}")))?;
 
         // Construct and return the ComputeOffer
@@ -0,0 +1,187 @@
+//! Brotli compression of systems against a shared dictionary. Requesters submitting many intents
+//! around the same guest program (ELF, circuit wasm) register it once as a dictionary, each
+//! system then compressing down to what differs from it, e.g. its inputs.
+//!
+//! Dictionaries are content-addressed by the keccak256 hash of their bytes. A system compressed
+//! against one is framed as `DICTIONARY_MAGIC | dictionary hash | brotli stream`, systems
+//! compressed without dictionary staying plain brotli streams.
+
+use std::io::{self, Write};
+
+use alloy::primitives::{keccak256, B256};
+use brotli::enc::{BrotliCompressCustomIoCustomDict, BrotliEncoderParams, StandardAlloc};
+use brotli::interface::{PredictionModeContextMap, StaticCommand};
+use brotli::{InputPair, InputReferenceMut, IoReaderWrapper, IoWriterWrapper};
+use serde::Serialize;
+
+use crate::{
+    compression_utils::compression::{decompress_brotli_bounded, DecompressionBudget},
+    encryption::SystemPayload,
+    error::{PrimitivesError, Result},
+};
+
+/// Leading bytes of a system compressed against a dictionary
+pub const DICTIONARY_MAGIC: [u8; 4] = *b"TBD1";
+
+/// Length of the frame preceding the brotli stream of a system compressed against a dictionary
+pub const DICTIONARY_HEADER_LEN: usize = DICTIONARY_MAGIC.len() + 32;
+
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// Dictionary of the systems sharing `base_system`'s guest program: its serialized JSON, what the
+/// systems are compressed from. The raw ELF or wasm bytes would hardly match, byte arrays being
+/// serialized as JSON arrays of numbers.
+pub fn system_dictionary<S: Serialize>(base_system: &S) -> Result<Vec<u8>> {
+    serde_json::to_vec(base_system).map_err(|e| PrimitivesError::CompressionError(e.to_string()))
+}
+
+/// Content address of a dictionary
+pub fn dictionary_hash(dictionary: &[u8]) -> B256 {
+    keccak256(dictionary)
+}
+
+/// Hash of the dictionary the compressed bytes reference, `None` for a plain brotli stream
+pub fn dictionary_reference(compressed_bytes: &[u8]) -> Option<B256> {
+    if compressed_bytes.len() < DICTIONARY_HEADER_LEN
+        || compressed_bytes[..DICTIONARY_MAGIC.len()] != DICTIONARY_MAGIC
+    {
+        return None;
+    }
+    Some(B256::from_slice(
+        &compressed_bytes[DICTIONARY_MAGIC.len()..DICTIONARY_HEADER_LEN],
+    ))
+}
+
+/// Compresses the payload using `dictionary` as Brotli custom dictionary, framed with the hash
+/// of the dictionary needed to decompress it. Compression level and window size are read from
+/// the same environment variables as `compress_brotli`.
+pub fn compress_brotli_with_dictionary<T>(payload: &T, dictionary: &[u8]) -> Result<Vec<u8>>
+where
+    T: AsRef<[u8]>,
+{
+    let mut params = BrotliEncoderParams::default();
+    params.quality = std::env::var("BROTLI_COMPRESSION_LEVEL")
+        .ok()
+        .and_then(|level| level.parse().ok())
+        .unwrap_or(7);
+    params.lgwin = std::env::var("BROTLI_WINDOW_SIZE")
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(24);
+
+    let mut compressed = Vec::with_capacity(DICTIONARY_HEADER_LEN);
+    compressed.extend_from_slice(&DICTIONARY_MAGIC);
+    compressed.extend_from_slice(dictionary_hash(dictionary).as_slice());
+
+    let mut input = payload.as_ref();
+    let mut input_buffer = vec![0u8; BUFFER_SIZE];
+    let mut output_buffer = vec![0u8; BUFFER_SIZE];
+    let mut nop_callback = |_: &mut PredictionModeContextMap<InputReferenceMut>,
+                            _: &mut [StaticCommand],
+                            _: InputPair,
+                            _: &mut StandardAlloc| ();
+    BrotliCompressCustomIoCustomDict(
+        &mut IoReaderWrapper(&mut input),
+        &mut IoWriterWrapper(&mut compressed),
+        &mut input_buffer[..],
+        &mut output_buffer[..],
+        &params,
+        StandardAlloc::default(),
+        &mut nop_callback,
+        dictionary,
+        io::Error::new(io::ErrorKind::UnexpectedEof, "Unexpected EOF"),
+    )
+    .map_err(|e| PrimitivesError::CompressionError(e.to_string()))?;
+    Ok(compressed)
+}
+
+/// Decompress bytes compressed with or without dictionary within the given budget.
+/// `dictionary` must be the one referenced by the compressed bytes, if any.
+/// # Errors
+/// * `UnknownDictionary` if the bytes reference a dictionary not given or not matching its hash
+/// * decompression errors as `decompress_brotli_bounded`
+pub async fn decompress_brotli_with_dictionary_bounded(
+    compressed_bytes: &[u8],
+    dictionary: Option<&[u8]>,
+    budget: &DecompressionBudget,
+) -> Result<Vec<u8>> {
+    let Some(hash) = dictionary_reference(compressed_bytes) else {
+        return decompress_brotli_bounded(compressed_bytes, budget).await;
+    };
+    let dictionary = dictionary
+        .filter(|dictionary| dictionary_hash(dictionary) == hash)
+        .ok_or(PrimitivesError::UnknownDictionary(hash))?
+        .to_vec();
+    let stream = compressed_bytes[DICTIONARY_HEADER_LEN..].to_vec();
+    let max_output_bytes = budget.max_output_bytes;
+
+    // the custom dictionary decoder is synchronous, run it off the runtime and stop waiting on
+    // it once out of time, its output bound stopping it soon after
+    let decompression = tokio::task::spawn_blocking(move || {
+        let mut output = BoundedWriter {
+            inner: Vec::new(),
+            max: max_output_bytes,
+            exceeded: false,
+        };
+        let mut input_buffer = vec![0u8; BUFFER_SIZE];
+        let mut output_buffer = vec![0u8; BUFFER_SIZE];
+        let result = brotli::BrotliDecompressCustomDict(
+            &mut stream.as_slice(),
+            &mut output,
+            &mut input_buffer,
+            &mut output_buffer,
+            dictionary,
+        );
+        match result {
+            Ok(()) => Ok(output.inner),
+            Err(_) if output.exceeded => Err(PrimitivesError::DecompressionLimitExceeded(
+                max_output_bytes,
+            )),
+            Err(e) => Err(PrimitivesError::DecompressionError(e.to_string())),
+        }
+    });
+
+    tokio::time::timeout(budget.max_duration, decompression)
+        .await
+        .map_err(|_| PrimitivesError::DecompressionTimeout(budget.max_duration.as_secs()))?
+        .map_err(|e| PrimitivesError::DecompressionError(e.to_string()))?
+}
+
+/// Decompress a system compressed with or without dictionary into system parameters, in the
+/// clear or encrypted, within the given budget
+pub async fn decompress_system_with_dictionary_bounded(
+    compressed_bytes: &[u8],
+    dictionary: Option<&[u8]>,
+    budget: &DecompressionBudget,
+) -> Result<SystemPayload> {
+    let decompressed =
+        decompress_brotli_with_dictionary_bounded(compressed_bytes, dictionary, budget).await?;
+    serde_json::from_slice(&decompressed)
+        .map_err(|e| PrimitivesError::DecompressionError(e.to_string()))
+}
+
+/// Output refusing writes past `max` bytes, remembering the overflow so it can be told apart
+/// from a corrupt stream
+struct BoundedWriter {
+    inner: Vec<u8>,
+    max: usize,
+    exceeded: bool,
+}
+
+impl Write for BoundedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.inner.len() + buf.len() > self.max {
+            self.exceeded = true;
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "decompressed output exceeds its limit",
+            ));
+        }
+        self.inner.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
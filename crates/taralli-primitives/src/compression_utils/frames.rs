@@ -0,0 +1,151 @@
+//! Deflated broadcast frames, sent to subscribers that negotiated frame compression.
+//!
+//! The websocket stacks of the server and the clients don't implement `permessage-deflate`, so
+//! compression is negotiated through `FRAME_COMPRESSION_HEADER` on the upgrade request and
+//! response and applied to the message itself. A deflated frame is laid out as
+//!
+//! `DEFLATED_FRAME_TAG | deflated fields length (u32 LE) | deflated fields | system`
+//!
+//! where the fields are the bincode encoded broadcast envelope of the request without its system,
+//! stamped with the server's `BroadcastMeta`, and the
+//! system is the already Brotli compressed blob appended as is, as deflating it again costs time
+//! without making it smaller. Uncompressed frames start with the little endian envelope version or
+//! legacy system id, both far below the tag.
+
+use std::io::{Read, Write};
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+
+use crate::{
+    error::{PrimitivesError, Result},
+    intents::envelope::{
+        decode_broadcast_envelope, BroadcastEnvelope, BroadcastMeta, IntentEnvelope, IntentKind,
+        BROADCAST_META_VERSION,
+    },
+};
+
+use super::intents::{
+    ComputeRequestCompressed, ComputeRequestCompressedRef, PartialComputeRequest,
+};
+
+/// Header through which subscribers ask for, and the server confirms, compressed frames
+pub const FRAME_COMPRESSION_HEADER: &str = "x-taralli-frame-compression";
+/// Value of `FRAME_COMPRESSION_HEADER` selecting deflated frames
+pub const DEFLATE_FRAME_COMPRESSION: &str = "deflate";
+/// First byte of a deflated frame
+pub const DEFLATED_FRAME_TAG: u8 = 0xdf;
+/// Upper bound of the inflated fields, guarding subscribers against deflate bombs
+pub const MAX_INFLATED_FIELDS_BYTES: usize = 16 * 1024 * 1024;
+
+/// bytes preceding the deflated fields
+const FRAME_HEADER_LENGTH: usize = 5;
+
+/// Encode a request as a deflated frame, wrapped within a broadcast envelope of
+/// `BROADCAST_META_VERSION`. Requests are laid out the same in later versions, so every
+/// subscriber negotiating deflated frames reads them.
+pub fn deflate_request_frame(
+    request: &ComputeRequestCompressed,
+    meta: BroadcastMeta,
+) -> Result<Vec<u8>> {
+    let fields = bincode::serialize(&BroadcastEnvelope {
+        v: BROADCAST_META_VERSION,
+        kind: IntentKind::Request,
+        meta,
+        payload: PartialComputeRequest {
+            system_id: request.system_id,
+            proof_request: request.proof_request.clone(),
+            signature: request.signature,
+        },
+    })
+    .map_err(|e| PrimitivesError::SerializationError(e.to_string()))?;
+
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(&fields)
+        .map_err(|e| PrimitivesError::CompressionError(e.to_string()))?;
+    let deflated = encoder
+        .finish()
+        .map_err(|e| PrimitivesError::CompressionError(e.to_string()))?;
+    let deflated_length = u32::try_from(deflated.len()).map_err(|_| {
+        PrimitivesError::CompressionError(format!("deflated fields of {} bytes", deflated.len()))
+    })?;
+
+    let mut frame = Vec::with_capacity(FRAME_HEADER_LENGTH + deflated.len() + request.system.len());
+    frame.push(DEFLATED_FRAME_TAG);
+    frame.extend_from_slice(&deflated_length.to_le_bytes());
+    frame.extend_from_slice(&deflated);
+    frame.extend_from_slice(&request.system);
+    Ok(frame)
+}
+
+#[must_use]
+pub fn is_deflated_frame(bytes: &[u8]) -> bool {
+    bytes.first() == Some(&DEFLATED_FRAME_TAG)
+}
+
+/// Decode a deflated frame back into the enveloped request it was encoded from, along with its
+/// broadcast metadata unless the frame predates it
+pub fn inflate_request_frame(
+    bytes: &[u8],
+) -> Result<(
+    IntentEnvelope<ComputeRequestCompressed>,
+    Option<BroadcastMeta>,
+)> {
+    let (envelope, meta) = inflate_request_frame_ref(bytes)?;
+    Ok((
+        IntentEnvelope {
+            v: envelope.v,
+            kind: envelope.kind,
+            payload: envelope.payload.into_owned(),
+        },
+        meta,
+    ))
+}
+
+/// `inflate_request_frame` borrowing the system appended to the frame rather than copying it
+pub fn inflate_request_frame_ref(
+    bytes: &[u8],
+) -> Result<(
+    IntentEnvelope<ComputeRequestCompressedRef<'_>>,
+    Option<BroadcastMeta>,
+)> {
+    if !is_deflated_frame(bytes) || bytes.len() < FRAME_HEADER_LENGTH {
+        return Err(PrimitivesError::DecompressionError(
+            "not a deflated frame".to_string(),
+        ));
+    }
+    let deflated_length = u32::from_le_bytes(
+        bytes[1..FRAME_HEADER_LENGTH]
+            .try_into()
+            .expect("slice of 4 bytes"),
+    ) as usize;
+    let deflated_end = FRAME_HEADER_LENGTH
+        .checked_add(deflated_length)
+        .filter(|end| *end <= bytes.len())
+        .ok_or_else(|| {
+            PrimitivesError::DecompressionError(format!(
+                "deflated fields of {deflated_length} bytes exceed the frame"
+            ))
+        })?;
+
+    let mut fields = Vec::new();
+    DeflateDecoder::new(&bytes[FRAME_HEADER_LENGTH..deflated_end])
+        .take(MAX_INFLATED_FIELDS_BYTES as u64 + 1)
+        .read_to_end(&mut fields)
+        .map_err(|e| PrimitivesError::DecompressionError(e.to_string()))?;
+    if fields.len() > MAX_INFLATED_FIELDS_BYTES {
+        return Err(PrimitivesError::DecompressionLimitExceeded(
+            MAX_INFLATED_FIELDS_BYTES,
+        ));
+    }
+
+    let (envelope, meta) = decode_broadcast_envelope::<PartialComputeRequest>(&fields)?;
+    Ok((
+        IntentEnvelope {
+            v: envelope.v,
+            kind: envelope.kind,
+            payload: ComputeRequestCompressedRef::from((envelope.payload, &bytes[deflated_end..])),
+        },
+        meta,
+    ))
+}
@@ -6,6 +6,10 @@ use crate::{
         universal_bombetta::UniversalBombetta::ProofRequest,
         universal_porchetta::UniversalPorchetta::ProofOffer,
     },
+    error::{PrimitivesError, Result},
+    intents::envelope::{
+        broadcast_kind, decode_broadcast_envelope, BroadcastMeta, IntentEnvelope, IntentKind,
+    },
     systems::SystemId,
 };
 
@@ -37,6 +41,53 @@ impl From<(PartialComputeRequest, Vec<u8>)> for ComputeRequestCompressed {
     }
 }
 
+/// `ComputeRequestCompressed` borrowing its system from the buffer it's decoded from, so the
+/// compressed system of a received broadcast isn't copied before being decompressed. Bincode
+/// encodes both the same way, either decodes what the other encoded.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ComputeRequestCompressedRef<'a> {
+    pub system_id: SystemId,
+    #[serde(borrow)]
+    pub system: &'a [u8],
+    pub proof_request: ProofRequest,
+    pub signature: PrimitiveSignature,
+}
+
+impl ComputeRequestCompressedRef<'_> {
+    /// Copy the borrowed system into an owned compressed request
+    #[must_use]
+    pub fn into_owned(self) -> ComputeRequestCompressed {
+        ComputeRequestCompressed {
+            system_id: self.system_id,
+            system: self.system.to_vec(),
+            proof_request: self.proof_request,
+            signature: self.signature,
+        }
+    }
+}
+
+impl<'a> From<&'a ComputeRequestCompressed> for ComputeRequestCompressedRef<'a> {
+    fn from(value: &'a ComputeRequestCompressed) -> Self {
+        ComputeRequestCompressedRef {
+            system_id: value.system_id,
+            system: &value.system,
+            proof_request: value.proof_request.clone(),
+            signature: value.signature,
+        }
+    }
+}
+
+impl<'a> From<(PartialComputeRequest, &'a [u8])> for ComputeRequestCompressedRef<'a> {
+    fn from(value: (PartialComputeRequest, &'a [u8])) -> Self {
+        ComputeRequestCompressedRef {
+            system_id: value.0.system_id,
+            system: value.1,
+            proof_request: value.0.proof_request,
+            signature: value.0.signature,
+        }
+    }
+}
+
 /// Same thing for compute offers as above
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PartialComputeOffer {
@@ -63,3 +114,59 @@ impl From<(PartialComputeOffer, Vec<u8>)> for ComputeOfferCompressed {
         }
     }
 }
+
+/// Either kind of compressed intent, as carried by a broadcast from `OFFER_BROADCAST_VERSION` on
+/// so a single subscription can receive requests and offers. The compressed system of requests is
+/// borrowed, from the request the server broadcasts or from the broadcast a client decodes.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum CompressedIntent<'a> {
+    #[serde(borrow)]
+    Request(ComputeRequestCompressedRef<'a>),
+    Offer(ComputeOfferCompressed),
+}
+
+impl<'a> CompressedIntent<'a> {
+    #[must_use]
+    pub fn kind(&self) -> IntentKind {
+        match self {
+            CompressedIntent::Request(_) => IntentKind::Request,
+            CompressedIntent::Offer(_) => IntentKind::Offer,
+        }
+    }
+
+    #[must_use]
+    pub fn system_id(&self) -> SystemId {
+        match self {
+            CompressedIntent::Request(request) => request.system_id,
+            CompressedIntent::Offer(offer) => offer.system_id,
+        }
+    }
+
+    /// Bincode encoding of the intent alone, which broadcasts wrap in the envelope of the version
+    /// each subscriber advertised
+    pub fn encode_payload(&self) -> Result<Vec<u8>> {
+        let encoded = match self {
+            CompressedIntent::Request(request) => bincode::serialize(request),
+            CompressedIntent::Offer(offer) => bincode::serialize(offer),
+        };
+        encoded.map_err(|e| PrimitivesError::SerializationError(e.to_string()))
+    }
+
+    /// Decode a bincode encoded broadcast, whichever kind of intent its envelope tags it with,
+    /// along with its metadata when its version carries one
+    pub fn decode_broadcast(
+        bytes: &'a [u8],
+    ) -> Result<(IntentEnvelope<CompressedIntent<'a>>, Option<BroadcastMeta>)> {
+        let (envelope, meta) = match broadcast_kind(bytes)? {
+            IntentKind::Request => {
+                let (envelope, meta) = decode_broadcast_envelope(bytes)?;
+                (envelope.map(CompressedIntent::Request), meta)
+            }
+            IntentKind::Offer => {
+                let (envelope, meta) = decode_broadcast_envelope(bytes)?;
+                (envelope.map(CompressedIntent::Offer), meta)
+            }
+        };
+        Ok((envelope, meta))
+    }
+}
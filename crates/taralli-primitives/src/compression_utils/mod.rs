@@ -2,4 +2,6 @@
 
 pub mod compression;
 pub mod db;
+pub mod dictionary;
+pub mod frames;
 pub mod intents;
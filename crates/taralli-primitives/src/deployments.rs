@@ -0,0 +1,157 @@
+//! Addresses of the protocol's deployments, per chain.
+//!
+//! The `DeploymentRegistry` starts from the built-in table of public deployments and can be
+//! extended with a JSON file for private ones, keyed by chain id:
+//! ```json
+//! {
+//!     "31337": {
+//!         "universal_bombetta": "0x...",
+//!         "universal_porchetta": "0x...",
+//!         "universal_bombetta_fee": { "fee_bps": 50 },
+//!         "verifiers": { "Risc0": "0x..." }
+//!     }
+//! }
+//! ```
+//! A deployment read from a file replaces the built-in one of its chain.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use alloy::primitives::{address, Address};
+use serde::{Deserialize, Serialize};
+
+use crate::markets::{
+    MarketFee, SEPOLIA_CHAIN_ID, SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+    SEPOLIA_UNIVERSAL_PORCHETTA_ADDRESS,
+};
+use crate::systems::SystemId;
+use crate::utils::PERMIT2_ADDRESS;
+use crate::{PrimitivesError, Result};
+
+fn default_permit2() -> Address {
+    PERMIT2_ADDRESS
+}
+
+/// Contracts of a deployment of the protocol
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Deployment {
+    pub universal_bombetta: Address,
+    pub universal_porchetta: Address,
+    /// protocol fee taken by the request market, none unless set
+    #[serde(default)]
+    pub universal_bombetta_fee: MarketFee,
+    /// protocol fee taken by the offer market, none unless set
+    #[serde(default)]
+    pub universal_porchetta_fee: MarketFee,
+    /// the canonical permit2 unless deployed elsewhere on the chain
+    #[serde(default = "default_permit2")]
+    pub permit2: Address,
+    /// canonical verifier of each system
+    #[serde(default)]
+    pub verifiers: HashMap<SystemId, Address>,
+}
+
+impl Deployment {
+    /// Canonical verifier of `system_id`, `None` when the deployment has none
+    #[must_use]
+    pub fn verifier(&self, system_id: SystemId) -> Option<Address> {
+        self.verifiers.get(&system_id).copied()
+    }
+
+    /// Protocol fee of the deployment's market at `market`, `None` when it isn't one of them
+    #[must_use]
+    pub fn market_fee(&self, market: Address) -> Option<MarketFee> {
+        if market == self.universal_bombetta {
+            Some(self.universal_bombetta_fee)
+        } else if market == self.universal_porchetta {
+            Some(self.universal_porchetta_fee)
+        } else {
+            None
+        }
+    }
+}
+
+/// Deployments of the protocol keyed by chain id
+#[derive(Clone, Debug)]
+pub struct DeploymentRegistry {
+    deployments: BTreeMap<u64, Deployment>,
+}
+
+impl Default for DeploymentRegistry {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl DeploymentRegistry {
+    /// Registry of the public deployments
+    #[must_use]
+    pub fn builtin() -> Self {
+        let sepolia = Deployment {
+            universal_bombetta: SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+            universal_porchetta: SEPOLIA_UNIVERSAL_PORCHETTA_ADDRESS,
+            universal_bombetta_fee: MarketFee::ZERO,
+            universal_porchetta_fee: MarketFee::ZERO,
+            permit2: PERMIT2_ADDRESS,
+            verifiers: HashMap::from([
+                (
+                    SystemId::Arkworks,
+                    address!("558D8D2f90c085A8Ed704084716F2797AAB26cC6"),
+                ),
+                (
+                    SystemId::Risc0,
+                    address!("AC292cF957Dd5BA174cdA13b05C16aFC71700327"),
+                ),
+                (
+                    SystemId::Sp1,
+                    address!("E780809121774D06aD9B0EEeC620fF4B3913Ced1"),
+                ),
+            ]),
+        };
+        Self {
+            deployments: BTreeMap::from([(SEPOLIA_CHAIN_ID, sepolia)]),
+        }
+    }
+
+    /// Add the deployment of `chain_id`, replacing the one known so far
+    #[must_use]
+    pub fn with_deployment(mut self, chain_id: u64, deployment: Deployment) -> Self {
+        self.deployments.insert(chain_id, deployment);
+        self
+    }
+
+    /// Add the deployments of the JSON file at `path`, replacing the ones known for their chains
+    pub fn with_overrides_file(self, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read_to_string(path).map_err(|e| {
+            PrimitivesError::ConfigError(format!("failed to read {}: {e}", path.display()))
+        })?;
+        self.with_overrides_json(&data)
+    }
+
+    /// Add the deployments of a JSON object keyed by chain id, replacing the ones known for
+    /// their chains
+    pub fn with_overrides_json(mut self, json: &str) -> Result<Self> {
+        let overrides: BTreeMap<u64, Deployment> = serde_json::from_str(json)
+            .map_err(|e| PrimitivesError::ConfigError(format!("invalid deployments: {e}")))?;
+        self.deployments.extend(overrides);
+        Ok(self)
+    }
+
+    /// Deployment of `chain_id`, failing with `PrimitivesError::UnknownChain` listing the known
+    /// chains when there's none
+    pub fn get(&self, chain_id: u64) -> Result<&Deployment> {
+        self.deployments
+            .get(&chain_id)
+            .ok_or_else(|| PrimitivesError::UnknownChain {
+                chain_id,
+                known: self.chain_ids(),
+            })
+    }
+
+    /// Chains with a known deployment, in ascending order
+    #[must_use]
+    pub fn chain_ids(&self) -> Vec<u64> {
+        self.deployments.keys().copied().collect()
+    }
+}
@@ -0,0 +1,380 @@
+//! Encrypted system params, letting requesters keep prover inputs away from every subscribed
+//! provider but the one winning the auction.
+//!
+//! The requester encrypts the system params under a random content key and broadcasts the
+//! ciphertext along with an ephemeral secp256k1 public key, the encapsulation key. Once a bid
+//! lands, the winning provider posts its own ephemeral public key through the server, signed by
+//! the bidding address. The requester answers with the content key wrapped under a key derived
+//! with HKDF from the ECDH secret of both ephemeral keys, signed by the requesting address. The
+//! server only relays the messages of the parties the market recorded for the request.
+//!
+//! Both the params and the wrapped key are sealed with AES-256-GCM, with the request id and the
+//! ephemeral public keys as associated data, so neither can be replayed for another request.
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use alloy::primitives::{keccak256, Address, Bytes, PrimitiveSignature, B256};
+use alloy::signers::k256::{
+    elliptic_curve::{
+        point::AffineCoordinates,
+        rand_core::{OsRng, RngCore},
+        sec1::ToEncodedPoint,
+    },
+    sha2::Sha256,
+    PublicKey, SecretKey,
+};
+use alloy::signers::Signer;
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{PrimitivesError, Result};
+use crate::systems::{System, SystemConfig, SystemId, SystemInputs, SystemParams};
+
+const NONCE_LENGTH: usize = 12;
+const TAG_LENGTH: usize = 16;
+const SYSTEM_PARAMS_DOMAIN: &[u8] = b"taralli system params";
+const KEY_REQUEST_DOMAIN: &[u8] = b"taralli key request";
+const KEY_DELIVERY_DOMAIN: &[u8] = b"taralli key delivery";
+const KEY_WRAPPING_INFO: &[u8] = b"taralli key wrapping";
+
+/// Key agreement and encryption scheme of encrypted system params
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EncryptionScheme {
+    /// ECDH over secp256k1 ephemeral keys, HKDF-SHA256 key derivation, AES-256-GCM encryption
+    #[default]
+    Secp256k1HkdfSha256Aes256Gcm,
+}
+
+/// System params encrypted by the requester. Only the system id and the ciphertext shape are
+/// public, which is all the validation of the request's public metadata needs.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedSystemParams {
+    pub system_id: SystemId,
+    pub scheme: EncryptionScheme,
+    /// compressed ephemeral public key of the requester
+    pub encapsulation_key: Bytes,
+    pub nonce: Bytes,
+    /// encrypted json of the `SystemParams`, followed by its authentication tag
+    pub ciphertext: Bytes,
+}
+
+impl System for EncryptedSystemParams {
+    type Config = EncryptionScheme;
+    type Inputs = Bytes;
+
+    fn system_id(&self) -> SystemId {
+        self.system_id
+    }
+
+    fn config(&self) -> &Self::Config {
+        &self.scheme
+    }
+
+    fn inputs(&self) -> SystemInputs {
+        SystemInputs::Bytes(self.ciphertext.to_vec())
+    }
+
+    fn validate_inputs(&self) -> Result<()> {
+        if self.ciphertext.len() <= TAG_LENGTH {
+            return Err(PrimitivesError::ProverInputsError(
+                "encrypted system params cannot be empty".to_string(),
+            ));
+        }
+        if self.nonce.len() != NONCE_LENGTH {
+            return Err(PrimitivesError::ProverInputsError(format!(
+                "encryption nonce must be {NONCE_LENGTH} bytes, got {}",
+                self.nonce.len()
+            )));
+        }
+        PublicKey::from_sec1_bytes(&self.encapsulation_key).map_err(|_| {
+            PrimitivesError::ProverInputsError(
+                "encapsulation key is not a secp256k1 public key".to_string(),
+            )
+        })?;
+        Ok(())
+    }
+}
+
+impl SystemConfig for EncryptionScheme {}
+
+impl EncryptedSystemParams {
+    /// Encrypt `params` of the request `intent_id` under a fresh content key, returning the keys
+    /// the requester keeps to hand the content key over to the winning provider.
+    pub fn encrypt(params: &SystemParams, intent_id: B256) -> Result<(Self, SystemKeys)> {
+        let system_id = params.system_id();
+        let plaintext = serde_json::to_vec(params)
+            .map_err(|e| PrimitivesError::SerializationError(e.to_string()))?;
+
+        let mut content_key = [0u8; 32];
+        OsRng.fill_bytes(&mut content_key);
+        let encapsulation_secret = SecretKey::random(&mut OsRng);
+        let encapsulation_key = public_key_bytes(&encapsulation_secret.public_key());
+
+        let aad = system_params_aad(system_id, intent_id, &encapsulation_key);
+        let (nonce, ciphertext) = seal(&content_key, &aad, &plaintext)?;
+        let encrypted = Self {
+            system_id,
+            scheme: EncryptionScheme::default(),
+            encapsulation_key,
+            nonce,
+            ciphertext,
+        };
+        let keys = SystemKeys {
+            content_key: B256::from(content_key),
+            encapsulation_secret: B256::from_slice(&encapsulation_secret.to_bytes()),
+        };
+        Ok((encrypted, keys))
+    }
+
+    /// Decrypt the system params of the request `intent_id` with the content key, checking they
+    /// have the shape their system expects.
+    pub fn decrypt(&self, content_key: &B256, intent_id: B256) -> Result<SystemParams> {
+        let aad = system_params_aad(self.system_id, intent_id, &self.encapsulation_key);
+        let plaintext = open(content_key.as_ref(), &aad, &self.nonce, &self.ciphertext)?;
+        SystemParams::try_from((&self.system_id, plaintext))
+            .map_err(PrimitivesError::EncryptionError)
+    }
+}
+
+/// Secrets the requester keeps after encrypting system params
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SystemKeys {
+    pub content_key: B256,
+    /// secret key of the broadcast encapsulation key
+    pub encapsulation_secret: B256,
+}
+
+impl SystemKeys {
+    /// Wrap the content key of the request `intent_id` for the holder of `provider_key`
+    pub fn wrap_for(&self, intent_id: B256, provider_key: &[u8]) -> Result<Bytes> {
+        let secret = SecretKey::from_slice(self.encapsulation_secret.as_slice())
+            .map_err(|e| PrimitivesError::EncryptionError(e.to_string()))?;
+        let encapsulation_key = public_key_bytes(&secret.public_key());
+        let provider = parse_public_key(provider_key)?;
+        let provider_key = public_key_bytes(&provider);
+
+        let wrapping_key = wrapping_key(&secret, &provider, &encapsulation_key, &provider_key)?;
+        let aad = key_delivery_aad(intent_id, &encapsulation_key, &provider_key);
+        let (nonce, wrapped) = seal(&wrapping_key, &aad, self.content_key.as_slice())?;
+        Ok([nonce.as_ref(), wrapped.as_ref()].concat().into())
+    }
+}
+
+/// Ephemeral key pair a provider generates to receive the content key of one request
+#[derive(Clone, Debug)]
+pub struct KeyExchangeKeypair {
+    secret: SecretKey,
+}
+
+impl KeyExchangeKeypair {
+    pub fn random() -> Self {
+        Self {
+            secret: SecretKey::random(&mut OsRng),
+        }
+    }
+
+    /// Compressed public key sent to the requester
+    pub fn public_key(&self) -> Bytes {
+        public_key_bytes(&self.secret.public_key())
+    }
+
+    /// Unwrap the content key of the request `intent_id` delivered by the requester owning
+    /// `encapsulation_key`
+    pub fn unwrap_key(
+        &self,
+        intent_id: B256,
+        encapsulation_key: &[u8],
+        wrapped_key: &[u8],
+    ) -> Result<B256> {
+        const WRAPPED_KEY_LENGTH: usize = NONCE_LENGTH + 32 + TAG_LENGTH;
+        if wrapped_key.len() != WRAPPED_KEY_LENGTH {
+            return Err(PrimitivesError::EncryptionError(format!(
+                "wrapped key must be {WRAPPED_KEY_LENGTH} bytes, got {}",
+                wrapped_key.len()
+            )));
+        }
+        let requester = parse_public_key(encapsulation_key)?;
+        let encapsulation_key = public_key_bytes(&requester);
+        let provider_key = self.public_key();
+
+        let wrapping_key =
+            wrapping_key(&self.secret, &requester, &encapsulation_key, &provider_key)?;
+        let aad = key_delivery_aad(intent_id, &encapsulation_key, &provider_key);
+        let (nonce, wrapped) = wrapped_key.split_at(NONCE_LENGTH);
+        let content_key = open(&wrapping_key, &aad, nonce, wrapped)?;
+        Ok(B256::from_slice(&content_key))
+    }
+}
+
+/// Provider's request for the content key of an encrypted request it won
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyRequest {
+    /// ephemeral public key the content key gets wrapped for
+    pub provider_key: Bytes,
+    /// signature of the bidding address over the request id and `provider_key`
+    pub signature: PrimitiveSignature,
+}
+
+impl KeyRequest {
+    pub async fn sign<S: Signer>(intent_id: B256, provider_key: Bytes, signer: &S) -> Result<Self> {
+        let digest = key_exchange_digest(KEY_REQUEST_DOMAIN, intent_id, &provider_key);
+        let signature = signer
+            .sign_hash(&digest)
+            .await
+            .map_err(|e| PrimitivesError::SignatureError(e.to_string()))?;
+        Ok(Self {
+            provider_key,
+            signature,
+        })
+    }
+
+    /// Address that signed the key request of the given request
+    pub fn signer(&self, intent_id: B256) -> Result<Address> {
+        let digest = key_exchange_digest(KEY_REQUEST_DOMAIN, intent_id, &self.provider_key);
+        self.signature
+            .recover_address_from_prehash(&digest)
+            .map_err(|e| PrimitivesError::SignatureError(e.to_string()))
+    }
+}
+
+/// Requester's answer to a `KeyRequest`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyDelivery {
+    /// content key wrapped for the provider key of the answered `KeyRequest`
+    pub wrapped_key: Bytes,
+    /// signature of the requesting address over the request id and `wrapped_key`
+    pub signature: PrimitiveSignature,
+}
+
+impl KeyDelivery {
+    pub async fn sign<S: Signer>(intent_id: B256, wrapped_key: Bytes, signer: &S) -> Result<Self> {
+        let digest = key_exchange_digest(KEY_DELIVERY_DOMAIN, intent_id, &wrapped_key);
+        let signature = signer
+            .sign_hash(&digest)
+            .await
+            .map_err(|e| PrimitivesError::SignatureError(e.to_string()))?;
+        Ok(Self {
+            wrapped_key,
+            signature,
+        })
+    }
+
+    /// Address that signed the key delivery of the given request
+    pub fn signer(&self, intent_id: B256) -> Result<Address> {
+        let digest = key_exchange_digest(KEY_DELIVERY_DOMAIN, intent_id, &self.wrapped_key);
+        self.signature
+            .recover_address_from_prehash(&digest)
+            .map_err(|e| PrimitivesError::SignatureError(e.to_string()))
+    }
+}
+
+/// System params as carried by a submission, in the clear or encrypted
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SystemPayload {
+    Plain(SystemParams),
+    Encrypted(EncryptedSystemParams),
+}
+
+impl SystemPayload {
+    pub fn system_id(&self) -> SystemId {
+        match self {
+            Self::Plain(params) => params.system_id(),
+            Self::Encrypted(encrypted) => encrypted.system_id,
+        }
+    }
+}
+
+fn key_exchange_digest(domain: &[u8], intent_id: B256, payload: &[u8]) -> B256 {
+    keccak256([domain, intent_id.as_slice(), payload].concat())
+}
+
+fn public_key_bytes(public_key: &PublicKey) -> Bytes {
+    Bytes::copy_from_slice(public_key.to_encoded_point(true).as_bytes())
+}
+
+fn parse_public_key(public_key: &[u8]) -> Result<PublicKey> {
+    PublicKey::from_sec1_bytes(public_key)
+        .map_err(|_| PrimitivesError::EncryptionError("not a secp256k1 public key".to_string()))
+}
+
+/// Associated data of encrypted system params, the variable length system id last
+fn system_params_aad(system_id: SystemId, intent_id: B256, encapsulation_key: &[u8]) -> Vec<u8> {
+    [
+        SYSTEM_PARAMS_DOMAIN,
+        intent_id.as_slice(),
+        encapsulation_key,
+        system_id.as_str().as_bytes(),
+    ]
+    .concat()
+}
+
+/// Associated data of a wrapped content key, both public keys compressed
+fn key_delivery_aad(intent_id: B256, encapsulation_key: &[u8], provider_key: &[u8]) -> Vec<u8> {
+    [
+        KEY_DELIVERY_DOMAIN,
+        intent_id.as_slice(),
+        encapsulation_key,
+        provider_key,
+    ]
+    .concat()
+}
+
+/// Key wrapping content keys, derived with HKDF-SHA256 from the ECDH secret of `secret` and
+/// `peer`, salted with both compressed public keys, the requester's first
+fn wrapping_key(
+    secret: &SecretKey,
+    peer: &PublicKey,
+    encapsulation_key: &[u8],
+    provider_key: &[u8],
+) -> Result<[u8; 32]> {
+    let shared = (peer.to_projective() * *secret.to_nonzero_scalar()).to_affine();
+    let salt = [encapsulation_key, provider_key].concat();
+    let mut wrapping_key = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(&salt), shared.x().as_slice())
+        .expand(KEY_WRAPPING_INFO, &mut wrapping_key)
+        .map_err(|e| PrimitivesError::EncryptionError(e.to_string()))?;
+    Ok(wrapping_key)
+}
+
+/// Encrypt and authenticate `plaintext` along with `aad`, returning (nonce, ciphertext || tag)
+fn seal(key: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<(Bytes, Bytes)> {
+    let mut nonce = [0u8; NONCE_LENGTH];
+    OsRng.fill_bytes(&mut nonce);
+    let ciphertext = Aes256Gcm::new_from_slice(key)
+        .map_err(|e| PrimitivesError::EncryptionError(e.to_string()))?
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .map_err(|e| PrimitivesError::EncryptionError(e.to_string()))?;
+    Ok((Bytes::copy_from_slice(&nonce), ciphertext.into()))
+}
+
+/// Authenticate and decrypt a ciphertext produced by `seal`
+fn open(key: &[u8], aad: &[u8], nonce: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+    if nonce.len() != NONCE_LENGTH {
+        return Err(PrimitivesError::EncryptionError(format!(
+            "encryption nonce must be {NONCE_LENGTH} bytes, got {}",
+            nonce.len()
+        )));
+    }
+    Aes256Gcm::new_from_slice(key)
+        .map_err(|e| PrimitivesError::EncryptionError(e.to_string()))?
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| {
+            PrimitivesError::EncryptionError(
+                "authentication failed, wrong key or tampered ciphertext".to_string(),
+            )
+        })
+}
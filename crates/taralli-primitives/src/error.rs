@@ -1,19 +1,36 @@
+use alloy::primitives::B256;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::validation::violation::Violation;
+
 #[derive(Error, Debug)]
 pub enum PrimitivesError {
     #[error("Compression error: {0}")]
     CompressionError(String),
     #[error("Decompression error: {0}")]
     DecompressionError(String),
+    #[error("Decompressed payload exceeds the maximum of {0} bytes")]
+    DecompressionLimitExceeded(usize),
+    #[error("Decompression timed out after {0} seconds")]
+    DecompressionTimeout(u64),
+    #[error("Unknown compression dictionary {0}")]
+    UnknownDictionary(B256),
     #[error("Contract interaction error: {0}")]
     ContractError(String),
     #[error("Configuration error: {0}")]
     ConfigError(String),
+    #[error(
+        "No deployment known for chain {chain_id}, known chains: {}",
+        known.iter().map(u64::to_string).collect::<Vec<_>>().join(", ")
+    )]
+    UnknownChain { chain_id: u64, known: Vec<u64> },
     #[error("Invalid signature: {0}")]
     SignatureError(String),
     #[error("Validation error: {0}")]
     ValidationError(String),
+    #[error("Validation error: {0}")]
+    ValidationViolation(Violation),
     #[error("RPC error: {0}")]
     RpcError(String),
     #[error("Encoding error: {0}")]
@@ -22,10 +39,14 @@ pub enum PrimitivesError {
     CommitmentError(String),
     #[error("Prover Inputs validation error: {0}")]
     ProverInputsError(String),
+    #[error("Encryption error: {0}")]
+    EncryptionError(String),
     #[error("Invalid systems error: {0}")]
     InvalidSystem(String),
     #[error("Intent serialization error: {0}")]
     SerializationError(String),
+    #[error("Unsupported intent envelope version: {0}")]
+    UnsupportedIntentVersion(u32),
     #[error("DB serialization error: {0}")]
     DbSerializeError(String),
     #[error("DB serialization error: {0}")]
@@ -33,3 +54,124 @@ pub enum PrimitivesError {
 }
 
 pub type Result<T> = core::result::Result<T, PrimitivesError>;
+
+/// Machine readable code of an error answered by the server, sent alongside the human readable
+/// message so clients can act on a rejection without parsing the message
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    BodyReadTimeout,
+    PayloadTooLarge,
+    InvalidSubmission,
+    UnknownMarket,
+    UnsupportedIntentVersion,
+    DecompressionLimitExceeded,
+    DecompressionTimeout,
+    DecompressionFailed,
+    UnknownDictionary,
+    DictionaryRegistrationLimited,
+    ValidationTimeout,
+    ValidationFailed,
+    NoProvidersAvailable,
+    InvalidCapabilities,
+    CapabilityRegistryFull,
+    KeyExchangeUnauthorized,
+    KeyNotAvailable,
+    ExtensionUnauthorized,
+    ExtensionNotAvailable,
+    InvalidExtension,
+    BroadcastFailed,
+    AdminUnauthorized,
+    InvalidLogFilter,
+    OfferInputsUnauthorized,
+    OfferInputsNotAvailable,
+    OfferInputsMismatch,
+    OfferInputsStoreFull,
+    RequestMetadataUnauthorized,
+    IdempotencyKeyInUse,
+    IdempotencyKeyReused,
+    Internal,
+}
+
+impl ErrorCode {
+    pub const ALL: [ErrorCode; 31] = [
+        ErrorCode::BodyReadTimeout,
+        ErrorCode::PayloadTooLarge,
+        ErrorCode::InvalidSubmission,
+        ErrorCode::UnknownMarket,
+        ErrorCode::UnsupportedIntentVersion,
+        ErrorCode::DecompressionLimitExceeded,
+        ErrorCode::DecompressionTimeout,
+        ErrorCode::DecompressionFailed,
+        ErrorCode::UnknownDictionary,
+        ErrorCode::DictionaryRegistrationLimited,
+        ErrorCode::ValidationTimeout,
+        ErrorCode::ValidationFailed,
+        ErrorCode::NoProvidersAvailable,
+        ErrorCode::InvalidCapabilities,
+        ErrorCode::CapabilityRegistryFull,
+        ErrorCode::KeyExchangeUnauthorized,
+        ErrorCode::KeyNotAvailable,
+        ErrorCode::ExtensionUnauthorized,
+        ErrorCode::ExtensionNotAvailable,
+        ErrorCode::InvalidExtension,
+        ErrorCode::BroadcastFailed,
+        ErrorCode::AdminUnauthorized,
+        ErrorCode::InvalidLogFilter,
+        ErrorCode::OfferInputsUnauthorized,
+        ErrorCode::OfferInputsNotAvailable,
+        ErrorCode::OfferInputsMismatch,
+        ErrorCode::OfferInputsStoreFull,
+        ErrorCode::RequestMetadataUnauthorized,
+        ErrorCode::IdempotencyKeyInUse,
+        ErrorCode::IdempotencyKeyReused,
+        ErrorCode::Internal,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::BodyReadTimeout => "body_read_timeout",
+            ErrorCode::PayloadTooLarge => "payload_too_large",
+            ErrorCode::InvalidSubmission => "invalid_submission",
+            ErrorCode::UnknownMarket => "unknown_market",
+            ErrorCode::UnsupportedIntentVersion => "unsupported_intent_version",
+            ErrorCode::DecompressionLimitExceeded => "decompression_limit_exceeded",
+            ErrorCode::DecompressionTimeout => "decompression_timeout",
+            ErrorCode::DecompressionFailed => "decompression_failed",
+            ErrorCode::UnknownDictionary => "unknown_dictionary",
+            ErrorCode::DictionaryRegistrationLimited => "dictionary_registration_limited",
+            ErrorCode::ValidationTimeout => "validation_timeout",
+            ErrorCode::ValidationFailed => "validation_failed",
+            ErrorCode::NoProvidersAvailable => "no_providers_available",
+            ErrorCode::InvalidCapabilities => "invalid_capabilities",
+            ErrorCode::CapabilityRegistryFull => "capability_registry_full",
+            ErrorCode::KeyExchangeUnauthorized => "key_exchange_unauthorized",
+            ErrorCode::KeyNotAvailable => "key_not_available",
+            ErrorCode::ExtensionUnauthorized => "extension_unauthorized",
+            ErrorCode::ExtensionNotAvailable => "extension_not_available",
+            ErrorCode::InvalidExtension => "invalid_extension",
+            ErrorCode::BroadcastFailed => "broadcast_failed",
+            ErrorCode::AdminUnauthorized => "admin_unauthorized",
+            ErrorCode::InvalidLogFilter => "invalid_log_filter",
+            ErrorCode::OfferInputsUnauthorized => "offer_inputs_unauthorized",
+            ErrorCode::OfferInputsNotAvailable => "offer_inputs_not_available",
+            ErrorCode::OfferInputsMismatch => "offer_inputs_mismatch",
+            ErrorCode::OfferInputsStoreFull => "offer_inputs_store_full",
+            ErrorCode::RequestMetadataUnauthorized => "request_metadata_unauthorized",
+            ErrorCode::IdempotencyKeyInUse => "idempotency_key_in_use",
+            ErrorCode::IdempotencyKeyReused => "idempotency_key_reused",
+            ErrorCode::Internal => "internal",
+        }
+    }
+
+    /// Code named `code`, `None` for codes unknown to this version, e.g. added by a newer server
+    pub fn parse(code: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|known| known.as_str() == code)
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
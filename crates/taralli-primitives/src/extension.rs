@@ -0,0 +1,153 @@
+//! Off-chain negotiation of a later resolution deadline between the requester of a request and
+//! the provider that won it, e.g. when the inputs turn out to take longer to prove than the
+//! request's proving time.
+//!
+//! The provider posts an `ExtensionRequest` through the server, signed by the bidding address.
+//! The requester answers with an `ExtensionResponse` signed by the requesting address, approving
+//! or declining the proposed deadline. An approval signs the request id and the new deadline, so
+//! a market supporting extensions can verify it on-chain. The markets deployed today don't, an
+//! approval then only binds the requester's client to keep waiting for the resolution.
+
+use alloy::primitives::{keccak256, Address, PrimitiveSignature, B256};
+use alloy::signers::Signer;
+use alloy::sol_types::SolValue;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{PrimitivesError, Result};
+
+/// domain separating extension requests from other signatures of the provider address
+const EXTENSION_REQUEST_DOMAIN: &[u8] = b"taralli extension request";
+/// domain separating extension approvals from other signatures of the requester address
+const EXTENSION_APPROVAL_DOMAIN: &[u8] = b"taralli extension approval";
+/// domain separating declined extensions from approved ones
+const EXTENSION_DECLINE_DOMAIN: &[u8] = b"taralli extension decline";
+
+/// Longest reason an extension request carries, in bytes
+pub const MAX_EXTENSION_REASON_LENGTH: usize = 1024;
+
+/// Winning provider's request for a later resolution deadline
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtensionRequest {
+    /// proposed resolution deadline, as a unix timestamp
+    pub new_deadline: u64,
+    /// why the proof needs more time, shown to the requester
+    pub reason: String,
+    /// signature of the bidding address over the request id, `new_deadline` and `reason`
+    pub signature: PrimitiveSignature,
+}
+
+impl ExtensionRequest {
+    pub async fn sign<S: Signer>(
+        intent_id: B256,
+        new_deadline: u64,
+        reason: String,
+        signer: &S,
+    ) -> Result<Self> {
+        let digest = request_digest(intent_id, new_deadline, &reason);
+        let signature = signer
+            .sign_hash(&digest)
+            .await
+            .map_err(|e| PrimitivesError::SignatureError(e.to_string()))?;
+        Ok(Self {
+            new_deadline,
+            reason,
+            signature,
+        })
+    }
+
+    /// Address that signed the extension request of the given request
+    pub fn signer(&self, intent_id: B256) -> Result<Address> {
+        let digest = request_digest(intent_id, self.new_deadline, &self.reason);
+        self.signature
+            .recover_address_from_prehash(&digest)
+            .map_err(|e| PrimitivesError::SignatureError(e.to_string()))
+    }
+
+    /// Check the request proposes a deadline later than `current_deadline` with a reason of
+    /// reasonable length
+    pub fn validate(&self, current_deadline: u64) -> Result<()> {
+        if self.new_deadline <= current_deadline {
+            return Err(PrimitivesError::ValidationError(format!(
+                "proposed deadline {} isn't after the current deadline {current_deadline}",
+                self.new_deadline
+            )));
+        }
+        if self.reason.len() > MAX_EXTENSION_REASON_LENGTH {
+            return Err(PrimitivesError::ValidationError(format!(
+                "reason exceeds the maximum of {MAX_EXTENSION_REASON_LENGTH} bytes"
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Requester's answer to an `ExtensionRequest`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtensionResponse {
+    /// deadline of the answered request
+    pub new_deadline: u64,
+    pub approved: bool,
+    /// signature of the requesting address over the request id and `new_deadline`, an approval
+    /// being what a market supporting extensions verifies
+    pub signature: PrimitiveSignature,
+}
+
+impl ExtensionResponse {
+    /// Approve extending the resolution deadline of the given request to `new_deadline`
+    pub async fn approve<S: Signer>(
+        intent_id: B256,
+        new_deadline: u64,
+        signer: &S,
+    ) -> Result<Self> {
+        Self::sign(intent_id, new_deadline, true, signer).await
+    }
+
+    /// Decline extending the resolution deadline of the given request to `new_deadline`
+    pub async fn decline<S: Signer>(
+        intent_id: B256,
+        new_deadline: u64,
+        signer: &S,
+    ) -> Result<Self> {
+        Self::sign(intent_id, new_deadline, false, signer).await
+    }
+
+    /// Hash signed by the requesting address
+    pub fn digest(intent_id: B256, new_deadline: u64, approved: bool) -> B256 {
+        let domain = if approved {
+            EXTENSION_APPROVAL_DOMAIN
+        } else {
+            EXTENSION_DECLINE_DOMAIN
+        };
+        keccak256([domain, &(intent_id, new_deadline).abi_encode()].concat())
+    }
+
+    /// Address that signed the answer for the given request
+    pub fn signer(&self, intent_id: B256) -> Result<Address> {
+        let digest = Self::digest(intent_id, self.new_deadline, self.approved);
+        self.signature
+            .recover_address_from_prehash(&digest)
+            .map_err(|e| PrimitivesError::SignatureError(e.to_string()))
+    }
+
+    async fn sign<S: Signer>(
+        intent_id: B256,
+        new_deadline: u64,
+        approved: bool,
+        signer: &S,
+    ) -> Result<Self> {
+        let signature = signer
+            .sign_hash(&Self::digest(intent_id, new_deadline, approved))
+            .await
+            .map_err(|e| PrimitivesError::SignatureError(e.to_string()))?;
+        Ok(Self {
+            new_deadline,
+            approved,
+            signature,
+        })
+    }
+}
+
+fn request_digest(intent_id: B256, new_deadline: u64, reason: &str) -> B256 {
+    let encoded = (intent_id, new_deadline, reason.to_string()).abi_encode();
+    keccak256([EXTENSION_REQUEST_DOMAIN, &encoded].concat())
+}
@@ -0,0 +1,123 @@
+//! Reward curve of `ComputeRequest` auctions, mirroring `UniversalBombetta.calculateReward()`
+
+use alloy::primitives::{U256, U512};
+
+use crate::abi::universal_bombetta::UniversalBombetta::ProofRequest;
+use crate::{PrimitivesError, Result};
+
+/// fixed point precision of the contract's reward increase factor
+const PRECISION: u64 = 1_000_000_000_000_000_000;
+
+/// Reward paid to the provider whose bid lands at a given timestamp
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RewardCurve {
+    /// `minRewardAmount == maxRewardAmount`, the first bid gets exactly this amount, there is no
+    /// price discovery so providers have no reason to wait before bidding
+    Fixed { amount: U256, start: u64 },
+    /// rises linearly from `min` at `start` to `max` at `end`
+    Linear {
+        min: U256,
+        max: U256,
+        start: u64,
+        end: u64,
+    },
+}
+
+impl TryFrom<&ProofRequest> for RewardCurve {
+    type Error = PrimitivesError;
+
+    /// Fails on requests whose rewards or auction timestamps are out of order, which the contract
+    /// can't settle
+    fn try_from(proof_request: &ProofRequest) -> Result<Self> {
+        if proof_request.minRewardAmount > proof_request.maxRewardAmount {
+            return Err(PrimitivesError::ValidationError(format!(
+                "min reward amount {} exceeds max reward amount {}",
+                proof_request.minRewardAmount, proof_request.maxRewardAmount
+            )));
+        }
+        if proof_request.endAuctionTimestamp < proof_request.startAuctionTimestamp {
+            return Err(PrimitivesError::ValidationError(format!(
+                "end auction timestamp {} is before start auction timestamp {}",
+                proof_request.endAuctionTimestamp, proof_request.startAuctionTimestamp
+            )));
+        }
+        Ok(
+            if proof_request.minRewardAmount == proof_request.maxRewardAmount {
+                Self::Fixed {
+                    amount: proof_request.maxRewardAmount,
+                    start: proof_request.startAuctionTimestamp,
+                }
+            } else {
+                Self::Linear {
+                    min: proof_request.minRewardAmount,
+                    max: proof_request.maxRewardAmount,
+                    start: proof_request.startAuctionTimestamp,
+                    end: proof_request.endAuctionTimestamp,
+                }
+            },
+        )
+    }
+}
+
+impl RewardCurve {
+    pub fn is_fixed_price(&self) -> bool {
+        matches!(self, Self::Fixed { .. })
+    }
+
+    /// Reward of a bid landing at `timestamp`, clamped to the auction window
+    pub fn reward_at(&self, timestamp: u64) -> U256 {
+        match *self {
+            Self::Fixed { amount, .. } => amount,
+            Self::Linear {
+                min,
+                max,
+                start,
+                end,
+            } => {
+                if timestamp <= start {
+                    return min;
+                }
+                if timestamp >= end {
+                    return max;
+                }
+                // start < timestamp < end, and min <= max unless the curve was built by hand
+                let elapsed = U256::from(timestamp - start);
+                let duration = U256::from(end - start);
+                let increase_factor = elapsed * U256::from(PRECISION) / duration;
+                // widened so large reward ranges can't overflow, the result is at most the range
+                let increase = U512::from(increase_factor) * U512::from(max.saturating_sub(min))
+                    / U512::from(PRECISION);
+                min.saturating_add(U256::from(increase))
+            }
+        }
+    }
+
+    /// Earliest timestamp at which the reward reaches `target_amount`, `None` if it never does
+    pub fn timestamp_for(&self, target_amount: U256) -> Option<u64> {
+        match *self {
+            // a fixed reward is available as soon as the auction opens
+            Self::Fixed { amount, start } => (target_amount <= amount).then_some(start),
+            Self::Linear {
+                min,
+                max,
+                start,
+                end,
+            } => {
+                if target_amount <= min {
+                    return Some(start);
+                }
+                // the contract rejects bids on auctions without a window
+                if target_amount > max || end <= start {
+                    return None;
+                }
+                let duration = U512::from(end - start);
+                let range = U512::from(max - min);
+                // round up so the reward at the returned timestamp is at least the target
+                let elapsed = (duration * U512::from(target_amount - min)).div_ceil(range);
+                u64::try_from(elapsed)
+                    .ok()
+                    .and_then(|elapsed| start.checked_add(elapsed))
+            }
+        }
+    }
+}
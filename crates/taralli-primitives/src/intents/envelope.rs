@@ -0,0 +1,318 @@
+//! Versioned envelope wrapping intents exchanged between clients and the protocol server.
+//!
+//! Submissions carry the partial intent as `{"v": 1, "kind": "request", "payload": {...}}` and
+//! broadcasts carry the bincode encoding of the same envelope. Payloads sent before the envelope
+//! existed are treated as version 0, so the server keeps accepting them from older clients.
+//!
+//! From version 2 on, broadcasts carry the `BroadcastMeta` the server stamped the intent with
+//! between the kind and the payload. Submissions are unchanged, the metadata is only assigned by
+//! the server.
+//!
+//! From version 3 on, broadcasts may carry offers as well as requests, told apart by the
+//! envelope's kind. Subscribers pick the systems they want the intents of for each kind through a
+//! `SubscriptionMask`, offers only being sent to subscribers advertising version 3. Requests are
+//! laid out as in version 2.
+//!
+//! Submissions may also carry the submitter's `IntentMetadata` next to the envelope's fields, see
+//! `SubmissionEnvelope`. It isn't part of the signed intent, the server keeps it for the submitter
+//! and never broadcasts it.
+
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{PrimitivesError, Result};
+use crate::systems::SystemIdMask;
+
+/// Envelope version produced by this crate
+pub const INTENT_ENVELOPE_VERSION: u32 = 3;
+/// First envelope version whose broadcasts carry a `BroadcastMeta`
+pub const BROADCAST_META_VERSION: u32 = 2;
+/// First envelope version whose broadcasts may carry offers
+pub const OFFER_BROADCAST_VERSION: u32 = 3;
+/// Version of the bare payloads sent before the envelope was introduced
+pub const LEGACY_INTENT_VERSION: u32 = 0;
+/// Header through which clients advertise the highest envelope version they support
+pub const INTENT_VERSION_HEADER: &str = "x-taralli-intent-version";
+/// Header carrying the key of one submission, sent unchanged on every retry so the server can
+/// answer a retried submission that already went through with its original response
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+/// Server-sent event carrying a broadcast intent, as the base64 encoding of its websocket message
+pub const SSE_INTENT_EVENT: &str = "intent";
+/// Server-sent event carrying the JSON of a control frame
+pub const SSE_CONTROL_EVENT: &str = "control";
+
+/// Kind of intent carried by an envelope
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IntentKind {
+    Request,
+    Offer,
+}
+
+impl TryFrom<&str> for IntentKind {
+    type Error = PrimitivesError;
+
+    /// Kind matching an intent's `type_string`
+    fn try_from(type_string: &str) -> Result<Self> {
+        match type_string {
+            "request" => Ok(IntentKind::Request),
+            "offer" => Ok(IntentKind::Offer),
+            other => Err(PrimitivesError::SerializationError(format!(
+                "unknown intent type: {other}"
+            ))),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntentEnvelope<T> {
+    pub v: u32,
+    pub kind: IntentKind,
+    pub payload: T,
+}
+
+impl<T> IntentEnvelope<T> {
+    /// Wrap the payload within an envelope of the current version
+    pub fn new(kind: IntentKind, payload: T) -> Self {
+        Self {
+            v: INTENT_ENVELOPE_VERSION,
+            kind,
+            payload,
+        }
+    }
+
+    /// Envelope of the same version and kind around the mapped payload
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> IntentEnvelope<U> {
+        IntentEnvelope {
+            v: self.v,
+            kind: self.kind,
+            payload: f(self.payload),
+        }
+    }
+}
+
+impl<T: DeserializeOwned> IntentEnvelope<T> {
+    /// Deserialize an enveloped JSON payload of the given kind. JSON without an envelope is read
+    /// as a bare payload of `LEGACY_INTENT_VERSION`.
+    pub fn from_json_slice(bytes: &[u8], kind: IntentKind) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_slice(bytes)
+            .map_err(|e| PrimitivesError::SerializationError(e.to_string()))?;
+        Self::from_json_value(value, kind)
+    }
+
+    fn from_json_value(value: serde_json::Value, kind: IntentKind) -> Result<Self> {
+        if !is_enveloped(&value) {
+            let payload = serde_json::from_value(value)
+                .map_err(|e| PrimitivesError::SerializationError(e.to_string()))?;
+            return Ok(Self {
+                v: LEGACY_INTENT_VERSION,
+                kind,
+                payload,
+            });
+        }
+
+        let envelope: IntentEnvelope<serde_json::Value> = serde_json::from_value(value)
+            .map_err(|e| PrimitivesError::SerializationError(e.to_string()))?;
+        if !is_supported_version(envelope.v) {
+            return Err(PrimitivesError::UnsupportedIntentVersion(envelope.v));
+        }
+        if envelope.kind != kind {
+            return Err(PrimitivesError::SerializationError(format!(
+                "expected a {kind:?} envelope, received a {:?} envelope",
+                envelope.kind
+            )));
+        }
+        let payload = serde_json::from_value(envelope.payload)
+            .map_err(|e| PrimitivesError::SerializationError(e.to_string()))?;
+        Ok(Self {
+            v: envelope.v,
+            kind,
+            payload,
+        })
+    }
+}
+
+fn is_enveloped(value: &serde_json::Value) -> bool {
+    value
+        .as_object()
+        .is_some_and(|object| object.contains_key("v") && object.contains_key("payload"))
+}
+
+/// Opaque tags a submitter attaches to its intent, e.g. its own job id, to map the intent back to
+/// its bookkeeping
+pub type IntentMetadata = BTreeMap<String, String>;
+
+/// JSON layout of submissions: the intent's envelope along with the submitter's metadata, e.g.
+/// `{"v": 3, "kind": "request", "payload": {...}, "metadata": {"job": "42"}}`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubmissionEnvelope<T> {
+    #[serde(flatten)]
+    pub envelope: IntentEnvelope<T>,
+    #[serde(default, skip_serializing_if = "IntentMetadata::is_empty")]
+    pub metadata: IntentMetadata,
+}
+
+impl<T> SubmissionEnvelope<T> {
+    /// Wrap the payload within an envelope of the current version, along with `metadata`
+    pub fn new(kind: IntentKind, payload: T, metadata: IntentMetadata) -> Self {
+        Self {
+            envelope: IntentEnvelope::new(kind, payload),
+            metadata,
+        }
+    }
+}
+
+impl<T: DeserializeOwned> SubmissionEnvelope<T> {
+    /// Deserialize a submission of the given kind as `IntentEnvelope::from_json_slice` does, bare
+    /// payloads carrying no metadata
+    pub fn from_json_slice(bytes: &[u8], kind: IntentKind) -> Result<Self> {
+        let mut value: serde_json::Value = serde_json::from_slice(bytes)
+            .map_err(|e| PrimitivesError::SerializationError(e.to_string()))?;
+        let metadata = is_enveloped(&value)
+            .then(|| {
+                value
+                    .as_object_mut()
+                    .and_then(|object| object.remove("metadata"))
+            })
+            .flatten()
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(|e| PrimitivesError::SerializationError(format!("invalid metadata: {e}")))?
+            .unwrap_or_default();
+        Ok(Self {
+            envelope: IntentEnvelope::from_json_value(value, kind)?,
+            metadata,
+        })
+    }
+}
+
+/// Systems a subscriber wants the intents of, for each kind of intent.
+///
+/// Sent as the `subscription_mask` query parameter of the subscribe routes, encoded as a u16
+/// whose low byte is the mask of the requests and high byte the mask of the offers. Subscribers
+/// that only send `subscribed_to` get the requests of its systems and no offers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubscriptionMask {
+    pub requests: SystemIdMask,
+    pub offers: SystemIdMask,
+}
+
+impl SubscriptionMask {
+    /// Mask of the requests of the given systems, without offers
+    #[must_use]
+    pub fn requests_only(requests: SystemIdMask) -> Self {
+        Self {
+            requests,
+            offers: 0,
+        }
+    }
+
+    /// Systems subscribed to for intents of the given kind
+    #[must_use]
+    pub fn systems(&self, kind: IntentKind) -> SystemIdMask {
+        match kind {
+            IntentKind::Request => self.requests,
+            IntentKind::Offer => self.offers,
+        }
+    }
+
+    #[must_use]
+    pub fn encode(&self) -> u16 {
+        u16::from_le_bytes([self.requests, self.offers])
+    }
+
+    #[must_use]
+    pub fn decode(encoded: u16) -> Self {
+        let [requests, offers] = encoded.to_le_bytes();
+        Self { requests, offers }
+    }
+}
+
+/// Stamped by the server on each broadcast intent
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BroadcastMeta {
+    /// increases by one with every intent the server broadcasts, a gap means a missed intent
+    pub sequence: u64,
+    /// unix time in milliseconds at which the server received the intent's submission
+    pub received_at_ms: u64,
+}
+
+/// Current unix time in milliseconds, the clock `BroadcastMeta::received_at_ms` is read from
+pub fn unix_time_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system clock before unix epoch")
+        .as_millis() as u64
+}
+
+/// Bincode layout of broadcasts from `BROADCAST_META_VERSION` on
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BroadcastEnvelope<T> {
+    pub v: u32,
+    pub kind: IntentKind,
+    pub meta: BroadcastMeta,
+    pub payload: T,
+}
+
+impl<T> BroadcastEnvelope<T> {
+    /// Wrap the payload within a broadcast envelope of the current version
+    pub fn new(kind: IntentKind, meta: BroadcastMeta, payload: T) -> Self {
+        Self {
+            v: INTENT_ENVELOPE_VERSION,
+            kind,
+            meta,
+            payload,
+        }
+    }
+}
+
+/// Decode a bincode encoded broadcast envelope, along with its metadata when its version carries
+/// one. Bincode encodes the version first as a little endian u32, which tells the layouts apart.
+/// Payloads borrowing from `bytes` are decoded without copying the borrowed fields.
+pub fn decode_broadcast_envelope<'a, T: Deserialize<'a>>(
+    bytes: &'a [u8],
+) -> Result<(IntentEnvelope<T>, Option<BroadcastMeta>)> {
+    let version = bytes
+        .get(..4)
+        .map(|version| u32::from_le_bytes(version.try_into().expect("slice of 4 bytes")));
+    if version.is_some_and(|version| version >= BROADCAST_META_VERSION) {
+        let envelope: BroadcastEnvelope<T> = bincode::deserialize(bytes)
+            .map_err(|e| PrimitivesError::SerializationError(e.to_string()))?;
+        return Ok((
+            IntentEnvelope {
+                v: envelope.v,
+                kind: envelope.kind,
+                payload: envelope.payload,
+            },
+            Some(envelope.meta),
+        ));
+    }
+    let envelope = bincode::deserialize(bytes)
+        .map_err(|e| PrimitivesError::SerializationError(e.to_string()))?;
+    Ok((envelope, None))
+}
+
+/// Kind of the intent a bincode encoded broadcast carries, read from its envelope without
+/// decoding the payload. Broadcasts predating the envelope only ever carried requests.
+pub fn broadcast_kind(bytes: &[u8]) -> Result<IntentKind> {
+    let version = bytes
+        .get(..4)
+        .map(|version| u32::from_le_bytes(version.try_into().expect("slice of 4 bytes")));
+    if !version
+        .is_some_and(|version| version != LEGACY_INTENT_VERSION && is_supported_version(version))
+    {
+        return Ok(IntentKind::Request);
+    }
+    // every enveloped layout starts with the version and the kind, bincode ignores what follows
+    let header: IntentEnvelope<()> = bincode::deserialize(bytes)
+        .map_err(|e| PrimitivesError::SerializationError(e.to_string()))?;
+    Ok(header.kind)
+}
+
+/// Whether this crate can read envelopes of the given version
+pub fn is_supported_version(version: u32) -> bool {
+    (LEGACY_INTENT_VERSION..=INTENT_ENVELOPE_VERSION).contains(&version)
+}
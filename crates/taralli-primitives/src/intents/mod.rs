@@ -1,9 +1,13 @@
 //! This module contains the `ComputeIntent` Implementations used by the protocol.
 
+use crate::commitment::PartialCommitmentCheck;
 use crate::systems::{System, SystemId};
-use alloy::primitives::{Address, FixedBytes, PrimitiveSignature, U256};
+use alloy::dyn_abi::TypedData;
+use alloy::primitives::{Address, Bytes, FixedBytes, PrimitiveSignature, U256};
 use serde::{Deserialize, Serialize};
 
+pub mod auction;
+pub mod envelope;
 pub mod offer;
 pub mod request;
 
@@ -15,6 +19,21 @@ pub trait CommonProofCommitment: Serialize + for<'de> Deserialize<'de> + Send +
     fn end_auction_timestamp(&self) -> u64;
     fn proving_time(&self) -> u32;
     fn inputs_commitment(&self) -> FixedBytes<32>;
+    /// abi encoded verifier details, possibly followed by extensions
+    fn extra_data(&self) -> &Bytes;
+
+    /// Deadline of the permit2 signature over the intent. The markets permit transfers until
+    /// the auction ends, so the signature can't be used to bid past `end_auction_timestamp` and
+    /// the witness carries no separate deadline.
+    fn signature_deadline(&self) -> u64 {
+        self.end_auction_timestamp()
+    }
+
+    /// Partial commitment result check the market runs when the intent is resolved, only
+    /// requests declaring one in their verifier details
+    fn partial_commitment_check(&self) -> Option<PartialCommitmentCheck> {
+        None
+    }
 }
 
 /// Trait representing common behavior for compute intents
@@ -33,6 +52,8 @@ pub trait ComputeIntent: Serialize + for<'de> Deserialize<'de> + Send + Sync {
     fn type_string(&self) -> String;
     // compute intent id
     fn compute_id(&self) -> FixedBytes<32>;
-    // compute permit2 digest for intent signing
-    fn compute_permit2_digest(&self) -> FixedBytes<32>;
+    // compute permit2 digest for intent signing on the given chain
+    fn compute_permit2_digest(&self, chain_id: u64) -> FixedBytes<32>;
+    // EIP-712 typed data of the permit2 message for external signers on the given chain
+    fn permit2_typed_data(&self, chain_id: u64) -> TypedData;
 }
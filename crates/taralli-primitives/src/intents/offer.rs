@@ -1,20 +1,14 @@
 use alloy::{
-    dyn_abi::DynSolValue,
-    primitives::{keccak256, Address, FixedBytes, PrimitiveSignature, B256, U256},
+    dyn_abi::{eip712::Resolver, DynSolValue, TypedData},
+    primitives::{keccak256, Address, Bytes, FixedBytes, PrimitiveSignature, B256, U256},
     sol_types::SolValue,
 };
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    abi::{
-        permit2::ISignatureTransfer::TokenPermissions,
-        universal_porchetta::UniversalPorchetta::ProofOffer,
-    },
+    abi::universal_porchetta::UniversalPorchetta::ProofOffer,
     systems::{System, SystemId},
-    utils::{
-        hash_typed_data, PERMIT2_DOMAIN_SEPARATOR, PERMIT_TRANSFER_FROM_WITNESS_TYPEHASH_STUB,
-        TOKEN_PERMISSIONS_TYPE_HASH,
-    },
+    utils::{permit2_domain, PERMIT_TRANSFER_FROM_WITNESS_TYPEHASH_STUB},
 };
 use lazy_static::lazy_static;
 
@@ -22,7 +16,7 @@ use super::{CommonProofCommitment, ComputeIntent};
 
 /// porchetta signature constants
 pub const FULL_PROOF_OFFER_WITNESS_TYPE_STRING_STUB: &str =
-    "ProofOffer witness)TokenPermissions(address token,uint256 amount)ProofOffer(address signer,address market,uint256 nonce,address rewardToken,uint256 rewardAmount,address stakeToken,uint256 stakeAmount,uint64 startAuctionTimestamp,uint64 endAuctionTimestamp,uint32 provingTime,bytes32 inputsCommitment,bytes extraData)";
+    "ProofOffer witness)ProofOffer(address signer,address market,uint256 nonce,address rewardToken,uint256 rewardAmount,address stakeToken,uint256 stakeAmount,uint64 startAuctionTimestamp,uint64 endAuctionTimestamp,uint32 provingTime,bytes32 inputsCommitment,bytes extraData)TokenPermissions(address token,uint256 amount)";
 pub const PROOF_OFFER_WITNESS_TYPE_STRING: &str =
     "ProofOffer(address signer,address market,uint256 nonce,address rewardToken,uint256 rewardAmount,address stakeToken,uint256 stakeAmount,uint64 startAuctionTimestamp,uint64 endAuctionTimestamp,uint32 provingTime,bytes32 inputsCommitment,bytes extraData)";
 
@@ -75,6 +69,10 @@ impl CommonProofCommitment for ProofOffer {
     fn inputs_commitment(&self) -> FixedBytes<32> {
         self.inputsCommitment
     }
+
+    fn extra_data(&self) -> &Bytes {
+        &self.extraData
+    }
 }
 
 /// Generic compute offer implementation
@@ -106,8 +104,12 @@ impl<S: System> ComputeIntent for ComputeOffer<S> {
         compute_offer_id(&self.proof_offer, &self.signature)
     }
 
-    fn compute_permit2_digest(&self) -> FixedBytes<32> {
-        compute_offer_permit2_digest(&self.proof_offer)
+    fn compute_permit2_digest(&self, chain_id: u64) -> FixedBytes<32> {
+        compute_offer_permit2_digest(&self.proof_offer, chain_id)
+    }
+
+    fn permit2_typed_data(&self, chain_id: u64) -> TypedData {
+        offer_permit2_typed_data(&self.proof_offer, chain_id)
     }
 }
 
@@ -141,7 +143,8 @@ pub fn compute_offer_id(
     keccak256(&preimage)
 }
 
-pub fn compute_offer_permit2_digest(proof_commitment: &ProofOffer) -> FixedBytes<32> {
+/// Hash of the `ProofOffer` witness the permit2 signature commits to
+pub fn compute_offer_witness(proof_commitment: &ProofOffer) -> B256 {
     // compute witness
     let extra_data_hash = keccak256(proof_commitment.extraData.clone());
     let offer_witness_values = DynSolValue::Tuple(vec![
@@ -152,7 +155,7 @@ pub fn compute_offer_permit2_digest(proof_commitment: &ProofOffer) -> FixedBytes
         DynSolValue::Address(proof_commitment.rewardToken),
         DynSolValue::Uint(proof_commitment.rewardAmount, 256),
         DynSolValue::Address(proof_commitment.stakeToken),
-        DynSolValue::Uint(U256::from(proof_commitment.stakeAmount), 128),
+        DynSolValue::Uint(proof_commitment.stakeAmount, 256),
         DynSolValue::Uint(U256::from(proof_commitment.startAuctionTimestamp), 64),
         DynSolValue::Uint(U256::from(proof_commitment.endAuctionTimestamp), 64),
         DynSolValue::Uint(U256::from(proof_commitment.provingTime), 32),
@@ -161,38 +164,56 @@ pub fn compute_offer_permit2_digest(proof_commitment: &ProofOffer) -> FixedBytes
     ]);
 
     // hash encoded witness
-    let witness = keccak256(offer_witness_values.abi_encode());
-
-    // encode token permissions data
-    let token_permissions = TokenPermissions {
-        token: proof_commitment.stakeToken,
-        amount: proof_commitment.stakeAmount,
-    };
-
-    let token_permissions_bytes = token_permissions.abi_encode();
-    let token_permissions_hash_preimage = [
-        TOKEN_PERMISSIONS_TYPE_HASH.abi_encode(),
-        token_permissions_bytes,
-    ]
-    .concat();
-
-    // hash token permissions encoding
-    let token_permissions_hash = keccak256(&token_permissions_hash_preimage);
-
-    // encode data hash preimage
-    let data_hash_preimage = DynSolValue::Tuple(vec![
-        DynSolValue::FixedBytes(*OFFER_PERMIT_TYPE_HASH, 32),
-        DynSolValue::FixedBytes(token_permissions_hash, 32),
-        DynSolValue::Address(proof_commitment.market),
-        DynSolValue::Uint(proof_commitment.nonce, 256),
-        DynSolValue::Uint(U256::from(proof_commitment.endAuctionTimestamp), 64),
-        DynSolValue::FixedBytes(witness, 32),
-    ])
-    .abi_encode();
+    keccak256(offer_witness_values.abi_encode())
+}
 
-    // hash data hash encoding
-    let data_hash = keccak256(&data_hash_preimage);
+/// Permit2 digest the market verifies, the signing hash of `offer_permit2_typed_data`
+pub fn compute_offer_permit2_digest(
+    proof_commitment: &ProofOffer,
+    chain_id: u64,
+) -> FixedBytes<32> {
+    offer_permit2_typed_data(proof_commitment, chain_id)
+        .eip712_signing_hash()
+        .expect("permit2 offer typed data is valid")
+}
 
-    // return the signable eip712 permit2 hash
-    hash_typed_data(PERMIT2_DOMAIN_SEPARATOR, data_hash)
+/// EIP-712 typed data of the permit2 `PermitWitnessTransferFrom` a provider signs over its stake,
+/// serializing to the `eth_signTypedData_v4` JSON external signers expect. Its signing hash is
+/// the digest `UniversalPorchetta` verifies.
+pub fn offer_permit2_typed_data(proof_offer: &ProofOffer, chain_id: u64) -> TypedData {
+    let mut resolver = Resolver::default();
+    resolver
+        .ingest_string(format!(
+            "{PERMIT_TRANSFER_FROM_WITNESS_TYPEHASH_STUB}{FULL_PROOF_OFFER_WITNESS_TYPE_STRING_STUB}"
+        ))
+        .expect("permit2 offer type string is valid");
+
+    TypedData {
+        domain: permit2_domain(chain_id),
+        resolver,
+        primary_type: "PermitWitnessTransferFrom".to_string(),
+        message: serde_json::json!({
+            "permitted": {
+                "token": proof_offer.stakeToken,
+                "amount": proof_offer.stakeAmount.to_string(),
+            },
+            "spender": proof_offer.market,
+            "nonce": proof_offer.nonce.to_string(),
+            "deadline": proof_offer.signature_deadline().to_string(),
+            "witness": {
+                "signer": proof_offer.signer,
+                "market": proof_offer.market,
+                "nonce": proof_offer.nonce.to_string(),
+                "rewardToken": proof_offer.rewardToken,
+                "rewardAmount": proof_offer.rewardAmount.to_string(),
+                "stakeToken": proof_offer.stakeToken,
+                "stakeAmount": proof_offer.stakeAmount.to_string(),
+                "startAuctionTimestamp": proof_offer.startAuctionTimestamp.to_string(),
+                "endAuctionTimestamp": proof_offer.endAuctionTimestamp.to_string(),
+                "provingTime": proof_offer.provingTime.to_string(),
+                "inputsCommitment": proof_offer.inputsCommitment,
+                "extraData": proof_offer.extraData,
+            },
+        }),
+    }
 }
@@ -1,20 +1,18 @@
 use alloy::{
-    dyn_abi::DynSolValue,
-    primitives::{keccak256, Address, FixedBytes, PrimitiveSignature, B256, U256},
+    dyn_abi::{eip712::Resolver, DynSolValue, TypedData},
+    primitives::{keccak256, Address, Bytes, FixedBytes, PrimitiveSignature, B256, U256},
     sol_types::SolValue,
 };
 use serde::{Deserialize, Serialize};
 
 use crate::{
     abi::{
-        permit2::ISignatureTransfer::TokenPermissions,
         universal_bombetta::UniversalBombetta::ProofRequest,
+        verifier_details::decode_verifier_details,
     },
+    commitment::PartialCommitmentCheck,
     systems::{System, SystemId},
-    utils::{
-        hash_typed_data, PERMIT2_DOMAIN_SEPARATOR, PERMIT_TRANSFER_FROM_WITNESS_TYPEHASH_STUB,
-        TOKEN_PERMISSIONS_TYPE_HASH,
-    },
+    utils::{permit2_domain, PERMIT_TRANSFER_FROM_WITNESS_TYPEHASH_STUB},
 };
 use lazy_static::lazy_static;
 
@@ -22,7 +20,7 @@ use super::{CommonProofCommitment, ComputeIntent};
 
 /// bombetta signature constants
 pub const FULL_PROOF_REQUEST_WITNESS_TYPE_STRING_STUB: &str =
-    "ProofRequest witness)TokenPermissions(address token,uint256 amount)ProofRequest(address signer,address market,uint256 nonce,address rewardToken,uint256 maxRewardAmount,uint256 minRewardAmount,uint128 minimumStake,uint64 startAuctionTimestamp,uint64 endAuctionTimestamp,uint32 provingTime,bytes32 inputsCommitment,bytes extraData)";
+    "ProofRequest witness)ProofRequest(address signer,address market,uint256 nonce,address rewardToken,uint256 maxRewardAmount,uint256 minRewardAmount,uint128 minimumStake,uint64 startAuctionTimestamp,uint64 endAuctionTimestamp,uint32 provingTime,bytes32 inputsCommitment,bytes extraData)TokenPermissions(address token,uint256 amount)";
 pub const PROOF_REQUEST_WITNESS_TYPE_STRING: &str =
     "ProofRequest(address signer,address market,uint256 nonce,address rewardToken,uint256 maxRewardAmount,uint256 minRewardAmount,uint128 minimumStake,uint64 startAuctionTimestamp,uint64 endAuctionTimestamp,uint32 provingTime,bytes32 inputsCommitment,bytes extraData)";
 
@@ -75,6 +73,19 @@ impl CommonProofCommitment for ProofRequest {
     fn inputs_commitment(&self) -> FixedBytes<32> {
         self.inputsCommitment
     }
+
+    fn extra_data(&self) -> &Bytes {
+        &self.extraData
+    }
+
+    /// Check of the leading verifier details, malformed ones declaring none as they fail the
+    /// request's validation
+    fn partial_commitment_check(&self) -> Option<PartialCommitmentCheck> {
+        decode_verifier_details(&self.extraData)
+            .ok()
+            .as_ref()
+            .and_then(PartialCommitmentCheck::from_verifier_details)
+    }
 }
 
 /// generic compute request implementation
@@ -106,8 +117,12 @@ impl<S: System> ComputeIntent for ComputeRequest<S> {
         compute_request_id(&self.proof_request, &self.signature)
     }
 
-    fn compute_permit2_digest(&self) -> FixedBytes<32> {
-        compute_request_permit2_digest(&self.proof_request)
+    fn compute_permit2_digest(&self, chain_id: u64) -> FixedBytes<32> {
+        compute_request_permit2_digest(&self.proof_request, chain_id)
+    }
+
+    fn permit2_typed_data(&self, chain_id: u64) -> TypedData {
+        request_permit2_typed_data(&self.proof_request, chain_id)
     }
 }
 
@@ -141,7 +156,8 @@ pub fn compute_request_id(
     keccak256(&preimage)
 }
 
-pub fn compute_request_permit2_digest(proof_commitment: &ProofRequest) -> FixedBytes<32> {
+/// Hash of the `ProofRequest` witness the permit2 signature commits to
+pub fn compute_request_witness(proof_commitment: &ProofRequest) -> B256 {
     // compute witness
     let extra_data_hash = keccak256(proof_commitment.extraData.clone());
 
@@ -162,38 +178,57 @@ pub fn compute_request_permit2_digest(proof_commitment: &ProofRequest) -> FixedB
     ]);
 
     // hash encoded witness
-    let witness = keccak256(request_witness_values.abi_encode());
-
-    // compute permit2 digest
-    let token_permissions = TokenPermissions {
-        token: proof_commitment.rewardToken,
-        amount: proof_commitment.maxRewardAmount,
-    };
-    let token_permissions_bytes = token_permissions.abi_encode();
-
-    let token_permissions_hash_preimage = [
-        TOKEN_PERMISSIONS_TYPE_HASH.abi_encode(),
-        token_permissions_bytes,
-    ]
-    .concat();
-
-    // hash token permissions encoding
-    let token_permissions_hash = keccak256(&token_permissions_hash_preimage);
-
-    // encode data hash preimage
-    let data_hash_preimage = DynSolValue::Tuple(vec![
-        DynSolValue::FixedBytes(*REQUEST_PERMIT_TYPE_HASH, 32),
-        DynSolValue::FixedBytes(token_permissions_hash, 32),
-        DynSolValue::Address(proof_commitment.market),
-        DynSolValue::Uint(proof_commitment.nonce, 256),
-        DynSolValue::Uint(U256::from(proof_commitment.endAuctionTimestamp), 64),
-        DynSolValue::FixedBytes(witness, 32),
-    ])
-    .abi_encode();
+    keccak256(request_witness_values.abi_encode())
+}
 
-    // hash data hash encoding
-    let data_hash = keccak256(&data_hash_preimage);
+/// Permit2 digest the market verifies, the signing hash of `request_permit2_typed_data`
+pub fn compute_request_permit2_digest(
+    proof_commitment: &ProofRequest,
+    chain_id: u64,
+) -> FixedBytes<32> {
+    request_permit2_typed_data(proof_commitment, chain_id)
+        .eip712_signing_hash()
+        .expect("permit2 request typed data is valid")
+}
 
-    // return the signable eip712 permit2 hash
-    hash_typed_data(PERMIT2_DOMAIN_SEPARATOR, data_hash)
+/// EIP-712 typed data of the permit2 `PermitWitnessTransferFrom` a requester signs, serializing to
+/// the `eth_signTypedData_v4` JSON external signers (hardware wallets, MPC) expect. Its signing
+/// hash is the digest `UniversalBombetta` verifies, the witness type string listing the referenced
+/// types in the alphabetical order of EIP-712.
+pub fn request_permit2_typed_data(proof_request: &ProofRequest, chain_id: u64) -> TypedData {
+    let mut resolver = Resolver::default();
+    resolver
+        .ingest_string(format!(
+            "{PERMIT_TRANSFER_FROM_WITNESS_TYPEHASH_STUB}{FULL_PROOF_REQUEST_WITNESS_TYPE_STRING_STUB}"
+        ))
+        .expect("permit2 request type string is valid");
+
+    TypedData {
+        domain: permit2_domain(chain_id),
+        resolver,
+        primary_type: "PermitWitnessTransferFrom".to_string(),
+        message: serde_json::json!({
+            "permitted": {
+                "token": proof_request.rewardToken,
+                "amount": proof_request.maxRewardAmount.to_string(),
+            },
+            "spender": proof_request.market,
+            "nonce": proof_request.nonce.to_string(),
+            "deadline": proof_request.signature_deadline().to_string(),
+            "witness": {
+                "signer": proof_request.signer,
+                "market": proof_request.market,
+                "nonce": proof_request.nonce.to_string(),
+                "rewardToken": proof_request.rewardToken,
+                "maxRewardAmount": proof_request.maxRewardAmount.to_string(),
+                "minRewardAmount": proof_request.minRewardAmount.to_string(),
+                "minimumStake": proof_request.minimumStake.to_string(),
+                "startAuctionTimestamp": proof_request.startAuctionTimestamp.to_string(),
+                "endAuctionTimestamp": proof_request.endAuctionTimestamp.to_string(),
+                "provingTime": proof_request.provingTime.to_string(),
+                "inputsCommitment": proof_request.inputsCommitment,
+                "extraData": proof_request.extraData,
+            },
+        }),
+    }
 }
@@ -6,24 +6,26 @@
 pub mod alloy {
     pub mod primitives {
         pub use alloy::primitives::{
-            address, b256, bytes, fixed_bytes, Address, Bytes, FixedBytes, PrimitiveSignature,
-            B256, U256,
+            address, b256, bytes, fixed_bytes, keccak256, utils, Address, Bytes, FixedBytes,
+            PrimitiveSignature, B256, U256,
         };
     }
 
     pub mod network {
         pub use alloy::network::{
             primitives::{BlockResponse, BlockTransactionsKind, HeaderResponse},
-            Ethereum, EthereumWallet, Network, ReceiptResponse,
+            Ethereum, EthereumWallet, Network, ReceiptResponse, TransactionBuilder,
         };
     }
 
     pub mod consensus {
-        pub use alloy::consensus::BlockHeader;
+        pub use alloy::consensus::{BlockHeader, Transaction};
     }
 
     pub mod providers {
-        pub use alloy::providers::{Provider, ProviderBuilder, RootProvider};
+        pub use alloy::providers::{
+            fillers, Identity, Provider, ProviderBuilder, RootProvider, SendableTx,
+        };
     }
 
     pub mod transports {
@@ -36,7 +38,7 @@ pub mod alloy {
     }
 
     pub mod eips {
-        pub use alloy::eips::{BlockId, BlockNumberOrTag};
+        pub use alloy::eips::{eip2718::Encodable2718, BlockId, BlockNumberOrTag};
     }
 
     pub mod utils {
@@ -44,11 +46,17 @@ pub mod alloy {
     }
 
     pub mod dyn_abi {
-        pub use alloy::dyn_abi::DynSolValue;
+        pub use alloy::dyn_abi::{DynSolValue, TypedData};
     }
 
     pub mod sol_types {
-        pub use alloy::sol_types::SolValue;
+        pub use alloy::sol_types::{SolCall, SolEvent, SolValue};
+    }
+
+    pub mod rpc {
+        pub mod types {
+            pub use alloy::rpc::types::{Log, TransactionReceipt, TransactionRequest};
+        }
     }
 
     pub mod signers {
@@ -58,11 +66,20 @@ pub mod alloy {
 
 // Taralli primitives
 pub mod abi;
+pub mod capabilities;
+pub mod commitment;
 pub mod compression_utils;
+pub mod deployments;
+pub mod encryption;
 pub mod env;
 pub mod error;
+pub mod extension;
 pub mod intents;
+pub mod logging;
 pub mod markets;
+pub mod offer_inputs;
+pub mod permit2_vectors;
+pub mod request_metadata;
 pub mod systems;
 pub mod utils;
 pub mod validation;
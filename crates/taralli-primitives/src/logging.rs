@@ -0,0 +1,33 @@
+//! Log conventions shared by the provider clients and the server.
+//!
+//! Events of the `STATE_TARGET` target dump a process' state and are meant to be kept at info
+//! whatever the filter, so state dumps show up even when only errors are logged.
+//!
+//! Each intent is handled within an `intent_span`, so every event logged while analyzing,
+//! bidding on, proving or resolving it carries its id, even when intents are interleaved. Its
+//! `ARTIFACT_FIELD` is left empty until a provider identified the artifacts the intent proves.
+
+use alloy::primitives::{Address, B256};
+use tracing::Span;
+
+use crate::systems::SystemId;
+
+/// target of the events dumping a process' state, logged at info whatever the filter
+pub const STATE_TARGET: &str = "taralli_state";
+
+/// name of the span an intent is handled within
+pub const INTENT_SPAN: &str = "intent";
+
+/// field of the intent span recording the label of the artifacts the intent proves
+pub const ARTIFACT_FIELD: &str = "artifact";
+
+/// Span of the handling of an intent, to enter or instrument its futures with
+pub fn intent_span(intent_id: B256, system_id: SystemId, market: Address) -> Span {
+    tracing::info_span!(
+        INTENT_SPAN,
+        intent_id = %intent_id,
+        system_id = ?system_id,
+        market = %market,
+        artifact = tracing::field::Empty
+    )
+}
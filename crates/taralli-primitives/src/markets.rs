@@ -1,13 +1,82 @@
-use alloy::primitives::{address, Address};
+use alloy::primitives::{address, Address, U256};
+use serde::{Deserialize, Serialize};
 
 pub enum Network {
     Sepolia,
     // add networks here
 }
 
+impl Network {
+    #[must_use]
+    pub const fn chain_id(&self) -> u64 {
+        match self {
+            Network::Sepolia => SEPOLIA_CHAIN_ID,
+        }
+    }
+}
+
+/// Sepolia chain id
+pub const SEPOLIA_CHAIN_ID: u64 = 11_155_111;
+
 /// Sepolia market addresses
 pub const SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS: Address =
     address!("6209431B6C8F38471dc65564Be2Fd08298705BBD");
 
 pub const SEPOLIA_UNIVERSAL_PORCHETTA_ADDRESS: Address =
     address!("67445680c74Fb82C46421374554e402e72E9e5d1");
+
+/// basis points in one
+const BPS: u16 = 10_000;
+
+/// Protocol fee a market takes out of the rewards it pays to providers, the requester paying
+/// the whole reward
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarketFee {
+    /// share of each reward taken, in basis points
+    pub fee_bps: u16,
+}
+
+impl MarketFee {
+    /// fee of the markets taking none
+    pub const ZERO: Self = Self { fee_bps: 0 };
+
+    #[must_use]
+    pub const fn from_bps(fee_bps: u16) -> Self {
+        Self { fee_bps }
+    }
+
+    /// Fee taken out of `reward`, rounded down
+    #[must_use]
+    pub fn fee_on(&self, reward: U256) -> U256 {
+        let fee_bps = self.fee_bps.min(BPS);
+        reward.saturating_mul(U256::from(fee_bps)) / U256::from(BPS)
+    }
+
+    /// Part of `reward` paid to the provider once the fee is taken
+    #[must_use]
+    pub fn net_reward(&self, reward: U256) -> U256 {
+        reward - self.fee_on(reward)
+    }
+
+    /// Smallest reward paying the provider at least `net_reward` once the fee is taken,
+    /// `U256::MAX` when the fee takes the whole reward
+    #[must_use]
+    pub fn gross_reward(&self, net_reward: U256) -> U256 {
+        if net_reward.is_zero() {
+            return U256::ZERO;
+        }
+        if self.fee_bps >= BPS {
+            return U256::MAX;
+        }
+        // the fee being rounded down, the provider is paid ceil(reward * kept / BPS)
+        let kept_bps = U256::from(BPS - self.fee_bps);
+        ((net_reward - U256::from(1)).saturating_mul(U256::from(BPS)) / kept_bps)
+            .saturating_add(U256::from(1))
+    }
+}
+
+impl std::fmt::Display for MarketFee {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{:02}%", self.fee_bps / 100, self.fee_bps % 100)
+    }
+}
@@ -0,0 +1,149 @@
+//! Inputs of compute offers, delivered by the requester once its bid lands.
+//!
+//! A `ComputeOffer` only commits to the inputs its provider will prove over through its
+//! `inputsCommitment`. The requester that won the offer posts the preimage through the server,
+//! compressed and signed by the bidding address. The server and the provider both check it
+//! against the commitment recorded by the market before it reaches a worker, where they replace
+//! the inputs of the offered system. Offers leaving their inputs to the requester therefore
+//! commit to the prover inputs themselves.
+
+use alloy::primitives::{keccak256, Address, Bytes, PrimitiveSignature, B256};
+use alloy::signers::Signer;
+use serde::{Deserialize, Serialize};
+
+use crate::compression_utils::compression::{
+    compress_brotli, decompress_brotli_bounded, DecompressionBudget,
+};
+use crate::error::{PrimitivesError, Result};
+use crate::systems::{System, SystemId, SystemInputs, SystemParams};
+
+const OFFER_INPUTS_DOMAIN: &[u8] = b"taralli offer inputs";
+const INPUTS_QUERY_DOMAIN: &[u8] = b"taralli offer inputs query";
+
+/// seconds an inputs query is accepted for, either side of its issue time
+pub const INPUTS_QUERY_VALIDITY_SECONDS: u64 = 300;
+
+/// Inputs of a won offer as posted by its requester
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OfferInputs {
+    /// brotli compressed preimage of the offer's inputs commitment
+    pub compressed_inputs: Bytes,
+    /// signature of the bidding address over the offer id and `compressed_inputs`
+    pub signature: PrimitiveSignature,
+}
+
+impl OfferInputs {
+    /// Compress `inputs` and sign them for the given offer
+    pub async fn sign<S: Signer>(offer_id: B256, inputs: &[u8], signer: &S) -> Result<Self> {
+        let compressed_inputs = Bytes::from(compress_brotli(&inputs)?);
+        let digest = offer_inputs_digest(offer_id, &compressed_inputs);
+        let signature = signer
+            .sign_hash(&digest)
+            .await
+            .map_err(|e| PrimitivesError::SignatureError(e.to_string()))?;
+        Ok(Self {
+            compressed_inputs,
+            signature,
+        })
+    }
+
+    /// Address that signed the inputs of the given offer
+    pub fn signer(&self, offer_id: B256) -> Result<Address> {
+        let digest = offer_inputs_digest(offer_id, &self.compressed_inputs);
+        self.signature
+            .recover_address_from_prehash(&digest)
+            .map_err(|e| PrimitivesError::SignatureError(e.to_string()))
+    }
+
+    /// Decompress the inputs within the given budget
+    pub async fn decompress(&self, budget: &DecompressionBudget) -> Result<Vec<u8>> {
+        decompress_brotli_bounded(&self.compressed_inputs, budget).await
+    }
+}
+
+/// Query of the inputs delivered for an offer, signed by its provider
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputsQuery {
+    /// unix timestamp the query was signed at
+    pub issued_at: u64,
+    /// signature of the provider over the offer id and `issued_at`
+    pub signature: PrimitiveSignature,
+}
+
+impl InputsQuery {
+    /// Sign a query of the inputs of the given offer issued at the unix timestamp `issued_at`
+    pub async fn sign<S: Signer>(offer_id: B256, issued_at: u64, signer: &S) -> Result<Self> {
+        let digest = inputs_query_digest(offer_id, issued_at);
+        let signature = signer
+            .sign_hash(&digest)
+            .await
+            .map_err(|e| PrimitivesError::SignatureError(e.to_string()))?;
+        Ok(Self {
+            issued_at,
+            signature,
+        })
+    }
+
+    /// Address that signed the query of the inputs of the given offer
+    pub fn signer(&self, offer_id: B256) -> Result<Address> {
+        let digest = inputs_query_digest(offer_id, self.issued_at);
+        self.signature
+            .recover_address_from_prehash(&digest)
+            .map_err(|e| PrimitivesError::SignatureError(e.to_string()))
+    }
+
+    /// Whether the query is still accepted at the unix timestamp `now`
+    pub fn is_fresh(&self, now: u64) -> bool {
+        self.issued_at.abs_diff(now) <= INPUTS_QUERY_VALIDITY_SECONDS
+    }
+}
+
+/// Replace the inputs of `system` with the delivered ones. Arkworks circuits take the inputs as
+/// a JSON object of signals, every other system takes the bytes as is.
+pub fn with_offer_inputs(system: &SystemParams, inputs: &[u8]) -> Result<SystemParams> {
+    let system_id = system.system_id();
+    let inputs = match system_id {
+        SystemId::Arkworks => SystemInputs::Json(
+            serde_json::from_slice(inputs)
+                .map_err(|e| PrimitivesError::ProverInputsError(e.to_string()))?,
+        ),
+        SystemId::Risc0 | SystemId::Sp1 => SystemInputs::Bytes(inputs.to_vec()),
+    };
+    // params are serialized tagged by their system name
+    let mut params = match serde_json::to_value(system) {
+        Ok(serde_json::Value::Object(tagged)) => tagged
+            .into_iter()
+            .next()
+            .map(|(_, params)| params)
+            .ok_or_else(|| PrimitivesError::ProverInputsError("empty system params".to_string()))?,
+        Ok(_) => {
+            return Err(PrimitivesError::ProverInputsError(
+                "system params must be a JSON object".to_string(),
+            ))
+        }
+        Err(e) => return Err(PrimitivesError::ProverInputsError(e.to_string())),
+    };
+    params
+        .as_object_mut()
+        .ok_or_else(|| {
+            PrimitivesError::ProverInputsError("system params must be a JSON object".to_string())
+        })?
+        .insert("inputs".to_string(), inputs.into_params_value(system_id)?);
+    SystemParams::try_from((&system_id, params.to_string().into_bytes()))
+        .map_err(PrimitivesError::ProverInputsError)
+}
+
+fn offer_inputs_digest(offer_id: B256, compressed_inputs: &[u8]) -> B256 {
+    keccak256([OFFER_INPUTS_DOMAIN, offer_id.as_slice(), compressed_inputs].concat())
+}
+
+fn inputs_query_digest(offer_id: B256, issued_at: u64) -> B256 {
+    keccak256(
+        [
+            INPUTS_QUERY_DOMAIN,
+            offer_id.as_slice(),
+            &issued_at.to_be_bytes(),
+        ]
+        .concat(),
+    )
+}
@@ -0,0 +1,482 @@
+//! Deterministic test vectors of the permit2 signatures of requests and offers, shared with the
+//! Solidity test suite so the digests computed here and the ones the markets verify can't drift
+//! apart unnoticed.
+//!
+//! The vectors are generated from a fixed seed and signed with a fixed key, so regenerating them
+//! with unchanged code reproduces the stored file byte for byte. The layout of the file is
+//! described in its `_format` header, see `FORMAT`.
+
+use alloy::hex;
+use alloy::primitives::{keccak256, Address, Bytes, PrimitiveSignature, B256, U256};
+use alloy::signers::{local::PrivateKeySigner, SignerSync};
+use serde::Serialize;
+
+use crate::abi::{
+    universal_bombetta::UniversalBombetta::ProofRequest,
+    universal_porchetta::UniversalPorchetta::ProofOffer, verifier_details::MAX_EXTRA_DATA_LENGTH,
+};
+use crate::intents::{
+    offer::{compute_offer_permit2_digest, compute_offer_witness},
+    request::{compute_request_permit2_digest, compute_request_witness},
+};
+use crate::markets::SEPOLIA_CHAIN_ID;
+use crate::utils::PERMIT2_ADDRESS;
+use crate::{PrimitivesError, Result};
+
+/// version of the vectors' layout, bumped on any change of it
+pub const PERMIT2_VECTORS_VERSION: u32 = 1;
+/// seed the randomized vectors are drawn from
+pub const PERMIT2_VECTORS_SEED: &[u8] = b"taralli permit2 vectors";
+/// key signing every vector, the first development account of anvil
+pub const PERMIT2_VECTORS_KEY: B256 =
+    alloy::primitives::b256!("ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80");
+/// chain the digests are computed for
+pub const PERMIT2_VECTORS_CHAIN_ID: u64 = SEPOLIA_CHAIN_ID;
+/// number of randomized vectors of each intent in the stored file
+pub const DEFAULT_RANDOM_VECTORS: usize = 16;
+/// path of the stored file, relative to the repository root
+pub const PERMIT2_VECTORS_PATH: &str = "contracts/test-proof-data/permit2/permit2_vectors.json";
+
+/// Description of the file's layout, stored as its header
+pub const FORMAT: &[&str] = &[
+    "Permit2 test vectors of ProofRequest (UniversalBombetta) and ProofOffer (UniversalPorchetta).",
+    "Generated by `cargo run -p taralli-primitives --bin permit2_vectors`, do not edit by hand.",
+    "version: layout version, chain_id: chain the digests are computed for, permit2: address of",
+    "the permit2 contract of the EIP-712 domain, signer_key and signer: key signing every vector",
+    "and its address.",
+    "requests[i] and offers[i] hold: name, the intent struct (proof_request or proof_offer) keyed",
+    "by its Solidity field names, witness: keccak256 of the ABI encoded witness type hash and",
+    "fields (extraData hashed), permit2_digest: EIP-712 digest of the PermitWitnessTransferFrom",
+    "the market verifies, signature: 65 bytes r || s || v (v in 27, 28) of the signer over it.",
+    "Integers are 32 byte big endian hex words, addresses 20 byte hex, bytes 0x prefixed hex, all",
+    "lowercase, readable with vm.parseJsonUint, vm.parseJsonAddress, vm.parseJsonBytes32 and",
+    "vm.parseJsonBytes.",
+];
+
+/// Vector of a signed `ProofRequest`
+#[derive(Clone, Debug)]
+pub struct RequestVector {
+    pub name: String,
+    pub proof_request: ProofRequest,
+    pub witness: B256,
+    pub permit2_digest: B256,
+    pub signature: PrimitiveSignature,
+}
+
+/// Vector of a signed `ProofOffer`
+#[derive(Clone, Debug)]
+pub struct OfferVector {
+    pub name: String,
+    pub proof_offer: ProofOffer,
+    pub witness: B256,
+    pub permit2_digest: B256,
+    pub signature: PrimitiveSignature,
+}
+
+/// Test vectors of both markets, signed by `signer`
+#[derive(Clone, Debug)]
+pub struct Permit2Vectors {
+    pub chain_id: u64,
+    pub signer: Address,
+    pub requests: Vec<RequestVector>,
+    pub offers: Vec<OfferVector>,
+}
+
+impl Permit2Vectors {
+    /// Generate the edge case vectors followed by `random` randomized vectors of each intent
+    pub fn generate(random: usize) -> Result<Self> {
+        let signer = PrivateKeySigner::from_bytes(&PERMIT2_VECTORS_KEY)
+            .map_err(|e| PrimitivesError::SignatureError(e.to_string()))?;
+        let mut rng = VectorRng::new(PERMIT2_VECTORS_SEED);
+
+        let mut requests = vec![
+            ("zero".to_string(), zero_request(signer.address())),
+            ("max".to_string(), max_request(signer.address())),
+        ];
+        let mut request = rng.request(signer.address());
+        request.extraData = Bytes::new();
+        requests.push(("empty_extra_data".to_string(), request));
+        let mut request = rng.request(signer.address());
+        request.extraData = rng.bytes(MAX_EXTRA_DATA_LENGTH);
+        requests.push(("max_extra_data".to_string(), request));
+        for i in 0..random {
+            requests.push((format!("random_{i}"), rng.request(signer.address())));
+        }
+
+        let mut offers = vec![
+            ("zero".to_string(), zero_offer(signer.address())),
+            ("max".to_string(), max_offer(signer.address())),
+        ];
+        let mut offer = rng.offer(signer.address());
+        offer.extraData = Bytes::new();
+        offers.push(("empty_extra_data".to_string(), offer));
+        let mut offer = rng.offer(signer.address());
+        offer.extraData = rng.bytes(MAX_EXTRA_DATA_LENGTH);
+        offers.push(("max_extra_data".to_string(), offer));
+        for i in 0..random {
+            offers.push((format!("random_{i}"), rng.offer(signer.address())));
+        }
+
+        let requests = requests
+            .into_iter()
+            .map(|(name, proof_request)| {
+                let permit2_digest =
+                    compute_request_permit2_digest(&proof_request, PERMIT2_VECTORS_CHAIN_ID);
+                Ok(RequestVector {
+                    name,
+                    witness: compute_request_witness(&proof_request),
+                    signature: sign(&signer, permit2_digest)?,
+                    permit2_digest,
+                    proof_request,
+                })
+            })
+            .collect::<Result<_>>()?;
+        let offers = offers
+            .into_iter()
+            .map(|(name, proof_offer)| {
+                let permit2_digest =
+                    compute_offer_permit2_digest(&proof_offer, PERMIT2_VECTORS_CHAIN_ID);
+                Ok(OfferVector {
+                    name,
+                    witness: compute_offer_witness(&proof_offer),
+                    signature: sign(&signer, permit2_digest)?,
+                    permit2_digest,
+                    proof_offer,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            chain_id: PERMIT2_VECTORS_CHAIN_ID,
+            signer: signer.address(),
+            requests,
+            offers,
+        })
+    }
+
+    /// The vectors as stored, pretty printed JSON ending with a newline
+    pub fn to_json(&self) -> Result<String> {
+        let file = VectorsFile {
+            format: FORMAT,
+            version: PERMIT2_VECTORS_VERSION,
+            chain_id: self.chain_id,
+            permit2: hex::encode_prefixed(PERMIT2_ADDRESS),
+            signer_key: hex::encode_prefixed(PERMIT2_VECTORS_KEY),
+            signer: hex::encode_prefixed(self.signer),
+            requests: self.requests.iter().map(RequestVectorJson::from).collect(),
+            offers: self.offers.iter().map(OfferVectorJson::from).collect(),
+        };
+        let mut json = serde_json::to_string_pretty(&file)
+            .map_err(|e| PrimitivesError::SerializationError(e.to_string()))?;
+        json.push('\n');
+        Ok(json)
+    }
+}
+
+fn sign(signer: &PrivateKeySigner, digest: B256) -> Result<PrimitiveSignature> {
+    signer
+        .sign_hash_sync(&digest)
+        .map_err(|e| PrimitivesError::SignatureError(e.to_string()))
+}
+
+fn zero_request(signer: Address) -> ProofRequest {
+    ProofRequest {
+        signer,
+        market: Address::ZERO,
+        nonce: U256::ZERO,
+        rewardToken: Address::ZERO,
+        maxRewardAmount: U256::ZERO,
+        minRewardAmount: U256::ZERO,
+        minimumStake: 0,
+        startAuctionTimestamp: 0,
+        endAuctionTimestamp: 0,
+        provingTime: 0,
+        inputsCommitment: B256::ZERO,
+        extraData: Bytes::new(),
+    }
+}
+
+fn max_request(signer: Address) -> ProofRequest {
+    ProofRequest {
+        signer,
+        market: Address::repeat_byte(0xff),
+        nonce: U256::MAX,
+        rewardToken: Address::repeat_byte(0xff),
+        maxRewardAmount: U256::MAX,
+        minRewardAmount: U256::MAX,
+        minimumStake: u128::MAX,
+        startAuctionTimestamp: u64::MAX,
+        endAuctionTimestamp: u64::MAX,
+        provingTime: u32::MAX,
+        inputsCommitment: B256::repeat_byte(0xff),
+        extraData: vec![0xff; MAX_EXTRA_DATA_LENGTH].into(),
+    }
+}
+
+fn zero_offer(signer: Address) -> ProofOffer {
+    ProofOffer {
+        signer,
+        market: Address::ZERO,
+        nonce: U256::ZERO,
+        rewardToken: Address::ZERO,
+        rewardAmount: U256::ZERO,
+        stakeToken: Address::ZERO,
+        stakeAmount: U256::ZERO,
+        startAuctionTimestamp: 0,
+        endAuctionTimestamp: 0,
+        provingTime: 0,
+        inputsCommitment: B256::ZERO,
+        extraData: Bytes::new(),
+    }
+}
+
+fn max_offer(signer: Address) -> ProofOffer {
+    ProofOffer {
+        signer,
+        market: Address::repeat_byte(0xff),
+        nonce: U256::MAX,
+        rewardToken: Address::repeat_byte(0xff),
+        rewardAmount: U256::MAX,
+        stakeToken: Address::repeat_byte(0xff),
+        stakeAmount: U256::MAX,
+        startAuctionTimestamp: u64::MAX,
+        endAuctionTimestamp: u64::MAX,
+        provingTime: u32::MAX,
+        inputsCommitment: B256::repeat_byte(0xff),
+        extraData: vec![0xff; MAX_EXTRA_DATA_LENGTH].into(),
+    }
+}
+
+/// Deterministic source of the randomized fields, the keccak256 of the seed followed by a
+/// big endian counter
+struct VectorRng {
+    seed: Vec<u8>,
+    counter: u64,
+}
+
+impl VectorRng {
+    fn new(seed: &[u8]) -> Self {
+        Self {
+            seed: seed.to_vec(),
+            counter: 0,
+        }
+    }
+
+    fn word(&mut self) -> B256 {
+        let word = keccak256([self.seed.as_slice(), &self.counter.to_be_bytes()].concat());
+        self.counter += 1;
+        word
+    }
+
+    fn u256(&mut self) -> U256 {
+        U256::from_be_bytes(self.word().0)
+    }
+
+    fn u128(&mut self) -> u128 {
+        u128::from_be_bytes(self.word()[16..].try_into().expect("16 bytes"))
+    }
+
+    /// uniform enough below `bound`, the bias of a 256 bits modulo being negligible
+    fn below(&mut self, bound: u64) -> u64 {
+        (self.u256() % U256::from(bound)).to::<u64>()
+    }
+
+    fn address(&mut self) -> Address {
+        Address::from_word(self.word())
+    }
+
+    fn bytes(&mut self, len: usize) -> Bytes {
+        let mut bytes = Vec::with_capacity(len + 32);
+        while bytes.len() < len {
+            bytes.extend_from_slice(self.word().as_slice());
+        }
+        bytes.truncate(len);
+        bytes.into()
+    }
+
+    fn extra_data(&mut self) -> Bytes {
+        let len = self.below(MAX_EXTRA_DATA_LENGTH as u64 + 1) as usize;
+        self.bytes(len)
+    }
+
+    /// start, end and proving time of a plausible auction
+    fn timestamps(&mut self) -> (u64, u64, u32) {
+        let start = 1_700_000_000 + self.below(100_000_000);
+        let end = start + 1 + self.below(3_600);
+        let proving_time = 1 + self.below(86_400) as u32;
+        (start, end, proving_time)
+    }
+
+    fn request(&mut self, signer: Address) -> ProofRequest {
+        let market = self.address();
+        let nonce = self.u256();
+        let reward_token = self.address();
+        let max_reward_amount = self.u256();
+        let min_reward_amount = max_reward_amount >> self.below(256) as usize;
+        let minimum_stake = self.u128();
+        let (start, end, proving_time) = self.timestamps();
+        let inputs_commitment = self.word();
+        let extra_data = self.extra_data();
+        ProofRequest {
+            signer,
+            market,
+            nonce,
+            rewardToken: reward_token,
+            maxRewardAmount: max_reward_amount,
+            minRewardAmount: min_reward_amount,
+            minimumStake: minimum_stake,
+            startAuctionTimestamp: start,
+            endAuctionTimestamp: end,
+            provingTime: proving_time,
+            inputsCommitment: inputs_commitment,
+            extraData: extra_data,
+        }
+    }
+
+    fn offer(&mut self, signer: Address) -> ProofOffer {
+        let market = self.address();
+        let nonce = self.u256();
+        let reward_token = self.address();
+        let reward_amount = self.u256();
+        let stake_token = self.address();
+        let stake_amount = self.u256();
+        let (start, end, proving_time) = self.timestamps();
+        let inputs_commitment = self.word();
+        let extra_data = self.extra_data();
+        ProofOffer {
+            signer,
+            market,
+            nonce,
+            rewardToken: reward_token,
+            rewardAmount: reward_amount,
+            stakeToken: stake_token,
+            stakeAmount: stake_amount,
+            startAuctionTimestamp: start,
+            endAuctionTimestamp: end,
+            provingTime: proving_time,
+            inputsCommitment: inputs_commitment,
+            extraData: extra_data,
+        }
+    }
+}
+
+fn word(value: U256) -> String {
+    hex::encode_prefixed(value.to_be_bytes::<32>())
+}
+
+#[derive(Serialize)]
+struct VectorsFile {
+    #[serde(rename = "_format")]
+    format: &'static [&'static str],
+    version: u32,
+    chain_id: u64,
+    permit2: String,
+    signer_key: String,
+    signer: String,
+    requests: Vec<RequestVectorJson>,
+    offers: Vec<OfferVectorJson>,
+}
+
+#[derive(Serialize)]
+struct RequestVectorJson {
+    name: String,
+    proof_request: ProofRequestJson,
+    witness: String,
+    permit2_digest: String,
+    signature: String,
+}
+
+#[derive(Serialize)]
+struct OfferVectorJson {
+    name: String,
+    proof_offer: ProofOfferJson,
+    witness: String,
+    permit2_digest: String,
+    signature: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProofRequestJson {
+    signer: String,
+    market: String,
+    nonce: String,
+    reward_token: String,
+    max_reward_amount: String,
+    min_reward_amount: String,
+    minimum_stake: String,
+    start_auction_timestamp: String,
+    end_auction_timestamp: String,
+    proving_time: String,
+    inputs_commitment: String,
+    extra_data: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProofOfferJson {
+    signer: String,
+    market: String,
+    nonce: String,
+    reward_token: String,
+    reward_amount: String,
+    stake_token: String,
+    stake_amount: String,
+    start_auction_timestamp: String,
+    end_auction_timestamp: String,
+    proving_time: String,
+    inputs_commitment: String,
+    extra_data: String,
+}
+
+impl From<&RequestVector> for RequestVectorJson {
+    fn from(vector: &RequestVector) -> Self {
+        let request = &vector.proof_request;
+        Self {
+            name: vector.name.clone(),
+            proof_request: ProofRequestJson {
+                signer: hex::encode_prefixed(request.signer),
+                market: hex::encode_prefixed(request.market),
+                nonce: word(request.nonce),
+                reward_token: hex::encode_prefixed(request.rewardToken),
+                max_reward_amount: word(request.maxRewardAmount),
+                min_reward_amount: word(request.minRewardAmount),
+                minimum_stake: word(U256::from(request.minimumStake)),
+                start_auction_timestamp: word(U256::from(request.startAuctionTimestamp)),
+                end_auction_timestamp: word(U256::from(request.endAuctionTimestamp)),
+                proving_time: word(U256::from(request.provingTime)),
+                inputs_commitment: hex::encode_prefixed(request.inputsCommitment),
+                extra_data: hex::encode_prefixed(&request.extraData),
+            },
+            witness: hex::encode_prefixed(vector.witness),
+            permit2_digest: hex::encode_prefixed(vector.permit2_digest),
+            signature: hex::encode_prefixed(vector.signature.as_bytes()),
+        }
+    }
+}
+
+impl From<&OfferVector> for OfferVectorJson {
+    fn from(vector: &OfferVector) -> Self {
+        let offer = &vector.proof_offer;
+        Self {
+            name: vector.name.clone(),
+            proof_offer: ProofOfferJson {
+                signer: hex::encode_prefixed(offer.signer),
+                market: hex::encode_prefixed(offer.market),
+                nonce: word(offer.nonce),
+                reward_token: hex::encode_prefixed(offer.rewardToken),
+                reward_amount: word(offer.rewardAmount),
+                stake_token: hex::encode_prefixed(offer.stakeToken),
+                stake_amount: word(offer.stakeAmount),
+                start_auction_timestamp: word(U256::from(offer.startAuctionTimestamp)),
+                end_auction_timestamp: word(U256::from(offer.endAuctionTimestamp)),
+                proving_time: word(U256::from(offer.provingTime)),
+                inputs_commitment: hex::encode_prefixed(offer.inputsCommitment),
+                extra_data: hex::encode_prefixed(&offer.extraData),
+            },
+            witness: hex::encode_prefixed(vector.witness),
+            permit2_digest: hex::encode_prefixed(vector.permit2_digest),
+            signature: hex::encode_prefixed(vector.signature.as_bytes()),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! Queries of the metadata requesters submit along with their compute requests.
+//!
+//! The metadata maps a request back to its requester's bookkeeping, so it is never broadcast nor
+//! served by the public `/requests` listing. A requester fetches the metadata of its own requests
+//! by signing a query with the address that signed them, bound to the chain of the server it's
+//! sent to so it isn't accepted by the servers of other chains, and dated so a query seen once
+//! can't be replayed for long.
+
+use alloy::primitives::{keccak256, Address, PrimitiveSignature, B256};
+use alloy::signers::Signer;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{PrimitivesError, Result};
+
+const METADATA_QUERY_DOMAIN: &[u8] = b"taralli request metadata query";
+
+/// seconds a metadata query is accepted for, either side of its issue time
+pub const METADATA_QUERY_VALIDITY_SECONDS: u64 = 300;
+
+/// Query of the metadata of the requests signed by the querying address
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetadataQuery {
+    /// chain id of the server the query is sent to
+    pub chain_id: u64,
+    /// unix timestamp the query was signed at
+    pub issued_at: u64,
+    /// signature of the requester over `chain_id` and `issued_at`
+    pub signature: PrimitiveSignature,
+}
+
+impl MetadataQuery {
+    /// Sign a query to a server of the chain `chain_id`, issued at the unix timestamp `issued_at`
+    pub async fn sign<S: Signer>(chain_id: u64, issued_at: u64, signer: &S) -> Result<Self> {
+        let digest = metadata_query_digest(chain_id, issued_at);
+        let signature = signer
+            .sign_hash(&digest)
+            .await
+            .map_err(|e| PrimitivesError::SignatureError(e.to_string()))?;
+        Ok(Self {
+            chain_id,
+            issued_at,
+            signature,
+        })
+    }
+
+    /// Address that signed the query, whose requests' metadata is served
+    pub fn signer(&self) -> Result<Address> {
+        let digest = metadata_query_digest(self.chain_id, self.issued_at);
+        self.signature
+            .recover_address_from_prehash(&digest)
+            .map_err(|e| PrimitivesError::SignatureError(e.to_string()))
+    }
+
+    /// Whether the query is still accepted at the unix timestamp `now`
+    pub fn is_fresh(&self, now: u64) -> bool {
+        self.issued_at.abs_diff(now) <= METADATA_QUERY_VALIDITY_SECONDS
+    }
+}
+
+fn metadata_query_digest(chain_id: u64, issued_at: u64) -> B256 {
+    keccak256(
+        [
+            METADATA_QUERY_DOMAIN,
+            &chain_id.to_be_bytes(),
+            &issued_at.to_be_bytes(),
+        ]
+        .concat(),
+    )
+}
@@ -40,6 +40,104 @@ impl System for ArkworksProofParams {
                 "r1cs or wasm bytes cannot be empty".to_string(),
             ));
         }
+        if !self.inputs.is_object() {
+            return Err(crate::PrimitivesError::ProverInputsError(
+                "inputs must be a JSON object of circuit signal names to values".to_string(),
+            ));
+        }
         Ok(())
     }
 }
+
+/// magic bytes opening every r1cs file
+const R1CS_MAGIC: &[u8; 4] = b"r1cs";
+/// type of the section holding the r1cs header
+const R1CS_HEADER_SECTION: u32 = 1;
+
+/// Header of an r1cs file, declaring the size of the circuit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct R1csHeader {
+    /// size in bytes of the field elements
+    pub field_size: u32,
+    /// number of signals of the circuit, including the constant one signal
+    pub wires: u32,
+    pub public_outputs: u32,
+    pub public_inputs: u32,
+    pub private_inputs: u32,
+    pub labels: u64,
+    pub constraints: u32,
+}
+
+impl R1csHeader {
+    /// Parse the header of an r1cs file, only walking the section table. Sections may come in
+    /// any order so the header may follow the constraints.
+    pub fn parse(r1cs: &[u8]) -> Result<Self> {
+        let malformed = |reason: &str| {
+            crate::PrimitivesError::ProverInputsError(format!("malformed r1cs: {reason}"))
+        };
+        let mut reader = R1csReader { bytes: r1cs };
+        if reader.take(4).ok_or_else(|| malformed("truncated magic"))? != R1CS_MAGIC {
+            return Err(malformed("missing r1cs magic"));
+        }
+        let _version = reader.u32().ok_or_else(|| malformed("truncated version"))?;
+        let sections = reader
+            .u32()
+            .ok_or_else(|| malformed("truncated section count"))?;
+        for _ in 0..sections {
+            let section_type = reader
+                .u32()
+                .ok_or_else(|| malformed("truncated section type"))?;
+            let section_size = reader
+                .u64()
+                .ok_or_else(|| malformed("truncated section size"))?;
+            let section = usize::try_from(section_size)
+                .ok()
+                .and_then(|size| reader.take(size))
+                .ok_or_else(|| malformed("section overruns the file"))?;
+            if section_type == R1CS_HEADER_SECTION {
+                return Self::parse_section(section).ok_or_else(|| malformed("truncated header"));
+            }
+        }
+        Err(malformed("no header section"))
+    }
+
+    fn parse_section(section: &[u8]) -> Option<Self> {
+        let mut reader = R1csReader { bytes: section };
+        let field_size = reader.u32()?;
+        // the prime of the field
+        reader.take(usize::try_from(field_size).ok()?)?;
+        Some(Self {
+            field_size,
+            wires: reader.u32()?,
+            public_outputs: reader.u32()?,
+            public_inputs: reader.u32()?,
+            private_inputs: reader.u32()?,
+            labels: reader.u64()?,
+            constraints: reader.u32()?,
+        })
+    }
+}
+
+/// little endian reader over the bytes of an r1cs file
+struct R1csReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> R1csReader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.bytes.len() < len {
+            return None;
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Some(taken)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.take(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.take(8)?.try_into().ok()?))
+    }
+}
@@ -1,6 +1,6 @@
 //! This module contains the system trait and its implementations.
 
-use crate::error::Result;
+use crate::error::{PrimitivesError, Result};
 use crate::systems::{arkworks::ArkworksProofParams, risc0::Risc0ProofParams, sp1::Sp1ProofParams};
 use serde::{Deserialize, Serialize};
 use std::fmt::Debug;
@@ -33,12 +33,50 @@ pub trait CompositeSystem: SystemConfig {
 }
 
 /// inputs can be represented as raw bytes or json
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum SystemInputs {
     Bytes(Vec<u8>),
     Json(serde_json::Value),
 }
 
+impl SystemInputs {
+    /// Value of the `inputs` field within the params of `system_id`. Arkworks circuits take a
+    /// JSON object of signals, every other system takes bytes.
+    pub fn into_params_value(self, system_id: SystemId) -> Result<serde_json::Value> {
+        match (system_id, self) {
+            (SystemId::Arkworks, Self::Json(inputs)) => Ok(inputs),
+            (SystemId::Risc0 | SystemId::Sp1, Self::Bytes(inputs)) => Ok(inputs.into()),
+            (SystemId::Arkworks, Self::Bytes(_)) => Err(PrimitivesError::ProverInputsError(
+                "arkworks inputs must be JSON, got bytes".to_string(),
+            )),
+            (system_id, Self::Json(_)) => Err(PrimitivesError::ProverInputsError(format!(
+                "{} inputs must be bytes, got JSON",
+                system_id.as_str()
+            ))),
+        }
+    }
+
+    /// Bytes of inputs taken as bytes, e.g. the program inputs of risc0 and sp1
+    pub fn into_bytes(self) -> Result<Vec<u8>> {
+        match self {
+            Self::Bytes(inputs) => Ok(inputs),
+            Self::Json(_) => Err(PrimitivesError::ProverInputsError(
+                "expected bytes inputs, got JSON".to_string(),
+            )),
+        }
+    }
+
+    /// JSON of inputs taken as JSON, e.g. the circuit signals of arkworks
+    pub fn into_json(self) -> Result<serde_json::Value> {
+        match self {
+            Self::Json(inputs) => Ok(inputs),
+            Self::Bytes(_) => Err(PrimitivesError::ProverInputsError(
+                "expected JSON inputs, got bytes".to_string(),
+            )),
+        }
+    }
+}
+
 // Main trait that all systems implement
 pub trait System: Send + Sync + 'static + Clone + Serialize + for<'de> Deserialize<'de> {
     type Config: SystemConfig;
@@ -170,15 +208,20 @@ macro_rules! systems {
             }
         }
 
+        /// Parse the serialized params of a system, rejecting params whose inputs don't have the
+        /// shape the system expects
         impl TryFrom<(&SystemId, Vec<u8>)> for SystemParams {
             type Error = String;
 
             fn try_from((id, data): (&SystemId, Vec<u8>)) -> core::result::Result<Self, Self::Error> {
                 match id {
                     $(SystemId::$variant => {
-                        serde_json::from_slice::<$params>(&data)
-                            .map(SystemParams::$variant)
-                            .map_err(|e| format!("Failed to parse {} params: {}", $str, e))
+                        let params = serde_json::from_slice::<$params>(&data)
+                            .map_err(|e| format!("Failed to parse {} params: {}", $str, e))?;
+                        params
+                            .validate_inputs()
+                            .map_err(|e| format!("Invalid {} params: {}", $str, e))?;
+                        Ok(SystemParams::$variant(params))
                     },)*
                 }
             }
@@ -42,6 +42,13 @@ impl System for Risc0ProofParams {
                 "elf or inputs bytes cannot be empty".to_string(),
             ));
         }
+        // guest programs read their inputs as abi encoded words
+        if self.inputs.len() % 32 != 0 {
+            return Err(crate::PrimitivesError::ProverInputsError(format!(
+                "inputs must be abi encoded 32 byte words, got {} bytes",
+                self.inputs.len()
+            )));
+        }
         Ok(())
     }
 }
@@ -1,11 +1,17 @@
-use alloy::primitives::{address, b256, keccak256, Address, B256};
-use alloy::sol_types::SolValue;
+use std::borrow::Cow;
+
+use alloy::primitives::{address, b256, keccak256, Address, B256, U256};
+use alloy::sol_types::{Eip712Domain, SolValue};
 use lazy_static::lazy_static;
 
 /// permit2 utilities needed for compute intent signing
 pub const PERMIT_TRANSFER_FROM_WITNESS_TYPEHASH_STUB: &str =
     "PermitWitnessTransferFrom(TokenPermissions permitted,address spender,uint256 nonce,uint256 deadline,";
 pub const TOKEN_PERMISSIONS_TYPE_STRING: &str = "TokenPermissions(address token,uint256 amount)";
+pub const PERMIT2_DOMAIN_NAME: &str = "Permit2";
+pub const EIP712_DOMAIN_TYPE_STRING: &str =
+    "EIP712Domain(string name,uint256 chainId,address verifyingContract)";
+/// permit2 domain separator on sepolia, see `permit2_domain_separator` for other chains
 pub const PERMIT2_DOMAIN_SEPARATOR: B256 =
     b256!("94c1dec87927751697bfc9ebf6fc4ca506bed30308b518f0e9d6c5f74bbafdb8");
 pub const PERMIT2_ADDRESS: Address = address!("000000000022D473030F116dDEE9F6B43aC78BA3");
@@ -13,6 +19,33 @@ pub const PERMIT2_ADDRESS: Address = address!("000000000022D473030F116dDEE9F6B43
 lazy_static! {
     pub static ref TOKEN_PERMISSIONS_TYPE_HASH: B256 =
         keccak256(TOKEN_PERMISSIONS_TYPE_STRING.as_bytes());
+    pub static ref EIP712_DOMAIN_TYPE_HASH: B256 = keccak256(EIP712_DOMAIN_TYPE_STRING.as_bytes());
+}
+
+/// compute the permit2 EIP-712 domain separator of the given chain
+#[must_use]
+pub fn permit2_domain_separator(chain_id: u64) -> B256 {
+    let domain_preimage = (
+        *EIP712_DOMAIN_TYPE_HASH,
+        keccak256(PERMIT2_DOMAIN_NAME.as_bytes()),
+        U256::from(chain_id),
+        PERMIT2_ADDRESS,
+    )
+        .abi_encode();
+
+    keccak256(domain_preimage)
+}
+
+/// permit2 EIP-712 domain of the given chain, as found in typed data payloads
+#[must_use]
+pub fn permit2_domain(chain_id: u64) -> Eip712Domain {
+    Eip712Domain::new(
+        Some(Cow::Borrowed(PERMIT2_DOMAIN_NAME)),
+        None,
+        Some(U256::from(chain_id)),
+        Some(PERMIT2_ADDRESS),
+        None,
+    )
 }
 
 #[must_use]
@@ -0,0 +1,162 @@
+//! Per system bounds on the workload an intent may declare, so programs and circuits no provider
+//! could prove within the intent's proving time are turned down before being broadcast.
+
+use serde::{Deserialize, Serialize};
+
+use crate::systems::arkworks::{ArkworksProofParams, R1csHeader};
+use crate::systems::SystemParams;
+use crate::{PrimitivesError, Result};
+
+const MIB: usize = 1024 * 1024;
+const MILLION: u64 = 1_000_000;
+
+/// Resource limits of each system
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceLimits {
+    pub risc0: ElfLimits,
+    pub sp1: ElfLimits,
+    pub arkworks: R1csLimits,
+}
+
+/// Limits of the systems proving the execution of an ELF program
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ElfLimits {
+    pub max_elf_bytes: usize,
+    pub max_input_bytes: usize,
+    /// proving time required for each started MiB of ELF and inputs, in seconds
+    pub proving_seconds_per_mib: u32,
+}
+
+impl Default for ElfLimits {
+    fn default() -> Self {
+        Self {
+            max_elf_bytes: 32 * MIB,
+            max_input_bytes: 8 * MIB,
+            proving_seconds_per_mib: 2,
+        }
+    }
+}
+
+/// Limits of the systems proving a circuit given as an r1cs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct R1csLimits {
+    pub max_r1cs_bytes: usize,
+    pub max_wasm_bytes: usize,
+    /// maximum number of signals declared by the r1cs header
+    pub max_signals: u64,
+    /// maximum size of the JSON inputs
+    pub max_input_bytes: usize,
+    /// proving time required for each started million of constraints, in seconds
+    pub proving_seconds_per_million_constraints: u32,
+}
+
+impl Default for R1csLimits {
+    fn default() -> Self {
+        Self {
+            max_r1cs_bytes: 32 * MIB,
+            max_wasm_bytes: 16 * MIB,
+            max_signals: 10 * MILLION,
+            max_input_bytes: MIB,
+            proving_seconds_per_million_constraints: 10,
+        }
+    }
+}
+
+impl ResourceLimits {
+    /// Check the workload declared by `system` stays within its system's limits and that
+    /// `proving_time` is long enough for it
+    pub fn check(&self, system: &SystemParams, proving_time: u32) -> Result<()> {
+        match system {
+            SystemParams::Risc0(params) => {
+                self.risc0
+                    .check("risc0", params.elf.len(), params.inputs.len(), proving_time)
+            }
+            SystemParams::Sp1(params) => {
+                self.sp1
+                    .check("sp1", params.elf.len(), params.inputs.len(), proving_time)
+            }
+            SystemParams::Arkworks(params) => self.arkworks.check(params, proving_time),
+        }
+    }
+}
+
+impl ElfLimits {
+    fn check(
+        &self,
+        system: &str,
+        elf_bytes: usize,
+        input_bytes: usize,
+        proving_time: u32,
+    ) -> Result<()> {
+        check_max(system, "elf size", elf_bytes, self.max_elf_bytes)?;
+        check_max(system, "inputs size", input_bytes, self.max_input_bytes)?;
+        let workload_mib = (elf_bytes + input_bytes).div_ceil(MIB) as u64;
+        check_proving_time(
+            system,
+            proving_time,
+            workload_mib * u64::from(self.proving_seconds_per_mib),
+        )
+    }
+}
+
+impl R1csLimits {
+    fn check(&self, params: &ArkworksProofParams, proving_time: u32) -> Result<()> {
+        check_max(
+            "arkworks",
+            "r1cs size",
+            params.r1cs.len(),
+            self.max_r1cs_bytes,
+        )?;
+        check_max(
+            "arkworks",
+            "wasm size",
+            params.wasm.len(),
+            self.max_wasm_bytes,
+        )?;
+        let input_bytes = serde_json::to_vec(&params.inputs)
+            .map_err(|e| PrimitivesError::ValidationError(e.to_string()))?
+            .len();
+        check_max("arkworks", "inputs size", input_bytes, self.max_input_bytes)?;
+
+        let header = R1csHeader::parse(&params.r1cs)
+            .map_err(|e| PrimitivesError::ValidationError(e.to_string()))?;
+        check_max(
+            "arkworks",
+            "signal count",
+            u64::from(header.wires),
+            self.max_signals,
+        )?;
+        let workload_millions = u64::from(header.constraints).div_ceil(MILLION);
+        check_proving_time(
+            "arkworks",
+            proving_time,
+            workload_millions * u64::from(self.proving_seconds_per_million_constraints),
+        )
+    }
+}
+
+fn check_max<T: PartialOrd + std::fmt::Display>(
+    system: &str,
+    what: &str,
+    value: T,
+    max: T,
+) -> Result<()> {
+    if value > max {
+        return Err(PrimitivesError::ValidationError(format!(
+            "{system} {what} {value} exceeds the maximum of {max}"
+        )));
+    }
+    Ok(())
+}
+
+fn check_proving_time(system: &str, proving_time: u32, required: u64) -> Result<()> {
+    if u64::from(proving_time) < required {
+        return Err(PrimitivesError::ValidationError(format!(
+            "proving time {proving_time}s is too low for the declared {system} workload, which requires at least {required}s"
+        )));
+    }
+    Ok(())
+}
@@ -1,23 +1,75 @@
 use std::any::Any;
+use std::collections::HashMap;
 use std::fmt::Debug;
 
 use crate::{
     intents::{CommonProofCommitment, ComputeIntent},
-    systems::{System, SystemId, SYSTEMS},
+    markets::SEPOLIA_CHAIN_ID,
+    systems::{System, SystemId, SystemParams, SYSTEMS},
     PrimitivesError, Result,
 };
 use alloy::primitives::{Address, FixedBytes, U256};
+use limits::ResourceLimits;
 use serde::{Deserialize, Serialize};
+use violation::{ValidationRule, Violation};
 
+pub mod limits;
 pub mod offer;
+pub mod offline;
 pub mod registry;
 pub mod request;
+pub mod violation;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BaseValidationConfig {
     pub minimum_proving_time: u32,
     pub maximum_start_delay: u32,
+    /// bounds of `endAuctionTimestamp - startAuctionTimestamp`
+    #[serde(default = "default_minimum_auction_length")]
+    pub minimum_auction_length: u32,
+    #[serde(default = "default_maximum_auction_length")]
+    pub maximum_auction_length: u32,
+    /// bound of `provingTime`, so every intent resolves within a known horizon
+    #[serde(default = "default_maximum_proving_time")]
+    pub maximum_proving_time: u32,
     pub supported_systems: Vec<SystemId>,
+    /// chain the intents must be signed for
+    #[serde(default = "default_chain_id")]
+    pub chain_id: u64,
+    /// bounds of the workload declared by the system of an intent
+    #[serde(default)]
+    pub resource_limits: ResourceLimits,
+    /// time bounds of the intents of a system, when they differ from the ones above
+    #[serde(default)]
+    pub system_overrides: HashMap<SystemId, SystemValidationOverrides>,
+}
+
+/// Time bounds overridden for the intents of a single system, e.g. systems whose proofs take
+/// minutes rather than seconds. Bounds left unset fall back to the ones of the base config.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SystemValidationOverrides {
+    pub minimum_proving_time: Option<u32>,
+    pub maximum_start_delay: Option<u32>,
+    pub minimum_auction_length: Option<u32>,
+    pub maximum_auction_length: Option<u32>,
+    pub maximum_proving_time: Option<u32>,
+}
+
+fn default_chain_id() -> u64 {
+    SEPOLIA_CHAIN_ID
+}
+
+fn default_minimum_auction_length() -> u32 {
+    10 // 10 secs
+}
+
+fn default_maximum_auction_length() -> u32 {
+    86_400 // 1 day
+}
+
+fn default_maximum_proving_time() -> u32 {
+    86_400 // 1 day
 }
 
 impl Default for BaseValidationConfig {
@@ -25,8 +77,40 @@ impl Default for BaseValidationConfig {
         Self {
             minimum_proving_time: 30, // 30 secs,
             maximum_start_delay: 300, // 5 mins
+            minimum_auction_length: default_minimum_auction_length(),
+            maximum_auction_length: default_maximum_auction_length(),
+            maximum_proving_time: default_maximum_proving_time(),
             supported_systems: SYSTEMS.to_vec(),
+            chain_id: SEPOLIA_CHAIN_ID,
+            resource_limits: ResourceLimits::default(),
+            system_overrides: HashMap::new(),
+        }
+    }
+}
+
+impl BaseValidationConfig {
+    /// Config the intents of `system_id` are validated against, its overrides applied
+    #[must_use]
+    pub fn for_system(&self, system_id: SystemId) -> Self {
+        let mut config = self.clone();
+        if let Some(overrides) = self.system_overrides.get(&system_id) {
+            config.minimum_proving_time = overrides
+                .minimum_proving_time
+                .unwrap_or(self.minimum_proving_time);
+            config.maximum_start_delay = overrides
+                .maximum_start_delay
+                .unwrap_or(self.maximum_start_delay);
+            config.minimum_auction_length = overrides
+                .minimum_auction_length
+                .unwrap_or(self.minimum_auction_length);
+            config.maximum_auction_length = overrides
+                .maximum_auction_length
+                .unwrap_or(self.maximum_auction_length);
+            config.maximum_proving_time = overrides
+                .maximum_proving_time
+                .unwrap_or(self.maximum_proving_time);
         }
+        config
     }
 }
 
@@ -34,7 +118,16 @@ impl Default for BaseValidationConfig {
 pub trait CommonValidationConfig: Any {
     fn minimum_proving_time(&self) -> u32;
     fn maximum_start_delay(&self) -> u32;
+    fn minimum_auction_length(&self) -> u32;
+    fn maximum_auction_length(&self) -> u32;
+    fn maximum_proving_time(&self) -> u32;
     fn supported_systems(&self) -> Vec<SystemId>;
+    fn chain_id(&self) -> u64;
+    fn resource_limits(&self) -> &ResourceLimits;
+    /// Config the intents of `system_id` are validated against, its overrides applied
+    fn for_system(&self, system_id: SystemId) -> Self
+    where
+        Self: Sized;
 }
 
 /// Common verifier constraints across all intent types
@@ -55,59 +148,172 @@ pub trait IntentValidator<I: ComputeIntent>: Send + Sync {
     /// Get the verifier constraints
     fn verifier_constraints(&self) -> &Self::VerifierConstraints;
 
-    /// Validate the intent with the given parameters
+    /// Every failed check that doesn't need the chain's state, see `offline::verify_intent_offline`
+    fn offline_violations(
+        &self,
+        intent: &I,
+        latest_timestamp: u64,
+        market_address: &Address,
+    ) -> Vec<Violation>;
+
+    /// Validate the intent with the given parameters, failing with the first failed check
     fn validate(&self, intent: &I, latest_timestamp: u64, market_address: &Address) -> Result<()> {
-        // Full validation logic
-        validate_system(intent, &self.validation_config().supported_systems())?;
-        validate_market_address(intent.proof_commitment().market(), market_address)?;
-        validate_time_constraints(
-            intent.proof_commitment().start_auction_timestamp(),
-            intent.proof_commitment().end_auction_timestamp(),
-            intent.proof_commitment().proving_time(),
-            latest_timestamp,
-            self.validation_config().minimum_proving_time(),
-            self.validation_config().maximum_start_delay(),
-        )?;
-        validate_nonce()?;
-        self.validate_specific(intent)
+        if let Some(violation) = self
+            .offline_violations(intent, latest_timestamp, market_address)
+            .into_iter()
+            .next()
+        {
+            return Err(violation.into());
+        }
+        // checks depending on the chain's state
+        validate_nonce()
     }
 
-    /// Validate intent-specific constraints
-    fn validate_specific(&self, intent: &I) -> Result<()>;
+    /// Run the checks of `validate` but the signature check, returning the reason of every
+    /// failed check instead of stopping at the first one, so an intent can be checked before
+    /// it is signed
+    fn unsigned_violations(
+        &self,
+        intent: &I,
+        latest_timestamp: u64,
+        market_address: &Address,
+    ) -> Vec<String> {
+        self.offline_violations(intent, latest_timestamp, market_address)
+            .into_iter()
+            .filter(|violation| violation.rule != ValidationRule::Signature)
+            .map(|violation| violation.message)
+            .collect()
+    }
 }
 
-pub fn validate_system<I: ComputeIntent>(intent: &I, supported_systems: &[SystemId]) -> Result<()> {
-    if !supported_systems.contains(&intent.system_id()) {
-        return Err(PrimitivesError::ValidationError(
-            "unsupported system".into(),
-        ));
+/// Reason of a failed validation check, without the prefix of its error
+pub fn violation_reason(error: PrimitivesError) -> String {
+    match error {
+        PrimitivesError::ValidationError(reason) => reason,
+        PrimitivesError::ValidationViolation(violation) => violation.message,
+        error => error.to_string(),
     }
+}
+
+/// Failed checks among `results`, ordered as the checks are. Failures that aren't violations of
+/// a known rule are reported under `ValidationRule::Other`.
+pub fn collect_violations(results: impl IntoIterator<Item = Result<()>>) -> Vec<Violation> {
+    results
+        .into_iter()
+        .filter_map(|result| {
+            result.err().map(|error| match error {
+                PrimitivesError::ValidationViolation(violation) => violation,
+                error => Violation::new(ValidationRule::Other, violation_reason(error)),
+            })
+        })
+        .collect()
+}
+
+/// Check the system of an intent is one the market accepts
+pub fn validate_supported_system(
+    system_id: SystemId,
+    supported_systems: &[SystemId],
+) -> Result<()> {
+    if supported_systems.contains(&system_id) {
+        return Ok(());
+    }
+    Err(
+        Violation::new(ValidationRule::UnsupportedSystem, "unsupported system")
+            .observed(system_id.as_str())
+            .allowed(
+                supported_systems
+                    .iter()
+                    .map(SystemId::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )
+            .into(),
+    )
+}
 
-    // Validate that the proving system information matches the system ID
-    if intent.system().system_id() != intent.system_id() {
-        return Err(PrimitivesError::ValidationError(
-            "provided system does not match system id".into(),
-        ));
+/// Check the system params of an intent belong to the system it declares, the intent would
+/// otherwise reach the providers of another system
+pub fn validate_system_id(system_id: SystemId, params_system_id: SystemId) -> Result<()> {
+    if params_system_id == system_id {
+        return Ok(());
     }
+    Err(Violation::new(
+        ValidationRule::SystemMismatch,
+        format!(
+            "system params of {} submitted for system {}",
+            params_system_id.as_str(),
+            system_id.as_str()
+        ),
+    )
+    .observed(params_system_id.as_str())
+    .allowed(system_id.as_str())
+    .into())
+}
 
-    // Validate the proving system specific parameters
-    intent
-        .system()
-        .validate_inputs()
-        .map_err(|e| PrimitivesError::ValidationError(format!("invalid system parameters: {e}")))?;
+/// Check the system specific parameters of an intent
+pub fn validate_system_params(system: &impl System) -> Result<()> {
+    system.validate_inputs().map_err(|e| {
+        Violation::new(
+            ValidationRule::SystemParams,
+            format!("invalid system parameters: {e}"),
+        )
+        .into()
+    })
+}
 
-    Ok(())
+pub fn validate_system<I: ComputeIntent>(intent: &I, supported_systems: &[SystemId]) -> Result<()> {
+    validate_supported_system(intent.system_id(), supported_systems)?;
+    validate_system_id(intent.system_id(), intent.system().system_id())?;
+    validate_system_params(intent.system())
+}
+
+/// Check the workload declared by the system of an intent stays within the limits of its system.
+/// Encrypted systems can't be inspected and are left unchecked.
+pub fn validate_resource_limits<I: ComputeIntent>(
+    intent: &I,
+    resource_limits: &ResourceLimits,
+) -> Result<()> {
+    match intent.system().system_params() {
+        Some(system) => validate_system_resources(
+            system,
+            intent.proof_commitment().proving_time(),
+            resource_limits,
+        ),
+        None => Ok(()),
+    }
+}
+
+/// Check the workload declared by `system` stays within the limits of its system
+pub fn validate_system_resources(
+    system: &SystemParams,
+    proving_time: u32,
+    resource_limits: &ResourceLimits,
+) -> Result<()> {
+    resource_limits
+        .check(system, proving_time)
+        .map_err(|e| match e {
+            PrimitivesError::ValidationError(reason) => {
+                Violation::new(ValidationRule::ResourceLimits, reason).into()
+            }
+            e => e,
+        })
 }
 
 pub fn validate_market_address(market: &Address, expected_market: &Address) -> Result<()> {
     if market != expected_market {
-        return Err(PrimitivesError::ValidationError(
-            "invalid market address".into(),
-        ));
+        return Err(
+            Violation::new(ValidationRule::MarketAddress, "invalid market address")
+                .observed(market)
+                .allowed(expected_market)
+                .into(),
+        );
     }
     Ok(())
 }
 
+/// Check the auction window and proving deadline of an intent are well formed and open at
+/// `latest_timestamp`
+#[allow(clippy::too_many_arguments)]
 pub fn validate_time_constraints(
     start_auction_timestamp: u64,
     end_auction_timestamp: u64,
@@ -115,22 +321,97 @@ pub fn validate_time_constraints(
     latest_timestamp: u64,
     min_proving_time: u32,
     max_start_delay: u32,
+    min_auction_length: u32,
+    max_auction_length: u32,
+    max_proving_time: u32,
 ) -> Result<()> {
-    if latest_timestamp < start_auction_timestamp.saturating_sub(u64::from(max_start_delay))
-        || latest_timestamp >= end_auction_timestamp
+    if end_auction_timestamp <= start_auction_timestamp {
+        return Err(Violation::new(
+            ValidationRule::AuctionWindow,
+            format!("end auction timestamp {end_auction_timestamp} is not after start auction timestamp {start_auction_timestamp}"),
+        )
+        .observed(end_auction_timestamp)
+        .allowed(format!("> {start_auction_timestamp}"))
+        .into());
+    }
+
+    let auction_length = end_auction_timestamp - start_auction_timestamp;
+    if auction_length < u64::from(min_auction_length)
+        || auction_length > u64::from(max_auction_length)
     {
-        return Err(PrimitivesError::ValidationError("invalid timestamp".into()));
+        return Err(Violation::new(
+            ValidationRule::AuctionLength,
+            format!("auction length {auction_length}s is outside of [{min_auction_length}s, {max_auction_length}s]"),
+        )
+        .observed(auction_length)
+        .allowed(format!("{min_auction_length}..={max_auction_length}"))
+        .into());
+    }
+
+    if end_auction_timestamp
+        .checked_add(u64::from(proving_time))
+        .is_none()
+    {
+        return Err(Violation::new(
+            ValidationRule::ProvingDeadline,
+            "proving deadline overflows",
+        )
+        .observed(proving_time)
+        .allowed(format!("<= {}", u64::MAX - end_auction_timestamp))
+        .into());
+    }
+
+    let earliest_timestamp = start_auction_timestamp.saturating_sub(u64::from(max_start_delay));
+    if latest_timestamp < earliest_timestamp || latest_timestamp >= end_auction_timestamp {
+        return Err(
+            Violation::new(ValidationRule::TimestampWindow, "invalid timestamp")
+                .observed(latest_timestamp)
+                .allowed(format!("{earliest_timestamp}..{end_auction_timestamp}"))
+                .into(),
+        );
     }
 
     if proving_time < min_proving_time {
-        return Err(PrimitivesError::ValidationError(
-            "proving time too low".into(),
-        ));
+        return Err(
+            Violation::new(ValidationRule::ProvingTime, "proving time too low")
+                .observed(proving_time)
+                .allowed(format!(">= {min_proving_time}"))
+                .into(),
+        );
+    }
+
+    if proving_time > max_proving_time {
+        return Err(
+            Violation::new(ValidationRule::ProvingTime, "proving time too high")
+                .observed(proving_time)
+                .allowed(format!("<= {max_proving_time}"))
+                .into(),
+        );
     }
 
     Ok(())
 }
 
+/// Check the auction window and proving deadline of a proof commitment against the time bounds
+/// of `config`, see `validate_time_constraints`
+pub fn validate_commitment_time_constraints(
+    proof_commitment: &impl CommonProofCommitment,
+    latest_timestamp: u64,
+    config: &BaseValidationConfig,
+) -> Result<()> {
+    validate_time_constraints(
+        proof_commitment.start_auction_timestamp(),
+        proof_commitment.end_auction_timestamp(),
+        proof_commitment.proving_time(),
+        latest_timestamp,
+        config.minimum_proving_time,
+        config.maximum_start_delay,
+        config.minimum_auction_length,
+        config.maximum_auction_length,
+        config.maximum_proving_time,
+    )
+}
+
 pub fn validate_nonce() -> Result<()> {
     // TODO: Implement nonce validation logic
     Ok(())
@@ -1,11 +1,15 @@
 use alloy::primitives::{Address, FixedBytes, PrimitiveSignature, U256};
-use alloy::sol_types::SolValue;
 use serde::{Deserialize, Serialize};
 
+use super::limits::ResourceLimits;
+use super::offline::{verify_intent_offline, OfflineVerifiable};
+use super::violation::{ValidationRule, Violation};
 use super::{
+    collect_violations, validate_commitment_time_constraints, validate_supported_system,
     BaseValidationConfig, CommonValidationConfig, CommonVerifierConstraints, IntentValidator,
 };
-use crate::abi::universal_porchetta::ProofOfferVerifierDetails;
+use crate::abi::verifier_details::{decode_offer_verifier_details, DecodeMode};
+use crate::commitment::{check_inputs_commitment, submission_field, CommitmentHasher};
 use crate::intents::offer::compute_offer_permit2_digest;
 use crate::Result;
 use crate::{
@@ -59,9 +63,37 @@ impl CommonValidationConfig for OfferValidationConfig {
         self.base.maximum_start_delay
     }
 
+    fn minimum_auction_length(&self) -> u32 {
+        self.base.minimum_auction_length
+    }
+
+    fn maximum_auction_length(&self) -> u32 {
+        self.base.maximum_auction_length
+    }
+
+    fn maximum_proving_time(&self) -> u32 {
+        self.base.maximum_proving_time
+    }
+
     fn supported_systems(&self) -> Vec<SystemId> {
         self.base.supported_systems.clone()
     }
+
+    fn chain_id(&self) -> u64 {
+        self.base.chain_id
+    }
+
+    fn resource_limits(&self) -> &ResourceLimits {
+        &self.base.resource_limits
+    }
+
+    fn for_system(&self, system_id: SystemId) -> Self {
+        Self {
+            base: self.base.for_system(system_id),
+            maximum_allowed_reward: self.maximum_allowed_reward,
+            minimum_allowed_stake: self.minimum_allowed_stake,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -95,11 +127,69 @@ impl<S: System> IntentValidator<ComputeOffer<S>> for ComputeOfferValidator {
         &self.verifier_constraints
     }
 
-    fn validate_specific(&self, offer: &ComputeOffer<S>) -> Result<()> {
-        validate_offer(offer, &self.validation_config, &self.verifier_constraints)
+    fn offline_violations(
+        &self,
+        offer: &ComputeOffer<S>,
+        latest_timestamp: u64,
+        market_address: &Address,
+    ) -> Vec<Violation> {
+        verify_intent_offline(
+            offer,
+            latest_timestamp,
+            market_address,
+            &self.validation_config,
+            &self.verifier_constraints,
+        )
     }
 }
 
+impl<S: System> OfflineVerifiable for ComputeOffer<S> {
+    type ValidationConfig = OfferValidationConfig;
+    type VerifierConstraints = OfferVerifierConstraints;
+
+    fn commitment_violations(
+        &self,
+        latest_timestamp: u64,
+        config: &OfferValidationConfig,
+    ) -> Vec<Violation> {
+        offer_commitment_violations(
+            &self.proof_offer,
+            &self.signature,
+            self.system_id,
+            latest_timestamp,
+            config,
+        )
+    }
+
+    fn verifier_violations(&self, constraints: &OfferVerifierConstraints) -> Vec<Violation> {
+        collect_violations([validate_offer_verifier_details(
+            &self.proof_offer,
+            constraints,
+        )])
+    }
+}
+
+/// Failed checks of a signed proof offer of `system_id`, against `config` as overridden for its
+/// system
+pub fn offer_commitment_violations(
+    proof_offer: &ProofOffer,
+    signature: &PrimitiveSignature,
+    system_id: SystemId,
+    latest_timestamp: u64,
+    config: &OfferValidationConfig,
+) -> Vec<Violation> {
+    collect_violations([
+        validate_supported_system(system_id, &config.base.supported_systems),
+        validate_commitment_time_constraints(proof_offer, latest_timestamp, &config.base),
+        validate_offer_amount_constraints(
+            proof_offer,
+            config.maximum_allowed_reward,
+            config.minimum_allowed_stake,
+        ),
+        validate_offer_signature(proof_offer, signature, config.base.chain_id),
+    ])
+}
+
 /// `ComputeOffer` specific validation
 pub fn validate_offer<S: System>(
     offer: &ComputeOffer<S>,
@@ -107,7 +197,7 @@ pub fn validate_offer<S: System>(
     verifier_constraints: &OfferVerifierConstraints,
 ) -> Result<()> {
     // Offer-specific validation logic
-    validate_offer_signature(&offer.proof_offer, &offer.signature)?;
+    validate_offer_signature(&offer.proof_offer, &offer.signature, config.base.chain_id)?;
     validate_offer_amount_constraints(
         &offer.proof_offer,
         config.maximum_allowed_reward,
@@ -123,13 +213,19 @@ pub fn validate_offer_amount_constraints(
     minimum_allowed_stake: U256,
 ) -> Result<()> {
     if proof_offer.rewardAmount > maximum_allowed_reward {
-        Err(PrimitivesError::ValidationError(
-            "token reward amount invalid".to_string(),
-        ))
+        Err(
+            Violation::new(ValidationRule::RewardBound, "token reward amount invalid")
+                .observed(proof_offer.rewardAmount)
+                .allowed(format!("<= {maximum_allowed_reward}"))
+                .into(),
+        )
     } else if proof_offer.stakeAmount < minimum_allowed_stake {
-        Err(PrimitivesError::ValidationError(
-            "token stake amount invalid".to_string(),
-        ))
+        Err(
+            Violation::new(ValidationRule::StakeBound, "token stake amount invalid")
+                .observed(proof_offer.stakeAmount)
+                .allowed(format!(">= {minimum_allowed_stake}"))
+                .into(),
+        )
     } else {
         Ok(())
     }
@@ -140,73 +236,127 @@ pub fn validate_offer_verifier_details(
     verifier_constraints: &OfferVerifierConstraints,
 ) -> Result<()> {
     // Decode and validate verifier details structure from the intent
-    let verifier_details = ProofOfferVerifierDetails::abi_decode(&proof_offer.extraData, true)
-        .map_err(|e| {
-            PrimitivesError::ValidationError(format!("failed to decode VerifierDetails: {e}"))
-        })?;
+    let verifier_details =
+        decode_offer_verifier_details(&proof_offer.extraData, DecodeMode::Lenient)
+            .map_err(|e| Violation::new(ValidationRule::VerifierDetails, e.to_string()))?;
 
     // Check each constraint only if it's set
     if let Some(expected_verifier) = verifier_constraints.verifier {
         if verifier_details.verifier != expected_verifier {
-            return Err(PrimitivesError::ValidationError(
-                "verifier address does not match constraints".to_string(),
-            ));
+            return Err(Violation::new(
+                ValidationRule::VerifierConstraints,
+                "verifier address does not match constraints",
+            )
+            .observed(verifier_details.verifier)
+            .allowed(expected_verifier)
+            .into());
         }
     }
 
     if let Some(expected_selector) = verifier_constraints.selector {
         if verifier_details.selector != expected_selector {
-            return Err(PrimitivesError::ValidationError(
-                "verifier selector does not match constraints".to_string(),
-            ));
+            return Err(Violation::new(
+                ValidationRule::VerifierConstraints,
+                "verifier selector does not match constraints",
+            )
+            .observed(verifier_details.selector)
+            .allowed(expected_selector)
+            .into());
         }
     }
 
     if let Some(expected_is_sha_commitment) = verifier_constraints.is_sha_commitment {
         if verifier_details.isShaCommitment != expected_is_sha_commitment {
-            return Err(PrimitivesError::ValidationError(
-                "isShaCommitment flag does not match constraints".to_string(),
-            ));
+            return Err(Violation::new(
+                ValidationRule::VerifierConstraints,
+                "isShaCommitment flag does not match constraints",
+            )
+            .observed(verifier_details.isShaCommitment)
+            .allowed(expected_is_sha_commitment)
+            .into());
         }
     }
 
     if let Some(expected_inputs_offset) = verifier_constraints.inputs_offset {
         if verifier_details.inputsOffset != expected_inputs_offset {
-            return Err(PrimitivesError::ValidationError(
-                "inputs offset does not match constraints".to_string(),
-            ));
+            return Err(Violation::new(
+                ValidationRule::VerifierConstraints,
+                "inputs offset does not match constraints",
+            )
+            .observed(verifier_details.inputsOffset)
+            .allowed(expected_inputs_offset)
+            .into());
         }
     }
 
     if let Some(expected_inputs_length) = verifier_constraints.inputs_length {
         if verifier_details.inputsLength != expected_inputs_length {
-            return Err(PrimitivesError::ValidationError(
-                "inputs length does not match constraints".to_string(),
-            ));
+            return Err(Violation::new(
+                ValidationRule::VerifierConstraints,
+                "inputs length does not match constraints",
+            )
+            .observed(verifier_details.inputsLength)
+            .allowed(expected_inputs_length)
+            .into());
         }
     }
 
     Ok(())
 }
 
+/// Check the offer's inputs commitment against the preimage it commits to, using the hash
+/// function declared by its verifier details
+pub fn validate_offer_inputs_commitment(proof_offer: &ProofOffer, preimage: &[u8]) -> Result<()> {
+    let hasher = CommitmentHasher::from_offer_extra_data(&proof_offer.extraData)?;
+    check_inputs_commitment(hasher, proof_offer.inputsCommitment, preimage)
+}
+
+/// Run the commitment check the market runs on resolve against an opaque submission
+pub fn validate_offer_submission(proof_offer: &ProofOffer, opaque_submission: &[u8]) -> Result<()> {
+    let verifier_details =
+        decode_offer_verifier_details(&proof_offer.extraData, DecodeMode::Lenient)
+            .map_err(|e| PrimitivesError::CommitmentError(e.to_string()))?;
+    if verifier_details.inputsLength.is_zero() {
+        return Ok(());
+    }
+    let inputs = submission_field(
+        opaque_submission,
+        verifier_details.inputsOffset,
+        verifier_details.inputsLength,
+    )?;
+    check_inputs_commitment(
+        CommitmentHasher::from_is_sha_commitment(verifier_details.isShaCommitment),
+        proof_offer.inputsCommitment,
+        inputs,
+    )
+}
+
 pub fn validate_offer_signature(
     proof_offer: &ProofOffer,
     signature: &PrimitiveSignature,
+    chain_id: u64,
 ) -> Result<()> {
-    // compute permit digest
-    let computed_digest = compute_offer_permit2_digest(proof_offer);
+    // compute permit digest under the permit2 domain of the expected chain, an intent signed
+    // for another chain recovers to a different signer
+    let computed_digest = compute_offer_permit2_digest(proof_offer, chain_id);
     // ec recover signing public key
     let computed_verifying_key = signature
         .recover_from_prehash(&computed_digest)
-        .map_err(|e| PrimitivesError::ValidationError(format!("ec recover failed: {e}")))?;
+        .map_err(|e| {
+            Violation::new(ValidationRule::Signature, format!("ec recover failed: {e}"))
+        })?;
     let computed_signer = Address::from_public_key(&computed_verifying_key);
 
     // check signature validity
     if computed_signer == proof_offer.signer {
         Ok(())
     } else {
-        Err(PrimitivesError::ValidationError(
-            "signature invalid: computed signer != request.signer".to_string(),
-        ))
+        Err(Violation::new(
+            ValidationRule::Signature,
+            "signature invalid: computed signer != request.signer",
+        )
+        .observed(computed_signer)
+        .allowed(proof_offer.signer)
+        .into())
     }
 }
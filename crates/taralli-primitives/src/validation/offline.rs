@@ -0,0 +1,63 @@
+//! Verification of an intent without a server or an rpc, given the timestamp of the latest block,
+//! e.g. for wallets and explorers telling whether an intent is currently valid. The validators of
+//! the server and the client run these same checks, layering the ones depending on the chain's
+//! state (nonce, code of the verifier) on top.
+
+use alloy::primitives::Address;
+
+use crate::intents::{CommonProofCommitment, ComputeIntent};
+use crate::systems::System;
+
+use super::violation::Violation;
+use super::{
+    collect_violations, validate_market_address, validate_resource_limits, validate_system_id,
+    validate_system_params, CommonValidationConfig, CommonVerifierConstraints,
+};
+
+/// Intent whose checks can all be run offline
+pub trait OfflineVerifiable: ComputeIntent {
+    type ValidationConfig: CommonValidationConfig;
+    type VerifierConstraints: CommonVerifierConstraints;
+
+    /// Failed checks of the signed proof commitment and its system id alone, the ones the server
+    /// runs before the system params are decompressed. `config` is the config of the intent's
+    /// system.
+    fn commitment_violations(
+        &self,
+        latest_timestamp: u64,
+        config: &Self::ValidationConfig,
+    ) -> Vec<Violation>;
+
+    /// Failed checks of the verifier details against `constraints`
+    fn verifier_violations(&self, constraints: &Self::VerifierConstraints) -> Vec<Violation>;
+}
+
+/// Every failed check of `intent` that doesn't need the chain's state, against the bounds of its
+/// system at `latest_timestamp`: signature, system params, time constraints, amount constraints
+/// and verifier details. An intent is valid when none fails.
+pub fn verify_intent_offline<I: OfflineVerifiable>(
+    intent: &I,
+    latest_timestamp: u64,
+    market_address: &Address,
+    config: &I::ValidationConfig,
+    constraints: &I::VerifierConstraints,
+) -> Vec<Violation> {
+    let config = config.for_system(intent.system_id());
+    let mut violations = intent.commitment_violations(latest_timestamp, &config);
+    violations.extend(collect_violations([
+        validate_system_id(intent.system_id(), intent.system().system_id()),
+        validate_system_params(intent.system()),
+        validate_resource_limits(intent, config.resource_limits()),
+        validate_market_address(intent.proof_commitment().market(), market_address),
+    ]));
+    violations.extend(intent.verifier_violations(constraints));
+
+    // the verifier details are decoded by more than one check, failing them all the same way
+    let mut unique = Vec::with_capacity(violations.len());
+    for violation in violations {
+        if !unique.contains(&violation) {
+            unique.push(violation);
+        }
+    }
+    unique
+}
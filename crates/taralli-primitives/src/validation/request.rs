@@ -1,8 +1,16 @@
+use std::collections::HashMap;
+
 use alloy::primitives::{Address, FixedBytes, PrimitiveSignature, B256, U256};
-use alloy::sol_types::SolValue;
 use serde::{Deserialize, Serialize};
 
+use crate::abi::extensions::{
+    decode_request_extensions, decode_request_verifier_alternatives, MAX_URGENCY,
+};
 use crate::abi::universal_bombetta::ProofRequestVerifierDetails;
+use crate::abi::verifier_details::decode_verifier_details;
+use crate::commitment::{
+    check_inputs_commitment, submission_field, CommitmentHasher, PartialCommitmentCheck,
+};
 use crate::intents::request::compute_request_permit2_digest;
 use crate::Result;
 use crate::{
@@ -12,7 +20,11 @@ use crate::{
     PrimitivesError,
 };
 
+use super::limits::ResourceLimits;
+use super::offline::{verify_intent_offline, OfflineVerifiable};
+use super::violation::{ValidationRule, Violation};
 use super::{
+    collect_violations, validate_commitment_time_constraints, validate_supported_system,
     BaseValidationConfig, CommonValidationConfig, CommonVerifierConstraints, IntentValidator,
 };
 
@@ -52,6 +64,9 @@ impl CommonVerifierConstraints for RequestVerifierConstraints {
 pub struct RequestValidationConfig {
     pub base: BaseValidationConfig,
     pub maximum_allowed_stake: u128,
+    /// maximum allowed stake of the requests of a system, when it differs from the one above
+    #[serde(default)]
+    pub system_maximum_allowed_stake: HashMap<SystemId, u128>,
 }
 
 impl CommonValidationConfig for RequestValidationConfig {
@@ -63,9 +78,41 @@ impl CommonValidationConfig for RequestValidationConfig {
         self.base.maximum_start_delay
     }
 
+    fn minimum_auction_length(&self) -> u32 {
+        self.base.minimum_auction_length
+    }
+
+    fn maximum_auction_length(&self) -> u32 {
+        self.base.maximum_auction_length
+    }
+
+    fn maximum_proving_time(&self) -> u32 {
+        self.base.maximum_proving_time
+    }
+
     fn supported_systems(&self) -> Vec<SystemId> {
         self.base.supported_systems.clone()
     }
+
+    fn chain_id(&self) -> u64 {
+        self.base.chain_id
+    }
+
+    fn resource_limits(&self) -> &ResourceLimits {
+        &self.base.resource_limits
+    }
+
+    fn for_system(&self, system_id: SystemId) -> Self {
+        Self {
+            base: self.base.for_system(system_id),
+            maximum_allowed_stake: self
+                .system_maximum_allowed_stake
+                .get(&system_id)
+                .copied()
+                .unwrap_or(self.maximum_allowed_stake),
+            system_maximum_allowed_stake: self.system_maximum_allowed_stake.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -99,11 +146,66 @@ impl<S: System> IntentValidator<ComputeRequest<S>> for ComputeRequestValidator {
         &self.verifier_constraints
     }
 
-    fn validate_specific(&self, request: &ComputeRequest<S>) -> Result<()> {
-        validate_request(request, &self.validation_config, &self.verifier_constraints)
+    fn offline_violations(
+        &self,
+        request: &ComputeRequest<S>,
+        latest_timestamp: u64,
+        market_address: &Address,
+    ) -> Vec<Violation> {
+        verify_intent_offline(
+            request,
+            latest_timestamp,
+            market_address,
+            &self.validation_config,
+            &self.verifier_constraints,
+        )
     }
 }
 
+impl<S: System> OfflineVerifiable for ComputeRequest<S> {
+    type ValidationConfig = RequestValidationConfig;
+    type VerifierConstraints = RequestVerifierConstraints;
+
+    fn commitment_violations(
+        &self,
+        latest_timestamp: u64,
+        config: &RequestValidationConfig,
+    ) -> Vec<Violation> {
+        request_commitment_violations(
+            &self.proof_request,
+            &self.signature,
+            self.system_id,
+            latest_timestamp,
+            config,
+        )
+    }
+
+    fn verifier_violations(&self, constraints: &RequestVerifierConstraints) -> Vec<Violation> {
+        collect_violations([validate_request_verifier_details(
+            &self.proof_request,
+            constraints,
+        )])
+    }
+}
+
+/// Failed checks of a signed proof request of `system_id`, against `config` as overridden for
+/// its system
+pub fn request_commitment_violations(
+    proof_request: &ProofRequest,
+    signature: &PrimitiveSignature,
+    system_id: SystemId,
+    latest_timestamp: u64,
+    config: &RequestValidationConfig,
+) -> Vec<Violation> {
+    collect_violations([
+        validate_supported_system(system_id, &config.base.supported_systems),
+        validate_commitment_time_constraints(proof_request, latest_timestamp, &config.base),
+        validate_request_amount_constraints(proof_request, config.maximum_allowed_stake),
+        validate_request_extensions(proof_request),
+        validate_request_signature(proof_request, signature, config.base.chain_id),
+    ])
+}
+
 /// `ComputeRequest` specific validation
 pub fn validate_request<S: System>(
     request: &ComputeRequest<S>,
@@ -111,12 +213,17 @@ pub fn validate_request<S: System>(
     verifier_constraints: &RequestVerifierConstraints,
 ) -> Result<()> {
     // Request-specific validation logic
-    validate_request_signature(&request.proof_request, &request.signature)?;
+    validate_request_signature(
+        &request.proof_request,
+        &request.signature,
+        validation_config.base.chain_id,
+    )?;
     validate_request_amount_constraints(
         &request.proof_request,
         validation_config.maximum_allowed_stake,
     )?;
     validate_request_verifier_details(&request.proof_request, verifier_constraints)?;
+    validate_request_extensions(&request.proof_request)?;
     Ok(())
 }
 
@@ -125,122 +232,233 @@ pub fn validate_request_amount_constraints(
     maximum_allowed_stake: u128,
 ) -> Result<()> {
     if proof_request.maxRewardAmount < proof_request.minRewardAmount {
-        Err(PrimitivesError::ValidationError(
-            "reward token amounts invalid".to_string(),
-        ))
+        Err(Violation::new(
+            ValidationRule::RewardAmounts,
+            "reward token amounts invalid",
+        )
+        .observed(proof_request.maxRewardAmount)
+        .allowed(format!(">= {}", proof_request.minRewardAmount))
+        .into())
     } else if proof_request.minimumStake > maximum_allowed_stake {
-        Err(PrimitivesError::ValidationError(
-            "eth stake amount invalid".to_string(),
-        ))
+        Err(
+            Violation::new(ValidationRule::StakeBound, "eth stake amount invalid")
+                .observed(proof_request.minimumStake)
+                .allowed(format!("<= {maximum_allowed_stake}"))
+                .into(),
+        )
     } else {
         Ok(())
     }
 }
 
+/// Check the verifier details of the request against the constraints. The markets resolve
+/// against the leading verifier details, so they are held to the constraints whatever the
+/// alternatives the request carries, which only have to decode.
 pub fn validate_request_verifier_details(
     proof_request: &ProofRequest,
     verifier_constraints: &RequestVerifierConstraints,
 ) -> Result<()> {
     // Decode and validate verifier details structure from the intent
-    let verifier_details = ProofRequestVerifierDetails::abi_decode(&proof_request.extraData, true)
-        .map_err(|e| {
-            PrimitivesError::ValidationError(format!("failed to decode VerifierDetails: {e}"))
-        })?;
+    let alternatives = decode_request_verifier_alternatives(&proof_request.extraData)
+        .map_err(|e| Violation::new(ValidationRule::VerifierDetails, e.to_string()))?;
+    check_verifier_constraints(&alternatives[0], verifier_constraints)
+}
 
+fn check_verifier_constraints(
+    verifier_details: &ProofRequestVerifierDetails,
+    verifier_constraints: &RequestVerifierConstraints,
+) -> Result<()> {
     // Check each constraint only if it's set
     if let Some(expected_verifier) = verifier_constraints.verifier {
         if verifier_details.verifier != expected_verifier {
-            return Err(PrimitivesError::ValidationError(
-                "verifier address does not match constraints".to_string(),
-            ));
+            return Err(Violation::new(
+                ValidationRule::VerifierConstraints,
+                "verifier address does not match constraints",
+            )
+            .observed(verifier_details.verifier)
+            .allowed(expected_verifier)
+            .into());
         }
     }
 
     if let Some(expected_selector) = verifier_constraints.selector {
         if verifier_details.selector != expected_selector {
-            return Err(PrimitivesError::ValidationError(
-                "verifier selector does not match constraints".to_string(),
-            ));
+            return Err(Violation::new(
+                ValidationRule::VerifierConstraints,
+                "verifier selector does not match constraints",
+            )
+            .observed(verifier_details.selector)
+            .allowed(expected_selector)
+            .into());
         }
     }
 
     if let Some(expected_is_sha_commitment) = verifier_constraints.is_sha_commitment {
         if verifier_details.isShaCommitment != expected_is_sha_commitment {
-            return Err(PrimitivesError::ValidationError(
-                "isShaCommitment flag does not match constraints".to_string(),
-            ));
+            return Err(Violation::new(
+                ValidationRule::VerifierConstraints,
+                "isShaCommitment flag does not match constraints",
+            )
+            .observed(verifier_details.isShaCommitment)
+            .allowed(expected_is_sha_commitment)
+            .into());
         }
     }
 
     if let Some(expected_inputs_offset) = verifier_constraints.inputs_offset {
         if verifier_details.inputsOffset != expected_inputs_offset {
-            return Err(PrimitivesError::ValidationError(
-                "inputs offset does not match constraints".to_string(),
-            ));
+            return Err(Violation::new(
+                ValidationRule::VerifierConstraints,
+                "inputs offset does not match constraints",
+            )
+            .observed(verifier_details.inputsOffset)
+            .allowed(expected_inputs_offset)
+            .into());
         }
     }
 
     if let Some(expected_inputs_length) = verifier_constraints.inputs_length {
         if verifier_details.inputsLength != expected_inputs_length {
-            return Err(PrimitivesError::ValidationError(
-                "inputs length does not match constraints".to_string(),
-            ));
+            return Err(Violation::new(
+                ValidationRule::VerifierConstraints,
+                "inputs length does not match constraints",
+            )
+            .observed(verifier_details.inputsLength)
+            .allowed(expected_inputs_length)
+            .into());
         }
     }
 
     if let Some(expected_has_partial) = verifier_constraints.has_partial_commitment_result_check {
         if verifier_details.hasPartialCommitmentResultCheck != expected_has_partial {
-            return Err(PrimitivesError::ValidationError(
-                "hasPartialCommitmentResultCheck flag does not match constraints".to_string(),
-            ));
+            return Err(Violation::new(
+                ValidationRule::VerifierConstraints,
+                "hasPartialCommitmentResultCheck flag does not match constraints",
+            )
+            .observed(verifier_details.hasPartialCommitmentResultCheck)
+            .allowed(expected_has_partial)
+            .into());
         }
     }
 
     if let Some(expected_offset) = verifier_constraints.submitted_partial_commitment_result_offset {
         if verifier_details.submittedPartialCommitmentResultOffset != expected_offset {
-            return Err(PrimitivesError::ValidationError(
-                "submittedPartialCommitmentResultOffset does not match constraints".to_string(),
-            ));
+            return Err(Violation::new(
+                ValidationRule::VerifierConstraints,
+                "submittedPartialCommitmentResultOffset does not match constraints",
+            )
+            .observed(verifier_details.submittedPartialCommitmentResultOffset)
+            .allowed(expected_offset)
+            .into());
         }
     }
 
     if let Some(expected_length) = verifier_constraints.submitted_partial_commitment_result_length {
         if verifier_details.submittedPartialCommitmentResultLength != expected_length {
-            return Err(PrimitivesError::ValidationError(
-                "submittedPartialCommitmentResultLength does not match constraints".to_string(),
-            ));
+            return Err(Violation::new(
+                ValidationRule::VerifierConstraints,
+                "submittedPartialCommitmentResultLength does not match constraints",
+            )
+            .observed(verifier_details.submittedPartialCommitmentResultLength)
+            .allowed(expected_length)
+            .into());
         }
     }
 
     if let Some(expected_commitment) = verifier_constraints.predetermined_partial_commitment {
         if verifier_details.predeterminedPartialCommitment != expected_commitment {
-            return Err(PrimitivesError::ValidationError(
-                "predeterminedPartialCommitment does not match constraints".to_string(),
-            ));
+            return Err(Violation::new(
+                ValidationRule::VerifierConstraints,
+                "predeterminedPartialCommitment does not match constraints",
+            )
+            .observed(verifier_details.predeterminedPartialCommitment)
+            .allowed(expected_commitment)
+            .into());
         }
     }
 
     Ok(())
 }
 
+/// Check the extensions following the verifier details decode, and the urgency they signal is
+/// within bounds
+pub fn validate_request_extensions(proof_request: &ProofRequest) -> Result<()> {
+    let extensions = decode_request_extensions(&proof_request.extraData)
+        .map_err(|e| Violation::new(ValidationRule::VerifierDetails, e.to_string()))?;
+    match extensions.urgency {
+        Some(urgency) if urgency > MAX_URGENCY => Err(Violation::new(
+            ValidationRule::Urgency,
+            "urgency level out of bounds",
+        )
+        .observed(urgency)
+        .allowed(format!("<= {MAX_URGENCY}"))
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+/// Check the request's inputs commitment against the preimage it commits to, using the hash
+/// function declared by its verifier details
+pub fn validate_request_inputs_commitment(
+    proof_request: &ProofRequest,
+    preimage: &[u8],
+) -> Result<()> {
+    let hasher = CommitmentHasher::from_request_extra_data(&proof_request.extraData)?;
+    check_inputs_commitment(hasher, proof_request.inputsCommitment, preimage)
+}
+
+/// Run the commitment checks the market runs on resolve against an opaque submission, so a
+/// submission that would fail them isn't sent on-chain
+pub fn validate_request_submission(
+    proof_request: &ProofRequest,
+    opaque_submission: &[u8],
+    submitted_partial_commitment: B256,
+) -> Result<()> {
+    let verifier_details = decode_verifier_details(&proof_request.extraData)
+        .map_err(|e| PrimitivesError::CommitmentError(e.to_string()))?;
+    if verifier_details.inputsLength.is_zero() {
+        return Ok(());
+    }
+    let hasher = CommitmentHasher::from(&verifier_details);
+
+    if let Some(check) = PartialCommitmentCheck::from_verifier_details(&verifier_details) {
+        check.verify(opaque_submission, submitted_partial_commitment)?;
+    }
+
+    let inputs = submission_field(
+        opaque_submission,
+        verifier_details.inputsOffset,
+        verifier_details.inputsLength,
+    )?;
+    check_inputs_commitment(hasher, proof_request.inputsCommitment, inputs)
+}
+
 pub fn validate_request_signature(
     proof_request: &ProofRequest,
     signature: &PrimitiveSignature,
+    chain_id: u64,
 ) -> Result<()> {
-    // compute permit digest
-    let computed_digest = compute_request_permit2_digest(proof_request);
+    // compute permit digest under the permit2 domain of the expected chain, an intent signed
+    // for another chain recovers to a different signer
+    let computed_digest = compute_request_permit2_digest(proof_request, chain_id);
     // ec recover signing public key
     let computed_verifying_key = signature
         .recover_from_prehash(&computed_digest)
-        .map_err(|e| PrimitivesError::ValidationError(format!("ec recover failed: {e}")))?;
+        .map_err(|e| {
+            Violation::new(ValidationRule::Signature, format!("ec recover failed: {e}"))
+        })?;
     let computed_signer = Address::from_public_key(&computed_verifying_key);
 
     // check signature validity
     if computed_signer == proof_request.signer {
         Ok(())
     } else {
-        Err(PrimitivesError::ValidationError(
-            "signature invalid: computed signer != request.signer".to_string(),
-        ))
+        Err(Violation::new(
+            ValidationRule::Signature,
+            "signature invalid: computed signer != request.signer",
+        )
+        .observed(computed_signer)
+        .allowed(proof_request.signer)
+        .into())
     }
 }
@@ -0,0 +1,74 @@
+//! Machine readable description of a failed validation check, answered by the server alongside
+//! the message of a rejection so scripted submitters can tell which rule failed and by how much.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::PrimitivesError;
+
+/// Validation rule an intent can break
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationRule {
+    UnsupportedSystem,
+    SystemMismatch,
+    SystemParams,
+    ResourceLimits,
+    MarketAddress,
+    AuctionWindow,
+    AuctionLength,
+    ProvingDeadline,
+    TimestampWindow,
+    ProvingTime,
+    Signature,
+    RewardAmounts,
+    RewardBound,
+    StakeBound,
+    VerifierDetails,
+    VerifierConstraints,
+    /// urgency signaled through the `extraData` extensions
+    Urgency,
+    /// a rule unknown to this version, e.g. added by a newer server
+    #[serde(other)]
+    Other,
+}
+
+/// A failed validation check, with the value observed in the intent and the values the rule
+/// allows when they can be told
+#[derive(Clone, Debug, PartialEq, Eq, Error, Serialize, Deserialize)]
+#[error("{message}")]
+pub struct Violation {
+    pub rule: ValidationRule,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub observed: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed: Option<String>,
+    pub message: String,
+}
+
+impl Violation {
+    pub fn new(rule: ValidationRule, message: impl Into<String>) -> Self {
+        Self {
+            rule,
+            observed: None,
+            allowed: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn observed(mut self, observed: impl ToString) -> Self {
+        self.observed = Some(observed.to_string());
+        self
+    }
+
+    pub fn allowed(mut self, allowed: impl ToString) -> Self {
+        self.allowed = Some(allowed.to_string());
+        self
+    }
+}
+
+impl From<Violation> for PrimitivesError {
+    fn from(violation: Violation) -> Self {
+        PrimitivesError::ValidationViolation(violation)
+    }
+}
@@ -0,0 +1,138 @@
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta::ProofRequest;
+use taralli_primitives::alloy::primitives::{Address, B256, U256};
+use taralli_primitives::intents::auction::RewardCurve;
+use taralli_primitives::validation::request::validate_request_amount_constraints;
+use taralli_primitives::validation::validate_time_constraints;
+
+fn proof_request_fixture(min_reward: u64, max_reward: u64, start: u64, end: u64) -> ProofRequest {
+    ProofRequest {
+        signer: Address::ZERO,
+        market: Address::ZERO,
+        nonce: U256::ZERO,
+        rewardToken: Address::ZERO,
+        maxRewardAmount: U256::from(max_reward),
+        minRewardAmount: U256::from(min_reward),
+        minimumStake: 1,
+        startAuctionTimestamp: start,
+        endAuctionTimestamp: end,
+        provingTime: 30,
+        inputsCommitment: B256::ZERO,
+        extraData: vec![].into(),
+    }
+}
+
+#[test]
+/// Ensures equal reward bounds form a fixed price curve available as soon as the auction opens.
+fn should_treat_equal_rewards_as_fixed_price() {
+    let curve = RewardCurve::try_from(&proof_request_fixture(500, 500, 1_000, 1_060)).unwrap();
+
+    assert!(curve.is_fixed_price());
+    for timestamp in [0, 1_000, 1_030, 1_060, u64::MAX] {
+        assert_eq!(curve.reward_at(timestamp), U256::from(500));
+    }
+    assert_eq!(curve.timestamp_for(U256::from(500)), Some(1_000));
+    assert_eq!(curve.timestamp_for(U256::from(100)), Some(1_000));
+    assert_eq!(curve.timestamp_for(U256::from(501)), None);
+}
+
+#[test]
+/// Ensures degenerate auctions with an empty window or range don't divide by zero.
+fn should_handle_degenerate_curves() {
+    let fixed = RewardCurve::try_from(&proof_request_fixture(500, 500, 1_000, 1_000)).unwrap();
+    assert_eq!(fixed.reward_at(1_000), U256::from(500));
+    assert_eq!(fixed.timestamp_for(U256::from(500)), Some(1_000));
+
+    let instant = RewardCurve::try_from(&proof_request_fixture(100, 500, 1_000, 1_000)).unwrap();
+    assert_eq!(instant.reward_at(999), U256::from(100));
+    assert_eq!(instant.reward_at(1_000), U256::from(100));
+    assert_eq!(instant.reward_at(1_001), U256::from(500));
+    assert_eq!(instant.timestamp_for(U256::from(300)), None);
+}
+
+#[test]
+/// Ensures the linear curve rises from the minimum to the maximum reward like the contract's.
+fn should_follow_linear_curve() {
+    let curve = RewardCurve::try_from(&proof_request_fixture(100, 500, 1_000, 1_100)).unwrap();
+
+    assert!(!curve.is_fixed_price());
+    assert_eq!(curve.reward_at(1_000), U256::from(100));
+    assert_eq!(curve.reward_at(1_025), U256::from(200));
+    assert_eq!(curve.reward_at(1_050), U256::from(300));
+    assert_eq!(curve.reward_at(1_100), U256::from(500));
+    assert_eq!(curve.timestamp_for(U256::from(300)), Some(1_050));
+    // rounds up to the first second at which the target is reached
+    assert_eq!(curve.timestamp_for(U256::from(301)), Some(1_051));
+    assert!(curve.reward_at(1_051) >= U256::from(301));
+    assert_eq!(curve.timestamp_for(U256::from(501)), None);
+}
+
+#[test]
+/// Ensures validation accepts fixed price requests.
+fn should_accept_fixed_price_request() {
+    assert!(
+        validate_request_amount_constraints(&proof_request_fixture(500, 500, 1_000, 1_060), 1)
+            .is_ok()
+    );
+    assert!(
+        validate_request_amount_constraints(&proof_request_fixture(501, 500, 1_000, 1_060), 1)
+            .is_err()
+    );
+}
+
+#[test]
+/// Ensures requests with rewards or auction timestamps out of order don't form a reward curve.
+fn should_reject_out_of_order_curves() {
+    assert!(RewardCurve::try_from(&proof_request_fixture(501, 500, 1_000, 1_060)).is_err());
+    assert!(RewardCurve::try_from(&proof_request_fixture(100, 500, 1_060, 1_000)).is_err());
+    assert!(RewardCurve::try_from(&proof_request_fixture(500, 500, 1_060, 1_000)).is_err());
+}
+
+#[test]
+/// Ensures huge reward ranges neither overflow nor exceed the maximum reward.
+fn should_not_overflow_on_huge_rewards() {
+    let mut proof_request = proof_request_fixture(0, 0, 1_000, 1_100);
+    proof_request.maxRewardAmount = U256::MAX;
+    let curve = RewardCurve::try_from(&proof_request).unwrap();
+
+    assert_eq!(curve.reward_at(1_050), U256::MAX / U256::from(2));
+    assert_eq!(curve.reward_at(1_100), U256::MAX);
+    assert_eq!(curve.timestamp_for(U256::MAX), Some(1_100));
+}
+
+#[test]
+/// Ensures auction windows that are empty, inverted, too short or too long, or whose proving
+/// deadline overflows, are rejected.
+fn should_reject_degenerate_auction_windows() {
+    let validate = |start: u64, end: u64, proving_time: u32| {
+        validate_time_constraints(start, end, proving_time, start, 30, 300, 10, 3_600, 3_600)
+    };
+
+    assert!(validate(1_000, 1_060, 60).is_ok());
+    // end before or at start
+    let err = validate(1_060, 1_000, 60).unwrap_err();
+    assert!(err.to_string().contains("is not after"), "{err}");
+    assert!(validate(1_000, 1_000, 60).is_err());
+    // auction length out of bounds
+    let err = validate(1_000, 1_005, 60).unwrap_err();
+    assert!(err.to_string().contains("auction length 5s"), "{err}");
+    assert!(validate(1_000, 1_000 + 3_601, 60).is_err());
+    assert!(validate(1_000, 1_000 + 3_600, 60).is_ok());
+    // proving deadline past u64::MAX
+    let err = validate(u64::MAX - 60, u64::MAX - 1, 60).unwrap_err();
+    assert!(err.to_string().contains("overflows"), "{err}");
+}
+
+#[test]
+/// Ensures auction lengths and proving times are accepted up to their maximum and rejected one
+/// second past it, so no intent stays open for ever.
+fn should_bound_auction_length_and_proving_time() {
+    let validate = |end: u64, proving_time: u32| {
+        validate_time_constraints(1_000, end, proving_time, 1_000, 30, 300, 10, 3_600, 7_200)
+    };
+
+    assert!(validate(1_000 + 3_600, 7_200).is_ok());
+    let err = validate(1_000 + 3_601, 7_200).unwrap_err();
+    assert!(err.to_string().contains("auction length 3601s"), "{err}");
+    let err = validate(1_000 + 3_600, 7_201).unwrap_err();
+    assert!(err.to_string().contains("proving time too high"), "{err}");
+}
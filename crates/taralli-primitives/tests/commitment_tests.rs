@@ -0,0 +1,322 @@
+use taralli_primitives::abi::universal_bombetta::{
+    ProofRequestVerifierDetails, UniversalBombetta::ProofRequest,
+};
+use taralli_primitives::abi::universal_porchetta::{
+    ProofOfferVerifierDetails, UniversalPorchetta::ProofOffer,
+};
+use taralli_primitives::alloy::dyn_abi::DynSolValue;
+use taralli_primitives::alloy::primitives::{
+    address, b256, fixed_bytes, Address, Bytes, B256, U256,
+};
+use taralli_primitives::alloy::sol_types::SolValue;
+use taralli_primitives::commitment::{
+    check_public_inputs_layout, public_inputs_range, submitted_partial_commitment,
+    CommitmentHasher, ARKWORKS_PUBLIC_INPUTS_OFFSET, ARKWORKS_PUBLIC_INPUT_LENGTH,
+};
+use taralli_primitives::intents::CommonProofCommitment;
+use taralli_primitives::systems::SystemId;
+use taralli_primitives::validation::offer::validate_offer_submission;
+use taralli_primitives::validation::request::{
+    validate_request_inputs_commitment, validate_request_submission,
+};
+
+const VERIFIER: Address = address!("1234567890123456789012345678901234567890");
+const PREDETERMINED_PARTIAL_COMMITMENT: B256 = B256::repeat_byte(0x11);
+const SUBMITTED_PARTIAL_COMMITMENT: B256 = B256::repeat_byte(0x33);
+/// hashes of `inputs_preimage()`
+const PREIMAGE_KECCAK256: B256 =
+    b256!("3f3983882e6c6e0d24d5674be120773efcbf9dcc7cb239f47c0eb14f0e4c17df");
+const PREIMAGE_SHA256: B256 =
+    b256!("a0ecb70d7d18c30c47f27c54e5c3982764545389a2063531e9126711f1d9abfe");
+
+/// `abi.encode(uint256(7), bytes32(0x22..22))`
+fn inputs_preimage() -> Vec<u8> {
+    (U256::from(7), B256::repeat_byte(0x22)).abi_encode()
+}
+
+/// a 32 byte word, the inputs at offset 32 and the partial commitment result at offset 96
+fn opaque_submission() -> Vec<u8> {
+    [
+        [0xaa; 32].as_slice(),
+        inputs_preimage().as_slice(),
+        PREDETERMINED_PARTIAL_COMMITMENT.as_slice(),
+        SUBMITTED_PARTIAL_COMMITMENT.as_slice(),
+    ]
+    .concat()
+}
+
+fn proof_request(is_sha_commitment: bool, inputs_commitment: B256) -> ProofRequest {
+    let verifier_details = ProofRequestVerifierDetails {
+        verifier: VERIFIER,
+        selector: fixed_bytes!("deadbeef"),
+        isShaCommitment: is_sha_commitment,
+        inputsOffset: U256::from(32),
+        inputsLength: U256::from(64),
+        hasPartialCommitmentResultCheck: true,
+        submittedPartialCommitmentResultOffset: U256::from(96),
+        submittedPartialCommitmentResultLength: U256::from(64),
+        predeterminedPartialCommitment: PREDETERMINED_PARTIAL_COMMITMENT,
+    };
+    ProofRequest {
+        signer: Address::ZERO,
+        market: Address::ZERO,
+        nonce: U256::from(1),
+        rewardToken: Address::ZERO,
+        maxRewardAmount: U256::from(1000),
+        minRewardAmount: U256::from(100),
+        minimumStake: 1000,
+        startAuctionTimestamp: 1_700_000_000,
+        endAuctionTimestamp: 1_700_000_060,
+        provingTime: 30,
+        inputsCommitment: inputs_commitment,
+        extraData: Bytes::from(verifier_details.abi_encode()),
+    }
+}
+
+#[test]
+/// Ensures both hashers produce the pinned digests of the same preimage.
+fn should_hash_preimage_with_both_functions() {
+    let preimage = inputs_preimage();
+
+    assert_eq!(
+        CommitmentHasher::Keccak256.hash(&preimage),
+        PREIMAGE_KECCAK256
+    );
+    assert_eq!(CommitmentHasher::Sha256.hash(&preimage), PREIMAGE_SHA256);
+    assert_eq!(
+        CommitmentHasher::from_is_sha_commitment(true),
+        CommitmentHasher::Sha256
+    );
+    assert_eq!(
+        CommitmentHasher::from_request_extra_data(&proof_request(false, B256::ZERO).extraData)
+            .unwrap(),
+        CommitmentHasher::Keccak256
+    );
+}
+
+#[test]
+/// Ensures a request declaring sha256 but committing with keccak256 is rejected, naming both.
+fn should_reject_commitment_hashed_with_undeclared_function() {
+    let request = proof_request(true, PREIMAGE_KECCAK256);
+
+    let err = validate_request_inputs_commitment(&request, &inputs_preimage()).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "Commitment error: inputs commitment is the keccak256 hash of the inputs, but the verifier details declare sha256"
+    );
+    assert!(validate_request_inputs_commitment(
+        &proof_request(true, PREIMAGE_SHA256),
+        &inputs_preimage()
+    )
+    .is_ok());
+}
+
+#[test]
+/// Ensures submissions are checked the way the market checks them on resolve.
+fn should_check_request_submission_like_the_market() {
+    for (is_sha_commitment, inputs_commitment) in
+        [(false, PREIMAGE_KECCAK256), (true, PREIMAGE_SHA256)]
+    {
+        let request = proof_request(is_sha_commitment, inputs_commitment);
+        validate_request_submission(&request, &opaque_submission(), SUBMITTED_PARTIAL_COMMITMENT)
+            .unwrap();
+
+        let err =
+            validate_request_submission(&request, &opaque_submission(), B256::ZERO).unwrap_err();
+        assert!(err.to_string().contains("partial commitment result"));
+
+        let err = validate_request_submission(&request, &opaque_submission()[..90], B256::ZERO)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("exceeds the 90 byte opaque submission"));
+    }
+}
+
+#[test]
+/// Ensures the partial commitment a request's check takes is read from the submission's result
+/// field, passing the market's check, and that requests declaring no check take none.
+fn should_derive_submitted_partial_commitment() {
+    let request = proof_request(true, PREIMAGE_SHA256);
+    let check = request.partial_commitment_check().unwrap();
+    assert_eq!(check.hasher, CommitmentHasher::Sha256);
+    assert_eq!(
+        check.predetermined_partial_commitment,
+        PREDETERMINED_PARTIAL_COMMITMENT
+    );
+
+    let submitted = submitted_partial_commitment(&request, &opaque_submission()).unwrap();
+    assert_eq!(submitted, SUBMITTED_PARTIAL_COMMITMENT);
+    check.verify(&opaque_submission(), submitted).unwrap();
+    validate_request_submission(&request, &opaque_submission(), submitted).unwrap();
+
+    // a result field not led by the predetermined partial commitment can't pass the check
+    let mut submission = opaque_submission();
+    submission[96] ^= 0xff;
+    let err = submitted_partial_commitment(&request, &submission).unwrap_err();
+    assert!(err.to_string().contains("predetermined partial commitment"));
+
+    let mut unchecked = proof_request(true, PREIMAGE_SHA256);
+    let mut verifier_details =
+        ProofRequestVerifierDetails::abi_decode(&unchecked.extraData, true).unwrap();
+    verifier_details.hasPartialCommitmentResultCheck = false;
+    unchecked.extraData = Bytes::from(verifier_details.abi_encode());
+    assert_eq!(unchecked.partial_commitment_check(), None);
+    assert_eq!(
+        submitted_partial_commitment(&unchecked, &submission).unwrap(),
+        B256::ZERO
+    );
+}
+
+#[test]
+/// Ensures offers commit to the inputs field of the submission with their declared hasher.
+fn should_check_offer_submission_inputs() {
+    let verifier_details = ProofOfferVerifierDetails {
+        verifier: VERIFIER,
+        selector: fixed_bytes!("deadbeef"),
+        isShaCommitment: false,
+        inputsOffset: U256::from(32),
+        inputsLength: U256::from(64),
+    };
+    let mut offer = ProofOffer {
+        signer: Address::ZERO,
+        market: Address::ZERO,
+        nonce: U256::from(1),
+        rewardToken: Address::ZERO,
+        rewardAmount: U256::from(1000),
+        stakeToken: Address::ZERO,
+        stakeAmount: U256::from(100),
+        startAuctionTimestamp: 1_700_000_000,
+        endAuctionTimestamp: 1_700_000_060,
+        provingTime: 30,
+        inputsCommitment: PREIMAGE_KECCAK256,
+        extraData: Bytes::from(verifier_details.abi_encode()),
+    };
+    validate_offer_submission(&offer, &opaque_submission()).unwrap();
+
+    offer.inputsCommitment = PREIMAGE_SHA256;
+    let err = validate_offer_submission(&offer, &opaque_submission()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Commitment error: inputs commitment is the sha256 hash of the inputs, but the verifier details declare keccak256"
+    );
+}
+
+#[test]
+/// Ensures the `pubSignals` of an arkworks submission are located past its proof points, and the
+/// inputs of an intent pointing elsewhere are refused.
+fn should_locate_arkworks_public_inputs() {
+    let uints = |values: &[u64]| {
+        DynSolValue::Array(
+            values
+                .iter()
+                .map(|value| DynSolValue::Uint(U256::from(*value), 256))
+                .collect(),
+        )
+    };
+    let opaque_submission = DynSolValue::Tuple(vec![
+        uints(&[1, 2]),
+        DynSolValue::Array(vec![uints(&[3, 4]), uints(&[5, 6])]),
+        uints(&[7, 8]),
+        uints(&[9, 10]),
+    ])
+    .abi_encode();
+    let pub_signals = (U256::from(9), U256::from(10)).abi_encode();
+
+    let range = public_inputs_range(SystemId::Arkworks, &opaque_submission).unwrap();
+    assert_eq!(range.start, ARKWORKS_PUBLIC_INPUTS_OFFSET);
+    assert_eq!(range.end, opaque_submission.len());
+    assert_eq!(&opaque_submission[range.clone()], pub_signals.as_slice());
+
+    let extra_data = |offset: usize, length: usize| {
+        ProofOfferVerifierDetails {
+            verifier: VERIFIER,
+            selector: fixed_bytes!("deadbeef"),
+            isShaCommitment: false,
+            inputsOffset: U256::from(offset),
+            inputsLength: U256::from(length),
+        }
+        .abi_encode()
+    };
+    // the second signal alone
+    check_public_inputs_layout(
+        SystemId::Arkworks,
+        &opaque_submission,
+        &extra_data(range.start + 32, 32),
+        &pub_signals,
+    )
+    .unwrap();
+    // the length word of the array precedes the signals
+    let err = check_public_inputs_layout(
+        SystemId::Arkworks,
+        &opaque_submission,
+        &extra_data(range.start - 32, 64),
+        &pub_signals,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("outside"), "{err}");
+    // nor is the submission of another system read as arkworks
+    assert!(public_inputs_range(SystemId::Arkworks, &[0; 64]).is_err());
+}
+
+#[test]
+/// Ensures a request committing to an arkworks public signal the way the arkworks requester does
+/// is resolvable with the submission of its proof: the shared layout points the verifier details
+/// at the signal, whose hash is the inputs commitment.
+fn should_round_trip_arkworks_inputs_commitment() {
+    let signal = U256::from(33);
+    // the requester commits to the abi encoded public signals
+    let preimage = DynSolValue::Tuple(vec![DynSolValue::Uint(signal, 256)]).abi_encode();
+    let words = |values: &[u64]| {
+        DynSolValue::Array(
+            values
+                .iter()
+                .map(|value| DynSolValue::Uint(U256::from(*value), 256))
+                .collect(),
+        )
+    };
+    let opaque_submission = DynSolValue::Tuple(vec![
+        words(&[1, 2]),
+        DynSolValue::Array(vec![words(&[3, 4]), words(&[5, 6])]),
+        words(&[7, 8]),
+        DynSolValue::Array(vec![DynSolValue::Uint(signal, 256)]),
+    ])
+    .abi_encode();
+
+    let verifier_details = ProofRequestVerifierDetails {
+        verifier: VERIFIER,
+        selector: fixed_bytes!("deadbeef"),
+        isShaCommitment: false,
+        inputsOffset: U256::from(ARKWORKS_PUBLIC_INPUTS_OFFSET),
+        inputsLength: U256::from(ARKWORKS_PUBLIC_INPUT_LENGTH),
+        hasPartialCommitmentResultCheck: false,
+        submittedPartialCommitmentResultOffset: U256::ZERO,
+        submittedPartialCommitmentResultLength: U256::ZERO,
+        predeterminedPartialCommitment: B256::ZERO,
+    };
+    let request = ProofRequest {
+        inputsCommitment: CommitmentHasher::Keccak256.hash(&preimage),
+        extraData: Bytes::from(verifier_details.abi_encode()),
+        ..proof_request(false, B256::ZERO)
+    };
+
+    validate_request_submission(&request, &opaque_submission, B256::ZERO).unwrap();
+    check_public_inputs_layout(
+        SystemId::Arkworks,
+        &opaque_submission,
+        &request.extraData,
+        &preimage,
+    )
+    .unwrap();
+
+    // a submission proving another signal does not resolve the request
+    let other = DynSolValue::Tuple(vec![
+        words(&[1, 2]),
+        DynSolValue::Array(vec![words(&[3, 4]), words(&[5, 6])]),
+        words(&[7, 8]),
+        words(&[34]),
+    ])
+    .abi_encode();
+    assert!(validate_request_submission(&request, &other, B256::ZERO).is_err());
+}
@@ -0,0 +1,64 @@
+use rstest::*;
+use taralli_primitives::abi::universal_bombetta::{
+    ProofRequestVerifierDetails, UniversalBombetta::ProofRequest,
+};
+use taralli_primitives::alloy::primitives::{
+    address, fixed_bytes, Address, PrimitiveSignature, B256, U256,
+};
+use taralli_primitives::alloy::sol_types::SolValue;
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::systems::risc0::Risc0ProofParams;
+use taralli_primitives::systems::{SystemId, SystemParams};
+
+pub const MARKET: Address = address!("2222222222222222222222222222222222222222");
+pub const VERIFIER: Address = address!("4444444444444444444444444444444444444444");
+pub const LATEST_TIMESTAMP: u64 = 1_000;
+
+pub fn risc0_system() -> SystemParams {
+    SystemParams::try_from((
+        &SystemId::Risc0,
+        serde_json::to_vec(&Risc0ProofParams {
+            elf: vec![1, 2, 3],
+            inputs: vec![4; 32],
+        })
+        .unwrap(),
+    ))
+    .unwrap()
+}
+
+#[fixture]
+/// Unsigned risc0 request on `MARKET` auctioned for a minute from `LATEST_TIMESTAMP`, proven
+/// within a minute and staking 500, whose verifier details pass the default constraints
+pub fn request_fixture() -> ComputeRequest<SystemParams> {
+    ComputeRequest {
+        system_id: SystemId::Risc0,
+        system: risc0_system(),
+        proof_request: ProofRequest {
+            signer: Address::ZERO,
+            market: MARKET,
+            nonce: U256::from(7),
+            rewardToken: address!("3333333333333333333333333333333333333333"),
+            maxRewardAmount: U256::from(100),
+            minRewardAmount: U256::from(10),
+            minimumStake: 500,
+            startAuctionTimestamp: LATEST_TIMESTAMP,
+            endAuctionTimestamp: LATEST_TIMESTAMP + 60,
+            provingTime: 60,
+            inputsCommitment: B256::repeat_byte(0x44),
+            extraData: ProofRequestVerifierDetails {
+                verifier: VERIFIER,
+                selector: fixed_bytes!("deadbeef"),
+                isShaCommitment: false,
+                inputsOffset: U256::ZERO,
+                inputsLength: U256::from(32),
+                hasPartialCommitmentResultCheck: false,
+                submittedPartialCommitmentResultOffset: U256::ZERO,
+                submittedPartialCommitmentResultLength: U256::ZERO,
+                predeterminedPartialCommitment: B256::ZERO,
+            }
+            .abi_encode()
+            .into(),
+        },
+        signature: PrimitiveSignature::new(U256::from(1), U256::from(2), false),
+    }
+}
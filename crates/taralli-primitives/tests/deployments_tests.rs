@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use taralli_primitives::alloy::primitives::{address, Address};
+use taralli_primitives::deployments::{Deployment, DeploymentRegistry};
+use taralli_primitives::markets::{
+    MarketFee, SEPOLIA_CHAIN_ID, SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+    SEPOLIA_UNIVERSAL_PORCHETTA_ADDRESS,
+};
+use taralli_primitives::systems::SystemId;
+use taralli_primitives::utils::PERMIT2_ADDRESS;
+use taralli_primitives::PrimitivesError;
+
+const ANVIL_CHAIN_ID: u64 = 31_337;
+const LOCAL_BOMBETTA: Address = address!("5FbDB2315678afecb367f032d93F642f64180aa3");
+const LOCAL_PORCHETTA: Address = address!("e7f1725E7734CE288F8367e1Bb143E90bb3F0512");
+const LOCAL_PERMIT2: Address = address!("9fE46736679d2D9a65F0992F2272dE9f3c7fa6e0");
+const LOCAL_VERIFIER: Address = address!("Cf7Ed3AccA5a467e9e704C703E8D87F634fB0Fc9");
+
+fn local_deployment_json() -> String {
+    format!(
+        r#"{{
+            "{ANVIL_CHAIN_ID}": {{
+                "universal_bombetta": "{LOCAL_BOMBETTA}",
+                "universal_porchetta": "{LOCAL_PORCHETTA}",
+                "permit2": "{LOCAL_PERMIT2}",
+                "universal_bombetta_fee": {{ "fee_bps": 50 }},
+                "verifiers": {{ "Risc0": "{LOCAL_VERIFIER}" }}
+            }},
+            "{SEPOLIA_CHAIN_ID}": {{
+                "universal_bombetta": "{LOCAL_BOMBETTA}",
+                "universal_porchetta": "{LOCAL_PORCHETTA}"
+            }}
+        }}"#
+    )
+}
+
+#[test]
+/// Ensures the built-in registry knows the sepolia deployment.
+fn should_look_up_builtin_deployment() {
+    let registry = DeploymentRegistry::builtin();
+    let sepolia = registry.get(SEPOLIA_CHAIN_ID).unwrap();
+
+    assert_eq!(
+        sepolia.universal_bombetta,
+        SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS
+    );
+    assert_eq!(
+        sepolia.universal_porchetta,
+        SEPOLIA_UNIVERSAL_PORCHETTA_ADDRESS
+    );
+    assert_eq!(sepolia.permit2, PERMIT2_ADDRESS);
+    assert_eq!(
+        sepolia.verifier(SystemId::Risc0),
+        Some(address!("AC292cF957Dd5BA174cdA13b05C16aFC71700327"))
+    );
+    assert_eq!(registry.chain_ids(), vec![SEPOLIA_CHAIN_ID]);
+}
+
+#[test]
+/// Ensures deployments read from a file are added, and replace the built-in ones of their chain.
+fn should_prefer_overrides_file() {
+    let path =
+        std::env::temp_dir().join(format!("taralli-deployments-{}.json", std::process::id()));
+    std::fs::write(&path, local_deployment_json()).unwrap();
+    let registry = DeploymentRegistry::builtin()
+        .with_overrides_file(&path)
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let local = registry.get(ANVIL_CHAIN_ID).unwrap();
+    assert_eq!(
+        *local,
+        Deployment {
+            universal_bombetta: LOCAL_BOMBETTA,
+            universal_porchetta: LOCAL_PORCHETTA,
+            universal_bombetta_fee: MarketFee::from_bps(50),
+            universal_porchetta_fee: MarketFee::ZERO,
+            permit2: LOCAL_PERMIT2,
+            verifiers: HashMap::from([(SystemId::Risc0, LOCAL_VERIFIER)]),
+        }
+    );
+    assert_eq!(local.verifier(SystemId::Sp1), None);
+    assert_eq!(
+        local.market_fee(LOCAL_BOMBETTA),
+        Some(MarketFee::from_bps(50))
+    );
+    assert_eq!(local.market_fee(LOCAL_PORCHETTA), Some(MarketFee::ZERO));
+    assert_eq!(local.market_fee(LOCAL_VERIFIER), None);
+
+    // the whole built-in deployment is replaced, the canonical permit2 filling the blank
+    let sepolia = registry.get(SEPOLIA_CHAIN_ID).unwrap();
+    assert_eq!(sepolia.universal_bombetta, LOCAL_BOMBETTA);
+    assert_eq!(sepolia.permit2, PERMIT2_ADDRESS);
+    assert!(sepolia.verifiers.is_empty());
+
+    assert_eq!(registry.chain_ids(), vec![ANVIL_CHAIN_ID, SEPOLIA_CHAIN_ID]);
+}
+
+#[test]
+/// Ensures deployments added last win over the ones known before.
+fn should_apply_overrides_in_order() {
+    let registry = DeploymentRegistry::builtin()
+        .with_overrides_json(&local_deployment_json())
+        .unwrap()
+        .with_deployment(
+            SEPOLIA_CHAIN_ID,
+            DeploymentRegistry::builtin()
+                .get(SEPOLIA_CHAIN_ID)
+                .unwrap()
+                .clone(),
+        );
+
+    assert_eq!(
+        registry.get(SEPOLIA_CHAIN_ID).unwrap().universal_bombetta,
+        SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS
+    );
+    assert_eq!(
+        registry.get(ANVIL_CHAIN_ID).unwrap().universal_bombetta,
+        LOCAL_BOMBETTA
+    );
+}
+
+#[test]
+/// Ensures looking up a chain without deployment fails listing the chains known.
+fn should_list_known_chains_for_unknown_chain() {
+    let registry = DeploymentRegistry::builtin()
+        .with_overrides_json(&local_deployment_json())
+        .unwrap();
+
+    let err = registry.get(1).unwrap_err();
+    assert!(
+        matches!(
+            &err,
+            PrimitivesError::UnknownChain { chain_id: 1, known }
+                if *known == vec![ANVIL_CHAIN_ID, SEPOLIA_CHAIN_ID]
+        ),
+        "{err}"
+    );
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "No deployment known for chain 1, known chains: {ANVIL_CHAIN_ID}, {SEPOLIA_CHAIN_ID}"
+        )
+    );
+}
+
+#[test]
+/// Ensures malformed override files are reported rather than ignored.
+fn should_reject_malformed_overrides() {
+    let err = DeploymentRegistry::builtin()
+        .with_overrides_json(r#"{ "mainnet": {} }"#)
+        .unwrap_err();
+    assert!(matches!(err, PrimitivesError::ConfigError(_)), "{err}");
+}
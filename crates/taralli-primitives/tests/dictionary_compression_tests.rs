@@ -0,0 +1,133 @@
+use std::time::Duration;
+
+use taralli_primitives::compression_utils::compression::{compress_brotli, DecompressionBudget};
+use taralli_primitives::compression_utils::dictionary::{
+    compress_brotli_with_dictionary, decompress_brotli_with_dictionary_bounded,
+    decompress_system_with_dictionary_bounded, dictionary_hash, dictionary_reference,
+    system_dictionary,
+};
+use taralli_primitives::encryption::SystemPayload;
+use taralli_primitives::systems::risc0::Risc0ProofParams;
+use taralli_primitives::systems::SystemParams;
+use taralli_primitives::PrimitivesError;
+
+/// Incompressible bytes standing in for a guest program
+fn elf_fixture(len: usize) -> Vec<u8> {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+fn risc0_system(elf: &[u8], inputs: &[u8]) -> SystemParams {
+    SystemParams::Risc0(Risc0ProofParams {
+        elf: elf.to_vec(),
+        inputs: inputs.to_vec(),
+    })
+}
+
+/// Dictionary of the intents sharing `elf`
+fn dictionary_fixture(elf: &[u8]) -> Vec<u8> {
+    system_dictionary(&risc0_system(elf, b"base")).unwrap()
+}
+
+#[tokio::test]
+/// Ensures two intents sharing an ELF compress to their differences against the dictionary of
+/// the first one, and decompress back to their systems.
+async fn should_round_trip_intents_sharing_elf() {
+    let elf = elf_fixture(256 * 1024);
+    let base = risc0_system(&elf, b"first inputs");
+    let dictionary = system_dictionary(&base).unwrap();
+
+    for system in [base.clone(), risc0_system(&elf, b"second inputs")] {
+        let serialized = serde_json::to_vec(&system).unwrap();
+        let plain = compress_brotli(&serialized).unwrap();
+        let compressed = compress_brotli_with_dictionary(&serialized, &dictionary).unwrap();
+        assert_eq!(
+            dictionary_reference(&compressed),
+            Some(dictionary_hash(&dictionary))
+        );
+        assert!(
+            compressed.len() * 10 < plain.len(),
+            "{} bytes with dictionary, {} without",
+            compressed.len(),
+            plain.len()
+        );
+
+        let decompressed = decompress_system_with_dictionary_bounded(
+            &compressed,
+            Some(&dictionary),
+            &DecompressionBudget::default(),
+        )
+        .await
+        .unwrap();
+        match decompressed {
+            SystemPayload::Plain(decompressed) => assert_eq!(
+                serde_json::to_value(decompressed).unwrap(),
+                serde_json::to_value(&system).unwrap()
+            ),
+            SystemPayload::Encrypted(_) => panic!("decompressed an encrypted system"),
+        }
+    }
+}
+
+#[tokio::test]
+/// Ensures a system referencing a dictionary isn't decompressed without it or with another one,
+/// while plain systems decompress whatever the dictionary.
+async fn should_reject_unknown_dictionary() {
+    let elf = elf_fixture(64 * 1024);
+    let dictionary = dictionary_fixture(&elf);
+    let serialized = serde_json::to_vec(&risc0_system(&elf, b"inputs")).unwrap();
+    let compressed = compress_brotli_with_dictionary(&serialized, &dictionary).unwrap();
+    let budget = DecompressionBudget::default();
+
+    let other = dictionary_fixture(&elf_fixture(1024));
+    for given in [None, Some(other.as_slice())] {
+        let err = decompress_brotli_with_dictionary_bounded(&compressed, given, &budget)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, PrimitivesError::UnknownDictionary(hash) if hash == dictionary_hash(&dictionary)),
+            "{err}"
+        );
+    }
+
+    // the plain fallback stays readable by dictionary unaware decoders
+    let plain = compress_brotli(&serialized).unwrap();
+    assert_eq!(dictionary_reference(&plain), None);
+    for dictionary in [None, Some(dictionary.as_slice())] {
+        let decompressed = decompress_brotli_with_dictionary_bounded(&plain, dictionary, &budget)
+            .await
+            .unwrap();
+        assert_eq!(decompressed, serialized);
+    }
+}
+
+#[tokio::test]
+/// Ensures decompressing against a dictionary is held to the output budget.
+async fn should_bound_dictionary_decompression() {
+    let elf = elf_fixture(64 * 1024);
+    let dictionary = dictionary_fixture(&elf);
+    let serialized = serde_json::to_vec(&risc0_system(&elf, &[7; 1024 * 1024])).unwrap();
+    let compressed = compress_brotli_with_dictionary(&serialized, &dictionary).unwrap();
+
+    let err = decompress_brotli_with_dictionary_bounded(
+        &compressed,
+        Some(&dictionary),
+        &DecompressionBudget {
+            max_output_bytes: 512 * 1024,
+            max_duration: Duration::from_secs(10),
+        },
+    )
+    .await
+    .unwrap_err();
+    assert!(
+        matches!(err, PrimitivesError::DecompressionLimitExceeded(max) if max == 512 * 1024),
+        "{err}"
+    );
+}
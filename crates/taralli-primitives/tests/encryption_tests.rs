@@ -0,0 +1,97 @@
+use serde_json::json;
+use taralli_primitives::alloy::primitives::B256;
+use taralli_primitives::encryption::{EncryptedSystemParams, KeyExchangeKeypair, SystemPayload};
+use taralli_primitives::systems::{System, SystemId, SystemInputs, SystemParams};
+
+fn risc0_params() -> SystemParams {
+    SystemParams::try_from((
+        &SystemId::Risc0,
+        json!({ "elf": [1, 2, 3], "inputs": vec![4u8; 32] })
+            .to_string()
+            .into_bytes(),
+    ))
+    .unwrap()
+}
+
+const INTENT_ID: B256 = B256::repeat_byte(0xaa);
+const OTHER_INTENT_ID: B256 = B256::repeat_byte(0xbb);
+
+#[test]
+/// Ensures encrypted params only reveal their system and ciphertext, and decrypt back to the
+/// original params with the content key.
+fn should_round_trip_encrypted_params() {
+    let params = risc0_params();
+    let (encrypted, keys) = EncryptedSystemParams::encrypt(&params, INTENT_ID).unwrap();
+    encrypted.validate_inputs().unwrap();
+    assert_eq!(encrypted.system_id(), SystemId::Risc0);
+    assert_eq!(
+        encrypted.inputs(),
+        SystemInputs::Bytes(encrypted.ciphertext.to_vec())
+    );
+
+    // a submission decodes to the payload it was built from
+    let payload: SystemPayload =
+        serde_json::from_value(serde_json::to_value(&encrypted).unwrap()).unwrap();
+    assert!(matches!(payload, SystemPayload::Encrypted(_)));
+    let payload: SystemPayload =
+        serde_json::from_value(serde_json::to_value(&params).unwrap()).unwrap();
+    assert!(matches!(payload, SystemPayload::Plain(_)));
+
+    let decrypted = encrypted.decrypt(&keys.content_key, INTENT_ID).unwrap();
+    assert_eq!(decrypted.inputs(), params.inputs());
+}
+
+#[test]
+/// Ensures a wrong key or a tampered ciphertext fails authentication instead of decrypting to
+/// garbage.
+fn should_reject_wrong_key_and_tampering() {
+    let (encrypted, keys) = EncryptedSystemParams::encrypt(&risc0_params(), INTENT_ID).unwrap();
+
+    let err = encrypted
+        .decrypt(&B256::repeat_byte(1), INTENT_ID)
+        .unwrap_err();
+    assert!(err.to_string().contains("authentication failed"), "{err}");
+
+    let mut tampered = encrypted.clone();
+    let mut ciphertext = tampered.ciphertext.to_vec();
+    ciphertext[0] ^= 1;
+    tampered.ciphertext = ciphertext.into();
+    let err = tampered.decrypt(&keys.content_key, INTENT_ID).unwrap_err();
+    assert!(err.to_string().contains("authentication failed"), "{err}");
+
+    // a key wrapped for one provider can't be unwrapped by another
+    let provider = KeyExchangeKeypair::random();
+    let wrapped = keys.wrap_for(INTENT_ID, &provider.public_key()).unwrap();
+    assert_eq!(
+        provider
+            .unwrap_key(INTENT_ID, &encrypted.encapsulation_key, &wrapped)
+            .unwrap(),
+        keys.content_key
+    );
+    assert!(KeyExchangeKeypair::random()
+        .unwrap_key(INTENT_ID, &encrypted.encapsulation_key, &wrapped)
+        .is_err());
+}
+
+#[test]
+/// Ensures params and wrapped keys are bound to their request and encapsulation key, so they
+/// can't be replayed for another request or paired with another requester key.
+fn should_bind_ciphertexts_to_request() {
+    let (encrypted, keys) = EncryptedSystemParams::encrypt(&risc0_params(), INTENT_ID).unwrap();
+
+    let err = encrypted
+        .decrypt(&keys.content_key, OTHER_INTENT_ID)
+        .unwrap_err();
+    assert!(err.to_string().contains("authentication failed"), "{err}");
+
+    let mut swapped = encrypted.clone();
+    swapped.encapsulation_key = KeyExchangeKeypair::random().public_key();
+    let err = swapped.decrypt(&keys.content_key, INTENT_ID).unwrap_err();
+    assert!(err.to_string().contains("authentication failed"), "{err}");
+
+    let provider = KeyExchangeKeypair::random();
+    let wrapped = keys.wrap_for(INTENT_ID, &provider.public_key()).unwrap();
+    assert!(provider
+        .unwrap_key(OTHER_INTENT_ID, &encrypted.encapsulation_key, &wrapped)
+        .is_err());
+}
@@ -0,0 +1,283 @@
+use taralli_primitives::abi::extensions::{
+    decode_request_extensions, decode_request_verifier_alternatives, with_leading_verifier,
+    with_request_extensions, Extensions, MAX_URGENCY, URGENCY_TAG, VERIFIER_ALTERNATIVE_TAG,
+    VERIFIER_ALTERNATIVE_VERSION,
+};
+use taralli_primitives::abi::universal_bombetta::{
+    ProofRequestVerifierDetails, UniversalBombetta::ProofRequest,
+};
+use taralli_primitives::abi::verifier_details::{
+    decode_request_verifier_details, DecodeError, DecodeMode,
+};
+use taralli_primitives::alloy::primitives::{address, fixed_bytes, Address, B256, U256};
+use taralli_primitives::alloy::sol_types::SolValue;
+use taralli_primitives::validation::request::{
+    validate_request_extensions, validate_request_verifier_details, RequestVerifierConstraints,
+};
+use taralli_primitives::validation::violation::ValidationRule;
+use taralli_primitives::PrimitivesError;
+
+fn verifier_details() -> Vec<u8> {
+    ProofRequestVerifierDetails {
+        verifier: address!("1234567890123456789012345678901234567890"),
+        selector: fixed_bytes!("deadbeef"),
+        isShaCommitment: true,
+        inputsOffset: U256::from(32),
+        inputsLength: U256::from(64),
+        hasPartialCommitmentResultCheck: false,
+        submittedPartialCommitmentResultOffset: U256::ZERO,
+        submittedPartialCommitmentResultLength: U256::ZERO,
+        predeterminedPartialCommitment: B256::ZERO,
+    }
+    .abi_encode()
+}
+
+fn proof_request(extra_data: Vec<u8>) -> ProofRequest {
+    ProofRequest {
+        signer: Address::ZERO,
+        market: Address::ZERO,
+        nonce: U256::ZERO,
+        rewardToken: Address::ZERO,
+        maxRewardAmount: U256::from(100),
+        minRewardAmount: U256::from(10),
+        minimumStake: 0,
+        startAuctionTimestamp: 100,
+        endAuctionTimestamp: 200,
+        provingTime: 60,
+        inputsCommitment: B256::ZERO,
+        extraData: extra_data.into(),
+    }
+}
+
+#[test]
+/// Ensures the urgency survives an encode/decode round trip, and the verifier details it follows
+/// still decode strictly.
+fn should_round_trip_urgency() {
+    let extensions = Extensions {
+        urgency: Some(2),
+        ..Default::default()
+    };
+    let extra_data = with_request_extensions(&verifier_details(), &extensions);
+    assert_eq!(&extra_data[verifier_details().len()..], [URGENCY_TAG, 1, 2]);
+
+    assert_eq!(decode_request_extensions(&extra_data).unwrap(), extensions);
+    assert!(decode_request_verifier_details(&extra_data, DecodeMode::Strict).is_ok());
+
+    // replacing the extensions keeps the verifier details
+    let cleared = with_request_extensions(&extra_data, &Extensions::default());
+    assert_eq!(cleared.to_vec(), verifier_details());
+}
+
+#[test]
+/// Ensures requests without extensions decode as they did before extensions existed.
+fn should_decode_requests_without_extensions() {
+    assert!(decode_request_extensions(&verifier_details())
+        .unwrap()
+        .is_empty());
+    assert!(decode_request_extensions(&[]).unwrap().is_empty());
+    assert!(validate_request_extensions(&proof_request(verifier_details())).is_ok());
+}
+
+#[test]
+/// Ensures unknown extensions are skipped leniently and rejected strictly, and truncated ones
+/// rejected by both.
+fn should_decode_unknown_and_truncated_extensions() {
+    let unknown_then_urgency = [0x7f, 2, 0xaa, 0xbb, URGENCY_TAG, 1, 3];
+    assert_eq!(
+        Extensions::decode(&unknown_then_urgency, DecodeMode::Lenient).unwrap(),
+        Extensions {
+            urgency: Some(3),
+            ..Default::default()
+        }
+    );
+    assert!(matches!(
+        Extensions::decode(&unknown_then_urgency, DecodeMode::Strict),
+        Err(DecodeError::InvalidExtension { offset: 0, .. })
+    ));
+
+    for truncated in [&[URGENCY_TAG][..], &[URGENCY_TAG, 1], &[URGENCY_TAG, 2, 1]] {
+        for mode in [DecodeMode::Lenient, DecodeMode::Strict] {
+            assert!(
+                matches!(
+                    Extensions::decode(truncated, mode),
+                    Err(DecodeError::InvalidExtension { .. })
+                ),
+                "{truncated:?} decoded in {mode:?} mode"
+            );
+        }
+    }
+}
+
+#[test]
+/// Ensures urgency levels above the maximum and malformed extensions are violations.
+fn should_bound_urgency() {
+    let extra_data = |urgency| {
+        with_request_extensions(
+            &verifier_details(),
+            &Extensions {
+                urgency: Some(urgency),
+                ..Default::default()
+            },
+        )
+        .to_vec()
+    };
+    assert!(validate_request_extensions(&proof_request(extra_data(MAX_URGENCY))).is_ok());
+
+    match validate_request_extensions(&proof_request(extra_data(MAX_URGENCY + 1))) {
+        Err(PrimitivesError::ValidationViolation(violation)) => {
+            assert_eq!(violation.rule, ValidationRule::Urgency);
+            assert_eq!(violation.observed.as_deref(), Some("4"));
+        }
+        other => panic!("expected an urgency violation, got {other:?}"),
+    }
+
+    let truncated = [verifier_details(), vec![URGENCY_TAG]].concat();
+    match validate_request_extensions(&proof_request(truncated)) {
+        Err(PrimitivesError::ValidationViolation(violation)) => {
+            assert_eq!(violation.rule, ValidationRule::VerifierDetails)
+        }
+        other => panic!("expected a verifier details violation, got {other:?}"),
+    }
+}
+
+/// Verifier details of a newer verifier, reading its inputs elsewhere with another hash function
+fn alternative_verifier_details() -> ProofRequestVerifierDetails {
+    ProofRequestVerifierDetails {
+        verifier: address!("abcdefabcdefabcdefabcdefabcdefabcdefabcd"),
+        selector: fixed_bytes!("cafebabe"),
+        isShaCommitment: false,
+        inputsOffset: U256::from(96),
+        inputsLength: U256::from(32),
+        hasPartialCommitmentResultCheck: true,
+        submittedPartialCommitmentResultOffset: U256::from(128),
+        submittedPartialCommitmentResultLength: U256::from(32),
+        predeterminedPartialCommitment: B256::repeat_byte(7),
+    }
+}
+
+#[test]
+/// Ensures verifier alternatives survive an encode/decode round trip in order, after the leading
+/// verifier details, and the verifier details they follow still decode strictly.
+fn should_round_trip_verifier_alternatives() {
+    let mut second = alternative_verifier_details();
+    second.verifier = address!("0000000000000000000000000000000000000001");
+    let extensions = Extensions {
+        urgency: Some(1),
+        verifier_alternatives: vec![alternative_verifier_details(), second.clone()],
+        ..Default::default()
+    };
+    let extra_data = with_request_extensions(&verifier_details(), &extensions);
+    let encoded = &extra_data[verifier_details().len()..];
+    assert_eq!(encoded[3..5], [VERIFIER_ALTERNATIVE_TAG, 187]);
+    assert_eq!(encoded[5], VERIFIER_ALTERNATIVE_VERSION);
+
+    assert_eq!(decode_request_extensions(&extra_data).unwrap(), extensions);
+    assert!(decode_request_verifier_details(&extra_data, DecodeMode::Strict).is_ok());
+
+    let alternatives = decode_request_verifier_alternatives(&extra_data).unwrap();
+    assert_eq!(alternatives.len(), 3);
+    assert_eq!(alternatives[0].abi_encode(), verifier_details());
+    assert_eq!(alternatives[1], alternative_verifier_details());
+    assert_eq!(alternatives[2], second);
+
+    // leading with an alternative keeps the extensions
+    let led = with_leading_verifier(&extra_data, &alternatives[1]);
+    assert_eq!(
+        decode_request_verifier_details(&led, DecodeMode::Strict).unwrap(),
+        alternative_verifier_details()
+    );
+    assert_eq!(led[verifier_details().len()..], *encoded);
+}
+
+#[test]
+/// Ensures single verifier requests encode as before and have their verifier details as sole
+/// alternative.
+fn should_keep_single_verifier_encoding() {
+    assert!(Extensions::default().encode().is_empty());
+    let extra_data = with_request_extensions(&verifier_details(), &Extensions::default());
+    assert_eq!(extra_data.to_vec(), verifier_details());
+
+    let alternatives = decode_request_verifier_alternatives(&verifier_details()).unwrap();
+    assert_eq!(alternatives.len(), 1);
+    assert_eq!(alternatives[0].abi_encode(), verifier_details());
+}
+
+#[test]
+/// Ensures alternatives of an unknown version are skipped leniently and rejected strictly, and
+/// malformed ones rejected by both.
+fn should_decode_unknown_and_malformed_verifier_alternatives() {
+    let encoded = Extensions {
+        verifier_alternatives: vec![alternative_verifier_details()],
+        ..Default::default()
+    }
+    .encode();
+
+    let mut newer = encoded.clone();
+    newer[2] = VERIFIER_ALTERNATIVE_VERSION + 1;
+    assert!(Extensions::decode(&newer, DecodeMode::Lenient)
+        .unwrap()
+        .is_empty());
+    assert!(matches!(
+        Extensions::decode(&newer, DecodeMode::Strict),
+        Err(DecodeError::InvalidExtension { offset: 0, .. })
+    ));
+
+    let mut bad_flag = encoded.clone();
+    // isShaCommitment follows the version, verifier and selector
+    bad_flag[2 + 1 + 20 + 4] = 2;
+    let mut short = encoded.clone();
+    short[1] -= 1;
+    short.pop();
+    for malformed in [bad_flag, short] {
+        for mode in [DecodeMode::Lenient, DecodeMode::Strict] {
+            assert!(matches!(
+                Extensions::decode(&malformed, mode),
+                Err(DecodeError::InvalidExtension { .. })
+            ));
+        }
+    }
+}
+
+#[test]
+/// Ensures the leading verifier details are held to the constraints whatever the alternatives,
+/// the market only ever resolving against them.
+fn should_validate_leading_verifier_only() {
+    let alternative_constraints = RequestVerifierConstraints {
+        verifier: Some(alternative_verifier_details().verifier),
+        selector: Some(alternative_verifier_details().selector),
+        ..Default::default()
+    };
+    let single = proof_request(verifier_details());
+    assert!(validate_request_verifier_details(&single, &alternative_constraints).is_err());
+
+    let with_alternative = proof_request(
+        with_request_extensions(
+            &verifier_details(),
+            &Extensions {
+                verifier_alternatives: vec![alternative_verifier_details()],
+                ..Default::default()
+            },
+        )
+        .to_vec(),
+    );
+    match validate_request_verifier_details(&with_alternative, &alternative_constraints) {
+        Err(PrimitivesError::ValidationViolation(violation)) => {
+            assert_eq!(violation.rule, ValidationRule::VerifierConstraints);
+            assert_eq!(
+                violation.observed.as_deref(),
+                Some(
+                    address!("1234567890123456789012345678901234567890")
+                        .to_string()
+                        .as_str()
+                )
+            );
+        }
+        other => panic!("expected a verifier constraints violation, got {other:?}"),
+    }
+
+    let leading_constraints = RequestVerifierConstraints {
+        verifier: Some(address!("1234567890123456789012345678901234567890")),
+        ..Default::default()
+    };
+    assert!(validate_request_verifier_details(&with_alternative, &leading_constraints).is_ok());
+}
@@ -0,0 +1,350 @@
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta::ProofRequest;
+use taralli_primitives::abi::universal_porchetta::UniversalPorchetta::ProofOffer;
+use taralli_primitives::alloy::primitives::{
+    address, bytes, Address, PrimitiveSignature, B256, U256,
+};
+use taralli_primitives::alloy::sol_types::SolValue;
+use taralli_primitives::compression_utils::intents::{
+    CompressedIntent, ComputeOfferCompressed, ComputeRequestCompressed, PartialComputeOffer,
+    PartialComputeRequest,
+};
+use taralli_primitives::intents::envelope::{
+    broadcast_kind, decode_broadcast_envelope, BroadcastEnvelope, BroadcastMeta, IntentEnvelope,
+    IntentKind, IntentMetadata, SubmissionEnvelope, SubscriptionMask, BROADCAST_META_VERSION,
+    INTENT_ENVELOPE_VERSION, LEGACY_INTENT_VERSION,
+};
+use taralli_primitives::systems::SystemId;
+use taralli_primitives::PrimitivesError;
+
+const REQUEST_ENVELOPE_V1: &str = include_str!("fixtures/request_envelope_v1.json");
+const OFFER_ENVELOPE_V1: &str = include_str!("fixtures/offer_envelope_v1.json");
+
+const SIGNER: Address = address!("1111111111111111111111111111111111111111");
+const MARKET: Address = address!("2222222222222222222222222222222222222222");
+const REWARD_TOKEN: Address = address!("3333333333333333333333333333333333333333");
+const STAKE_TOKEN: Address = address!("5555555555555555555555555555555555555555");
+
+fn signature_fixture() -> PrimitiveSignature {
+    PrimitiveSignature::new(U256::from(1), U256::from(2), false)
+}
+
+fn partial_request_fixture() -> PartialComputeRequest {
+    PartialComputeRequest {
+        system_id: SystemId::Risc0,
+        proof_request: ProofRequest {
+            signer: SIGNER,
+            market: MARKET,
+            nonce: U256::from(1),
+            rewardToken: REWARD_TOKEN,
+            maxRewardAmount: U256::from(1000),
+            minRewardAmount: U256::from(100),
+            minimumStake: 1000,
+            startAuctionTimestamp: 1_700_000_000,
+            endAuctionTimestamp: 1_700_000_060,
+            provingTime: 30,
+            inputsCommitment: B256::repeat_byte(0x44),
+            extraData: bytes!("0102"),
+        },
+        signature: signature_fixture(),
+    }
+}
+
+fn partial_offer_fixture() -> PartialComputeOffer {
+    PartialComputeOffer {
+        system_id: SystemId::Sp1,
+        proof_offer: ProofOffer {
+            signer: SIGNER,
+            market: MARKET,
+            nonce: U256::from(1),
+            rewardToken: REWARD_TOKEN,
+            rewardAmount: U256::from(1000),
+            stakeToken: STAKE_TOKEN,
+            stakeAmount: U256::from(100),
+            startAuctionTimestamp: 1_700_000_000,
+            endAuctionTimestamp: 1_700_000_060,
+            provingTime: 30,
+            inputsCommitment: B256::repeat_byte(0x44),
+            extraData: bytes!("0102"),
+        },
+        signature: signature_fixture(),
+    }
+}
+
+fn fixture_json(fixture: &str) -> serde_json::Value {
+    serde_json::from_str(fixture).expect("Couldn't parse fixture")
+}
+
+/// The v1 fixture at the current version, the JSON layout hasn't changed since
+fn current_fixture_json(fixture: &str) -> serde_json::Value {
+    let mut json = fixture_json(fixture);
+    json["v"] = INTENT_ENVELOPE_VERSION.into();
+    json
+}
+
+#[test]
+/// Pins the JSON of an enveloped request, renaming or retyping a field breaks this test.
+fn should_serialize_request_envelope_to_pinned_json() {
+    let envelope = IntentEnvelope::new(IntentKind::Request, partial_request_fixture());
+
+    let json = serde_json::to_value(&envelope).unwrap();
+
+    assert_eq!(json, current_fixture_json(REQUEST_ENVELOPE_V1));
+}
+
+#[test]
+/// Pins the JSON of an enveloped offer, renaming or retyping a field breaks this test.
+fn should_serialize_offer_envelope_to_pinned_json() {
+    let envelope = IntentEnvelope::new(IntentKind::Offer, partial_offer_fixture());
+
+    let json = serde_json::to_value(&envelope).unwrap();
+
+    assert_eq!(json, current_fixture_json(OFFER_ENVELOPE_V1));
+}
+
+#[test]
+/// Ensures stored v1 envelopes still deserialize with the current code.
+fn should_deserialize_stored_v1_envelopes() {
+    let request = IntentEnvelope::<PartialComputeRequest>::from_json_slice(
+        REQUEST_ENVELOPE_V1.as_bytes(),
+        IntentKind::Request,
+    )
+    .unwrap();
+    let expected_request = partial_request_fixture();
+    assert_eq!(request.v, 1);
+    assert_eq!(request.payload.system_id, expected_request.system_id);
+    assert_eq!(
+        request.payload.proof_request.abi_encode(),
+        expected_request.proof_request.abi_encode()
+    );
+    assert_eq!(request.payload.signature, expected_request.signature);
+
+    let offer = IntentEnvelope::<PartialComputeOffer>::from_json_slice(
+        OFFER_ENVELOPE_V1.as_bytes(),
+        IntentKind::Offer,
+    )
+    .unwrap();
+    let expected_offer = partial_offer_fixture();
+    assert_eq!(offer.v, 1);
+    assert_eq!(offer.payload.system_id, expected_offer.system_id);
+    assert_eq!(
+        offer.payload.proof_offer.abi_encode(),
+        expected_offer.proof_offer.abi_encode()
+    );
+    assert_eq!(offer.payload.signature, expected_offer.signature);
+}
+
+#[test]
+/// Ensures bare payloads sent by clients predating the envelope are read as the legacy version.
+fn should_deserialize_legacy_payload() {
+    let bare = fixture_json(REQUEST_ENVELOPE_V1)["payload"].to_string();
+
+    let request = IntentEnvelope::<PartialComputeRequest>::from_json_slice(
+        bare.as_bytes(),
+        IntentKind::Request,
+    )
+    .unwrap();
+
+    assert_eq!(request.v, LEGACY_INTENT_VERSION);
+    assert_eq!(request.payload.system_id, SystemId::Risc0);
+}
+
+#[test]
+/// Ensures the submitter's metadata is read next to the envelope's fields, left out of the JSON
+/// when empty, and absent from bare payloads.
+fn should_deserialize_submission_metadata() {
+    let metadata = IntentMetadata::from([
+        ("job".to_string(), "42".to_string()),
+        ("team".to_string(), "proving".to_string()),
+    ]);
+    let submission = SubmissionEnvelope::new(
+        IntentKind::Request,
+        partial_request_fixture(),
+        metadata.clone(),
+    );
+    let mut expected = current_fixture_json(REQUEST_ENVELOPE_V1);
+    expected["metadata"] = serde_json::to_value(&metadata).unwrap();
+    let json = serde_json::to_vec(&submission).unwrap();
+    assert_eq!(
+        serde_json::from_slice::<serde_json::Value>(&json).unwrap(),
+        expected
+    );
+
+    let read =
+        SubmissionEnvelope::<PartialComputeRequest>::from_json_slice(&json, IntentKind::Request)
+            .unwrap();
+    assert_eq!(read.metadata, metadata);
+    assert_eq!(read.envelope.v, INTENT_ENVELOPE_VERSION);
+    assert_eq!(read.envelope.payload.system_id, SystemId::Risc0);
+    // servers reading the envelope alone ignore the metadata
+    assert!(
+        IntentEnvelope::<PartialComputeRequest>::from_json_slice(&json, IntentKind::Request)
+            .is_ok()
+    );
+
+    let unlabelled = serde_json::to_value(SubmissionEnvelope::new(
+        IntentKind::Request,
+        partial_request_fixture(),
+        IntentMetadata::new(),
+    ))
+    .unwrap();
+    assert_eq!(unlabelled, current_fixture_json(REQUEST_ENVELOPE_V1));
+
+    let bare = fixture_json(REQUEST_ENVELOPE_V1)["payload"].to_string();
+    let read = SubmissionEnvelope::<PartialComputeRequest>::from_json_slice(
+        bare.as_bytes(),
+        IntentKind::Request,
+    )
+    .unwrap();
+    assert_eq!(read.envelope.v, LEGACY_INTENT_VERSION);
+    assert!(read.metadata.is_empty());
+
+    let mut invalid = expected;
+    invalid["metadata"] = serde_json::json!({ "job": 42 });
+    assert!(
+        SubmissionEnvelope::<PartialComputeRequest>::from_json_slice(
+            invalid.to_string().as_bytes(),
+            IntentKind::Request,
+        )
+        .is_err()
+    );
+}
+
+#[test]
+/// Ensures envelopes of unknown versions or of the wrong kind are rejected.
+fn should_reject_unsupported_envelopes() {
+    let mut json = fixture_json(REQUEST_ENVELOPE_V1);
+    json["v"] = (INTENT_ENVELOPE_VERSION + 1).into();
+    let err = IntentEnvelope::<PartialComputeRequest>::from_json_slice(
+        json.to_string().as_bytes(),
+        IntentKind::Request,
+    )
+    .unwrap_err();
+    assert!(matches!(
+        err,
+        PrimitivesError::UnsupportedIntentVersion(v) if v == INTENT_ENVELOPE_VERSION + 1
+    ));
+
+    assert!(IntentEnvelope::<PartialComputeRequest>::from_json_slice(
+        REQUEST_ENVELOPE_V1.as_bytes(),
+        IntentKind::Offer,
+    )
+    .is_err());
+}
+
+#[test]
+/// Ensures broadcasts carry their metadata from version 2 on, while v1 broadcasts still decode
+/// without it.
+fn should_decode_broadcast_envelopes_of_both_layouts() {
+    let meta = BroadcastMeta {
+        sequence: 42,
+        received_at_ms: 1_700_000_000_123,
+    };
+    let current = bincode::serialize(&BroadcastEnvelope::new(
+        IntentKind::Request,
+        meta,
+        partial_request_fixture(),
+    ))
+    .unwrap();
+    let (envelope, decoded_meta) =
+        decode_broadcast_envelope::<PartialComputeRequest>(&current).unwrap();
+    assert_eq!(envelope.v, INTENT_ENVELOPE_VERSION);
+    assert_eq!(envelope.kind, IntentKind::Request);
+    assert_eq!(envelope.payload.signature, signature_fixture());
+    assert_eq!(decoded_meta, Some(meta));
+
+    let v1 = bincode::serialize(&IntentEnvelope {
+        v: 1,
+        kind: IntentKind::Request,
+        payload: partial_request_fixture(),
+    })
+    .unwrap();
+    let (envelope, decoded_meta) = decode_broadcast_envelope::<PartialComputeRequest>(&v1).unwrap();
+    assert_eq!(envelope.v, 1);
+    assert_eq!(
+        envelope.payload.proof_request.abi_encode(),
+        partial_request_fixture().proof_request.abi_encode()
+    );
+    assert_eq!(decoded_meta, None);
+}
+
+#[test]
+/// Ensures subscription masks encode the requests' systems in the low byte and the offers' in the
+/// high byte, and decode back.
+fn should_encode_subscription_masks() {
+    let mask = SubscriptionMask {
+        requests: 0b01,
+        offers: 0b10,
+    };
+    assert_eq!(mask.encode(), 0x0201);
+    assert_eq!(SubscriptionMask::decode(mask.encode()), mask);
+    assert_eq!(mask.systems(IntentKind::Request), 0b01);
+    assert_eq!(mask.systems(IntentKind::Offer), 0b10);
+
+    let requests_only = SubscriptionMask::requests_only(0b11);
+    assert_eq!(requests_only.encode(), 0x0003);
+    assert_eq!(requests_only.systems(IntentKind::Offer), 0);
+}
+
+#[test]
+/// Ensures requests and offers broadcast over the same connection are told apart by their
+/// envelope, and that broadcasts of earlier versions are read as requests.
+fn should_decode_broadcasts_of_either_kind() {
+    let meta = BroadcastMeta {
+        sequence: 7,
+        received_at_ms: 1_700_000_000_123,
+    };
+    let request = ComputeRequestCompressed::from((partial_request_fixture(), vec![1, 2, 3]));
+    let offer = ComputeOfferCompressed::from((partial_offer_fixture(), vec![4, 5, 6]));
+    // broadcasts carry the payload of either kind as its bincode encoding
+    assert_eq!(
+        CompressedIntent::Request((&request).into())
+            .encode_payload()
+            .unwrap(),
+        bincode::serialize(&request).unwrap()
+    );
+    assert_eq!(
+        CompressedIntent::Offer(offer.clone())
+            .encode_payload()
+            .unwrap(),
+        bincode::serialize(&offer).unwrap()
+    );
+
+    let request_broadcast =
+        bincode::serialize(&BroadcastEnvelope::new(IntentKind::Request, meta, request)).unwrap();
+    let offer_broadcast =
+        bincode::serialize(&BroadcastEnvelope::new(IntentKind::Offer, meta, offer)).unwrap();
+    assert_eq!(
+        broadcast_kind(&request_broadcast).unwrap(),
+        IntentKind::Request
+    );
+    assert_eq!(broadcast_kind(&offer_broadcast).unwrap(), IntentKind::Offer);
+
+    let (envelope, decoded_meta) = CompressedIntent::decode_broadcast(&offer_broadcast).unwrap();
+    assert_eq!(envelope.v, INTENT_ENVELOPE_VERSION);
+    assert_eq!(envelope.payload.kind(), IntentKind::Offer);
+    assert_eq!(envelope.payload.system_id(), SystemId::Risc0);
+    assert_eq!(decoded_meta, Some(meta));
+    match envelope.payload {
+        CompressedIntent::Offer(offer) => assert_eq!(offer.system, vec![4, 5, 6]),
+        other => panic!("Unexpected intent: {other:?}"),
+    }
+
+    let (envelope, _) = CompressedIntent::decode_broadcast(&request_broadcast).unwrap();
+    match envelope.payload {
+        CompressedIntent::Request(request) => assert_eq!(request.system, vec![1, 2, 3]),
+        other => panic!("Unexpected intent: {other:?}"),
+    }
+
+    // requests of the version preceding offer broadcasts are laid out the same
+    let v2 = bincode::serialize(&BroadcastEnvelope {
+        v: BROADCAST_META_VERSION,
+        kind: IntentKind::Request,
+        meta,
+        payload: ComputeRequestCompressed::from((partial_request_fixture(), vec![1, 2, 3])),
+    })
+    .unwrap();
+    let (envelope, decoded_meta) = CompressedIntent::decode_broadcast(&v2).unwrap();
+    assert_eq!(envelope.v, BROADCAST_META_VERSION);
+    assert_eq!(envelope.payload.kind(), IntentKind::Request);
+    assert_eq!(decoded_meta, Some(meta));
+}
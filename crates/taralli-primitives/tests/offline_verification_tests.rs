@@ -0,0 +1,191 @@
+use std::str::FromStr;
+
+use taralli_primitives::abi::universal_porchetta::{
+    ProofOfferVerifierDetails, UniversalPorchetta::ProofOffer,
+};
+use taralli_primitives::alloy::primitives::{
+    address, fixed_bytes, Address, PrimitiveSignature, B256, U256,
+};
+use taralli_primitives::alloy::signers::{local::PrivateKeySigner, Signer};
+use taralli_primitives::alloy::sol_types::SolValue;
+use taralli_primitives::intents::offer::ComputeOffer;
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::intents::ComputeIntent;
+use taralli_primitives::markets::SEPOLIA_CHAIN_ID;
+use taralli_primitives::systems::{SystemId, SystemParams};
+use taralli_primitives::validation::offer::{
+    ComputeOfferValidator, OfferValidationConfig, OfferVerifierConstraints,
+};
+use taralli_primitives::validation::offline::verify_intent_offline;
+use taralli_primitives::validation::request::{
+    ComputeRequestValidator, RequestValidationConfig, RequestVerifierConstraints,
+};
+use taralli_primitives::validation::violation::ValidationRule;
+use taralli_primitives::validation::IntentValidator;
+use taralli_primitives::PrimitivesError;
+
+pub mod common;
+use common::fixtures::{request_fixture, risc0_system, LATEST_TIMESTAMP, MARKET, VERIFIER};
+
+const DUMMY_PRIV_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+fn offer_fixture(signer: Address) -> ComputeOffer<SystemParams> {
+    ComputeOffer {
+        system_id: SystemId::Risc0,
+        system: risc0_system(),
+        proof_offer: ProofOffer {
+            signer,
+            market: MARKET,
+            nonce: U256::from(7),
+            rewardToken: address!("3333333333333333333333333333333333333333"),
+            rewardAmount: U256::from(1_000),
+            stakeToken: address!("5555555555555555555555555555555555555555"),
+            stakeAmount: U256::from(100),
+            startAuctionTimestamp: LATEST_TIMESTAMP,
+            endAuctionTimestamp: LATEST_TIMESTAMP + 60,
+            provingTime: 60,
+            inputsCommitment: B256::repeat_byte(0x44),
+            extraData: ProofOfferVerifierDetails {
+                verifier: VERIFIER,
+                selector: fixed_bytes!("deadbeef"),
+                isShaCommitment: false,
+                inputsOffset: U256::ZERO,
+                inputsLength: U256::from(96),
+            }
+            .abi_encode()
+            .into(),
+        },
+        signature: PrimitiveSignature::new(U256::from(1), U256::from(2), false),
+    }
+}
+
+fn request_config() -> RequestValidationConfig {
+    RequestValidationConfig {
+        maximum_allowed_stake: 1_000,
+        ..Default::default()
+    }
+}
+
+async fn signed_request() -> ComputeRequest<SystemParams> {
+    let signer = PrivateKeySigner::from_str(DUMMY_PRIV_KEY).unwrap();
+    let mut request = request_fixture();
+    request.proof_request.signer = signer.address();
+    request.signature = signer
+        .sign_hash(&request.compute_permit2_digest(SEPOLIA_CHAIN_ID))
+        .await
+        .unwrap();
+    request
+}
+
+#[tokio::test]
+/// Ensures a signed request within every bound passes, and every check a request fails is
+/// reported at once, the signature check included.
+async fn should_report_every_offline_violation() {
+    let request = signed_request().await;
+    let constraints = RequestVerifierConstraints {
+        verifier: Some(VERIFIER),
+        ..Default::default()
+    };
+    assert!(verify_intent_offline(
+        &request,
+        LATEST_TIMESTAMP,
+        &MARKET,
+        &request_config(),
+        &constraints
+    )
+    .is_empty());
+
+    // tampering with the signed fields invalidates the signature along with the bounds they break
+    let mut tampered = request.clone();
+    tampered.proof_request.provingTime = 10;
+    tampered.proof_request.minimumStake = 1_001;
+    let other_market = address!("9999999999999999999999999999999999999999");
+    let constraints = RequestVerifierConstraints {
+        verifier: Some(other_market),
+        ..Default::default()
+    };
+    let rules: Vec<ValidationRule> = verify_intent_offline(
+        &tampered,
+        LATEST_TIMESTAMP,
+        &other_market,
+        &request_config(),
+        &constraints,
+    )
+    .into_iter()
+    .map(|violation| violation.rule)
+    .collect();
+    assert_eq!(
+        rules,
+        vec![
+            ValidationRule::ProvingTime,
+            ValidationRule::StakeBound,
+            ValidationRule::Signature,
+            ValidationRule::MarketAddress,
+            ValidationRule::VerifierConstraints,
+        ]
+    );
+
+    // past the auction, the same signed request isn't valid anymore
+    let violations = verify_intent_offline(
+        &request,
+        LATEST_TIMESTAMP + 60,
+        &MARKET,
+        &request_config(),
+        &RequestVerifierConstraints::default(),
+    );
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].rule, ValidationRule::TimestampWindow);
+}
+
+#[tokio::test]
+/// Ensures the validators fail with the first offline violation and report the unsigned ones, so
+/// they can't diverge from the offline verification.
+async fn should_validate_like_the_offline_verification() {
+    let mut request = signed_request().await;
+    request.proof_request.provingTime = 10;
+    let validator =
+        ComputeRequestValidator::new(request_config(), RequestVerifierConstraints::default());
+    let offline = verify_intent_offline(
+        &request,
+        LATEST_TIMESTAMP,
+        &MARKET,
+        &request_config(),
+        &RequestVerifierConstraints::default(),
+    );
+
+    let err = validator
+        .validate(&request, LATEST_TIMESTAMP, &MARKET)
+        .unwrap_err();
+    assert!(
+        matches!(err, PrimitivesError::ValidationViolation(ref violation) if *violation == offline[0]),
+        "{err}"
+    );
+    assert_eq!(
+        validator.unsigned_violations(&request, LATEST_TIMESTAMP, &MARKET),
+        vec![offline[0].message.clone()]
+    );
+
+    // offers, the placeholder signature failing along with the reward
+    let offer = offer_fixture(Address::ZERO);
+    let config = OfferValidationConfig {
+        maximum_allowed_reward: U256::from(999),
+        ..Default::default()
+    };
+    let offline = verify_intent_offline(
+        &offer,
+        LATEST_TIMESTAMP,
+        &MARKET,
+        &config,
+        &OfferVerifierConstraints::default(),
+    );
+    let rules: Vec<ValidationRule> = offline.iter().map(|violation| violation.rule).collect();
+    assert_eq!(
+        rules,
+        vec![ValidationRule::RewardBound, ValidationRule::Signature]
+    );
+    let validator = ComputeOfferValidator::new(config, OfferVerifierConstraints::default());
+    assert_eq!(
+        validator.unsigned_violations(&offer, LATEST_TIMESTAMP, &MARKET),
+        vec!["token reward amount invalid"]
+    );
+}
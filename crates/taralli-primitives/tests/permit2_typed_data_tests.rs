@@ -0,0 +1,182 @@
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta::ProofRequest;
+use taralli_primitives::abi::universal_porchetta::UniversalPorchetta::ProofOffer;
+use taralli_primitives::alloy::dyn_abi::TypedData;
+use taralli_primitives::alloy::primitives::{address, b256, bytes, Address, B256, U256};
+use taralli_primitives::intents::offer::{compute_offer_permit2_digest, offer_permit2_typed_data};
+use taralli_primitives::intents::request::{
+    compute_request_permit2_digest, request_permit2_typed_data,
+};
+use taralli_primitives::intents::CommonProofCommitment;
+use taralli_primitives::markets::SEPOLIA_CHAIN_ID;
+use taralli_primitives::validation::validate_time_constraints;
+
+const REQUEST_TYPED_DATA: &str = include_str!("fixtures/request_permit2_typed_data.json");
+const OFFER_TYPED_DATA: &str = include_str!("fixtures/offer_permit2_typed_data.json");
+
+/// digests verified by the markets for the fixtures on sepolia, which an `eth_signTypedData_v4`
+/// signer computes over their typed data
+const REQUEST_PERMIT2_DIGEST: B256 =
+    b256!("740b0105e6c81773928b2bf4b21a15b5f37b7c9a5b3eead92e74d9686d9e88a7");
+const OFFER_PERMIT2_DIGEST: B256 =
+    b256!("19fea5e7241aebdb424f765472c12752576edd3d104d591c791d0164535f2770");
+
+const SIGNER: Address = address!("1111111111111111111111111111111111111111");
+const MARKET: Address = address!("2222222222222222222222222222222222222222");
+const REWARD_TOKEN: Address = address!("3333333333333333333333333333333333333333");
+const STAKE_TOKEN: Address = address!("5555555555555555555555555555555555555555");
+
+fn proof_request_fixture() -> ProofRequest {
+    ProofRequest {
+        signer: SIGNER,
+        market: MARKET,
+        nonce: U256::from(1),
+        rewardToken: REWARD_TOKEN,
+        maxRewardAmount: U256::from(1000),
+        minRewardAmount: U256::from(100),
+        minimumStake: 1000,
+        startAuctionTimestamp: 1_700_000_000,
+        endAuctionTimestamp: 1_700_000_060,
+        provingTime: 30,
+        inputsCommitment: B256::repeat_byte(0x44),
+        extraData: bytes!("0102"),
+    }
+}
+
+fn proof_offer_fixture() -> ProofOffer {
+    ProofOffer {
+        signer: SIGNER,
+        market: MARKET,
+        nonce: U256::from(1),
+        rewardToken: REWARD_TOKEN,
+        rewardAmount: U256::from(1000),
+        stakeToken: STAKE_TOKEN,
+        stakeAmount: U256::from(100),
+        startAuctionTimestamp: 1_700_000_000,
+        endAuctionTimestamp: 1_700_000_060,
+        provingTime: 30,
+        inputsCommitment: B256::repeat_byte(0x44),
+        extraData: bytes!("0102"),
+    }
+}
+
+fn fixture_json(fixture: &str) -> serde_json::Value {
+    serde_json::from_str(fixture).expect("Couldn't parse fixture")
+}
+
+#[test]
+/// Pins the permit2 digests of the fixtures, any change breaks signatures the markets accept.
+fn should_compute_pinned_permit2_digests() {
+    assert_eq!(
+        compute_request_permit2_digest(&proof_request_fixture(), SEPOLIA_CHAIN_ID),
+        REQUEST_PERMIT2_DIGEST
+    );
+    assert_eq!(
+        compute_offer_permit2_digest(&proof_offer_fixture(), SEPOLIA_CHAIN_ID),
+        OFFER_PERMIT2_DIGEST
+    );
+}
+
+#[test]
+/// Pins the typed data JSON handed to wallets, renaming or retyping a field breaks this test.
+fn should_serialize_typed_data_to_pinned_json() {
+    let request_typed_data = request_permit2_typed_data(&proof_request_fixture(), SEPOLIA_CHAIN_ID);
+    let offer_typed_data = offer_permit2_typed_data(&proof_offer_fixture(), SEPOLIA_CHAIN_ID);
+
+    assert_eq!(
+        serde_json::to_value(&request_typed_data).unwrap(),
+        fixture_json(REQUEST_TYPED_DATA)
+    );
+    assert_eq!(
+        serde_json::to_value(&offer_typed_data).unwrap(),
+        fixture_json(OFFER_TYPED_DATA)
+    );
+}
+
+#[test]
+/// Ensures the pinned typed data hashes like a wallet would hash it, both built and parsed back.
+fn should_hash_typed_data_like_wallets() {
+    let request_typed_data = request_permit2_typed_data(&proof_request_fixture(), SEPOLIA_CHAIN_ID);
+    let parsed_request: TypedData = serde_json::from_str(REQUEST_TYPED_DATA).unwrap();
+    assert_eq!(
+        request_typed_data.eip712_signing_hash().unwrap(),
+        REQUEST_PERMIT2_DIGEST
+    );
+    assert_eq!(
+        parsed_request.eip712_signing_hash().unwrap(),
+        REQUEST_PERMIT2_DIGEST
+    );
+
+    let offer_typed_data = offer_permit2_typed_data(&proof_offer_fixture(), SEPOLIA_CHAIN_ID);
+    let parsed_offer: TypedData = serde_json::from_str(OFFER_TYPED_DATA).unwrap();
+    assert_eq!(
+        offer_typed_data.eip712_signing_hash().unwrap(),
+        OFFER_PERMIT2_DIGEST
+    );
+    assert_eq!(
+        parsed_offer.eip712_signing_hash().unwrap(),
+        OFFER_PERMIT2_DIGEST
+    );
+}
+
+#[test]
+/// Ensures the digests the markets verify are the signing hashes of the typed data, so
+/// signatures of external signers over it are accepted on-chain.
+fn should_digest_typed_data() {
+    assert_eq!(
+        request_permit2_typed_data(&proof_request_fixture(), SEPOLIA_CHAIN_ID)
+            .eip712_signing_hash()
+            .unwrap(),
+        compute_request_permit2_digest(&proof_request_fixture(), SEPOLIA_CHAIN_ID)
+    );
+    assert_eq!(
+        offer_permit2_typed_data(&proof_offer_fixture(), SEPOLIA_CHAIN_ID)
+            .eip712_signing_hash()
+            .unwrap(),
+        compute_offer_permit2_digest(&proof_offer_fixture(), SEPOLIA_CHAIN_ID)
+    );
+}
+
+#[test]
+/// Ensures the permit2 deadline signed over is the end of the auction, so a signature can't be
+/// used to bid once the auction ended, and that intents past it are rejected.
+fn should_sign_until_auction_end() {
+    let mut request = proof_request_fixture();
+    let mut offer = proof_offer_fixture();
+    assert_eq!(request.signature_deadline(), request.endAuctionTimestamp);
+    assert_eq!(offer.signature_deadline(), offer.endAuctionTimestamp);
+    assert_eq!(
+        serde_json::to_value(request_permit2_typed_data(&request, SEPOLIA_CHAIN_ID)).unwrap()
+            ["message"]["deadline"],
+        request.endAuctionTimestamp.to_string()
+    );
+
+    // extending the auction extends the signature, changing what is signed
+    request.endAuctionTimestamp += 1;
+    offer.endAuctionTimestamp += 1;
+    assert_ne!(
+        compute_request_permit2_digest(&request, SEPOLIA_CHAIN_ID),
+        REQUEST_PERMIT2_DIGEST
+    );
+    assert_ne!(
+        compute_offer_permit2_digest(&offer, SEPOLIA_CHAIN_ID),
+        OFFER_PERMIT2_DIGEST
+    );
+
+    let deadline = request.signature_deadline();
+    let validate = |latest_timestamp| {
+        validate_time_constraints(
+            request.startAuctionTimestamp,
+            request.endAuctionTimestamp,
+            request.provingTime,
+            latest_timestamp,
+            0,
+            0,
+            0,
+            3_600,
+            3_600,
+        )
+    };
+    assert!(validate(deadline - 1).is_ok());
+    let err = validate(deadline).unwrap_err();
+    assert!(err.to_string().contains("invalid timestamp"), "{err}");
+}
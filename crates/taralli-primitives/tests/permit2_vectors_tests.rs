@@ -0,0 +1,82 @@
+use taralli_primitives::abi::verifier_details::MAX_EXTRA_DATA_LENGTH;
+use taralli_primitives::alloy::primitives::U256;
+use taralli_primitives::intents::offer::compute_offer_permit2_digest;
+use taralli_primitives::intents::request::compute_request_permit2_digest;
+use taralli_primitives::permit2_vectors::{
+    Permit2Vectors, DEFAULT_RANDOM_VECTORS, PERMIT2_VECTORS_CHAIN_ID,
+};
+
+const STORED_VECTORS: &str =
+    include_str!("../../../contracts/test-proof-data/permit2/permit2_vectors.json");
+
+#[test]
+/// Ensures the current digest and signing code reproduces the vectors shared with the Solidity
+/// tests byte for byte. Regenerate them with `cargo run -p taralli-primitives --bin
+/// permit2_vectors` only when the change of digest is intended.
+fn should_reproduce_stored_vectors() {
+    let generated = Permit2Vectors::generate(DEFAULT_RANDOM_VECTORS)
+        .unwrap()
+        .to_json()
+        .unwrap();
+    assert!(
+        generated == STORED_VECTORS,
+        "generated permit2 vectors differ from the stored ones"
+    );
+}
+
+#[test]
+/// Ensures every vector is signed by the vectors' signer over its digest, and the edge cases are
+/// covered.
+fn should_sign_vectors_with_fixed_key() {
+    let vectors = Permit2Vectors::generate(2).unwrap();
+    assert_eq!(vectors.requests.len(), 6);
+    assert_eq!(vectors.offers.len(), 6);
+
+    for vector in &vectors.requests {
+        assert_eq!(
+            vector.permit2_digest,
+            compute_request_permit2_digest(&vector.proof_request, PERMIT2_VECTORS_CHAIN_ID)
+        );
+        assert_eq!(
+            vector
+                .signature
+                .recover_address_from_prehash(&vector.permit2_digest)
+                .unwrap(),
+            vectors.signer,
+            "{}",
+            vector.name
+        );
+    }
+    for vector in &vectors.offers {
+        assert_eq!(
+            vector.permit2_digest,
+            compute_offer_permit2_digest(&vector.proof_offer, PERMIT2_VECTORS_CHAIN_ID)
+        );
+        assert_eq!(
+            vector
+                .signature
+                .recover_address_from_prehash(&vector.permit2_digest)
+                .unwrap(),
+            vectors.signer,
+            "{}",
+            vector.name
+        );
+    }
+
+    let request = |name: &str| {
+        &vectors
+            .requests
+            .iter()
+            .find(|vector| vector.name == name)
+            .unwrap()
+            .proof_request
+    };
+    assert_eq!(request("zero").maxRewardAmount, U256::ZERO);
+    assert_eq!(request("max").maxRewardAmount, U256::MAX);
+    assert_eq!(request("max").minimumStake, u128::MAX);
+    assert!(request("empty_extra_data").extraData.is_empty());
+    assert_eq!(
+        request("max_extra_data").extraData.len(),
+        MAX_EXTRA_DATA_LENGTH
+    );
+}
@@ -0,0 +1,196 @@
+use serde_json::json;
+use taralli_primitives::systems::arkworks::{ArkworksProofParams, R1csHeader};
+use taralli_primitives::systems::risc0::Risc0ProofParams;
+use taralli_primitives::systems::sp1::{Sp1Config, Sp1Mode, Sp1ProofParams};
+use taralli_primitives::systems::SystemParams;
+use taralli_primitives::validation::limits::{ElfLimits, R1csLimits, ResourceLimits};
+use taralli_primitives::PrimitivesError;
+
+const MIB: usize = 1024 * 1024;
+
+/// r1cs file whose constraints section precedes its header section, as circom writes them
+fn r1cs_fixture(wires: u32, constraints: u32) -> Vec<u8> {
+    let mut header = Vec::new();
+    header.extend_from_slice(&32u32.to_le_bytes()); // field size
+    header.extend_from_slice(&[0xff; 32]); // prime
+    header.extend_from_slice(&wires.to_le_bytes());
+    header.extend_from_slice(&1u32.to_le_bytes()); // public outputs
+    header.extend_from_slice(&2u32.to_le_bytes()); // public inputs
+    header.extend_from_slice(&3u32.to_le_bytes()); // private inputs
+    header.extend_from_slice(&4u64.to_le_bytes()); // labels
+    header.extend_from_slice(&constraints.to_le_bytes());
+
+    let constraints_section = vec![0u8; 48];
+    let mut r1cs = b"r1cs".to_vec();
+    r1cs.extend_from_slice(&1u32.to_le_bytes()); // version
+    r1cs.extend_from_slice(&2u32.to_le_bytes()); // sections
+    for (section_type, section) in [(2u32, &constraints_section), (1u32, &header)] {
+        r1cs.extend_from_slice(&section_type.to_le_bytes());
+        r1cs.extend_from_slice(&(section.len() as u64).to_le_bytes());
+        r1cs.extend_from_slice(section);
+    }
+    r1cs
+}
+
+fn risc0_system(elf_bytes: usize, input_bytes: usize) -> SystemParams {
+    SystemParams::Risc0(Risc0ProofParams {
+        elf: vec![1; elf_bytes],
+        inputs: vec![2; input_bytes],
+    })
+}
+
+fn sp1_system(elf_bytes: usize, input_bytes: usize) -> SystemParams {
+    SystemParams::Sp1(Sp1ProofParams {
+        config: Sp1Config {
+            mode: Sp1Mode::Groth16,
+        },
+        elf: vec![1; elf_bytes],
+        inputs: vec![2; input_bytes],
+    })
+}
+
+fn arkworks_system(r1cs: Vec<u8>) -> SystemParams {
+    SystemParams::Arkworks(ArkworksProofParams {
+        r1cs,
+        wasm: vec![1; 16],
+        inputs: json!({ "in": ["0", "1"] }),
+    })
+}
+
+fn elf_limits() -> ElfLimits {
+    ElfLimits {
+        max_elf_bytes: 2 * MIB,
+        max_input_bytes: 1024,
+        proving_seconds_per_mib: 10,
+    }
+}
+
+fn limits() -> ResourceLimits {
+    ResourceLimits {
+        risc0: elf_limits(),
+        sp1: elf_limits(),
+        arkworks: R1csLimits {
+            max_r1cs_bytes: 1024,
+            max_wasm_bytes: 1024,
+            max_signals: 1_000,
+            max_input_bytes: 1024,
+            proving_seconds_per_million_constraints: 30,
+        },
+    }
+}
+
+fn rejection(result: taralli_primitives::Result<()>) -> String {
+    match result {
+        Err(PrimitivesError::ValidationError(reason)) => reason,
+        other => panic!("expected a validation error, got {other:?}"),
+    }
+}
+
+#[test]
+/// Ensures ELF programs and their inputs are accepted up to their maximum size, inclusive.
+fn should_bound_elf_and_input_sizes() {
+    let limits = limits();
+    for system in [risc0_system, sp1_system] {
+        // 2 MiB of elf and 1 KiB of inputs start a third MiB
+        limits
+            .check(&system(2 * MIB, 1024), 30)
+            .expect("workload at the limits should pass");
+
+        let reason = rejection(limits.check(&system(2 * MIB + 1, 32), 30));
+        assert!(reason.contains("elf size"), "{reason}");
+        let reason = rejection(limits.check(&system(32, 1025), 30));
+        assert!(reason.contains("inputs size"), "{reason}");
+    }
+}
+
+#[test]
+/// Ensures the proving time floor grows with each started MiB of ELF and inputs.
+fn should_scale_proving_time_floor_with_elf_workload() {
+    let limits = limits();
+    // a single started MiB
+    limits.check(&risc0_system(MIB, 0), 10).unwrap();
+    let reason = rejection(limits.check(&risc0_system(MIB, 0), 9));
+    assert!(reason.contains("at least 10s"), "{reason}");
+
+    // one byte past the MiB starts a second one
+    limits.check(&risc0_system(MIB, 1), 20).unwrap();
+    let reason = rejection(limits.check(&risc0_system(MIB, 1), 19));
+    assert!(reason.contains("at least 20s"), "{reason}");
+}
+
+#[test]
+/// Ensures r1cs circuits are bounded by their size, their declared signal count and a proving
+/// time floor scaled by their declared constraint count.
+fn should_bound_r1cs_workload() {
+    let limits = limits();
+    limits
+        .check(&arkworks_system(r1cs_fixture(1_000, 1_000_000)), 30)
+        .expect("circuit at the limits should pass");
+
+    let reason = rejection(limits.check(&arkworks_system(r1cs_fixture(1_001, 1)), 30));
+    assert!(reason.contains("signal count 1001"), "{reason}");
+
+    let reason = rejection(limits.check(&arkworks_system(r1cs_fixture(10, 1_000_001)), 59));
+    assert!(reason.contains("at least 60s"), "{reason}");
+    limits
+        .check(&arkworks_system(r1cs_fixture(10, 1_000_001)), 60)
+        .unwrap();
+
+    let mut oversized = r1cs_fixture(10, 1);
+    oversized.resize(1025, 0);
+    let reason = rejection(limits.check(&arkworks_system(oversized), 30));
+    assert!(reason.contains("r1cs size 1025"), "{reason}");
+}
+
+#[test]
+/// Ensures r1cs files whose header can't be read are rejected rather than let through.
+fn should_reject_malformed_r1cs_header() {
+    let well_formed = r1cs_fixture(10, 1);
+    // the header section is declared and cut two bytes short of the constraint count
+    let mut truncated_header = well_formed[..well_formed.len() - 2].to_vec();
+    truncated_header[76..84].copy_from_slice(&62u64.to_le_bytes());
+    let mut without_header = well_formed[..12].to_vec();
+    without_header[8..12].copy_from_slice(&0u32.to_le_bytes());
+    let mut overrunning_section = well_formed.clone();
+    overrunning_section[16..24].copy_from_slice(&u64::MAX.to_le_bytes());
+
+    for (r1cs, expected) in [
+        (b"r1c".to_vec(), "truncated magic"),
+        (b"wasm\x01\x00\x00\x00".to_vec(), "missing r1cs magic"),
+        (truncated_header, "truncated header"),
+        (without_header, "no header section"),
+        (overrunning_section, "section overruns the file"),
+    ] {
+        match R1csHeader::parse(&r1cs) {
+            Err(PrimitivesError::ProverInputsError(reason)) => {
+                assert!(reason.contains(expected), "{reason}")
+            }
+            other => panic!("expected a malformed r1cs error, got {other:?}"),
+        }
+        let reason = rejection(limits().check(&arkworks_system(r1cs), 30));
+        assert!(reason.contains("malformed r1cs"), "{reason}");
+    }
+}
+
+#[test]
+/// Ensures the header of a circom compiled circuit is read past its constraints section.
+fn should_parse_compiled_r1cs_header() {
+    let r1cs = std::fs::read(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/../../contracts/test-proof-data/groth16/sha/sha256_test512.r1cs"
+    ))
+    .expect("Couldn't read r1cs");
+
+    let header = R1csHeader::parse(&r1cs).unwrap();
+    assert_eq!(header.field_size, 32);
+    assert_eq!(header.wires, 62_417);
+    assert_eq!(header.public_outputs, 256);
+    assert_eq!(header.public_inputs, 0);
+    assert_eq!(header.private_inputs, 512);
+    assert_eq!(header.constraints, 62_528);
+
+    // the default limits accept the circuit
+    ResourceLimits::default()
+        .check(&arkworks_system(r1cs), 30)
+        .unwrap();
+}
@@ -0,0 +1,137 @@
+use serde_json::json;
+use taralli_primitives::systems::{System, SystemId, SystemInputs, SystemParams};
+
+fn parse(system_id: SystemId, params: serde_json::Value) -> Result<SystemParams, String> {
+    SystemParams::try_from((&system_id, params.to_string().into_bytes()))
+}
+
+#[test]
+/// Ensures well shaped params of every system convert, exposing their inputs with the right type.
+fn should_convert_params_of_all_systems() {
+    let risc0 = parse(
+        SystemId::Risc0,
+        json!({ "elf": [1, 2, 3], "inputs": vec![4u8; 64] }),
+    )
+    .unwrap();
+    assert_eq!(risc0.system_id(), SystemId::Risc0);
+    assert_eq!(risc0.inputs(), SystemInputs::Bytes(vec![4; 64]));
+
+    let sp1 = parse(
+        SystemId::Sp1,
+        json!({ "config": { "mode": "Groth16" }, "elf": [1, 2, 3], "inputs": [4, 5, 6] }),
+    )
+    .unwrap();
+    assert_eq!(sp1.system_id(), SystemId::Sp1);
+    assert_eq!(sp1.inputs(), SystemInputs::Bytes(vec![4, 5, 6]));
+
+    let arkworks = parse(
+        SystemId::Arkworks,
+        json!({ "r1cs": [1, 2, 3], "wasm": [4, 5, 6], "inputs": { "in": ["0", "1"] } }),
+    )
+    .unwrap();
+    assert_eq!(arkworks.system_id(), SystemId::Arkworks);
+    assert_eq!(
+        arkworks.inputs(),
+        SystemInputs::Json(json!({ "in": ["0", "1"] }))
+    );
+}
+
+#[test]
+/// Ensures params parsing but misshaped for their system are rejected on conversion.
+fn should_reject_misshaped_inputs() {
+    let err = parse(
+        SystemId::Risc0,
+        json!({ "elf": [1, 2, 3], "inputs": [4, 5, 6] }),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        "Invalid risc0 params: Prover Inputs validation error: inputs must be abi encoded 32 byte words, got 3 bytes"
+    );
+
+    let err = parse(
+        SystemId::Arkworks,
+        json!({ "r1cs": [1, 2, 3], "wasm": [4, 5, 6], "inputs": ["0", "1"] }),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        "Invalid arkworks params: Prover Inputs validation error: inputs must be a JSON object of circuit signal names to values"
+    );
+
+    let err = parse(
+        SystemId::Sp1,
+        json!({ "config": { "mode": "Plonk" }, "elf": [], "inputs": [1] }),
+    )
+    .unwrap_err();
+    assert!(
+        err.starts_with("Invalid sp1 params"),
+        "unexpected error: {err}"
+    );
+}
+
+#[test]
+/// Ensures a missing field is reported along with the system whose params lack it.
+fn should_name_missing_field() {
+    let err = parse(SystemId::Risc0, json!({ "inputs": vec![4u8; 32] })).unwrap_err();
+
+    assert!(
+        err.starts_with("Failed to parse risc0 params: missing field `elf`"),
+        "unexpected error: {err}"
+    );
+}
+
+#[test]
+/// Ensures inputs only convert to the params value of systems taking their type.
+fn should_match_inputs_type_to_system() {
+    assert_eq!(
+        SystemInputs::Bytes(vec![1, 2])
+            .into_params_value(SystemId::Sp1)
+            .unwrap(),
+        json!([1, 2])
+    );
+    assert_eq!(
+        SystemInputs::Json(json!({ "a": 1 }))
+            .into_params_value(SystemId::Arkworks)
+            .unwrap(),
+        json!({ "a": 1 })
+    );
+
+    let err = SystemInputs::Json(json!({ "a": 1 }))
+        .into_params_value(SystemId::Risc0)
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Prover Inputs validation error: risc0 inputs must be bytes, got JSON"
+    );
+    assert!(SystemInputs::Bytes(vec![1])
+        .into_params_value(SystemId::Arkworks)
+        .is_err());
+}
+
+#[test]
+/// Ensures the inputs workers take are read with the type their system takes.
+fn should_read_typed_inputs_for_workers() {
+    let risc0 = parse(
+        SystemId::Risc0,
+        json!({ "elf": [1, 2, 3], "inputs": vec![4u8; 32] }),
+    )
+    .unwrap();
+    assert_eq!(risc0.inputs().into_bytes().unwrap(), vec![4; 32]);
+    assert!(risc0.inputs().into_json().is_err());
+
+    let arkworks = parse(
+        SystemId::Arkworks,
+        json!({ "r1cs": [1, 2, 3], "wasm": [4, 5, 6], "inputs": { "in": ["0", "1"] } }),
+    )
+    .unwrap();
+    assert_eq!(
+        arkworks.inputs().into_json().unwrap(),
+        json!({ "in": ["0", "1"] })
+    );
+    let err = arkworks.inputs().into_bytes().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Prover Inputs validation error: expected bytes inputs, got JSON"
+    );
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+
+use serde_json::json;
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::systems::risc0::Risc0ProofParams;
+use taralli_primitives::systems::sp1::{Sp1Config, Sp1Mode, Sp1ProofParams};
+use taralli_primitives::systems::{System, SystemId, SystemParams};
+use taralli_primitives::validation::request::{
+    ComputeRequestValidator, RequestValidationConfig, RequestVerifierConstraints,
+};
+use taralli_primitives::validation::{
+    BaseValidationConfig, CommonValidationConfig, IntentValidator, SystemValidationOverrides,
+};
+
+pub mod common;
+use common::fixtures::{request_fixture, LATEST_TIMESTAMP, MARKET};
+
+/// Request of `system` proven within a minute, staking 500
+fn request_of(system: SystemParams) -> ComputeRequest<SystemParams> {
+    let mut request = request_fixture();
+    request.system_id = system.system_id();
+    request.system = system;
+    request
+}
+
+fn risc0_request() -> ComputeRequest<SystemParams> {
+    request_of(SystemParams::Risc0(Risc0ProofParams {
+        elf: vec![1; 32],
+        inputs: vec![2; 32],
+    }))
+}
+
+fn sp1_request() -> ComputeRequest<SystemParams> {
+    request_of(SystemParams::Sp1(Sp1ProofParams {
+        config: Sp1Config {
+            mode: Sp1Mode::Groth16,
+        },
+        elf: vec![1; 32],
+        inputs: vec![2; 32],
+    }))
+}
+
+/// Risc0 requests need two minutes of proving time, sp1 requests may stake 100 at most
+fn config() -> RequestValidationConfig {
+    RequestValidationConfig {
+        base: BaseValidationConfig {
+            minimum_proving_time: 30,
+            system_overrides: HashMap::from([(
+                SystemId::Risc0,
+                SystemValidationOverrides {
+                    minimum_proving_time: Some(120),
+                    ..Default::default()
+                },
+            )]),
+            ..Default::default()
+        },
+        maximum_allowed_stake: 1_000,
+        system_maximum_allowed_stake: HashMap::from([(SystemId::Sp1, 100)]),
+    }
+}
+
+#[test]
+/// Ensures the same request passes the bounds of one system and fails the overridden bounds of
+/// another.
+fn should_validate_against_the_bounds_of_the_intent_system() {
+    let validator = ComputeRequestValidator::new(config(), RequestVerifierConstraints::default());
+    let violations = |request: &ComputeRequest<SystemParams>| {
+        validator.unsigned_violations(request, LATEST_TIMESTAMP, &MARKET)
+    };
+
+    assert_eq!(violations(&risc0_request()), vec!["proving time too low"]);
+    assert_eq!(violations(&sp1_request()), vec!["eth stake amount invalid"]);
+
+    // without overrides both pass
+    let validator = ComputeRequestValidator::new(
+        RequestValidationConfig {
+            base: BaseValidationConfig {
+                minimum_proving_time: 30,
+                ..Default::default()
+            },
+            maximum_allowed_stake: 1_000,
+            ..Default::default()
+        },
+        RequestVerifierConstraints::default(),
+    );
+    assert!(validator
+        .unsigned_violations(&risc0_request(), LATEST_TIMESTAMP, &MARKET)
+        .is_empty());
+    assert!(validator
+        .unsigned_violations(&sp1_request(), LATEST_TIMESTAMP, &MARKET)
+        .is_empty());
+}
+
+#[test]
+/// Ensures overridden bounds are parsed by system and unset ones fall back to the base config.
+fn should_fall_back_to_base_bounds() {
+    let config: RequestValidationConfig = serde_json::from_value(json!({
+        "base": {
+            "minimum_proving_time": 30,
+            "maximum_start_delay": 300,
+            "supported_systems": ["Arkworks", "Risc0", "Sp1"],
+            "system_overrides": {
+                "Risc0": { "minimum_proving_time": 1800, "maximum_auction_length": 172800 }
+            }
+        },
+        "maximum_allowed_stake": 1000,
+        "system_maximum_allowed_stake": { "Risc0": 5000 }
+    }))
+    .unwrap();
+
+    let risc0 = config.for_system(SystemId::Risc0);
+    assert_eq!(risc0.minimum_proving_time(), 1800);
+    assert_eq!(risc0.maximum_auction_length(), 172_800);
+    assert_eq!(risc0.maximum_start_delay(), 300);
+    assert_eq!(risc0.maximum_allowed_stake, 5_000);
+
+    let arkworks = config.for_system(SystemId::Arkworks);
+    assert_eq!(arkworks.minimum_proving_time(), 30);
+    assert_eq!(
+        arkworks.maximum_auction_length(),
+        BaseValidationConfig::default().maximum_auction_length
+    );
+    assert_eq!(arkworks.maximum_allowed_stake, 1_000);
+}
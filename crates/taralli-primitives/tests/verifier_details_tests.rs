@@ -0,0 +1,125 @@
+use taralli_primitives::abi::universal_bombetta::ProofRequestVerifierDetails;
+use taralli_primitives::abi::universal_porchetta::ProofOfferVerifierDetails;
+use taralli_primitives::abi::verifier_details::{
+    decode_offer_verifier_details, decode_request_verifier_details, decode_verifier_details,
+    DecodeError, DecodeMode, MAX_EXTRA_DATA_LENGTH,
+};
+use taralli_primitives::alloy::primitives::{address, fixed_bytes, Address, B256, U256};
+use taralli_primitives::alloy::sol_types::SolValue;
+
+const VERIFIER: Address = address!("1234567890123456789012345678901234567890");
+
+fn request_verifier_details() -> ProofRequestVerifierDetails {
+    ProofRequestVerifierDetails {
+        verifier: VERIFIER,
+        selector: fixed_bytes!("deadbeef"),
+        isShaCommitment: true,
+        inputsOffset: U256::from(32),
+        inputsLength: U256::from(64),
+        hasPartialCommitmentResultCheck: false,
+        submittedPartialCommitmentResultOffset: U256::ZERO,
+        submittedPartialCommitmentResultLength: U256::ZERO,
+        predeterminedPartialCommitment: B256::repeat_byte(0x11),
+    }
+}
+
+#[test]
+/// Ensures well formed encodings decode in both modes.
+fn should_decode_verifier_details() {
+    let encoded = request_verifier_details().abi_encode();
+
+    let lenient = decode_verifier_details(&encoded).unwrap();
+    let strict = decode_request_verifier_details(&encoded, DecodeMode::Strict).unwrap();
+
+    assert_eq!(lenient.abi_encode(), encoded);
+    assert_eq!(strict.abi_encode(), encoded);
+
+    let offer = ProofOfferVerifierDetails {
+        verifier: VERIFIER,
+        selector: fixed_bytes!("deadbeef"),
+        isShaCommitment: false,
+        inputsOffset: U256::ZERO,
+        inputsLength: U256::from(96),
+    };
+    let decoded = decode_offer_verifier_details(&offer.abi_encode(), DecodeMode::Strict).unwrap();
+    assert_eq!(decoded.abi_encode(), offer.abi_encode());
+}
+
+#[test]
+/// Ensures truncated encodings name the first incomplete field.
+fn should_reject_truncated_verifier_details() {
+    let encoded = request_verifier_details().abi_encode();
+
+    let err = decode_verifier_details(&encoded[..100]).unwrap_err();
+
+    assert_eq!(
+        err,
+        DecodeError::TooShort {
+            actual: 100,
+            minimum: 288,
+            field: "inputsOffset",
+            offset: 96,
+        }
+    );
+}
+
+#[test]
+/// Ensures trailing bytes are only accepted by the lenient decoder, within the length bound.
+fn should_reject_over_long_verifier_details() {
+    let mut encoded = request_verifier_details().abi_encode();
+    encoded.extend_from_slice(&[0xff; 4]);
+
+    assert!(decode_verifier_details(&encoded).is_ok());
+    assert_eq!(
+        decode_request_verifier_details(&encoded, DecodeMode::Strict).unwrap_err(),
+        DecodeError::TrailingBytes {
+            trailing: 4,
+            snippet: "ffffffff".to_string(),
+        }
+    );
+
+    encoded.resize(MAX_EXTRA_DATA_LENGTH + 1, 0);
+    assert_eq!(
+        decode_verifier_details(&encoded).unwrap_err(),
+        DecodeError::TooLong {
+            actual: MAX_EXTRA_DATA_LENGTH + 1,
+            maximum: MAX_EXTRA_DATA_LENGTH,
+        }
+    );
+}
+
+#[test]
+/// Ensures swapped fields are reported at the offset of the first field they break.
+fn should_reject_field_swapped_verifier_details() {
+    let encoded = request_verifier_details().abi_encode();
+
+    // swap the verifier and selector words
+    let mut swapped = encoded.clone();
+    swapped[..32].copy_from_slice(&encoded[32..64]);
+    swapped[32..64].copy_from_slice(&encoded[..32]);
+    match decode_verifier_details(&swapped).unwrap_err() {
+        DecodeError::InvalidField {
+            field,
+            offset,
+            snippet,
+            ..
+        } => {
+            assert_eq!(field, "verifier");
+            assert_eq!(offset, 0);
+            assert!(snippet.starts_with("deadbeef"));
+        }
+        err => panic!("unexpected error: {err}"),
+    }
+
+    // swap the isShaCommitment and inputsLength words
+    let mut swapped = encoded.clone();
+    swapped[64..96].copy_from_slice(&encoded[128..160]);
+    swapped[128..160].copy_from_slice(&encoded[64..96]);
+    match decode_verifier_details(&swapped).unwrap_err() {
+        DecodeError::InvalidField { field, offset, .. } => {
+            assert_eq!(field, "isShaCommitment");
+            assert_eq!(offset, 64);
+        }
+        err => panic!("unexpected error: {err}"),
+    }
+}
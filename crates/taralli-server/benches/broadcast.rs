@@ -0,0 +1,62 @@
+//! Fan out of a broadcast to every subscriber, comparing owned payloads cloned per subscriber
+//! with the shared buffers of `BroadcastedMessage`
+
+use std::hint::black_box;
+
+use bytes::Bytes;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use taralli_primitives::systems::SystemId;
+use taralli_server::subscription_manager::{
+    BroadcastKind, BroadcastedMessage, SubscriptionManager,
+};
+
+const SUBSCRIBERS: usize = 100;
+/// About the size of a large arkworks request
+const PAYLOAD_SIZE: usize = 2 * 1024 * 1024;
+
+/// Broadcast `message` through `manager` and receive it once per subscriber
+fn fan_out<M: Clone>(manager: &SubscriptionManager<M>, message: M) {
+    let mut subscriptions: Vec<_> = (0..SUBSCRIBERS)
+        .map(|_| manager.add_subscription())
+        .collect();
+    manager.broadcast(message).unwrap();
+    for subscription in &mut subscriptions {
+        black_box(subscription.try_recv().unwrap());
+    }
+}
+
+fn fan_out_benches(c: &mut Criterion) {
+    let payload: Vec<u8> = (0..PAYLOAD_SIZE).map(|i| (i % 251) as u8).collect();
+    let content = Bytes::from(payload.clone());
+    let deflated = Bytes::from(payload[..PAYLOAD_SIZE / 2].to_vec());
+
+    let mut group = c.benchmark_group(format!("fan_out/{SUBSCRIBERS}_subscribers"));
+    group.throughput(Throughput::Bytes((PAYLOAD_SIZE * SUBSCRIBERS) as u64));
+    group.bench_function("copied", |b| {
+        let manager = SubscriptionManager::<Vec<u8>>::new(4);
+        b.iter_batched(
+            || payload.clone(),
+            |message| fan_out(&manager, message),
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("shared", |b| {
+        let manager = SubscriptionManager::<BroadcastedMessage>::new(4);
+        b.iter(|| {
+            fan_out(
+                &manager,
+                BroadcastedMessage {
+                    content: content.clone(),
+                    subscribed_to: SystemId::Arkworks.as_bit(),
+                    kind: BroadcastKind::Intent,
+                    deflated: Some(deflated.clone()),
+                    meta: None,
+                },
+            )
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, fan_out_benches);
+criterion_main!(benches);
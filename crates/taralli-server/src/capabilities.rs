@@ -0,0 +1,178 @@
+//! Capabilities advertised by the providers, kept for as long as the providers refresh them.
+//! Advertising is optional, providers that don't are only counted through their subscriptions.
+//!
+//! Records are self-declared by whoever holds a key, so they're only advisory: they shape the
+//! aggregates of `/status`, which subscriptions of a saturated provider get a request, and the
+//! warning answered to a requester, but never turn a request down.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use taralli_primitives::alloy::primitives::{Address, U256};
+use taralli_primitives::capabilities::{
+    CapabilitySummary, ProviderCapabilities, SignedCapabilities,
+};
+use taralli_primitives::systems::{SystemId, SystemIdMask};
+
+use crate::error::{Result, ServerError};
+
+/// time after which the record of a provider that stopped refreshing it is dropped
+pub const DEFAULT_CAPABILITY_TTL: Duration = Duration::from_secs(90);
+
+/// number of providers whose capabilities are recorded at once
+pub const DEFAULT_MAX_CAPABILITY_RECORDS: usize = 1024;
+
+struct CapabilityEntry {
+    capabilities: ProviderCapabilities,
+    refreshed_at: Instant,
+}
+
+/// Live capability records, keyed by provider address. Records expire after the ttl unless
+/// refreshed, and new providers are turned away while `max_records` live ones are recorded.
+pub struct CapabilityRegistry {
+    entries: Mutex<HashMap<Address, CapabilityEntry>>,
+    ttl: Duration,
+    max_records: usize,
+}
+
+impl Default for CapabilityRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPABILITY_TTL)
+    }
+}
+
+impl CapabilityRegistry {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+            max_records: DEFAULT_MAX_CAPABILITY_RECORDS,
+        }
+    }
+
+    /// Record the capabilities of at most `max_records` providers at once
+    pub fn with_max_records(mut self, max_records: usize) -> Self {
+        self.max_records = max_records;
+        self
+    }
+
+    /// Record or refresh the capabilities of a provider, rejecting records not signed by the
+    /// provider they describe or issued before the one already recorded, and new providers
+    /// while the registry is full
+    pub fn upsert(&self, signed: SignedCapabilities) -> Result<()> {
+        signed
+            .verify()
+            .map_err(|e| ServerError::InvalidCapabilities(e.to_string()))?;
+        let capabilities = signed.capabilities;
+        let mut entries = self
+            .entries
+            .lock()
+            .expect("capability registry lock poisoned");
+        entries.retain(|_, entry| entry.refreshed_at.elapsed() < self.ttl);
+        match entries.get(&capabilities.provider) {
+            Some(entry) if entry.capabilities.issued_at > capabilities.issued_at => {
+                return Err(ServerError::InvalidCapabilities(format!(
+                    "capabilities of {} issued at {} are older than the recorded ones",
+                    capabilities.provider, capabilities.issued_at
+                )));
+            }
+            None if entries.len() >= self.max_records => {
+                return Err(ServerError::CapabilityRegistryFull(self.max_records));
+            }
+            _ => {}
+        }
+        entries.insert(
+            capabilities.provider,
+            CapabilityEntry {
+                capabilities,
+                refreshed_at: Instant::now(),
+            },
+        );
+        Ok(())
+    }
+
+    /// Capabilities of the providers that refreshed them within the ttl
+    pub fn live(&self) -> Vec<ProviderCapabilities> {
+        let mut entries = self
+            .entries
+            .lock()
+            .expect("capability registry lock poisoned");
+        entries.retain(|_, entry| entry.refreshed_at.elapsed() < self.ttl);
+        entries
+            .values()
+            .map(|entry| entry.capabilities.clone())
+            .collect()
+    }
+
+    /// Live providers advertising `system_id` without any free slot left
+    pub fn saturated(&self, system_id: SystemId) -> Vec<Address> {
+        self.live()
+            .into_iter()
+            .filter(|provider| provider.system(system_id).is_some() && provider.free_slots() == 0)
+            .map(|provider| provider.provider)
+            .collect()
+    }
+
+    /// Whether `provider` advertised one of the systems of `systems` without any free slot left.
+    /// Providers that didn't advertise their capabilities are never saturated.
+    pub fn is_saturated(&self, provider: &Address, systems: SystemIdMask) -> bool {
+        let mut entries = self
+            .entries
+            .lock()
+            .expect("capability registry lock poisoned");
+        let Some(entry) = entries.get(provider) else {
+            return false;
+        };
+        if entry.refreshed_at.elapsed() >= self.ttl {
+            entries.remove(provider);
+            return false;
+        }
+        entry.capabilities.free_slots() == 0
+            && entry
+                .capabilities
+                .systems
+                .iter()
+                .any(|capability| capability.system_id.as_bit() & systems != 0)
+    }
+
+    /// Why a request might go unanswered, when every provider subscribed to its system advertised
+    /// capabilities and none of them takes it. Unadvertised subscribers might, so there's no
+    /// reason as soon as the system has more `subscribers` than advertised providers.
+    pub fn unaccepted_reason(
+        &self,
+        system_id: SystemId,
+        proving_time: u32,
+        max_reward: U256,
+        subscribers: usize,
+    ) -> Option<String> {
+        let providers: Vec<ProviderCapabilities> = self
+            .live()
+            .into_iter()
+            .filter(|provider| provider.system(system_id).is_some())
+            .collect();
+        if providers.is_empty()
+            || providers.len() < subscribers
+            || providers
+                .iter()
+                .any(|provider| provider.accepts(system_id, proving_time, max_reward))
+        {
+            return None;
+        }
+
+        let system = system_id.as_str();
+        let summary = CapabilitySummary::aggregate(system_id, &providers);
+        let reason = match (summary.fastest_proving_time, summary.lowest_minimum_reward) {
+            (Some(fastest), _) if fastest > proving_time => {
+                format!("no providers accept {system} with proving_time < {fastest}s")
+            }
+            (_, Some(lowest)) if lowest > max_reward => {
+                format!("no providers accept {system} with max reward < {lowest}")
+            }
+            _ => format!(
+                "no provider accepts {system} with both proving_time {proving_time}s and max reward {max_reward}"
+            ),
+        };
+        Some(reason)
+    }
+}
@@ -1,19 +1,43 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
 use std::fs;
 use std::str::FromStr;
+use std::time::Duration;
 use taralli_primitives::alloy::primitives::{Address, U256};
+use taralli_primitives::compression_utils::compression::DecompressionBudget;
+use taralli_primitives::deployments::DeploymentRegistry;
+use taralli_primitives::systems::SystemId;
 use taralli_primitives::validation::offer::OfferValidationConfig;
 use taralli_primitives::validation::request::RequestValidationConfig;
 use taralli_primitives::validation::BaseValidationConfig;
+use taralli_primitives::PrimitivesError;
 use thiserror::Error;
 use tracing::Level;
 
+use crate::subscription_manager::SaturatedDelivery;
+
+/// prefix of the environment variables overriding the config file. Nested fields are joined by
+/// `__`, e.g. `TARALLI_SERVER__BASE_VALIDATION_CONFIG__CHAIN_ID=1`, and values are read as JSON
+/// when they parse as such, as strings otherwise.
+pub const ENV_PREFIX: &str = "TARALLI_SERVER__";
+
+/// placeholder of the secrets of a printed config
+pub const REDACTED: &str = "<redacted>";
+
 #[derive(Clone, Debug, Deserialize)]
 pub struct Markets {
     pub universal_bombetta: Address,
     pub universal_porchetta: Address,
 }
 
+/// Market addresses overriding the ones of the deployment of the configured chain
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RawMarkets {
+    pub universal_bombetta: Option<Address>,
+    pub universal_porchetta: Option<Address>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct RawValidationConfig {
     pub base_validation_config: BaseValidationConfig,
@@ -21,29 +45,176 @@ pub struct RawValidationConfig {
     pub offer_validation_config: RawOfferConfig,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RawRequestConfig {
+    #[serde(with = "amount")]
     pub maximum_allowed_stake: u128,
+    /// maximum allowed stake of the requests of a system, when it differs from the one above
+    #[serde(default, with = "amount::per_system")]
+    pub system_maximum_allowed_stake: HashMap<SystemId, u128>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RawOfferConfig {
     pub maximum_allowed_reward: String,
     pub minimum_allowed_stake: String,
 }
 
+/// Limits applied to each phase of handling a submitted intent, before validation starts
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SubmissionLimits {
+    /// time allowed to read the whole multipart body
+    pub body_read_timeout_seconds: u64,
+    /// maximum size of the (compressed) multipart body
+    pub max_body_size_bytes: usize,
+    /// time allowed to decompress the submitted system
+    pub decompression_timeout_seconds: u64,
+    /// maximum size of the decompressed system
+    pub max_decompressed_bytes: usize,
+    /// maximum number of metadata entries a submission may carry, none when 0
+    pub max_metadata_entries: usize,
+    /// maximum size of a submission's metadata, its keys and values summed
+    pub max_metadata_bytes: usize,
+}
+
+impl Default for SubmissionLimits {
+    fn default() -> Self {
+        Self {
+            body_read_timeout_seconds: 10,
+            max_body_size_bytes: 16 * 1024 * 1024, // 16 MiB
+            decompression_timeout_seconds: 10,
+            max_decompressed_bytes: 64 * 1024 * 1024, // 64 MiB
+            max_metadata_entries: 16,
+            max_metadata_bytes: 1024,
+        }
+    }
+}
+
+impl SubmissionLimits {
+    #[must_use]
+    pub fn body_read_timeout(&self) -> Duration {
+        Duration::from_secs(self.body_read_timeout_seconds)
+    }
+
+    #[must_use]
+    pub fn decompression_budget(&self) -> DecompressionBudget {
+        DecompressionBudget {
+            max_output_bytes: self.max_decompressed_bytes,
+            max_duration: Duration::from_secs(self.decompression_timeout_seconds),
+        }
+    }
+}
+
+/// Garbage collection of intents whose auction and resolve window are over
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GcConfig {
+    /// time between two collections
+    pub interval_seconds: u64,
+    /// time stored requests are kept served by default once they can't be resolved anymore,
+    /// before being archived
+    pub request_retention_seconds: u64,
+    /// time stored offers are kept once they can't be resolved anymore, before being deleted
+    pub offer_retention_seconds: u64,
+}
+
+impl Default for GcConfig {
+    fn default() -> Self {
+        Self {
+            interval_seconds: 60,
+            request_retention_seconds: 24 * 60 * 60, // 1 day
+            offer_retention_seconds: 24 * 60 * 60,   // 1 day
+        }
+    }
+}
+
+impl GcConfig {
+    #[must_use]
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs(self.interval_seconds)
+    }
+}
+
+/// Tolerance of the latest block fetched lagging behind the wall clock, e.g. under rpc lag, so
+/// intents whose auction starts now aren't rejected for the server seeing a stale chain time
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClockSkewConfig {
+    /// expected time between two blocks of the chain
+    pub block_time_seconds: u64,
+    /// blocks the latest block timestamp is moved forward by at most to catch up with the wall
+    /// clock, no adjustment when 0
+    pub tolerated_blocks: u64,
+}
+
+impl Default for ClockSkewConfig {
+    fn default() -> Self {
+        Self {
+            block_time_seconds: 12,
+            tolerated_blocks: 0,
+        }
+    }
+}
+
+impl ClockSkewConfig {
+    /// Most seconds a block timestamp is moved forward by
+    #[must_use]
+    pub fn tolerance_seconds(&self) -> u64 {
+        self.block_time_seconds
+            .saturating_mul(self.tolerated_blocks)
+    }
+
+    /// Timestamp of the block fetched moved towards the wall clock, by no more than the
+    /// tolerance. Never moves it past the wall clock, nor back when the wall clock is behind.
+    #[must_use]
+    pub fn adjust(&self, block_timestamp: u64, wall_clock: u64) -> u64 {
+        let drift = wall_clock.saturating_sub(block_timestamp);
+        block_timestamp + drift.min(self.tolerance_seconds())
+    }
+}
+
 #[derive(Clone)]
 pub struct ServerValidationConfigs {
     pub request: RequestValidationConfig,
     pub offer: OfferValidationConfig,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Config {
     pub server_port: u16,
     pub log_level: String,
     pub validation_timeout_seconds: u32,
-    pub markets: Markets,
+    #[serde(default)]
+    pub submission_limits: SubmissionLimits,
+    /// capacity of the broadcast buffer shared by all subscriptions, which also bounds the
+    /// buffer size a single subscription may ask for. Falls back to `SERVER_SUBSCRIPTION_LAG`.
+    #[serde(default)]
+    pub subscription_buffer_size: Option<usize>,
+    /// send intents as deflated frames to the subscribers asking for them, trading server cpu
+    /// for bandwidth
+    #[serde(default)]
+    pub subscription_frame_compression: bool,
+    /// whether requests are skipped or sent flagged to the subscriptions of providers whose
+    /// advertised capabilities report no free slot for the request's system
+    #[serde(default)]
+    pub subscription_saturated_delivery: SaturatedDelivery,
+    /// markets are the ones deployed on `base_validation_config.chain_id` unless set here
+    #[serde(default)]
+    pub markets: RawMarkets,
+    /// JSON file of deployments added to the built-in ones, e.g. private deployments
+    #[serde(default)]
+    pub deployments_file: Option<String>,
+    /// key guarding the admin routes (log filter, state dump), which aren't served when unset
+    #[serde(default)]
+    pub admin_api_key: Option<String>,
+    /// archival of stored requests and pruning of stored offers once they're over
+    #[serde(default)]
+    pub gc: GcConfig,
+    /// catching up with the wall clock when the latest block the time constraints of intents are
+    /// validated against is stale
+    #[serde(default)]
+    pub clock_skew: ClockSkewConfig,
     pub base_validation_config: BaseValidationConfig,
     pub request_validation_config: RawRequestConfig,
     pub offer_validation_config: RawOfferConfig,
@@ -61,13 +232,186 @@ pub enum ConfigError {
     LogLevelParseError(String),
     #[error("Failed to decode hex: {0}")]
     HexDecodeError(#[from] hex::FromHexError),
+    #[error("Failed to resolve deployment: {0}")]
+    DeploymentError(#[from] PrimitivesError),
+    #[error("Invalid environment override {0}: {1}")]
+    EnvOverrideError(String, String),
+    #[error("Invalid config:{}", .0.iter().map(|problem| format!("\n  - {problem}")).collect::<String>())]
+    Invalid(Vec<String>),
 }
 
 impl Config {
     pub fn from_file(path: &str) -> Result<Self, ConfigError> {
+        Self::load(path, std::iter::empty())
+    }
+
+    /// Config of the file at `path` overridden by the `ENV_PREFIX` variables of `env`, e.g.
+    /// `std::env::vars()`. Every problem found is reported at once.
+    pub fn load(
+        path: &str,
+        env: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, ConfigError> {
         let data = fs::read_to_string(path)?;
-        let config: Config = serde_json::from_str(&data)?;
-        Ok(config)
+        let mut layered = layered_json(&data)?;
+        apply_env_overrides(&mut layered, env)?;
+        Self::from_layered(layered)
+    }
+
+    /// Parse and validate the layers of a config once merged. Amounts beyond u64 are read from
+    /// decimal strings, or from integers parsed through `layered_json`.
+    pub fn from_layered(layered: Value) -> Result<Self, ConfigError> {
+        let mut problems = address_problems(&layered);
+        match serde_json::from_value::<Config>(layered) {
+            Ok(config) => {
+                problems.extend(config.problems());
+                if problems.is_empty() {
+                    Ok(config)
+                } else {
+                    Err(ConfigError::Invalid(problems))
+                }
+            }
+            Err(e) => {
+                problems.push(e.to_string());
+                Err(ConfigError::Invalid(problems))
+            }
+        }
+    }
+
+    /// Check the fields against each other, reporting every problem found
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let problems = self.problems();
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(problems))
+        }
+    }
+
+    /// The config as JSON with its secrets redacted, to check what a deployment runs with
+    pub fn redacted_json(&self) -> Result<String, ConfigError> {
+        let mut config = self.clone();
+        if config.admin_api_key.is_some() {
+            config.admin_api_key = Some(REDACTED.to_string());
+        }
+        Ok(serde_json::to_string_pretty(&config)?)
+    }
+
+    fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.server_port == 0 {
+            problems.push("server_port must be between 1 and 65535".to_string());
+        }
+        if self.log_level().is_err() {
+            problems.push(format!(
+                "log_level {:?} isn't one of TRACE, DEBUG, INFO, WARN or ERROR",
+                self.log_level
+            ));
+        }
+        if self.validation_timeout_seconds == 0 {
+            problems.push("validation_timeout_seconds must be nonzero".to_string());
+        }
+        let limits = &self.submission_limits;
+        for (field, value) in [
+            (
+                "body_read_timeout_seconds",
+                limits.body_read_timeout_seconds,
+            ),
+            (
+                "decompression_timeout_seconds",
+                limits.decompression_timeout_seconds,
+            ),
+            ("max_body_size_bytes", limits.max_body_size_bytes as u64),
+            (
+                "max_decompressed_bytes",
+                limits.max_decompressed_bytes as u64,
+            ),
+        ] {
+            if value == 0 {
+                problems.push(format!("submission_limits.{field} must be nonzero"));
+            }
+        }
+        if self.clock_skew.tolerated_blocks > 0 && self.clock_skew.block_time_seconds == 0 {
+            problems.push(
+                "clock_skew.block_time_seconds must be nonzero to tolerate stale blocks"
+                    .to_string(),
+            );
+        }
+        if self.gc.interval_seconds == 0 {
+            problems.push("gc.interval_seconds must be nonzero".to_string());
+        }
+        if self.subscription_buffer_size == Some(0) {
+            problems.push("subscription_buffer_size must be nonzero".to_string());
+        }
+        if self.admin_api_key.as_deref() == Some("") {
+            problems.push(
+                "admin_api_key is empty, leave it unset to serve no admin routes".to_string(),
+            );
+        }
+
+        let base = &self.base_validation_config;
+        if base.supported_systems.is_empty() {
+            problems.push(
+                "base_validation_config.supported_systems must list at least one system"
+                    .to_string(),
+            );
+        }
+        if base.minimum_auction_length > base.maximum_auction_length {
+            problems.push(format!(
+                "base_validation_config.minimum_auction_length {} exceeds maximum_auction_length {}",
+                base.minimum_auction_length, base.maximum_auction_length
+            ));
+        }
+        if base.minimum_proving_time > base.maximum_proving_time {
+            problems.push(format!(
+                "base_validation_config.minimum_proving_time {} exceeds maximum_proving_time {}",
+                base.minimum_proving_time, base.maximum_proving_time
+            ));
+        }
+        for (field, systems) in [
+            (
+                "base_validation_config.system_overrides",
+                base.system_overrides.keys().collect::<Vec<_>>(),
+            ),
+            (
+                "request_validation_config.system_maximum_allowed_stake",
+                self.request_validation_config
+                    .system_maximum_allowed_stake
+                    .keys()
+                    .collect(),
+            ),
+        ] {
+            let mut unsupported: Vec<String> = systems
+                .into_iter()
+                .filter(|system_id| !base.supported_systems.contains(system_id))
+                .map(|system_id| format!("{system_id:?}"))
+                .collect();
+            unsupported.sort();
+            problems.extend(
+                unsupported
+                    .into_iter()
+                    .map(|system| format!("{field} sets {system}, which isn't a supported system")),
+            );
+        }
+        for (field, value) in [
+            (
+                "maximum_allowed_reward",
+                &self.offer_validation_config.maximum_allowed_reward,
+            ),
+            (
+                "minimum_allowed_stake",
+                &self.offer_validation_config.minimum_allowed_stake,
+            ),
+        ] {
+            if U256::from_str(value).is_err() {
+                problems.push(format!(
+                    "offer_validation_config.{field} {value:?} isn't an amount"
+                ));
+            }
+        }
+        if let Err(e) = self.markets() {
+            problems.push(format!("markets: {e}"));
+        }
+        problems
     }
 
     pub fn log_level(&self) -> Result<Level, ConfigError> {
@@ -75,11 +419,50 @@ impl Config {
             .map_err(|_| ConfigError::LogLevelParseError(self.log_level.clone()))
     }
 
+    /// Registry of the built-in deployments, along with the ones of `deployments_file`
+    pub fn deployments(&self) -> Result<DeploymentRegistry, ConfigError> {
+        let registry = DeploymentRegistry::builtin();
+        Ok(match &self.deployments_file {
+            Some(path) => registry.with_overrides_file(path)?,
+            None => registry,
+        })
+    }
+
+    /// Markets of the deployment of the configured chain, overridden by the ones set explicitly.
+    /// The deployment is only looked up when a market isn't set.
+    pub fn markets(&self) -> Result<Markets, ConfigError> {
+        if let (Some(universal_bombetta), Some(universal_porchetta)) = (
+            self.markets.universal_bombetta,
+            self.markets.universal_porchetta,
+        ) {
+            return Ok(Markets {
+                universal_bombetta,
+                universal_porchetta,
+            });
+        }
+        let registry = self.deployments()?;
+        let deployment = registry.get(self.base_validation_config.chain_id)?;
+        Ok(Markets {
+            universal_bombetta: self
+                .markets
+                .universal_bombetta
+                .unwrap_or(deployment.universal_bombetta),
+            universal_porchetta: self
+                .markets
+                .universal_porchetta
+                .unwrap_or(deployment.universal_porchetta),
+        })
+    }
+
     #[must_use]
     pub fn get_request_validation_config(&self) -> RequestValidationConfig {
         RequestValidationConfig {
             base: self.base_validation_config.clone(),
             maximum_allowed_stake: self.request_validation_config.maximum_allowed_stake,
+            system_maximum_allowed_stake: self
+                .request_validation_config
+                .system_maximum_allowed_stake
+                .clone(),
         }
     }
 
@@ -106,3 +489,193 @@ impl Config {
         }
     }
 }
+
+/// Override the fields of `layered` set by the `ENV_PREFIX` variables of `env`. Overrides are
+/// applied in order of their names, so a field set along with one nested in it always gets both.
+fn apply_env_overrides(
+    layered: &mut Value,
+    env: impl IntoIterator<Item = (String, String)>,
+) -> Result<(), ConfigError> {
+    let mut overrides: Vec<(String, String)> = env
+        .into_iter()
+        .filter(|(name, _)| name.starts_with(ENV_PREFIX))
+        .collect();
+    overrides.sort();
+    for (name, raw) in overrides {
+        let path: Vec<String> = name[ENV_PREFIX.len()..]
+            .split("__")
+            .map(str::to_lowercase)
+            .collect();
+        if path.iter().any(String::is_empty) {
+            return Err(ConfigError::EnvOverrideError(
+                name,
+                "empty field name".to_string(),
+            ));
+        }
+        let mut target = &mut *layered;
+        for field in &path {
+            let Value::Object(object) = target else {
+                return Err(ConfigError::EnvOverrideError(
+                    name,
+                    format!("{field} is nested in a field that isn't an object"),
+                ));
+            };
+            target = object
+                .entry(field.clone())
+                .or_insert_with(|| Value::Object(Map::new()));
+        }
+        *target = env_value(&raw);
+    }
+    Ok(())
+}
+
+/// Value of an override: JSON when it parses as such, a string otherwise
+fn env_value(raw: &str) -> Value {
+    layered_json(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+/// Parse a layer of the config. Integers beyond u64, which JSON values can't hold exactly, are
+/// kept as decimal strings, read back exactly as amounts.
+pub fn layered_json(json: &str) -> serde_json::Result<Value> {
+    serde_json::from_str(&quote_large_integers(json))
+}
+
+/// Quote the integer literals of `json` not fitting a u64, leaving strings and the other
+/// numbers as they are
+fn quote_large_integers(json: &str) -> String {
+    let bytes = json.as_bytes();
+    let mut quoted = String::with_capacity(json.len());
+    let mut copied = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes[i];
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if byte == b'\\' {
+                escaped = true;
+            } else if byte == b'"' {
+                in_string = false;
+            }
+            i += 1;
+            continue;
+        }
+        if byte == b'"' {
+            in_string = true;
+            i += 1;
+            continue;
+        }
+        if !(byte.is_ascii_digit() || byte == b'-') {
+            i += 1;
+            continue;
+        }
+        // a number runs until the next delimiter, fractions and exponents included
+        let start = i;
+        while i < bytes.len() && matches!(bytes[i], b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E')
+        {
+            i += 1;
+        }
+        let literal = &json[start..i];
+        if literal.bytes().all(|b| b.is_ascii_digit()) && literal.parse::<u64>().is_err() {
+            quoted.push_str(&json[copied..start]);
+            quoted.push('"');
+            quoted.push_str(literal);
+            quoted.push('"');
+            copied = i;
+        }
+    }
+    quoted.push_str(&json[copied..]);
+    quoted
+}
+
+/// Mixed case market addresses whose checksum doesn't match, most likely mistyped
+fn address_problems(layered: &Value) -> Vec<String> {
+    ["universal_bombetta", "universal_porchetta"]
+        .into_iter()
+        .filter_map(|market| {
+            let address = layered.get("markets")?.get(market)?.as_str()?;
+            let hex = address.strip_prefix("0x").unwrap_or(address);
+            let mixed_case = hex.bytes().any(|b| b.is_ascii_uppercase())
+                && hex.bytes().any(|b| b.is_ascii_lowercase());
+            (mixed_case && Address::parse_checksummed(address, None).is_err())
+                .then(|| format!("markets.{market} {address} has an invalid checksum"))
+        })
+        .collect()
+}
+
+/// Amounts read from a decimal string or an integer, and written as decimal strings. JSON values
+/// don't hold integers beyond u64 exactly, `layered_json` reading them as decimal strings.
+mod amount {
+    use std::collections::HashMap;
+    use std::fmt;
+
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+    use taralli_primitives::systems::SystemId;
+
+    pub fn serialize<S: Serializer>(amount: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(amount)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+        deserializer.deserialize_any(AmountVisitor)
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct Amount(#[serde(with = "super::amount")] u128);
+
+    struct AmountVisitor;
+
+    impl de::Visitor<'_> for AmountVisitor {
+        type Value = u128;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("an amount as a decimal string or an integer")
+        }
+
+        fn visit_u64<E: de::Error>(self, v: u64) -> Result<u128, E> {
+            Ok(u128::from(v))
+        }
+
+        fn visit_u128<E: de::Error>(self, v: u128) -> Result<u128, E> {
+            Ok(v)
+        }
+
+        fn visit_f64<E: de::Error>(self, v: f64) -> Result<u128, E> {
+            Err(E::custom(format!(
+                "amount {v} isn't an integer up to u64, write larger amounts as decimal strings"
+            )))
+        }
+
+        fn visit_str<E: de::Error>(self, v: &str) -> Result<u128, E> {
+            v.parse()
+                .map_err(|_| E::custom(format!("amount {v:?} isn't a decimal integer")))
+        }
+    }
+
+    pub mod per_system {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(
+            amounts: &HashMap<SystemId, u128>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.collect_map(
+                amounts
+                    .iter()
+                    .map(|(system_id, amount)| (system_id, Amount(*amount))),
+            )
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<HashMap<SystemId, u128>, D::Error> {
+            let amounts = HashMap::<SystemId, Amount>::deserialize(deserializer)?;
+            Ok(amounts
+                .into_iter()
+                .map(|(system_id, Amount(amount))| (system_id, amount))
+                .collect())
+        }
+    }
+}
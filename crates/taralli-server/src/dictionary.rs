@@ -0,0 +1,181 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use axum::body::Bytes;
+use taralli_primitives::alloy::primitives::B256;
+use taralli_primitives::compression_utils::dictionary::dictionary_hash;
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::{Result, ServerError};
+
+/// number of dictionaries kept
+const DEFAULT_CAPACITY: usize = 256;
+/// largest dictionary accepted by default, brotli only referencing the last 16 MiB of its window
+pub const DEFAULT_MAX_DICTIONARY_BYTES: usize = 16 * 1024 * 1024;
+/// total size of the dictionaries kept
+pub const DEFAULT_MAX_TOTAL_DICTIONARY_BYTES: usize = 256 * 1024 * 1024;
+/// dictionaries registered per minute, across requesters
+pub const DEFAULT_DICTIONARY_REGISTRATIONS_PER_MINUTE: usize = 60;
+
+/// Compression dictionaries registered by requesters, content-addressed by their hash. Systems
+/// compressed against one are decompressed with it on submission, providers fetching it by hash
+/// to decompress the broadcast systems. The least recently registered dictionaries are evicted
+/// once at capacity or over the total size, submissions referencing them being rejected until
+/// registered again.
+///
+/// A dictionary referenced by a broadcast intent is pinned until the intent's auction ends, so
+/// providers can still fetch it. Registrations are rate limited, and turned down while the
+/// pinned dictionaries leave no room.
+#[derive(Clone)]
+pub struct DictionaryStore {
+    inner: Arc<Mutex<Dictionaries>>,
+    max_dictionary_bytes: usize,
+    clock: Arc<dyn Clock>,
+}
+
+struct Dictionaries {
+    capacity: usize,
+    max_total_bytes: usize,
+    total_bytes: usize,
+    order: VecDeque<B256>,
+    dictionaries: HashMap<B256, Bytes>,
+    // unix timestamp until which a dictionary is kept from eviction
+    pinned_until: HashMap<B256, u64>,
+    registrations_per_minute: usize,
+    // minute of the current rate limiting window, and the registrations it counted
+    window: (u64, usize),
+}
+
+impl Default for DictionaryStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY, DEFAULT_MAX_DICTIONARY_BYTES)
+    }
+}
+
+impl DictionaryStore {
+    pub fn new(capacity: usize, max_dictionary_bytes: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Dictionaries {
+                capacity,
+                max_total_bytes: DEFAULT_MAX_TOTAL_DICTIONARY_BYTES,
+                total_bytes: 0,
+                order: VecDeque::with_capacity(capacity),
+                dictionaries: HashMap::with_capacity(capacity),
+                pinned_until: HashMap::new(),
+                registrations_per_minute: DEFAULT_DICTIONARY_REGISTRATIONS_PER_MINUTE,
+                window: (0, 0),
+            })),
+            max_dictionary_bytes,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Keep at most `max_total_bytes` of dictionaries
+    pub fn with_max_total_bytes(self, max_total_bytes: usize) -> Self {
+        self.lock().max_total_bytes = max_total_bytes;
+        self
+    }
+
+    /// Accept at most `registrations_per_minute` registrations per minute
+    pub fn with_registrations_per_minute(self, registrations_per_minute: usize) -> Self {
+        self.lock().registrations_per_minute = registrations_per_minute;
+        self
+    }
+
+    /// Read the time pins and the rate limiting window are checked against from `clock`
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn max_dictionary_bytes(&self) -> usize {
+        self.max_dictionary_bytes
+    }
+
+    /// Register a dictionary, returning its hash. Registering a known dictionary again refreshes
+    /// it, moving it last in the eviction order.
+    pub fn insert(&self, dictionary: Bytes) -> Result<B256> {
+        let hash = dictionary_hash(&dictionary);
+        let now = self.clock.now();
+        let mut inner = self.lock();
+        inner.count_registration(now)?;
+        if inner.dictionaries.contains_key(&hash) {
+            inner.order.retain(|known| *known != hash);
+            inner.order.push_back(hash);
+            return Ok(hash);
+        }
+        inner.make_room(dictionary.len(), now)?;
+        inner.total_bytes += dictionary.len();
+        inner.dictionaries.insert(hash, dictionary);
+        inner.order.push_back(hash);
+        Ok(hash)
+    }
+
+    /// Keep the dictionary `hash` from being evicted until the unix timestamp `until`, e.g. the
+    /// end of the auction of an intent referencing it
+    pub fn pin(&self, hash: B256, until: u64) {
+        let mut inner = self.lock();
+        if inner.dictionaries.contains_key(&hash) {
+            let pinned_until = inner.pinned_until.entry(hash).or_default();
+            *pinned_until = (*pinned_until).max(until);
+        }
+    }
+
+    pub fn get(&self, hash: &B256) -> Option<Bytes> {
+        self.lock().dictionaries.get(hash).cloned()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Dictionaries> {
+        self.inner.lock().expect("dictionary store lock poisoned")
+    }
+}
+
+impl Dictionaries {
+    /// Count a registration in the window of the current minute, unless it's used up
+    fn count_registration(&mut self, now: u64) -> Result<()> {
+        let minute = now / 60;
+        if self.window.0 != minute {
+            self.window = (minute, 0);
+        }
+        if self.window.1 >= self.registrations_per_minute {
+            return Err(ServerError::DictionaryRegistrationLimited(format!(
+                "at most {} dictionaries are registered per minute",
+                self.registrations_per_minute
+            )));
+        }
+        self.window.1 += 1;
+        Ok(())
+    }
+
+    /// Evict the oldest unpinned dictionaries until one of `size` bytes fits, evicting none if it
+    /// can't fit
+    fn make_room(&mut self, size: usize, now: u64) -> Result<()> {
+        self.pinned_until.retain(|_, until| *until > now);
+        let mut excess_count = (self.order.len() + 1).saturating_sub(self.capacity);
+        let mut excess_bytes = (self.total_bytes + size).saturating_sub(self.max_total_bytes);
+        let mut evicted = Vec::new();
+        for hash in &self.order {
+            if excess_count == 0 && excess_bytes == 0 {
+                break;
+            }
+            if self.pinned_until.contains_key(hash) {
+                continue;
+            }
+            excess_count = excess_count.saturating_sub(1);
+            excess_bytes = excess_bytes.saturating_sub(self.dictionaries[hash].len());
+            evicted.push(*hash);
+        }
+        if excess_count > 0 || excess_bytes > 0 {
+            return Err(ServerError::DictionaryRegistrationLimited(
+                "no room left besides the dictionaries of live intents".to_string(),
+            ));
+        }
+        for hash in evicted {
+            self.order.retain(|known| *known != hash);
+            if let Some(dictionary) = self.dictionaries.remove(&hash) {
+                self.total_bytes -= dictionary.len();
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,8 @@
 use axum::{http::StatusCode, response::IntoResponse, Json};
 use serde_json::Value;
+use taralli_primitives::alloy::primitives::{Address, B256};
+use taralli_primitives::error::ErrorCode;
+use taralli_primitives::validation::violation::Violation;
 use taralli_primitives::PrimitivesError;
 use thiserror::Error;
 
@@ -9,16 +12,76 @@ pub enum ServerError {
     AppStateError(String),
     #[error("Validation: Error when fetching latest block")]
     FetchLatestBlockTimestampError,
+    #[error("Submit: reading the request body timed out after {0} seconds")]
+    BodyReadTimeout(u64),
+    #[error("Submit: request body exceeds the maximum of {0} bytes")]
+    PayloadTooLarge(usize),
+    #[error("Submit: invalid submission -> {0}")]
+    InvalidSubmission(String),
+    #[error("Submit: unknown market {market}, supported markets: {supported}")]
+    UnknownMarket { market: Address, supported: String },
+    #[error("Submit: unsupported intent envelope version {0}")]
+    UnsupportedIntentVersion(u32),
+    #[error("Submit: decompressed system exceeds the maximum of {0} bytes")]
+    DecompressionLimitExceeded(usize),
+    #[error("Submit: decompression timed out after {0} seconds")]
+    DecompressionTimeout(u64),
+    #[error("Submit: decompression failed -> {0}")]
+    DecompressionError(String),
+    #[error("Submit: unknown compression dictionary {0}")]
+    UnknownDictionary(B256),
+    #[error("Dictionaries: registration turned down -> {0}")]
+    DictionaryRegistrationLimited(String),
     #[error("Submit: validation timed out after {0} seconds")]
     ValidationTimeout(u64),
     #[error("Submit: validation error -> {0}")]
     ValidationError(String),
+    #[error("Submit: validation error -> {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+    ValidationViolations(Vec<Violation>),
     #[error("Subscribe: invalid system id -> {0}")]
     SystemIdError(String),
     #[error("Subscription manager: no proof providers available for selected proving system.")]
     NoProvidersAvailable(),
+    #[error("Capabilities: invalid capabilities -> {0}")]
+    InvalidCapabilities(String),
+    #[error("Capabilities: registry full, {0} providers recorded")]
+    CapabilityRegistryFull(usize),
     #[error("Broadcast failed: {0}")]
     BroadcastError(String),
+    #[error("Key exchange: unauthorized -> {0}")]
+    KeyExchangeUnauthorized(String),
+    #[error("Key exchange: key not available -> {0}")]
+    KeyNotAvailable(String),
+    #[error("Key exchange: failed to read the request from the market -> {0}")]
+    KeyExchangeError(String),
+    #[error("Extension: unauthorized -> {0}")]
+    ExtensionUnauthorized(String),
+    #[error("Extension: not available -> {0}")]
+    ExtensionNotAvailable(String),
+    #[error("Extension: invalid extension -> {0}")]
+    InvalidExtension(String),
+    #[error("Extension: failed to read the request from the market -> {0}")]
+    ExtensionError(String),
+    #[error("Offer inputs: unauthorized -> {0}")]
+    OfferInputsUnauthorized(String),
+    #[error("Offer inputs: not available -> {0}")]
+    OfferInputsNotAvailable(String),
+    #[error("Offer inputs: inputs don't match the offer's commitment -> {0}")]
+    OfferInputsMismatch(String),
+    #[error("Offer inputs: store full -> {0}")]
+    OfferInputsStoreFull(String),
+    #[error("Offer inputs: failed to read the offer from the market -> {0}")]
+    OfferInputsError(String),
+    #[error("Request metadata: unauthorized -> {0}")]
+    RequestMetadataUnauthorized(String),
+    #[error("Submit: idempotency key in use -> {0}")]
+    IdempotencyKeyInUse(String),
+    #[error("Submit: idempotency key reused -> {0}")]
+    IdempotencyKeyReused(String),
+    #[error("Admin: unauthorized -> {0}")]
+    AdminUnauthorized(String),
+    #[error("Admin: invalid log filter -> {0}")]
+    InvalidLogFilter(String),
     #[error("Query failed: {0}")]
     QueryError(String),
     #[error("Database error: {0}")]
@@ -32,14 +95,117 @@ pub enum ServerError {
     #[error("Validation config type mismatch error")]
     ValidationConfigTypeMismatch,
     #[error("Primitives error: {0}")]
-    PrimitivesError(#[from] PrimitivesError),
+    PrimitivesError(PrimitivesError),
 }
 
 pub type Result<T> = core::result::Result<T, ServerError>;
 
+impl ServerError {
+    /// Machine readable code answered alongside the error message
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ServerError::BodyReadTimeout(_) => ErrorCode::BodyReadTimeout,
+            ServerError::PayloadTooLarge(_) => ErrorCode::PayloadTooLarge,
+            ServerError::InvalidSubmission(_) => ErrorCode::InvalidSubmission,
+            ServerError::UnsupportedIntentVersion(_) => ErrorCode::UnsupportedIntentVersion,
+            ServerError::UnknownMarket { .. } => ErrorCode::UnknownMarket,
+            ServerError::DecompressionLimitExceeded(_) => ErrorCode::DecompressionLimitExceeded,
+            ServerError::DecompressionTimeout(_) => ErrorCode::DecompressionTimeout,
+            ServerError::DecompressionError(_) => ErrorCode::DecompressionFailed,
+            ServerError::UnknownDictionary(_) => ErrorCode::UnknownDictionary,
+            ServerError::DictionaryRegistrationLimited(_) => {
+                ErrorCode::DictionaryRegistrationLimited
+            }
+            ServerError::ValidationTimeout(_) => ErrorCode::ValidationTimeout,
+            ServerError::ValidationError(_) | ServerError::ValidationViolations(_) => {
+                ErrorCode::ValidationFailed
+            }
+            ServerError::NoProvidersAvailable() => ErrorCode::NoProvidersAvailable,
+            ServerError::InvalidCapabilities(_) => ErrorCode::InvalidCapabilities,
+            ServerError::CapabilityRegistryFull(_) => ErrorCode::CapabilityRegistryFull,
+            ServerError::KeyExchangeUnauthorized(_) => ErrorCode::KeyExchangeUnauthorized,
+            ServerError::KeyNotAvailable(_) => ErrorCode::KeyNotAvailable,
+            ServerError::ExtensionUnauthorized(_) => ErrorCode::ExtensionUnauthorized,
+            ServerError::ExtensionNotAvailable(_) => ErrorCode::ExtensionNotAvailable,
+            ServerError::InvalidExtension(_) => ErrorCode::InvalidExtension,
+            ServerError::OfferInputsUnauthorized(_) => ErrorCode::OfferInputsUnauthorized,
+            ServerError::OfferInputsNotAvailable(_) => ErrorCode::OfferInputsNotAvailable,
+            ServerError::OfferInputsMismatch(_) => ErrorCode::OfferInputsMismatch,
+            ServerError::OfferInputsStoreFull(_) => ErrorCode::OfferInputsStoreFull,
+            ServerError::RequestMetadataUnauthorized(_) => ErrorCode::RequestMetadataUnauthorized,
+            ServerError::IdempotencyKeyInUse(_) => ErrorCode::IdempotencyKeyInUse,
+            ServerError::IdempotencyKeyReused(_) => ErrorCode::IdempotencyKeyReused,
+            ServerError::BroadcastError(_) => ErrorCode::BroadcastFailed,
+            ServerError::AdminUnauthorized(_) => ErrorCode::AdminUnauthorized,
+            ServerError::InvalidLogFilter(_) => ErrorCode::InvalidLogFilter,
+            // answered as an internal server error, without details
+            ServerError::AppStateError(_)
+            | ServerError::FetchLatestBlockTimestampError
+            | ServerError::SystemIdError(_)
+            | ServerError::KeyExchangeError(_)
+            | ServerError::ExtensionError(_)
+            | ServerError::OfferInputsError(_)
+            | ServerError::QueryError(_)
+            | ServerError::DatabaseError(_)
+            | ServerError::SerializationError(_)
+            | ServerError::DeserializationError(_)
+            | ServerError::UnsupportedIntentType
+            | ServerError::ValidationConfigTypeMismatch
+            | ServerError::PrimitivesError(_) => ErrorCode::Internal,
+        }
+    }
+}
+
+impl From<PrimitivesError> for ServerError {
+    fn from(e: PrimitivesError) -> Self {
+        match e {
+            PrimitivesError::DecompressionLimitExceeded(max) => {
+                ServerError::DecompressionLimitExceeded(max)
+            }
+            PrimitivesError::DecompressionTimeout(secs) => ServerError::DecompressionTimeout(secs),
+            PrimitivesError::DecompressionError(s) => ServerError::DecompressionError(s),
+            PrimitivesError::UnknownDictionary(hash) => ServerError::UnknownDictionary(hash),
+            e => ServerError::PrimitivesError(e),
+        }
+    }
+}
+
 impl IntoResponse for ServerError {
     fn into_response(self) -> axum::response::Response {
         let (status, error_message) = match &self {
+            ServerError::BodyReadTimeout(secs) => (
+                StatusCode::REQUEST_TIMEOUT,
+                format!("Reading the request body timed out after {secs} seconds"),
+            ),
+            ServerError::PayloadTooLarge(max) => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("Request body exceeds the maximum of {max} bytes"),
+            ),
+            ServerError::InvalidSubmission(s) => (StatusCode::BAD_REQUEST, s.to_owned()),
+            ServerError::UnknownMarket { .. } => (StatusCode::BAD_REQUEST, self.to_string()),
+            ServerError::UnsupportedIntentVersion(version) => (
+                StatusCode::BAD_REQUEST,
+                format!("Unsupported intent envelope version {version}"),
+            ),
+            ServerError::DecompressionLimitExceeded(max) => (
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("Decompressed system exceeds the maximum of {max} bytes"),
+            ),
+            ServerError::DecompressionTimeout(secs) => (
+                StatusCode::REQUEST_TIMEOUT,
+                format!("Decompression timed out after {secs} seconds"),
+            ),
+            ServerError::DecompressionError(s) => (
+                StatusCode::BAD_REQUEST,
+                format!("Decompression failed: {s}"),
+            ),
+            ServerError::UnknownDictionary(hash) => (
+                StatusCode::NOT_FOUND,
+                format!("Unknown compression dictionary {hash}, register it first"),
+            ),
+            ServerError::DictionaryRegistrationLimited(s) => {
+                (StatusCode::TOO_MANY_REQUESTS, s.to_owned())
+            }
             ServerError::ValidationTimeout(secs) => (
                 StatusCode::REQUEST_TIMEOUT,
                 format!("Validation timed out after {secs} seconds"),
@@ -48,7 +214,38 @@ impl IntoResponse for ServerError {
                 StatusCode::SERVICE_UNAVAILABLE,
                 "No proof providers available".to_string(),
             ),
+            ServerError::InvalidCapabilities(s) => (StatusCode::BAD_REQUEST, s.to_owned()),
+            ServerError::CapabilityRegistryFull(max) => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!("Capability registry full with {max} providers, retry later"),
+            ),
             ServerError::ValidationError(s) => (StatusCode::BAD_REQUEST, s.to_owned()),
+            // the reason of the first failed check, every failed check is listed in `violations`
+            ServerError::ValidationViolations(violations) => (
+                StatusCode::BAD_REQUEST,
+                violations
+                    .first()
+                    .map(|violation| violation.message.clone())
+                    .unwrap_or_default(),
+            ),
+            ServerError::KeyExchangeUnauthorized(s) => (StatusCode::FORBIDDEN, s.to_owned()),
+            ServerError::KeyNotAvailable(s) => (StatusCode::NOT_FOUND, s.to_owned()),
+            ServerError::ExtensionUnauthorized(s) => (StatusCode::FORBIDDEN, s.to_owned()),
+            ServerError::ExtensionNotAvailable(s) => (StatusCode::NOT_FOUND, s.to_owned()),
+            ServerError::InvalidExtension(s) => (StatusCode::BAD_REQUEST, s.to_owned()),
+            ServerError::OfferInputsUnauthorized(s) => (StatusCode::FORBIDDEN, s.to_owned()),
+            ServerError::OfferInputsNotAvailable(s) => (StatusCode::NOT_FOUND, s.to_owned()),
+            ServerError::OfferInputsMismatch(s) => (StatusCode::BAD_REQUEST, s.to_owned()),
+            ServerError::OfferInputsStoreFull(s) => {
+                (StatusCode::SERVICE_UNAVAILABLE, format!("{s}, retry later"))
+            }
+            ServerError::RequestMetadataUnauthorized(s) => (StatusCode::FORBIDDEN, s.to_owned()),
+            ServerError::IdempotencyKeyInUse(s) => (StatusCode::CONFLICT, s.to_owned()),
+            ServerError::IdempotencyKeyReused(s) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, s.to_owned())
+            }
+            ServerError::AdminUnauthorized(s) => (StatusCode::UNAUTHORIZED, s.to_owned()),
+            ServerError::InvalidLogFilter(s) => (StatusCode::BAD_REQUEST, s.to_owned()),
             ServerError::BroadcastError(s) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Broadcast failed: {s}"),
@@ -58,7 +255,11 @@ impl IntoResponse for ServerError {
                 "Internal server error".to_string(),
             ),
         };
-        (status, ApiResponse::failure(&error_message)).into_response()
+        let mut body = ApiResponse::failure_with_code(self.code(), &error_message);
+        if let ServerError::ValidationViolations(violations) = &self {
+            body.0["violations"] = serde_json::to_value(violations).unwrap_or_default();
+        }
+        (status, body).into_response()
     }
 }
 
@@ -67,4 +268,8 @@ impl ApiResponse {
     pub fn failure(s: &str) -> Json<Value> {
         Json(serde_json::json!({"error": s}))
     }
+
+    pub fn failure_with_code(code: ErrorCode, s: &str) -> Json<Value> {
+        Json(serde_json::json!({"error": s, "code": code.as_str()}))
+    }
 }
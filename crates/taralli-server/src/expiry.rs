@@ -0,0 +1,120 @@
+//! Notifies subscribers once the auction of a broadcast intent has ended, so providers can drop it
+//! from their queues without waiting for an on-chain check or a failed bid.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::json;
+use taralli_primitives::alloy::primitives::B256;
+use taralli_primitives::systems::SystemId;
+use tokio::sync::Notify;
+
+use crate::clock::unix_timestamp;
+use crate::subscription_manager::{BroadcastKind, BroadcastedMessage, SubscriptionManager};
+
+/// Broadcast intent whose auction ended
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExpiredIntent {
+    pub end_auction_timestamp: u64,
+    pub intent_id: B256,
+    pub system_id: SystemId,
+}
+
+impl ExpiredIntent {
+    /// Control frame sent to subscribers of the intent's system:
+    /// `{"type": "expired", "intent_id": "0x..", "system_id": ".."}`
+    pub fn to_message(&self) -> BroadcastedMessage {
+        let notification = json!({
+            "type": "expired",
+            "intent_id": self.intent_id,
+            "system_id": self.system_id,
+        });
+        BroadcastedMessage {
+            content: notification.to_string().into_bytes().into(),
+            subscribed_to: self.system_id.as_bit(),
+            kind: BroadcastKind::Control,
+            deflated: None,
+            meta: None,
+        }
+    }
+}
+
+/// Broadcast intents ordered by the end of their auction
+#[derive(Default)]
+pub struct ExpiryQueue {
+    queue: Mutex<BTreeMap<(u64, B256), SystemId>>,
+    scheduled: Notify,
+}
+
+impl ExpiryQueue {
+    /// Track a broadcast intent until its auction ends at `end_auction_timestamp`
+    pub fn schedule(&self, intent_id: B256, system_id: SystemId, end_auction_timestamp: u64) {
+        self.queue
+            .lock()
+            .expect("expiry queue lock poisoned")
+            .insert((end_auction_timestamp, intent_id), system_id);
+        // wake the notifier in case this intent expires before the one it waits for
+        self.scheduled.notify_one();
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.lock().expect("expiry queue lock poisoned").len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// End of the earliest tracked auction
+    pub fn next_expiry(&self) -> Option<u64> {
+        self.queue
+            .lock()
+            .expect("expiry queue lock poisoned")
+            .first_key_value()
+            .map(|((end_auction_timestamp, _), _)| *end_auction_timestamp)
+    }
+
+    /// Remove and return the intents whose auction ended before `now`, earliest first
+    pub fn pop_expired(&self, now: u64) -> Vec<ExpiredIntent> {
+        let mut queue = self.queue.lock().expect("expiry queue lock poisoned");
+        let mut expired = Vec::new();
+        while let Some((&(end_auction_timestamp, intent_id), &system_id)) = queue.first_key_value()
+        {
+            if end_auction_timestamp >= now {
+                break;
+            }
+            queue.pop_first();
+            expired.push(ExpiredIntent {
+                end_auction_timestamp,
+                intent_id,
+                system_id,
+            });
+        }
+        expired
+    }
+
+    /// Broadcast an expiry notification for every intent as its auction ends, runs forever
+    pub async fn notify_expired(self: Arc<Self>, subscription_manager: Arc<SubscriptionManager>) {
+        loop {
+            let now = unix_timestamp();
+            for expired in self.pop_expired(now) {
+                tracing::debug!("auction of intent {} ended", expired.intent_id);
+                // nobody to notify is fine, the intent expired all the same
+                let _ = subscription_manager.broadcast(expired.to_message());
+            }
+
+            match self.next_expiry() {
+                // bids are accepted up to and including the end timestamp
+                Some(end_auction_timestamp) => {
+                    let wait = Duration::from_secs(end_auction_timestamp.saturating_sub(now) + 1);
+                    tokio::select! {
+                        _ = tokio::time::sleep(wait) => {}
+                        _ = self.scheduled.notified() => {}
+                    }
+                }
+                None => self.scheduled.notified().await,
+            }
+        }
+    }
+}
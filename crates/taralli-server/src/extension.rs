@@ -0,0 +1,123 @@
+//! Relays the deadline extension negotiation of won requests between their provider and their
+//! requester. The server only holds the latest extension request of each request and the
+//! requester's answer to it, until the provider collects the answer. The deadlines the requester
+//! approved are kept on their own until the request is garbage collected, as it stays resolvable
+//! until then.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+use taralli_primitives::alloy::primitives::B256;
+use taralli_primitives::extension::{ExtensionRequest, ExtensionResponse};
+
+/// Extension negotiation of one request
+#[derive(Clone, Debug, Default)]
+struct ExtensionEntry {
+    request: Option<ExtensionRequest>,
+    response: Option<ExtensionResponse>,
+}
+
+#[derive(Default)]
+struct Negotiations {
+    entries: HashMap<B256, ExtensionEntry>,
+    /// latest deadline approved for each request
+    approved_deadlines: HashMap<B256, u64>,
+}
+
+/// Pending extension requests and their answers, keyed by request id
+#[derive(Default)]
+pub struct ExtensionNegotiation {
+    inner: Mutex<Negotiations>,
+}
+
+impl ExtensionNegotiation {
+    /// Record the provider's extension request, replacing any earlier answer that was given to a
+    /// previous request
+    pub fn request_extension(&self, intent_id: B256, request: ExtensionRequest) {
+        let mut inner = self.lock();
+        let entry = inner.entries.entry(intent_id).or_default();
+        if entry.request.as_ref() != Some(&request) {
+            entry.response = None;
+        }
+        entry.request = Some(request);
+    }
+
+    pub fn extension_request(&self, intent_id: &B256) -> Option<ExtensionRequest> {
+        self.lock()
+            .entries
+            .get(intent_id)
+            .and_then(|entry| entry.request.clone())
+    }
+
+    /// Record the requester's answer to the pending extension request, returning false when no
+    /// extension to the answered deadline is pending
+    pub fn respond(&self, intent_id: B256, response: ExtensionResponse) -> bool {
+        let mut inner = self.lock();
+        let Some(entry) = inner.entries.get_mut(&intent_id) else {
+            return false;
+        };
+        if !entry
+            .request
+            .as_ref()
+            .is_some_and(|request| request.new_deadline == response.new_deadline)
+        {
+            return false;
+        }
+        let approved_deadline = response.approved.then_some(response.new_deadline);
+        entry.response = Some(response);
+        if let Some(new_deadline) = approved_deadline {
+            let deadline = inner.approved_deadlines.entry(intent_id).or_default();
+            *deadline = (*deadline).max(new_deadline);
+        }
+        true
+    }
+
+    pub fn extension_response(&self, intent_id: &B256) -> Option<ExtensionResponse> {
+        self.lock()
+            .entries
+            .get(intent_id)
+            .and_then(|entry| entry.response.clone())
+    }
+
+    /// Hand the requester's answer over to the provider, ending the negotiation. The deadline it
+    /// approves, if any, is still told by `approved_deadline`.
+    pub fn take_response(&self, intent_id: &B256) -> Option<ExtensionResponse> {
+        let mut inner = self.lock();
+        let response = inner.entries.get(intent_id)?.response.clone()?;
+        inner.entries.remove(intent_id);
+        Some(response)
+    }
+
+    /// Latest deadline the requester approved for an intent
+    pub fn approved_deadline(&self, intent_id: &B256) -> Option<u64> {
+        self.lock().approved_deadlines.get(intent_id).copied()
+    }
+
+    /// Drop the extension negotiation and approved deadline of an intent, returning whether there
+    /// was either
+    pub fn remove(&self, intent_id: &B256) -> bool {
+        let mut inner = self.lock();
+        let negotiated = inner.entries.remove(intent_id).is_some();
+        let approved = inner.approved_deadlines.remove(intent_id).is_some();
+        negotiated || approved
+    }
+
+    /// Number of intents with a negotiation or approved deadline held
+    pub fn len(&self) -> usize {
+        let inner = self.lock();
+        let approved_only = inner
+            .approved_deadlines
+            .keys()
+            .filter(|intent_id| !inner.entries.contains_key(*intent_id))
+            .count();
+        inner.entries.len() + approved_only
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Negotiations> {
+        self.inner.lock().expect("extension lock poisoned")
+    }
+}
@@ -1,85 +1,42 @@
 use axum::{
     async_trait,
     body::Body,
-    extract::{FromRequest, Multipart, Request},
+    extract::{FromRef, FromRequest, Multipart, Request},
     http::StatusCode,
 };
+use http_body_util::Limited;
+use serde::de::DeserializeOwned;
 use taralli_primitives::compression_utils::intents::{PartialComputeOffer, PartialComputeRequest};
+use taralli_primitives::intents::envelope::{IntentKind, IntentMetadata, SubmissionEnvelope};
+use taralli_primitives::PrimitivesError;
+
+use crate::config::SubmissionLimits;
+use crate::error::ServerError;
 
 /// A custom extracted type that contains both all ComputeRequest data of `ComputeRequest<S: System>`.
 /// Although we use a vector of bytes to represent the compressed system.
 pub struct ExtractedRequest {
     pub partial_request: PartialComputeRequest,
     pub system_bytes: Vec<u8>,
+    /// the requester's metadata, sanitized, see `sanitize_metadata`
+    pub metadata: IntentMetadata,
 }
 
 #[async_trait]
 impl<S> FromRequest<S> for ExtractedRequest
 where
     S: Send + Sync,
+    SubmissionLimits: FromRef<S>,
 {
-    type Rejection = (StatusCode, String);
+    type Rejection = ServerError;
 
     async fn from_request(req: Request<Body>, state: &S) -> Result<Self, Self::Rejection> {
-        let mut multipart = Multipart::from_request(req, state)
-            .await
-            .map_err(|e| (e.status(), e.body_text()))?;
-
-        let mut partial_request: Option<PartialComputeRequest> = None;
-        let mut system_bytes: Option<Vec<u8>> = None;
-        while let Some(part) = multipart
-            .next_field()
-            .await
-            .map_err(|e| (e.status(), e.body_text()))?
-        {
-            match part.name() {
-                Some("partial_request") => {
-                    let bytes = part
-                        .bytes()
-                        .await
-                        .map_err(|e| (e.status(), e.body_text()))?;
-                    partial_request = Some(serde_json::from_slice(&bytes).map_err(|_| {
-                        (
-                            StatusCode::BAD_REQUEST,
-                            "Invalid JSON in partial request".to_string(),
-                        )
-                    })?);
-                }
-                Some("system_bytes") => {
-                    let bytes = part.bytes().await.map_err(|_| {
-                        (
-                            StatusCode::BAD_REQUEST,
-                            "Error reading system information as binary".to_string(),
-                        )
-                    })?;
-                    system_bytes = Some(bytes.to_vec());
-                }
-                Some(s) => {
-                    return Err((
-                        StatusCode::BAD_REQUEST,
-                        format!("Field not recognized on submission {s}"),
-                    ));
-                }
-                None => {
-                    return Err((
-                        StatusCode::BAD_REQUEST,
-                        "Missing name for some multipart submitted field".to_string(),
-                    ))
-                }
-            }
-        }
-
-        // The `ok_or()` clauses below should never trigger, any error should've been filtered above.
-        // Nonetheless, I'm opting for this rather than std::mem::MaybeUninit for the sake of making sure we're not returning something empty.
+        let (partial_request, system_bytes, metadata) =
+            read_submission(req, state, "partial_request", IntentKind::Request).await?;
         Ok(ExtractedRequest {
-            partial_request: partial_request.ok_or((
-                StatusCode::BAD_REQUEST,
-                "Missing partial request data".to_string(),
-            ))?,
-            system_bytes: system_bytes.ok_or((
-                StatusCode::BAD_REQUEST,
-                "Missing system information as binary".to_string(),
-            ))?,
+            partial_request,
+            system_bytes,
+            metadata,
         })
     }
 }
@@ -95,69 +52,142 @@ pub struct ExtractedOffer {
 impl<S> FromRequest<S> for ExtractedOffer
 where
     S: Send + Sync,
+    SubmissionLimits: FromRef<S>,
 {
-    type Rejection = (StatusCode, String);
+    type Rejection = ServerError;
 
     async fn from_request(req: Request<Body>, state: &S) -> Result<Self, Self::Rejection> {
+        // offers aren't queried back by their submitter, their metadata is only checked
+        let (partial_offer, system_bytes, _metadata) =
+            read_submission(req, state, "partial_offer", IntentKind::Offer).await?;
+        Ok(ExtractedOffer {
+            partial_offer,
+            system_bytes,
+        })
+    }
+}
+
+/// Read the multipart body of a submission, bounding both its size and the time spent reading it
+/// so slow or oversized bodies are rejected before any decompression or validation happens.
+/// The partial intent is accepted both within a versioned envelope and as a bare legacy payload.
+async fn read_submission<S, P>(
+    req: Request<Body>,
+    state: &S,
+    partial_field: &str,
+    kind: IntentKind,
+) -> Result<(P, Vec<u8>, IntentMetadata), ServerError>
+where
+    S: Send + Sync,
+    SubmissionLimits: FromRef<S>,
+    P: DeserializeOwned,
+{
+    let limits = SubmissionLimits::from_ref(state);
+    let max_body_size = limits.max_body_size_bytes;
+    let req = req.map(|body| Body::new(Limited::new(body, max_body_size)));
+
+    let multipart_error = |e: axum::extract::multipart::MultipartError| {
+        if e.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            ServerError::PayloadTooLarge(max_body_size)
+        } else {
+            ServerError::InvalidSubmission(e.body_text())
+        }
+    };
+
+    let read_body = async {
         let mut multipart = Multipart::from_request(req, state)
             .await
-            .map_err(|e| (e.status(), e.body_text()))?;
+            .map_err(|e| ServerError::InvalidSubmission(e.body_text()))?;
 
-        let mut partial_offer: Option<PartialComputeOffer> = None;
+        let mut partial: Option<(P, IntentMetadata)> = None;
         let mut system_bytes: Option<Vec<u8>> = None;
-        while let Some(part) = multipart
-            .next_field()
-            .await
-            .map_err(|e| (e.status(), e.body_text()))?
-        {
+        while let Some(part) = multipart.next_field().await.map_err(multipart_error)? {
             match part.name() {
-                Some("partial_offer") => {
-                    let bytes = part
-                        .bytes()
-                        .await
-                        .map_err(|e| (e.status(), e.body_text()))?;
-                    partial_offer = Some(serde_json::from_slice(&bytes).map_err(|_| {
-                        (
-                            StatusCode::BAD_REQUEST,
-                            "Invalid JSON in partial offer".to_string(),
-                        )
-                    })?);
+                Some(name) if name == partial_field => {
+                    let bytes = part.bytes().await.map_err(multipart_error)?;
+                    let submission = SubmissionEnvelope::<P>::from_json_slice(&bytes, kind)
+                        .map_err(|e| match e {
+                            PrimitivesError::UnsupportedIntentVersion(version) => {
+                                ServerError::UnsupportedIntentVersion(version)
+                            }
+                            _ => ServerError::InvalidSubmission(format!(
+                                "Invalid JSON in {partial_field}"
+                            )),
+                        })?;
+                    let metadata = sanitize_metadata(submission.metadata, &limits)?;
+                    partial = Some((submission.envelope.payload, metadata));
                 }
                 Some("system_bytes") => {
-                    let bytes = part.bytes().await.map_err(|_| {
-                        (
-                            StatusCode::BAD_REQUEST,
-                            "Error reading system information as binary".to_string(),
-                        )
-                    })?;
+                    let bytes = part.bytes().await.map_err(multipart_error)?;
                     system_bytes = Some(bytes.to_vec());
                 }
                 Some(s) => {
-                    return Err((
-                        StatusCode::BAD_REQUEST,
-                        format!("Field not recognized on submission {s}"),
-                    ));
+                    return Err(ServerError::InvalidSubmission(format!(
+                        "Field not recognized on submission {s}"
+                    )));
                 }
                 None => {
-                    return Err((
-                        StatusCode::BAD_REQUEST,
+                    return Err(ServerError::InvalidSubmission(
                         "Missing name for some multipart submitted field".to_string(),
                     ))
                 }
             }
         }
 
-        // The `ok_or()` clauses below should never trigger, any error should've been filtered above.
-        // Nonetheless, I'm opting for this rather than std::mem::MaybeUninit for the sake of making sure we're not returning something empty.
-        Ok(ExtractedOffer {
-            partial_offer: partial_offer.ok_or((
-                StatusCode::BAD_REQUEST,
-                "Missing partial request data".to_string(),
-            ))?,
-            system_bytes: system_bytes.ok_or((
-                StatusCode::BAD_REQUEST,
-                "Missing system information as binary".to_string(),
-            ))?,
-        })
+        let (partial, metadata) = partial.ok_or_else(|| {
+            ServerError::InvalidSubmission(format!("Missing {partial_field} data"))
+        })?;
+        Ok((
+            partial,
+            system_bytes.ok_or_else(|| {
+                ServerError::InvalidSubmission("Missing system information as binary".to_string())
+            })?,
+            metadata,
+        ))
+    };
+
+    tokio::time::timeout(limits.body_read_timeout(), read_body)
+        .await
+        .map_err(|_| ServerError::BodyReadTimeout(limits.body_read_timeout_seconds))?
+}
+
+/// Maximum length of a metadata key
+pub const MAX_METADATA_KEY_LENGTH: usize = 64;
+
+/// Check a submission's metadata against the limits, its keys made of 1 to
+/// `MAX_METADATA_KEY_LENGTH` ascii alphanumerics, `_`, `-`, `.` or `:`. Control characters are
+/// stripped from the values, which are served back as is otherwise.
+pub fn sanitize_metadata(
+    metadata: IntentMetadata,
+    limits: &SubmissionLimits,
+) -> Result<IntentMetadata, ServerError> {
+    if metadata.len() > limits.max_metadata_entries {
+        return Err(ServerError::InvalidSubmission(format!(
+            "metadata has {} entries, at most {} are accepted",
+            metadata.len(),
+            limits.max_metadata_entries
+        )));
+    }
+    let mut size = 0;
+    let mut sanitized = IntentMetadata::new();
+    for (key, value) in metadata {
+        let valid_key = (1..=MAX_METADATA_KEY_LENGTH).contains(&key.len())
+            && key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'));
+        if !valid_key {
+            return Err(ServerError::InvalidSubmission(format!(
+                "invalid metadata key {key:?}"
+            )));
+        }
+        let value: String = value.chars().filter(|c| !c.is_control()).collect();
+        size += key.len() + value.len();
+        sanitized.insert(key, value);
+    }
+    if size > limits.max_metadata_bytes {
+        return Err(ServerError::InvalidSubmission(format!(
+            "metadata of {size} bytes exceeds the maximum of {} bytes",
+            limits.max_metadata_bytes
+        )));
     }
+    Ok(sanitized)
 }
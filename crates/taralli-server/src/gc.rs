@@ -0,0 +1,229 @@
+//! Garbage collection of intents that can't be resolved anymore. Stored requests are archived once
+//! their auction and resolve window have been over for longer than their retention window, and
+//! stop being served unless asked for. Stored offers are deleted past theirs. The relayed key
+//! exchanges, extension negotiations and offer inputs of collected intents are dropped with them.
+//! No intent still in an open auction or within its resolve window, extensions approved by the
+//! requester included, is ever collected.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::clock::Clock;
+use crate::config::GcConfig;
+use crate::error::Result;
+use crate::extension::ExtensionNegotiation;
+use crate::intent_store::{IntentStatus, IntentStore, RequestFilter};
+use crate::key_exchange::KeyExchange;
+use crate::offer_inputs::OfferInputsStore;
+use crate::postgres::Db;
+
+/// Intents collected by a garbage collection
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct GcReport {
+    pub archived_requests: u64,
+    pub pruned_offers: u64,
+    /// key exchanges, extension negotiations and offer inputs dropped along with their intent
+    pub pruned_entries: u64,
+}
+
+/// Intents collected since the server started, reported by the status route
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct GcMetrics {
+    pub runs: u64,
+    /// unix timestamp of the last collection, as read from the collector's clock
+    pub last_run_at: Option<u64>,
+    #[serde(flatten)]
+    pub collected: GcReport,
+}
+
+#[derive(Default)]
+struct GcCounters {
+    runs: AtomicU64,
+    last_run_at: AtomicU64,
+    archived_requests: AtomicU64,
+    pruned_offers: AtomicU64,
+    pruned_entries: AtomicU64,
+}
+
+/// Collects the intents of the stores it's given every `GcConfig::interval_seconds`
+pub struct IntentGc {
+    config: GcConfig,
+    clock: Arc<dyn Clock>,
+    intent_store: Option<Arc<dyn IntentStore>>,
+    offer_db: Option<Db>,
+    // longest proving time an accepted offer may have, offers being stored without theirs
+    offer_resolve_window: u64,
+    key_exchange: Option<Arc<KeyExchange>>,
+    extensions: Option<Arc<ExtensionNegotiation>>,
+    offer_inputs: Option<Arc<OfferInputsStore>>,
+    counters: GcCounters,
+}
+
+impl IntentGc {
+    #[must_use]
+    pub fn new(config: GcConfig, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            config,
+            clock,
+            intent_store: None,
+            offer_db: None,
+            offer_resolve_window: 0,
+            key_exchange: None,
+            extensions: None,
+            offer_inputs: None,
+            counters: GcCounters::default(),
+        }
+    }
+
+    /// Archive the requests of `intent_store`
+    #[must_use]
+    pub fn with_intent_store(mut self, intent_store: Arc<dyn IntentStore>) -> Self {
+        self.intent_store = Some(intent_store);
+        self
+    }
+
+    /// Delete the offers of `offer_db`, which can be resolved up to `resolve_window_seconds`
+    /// after their auction ended
+    #[must_use]
+    pub fn with_offer_db(mut self, offer_db: Db, resolve_window_seconds: u64) -> Self {
+        self.offer_db = Some(offer_db);
+        self.offer_resolve_window = resolve_window_seconds;
+        self
+    }
+
+    /// Drop the key exchanges and extension negotiations of archived requests
+    #[must_use]
+    pub fn with_request_relays(
+        mut self,
+        key_exchange: Arc<KeyExchange>,
+        extensions: Arc<ExtensionNegotiation>,
+    ) -> Self {
+        self.key_exchange = Some(key_exchange);
+        self.extensions = Some(extensions);
+        self
+    }
+
+    /// Drop the inputs delivered for deleted offers
+    #[must_use]
+    pub fn with_offer_inputs(mut self, offer_inputs: Arc<OfferInputsStore>) -> Self {
+        self.offer_inputs = Some(offer_inputs);
+        self
+    }
+
+    pub fn config(&self) -> &GcConfig {
+        &self.config
+    }
+
+    pub fn metrics(&self) -> GcMetrics {
+        let counters = &self.counters;
+        let runs = counters.runs.load(Ordering::Relaxed);
+        GcMetrics {
+            runs,
+            last_run_at: (runs > 0).then(|| counters.last_run_at.load(Ordering::Relaxed)),
+            collected: GcReport {
+                archived_requests: counters.archived_requests.load(Ordering::Relaxed),
+                pruned_offers: counters.pruned_offers.load(Ordering::Relaxed),
+                pruned_entries: counters.pruned_entries.load(Ordering::Relaxed),
+            },
+        }
+    }
+
+    /// Collect the intents over for longer than their retention window at the clock's time
+    pub async fn run_once(&self) -> Result<GcReport> {
+        let now = self.clock.now();
+        let mut report = GcReport::default();
+
+        if let Some(intent_store) = &self.intent_store {
+            let retention = self.config.request_retention_seconds;
+            // a request can't be resolved past its deadline, which is never before its auction
+            // ended, so requests whose auction ended since are left out of the query
+            let candidates = intent_store
+                .get_requests(&RequestFilter {
+                    ended_before: Some(now.saturating_sub(retention)),
+                    ..Default::default()
+                })
+                .await?;
+            for request in candidates {
+                let deadline = match request.resolve_deadline() {
+                    Ok(deadline) => deadline,
+                    Err(e) => {
+                        tracing::warn!(
+                            "Keeping request {} with unknown resolve deadline: {}",
+                            request.intent_id,
+                            e
+                        );
+                        continue;
+                    }
+                };
+                // a deadline extension approved by the requester keeps the request resolvable
+                let extended_deadline = self
+                    .extensions
+                    .as_ref()
+                    .and_then(|extensions| extensions.approved_deadline(&request.intent_id));
+                let resolvable_until = deadline.max(extended_deadline.unwrap_or_default());
+                if resolvable_until.saturating_add(retention) >= now {
+                    continue;
+                }
+                intent_store
+                    .update_request_status(request.intent_id, IntentStatus::Archived)
+                    .await?;
+                report.archived_requests += 1;
+                if let Some(key_exchange) = &self.key_exchange {
+                    report.pruned_entries += u64::from(key_exchange.remove(&request.intent_id));
+                }
+                if let Some(extensions) = &self.extensions {
+                    report.pruned_entries += u64::from(extensions.remove(&request.intent_id));
+                }
+            }
+        }
+
+        if let Some(offer_db) = &self.offer_db {
+            let window = self
+                .offer_resolve_window
+                .saturating_add(self.config.offer_retention_seconds);
+            if let Some(ended_before) = now.checked_sub(window) {
+                let pruned = offer_db.prune_offers(ended_before).await?;
+                report.pruned_offers += pruned.len() as u64;
+                if let Some(offer_inputs) = &self.offer_inputs {
+                    for offer_id in &pruned {
+                        report.pruned_entries += u64::from(offer_inputs.remove(offer_id));
+                    }
+                }
+            }
+        }
+
+        self.record(now, report);
+        Ok(report)
+    }
+
+    fn record(&self, now: u64, report: GcReport) {
+        let counters = &self.counters;
+        counters.runs.fetch_add(1, Ordering::Relaxed);
+        counters.last_run_at.store(now, Ordering::Relaxed);
+        counters
+            .archived_requests
+            .fetch_add(report.archived_requests, Ordering::Relaxed);
+        counters
+            .pruned_offers
+            .fetch_add(report.pruned_offers, Ordering::Relaxed);
+        counters
+            .pruned_entries
+            .fetch_add(report.pruned_entries, Ordering::Relaxed);
+    }
+
+    /// Collect intents every interval, runs forever
+    pub async fn run(self: Arc<Self>) {
+        loop {
+            tokio::time::sleep(self.config.interval()).await;
+            match self.run_once().await {
+                Ok(report) if report != GcReport::default() => {
+                    tracing::info!("Collected intents: {:?}", report);
+                }
+                Ok(_) => {}
+                Err(e) => tracing::error!("Failed to collect intents: {}", e),
+            }
+        }
+    }
+}
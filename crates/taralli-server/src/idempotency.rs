@@ -0,0 +1,172 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use axum::http::{HeaderMap, StatusCode};
+use serde_json::Value;
+use taralli_primitives::alloy::primitives::B256;
+use taralli_primitives::intents::envelope::IDEMPOTENCY_KEY_HEADER;
+
+use crate::error::{Result, ServerError};
+
+/// number of submissions remembered by idempotency key
+const DEFAULT_CAPACITY: usize = 4096;
+
+/// Remembers the most recent submissions by idempotency key, so a client retrying a submission
+/// whose response got lost (e.g. a 502 from a load balancer) is answered with the original
+/// response instead of the intent being broadcast or stored twice.
+///
+/// A key is reserved for the intent submitted with it until the submission completes. Retries
+/// arriving meanwhile are answered with a conflict, and the key can't be reused for another
+/// intent. A submission failing releases its key, so it can be retried.
+#[derive(Clone)]
+pub struct IdempotencyCache {
+    inner: Arc<Mutex<Entries>>,
+}
+
+/// Outcome of reserving the idempotency key of a submission
+pub enum Reservation {
+    /// the submission goes through, its response remembered once the guard completes
+    Proceed(IdempotencyGuard),
+    /// the submission already went through, answered with its original response
+    Replay(StatusCode, Value),
+}
+
+/// Reservation of an idempotency key, released unless completed
+pub struct IdempotencyGuard {
+    reserved: Option<(Arc<Mutex<Entries>>, String, B256)>,
+}
+
+enum Entry {
+    Pending {
+        intent_id: B256,
+    },
+    Completed {
+        intent_id: B256,
+        status: StatusCode,
+        body: Value,
+    },
+}
+
+struct Entries {
+    capacity: usize,
+    order: VecDeque<String>,
+    entries: HashMap<String, Entry>,
+}
+
+impl Default for IdempotencyCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl IdempotencyCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Entries {
+                capacity,
+                order: VecDeque::with_capacity(capacity),
+                entries: HashMap::with_capacity(capacity),
+            })),
+        }
+    }
+
+    /// Reserve the idempotency key of the given headers, if any, for the intent `intent_id`.
+    /// Fails while another submission with the key is in progress, or if the key was used for
+    /// another intent.
+    pub fn reserve(&self, headers: &HeaderMap, intent_id: B256) -> Result<Reservation> {
+        let Some(key) = idempotency_key(headers) else {
+            return Ok(Reservation::Proceed(IdempotencyGuard { reserved: None }));
+        };
+        let mut inner = self.inner.lock().expect("idempotency cache lock poisoned");
+        if let Some(entry) = inner.entries.get(key) {
+            return match entry {
+                entry if entry.intent_id() != intent_id => {
+                    Err(ServerError::IdempotencyKeyReused(format!(
+                        "idempotency key {key} was used for intent {}",
+                        entry.intent_id()
+                    )))
+                }
+                Entry::Pending { .. } => Err(ServerError::IdempotencyKeyInUse(format!(
+                    "a submission with idempotency key {key} is in progress"
+                ))),
+                Entry::Completed { status, body, .. } => {
+                    Ok(Reservation::Replay(*status, body.clone()))
+                }
+            };
+        }
+        inner.insert(key.to_string(), Entry::Pending { intent_id });
+        Ok(Reservation::Proceed(IdempotencyGuard {
+            reserved: Some((self.inner.clone(), key.to_string(), intent_id)),
+        }))
+    }
+}
+
+impl IdempotencyGuard {
+    /// Remember the response of the submission, answered to its retries
+    pub fn complete(mut self, status: StatusCode, body: &Value) {
+        if let Some((inner, key, intent_id)) = self.reserved.take() {
+            inner
+                .lock()
+                .expect("idempotency cache lock poisoned")
+                .insert(
+                    key,
+                    Entry::Completed {
+                        intent_id,
+                        status,
+                        body: body.clone(),
+                    },
+                );
+        }
+    }
+}
+
+impl Drop for IdempotencyGuard {
+    fn drop(&mut self) {
+        if let Some((inner, key, intent_id)) = self.reserved.take() {
+            inner
+                .lock()
+                .expect("idempotency cache lock poisoned")
+                .release(&key, intent_id);
+        }
+    }
+}
+
+impl Entry {
+    fn intent_id(&self) -> B256 {
+        match self {
+            Entry::Pending { intent_id } | Entry::Completed { intent_id, .. } => *intent_id,
+        }
+    }
+}
+
+impl Entries {
+    /// Set the entry of `key`, evicting the oldest entries once at capacity
+    fn insert(&mut self, key: String, entry: Entry) {
+        if self.entries.insert(key.clone(), entry).is_none() {
+            self.order.push_back(key);
+        }
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+
+    /// Forget the pending entry of `key` reserved for `intent_id`
+    fn release(&mut self, key: &str, intent_id: B256) {
+        if matches!(
+            self.entries.get(key),
+            Some(Entry::Pending { intent_id: pending }) if *pending == intent_id
+        ) {
+            self.entries.remove(key);
+            self.order.retain(|k| k != key);
+        }
+    }
+}
+
+fn idempotency_key(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|key| !key.is_empty())
+}
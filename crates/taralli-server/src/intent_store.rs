@@ -0,0 +1,228 @@
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta::ProofRequest;
+use taralli_primitives::alloy::primitives::{Address, Bytes, B256};
+use taralli_primitives::compression_utils::intents::ComputeRequestCompressed;
+use taralli_primitives::intents::envelope::IntentMetadata;
+use taralli_primitives::intents::request::compute_request_id;
+use taralli_primitives::systems::SystemId;
+
+use crate::error::{Result, ServerError};
+
+/// Lifecycle of a submitted intent as seen by the server
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IntentStatus {
+    /// validated and accepted by the server
+    Submitted,
+    /// sent to at least one subscribed provider
+    Broadcast,
+    /// auction and resolve window over for longer than the retention window, only served when
+    /// asked for
+    Archived,
+}
+
+impl IntentStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Submitted => "submitted",
+            Self::Broadcast => "broadcast",
+            Self::Archived => "archived",
+        }
+    }
+}
+
+impl TryFrom<&str> for IntentStatus {
+    type Error = ServerError;
+
+    fn try_from(status: &str) -> Result<Self> {
+        match status {
+            "submitted" => Ok(Self::Submitted),
+            "broadcast" => Ok(Self::Broadcast),
+            "archived" => Ok(Self::Archived),
+            other => Err(ServerError::DatabaseError(format!(
+                "unknown intent status: {other}"
+            ))),
+        }
+    }
+}
+
+/// Submitted `ComputeRequest` with its metadata broken out for querying. The system stays in the
+/// brotli compressed form it was submitted in, the blobs being hex encoded when serialized.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StoredRequest {
+    pub intent_id: B256,
+    pub system_id: SystemId,
+    pub signer: Address,
+    pub market: Address,
+    pub start_auction_timestamp: u64,
+    pub end_auction_timestamp: u64,
+    pub status: IntentStatus,
+    pub system: Bytes,
+    pub proof_commitment: Bytes,
+    pub signature: Bytes,
+}
+
+impl TryFrom<&ComputeRequestCompressed> for StoredRequest {
+    type Error = ServerError;
+
+    fn try_from(request: &ComputeRequestCompressed) -> Result<Self> {
+        Ok(Self {
+            intent_id: compute_request_id(&request.proof_request, &request.signature),
+            system_id: request.system_id,
+            signer: request.proof_request.signer,
+            market: request.proof_request.market,
+            start_auction_timestamp: request.proof_request.startAuctionTimestamp,
+            end_auction_timestamp: request.proof_request.endAuctionTimestamp,
+            status: IntentStatus::Submitted,
+            system: request.system.clone().into(),
+            proof_commitment: serde_json::to_vec(&request.proof_request)
+                .map_err(|e| ServerError::SerializationError(e.to_string()))?
+                .into(),
+            signature: request.signature.as_bytes().to_vec().into(),
+        })
+    }
+}
+
+impl StoredRequest {
+    /// Latest unix timestamp the request can be resolved at: the end of its auction followed by
+    /// its proving time
+    pub fn resolve_deadline(&self) -> Result<u64> {
+        let proof_request: ProofRequest = serde_json::from_slice(&self.proof_commitment)
+            .map_err(|e| ServerError::SerializationError(e.to_string()))?;
+        Ok(self
+            .end_auction_timestamp
+            .saturating_add(proof_request.provingTime.into()))
+    }
+}
+
+/// Filters of stored request queries, unset filters match every request but archived ones
+#[derive(Clone, Debug, Default)]
+pub struct RequestFilter {
+    pub system_id: Option<SystemId>,
+    pub signer: Option<Address>,
+    /// asking for `Archived` requests includes them regardless of `include_archived`
+    pub status: Option<IntentStatus>,
+    /// only requests whose auction is still open at this unix timestamp
+    pub active_at: Option<u64>,
+    /// only requests whose auction ended before this unix timestamp
+    pub ended_before: Option<u64>,
+    pub include_archived: bool,
+    /// only requests whose intent id sorts after this one, the last of the previous page
+    pub after: Option<B256>,
+    /// at most this many requests, every matching one when unset
+    pub limit: Option<usize>,
+}
+
+impl RequestFilter {
+    pub fn matches(&self, request: &StoredRequest) -> bool {
+        self.system_id.map_or(true, |id| id == request.system_id)
+            && self.signer.map_or(true, |signer| signer == request.signer)
+            && self.status.map_or(true, |status| status == request.status)
+            && self
+                .active_at
+                .map_or(true, |ts| request.end_auction_timestamp > ts)
+            && self
+                .ended_before
+                .map_or(true, |ts| request.end_auction_timestamp < ts)
+            && self.after.map_or(true, |after| request.intent_id > after)
+            && (request.status != IntentStatus::Archived || self.includes_archived())
+    }
+
+    /// Whether archived requests are served
+    pub fn includes_archived(&self) -> bool {
+        self.include_archived || self.status == Some(IntentStatus::Archived)
+    }
+}
+
+/// Persists submitted intents so they outlive the server process
+#[async_trait]
+pub trait IntentStore: Send + Sync {
+    /// Persist a validated request with the `Submitted` status along with its requester's
+    /// metadata, storing it twice is a no-op
+    async fn store_request(
+        &self,
+        request: &ComputeRequestCompressed,
+        metadata: &IntentMetadata,
+    ) -> Result<StoredRequest>;
+    /// Move a stored request to the given status
+    async fn update_request_status(&self, intent_id: B256, status: IntentStatus) -> Result<()>;
+    /// Stored requests matching the filter, ordered by intent id and capped at its limit
+    async fn get_requests(&self, filter: &RequestFilter) -> Result<Vec<StoredRequest>>;
+    /// Metadata of the stored requests signed by `signer` by intent id, leaving out the requests
+    /// submitted without any. Only served to the requester, never along with the requests.
+    async fn get_request_metadata(&self, signer: Address)
+        -> Result<BTreeMap<B256, IntentMetadata>>;
+}
+
+/// `IntentStore` keeping intents in memory, for tests and deployments without a database
+#[derive(Default)]
+pub struct InMemoryIntentStore {
+    requests: RwLock<BTreeMap<B256, (StoredRequest, IntentMetadata)>>,
+}
+
+#[async_trait]
+impl IntentStore for InMemoryIntentStore {
+    async fn store_request(
+        &self,
+        request: &ComputeRequestCompressed,
+        metadata: &IntentMetadata,
+    ) -> Result<StoredRequest> {
+        let stored = StoredRequest::try_from(request)?;
+        Ok(self
+            .requests
+            .write()
+            .expect("intent store lock poisoned")
+            .entry(stored.intent_id)
+            .or_insert((stored, metadata.clone()))
+            .0
+            .clone())
+    }
+
+    async fn update_request_status(&self, intent_id: B256, status: IntentStatus) -> Result<()> {
+        match self
+            .requests
+            .write()
+            .expect("intent store lock poisoned")
+            .get_mut(&intent_id)
+        {
+            Some((request, _)) => {
+                request.status = status;
+                Ok(())
+            }
+            None => Err(ServerError::DatabaseError(format!(
+                "request {intent_id} is not stored"
+            ))),
+        }
+    }
+
+    async fn get_requests(&self, filter: &RequestFilter) -> Result<Vec<StoredRequest>> {
+        Ok(self
+            .requests
+            .read()
+            .expect("intent store lock poisoned")
+            .values()
+            .map(|(request, _)| request)
+            .filter(|request| filter.matches(request))
+            .take(filter.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect())
+    }
+
+    async fn get_request_metadata(
+        &self,
+        signer: Address,
+    ) -> Result<BTreeMap<B256, IntentMetadata>> {
+        Ok(self
+            .requests
+            .read()
+            .expect("intent store lock poisoned")
+            .values()
+            .filter(|(request, metadata)| request.signer == signer && !metadata.is_empty())
+            .map(|(request, metadata)| (request.intent_id, metadata.clone()))
+            .collect())
+    }
+}
@@ -0,0 +1,84 @@
+//! Relays the content keys of encrypted requests from requesters to the providers that won them.
+//! The server only holds the messages both parties exchange: the provider's ephemeral public key
+//! and the content key wrapped for it, never anything that decrypts the system params.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use taralli_primitives::alloy::primitives::B256;
+use taralli_primitives::encryption::{KeyDelivery, KeyRequest};
+
+/// Key exchange of one encrypted request
+#[derive(Clone, Debug, Default)]
+struct KeyExchangeEntry {
+    request: Option<KeyRequest>,
+    delivery: Option<KeyDelivery>,
+}
+
+/// Pending key requests and key deliveries, keyed by request id
+#[derive(Default)]
+pub struct KeyExchange {
+    entries: Mutex<HashMap<B256, KeyExchangeEntry>>,
+}
+
+impl KeyExchange {
+    /// Record the provider's key request, replacing any earlier delivery that was wrapped for a
+    /// previous provider key
+    pub fn request_key(&self, intent_id: B256, request: KeyRequest) {
+        let mut entries = self.entries.lock().expect("key exchange lock poisoned");
+        let entry = entries.entry(intent_id).or_default();
+        if entry.request.as_ref() != Some(&request) {
+            entry.delivery = None;
+        }
+        entry.request = Some(request);
+    }
+
+    pub fn key_request(&self, intent_id: &B256) -> Option<KeyRequest> {
+        self.entries
+            .lock()
+            .expect("key exchange lock poisoned")
+            .get(intent_id)
+            .and_then(|entry| entry.request.clone())
+    }
+
+    /// Record the requester's answer to the pending key request, returning false when no key was
+    /// requested for the intent
+    pub fn deliver_key(&self, intent_id: B256, delivery: KeyDelivery) -> bool {
+        let mut entries = self.entries.lock().expect("key exchange lock poisoned");
+        match entries.get_mut(&intent_id) {
+            Some(entry) if entry.request.is_some() => {
+                entry.delivery = Some(delivery);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn key_delivery(&self, intent_id: &B256) -> Option<KeyDelivery> {
+        self.entries
+            .lock()
+            .expect("key exchange lock poisoned")
+            .get(intent_id)
+            .and_then(|entry| entry.delivery.clone())
+    }
+
+    /// Drop the key exchange of an intent, returning whether there was one
+    pub fn remove(&self, intent_id: &B256) -> bool {
+        self.entries
+            .lock()
+            .expect("key exchange lock poisoned")
+            .remove(intent_id)
+            .is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .expect("key exchange lock poisoned")
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
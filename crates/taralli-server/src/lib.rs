@@ -1,7 +1,20 @@
+pub mod capabilities;
+pub mod clock;
 pub mod config;
+pub mod dictionary;
 pub mod error;
+pub mod expiry;
+pub mod extension;
 pub mod extracted_intents;
+pub mod gc;
+pub mod idempotency;
+pub mod intent_store;
+pub mod key_exchange;
+pub mod log_control;
+pub mod offer_inputs;
+pub mod openapi;
 pub mod postgres;
+pub mod router;
 pub mod routes;
 pub mod state;
 pub mod subscription_manager;
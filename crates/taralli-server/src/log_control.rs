@@ -0,0 +1,83 @@
+//! Log filtering switchable while the server runs, through the admin routes, so it can be
+//! diagnosed without restarting it and dropping the subscriptions.
+//!
+//! The filter is an `EnvFilter` (`RUST_LOG` syntax) behind a reload layer. Events of the
+//! `STATE_TARGET` target are always kept at info, so state dumps show whatever the filter is.
+
+use std::sync::{Arc, Mutex};
+
+use taralli_primitives::logging::STATE_TARGET;
+use tracing::Subscriber;
+use tracing_subscriber::{
+    fmt, layer::SubscriberExt, registry::LookupSpan, reload, util::SubscriberInitExt, EnvFilter,
+    Registry,
+};
+
+use crate::error::{Result, ServerError};
+
+type Reload = dyn Fn(EnvFilter) -> std::result::Result<(), String> + Send + Sync;
+
+/// Handle to the log filter of the server, cloned freely
+#[derive(Clone)]
+pub struct LogControl {
+    reload: Arc<Reload>,
+    current: Arc<Mutex<String>>,
+}
+
+impl std::fmt::Debug for LogControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogControl")
+            .field("filter", &self.log_filter())
+            .finish()
+    }
+}
+
+impl LogControl {
+    /// Install a global fmt subscriber filtered by `filter`, returning the handle changing it
+    pub fn init(filter: &str) -> Result<Self> {
+        let (layer, control) = Self::layer::<Registry>(filter)?;
+        tracing_subscriber::registry()
+            .with(layer)
+            .with(fmt::layer())
+            .try_init()
+            .map_err(|e| ServerError::AppStateError(format!("installing subscriber: {e}")))?;
+        Ok(control)
+    }
+
+    /// Reloadable filter layer to compose a subscriber with, along with the handle changing it
+    pub fn layer<S>(filter: &str) -> Result<(reload::Layer<EnvFilter, S>, Self)>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span> + Send + Sync + 'static,
+    {
+        let (layer, handle) = reload::Layer::new(parse_filter(filter)?);
+        let control = Self {
+            reload: Arc::new(move |filter| handle.reload(filter).map_err(|e| e.to_string())),
+            current: Arc::new(Mutex::new(filter.to_string())),
+        };
+        Ok((layer, control))
+    }
+
+    /// Filter in effect, as last set
+    pub fn log_filter(&self) -> String {
+        self.current
+            .lock()
+            .expect("log filter lock poisoned")
+            .clone()
+    }
+
+    /// Replace the filter, e.g. `info,taralli_server::routes::submit=debug`. An invalid filter
+    /// leaves the one in effect.
+    pub fn set_log_filter(&self, filter: impl Into<String>) -> Result<()> {
+        let filter = filter.into();
+        (self.reload)(parse_filter(&filter)?)
+            .map_err(|e| ServerError::InvalidLogFilter(format!("reloading log filter: {e}")))?;
+        tracing::info!(target: STATE_TARGET, "log filter set to {filter}");
+        *self.current.lock().expect("log filter lock poisoned") = filter;
+        Ok(())
+    }
+}
+
+fn parse_filter(filter: &str) -> Result<EnvFilter> {
+    EnvFilter::try_new(format!("{filter},{STATE_TARGET}=info"))
+        .map_err(|e| ServerError::InvalidLogFilter(format!("invalid log filter {filter:?}: {e}")))
+}
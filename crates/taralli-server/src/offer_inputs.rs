@@ -0,0 +1,144 @@
+//! Relays the inputs of won offers from their requesters to the providers proving them. Inputs
+//! are only accepted once they match the inputs commitment the market recorded for the offer,
+//! and only served to the offer's provider.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use taralli_primitives::alloy::primitives::{Address, B256};
+use taralli_primitives::offer_inputs::OfferInputs;
+
+use crate::clock::{Clock, SystemClock};
+use crate::error::{Result, ServerError};
+
+/// number of offers whose inputs are kept
+pub const DEFAULT_OFFER_INPUTS_CAPACITY: usize = 1024;
+/// total size of the compressed inputs kept
+pub const DEFAULT_MAX_TOTAL_OFFER_INPUTS_BYTES: usize = 256 * 1024 * 1024;
+
+/// Delivered offer inputs, keyed by offer id. Entries are dropped once their offer's resolution
+/// deadline passes, the provider having no use for them after it. Deliveries are turned down
+/// while the live entries leave no room, rather than evicting inputs a provider still awaits.
+pub struct OfferInputsStore {
+    inner: Mutex<Entries>,
+    clock: Arc<dyn Clock>,
+}
+
+struct Entries {
+    capacity: usize,
+    max_total_bytes: usize,
+    total_bytes: usize,
+    entries: HashMap<B256, DeliveredInputs>,
+}
+
+/// Inputs delivered for an offer, along with the provider they are served to
+#[derive(Clone, Debug)]
+pub struct DeliveredInputs {
+    pub inputs: OfferInputs,
+    pub provider: Address,
+    /// unix timestamp the offer's resolution deadline passes at
+    pub expires_at: u64,
+}
+
+impl Default for OfferInputsStore {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_OFFER_INPUTS_CAPACITY,
+            DEFAULT_MAX_TOTAL_OFFER_INPUTS_BYTES,
+        )
+    }
+}
+
+impl OfferInputsStore {
+    pub fn new(capacity: usize, max_total_bytes: usize) -> Self {
+        Self {
+            inner: Mutex::new(Entries {
+                capacity,
+                max_total_bytes,
+                total_bytes: 0,
+                entries: HashMap::new(),
+            }),
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Read the time entries expire against from `clock`
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Record the inputs delivered for an offer, replacing any earlier delivery. Fails with
+    /// `ServerError::OfferInputsStoreFull` when the unexpired entries leave no room for them.
+    pub fn deliver(&self, offer_id: B256, delivered: DeliveredInputs) -> Result<()> {
+        let now = self.clock.now();
+        let mut inner = self.lock();
+        inner.prune_expired(now);
+        inner.remove(&offer_id);
+        let size = delivered.inputs.compressed_inputs.len();
+        if inner.entries.len() >= inner.capacity {
+            return Err(ServerError::OfferInputsStoreFull(format!(
+                "inputs of {} offers awaiting resolution kept",
+                inner.entries.len()
+            )));
+        }
+        if inner.total_bytes + size > inner.max_total_bytes {
+            return Err(ServerError::OfferInputsStoreFull(format!(
+                "{} bytes of inputs awaiting resolution kept, {size} more don't fit",
+                inner.total_bytes
+            )));
+        }
+        inner.total_bytes += size;
+        inner.entries.insert(offer_id, delivered);
+        Ok(())
+    }
+
+    /// Inputs delivered for an offer, unless its resolution deadline passed
+    pub fn inputs(&self, offer_id: &B256) -> Option<DeliveredInputs> {
+        let now = self.clock.now();
+        let mut inner = self.lock();
+        inner.prune_expired(now);
+        inner.entries.get(offer_id).cloned()
+    }
+
+    /// Drop the inputs delivered for an offer, returning whether there was one
+    pub fn remove(&self, offer_id: &B256) -> bool {
+        self.lock().remove(offer_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Entries> {
+        self.inner.lock().expect("offer inputs lock poisoned")
+    }
+}
+
+impl Entries {
+    fn prune_expired(&mut self, now: u64) {
+        let mut freed = 0;
+        self.entries.retain(|_, delivered| {
+            let live = delivered.expires_at > now;
+            if !live {
+                freed += delivered.inputs.compressed_inputs.len();
+            }
+            live
+        });
+        self.total_bytes -= freed;
+    }
+
+    fn remove(&mut self, offer_id: &B256) -> bool {
+        match self.entries.remove(offer_id) {
+            Some(delivered) => {
+                self.total_bytes -= delivered.inputs.compressed_inputs.len();
+                true
+            }
+            None => false,
+        }
+    }
+}
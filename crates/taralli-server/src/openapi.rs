@@ -0,0 +1,1025 @@
+//! OpenAPI description of the server's HTTP API, served at `/api-docs` so integrations outside
+//! of Rust don't have to reverse-engineer the submission bodies and error shapes.
+//!
+//! The description is written alongside the routes rather than derived from the handlers, the
+//! enumerations it documents (systems, error codes) being taken from the primitives. Every route
+//! the server binary registers has to be described here, which the server tests check against
+//! the routes of `router` the binary's router is built from.
+//!
+//! Submissions are multipart bodies whose `system_bytes` part is the brotli compressed system,
+//! optionally against a registered dictionary. It's documented as opaque binary, as are the
+//! bincode frames broadcast to subscribers.
+
+use serde_json::{json, Map, Value};
+use taralli_primitives::compression_utils::frames::FRAME_COMPRESSION_HEADER;
+use taralli_primitives::error::ErrorCode;
+use taralli_primitives::intents::envelope::{
+    IDEMPOTENCY_KEY_HEADER, INTENT_ENVELOPE_VERSION, INTENT_VERSION_HEADER, SSE_CONTROL_EVENT,
+    SSE_INTENT_EVENT,
+};
+use taralli_primitives::offer_inputs::INPUTS_QUERY_VALIDITY_SECONDS;
+use taralli_primitives::request_metadata::METADATA_QUERY_VALIDITY_SECONDS;
+use taralli_primitives::systems::SYSTEMS;
+
+use crate::routes::admin::ADMIN_KEY_HEADER;
+use crate::routes::query::{DEFAULT_REQUESTS_LIMIT, MAX_REQUESTS_LIMIT};
+
+/// OpenAPI version of the description
+pub const OPENAPI_VERSION: &str = "3.0.3";
+/// route the description is served at
+pub const API_DOCS_PATH: &str = "/api-docs";
+
+/// A method and path the server answers, the path in axum's syntax, e.g. `/dictionaries/:hash`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ApiRoute {
+    pub method: &'static str,
+    pub path: &'static str,
+}
+
+impl ApiRoute {
+    const fn new(method: &'static str, path: &'static str) -> Self {
+        Self { method, path }
+    }
+
+    /// Path in OpenAPI's syntax, e.g. `/dictionaries/{hash}`
+    pub fn openapi_path(&self) -> String {
+        self.path
+            .split('/')
+            .map(|segment| match segment.strip_prefix(':') {
+                Some(param) => format!("{{{param}}}"),
+                None => segment.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+/// Routes described, in the order they're documented
+pub fn api_routes() -> Vec<ApiRoute> {
+    operations().into_iter().map(|(route, _)| route).collect()
+}
+
+/// OpenAPI description of every route of the server
+pub fn openapi_spec() -> Value {
+    let mut paths = Map::new();
+    for (route, operation) in operations() {
+        let path = paths
+            .entry(route.openapi_path())
+            .or_insert_with(|| json!({}));
+        path[route.method] = operation;
+    }
+
+    json!({
+        "openapi": OPENAPI_VERSION,
+        "info": {
+            "title": "Taralli protocol server",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Submission, subscription and coordination of compute intents. \
+                Errors are answered as an `Error` body whose `code` is stable across versions.",
+        },
+        "tags": [
+            { "name": "intents", "description": "submission and query of compute intents" },
+            { "name": "subscriptions", "description": "broadcast of submitted intents to providers" },
+            { "name": "coordination", "description": "exchanges between the parties of a bid intent" },
+            { "name": "server", "description": "status and capabilities" },
+            { "name": "admin", "description": "only served when an admin api key is configured" },
+        ],
+        "paths": paths,
+        "components": {
+            "parameters": parameters(),
+            "responses": responses(),
+            "schemas": schemas(),
+            "securitySchemes": {
+                "adminKey": { "type": "apiKey", "in": "header", "name": ADMIN_KEY_HEADER },
+            },
+        },
+    })
+}
+
+fn operations() -> Vec<(ApiRoute, Value)> {
+    vec![
+        (
+            ApiRoute::new("post", "/submit/request"),
+            json!({
+                "tags": ["intents"],
+                "summary": "Submit a compute request, broadcast to the providers subscribed to its system",
+                "parameters": [{ "$ref": "#/components/parameters/IdempotencyKey" }],
+                "requestBody": submission_body("partial_request", "PartialComputeRequest"),
+                "responses": {
+                    "200": json_response("request broadcast", json!({
+                        "type": "object",
+                        "properties": {
+                            "message": { "type": "string" },
+                            "broadcast_receivers": {
+                                "type": "integer",
+                                "minimum": 0,
+                                "description": "subscribers the request is sent to, the ones of saturated providers skipped",
+                            },
+                            "metadata": ref_schema("IntentMetadata"),
+                            "warning": {
+                                "type": "string",
+                                "description": "why no provider advertising capabilities for the system would take the request, which is broadcast anyway",
+                            },
+                        },
+                    })),
+                    "400": { "$ref": "#/components/responses/Rejected" },
+                    "404": { "$ref": "#/components/responses/Error" },
+                    "408": { "$ref": "#/components/responses/Error" },
+                    "409": { "$ref": "#/components/responses/Error" },
+                    "413": { "$ref": "#/components/responses/Error" },
+                    "422": { "$ref": "#/components/responses/Error" },
+                    "503": { "$ref": "#/components/responses/Error" },
+                },
+            }),
+        ),
+        (
+            ApiRoute::new("post", "/submit/offer"),
+            json!({
+                "tags": ["intents"],
+                "summary": "Submit a compute offer, stored and broadcast to the subscribers of offers",
+                "parameters": [{ "$ref": "#/components/parameters/IdempotencyKey" }],
+                "requestBody": submission_body("partial_offer", "PartialComputeOffer"),
+                "responses": {
+                    "201": json_response("offer stored", ref_schema("Message")),
+                    "400": { "$ref": "#/components/responses/Rejected" },
+                    "404": { "$ref": "#/components/responses/Error" },
+                    "408": { "$ref": "#/components/responses/Error" },
+                    "409": { "$ref": "#/components/responses/Error" },
+                    "413": { "$ref": "#/components/responses/Error" },
+                    "422": { "$ref": "#/components/responses/Error" },
+                },
+            }),
+        ),
+        (
+            ApiRoute::new("get", "/requests"),
+            json!({
+                "tags": ["intents"],
+                "summary": "Query the requests persisted by the server's intent store",
+                "parameters": [
+                    query_param("system_id", ref_schema("SystemName"), "system of the requests"),
+                    query_param("signer", ref_schema("Address"), "signer of the requests"),
+                    query_param("status", json!({ "type": "string", "enum": ["submitted", "broadcast", "archived"] }), "lifecycle status"),
+                    query_param("active_at", json!({ "type": "integer", "format": "int64" }), "unix timestamp the requests' auctions are running at"),
+                    query_param("include_archived", json!({ "type": "boolean", "default": false }), "also serve the requests archived once over for longer than the retention window"),
+                    query_param("limit", json!({ "type": "integer", "minimum": 1, "maximum": MAX_REQUESTS_LIMIT, "default": DEFAULT_REQUESTS_LIMIT }), "requests per page"),
+                    query_param("after", ref_schema("Bytes32"), "cursor of the page, the `next_cursor` of the previous one"),
+                ],
+                "responses": {
+                    "200": json_response("a page of stored requests, ordered by intent id", json!({
+                        "type": "object",
+                        "properties": {
+                            "requests": { "type": "array", "items": ref_schema("StoredRequest") },
+                            "next_cursor": {
+                                "allOf": [ref_schema("Bytes32")],
+                                "nullable": true,
+                                "description": "cursor of the next page, null on the last one",
+                            },
+                        },
+                    })),
+                    "400": { "$ref": "#/components/responses/Error" },
+                    "500": { "$ref": "#/components/responses/Error" },
+                },
+            }),
+        ),
+        (
+            ApiRoute::new("post", "/requests/metadata"),
+            json!({
+                "tags": ["intents"],
+                "summary": "The requester queries the metadata submitted along with its requests",
+                "requestBody": json_body(ref_schema("MetadataQuery")),
+                "responses": {
+                    "200": json_response("metadata of the signer's requests submitted with any", json!({
+                        "type": "object",
+                        "properties": {
+                            "metadata": {
+                                "type": "object",
+                                "description": "metadata by intent id",
+                                "additionalProperties": ref_schema("IntentMetadata"),
+                            },
+                        },
+                    })),
+                    "400": { "$ref": "#/components/responses/Error" },
+                    "403": { "$ref": "#/components/responses/Error" },
+                    "500": { "$ref": "#/components/responses/Error" },
+                },
+            }),
+        ),
+        (
+            ApiRoute::new("get", "/query/:system_id"),
+            json!({
+                "tags": ["intents"],
+                "summary": "Query the active offers of a system",
+                "parameters": [path_param("system_id", ref_schema("SystemName"))],
+                "responses": {
+                    "200": json_response("active offers", json!({
+                        "type": "object",
+                        "properties": {
+                            "intents": { "type": "array", "items": ref_schema("StoredIntent") },
+                        },
+                    })),
+                    "400": { "$ref": "#/components/responses/Error" },
+                    "500": { "$ref": "#/components/responses/Error" },
+                },
+            }),
+        ),
+        (
+            ApiRoute::new("get", "/subscribe"),
+            json!({
+                "tags": ["subscriptions"],
+                "summary": "Subscribe to the intents of a set of systems over a websocket",
+                "description": "Upgraded to a websocket streaming each broadcast intent as a binary \
+                    message: a bincode frame of the compressed intent, deflated when frame compression \
+                    was negotiated. Control frames (expiry, lag) are sent as JSON text messages.",
+                "parameters": subscribe_params(),
+                "responses": {
+                    "101": {
+                        "description": "switched to the websocket protocol",
+                        "headers": { FRAME_COMPRESSION_HEADER: frame_compression_header() },
+                    },
+                    "400": { "$ref": "#/components/responses/Error" },
+                },
+            }),
+        ),
+        (
+            ApiRoute::new("get", "/subscribe/sse"),
+            json!({
+                "tags": ["subscriptions"],
+                "summary": "Subscribe to the intents of a set of systems over server-sent events",
+                "description": format!(
+                    "For networks blocking websocket upgrades. `{SSE_INTENT_EVENT}` events hold the \
+                    base64 encoding of the binary websocket message, `{SSE_CONTROL_EVENT}` events \
+                    the JSON of control frames."
+                ),
+                "parameters": subscribe_params(),
+                "responses": {
+                    "200": {
+                        "description": "event stream, kept open until the client disconnects",
+                        "headers": { FRAME_COMPRESSION_HEADER: frame_compression_header() },
+                        "content": { "text/event-stream": { "schema": { "type": "string" } } },
+                    },
+                    "400": { "$ref": "#/components/responses/Error" },
+                },
+            }),
+        ),
+        (
+            ApiRoute::new("get", "/status"),
+            json!({
+                "tags": ["server"],
+                "summary": "Version, supported systems with their subscribers and capabilities, validation bounds and markets",
+                "responses": {
+                    "200": json_response("server status", ref_schema("ServerStatus")),
+                },
+            }),
+        ),
+        (
+            ApiRoute::new("get", API_DOCS_PATH),
+            json!({
+                "tags": ["server"],
+                "summary": "This OpenAPI description",
+                "responses": {
+                    "200": json_response("OpenAPI description", json!({ "type": "object" })),
+                },
+            }),
+        ),
+        (
+            ApiRoute::new("post", "/capabilities"),
+            json!({
+                "tags": ["server"],
+                "summary": "Advertise the systems a provider proves and how many jobs it takes",
+                "requestBody": json_body(ref_schema("SignedCapabilities")),
+                "responses": {
+                    "201": json_response("capabilities registered", ref_schema("Message")),
+                    "400": { "$ref": "#/components/responses/Error" },
+                    "503": { "$ref": "#/components/responses/Error" },
+                },
+            }),
+        ),
+        (
+            ApiRoute::new("put", "/capabilities/:provider"),
+            json!({
+                "tags": ["server"],
+                "summary": "Refresh the capabilities of a provider, keeping them alive",
+                "parameters": [path_param("provider", ref_schema("Address"))],
+                "requestBody": json_body(ref_schema("SignedCapabilities")),
+                "responses": {
+                    "200": json_response("capabilities refreshed", ref_schema("Message")),
+                    "400": { "$ref": "#/components/responses/Error" },
+                    "503": { "$ref": "#/components/responses/Error" },
+                },
+            }),
+        ),
+        (
+            ApiRoute::new("post", "/dictionaries"),
+            json!({
+                "tags": ["intents"],
+                "summary": "Register a compression dictionary systems can be compressed against",
+                "requestBody": {
+                    "required": true,
+                    "content": { "application/octet-stream": { "schema": { "type": "string", "format": "binary" } } },
+                },
+                "responses": {
+                    "201": json_response("dictionary registered", json!({
+                        "type": "object",
+                        "properties": { "hash": ref_schema("Bytes32") },
+                    })),
+                    "400": { "$ref": "#/components/responses/Error" },
+                    "413": { "$ref": "#/components/responses/Error" },
+                    "429": { "$ref": "#/components/responses/Error" },
+                },
+            }),
+        ),
+        (
+            ApiRoute::new("get", "/dictionaries/:hash"),
+            json!({
+                "tags": ["intents"],
+                "summary": "Fetch a registered compression dictionary",
+                "parameters": [path_param("hash", ref_schema("Bytes32"))],
+                "responses": {
+                    "200": {
+                        "description": "raw dictionary",
+                        "content": { "application/octet-stream": { "schema": { "type": "string", "format": "binary" } } },
+                    },
+                    "404": { "$ref": "#/components/responses/Error" },
+                },
+            }),
+        ),
+        (
+            ApiRoute::new("post", "/intents/:intent_id/key/request"),
+            coordination(
+                "The provider that won an encrypted request asks for its content key",
+                Some("KeyRequest"),
+                ("202", "key request relayed", "Message"),
+            ),
+        ),
+        (
+            ApiRoute::new("get", "/intents/:intent_id/key/request"),
+            coordination(
+                "The requester polls the pending key request of its encrypted request",
+                None,
+                ("200", "pending key request", "KeyRequest"),
+            ),
+        ),
+        (
+            ApiRoute::new("post", "/intents/:intent_id/key"),
+            coordination(
+                "The requester delivers the content key wrapped for the provider",
+                Some("KeyDelivery"),
+                ("200", "key delivered", "Message"),
+            ),
+        ),
+        (
+            ApiRoute::new("get", "/intents/:intent_id/key"),
+            coordination(
+                "The provider polls the delivered key",
+                None,
+                ("200", "delivered key", "KeyDelivery"),
+            ),
+        ),
+        (
+            ApiRoute::new("post", "/intents/:intent_id/extension-request"),
+            coordination(
+                "The winning provider asks the requester for a later resolution deadline",
+                Some("ExtensionRequest"),
+                ("202", "extension request relayed", "Message"),
+            ),
+        ),
+        (
+            ApiRoute::new("get", "/intents/:intent_id/extension-request"),
+            coordination(
+                "The requester polls the pending extension request",
+                None,
+                ("200", "pending extension request", "ExtensionRequest"),
+            ),
+        ),
+        (
+            ApiRoute::new("post", "/intents/:intent_id/extension-response"),
+            coordination(
+                "The requester answers the extension request",
+                Some("ExtensionResponse"),
+                ("200", "extension response relayed", "Message"),
+            ),
+        ),
+        (
+            ApiRoute::new("get", "/intents/:intent_id/extension-response"),
+            coordination(
+                "The provider polls the requester's answer",
+                None,
+                ("200", "extension response", "ExtensionResponse"),
+            ),
+        ),
+        (
+            ApiRoute::new("post", "/offers/:offer_id/inputs"),
+            json!({
+                "tags": ["coordination"],
+                "summary": "The requester that bid on an offer delivers the preimage of its inputs commitment",
+                "parameters": [path_param("offer_id", ref_schema("Bytes32"))],
+                "requestBody": json_body(ref_schema("OfferInputs")),
+                "responses": {
+                    "200": json_response("inputs delivered", ref_schema("Message")),
+                    "400": { "$ref": "#/components/responses/Error" },
+                    "403": { "$ref": "#/components/responses/Error" },
+                    "413": { "$ref": "#/components/responses/Error" },
+                    "503": { "$ref": "#/components/responses/Error" },
+                },
+            }),
+        ),
+        (
+            ApiRoute::new("post", "/offers/:offer_id/inputs/query"),
+            json!({
+                "tags": ["coordination"],
+                "summary": "The provider of an offer polls the delivered inputs through a query it signed",
+                "parameters": [path_param("offer_id", ref_schema("Bytes32"))],
+                "requestBody": json_body(ref_schema("InputsQuery")),
+                "responses": {
+                    "200": json_response("delivered inputs", ref_schema("OfferInputs")),
+                    "403": { "$ref": "#/components/responses/Error" },
+                    "404": { "$ref": "#/components/responses/Error" },
+                },
+            }),
+        ),
+        (
+            ApiRoute::new("get", "/admin/log-filter"),
+            admin(
+                "Log filter in effect",
+                None,
+                json_response("log filter", ref_schema("LogFilter")),
+            ),
+        ),
+        (
+            ApiRoute::new("put", "/admin/log-filter"),
+            admin(
+                "Replace the log filter without restarting",
+                Some(ref_schema("LogFilter")),
+                json_response("log filter", ref_schema("LogFilter")),
+            ),
+        ),
+        (
+            ApiRoute::new("post", "/admin/dump-state"),
+            admin(
+                "Log the subscriptions' state, whatever the log filter",
+                None,
+                json!({ "description": "state logged" }),
+            ),
+        ),
+    ]
+}
+
+/// Operation of the parties of a bid request exchanging through the server
+fn coordination(
+    summary: &str,
+    body: Option<&str>,
+    (status, description, schema): (&str, &str, &str),
+) -> Value {
+    let mut operation = json!({
+        "tags": ["coordination"],
+        "summary": summary,
+        "parameters": [path_param("intent_id", ref_schema("Bytes32"))],
+        "responses": {
+            status: json_response(description, ref_schema(schema)),
+            "400": { "$ref": "#/components/responses/Error" },
+            "403": { "$ref": "#/components/responses/Error" },
+            "404": { "$ref": "#/components/responses/Error" },
+        },
+    });
+    if let Some(body) = body {
+        operation["requestBody"] = json_body(ref_schema(body));
+    }
+    operation
+}
+
+/// Operation of the admin routes, authorized by the admin api key
+fn admin(summary: &str, body: Option<Value>, success: Value) -> Value {
+    let status = if success.get("content").is_some() {
+        "200"
+    } else {
+        "204"
+    };
+    let mut operation = json!({
+        "tags": ["admin"],
+        "summary": summary,
+        "security": [{ "adminKey": [] }],
+        "responses": {
+            status: success,
+            "400": { "$ref": "#/components/responses/Error" },
+            "401": { "$ref": "#/components/responses/Error" },
+        },
+    });
+    if let Some(body) = body {
+        operation["requestBody"] = json_body(body);
+    }
+    operation
+}
+
+fn submission_body(partial_field: &str, partial_schema: &str) -> Value {
+    json!({
+        "required": true,
+        "content": {
+            "multipart/form-data": {
+                "schema": {
+                    "type": "object",
+                    "required": [partial_field, "system_bytes"],
+                    "properties": {
+                        partial_field: {
+                            "description": format!(
+                                "JSON of the intent without its system, wrapped in an `IntentEnvelope` \
+                                (version {INTENT_ENVELOPE_VERSION}) or sent bare as the legacy payload"
+                            ),
+                            "oneOf": [ref_schema("IntentEnvelope"), ref_schema(partial_schema)],
+                        },
+                        "system_bytes": {
+                            "type": "string",
+                            "format": "binary",
+                            "description": "brotli compressed JSON of the system params, prefixed \
+                                with the hash of a registered dictionary when compressed against one",
+                        },
+                    },
+                },
+            },
+        },
+    })
+}
+
+fn subscribe_params() -> Value {
+    json!([
+        query_param(
+            "subscribed_to",
+            json!({ "type": "integer", "minimum": 0, "maximum": 255 }),
+            "bitmask of the systems whose requests are sent, all of them when unset",
+        ),
+        query_param(
+            "subscription_mask",
+            json!({ "type": "integer", "minimum": 0, "maximum": 65535 }),
+            "encoded mask of the systems subscribed to per kind of intent, taking precedence over `subscribed_to`",
+        ),
+        query_param(
+            "buffer_size",
+            json!({ "type": "integer", "minimum": 1 }),
+            "messages left unsent before the oldest ones are skipped, bounded by the server's buffer",
+        ),
+        query_param(
+            "provider",
+            ref_schema("Address"),
+            "address of the provider subscribing, whose requests are skipped or flagged while its capabilities report no free slot",
+        ),
+        {
+            "name": INTENT_VERSION_HEADER,
+            "in": "header",
+            "description": "intent envelope version the subscriber supports, the legacy frames being sent when unset",
+            "schema": { "type": "integer" },
+        },
+        {
+            "name": FRAME_COMPRESSION_HEADER,
+            "in": "header",
+            "description": "frame compression the subscriber takes",
+            "schema": { "type": "string" },
+        },
+    ])
+}
+
+fn frame_compression_header() -> Value {
+    json!({
+        "description": "set when the server deflates the frames sent",
+        "schema": { "type": "string" },
+    })
+}
+
+fn parameters() -> Value {
+    json!({
+        "IdempotencyKey": {
+            "name": IDEMPOTENCY_KEY_HEADER,
+            "in": "header",
+            "description": "retries carrying the key of a submission that went through get its original response, a key being bound to the intent first submitted with it",
+            "schema": { "type": "string" },
+        },
+    })
+}
+
+fn responses() -> Value {
+    json!({
+        "Error": json_response("error", ref_schema("Error")),
+        "Rejected": json_response(
+            "submission rejected, every failed validation check listed in `violations`",
+            ref_schema("Error"),
+        ),
+    })
+}
+
+fn schemas() -> Value {
+    let system_ids: Vec<Value> = SYSTEMS.iter().map(|system_id| json!(system_id)).collect();
+    let system_names: Vec<&str> = SYSTEMS.iter().map(|system_id| system_id.as_str()).collect();
+    let error_codes: Vec<&str> = ErrorCode::ALL.iter().map(ErrorCode::as_str).collect();
+
+    let schemas = [
+        ("Address", hex_string(20, "address")),
+        ("Bytes32", hex_string(32, "32 bytes")),
+        (
+            "Uint256",
+            json!({ "type": "string", "pattern": "^0x[0-9a-fA-F]{1,64}$", "description": "hex encoded uint256" }),
+        ),
+        (
+            "Bytes",
+            json!({ "type": "string", "pattern": "^0x([0-9a-fA-F]{2})*$", "description": "hex encoded bytes" }),
+        ),
+        (
+            "Signature",
+            json!({
+                "type": "object",
+                "required": ["r", "s", "yParity"],
+                "properties": {
+                    "r": ref_schema("Uint256"),
+                    "s": ref_schema("Uint256"),
+                    "yParity": { "type": "string", "enum": ["0x0", "0x1"] },
+                },
+            }),
+        ),
+        ("SystemId", json!({ "type": "string", "enum": system_ids })),
+        (
+            "SystemName",
+            json!({ "type": "string", "enum": system_names }),
+        ),
+        (
+            "ProofRequest",
+            json!({
+                "type": "object",
+                "description": "request as signed for the universal bombetta market",
+                "properties": {
+                    "signer": ref_schema("Address"),
+                    "market": ref_schema("Address"),
+                    "nonce": ref_schema("Uint256"),
+                    "rewardToken": ref_schema("Address"),
+                    "maxRewardAmount": ref_schema("Uint256"),
+                    "minRewardAmount": ref_schema("Uint256"),
+                    "minimumStake": { "type": "integer" },
+                    "startAuctionTimestamp": { "type": "integer", "format": "int64" },
+                    "endAuctionTimestamp": { "type": "integer", "format": "int64" },
+                    "provingTime": { "type": "integer", "format": "int32" },
+                    "inputsCommitment": ref_schema("Bytes32"),
+                    "extraData": ref_schema("Bytes"),
+                },
+            }),
+        ),
+        (
+            "ProofOffer",
+            json!({
+                "type": "object",
+                "description": "offer as signed for the universal porchetta market",
+                "properties": {
+                    "signer": ref_schema("Address"),
+                    "market": ref_schema("Address"),
+                    "nonce": ref_schema("Uint256"),
+                    "rewardToken": ref_schema("Address"),
+                    "rewardAmount": ref_schema("Uint256"),
+                    "stakeToken": ref_schema("Address"),
+                    "stakeAmount": ref_schema("Uint256"),
+                    "startAuctionTimestamp": { "type": "integer", "format": "int64" },
+                    "endAuctionTimestamp": { "type": "integer", "format": "int64" },
+                    "provingTime": { "type": "integer", "format": "int32" },
+                    "inputsCommitment": ref_schema("Bytes32"),
+                    "extraData": ref_schema("Bytes"),
+                },
+            }),
+        ),
+        (
+            "PartialComputeRequest",
+            json!({
+                "type": "object",
+                "required": ["system_id", "proof_request", "signature"],
+                "properties": {
+                    "system_id": ref_schema("SystemId"),
+                    "proof_request": ref_schema("ProofRequest"),
+                    "signature": ref_schema("Signature"),
+                },
+            }),
+        ),
+        (
+            "PartialComputeOffer",
+            json!({
+                "type": "object",
+                "required": ["system_id", "proof_offer", "signature"],
+                "properties": {
+                    "system_id": ref_schema("SystemId"),
+                    "proof_offer": ref_schema("ProofOffer"),
+                    "signature": ref_schema("Signature"),
+                },
+            }),
+        ),
+        (
+            "IntentEnvelope",
+            json!({
+                "type": "object",
+                "required": ["v", "kind", "payload"],
+                "properties": {
+                    "v": { "type": "integer", "maximum": INTENT_ENVELOPE_VERSION },
+                    "kind": { "type": "string", "enum": ["request", "offer"] },
+                    "payload": {
+                        "description": "the partial intent of `kind`",
+                        "oneOf": [ref_schema("PartialComputeRequest"), ref_schema("PartialComputeOffer")],
+                    },
+                    "metadata": ref_schema("IntentMetadata"),
+                },
+            }),
+        ),
+        (
+            "IntentMetadata",
+            json!({
+                "type": "object",
+                "description": "submitter's tags, not signed and never broadcast. Keys are 1 to 64 \
+                    ascii alphanumerics, `_`, `-`, `.` or `:`, control characters are stripped from \
+                    the values, and the entries and their size are bounded by the server's submission limits",
+                "additionalProperties": { "type": "string" },
+            }),
+        ),
+        (
+            "StoredIntent",
+            json!({
+                "type": "object",
+                "description": "intent as persisted, its system compressed as submitted",
+                "properties": {
+                    "system_id": ref_schema("SystemId"),
+                    "system": byte_array("compressed system, as submitted in `system_bytes`"),
+                    "proof_commitment": byte_array("JSON of the `ProofRequest` or `ProofOffer`"),
+                    "signature": byte_array("65 bytes signature"),
+                    "metadata": ref_schema("IntentMetadata"),
+                },
+            }),
+        ),
+        (
+            "StoredRequest",
+            json!({
+                "type": "object",
+                "description": "request as persisted, its system compressed as submitted",
+                "properties": {
+                    "intent_id": ref_schema("Bytes32"),
+                    "system_id": ref_schema("SystemId"),
+                    "signer": ref_schema("Address"),
+                    "market": ref_schema("Address"),
+                    "start_auction_timestamp": { "type": "integer", "format": "int64" },
+                    "end_auction_timestamp": { "type": "integer", "format": "int64" },
+                    "status": { "type": "string", "enum": ["submitted", "broadcast", "archived"] },
+                    "system": ref_schema("Bytes"),
+                    "proof_commitment": ref_schema("Bytes"),
+                    "signature": ref_schema("Bytes"),
+                },
+            }),
+        ),
+        (
+            "MetadataQuery",
+            json!({
+                "type": "object",
+                "required": ["chain_id", "issued_at", "signature"],
+                "properties": {
+                    "chain_id": {
+                        "type": "integer",
+                        "format": "int64",
+                        "description": "chain id of the server the query is sent to, queries for other chains are rejected",
+                    },
+                    "issued_at": {
+                        "type": "integer",
+                        "format": "int64",
+                        "description": format!("unix timestamp the query was signed at, accepted for {METADATA_QUERY_VALIDITY_SECONDS} seconds either side"),
+                    },
+                    "signature": ref_schema("Signature"),
+                },
+            }),
+        ),
+        (
+            "InputsQuery",
+            json!({
+                "type": "object",
+                "required": ["issued_at", "signature"],
+                "properties": {
+                    "issued_at": {
+                        "type": "integer",
+                        "format": "int64",
+                        "description": format!("unix timestamp the provider signed the query of the offer's inputs at, accepted for {INPUTS_QUERY_VALIDITY_SECONDS} seconds either side"),
+                    },
+                    "signature": ref_schema("Signature"),
+                },
+            }),
+        ),
+        (
+            "Message",
+            json!({
+                "type": "object",
+                "properties": { "message": { "type": "string" } },
+            }),
+        ),
+        (
+            "Error",
+            json!({
+                "type": "object",
+                "required": ["error", "code"],
+                "properties": {
+                    "error": { "type": "string", "description": "human readable reason" },
+                    "code": { "type": "string", "enum": error_codes },
+                    "violations": { "type": "array", "items": ref_schema("Violation") },
+                },
+            }),
+        ),
+        (
+            "Violation",
+            json!({
+                "type": "object",
+                "required": ["rule", "message"],
+                "properties": {
+                    "rule": { "type": "string", "description": "snake case name of the validation rule broken" },
+                    "observed": { "type": "string" },
+                    "allowed": { "type": "string" },
+                    "message": { "type": "string" },
+                },
+            }),
+        ),
+        (
+            "SystemCapability",
+            json!({
+                "type": "object",
+                "properties": {
+                    "system_id": ref_schema("SystemId"),
+                    "typical_proving_time": { "type": "integer", "format": "int32" },
+                    "minimum_reward": ref_schema("Uint256"),
+                },
+            }),
+        ),
+        (
+            "ProviderCapabilities",
+            json!({
+                "type": "object",
+                "properties": {
+                    "provider": ref_schema("Address"),
+                    "systems": { "type": "array", "items": ref_schema("SystemCapability") },
+                    "max_concurrent_jobs": { "type": "integer", "format": "int32" },
+                    "active_jobs": { "type": "integer", "format": "int32" },
+                    "issued_at": { "type": "integer", "format": "int64" },
+                },
+            }),
+        ),
+        (
+            "SignedCapabilities",
+            json!({
+                "type": "object",
+                "required": ["capabilities", "signature"],
+                "properties": {
+                    "capabilities": ref_schema("ProviderCapabilities"),
+                    "signature": ref_schema("Signature"),
+                },
+            }),
+        ),
+        (
+            "KeyRequest",
+            signed(
+                "ephemeral public key the content key gets wrapped for",
+                "provider_key",
+            ),
+        ),
+        (
+            "KeyDelivery",
+            signed("content key wrapped for the provider key", "wrapped_key"),
+        ),
+        (
+            "OfferInputs",
+            signed(
+                "brotli compressed preimage of the offer's inputs commitment",
+                "compressed_inputs",
+            ),
+        ),
+        (
+            "ExtensionRequest",
+            json!({
+                "type": "object",
+                "required": ["new_deadline", "reason", "signature"],
+                "properties": {
+                    "new_deadline": { "type": "integer", "format": "int64" },
+                    "reason": { "type": "string" },
+                    "signature": ref_schema("Signature"),
+                },
+            }),
+        ),
+        (
+            "ExtensionResponse",
+            json!({
+                "type": "object",
+                "required": ["new_deadline", "approved", "signature"],
+                "properties": {
+                    "new_deadline": { "type": "integer", "format": "int64" },
+                    "approved": { "type": "boolean" },
+                    "signature": ref_schema("Signature"),
+                },
+            }),
+        ),
+        (
+            "ServerStatus",
+            json!({
+                "type": "object",
+                "properties": {
+                    "version": { "type": "string" },
+                    "systems": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "system_id": ref_schema("SystemId"),
+                                "bit": { "type": "integer" },
+                                "subscribers": { "type": "integer" },
+                                "capabilities": { "type": "object" },
+                                "validation": { "type": "object" },
+                            },
+                        },
+                    },
+                    "active_subscriptions": { "type": "integer" },
+                    "skipped_saturated_deliveries": { "type": "integer" },
+                    "gc": {
+                        "type": "object",
+                        "nullable": true,
+                        "properties": {
+                            "runs": { "type": "integer" },
+                            "last_run_at": { "type": "integer", "format": "int64", "nullable": true },
+                            "archived_requests": { "type": "integer" },
+                            "pruned_offers": { "type": "integer" },
+                            "pruned_entries": { "type": "integer" },
+                        },
+                    },
+                    "validation": {
+                        "type": "object",
+                        "properties": {
+                            "request": { "type": "object" },
+                            "offer": { "type": "object" },
+                        },
+                    },
+                    "markets": {
+                        "type": "object",
+                        "properties": {
+                            "universal_bombetta": ref_schema("Address"),
+                            "universal_porchetta": ref_schema("Address"),
+                        },
+                    },
+                },
+            }),
+        ),
+        (
+            "LogFilter",
+            json!({
+                "type": "object",
+                "required": ["filter"],
+                "properties": { "filter": { "type": "string" } },
+            }),
+        ),
+    ];
+    Value::Object(
+        schemas
+            .into_iter()
+            .map(|(name, schema)| (name.to_string(), schema))
+            .collect(),
+    )
+}
+
+fn ref_schema(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{name}") })
+}
+
+fn json_body(schema: Value) -> Value {
+    json!({ "required": true, "content": { "application/json": { "schema": schema } } })
+}
+
+fn json_response(description: &str, schema: Value) -> Value {
+    json!({ "description": description, "content": { "application/json": { "schema": schema } } })
+}
+
+fn path_param(name: &str, schema: Value) -> Value {
+    json!({ "name": name, "in": "path", "required": true, "schema": schema })
+}
+
+fn query_param(name: &str, schema: Value, description: &str) -> Value {
+    json!({ "name": name, "in": "query", "required": false, "description": description, "schema": schema })
+}
+
+fn hex_string(bytes: usize, description: &str) -> Value {
+    json!({
+        "type": "string",
+        "pattern": format!("^0x[0-9a-fA-F]{{{}}}$", bytes * 2),
+        "description": format!("hex encoded {description}"),
+    })
+}
+
+fn byte_array(description: &str) -> Value {
+    json!({
+        "type": "array",
+        "items": { "type": "integer", "minimum": 0, "maximum": 255 },
+        "description": description,
+    })
+}
+
+/// Hex encoded value, signed by the party sending it
+fn signed(description: &str, field: &str) -> Value {
+    json!({
+        "type": "object",
+        "required": [field, "signature"],
+        "properties": {
+            field: {
+                "type": "string",
+                "pattern": "^0x([0-9a-fA-F]{2})*$",
+                "description": description,
+            },
+            "signature": ref_schema("Signature"),
+        },
+    })
+}
@@ -1,11 +1,18 @@
 use crate::error::{Result, ServerError};
+use crate::intent_store::{IntentStatus, IntentStore, RequestFilter, StoredRequest};
+use async_trait::async_trait;
 use deadpool_postgres::{Manager, Pool};
+use std::collections::BTreeMap;
 use taralli_primitives::{
-    compression_utils::{db::StoredIntent, intents::ComputeOfferCompressed},
-    intents::offer::compute_offer_id,
+    alloy::primitives::{Address, B256},
+    compression_utils::{
+        db::StoredIntent,
+        intents::{ComputeOfferCompressed, ComputeRequestCompressed},
+    },
+    intents::{envelope::IntentMetadata, offer::compute_offer_id},
     systems::SystemId,
 };
-use tokio_postgres::{Config, NoTls};
+use tokio_postgres::{Config, NoTls, Row};
 
 pub const CREATE_INTENTS_TABLE: &str = "
     CREATE TABLE IF NOT EXISTS intents (
@@ -44,6 +51,68 @@ pub const GET_INTENTS_BY_ID: &str = "
     AND intents.expired_at IS NULL;
 ";
 
+pub const DELETE_ENDED_INTENTS: &str = "
+    DELETE FROM intents
+    WHERE expiration_ts < to_timestamp($1)
+    RETURNING intent_id;
+";
+
+pub const CREATE_REQUESTS_TABLE: &str = "
+    CREATE TABLE IF NOT EXISTS requests (
+        intent_id BYTEA PRIMARY KEY,
+        system_id TEXT NOT NULL,
+        signer BYTEA NOT NULL,
+        market BYTEA NOT NULL,
+        start_auction_ts BIGINT NOT NULL,
+        end_auction_ts BIGINT NOT NULL,
+        status TEXT NOT NULL,
+        system BYTEA NOT NULL,
+        proof_commitment BYTEA NOT NULL,
+        signature BYTEA NOT NULL,
+        metadata TEXT NOT NULL DEFAULT '{}',
+        created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+    );
+
+    ALTER TABLE requests ADD COLUMN IF NOT EXISTS metadata TEXT NOT NULL DEFAULT '{}';
+
+    CREATE INDEX IF NOT EXISTS idx_requests_system ON requests(system_id);
+    CREATE INDEX IF NOT EXISTS idx_requests_signer ON requests(signer);
+    CREATE INDEX IF NOT EXISTS idx_requests_end_auction ON requests(end_auction_ts);
+";
+
+pub const INSERT_REQUEST: &str = "
+    INSERT INTO requests (intent_id, system_id, signer, market, start_auction_ts, end_auction_ts, status, system, proof_commitment, signature, metadata)
+    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+    ON CONFLICT (intent_id) DO NOTHING;
+";
+
+pub const UPDATE_REQUEST_STATUS: &str = "
+    UPDATE requests SET status = $2 WHERE intent_id = $1;
+";
+
+pub const GET_REQUESTS: &str = "
+    SELECT intent_id, system_id, signer, market, start_auction_ts, end_auction_ts, status, system, proof_commitment, signature FROM requests
+    WHERE ($1::TEXT IS NULL OR system_id = $1)
+    AND ($2::BYTEA IS NULL OR signer = $2)
+    AND ($3::TEXT IS NULL OR status = $3)
+    AND ($4::BIGINT IS NULL OR end_auction_ts > $4)
+    AND ($5::BIGINT IS NULL OR end_auction_ts < $5)
+    AND ($6 OR status <> 'archived')
+    AND ($7::BYTEA IS NULL OR intent_id > $7)
+    ORDER BY intent_id
+    LIMIT $8;
+";
+
+pub const GET_REQUEST_BY_ID: &str = "
+    SELECT intent_id, system_id, signer, market, start_auction_ts, end_auction_ts, status, system, proof_commitment, signature FROM requests
+    WHERE intent_id = $1;
+";
+
+pub const GET_REQUEST_METADATA: &str = "
+    SELECT intent_id, metadata FROM requests
+    WHERE signer = $1 AND metadata <> '{}';
+";
+
 /// Postgres database used to store compute intents (currently `ComputeOffers` only)
 #[derive(Clone)]
 pub struct Db {
@@ -90,6 +159,7 @@ impl Db {
         // Define all tables and their creation statements
         let tables = vec![
             ("intents", CREATE_INTENTS_TABLE), // Add more tables here as needed
+            ("requests", CREATE_REQUESTS_TABLE),
         ];
 
         for (table_name, ddl) in tables {
@@ -191,4 +261,217 @@ impl Db {
             .map(|r| r.map_err(ServerError::PrimitivesError))
             .collect::<Result<Vec<_>>>()
     }
+
+    /// Delete the compute offers whose auction ended before `ended_before`, returning their ids
+    pub async fn prune_offers(&self, ended_before: u64) -> Result<Vec<B256>> {
+        let conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| ServerError::DatabaseError(e.to_string()))?;
+        let rows = conn
+            .query(DELETE_ENDED_INTENTS, &[&(ended_before as f64)])
+            .await
+            .map_err(|e| ServerError::DatabaseError(e.to_string()))?;
+
+        rows.iter()
+            .map(|row| {
+                row.try_get::<_, Vec<u8>>("intent_id")
+                    .ok()
+                    .filter(|bytes| bytes.len() == 32)
+                    .map(|bytes| B256::from_slice(&bytes))
+                    .ok_or_else(|| {
+                        ServerError::DatabaseError("malformed pruned intent id".to_string())
+                    })
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl IntentStore for Db {
+    async fn store_request(
+        &self,
+        request: &ComputeRequestCompressed,
+        metadata: &IntentMetadata,
+    ) -> Result<StoredRequest> {
+        let stored = StoredRequest::try_from(request)?;
+        let metadata = serde_json::to_string(metadata)
+            .map_err(|e| ServerError::SerializationError(e.to_string()))?;
+        let conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| ServerError::DatabaseError(e.to_string()))?;
+        let prepared_stmt = conn
+            .prepare(INSERT_REQUEST)
+            .await
+            .map_err(|e| ServerError::DatabaseError(e.to_string()))?;
+        conn.execute(
+            &prepared_stmt,
+            &[
+                &stored.intent_id.as_slice(),
+                &stored.system_id.as_str(),
+                &stored.signer.as_slice(),
+                &stored.market.as_slice(),
+                &to_db_timestamp(stored.start_auction_timestamp)?,
+                &to_db_timestamp(stored.end_auction_timestamp)?,
+                &stored.status.as_str(),
+                &&stored.system[..],
+                &&stored.proof_commitment[..],
+                &&stored.signature[..],
+                &metadata,
+            ],
+        )
+        .await
+        .map_err(|e| ServerError::DatabaseError(e.to_string()))?;
+
+        // return the row as stored, a resubmitted request keeps its original status
+        let row = conn
+            .query_one(GET_REQUEST_BY_ID, &[&stored.intent_id.as_slice()])
+            .await
+            .map_err(|e| ServerError::DatabaseError(e.to_string()))?;
+        stored_request_from_row(&row)
+    }
+
+    async fn update_request_status(&self, intent_id: B256, status: IntentStatus) -> Result<()> {
+        let conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| ServerError::DatabaseError(e.to_string()))?;
+        let updated = conn
+            .execute(
+                UPDATE_REQUEST_STATUS,
+                &[&intent_id.as_slice(), &status.as_str()],
+            )
+            .await
+            .map_err(|e| ServerError::DatabaseError(e.to_string()))?;
+        if updated == 0 {
+            return Err(ServerError::DatabaseError(format!(
+                "request {intent_id} is not stored"
+            )));
+        }
+        Ok(())
+    }
+
+    async fn get_requests(&self, filter: &RequestFilter) -> Result<Vec<StoredRequest>> {
+        let conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| ServerError::DatabaseError(e.to_string()))?;
+        let prepared_stmt = conn
+            .prepare(GET_REQUESTS)
+            .await
+            .map_err(|e| ServerError::DatabaseError(e.to_string()))?;
+        let active_at = filter.active_at.map(to_db_timestamp).transpose()?;
+        let ended_before = filter.ended_before.map(to_db_timestamp).transpose()?;
+        // a null limit is no limit
+        let limit = filter
+            .limit
+            .map(|limit| i64::try_from(limit).unwrap_or(i64::MAX));
+        let rows = conn
+            .query(
+                &prepared_stmt,
+                &[
+                    &filter.system_id.map(|id| id.as_str()),
+                    &filter.signer.as_ref().map(|signer| signer.as_slice()),
+                    &filter.status.map(|status| status.as_str()),
+                    &active_at,
+                    &ended_before,
+                    &filter.includes_archived(),
+                    &filter.after.as_ref().map(|after| after.as_slice()),
+                    &limit,
+                ],
+            )
+            .await
+            .map_err(|e| ServerError::DatabaseError(e.to_string()))?;
+
+        rows.iter().map(stored_request_from_row).collect()
+    }
+
+    async fn get_request_metadata(
+        &self,
+        signer: Address,
+    ) -> Result<BTreeMap<B256, IntentMetadata>> {
+        let conn = self
+            .pool
+            .get()
+            .await
+            .map_err(|e| ServerError::DatabaseError(e.to_string()))?;
+        let rows = conn
+            .query(GET_REQUEST_METADATA, &[&signer.as_slice()])
+            .await
+            .map_err(|e| ServerError::DatabaseError(e.to_string()))?;
+
+        rows.iter().map(request_metadata_from_row).collect()
+    }
+}
+
+fn to_db_timestamp(timestamp: u64) -> Result<i64> {
+    i64::try_from(timestamp)
+        .map_err(|_| ServerError::DatabaseError(format!("timestamp {timestamp} out of range")))
+}
+
+fn stored_request_from_row(row: &Row) -> Result<StoredRequest> {
+    let column_error = |column: &str, e: tokio_postgres::Error| {
+        ServerError::DatabaseError(format!("Failed to get {column}: {e}"))
+    };
+    let bytes = |column: &str| {
+        row.try_get::<_, Vec<u8>>(column)
+            .map_err(|e| column_error(column, e))
+    };
+    let text = |column: &str| {
+        row.try_get::<_, String>(column)
+            .map_err(|e| column_error(column, e))
+    };
+    let timestamp = |column: &str| {
+        row.try_get::<_, i64>(column)
+            .map_err(|e| column_error(column, e))
+            .and_then(|ts| {
+                u64::try_from(ts)
+                    .map_err(|_| ServerError::DatabaseError(format!("negative {column}: {ts}")))
+            })
+    };
+    let fixed_bytes = |column: &str, length: usize| {
+        bytes(column).and_then(|bytes| {
+            if bytes.len() == length {
+                Ok(bytes)
+            } else {
+                Err(ServerError::DatabaseError(format!(
+                    "{column} has {} bytes, expected {length}",
+                    bytes.len()
+                )))
+            }
+        })
+    };
+
+    Ok(StoredRequest {
+        intent_id: B256::from_slice(&fixed_bytes("intent_id", 32)?),
+        system_id: SystemId::try_from(text("system_id")?.as_str())
+            .map_err(|e| ServerError::DatabaseError(e.to_string()))?,
+        signer: Address::from_slice(&fixed_bytes("signer", 20)?),
+        market: Address::from_slice(&fixed_bytes("market", 20)?),
+        start_auction_timestamp: timestamp("start_auction_ts")?,
+        end_auction_timestamp: timestamp("end_auction_ts")?,
+        status: IntentStatus::try_from(text("status")?.as_str())?,
+        system: bytes("system")?.into(),
+        proof_commitment: bytes("proof_commitment")?.into(),
+        signature: bytes("signature")?.into(),
+    })
+}
+
+fn request_metadata_from_row(row: &Row) -> Result<(B256, IntentMetadata)> {
+    let intent_id = row
+        .try_get::<_, Vec<u8>>("intent_id")
+        .map_err(|e| ServerError::DatabaseError(format!("Failed to get intent_id: {e}")))?;
+    let intent_id = B256::try_from(&intent_id[..])
+        .map_err(|e| ServerError::DatabaseError(format!("Failed to parse intent_id: {e}")))?;
+    let metadata = row
+        .try_get::<_, String>("metadata")
+        .map_err(|e| ServerError::DatabaseError(format!("Failed to get metadata: {e}")))?;
+    let metadata = serde_json::from_str(&metadata)
+        .map_err(|e| ServerError::DatabaseError(format!("Failed to parse metadata: {e}")))?;
+    Ok((intent_id, metadata))
 }
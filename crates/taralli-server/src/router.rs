@@ -0,0 +1,189 @@
+//! Routes of the server, registered by the server binary and listed for the OpenAPI description.
+//!
+//! Each route records the methods it's registered with as its handlers are added, so the routes
+//! the description is checked against are the ones the binary's router is built from rather than
+//! a list kept alongside it.
+
+use axum::extract::DefaultBodyLimit;
+use axum::handler::Handler;
+use axum::routing::MethodRouter;
+use axum::Router;
+use taralli_primitives::alloy::providers::RootProvider;
+use taralli_primitives::alloy::transports::http::{Client, Http};
+
+use crate::config::SubmissionLimits;
+use crate::dictionary::DEFAULT_MAX_DICTIONARY_BYTES;
+use crate::openapi::ApiRoute;
+use crate::routes::{
+    admin::{dump_state_handler, get_log_filter_handler, set_log_filter_handler, AdminState},
+    api_docs::get_api_docs_handler,
+    capabilities::{refresh_capabilities_handler, register_capabilities_handler},
+    dictionary::{get_dictionary_handler, register_dictionary_handler},
+    extension::{
+        get_extension_request_handler, get_extension_response_handler, request_extension_handler,
+        respond_extension_handler,
+    },
+    inputs::{deliver_inputs_handler, get_inputs_handler},
+    key::{deliver_key_handler, get_key_handler, get_key_request_handler, request_key_handler},
+    query::{get_active_intents_by_id_handler, get_request_metadata_handler, get_requests_handler},
+    status::get_status_handler,
+    submit::{submit_offer_handler, submit_request_handler},
+    subscribe::{sse_subscribe_handler, websocket_subscribe_handler},
+};
+use crate::state::{offer::OfferState, request::RequestState};
+
+/// Transport of the rpc provider the server binary validates intents with
+pub type ServerTransport = Http<Client>;
+/// Rpc provider the server binary validates intents with
+pub type ServerProvider = RootProvider<ServerTransport>;
+pub type ServerRequestState = RequestState<ServerTransport, ServerProvider>;
+pub type ServerOfferState = OfferState<ServerTransport, ServerProvider>;
+
+/// A path and the handlers of the methods it's served with
+pub struct ServerRoute<S> {
+    pub path: &'static str,
+    methods: Vec<&'static str>,
+    method_router: MethodRouter<S>,
+}
+
+impl<S> ServerRoute<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    pub fn new(path: &'static str) -> Self {
+        Self {
+            path,
+            methods: Vec::new(),
+            method_router: MethodRouter::new(),
+        }
+    }
+
+    pub fn get<H, T>(mut self, handler: H) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        self.methods.push("get");
+        self.method_router = self.method_router.get(handler);
+        self
+    }
+
+    pub fn post<H, T>(mut self, handler: H) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        self.methods.push("post");
+        self.method_router = self.method_router.post(handler);
+        self
+    }
+
+    pub fn put<H, T>(mut self, handler: H) -> Self
+    where
+        H: Handler<T, S>,
+        T: 'static,
+    {
+        self.methods.push("put");
+        self.method_router = self.method_router.put(handler);
+        self
+    }
+
+    /// Limit the bodies of every method of the route, replacing axum's default limit
+    pub fn with_body_limit(mut self, body_limit: DefaultBodyLimit) -> Self {
+        self.method_router = self.method_router.layer(body_limit);
+        self
+    }
+
+    /// Method and path of every handler of the route
+    pub fn api_routes(&self) -> Vec<ApiRoute> {
+        self.methods
+            .iter()
+            .copied()
+            .map(|method| ApiRoute {
+                method,
+                path: self.path,
+            })
+            .collect()
+    }
+}
+
+/// Router serving `routes`, its state still to be provided
+pub fn build<S>(routes: Vec<ServerRoute<S>>) -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    routes.into_iter().fold(Router::new(), |router, route| {
+        router.route(route.path, route.method_router)
+    })
+}
+
+/// Routes served with the request state, which also serves the server wide routes
+pub fn request_routes() -> Vec<ServerRoute<ServerRequestState>> {
+    vec![
+        // body size limits of submissions are enforced by their extractors
+        ServerRoute::new("/submit/request")
+            .post(submit_request_handler)
+            .with_body_limit(DefaultBodyLimit::disable()),
+        ServerRoute::new("/subscribe").get(websocket_subscribe_handler),
+        ServerRoute::new("/subscribe/sse").get(sse_subscribe_handler),
+        ServerRoute::new("/status").get(get_status_handler),
+        ServerRoute::new("/api-docs").get(get_api_docs_handler),
+        ServerRoute::new("/requests").get(get_requests_handler),
+        ServerRoute::new("/requests/metadata").post(get_request_metadata_handler),
+        ServerRoute::new("/capabilities").post(register_capabilities_handler),
+        ServerRoute::new("/capabilities/:provider").put(refresh_capabilities_handler),
+        ServerRoute::new("/dictionaries")
+            .post(register_dictionary_handler)
+            .with_body_limit(DefaultBodyLimit::max(DEFAULT_MAX_DICTIONARY_BYTES)),
+        ServerRoute::new("/dictionaries/:hash").get(get_dictionary_handler),
+        ServerRoute::new("/intents/:intent_id/key/request")
+            .post(request_key_handler)
+            .get(get_key_request_handler),
+        ServerRoute::new("/intents/:intent_id/key")
+            .post(deliver_key_handler)
+            .get(get_key_handler),
+        ServerRoute::new("/intents/:intent_id/extension-request")
+            .post(request_extension_handler)
+            .get(get_extension_request_handler),
+        ServerRoute::new("/intents/:intent_id/extension-response")
+            .post(respond_extension_handler)
+            .get(get_extension_response_handler),
+    ]
+}
+
+/// Routes served with the offer state, delivered inputs bounded by `max_inputs_body_bytes`
+pub fn offer_routes(max_inputs_body_bytes: usize) -> Vec<ServerRoute<ServerOfferState>> {
+    vec![
+        ServerRoute::new("/submit/offer")
+            .post(submit_offer_handler)
+            .with_body_limit(DefaultBodyLimit::disable()),
+        ServerRoute::new("/query/:system_id").get(get_active_intents_by_id_handler),
+        ServerRoute::new("/offers/:offer_id/inputs")
+            .post(deliver_inputs_handler)
+            .with_body_limit(DefaultBodyLimit::max(max_inputs_body_bytes)),
+        ServerRoute::new("/offers/:offer_id/inputs/query").post(get_inputs_handler),
+    ]
+}
+
+/// Routes only served behind a configured admin api key
+pub fn admin_routes() -> Vec<ServerRoute<AdminState>> {
+    vec![
+        ServerRoute::new("/admin/log-filter")
+            .get(get_log_filter_handler)
+            .put(set_log_filter_handler),
+        ServerRoute::new("/admin/dump-state").post(dump_state_handler),
+    ]
+}
+
+/// Method and path of every handler the server binary may register
+pub fn registered_routes() -> Vec<ApiRoute> {
+    let mut routes = Vec::new();
+    routes.extend(request_routes().iter().flat_map(ServerRoute::api_routes));
+    routes.extend(
+        offer_routes(SubmissionLimits::default().max_body_size_bytes)
+            .iter()
+            .flat_map(ServerRoute::api_routes),
+    );
+    routes.extend(admin_routes().iter().flat_map(ServerRoute::api_routes));
+    routes
+}
@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+use taralli_primitives::logging::STATE_TARGET;
+
+use crate::error::{Result, ServerError};
+use crate::log_control::LogControl;
+use crate::subscription_manager::SubscriptionManager;
+
+/// header carrying the admin api key
+pub const ADMIN_KEY_HEADER: &str = "x-admin-key";
+
+/// State of the admin routes, only served when an admin api key is configured
+#[derive(Clone)]
+pub struct AdminState {
+    api_key: Arc<str>,
+    log_control: LogControl,
+    subscription_manager: Arc<SubscriptionManager>,
+}
+
+impl AdminState {
+    pub fn new(
+        api_key: impl Into<Arc<str>>,
+        log_control: LogControl,
+        subscription_manager: Arc<SubscriptionManager>,
+    ) -> Self {
+        Self {
+            api_key: api_key.into(),
+            log_control,
+            subscription_manager,
+        }
+    }
+
+    fn authorize(&self, headers: &HeaderMap) -> Result<()> {
+        let key = headers
+            .get(ADMIN_KEY_HEADER)
+            .ok_or_else(|| ServerError::AdminUnauthorized(format!("missing {ADMIN_KEY_HEADER}")))?
+            .as_bytes();
+        // compared in constant time, the key being the only thing guarding the admin routes
+        let expected = self.api_key.as_bytes();
+        let mismatch = key.len() != expected.len()
+            || key
+                .iter()
+                .zip(expected)
+                .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+                != 0;
+        if mismatch {
+            return Err(ServerError::AdminUnauthorized(
+                "invalid admin key".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+pub struct LogFilter {
+    pub filter: String,
+}
+
+/// Report the log filter in effect
+pub async fn get_log_filter_handler(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    state.authorize(&headers)?;
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "filter": state.log_control.log_filter() })),
+    ))
+}
+
+/// Replace the log filter, e.g. `info,taralli_server::routes::submit=debug`, without restarting
+pub async fn set_log_filter_handler(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+    Json(log_filter): Json<LogFilter>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    state.authorize(&headers)?;
+    state.log_control.set_log_filter(log_filter.filter)?;
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "filter": state.log_control.log_filter() })),
+    ))
+}
+
+/// Log the subscriptions' state at info, whatever the log filter
+pub async fn dump_state_handler(
+    State(state): State<AdminState>,
+    headers: HeaderMap,
+) -> Result<StatusCode> {
+    state.authorize(&headers)?;
+    let subscription_manager = &state.subscription_manager;
+    tracing::info!(
+        target: STATE_TARGET,
+        "{} active subscriptions, {}/{} broadcast messages buffered, log filter: {}",
+        subscription_manager.active_subscriptions(),
+        subscription_manager.buffer_len(),
+        subscription_manager.capacity(),
+        state.log_control.log_filter()
+    );
+    for (system_id, subscribers) in subscription_manager.subscribers_per_system() {
+        tracing::info!(
+            target: STATE_TARGET,
+            "{subscribers} subscribers of system {system_id:?}"
+        );
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,8 @@
+use axum::{http::StatusCode, Json};
+
+use crate::openapi::openapi_spec;
+
+/// Serve the OpenAPI description of the server's routes.
+pub async fn get_api_docs_handler() -> (StatusCode, Json<serde_json::Value>) {
+    (StatusCode::OK, Json(openapi_spec()))
+}
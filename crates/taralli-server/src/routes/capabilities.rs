@@ -0,0 +1,44 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde_json::json;
+use taralli_primitives::alloy::{primitives::Address, providers::Provider, transports::Transport};
+use taralli_primitives::capabilities::SignedCapabilities;
+
+use crate::error::{Result, ServerError};
+use crate::state::request::RequestState;
+
+/// A provider advertises the systems it proves and how many jobs it takes
+pub async fn register_capabilities_handler<T: Transport + Clone, P: Provider<T> + Clone>(
+    State(state): State<RequestState<T, P>>,
+    Json(signed): Json<SignedCapabilities>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    let provider = signed.capabilities.provider;
+    state.capabilities().upsert(signed)?;
+    tracing::info!("capabilities of provider {} registered", provider);
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({ "message": "capabilities registered" })),
+    ))
+}
+
+/// A provider refreshes its capabilities, e.g. as jobs start and finish, keeping them alive
+pub async fn refresh_capabilities_handler<T: Transport + Clone, P: Provider<T> + Clone>(
+    State(state): State<RequestState<T, P>>,
+    Path(provider): Path<Address>,
+    Json(signed): Json<SignedCapabilities>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    if signed.capabilities.provider != provider {
+        return Err(ServerError::InvalidCapabilities(format!(
+            "capabilities of {} sent for provider {provider}",
+            signed.capabilities.provider
+        )));
+    }
+    state.capabilities().upsert(signed)?;
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "message": "capabilities refreshed" })),
+    ))
+}
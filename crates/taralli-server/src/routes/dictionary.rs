@@ -0,0 +1,47 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+use taralli_primitives::alloy::{primitives::B256, providers::Provider, transports::Transport};
+
+use crate::error::{Result, ServerError};
+use crate::state::request::RequestState;
+
+/// A requester registers the raw bytes of a compression dictionary, answered with its hash
+pub async fn register_dictionary_handler<T: Transport + Clone, P: Provider<T> + Clone>(
+    State(state): State<RequestState<T, P>>,
+    dictionary: Bytes,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    if dictionary.is_empty() {
+        return Err(ServerError::InvalidSubmission(
+            "empty compression dictionary".to_string(),
+        ));
+    }
+    let max = state.dictionaries().max_dictionary_bytes();
+    if dictionary.len() > max {
+        return Err(ServerError::PayloadTooLarge(max));
+    }
+    let hash = state.dictionaries().insert(dictionary)?;
+    tracing::info!("compression dictionary {} registered", hash);
+    Ok((StatusCode::CREATED, Json(json!({ "hash": hash }))))
+}
+
+/// Providers fetch the dictionary a broadcast system was compressed against
+pub async fn get_dictionary_handler<T: Transport + Clone, P: Provider<T> + Clone>(
+    State(state): State<RequestState<T, P>>,
+    Path(hash): Path<B256>,
+) -> Result<impl IntoResponse> {
+    let dictionary = state
+        .dictionaries()
+        .get(&hash)
+        .ok_or(ServerError::UnknownDictionary(hash))?;
+    Ok((
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "application/octet-stream")],
+        dictionary,
+    ))
+}
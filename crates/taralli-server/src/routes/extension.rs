@@ -0,0 +1,124 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde_json::json;
+use taralli_primitives::alloy::{
+    primitives::{Address, B256},
+    providers::Provider,
+    transports::Transport,
+};
+use taralli_primitives::extension::{ExtensionRequest, ExtensionResponse};
+
+use crate::error::{Result, ServerError};
+use crate::state::request::RequestState;
+
+use super::party::{ActiveRequest, Party};
+
+/// Check `signer` is the given party of the active request `intent_id`, returning the request
+async fn authorize<T: Transport + Clone, P: Provider<T> + Clone>(
+    state: &RequestState<T, P>,
+    intent_id: B256,
+    signer: Address,
+    party: Party,
+) -> Result<ActiveRequest> {
+    let active_request = ActiveRequest::fetch(state, intent_id)
+        .await
+        .map_err(ServerError::ExtensionError)?;
+    active_request
+        .authorize(intent_id, signer, party)
+        .map_err(ServerError::ExtensionUnauthorized)?;
+    Ok(active_request)
+}
+
+/// The provider that won a request asks its requester for a later resolution deadline
+pub async fn request_extension_handler<T: Transport + Clone, P: Provider<T> + Clone>(
+    State(state): State<RequestState<T, P>>,
+    Path(intent_id): Path<B256>,
+    Json(extension_request): Json<ExtensionRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    let signer = extension_request
+        .signer(intent_id)
+        .map_err(|e| ServerError::ExtensionUnauthorized(e.to_string()))?;
+    let active_request = authorize(&state, intent_id, signer, Party::Provider).await?;
+    let current_deadline = u64::try_from(active_request.resolution_deadline).unwrap_or(u64::MAX);
+    extension_request
+        .validate(current_deadline)
+        .map_err(|e| ServerError::InvalidExtension(e.to_string()))?;
+    tracing::info!(
+        "extension of request {} to {} requested by {}",
+        intent_id,
+        extension_request.new_deadline,
+        signer
+    );
+    state
+        .extensions()
+        .request_extension(intent_id, extension_request);
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({ "message": "extension request relayed to the requester" })),
+    ))
+}
+
+/// The requester polls the pending extension request of its request
+pub async fn get_extension_request_handler<T: Transport + Clone, P: Provider<T> + Clone>(
+    State(state): State<RequestState<T, P>>,
+    Path(intent_id): Path<B256>,
+) -> Result<(StatusCode, Json<ExtensionRequest>)> {
+    let extension_request = state
+        .extensions()
+        .extension_request(&intent_id)
+        .ok_or_else(|| {
+            ServerError::ExtensionNotAvailable(format!(
+                "no extension requested for request {intent_id}"
+            ))
+        })?;
+    Ok((StatusCode::OK, Json(extension_request)))
+}
+
+/// The requester approves or declines the pending extension request
+pub async fn respond_extension_handler<T: Transport + Clone, P: Provider<T> + Clone>(
+    State(state): State<RequestState<T, P>>,
+    Path(intent_id): Path<B256>,
+    Json(extension_response): Json<ExtensionResponse>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    let signer = extension_response
+        .signer(intent_id)
+        .map_err(|e| ServerError::ExtensionUnauthorized(e.to_string()))?;
+    authorize(&state, intent_id, signer, Party::Requester).await?;
+    let approved = extension_response.approved;
+    let new_deadline = extension_response.new_deadline;
+    if !state.extensions().respond(intent_id, extension_response) {
+        return Err(ServerError::ExtensionNotAvailable(format!(
+            "no extension of request {intent_id} to {new_deadline} requested"
+        )));
+    }
+    tracing::info!(
+        "extension of request {} to {} {}",
+        intent_id,
+        new_deadline,
+        if approved { "approved" } else { "declined" }
+    );
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "message": "extension response relayed to the provider" })),
+    ))
+}
+
+/// The provider polls the requester's answer to its extension request, which ends the negotiation
+/// once served
+pub async fn get_extension_response_handler<T: Transport + Clone, P: Provider<T> + Clone>(
+    State(state): State<RequestState<T, P>>,
+    Path(intent_id): Path<B256>,
+) -> Result<(StatusCode, Json<ExtensionResponse>)> {
+    let extension_response = state
+        .extensions()
+        .take_response(&intent_id)
+        .ok_or_else(|| {
+            ServerError::ExtensionNotAvailable(format!(
+                "no extension response for request {intent_id}"
+            ))
+        })?;
+    Ok((StatusCode::OK, Json(extension_response)))
+}
@@ -0,0 +1,89 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde_json::json;
+use taralli_primitives::alloy::{primitives::B256, providers::Provider, transports::Transport};
+use taralli_primitives::commitment::{check_inputs_commitment, CommitmentHasher};
+use taralli_primitives::offer_inputs::{InputsQuery, OfferInputs};
+
+use crate::clock::unix_timestamp;
+use crate::error::{Result, ServerError};
+use crate::offer_inputs::DeliveredInputs;
+use crate::state::offer::OfferInputsState;
+
+use super::party::{ActiveOffer, Party};
+
+/// The requester that won an offer delivers the inputs its provider proves over
+pub async fn deliver_inputs_handler<T: Transport + Clone, P: Provider<T> + Clone>(
+    State(state): State<OfferInputsState<T, P>>,
+    Path(intent_id): Path<B256>,
+    Json(offer_inputs): Json<OfferInputs>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    let signer = offer_inputs
+        .signer(intent_id)
+        .map_err(|e| ServerError::OfferInputsUnauthorized(e.to_string()))?;
+    let active_offer = ActiveOffer::fetch(&state.base, intent_id)
+        .await
+        .map_err(ServerError::OfferInputsError)?;
+    active_offer
+        .authorize(intent_id, signer, Party::Requester)
+        .map_err(ServerError::OfferInputsUnauthorized)?;
+
+    // only inputs the provider can resolve the offer with are relayed
+    let inputs = offer_inputs
+        .decompress(&state.submission_limits().decompression_budget())
+        .await?;
+    let hasher = CommitmentHasher::from_offer_extra_data(&active_offer.verifier_details)
+        .map_err(|e| ServerError::OfferInputsMismatch(e.to_string()))?;
+    check_inputs_commitment(hasher, active_offer.inputs_commitment, &inputs)
+        .map_err(|e| ServerError::OfferInputsMismatch(e.to_string()))?;
+
+    tracing::info!(
+        "{} bytes of inputs of offer {} delivered by {}",
+        inputs.len(),
+        intent_id,
+        signer
+    );
+    // kept for the provider until the offer can no longer be resolved
+    state.offer_inputs().deliver(
+        intent_id,
+        DeliveredInputs {
+            inputs: offer_inputs,
+            provider: active_offer.provider,
+            expires_at: u64::try_from(active_offer.resolution_deadline).unwrap_or(u64::MAX),
+        },
+    )?;
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "message": "inputs delivered to the provider" })),
+    ))
+}
+
+/// The provider polls the inputs of the offer it is proving, through a query it signed. The
+/// provider is the one the market recorded when the inputs were delivered.
+pub async fn get_inputs_handler<T: Transport + Clone, P: Provider<T> + Clone>(
+    State(state): State<OfferInputsState<T, P>>,
+    Path(intent_id): Path<B256>,
+    Json(inputs_query): Json<InputsQuery>,
+) -> Result<(StatusCode, Json<OfferInputs>)> {
+    let signer = inputs_query
+        .signer(intent_id)
+        .map_err(|e| ServerError::OfferInputsUnauthorized(e.to_string()))?;
+    if !inputs_query.is_fresh(unix_timestamp()) {
+        return Err(ServerError::OfferInputsUnauthorized(format!(
+            "query issued at {} is stale",
+            inputs_query.issued_at
+        )));
+    }
+    let delivered = state.offer_inputs().inputs(&intent_id).ok_or_else(|| {
+        ServerError::OfferInputsNotAvailable(format!("no inputs delivered for offer {intent_id}"))
+    })?;
+    if signer != delivered.provider {
+        return Err(ServerError::OfferInputsUnauthorized(format!(
+            "{signer} is not the provider of offer {intent_id}"
+        )));
+    }
+    Ok((StatusCode::OK, Json(delivered.inputs)))
+}
@@ -0,0 +1,99 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use serde_json::json;
+use taralli_primitives::alloy::{
+    primitives::{Address, B256},
+    providers::Provider,
+    transports::Transport,
+};
+use taralli_primitives::encryption::{KeyDelivery, KeyRequest};
+
+use crate::error::{Result, ServerError};
+use crate::state::request::RequestState;
+
+use super::party::{ActiveRequest, Party};
+
+/// Check `signer` is the given party of the active request `intent_id`
+async fn authorize<T: Transport + Clone, P: Provider<T> + Clone>(
+    state: &RequestState<T, P>,
+    intent_id: B256,
+    signer: Address,
+    party: Party,
+) -> Result<()> {
+    ActiveRequest::fetch(state, intent_id)
+        .await
+        .map_err(ServerError::KeyExchangeError)?
+        .authorize(intent_id, signer, party)
+        .map_err(ServerError::KeyExchangeUnauthorized)
+}
+
+/// The provider that won an encrypted request asks for its content key
+pub async fn request_key_handler<T: Transport + Clone, P: Provider<T> + Clone>(
+    State(state): State<RequestState<T, P>>,
+    Path(intent_id): Path<B256>,
+    Json(key_request): Json<KeyRequest>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    let signer = key_request
+        .signer(intent_id)
+        .map_err(|e| ServerError::KeyExchangeUnauthorized(e.to_string()))?;
+    authorize(&state, intent_id, signer, Party::Provider).await?;
+    tracing::info!("key of request {} requested by {}", intent_id, signer);
+    state.key_exchange().request_key(intent_id, key_request);
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({ "message": "key request relayed to the requester" })),
+    ))
+}
+
+/// The requester polls the pending key request of its encrypted request
+pub async fn get_key_request_handler<T: Transport + Clone, P: Provider<T> + Clone>(
+    State(state): State<RequestState<T, P>>,
+    Path(intent_id): Path<B256>,
+) -> Result<(StatusCode, Json<KeyRequest>)> {
+    let key_request = state
+        .key_exchange()
+        .key_request(&intent_id)
+        .ok_or_else(|| {
+            ServerError::KeyNotAvailable(format!("no key requested for request {intent_id}"))
+        })?;
+    Ok((StatusCode::OK, Json(key_request)))
+}
+
+/// The requester answers the pending key request with the wrapped content key
+pub async fn deliver_key_handler<T: Transport + Clone, P: Provider<T> + Clone>(
+    State(state): State<RequestState<T, P>>,
+    Path(intent_id): Path<B256>,
+    Json(key_delivery): Json<KeyDelivery>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    let signer = key_delivery
+        .signer(intent_id)
+        .map_err(|e| ServerError::KeyExchangeUnauthorized(e.to_string()))?;
+    authorize(&state, intent_id, signer, Party::Requester).await?;
+    if !state.key_exchange().deliver_key(intent_id, key_delivery) {
+        return Err(ServerError::KeyNotAvailable(format!(
+            "no key requested for request {intent_id}"
+        )));
+    }
+    tracing::info!("key of request {} delivered", intent_id);
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "message": "key delivered to the provider" })),
+    ))
+}
+
+/// The provider polls the content key wrapped for it
+pub async fn get_key_handler<T: Transport + Clone, P: Provider<T> + Clone>(
+    State(state): State<RequestState<T, P>>,
+    Path(intent_id): Path<B256>,
+) -> Result<(StatusCode, Json<KeyDelivery>)> {
+    let key_delivery = state
+        .key_exchange()
+        .key_delivery(&intent_id)
+        .ok_or_else(|| {
+            ServerError::KeyNotAvailable(format!("no key delivered for request {intent_id}"))
+        })?;
+    Ok((StatusCode::OK, Json(key_delivery)))
+}
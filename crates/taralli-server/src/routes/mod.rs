@@ -1,3 +1,12 @@
+pub mod admin;
+pub mod api_docs;
+pub mod capabilities;
+pub mod dictionary;
+pub mod extension;
+pub mod inputs;
+pub mod key;
+pub(crate) mod party;
 pub mod query;
+pub mod status;
 pub mod submit;
 pub mod subscribe;
@@ -0,0 +1,113 @@
+//! Parties of an active request or offer as recorded by the market, authorizing the messages
+//! they relay through the server to each other
+
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta::UniversalBombettaInstance;
+use taralli_primitives::abi::universal_porchetta::UniversalPorchetta::UniversalPorchettaInstance;
+use taralli_primitives::alloy::{
+    primitives::{Address, Bytes, B256, U256},
+    providers::Provider,
+    transports::Transport,
+};
+
+use crate::state::request::RequestState;
+use crate::state::BaseState;
+
+/// Party of an active request or offer recorded by the market
+pub(crate) enum Party {
+    Requester,
+    Provider,
+}
+
+/// Active request as recorded by the market
+pub(crate) struct ActiveRequest {
+    pub requester: Address,
+    pub provider: Address,
+    pub resolution_deadline: U256,
+}
+
+impl ActiveRequest {
+    /// Read the active request `intent_id` from the market, failing with the rpc error
+    pub async fn fetch<T: Transport + Clone, P: Provider<T> + Clone>(
+        state: &RequestState<T, P>,
+        intent_id: B256,
+    ) -> Result<Self, String> {
+        let market_contract = UniversalBombettaInstance::new(
+            state.universal_bombetta_address(),
+            state.rpc_provider(),
+        );
+        let active_request = market_contract
+            .activeProofRequestData(intent_id)
+            .call()
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            requester: active_request.requester,
+            provider: active_request.provider,
+            resolution_deadline: active_request.resolutionDeadline,
+        })
+    }
+
+    /// Check `signer` is the given party of the request, failing with the reason it isn't
+    pub fn authorize(&self, intent_id: B256, signer: Address, party: Party) -> Result<(), String> {
+        if self.requester == Address::ZERO {
+            return Err(format!("request {intent_id} has no winning bid"));
+        }
+        let (expected, role) = match party {
+            Party::Requester => (self.requester, "requester"),
+            Party::Provider => (self.provider, "provider"),
+        };
+        if signer != expected {
+            return Err(format!("{signer} is not the {role} of request {intent_id}"));
+        }
+        Ok(())
+    }
+}
+
+/// Active offer as recorded by the market
+pub(crate) struct ActiveOffer {
+    pub requester: Address,
+    pub provider: Address,
+    pub inputs_commitment: B256,
+    pub verifier_details: Bytes,
+    pub resolution_deadline: U256,
+}
+
+impl ActiveOffer {
+    /// Read the active offer `intent_id` from the market, failing with the rpc error
+    pub async fn fetch<T: Transport + Clone, P: Provider<T> + Clone>(
+        state: &BaseState<T, P>,
+        intent_id: B256,
+    ) -> Result<Self, String> {
+        let market_contract = UniversalPorchettaInstance::new(
+            state.universal_porchetta_address(),
+            state.rpc_provider(),
+        );
+        let active_offer = market_contract
+            .activeProofOfferData(intent_id)
+            .call()
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            requester: active_offer.requester,
+            provider: active_offer.provider,
+            inputs_commitment: active_offer.inputsCommitment,
+            verifier_details: active_offer.verifierDetails,
+            resolution_deadline: active_offer.resolutionDeadline,
+        })
+    }
+
+    /// Check `signer` is the given party of the offer, failing with the reason it isn't
+    pub fn authorize(&self, intent_id: B256, signer: Address, party: Party) -> Result<(), String> {
+        if self.requester == Address::ZERO {
+            return Err(format!("offer {intent_id} has no winning bid"));
+        }
+        let (expected, role) = match party {
+            Party::Requester => (self.requester, "requester"),
+            Party::Provider => (self.provider, "provider"),
+        };
+        if signer != expected {
+            return Err(format!("{signer} is not the {role} of offer {intent_id}"));
+        }
+        Ok(())
+    }
+}
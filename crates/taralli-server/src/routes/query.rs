@@ -1,16 +1,45 @@
 use crate::{
+    clock::unix_timestamp,
     error::{Result, ServerError},
-    state::offer::OfferState,
+    intent_store::{IntentStatus, RequestFilter},
+    state::{offer::OfferState, request::RequestState},
 };
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
     Json,
 };
+use serde::Deserialize;
 use serde_json::json;
-use taralli_primitives::alloy::{providers::Provider, transports::Transport};
+use taralli_primitives::alloy::{
+    primitives::{Address, B256},
+    providers::Provider,
+    transports::Transport,
+};
+use taralli_primitives::request_metadata::MetadataQuery;
 use taralli_primitives::systems::SystemId;
 
+/// requests served per page of the stored requests route when the query sets no limit
+pub const DEFAULT_REQUESTS_LIMIT: usize = 100;
+/// most requests served per page of the stored requests route
+pub const MAX_REQUESTS_LIMIT: usize = 1000;
+
+/// query string filters of the stored requests route
+#[derive(Debug, Default, Deserialize)]
+pub struct RequestQuery {
+    pub system_id: Option<String>,
+    pub signer: Option<Address>,
+    pub status: Option<IntentStatus>,
+    pub active_at: Option<u64>,
+    /// serve archived requests along with the others
+    #[serde(default)]
+    pub include_archived: bool,
+    /// requests per page, `DEFAULT_REQUESTS_LIMIT` when unset and capped at `MAX_REQUESTS_LIMIT`
+    pub limit: Option<usize>,
+    /// cursor of the page, the `next_cursor` answered with the previous one
+    pub after: Option<B256>,
+}
+
 /// query compute intents of a given system id from the server's intent database
 pub async fn get_active_intents_by_id_handler<T: Transport + Clone, P: Provider<T> + Clone>(
     State(app_state): State<OfferState<T, P>>,
@@ -36,3 +65,87 @@ pub async fn get_active_intents_by_id_handler<T: Transport + Clone, P: Provider<
 
     Ok((StatusCode::OK, Json(json!({ "intents": intents }))))
 }
+
+/// query submitted compute requests persisted in the server's intent store, a page at a time.
+/// A full page is answered with the cursor of the next one.
+pub async fn get_requests_handler<T: Transport + Clone, P: Provider<T> + Clone>(
+    State(app_state): State<RequestState<T, P>>,
+    Query(query): Query<RequestQuery>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    tracing::info!("Stored requests queried: {:?}", query);
+    let intent_store = app_state.intent_store().ok_or_else(|| {
+        ServerError::QueryError("requests are not persisted by this server".to_string())
+    })?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_REQUESTS_LIMIT)
+        .clamp(1, MAX_REQUESTS_LIMIT);
+    let filter = RequestFilter {
+        system_id: query
+            .system_id
+            .as_deref()
+            .map(SystemId::try_from)
+            .transpose()
+            .map_err(|e| ServerError::QueryError(e.to_string()))?,
+        signer: query.signer,
+        status: query.status,
+        active_at: query.active_at,
+        include_archived: query.include_archived,
+        after: query.after,
+        limit: Some(limit),
+        ..Default::default()
+    };
+
+    let requests = intent_store.get_requests(&filter).await.map_err(|e| {
+        tracing::error!("Database error when querying requests: {}", e);
+        ServerError::DatabaseError(format!("Failed to query requests: {e}"))
+    })?;
+
+    let next_cursor = requests
+        .last()
+        .filter(|_| requests.len() == limit)
+        .map(|request| request.intent_id);
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({ "requests": requests, "next_cursor": next_cursor })),
+    ))
+}
+
+/// the requester queries the metadata it submitted along with its requests, by intent id. The
+/// metadata is served to the address that signed the requests only, never by `/requests`.
+pub async fn get_request_metadata_handler<T: Transport + Clone, P: Provider<T> + Clone>(
+    State(app_state): State<RequestState<T, P>>,
+    Json(metadata_query): Json<MetadataQuery>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    let signer = metadata_query
+        .signer()
+        .map_err(|e| ServerError::RequestMetadataUnauthorized(e.to_string()))?;
+    let chain_id = app_state.validation_configs().request.base.chain_id;
+    if metadata_query.chain_id != chain_id {
+        return Err(ServerError::RequestMetadataUnauthorized(format!(
+            "query signed for chain {} sent to a server of chain {chain_id}",
+            metadata_query.chain_id
+        )));
+    }
+    if !metadata_query.is_fresh(unix_timestamp()) {
+        return Err(ServerError::RequestMetadataUnauthorized(format!(
+            "query issued at {} is no longer accepted",
+            metadata_query.issued_at
+        )));
+    }
+    let intent_store = app_state.intent_store().ok_or_else(|| {
+        ServerError::QueryError("requests are not persisted by this server".to_string())
+    })?;
+
+    tracing::info!("Request metadata queried by {}", signer);
+    let metadata = intent_store
+        .get_request_metadata(signer)
+        .await
+        .map_err(|e| {
+            tracing::error!("Database error when querying request metadata: {}", e);
+            ServerError::DatabaseError(format!("Failed to query request metadata: {e}"))
+        })?;
+
+    Ok((StatusCode::OK, Json(json!({ "metadata": metadata }))))
+}
@@ -0,0 +1,94 @@
+use axum::{extract::State, http::StatusCode, Json};
+use serde_json::json;
+use taralli_primitives::alloy::{providers::Provider, transports::Transport};
+use taralli_primitives::capabilities::CapabilitySummary;
+use taralli_primitives::validation::CommonValidationConfig;
+
+use crate::error::Result;
+use crate::state::request::RequestState;
+
+/// Report the server's version, supported systems with their live subscriber counts and the
+/// capabilities advertised for them and the validation bounds in effect for their intents, the
+/// validation bounds and per system resource limits intents are checked against and the market
+/// addresses, along with the intents garbage collected so far and the requests not sent to
+/// saturated providers.
+pub async fn get_status_handler<T: Transport + Clone, P: Provider<T> + Clone>(
+    State(app_state): State<RequestState<T, P>>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    let subscription_manager = app_state.subscription_manager();
+    let validation_configs = app_state.validation_configs();
+    let capabilities = app_state.capabilities().live();
+
+    let systems: Vec<serde_json::Value> = subscription_manager
+        .subscribers_per_system()
+        .into_iter()
+        .map(|(system_id, subscribers)| {
+            let request = validation_configs.request.for_system(system_id);
+            let offer = validation_configs.offer.for_system(system_id);
+            json!({
+                "system_id": system_id,
+                "bit": system_id.as_bit(),
+                "subscribers": subscribers,
+                "capabilities": CapabilitySummary::aggregate(system_id, &capabilities),
+                // bounds in effect for the system's intents, its overrides applied
+                "validation": {
+                    "request": {
+                        "minimum_proving_time": request.base.minimum_proving_time,
+                        "maximum_start_delay": request.base.maximum_start_delay,
+                        "minimum_auction_length": request.base.minimum_auction_length,
+                        "maximum_auction_length": request.base.maximum_auction_length,
+                        "maximum_proving_time": request.base.maximum_proving_time,
+                        "maximum_allowed_stake": request.maximum_allowed_stake,
+                    },
+                    "offer": {
+                        "minimum_proving_time": offer.base.minimum_proving_time,
+                        "maximum_start_delay": offer.base.maximum_start_delay,
+                        "minimum_auction_length": offer.base.minimum_auction_length,
+                        "maximum_auction_length": offer.base.maximum_auction_length,
+                        "maximum_proving_time": offer.base.maximum_proving_time,
+                    },
+                },
+            })
+        })
+        .collect();
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "systems": systems,
+            "active_subscriptions": subscription_manager.active_subscriptions(),
+            "skipped_saturated_deliveries": subscription_manager.skipped_deliveries(),
+            "gc": app_state.gc().map(|gc| gc.metrics()),
+            "validation": {
+                "request": {
+                    "minimum_proving_time": validation_configs.request.base.minimum_proving_time,
+                    "maximum_start_delay": validation_configs.request.base.maximum_start_delay,
+                    "minimum_auction_length": validation_configs.request.base.minimum_auction_length,
+                    "maximum_auction_length": validation_configs.request.base.maximum_auction_length,
+                    "maximum_proving_time": validation_configs.request.base.maximum_proving_time,
+                    "maximum_allowed_stake": validation_configs.request.maximum_allowed_stake,
+                    "supported_systems": validation_configs.request.base.supported_systems,
+                    "chain_id": validation_configs.request.base.chain_id,
+                    "resource_limits": validation_configs.request.base.resource_limits,
+                },
+                "offer": {
+                    "minimum_proving_time": validation_configs.offer.base.minimum_proving_time,
+                    "maximum_start_delay": validation_configs.offer.base.maximum_start_delay,
+                    "minimum_auction_length": validation_configs.offer.base.minimum_auction_length,
+                    "maximum_auction_length": validation_configs.offer.base.maximum_auction_length,
+                    "maximum_proving_time": validation_configs.offer.base.maximum_proving_time,
+                    "maximum_allowed_reward": validation_configs.offer.maximum_allowed_reward,
+                    "minimum_allowed_stake": validation_configs.offer.minimum_allowed_stake,
+                    "supported_systems": validation_configs.offer.base.supported_systems,
+                    "chain_id": validation_configs.offer.base.chain_id,
+                    "resource_limits": validation_configs.offer.base.resource_limits,
+                },
+            },
+            "markets": {
+                "universal_bombetta": app_state.universal_bombetta_address(),
+                "universal_porchetta": app_state.universal_porchetta_address(),
+            },
+        })),
+    ))
+}
@@ -1,52 +1,210 @@
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
 use serde_json::json;
-use taralli_primitives::alloy::{providers::Provider, transports::Transport};
-use taralli_primitives::compression_utils::intents::{
-    ComputeOfferCompressed, ComputeRequestCompressed,
+use taralli_primitives::alloy::{primitives::B256, providers::Provider, transports::Transport};
+use taralli_primitives::compression_utils::{
+    dictionary::{decompress_system_with_dictionary_bounded, dictionary_reference},
+    frames::deflate_request_frame,
+    intents::{
+        CompressedIntent, ComputeOfferCompressed, ComputeRequestCompressed, PartialComputeOffer,
+        PartialComputeRequest,
+    },
 };
+use taralli_primitives::encryption::SystemPayload;
+use taralli_primitives::intents::envelope::{unix_time_ms, BroadcastMeta, IntentMetadata};
+use taralli_primitives::intents::offer::compute_offer_id;
+use taralli_primitives::intents::request::compute_request_id;
+use taralli_primitives::logging::intent_span;
+use tracing::Instrument;
 
 use crate::error::{Result, ServerError};
 use crate::extracted_intents::{ExtractedOffer, ExtractedRequest};
+use crate::idempotency::Reservation;
 use crate::state::offer::OfferState;
 use crate::state::request::RequestState;
+use crate::state::BaseState;
 use crate::subscription_manager::BroadcastedMessage;
 use crate::validation::{validate_partial_offer, validate_partial_request};
 
+/// Decompress a submitted system within the submission budget, with the registered dictionary it
+/// references if any
+async fn decompress_system<T: Transport + Clone, P: Provider<T> + Clone>(
+    state: &BaseState<T, P>,
+    system_bytes: &[u8],
+) -> Result<SystemPayload> {
+    let dictionary = match dictionary_reference(system_bytes) {
+        Some(hash) => Some(
+            state
+                .dictionaries()
+                .get(&hash)
+                .ok_or(ServerError::UnknownDictionary(hash))?,
+        ),
+        None => None,
+    };
+    Ok(decompress_system_with_dictionary_bounded(
+        system_bytes,
+        dictionary.as_deref(),
+        &state.submission_limits().decompression_budget(),
+    )
+    .await?)
+}
+
+/// Keep the dictionary a validated system references, if any, until the auction of its intent
+/// ends, so providers can still fetch it
+fn pin_dictionary<T: Transport + Clone, P: Provider<T> + Clone>(
+    state: &BaseState<T, P>,
+    system_bytes: &[u8],
+    end_auction_timestamp: u64,
+) {
+    if let Some(hash) = dictionary_reference(system_bytes) {
+        state.dictionaries().pin(hash, end_auction_timestamp);
+    }
+}
+
 /// submit `ComputeRequest`
 pub async fn submit_request_handler<T: Transport + Clone, P: Provider<T> + Clone>(
     State(state): State<RequestState<T, P>>,
+    headers: HeaderMap,
     ExtractedRequest {
         partial_request,
         system_bytes,
+        metadata,
     }: ExtractedRequest,
 ) -> Result<impl IntoResponse> {
+    let request_id = compute_request_id(&partial_request.proof_request, &partial_request.signature);
+    let span = intent_span(
+        request_id,
+        partial_request.system_id,
+        partial_request.proof_request.market,
+    );
+    submit_request(
+        state,
+        headers,
+        request_id,
+        partial_request,
+        system_bytes,
+        metadata,
+    )
+    .instrument(span)
+    .await
+}
+
+async fn submit_request<T: Transport + Clone, P: Provider<T> + Clone>(
+    state: RequestState<T, P>,
+    headers: HeaderMap,
+    request_id: B256,
+    partial_request: PartialComputeRequest,
+    system_bytes: Vec<u8>,
+    metadata: IntentMetadata,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    // stamped on the broadcast so providers can tell how long delivering it took
+    let received_at_ms = unix_time_ms();
     tracing::info!("ComputeRequest submitted: {:?}", partial_request);
-    validate_partial_request(&partial_request, &state).await?;
+    // a retry of a submission that already went through gets the original response
+    let idempotency = match state.idempotency_cache().reserve(&headers, request_id)? {
+        Reservation::Proceed(guard) => guard,
+        Reservation::Replay(status, body) => {
+            tracing::info!("replaying response to retried compute request submission");
+            return Ok((status, Json(body)));
+        }
+    };
+    // make sure the system decompresses within budget before spending time validating
+    let system = decompress_system(&state, &system_bytes).await?;
+    tokio::time::timeout(
+        state.validation_timeout_seconds(),
+        validate_partial_request(&partial_request, &system, &state),
+    )
+    .await
+    .map_err(|_| ServerError::ValidationTimeout(state.validation_timeout_seconds().as_secs()))??;
+    // providers declare their capabilities themselves, so a request none of them would take is
+    // only flagged to the requester, never turned down
+    let capability_warning = state.capabilities().unaccepted_reason(
+        partial_request.system_id,
+        partial_request.proof_request.provingTime,
+        partial_request.proof_request.maxRewardAmount,
+        state
+            .subscription_manager()
+            .subscribers(partial_request.system_id),
+    );
+    if let Some(warning) = &capability_warning {
+        tracing::warn!("compute request may go unanswered: {warning}");
+    }
+    pin_dictionary(
+        &state,
+        &system_bytes,
+        partial_request.proof_request.endAuctionTimestamp,
+    );
     tracing::info!("compute request validated, broadcasting");
 
     let request_compressed =
         ComputeRequestCompressed::from((partial_request.clone(), system_bytes));
 
-    let request_serialized = bincode::serialize(&request_compressed).map_err(|_e| {
-        tracing::info!("Couldn't serialize partial request: {:?}", partial_request);
-        ServerError::SerializationError(
-            "Couldn't serialize request before broadcasting".to_string(),
-        )
-    })?;
+    let intent = CompressedIntent::Request((&request_compressed).into());
 
-    let message_to_broadcast = BroadcastedMessage {
-        content: request_serialized,
-        subscribed_to: partial_request.system_id.as_bit(),
+    // providers saturated for the system don't get the request, or get it flagged
+    let saturated = state.capabilities().saturated(partial_request.system_id);
+    let broadcast = state
+        .subscription_manager()
+        .broadcast_sequenced(|sequence| {
+            let meta = BroadcastMeta {
+                sequence,
+                received_at_ms,
+            };
+            // encoded once here rather than for each subscriber that negotiated frame compression
+            let deflated = if state.subscription_manager().frame_compression() {
+                Some(
+                    deflate_request_frame(&request_compressed, meta)
+                        .map_err(|e| {
+                            ServerError::SerializationError(format!(
+                                "Couldn't deflate request: {e}"
+                            ))
+                        })?
+                        .into(),
+                )
+            } else {
+                None
+            };
+            BroadcastedMessage::intent(&intent, deflated, meta)
+        });
+    let broadcast = match broadcast {
+        Err(e @ ServerError::SerializationError(_)) => return Err(e),
+        broadcast => broadcast,
     };
+    if broadcast.is_ok() {
+        // subscribers are told once the auction ends
+        state.expiry_queue().schedule(
+            request_id,
+            request_compressed.system_id,
+            request_compressed.proof_request.endAuctionTimestamp,
+        );
+    }
+    // the metadata is kept for the requester only, it isn't part of the broadcast
+    state.persist_request(request_compressed, metadata.clone(), broadcast.is_ok());
 
-    match state.subscription_manager().broadcast(message_to_broadcast) {
-        Ok(recv_count) => Ok((
-            StatusCode::OK,
-            Json(json!({
+    match broadcast {
+        Ok(recv_count) => {
+            let mut body = json!({
                 "message": "compute request broadcast to providers",
-                "broadcast_receivers": recv_count
-            })),
-        )),
+                "broadcast_receivers": state.subscription_manager().eligible_receivers(
+                    recv_count,
+                    partial_request.system_id,
+                    &saturated,
+                )
+            });
+            // echoed so the requester can match the response to its bookkeeping
+            if !metadata.is_empty() {
+                body["metadata"] = json!(metadata);
+            }
+            if let Some(warning) = capability_warning {
+                body["warning"] = json!(warning);
+            }
+            idempotency.complete(StatusCode::OK, &body);
+            Ok((StatusCode::OK, Json(body)))
+        }
         Err(_) => Err(ServerError::NoProvidersAvailable()),
     }
 }
@@ -54,22 +212,89 @@ pub async fn submit_request_handler<T: Transport + Clone, P: Provider<T> + Clone
 /// submit `ComputeOffer`
 pub async fn submit_offer_handler<T: Transport + Clone, P: Provider<T> + Clone>(
     State(state): State<OfferState<T, P>>,
+    headers: HeaderMap,
     ExtractedOffer {
         partial_offer,
         system_bytes,
     }: ExtractedOffer,
 ) -> Result<impl IntoResponse> {
+    let offer_id = compute_offer_id(&partial_offer.proof_offer, &partial_offer.signature);
+    let span = intent_span(
+        offer_id,
+        partial_offer.system_id,
+        partial_offer.proof_offer.market,
+    );
+    submit_offer(state, headers, offer_id, partial_offer, system_bytes)
+        .instrument(span)
+        .await
+}
+
+async fn submit_offer<T: Transport + Clone, P: Provider<T> + Clone>(
+    state: OfferState<T, P>,
+    headers: HeaderMap,
+    offer_id: B256,
+    partial_offer: PartialComputeOffer,
+    system_bytes: Vec<u8>,
+) -> Result<(StatusCode, Json<serde_json::Value>)> {
+    // stamped on the broadcast like the requests' receive time
+    let received_at_ms = unix_time_ms();
     tracing::info!("ComputeOffer submitted: {:?}", partial_offer);
-    validate_partial_offer(&partial_offer, &state).await?;
+    // a retry of a submission that already went through gets the original response
+    let idempotency = match state.idempotency_cache().reserve(&headers, offer_id)? {
+        Reservation::Proceed(guard) => guard,
+        Reservation::Replay(status, body) => {
+            tracing::info!("replaying response to retried compute offer submission");
+            return Ok((status, Json(body)));
+        }
+    };
+    // make sure the system decompresses within budget before spending time validating
+    let system = decompress_system(&state, &system_bytes).await?;
+    tokio::time::timeout(
+        state.validation_timeout_seconds(),
+        validate_partial_offer(&partial_offer, &system, &state),
+    )
+    .await
+    .map_err(|_| ServerError::ValidationTimeout(state.validation_timeout_seconds().as_secs()))??;
     tracing::info!("compute offer validated, storing");
+    pin_dictionary(
+        &state,
+        &system_bytes,
+        partial_offer.proof_offer.endAuctionTimestamp,
+    );
 
     let offer_compressed = ComputeOfferCompressed::from((partial_offer, system_bytes));
 
     match state.intent_db().store_offer(&offer_compressed).await {
-        Ok(_) => Ok((
-            StatusCode::CREATED,
-            Json(json!({"message": "Offer stored successfully"})),
-        )),
+        Ok(_) => {
+            broadcast_offer(&state, offer_compressed, received_at_ms);
+            let body = json!({"message": "Offer stored successfully"});
+            idempotency.complete(StatusCode::CREATED, &body);
+            Ok((StatusCode::CREATED, Json(body)))
+        }
         Err(e) => Err(e),
     }
 }
+
+/// Broadcast a stored offer to the subscribers of offers. Offers are taken from the store rather
+/// than the broadcast, so an offer nobody subscribed to is still submitted.
+fn broadcast_offer<T: Transport + Clone, P: Provider<T> + Clone>(
+    state: &OfferState<T, P>,
+    offer_compressed: ComputeOfferCompressed,
+    received_at_ms: u64,
+) {
+    let Some(subscription_manager) = state.subscription_manager() else {
+        return;
+    };
+    let intent = CompressedIntent::Offer(offer_compressed);
+    let broadcast = subscription_manager.broadcast_sequenced(|sequence| {
+        let meta = BroadcastMeta {
+            sequence,
+            received_at_ms,
+        };
+        BroadcastedMessage::intent(&intent, None, meta)
+    });
+    match broadcast {
+        Ok(_) | Err(ServerError::NoProvidersAvailable()) => {}
+        Err(e) => tracing::warn!("Couldn't broadcast offer: {:?}", e),
+    }
+}
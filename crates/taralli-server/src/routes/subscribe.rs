@@ -5,21 +5,56 @@ use axum::{
         ws::{Message, WebSocket, WebSocketUpgrade},
         Query, State,
     },
-    response::IntoResponse,
+    http::{HeaderMap, HeaderName, HeaderValue},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
 };
+use base64::prelude::{Engine, BASE64_STANDARD};
+use bytes::{Bytes, BytesMut};
 use futures::{stream::StreamExt, SinkExt};
 use serde::Deserialize;
+use serde_json::json;
+use std::convert::Infallible;
 use std::sync::Arc;
+use taralli_primitives::alloy::primitives::Address;
 use taralli_primitives::alloy::providers::Provider;
 use taralli_primitives::alloy::transports::Transport;
+use taralli_primitives::compression_utils::frames::{
+    DEFLATE_FRAME_COMPRESSION, FRAME_COMPRESSION_HEADER,
+};
+use taralli_primitives::intents::envelope::{
+    BroadcastEnvelope, BroadcastMeta, IntentEnvelope, IntentKind, SubscriptionMask,
+    BROADCAST_META_VERSION, INTENT_ENVELOPE_VERSION, INTENT_VERSION_HEADER, LEGACY_INTENT_VERSION,
+    OFFER_BROADCAST_VERSION, SSE_CONTROL_EVENT, SSE_INTENT_EVENT,
+};
 use taralli_primitives::systems::{SystemIdMask, ALL_SYSTEMS_MASK};
-use tokio_stream::wrappers::BroadcastStream;
+use tokio::sync::broadcast::{
+    error::{RecvError, TryRecvError},
+    Receiver,
+};
 
+use crate::capabilities::CapabilityRegistry;
 use crate::state::request::RequestState;
+use crate::subscription_manager::{
+    BroadcastKind, BroadcastedMessage, ProviderSubscriptionGuard, SaturatedDelivery,
+    SubscriptionManager, SystemSubscriptionGuard,
+};
 
 #[derive(Debug, Deserialize)]
 pub struct SubscribeArgs {
     pub subscribed_to: Option<SystemIdMask>,
+    /// Encoded `SubscriptionMask` of the systems subscribed to for each kind of intent, taking
+    /// precedence over `subscribed_to`. Offers are only sent to clients advertising
+    /// `OFFER_BROADCAST_VERSION`.
+    pub subscription_mask: Option<u16>,
+    /// Maximum number of messages left unsent to this subscriber before the oldest ones are
+    /// skipped. Bounded by the server's broadcast buffer capacity.
+    pub buffer_size: Option<usize>,
+    /// Address of the provider subscribing, whose requests are skipped or flagged while its
+    /// advertised capabilities report no free slot for their system.
+    pub provider: Option<Address>,
 }
 
 /// WebSocket subscription handler that upgrades the connection to a WebSocket session.
@@ -31,6 +66,10 @@ pub struct SubscribeArgs {
 /// # Parameters
 /// - `ws`: The WebSocket upgrade request from the client.
 /// - `app_state`: Shared application state, containing the subscription manager.
+/// - `headers`: Request headers, advertising the intent envelope version the client supports and
+///   whether it takes deflated frames. Frame compression is confirmed by the same header on the
+///   upgrade response, it's only offered to clients supporting the broadcast metadata since
+///   deflated frames always carry it.
 ///
 /// # Returns
 /// An `IntoResponse` that upgrades the HTTP connection to a WebSocket session, which is needed since we expose the WebSocket endpoint as an HTTP route.
@@ -41,82 +80,156 @@ pub async fn websocket_subscribe_handler<
     ws: WebSocketUpgrade,
     State(app_state): State<RequestState<T, P>>,
     Query(args): Query<SubscribeArgs>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse> {
-    // We check for system ids that are not valid/matching with our current, so we don't spend resources needlessly.
-    // Otherwise, we'd keep the connection open but, below, we'd never send any messages.
-    // Also we need to fail before the upgrade, otherwise the client would see a success on connecting to the websocket.
-    if args
-        .subscribed_to
-        .is_some_and(|subscribed_to| subscribed_to > *ALL_SYSTEMS_MASK)
-    {
-        return Err(ServerError::SystemIdError(
-            args.subscribed_to.unwrap().to_string(),
-        ));
-    }
+    // We also need to fail before the upgrade, otherwise the client would see a success on connecting to the websocket.
+    let mask = subscription_mask(&args)?;
+    let (envelope_version, deflate) = negotiate_encoding(&app_state, &headers);
 
-    Ok(ws.on_upgrade(move |socket| async move {
-        if let Err(e) = websocket_subscribe(socket, Arc::new(app_state), args.subscribed_to).await {
-            tracing::error!("Failed to subscribe websocket: {:?}", e);
-        }
-    }))
+    let mut response = ws
+        .on_upgrade(move |socket| async move {
+            let feed = SubscriptionFeed::new(
+                &app_state,
+                mask,
+                args.buffer_size,
+                args.provider,
+                envelope_version,
+                deflate,
+            );
+            if let Err(e) = websocket_subscribe(socket, feed).await {
+                tracing::error!("Failed to subscribe websocket: {:?}", e);
+            }
+        })
+        .into_response();
+    if deflate {
+        confirm_frame_compression(&mut response);
+    }
+    Ok(response)
 }
 
-/// Handles an active WebSocket session, streaming messages from the subscription system.
+/// Server-sent events subscription handler, for clients whose network blocks websocket upgrades.
 ///
-/// This function listens to the broadcast stream from the `subscription_manager` and sends
-/// new messages to the connected WebSocket client. If an error occurs while sending,
-/// the connection is closed.
+/// Subscribers receive the same messages as over a websocket: intents are sent as `intent` events
+/// whose data is the base64 encoding of the binary websocket message, control frames as `control`
+/// events holding their JSON. Keepalive comments are sent while no message is broadcast, so
+/// proxies don't close the idle connection.
 ///
 /// # Parameters
-/// - `socket`: The WebSocket connection.
 /// - `app_state`: Shared application state, containing the subscription manager.
-async fn websocket_subscribe<T: Transport + Clone, P: Provider<T> + Clone>(
-    socket: WebSocket,
-    app_state: Arc<RequestState<T, P>>,
-    subscribed_to: Option<SystemIdMask>,
-) -> Result<()> {
-    // Register a new subscription. In other words, create a new receiver for the broadcasted proofs.
-    let subscription = app_state.subscription_manager().add_subscription();
-    tracing::info!(
-        "Subscription added, active subscriptions: {}",
-        app_state.subscription_manager().active_subscriptions()
+/// - `headers`: Request headers, negotiating the intent envelope version and frame compression
+///   like the websocket handler does.
+///
+/// # Returns
+/// A `text/event-stream` response streaming the broadcast until the client disconnects.
+pub async fn sse_subscribe_handler<
+    T: Transport + Clone + 'static,
+    P: Provider<T> + Clone + 'static,
+>(
+    State(app_state): State<RequestState<T, P>>,
+    Query(args): Query<SubscribeArgs>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse> {
+    let mask = subscription_mask(&args)?;
+    let (envelope_version, deflate) = negotiate_encoding(&app_state, &headers);
+
+    let feed = SubscriptionFeed::new(
+        &app_state,
+        mask,
+        args.buffer_size,
+        args.provider,
+        envelope_version,
+        deflate,
     );
+    let events = futures::stream::unfold(feed, |mut feed| async move {
+        let event = match feed.next().await? {
+            Outgoing::Intent(bytes) => Event::default()
+                .event(SSE_INTENT_EVENT)
+                .data(BASE64_STANDARD.encode(bytes)),
+            Outgoing::Control(text) => Event::default().event(SSE_CONTROL_EVENT).data(text),
+        };
+        Some((Ok::<_, Infallible>(event), feed))
+    });
 
-    // Create a broadcast stream from the subscription receiver.
-    let mut broadcast_stream = BroadcastStream::new(subscription);
+    let mut response = Sse::new(events)
+        .keep_alive(KeepAlive::default())
+        .into_response();
+    if deflate {
+        confirm_frame_compression(&mut response);
+    }
+    Ok(response)
+}
+
+/// Systems subscribed to for each kind of intent, the requests of every system when unset.
+/// We check for system ids that are not valid/matching with our current, so we don't spend resources needlessly.
+/// Otherwise, we'd keep the connection open but we'd never send any messages.
+fn subscription_mask(args: &SubscribeArgs) -> Result<SubscriptionMask> {
+    let mask = match args.subscription_mask {
+        Some(encoded) => SubscriptionMask::decode(encoded),
+        None => SubscriptionMask::requests_only(args.subscribed_to.unwrap_or(*ALL_SYSTEMS_MASK)),
+    };
+    for systems in [mask.requests, mask.offers] {
+        if systems > *ALL_SYSTEMS_MASK {
+            return Err(ServerError::SystemIdError(systems.to_string()));
+        }
+    }
+    Ok(mask)
+}
 
+/// Intent envelope version advertised by the client and whether it gets deflated frames.
+fn negotiate_encoding<T, P>(app_state: &RequestState<T, P>, headers: &HeaderMap) -> (u32, bool)
+where
+    T: Transport + Clone,
+    P: Provider<T> + Clone,
+{
+    // Clients that don't advertise a version predate the envelope and receive bare payloads.
+    let envelope_version = headers
+        .get(INTENT_VERSION_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u32>().ok())
+        .unwrap_or(LEGACY_INTENT_VERSION);
+    let deflate = app_state.subscription_manager().frame_compression()
+        && envelope_version >= BROADCAST_META_VERSION
+        && headers
+            .get(FRAME_COMPRESSION_HEADER)
+            .is_some_and(|value| value == DEFLATE_FRAME_COMPRESSION);
+    (envelope_version, deflate)
+}
+
+fn confirm_frame_compression(response: &mut Response) {
+    response.headers_mut().insert(
+        HeaderName::from_static(FRAME_COMPRESSION_HEADER),
+        HeaderValue::from_static(DEFLATE_FRAME_COMPRESSION),
+    );
+}
+
+/// Handles an active WebSocket session, streaming messages from the subscription system.
+///
+/// This function sends the messages of the subscription's feed to the connected WebSocket
+/// client: intents as binary messages, control frames as JSON text messages. If an error occurs
+/// while sending, the connection is closed.
+///
+/// # Parameters
+/// - `socket`: The WebSocket connection.
+/// - `feed`: The subscription's feed of broadcast messages.
+async fn websocket_subscribe(socket: WebSocket, mut feed: SubscriptionFeed) -> Result<()> {
     // Split the WebSocket into sender/receiver so we can handle them separately
     let (mut ws_sender, mut ws_receiver) = socket.split();
     // Use a `tokio::select!` loop to handle both reading and writing since we're in an async context.
     loop {
         tokio::select! {
-            // Outbound: messages from broadcast_stream => client
-            maybe_broadcast = broadcast_stream.next() => {
-                match maybe_broadcast {
-                    Some(Ok(message)) => {
-                        let bytes = message.content;
-                        let message_system_id: SystemIdMask = message.subscribed_to;
-                        // Check if the message is for any of the subscribed systems
-                        // If no system is specified upon subscription, client is subscribed to all systems.
-                        if message_system_id & subscribed_to.unwrap_or(*ALL_SYSTEMS_MASK) == 0 {
-                            continue;
-                        }
-                        // Try sending a binary message to the client
-                        if let Err(e) = ws_sender.send(Message::Binary(bytes)).await {
-                            tracing::error!("Failed to send WebSocket message: {:?}", e);
-                            break;
-                        }
-                    }
-                    Some(Err(e)) => {
-                        tracing::error!("Broadcast stream error: {:?}", e);
-                        // We don't break here, since stream errors from `tokyo::sync::broadcast` include returning errors if you're lagging behind.
-                        // Which should not be fatal. If the configured queue for the broadcast is big enough, this will just be sent on the next iteration.
-                        // Otherwise, it won't be sent at all. But still not a reason to break the connection.
-                    }
-                    None => {
-                        // The broadcast_stream ended (channel closed, etc.)
-                        break;
-                    }
+            // Outbound: messages from the broadcast => client
+            maybe_outgoing = feed.next() => {
+                let outgoing = match maybe_outgoing {
+                    // axum's messages own their payload, at most one copy made for this subscriber
+                    Some(Outgoing::Intent(bytes)) => Message::Binary(Vec::from(bytes)),
+                    Some(Outgoing::Control(text)) => Message::Text(text),
+                    // The broadcast channel closed
+                    None => break,
+                };
+                // Try sending the message to the client
+                if let Err(e) = ws_sender.send(outgoing).await {
+                    tracing::error!("Failed to send WebSocket message: {:?}", e);
+                    break;
                 }
             },
 
@@ -150,3 +263,241 @@ async fn websocket_subscribe<T: Transport + Clone, P: Provider<T> + Clone>(
     }
     Ok(())
 }
+
+/// Message to send to a subscriber, whichever transport it subscribed over
+enum Outgoing {
+    /// Intent, enveloped or deflated as negotiated with the subscriber
+    Intent(Bytes),
+    /// JSON control frame
+    Control(String),
+}
+
+/// Broadcast messages a single subscription gets, filtered on its systems for each kind of intent
+/// and encoded as negotiated.
+/// Whenever the subscriber falls behind, either because the broadcast buffer overflowed or
+/// because more than `buffer_size` messages are left unsent, the oldest messages are skipped
+/// and a lag notification is sent as a JSON control frame: `{"type": "lagged", "skipped": n}`.
+/// Control frames broadcast to the subscribed systems, such as auction expiry notifications, are
+/// passed on as they are to the subscribers of requests.
+/// Subscriptions identifying their provider don't get the requests of the systems the provider
+/// advertised no free slot for, or get them flagged, as the server is configured to.
+struct SubscriptionFeed {
+    subscription: Receiver<BroadcastedMessage>,
+    // Counted towards its systems until the subscription ends and the guard is dropped.
+    _system_subscription: SystemSubscriptionGuard,
+    // Attributed to the provider until the subscription ends, when it identified one.
+    provider_subscription: Option<(Address, ProviderSubscriptionGuard)>,
+    subscription_manager: Arc<SubscriptionManager>,
+    capabilities: Arc<CapabilityRegistry>,
+    mask: SubscriptionMask,
+    buffer_size: usize,
+    envelope_version: u32,
+    deflate: bool,
+    // message kept back while the lag or saturation notification preceding it is sent
+    pending: Option<BroadcastedMessage>,
+    // whether the pending message was flagged already
+    pending_flagged: bool,
+}
+
+impl SubscriptionFeed {
+    /// Register a new subscription. In other words, create a new receiver for the broadcasted proofs.
+    ///
+    /// # Parameters
+    /// - `mask`: Systems the client subscribed to for each kind of intent.
+    /// - `buffer_size`: Requested buffer size of this subscription.
+    /// - `provider`: Address of the provider subscribing, if it identified itself.
+    /// - `envelope_version`: Intent envelope version advertised by the client.
+    /// - `deflate`: Whether intents are sent as the deflated frames encoded on submission.
+    fn new<T, P>(
+        app_state: &RequestState<T, P>,
+        mask: SubscriptionMask,
+        buffer_size: Option<usize>,
+        provider: Option<Address>,
+        envelope_version: u32,
+        deflate: bool,
+    ) -> Self
+    where
+        T: Transport + Clone,
+        P: Provider<T> + Clone,
+    {
+        let subscription_manager = app_state.subscription_manager();
+        let feed = Self {
+            subscription: subscription_manager.add_subscription(),
+            // subscribers of offers are requesters, only the providers of requests are counted
+            _system_subscription: subscription_manager.track_systems(mask.requests),
+            provider_subscription: provider.map(|provider| {
+                (
+                    provider,
+                    subscription_manager.track_provider(provider, mask.requests),
+                )
+            }),
+            capabilities: app_state.capabilities(),
+            mask,
+            buffer_size: subscription_manager.subscription_buffer_size(buffer_size),
+            envelope_version,
+            deflate,
+            pending: None,
+            pending_flagged: false,
+            subscription_manager,
+        };
+        tracing::info!(
+            "Subscription added, active subscriptions: {}, buffer size: {}",
+            feed.subscription_manager.active_subscriptions(),
+            feed.buffer_size
+        );
+        feed
+    }
+
+    /// Next message to send to the subscriber, `None` once the broadcast channel closed.
+    async fn next(&mut self) -> Option<Outgoing> {
+        loop {
+            let mut flagged = std::mem::take(&mut self.pending_flagged);
+            let message = match self.pending.take() {
+                Some(message) => message,
+                None => match self.subscription.recv().await {
+                    Ok(message) => message,
+                    Err(RecvError::Lagged(skipped)) => {
+                        // The broadcast buffer overflowed before we could send these messages.
+                        // Let the subscriber know, so it can backfill what it missed.
+                        tracing::warn!("Subscriber lagged behind, skipped {} message(s)", skipped);
+                        return Some(lag_notification(skipped));
+                    }
+                    Err(RecvError::Closed) => return None,
+                },
+            };
+
+            // Skip the oldest messages when more than this subscription's buffer size are pending.
+            let mut skipped = 0u64;
+            let mut message = message;
+            while self.subscription.len() > self.buffer_size {
+                match self.subscription.try_recv() {
+                    Ok(newer) => {
+                        message = newer;
+                        flagged = false;
+                        skipped += 1;
+                    }
+                    Err(TryRecvError::Lagged(n)) => skipped += n,
+                    Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+                }
+            }
+            if skipped > 0 {
+                tracing::warn!("Subscriber buffer full, skipped {} message(s)", skipped);
+                self.pending = Some(message);
+                return Some(lag_notification(skipped));
+            }
+
+            // Check if the message is for any of the systems subscribed to for its kind
+            let subscribed_to = match message.kind {
+                BroadcastKind::Offer if self.envelope_version < OFFER_BROADCAST_VERSION => continue,
+                BroadcastKind::Offer => self.mask.offers,
+                BroadcastKind::Intent | BroadcastKind::Control => self.mask.requests,
+            };
+            if message.subscribed_to & subscribed_to == 0 {
+                continue;
+            }
+            if message.kind == BroadcastKind::Intent && !flagged && self.is_saturated(&message) {
+                match self.subscription_manager.saturated_delivery() {
+                    SaturatedDelivery::Skip => {
+                        self.subscription_manager.record_skipped_delivery();
+                        continue;
+                    }
+                    SaturatedDelivery::Flag => {
+                        let notification = saturation_notification(message.meta);
+                        self.pending = Some(message);
+                        self.pending_flagged = true;
+                        return Some(notification);
+                    }
+                }
+            }
+            let deflated = message.deflated.filter(|_| self.deflate);
+            let outgoing = match (message.kind, deflated) {
+                (BroadcastKind::Control, _) => match std::str::from_utf8(&message.content) {
+                    Ok(text) => Outgoing::Control(text.to_string()),
+                    Err(e) => {
+                        tracing::error!("Skipping non utf-8 control frame: {:?}", e);
+                        continue;
+                    }
+                },
+                (BroadcastKind::Intent, Some(frame)) => Outgoing::Intent(frame),
+                (BroadcastKind::Intent | BroadcastKind::Offer, _) => {
+                    let kind = if message.kind == BroadcastKind::Offer {
+                        IntentKind::Offer
+                    } else {
+                        IntentKind::Request
+                    };
+                    match envelope_prefix(self.envelope_version, kind, message.meta) {
+                        Ok(Some(prefix)) => {
+                            let mut enveloped =
+                                BytesMut::with_capacity(prefix.len() + message.content.len());
+                            enveloped.extend_from_slice(&prefix);
+                            enveloped.extend_from_slice(&message.content);
+                            Outgoing::Intent(enveloped.freeze())
+                        }
+                        Ok(None) => Outgoing::Intent(message.content),
+                        Err(e) => {
+                            tracing::error!("Skipping intent, couldn't envelope it: {:?}", e);
+                            continue;
+                        }
+                    }
+                }
+            };
+            return Some(outgoing);
+        }
+    }
+
+    /// Whether the subscription's provider has no free slot for the systems of `message`.
+    fn is_saturated(&self, message: &BroadcastedMessage) -> bool {
+        self.provider_subscription
+            .as_ref()
+            .is_some_and(|(provider, _)| {
+                self.capabilities
+                    .is_saturated(provider, message.subscribed_to)
+            })
+    }
+}
+
+/// Bytes to prepend to an intent's broadcast content for clients supporting the intent envelope.
+/// Bincode encodes a struct as its fields back to back and `()` as nothing, so an envelope with a
+/// unit payload followed by the bincode encoded request is the encoding of the full envelope.
+/// Clients predating the broadcast metadata get the last envelope version without it, the others
+/// the highest version both sides support.
+fn envelope_prefix(
+    envelope_version: u32,
+    kind: IntentKind,
+    meta: Option<BroadcastMeta>,
+) -> Result<Option<Vec<u8>>> {
+    if envelope_version == LEGACY_INTENT_VERSION {
+        return Ok(None);
+    }
+    let prefix = match meta {
+        Some(meta) if envelope_version >= BROADCAST_META_VERSION => {
+            bincode::serialize(&BroadcastEnvelope {
+                v: envelope_version.min(INTENT_ENVELOPE_VERSION),
+                kind,
+                meta,
+                payload: (),
+            })
+        }
+        _ => bincode::serialize(&IntentEnvelope {
+            v: BROADCAST_META_VERSION - 1,
+            kind,
+            payload: (),
+        }),
+    };
+    prefix
+        .map(Some)
+        .map_err(|e| ServerError::SerializationError(e.to_string()))
+}
+
+/// Control frame telling the subscriber the request following it was sent despite its provider
+/// having no free slot for it.
+fn saturation_notification(meta: Option<BroadcastMeta>) -> Outgoing {
+    Outgoing::Control(
+        json!({ "type": "saturated", "sequence": meta.map(|meta| meta.sequence) }).to_string(),
+    )
+}
+
+/// Control frame telling the subscriber how many messages it missed.
+fn lag_notification(skipped: u64) -> Outgoing {
+    Outgoing::Control(json!({ "type": "lagged", "skipped": skipped }).to_string())
+}
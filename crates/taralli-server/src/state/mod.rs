@@ -1,21 +1,49 @@
+use std::collections::BTreeMap;
 use std::marker::PhantomData;
 use std::time::Duration;
 use taralli_primitives::alloy::{
     network::Ethereum, primitives::Address, providers::Provider, transports::Transport,
 };
+use taralli_primitives::validation::{
+    offer::OfferValidationConfig, request::RequestValidationConfig,
+};
 
-use crate::config::{Markets, ServerValidationConfigs};
+use crate::config::{ClockSkewConfig, Markets, ServerValidationConfigs, SubmissionLimits};
+use crate::dictionary::DictionaryStore;
+use crate::error::{Result, ServerError};
+use crate::idempotency::IdempotencyCache;
 
 pub mod offer;
 pub mod request;
 
+/// Kind of intent a market settles
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarketKind {
+    /// `ComputeRequest`s, settled by a UniversalBombetta market
+    Request,
+    /// `ComputeOffer`s, settled by a UniversalPorchetta market
+    Offer,
+}
+
+/// Validation config of the market an intent references
+#[derive(Debug)]
+pub enum MarketValidator<'a> {
+    Request(&'a RequestValidationConfig),
+    Offer(&'a OfferValidationConfig),
+}
+
 /// Common base state with shared fields between compute intent types
 #[derive(Clone)]
 pub struct BaseState<T, P> {
     rpc_provider: P,
     markets: Markets,
+    market_kinds: BTreeMap<Address, MarketKind>,
     validation_timeout_seconds: Duration,
     validation_configs: ServerValidationConfigs,
+    submission_limits: SubmissionLimits,
+    clock_skew: ClockSkewConfig,
+    idempotency_cache: IdempotencyCache,
+    dictionaries: DictionaryStore,
     phantom: PhantomData<T>,
 }
 
@@ -30,15 +58,39 @@ where
         validation_timeout_seconds: Duration,
         validation_configs: ServerValidationConfigs,
     ) -> Self {
+        let market_kinds = BTreeMap::from([
+            (markets.universal_bombetta, MarketKind::Request),
+            (markets.universal_porchetta, MarketKind::Offer),
+        ]);
         Self {
             rpc_provider,
             markets,
+            market_kinds,
             validation_timeout_seconds,
             validation_configs,
+            submission_limits: SubmissionLimits::default(),
+            clock_skew: ClockSkewConfig::default(),
+            idempotency_cache: IdempotencyCache::default(),
+            dictionaries: DictionaryStore::default(),
             phantom: PhantomData,
         }
     }
 
+    pub fn with_submission_limits(mut self, submission_limits: SubmissionLimits) -> Self {
+        self.submission_limits = submission_limits;
+        self
+    }
+
+    pub fn with_clock_skew(mut self, clock_skew: ClockSkewConfig) -> Self {
+        self.clock_skew = clock_skew;
+        self
+    }
+
+    pub fn with_dictionaries(mut self, dictionaries: DictionaryStore) -> Self {
+        self.dictionaries = dictionaries;
+        self
+    }
+
     pub fn rpc_provider(&self) -> P {
         self.rpc_provider.clone()
     }
@@ -51,6 +103,31 @@ where
         self.markets.universal_porchetta
     }
 
+    /// Kind of intent the given market settles, if the server knows the market
+    pub fn market_kind(&self, market: &Address) -> Option<MarketKind> {
+        self.market_kinds.get(market).copied()
+    }
+
+    /// Select the validation config of the market an intent references, rejecting markets the
+    /// server doesn't know about.
+    pub fn market_validator(&self, market: &Address) -> Result<MarketValidator<'_>> {
+        match self.market_kind(market) {
+            Some(MarketKind::Request) => {
+                Ok(MarketValidator::Request(&self.validation_configs.request))
+            }
+            Some(MarketKind::Offer) => Ok(MarketValidator::Offer(&self.validation_configs.offer)),
+            None => Err(ServerError::UnknownMarket {
+                market: *market,
+                supported: self
+                    .market_kinds
+                    .iter()
+                    .map(|(address, kind)| format!("{address} ({kind:?})"))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            }),
+        }
+    }
+
     pub fn validation_timeout_seconds(&self) -> Duration {
         self.validation_timeout_seconds
     }
@@ -58,4 +135,21 @@ where
     pub fn validation_configs(&self) -> &ServerValidationConfigs {
         &self.validation_configs
     }
+
+    pub fn submission_limits(&self) -> &SubmissionLimits {
+        &self.submission_limits
+    }
+
+    pub fn clock_skew(&self) -> &ClockSkewConfig {
+        &self.clock_skew
+    }
+
+    pub fn idempotency_cache(&self) -> &IdempotencyCache {
+        &self.idempotency_cache
+    }
+
+    /// Compression dictionaries shared by the request and offer routes
+    pub fn dictionaries(&self) -> &DictionaryStore {
+        &self.dictionaries
+    }
 }
@@ -1,6 +1,12 @@
+use std::sync::Arc;
+
+use axum::extract::FromRef;
 use taralli_primitives::alloy::{network::Ethereum, providers::Provider, transports::Transport};
 
+use crate::config::SubmissionLimits;
+use crate::offer_inputs::OfferInputsStore;
 use crate::postgres::Db;
+use crate::subscription_manager::SubscriptionManager;
 
 use super::BaseState;
 
@@ -9,6 +15,10 @@ use super::BaseState;
 pub struct OfferState<T, P> {
     pub base: BaseState<T, P>,
     intent_db: Db,
+    // inputs delivered by the requesters of won offers
+    offer_inputs: Arc<OfferInputsStore>,
+    // broadcasts stored offers to the subscribers of offers, when shared with the request routes
+    subscription_manager: Option<Arc<SubscriptionManager>>,
 }
 
 impl<T, P> OfferState<T, P>
@@ -17,12 +27,35 @@ where
     P: Provider<T, Ethereum> + Clone,
 {
     pub fn new(base: BaseState<T, P>, intent_db: Db) -> Self {
-        Self { base, intent_db }
+        Self {
+            base,
+            intent_db,
+            offer_inputs: Arc::new(OfferInputsStore::default()),
+            subscription_manager: None,
+        }
+    }
+
+    /// Broadcast the offers stored to the subscribers of offers of `subscription_manager`
+    #[must_use]
+    pub fn with_subscription_manager(
+        mut self,
+        subscription_manager: Arc<SubscriptionManager>,
+    ) -> Self {
+        self.subscription_manager = Some(subscription_manager);
+        self
     }
 
     pub fn intent_db(&self) -> &Db {
         &self.intent_db
     }
+
+    pub fn subscription_manager(&self) -> Option<&Arc<SubscriptionManager>> {
+        self.subscription_manager.as_ref()
+    }
+
+    pub fn offer_inputs(&self) -> Arc<OfferInputsStore> {
+        self.offer_inputs.clone()
+    }
 }
 
 impl<T, P> std::ops::Deref for OfferState<T, P> {
@@ -32,3 +65,57 @@ impl<T, P> std::ops::Deref for OfferState<T, P> {
         &self.base
     }
 }
+
+impl<T, P> FromRef<OfferState<T, P>> for SubmissionLimits {
+    fn from_ref(state: &OfferState<T, P>) -> Self {
+        state.base.submission_limits.clone()
+    }
+}
+
+/// State of the offer inputs routes, which only need the market and the delivered inputs
+#[derive(Clone)]
+pub struct OfferInputsState<T, P> {
+    pub base: BaseState<T, P>,
+    offer_inputs: Arc<OfferInputsStore>,
+}
+
+impl<T, P> OfferInputsState<T, P>
+where
+    T: Transport + Clone,
+    P: Provider<T, Ethereum> + Clone,
+{
+    pub fn new(base: BaseState<T, P>) -> Self {
+        Self {
+            base,
+            offer_inputs: Arc::new(OfferInputsStore::default()),
+        }
+    }
+
+    /// Relay the offer inputs through `offer_inputs` rather than a default store
+    #[must_use]
+    pub fn with_offer_inputs(mut self, offer_inputs: Arc<OfferInputsStore>) -> Self {
+        self.offer_inputs = offer_inputs;
+        self
+    }
+
+    pub fn offer_inputs(&self) -> Arc<OfferInputsStore> {
+        self.offer_inputs.clone()
+    }
+}
+
+impl<T, P> std::ops::Deref for OfferInputsState<T, P> {
+    type Target = BaseState<T, P>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.base
+    }
+}
+
+impl<T: Clone, P: Clone> FromRef<OfferState<T, P>> for OfferInputsState<T, P> {
+    fn from_ref(state: &OfferState<T, P>) -> Self {
+        Self {
+            base: state.base.clone(),
+            offer_inputs: state.offer_inputs.clone(),
+        }
+    }
+}
@@ -1,7 +1,20 @@
 use std::sync::Arc;
 
+use axum::extract::FromRef;
 use taralli_primitives::alloy::{network::Ethereum, providers::Provider, transports::Transport};
 
+use taralli_primitives::compression_utils::intents::ComputeRequestCompressed;
+use taralli_primitives::intents::envelope::IntentMetadata;
+use tracing::Instrument;
+
+use crate::capabilities::CapabilityRegistry;
+use crate::config::SubmissionLimits;
+use crate::error::ServerError;
+use crate::expiry::ExpiryQueue;
+use crate::extension::ExtensionNegotiation;
+use crate::gc::IntentGc;
+use crate::intent_store::{IntentStatus, IntentStore};
+use crate::key_exchange::KeyExchange;
 use crate::subscription_manager::SubscriptionManager;
 
 use super::BaseState;
@@ -11,6 +24,18 @@ use super::BaseState;
 pub struct RequestState<T, P> {
     pub base: BaseState<T, P>,
     subscription_manager: Arc<SubscriptionManager>,
+    // persists submitted requests when set
+    intent_store: Option<Arc<dyn IntentStore>>,
+    // broadcast requests awaiting the end of their auction
+    expiry_queue: Arc<ExpiryQueue>,
+    // key requests and deliveries of encrypted requests
+    key_exchange: Arc<KeyExchange>,
+    // deadline extension requests of won requests and their answers
+    extensions: Arc<ExtensionNegotiation>,
+    // capabilities advertised by the providers
+    capabilities: Arc<CapabilityRegistry>,
+    // collects the intents that are over, reported by the status route when set
+    gc: Option<Arc<IntentGc>>,
 }
 
 impl<T, P> RequestState<T, P>
@@ -22,12 +47,95 @@ where
         Self {
             base,
             subscription_manager,
+            intent_store: None,
+            expiry_queue: Arc::new(ExpiryQueue::default()),
+            key_exchange: Arc::new(KeyExchange::default()),
+            extensions: Arc::new(ExtensionNegotiation::default()),
+            capabilities: Arc::new(CapabilityRegistry::default()),
+            gc: None,
         }
     }
 
+    /// persist submitted requests in the given store
+    pub fn with_intent_store(mut self, intent_store: Arc<dyn IntentStore>) -> Self {
+        self.intent_store = Some(intent_store);
+        self
+    }
+
+    /// report the intents collected by `gc` on the status route
+    pub fn with_gc(mut self, gc: Arc<IntentGc>) -> Self {
+        self.gc = Some(gc);
+        self
+    }
+
     pub fn subscription_manager(&self) -> Arc<SubscriptionManager> {
         self.subscription_manager.clone()
     }
+
+    pub fn intent_store(&self) -> Option<&Arc<dyn IntentStore>> {
+        self.intent_store.as_ref()
+    }
+
+    pub fn expiry_queue(&self) -> Arc<ExpiryQueue> {
+        self.expiry_queue.clone()
+    }
+
+    pub fn key_exchange(&self) -> Arc<KeyExchange> {
+        self.key_exchange.clone()
+    }
+
+    pub fn extensions(&self) -> Arc<ExtensionNegotiation> {
+        self.extensions.clone()
+    }
+
+    pub fn capabilities(&self) -> Arc<CapabilityRegistry> {
+        self.capabilities.clone()
+    }
+
+    pub fn gc(&self) -> Option<&Arc<IntentGc>> {
+        self.gc.as_ref()
+    }
+
+    /// Start broadcasting expiry notifications of broadcast requests as their auctions end
+    pub fn spawn_expiry_notifier(&self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(
+            self.expiry_queue
+                .clone()
+                .notify_expired(self.subscription_manager.clone()),
+        )
+    }
+
+    /// Write a submitted request through to the intent store in the background, so persisting it
+    /// never delays the broadcast. Failures are logged, the request was already answered.
+    pub fn persist_request(
+        &self,
+        request: ComputeRequestCompressed,
+        metadata: IntentMetadata,
+        broadcast: bool,
+    ) {
+        let Some(intent_store) = self.intent_store.clone() else {
+            return;
+        };
+        tokio::spawn(
+            async move {
+                let result = async {
+                    let stored = intent_store.store_request(&request, &metadata).await?;
+                    if broadcast {
+                        intent_store
+                            .update_request_status(stored.intent_id, IntentStatus::Broadcast)
+                            .await?;
+                    }
+                    Ok::<_, ServerError>(())
+                }
+                .await;
+                if let Err(e) = result {
+                    tracing::error!("Failed to persist submitted request: {}", e);
+                }
+            }
+            // logged within the span of the submission
+            .in_current_span(),
+        );
+    }
 }
 
 impl<T, P> std::ops::Deref for RequestState<T, P> {
@@ -37,3 +145,9 @@ impl<T, P> std::ops::Deref for RequestState<T, P> {
         &self.base
     }
 }
+
+impl<T, P> FromRef<RequestState<T, P>> for SubmissionLimits {
+    fn from_ref(state: &RequestState<T, P>) -> Self {
+        state.base.submission_limits.clone()
+    }
+}
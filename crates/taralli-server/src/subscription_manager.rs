@@ -1,15 +1,86 @@
-use taralli_primitives::{env::Environment, systems::SystemIdMask};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use taralli_primitives::{
+    alloy::primitives::Address,
+    compression_utils::intents::CompressedIntent,
+    env::Environment,
+    intents::envelope::{BroadcastMeta, IntentKind},
+    systems::{SystemId, SystemIdMask, SYSTEMS},
+};
 use tokio::sync::broadcast::{self, Receiver};
 
 use crate::error::{Result, ServerError};
 
 #[derive(Clone)]
 /// A wrapper type for the message that is broadcasted to all subscribers.
-/// content: The serialized compute request, with system information being compressed.
+/// content: The serialized compute request or offer, with system information being compressed, or
+/// the JSON of a control frame.
 /// `subscribed_to`: The system id that the compute request is related to. See `systems` macro in primitives.
+/// kind: How the content is sent to subscribers.
+/// deflated: The intent encoded as a deflated frame once for all the subscribers that negotiated
+/// frame compression, see `compression_utils::frames` in primitives.
+/// meta: The sequence number and receive time the intent was stamped with, sent to the subscribers
+/// supporting it. Control frames aren't stamped.
+/// The broadcast channel clones the message for each subscriber, so the content and deflated frame
+/// are shared buffers that every subscriber reads without copying them.
 pub struct BroadcastedMessage {
-    pub content: Vec<u8>,
+    pub content: Bytes,
     pub subscribed_to: SystemIdMask,
+    pub kind: BroadcastKind,
+    pub deflated: Option<Bytes>,
+    pub meta: Option<BroadcastMeta>,
+}
+
+impl BroadcastedMessage {
+    /// Message broadcasting `intent` to the subscribers of its system and kind, along with its
+    /// deflated frame when the subscription manager negotiates frame compression
+    pub fn intent(
+        intent: &CompressedIntent<'_>,
+        deflated: Option<Bytes>,
+        meta: BroadcastMeta,
+    ) -> Result<Self> {
+        let content = intent
+            .encode_payload()
+            .map_err(|e| ServerError::SerializationError(e.to_string()))?;
+        Ok(Self {
+            content: content.into(),
+            subscribed_to: intent.system_id().as_bit(),
+            kind: match intent.kind() {
+                IntentKind::Request => BroadcastKind::Intent,
+                IntentKind::Offer => BroadcastKind::Offer,
+            },
+            deflated,
+            meta: Some(meta),
+        })
+    }
+}
+
+/// Kind of a broadcast message's content
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BroadcastKind {
+    /// bincode encoded compressed request, sent as a binary message within the intent envelope
+    #[default]
+    Intent,
+    /// bincode encoded compressed offer, sent like requests to the subscribers of offers
+    Offer,
+    /// JSON control frame (e.g. an expiry notification), sent as a text message
+    Control,
+}
+
+/// How requests are delivered to the subscriptions of providers whose latest capabilities report
+/// no free slot for the request's system
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SaturatedDelivery {
+    /// the request isn't sent, sparing the provider the decompression of a request it would drop
+    #[default]
+    Skip,
+    /// the request is sent, preceded by a JSON control frame:
+    /// `{"type": "saturated", "sequence": n}`, `n` being the sequence number of the request
+    Flag,
 }
 
 // Generic over a Message type M
@@ -19,6 +90,57 @@ where
     M: Clone,
 {
     sender: broadcast::Sender<M>,
+    capacity: usize,
+    /// Whether subscribers asking for deflated frames get them.
+    frame_compression: bool,
+    /// Number of live subscriptions interested in each system, indexed like `SYSTEMS`.
+    system_subscribers: Arc<[AtomicUsize; SYSTEMS.len()]>,
+    /// Sequence number of the next broadcast intent, locked while broadcasting so the subscribers
+    /// receive the intents in sequence order.
+    sequence: Mutex<u64>,
+    /// How requests are delivered to the subscriptions of saturated providers.
+    saturated_delivery: SaturatedDelivery,
+    /// Provider and request systems of the live subscriptions identifying their provider.
+    provider_subscriptions: Arc<Mutex<Vec<(Address, SystemIdMask)>>>,
+    /// Requests not sent to the subscriptions of saturated providers.
+    skipped_deliveries: AtomicU64,
+}
+
+/// Keeps a subscription counted towards the systems it subscribed to, until dropped.
+pub struct SystemSubscriptionGuard {
+    mask: SystemIdMask,
+    system_subscribers: Arc<[AtomicUsize; SYSTEMS.len()]>,
+}
+
+impl Drop for SystemSubscriptionGuard {
+    fn drop(&mut self) {
+        for (system_id, count) in SYSTEMS.iter().zip(self.system_subscribers.iter()) {
+            if system_id.as_bit() & self.mask != 0 {
+                count.fetch_sub(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// Keeps a subscription attributed to its provider, until dropped.
+pub struct ProviderSubscriptionGuard {
+    subscription: (Address, SystemIdMask),
+    provider_subscriptions: Arc<Mutex<Vec<(Address, SystemIdMask)>>>,
+}
+
+impl Drop for ProviderSubscriptionGuard {
+    fn drop(&mut self) {
+        let mut subscriptions = self
+            .provider_subscriptions
+            .lock()
+            .expect("provider subscriptions lock poisoned");
+        if let Some(index) = subscriptions
+            .iter()
+            .position(|subscription| *subscription == self.subscription)
+        {
+            subscriptions.swap_remove(index);
+        }
+    }
 }
 
 impl<M> SubscriptionManager<M>
@@ -28,7 +150,60 @@ where
     #[must_use]
     pub fn new(capacity: usize) -> Self {
         let (sender, _) = broadcast::channel(capacity);
-        Self { sender }
+        Self {
+            sender,
+            capacity,
+            frame_compression: false,
+            system_subscribers: Arc::new(std::array::from_fn(|_| AtomicUsize::new(0))),
+            sequence: Mutex::new(0),
+            saturated_delivery: SaturatedDelivery::default(),
+            provider_subscriptions: Arc::new(Mutex::new(Vec::new())),
+            skipped_deliveries: AtomicU64::new(0),
+        }
+    }
+
+    /// Reserve the sequence number following the previous one, for a message stamped ahead of its
+    /// broadcast. Intents are stamped by `broadcast_sequenced`, which keeps them in order.
+    pub fn next_sequence(&self) -> u64 {
+        let mut sequence = self.sequence.lock().expect("sequence lock poisoned");
+        *sequence += 1;
+        *sequence - 1
+    }
+
+    /// Number of messages the broadcast buffer holds before lagging subscribers miss them.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Offer deflated frames to the subscribers asking for them.
+    #[must_use]
+    pub fn with_frame_compression(mut self, enabled: bool) -> Self {
+        self.frame_compression = enabled;
+        self
+    }
+
+    #[must_use]
+    pub fn frame_compression(&self) -> bool {
+        self.frame_compression
+    }
+
+    /// Deliver requests to the subscriptions of saturated providers as `saturated_delivery` says.
+    #[must_use]
+    pub fn with_saturated_delivery(mut self, saturated_delivery: SaturatedDelivery) -> Self {
+        self.saturated_delivery = saturated_delivery;
+        self
+    }
+
+    #[must_use]
+    pub fn saturated_delivery(&self) -> SaturatedDelivery {
+        self.saturated_delivery
+    }
+
+    /// Buffer size of a single subscription, bounded by the broadcast buffer's capacity.
+    #[must_use]
+    pub fn subscription_buffer_size(&self, requested: Option<usize>) -> usize {
+        requested.map_or(self.capacity, |requested| requested.clamp(1, self.capacity))
     }
 
     #[must_use]
@@ -46,10 +221,114 @@ where
         self.sender.receiver_count()
     }
 
+    /// Count a subscription towards every system within `mask` for as long as the returned guard lives.
+    #[must_use]
+    pub fn track_systems(&self, mask: SystemIdMask) -> SystemSubscriptionGuard {
+        for (system_id, count) in SYSTEMS.iter().zip(self.system_subscribers.iter()) {
+            if system_id.as_bit() & mask != 0 {
+                count.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        SystemSubscriptionGuard {
+            mask,
+            system_subscribers: self.system_subscribers.clone(),
+        }
+    }
+
+    /// Attribute a subscription to the requests of the systems within `mask` to `provider` for as
+    /// long as the returned guard lives.
+    #[must_use]
+    pub fn track_provider(
+        &self,
+        provider: Address,
+        mask: SystemIdMask,
+    ) -> ProviderSubscriptionGuard {
+        self.provider_subscriptions
+            .lock()
+            .expect("provider subscriptions lock poisoned")
+            .push((provider, mask));
+        ProviderSubscriptionGuard {
+            subscription: (provider, mask),
+            provider_subscriptions: self.provider_subscriptions.clone(),
+        }
+    }
+
+    /// Number of the `receivers` of a request of `system_id` it's delivered to, the subscriptions
+    /// of the `saturated` providers being skipped.
+    #[must_use]
+    pub fn eligible_receivers(
+        &self,
+        receivers: usize,
+        system_id: SystemId,
+        saturated: &[Address],
+    ) -> usize {
+        if self.saturated_delivery == SaturatedDelivery::Flag {
+            return receivers;
+        }
+        let skipped = self
+            .provider_subscriptions
+            .lock()
+            .expect("provider subscriptions lock poisoned")
+            .iter()
+            .filter(|(provider, mask)| {
+                system_id.as_bit() & mask != 0 && saturated.contains(provider)
+            })
+            .count();
+        receivers.saturating_sub(skipped)
+    }
+
+    /// Count a request not sent to a saturated provider.
+    pub fn record_skipped_delivery(&self) {
+        self.skipped_deliveries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Number of requests not sent to saturated providers so far.
+    #[must_use]
+    pub fn skipped_deliveries(&self) -> u64 {
+        self.skipped_deliveries.load(Ordering::Relaxed)
+    }
+
+    /// Number of live subscriptions per system.
+    #[must_use]
+    pub fn subscribers_per_system(&self) -> Vec<(SystemId, usize)> {
+        SYSTEMS
+            .iter()
+            .zip(self.system_subscribers.iter())
+            .map(|(system_id, count)| (*system_id, count.load(Ordering::Relaxed)))
+            .collect()
+    }
+
+    /// Number of live subscriptions to the given system.
+    #[must_use]
+    pub fn subscribers(&self, system_id: SystemId) -> usize {
+        self.subscribers_per_system()
+            .into_iter()
+            .find(|(subscribed, _)| *subscribed == system_id)
+            .map_or(0, |(_, count)| count)
+    }
+
     /// Send an event to all the receivers in the broadcast.
-    /// Although this function is just a wrapper around `tokio::sync::broadcast::Sender::send` as of now,
-    /// in the future we might want to add custom logic to it.
     pub fn broadcast(&self, event: M) -> Result<usize> {
+        let _sequence = self.sequence.lock().expect("sequence lock poisoned");
+        self.send(event)
+    }
+
+    /// Stamp an event with the next sequence number through `stamp` and send it to all the
+    /// receivers, the number being assigned under the same lock as the send so concurrent
+    /// broadcasts reach the subscribers in sequence order. The number is only consumed once the
+    /// event is sent.
+    pub fn broadcast_sequenced(&self, stamp: impl FnOnce(u64) -> Result<M>) -> Result<usize> {
+        let mut sequence = self.sequence.lock().expect("sequence lock poisoned");
+        if self.active_subscriptions() == 0 {
+            tracing::warn!("Attempted to broadcast event but found no active subscribers");
+            return Err(ServerError::NoProvidersAvailable());
+        }
+        let recv_count = self.send(stamp(*sequence)?)?;
+        *sequence += 1;
+        Ok(recv_count)
+    }
+
+    fn send(&self, event: M) -> Result<usize> {
         let subscriber_count = self.active_subscriptions();
         if subscriber_count == 0 {
             tracing::warn!("Attempted to broadcast event but found no active subscribers");
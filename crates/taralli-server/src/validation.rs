@@ -1,6 +1,8 @@
 use crate::{
+    clock::unix_timestamp,
+    config::ClockSkewConfig,
     error::{Result, ServerError},
-    state::{offer::OfferState, request::RequestState},
+    state::{offer::OfferState, request::RequestState, MarketValidator},
 };
 use taralli_primitives::{
     alloy::{
@@ -10,121 +12,234 @@ use taralli_primitives::{
         transports::Transport,
     },
     compression_utils::intents::{PartialComputeOffer, PartialComputeRequest},
+    encryption::SystemPayload,
+    systems::SystemId,
     validation::{
-        offer::{validate_offer_amount_constraints, validate_offer_signature},
-        request::{validate_request_amount_constraints, validate_request_signature},
-        validate_market_address, validate_time_constraints,
+        limits::ResourceLimits, offer::offer_commitment_violations,
+        request::request_commitment_violations, validate_system_id, validate_system_resources,
+        violation::Violation, CommonValidationConfig,
     },
+    PrimitivesError,
 };
 
 /// Validate a submitted compute intent
 pub async fn validate_partial_request<T: Transport + Clone, P: Provider<T> + Clone>(
     partial_request: &PartialComputeRequest,
+    system: &SystemPayload,
     state: &RequestState<T, P>,
 ) -> Result<()> {
+    // validate against the config of the market the request references, with the bounds of the
+    // request's system
+    let config = match state.market_validator(&partial_request.proof_request.market)? {
+        MarketValidator::Request(config) => config.for_system(partial_request.system_id),
+        MarketValidator::Offer(_) => {
+            return Err(ServerError::ValidationError(format!(
+                "market {} settles ComputeOffers, submit offers to /submit/offer",
+                partial_request.proof_request.market
+            )))
+        }
+    };
+
+    // the workload is checked locally, before fetching anything from the chain
+    validate_payload_system_id(partial_request.system_id, system)?;
+    validate_payload_resources(
+        system,
+        partial_request.proof_request.provingTime,
+        &config.base.resource_limits,
+    )?;
+
     // TODO: separate this timestamp fetch from the validation execution of the server
     #[cfg(not(feature = "ci-test"))]
-    let latest_timestamp = get_latest_timestamp(state.rpc_provider()).await?;
+    let validation_time = fetch_validation_time(state.rpc_provider(), state.clock_skew()).await?;
 
     // We have some tests for the transport of data between submit/subscribe.
     // Since said tests are carried by communicating with the deployed binary of the server, mocking this function
     // is only possible via feature flags.
     #[cfg(feature = "ci-test")]
-    let latest_timestamp = partial_request.proof_request.startAuctionTimestamp
-        - state
-            .base
-            .validation_configs()
-            .request
-            .base
-            .maximum_start_delay as u64;
-
-    let config = &state.validation_configs().request;
-
-    // check system id exists, skip full system validation
-    if !config
-        .base
-        .supported_systems
-        .contains(&partial_request.system_id)
-    {
-        return Err(ServerError::ValidationError("unsupported system id".into()));
-    }
+    let validation_time = ValidationTime::unadjusted(
+        partial_request.proof_request.startAuctionTimestamp
+            - config.base.maximum_start_delay as u64,
+    );
 
-    // complete partial valiation of non compressed fields in the intent
-    validate_market_address(
-        &partial_request.proof_request.market,
-        &state.universal_bombetta_address(),
-    )?;
-    validate_request_amount_constraints(
-        &partial_request.proof_request,
-        config.maximum_allowed_stake,
-    )?;
-    validate_time_constraints(
-        partial_request.proof_request.startAuctionTimestamp,
-        partial_request.proof_request.endAuctionTimestamp,
-        partial_request.proof_request.provingTime,
-        latest_timestamp,
-        config.base.minimum_proving_time,
-        config.base.maximum_start_delay,
-    )?;
-    validate_request_signature(&partial_request.proof_request, &partial_request.signature)?;
+    // check system id exists, skip full system validation, then complete partial validation of
+    // the remaining non compressed fields in the intent as the offline verification of intents
+    // does, reporting every failed check at once
+    ensure_no_violations(validation_time.violations(|latest_timestamp| {
+        request_commitment_violations(
+            &partial_request.proof_request,
+            &partial_request.signature,
+            partial_request.system_id,
+            latest_timestamp,
+            &config,
+        )
+    }))?;
 
     Ok(())
 }
 
 pub async fn validate_partial_offer<T: Transport + Clone, P: Provider<T> + Clone>(
     partial_offer: &PartialComputeOffer,
+    system: &SystemPayload,
     state: &OfferState<T, P>,
 ) -> Result<()> {
+    // validate against the config of the market the offer references, with the bounds of the
+    // offer's system
+    let config = match state.market_validator(&partial_offer.proof_offer.market)? {
+        MarketValidator::Offer(config) => config.for_system(partial_offer.system_id),
+        MarketValidator::Request(_) => {
+            return Err(ServerError::ValidationError(format!(
+                "market {} settles ComputeRequests, submit requests to /submit/request",
+                partial_offer.proof_offer.market
+            )))
+        }
+    };
+
+    // offers are proven by their own signer, there is no requester to deliver a key to
+    if let SystemPayload::Encrypted(_) = system {
+        return Err(ServerError::ValidationError(
+            "offers can't carry encrypted system params".into(),
+        ));
+    }
+    // the workload is checked locally, before fetching anything from the chain
+    validate_payload_system_id(partial_offer.system_id, system)?;
+    validate_payload_resources(
+        system,
+        partial_offer.proof_offer.provingTime,
+        &config.base.resource_limits,
+    )?;
+
     // TODO: separate this timestamp fetch from the validation execution of the server
     #[cfg(not(feature = "ci-test"))]
-    let latest_timestamp = get_latest_timestamp(state.rpc_provider()).await?;
+    let validation_time = fetch_validation_time(state.rpc_provider(), state.clock_skew()).await?;
 
     // We have some tests for the transport of data between submit/subscribe.
     // Since said tests are carried by communicating with the deployed binary of the server, mocking this function
     // is only possible via feature flags.
     #[cfg(feature = "ci-test")]
-    let latest_timestamp = partial_offer.proof_offer.startAuctionTimestamp
-        - state
-            .base
-            .validation_configs()
-            .offer
-            .base
-            .maximum_start_delay as u64;
-
-    let config = &state.validation_configs().offer;
-
-    // check system id exists, skip full system validation
-    if !config
-        .base
-        .supported_systems
-        .contains(&partial_offer.system_id)
-    {
-        return Err(ServerError::ValidationError("unsupported system id".into()));
-    }
+    let validation_time = ValidationTime::unadjusted(
+        partial_offer.proof_offer.startAuctionTimestamp - config.base.maximum_start_delay as u64,
+    );
 
-    // complete partial valiation of non compressed fields in the intent
-    validate_market_address(
-        &partial_offer.proof_offer.market,
-        &state.universal_porchetta_address(),
-    )?;
-    validate_offer_amount_constraints(
-        &partial_offer.proof_offer,
-        config.maximum_allowed_reward,
-        config.minimum_allowed_stake,
-    )?;
-    validate_time_constraints(
-        partial_offer.proof_offer.startAuctionTimestamp,
-        partial_offer.proof_offer.endAuctionTimestamp,
-        partial_offer.proof_offer.provingTime,
-        latest_timestamp,
-        config.base.minimum_proving_time,
-        config.base.maximum_start_delay,
-    )?;
-    validate_offer_signature(&partial_offer.proof_offer, &partial_offer.signature)?;
+    // check system id exists, skip full system validation, then complete partial validation of
+    // the remaining non compressed fields in the intent as the offline verification of intents
+    // does, reporting every failed check at once
+    ensure_no_violations(validation_time.violations(|latest_timestamp| {
+        offer_commitment_violations(
+            &partial_offer.proof_offer,
+            &partial_offer.signature,
+            partial_offer.system_id,
+            latest_timestamp,
+            &config,
+        )
+    }))?;
 
     Ok(())
 }
 
+/// Check the params of a submitted system belong to the system the intent declares
+fn validate_payload_system_id(system_id: SystemId, system: &SystemPayload) -> Result<()> {
+    validate_system_id(system_id, system.system_id()).map_err(rejection)
+}
+
+/// Check the workload declared by a submitted system stays within the limits of its system, the
+/// workload of encrypted systems can't be inspected
+fn validate_payload_resources(
+    system: &SystemPayload,
+    proving_time: u32,
+    resource_limits: &ResourceLimits,
+) -> Result<()> {
+    match system {
+        SystemPayload::Plain(system) => {
+            validate_system_resources(system, proving_time, resource_limits).map_err(rejection)
+        }
+        SystemPayload::Encrypted(_) => Ok(()),
+    }
+}
+
+/// Answer failed checks of the submitted intent with their reason, rather than as internal errors
+fn rejection(e: PrimitivesError) -> ServerError {
+    match e {
+        PrimitivesError::ValidationError(reason) => ServerError::ValidationError(reason),
+        PrimitivesError::ValidationViolation(violation) => {
+            ServerError::ValidationViolations(vec![violation])
+        }
+        e => e.into(),
+    }
+}
+
+/// Answer every failed check at once, ordered as the checks are
+fn ensure_no_violations(violations: Vec<Violation>) -> Result<()> {
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(ServerError::ValidationViolations(violations))
+    }
+}
+
+/// Timestamp the time constraints of submitted intents are validated against
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidationTime {
+    /// timestamp of the latest block fetched
+    pub block_timestamp: u64,
+    /// block timestamp caught up with the wall clock as the clock skew tolerance allows
+    pub adjusted: u64,
+}
+
+impl ValidationTime {
+    #[must_use]
+    pub fn new(block_timestamp: u64, wall_clock: u64, clock_skew: &ClockSkewConfig) -> Self {
+        Self {
+            block_timestamp,
+            adjusted: clock_skew.adjust(block_timestamp, wall_clock),
+        }
+    }
+
+    /// Validation time taking the block timestamp as is
+    #[must_use]
+    pub fn unadjusted(block_timestamp: u64) -> Self {
+        Self {
+            block_timestamp,
+            adjusted: block_timestamp,
+        }
+    }
+
+    /// Violations of an intent at the adjusted timestamp, as listed by `violations`. Logs the
+    /// intents the adjustment turned from rejected to accepted, or the other way around.
+    pub fn violations(&self, violations: impl Fn(u64) -> Vec<Violation>) -> Vec<Violation> {
+        let adjusted = violations(self.adjusted);
+        if self.adjusted != self.block_timestamp {
+            let unadjusted = violations(self.block_timestamp);
+            if adjusted.is_empty() != unadjusted.is_empty() {
+                tracing::info!(
+                    "clock skew adjustment of block timestamp {} to {} {} the intent",
+                    self.block_timestamp,
+                    self.adjusted,
+                    if adjusted.is_empty() {
+                        "accepted"
+                    } else {
+                        "rejected"
+                    }
+                );
+            }
+        }
+        adjusted
+    }
+}
+
+/// Fetch the latest block timestamp, adjusted for the clock skew tolerated
+#[allow(dead_code)]
+async fn fetch_validation_time<P: Provider<T, Ethereum> + Clone, T: Transport + Clone>(
+    provider: P,
+    clock_skew: &ClockSkewConfig,
+) -> Result<ValidationTime> {
+    let block_timestamp = get_latest_timestamp(provider).await?;
+    Ok(ValidationTime::new(
+        block_timestamp,
+        unix_timestamp(),
+        clock_skew,
+    ))
+}
+
 #[allow(dead_code)]
 async fn get_latest_timestamp<P: Provider<T, Ethereum> + Clone, T: Transport + Clone>(
     provider: P,
@@ -0,0 +1,59 @@
+use bytes::Bytes;
+use taralli_primitives::systems::SystemId;
+use taralli_server::subscription_manager::{
+    BroadcastKind, BroadcastedMessage, SubscriptionManager,
+};
+
+const SUBSCRIBERS: usize = 100;
+const PAYLOAD_SIZE: usize = 2 * 1024 * 1024;
+
+/// Payload about the size of a large arkworks request, not all the same byte so copies can't be
+/// mistaken for the original
+fn payload() -> Vec<u8> {
+    (0..PAYLOAD_SIZE).map(|i| (i % 251) as u8).collect()
+}
+
+/// `message` as each of `SUBSCRIBERS` subscribers of `manager` receives it
+async fn fan_out<M: Clone>(manager: &SubscriptionManager<M>, message: M) -> Vec<M> {
+    let mut subscriptions: Vec<_> = (0..SUBSCRIBERS)
+        .map(|_| manager.add_subscription())
+        .collect();
+    manager.broadcast(message).unwrap();
+    let mut received = Vec::with_capacity(SUBSCRIBERS);
+    for subscription in &mut subscriptions {
+        received.push(subscription.recv().await.unwrap());
+    }
+    received
+}
+
+#[tokio::test]
+/// Ensures every subscriber receives the broadcast content unchanged and sharing the buffer of the
+/// broadcast message rather than a copy of it. The cost of copying is measured by the `broadcast`
+/// bench.
+async fn should_share_broadcast_content_across_subscribers() {
+    let payload = payload();
+
+    let shared_manager = SubscriptionManager::<BroadcastedMessage>::new(4);
+    let content = Bytes::from(payload.clone());
+    let deflated = Bytes::from(payload[..PAYLOAD_SIZE / 2].to_vec());
+    let received = fan_out(
+        &shared_manager,
+        BroadcastedMessage {
+            content: content.clone(),
+            subscribed_to: SystemId::Arkworks.as_bit(),
+            kind: BroadcastKind::Intent,
+            deflated: Some(deflated.clone()),
+            meta: None,
+        },
+    )
+    .await;
+
+    assert_eq!(received.len(), SUBSCRIBERS);
+    for message in &received {
+        assert_eq!(message.content, payload);
+        assert_eq!(message.content.as_ptr(), content.as_ptr());
+        let message_deflated = message.deflated.as_ref().unwrap();
+        assert_eq!(message_deflated, &deflated);
+        assert_eq!(message_deflated.as_ptr(), deflated.as_ptr());
+    }
+}
@@ -0,0 +1,190 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{routing::get, Router};
+use futures::StreamExt;
+use rstest::*;
+use serial_test::serial;
+use taralli_client::api::subscribe::{StreamItem, SubscribeApiClient};
+use taralli_client::metrics::DeliveryMetrics;
+use taralli_primitives::alloy::{primitives::address, providers::ProviderBuilder};
+use taralli_primitives::compression_utils::{
+    compression,
+    frames::deflate_request_frame,
+    intents::{ComputeRequestCompressed, PartialComputeRequest},
+};
+use taralli_primitives::intents::envelope::{unix_time_ms, BroadcastMeta};
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::markets::SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS;
+use taralli_primitives::systems::SystemParams;
+use taralli_server::{
+    config::{Markets, ServerValidationConfigs},
+    routes::subscribe::websocket_subscribe_handler,
+    state::{request::RequestState, BaseState},
+    subscription_manager::{BroadcastKind, BroadcastedMessage, SubscriptionManager},
+};
+use tokio::net::TcpListener;
+use url::Url;
+pub mod common;
+use crate::common::fixtures::risc0_request_fixture;
+
+/// time the broadcasts of the tests are stamped as received before being sent
+const SUBMISSION_LAG: Duration = Duration::from_millis(150);
+
+/// Serve the subscription route of a server broadcasting through `subscription_manager`
+async fn serve_subscriptions(subscription_manager: Arc<SubscriptionManager>) -> Url {
+    let base_state = BaseState::new(
+        ProviderBuilder::new().on_http("http://localhost:8545".parse().unwrap()),
+        Markets {
+            universal_bombetta: SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+            universal_porchetta: address!("0000000000000000000000000000000000000001"),
+        },
+        Duration::from_secs(10),
+        ServerValidationConfigs {
+            request: Default::default(),
+            offer: Default::default(),
+        },
+    );
+    let app = Router::new()
+        .route("/subscribe", get(websocket_subscribe_handler))
+        .with_state(RequestState::new(base_state, subscription_manager));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let server_url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("Couldn't serve");
+    });
+    server_url
+}
+
+/// Broadcast `request` the way the submission handler does, stamped as received `SUBMISSION_LAG`
+/// ago
+fn broadcast_request(
+    subscription_manager: &SubscriptionManager,
+    request: &ComputeRequest<SystemParams>,
+) -> BroadcastMeta {
+    let system = compression::compress_brotli(&serde_json::to_vec(&request.system).unwrap())
+        .expect("Couldn't compress system");
+    let request_compressed = ComputeRequestCompressed::from((
+        PartialComputeRequest {
+            system_id: request.system_id,
+            proof_request: request.proof_request.clone(),
+            signature: request.signature,
+        },
+        system,
+    ));
+    let received_at_ms = unix_time_ms() - SUBMISSION_LAG.as_millis() as u64;
+    let mut stamped = None;
+    subscription_manager
+        .broadcast_sequenced(|sequence| {
+            let meta = BroadcastMeta {
+                sequence,
+                received_at_ms,
+            };
+            stamped = Some(meta);
+            Ok(BroadcastedMessage {
+                content: bincode::serialize(&request_compressed).unwrap().into(),
+                subscribed_to: request.system_id.as_bit(),
+                kind: BroadcastKind::Intent,
+                deflated: Some(
+                    deflate_request_frame(&request_compressed, meta)
+                        .unwrap()
+                        .into(),
+                ),
+                meta: Some(meta),
+            })
+        })
+        .expect("Couldn't broadcast");
+    stamped.unwrap()
+}
+
+#[test]
+/// Ensures each broadcast gets the sequence number following the previous one, concurrent
+/// broadcasts reaching the subscribers in sequence order.
+fn should_assign_increasing_sequences() {
+    let subscription_manager: Arc<SubscriptionManager> = Arc::new(SubscriptionManager::new(400));
+    let mut receiver = subscription_manager.add_subscription();
+
+    let broadcasters: Vec<_> = (0..8)
+        .map(|_| {
+            let subscription_manager = subscription_manager.clone();
+            std::thread::spawn(move || {
+                for _ in 0..50 {
+                    subscription_manager
+                        .broadcast_sequenced(|sequence| {
+                            Ok(BroadcastedMessage {
+                                content: Default::default(),
+                                subscribed_to: 0,
+                                kind: BroadcastKind::Intent,
+                                deflated: None,
+                                meta: Some(BroadcastMeta {
+                                    sequence,
+                                    received_at_ms: 0,
+                                }),
+                            })
+                        })
+                        .unwrap();
+                }
+            })
+        })
+        .collect();
+    for broadcaster in broadcasters {
+        broadcaster.join().unwrap();
+    }
+
+    let sequences: Vec<u64> = (0..400)
+        .map(|_| receiver.try_recv().unwrap().meta.unwrap().sequence)
+        .collect();
+    assert_eq!(sequences, (0..400).collect::<Vec<u64>>());
+    assert_eq!(subscription_manager.next_sequence(), 400);
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures subscribers receive the sequence and receive time each request was stamped with,
+/// deflated or not, and that the delivery delay is computed from them.
+async fn should_surface_broadcast_meta(risc0_request_fixture: ComputeRequest<SystemParams>) {
+    let subscription_manager = Arc::new(SubscriptionManager::new(10).with_frame_compression(true));
+    let server_url = serve_subscriptions(subscription_manager.clone()).await;
+
+    let system_id = risc0_request_fixture.system_id;
+    let mut deflating = SubscribeApiClient::new(server_url.clone(), system_id.as_bit())
+        .with_frame_compression(true)
+        .subscribe()
+        .await
+        .unwrap();
+    let mut plain = SubscribeApiClient::new(server_url, system_id.as_bit())
+        .subscribe()
+        .await
+        .unwrap();
+    // give the server time to register the upgraded connections
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let stamped: Vec<BroadcastMeta> = (0..3)
+        .map(|_| broadcast_request(&subscription_manager, &risc0_request_fixture))
+        .collect();
+
+    for subscription in [&mut deflating, &mut plain] {
+        let metrics = DeliveryMetrics::default();
+        let mut sequences = Vec::new();
+        for meta in &stamped {
+            let item = tokio::time::timeout(Duration::from_secs(5), subscription.next())
+                .await
+                .expect("No request received")
+                .expect("Subscription ended");
+            let delivery = match item {
+                Ok(StreamItem::Request(_, Some(delivery))) => delivery,
+                other => panic!("Unexpected item: {other:?}"),
+            };
+            assert_eq!(delivery.server_received_at_ms, meta.received_at_ms);
+            assert!(delivery.delay() >= SUBMISSION_LAG, "{delivery:?}");
+            metrics.record(&delivery);
+            sequences.push(delivery.sequence);
+        }
+        assert!(sequences.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(metrics.delivered(), 3);
+        assert_eq!(metrics.last_sequence(), sequences.last().copied());
+        assert!(metrics.mean_delay() >= SUBMISSION_LAG);
+        assert!(metrics.max_delay() >= metrics.mean_delay());
+    }
+}
@@ -0,0 +1,317 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    http::{header::CONTENT_TYPE, Request},
+    routing::post,
+    Router,
+};
+use hyper::StatusCode;
+use serde_json::{json, Value};
+use taralli_client::error::ClientError;
+use taralli_client::intent_builder::request::ComputeRequestBuilder;
+use taralli_client::intent_builder::IntentBuilder;
+use taralli_primitives::{
+    abi::universal_bombetta::VerifierDetails,
+    alloy::{
+        network::Ethereum,
+        primitives::{address, fixed_bytes, Address, B256, U256},
+        providers::{ProviderBuilder, RootProvider},
+        sol_types::SolValue,
+        transports::http::{Client, Http},
+    },
+    compression_utils::{compression, intents::PartialComputeRequest},
+    intents::request::ComputeRequest,
+    markets::SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+    systems::{SystemId, SystemParams},
+    validation::{
+        offline::verify_intent_offline,
+        request::{RequestValidationConfig, RequestVerifierConstraints},
+        violation::{ValidationRule, Violation},
+        BaseValidationConfig,
+    },
+};
+use taralli_server::{
+    config::{Markets, ServerValidationConfigs},
+    routes::submit::submit_request_handler,
+    state::{request::RequestState, BaseState},
+    subscription_manager::SubscriptionManager,
+};
+use tower::ServiceExt;
+
+pub mod common;
+use crate::common::rpc::{block_json, method_not_found, start_stub_node};
+
+const BOUNDARY: &str = "taralli-test-boundary";
+const SIGNER_ADDRESS: Address = address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+const LATEST_TIMESTAMP: u64 = 1_000;
+
+/// Start a stub JSON-RPC node whose latest block has the timestamp `LATEST_TIMESTAMP`
+async fn start_block_node() -> reqwest::Url {
+    start_stub_node(|request| match request["method"].as_str().unwrap() {
+        "eth_getBlockByNumber" => json!({ "result": block_json(LATEST_TIMESTAMP) }),
+        _ => method_not_found(),
+    })
+    .await
+}
+
+fn validation_config() -> RequestValidationConfig {
+    RequestValidationConfig {
+        base: BaseValidationConfig::default(),
+        maximum_allowed_stake: 1_000,
+        ..Default::default()
+    }
+}
+
+/// Server validating requests with `validation_config` against the chain of the node at `url`
+fn app(url: reqwest::Url) -> Router {
+    let base_state = BaseState::new(
+        ProviderBuilder::new().on_http(url),
+        Markets {
+            universal_bombetta: SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+            universal_porchetta: address!("0000000000000000000000000000000000000001"),
+        },
+        std::time::Duration::from_secs(10),
+        ServerValidationConfigs {
+            request: validation_config(),
+            offer: Default::default(),
+        },
+    );
+    Router::new()
+        .route("/submit", post(submit_request_handler))
+        .with_state(RequestState::new(
+            base_state,
+            Arc::new(SubscriptionManager::new(2)),
+        ))
+}
+
+/// Unsigned request builder whose only violation is a stake of `minimum_stake` above the
+/// maximum allowed one
+fn builder(
+    url: reqwest::Url,
+    minimum_stake: u128,
+) -> ComputeRequestBuilder<Http<Client>, RootProvider<Http<Client>>, Ethereum> {
+    let extra_data = VerifierDetails {
+        verifier: address!("AC292cF957Dd5BA174cdA13b05C16aFC71700327"),
+        selector: fixed_bytes!("ab750e75"),
+        isShaCommitment: false,
+        inputsOffset: U256::from(32),
+        inputsLength: U256::from(64),
+        hasPartialCommitmentResultCheck: false,
+        submittedPartialCommitmentResultOffset: U256::ZERO,
+        submittedPartialCommitmentResultLength: U256::ZERO,
+        predeterminedPartialCommitment: B256::ZERO,
+    }
+    .abi_encode();
+
+    ComputeRequestBuilder::new(
+        ProviderBuilder::new().on_http(url),
+        SIGNER_ADDRESS,
+        SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+        SystemId::Risc0,
+    )
+    .system(json!({ "elf": [1, 2, 3], "inputs": vec![0u8; 32] }))
+    .nonce(U256::from(7))
+    .set_time_params(LATEST_TIMESTAMP, LATEST_TIMESTAMP + 60, 120)
+    .set_token_params(minimum_stake, U256::from(10), U256::from(1001))
+    .set_verification_commitment_params(B256::ZERO, extra_data.into())
+}
+
+fn submit_request(request: &ComputeRequest<SystemParams>) -> Request<Body> {
+    let partial = serde_json::to_vec(&PartialComputeRequest {
+        system_id: request.system_id,
+        proof_request: request.proof_request.clone(),
+        signature: request.signature,
+    })
+    .unwrap();
+    let system =
+        compression::compress_brotli(&serde_json::to_vec(&request.system).unwrap()).unwrap();
+
+    let mut body = Vec::new();
+    for (name, data) in [("partial_request", &partial), ("system_bytes", &system)] {
+        body.extend_from_slice(
+            format!("--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n")
+                .as_bytes(),
+        );
+        body.extend_from_slice(data);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{BOUNDARY}--\r\n").as_bytes());
+
+    Request::builder()
+        .method("POST")
+        .uri("/submit")
+        .header(
+            CONTENT_TYPE,
+            format!("multipart/form-data; boundary={BOUNDARY}"),
+        )
+        .body(Body::from(body))
+        .unwrap()
+}
+
+/// Submit `request` to the server, returning the response it is rejected with
+async fn server_response(
+    url: reqwest::Url,
+    request: &ComputeRequest<SystemParams>,
+) -> reqwest::Response {
+    let response = app(url).oneshot(submit_request(request)).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    axum::http::Response::builder()
+        .status(status)
+        .body(body)
+        .unwrap()
+        .into()
+}
+
+/// Submit `request` to the server, returning the status and error it is rejected with
+async fn server_rejection(
+    url: reqwest::Url,
+    request: &ComputeRequest<SystemParams>,
+) -> (StatusCode, String) {
+    let response = server_response(url, request).await;
+    let status = response.status();
+    let body: Value = response.json().await.unwrap();
+    (
+        status,
+        body["error"].as_str().unwrap_or_default().to_string(),
+    )
+}
+
+#[tokio::test]
+/// Ensures a request the builder validation turns down is rejected by the server for the same
+/// reason.
+async fn should_reject_like_the_server() {
+    let url = start_block_node().await;
+    let builder = builder(url.clone(), 1_001);
+
+    let violations = match builder
+        .validate_against(&validation_config(), &RequestVerifierConstraints::default())
+        .await
+    {
+        Err(ClientError::ValidationViolations(violations)) => violations,
+        other => panic!("expected validation violations, got {other:?}"),
+    };
+    assert_eq!(violations, vec!["eth stake amount invalid"]);
+
+    let (status, error) = server_rejection(url, &builder.build().unwrap()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(error, violations[0]);
+}
+
+#[tokio::test]
+/// Ensures a request the builder validation lets through is only rejected by the server for
+/// lacking a signature.
+async fn should_accept_like_the_server() {
+    let url = start_block_node().await;
+    let builder = builder(url.clone(), 1_000);
+
+    builder
+        .validate_against(&validation_config(), &RequestVerifierConstraints::default())
+        .await
+        .expect("request should pass validation");
+
+    // the built request carries a placeholder signature
+    let (status, error) = server_rejection(url, &builder.build().unwrap()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(
+        error.starts_with("signature invalid") || error.starts_with("ec recover failed"),
+        "{error}"
+    );
+}
+
+#[tokio::test]
+/// Ensures every check a request fails is reported by the server with its rule and the observed
+/// and allowed values, and parsed back as such by the client.
+async fn should_report_every_violation_with_its_rule() {
+    let url = start_block_node().await;
+    let builder =
+        builder(url.clone(), 1_001).set_time_params(LATEST_TIMESTAMP, LATEST_TIMESTAMP + 60, 10);
+    let request = builder.build().unwrap();
+
+    let response = server_response(url, &request).await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let (message, violations) = match ClientError::from_server_response(response).await {
+        ClientError::ServerRejected {
+            ref message,
+            ref violations,
+            ..
+        } => (message.clone(), violations.clone()),
+        other => panic!("expected a server rejection, got {other:?}"),
+    };
+
+    assert_eq!(violations.len(), 3, "{violations:?}");
+    assert_eq!(
+        violations[0],
+        Violation::new(ValidationRule::ProvingTime, "proving time too low")
+            .observed(10)
+            .allowed(">= 30")
+    );
+    assert_eq!(
+        violations[1],
+        Violation::new(ValidationRule::StakeBound, "eth stake amount invalid")
+            .observed(1_001)
+            .allowed("<= 1000")
+    );
+    // the built request carries a placeholder signature
+    assert_eq!(violations[2].rule, ValidationRule::Signature);
+    assert_eq!(message, violations[0].message);
+
+    // the builder reports the same checks, apart from the signature it doesn't validate
+    let mut expected = match builder
+        .validate_against(&validation_config(), &RequestVerifierConstraints::default())
+        .await
+    {
+        Err(ClientError::ValidationViolations(violations)) => violations,
+        other => panic!("expected validation violations, got {other:?}"),
+    };
+    let mut reported: Vec<String> = violations[..2]
+        .iter()
+        .map(|violation| violation.message.clone())
+        .collect();
+    expected.sort();
+    reported.sort();
+    assert_eq!(reported, expected);
+}
+
+#[tokio::test]
+/// Ensures requests rejected by the server are rejected with the same violations by the offline
+/// verification of intents.
+async fn should_reject_like_the_offline_verification() {
+    let url = start_block_node().await;
+    let max_proving_time = BaseValidationConfig::default().maximum_proving_time;
+    let requests = [
+        builder(url.clone(), 1_001),
+        builder(url.clone(), 1_001).set_time_params(LATEST_TIMESTAMP, LATEST_TIMESTAMP + 60, 10),
+        builder(url.clone(), 1_000).set_time_params(
+            LATEST_TIMESTAMP,
+            LATEST_TIMESTAMP + 60,
+            max_proving_time + 1,
+        ),
+        builder(url.clone(), 1_000).set_time_params(LATEST_TIMESTAMP, LATEST_TIMESTAMP + 5, 120),
+        // only failing the signature check, the built request carrying a placeholder signature
+        builder(url.clone(), 1_000),
+    ]
+    .map(|builder| builder.build().unwrap());
+
+    for request in &requests {
+        let response = server_response(url.clone(), request).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let violations = match ClientError::from_server_response(response).await {
+            ClientError::ServerRejected { violations, .. } => violations,
+            other => panic!("expected a server rejection, got {other:?}"),
+        };
+
+        let offline = verify_intent_offline(
+            request,
+            LATEST_TIMESTAMP,
+            &SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+            &validation_config(),
+            &RequestVerifierConstraints::default(),
+        );
+        assert!(!offline.is_empty());
+        assert_eq!(offline, violations);
+    }
+}
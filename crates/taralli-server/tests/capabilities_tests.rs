@@ -0,0 +1,211 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    response::IntoResponse,
+    routing::{get, post, put},
+    Router,
+};
+use hyper::StatusCode;
+use serde_json::{json, Value};
+use serial_test::serial;
+use taralli_client::api::{capabilities::CapabilitiesApiClient, status::StatusApiClient};
+use taralli_client::client::provider::capabilities::CapabilityAdvertiser;
+use taralli_primitives::alloy::{primitives::U256, signers::local::PrivateKeySigner};
+use taralli_primitives::capabilities::{
+    CapabilitySummary, ProviderCapabilities, SignedCapabilities, SystemCapability,
+};
+use taralli_primitives::systems::SystemId;
+use taralli_server::{
+    capabilities::CapabilityRegistry,
+    routes::{
+        capabilities::{refresh_capabilities_handler, register_capabilities_handler},
+        status::get_status_handler,
+    },
+    state::request::RequestState,
+    subscription_manager::SubscriptionManager,
+};
+
+pub mod common;
+use crate::common::fixtures::{base_state_fixture, start_server};
+
+/// Capabilities of a provider proving arkworks requests in `proving_time` seconds for at least
+/// `minimum_reward`
+fn arkworks_capabilities(
+    signer: &PrivateKeySigner,
+    proving_time: u32,
+    minimum_reward: u64,
+    issued_at: u64,
+) -> ProviderCapabilities {
+    ProviderCapabilities {
+        provider: signer.address(),
+        systems: vec![SystemCapability {
+            system_id: SystemId::Arkworks,
+            typical_proving_time: proving_time,
+            minimum_reward: U256::from(minimum_reward),
+        }],
+        max_concurrent_jobs: 2,
+        active_jobs: 0,
+        issued_at,
+    }
+}
+
+/// Record of an arkworks provider signed by itself
+async fn signed(signer: &PrivateKeySigner, issued_at: u64) -> SignedCapabilities {
+    SignedCapabilities::sign(arkworks_capabilities(signer, 60, 100, issued_at), signer)
+        .await
+        .unwrap()
+}
+
+/// Serve the capability and status routes
+fn capabilities_app() -> Router {
+    let base_state = base_state_fixture("http://localhost:8545".parse().unwrap());
+    Router::new()
+        .route("/status", get(get_status_handler))
+        .route("/capabilities", post(register_capabilities_handler))
+        .route("/capabilities/:provider", put(refresh_capabilities_handler))
+        .with_state(RequestState::new(
+            base_state,
+            Arc::new(SubscriptionManager::new(2)),
+        ))
+}
+
+#[tokio::test]
+#[serial]
+/// Ensures the capabilities two providers advertise are aggregated per system in `/status`, follow
+/// the jobs the providers report, and can't be forged or rolled back.
+async fn should_aggregate_advertised_capabilities() {
+    let server_url = start_server(capabilities_app()).await;
+    let status_client = StatusApiClient::new(server_url.clone());
+    let api = CapabilitiesApiClient::new(server_url.clone());
+
+    let fast = PrivateKeySigner::random();
+    let fast_advertiser = CapabilityAdvertiser::new(
+        server_url.clone(),
+        fast.clone(),
+        arkworks_capabilities(&fast, 60, 500, 0).systems,
+        2,
+    );
+    fast_advertiser.register().await.unwrap();
+    let cheap = PrivateKeySigner::random();
+    let cheap_record =
+        SignedCapabilities::sign(arkworks_capabilities(&cheap, 120, 100, 1_000), &cheap)
+            .await
+            .unwrap();
+    api.register(&cheap_record).await.unwrap();
+
+    let status = status_client.fetch_status().await.unwrap();
+    assert_eq!(
+        status.capabilities(SystemId::Arkworks),
+        CapabilitySummary {
+            registered_providers: 2,
+            available_slots: 4,
+            fastest_proving_time: Some(60),
+            lowest_minimum_reward: Some(U256::from(100)),
+        }
+    );
+    assert_eq!(
+        status.capabilities(SystemId::Risc0),
+        CapabilitySummary::default()
+    );
+
+    // a started job is reported on the next refresh
+    let job = fast_advertiser.track_job();
+    fast_advertiser.refresh().await.unwrap();
+    let status = status_client.fetch_status().await.unwrap();
+    assert_eq!(status.capabilities(SystemId::Arkworks).available_slots, 3);
+    drop(job);
+    assert_eq!(fast_advertiser.active_jobs(), 0);
+
+    // nobody else can advertise for a provider, nor replay an older record
+    let forged = SignedCapabilities::sign(
+        arkworks_capabilities(&cheap, 1, 0, 2_000),
+        &PrivateKeySigner::random(),
+    )
+    .await
+    .unwrap();
+    let err = api.refresh(&forged).await.unwrap_err();
+    assert!(err.to_string().contains("400"), "{err}");
+    let older = SignedCapabilities::sign(arkworks_capabilities(&cheap, 1, 0, 999), &cheap)
+        .await
+        .unwrap();
+    let err = api.refresh(&older).await.unwrap_err();
+    assert!(err.to_string().contains("older"), "{err}");
+
+    let status = status_client.fetch_status().await.unwrap();
+    assert_eq!(
+        status.capabilities(SystemId::Arkworks).fastest_proving_time,
+        Some(60)
+    );
+}
+
+#[tokio::test]
+/// Ensures a request no advertising provider takes is flagged with the bound it misses, unless
+/// unadvertised subscribers might take it.
+async fn should_explain_requests_no_provider_accepts() {
+    let registry = CapabilityRegistry::default();
+    for (proving_time, minimum_reward) in [(60, 500), (120, 100)] {
+        let signer = PrivateKeySigner::random();
+        let record = arkworks_capabilities(&signer, proving_time, minimum_reward, 0);
+        registry
+            .upsert(SignedCapabilities::sign(record, &signer).await.unwrap())
+            .unwrap();
+    }
+
+    assert_eq!(
+        registry
+            .unaccepted_reason(SystemId::Arkworks, 30, U256::from(1_000), 2)
+            .unwrap(),
+        "no providers accept arkworks with proving_time < 60s"
+    );
+    assert_eq!(
+        registry
+            .unaccepted_reason(SystemId::Arkworks, 600, U256::from(50), 2)
+            .unwrap(),
+        "no providers accept arkworks with max reward < 100"
+    );
+    // the fast provider wants more than the slow one
+    assert!(registry
+        .unaccepted_reason(SystemId::Arkworks, 60, U256::from(100), 2)
+        .is_some());
+
+    // one of them takes it
+    assert!(registry
+        .unaccepted_reason(SystemId::Arkworks, 60, U256::from(500), 2)
+        .is_none());
+    // a third, unadvertised subscriber might take it
+    assert!(registry
+        .unaccepted_reason(SystemId::Arkworks, 30, U256::from(1_000), 3)
+        .is_none());
+    // nobody advertised risc0
+    assert!(registry
+        .unaccepted_reason(SystemId::Risc0, 1, U256::ZERO, 2)
+        .is_none());
+}
+
+#[tokio::test]
+/// Ensures the registry turns new providers away once full, refreshes known ones, and makes
+/// room again as records expire.
+async fn should_bound_capability_records() {
+    let registry = CapabilityRegistry::new(Duration::from_millis(200)).with_max_records(2);
+    let signers: Vec<PrivateKeySigner> = (0..3).map(|_| PrivateKeySigner::random()).collect();
+
+    registry.upsert(signed(&signers[0], 0).await).unwrap();
+    registry.upsert(signed(&signers[1], 0).await).unwrap();
+    let err = registry.upsert(signed(&signers[2], 0).await).unwrap_err();
+    let response = err.into_response();
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    assert_eq!(
+        serde_json::from_slice::<Value>(&body).unwrap()["code"],
+        json!("capability_registry_full")
+    );
+    // recorded providers keep refreshing
+    registry.upsert(signed(&signers[0], 1).await).unwrap();
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    registry.upsert(signed(&signers[2], 0).await).unwrap();
+    assert_eq!(registry.live().len(), 1);
+}
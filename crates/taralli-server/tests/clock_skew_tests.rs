@@ -0,0 +1,120 @@
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta::ProofRequest;
+use taralli_primitives::alloy::primitives::{address, Address, Bytes, B256, U256};
+use taralli_primitives::validation::violation::{ValidationRule, Violation};
+use taralli_primitives::validation::{
+    collect_violations, validate_commitment_time_constraints, BaseValidationConfig,
+};
+use taralli_server::config::ClockSkewConfig;
+use taralli_server::validation::ValidationTime;
+
+const WALL_CLOCK: u64 = 1_700_000_000;
+/// start delay tuned tight, below the lag of the rpc
+const MAXIMUM_START_DELAY: u32 = 10;
+const RPC_LAG: u64 = 30;
+
+fn clock_skew(tolerated_blocks: u64) -> ClockSkewConfig {
+    ClockSkewConfig {
+        block_time_seconds: 12,
+        tolerated_blocks,
+    }
+}
+
+fn proof_request(start_auction_timestamp: u64) -> ProofRequest {
+    ProofRequest {
+        signer: address!("1111111111111111111111111111111111111111"),
+        market: address!("2222222222222222222222222222222222222222"),
+        nonce: U256::ZERO,
+        rewardToken: Address::ZERO,
+        maxRewardAmount: U256::from(100),
+        minRewardAmount: U256::from(10),
+        minimumStake: 0,
+        startAuctionTimestamp: start_auction_timestamp,
+        endAuctionTimestamp: start_auction_timestamp + 60,
+        provingTime: 120,
+        inputsCommitment: B256::ZERO,
+        extraData: Bytes::new(),
+    }
+}
+
+/// Time constraint violations of a request starting at `start_auction_timestamp`, validated at
+/// `validation_time`
+fn violations(start_auction_timestamp: u64, validation_time: ValidationTime) -> Vec<Violation> {
+    let config = BaseValidationConfig {
+        maximum_start_delay: MAXIMUM_START_DELAY,
+        ..Default::default()
+    };
+    let request = proof_request(start_auction_timestamp);
+    validation_time.violations(|latest_timestamp| {
+        collect_violations([validate_commitment_time_constraints(
+            &request,
+            latest_timestamp,
+            &config,
+        )])
+    })
+}
+
+#[test]
+/// Ensures block timestamps only move towards the wall clock, by no more than the tolerated
+/// blocks.
+fn should_adjust_stale_block_timestamp_within_tolerance() {
+    let stale = WALL_CLOCK - RPC_LAG;
+
+    assert_eq!(clock_skew(0).adjust(stale, WALL_CLOCK), stale);
+    assert_eq!(clock_skew(3).adjust(stale, WALL_CLOCK), WALL_CLOCK);
+    // lagging more than tolerated
+    assert_eq!(clock_skew(1).adjust(stale, WALL_CLOCK), stale + 12);
+    // wall clock behind the chain
+    assert_eq!(clock_skew(3).adjust(WALL_CLOCK, stale), WALL_CLOCK);
+
+    let validation_time = ValidationTime::new(stale, WALL_CLOCK, &clock_skew(3));
+    assert_eq!(validation_time.block_timestamp, stale);
+    assert_eq!(validation_time.adjusted, WALL_CLOCK);
+}
+
+#[test]
+/// Ensures a request whose auction starts now is accepted despite a block timestamp staler than
+/// the maximum start delay once the lag is tolerated, while it was rejected before.
+fn should_accept_request_starting_now_despite_stale_block() {
+    let stale = WALL_CLOCK - RPC_LAG;
+
+    let rejected = violations(
+        WALL_CLOCK,
+        ValidationTime::new(stale, WALL_CLOCK, &clock_skew(0)),
+    );
+    assert_eq!(rejected.len(), 1);
+    assert_eq!(rejected[0].rule, ValidationRule::TimestampWindow);
+
+    assert!(violations(
+        WALL_CLOCK,
+        ValidationTime::new(stale, WALL_CLOCK, &clock_skew(3))
+    )
+    .is_empty());
+    // right at the start delay boundary of the wall clock
+    assert!(violations(
+        WALL_CLOCK + u64::from(MAXIMUM_START_DELAY),
+        ValidationTime::new(stale, WALL_CLOCK, &clock_skew(3))
+    )
+    .is_empty());
+}
+
+#[test]
+/// Ensures the tolerance doesn't admit requests starting later than the maximum start delay from
+/// the wall clock, nor catches up with a lag larger than tolerated.
+fn should_reject_genuinely_early_request() {
+    let stale = WALL_CLOCK - RPC_LAG;
+    let validation_time = ValidationTime::new(stale, WALL_CLOCK, &clock_skew(3));
+
+    let early = violations(
+        WALL_CLOCK + u64::from(MAXIMUM_START_DELAY) + 1,
+        validation_time,
+    );
+    assert_eq!(early.len(), 1);
+    assert_eq!(early[0].rule, ValidationRule::TimestampWindow);
+
+    let lagging = WALL_CLOCK - 60;
+    assert!(!violations(
+        WALL_CLOCK,
+        ValidationTime::new(lagging, WALL_CLOCK, &clock_skew(3))
+    )
+    .is_empty());
+}
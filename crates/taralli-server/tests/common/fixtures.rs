@@ -1,6 +1,7 @@
 use std::{fs::File, path::Path, str::FromStr};
 
 use axum::{
+    extract::DefaultBodyLimit,
     routing::{get, post},
     Router,
 };
@@ -11,30 +12,48 @@ use serde_json::Value;
 use std::sync::Arc;
 use taralli_client::api::{submit::SubmitApiClient, subscribe::SubscribeApiClient};
 use taralli_primitives::{
-    abi::universal_bombetta::UniversalBombetta::ProofRequest,
+    abi::{
+        universal_bombetta::UniversalBombetta::ProofRequest,
+        universal_porchetta::{UniversalPorchetta::ProofOffer, VerifierDetails},
+    },
     alloy::{
         primitives::{address, Address, FixedBytes, PrimitiveSignature, U256},
-        providers::ProviderBuilder,
+        providers::{ProviderBuilder, RootProvider},
         signers::{local::PrivateKeySigner, Signer},
         sol_types::SolValue,
+        transports::http::{Client, Http},
     },
-    intents::ComputeIntent,
+    commitment::CommitmentHasher,
+    intents::{offer::ComputeOffer, ComputeIntent},
     systems::{arkworks::ArkworksProofParams, risc0::Risc0ProofParams, ALL_SYSTEMS_MASK},
 };
 use taralli_primitives::{
     intents::request::ComputeRequest,
-    markets::SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+    markets::{SEPOLIA_CHAIN_ID, SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS},
     systems::{SystemId, SystemParams},
 };
 use taralli_server::{
-    config::{Markets, ServerValidationConfigs},
-    routes::{submit::submit_request_handler, subscribe::websocket_subscribe_handler},
-    state::{request::RequestState, BaseState},
+    config::{Markets, ServerValidationConfigs, SubmissionLimits},
+    postgres::Db,
+    routes::{
+        query::get_active_intents_by_id_handler,
+        status::get_status_handler,
+        submit::{submit_offer_handler, submit_request_handler},
+        subscribe::{sse_subscribe_handler, websocket_subscribe_handler},
+    },
+    state::{offer::OfferState, request::RequestState, BaseState},
     subscription_manager::{self, SubscriptionManager},
 };
+use tokio::net::TcpListener;
 use tower_http::trace::TraceLayer;
 use url::Url;
 
+/// universal porchetta market of the server fixtures
+pub const PORCHETTA_FIXTURE_ADDRESS: Address = address!("0000000000000000000000000000000000000001");
+
+/// Server state over a plain http provider
+pub type FixtureState = BaseState<Http<Client>, RootProvider<Http<Client>>>;
+
 const DUMMY_PRIV_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
 
 /// signature bytes used as placeholder before signing
@@ -72,7 +91,7 @@ pub fn risc0_request_fixture() -> ComputeRequest<SystemParams> {
     let proof_input = U256::from(1304);
     let inputs = proof_input.abi_encode();
     let elf = std::fs::read(risc0_guest_program_path).expect("Couldn't read elf");
-    let mut compute_request: ComputeRequest<SystemParams> = ComputeRequest {
+    let compute_request: ComputeRequest<SystemParams> = ComputeRequest {
         system_id: SystemId::Risc0,
         system: SystemParams::try_from((
             &SystemId::Risc0,
@@ -99,15 +118,27 @@ pub fn risc0_request_fixture() -> ComputeRequest<SystemParams> {
                 .unwrap()
                 .as_secs()
                 + 86400, // 1 day in the future
-            provingTime: u32::MAX,
+            provingTime: 3_600, // 1 hour
             inputsCommitment: FixedBytes::<32>::new([0u8; 32]),
             extraData: vec![].into(),
         },
         signature: signature_fixture(),
     };
 
-    let signer = PrivateKeySigner::from_str(DUMMY_PRIV_KEY).expect("Couldn't get priv key");
-    let permit2_digest = compute_request.compute_permit2_digest();
+    sign_request_fixture(compute_request)
+}
+
+/// Signer of the request fixtures
+pub fn requester_signer_fixture() -> PrivateKeySigner {
+    PrivateKeySigner::from_str(DUMMY_PRIV_KEY).expect("Couldn't get priv key")
+}
+
+/// Sign a request with the key of the fixtures, e.g. after changing its proof request
+pub fn sign_request_fixture(
+    mut compute_request: ComputeRequest<SystemParams>,
+) -> ComputeRequest<SystemParams> {
+    let signer = requester_signer_fixture();
+    let permit2_digest = compute_request.compute_permit2_digest(SEPOLIA_CHAIN_ID);
     let signature = signer
         .sign_hash(&permit2_digest)
         .now_or_never()
@@ -165,7 +196,7 @@ pub fn groth16_request_fixture() -> ComputeRequest<SystemParams> {
                 .unwrap()
                 .as_secs()
                 + 86400, // 1 day in the future
-            provingTime: u32::MAX,
+            provingTime: 3_600, // 1 hour
             inputsCommitment: FixedBytes::<32>::new([0u8; 32]),
             extraData: vec![].into(),
         },
@@ -173,7 +204,7 @@ pub fn groth16_request_fixture() -> ComputeRequest<SystemParams> {
     };
 
     let signer = PrivateKeySigner::from_str(DUMMY_PRIV_KEY).expect("Couldn't get priv key");
-    let permit2_digest = proof_request.compute_permit2_digest();
+    let permit2_digest = proof_request.compute_permit2_digest(SEPOLIA_CHAIN_ID);
     let signature = signer
         .sign_hash(&permit2_digest)
         .now_or_never()
@@ -184,6 +215,79 @@ pub fn groth16_request_fixture() -> ComputeRequest<SystemParams> {
     proof_request
 }
 
+#[fixture]
+/// Generate a compute offer to be sent to the server, mirroring `risc0_request_fixture`. Its
+/// `extraData` holds the abi encoded porchetta `VerifierDetails` committing to the sha256 of the
+/// inputs, as the market checks them when resolving.
+pub fn risc0_offer_fixture() -> ComputeOffer<SystemParams> {
+    let repo_root = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("Failed to get crates")
+        .parent()
+        .expect("Failed to get root");
+    let risc0_guest_program_path = repo_root.join("contracts/test-proof-data/risc0/is-even");
+
+    let proof_input = U256::from(1304);
+    let inputs = proof_input.abi_encode();
+    let elf = std::fs::read(risc0_guest_program_path).expect("Couldn't read elf");
+    let verifier_details = VerifierDetails {
+        verifier: address!("0000000000000000000000000000000000000002"),
+        selector: FixedBytes::<4>::new([0xab, 0x75, 0x0e, 0x75]),
+        isShaCommitment: true,
+        inputsOffset: U256::from(0),
+        inputsLength: U256::from(inputs.len()),
+    };
+    let inputs_commitment = CommitmentHasher::from_is_sha_commitment(true).hash(&inputs);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let compute_offer: ComputeOffer<SystemParams> = ComputeOffer {
+        system_id: SystemId::Risc0,
+        system: SystemParams::try_from((
+            &SystemId::Risc0,
+            serde_json::to_value(Risc0ProofParams { elf, inputs })
+                .unwrap()
+                .to_string()
+                .into_bytes(),
+        ))
+        .unwrap(),
+        proof_offer: ProofOffer {
+            signer: address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266"),
+            market: PORCHETTA_FIXTURE_ADDRESS,
+            nonce: U256::from(0u64),
+            rewardToken: Address::ZERO,
+            rewardAmount: U256::from(0),
+            stakeToken: Address::ZERO,
+            stakeAmount: U256::from(0),
+            startAuctionTimestamp: now,
+            endAuctionTimestamp: now + 86400, // 1 day in the future
+            provingTime: 3_600,               // 1 hour
+            inputsCommitment: inputs_commitment,
+            extraData: verifier_details.abi_encode().into(),
+        },
+        signature: signature_fixture(),
+    };
+
+    sign_offer_fixture(compute_offer)
+}
+
+/// Sign an offer with the key of the fixtures, e.g. after changing its proof offer
+pub fn sign_offer_fixture(
+    mut compute_offer: ComputeOffer<SystemParams>,
+) -> ComputeOffer<SystemParams> {
+    let signer = PrivateKeySigner::from_str(DUMMY_PRIV_KEY).expect("Couldn't get priv key");
+    let permit2_digest = compute_offer.compute_permit2_digest(SEPOLIA_CHAIN_ID);
+    let signature = signer
+        .sign_hash(&permit2_digest)
+        .now_or_never()
+        .expect("Couldn't sign offer async")
+        .expect("Couldn't sign offer");
+    compute_offer.signature = signature;
+
+    compute_offer
+}
+
 /// create dummy ECDSA signature
 #[must_use]
 pub fn signature_fixture() -> PrimitiveSignature {
@@ -207,7 +311,7 @@ pub fn setup_app() -> (Router, Arc<SubscriptionManager>) {
         rpc_provider.clone(),
         Markets {
             universal_bombetta: SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
-            universal_porchetta: address!("0000000000000000000000000000000000000001"),
+            universal_porchetta: PORCHETTA_FIXTURE_ADDRESS,
         },
         std::time::Duration::from_secs(10),
         ServerValidationConfigs {
@@ -221,8 +325,104 @@ pub fn setup_app() -> (Router, Arc<SubscriptionManager>) {
         Router::new()
             .route("/submit", post(submit_request_handler))
             .route("/subscribe", get(websocket_subscribe_handler))
+            .route("/subscribe/sse", get(sse_subscribe_handler))
+            .route("/status", get(get_status_handler))
             .with_state(request_state)
             .layer(TraceLayer::new_for_http()),
         subscription_manager,
     )
 }
+
+#[fixture]
+/// Server instance with tight submission limits, used to check each submission phase
+/// (body read, decompression) is rejected with its own error code.
+pub fn setup_limited_app() -> Router {
+    let rpc_provider =
+        ProviderBuilder::new().on_http(reqwest::Url::parse("http://localhost:8080").unwrap());
+    let subscription_manager: Arc<SubscriptionManager> =
+        subscription_manager::SubscriptionManager::new(2).into();
+
+    let base_state = BaseState::new(
+        rpc_provider,
+        Markets {
+            universal_bombetta: SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+            universal_porchetta: PORCHETTA_FIXTURE_ADDRESS,
+        },
+        std::time::Duration::from_secs(10),
+        ServerValidationConfigs {
+            request: Default::default(),
+            offer: Default::default(),
+        },
+    )
+    .with_submission_limits(SubmissionLimits {
+        body_read_timeout_seconds: 1,
+        max_body_size_bytes: 1024 * 1024,
+        decompression_timeout_seconds: 5,
+        max_decompressed_bytes: 1024 * 1024,
+        max_metadata_entries: 2,
+        max_metadata_bytes: 64,
+    });
+    let request_state = RequestState::new(base_state, subscription_manager);
+
+    Router::new()
+        .route(
+            "/submit",
+            post(submit_request_handler).layer(DefaultBodyLimit::disable()),
+        )
+        .with_state(request_state)
+}
+
+/// Server instance of the offer routes, storing the submitted offers in the postgres of
+/// `docker-compose.yml`
+pub async fn setup_offer_app() -> Router {
+    let rpc_provider =
+        ProviderBuilder::new().on_http(reqwest::Url::parse("http://localhost:8080").unwrap());
+
+    let base_state = BaseState::new(
+        rpc_provider,
+        Markets {
+            universal_bombetta: SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+            universal_porchetta: PORCHETTA_FIXTURE_ADDRESS,
+        },
+        std::time::Duration::from_secs(10),
+        ServerValidationConfigs {
+            request: Default::default(),
+            offer: Default::default(),
+        },
+    );
+    let offer_state = OfferState::new(base_state, Db::new().await);
+
+    Router::new()
+        .route(
+            "/submit/offer",
+            post(submit_offer_handler).layer(DefaultBodyLimit::disable()),
+        )
+        .route("/query/:system_id", get(get_active_intents_by_id_handler))
+        .with_state(offer_state)
+}
+
+/// Server state of the fixture markets with default validation, its provider pointed at `rpc_url`
+pub fn base_state_fixture(rpc_url: Url) -> FixtureState {
+    BaseState::new(
+        ProviderBuilder::new().on_http(rpc_url),
+        Markets {
+            universal_bombetta: SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+            universal_porchetta: PORCHETTA_FIXTURE_ADDRESS,
+        },
+        std::time::Duration::from_secs(10),
+        ServerValidationConfigs {
+            request: Default::default(),
+            offer: Default::default(),
+        },
+    )
+}
+
+/// Serve `app` on a free local port, returning its url
+pub async fn start_server(app: Router) -> Url {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("Couldn't serve");
+    });
+    url
+}
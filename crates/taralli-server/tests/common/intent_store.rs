@@ -0,0 +1,35 @@
+use taralli_primitives::{
+    abi::universal_bombetta::UniversalBombetta::ProofRequest,
+    alloy::primitives::{address, Address, PrimitiveSignature, B256, U256},
+    compression_utils::intents::ComputeRequestCompressed,
+    systems::SystemId,
+};
+
+pub const SIGNER: Address = address!("1111111111111111111111111111111111111111");
+
+/// Compressed request as the submit handler persists it, told apart by `nonce`
+pub fn compressed_request_fixture(
+    system_id: SystemId,
+    nonce: u64,
+    end_auction_timestamp: u64,
+) -> ComputeRequestCompressed {
+    ComputeRequestCompressed {
+        system_id,
+        system: vec![1, 2, 3],
+        proof_request: ProofRequest {
+            signer: SIGNER,
+            market: address!("2222222222222222222222222222222222222222"),
+            nonce: U256::from(nonce),
+            rewardToken: Address::ZERO,
+            maxRewardAmount: U256::from(100),
+            minRewardAmount: U256::from(10),
+            minimumStake: 1,
+            startAuctionTimestamp: 1_000,
+            endAuctionTimestamp: end_auction_timestamp,
+            provingTime: 30,
+            inputsCommitment: B256::ZERO,
+            extraData: vec![].into(),
+        },
+        signature: PrimitiveSignature::new(U256::from(1), U256::from(2), false),
+    }
+}
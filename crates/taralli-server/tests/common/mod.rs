@@ -1 +1,3 @@
 pub mod fixtures;
+pub mod intent_store;
+pub mod rpc;
@@ -0,0 +1,90 @@
+//! Stub JSON-RPC node the rpc providers of the tests are pointed at
+
+use serde_json::{json, Value};
+use taralli_primitives::alloy::primitives::{Address, B256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Start a stub JSON-RPC node answering each request with the `result` or `error` member
+/// `handler` returns given the request, returning its url
+pub async fn start_stub_node(handler: impl Fn(&Value) -> Value + Send + 'static) -> reqwest::Url {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap())
+        .parse()
+        .unwrap();
+
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let request = read_body(&mut stream).await;
+            let mut response = handler(&request);
+            response["jsonrpc"] = json!("2.0");
+            response["id"] = request["id"].clone();
+            let body = response.to_string();
+            let http = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(http.as_bytes()).await.unwrap();
+            stream.shutdown().await.unwrap();
+        }
+    });
+
+    url
+}
+
+/// Answer of a stub node to the methods it doesn't serve
+pub fn method_not_found() -> Value {
+    json!({ "error": { "code": -32601, "message": "method not found" } })
+}
+
+/// Read a whole request, returning its JSON body
+pub async fn read_body(stream: &mut TcpStream) -> Value {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 4096];
+    let header_end = loop {
+        let read = stream.read(&mut buffer).await.unwrap();
+        request.extend_from_slice(&buffer[..read]);
+        if let Some(end) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+            break end + 4;
+        }
+    };
+    let headers = String::from_utf8_lossy(&request[..header_end]).to_lowercase();
+    let content_length: usize = headers
+        .lines()
+        .find_map(|line| line.strip_prefix("content-length: "))
+        .expect("request without content-length")
+        .trim()
+        .parse()
+        .unwrap();
+    while request.len() < header_end + content_length {
+        let read = stream.read(&mut buffer).await.unwrap();
+        request.extend_from_slice(&buffer[..read]);
+    }
+    serde_json::from_slice(&request[header_end..]).unwrap()
+}
+
+/// Latest block as served by `eth_getBlockByNumber`, with the given timestamp
+pub fn block_json(timestamp: u64) -> Value {
+    json!({
+        "hash": B256::repeat_byte(0x11),
+        "parentHash": B256::ZERO,
+        "sha3Uncles": B256::ZERO,
+        "miner": Address::ZERO,
+        "stateRoot": B256::ZERO,
+        "transactionsRoot": B256::ZERO,
+        "receiptsRoot": B256::ZERO,
+        "logsBloom": format!("0x{}", "00".repeat(256)),
+        "difficulty": "0x0",
+        "number": "0x1",
+        "gasLimit": "0x1c9c380",
+        "gasUsed": "0x0",
+        "timestamp": format!("{timestamp:#x}"),
+        "extraData": "0x",
+        "mixHash": B256::ZERO,
+        "nonce": "0x0000000000000000",
+        "baseFeePerGas": "0x1",
+        "uncles": [],
+        "transactions": [],
+    })
+}
@@ -0,0 +1,231 @@
+use serde_json::{json, Value};
+use taralli_primitives::alloy::primitives::address;
+use taralli_primitives::systems::SystemId;
+use taralli_server::config::{layered_json, Config, ConfigError, REDACTED};
+
+const SHIPPED_CONFIG: &str = include_str!("../../../config.json");
+
+/// Write `config` to a file of its own, returning its path
+fn config_file(name: &str, config: &Value) -> String {
+    let path =
+        std::env::temp_dir().join(format!("taralli-config-{name}-{}.json", std::process::id()));
+    std::fs::write(&path, config.to_string()).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+fn shipped() -> Value {
+    serde_json::from_str(SHIPPED_CONFIG).unwrap()
+}
+
+fn env(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars.iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+fn problems_of(err: ConfigError) -> Vec<String> {
+    match err {
+        ConfigError::Invalid(problems) => problems,
+        err => panic!("expected validation problems, got {err}"),
+    }
+}
+
+#[test]
+/// Ensures the shipped config loads as is, amounts beyond u64 included.
+fn should_load_shipped_config() {
+    let path = config_file("shipped", &shipped());
+    let config = Config::load(&path, env(&[])).unwrap();
+    assert_eq!(config.server_port, 8080);
+    assert_eq!(
+        config.request_validation_config.maximum_allowed_stake,
+        1_000_000_000_000_000_000_000
+    );
+}
+
+#[test]
+/// Ensures amounts beyond u64 are read exactly whether written as numbers or as decimal strings,
+/// in the file, in the overrides or in layers parsed with `layered_json`.
+fn should_read_amounts_beyond_u64() {
+    let above_u64 = u128::from(u64::MAX) + 1;
+    let mut config = shipped();
+    config["request_validation_config"]["maximum_allowed_stake"] = json!("MAXIMUM_STAKE");
+    config["request_validation_config"]["system_maximum_allowed_stake"] =
+        json!({ "Risc0": "RISC0_STAKE" });
+    // numbers too large for a JSON value are only written as such in raw text
+    let raw = config
+        .to_string()
+        .replace("\"MAXIMUM_STAKE\"", &u128::MAX.to_string())
+        .replace("\"RISC0_STAKE\"", &above_u64.to_string());
+    let path = std::env::temp_dir().join(format!(
+        "taralli-config-above-u64-{}.json",
+        std::process::id()
+    ));
+    std::fs::write(&path, &raw).unwrap();
+    let config = Config::load(path.to_str().unwrap(), env(&[])).unwrap();
+    let request_config = &config.request_validation_config;
+    assert_eq!(request_config.maximum_allowed_stake, u128::MAX);
+    assert_eq!(
+        request_config.system_maximum_allowed_stake[&SystemId::Risc0],
+        above_u64
+    );
+
+    // a layer parsed from text, strings holding digits untouched
+    let layered = layered_json(&raw.replace(
+        &format!(r#""log_level":"{}""#, config.log_level),
+        r#""log_level":"INFO","admin_api_key":"18446744073709551616""#,
+    ))
+    .unwrap();
+    let config = Config::from_layered(layered).unwrap();
+    assert_eq!(
+        config.request_validation_config.maximum_allowed_stake,
+        u128::MAX
+    );
+    assert_eq!(
+        config.admin_api_key.as_deref(),
+        Some("18446744073709551616")
+    );
+
+    // as decimal strings, and through an override holding a JSON object
+    let path = config_file("above-u64", &shipped());
+    let request_override = format!(
+        r#"{{"maximum_allowed_stake": {above_u64}, "system_maximum_allowed_stake": {{"Risc0": "{}"}}}}"#,
+        u128::MAX
+    );
+    let config = Config::load(
+        &path,
+        env(&[(
+            "TARALLI_SERVER__REQUEST_VALIDATION_CONFIG",
+            request_override.as_str(),
+        )]),
+    )
+    .unwrap();
+    let request_config = &config.request_validation_config;
+    assert_eq!(request_config.maximum_allowed_stake, above_u64);
+    assert_eq!(
+        request_config.system_maximum_allowed_stake[&SystemId::Risc0],
+        u128::MAX
+    );
+}
+
+#[test]
+/// Ensures environment overrides take precedence over the file, nested fields applied over the
+/// ones set as a whole, and only the variables of the prefix are read.
+fn should_layer_env_over_file() {
+    let path = config_file("layered", &shipped());
+    let config = Config::load(
+        &path,
+        env(&[
+            ("TARALLI_SERVER__SERVER_PORT", "9090"),
+            ("TARALLI_SERVER__LOG_LEVEL", "DEBUG"),
+            (
+                "TARALLI_SERVER__REQUEST_VALIDATION_CONFIG__MAXIMUM_ALLOWED_STAKE",
+                "2000000000000000000000",
+            ),
+            (
+                "TARALLI_SERVER__MARKETS__UNIVERSAL_PORCHETTA",
+                "0x0000000000000000000000000000000000000002",
+            ),
+            (
+                "TARALLI_SERVER__MARKETS",
+                r#"{"universal_bombetta": "0x0000000000000000000000000000000000000001"}"#,
+            ),
+            ("TARALLI_SERVER__ADMIN_API_KEY", "secret"),
+            ("SERVER_PORT", "1"),
+        ]),
+    )
+    .unwrap();
+    assert_eq!(config.server_port, 9090);
+    assert_eq!(config.log_level, "DEBUG");
+    assert_eq!(
+        config.request_validation_config.maximum_allowed_stake,
+        2_000_000_000_000_000_000_000
+    );
+    let markets = config.markets().unwrap();
+    assert_eq!(
+        markets.universal_bombetta,
+        address!("0000000000000000000000000000000000000001")
+    );
+    assert_eq!(
+        markets.universal_porchetta,
+        address!("0000000000000000000000000000000000000002")
+    );
+    // untouched fields keep the value of the file
+    assert_eq!(config.validation_timeout_seconds, 30);
+    assert_eq!(config.admin_api_key.as_deref(), Some("secret"));
+
+    let err = Config::load(&path, env(&[("TARALLI_SERVER__SERVER_PORT__INNER", "1")])).unwrap_err();
+    assert!(
+        matches!(&err, ConfigError::EnvOverrideError(name, _) if name == "TARALLI_SERVER__SERVER_PORT__INNER"),
+        "{err}"
+    );
+}
+
+#[test]
+/// Ensures every problem of a config is reported at once.
+fn should_report_all_problems() {
+    let mut config = shipped();
+    config["validation_timeout_seconds"] = json!(0);
+    config["log_level"] = json!("LOUD");
+    config["submission_limits"]["max_body_size_bytes"] = json!(0);
+    config["base_validation_config"]["supported_systems"] = json!([]);
+    config["base_validation_config"]["minimum_auction_length"] = json!(100);
+    config["base_validation_config"]["maximum_auction_length"] = json!(10);
+    config["offer_validation_config"]["minimum_allowed_stake"] = json!("one");
+    config["markets"]["universal_bombetta"] = json!("0x6209431b6C8F38471dc65564Be2Fd08298705BBD");
+    config["clock_skew"]["block_time_seconds"] = json!(0);
+    let problems = problems_of(Config::from_layered(config).unwrap_err());
+    for expected in [
+        "markets.universal_bombetta 0x6209431b6C8F38471dc65564Be2Fd08298705BBD has an invalid checksum",
+        "log_level \"LOUD\" isn't one of TRACE, DEBUG, INFO, WARN or ERROR",
+        "validation_timeout_seconds must be nonzero",
+        "submission_limits.max_body_size_bytes must be nonzero",
+        "base_validation_config.supported_systems must list at least one system",
+        "base_validation_config.minimum_auction_length 100 exceeds maximum_auction_length 10",
+        "base_validation_config.system_overrides sets Risc0, which isn't a supported system",
+        "base_validation_config.system_overrides sets Sp1, which isn't a supported system",
+        "offer_validation_config.minimum_allowed_stake \"one\" isn't an amount",
+        "clock_skew.block_time_seconds must be nonzero to tolerate stale blocks",
+    ] {
+        assert!(
+            problems.iter().any(|problem| problem == expected),
+            "{expected} missing from {problems:#?}"
+        );
+    }
+    assert_eq!(problems.len(), 10, "{problems:#?}");
+
+    // problems found before parsing are reported along with the parse error
+    let mut config = shipped();
+    config["markets"]["universal_porchetta"] = json!("0x67445680C74Fb82C46421374554e402e72E9e5d1");
+    config["request_validation_config"]["maximum_allowed_stake"] = json!(1e21);
+    let problems = problems_of(Config::from_layered(config).unwrap_err());
+    assert_eq!(problems.len(), 2, "{problems:#?}");
+    assert!(problems[0].contains("markets.universal_porchetta"));
+    assert!(problems[1].contains("write larger amounts as decimal strings"));
+}
+
+#[test]
+/// Ensures the printed config redacts its secrets and loads back as the same config.
+fn should_print_config_redacted() {
+    let mut config = shipped();
+    config["admin_api_key"] = json!("secret");
+    config["request_validation_config"]["system_maximum_allowed_stake"] =
+        json!({ "Risc0": "3000000000000000000000" });
+    let config = Config::from_layered(config).unwrap();
+
+    let printed = config.redacted_json().unwrap();
+    assert!(!printed.contains("secret"), "{printed}");
+    let printed: Value = serde_json::from_str(&printed).unwrap();
+    assert_eq!(printed["admin_api_key"], json!(REDACTED));
+
+    let reloaded = Config::from_layered(printed).unwrap();
+    assert_eq!(
+        reloaded
+            .request_validation_config
+            .system_maximum_allowed_stake[&SystemId::Risc0],
+        3_000_000_000_000_000_000_000
+    );
+    assert_eq!(
+        reloaded.base_validation_config.supported_systems,
+        config.base_validation_config.supported_systems
+    );
+}
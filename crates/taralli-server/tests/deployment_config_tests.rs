@@ -0,0 +1,77 @@
+use serde_json::{json, Value};
+use taralli_primitives::alloy::primitives::{address, Address};
+use taralli_primitives::markets::{
+    SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS, SEPOLIA_UNIVERSAL_PORCHETTA_ADDRESS,
+};
+use taralli_primitives::PrimitivesError;
+use taralli_server::config::{Config, ConfigError};
+
+const SHIPPED_CONFIG: &str = include_str!("../../../config.json");
+const OVERRIDE_BOMBETTA: Address = address!("5FbDB2315678afecb367f032d93F642f64180aa3");
+
+/// The shipped config with its markets replaced by `markets`
+fn config(markets: Value) -> Value {
+    let mut config: Value = serde_json::from_str(SHIPPED_CONFIG).unwrap();
+    config["markets"] = markets;
+    config
+}
+
+fn parse(config: Value) -> Config {
+    serde_json::from_value(config).unwrap()
+}
+
+#[test]
+/// Ensures the markets default to the ones deployed on the configured chain.
+fn should_resolve_markets_from_chain_id() {
+    let markets = parse(config(json!({}))).markets().unwrap();
+    assert_eq!(
+        markets.universal_bombetta,
+        SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS
+    );
+    assert_eq!(
+        markets.universal_porchetta,
+        SEPOLIA_UNIVERSAL_PORCHETTA_ADDRESS
+    );
+
+    let mut without_markets = config(Value::Null);
+    without_markets.as_object_mut().unwrap().remove("markets");
+    let markets = parse(without_markets).markets().unwrap();
+    assert_eq!(
+        markets.universal_bombetta,
+        SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS
+    );
+}
+
+#[test]
+/// Ensures markets set explicitly override the ones of the deployment.
+fn should_prefer_explicit_markets() {
+    let markets = parse(config(json!({ "universal_bombetta": OVERRIDE_BOMBETTA })))
+        .markets()
+        .unwrap();
+    assert_eq!(markets.universal_bombetta, OVERRIDE_BOMBETTA);
+    assert_eq!(
+        markets.universal_porchetta,
+        SEPOLIA_UNIVERSAL_PORCHETTA_ADDRESS
+    );
+}
+
+#[test]
+/// Ensures a chain without deployment is reported along with the chains known, unless both
+/// markets are set explicitly.
+fn should_reject_unknown_chain_without_markets() {
+    let mut unknown_chain = config(json!({ "universal_bombetta": OVERRIDE_BOMBETTA }));
+    unknown_chain["base_validation_config"]["chain_id"] = json!(1);
+    let err = parse(unknown_chain.clone()).markets().unwrap_err();
+    assert!(
+        matches!(
+            &err,
+            ConfigError::DeploymentError(PrimitivesError::UnknownChain { chain_id: 1, known })
+                if !known.is_empty()
+        ),
+        "{err}"
+    );
+
+    unknown_chain["markets"]["universal_porchetta"] = json!(OVERRIDE_BOMBETTA);
+    let markets = parse(unknown_chain).markets().unwrap();
+    assert_eq!(markets.universal_porchetta, OVERRIDE_BOMBETTA);
+}
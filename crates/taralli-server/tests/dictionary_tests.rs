@@ -0,0 +1,237 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    routing::{get, post},
+    Router,
+};
+use futures::StreamExt;
+use rstest::*;
+use serial_test::serial;
+use taralli_client::api::dictionary::DictionaryCache;
+use taralli_client::api::submit::SubmitApiClient;
+use taralli_client::api::subscribe::{StreamItem, SubscribeApiClient};
+use taralli_client::error::ClientError;
+use taralli_primitives::alloy::primitives::{address, Address, Bytes, B256};
+use taralli_primitives::alloy::providers::ProviderBuilder;
+use taralli_primitives::compression_utils::{
+    dictionary::{compress_brotli_with_dictionary, dictionary_hash, system_dictionary},
+    intents::{ComputeRequestCompressed, PartialComputeRequest},
+};
+use taralli_primitives::error::ErrorCode;
+use taralli_primitives::intents::envelope::BroadcastMeta;
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::markets::SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS;
+use taralli_primitives::systems::{SystemId, SystemParams};
+use taralli_server::{
+    clock::ManualClock,
+    config::{Markets, ServerValidationConfigs},
+    dictionary::DictionaryStore,
+    routes::{
+        dictionary::{get_dictionary_handler, register_dictionary_handler},
+        submit::submit_request_handler,
+        subscribe::sse_subscribe_handler,
+    },
+    state::{request::RequestState, BaseState},
+    subscription_manager::{BroadcastKind, BroadcastedMessage, SubscriptionManager},
+};
+use tokio::net::TcpListener;
+use url::Url;
+
+use crate::common::fixtures::risc0_request_fixture;
+
+pub mod common;
+
+const PORCHETTA_ADDRESS: Address = address!("0000000000000000000000000000000000000001");
+
+/// Serve the dictionary, submission and subscription routes sharing `dictionaries`
+async fn serve_app(dictionaries: DictionaryStore) -> (Url, Arc<SubscriptionManager>) {
+    let subscription_manager = Arc::new(SubscriptionManager::new(2));
+    let base_state = BaseState::new(
+        ProviderBuilder::new().on_http("http://localhost:8545".parse().unwrap()),
+        Markets {
+            universal_bombetta: SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+            universal_porchetta: PORCHETTA_ADDRESS,
+        },
+        Duration::from_secs(10),
+        ServerValidationConfigs {
+            request: Default::default(),
+            offer: Default::default(),
+        },
+    )
+    .with_dictionaries(dictionaries);
+    let app = Router::new()
+        .route("/submit/request", post(submit_request_handler))
+        .route("/subscribe/sse", get(sse_subscribe_handler))
+        .route("/dictionaries", post(register_dictionary_handler))
+        .route("/dictionaries/:hash", get(get_dictionary_handler))
+        .with_state(RequestState::new(base_state, subscription_manager.clone()));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let server_url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("Couldn't serve");
+    });
+    (server_url, subscription_manager)
+}
+
+/// Request sharing the ELF of `base` with other inputs
+fn sibling_request(base: &ComputeRequest<SystemParams>) -> ComputeRequest<SystemParams> {
+    let mut sibling = base.clone();
+    if let SystemParams::Risc0(params) = &mut sibling.system {
+        params.inputs = b"sibling inputs".to_vec();
+    }
+    sibling
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures a registered dictionary is served by its hash, unknown hashes being answered with
+/// their own code.
+async fn should_serve_registered_dictionary(risc0_request_fixture: ComputeRequest<SystemParams>) {
+    let (server_url, _) = serve_app(DictionaryStore::default()).await;
+    let dictionary = system_dictionary(&risc0_request_fixture.system).unwrap();
+
+    let hash = SubmitApiClient::new(server_url.clone())
+        .register_dictionary(&dictionary)
+        .await
+        .unwrap();
+    assert_eq!(hash, dictionary_hash(&dictionary));
+
+    let cache = DictionaryCache::new(server_url);
+    assert_eq!(&*cache.get(hash).await.unwrap(), dictionary.as_slice());
+    let err = cache.get(B256::repeat_byte(0x0d)).await.unwrap_err();
+    assert_eq!(err.server_code(), Some(ErrorCode::UnknownDictionary));
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures a submission compressed against a registered dictionary is decompressed by the
+/// server, going on to validation, while one referencing an unknown dictionary falls back to
+/// plain compression.
+async fn should_decompress_submission_or_fall_back(
+    risc0_request_fixture: ComputeRequest<SystemParams>,
+) {
+    let dictionaries = DictionaryStore::default();
+    let (server_url, _) = serve_app(dictionaries.clone()).await;
+    let dictionary = system_dictionary(&risc0_request_fixture.system).unwrap();
+    // requests settled by the offer market fail validation once their system decompressed
+    let mut request = sibling_request(&risc0_request_fixture);
+    request.proof_request.market = PORCHETTA_ADDRESS;
+
+    let unregistered = SubmitApiClient::new(server_url.clone()).with_dictionary(dictionary.clone());
+    let response = unregistered.submit_intent(request.clone()).await.unwrap();
+    let err = ClientError::from_server_response(response).await;
+    assert_eq!(err.server_code(), Some(ErrorCode::ValidationFailed));
+    assert!(dictionaries.get(&dictionary_hash(&dictionary)).is_none());
+
+    let registered = SubmitApiClient::new(server_url).with_dictionary(dictionary.clone());
+    registered.register_dictionary(&dictionary).await.unwrap();
+    let response = registered.submit_intent(request).await.unwrap();
+    let err = ClientError::from_server_response(response).await;
+    assert_eq!(err.server_code(), Some(ErrorCode::ValidationFailed));
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures subscribers decompress a request compressed against a dictionary, fetching it from
+/// the server by its hash.
+async fn should_decode_broadcast_against_fetched_dictionary(
+    risc0_request_fixture: ComputeRequest<SystemParams>,
+) {
+    let dictionaries = DictionaryStore::default();
+    let (server_url, subscription_manager) = serve_app(dictionaries.clone()).await;
+    let dictionary = system_dictionary(&risc0_request_fixture.system).unwrap();
+    dictionaries.insert(dictionary.clone().into()).unwrap();
+
+    let api = SubscribeApiClient::new(server_url, SystemId::Risc0.as_bit());
+    let mut subscription = api.subscribe_sse().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let request = sibling_request(&risc0_request_fixture);
+    let system =
+        compress_brotli_with_dictionary(&serde_json::to_vec(&request.system).unwrap(), &dictionary)
+            .unwrap();
+    let compressed = ComputeRequestCompressed::from((
+        PartialComputeRequest {
+            system_id: request.system_id,
+            proof_request: request.proof_request.clone(),
+            signature: request.signature,
+        },
+        system,
+    ));
+    subscription_manager
+        .broadcast(BroadcastedMessage {
+            content: bincode::serialize(&compressed).unwrap().into(),
+            subscribed_to: request.system_id.as_bit(),
+            kind: BroadcastKind::Intent,
+            deflated: None,
+            meta: Some(BroadcastMeta {
+                sequence: subscription_manager.next_sequence(),
+                received_at_ms: 1_700_000_000_000,
+            }),
+        })
+        .expect("Couldn't broadcast");
+
+    let item = tokio::time::timeout(Duration::from_secs(5), subscription.next())
+        .await
+        .expect("No item received")
+        .expect("Subscription ended")
+        .expect("Invalid item");
+    match item {
+        StreamItem::Request(received, _) => assert_eq!(
+            serde_json::to_value(received).unwrap(),
+            serde_json::to_value(&request).unwrap()
+        ),
+        other => panic!("Unexpected item: {other:?}"),
+    }
+}
+
+#[test]
+/// Ensures the oldest dictionaries are evicted past the capacity or the total size, except the
+/// ones pinned by live intents, registrations being turned down when only those are left.
+fn should_keep_dictionaries_of_live_intents() {
+    let clock = Arc::new(ManualClock::new(1_000));
+    let dictionaries = DictionaryStore::new(2, 1024)
+        .with_max_total_bytes(64)
+        .with_clock(clock.clone());
+    let a = dictionaries.insert(Bytes::from(vec![1; 16])).unwrap();
+    let b = dictionaries.insert(Bytes::from(vec![2; 16])).unwrap();
+    dictionaries.pin(a, 1_100);
+
+    // over capacity, the oldest unpinned dictionary goes
+    let c = dictionaries.insert(Bytes::from(vec![3; 16])).unwrap();
+    assert!(dictionaries.get(&a).is_some());
+    assert!(dictionaries.get(&b).is_none());
+    dictionaries.pin(c, 1_100);
+
+    // nothing can be evicted until the pins expire
+    let err = dictionaries.insert(Bytes::from(vec![4; 16])).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::DictionaryRegistrationLimited);
+    assert!(dictionaries.get(&a).is_some() && dictionaries.get(&c).is_some());
+
+    clock.advance(100);
+    let d = dictionaries.insert(Bytes::from(vec![4; 56])).unwrap();
+    // over the total size as well, both older dictionaries go
+    assert!(dictionaries.get(&a).is_none() && dictionaries.get(&c).is_none());
+    assert!(dictionaries.get(&d).is_some());
+}
+
+#[test]
+/// Ensures registrations beyond the rate limit are turned down until the next minute.
+fn should_rate_limit_registrations() {
+    let clock = Arc::new(ManualClock::new(600));
+    let dictionaries = DictionaryStore::default()
+        .with_registrations_per_minute(2)
+        .with_clock(clock.clone());
+
+    dictionaries.insert(Bytes::from(vec![1; 16])).unwrap();
+    dictionaries.insert(Bytes::from(vec![2; 16])).unwrap();
+    let err = dictionaries.insert(Bytes::from(vec![3; 16])).unwrap_err();
+    assert_eq!(err.code(), ErrorCode::DictionaryRegistrationLimited);
+
+    clock.advance(60);
+    dictionaries.insert(Bytes::from(vec![3; 16])).unwrap();
+}
@@ -0,0 +1,153 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{response::IntoResponse, routing::post, Router};
+use rstest::*;
+use serial_test::serial;
+use taralli_client::api::submit::SubmitApiClient;
+use taralli_client::error::ClientError;
+use taralli_primitives::alloy::primitives::{address, Address, B256};
+use taralli_primitives::alloy::providers::ProviderBuilder;
+use taralli_primitives::error::ErrorCode;
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::markets::SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS;
+use taralli_primitives::systems::SystemParams;
+use taralli_server::{
+    config::{Markets, ServerValidationConfigs},
+    error::ServerError,
+    routes::submit::submit_request_handler,
+    state::{request::RequestState, BaseState},
+    subscription_manager::SubscriptionManager,
+};
+use tokio::net::TcpListener;
+use url::Url;
+
+use crate::common::fixtures::risc0_request_fixture;
+
+pub mod common;
+
+const PORCHETTA_ADDRESS: Address = address!("0000000000000000000000000000000000000001");
+const UNKNOWN_MARKET: Address = address!("00000000000000000000000000000000000000ff");
+
+/// Serve the request submission route, returning a client submitting to it
+async fn serve_app() -> SubmitApiClient {
+    let base_state = BaseState::new(
+        ProviderBuilder::new().on_http("http://localhost:8545".parse().unwrap()),
+        Markets {
+            universal_bombetta: SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+            universal_porchetta: PORCHETTA_ADDRESS,
+        },
+        Duration::from_secs(10),
+        ServerValidationConfigs {
+            request: Default::default(),
+            offer: Default::default(),
+        },
+    );
+    let app = Router::new()
+        .route("/submit/request", post(submit_request_handler))
+        .with_state(RequestState::new(
+            base_state,
+            Arc::new(SubscriptionManager::new(2)),
+        ));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let server_url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("Couldn't serve");
+    });
+    SubmitApiClient::new(server_url)
+}
+
+#[tokio::test]
+/// Ensures every error response carries the code of its error, which clients parse back.
+async fn should_answer_every_error_with_its_code() {
+    let errors = [
+        ServerError::BodyReadTimeout(5),
+        ServerError::PayloadTooLarge(1024),
+        ServerError::InvalidSubmission("missing field".into()),
+        ServerError::UnknownMarket {
+            market: UNKNOWN_MARKET,
+            supported: PORCHETTA_ADDRESS.to_string(),
+        },
+        ServerError::UnsupportedIntentVersion(9),
+        ServerError::DecompressionLimitExceeded(1024),
+        ServerError::DecompressionTimeout(5),
+        ServerError::DecompressionError("corrupt".into()),
+        ServerError::UnknownDictionary(B256::repeat_byte(0x0d)),
+        ServerError::DictionaryRegistrationLimited("too many registrations".into()),
+        ServerError::ValidationTimeout(5),
+        ServerError::ValidationError("unsupported system id".into()),
+        ServerError::NoProvidersAvailable(),
+        ServerError::InvalidCapabilities("bad signature".into()),
+        ServerError::CapabilityRegistryFull(1024),
+        ServerError::KeyExchangeUnauthorized("not the bidder".into()),
+        ServerError::KeyNotAvailable("not delivered".into()),
+        ServerError::ExtensionUnauthorized("not the requester".into()),
+        ServerError::ExtensionNotAvailable("not requested".into()),
+        ServerError::InvalidExtension("deadline not later".into()),
+        ServerError::BroadcastError("closed".into()),
+        ServerError::AdminUnauthorized("missing admin key".into()),
+        ServerError::InvalidLogFilter("unbalanced brackets".into()),
+        ServerError::OfferInputsUnauthorized("not the requester".into()),
+        ServerError::OfferInputsNotAvailable("not delivered".into()),
+        ServerError::OfferInputsMismatch("wrong preimage".into()),
+        ServerError::OfferInputsStoreFull("1024 offers awaiting inputs".into()),
+        ServerError::RequestMetadataUnauthorized("stale query".into()),
+        ServerError::IdempotencyKeyInUse("submission in progress".into()),
+        ServerError::IdempotencyKeyReused("another intent".into()),
+        ServerError::DatabaseError("connection refused".into()),
+    ];
+    // every code is answered by one of the errors above
+    assert_eq!(errors.len(), ErrorCode::ALL.len());
+
+    for error in errors {
+        let code = error.code();
+        let body = axum::body::to_bytes(error.into_response().into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"].as_str().and_then(ErrorCode::parse), Some(code));
+        assert!(body["error"].is_string());
+    }
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures a request failing validation is surfaced to the client as a rejection carrying the
+/// validation code, with the server's message kept readable.
+async fn should_round_trip_validation_failure(
+    mut risc0_request_fixture: ComputeRequest<SystemParams>,
+) {
+    let client = serve_app().await;
+    risc0_request_fixture.proof_request.market = PORCHETTA_ADDRESS;
+
+    let response = client.submit_intent(risc0_request_fixture).await.unwrap();
+    let err = ClientError::from_server_response(response).await;
+
+    assert_eq!(err.error_code(), "server_rejected");
+    assert_eq!(err.server_code(), Some(ErrorCode::ValidationFailed));
+    match &err {
+        ClientError::ServerRejected {
+            status, message, ..
+        } => {
+            assert_eq!(*status, 400);
+            assert!(message.contains("/submit/offer"), "{message}");
+        }
+        err => panic!("unexpected error: {err}"),
+    }
+    assert!(err.to_string().contains("/submit/offer"));
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures rejections of unknown markets keep their own code on the client.
+async fn should_round_trip_unknown_market(mut risc0_request_fixture: ComputeRequest<SystemParams>) {
+    let client = serve_app().await;
+    risc0_request_fixture.proof_request.market = UNKNOWN_MARKET;
+
+    let response = client.submit_intent(risc0_request_fixture).await.unwrap();
+    let err = ClientError::from_server_response(response).await;
+
+    assert_eq!(err.server_code(), Some(ErrorCode::UnknownMarket));
+}
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use axum::Router;
+use futures::StreamExt;
+use rstest::*;
+use serial_test::serial;
+use taralli_client::api::subscribe::{StreamItem, SubscribeApiClient};
+use taralli_primitives::alloy::primitives::B256;
+use taralli_primitives::systems::SystemId;
+use taralli_server::expiry::ExpiryQueue;
+use taralli_server::subscription_manager::SubscriptionManager;
+use tokio::net::TcpListener;
+use url::Url;
+pub mod common;
+use crate::common::fixtures::setup_app;
+
+#[test]
+/// Ensures intents are popped once their auction ended, earliest first.
+fn test_pop_expired_in_auction_end_order() {
+    let queue = ExpiryQueue::default();
+    queue.schedule(B256::repeat_byte(3), SystemId::Risc0, 300);
+    queue.schedule(B256::repeat_byte(1), SystemId::Arkworks, 100);
+    queue.schedule(B256::repeat_byte(2), SystemId::Risc0, 200);
+    assert_eq!(queue.len(), 3);
+    assert_eq!(queue.next_expiry(), Some(100));
+
+    // bids are still accepted at the end timestamp itself
+    assert!(queue.pop_expired(100).is_empty());
+
+    let expired = queue.pop_expired(201);
+    assert_eq!(
+        expired
+            .iter()
+            .map(|expired| expired.intent_id)
+            .collect::<Vec<_>>(),
+        vec![B256::repeat_byte(1), B256::repeat_byte(2)]
+    );
+    assert_eq!(expired[0].system_id, SystemId::Arkworks);
+    assert_eq!(queue.next_expiry(), Some(300));
+
+    assert_eq!(queue.pop_expired(u64::MAX).len(), 1);
+    assert!(queue.is_empty());
+    assert_eq!(queue.next_expiry(), None);
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures subscribers of a system are notified once the auction of an intent of that system ends.
+async fn test_expiry_notification_reaches_subscriber(
+    setup_app: (Router, Arc<SubscriptionManager>),
+) {
+    let port = 8891;
+    let listener = TcpListener::bind(format!("0.0.0.0:{port}"))
+        .await
+        .expect("Couldn't bind server");
+    let (app, subscription_manager) = setup_app;
+    let server_handle = tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("Couldn't serve");
+    });
+    let expiry_queue = Arc::new(ExpiryQueue::default());
+    let notifier_handle = tokio::spawn(expiry_queue.clone().notify_expired(subscription_manager));
+
+    let mut subscription = SubscribeApiClient::new(
+        Url::parse(&format!("http://localhost:{port}")).unwrap(),
+        SystemId::Risc0.as_bit(),
+    )
+    .subscribe()
+    .await
+    .expect("Couldn't subscribe provider");
+    // give the server time to register the upgraded connection
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let intent_id = B256::repeat_byte(7);
+    // the notifier is already waiting on an empty queue, scheduling has to wake it up
+    expiry_queue.schedule(intent_id, SystemId::Risc0, now + 1);
+
+    let (expired_id, system_id) = tokio::time::timeout(Duration::from_secs(5), async {
+        while let Some(item) = subscription.next().await {
+            if let Ok(StreamItem::Expired {
+                intent_id,
+                system_id,
+            }) = item
+            {
+                return (intent_id, system_id);
+            }
+        }
+        panic!("Subscription ended without expiry notification");
+    })
+    .await
+    .expect("No expiry notification received");
+
+    assert_eq!(expired_id, intent_id);
+    assert_eq!(system_id, SystemId::Risc0);
+    assert!(expiry_queue.is_empty());
+
+    notifier_handle.abort();
+    server_handle.abort();
+}
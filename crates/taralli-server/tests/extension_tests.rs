@@ -0,0 +1,413 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{routing::post, Router};
+use rstest::*;
+use serial_test::serial;
+use taralli_client::api::extension::ExtensionApiClient;
+use taralli_client::client::provider::streaming::ProviderStreamingClient;
+use taralli_client::client::requester::extension::MaxExtensionPolicy;
+use taralli_client::client::requester::requesting::RequesterRequestingClient;
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta;
+use taralli_primitives::alloy::{
+    network::Ethereum,
+    primitives::{Address, Bytes, B256, U256},
+    providers::{ProviderBuilder, RootProvider},
+    signers::local::PrivateKeySigner,
+    sol_types::{SolEvent, SolValue},
+    transports::http::{Client, Http},
+};
+use taralli_primitives::error::ErrorCode;
+use taralli_primitives::extension::{ExtensionRequest, ExtensionResponse};
+use taralli_primitives::intents::envelope::unix_time_ms;
+use taralli_primitives::intents::{request::ComputeRequest, ComputeIntent};
+use taralli_primitives::markets::SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS;
+use taralli_primitives::systems::{SystemId, SystemParams};
+use taralli_primitives::validation::request::{
+    RequestValidationConfig, RequestVerifierConstraints,
+};
+use taralli_server::{
+    routes::extension::{
+        get_extension_request_handler, get_extension_response_handler, request_extension_handler,
+        respond_extension_handler,
+    },
+    state::request::RequestState,
+    subscription_manager::SubscriptionManager,
+};
+use url::Url;
+pub mod common;
+use crate::common::fixtures::{base_state_fixture, risc0_request_fixture, start_server};
+use crate::common::rpc::start_stub_node;
+
+type StubProvider = RootProvider<Http<Client>>;
+
+/// Active request as recorded by the stub market
+#[derive(Clone)]
+struct Market {
+    requester: Address,
+    provider: Address,
+    resolution_deadline: u64,
+}
+
+/// Stub rpc answering `eth_call` with the active request of `market`, and delivering a resolve
+/// of `request_id` through log filters once `resolve_at` passed, if set
+async fn start_rpc_stub(market: Market, request_id: B256, resolve_at: Option<Instant>) -> Url {
+    let active_request = Bytes::from(
+        (
+            market.requester,
+            market.provider,
+            U256::from(market.resolution_deadline),
+            Address::ZERO,
+            U256::from(100),
+            U256::from(10),
+            B256::ZERO,
+            Bytes::new(),
+        )
+            .abi_encode_params(),
+    );
+    let resolve = UniversalBombetta::Resolve {
+        signer: market.requester,
+        requestId: request_id,
+        resolver: market.provider,
+    }
+    .encode_log_data();
+    let resolve_log = serde_json::json!({
+        "address": SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+        "topics": resolve.topics(),
+        "data": resolve.data,
+        "blockHash": B256::repeat_byte(0x01),
+        "blockNumber": "0x10",
+        "transactionHash": B256::repeat_byte(0x42),
+        "transactionIndex": "0x0",
+        "logIndex": "0x0",
+        "removed": false
+    });
+    let delivered = Arc::new(Mutex::new(false));
+
+    start_stub_node(move |request| {
+        let result = match request["method"].as_str().unwrap() {
+            "eth_call" => serde_json::json!(active_request),
+            "eth_blockNumber" => serde_json::json!("0x10"),
+            "eth_chainId" => serde_json::json!("0x1"),
+            "eth_newFilter" => serde_json::json!("0x1"),
+            "eth_getFilterChanges" => {
+                let mut delivered = delivered.lock().unwrap();
+                match resolve_at {
+                    Some(resolve_at) if !*delivered && Instant::now() >= resolve_at => {
+                        *delivered = true;
+                        serde_json::json!([resolve_log.clone()])
+                    }
+                    _ => serde_json::json!([]),
+                }
+            }
+            _ => serde_json::json!(true),
+        };
+        serde_json::json!({ "result": result })
+    })
+    .await
+}
+
+/// Serve the extension routes with a market reading from the given rpc
+fn extension_app(rpc_url: Url) -> Router {
+    let base_state = base_state_fixture(rpc_url);
+    Router::new()
+        .route(
+            "/intents/:intent_id/extension-request",
+            post(request_extension_handler).get(get_extension_request_handler),
+        )
+        .route(
+            "/intents/:intent_id/extension-response",
+            post(respond_extension_handler).get(get_extension_response_handler),
+        )
+        .with_state(RequestState::new(
+            base_state,
+            SubscriptionManager::new(2).into(),
+        ))
+}
+
+fn now() -> u64 {
+    unix_time_ms() / 1000
+}
+
+fn requester_client(
+    server_url: Url,
+    rpc_url: Url,
+    requester: PrivateKeySigner,
+    max_extension: Duration,
+) -> RequesterRequestingClient<Http<Client>, StubProvider, Ethereum, PrivateKeySigner> {
+    RequesterRequestingClient::new(
+        server_url,
+        ProviderBuilder::new().on_http(rpc_url),
+        requester,
+        SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+        SystemId::Risc0,
+        RequestValidationConfig::default(),
+        RequestVerifierConstraints::default(),
+    )
+    .with_extension_policy(
+        Arc::new(MaxExtensionPolicy { max_extension }),
+        Duration::from_millis(100),
+    )
+}
+
+fn provider_client(
+    server_url: Url,
+    rpc_url: Url,
+    provider: PrivateKeySigner,
+) -> ProviderStreamingClient<Http<Client>, StubProvider, Ethereum, PrivateKeySigner> {
+    ProviderStreamingClient::new(
+        server_url,
+        ProviderBuilder::new().on_http(rpc_url),
+        provider,
+        SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+        RequestValidationConfig::default(),
+    )
+}
+
+fn bid(request_id: B256, market: &Market) -> UniversalBombetta::Bid {
+    UniversalBombetta::Bid {
+        signer: market.requester,
+        requestId: request_id,
+        rewardToken: Address::ZERO,
+        rewardAmount: U256::from(100),
+        ethStake: U256::from(10),
+        provider: market.provider,
+    }
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures extension requests and their answers are relayed between the parties of a won
+/// request, while other parties and deadlines that aren't later are turned away.
+async fn should_relay_extension_between_parties(
+    risc0_request_fixture: ComputeRequest<SystemParams>,
+) {
+    let requester = PrivateKeySigner::random();
+    let provider = PrivateKeySigner::random();
+    let outsider = PrivateKeySigner::random();
+    let request_id = risc0_request_fixture.compute_id();
+    let deadline = now() + 600;
+    let market = Market {
+        requester: requester.address(),
+        provider: provider.address(),
+        resolution_deadline: deadline,
+    };
+    let server_url = start_server(extension_app(
+        start_rpc_stub(market, request_id, None).await,
+    ))
+    .await;
+    let api = ExtensionApiClient::new(server_url);
+
+    // nobody but the winning provider can ask for an extension
+    let forged =
+        ExtensionRequest::sign(request_id, deadline + 60, "bigger inputs".into(), &outsider)
+            .await
+            .unwrap();
+    let err = api
+        .request_extension(request_id, &forged)
+        .await
+        .unwrap_err();
+    assert_eq!(err.server_code(), Some(ErrorCode::ExtensionUnauthorized));
+    assert!(api
+        .pending_extension_request(request_id)
+        .await
+        .unwrap()
+        .is_none());
+
+    // the proposed deadline has to be later than the recorded one
+    let earlier = ExtensionRequest::sign(request_id, deadline, "no".into(), &provider)
+        .await
+        .unwrap();
+    let err = api
+        .request_extension(request_id, &earlier)
+        .await
+        .unwrap_err();
+    assert_eq!(err.server_code(), Some(ErrorCode::InvalidExtension));
+
+    let extension_request = ExtensionRequest::sign(
+        request_id,
+        deadline + 60,
+        "inputs turned out bigger than expected".into(),
+        &provider,
+    )
+    .await
+    .unwrap();
+    api.request_extension(request_id, &extension_request)
+        .await
+        .unwrap();
+    let pending = api
+        .pending_extension_request(request_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(pending, extension_request);
+    assert_eq!(pending.signer(request_id).unwrap(), provider.address());
+
+    // until the requester answers, waiting on the answer times out
+    let err = api
+        .await_response(
+            request_id,
+            Duration::from_millis(50),
+            Duration::from_millis(200),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("no answer"), "{err}");
+
+    // nobody but the requester answers, and only for the requested deadline
+    let forged = ExtensionResponse::approve(request_id, deadline + 60, &outsider)
+        .await
+        .unwrap();
+    let err = api.respond(request_id, &forged).await.unwrap_err();
+    assert_eq!(err.server_code(), Some(ErrorCode::ExtensionUnauthorized));
+    let other_deadline = ExtensionResponse::approve(request_id, deadline + 3600, &requester)
+        .await
+        .unwrap();
+    let err = api.respond(request_id, &other_deadline).await.unwrap_err();
+    assert_eq!(err.server_code(), Some(ErrorCode::ExtensionNotAvailable));
+
+    let approval = ExtensionResponse::approve(request_id, deadline + 60, &requester)
+        .await
+        .unwrap();
+    api.respond(request_id, &approval).await.unwrap();
+    let answer = api.fetch_response(request_id).await.unwrap().unwrap();
+    assert!(answer.approved);
+    // collecting the answer ends the negotiation
+    assert!(api.fetch_response(request_id).await.unwrap().is_none());
+    assert!(api
+        .pending_extension_request(request_id)
+        .await
+        .unwrap()
+        .is_none());
+    // the approval is the requester's signature over the new deadline
+    assert_eq!(answer.signer(request_id).unwrap(), requester.address());
+    assert_eq!(
+        answer
+            .signature
+            .recover_address_from_prehash(&ExtensionResponse::digest(
+                request_id,
+                deadline + 60,
+                true
+            ))
+            .unwrap(),
+        requester.address()
+    );
+
+    // a new request replaces an answer the provider didn't collect
+    let again = ExtensionRequest::sign(request_id, deadline + 120, "again".into(), &provider)
+        .await
+        .unwrap();
+    api.request_extension(request_id, &again).await.unwrap();
+    let declined = ExtensionResponse::decline(request_id, deadline + 120, &requester)
+        .await
+        .unwrap();
+    api.respond(request_id, &declined).await.unwrap();
+    let later = ExtensionRequest::sign(request_id, deadline + 180, "later".into(), &provider)
+        .await
+        .unwrap();
+    api.request_extension(request_id, &later).await.unwrap();
+    assert!(api.fetch_response(request_id).await.unwrap().is_none());
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures a requester approving the winning provider's extension keeps tracking the request
+/// past its original deadline, finding the resolve landing after it, rather than counting the
+/// request as defaulted.
+async fn should_track_resolution_until_approved_deadline(
+    risc0_request_fixture: ComputeRequest<SystemParams>,
+) {
+    let requester = PrivateKeySigner::random();
+    let provider = PrivateKeySigner::random();
+    let request_id = risc0_request_fixture.compute_id();
+    let deadline = now() + 2;
+    let market = Market {
+        requester: requester.address(),
+        provider: provider.address(),
+        resolution_deadline: deadline,
+    };
+    // the proof lands after the original deadline
+    let rpc_url = start_rpc_stub(
+        market.clone(),
+        request_id,
+        Some(Instant::now() + Duration::from_secs(3)),
+    )
+    .await;
+    let server_url = start_server(extension_app(rpc_url.clone())).await;
+
+    let requester_client = requester_client(
+        server_url.clone(),
+        rpc_url.clone(),
+        requester.clone(),
+        Duration::from_secs(60),
+    );
+    let provider_client = provider_client(server_url, rpc_url, provider);
+
+    let (resolution, extension) = tokio::join!(
+        requester_client.track_resolution(request_id, &bid(request_id, &market), deadline),
+        provider_client.request_extension(
+            request_id,
+            deadline + 4,
+            "inputs turned out bigger than expected",
+            Duration::from_secs(5),
+        ),
+    );
+    let extension = extension.unwrap();
+    assert!(extension.approved);
+    assert_eq!(extension.signer(request_id).unwrap(), requester.address());
+
+    let (resolution, approved) = resolution.unwrap();
+    let (resolve_event, _) = resolution.expect("resolve past the original deadline missed");
+    assert_eq!(resolve_event.requestId, request_id);
+    assert_eq!(approved, Some(extension));
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures an extension beyond the requester's policy is declined, the request then counting
+/// as defaulted at its original deadline.
+async fn should_decline_extension_beyond_policy(
+    risc0_request_fixture: ComputeRequest<SystemParams>,
+) {
+    let requester = PrivateKeySigner::random();
+    let provider = PrivateKeySigner::random();
+    let request_id = risc0_request_fixture.compute_id();
+    let deadline = now() + 2;
+    let market = Market {
+        requester: requester.address(),
+        provider: provider.address(),
+        resolution_deadline: deadline,
+    };
+    let rpc_url = start_rpc_stub(
+        market.clone(),
+        request_id,
+        Some(Instant::now() + Duration::from_secs(3)),
+    )
+    .await;
+    let server_url = start_server(extension_app(rpc_url.clone())).await;
+
+    let requester_client = requester_client(
+        server_url.clone(),
+        rpc_url.clone(),
+        requester,
+        Duration::from_secs(1),
+    );
+    let provider_client = provider_client(server_url, rpc_url, provider);
+
+    let (resolution, extension) = tokio::join!(
+        requester_client.track_resolution(request_id, &bid(request_id, &market), deadline),
+        provider_client.request_extension(
+            request_id,
+            deadline + 4,
+            "inputs turned out bigger than expected",
+            Duration::from_secs(5),
+        ),
+    );
+    assert!(!extension.unwrap().approved);
+    let (resolution, approved) = resolution.unwrap();
+    assert!(resolution.is_none());
+    assert!(approved.is_none());
+}
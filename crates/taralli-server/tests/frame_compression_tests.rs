@@ -0,0 +1,177 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{routing::get, Router};
+use futures::StreamExt;
+use rstest::*;
+use serial_test::serial;
+use taralli_client::api::subscribe::{StreamItem, SubscribeApiClient};
+use taralli_primitives::alloy::{
+    primitives::{address, U256},
+    providers::ProviderBuilder,
+    sol_types::SolValue,
+};
+use taralli_primitives::compression_utils::{
+    compression,
+    frames::{deflate_request_frame, inflate_request_frame},
+    intents::{ComputeRequestCompressed, PartialComputeRequest},
+};
+use taralli_primitives::intents::envelope::{BroadcastMeta, IntentEnvelope, IntentKind};
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::markets::SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS;
+use taralli_primitives::systems::SystemParams;
+use taralli_server::{
+    config::{Markets, ServerValidationConfigs},
+    routes::subscribe::websocket_subscribe_handler,
+    state::{request::RequestState, BaseState},
+    subscription_manager::{BroadcastKind, BroadcastedMessage, SubscriptionManager},
+};
+use tokio::net::TcpListener;
+use url::Url;
+pub mod common;
+use crate::common::fixtures::groth16_request_fixture;
+
+/// The arkworks fixture with the large extra data of e.g. offer-heavy traffic, compressed the
+/// way it's broadcast
+fn compressed_arkworks_request(
+    mut request: ComputeRequest<SystemParams>,
+) -> ComputeRequestCompressed {
+    request.proof_request.extraData = (0..256u64)
+        .map(U256::from)
+        .collect::<Vec<_>>()
+        .abi_encode()
+        .into();
+    let system = compression::compress_brotli(&serde_json::to_vec(&request.system).unwrap())
+        .expect("Couldn't compress system");
+    ComputeRequestCompressed::from((
+        PartialComputeRequest {
+            system_id: request.system_id,
+            proof_request: request.proof_request,
+            signature: request.signature,
+        },
+        system,
+    ))
+}
+
+#[rstest]
+/// Ensures deflated frames are smaller than the enveloped ones, leave the Brotli compressed system
+/// untouched and decode back into the same request.
+fn should_deflate_frames_around_compressed_system(
+    groth16_request_fixture: ComputeRequest<SystemParams>,
+) {
+    let request = compressed_arkworks_request(groth16_request_fixture);
+    let plain =
+        bincode::serialize(&IntentEnvelope::new(IntentKind::Request, request.clone())).unwrap();
+    let meta = BroadcastMeta {
+        sequence: 7,
+        received_at_ms: 1_700_000_000_000,
+    };
+    let deflated = deflate_request_frame(&request, meta).unwrap();
+
+    let system_length = request.system.len();
+    println!(
+        "arkworks frame: {} bytes plain, {} bytes deflated ({} bytes of fields around a {} bytes system)",
+        plain.len(),
+        deflated.len(),
+        deflated.len() - system_length,
+        system_length
+    );
+    assert!(deflated.len() < plain.len());
+    // the fields alone shrink, while the system is carried verbatim
+    assert!(deflated.len() - system_length < (plain.len() - system_length) / 2);
+    assert!(deflated.ends_with(&request.system));
+
+    let (envelope, inflated_meta) = inflate_request_frame(&deflated).unwrap();
+    assert_eq!(envelope.kind, IntentKind::Request);
+    assert_eq!(inflated_meta, Some(meta));
+    assert_eq!(
+        bincode::serialize(&IntentEnvelope::new(IntentKind::Request, envelope.payload)).unwrap(),
+        plain
+    );
+
+    // truncated or oversized length prefixes are rejected rather than read past the frame
+    assert!(inflate_request_frame(&deflated[..3]).is_err());
+    let mut corrupted = deflated.clone();
+    corrupted[1..5].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(inflate_request_frame(&corrupted).is_err());
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures a subscriber negotiating frame compression and one that doesn't receive the same
+/// request from a server with frame compression enabled.
+async fn should_negotiate_frame_compression(groth16_request_fixture: ComputeRequest<SystemParams>) {
+    let subscription_manager = Arc::new(SubscriptionManager::new(2).with_frame_compression(true));
+    let base_state = BaseState::new(
+        ProviderBuilder::new().on_http("http://localhost:8545".parse().unwrap()),
+        Markets {
+            universal_bombetta: SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+            universal_porchetta: address!("0000000000000000000000000000000000000001"),
+        },
+        Duration::from_secs(10),
+        ServerValidationConfigs {
+            request: Default::default(),
+            offer: Default::default(),
+        },
+    );
+    let app = Router::new()
+        .route("/subscribe", get(websocket_subscribe_handler))
+        .with_state(RequestState::new(base_state, subscription_manager.clone()));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let server_url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("Couldn't serve");
+    });
+
+    let system_id = groth16_request_fixture.system_id;
+    let mut deflating = SubscribeApiClient::new(server_url.clone(), system_id.as_bit())
+        .with_frame_compression(true)
+        .subscribe()
+        .await
+        .unwrap();
+    let mut plain = SubscribeApiClient::new(server_url, system_id.as_bit())
+        .subscribe()
+        .await
+        .unwrap();
+    // give the server time to register the upgraded connections
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let request = compressed_arkworks_request(groth16_request_fixture);
+    let meta = BroadcastMeta {
+        sequence: subscription_manager.next_sequence(),
+        received_at_ms: 1_700_000_000_000,
+    };
+    subscription_manager
+        .broadcast(BroadcastedMessage {
+            content: bincode::serialize(&request).unwrap().into(),
+            subscribed_to: system_id.as_bit(),
+            kind: BroadcastKind::Intent,
+            deflated: Some(deflate_request_frame(&request, meta).unwrap().into()),
+            meta: Some(meta),
+        })
+        .unwrap();
+
+    let mut received = Vec::new();
+    for subscription in [&mut deflating, &mut plain] {
+        let item = tokio::time::timeout(Duration::from_secs(5), subscription.next())
+            .await
+            .expect("No request received")
+            .expect("Subscription ended");
+        match item {
+            Ok(StreamItem::Request(request, delivery)) => {
+                assert_eq!(
+                    delivery.map(|delivery| delivery.sequence),
+                    Some(meta.sequence)
+                );
+                received.push(serde_json::to_value(&request).unwrap())
+            }
+            other => panic!("Unexpected item: {other:?}"),
+        }
+    }
+    assert_eq!(received[0], received[1]);
+    assert_eq!(
+        received[0]["proof_request"]["extraData"],
+        serde_json::to_value(&request.proof_request.extraData).unwrap()
+    );
+}
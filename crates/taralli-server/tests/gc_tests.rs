@@ -0,0 +1,226 @@
+use std::sync::Arc;
+
+use taralli_primitives::alloy::primitives::{PrimitiveSignature, B256, U256};
+use taralli_primitives::extension::{ExtensionRequest, ExtensionResponse};
+use taralli_primitives::intents::envelope::IntentMetadata;
+use taralli_primitives::systems::SystemId;
+use taralli_server::clock::ManualClock;
+use taralli_server::config::GcConfig;
+use taralli_server::extension::ExtensionNegotiation;
+use taralli_server::gc::{GcReport, IntentGc};
+use taralli_server::intent_store::{
+    InMemoryIntentStore, IntentStatus, IntentStore, RequestFilter, StoredRequest,
+};
+use taralli_server::key_exchange::KeyExchange;
+
+use crate::common::intent_store::compressed_request_fixture;
+
+pub mod common;
+
+// auction of the fixture requests ending at 2_000, with a proving time of 30
+const END_AUCTION: u64 = 2_000;
+const RESOLVE_DEADLINE: u64 = END_AUCTION + 30;
+
+struct Collector {
+    clock: Arc<ManualClock>,
+    store: Arc<InMemoryIntentStore>,
+    extensions: Arc<ExtensionNegotiation>,
+    gc: IntentGc,
+}
+
+fn collector(request_retention_seconds: u64) -> Collector {
+    let clock = Arc::new(ManualClock::new(END_AUCTION));
+    let store = Arc::new(InMemoryIntentStore::default());
+    let extensions = Arc::new(ExtensionNegotiation::default());
+    let gc = IntentGc::new(
+        GcConfig {
+            request_retention_seconds,
+            ..Default::default()
+        },
+        clock.clone(),
+    )
+    .with_intent_store(store.clone())
+    .with_request_relays(Arc::new(KeyExchange::default()), extensions.clone());
+    Collector {
+        clock,
+        store,
+        extensions,
+        gc,
+    }
+}
+
+fn archived(archived_requests: u64) -> GcReport {
+    GcReport {
+        archived_requests,
+        ..Default::default()
+    }
+}
+
+async fn ids(store: &InMemoryIntentStore, filter: RequestFilter) -> Vec<B256> {
+    store
+        .get_requests(&filter)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|request: StoredRequest| request.intent_id)
+        .collect()
+}
+
+#[tokio::test]
+/// Ensures requests are archived once their resolve deadline is over for longer than the
+/// retention window, and are only served afterwards when asked for.
+async fn should_archive_requests_past_retention() {
+    let Collector {
+        clock, store, gc, ..
+    } = collector(100);
+    let ended = store
+        .store_request(
+            &compressed_request_fixture(SystemId::Risc0, 1, END_AUCTION),
+            &IntentMetadata::new(),
+        )
+        .await
+        .unwrap();
+    let running = store
+        .store_request(
+            &compressed_request_fixture(SystemId::Risc0, 2, 5_000),
+            &IntentMetadata::new(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(gc.run_once().await.unwrap(), archived(0));
+    clock.set(RESOLVE_DEADLINE + 100);
+    assert_eq!(gc.run_once().await.unwrap(), archived(0));
+    clock.advance(1);
+    assert_eq!(gc.run_once().await.unwrap(), archived(1));
+    // archived requests aren't collected twice
+    clock.advance(1_000);
+    assert_eq!(gc.run_once().await.unwrap(), archived(0));
+
+    assert_eq!(
+        ids(&store, RequestFilter::default()).await,
+        vec![running.intent_id]
+    );
+    let mut all = vec![ended.intent_id, running.intent_id];
+    all.sort();
+    assert_eq!(
+        ids(
+            &store,
+            RequestFilter {
+                include_archived: true,
+                ..Default::default()
+            }
+        )
+        .await,
+        all
+    );
+    assert_eq!(
+        ids(
+            &store,
+            RequestFilter {
+                status: Some(IntentStatus::Archived),
+                ..Default::default()
+            }
+        )
+        .await,
+        vec![ended.intent_id]
+    );
+
+    let metrics = gc.metrics();
+    assert_eq!(metrics.runs, 4);
+    assert_eq!(metrics.last_run_at, Some(RESOLVE_DEADLINE + 1_101));
+    assert_eq!(metrics.collected, archived(1));
+}
+
+fn extension_request(new_deadline: u64) -> ExtensionRequest {
+    ExtensionRequest {
+        new_deadline,
+        reason: "slow prover".to_string(),
+        signature: PrimitiveSignature::new(U256::from(1), U256::from(2), false),
+    }
+}
+
+#[tokio::test]
+/// Ensures requests in their auction or resolve window are kept without any retention, approved
+/// deadline extensions included even once the provider collected the approval, and their relayed
+/// extension negotiation is dropped once they're archived.
+async fn should_keep_requests_within_resolve_window() {
+    let Collector {
+        clock,
+        store,
+        extensions,
+        gc,
+    } = collector(0);
+    let pending = store
+        .store_request(
+            &compressed_request_fixture(SystemId::Sp1, 1, END_AUCTION),
+            &IntentMetadata::new(),
+        )
+        .await
+        .unwrap();
+    let extended = store
+        .store_request(
+            &compressed_request_fixture(SystemId::Sp1, 2, END_AUCTION),
+            &IntentMetadata::new(),
+        )
+        .await
+        .unwrap();
+    let extended_deadline = RESOLVE_DEADLINE + 60;
+    // only answered extensions push the deadline back
+    extensions.request_extension(pending.intent_id, extension_request(extended_deadline));
+    extensions.request_extension(extended.intent_id, extension_request(extended_deadline));
+    assert!(extensions.respond(
+        extended.intent_id,
+        ExtensionResponse {
+            new_deadline: extended_deadline,
+            approved: true,
+            signature: PrimitiveSignature::new(U256::from(3), U256::from(4), false),
+        }
+    ));
+
+    for now in [
+        END_AUCTION - 1,
+        END_AUCTION,
+        END_AUCTION + 1,
+        RESOLVE_DEADLINE,
+    ] {
+        clock.set(now);
+        assert_eq!(gc.run_once().await.unwrap(), archived(0), "at {now}");
+    }
+    assert_eq!(extensions.len(), 2);
+    // the provider collects the approval, the approved deadline still holds
+    assert!(
+        extensions
+            .take_response(&extended.intent_id)
+            .unwrap()
+            .approved
+    );
+    assert!(extensions.extension_request(&extended.intent_id).is_none());
+    assert_eq!(
+        extensions.approved_deadline(&extended.intent_id),
+        Some(extended_deadline)
+    );
+    assert_eq!(extensions.len(), 2);
+
+    clock.set(RESOLVE_DEADLINE + 1);
+    assert_eq!(
+        gc.run_once().await.unwrap(),
+        GcReport {
+            archived_requests: 1,
+            pruned_entries: 1,
+            ..Default::default()
+        }
+    );
+    assert!(extensions.extension_request(&pending.intent_id).is_none());
+    assert_eq!(
+        ids(&store, RequestFilter::default()).await,
+        vec![extended.intent_id]
+    );
+
+    clock.set(extended_deadline);
+    assert_eq!(gc.run_once().await.unwrap(), archived(0));
+    clock.advance(1);
+    assert_eq!(gc.run_once().await.unwrap().archived_requests, 1);
+    assert!(extensions.is_empty());
+    assert!(ids(&store, RequestFilter::default()).await.is_empty());
+}
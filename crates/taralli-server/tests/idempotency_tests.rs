@@ -0,0 +1,120 @@
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use serde_json::{json, Value};
+use taralli_primitives::alloy::primitives::B256;
+use taralli_primitives::intents::envelope::IDEMPOTENCY_KEY_HEADER;
+use taralli_server::error::ServerError;
+use taralli_server::idempotency::{IdempotencyCache, IdempotencyGuard, Reservation};
+
+const INTENT_ID: B256 = B256::repeat_byte(0x01);
+const OTHER_INTENT_ID: B256 = B256::repeat_byte(0x02);
+
+fn headers(key: &'static str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(IDEMPOTENCY_KEY_HEADER, HeaderValue::from_static(key));
+    headers
+}
+
+fn proceed(cache: &IdempotencyCache, headers: &HeaderMap, intent_id: B256) -> IdempotencyGuard {
+    match cache.reserve(headers, intent_id) {
+        Ok(Reservation::Proceed(guard)) => guard,
+        Ok(Reservation::Replay(status, body)) => panic!("replayed {status}: {body}"),
+        Err(e) => panic!("{e}"),
+    }
+}
+
+fn replay(cache: &IdempotencyCache, headers: &HeaderMap, intent_id: B256) -> (StatusCode, Value) {
+    match cache.reserve(headers, intent_id) {
+        Ok(Reservation::Replay(status, body)) => (status, body),
+        Ok(Reservation::Proceed(_)) => panic!("not replayed"),
+        Err(e) => panic!("{e}"),
+    }
+}
+
+#[test]
+/// Ensures a retried submission is answered with the response of the original one.
+fn should_replay_remembered_response() {
+    let cache = IdempotencyCache::default();
+    let body =
+        json!({"message": "compute request broadcast to providers", "broadcast_receivers": 1});
+
+    proceed(&cache, &headers("a"), INTENT_ID).complete(StatusCode::OK, &body);
+
+    assert_eq!(
+        replay(&cache, &headers("a"), INTENT_ID),
+        (StatusCode::OK, body)
+    );
+    proceed(&cache, &headers("b"), INTENT_ID);
+}
+
+#[test]
+/// Ensures submissions without an idempotency key are never replayed.
+fn should_ignore_submissions_without_key() {
+    let cache = IdempotencyCache::default();
+
+    proceed(&cache, &HeaderMap::new(), INTENT_ID).complete(StatusCode::OK, &json!({}));
+
+    proceed(&cache, &HeaderMap::new(), INTENT_ID);
+    proceed(&cache, &headers(""), INTENT_ID);
+}
+
+#[test]
+/// Ensures a retry arriving while the original submission is in progress is turned down rather
+/// than submitted again.
+fn should_reject_retry_while_in_progress() {
+    let cache = IdempotencyCache::default();
+
+    let _guard = proceed(&cache, &headers("a"), INTENT_ID);
+
+    assert!(matches!(
+        cache.reserve(&headers("a"), INTENT_ID),
+        Err(ServerError::IdempotencyKeyInUse(_))
+    ));
+}
+
+#[test]
+/// Ensures a failed submission releases its key, so it can be retried.
+fn should_release_key_of_failed_submission() {
+    let cache = IdempotencyCache::default();
+
+    drop(proceed(&cache, &headers("a"), INTENT_ID));
+
+    proceed(&cache, &headers("a"), INTENT_ID);
+}
+
+#[test]
+/// Ensures a key can't be reused for another intent, whether its submission completed or not.
+fn should_reject_key_reused_for_another_intent() {
+    let cache = IdempotencyCache::default();
+
+    let guard = proceed(&cache, &headers("a"), INTENT_ID);
+    assert!(matches!(
+        cache.reserve(&headers("a"), OTHER_INTENT_ID),
+        Err(ServerError::IdempotencyKeyReused(_))
+    ));
+
+    guard.complete(StatusCode::OK, &json!({}));
+    assert!(matches!(
+        cache.reserve(&headers("a"), OTHER_INTENT_ID),
+        Err(ServerError::IdempotencyKeyReused(_))
+    ));
+}
+
+#[test]
+/// Ensures the oldest responses are forgotten once the cache is full.
+fn should_evict_oldest_response() {
+    let cache = IdempotencyCache::new(2);
+
+    proceed(&cache, &headers("a"), INTENT_ID).complete(StatusCode::OK, &json!(1));
+    proceed(&cache, &headers("b"), INTENT_ID).complete(StatusCode::OK, &json!(2));
+    proceed(&cache, &headers("c"), INTENT_ID).complete(StatusCode::CREATED, &json!(3));
+
+    assert_eq!(
+        replay(&cache, &headers("b"), INTENT_ID),
+        (StatusCode::OK, json!(2))
+    );
+    assert_eq!(
+        replay(&cache, &headers("c"), INTENT_ID),
+        (StatusCode::CREATED, json!(3))
+    );
+    proceed(&cache, &headers("a"), INTENT_ID);
+}
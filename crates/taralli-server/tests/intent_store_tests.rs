@@ -0,0 +1,276 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{extract::State, Json};
+use taralli_primitives::alloy::primitives::{address, B256};
+use taralli_primitives::alloy::providers::ProviderBuilder;
+use taralli_primitives::alloy::signers::local::PrivateKeySigner;
+use taralli_primitives::intents::envelope::IntentMetadata;
+use taralli_primitives::intents::request::compute_request_id;
+use taralli_primitives::markets::{SEPOLIA_CHAIN_ID, SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS};
+use taralli_primitives::request_metadata::{MetadataQuery, METADATA_QUERY_VALIDITY_SECONDS};
+use taralli_primitives::systems::SystemId;
+use taralli_server::clock::unix_timestamp;
+use taralli_server::config::{Markets, ServerValidationConfigs};
+use taralli_server::error::ServerError;
+use taralli_server::intent_store::{
+    InMemoryIntentStore, IntentStatus, IntentStore, RequestFilter, StoredRequest,
+};
+use taralli_server::routes::query::get_request_metadata_handler;
+use taralli_server::state::{request::RequestState, BaseState};
+use taralli_server::subscription_manager::SubscriptionManager;
+
+use crate::common::fixtures::base_state_fixture;
+use crate::common::intent_store::{compressed_request_fixture, SIGNER};
+
+pub mod common;
+
+#[tokio::test]
+/// Ensures stored requests keep their metadata and storing one twice keeps the first copy.
+async fn should_store_request_once() {
+    let store = InMemoryIntentStore::default();
+    let request = compressed_request_fixture(SystemId::Risc0, 1, 2_000);
+    let metadata = IntentMetadata::from([("job".to_string(), "42".to_string())]);
+
+    let stored = store.store_request(&request, &metadata).await.unwrap();
+    store
+        .update_request_status(stored.intent_id, IntentStatus::Broadcast)
+        .await
+        .unwrap();
+    let restored = store
+        .store_request(&request, &IntentMetadata::new())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        stored.intent_id,
+        compute_request_id(&request.proof_request, &request.signature)
+    );
+    assert_eq!(stored.signer, SIGNER);
+    assert_eq!(stored.end_auction_timestamp, 2_000);
+    assert_eq!(stored.system[..], request.system[..]);
+    assert_eq!(restored.status, IntentStatus::Broadcast);
+    assert_eq!(
+        store.get_request_metadata(SIGNER).await.unwrap(),
+        BTreeMap::from([(stored.intent_id, metadata)])
+    );
+    assert_eq!(
+        store
+            .get_requests(&RequestFilter::default())
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[tokio::test]
+/// Ensures status updates of requests that were never stored are reported.
+async fn should_reject_status_update_of_unknown_request() {
+    let store = InMemoryIntentStore::default();
+
+    assert!(store
+        .update_request_status(B256::repeat_byte(1), IntentStatus::Broadcast)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+/// Ensures each filter narrows the stored requests down.
+async fn should_filter_stored_requests() {
+    let store = InMemoryIntentStore::default();
+    let risc0 = store
+        .store_request(
+            &compressed_request_fixture(SystemId::Risc0, 1, 2_000),
+            &IntentMetadata::new(),
+        )
+        .await
+        .unwrap();
+    let sp1 = store
+        .store_request(
+            &compressed_request_fixture(SystemId::Sp1, 2, 3_000),
+            &IntentMetadata::new(),
+        )
+        .await
+        .unwrap();
+    store
+        .update_request_status(sp1.intent_id, IntentStatus::Broadcast)
+        .await
+        .unwrap();
+
+    let ids = |requests: Vec<StoredRequest>| {
+        requests
+            .into_iter()
+            .map(|request| request.intent_id)
+            .collect::<Vec<_>>()
+    };
+    let query = |filter: RequestFilter| {
+        let store = &store;
+        async move { ids(store.get_requests(&filter).await.unwrap()) }
+    };
+
+    assert_eq!(
+        query(RequestFilter {
+            system_id: Some(SystemId::Risc0),
+            ..Default::default()
+        })
+        .await,
+        vec![risc0.intent_id]
+    );
+    assert_eq!(
+        query(RequestFilter {
+            status: Some(IntentStatus::Broadcast),
+            ..Default::default()
+        })
+        .await,
+        vec![sp1.intent_id]
+    );
+    assert_eq!(
+        query(RequestFilter {
+            active_at: Some(2_500),
+            ..Default::default()
+        })
+        .await,
+        vec![sp1.intent_id]
+    );
+    assert!(query(RequestFilter {
+        signer: Some(address!("9999999999999999999999999999999999999999")),
+        ..Default::default()
+    })
+    .await
+    .is_empty());
+}
+
+#[tokio::test]
+/// Ensures requests are served a page at a time, each page starting after the cursor.
+async fn should_page_stored_requests() {
+    let store = InMemoryIntentStore::default();
+    let mut intent_ids = Vec::new();
+    for nonce in 1..=3 {
+        let stored = store
+            .store_request(
+                &compressed_request_fixture(SystemId::Risc0, nonce, 2_000),
+                &IntentMetadata::new(),
+            )
+            .await
+            .unwrap();
+        intent_ids.push(stored.intent_id);
+    }
+    intent_ids.sort();
+
+    let page = |after: Option<B256>| {
+        let store = &store;
+        async move {
+            store
+                .get_requests(&RequestFilter {
+                    after,
+                    limit: Some(2),
+                    ..Default::default()
+                })
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|request| request.intent_id)
+                .collect::<Vec<_>>()
+        }
+    };
+
+    assert_eq!(page(None).await, intent_ids[..2]);
+    assert_eq!(page(Some(intent_ids[1])).await, intent_ids[2..]);
+    assert!(page(Some(intent_ids[2])).await.is_empty());
+}
+
+#[tokio::test]
+/// Ensures submitted requests are written through to the intent store in the background.
+async fn should_persist_submitted_request() {
+    let store = Arc::new(InMemoryIntentStore::default());
+    let base_state = BaseState::new(
+        ProviderBuilder::new().on_http(reqwest::Url::parse("http://localhost:8080").unwrap()),
+        Markets {
+            universal_bombetta: SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+            universal_porchetta: address!("0000000000000000000000000000000000000001"),
+        },
+        Duration::from_secs(10),
+        ServerValidationConfigs {
+            request: Default::default(),
+            offer: Default::default(),
+        },
+    );
+    let state = RequestState::new(base_state, Arc::new(SubscriptionManager::new(2)))
+        .with_intent_store(store.clone());
+
+    state.persist_request(
+        compressed_request_fixture(SystemId::Risc0, 1, 2_000),
+        IntentMetadata::new(),
+        true,
+    );
+
+    let stored = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let stored = store.get_requests(&RequestFilter::default()).await.unwrap();
+            if stored.first().map(|request| request.status) == Some(IntentStatus::Broadcast) {
+                return stored;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("request wasn't persisted");
+    assert_eq!(stored.len(), 1);
+}
+
+#[tokio::test]
+/// Ensures the metadata of requests is only served to their signer, through a fresh query.
+async fn should_serve_metadata_to_requester_only() {
+    let store = Arc::new(InMemoryIntentStore::default());
+    let requester = PrivateKeySigner::random();
+    let mut request = compressed_request_fixture(SystemId::Risc0, 1, 2_000);
+    request.proof_request.signer = requester.address();
+    let metadata = IntentMetadata::from([("job".to_string(), "42".to_string())]);
+    let stored = store.store_request(&request, &metadata).await.unwrap();
+    let query = |signer: PrivateKeySigner, chain_id: u64, issued_at: u64| {
+        let rpc_url = reqwest::Url::parse("http://localhost:8080").unwrap();
+        let state = RequestState::new(
+            base_state_fixture(rpc_url),
+            Arc::new(SubscriptionManager::new(2)),
+        )
+        .with_intent_store(store.clone());
+        async move {
+            let metadata_query = MetadataQuery::sign(chain_id, issued_at, &signer)
+                .await
+                .unwrap();
+            get_request_metadata_handler(State(state), Json(metadata_query))
+                .await
+                .map(|(_, Json(body))| body["metadata"].clone())
+        }
+    };
+    let now = unix_timestamp();
+
+    assert_eq!(
+        query(requester.clone(), SEPOLIA_CHAIN_ID, now)
+            .await
+            .unwrap(),
+        serde_json::json!({ stored.intent_id.to_string(): { "job": "42" } })
+    );
+    assert_eq!(
+        query(PrivateKeySigner::random(), SEPOLIA_CHAIN_ID, now)
+            .await
+            .unwrap(),
+        serde_json::json!({})
+    );
+    assert!(matches!(
+        query(
+            requester.clone(),
+            SEPOLIA_CHAIN_ID,
+            now - 2 * METADATA_QUERY_VALIDITY_SECONDS
+        )
+        .await,
+        Err(ServerError::RequestMetadataUnauthorized(_))
+    ));
+    // a query signed for the server of another chain isn't replayed against this one
+    assert!(matches!(
+        query(requester, 1, now).await,
+        Err(ServerError::RequestMetadataUnauthorized(_))
+    ));
+}
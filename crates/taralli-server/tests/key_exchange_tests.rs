@@ -0,0 +1,167 @@
+use std::time::Duration;
+
+use axum::{routing::post, Router};
+use rstest::*;
+use serial_test::serial;
+use taralli_client::api::key::KeyExchangeApiClient;
+use taralli_primitives::alloy::{
+    primitives::{Address, Bytes, B256, U256},
+    signers::local::PrivateKeySigner,
+    sol_types::SolValue,
+};
+use taralli_primitives::encryption::{
+    EncryptedSystemParams, KeyDelivery, KeyExchangeKeypair, KeyRequest,
+};
+use taralli_primitives::intents::{request::ComputeRequest, ComputeIntent};
+use taralli_primitives::systems::{System, SystemParams};
+use taralli_server::{
+    routes::key::{
+        deliver_key_handler, get_key_handler, get_key_request_handler, request_key_handler,
+    },
+    state::request::RequestState,
+    subscription_manager::SubscriptionManager,
+};
+use url::Url;
+pub mod common;
+use crate::common::fixtures::{base_state_fixture, risc0_request_fixture, start_server};
+use crate::common::rpc::start_stub_node;
+
+/// Stub rpc answering every `eth_call` with an active request won by `provider`
+async fn start_rpc_stub(requester: Address, provider: Address) -> Url {
+    let active_request = (
+        requester,
+        provider,
+        U256::from(2_000_000_000u64),
+        Address::ZERO,
+        U256::from(100),
+        U256::from(10),
+        B256::ZERO,
+        Bytes::new(),
+    )
+        .abi_encode_params();
+    let result = Bytes::from(active_request);
+
+    start_stub_node(move |_| serde_json::json!({ "result": result })).await
+}
+
+/// Serve the key routes with a market reading from the given rpc
+fn key_exchange_app(rpc_url: Url) -> Router {
+    let base_state = base_state_fixture(rpc_url);
+    Router::new()
+        .route(
+            "/intents/:intent_id/key/request",
+            post(request_key_handler).get(get_key_request_handler),
+        )
+        .route(
+            "/intents/:intent_id/key",
+            post(deliver_key_handler).get(get_key_handler),
+        )
+        .with_state(RequestState::new(
+            base_state,
+            SubscriptionManager::new(2).into(),
+        ))
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures the winning provider of an encrypted request gets its key through the server and
+/// decrypts the exact system params the requester encrypted, while other parties are turned away.
+/// Risc0 params stand in for the system, the worker never runs.
+async fn should_hand_key_over_to_winning_provider(
+    risc0_request_fixture: ComputeRequest<SystemParams>,
+) {
+    let requester = PrivateKeySigner::random();
+    let provider = PrivateKeySigner::random();
+    let outsider = PrivateKeySigner::random();
+    let server_url = start_server(key_exchange_app(
+        start_rpc_stub(requester.address(), provider.address()).await,
+    ))
+    .await;
+    let api = KeyExchangeApiClient::new(server_url);
+
+    // the requester broadcasts the request with its system params encrypted
+    let request_id = risc0_request_fixture.compute_id();
+    let (encrypted, keys) =
+        EncryptedSystemParams::encrypt(&risc0_request_fixture.system, request_id).unwrap();
+    encrypted.validate_inputs().unwrap();
+    assert_eq!(encrypted.system_id(), risc0_request_fixture.system_id);
+
+    // nobody but the winning provider can ask for the key
+    let outsider_request = KeyRequest::sign(
+        request_id,
+        KeyExchangeKeypair::random().public_key(),
+        &outsider,
+    )
+    .await
+    .unwrap();
+    let err = api
+        .request_key(request_id, &outsider_request)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("403"), "unexpected error: {err}");
+    assert!(api.pending_key_request(request_id).await.unwrap().is_none());
+
+    let keypair = KeyExchangeKeypair::random();
+    let key_request = KeyRequest::sign(request_id, keypair.public_key(), &provider)
+        .await
+        .unwrap();
+    api.request_key(request_id, &key_request).await.unwrap();
+
+    // until the requester answers, waiting on the key times out
+    let err = api
+        .await_key(
+            request_id,
+            Duration::from_millis(50),
+            Duration::from_millis(200),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("no key delivered"), "{err}");
+
+    // the requester wraps the key for the provider key it was asked for, nobody else can deliver
+    let pending = api.pending_key_request(request_id).await.unwrap().unwrap();
+    assert_eq!(pending, key_request);
+    let wrapped_key = keys.wrap_for(request_id, &pending.provider_key).unwrap();
+    let forged = KeyDelivery::sign(request_id, wrapped_key.clone(), &outsider)
+        .await
+        .unwrap();
+    let err = api.deliver_key(request_id, &forged).await.unwrap_err();
+    assert!(err.to_string().contains("403"), "unexpected error: {err}");
+    let delivery = KeyDelivery::sign(request_id, wrapped_key, &requester)
+        .await
+        .unwrap();
+    api.deliver_key(request_id, &delivery).await.unwrap();
+
+    // the provider unwraps the key and decrypts the system params
+    let delivered = api
+        .await_key(
+            request_id,
+            Duration::from_millis(50),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+    assert_eq!(delivered.signer(request_id).unwrap(), requester.address());
+    let content_key = keypair
+        .unwrap_key(
+            request_id,
+            &encrypted.encapsulation_key,
+            &delivered.wrapped_key,
+        )
+        .unwrap();
+    let decrypted = encrypted.decrypt(&content_key, request_id).unwrap();
+    assert_eq!(
+        serde_json::to_value(&decrypted).unwrap(),
+        serde_json::to_value(&risc0_request_fixture.system).unwrap()
+    );
+
+    // the wrapped key is useless to anyone else
+    assert!(KeyExchangeKeypair::random()
+        .unwrap_key(
+            request_id,
+            &encrypted.encapsulation_key,
+            &delivered.wrapped_key
+        )
+        .is_err());
+}
@@ -0,0 +1,79 @@
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+use taralli_primitives::logging::STATE_TARGET;
+use taralli_server::log_control::LogControl;
+use tracing_subscriber::{fmt, layer::SubscriberExt, Registry};
+
+/// Log lines written by a subscriber
+#[derive(Clone, Default)]
+struct Captured(Arc<Mutex<Vec<u8>>>);
+
+impl Captured {
+    fn take(&self) -> String {
+        String::from_utf8(std::mem::take(&mut *self.0.lock().unwrap())).unwrap()
+    }
+}
+
+impl Write for Captured {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+mod bidder {
+    pub fn work() {
+        tracing::debug!("bidder detail");
+        tracing::info!("bidder progress");
+    }
+}
+
+#[test]
+/// Ensures tightening the filter at runtime stops the debug events of a module from being
+/// logged, while state dumps are logged whatever the filter.
+fn should_switch_filter_at_runtime() {
+    let captured = Captured::default();
+    let writer = captured.clone();
+    let (filter, control) = LogControl::layer::<Registry>("debug").unwrap();
+    let subscriber = Registry::default().with(filter).with(
+        fmt::layer()
+            .with_ansi(false)
+            .with_writer(move || writer.clone()),
+    );
+
+    tracing::subscriber::with_default(subscriber, || {
+        bidder::work();
+        let logged = captured.take();
+        assert!(logged.contains("bidder detail"), "{logged}");
+        assert!(logged.contains("bidder progress"), "{logged}");
+
+        let module = module_path!();
+        control
+            .set_log_filter(format!("debug,{module}::bidder=info"))
+            .unwrap();
+        assert_eq!(control.log_filter(), format!("debug,{module}::bidder=info"));
+        captured.take();
+        bidder::work();
+        let logged = captured.take();
+        assert!(!logged.contains("bidder detail"), "{logged}");
+        assert!(logged.contains("bidder progress"), "{logged}");
+
+        control.set_log_filter("error").unwrap();
+        captured.take();
+        bidder::work();
+        tracing::info!(target: STATE_TARGET, "state dump");
+        let logged = captured.take();
+        assert!(!logged.contains("bidder"), "{logged}");
+        assert!(logged.contains("state dump"), "{logged}");
+
+        // an invalid filter leaves the one in effect
+        let err = control.set_log_filter("bidder=loud").unwrap_err();
+        assert!(err.to_string().contains("invalid log filter"), "{err}");
+        assert_eq!(control.log_filter(), "error");
+    });
+}
@@ -0,0 +1,163 @@
+use axum::{
+    body::Body,
+    http::{header::CONTENT_TYPE, Request},
+    Router,
+};
+use hyper::StatusCode;
+use rstest::*;
+use serde_json::Value;
+use taralli_primitives::{
+    alloy::{
+        primitives::{address, Address, U256},
+        providers::ProviderBuilder,
+    },
+    compression_utils::{compression, intents::PartialComputeRequest},
+    intents::request::ComputeRequest,
+    markets::SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+    systems::SystemParams,
+    validation::{offer::OfferValidationConfig, request::RequestValidationConfig},
+};
+use taralli_server::{
+    config::{Markets, ServerValidationConfigs},
+    error::ServerError,
+    state::{BaseState, MarketKind, MarketValidator},
+};
+use tower::ServiceExt;
+
+use crate::common::fixtures::{risc0_request_fixture, setup_app};
+
+pub mod common;
+
+const BOUNDARY: &str = "taralli-test-boundary";
+const PORCHETTA_ADDRESS: Address = address!("0000000000000000000000000000000000000001");
+const UNKNOWN_MARKET: Address = address!("00000000000000000000000000000000000000ff");
+
+fn submit_request(request: &ComputeRequest<SystemParams>) -> Request<Body> {
+    let partial = serde_json::to_vec(&PartialComputeRequest {
+        system_id: request.system_id,
+        proof_request: request.proof_request.clone(),
+        signature: request.signature,
+    })
+    .unwrap();
+    let system =
+        compression::compress_brotli(&serde_json::to_vec(&request.system).unwrap()).unwrap();
+
+    let mut body = Vec::new();
+    for (name, data) in [("partial_request", &partial), ("system_bytes", &system)] {
+        body.extend_from_slice(
+            format!("--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n")
+                .as_bytes(),
+        );
+        body.extend_from_slice(data);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{BOUNDARY}--\r\n").as_bytes());
+
+    Request::builder()
+        .method("POST")
+        .uri("/submit")
+        .header(
+            CONTENT_TYPE,
+            format!("multipart/form-data; boundary={BOUNDARY}"),
+        )
+        .body(Body::from(body))
+        .unwrap()
+}
+
+async fn error_body(response: axum::response::Response) -> Value {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[test]
+/// Ensures each market is validated against its own config, told apart by distinct limits.
+fn should_select_validator_by_market() {
+    let rpc_provider =
+        ProviderBuilder::new().on_http(reqwest::Url::parse("http://localhost:8080").unwrap());
+    let state = BaseState::new(
+        rpc_provider,
+        Markets {
+            universal_bombetta: SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+            universal_porchetta: PORCHETTA_ADDRESS,
+        },
+        std::time::Duration::from_secs(10),
+        ServerValidationConfigs {
+            request: RequestValidationConfig {
+                maximum_allowed_stake: 7,
+                ..Default::default()
+            },
+            offer: OfferValidationConfig {
+                minimum_allowed_stake: U256::from(11),
+                ..Default::default()
+            },
+        },
+    );
+
+    match state
+        .market_validator(&SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS)
+        .unwrap()
+    {
+        MarketValidator::Request(config) => assert_eq!(config.maximum_allowed_stake, 7),
+        other => panic!("unexpected validator: {other:?}"),
+    }
+    match state.market_validator(&PORCHETTA_ADDRESS).unwrap() {
+        MarketValidator::Offer(config) => {
+            assert_eq!(config.minimum_allowed_stake, U256::from(11))
+        }
+        other => panic!("unexpected validator: {other:?}"),
+    }
+    assert_eq!(
+        state.market_kind(&SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS),
+        Some(MarketKind::Request)
+    );
+
+    match state.market_validator(&UNKNOWN_MARKET).unwrap_err() {
+        ServerError::UnknownMarket { market, supported } => {
+            assert_eq!(market, UNKNOWN_MARKET);
+            assert!(supported.contains(&SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS.to_string()));
+            assert!(supported.contains(&PORCHETTA_ADDRESS.to_string()));
+        }
+        err => panic!("unexpected error: {err}"),
+    }
+}
+
+#[tokio::test]
+#[rstest]
+/// Ensures a request referencing the offer market is pointed at the offer route.
+async fn should_reject_request_for_offer_market(
+    mut risc0_request_fixture: ComputeRequest<SystemParams>,
+) {
+    let (app, _) = setup_app();
+    risc0_request_fixture.proof_request.market = PORCHETTA_ADDRESS;
+
+    let response = app
+        .oneshot(submit_request(&risc0_request_fixture))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = error_body(response).await;
+    assert!(body["error"].as_str().unwrap().contains("/submit/offer"));
+}
+
+#[tokio::test]
+#[rstest]
+/// Ensures intents referencing unknown markets are rejected with the supported markets listed.
+async fn should_reject_unknown_market(mut risc0_request_fixture: ComputeRequest<SystemParams>) {
+    let (app, _) = setup_app();
+    risc0_request_fixture.proof_request.market = UNKNOWN_MARKET;
+
+    let response = app
+        .oneshot(submit_request(&risc0_request_fixture))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = error_body(response).await;
+    assert_eq!(body["code"], "unknown_market");
+    let error = body["error"].as_str().unwrap();
+    assert!(error.contains(&SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS.to_string()));
+    assert!(error.contains(&PORCHETTA_ADDRESS.to_string()));
+}
@@ -0,0 +1,234 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{routing::get, Router};
+use futures::StreamExt;
+use rstest::*;
+use serial_test::serial;
+use taralli_client::api::subscribe::{StreamItem, SubscribeApiClient, SubscriptionStream};
+use taralli_primitives::alloy::{
+    primitives::{address, U256},
+    providers::ProviderBuilder,
+};
+use taralli_primitives::compression_utils::{
+    compression,
+    frames::deflate_request_frame,
+    intents::{
+        ComputeOfferCompressed, ComputeRequestCompressed, PartialComputeOffer,
+        PartialComputeRequest,
+    },
+};
+use taralli_primitives::intents::envelope::{unix_time_ms, BroadcastMeta};
+use taralli_primitives::intents::offer::ComputeOffer;
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::intents::ComputeIntent;
+use taralli_primitives::markets::SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS;
+use taralli_primitives::systems::SystemParams;
+use taralli_server::{
+    config::{Markets, ServerValidationConfigs},
+    routes::subscribe::websocket_subscribe_handler,
+    state::{request::RequestState, BaseState},
+    subscription_manager::{BroadcastKind, BroadcastedMessage, SubscriptionManager},
+};
+use tokio::net::TcpListener;
+use url::Url;
+pub mod common;
+use crate::common::fixtures::{risc0_offer_fixture, risc0_request_fixture};
+
+/// Serve the subscription route of a server broadcasting through `subscription_manager`
+async fn serve_subscriptions(subscription_manager: Arc<SubscriptionManager>) -> Url {
+    let base_state = BaseState::new(
+        ProviderBuilder::new().on_http("http://localhost:8545".parse().unwrap()),
+        Markets {
+            universal_bombetta: SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+            universal_porchetta: address!("0000000000000000000000000000000000000001"),
+        },
+        Duration::from_secs(10),
+        ServerValidationConfigs {
+            request: Default::default(),
+            offer: Default::default(),
+        },
+    );
+    let app = Router::new()
+        .route("/subscribe", get(websocket_subscribe_handler))
+        .with_state(RequestState::new(base_state, subscription_manager));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let server_url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("Couldn't serve");
+    });
+    server_url
+}
+
+fn next_meta(subscription_manager: &SubscriptionManager) -> BroadcastMeta {
+    BroadcastMeta {
+        sequence: subscription_manager.next_sequence(),
+        received_at_ms: unix_time_ms(),
+    }
+}
+
+/// Broadcast `request` the way the request submission handler does
+fn broadcast_request(
+    subscription_manager: &SubscriptionManager,
+    request: &ComputeRequest<SystemParams>,
+) {
+    let system = compression::compress_brotli(&serde_json::to_vec(&request.system).unwrap())
+        .expect("Couldn't compress system");
+    let request_compressed = ComputeRequestCompressed::from((
+        PartialComputeRequest {
+            system_id: request.system_id,
+            proof_request: request.proof_request.clone(),
+            signature: request.signature,
+        },
+        system,
+    ));
+    let meta = next_meta(subscription_manager);
+    subscription_manager
+        .broadcast(BroadcastedMessage {
+            content: bincode::serialize(&request_compressed).unwrap().into(),
+            subscribed_to: request.system_id.as_bit(),
+            kind: BroadcastKind::Intent,
+            deflated: Some(
+                deflate_request_frame(&request_compressed, meta)
+                    .unwrap()
+                    .into(),
+            ),
+            meta: Some(meta),
+        })
+        .expect("Couldn't broadcast");
+}
+
+/// Broadcast `offer` the way the offer submission handler does
+fn broadcast_offer(subscription_manager: &SubscriptionManager, offer: &ComputeOffer<SystemParams>) {
+    let system = compression::compress_brotli(&serde_json::to_vec(&offer.system).unwrap())
+        .expect("Couldn't compress system");
+    let offer_compressed = ComputeOfferCompressed::from((
+        PartialComputeOffer {
+            system_id: offer.system_id,
+            proof_offer: offer.proof_offer.clone(),
+            signature: offer.signature,
+        },
+        system,
+    ));
+    subscription_manager
+        .broadcast(BroadcastedMessage {
+            content: bincode::serialize(&offer_compressed).unwrap().into(),
+            subscribed_to: offer.system_id.as_bit(),
+            kind: BroadcastKind::Offer,
+            deflated: None,
+            meta: Some(next_meta(subscription_manager)),
+        })
+        .expect("Couldn't broadcast");
+}
+
+async fn next_item(subscription: &mut SubscriptionStream) -> StreamItem {
+    tokio::time::timeout(Duration::from_secs(5), subscription.next())
+        .await
+        .expect("No intent received")
+        .expect("Subscription ended")
+        .expect("Couldn't decode intent")
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures a client subscribed to both kinds of intents receives requests and offers in the order
+/// they're broadcast over a single connection, deflated or not, while a client subscribed to
+/// requests only never sees the offers.
+async fn should_deliver_mixed_traffic_to_dual_subscriptions(
+    risc0_request_fixture: ComputeRequest<SystemParams>,
+    risc0_offer_fixture: ComputeOffer<SystemParams>,
+) {
+    let subscription_manager = Arc::new(SubscriptionManager::new(10).with_frame_compression(true));
+    let server_url = serve_subscriptions(subscription_manager.clone()).await;
+
+    let system_mask = risc0_request_fixture.system_id.as_bit();
+    let dual = SubscribeApiClient::new(server_url.clone(), system_mask).with_offers(system_mask);
+    let mut dual_plain = dual.subscribe().await.unwrap();
+    let mut dual_deflating = dual
+        .clone()
+        .with_frame_compression(true)
+        .subscribe()
+        .await
+        .unwrap();
+    let mut requests_only = SubscribeApiClient::new(server_url, system_mask)
+        .subscribe()
+        .await
+        .unwrap();
+    // give the server time to register the upgraded connections
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    broadcast_request(&subscription_manager, &risc0_request_fixture);
+    broadcast_offer(&subscription_manager, &risc0_offer_fixture);
+    let mut second_request = risc0_request_fixture.clone();
+    second_request.proof_request.nonce += U256::from(1);
+    broadcast_request(&subscription_manager, &second_request);
+
+    for subscription in [&mut dual_plain, &mut dual_deflating] {
+        match next_item(subscription).await {
+            StreamItem::Request(request, Some(_)) => {
+                assert_eq!(request.compute_id(), risc0_request_fixture.compute_id());
+            }
+            other => panic!("Unexpected item: {other:?}"),
+        }
+        match next_item(subscription).await {
+            StreamItem::Offer(offer, Some(delivery)) => {
+                assert_eq!(offer.compute_id(), risc0_offer_fixture.compute_id());
+                assert_eq!(offer.system_id, risc0_offer_fixture.system_id);
+                assert_eq!(delivery.sequence, 1);
+            }
+            other => panic!("Unexpected item: {other:?}"),
+        }
+        match next_item(subscription).await {
+            StreamItem::Request(request, _) => {
+                assert_eq!(request.compute_id(), second_request.compute_id());
+            }
+            other => panic!("Unexpected item: {other:?}"),
+        }
+    }
+
+    for expected in [&risc0_request_fixture, &second_request] {
+        match next_item(&mut requests_only).await {
+            StreamItem::Request(request, _) => {
+                assert_eq!(request.compute_id(), expected.compute_id());
+            }
+            other => panic!("Unexpected item: {other:?}"),
+        }
+    }
+    assert!(
+        tokio::time::timeout(Duration::from_millis(300), requests_only.next())
+            .await
+            .is_err(),
+        "requests only subscription received an offer"
+    );
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures a client subscribed to the offers of a system only, and no requests, receives nothing
+/// but those offers.
+async fn should_deliver_offers_only_subscriptions(
+    risc0_request_fixture: ComputeRequest<SystemParams>,
+    risc0_offer_fixture: ComputeOffer<SystemParams>,
+) {
+    let subscription_manager = Arc::new(SubscriptionManager::new(10));
+    let server_url = serve_subscriptions(subscription_manager.clone()).await;
+
+    let mut offers_only = SubscribeApiClient::new(server_url, 0)
+        .with_offers(risc0_offer_fixture.system_id.as_bit())
+        .subscribe()
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    broadcast_request(&subscription_manager, &risc0_request_fixture);
+    broadcast_offer(&subscription_manager, &risc0_offer_fixture);
+
+    match next_item(&mut offers_only).await {
+        StreamItem::Offer(offer, _) => {
+            assert_eq!(offer.compute_id(), risc0_offer_fixture.compute_id());
+        }
+        other => panic!("Unexpected item: {other:?}"),
+    }
+}
@@ -0,0 +1,267 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::{routing::post, Router};
+use rstest::*;
+use serial_test::serial;
+use taralli_client::api::inputs::OfferInputsApiClient;
+use taralli_client::client::provider::offering::ProviderOfferingClient;
+use taralli_client::error::{ClientError, Result as ClientResult};
+use taralli_client::worker::{ComputeWorker, WorkResult};
+use taralli_primitives::alloy::{
+    primitives::{address, Address, Bytes, PrimitiveSignature, B256, U256},
+    providers::ProviderBuilder,
+    signers::local::PrivateKeySigner,
+    sol_types::SolValue,
+};
+use taralli_primitives::error::ErrorCode;
+use taralli_primitives::intents::{offer::ComputeOffer, ComputeIntent};
+use taralli_primitives::offer_inputs::{with_offer_inputs, OfferInputs};
+use taralli_primitives::systems::{risc0::Risc0ProofParams, SystemId, SystemParams};
+use taralli_server::{
+    clock::ManualClock,
+    error::ServerError,
+    offer_inputs::{DeliveredInputs, OfferInputsStore},
+    routes::inputs::{deliver_inputs_handler, get_inputs_handler},
+    state::offer::OfferInputsState,
+};
+use url::Url;
+pub mod common;
+use crate::common::fixtures::{
+    base_state_fixture, risc0_offer_fixture, start_server, PORCHETTA_FIXTURE_ADDRESS,
+};
+use crate::common::rpc::start_stub_node;
+
+type Offer = ComputeOffer<SystemParams>;
+
+struct UnreachableWorker;
+
+#[async_trait]
+impl ComputeWorker<Offer> for UnreachableWorker {
+    async fn execute(&self, _intent: &Offer) -> ClientResult<WorkResult> {
+        Err(ClientError::WorkerError(
+            "offers aren't proven by this test".to_string(),
+        ))
+    }
+}
+
+/// Stub rpc answering every `eth_call` with the given offer, bid upon by `requester`
+async fn start_rpc_stub(offer: &Offer, requester: Address, provider: Address) -> Url {
+    let active_offer = (
+        provider,
+        requester,
+        U256::from(2_000_000_000u64),
+        Address::ZERO,
+        U256::from(100),
+        Address::ZERO,
+        U256::from(10),
+        offer.proof_offer.inputsCommitment,
+        offer.proof_offer.extraData.clone(),
+    )
+        .abi_encode_params();
+    let result = Bytes::from(active_offer);
+
+    start_stub_node(move |_| serde_json::json!({ "result": result })).await
+}
+
+/// Serve the offer inputs routes with a market reading from the given rpc
+fn offer_inputs_app(rpc_url: Url) -> Router {
+    let base_state = base_state_fixture(rpc_url);
+    Router::new()
+        .route("/offers/:offer_id/inputs", post(deliver_inputs_handler))
+        .route("/offers/:offer_id/inputs/query", post(get_inputs_handler))
+        .with_state(OfferInputsState::new(base_state))
+}
+
+/// Inputs the risc0 offer fixture commits to
+fn committed_inputs() -> Vec<u8> {
+    U256::from(1304).abi_encode()
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures the inputs the requester of a won offer delivers reach its provider, which proves the
+/// offer over them, while inputs not matching the commitment or delivered by another party are
+/// turned away, and only the provider is served them. Risc0 params stand in for the system, the worker never runs.
+async fn should_deliver_inputs_to_offer_provider(risc0_offer_fixture: Offer) {
+    let requester = PrivateKeySigner::random();
+    let provider = PrivateKeySigner::random();
+    let outsider = PrivateKeySigner::random();
+    let rpc_url = start_rpc_stub(
+        &risc0_offer_fixture,
+        requester.address(),
+        provider.address(),
+    )
+    .await;
+    let server_url = start_server(offer_inputs_app(rpc_url.clone())).await;
+    let api = OfferInputsApiClient::new(server_url.clone());
+    let offer_id = risc0_offer_fixture.compute_id();
+
+    // the offer left its inputs to the requester
+    let mut offer = risc0_offer_fixture.clone();
+    offer.system = with_offer_inputs(&offer.system, &[0; 32]).unwrap();
+
+    // inputs of another party are refused
+    let outsider_inputs = OfferInputs::sign(offer_id, &committed_inputs(), &outsider)
+        .await
+        .unwrap();
+    let err = api
+        .deliver_inputs(offer_id, &outsider_inputs)
+        .await
+        .unwrap_err();
+    assert_eq!(err.server_code(), Some(ErrorCode::OfferInputsUnauthorized));
+
+    // inputs the offer doesn't commit to are refused
+    let wrong_inputs = OfferInputs::sign(offer_id, &U256::from(1305).abi_encode(), &requester)
+        .await
+        .unwrap();
+    let err = api
+        .deliver_inputs(offer_id, &wrong_inputs)
+        .await
+        .unwrap_err();
+    assert_eq!(err.server_code(), Some(ErrorCode::OfferInputsMismatch));
+    assert!(api
+        .fetch_inputs(offer_id, &provider)
+        .await
+        .unwrap()
+        .is_none());
+
+    // the provider gets the committed inputs into the system it proves
+    let client = ProviderOfferingClient::new(
+        server_url,
+        ProviderBuilder::new().on_http(rpc_url),
+        provider.clone(),
+        PORCHETTA_FIXTURE_ADDRESS,
+        SystemId::Risc0,
+        std::sync::Arc::new(UnreachableWorker),
+        Default::default(),
+        Default::default(),
+    )
+    .with_inputs_delivery(Duration::from_secs(5));
+    let inputs = OfferInputs::sign(offer_id, &committed_inputs(), &requester)
+        .await
+        .unwrap();
+    api.deliver_inputs(offer_id, &inputs).await.unwrap();
+
+    // the inputs are only served to the provider's query
+    let err = api.fetch_inputs(offer_id, &outsider).await.unwrap_err();
+    assert_eq!(err.server_code(), Some(ErrorCode::OfferInputsUnauthorized));
+    let err = api.fetch_inputs(offer_id, &requester).await.unwrap_err();
+    assert_eq!(err.server_code(), Some(ErrorCode::OfferInputsUnauthorized));
+    let received = client
+        .receive_inputs(
+            offer_id,
+            offer.clone(),
+            requester.address(),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap();
+    match received.system {
+        SystemParams::Risc0(Risc0ProofParams { inputs, .. }) => {
+            assert_eq!(inputs, committed_inputs())
+        }
+        system => panic!("unexpected system {system:?}"),
+    }
+
+    // inputs not signed by the bidder are never proven
+    let err = client
+        .receive_inputs(
+            offer_id,
+            offer.clone(),
+            outsider.address(),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.error_code(), "offer_inputs");
+
+    // nor are inputs another offer commits to
+    let mut other_offer = offer.clone();
+    other_offer.proof_offer.inputsCommitment =
+        address!("0000000000000000000000000000000000000003").into_word();
+    let err = client
+        .receive_inputs(
+            offer_id,
+            other_offer,
+            requester.address(),
+            Duration::from_secs(5),
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, ClientError::OfferInputsError(message) if message.contains("commitment")),
+        "{err}"
+    );
+
+    // the provider gives up on an offer whose inputs never arrive
+    let unknown_offer = address!("0000000000000000000000000000000000000004").into_word();
+    let err = client
+        .receive_inputs(
+            unknown_offer,
+            offer,
+            requester.address(),
+            Duration::from_millis(100),
+        )
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, ClientError::OfferInputsError(message) if message.contains("no inputs delivered")),
+        "{err}"
+    );
+}
+
+fn delivered(size: usize, expires_at: u64) -> DeliveredInputs {
+    DeliveredInputs {
+        inputs: OfferInputs {
+            compressed_inputs: Bytes::from(vec![0; size]),
+            signature: PrimitiveSignature::new(U256::from(1), U256::from(1), false),
+        },
+        provider: Address::ZERO,
+        expires_at,
+    }
+}
+
+#[test]
+/// Ensures the store caps the offers and bytes of inputs it keeps, turning deliveries down
+/// rather than evicting inputs a provider awaits, and drops inputs once their offer ends.
+fn should_bound_and_expire_offer_inputs() {
+    let clock = Arc::new(ManualClock::new(1_000));
+    let store = OfferInputsStore::new(2, 100).with_clock(clock.clone());
+
+    store
+        .deliver(B256::repeat_byte(1), delivered(60, 1_100))
+        .unwrap();
+    // over the total size
+    let err = store
+        .deliver(B256::repeat_byte(2), delivered(50, 1_200))
+        .unwrap_err();
+    assert!(matches!(err, ServerError::OfferInputsStoreFull(_)), "{err}");
+    // a delivery replacing an earlier one only counts once
+    store
+        .deliver(B256::repeat_byte(1), delivered(90, 1_100))
+        .unwrap();
+    store
+        .deliver(B256::repeat_byte(1), delivered(60, 1_100))
+        .unwrap();
+    store
+        .deliver(B256::repeat_byte(2), delivered(40, 1_200))
+        .unwrap();
+    // over the number of offers
+    let err = store
+        .deliver(B256::repeat_byte(3), delivered(0, 1_200))
+        .unwrap_err();
+    assert!(matches!(err, ServerError::OfferInputsStoreFull(_)), "{err}");
+
+    // the inputs of an ended offer are dropped, making room
+    clock.set(1_100);
+    assert!(store.inputs(&B256::repeat_byte(1)).is_none());
+    assert!(store.inputs(&B256::repeat_byte(2)).is_some());
+    assert_eq!(store.len(), 1);
+    store
+        .deliver(B256::repeat_byte(3), delivered(60, 1_200))
+        .unwrap();
+    assert_eq!(store.len(), 2);
+}
@@ -0,0 +1,270 @@
+use axum::{
+    body::Body,
+    http::{header::CONTENT_TYPE, Request},
+    Router,
+};
+use chrono::Utc;
+use hyper::StatusCode;
+use rstest::*;
+use serde_json::Value;
+use std::sync::Arc;
+use taralli_primitives::{
+    abi::verifier_details::{decode_offer_verifier_details, DecodeMode},
+    compression_utils::{
+        compression,
+        db::StoredIntent,
+        intents::{
+            ComputeOfferCompressed, ComputeRequestCompressed, PartialComputeOffer,
+            PartialComputeRequest,
+        },
+    },
+    encryption::SystemPayload,
+    intents::{
+        envelope::{IntentEnvelope, IntentKind},
+        offer::{compute_offer_id, ComputeOffer},
+        request::ComputeRequest,
+        ComputeIntent,
+    },
+    systems::{System, SystemId, SystemParams},
+    validation::{
+        offer::{validate_offer_inputs_commitment, ComputeOfferValidator},
+        IntentValidator,
+    },
+};
+use taralli_server::subscription_manager::SubscriptionManager;
+use tower::ServiceExt;
+
+use crate::common::fixtures::{
+    risc0_offer_fixture, risc0_request_fixture, setup_app, PORCHETTA_FIXTURE_ADDRESS,
+};
+
+pub mod common;
+
+const BOUNDARY: &str = "taralli-test-boundary";
+
+/// Compress the system of an intent the way `SubmitApiClient` does, then decompress it the way
+/// the server does
+async fn compress_and_decompress(system: &SystemParams) -> (Vec<u8>, SystemParams) {
+    let compressed = compression::compress_brotli(&serde_json::to_vec(system).unwrap()).unwrap();
+    let payload = compression::decompress_system_bounded(&compressed, &Default::default())
+        .await
+        .unwrap();
+    match payload {
+        SystemPayload::Plain(system) => (compressed, system),
+        SystemPayload::Encrypted(_) => panic!("plain system decompressed as encrypted"),
+    }
+}
+
+/// Stored intent as `Db::store_offer` writes it and the query route returns it
+fn stored_offer(compressed: &ComputeOfferCompressed) -> StoredIntent {
+    let stored = StoredIntent {
+        intent_id: compute_offer_id(&compressed.proof_offer, &compressed.signature),
+        system_id: compressed.system_id.as_str().to_string(),
+        system: compressed.system.clone(),
+        proof_commitment: serde_json::to_vec(&compressed.proof_offer).unwrap(),
+        signature: compressed.signature.as_bytes().to_vec(),
+        expiration_ts: Utc::now(),
+        created_at: Utc::now(),
+        expired_at: None,
+    };
+    // sent to searchers as JSON
+    serde_json::from_value(serde_json::to_value(&stored).unwrap()).unwrap()
+}
+
+fn submit_request(request: &ComputeRequest<SystemParams>) -> Request<Body> {
+    let partial = serde_json::to_vec(&PartialComputeRequest {
+        system_id: request.system_id,
+        proof_request: request.proof_request.clone(),
+        signature: request.signature,
+    })
+    .unwrap();
+    let system =
+        compression::compress_brotli(&serde_json::to_vec(&request.system).unwrap()).unwrap();
+
+    let mut body = Vec::new();
+    for (name, data) in [("partial_request", &partial), ("system_bytes", &system)] {
+        body.extend_from_slice(
+            format!("--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n")
+                .as_bytes(),
+        );
+        body.extend_from_slice(data);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{BOUNDARY}--\r\n").as_bytes());
+
+    Request::builder()
+        .method("POST")
+        .uri("/submit")
+        .header(
+            CONTENT_TYPE,
+            format!("multipart/form-data; boundary={BOUNDARY}"),
+        )
+        .body(Body::from(body))
+        .unwrap()
+}
+
+#[rstest]
+/// Ensures the offer fixture is one the server and searchers accept: signed for the porchetta
+/// market, with strictly decodable verifier details committing to its inputs.
+fn should_validate_offer_fixture(risc0_offer_fixture: ComputeOffer<SystemParams>) {
+    let offer = risc0_offer_fixture;
+    ComputeOfferValidator::new(Default::default(), Default::default())
+        .validate(
+            &offer,
+            offer.proof_offer.startAuctionTimestamp,
+            &PORCHETTA_FIXTURE_ADDRESS,
+        )
+        .unwrap();
+
+    let verifier_details =
+        decode_offer_verifier_details(&offer.proof_offer.extraData, DecodeMode::Strict).unwrap();
+    assert!(verifier_details.isShaCommitment);
+
+    let SystemParams::Risc0(params) = &offer.system else {
+        panic!("unexpected system: {:?}", offer.system);
+    };
+    validate_offer_inputs_commitment(&offer.proof_offer, &params.inputs).unwrap();
+}
+
+#[tokio::test]
+#[rstest]
+/// Ensures an offer comes out of the submission envelope, the compressed form the server stores
+/// and the stored intent searchers query exactly as it went in.
+async fn should_round_trip_offer_through_compression_envelope(
+    risc0_offer_fixture: ComputeOffer<SystemParams>,
+) {
+    let offer = risc0_offer_fixture;
+
+    let envelope = serde_json::to_vec(&IntentEnvelope::new(
+        IntentKind::Offer,
+        PartialComputeOffer {
+            system_id: offer.system_id,
+            proof_offer: offer.proof_offer.clone(),
+            signature: offer.signature,
+        },
+    ))
+    .unwrap();
+    let partial_offer =
+        IntentEnvelope::<PartialComputeOffer>::from_json_slice(&envelope, IntentKind::Offer)
+            .unwrap()
+            .payload;
+    let (system_bytes, system) = compress_and_decompress(&offer.system).await;
+    assert_eq!(system.system_id(), partial_offer.system_id);
+
+    let compressed = ComputeOfferCompressed::from((partial_offer, system_bytes));
+    let compressed: ComputeOfferCompressed =
+        bincode::deserialize(&bincode::serialize(&compressed).unwrap()).unwrap();
+
+    let stored = stored_offer(&compressed);
+    assert_eq!(stored.intent_id, offer.compute_id());
+    let restored = ComputeOffer::<SystemParams>::try_from(stored).unwrap();
+    assert_eq!(
+        serde_json::to_value(&restored).unwrap(),
+        serde_json::to_value(&offer).unwrap()
+    );
+    assert_eq!(restored.compute_id(), offer.compute_id());
+}
+
+#[tokio::test]
+#[rstest]
+/// Ensures requests go through the same envelope and compressed form as offers, so both intents
+/// serialize their commitments, signatures and systems the same way.
+async fn should_round_trip_request_like_offer(
+    risc0_request_fixture: ComputeRequest<SystemParams>,
+    risc0_offer_fixture: ComputeOffer<SystemParams>,
+) {
+    let request = risc0_request_fixture;
+    let envelope = serde_json::to_vec(&IntentEnvelope::new(
+        IntentKind::Request,
+        PartialComputeRequest {
+            system_id: request.system_id,
+            proof_request: request.proof_request.clone(),
+            signature: request.signature,
+        },
+    ))
+    .unwrap();
+    let partial_request =
+        IntentEnvelope::<PartialComputeRequest>::from_json_slice(&envelope, IntentKind::Request)
+            .unwrap()
+            .payload;
+    let (system_bytes, _) = compress_and_decompress(&request.system).await;
+    let compressed = ComputeRequestCompressed::from((partial_request, system_bytes));
+    let compressed: ComputeRequestCompressed =
+        bincode::deserialize(&bincode::serialize(&compressed).unwrap()).unwrap();
+    let system = compression::decompress_system(compressed.system)
+        .await
+        .unwrap();
+    assert_eq!(
+        serde_json::to_value(&system).unwrap(),
+        serde_json::to_value(&request.system).unwrap()
+    );
+    assert_eq!(compressed.signature, request.signature);
+
+    // the same system, compressed the same way, whichever intent carries it
+    let offer_system =
+        compression::compress_brotli(&serde_json::to_vec(&risc0_offer_fixture.system).unwrap())
+            .unwrap();
+    let request_system =
+        compression::compress_brotli(&serde_json::to_vec(&request.system).unwrap()).unwrap();
+    assert_eq!(offer_system, request_system);
+
+    // an envelope of one kind isn't accepted as the other
+    assert!(
+        IntentEnvelope::<PartialComputeOffer>::from_json_slice(&envelope, IntentKind::Offer)
+            .is_err()
+    );
+}
+
+#[tokio::test]
+#[rstest]
+/// Ensures stored offers whose system params belong to another system than the one they were
+/// stored for are rejected rather than handed to searchers of the wrong system.
+async fn should_reject_stored_offer_of_another_system(
+    risc0_offer_fixture: ComputeOffer<SystemParams>,
+) {
+    let offer = risc0_offer_fixture;
+    let (system_bytes, _) = compress_and_decompress(&offer.system).await;
+    let mut stored = stored_offer(&ComputeOfferCompressed::from((
+        PartialComputeOffer {
+            system_id: offer.system_id,
+            proof_offer: offer.proof_offer.clone(),
+            signature: offer.signature,
+        },
+        system_bytes,
+    )));
+    stored.system_id = SystemId::Sp1.as_str().to_string();
+
+    let err = ComputeOffer::<SystemParams>::try_from(stored).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("system params of risc0 stored for system sp1"),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+#[rstest]
+/// Ensures the server turns down intents whose system params belong to another system than the
+/// one they declare, before they reach that system's subscribers.
+async fn should_reject_request_of_another_system(
+    setup_app: (Router, Arc<SubscriptionManager>),
+    risc0_request_fixture: ComputeRequest<SystemParams>,
+) {
+    let (app, _) = setup_app;
+    let mut request = risc0_request_fixture;
+    request.system_id = SystemId::Sp1;
+
+    let response = app.oneshot(submit_request(&request)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: Value = serde_json::from_slice(
+        &axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(body["code"], "validation_failed");
+    assert_eq!(
+        body["error"],
+        "system params of risc0 submitted for system sp1"
+    );
+}
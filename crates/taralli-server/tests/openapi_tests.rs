@@ -0,0 +1,172 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{Request, StatusCode};
+use serde_json::Value;
+use taralli_primitives::error::ErrorCode;
+use taralli_primitives::systems::SYSTEMS;
+use taralli_server::openapi::{api_routes, openapi_spec, ApiRoute, API_DOCS_PATH, OPENAPI_VERSION};
+use taralli_server::router;
+use taralli_server::state::request::RequestState;
+use taralli_server::subscription_manager::SubscriptionManager;
+use tower::ServiceExt;
+
+pub mod common;
+use common::fixtures::base_state_fixture;
+
+/// (method, path) of every route the server binary's router is built from
+fn registered_routes() -> BTreeSet<(String, String)> {
+    router::registered_routes()
+        .into_iter()
+        .map(|ApiRoute { method, path }| (method.to_string(), path.to_string()))
+        .collect()
+}
+
+fn described_routes() -> BTreeSet<(String, String)> {
+    api_routes()
+        .into_iter()
+        .map(|ApiRoute { method, path }| (method.to_string(), path.to_string()))
+        .collect()
+}
+
+/// Every `$ref` of `value`
+fn collect_refs<'a>(value: &'a Value, refs: &mut Vec<&'a str>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                match (key.as_str(), value) {
+                    ("$ref", Value::String(reference)) => refs.push(reference),
+                    _ => collect_refs(value, refs),
+                }
+            }
+        }
+        Value::Array(values) => values.iter().for_each(|value| collect_refs(value, refs)),
+        _ => {}
+    }
+}
+
+#[test]
+/// Ensures the description covers exactly the routes the server binary registers, so a route
+/// added without being described fails here.
+fn should_describe_every_registered_route() {
+    let registered = registered_routes();
+    let described = described_routes();
+    assert_eq!(
+        registered.len(),
+        router::registered_routes().len(),
+        "route registered twice"
+    );
+    assert_eq!(
+        registered.difference(&described).collect::<Vec<_>>(),
+        Vec::<&(String, String)>::new(),
+        "routes registered but not described"
+    );
+    assert_eq!(
+        described.difference(&registered).collect::<Vec<_>>(),
+        Vec::<&(String, String)>::new(),
+        "routes described but not registered"
+    );
+    assert_eq!(described.len(), api_routes().len(), "route described twice");
+}
+
+#[tokio::test]
+/// Ensures the router built from the registered routes serves the description.
+async fn should_serve_description_from_router() {
+    let state = RequestState::new(
+        base_state_fixture("http://localhost:8545".parse().unwrap()),
+        Arc::new(SubscriptionManager::new(2)),
+    );
+    let app = router::build(router::request_routes()).with_state(state);
+
+    let response = app
+        .oneshot(Request::get(API_DOCS_PATH).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let served: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(served, openapi_spec());
+}
+
+#[test]
+/// Ensures every described route is an operation of the spec, paths in OpenAPI's syntax.
+fn should_build_paths_from_routes() {
+    let spec = openapi_spec();
+    assert_eq!(spec["openapi"], OPENAPI_VERSION);
+    assert_eq!(spec["info"]["version"], env!("CARGO_PKG_VERSION"));
+
+    let paths = spec["paths"].as_object().unwrap();
+    for route in api_routes() {
+        let path = route.openapi_path();
+        assert!(!path.contains(':'), "{path}");
+        let operation = &paths[&path][route.method];
+        assert!(operation.is_object(), "{} {path} missing", route.method);
+        assert!(!operation["responses"].as_object().unwrap().is_empty());
+
+        let path_params: Vec<&str> = path
+            .split('/')
+            .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+            .collect();
+        let declared: Vec<&str> = operation["parameters"]
+            .as_array()
+            .map(|parameters| {
+                parameters
+                    .iter()
+                    .filter(|parameter| parameter["in"] == "path")
+                    .map(|parameter| parameter["name"].as_str().unwrap())
+                    .collect()
+            })
+            .unwrap_or_default();
+        assert_eq!(path_params, declared, "{} {path}", route.method);
+    }
+    assert_eq!(
+        ApiRoute {
+            method: "get",
+            path: "/intents/:intent_id/key/request"
+        }
+        .openapi_path(),
+        "/intents/{intent_id}/key/request"
+    );
+}
+
+#[test]
+/// Ensures the documented enumerations are those of the primitives and every reference resolves.
+fn should_document_error_codes_and_systems() {
+    let spec = openapi_spec();
+    let schemas = &spec["components"]["schemas"];
+
+    let codes: Vec<&str> = schemas["Error"]["properties"]["code"]["enum"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|code| code.as_str().unwrap())
+        .collect();
+    assert_eq!(
+        codes,
+        ErrorCode::ALL
+            .iter()
+            .map(ErrorCode::as_str)
+            .collect::<Vec<_>>()
+    );
+
+    let system_ids: Vec<Value> = SYSTEMS
+        .iter()
+        .map(|system_id| serde_json::to_value(system_id).unwrap())
+        .collect();
+    assert_eq!(schemas["SystemId"]["enum"].as_array().unwrap(), &system_ids);
+    assert_eq!(
+        schemas["SystemName"]["enum"].as_array().unwrap().len(),
+        SYSTEMS.len()
+    );
+
+    let mut refs = Vec::new();
+    collect_refs(&spec, &mut refs);
+    assert!(!refs.is_empty());
+    for reference in refs {
+        let pointer = reference
+            .strip_prefix('#')
+            .unwrap_or_else(|| panic!("external reference {reference}"));
+        assert!(spec.pointer(pointer).is_some(), "dangling {reference}");
+    }
+}
@@ -0,0 +1,117 @@
+//! Runs against the postgres of `docker-compose.yml`, enable with `--features postgres-tests`
+#![cfg(feature = "postgres-tests")]
+
+use serial_test::serial;
+use taralli_primitives::alloy::primitives::U256;
+use taralli_primitives::intents::envelope::IntentMetadata;
+use taralli_primitives::systems::SystemId;
+use taralli_server::intent_store::{IntentStatus, IntentStore, RequestFilter};
+use taralli_server::postgres::Db;
+
+use crate::common::intent_store::{compressed_request_fixture, SIGNER};
+
+pub mod common;
+
+#[tokio::test]
+#[serial]
+/// Ensures requests survive a reconnect with their fields, requester metadata and status.
+async fn should_persist_requests_across_connections() {
+    // unique per run so reruns against the same database don't collide
+    let nonce = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos() as u64;
+    let request = compressed_request_fixture(SystemId::Risc0, nonce, u64::from(u32::MAX));
+
+    let metadata = IntentMetadata::from([("job".to_string(), "42".to_string())]);
+
+    let stored = Db::new()
+        .await
+        .store_request(&request, &metadata)
+        .await
+        .unwrap();
+    Db::new()
+        .await
+        .update_request_status(stored.intent_id, IntentStatus::Broadcast)
+        .await
+        .unwrap();
+
+    let requests = Db::new()
+        .await
+        .get_requests(&RequestFilter {
+            system_id: Some(SystemId::Risc0),
+            signer: Some(SIGNER),
+            status: Some(IntentStatus::Broadcast),
+            active_at: Some(1_500),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    let restored = requests
+        .into_iter()
+        .find(|stored_request| stored_request.intent_id == stored.intent_id)
+        .expect("stored request not found");
+
+    assert_eq!(restored.system[..], request.system[..]);
+    assert_eq!(restored.end_auction_timestamp, u64::from(u32::MAX));
+    assert_eq!(
+        Db::new()
+            .await
+            .get_request_metadata(SIGNER)
+            .await
+            .unwrap()
+            .get(&stored.intent_id),
+        Some(&metadata)
+    );
+    let proof_request: taralli_primitives::abi::universal_bombetta::UniversalBombetta::ProofRequest =
+        serde_json::from_slice(&restored.proof_commitment).unwrap();
+    assert_eq!(proof_request.nonce, U256::from(nonce));
+}
+
+#[tokio::test]
+#[serial]
+/// Ensures archived requests are only returned when asked for.
+async fn should_exclude_archived_requests() {
+    let nonce = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos() as u64;
+    let db = Db::new().await;
+    let stored = db
+        .store_request(
+            &compressed_request_fixture(SystemId::Sp1, nonce, 2_000),
+            &IntentMetadata::new(),
+        )
+        .await
+        .unwrap();
+    db.update_request_status(stored.intent_id, IntentStatus::Archived)
+        .await
+        .unwrap();
+
+    let found = |filter: RequestFilter| {
+        let db = &db;
+        async move {
+            db.get_requests(&filter)
+                .await
+                .unwrap()
+                .iter()
+                .any(|request| request.intent_id == stored.intent_id)
+        }
+    };
+    assert!(!found(RequestFilter::default()).await);
+    assert!(
+        found(RequestFilter {
+            include_archived: true,
+            ended_before: Some(2_001),
+            ..Default::default()
+        })
+        .await
+    );
+    assert!(
+        found(RequestFilter {
+            status: Some(IntentStatus::Archived),
+            ..Default::default()
+        })
+        .await
+    );
+}
@@ -0,0 +1,118 @@
+//! Runs against the postgres of `docker-compose.yml`, enable with
+//! `--features postgres-tests,ci-test` so offer timestamps aren't checked against a chain
+#![cfg(all(feature = "postgres-tests", feature = "ci-test"))]
+
+use axum::Router;
+use hyper::StatusCode;
+use rstest::*;
+use serde_json::Value;
+use serial_test::serial;
+use taralli_client::api::{query::QueryApiClient, submit::SubmitApiClient};
+use taralli_primitives::alloy::primitives::U256;
+use taralli_primitives::encryption::EncryptedSystemParams;
+use taralli_primitives::intents::{offer::ComputeOffer, ComputeIntent};
+use taralli_primitives::systems::{SystemId, SystemParams};
+use tokio::net::TcpListener;
+use url::Url;
+
+use crate::common::fixtures::{risc0_offer_fixture, setup_offer_app, sign_offer_fixture};
+
+pub mod common;
+
+async fn serve(app: Router) -> Url {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let server_url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("Couldn't serve");
+    });
+    server_url
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures a submitted offer is stored and handed back unchanged to searchers querying its
+/// system, and only to them.
+async fn should_store_and_query_submitted_offer(risc0_offer_fixture: ComputeOffer<SystemParams>) {
+    let server_url = serve(setup_offer_app().await).await;
+    // unique per run so reruns against the same database don't collide
+    let mut offer = risc0_offer_fixture;
+    offer.proof_offer.nonce = U256::from(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos(),
+    );
+    let offer = sign_offer_fixture(offer);
+
+    let response = SubmitApiClient::new(server_url.clone())
+        .submit_intent(offer.clone())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::CREATED);
+
+    let query = QueryApiClient::new(server_url);
+    let offers = query.query_market_offers(SystemId::Risc0).await.unwrap();
+    let stored = offers
+        .into_iter()
+        .find(|stored| stored.compute_id() == offer.compute_id())
+        .expect("submitted offer not found");
+    assert_eq!(
+        serde_json::to_value(&stored).unwrap(),
+        serde_json::to_value(&offer).unwrap()
+    );
+
+    let offers = query.query_market_offers(SystemId::Sp1).await.unwrap();
+    assert!(offers
+        .iter()
+        .all(|stored| stored.compute_id() != offer.compute_id()));
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures offers with encrypted system params are rejected, as no key could ever be delivered
+/// for them and searchers couldn't decode them.
+async fn should_reject_encrypted_offer(risc0_offer_fixture: ComputeOffer<SystemParams>) {
+    let server_url = serve(setup_offer_app().await).await;
+    let offer = risc0_offer_fixture;
+    let (encrypted, _) = EncryptedSystemParams::encrypt(&offer.system, offer.compute_id()).unwrap();
+    let encrypted_offer = ComputeOffer {
+        system_id: offer.system_id,
+        system: encrypted,
+        proof_offer: offer.proof_offer,
+        signature: offer.signature,
+    };
+
+    let response = SubmitApiClient::new(server_url)
+        .submit_intent(encrypted_offer)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "validation_failed");
+    assert_eq!(body["error"], "offers can't carry encrypted system params");
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures offers whose system params belong to another system than the one they declare are
+/// rejected.
+async fn should_reject_offer_of_another_system(risc0_offer_fixture: ComputeOffer<SystemParams>) {
+    let server_url = serve(setup_offer_app().await).await;
+    let mut offer = risc0_offer_fixture;
+    offer.system_id = SystemId::Sp1;
+
+    let response = SubmitApiClient::new(server_url)
+        .submit_intent(offer)
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["code"], "validation_failed");
+    assert_eq!(
+        body["error"],
+        "system params of risc0 submitted for system sp1"
+    );
+}
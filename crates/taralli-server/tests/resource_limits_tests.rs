@@ -0,0 +1,224 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::DefaultBodyLimit,
+    http::{header::CONTENT_TYPE, Request},
+    routing::{get, post},
+    Router,
+};
+use hyper::StatusCode;
+use rstest::*;
+use serde_json::Value;
+use taralli_client::api::status::ServerStatus;
+use taralli_primitives::{
+    alloy::{primitives::address, providers::ProviderBuilder},
+    compression_utils::{compression, intents::PartialComputeRequest},
+    intents::request::ComputeRequest,
+    markets::SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+    systems::SystemParams,
+    validation::{
+        limits::{ElfLimits, R1csLimits, ResourceLimits},
+        request::RequestValidationConfig,
+        BaseValidationConfig,
+    },
+};
+use taralli_server::{
+    config::{Markets, ServerValidationConfigs},
+    routes::{status::get_status_handler, submit::submit_request_handler},
+    state::{request::RequestState, BaseState},
+    subscription_manager::SubscriptionManager,
+};
+use tower::ServiceExt;
+
+use crate::common::fixtures::{groth16_request_fixture, risc0_request_fixture};
+
+pub mod common;
+
+const BOUNDARY: &str = "taralli-test-boundary";
+
+/// Server whose requests are checked against `resource_limits`
+fn limited_app(resource_limits: ResourceLimits) -> Router {
+    let base_state = BaseState::new(
+        ProviderBuilder::new().on_http(reqwest::Url::parse("http://localhost:8080").unwrap()),
+        Markets {
+            universal_bombetta: SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+            universal_porchetta: address!("0000000000000000000000000000000000000001"),
+        },
+        std::time::Duration::from_secs(10),
+        ServerValidationConfigs {
+            request: RequestValidationConfig {
+                base: BaseValidationConfig {
+                    resource_limits,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            offer: Default::default(),
+        },
+    );
+    Router::new()
+        // the groth16 fixture is past the default body limit
+        .route(
+            "/submit",
+            post(submit_request_handler).layer(DefaultBodyLimit::disable()),
+        )
+        .route("/status", get(get_status_handler))
+        .with_state(RequestState::new(
+            base_state,
+            Arc::new(SubscriptionManager::new(2)),
+        ))
+}
+
+fn submit_request(request: &ComputeRequest<SystemParams>) -> Request<Body> {
+    let partial = serde_json::to_vec(&PartialComputeRequest {
+        system_id: request.system_id,
+        proof_request: request.proof_request.clone(),
+        signature: request.signature,
+    })
+    .unwrap();
+    let system =
+        compression::compress_brotli(&serde_json::to_vec(&request.system).unwrap()).unwrap();
+
+    let mut body = Vec::new();
+    for (name, data) in [("partial_request", &partial), ("system_bytes", &system)] {
+        body.extend_from_slice(
+            format!("--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n")
+                .as_bytes(),
+        );
+        body.extend_from_slice(data);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{BOUNDARY}--\r\n").as_bytes());
+
+    Request::builder()
+        .method("POST")
+        .uri("/submit")
+        .header(
+            CONTENT_TYPE,
+            format!("multipart/form-data; boundary={BOUNDARY}"),
+        )
+        .body(Body::from(body))
+        .unwrap()
+}
+
+async fn response_body(response: axum::response::Response) -> Value {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+fn elf_len(request: &ComputeRequest<SystemParams>) -> usize {
+    match &request.system {
+        SystemParams::Risc0(params) => params.elf.len(),
+        other => panic!("unexpected system: {other:?}"),
+    }
+}
+
+#[tokio::test]
+#[rstest]
+/// Ensures an ELF one byte over the configured maximum is rejected before anything is fetched
+/// from the chain, while one at the maximum gets past the check.
+async fn test_elf_size_limit(risc0_request_fixture: ComputeRequest<SystemParams>) {
+    let elf_limits = |max_elf_bytes| ResourceLimits {
+        risc0: ElfLimits {
+            max_elf_bytes,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let elf_len = elf_len(&risc0_request_fixture);
+
+    let response = limited_app(elf_limits(elf_len - 1))
+        .oneshot(submit_request(&risc0_request_fixture))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = response_body(response).await;
+    assert_eq!(body["code"], "validation_failed");
+    assert_eq!(
+        body["error"],
+        format!(
+            "risc0 elf size {elf_len} exceeds the maximum of {}",
+            elf_len - 1
+        )
+    );
+
+    // later validation steps may turn it down, the resource limits don't
+    let response = limited_app(elf_limits(elf_len))
+        .oneshot(submit_request(&risc0_request_fixture))
+        .await
+        .unwrap();
+    let body = response_body(response).await;
+    assert!(
+        !body["error"]
+            .as_str()
+            .is_some_and(|error| error.contains("elf size")),
+        "{body}"
+    );
+}
+
+#[tokio::test]
+#[rstest]
+/// Ensures circuits declaring more signals than allowed in their r1cs header are rejected.
+async fn test_r1cs_signal_limit(groth16_request_fixture: ComputeRequest<SystemParams>) {
+    let app = limited_app(ResourceLimits {
+        arkworks: R1csLimits {
+            max_signals: 1_000,
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+
+    let response = app
+        .oneshot(submit_request(&groth16_request_fixture))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let body = response_body(response).await;
+    assert_eq!(body["code"], "validation_failed");
+    assert_eq!(
+        body["error"],
+        "arkworks signal count 62417 exceeds the maximum of 1000"
+    );
+}
+
+#[tokio::test]
+/// Ensures `/status` reports the configured resource limits of each system.
+async fn test_status_reports_resource_limits() {
+    let app = limited_app(ResourceLimits {
+        sp1: ElfLimits {
+            max_input_bytes: 64,
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/status")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let status: ServerStatus = serde_json::from_value(response_body(response).await).unwrap();
+
+    let limits = &status.validation.request.resource_limits;
+    assert_eq!(limits.sp1.max_input_bytes, 64);
+    assert_eq!(
+        limits.risc0.max_elf_bytes,
+        ElfLimits::default().max_elf_bytes
+    );
+    assert_eq!(
+        limits.arkworks.max_signals,
+        R1csLimits::default().max_signals
+    );
+    assert_eq!(
+        status.validation.offer.resource_limits.sp1.max_input_bytes,
+        ElfLimits::default().max_input_bytes
+    );
+}
@@ -1,26 +1,42 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::Router;
+use base64::prelude::{Engine, BASE64_STANDARD};
 use common::fixtures::provider_fixture;
 use hyper::StatusCode;
 use rstest::*;
 use serde_json::{json, Value};
 use serial_test::serial;
-use taralli_client::api::{submit::SubmitApiClient, subscribe::SubscribeApiClient};
+use taralli_client::api::{
+    query::QueryApiClient,
+    submit::SubmitApiClient,
+    subscribe::{StreamItem, SubscribeApiClient},
+};
+use taralli_client::client::provider::capabilities::CapabilityAdvertiser;
 use taralli_primitives::{
+    alloy::{primitives::U256, signers::local::PrivateKeySigner},
+    capabilities::SystemCapability,
     compression_utils::{
         compression,
         intents::{ComputeRequestCompressed, PartialComputeRequest},
     },
-    intents::request::ComputeRequest,
+    intents::envelope::{IntentMetadata, SSE_INTENT_EVENT},
+    intents::{request::ComputeRequest, ComputeIntent},
+    markets::SEPOLIA_CHAIN_ID,
     systems::{SystemId, SystemParams},
 };
-use taralli_server::subscription_manager::{BroadcastedMessage, SubscriptionManager};
+use taralli_server::subscription_manager::{
+    BroadcastKind, BroadcastedMessage, SubscriptionManager,
+};
 use tokio::net::TcpListener;
 use tokio_stream::StreamExt;
 use url::Url;
-mod common;
-use crate::common::fixtures::{requester_fixture, risc0_request_fixture, setup_app};
+pub mod common;
+use crate::common::fixtures::{
+    requester_fixture, requester_signer_fixture, risc0_request_fixture, setup_app,
+    sign_request_fixture,
+};
 use futures::FutureExt;
 
 #[tokio::test]
@@ -38,7 +54,7 @@ async fn test_submit_with_no_subscribers(
     let response_body: Value = response.json().await.unwrap();
     assert_eq!(
         response_body,
-        json!({"error": "No proof providers available"})
+        json!({"error": "No proof providers available", "code": "no_providers_available"})
     );
 }
 
@@ -100,6 +116,69 @@ async fn test_broadcast_multiple(
     );
 }
 
+#[tokio::test]
+#[rstest]
+#[serial]
+// Assert a provider whose capabilities report no free slot for the request's system isn't sent it
+// nor counted among its receivers, until it frees a slot.
+async fn test_broadcast_skips_saturated_provider(
+    requester_fixture: SubmitApiClient,
+    risc0_request_fixture: ComputeRequest<SystemParams>,
+) {
+    let advertiser = CapabilityAdvertiser::new(
+        Url::parse("http://localhost:8080").unwrap(),
+        PrivateKeySigner::random(),
+        vec![SystemCapability {
+            system_id: SystemId::Risc0,
+            typical_proving_time: 1,
+            minimum_reward: U256::ZERO,
+        }],
+        1,
+    );
+    let job = advertiser.track_job();
+    advertiser.register().await.unwrap();
+    let mut saturated_subscription = provider_fixture()
+        .with_provider(advertiser.provider())
+        .subscribe_to_markets()
+        .await
+        .expect("Couldn't subscribe");
+    let mut free_subscription = provider_fixture()
+        .subscribe_to_markets()
+        .await
+        .expect("Couldn't subscribe");
+
+    let response = requester_fixture
+        .submit_intent(risc0_request_fixture.clone())
+        .await
+        .expect("Couldn't submit");
+    assert_eq!(response.status(), StatusCode::OK);
+    let response_body: Value = response.json().await.unwrap();
+    assert_eq!(
+        response_body,
+        json!({
+            "message": "compute request broadcast to providers",
+            "broadcast_receivers": 1
+        })
+    );
+    assert!(free_subscription.next().await.unwrap().is_ok());
+    assert!(
+        tokio::time::timeout(Duration::from_millis(500), saturated_subscription.next())
+            .await
+            .is_err()
+    );
+
+    // the job finished, the provider gets requests again
+    drop(job);
+    advertiser.refresh().await.unwrap();
+    let response = requester_fixture
+        .submit_intent(risc0_request_fixture)
+        .await
+        .expect("Couldn't submit");
+    let response_body: Value = response.json().await.unwrap();
+    assert_eq!(response_body["broadcast_receivers"], 2);
+    assert!(saturated_subscription.next().await.unwrap().is_ok());
+}
+
 #[tokio::test]
 #[rstest]
 #[serial]
@@ -134,7 +213,7 @@ async fn test_broadcast_dropped_subscriber(
     let response_body: Value = response.json().await.unwrap();
     assert_eq!(
         response_body,
-        json!({"error": "No proof providers available"})
+        json!({"error": "No proof providers available", "code": "no_providers_available"})
     );
 }
 
@@ -289,6 +368,50 @@ async fn test_reconnect_dropped_subscriber(
     assert!(message.is_ok());
 }
 
+#[tokio::test]
+#[rstest]
+#[serial]
+// Assert the requests broadcast for successive submissions carry increasing sequence numbers and
+// the time the server received them, from which the delivery delay is computed.
+async fn test_broadcast_sequence_across_submissions(
+    requester_fixture: SubmitApiClient,
+    provider_fixture: SubscribeApiClient,
+    risc0_request_fixture: ComputeRequest<SystemParams>,
+) {
+    let mut subscription = provider_fixture
+        .subscribe()
+        .await
+        .expect("Couldn't subscribe");
+    for _ in 0..3 {
+        let response = requester_fixture
+            .submit_intent(risc0_request_fixture.clone())
+            .await
+            .expect("Couldn't submit");
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let mut sequences = Vec::new();
+    while sequences.len() < 3 {
+        let item = tokio::time::timeout(std::time::Duration::from_secs(5), subscription.next())
+            .await
+            .expect("No request received")
+            .expect("Subscription ended")
+            .expect("Couldn't parse request");
+        let delivery = match item {
+            StreamItem::Request(_, delivery) => delivery.expect("Request without delivery"),
+            // expiry notifications of previous tests' requests may be interleaved
+            _ => continue,
+        };
+        assert!(delivery.received_at_ms >= delivery.server_received_at_ms);
+        assert_eq!(
+            delivery.delay().as_millis() as u64,
+            delivery.received_at_ms - delivery.server_received_at_ms
+        );
+        sequences.push(delivery.sequence);
+    }
+    assert!(sequences.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
 #[tokio::test]
 #[rstest]
 #[serial]
@@ -399,8 +522,11 @@ async fn test_corrupted_data_on_subscribe(
     let request_serialized = bincode::serialize(&request_compressed)
         .expect("Couldn't serialize request for BroadcastedMessage");
     let message_to_broadcast = BroadcastedMessage {
-        content: request_serialized,
+        content: request_serialized.into(),
         subscribed_to: partial_request.system_id.as_bit(),
+        kind: BroadcastKind::Intent,
+        deflated: None,
+        meta: None,
     };
 
     // Let's add some bogus data to system_information_bytes so we can check how the subscriber handles it.
@@ -414,8 +540,11 @@ async fn test_corrupted_data_on_subscribe(
     let corrupted_serialized = bincode::serialize(&request_compressed)
         .expect("Couldn't serialize corrupted request for BroadcastedMessage");
     let message_to_broadcast_corrupted = BroadcastedMessage {
-        content: corrupted_serialized,
+        content: corrupted_serialized.into(),
         subscribed_to: partial_request.system_id.as_bit(),
+        kind: BroadcastKind::Intent,
+        deflated: None,
+        meta: None,
     };
 
     let mut subscription = SubscribeApiClient::new(
@@ -449,3 +578,109 @@ async fn test_corrupted_data_on_subscribe(
 
     server_handle.abort();
 }
+
+/// Next intent broadcast over an SSE subscription, as the raw binary message sent to providers
+async fn next_raw_sse_intent(response: &mut reqwest::Response) -> Vec<u8> {
+    let mut buffer = String::new();
+    loop {
+        let chunk = tokio::time::timeout(Duration::from_secs(5), response.chunk())
+            .await
+            .expect("No intent received")
+            .expect("Couldn't read the subscription")
+            .expect("Subscription ended");
+        buffer.push_str(&String::from_utf8_lossy(&chunk));
+        while let Some(end) = buffer.find("\n\n") {
+            let event: String = buffer.drain(..end + 2).collect();
+            if !event
+                .lines()
+                .any(|line| line == format!("event: {SSE_INTENT_EVENT}"))
+            {
+                continue;
+            }
+            let data = event
+                .lines()
+                .find_map(|line| line.strip_prefix("data: "))
+                .expect("Intent event without data");
+            return BASE64_STANDARD.decode(data).expect("Invalid base64 data");
+        }
+    }
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+// Assert the metadata submitted along with a request is echoed back and served to the requester's
+// signed queries, while neither the message broadcast to providers nor the public listing of
+// requests carries it.
+async fn test_request_metadata_kept_for_requester(
+    requester_fixture: SubmitApiClient,
+    risc0_request_fixture: ComputeRequest<SystemParams>,
+) {
+    // unique per run so the stored request is this one, not one of a previous run
+    let nonce = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let mut request = risc0_request_fixture;
+    request.proof_request.nonce = U256::from(nonce);
+    let request = sign_request_fixture(request);
+    let job = format!("job-{nonce}");
+    let metadata = IntentMetadata::from([("job".to_string(), job.clone())]);
+
+    let mut provider_stream = reqwest::get("http://localhost:8080/subscribe/sse")
+        .await
+        .expect("Couldn't subscribe");
+    let response = requester_fixture
+        .submit_intent_with_metadata(request.clone(), &metadata)
+        .await
+        .expect("Couldn't submit");
+    assert_eq!(response.status(), StatusCode::OK);
+    let response_body: Value = response.json().await.unwrap();
+    assert_eq!(response_body["metadata"], json!({ "job": job }));
+
+    let broadcast = next_raw_sse_intent(&mut provider_stream).await;
+    assert!(!broadcast
+        .windows(job.len())
+        .any(|window| window == job.as_bytes()));
+
+    // persisted in the background once answered
+    let query = QueryApiClient::new(Url::parse("http://localhost:8080").unwrap());
+    let stored_metadata = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let requests = query
+                .query_signer_requests(&requester_signer_fixture(), SEPOLIA_CHAIN_ID)
+                .await
+                .expect("Couldn't query requests");
+            if let Some((_, metadata)) = requests
+                .into_iter()
+                .find(|(stored, _)| stored.compute_id() == request.compute_id())
+            {
+                return metadata;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("request wasn't stored");
+    assert_eq!(stored_metadata, metadata);
+
+    let listing = reqwest::get(format!(
+        "http://localhost:8080/requests?signer={}",
+        request.proof_request.signer
+    ))
+    .await
+    .expect("Couldn't list requests")
+    .bytes()
+    .await
+    .unwrap();
+    assert!(!listing
+        .windows(job.len())
+        .any(|window| window == job.as_bytes()));
+
+    // another address is served the metadata of its own requests only
+    let outsider_metadata = query
+        .query_request_metadata(&PrivateKeySigner::random(), SEPOLIA_CHAIN_ID)
+        .await
+        .expect("Couldn't query metadata");
+    assert!(outsider_metadata.is_empty());
+}
@@ -0,0 +1,53 @@
+use rstest::*;
+use taralli_primitives::{
+    intents::{request::ComputeRequest, ComputeIntent},
+    markets::SEPOLIA_CHAIN_ID,
+    systems::SystemParams,
+    utils::{permit2_domain_separator, PERMIT2_DOMAIN_SEPARATOR},
+    validation::request::validate_request_signature,
+};
+
+use crate::common::fixtures::risc0_request_fixture;
+
+pub mod common;
+
+const MAINNET_CHAIN_ID: u64 = 1;
+
+#[test]
+/// Ensures the computed permit2 domain matches the deployed sepolia domain separator.
+fn test_permit2_domain_separator_matches_sepolia() {
+    assert_eq!(
+        permit2_domain_separator(SEPOLIA_CHAIN_ID),
+        PERMIT2_DOMAIN_SEPARATOR
+    );
+    assert_ne!(
+        permit2_domain_separator(MAINNET_CHAIN_ID),
+        PERMIT2_DOMAIN_SEPARATOR
+    );
+}
+
+#[rstest]
+/// Ensures an intent signed for the configured chain passes signature validation.
+fn test_signature_valid_on_configured_chain(risc0_request_fixture: ComputeRequest<SystemParams>) {
+    assert!(validate_request_signature(
+        &risc0_request_fixture.proof_request,
+        &risc0_request_fixture.signature,
+        SEPOLIA_CHAIN_ID,
+    )
+    .is_ok());
+}
+
+#[rstest]
+/// Ensures a sepolia intent replayed against a provider configured for another chain is rejected.
+fn test_cross_chain_replay_rejected(risc0_request_fixture: ComputeRequest<SystemParams>) {
+    assert_ne!(
+        risc0_request_fixture.compute_permit2_digest(SEPOLIA_CHAIN_ID),
+        risc0_request_fixture.compute_permit2_digest(MAINNET_CHAIN_ID)
+    );
+    assert!(validate_request_signature(
+        &risc0_request_fixture.proof_request,
+        &risc0_request_fixture.signature,
+        MAINNET_CHAIN_ID,
+    )
+    .is_err());
+}
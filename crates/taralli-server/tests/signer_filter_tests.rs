@@ -0,0 +1,160 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::{routing::get, Router};
+use rstest::*;
+use serial_test::serial;
+use taralli_client::client::provider::streaming::ProviderStreamingClient;
+use taralli_client::error::{ClientError, Result as ClientResult};
+use taralli_client::hooks::ProviderHook;
+use taralli_client::signer_filter::{SignerFilter, SignerFilterConfig};
+use taralli_client::worker::{ComputeWorker, WorkResult};
+use taralli_primitives::alloy::{
+    primitives::{address, Address, FixedBytes},
+    providers::ProviderBuilder,
+    signers::local::PrivateKeySigner,
+};
+use taralli_primitives::compression_utils::{
+    compression,
+    intents::{ComputeRequestCompressed, PartialComputeRequest},
+};
+use taralli_primitives::intents::{request::ComputeRequest, ComputeIntent};
+use taralli_primitives::markets::SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS;
+use taralli_primitives::systems::SystemParams;
+use taralli_primitives::validation::request::{
+    ComputeRequestValidator, RequestValidationConfig, RequestVerifierConstraints,
+};
+use taralli_server::{
+    routes::subscribe::websocket_subscribe_handler,
+    state::request::RequestState,
+    subscription_manager::{BroadcastKind, BroadcastedMessage, SubscriptionManager},
+};
+use url::Url;
+pub mod common;
+use crate::common::fixtures::{base_state_fixture, groth16_request_fixture, start_server};
+
+const ALLOWED_SIGNER: Address = address!("70997970C51812dc3A010C7d01b50e0d17dc79C8");
+
+type Request = ComputeRequest<SystemParams>;
+
+/// Records the requests reaching analysis, `on_intent_received` being called right before it
+#[derive(Default)]
+struct ReceivedRecorder {
+    received: Mutex<Vec<FixedBytes<32>>>,
+}
+
+#[async_trait]
+impl ProviderHook<Request> for ReceivedRecorder {
+    async fn on_intent_received(&self, intent_id: FixedBytes<32>, _intent: &Request) {
+        self.received.lock().unwrap().push(intent_id);
+    }
+}
+
+struct UnreachableWorker;
+
+#[async_trait]
+impl ComputeWorker<Request> for UnreachableWorker {
+    async fn execute(&self, _intent: &Request) -> ClientResult<WorkResult> {
+        Err(ClientError::WorkerError(
+            "requests aren't expected to be won".to_string(),
+        ))
+    }
+}
+
+fn subscribe_app(subscription_manager: Arc<SubscriptionManager>) -> Router {
+    let base_state = base_state_fixture("http://localhost:8545".parse().unwrap());
+    Router::new()
+        .route("/subscribe", get(websocket_subscribe_handler))
+        .with_state(RequestState::new(base_state, subscription_manager))
+}
+
+fn broadcast(subscription_manager: &SubscriptionManager, request: &Request) {
+    let system = compression::compress_brotli(&serde_json::to_vec(&request.system).unwrap())
+        .expect("Couldn't compress system");
+    let compressed = ComputeRequestCompressed::from((
+        PartialComputeRequest {
+            system_id: request.system_id,
+            proof_request: request.proof_request.clone(),
+            signature: request.signature,
+        },
+        system,
+    ));
+    subscription_manager
+        .broadcast(BroadcastedMessage {
+            content: bincode::serialize(&compressed).unwrap().into(),
+            subscribed_to: request.system_id.as_bit(),
+            kind: BroadcastKind::Intent,
+            deflated: None,
+            meta: None,
+        })
+        .unwrap();
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures the requests of a denylisted signer are dropped before reaching analysis, while the
+/// ones of other signers go on to it.
+async fn should_drop_denylisted_signer_before_analysis(groth16_request_fixture: Request) {
+    let subscription_manager = Arc::new(SubscriptionManager::new(4));
+    let server_url = start_server(subscribe_app(subscription_manager.clone())).await;
+
+    let denied = groth16_request_fixture;
+    let mut allowed = denied.clone();
+    allowed.proof_request.signer = ALLOWED_SIGNER;
+    let (denied_id, allowed_id) = (denied.compute_id(), allowed.compute_id());
+
+    let signer_filter = Arc::new(SignerFilter::new(SignerFilterConfig::default()));
+    signer_filter.deny(denied.proof_request.signer, "inputs crash the prover");
+    let recorder = Arc::new(ReceivedRecorder::default());
+    let client = ProviderStreamingClient::new(
+        server_url,
+        // analysis fails to reach the node, which is past the point of interest
+        ProviderBuilder::new().on_http("http://127.0.0.1:1".parse().unwrap()),
+        PrivateKeySigner::random(),
+        SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+        RequestValidationConfig::default(),
+    )
+    .with_system_configuration(
+        denied.system_id,
+        UnreachableWorker,
+        ComputeRequestValidator::new(
+            RequestValidationConfig::default(),
+            RequestVerifierConstraints::default(),
+        ),
+    )
+    .unwrap()
+    .with_signer_filter(signer_filter.clone())
+    .with_hook(recorder.clone());
+
+    let (control, run) = client.run_with_control();
+    let drive = async {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while subscription_manager.active_subscriptions() == 0 {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("provider didn't subscribe");
+
+        broadcast(&subscription_manager, &denied);
+        broadcast(&subscription_manager, &allowed);
+        // requests are processed in order, so the denied one was handled once the allowed one
+        // reached analysis
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !recorder.received.lock().unwrap().contains(&allowed_id) {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("allowed request didn't reach analysis");
+        control.shutdown(Duration::from_millis(100)).await
+    };
+    let (report, _) = tokio::join!(run, drive);
+    report.unwrap();
+
+    assert!(!recorder.received.lock().unwrap().contains(&denied_id));
+    assert_eq!(signer_filter.stats().denied, 1);
+    assert_eq!(signer_filter.stats().filtered(), 1);
+}
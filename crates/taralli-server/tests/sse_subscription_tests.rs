@@ -0,0 +1,206 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{routing::get, Router};
+use futures::StreamExt;
+use rstest::*;
+use serial_test::serial;
+use taralli_client::api::subscribe::{StreamItem, SubscribeApiClient, SubscriptionStream};
+use taralli_primitives::alloy::primitives::{address, B256};
+use taralli_primitives::alloy::providers::ProviderBuilder;
+use taralli_primitives::compression_utils::{
+    compression,
+    intents::{ComputeRequestCompressed, PartialComputeRequest},
+};
+use taralli_primitives::intents::envelope::BroadcastMeta;
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::markets::SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS;
+use taralli_primitives::systems::{SystemId, SystemParams};
+use taralli_server::{
+    config::{Markets, ServerValidationConfigs},
+    expiry::ExpiredIntent,
+    routes::subscribe::sse_subscribe_handler,
+    state::{request::RequestState, BaseState},
+    subscription_manager::{BroadcastKind, BroadcastedMessage, SubscriptionManager},
+};
+use tokio::net::TcpListener;
+use url::Url;
+pub mod common;
+use crate::common::fixtures::{risc0_request_fixture, setup_app};
+
+async fn serve(app: Router) -> Url {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let server_url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.expect("Couldn't serve");
+    });
+    server_url
+}
+
+/// Broadcast `request` the way the submit route does, stamped with the next sequence number
+fn broadcast_request(
+    subscription_manager: &SubscriptionManager,
+    request: ComputeRequest<SystemParams>,
+) -> BroadcastMeta {
+    let system = compression::compress_brotli(&serde_json::to_vec(&request.system).unwrap())
+        .expect("Couldn't compress system");
+    let compressed = ComputeRequestCompressed::from((
+        PartialComputeRequest {
+            system_id: request.system_id,
+            proof_request: request.proof_request,
+            signature: request.signature,
+        },
+        system,
+    ));
+    let meta = BroadcastMeta {
+        sequence: subscription_manager.next_sequence(),
+        received_at_ms: 1_700_000_000_000,
+    };
+    subscription_manager
+        .broadcast(BroadcastedMessage {
+            content: bincode::serialize(&compressed).unwrap().into(),
+            subscribed_to: request.system_id.as_bit(),
+            kind: BroadcastKind::Intent,
+            deflated: None,
+            meta: Some(meta),
+        })
+        .expect("Couldn't broadcast");
+    meta
+}
+
+async fn next_item(subscription: &mut SubscriptionStream) -> StreamItem {
+    tokio::time::timeout(Duration::from_secs(5), subscription.next())
+        .await
+        .expect("No item received")
+        .expect("Subscription ended")
+        .expect("Invalid item")
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures a provider subscribed over server-sent events and one subscribed over a websocket
+/// receive the same request.
+async fn should_receive_same_request_over_sse_and_websocket(
+    setup_app: (Router, Arc<SubscriptionManager>),
+    risc0_request_fixture: ComputeRequest<SystemParams>,
+) {
+    let (app, subscription_manager) = setup_app;
+    let server_url = serve(app).await;
+
+    let api = SubscribeApiClient::new(server_url, SystemId::Risc0.as_bit());
+    let mut websocket = api.subscribe_websocket().await.unwrap();
+    let mut sse = api.subscribe_sse().await.unwrap();
+    // give the server time to register both subscriptions
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(subscription_manager.active_subscriptions(), 2);
+
+    let meta = broadcast_request(&subscription_manager, risc0_request_fixture.clone());
+
+    let mut received = Vec::new();
+    for subscription in [&mut websocket, &mut sse] {
+        match next_item(subscription).await {
+            StreamItem::Request(request, delivery) => {
+                assert_eq!(
+                    delivery.map(|delivery| delivery.sequence),
+                    Some(meta.sequence)
+                );
+                received.push(serde_json::to_value(&request).unwrap());
+            }
+            other => panic!("Unexpected item: {other:?}"),
+        }
+    }
+    assert_eq!(received[0], received[1]);
+    assert_eq!(
+        received[0],
+        serde_json::to_value(&risc0_request_fixture).unwrap()
+    );
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures server-sent events subscribers only receive the messages of the systems they
+/// subscribed to, control frames included.
+async fn should_filter_sse_subscription_by_system(
+    setup_app: (Router, Arc<SubscriptionManager>),
+    risc0_request_fixture: ComputeRequest<SystemParams>,
+) {
+    let (app, subscription_manager) = setup_app;
+    let server_url = serve(app).await;
+
+    let mut sse = SubscribeApiClient::new(server_url, SystemId::Arkworks.as_bit())
+        .subscribe_sse()
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    // neither the risc0 request nor the expiry of a risc0 request reach the subscriber
+    broadcast_request(&subscription_manager, risc0_request_fixture);
+    for system_id in [SystemId::Risc0, SystemId::Arkworks] {
+        subscription_manager
+            .broadcast(
+                ExpiredIntent {
+                    end_auction_timestamp: 0,
+                    intent_id: B256::repeat_byte(system_id.as_bit()),
+                    system_id,
+                }
+                .to_message(),
+            )
+            .expect("Couldn't broadcast");
+    }
+
+    match next_item(&mut sse).await {
+        StreamItem::Expired {
+            intent_id,
+            system_id,
+        } => {
+            assert_eq!(system_id, SystemId::Arkworks);
+            assert_eq!(intent_id, B256::repeat_byte(SystemId::Arkworks.as_bit()));
+        }
+        other => panic!("Unexpected item: {other:?}"),
+    }
+}
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures subscribing falls back to server-sent events when the websocket upgrade fails, only
+/// when the fallback is enabled.
+async fn should_fall_back_to_sse_when_websocket_fails(
+    risc0_request_fixture: ComputeRequest<SystemParams>,
+) {
+    let subscription_manager = Arc::new(SubscriptionManager::new(2));
+    let base_state = BaseState::new(
+        ProviderBuilder::new().on_http("http://localhost:8545".parse().unwrap()),
+        Markets {
+            universal_bombetta: SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+            universal_porchetta: address!("0000000000000000000000000000000000000001"),
+        },
+        Duration::from_secs(10),
+        ServerValidationConfigs {
+            request: Default::default(),
+            offer: Default::default(),
+        },
+    );
+    // a server behind a proxy rejecting websocket upgrades
+    let app = Router::new()
+        .route("/subscribe/sse", get(sse_subscribe_handler))
+        .with_state(RequestState::new(base_state, subscription_manager.clone()));
+    let server_url = serve(app).await;
+
+    let api = SubscribeApiClient::new(server_url, SystemId::Risc0.as_bit());
+    assert!(api.subscribe().await.is_err());
+
+    let mut subscription = api.with_sse_fallback(true).subscribe().await.unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    broadcast_request(&subscription_manager, risc0_request_fixture.clone());
+    match next_item(&mut subscription).await {
+        StreamItem::Request(request, _) => assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::to_value(&risc0_request_fixture).unwrap()
+        ),
+        other => panic!("Unexpected item: {other:?}"),
+    }
+}
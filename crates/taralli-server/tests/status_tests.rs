@@ -0,0 +1,87 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::Router;
+use rstest::*;
+use serial_test::serial;
+use taralli_client::api::{status::StatusApiClient, subscribe::SubscribeApiClient};
+use taralli_primitives::markets::SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS;
+use taralli_primitives::systems::{SystemId, ALL_SYSTEMS_MASK, SYSTEMS};
+use taralli_server::subscription_manager::SubscriptionManager;
+use tokio::net::TcpListener;
+use url::Url;
+pub mod common;
+use crate::common::fixtures::setup_app;
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures `/status` reports the supported systems and that per-system subscriber counts
+/// follow providers connecting and disconnecting.
+async fn test_status_subscriber_counts(setup_app: (Router, Arc<SubscriptionManager>)) {
+    let port = 8889;
+    let listener = TcpListener::bind(format!("0.0.0.0:{port}"))
+        .await
+        .expect("Couldn't bind server");
+    let server_handle = tokio::spawn(async move {
+        axum::serve(listener, setup_app.0)
+            .await
+            .expect("Couldn't serve");
+    });
+    let server_url = Url::parse(&format!("http://localhost:{port}")).unwrap();
+    let status_client = StatusApiClient::new(server_url.clone());
+
+    let status = status_client
+        .fetch_status()
+        .await
+        .expect("Couldn't fetch status");
+    assert_eq!(status.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(status.systems.len(), SYSTEMS.len());
+    assert_eq!(
+        status.markets.universal_bombetta,
+        SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS
+    );
+    assert_eq!(status.active_subscriptions, 0);
+    for system in &status.systems {
+        assert_eq!(system.bit, system.system_id.as_bit());
+        assert_eq!(system.subscribers, 0);
+    }
+
+    // one provider on every system, another one on risc0 only
+    let all_systems = SubscribeApiClient::new(server_url.clone(), *ALL_SYSTEMS_MASK)
+        .subscribe_to_markets()
+        .await
+        .expect("Couldn't subscribe");
+    let risc0_only = SubscribeApiClient::new(server_url.clone(), SystemId::Risc0.as_bit())
+        .subscribe_to_markets()
+        .await
+        .expect("Couldn't subscribe");
+    // give the server time to register the upgraded connections
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let status = status_client
+        .fetch_status()
+        .await
+        .expect("Couldn't fetch status");
+    assert_eq!(status.active_subscriptions, 2);
+    for system in &status.systems {
+        let expected = if system.system_id == SystemId::Risc0 {
+            2
+        } else {
+            1
+        };
+        assert_eq!(system.subscribers, expected);
+    }
+
+    drop(risc0_only);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let status = status_client
+        .fetch_status()
+        .await
+        .expect("Couldn't fetch status");
+    assert_eq!(status.subscribers(SystemId::Risc0), 1);
+
+    drop(all_systems);
+    server_handle.abort();
+}
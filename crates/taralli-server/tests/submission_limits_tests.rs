@@ -0,0 +1,238 @@
+use std::time::Duration;
+
+use axum::{
+    body::{Body, Bytes},
+    http::{header::CONTENT_TYPE, Request},
+    Router,
+};
+use futures::StreamExt;
+use hyper::StatusCode;
+use rstest::*;
+use serde_json::Value;
+use taralli_primitives::{
+    compression_utils::{
+        compression::{self, DecompressionBudget},
+        intents::PartialComputeRequest,
+    },
+    intents::envelope::{IntentKind, IntentMetadata, SubmissionEnvelope},
+    intents::request::ComputeRequest,
+    systems::SystemParams,
+    PrimitivesError,
+};
+use taralli_server::config::SubmissionLimits;
+use taralli_server::extracted_intents::sanitize_metadata;
+use tower::ServiceExt;
+
+use crate::common::fixtures::{risc0_request_fixture, setup_limited_app};
+
+pub mod common;
+
+const BOUNDARY: &str = "taralli-test-boundary";
+
+fn multipart_body(parts: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (name, data) in parts {
+        body.extend_from_slice(
+            format!("--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n")
+                .as_bytes(),
+        );
+        body.extend_from_slice(data);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{BOUNDARY}--\r\n").as_bytes());
+    body
+}
+
+fn submit_request(body: Body) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri("/submit")
+        .header(
+            CONTENT_TYPE,
+            format!("multipart/form-data; boundary={BOUNDARY}"),
+        )
+        .body(body)
+        .unwrap()
+}
+
+fn partial_request_json(request: &ComputeRequest<SystemParams>) -> Vec<u8> {
+    serde_json::to_vec(&PartialComputeRequest {
+        system_id: request.system_id,
+        proof_request: request.proof_request.clone(),
+        signature: request.signature,
+    })
+    .unwrap()
+}
+
+/// Highly compressible payload that expands far past the configured decompression budget
+fn compression_bomb() -> Vec<u8> {
+    compression::compress_brotli(&vec![0u8; 32 * 1024 * 1024]).unwrap()
+}
+
+async fn error_code(response: axum::response::Response) -> String {
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    body["code"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+#[rstest]
+/// Ensures a client trickling its body is cut off by the body read timeout.
+async fn test_slow_body_times_out(
+    setup_limited_app: Router,
+    risc0_request_fixture: ComputeRequest<SystemParams>,
+) {
+    let body = multipart_body(&[(
+        "partial_request",
+        &partial_request_json(&risc0_request_fixture),
+    )]);
+    // send the first half of the body then stall forever
+    let first_half = Bytes::copy_from_slice(&body[..body.len() / 2]);
+    let stream = futures::stream::once(async move { Ok::<_, std::io::Error>(first_half) })
+        .chain(futures::stream::pending());
+
+    let response = tokio::time::timeout(
+        Duration::from_secs(5),
+        setup_limited_app.oneshot(submit_request(Body::from_stream(stream))),
+    )
+    .await
+    .expect("handler was not cut off by the body read timeout")
+    .unwrap();
+
+    assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+    assert_eq!(error_code(response).await, "body_read_timeout");
+}
+
+#[tokio::test]
+#[rstest]
+/// Ensures bodies larger than the configured maximum are rejected while being read.
+async fn test_oversized_body_rejected(
+    setup_limited_app: Router,
+    risc0_request_fixture: ComputeRequest<SystemParams>,
+) {
+    let oversized_system = vec![1u8; 2 * 1024 * 1024];
+    let body = multipart_body(&[
+        (
+            "partial_request",
+            &partial_request_json(&risc0_request_fixture),
+        ),
+        ("system_bytes", &oversized_system),
+    ]);
+
+    let response = setup_limited_app
+        .oneshot(submit_request(Body::from(body)))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(error_code(response).await, "payload_too_large");
+}
+
+#[tokio::test]
+#[rstest]
+/// Ensures a small, high-ratio compressed system is rejected once it exceeds the decompression budget.
+async fn test_compression_bomb_rejected(
+    setup_limited_app: Router,
+    risc0_request_fixture: ComputeRequest<SystemParams>,
+) {
+    let bomb = compression_bomb();
+    let body = multipart_body(&[
+        (
+            "partial_request",
+            &partial_request_json(&risc0_request_fixture),
+        ),
+        ("system_bytes", &bomb),
+    ]);
+
+    let response = setup_limited_app
+        .oneshot(submit_request(Body::from(body)))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(error_code(response).await, "decompression_limit_exceeded");
+}
+
+fn metadata(entries: &[(&str, &str)]) -> IntentMetadata {
+    entries
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+#[tokio::test]
+#[rstest]
+/// Ensures metadata with more entries or bytes than configured, or keys outside the accepted
+/// charset, is rejected while the body is read.
+async fn test_invalid_metadata_rejected(
+    setup_limited_app: Router,
+    risc0_request_fixture: ComputeRequest<SystemParams>,
+) {
+    let partial_request = PartialComputeRequest {
+        system_id: risc0_request_fixture.system_id,
+        proof_request: risc0_request_fixture.proof_request.clone(),
+        signature: risc0_request_fixture.signature,
+    };
+    for invalid in [
+        metadata(&[("a", "1"), ("b", "2"), ("c", "3")]),
+        metadata(&[("job", &"x".repeat(64))]),
+        metadata(&[("job id", "1")]),
+        metadata(&[("", "1")]),
+    ] {
+        let submission =
+            SubmissionEnvelope::new(IntentKind::Request, partial_request.clone(), invalid);
+        let body = multipart_body(&[
+            ("partial_request", &serde_json::to_vec(&submission).unwrap()),
+            ("system_bytes", b"unused"),
+        ]);
+
+        let response = setup_limited_app
+            .clone()
+            .oneshot(submit_request(Body::from(body)))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(error_code(response).await, "invalid_submission");
+    }
+}
+
+#[test]
+/// Ensures control characters are stripped from metadata values, which are kept otherwise.
+fn test_metadata_values_sanitized() {
+    let sanitized = sanitize_metadata(
+        metadata(&[("job", "42\u{1b}[2J\n"), ("team:proving", "zk-team")]),
+        &SubmissionLimits::default(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        sanitized,
+        metadata(&[("job", "42[2J"), ("team:proving", "zk-team")])
+    );
+
+    let disabled = SubmissionLimits {
+        max_metadata_entries: 0,
+        ..Default::default()
+    };
+    assert!(sanitize_metadata(IntentMetadata::new(), &disabled).is_ok());
+    assert!(sanitize_metadata(metadata(&[("job", "42")]), &disabled).is_err());
+}
+
+#[tokio::test]
+/// Ensures the incremental decoder stops at the output budget rather than inflating the whole payload.
+async fn test_bounded_decompression_stops_at_budget() {
+    let budget = DecompressionBudget {
+        max_output_bytes: 1024 * 1024,
+        max_duration: Duration::from_secs(5),
+    };
+
+    let result = compression::decompress_brotli_bounded(&compression_bomb(), &budget).await;
+
+    assert!(matches!(
+        result,
+        Err(PrimitivesError::DecompressionLimitExceeded(max)) if max == budget.max_output_bytes
+    ));
+}
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::Router;
+use futures::StreamExt;
+use rstest::*;
+use serial_test::serial;
+use taralli_client::api::subscribe::{StreamItem, SubscribeApiClient};
+use taralli_primitives::systems::SystemId;
+use taralli_server::subscription_manager::{
+    BroadcastKind, BroadcastedMessage, SubscriptionManager,
+};
+use tokio::net::TcpListener;
+use url::Url;
+pub mod common;
+use crate::common::fixtures::setup_app;
+
+#[tokio::test]
+#[rstest]
+#[serial]
+/// Ensures a subscriber overwhelmed past the (tiny) broadcast buffer of the fixture server
+/// is notified of how many messages it missed.
+async fn test_lag_notification_on_full_buffer(setup_app: (Router, Arc<SubscriptionManager>)) {
+    let port = 8890;
+    let listener = TcpListener::bind(format!("0.0.0.0:{port}"))
+        .await
+        .expect("Couldn't bind server");
+    let server_handle = tokio::spawn(async move {
+        axum::serve(listener, setup_app.0)
+            .await
+            .expect("Couldn't serve");
+    });
+
+    let mut subscription = SubscribeApiClient::new(
+        Url::parse(&format!("http://localhost:{port}")).unwrap(),
+        SystemId::Risc0.as_bit(),
+    )
+    .subscribe()
+    .await
+    .expect("Couldn't subscribe provider");
+    // give the server time to register the upgraded connection
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    // Broadcast way more messages than the buffer of 2 holds, without yielding to the server.
+    let broadcast_count = 20;
+    for i in 0..broadcast_count {
+        setup_app
+            .1
+            .broadcast(BroadcastedMessage {
+                content: vec![i].into(),
+                subscribed_to: SystemId::Risc0.as_bit(),
+                kind: BroadcastKind::Intent,
+                deflated: None,
+                meta: None,
+            })
+            .expect("Couldn't broadcast");
+    }
+
+    let skipped = tokio::time::timeout(Duration::from_secs(5), async {
+        while let Some(item) = subscription.next().await {
+            // the broadcast contents aren't valid requests, so those items are errors
+            if let Ok(StreamItem::Lagged(skipped)) = item {
+                return skipped;
+            }
+        }
+        panic!("Subscription ended without lag notification");
+    })
+    .await
+    .expect("No lag notification received");
+
+    assert!(skipped > 0);
+    assert!(skipped < u64::from(broadcast_count));
+
+    server_handle.abort();
+}
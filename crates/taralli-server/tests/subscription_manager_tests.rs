@@ -4,14 +4,17 @@ use crate::common::fixtures::groth16_request_fixture;
 use rstest::rstest;
 use serial_test::serial;
 use taralli_primitives::{
+    alloy::primitives::address,
     compression_utils::{
         compression,
         intents::{ComputeRequestCompressed, PartialComputeRequest},
     },
     intents::request::ComputeRequest,
-    systems::SystemParams,
+    systems::{SystemId, SystemParams},
+};
+use taralli_server::subscription_manager::{
+    BroadcastKind, BroadcastedMessage, SaturatedDelivery, SubscriptionManager,
 };
-use taralli_server::subscription_manager::{BroadcastedMessage, SubscriptionManager};
 use tokio::time::sleep;
 
 pub mod common;
@@ -124,8 +127,11 @@ async fn should_handle_lagging_subscribers(groth16_request_fixture: ComputeReque
     let request_serialized = bincode::serialize(&request_compressed)
         .expect("Couldn't serialize request for BroadcastedMessage");
     let message_to_broadcast = BroadcastedMessage {
-        content: request_serialized,
+        content: request_serialized.into(),
         subscribed_to: partial_request.system_id.as_bit(),
+        kind: BroadcastKind::Intent,
+        deflated: None,
+        meta: None,
     };
 
     // Once we have the compressed and serialized message, we broadcast it.
@@ -156,3 +162,48 @@ async fn should_handle_lagging_subscribers(groth16_request_fixture: ComputeReque
 
     assert!(SUBSCRIPTION_MANAGER.buffer_len() == 0);
 }
+
+#[test]
+/// Ensures subscription buffer sizes are bounded by the broadcast buffer capacity.
+fn should_bound_subscription_buffer_size() {
+    let subscription_manager: SubscriptionManager<i32> = SubscriptionManager::new(10);
+    assert_eq!(subscription_manager.subscription_buffer_size(None), 10);
+    assert_eq!(subscription_manager.subscription_buffer_size(Some(3)), 3);
+    assert_eq!(subscription_manager.subscription_buffer_size(Some(0)), 1);
+    assert_eq!(
+        subscription_manager.subscription_buffer_size(Some(1000)),
+        10
+    );
+}
+
+#[test]
+/// Ensures the subscriptions of saturated providers to a request's system aren't counted among its
+/// receivers when requests are skipped for them, and stop being attributed once dropped.
+fn should_count_eligible_receivers() {
+    let provider = address!("1111111111111111111111111111111111111111");
+    let subscription_manager: SubscriptionManager<i32> = SubscriptionManager::new(10);
+    let saturated = subscription_manager.track_provider(provider, SystemId::Risc0.as_bit());
+    let _other_system = subscription_manager.track_provider(provider, SystemId::Sp1.as_bit());
+
+    assert_eq!(
+        subscription_manager.eligible_receivers(3, SystemId::Risc0, &[provider]),
+        2
+    );
+    assert_eq!(
+        subscription_manager.eligible_receivers(3, SystemId::Risc0, &[]),
+        3
+    );
+    let flagging =
+        SubscriptionManager::<i32>::new(10).with_saturated_delivery(SaturatedDelivery::Flag);
+    let _flagged = flagging.track_provider(provider, SystemId::Risc0.as_bit());
+    assert_eq!(
+        flagging.eligible_receivers(3, SystemId::Risc0, &[provider]),
+        3
+    );
+
+    drop(saturated);
+    assert_eq!(
+        subscription_manager.eligible_receivers(3, SystemId::Risc0, &[provider]),
+        3
+    );
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::{body::Body, http::Request, routing::get, Router};
+use hyper::StatusCode;
+use taralli_client::api::status::ServerStatus;
+use taralli_primitives::{
+    alloy::{primitives::address, providers::ProviderBuilder},
+    markets::SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+    systems::SystemId,
+    validation::{
+        request::RequestValidationConfig, BaseValidationConfig, CommonValidationConfig,
+        SystemValidationOverrides,
+    },
+};
+use taralli_server::{
+    config::{Config, Markets, ServerValidationConfigs},
+    routes::status::get_status_handler,
+    state::{request::RequestState, BaseState},
+    subscription_manager::SubscriptionManager,
+};
+use tower::ServiceExt;
+
+const SHIPPED_CONFIG: &str = include_str!("../../../config.json");
+
+/// Server whose risc0 requests get an hour to be proven and may stake up to 5000
+fn app() -> Router {
+    let base_state = BaseState::new(
+        ProviderBuilder::new().on_http(reqwest::Url::parse("http://localhost:8080").unwrap()),
+        Markets {
+            universal_bombetta: SEPOLIA_UNIVERSAL_BOMBETTA_ADDRESS,
+            universal_porchetta: address!("0000000000000000000000000000000000000001"),
+        },
+        std::time::Duration::from_secs(10),
+        ServerValidationConfigs {
+            request: RequestValidationConfig {
+                base: BaseValidationConfig {
+                    system_overrides: HashMap::from([(
+                        SystemId::Risc0,
+                        SystemValidationOverrides {
+                            minimum_proving_time: Some(3_600),
+                            ..Default::default()
+                        },
+                    )]),
+                    ..Default::default()
+                },
+                maximum_allowed_stake: 1_000,
+                system_maximum_allowed_stake: HashMap::from([(SystemId::Risc0, 5_000)]),
+            },
+            offer: Default::default(),
+        },
+    );
+    Router::new()
+        .route("/status", get(get_status_handler))
+        .with_state(RequestState::new(
+            base_state,
+            Arc::new(SubscriptionManager::new(2)),
+        ))
+}
+
+#[tokio::test]
+/// Ensures `/status` reports the bounds in effect for each system, overrides applied.
+async fn test_status_reports_bounds_per_system() {
+    let response = app()
+        .oneshot(
+            Request::builder()
+                .uri("/status")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let status: ServerStatus = serde_json::from_slice(&body).unwrap();
+
+    let defaults = BaseValidationConfig::default();
+    let risc0 = status.system_bounds(SystemId::Risc0).unwrap();
+    assert_eq!(risc0.request.time.minimum_proving_time, 3_600);
+    assert_eq!(
+        risc0.request.time.maximum_start_delay,
+        defaults.maximum_start_delay
+    );
+    assert_eq!(risc0.request.maximum_allowed_stake, 5_000);
+    // offers aren't overridden
+    assert_eq!(
+        risc0.offer.minimum_proving_time,
+        defaults.minimum_proving_time
+    );
+
+    let sp1 = status.system_bounds(SystemId::Sp1).unwrap();
+    assert_eq!(
+        sp1.request.time.minimum_proving_time,
+        defaults.minimum_proving_time
+    );
+    assert_eq!(sp1.request.maximum_allowed_stake, 1_000);
+
+    // the bounds shared by every system are still reported as they were
+    assert_eq!(
+        status.validation.request.minimum_proving_time,
+        defaults.minimum_proving_time
+    );
+    assert_eq!(status.validation.request.maximum_allowed_stake, 1_000);
+    // so are the bounds every intent must resolve within
+    assert_eq!(
+        risc0.request.time.maximum_proving_time,
+        defaults.maximum_proving_time
+    );
+    assert_eq!(
+        status.validation.request.maximum_auction_length,
+        defaults.maximum_auction_length
+    );
+    assert_eq!(
+        status.validation.offer.maximum_proving_time,
+        defaults.maximum_proving_time
+    );
+}
+
+#[test]
+/// Ensures the overrides of the shipped config are parsed and applied to their system only.
+fn test_shipped_config_overrides() {
+    let config: Config = serde_json::from_str(SHIPPED_CONFIG).unwrap();
+    let request = config.get_request_validation_config();
+
+    let risc0 = request.for_system(SystemId::Risc0);
+    assert_eq!(risc0.maximum_auction_length(), 172_800);
+    assert_eq!(
+        risc0.minimum_proving_time(),
+        config.base_validation_config.minimum_proving_time
+    );
+    assert_eq!(
+        request
+            .for_system(SystemId::Arkworks)
+            .maximum_auction_length(),
+        config.base_validation_config.maximum_auction_length
+    );
+    assert_eq!(
+        config
+            .get_offer_validation_config()
+            .for_system(SystemId::Sp1)
+            .maximum_auction_length(),
+        172_800
+    );
+}
@@ -0,0 +1,120 @@
+use std::ops::Range;
+
+use rstest::*;
+use taralli_primitives::compression_utils::{
+    compression,
+    frames::{deflate_request_frame, inflate_request_frame_ref},
+    intents::{ComputeRequestCompressed, ComputeRequestCompressedRef, PartialComputeRequest},
+};
+use taralli_primitives::intents::envelope::{
+    decode_broadcast_envelope, BroadcastEnvelope, BroadcastMeta, IntentKind,
+};
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::systems::SystemParams;
+pub mod common;
+use crate::common::fixtures::groth16_request_fixture;
+
+fn compress(request: ComputeRequest<SystemParams>) -> ComputeRequestCompressed {
+    let system = compression::compress_brotli(&serde_json::to_vec(&request.system).unwrap())
+        .expect("Couldn't compress system");
+    ComputeRequestCompressed::from((
+        PartialComputeRequest {
+            system_id: request.system_id,
+            proof_request: request.proof_request,
+            signature: request.signature,
+        },
+        system,
+    ))
+}
+
+fn broadcast(request: &ComputeRequestCompressed) -> Vec<u8> {
+    bincode::serialize(&BroadcastEnvelope::new(
+        IntentKind::Request,
+        BroadcastMeta {
+            sequence: 3,
+            received_at_ms: 1_700_000_000_000,
+        },
+        request,
+    ))
+    .unwrap()
+}
+
+fn address_range(bytes: &[u8]) -> Range<usize> {
+    let start = bytes.as_ptr() as usize;
+    start..start + bytes.len()
+}
+
+/// Whether `inner` lies within the memory of `outer`, that is was borrowed rather than copied
+fn borrows_from(inner: &[u8], outer: &[u8]) -> bool {
+    let outer = address_range(outer);
+    let inner = address_range(inner);
+    outer.start <= inner.start && inner.end <= outer.end
+}
+
+#[rstest]
+/// Ensures the borrowed request encodes exactly as the owned one, and decodes bare, enveloped and
+/// deflated broadcasts with its system borrowed from the received bytes.
+fn should_round_trip_borrowed_requests(groth16_request_fixture: ComputeRequest<SystemParams>) {
+    let request = compress(groth16_request_fixture);
+    let bare = bincode::serialize(&request).unwrap();
+
+    let decoded: ComputeRequestCompressedRef = bincode::deserialize(&bare).unwrap();
+    assert_eq!(decoded.system, request.system.as_slice());
+    assert!(borrows_from(decoded.system, &bare));
+    assert_eq!(decoded.system_id, request.system_id);
+    assert_eq!(decoded.signature, request.signature);
+    // wire compatible both ways
+    assert_eq!(bincode::serialize(&decoded).unwrap(), bare);
+    assert_eq!(bincode::serialize(&decoded.into_owned()).unwrap(), bare);
+
+    let enveloped = broadcast(&request);
+    let (envelope, meta) =
+        decode_broadcast_envelope::<ComputeRequestCompressedRef>(&enveloped).unwrap();
+    assert_eq!(envelope.kind, IntentKind::Request);
+    assert_eq!(meta.unwrap().sequence, 3);
+    assert!(borrows_from(envelope.payload.system, &enveloped));
+    assert_eq!(
+        bincode::serialize(&envelope.payload).unwrap(),
+        bincode::serialize(&request).unwrap()
+    );
+
+    let deflated = deflate_request_frame(&request, BroadcastMeta::default()).unwrap();
+    let (envelope, meta) = inflate_request_frame_ref(&deflated).unwrap();
+    assert_eq!(meta, Some(BroadcastMeta::default()));
+    assert!(borrows_from(envelope.payload.system, &deflated));
+    assert_eq!(bincode::serialize(&envelope.payload).unwrap(), bare);
+}
+
+#[rstest]
+/// Ensures truncated and corrupted broadcasts are rejected by the borrowed decoding rather than
+/// read past their end.
+fn should_reject_corrupted_borrowed_requests(
+    groth16_request_fixture: ComputeRequest<SystemParams>,
+) {
+    let request = compress(groth16_request_fixture);
+    let enveloped = broadcast(&request);
+
+    for length in [0, 3, 4, 20, enveloped.len() / 2, enveloped.len() - 1] {
+        assert!(
+            decode_broadcast_envelope::<ComputeRequestCompressedRef>(&enveloped[..length]).is_err(),
+            "decoded a broadcast truncated to {length} bytes"
+        );
+    }
+
+    // a system length prefix pointing past the end of the broadcast
+    let bare = bincode::serialize(&request).unwrap();
+    let mut corrupted = bare.clone();
+    // the system follows the system id, encoded as a u32 variant index
+    corrupted[4..12].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert!(bincode::deserialize::<ComputeRequestCompressedRef>(&corrupted).is_err());
+
+    let deflated = deflate_request_frame(&request, BroadcastMeta::default()).unwrap();
+    assert!(inflate_request_frame_ref(&deflated[..3]).is_err());
+    let mut corrupted = deflated.clone();
+    corrupted[1..5].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(inflate_request_frame_ref(&corrupted).is_err());
+    // garbage in place of the deflated fields
+    let mut corrupted = deflated;
+    corrupted[5..25].fill(0xff);
+    assert!(inflate_request_frame_ref(&corrupted).is_err());
+}
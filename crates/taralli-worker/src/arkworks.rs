@@ -1,17 +1,20 @@
 use crate::error::{Result, WorkerError};
-use ark_bn254::{Bn254, Fr};
+use ark_bn254::{Bn254, Fq2, Fr, G1Affine, G2Affine};
 use ark_circom::{circom::R1CSFile, CircomCircuit, WitnessCalculator};
 use ark_crypto_primitives::snark::SNARK;
-use ark_groth16::{Groth16, Proof};
+use ark_groth16::{Groth16, Proof, VerifyingKey};
 use ark_std::rand::thread_rng;
 use async_trait::async_trait;
 use num_bigint::BigInt;
 use serde_json::Value;
 use std::str::FromStr;
 use std::{collections::HashMap, fs::File, io::Write};
+use taralli_client::audit::{ProofVerdict, ProofVerifier};
 use taralli_client::error::ClientError;
+use taralli_client::tracker::payload::DecodedProof;
 use taralli_client::worker::{ComputeWorker, WorkResult};
-use taralli_primitives::intents::ComputeIntent;
+use taralli_primitives::commitment::{check_public_inputs_layout, submitted_partial_commitment};
+use taralli_primitives::intents::{CommonProofCommitment, ComputeIntent};
 use taralli_primitives::systems::arkworks::ArkworksProofParams;
 use taralli_primitives::systems::System;
 use taralli_primitives::{
@@ -19,7 +22,7 @@ use taralli_primitives::{
         dyn_abi::DynSolValue,
         primitives::{Bytes, FixedBytes, U256},
     },
-    systems::SystemParams,
+    systems::{SystemId, SystemParams},
 };
 use tempfile::NamedTempFile;
 use wasmer::Store;
@@ -95,6 +98,40 @@ impl ArkworksWorker {
         let (p_a, p_b, p_c) = Self::proof_to_sol_values(proof)?;
         let pub_signals = Self::public_inputs_to_sol_values(public_inputs)?;
 
+        Ok(Self::encode_submission((p_a, p_b, p_c), pub_signals))
+    }
+
+    /// Same encoding out of a proof given as the 8 words `pA`, `pB` and `pC` of the verifier in
+    /// order, and public signals given as 32 byte words
+    pub(crate) fn format_raw_submission(proof: &[u8], pub_signals: &[u8]) -> Result<Bytes> {
+        if proof.len() != 8 * 32 {
+            return Err(WorkerError::ExecutionFailed(format!(
+                "groth16 proof must be 8 words of 32 bytes, got {} bytes",
+                proof.len()
+            )));
+        }
+        if pub_signals.len() % 32 != 0 {
+            return Err(WorkerError::ExecutionFailed(format!(
+                "public signals must be 32 byte words, got {} bytes",
+                pub_signals.len()
+            )));
+        }
+        let words = |bytes: &[u8]| -> Vec<DynSolValue> {
+            bytes
+                .chunks(32)
+                .map(|word| DynSolValue::Uint(U256::from_be_slice(word), 256))
+                .collect()
+        };
+        let proof = words(proof);
+        let proof_values = (
+            proof[0..2].to_vec(),
+            vec![proof[2..4].to_vec(), proof[4..6].to_vec()],
+            proof[6..8].to_vec(),
+        );
+        Ok(Self::encode_submission(proof_values, words(pub_signals)))
+    }
+
+    fn encode_submission((p_a, p_b, p_c): ProofValues, pub_signals: Vec<DynSolValue>) -> Bytes {
         // Create the final tuple for solidity encoding
         let proof_input_values = DynSolValue::Tuple(vec![
             DynSolValue::Array(p_a),
@@ -106,17 +143,53 @@ impl ArkworksWorker {
             DynSolValue::Array(pub_signals),
         ]);
 
-        Ok(Bytes::from(proof_input_values.abi_encode()))
+        Bytes::from(proof_input_values.abi_encode())
     }
 
-    fn compute_partial_commitment() -> FixedBytes<32> {
-        // Implement commitment computation if needed
-        FixedBytes::new([0u8; 32])
+    /// Check the inputs the verifier details of the intent point at are within the encoded
+    /// `pubSignals` of the formatted submission
+    fn check_submission_layout(
+        opaque_submission: &[u8],
+        extra_data: &[u8],
+        public_inputs: &[Fr],
+    ) -> Result<()> {
+        let pub_signals = Self::public_inputs_to_sol_values(public_inputs)?
+            .iter()
+            .flat_map(DynSolValue::abi_encode)
+            .collect::<Vec<u8>>();
+        Self::check_pub_signals_layout(opaque_submission, extra_data, &pub_signals)
+    }
+
+    /// Same check against the public signals encoded as 32 byte words
+    pub(crate) fn check_pub_signals_layout(
+        opaque_submission: &[u8],
+        extra_data: &[u8],
+        pub_signals: &[u8],
+    ) -> Result<()> {
+        check_public_inputs_layout(
+            SystemId::Arkworks,
+            opaque_submission,
+            extra_data,
+            pub_signals,
+        )
+        .map_err(|e| WorkerError::SubmissionLayout(e.to_string()))
     }
 
+    /// Partial commitment the intent's partial commitment result check takes, zero when it
+    /// declares none. The check's result field lies within the public signals.
+    pub(crate) fn compute_partial_commitment<C: CommonProofCommitment>(
+        proof_commitment: &C,
+        opaque_submission: &[u8],
+    ) -> Result<FixedBytes<32>> {
+        submitted_partial_commitment(proof_commitment, opaque_submission)
+            .map_err(|e| WorkerError::SubmissionLayout(e.to_string()))
+    }
+
+    /// Prove the circuit of `params` on `inputs`, the typed circuit signals of the intent
     async fn generate_proof(
         &self,
         params: &ArkworksProofParams,
+        inputs: &Value,
     ) -> Result<(Proof<Bn254>, Vec<Fr>)> {
         // Create temp files for prover inputs and keep them alive for the entire function
         let mut r1cs_file =
@@ -143,31 +216,7 @@ impl ArkworksWorker {
             .map_err(|e| WorkerError::ExecutionFailed(e.to_string()))?;
 
         // Convert JSON inputs to the format expected by WitnessCalculator
-        let inputs: HashMap<String, Vec<BigInt>> = if let Value::Object(map) = &params.inputs {
-            map.iter()
-                .map(|(key, value)| {
-                    let values = match value {
-                        Value::String(s) => vec![BigInt::parse_bytes(s.as_bytes(), 10)
-                            .ok_or_else(|| {
-                                WorkerError::ExecutionFailed("Invalid input format".to_string())
-                            })?],
-                        Value::Number(n) => vec![BigInt::from(n.as_i64().ok_or_else(|| {
-                            WorkerError::ExecutionFailed("Invalid number format".to_string())
-                        })?)],
-                        _ => {
-                            return Err(WorkerError::ExecutionFailed(
-                                "Invalid input type".to_string(),
-                            ))
-                        }
-                    };
-                    Ok((key.clone(), values))
-                })
-                .collect::<Result<HashMap<_, _>>>()?
-        } else {
-            return Err(WorkerError::ExecutionFailed(
-                "Invalid input format".to_string(),
-            ));
-        };
+        let inputs = circom_inputs(inputs)?;
 
         // Calculate the witness
         let witness = witness_calculator
@@ -219,23 +268,181 @@ where
             SystemParams::Arkworks(params) => params.clone(),
             _ => return Err(ClientError::WorkerError("Expected Arkworks params".into())),
         };
+        // validated as a JSON object of signals when the params were parsed
+        let inputs = system_params.inputs().into_json()?;
 
         // Generate proof
         let (proof, public_inputs) = self
-            .generate_proof(&params)
+            .generate_proof(&params, &inputs)
             .await
             .map_err(ClientError::from)?;
 
         // Format proof data for resolution
         let opaque_submission =
             Self::format_opaque_submission(&proof, &public_inputs).map_err(ClientError::from)?;
+        Self::check_submission_layout(
+            &opaque_submission,
+            intent.proof_commitment().extra_data(),
+            &public_inputs,
+        )?;
 
-        // get empty partial commitment
-        let partial_commitment = Self::compute_partial_commitment();
+        let partial_commitment =
+            Self::compute_partial_commitment(intent.proof_commitment(), &opaque_submission)?;
 
         Ok(WorkResult {
             opaque_submission,
             partial_commitment,
         })
     }
+
+    fn produces_partial_commitment(&self) -> bool {
+        true
+    }
+}
+
+/// Groth16 verifier of arkworks proofs against the verifying key of their circuit
+pub struct ArkworksVerifier {
+    verifying_key: VerifyingKey<Bn254>,
+}
+
+impl ArkworksVerifier {
+    pub fn new(verifying_key: VerifyingKey<Bn254>) -> Self {
+        Self { verifying_key }
+    }
+
+    /// Rebuild the proof and public inputs from their submitted values, checking each point is
+    /// on its curve and within the prime order subgroup
+    fn decode_proof(
+        p_a: &[U256],
+        p_b: &[Vec<U256>],
+        p_c: &[U256],
+        pub_signals: &[U256],
+    ) -> std::result::Result<(Proof<Bn254>, Vec<Fr>), String> {
+        fn field<F: FromStr>(value: &U256) -> std::result::Result<F, String> {
+            F::from_str(&value.to_string()).map_err(|_| format!("{value} is not a field element"))
+        }
+        fn g1(point: &[U256], name: &str) -> std::result::Result<G1Affine, String> {
+            let [x, y] = point else {
+                return Err(format!("{name} should have 2 coordinates"));
+            };
+            let point = G1Affine::new_unchecked(field(x)?, field(y)?);
+            if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+                return Err(format!("{name} is not a valid G1 point"));
+            }
+            Ok(point)
+        }
+
+        // pB coordinates are submitted as [[x.c1, x.c0], [y.c1, y.c0]]
+        let [x, y] = p_b else {
+            return Err("pB should have 2 coordinates".to_string());
+        };
+        let ([x_c1, x_c0], [y_c1, y_c0]) = (x.as_slice(), y.as_slice()) else {
+            return Err("pB coordinates should have 2 limbs".to_string());
+        };
+        let b = G2Affine::new_unchecked(
+            Fq2::new(field(x_c0)?, field(x_c1)?),
+            Fq2::new(field(y_c0)?, field(y_c1)?),
+        );
+        if !b.is_on_curve() || !b.is_in_correct_subgroup_assuming_on_curve() {
+            return Err("pB is not a valid G2 point".to_string());
+        }
+
+        let proof = Proof {
+            a: g1(p_a, "pA")?,
+            b,
+            c: g1(p_c, "pC")?,
+        };
+        let public_inputs = pub_signals
+            .iter()
+            .map(field)
+            .collect::<std::result::Result<_, _>>()?;
+        Ok((proof, public_inputs))
+    }
+}
+
+#[async_trait]
+impl ProofVerifier for ArkworksVerifier {
+    async fn verify(
+        &self,
+        proof: &DecodedProof,
+        _system: Option<&SystemParams>,
+    ) -> taralli_client::error::Result<ProofVerdict> {
+        let DecodedProof::Arkworks {
+            p_a,
+            p_b,
+            p_c,
+            pub_signals,
+        } = proof
+        else {
+            return Err(ClientError::WorkerError(
+                "Expected an Arkworks proof".into(),
+            ));
+        };
+
+        let (proof, public_inputs) = match Self::decode_proof(p_a, p_b, p_c, pub_signals) {
+            Ok(decoded) => decoded,
+            Err(reason) => return Ok(ProofVerdict::Invalid(reason)),
+        };
+        let verified = Groth16::<Bn254>::verify(&self.verifying_key, &public_inputs, &proof)
+            .map_err(|e| ClientError::WorkerError(e.to_string()))?;
+        Ok(if verified {
+            ProofVerdict::Valid
+        } else {
+            ProofVerdict::Invalid("groth16 proof does not verify".to_string())
+        })
+    }
+}
+
+/// Convert a circom `input.json` object into the flattened signal values expected by the
+/// `WitnessCalculator`. Signal values may be decimal or `0x` prefixed hex strings, integers,
+/// booleans, or (nested) arrays of those, which are flattened in row-major order.
+pub fn circom_inputs(inputs: &Value) -> Result<HashMap<String, Vec<BigInt>>> {
+    let Value::Object(map) = inputs else {
+        return Err(WorkerError::ExecutionFailed(
+            "circuit inputs must be a JSON object of signal names to values".to_string(),
+        ));
+    };
+
+    map.iter()
+        .map(|(signal, value)| {
+            let mut values = Vec::new();
+            flatten_signal(signal, value, &mut values)?;
+            Ok((signal.clone(), values))
+        })
+        .collect()
+}
+
+/// Append the values of `value` to `values`, `path` naming the signal element being converted
+fn flatten_signal(path: &str, value: &Value, values: &mut Vec<BigInt>) -> Result<()> {
+    let invalid = |reason: String| {
+        WorkerError::ExecutionFailed(format!("invalid value for signal `{path}`: {reason}"))
+    };
+
+    match value {
+        Value::Array(elements) => {
+            for (index, element) in elements.iter().enumerate() {
+                flatten_signal(&format!("{path}[{index}]"), element, values)?;
+            }
+        }
+        Value::String(s) => {
+            let parsed = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+                Some(hex) => BigInt::parse_bytes(hex.as_bytes(), 16),
+                None => BigInt::parse_bytes(s.as_bytes(), 10),
+            };
+            values.push(parsed.ok_or_else(|| invalid(format!("`{s}` is not an integer")))?);
+        }
+        Value::Number(n) => {
+            let parsed = n
+                .as_i64()
+                .map(BigInt::from)
+                .or_else(|| n.as_u64().map(BigInt::from))
+                .ok_or_else(|| invalid(format!("`{n}` is not an integer")))?;
+            values.push(parsed);
+        }
+        Value::Bool(b) => values.push(BigInt::from(u8::from(*b))),
+        Value::Null | Value::Object(_) => {
+            return Err(invalid(format!("unsupported value `{value}`")));
+        }
+    }
+    Ok(())
 }
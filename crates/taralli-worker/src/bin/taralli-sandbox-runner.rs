@@ -0,0 +1,37 @@
+//! Runner of sandboxed jobs, spawned by `taralli_worker::sandbox::Sandboxed` with a job on its
+//! stdin, writing the outcome to its stdout.
+
+use std::io::{Read, Write};
+use std::process::ExitCode;
+
+use taralli_worker::sandbox::run_job;
+use tracing_subscriber::EnvFilter;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    // stdout carries the outcome, logs go to stderr
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+
+    let mut job = Vec::new();
+    if let Err(e) = std::io::stdin().read_to_end(&mut job) {
+        eprintln!("failed to read job: {e}");
+        return ExitCode::FAILURE;
+    }
+    let outcome = run_job(&job).await;
+    let output = match bincode::serialize(&outcome) {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("failed to encode outcome: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut stdout = std::io::stdout().lock();
+    if let Err(e) = stdout.write_all(&output).and_then(|()| stdout.flush()) {
+        eprintln!("failed to write outcome: {e}");
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
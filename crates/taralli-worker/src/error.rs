@@ -1,4 +1,5 @@
 use taralli_client::error::ClientError;
+use taralli_primitives::systems::SystemId;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -7,6 +8,20 @@ pub enum WorkerError {
     ParamsError(String),
     #[error("Failed to execute worker: {0}")]
     ExecutionFailed(String),
+    #[error("Remote prover quota exhausted: {0}")]
+    QuotaExhausted(String),
+    #[error("Remote proof not fulfilled in time: {0}")]
+    ProofTimeout(String),
+    #[error("Opaque submission doesn't match the verifier details: {0}")]
+    SubmissionLayout(String),
+    #[error(
+        "{} workers are not compiled in, rebuild taralli-worker with the `{feature}` feature",
+        .system_id.as_str()
+    )]
+    SystemNotCompiled {
+        system_id: SystemId,
+        feature: &'static str,
+    },
 }
 
 // Implement conversion from WorkerError to ClientError
@@ -15,6 +30,14 @@ impl From<WorkerError> for ClientError {
         match err {
             WorkerError::ExecutionFailed(msg) => ClientError::WorkerError(msg),
             WorkerError::ParamsError(msg) => ClientError::WorkerError(msg),
+            WorkerError::QuotaExhausted(msg) => ClientError::ProverQuotaExhausted(msg),
+            WorkerError::ProofTimeout(msg) => ClientError::ProofTimeout(msg),
+            WorkerError::SubmissionLayout(msg) => ClientError::WorkerError(format!(
+                "opaque submission doesn't match the verifier details: {msg}"
+            )),
+            err @ WorkerError::SystemNotCompiled { .. } => {
+                ClientError::ConfigError(err.to_string())
+            }
         }
     }
 }
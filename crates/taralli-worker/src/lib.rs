@@ -1,5 +1,11 @@
+#[cfg(feature = "arkworks")]
 pub mod arkworks;
+pub mod remote;
+#[cfg(feature = "risc0")]
 pub mod risc0;
+pub mod sandbox;
+#[cfg(feature = "sp1")]
 pub mod sp1;
+pub mod systems;
 
 pub mod error;
@@ -0,0 +1,565 @@
+//! Proofs generated outside of this process. Remote provers are handed a job and polled for its
+//! status until the proof comes back or the proving time runs out.
+//!
+//! `RemoteWorker` hands the proof generation of each intent to an external prover fleet behind
+//! an HTTP sidecar speaking the following protocol, every call carrying the configured auth
+//! header:
+//! - `POST {endpoint}/jobs` with a JSON `ProveJob`, answered with a JSON `JobAccepted`
+//! - `GET {endpoint}/jobs/{job_id}`, answered with the JSON `JobStatus` of the job
+//! - `DELETE {endpoint}/jobs/{job_id}` once the worker gives up on the job, its answer ignored
+//!
+//! Bytes are encoded as 0x prefixed hex strings.
+//!
+//! The proof and public values of a succeeded job are formatted into the opaque submission of
+//! the intent by the formatter of its system, which has to be compiled in.
+
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use reqwest::{Method, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use taralli_client::api::retry::{
+    is_retryable_error, is_retryable_status, retry_after, RetryPolicy,
+};
+use taralli_client::error::ClientError;
+use taralli_client::worker::{ComputeWorker, WorkResult};
+use taralli_primitives::alloy::primitives::{Bytes, B256};
+use taralli_primitives::intents::{CommonProofCommitment, ComputeIntent};
+use taralli_primitives::systems::{System, SystemId, SystemParams};
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+use url::Url;
+
+use crate::error::{Result, WorkerError};
+use crate::systems::{ensure_compiled, not_compiled};
+
+/// Status of a proof request as reported by the remote prover
+#[derive(Debug)]
+pub enum RemoteProofStatus<T> {
+    Pending,
+    Fulfilled(T),
+    /// the prover gave up on the request, e.g. the program failed to execute
+    Unfulfillable(String),
+}
+
+/// Poll the status of a proof request until it is fulfilled, failing once `timeout` has elapsed
+/// or the prover reports the request as unfulfillable.
+pub async fn wait_for_proof<T, F, Fut>(
+    mut poll_status: F,
+    poll_interval: Duration,
+    timeout: Duration,
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<RemoteProofStatus<T>>>,
+{
+    let deadline = Instant::now() + timeout;
+    loop {
+        match poll_status().await? {
+            RemoteProofStatus::Fulfilled(proof) => return Ok(proof),
+            RemoteProofStatus::Unfulfillable(reason) => {
+                return Err(WorkerError::ExecutionFailed(format!(
+                    "proof request unfulfillable: {reason}"
+                )))
+            }
+            RemoteProofStatus::Pending => {}
+        }
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(WorkerError::ProofTimeout(format!(
+                "no proof after {}s",
+                timeout.as_secs()
+            )));
+        }
+        tokio::time::sleep(poll_interval.min(remaining)).await;
+    }
+}
+
+/// Job handed to the sidecar
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProveJob {
+    pub system_id: SystemId,
+    /// system params of the intent, as encoded by the worker's `ParamsEncoding`
+    pub params: Value,
+    /// seconds the sidecar has to prove, the worker giving up on the job afterwards
+    pub timeout_seconds: u64,
+}
+
+/// Answer of the sidecar to a job
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobAccepted {
+    pub job_id: String,
+}
+
+/// Proof of a succeeded job
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemoteProof {
+    /// proof as the verifier of the system takes it: the groth16 seal for risc0, the proof bytes
+    /// led by their verifier selector for sp1, the 8 words of `pA`, `pB` and `pC` for arkworks
+    pub proof: Bytes,
+    /// public values the proof was produced with: the journal for risc0, the public values for
+    /// sp1, the public signals as 32 byte words for arkworks
+    pub public_values: Bytes,
+    /// hash of the program's verifying key, only needed for sp1 proofs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verifying_key: Option<B256>,
+}
+
+/// Status of a job, as reported by the sidecar
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Succeeded(RemoteProof),
+    Failed { error: String },
+}
+
+/// Function encoding the system params handed to the sidecar
+pub type ParamsEncoder = Arc<dyn Fn(&SystemParams) -> Result<Value> + Send + Sync>;
+
+/// How the system params of intents are encoded for the sidecar
+#[derive(Clone)]
+pub enum ParamsEncoding {
+    /// the params of the intent's system as JSON, byte fields being arrays of numbers
+    Json,
+    /// the params as encoded by the given function, e.g. with byte fields hex encoded
+    Custom(ParamsEncoder),
+}
+
+impl fmt::Debug for ParamsEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json => f.write_str("Json"),
+            Self::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+impl ParamsEncoding {
+    pub fn encode(&self, params: &SystemParams) -> Result<Value> {
+        let encoded = match self {
+            Self::Json => match params {
+                SystemParams::Arkworks(params) => serde_json::to_value(params),
+                SystemParams::Risc0(params) => serde_json::to_value(params),
+                SystemParams::Sp1(params) => serde_json::to_value(params),
+            },
+            Self::Custom(encode) => return encode(params),
+        };
+        encoded.map_err(|e| WorkerError::ParamsError(e.to_string()))
+    }
+}
+
+/// Sidecar of a `RemoteWorker` and how it's called
+#[derive(Clone)]
+pub struct RemoteWorkerConfig {
+    /// base url of the sidecar, its routes being nested under it
+    pub endpoint: Url,
+    /// header authenticating the worker to the sidecar, e.g. `("authorization", "Bearer ...")`
+    pub auth_header: Option<(String, String)>,
+    pub params_encoding: ParamsEncoding,
+    /// delay between two job status checks
+    pub poll_interval: Duration,
+    /// time after which the job is given up on, the intent's proving time when unset
+    pub timeout: Option<Duration>,
+    /// how failed job submissions are retried, status checks failing transiently being
+    /// attempted again on the next poll
+    pub retry_policy: RetryPolicy,
+}
+
+impl fmt::Debug for RemoteWorkerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteWorkerConfig")
+            .field("endpoint", &self.endpoint.as_str())
+            .field(
+                "auth_header",
+                &self
+                    .auth_header
+                    .as_ref()
+                    .map(|(name, _)| (name, "<redacted>")),
+            )
+            .field("params_encoding", &self.params_encoding)
+            .field("poll_interval", &self.poll_interval)
+            .field("timeout", &self.timeout)
+            .field("retry_policy", &self.retry_policy)
+            .finish()
+    }
+}
+
+impl RemoteWorkerConfig {
+    /// Sidecar at `endpoint` taking the params as JSON, polled every 5 seconds
+    pub fn new(endpoint: Url) -> Self {
+        Self {
+            endpoint,
+            auth_header: None,
+            params_encoding: ParamsEncoding::Json,
+            poll_interval: Duration::from_secs(5),
+            timeout: None,
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    pub fn with_auth_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.auth_header = Some((name.into(), value.into()));
+        self
+    }
+
+    pub fn with_params_encoding(mut self, params_encoding: ParamsEncoding) -> Self {
+        self.params_encoding = params_encoding;
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+}
+
+/// Compute worker handing the proof generation of each intent to a sidecar, see the module docs
+#[derive(Clone, Debug)]
+pub struct RemoteWorker {
+    config: RemoteWorkerConfig,
+    client: reqwest::Client,
+}
+
+impl RemoteWorker {
+    pub fn new(config: RemoteWorkerConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub fn config(&self) -> &RemoteWorkerConfig {
+        &self.config
+    }
+
+    /// Hand `job` to the sidecar, retrying transient failures according to the retry policy.
+    /// Returns the id of the job.
+    pub async fn submit(&self, job: &ProveJob) -> Result<String> {
+        let url = self.jobs_url(None)?;
+        let deadline = Instant::now() + self.config.retry_policy.deadline;
+        let mut retry = 0;
+
+        let response = loop {
+            let result = self
+                .request(Method::POST, url.clone())
+                .json(job)
+                .send()
+                .await;
+
+            let backoff = match &result {
+                Ok(response) if is_retryable_status(response.status()) => {
+                    retry_after(response).unwrap_or_else(|| self.config.retry_policy.backoff(retry))
+                }
+                Err(e) if is_retryable_error(e) => self.config.retry_policy.backoff(retry),
+                _ => break result,
+            };
+
+            let resume_at = Instant::now() + backoff;
+            if retry >= self.config.retry_policy.max_retries || resume_at > deadline {
+                break result;
+            }
+            tracing::warn!(
+                "remote job submission attempt {} failed ({}), retrying in {:?}",
+                retry + 1,
+                match &result {
+                    Ok(response) => response.status().to_string(),
+                    Err(e) => e.to_string(),
+                },
+                backoff
+            );
+            tokio::time::sleep_until(resume_at.into()).await;
+            retry += 1;
+        };
+
+        let accepted: JobAccepted = answer(response).await?;
+        Ok(accepted.job_id)
+    }
+
+    /// Status of job `job_id`
+    pub async fn status(&self, job_id: &str) -> Result<JobStatus> {
+        let response = self
+            .request(Method::GET, self.jobs_url(Some(job_id))?)
+            .send()
+            .await;
+        answer(response).await
+    }
+
+    /// Tell the sidecar to give up on job `job_id`
+    pub async fn cancel(&self, job_id: &str) {
+        let result = match self.jobs_url(Some(job_id)) {
+            Ok(url) => self.request(Method::DELETE, url).send().await,
+            Err(_) => return,
+        };
+        if let Err(e) = result {
+            tracing::warn!("failed to cancel remote job {job_id}: {e}");
+        }
+    }
+
+    /// Prove `params` on the sidecar within `budget`, or the configured timeout when shorter,
+    /// giving up on its job once cancelled or timed out. A job whose execution is dropped is given
+    /// up on as well.
+    async fn prove(
+        &self,
+        params: &SystemParams,
+        budget: Duration,
+        cancel: CancellationToken,
+    ) -> Result<RemoteProof> {
+        let timeout = self
+            .config
+            .timeout
+            .map_or(budget, |timeout| timeout.min(budget));
+        let job = ProveJob {
+            system_id: params.system_id(),
+            params: self.config.params_encoding.encode(params)?,
+            timeout_seconds: timeout.as_secs(),
+        };
+        let job_id = self.submit(&job).await?;
+        tracing::info!("remote {} job {job_id} submitted", job.system_id.as_str());
+
+        // only sent once the proof is in, the job is cancelled when the sender is dropped
+        let (proved, outcome) = oneshot::channel();
+        tokio::spawn({
+            let worker = self.clone();
+            let job_id = job_id.clone();
+            let cancel = cancel.clone();
+            async move {
+                // the token is also cancelled once a proved execution ends
+                tokio::select! {
+                    biased;
+                    outcome = outcome => if outcome.is_ok() {
+                        return;
+                    },
+                    () = cancel.cancelled() => {}
+                }
+                worker.cancel(&job_id).await;
+            }
+        });
+
+        let polled = wait_for_proof(|| self.poll(&job_id), self.config.poll_interval, timeout);
+        let proof = tokio::select! {
+            proof = polled => proof?,
+            () = cancel.cancelled() => {
+                return Err(WorkerError::ExecutionFailed(format!(
+                    "remote job {job_id} cancelled"
+                )))
+            }
+        };
+        let _ = proved.send(());
+        Ok(proof)
+    }
+
+    /// Status check of job `job_id`, transient failures being reported as pending
+    async fn poll(&self, job_id: &str) -> Result<RemoteProofStatus<RemoteProof>> {
+        let response = self
+            .request(Method::GET, self.jobs_url(Some(job_id))?)
+            .send()
+            .await;
+        // the job keeps running on the sidecar, check again on the next poll
+        match &response {
+            Ok(response) if is_retryable_status(response.status()) => {
+                tracing::warn!("failed to fetch remote job status: {}", response.status());
+                return Ok(RemoteProofStatus::Pending);
+            }
+            Err(e) if is_retryable_error(e) => {
+                tracing::warn!("failed to fetch remote job status: {e}");
+                return Ok(RemoteProofStatus::Pending);
+            }
+            _ => {}
+        }
+        Ok(match answer(response).await? {
+            JobStatus::Pending | JobStatus::Running => RemoteProofStatus::Pending,
+            JobStatus::Succeeded(proof) => RemoteProofStatus::Fulfilled(proof),
+            JobStatus::Failed { error } => RemoteProofStatus::Unfulfillable(error),
+        })
+    }
+
+    fn jobs_url(&self, job_id: Option<&str>) -> Result<Url> {
+        let mut url = self.config.endpoint.clone();
+        {
+            let mut segments = url.path_segments_mut().map_err(|()| {
+                WorkerError::ParamsError(format!(
+                    "sidecar endpoint {} can't be a base url",
+                    self.config.endpoint
+                ))
+            })?;
+            segments.pop_if_empty().push("jobs");
+            if let Some(job_id) = job_id {
+                segments.push(job_id);
+            }
+        }
+        Ok(url)
+    }
+
+    fn request(&self, method: Method, url: Url) -> RequestBuilder {
+        let request = self.client.request(method, url);
+        match &self.config.auth_header {
+            Some((name, value)) => request.header(name, value),
+            None => request,
+        }
+    }
+}
+
+/// JSON answer of a successful call to the sidecar
+async fn answer<T: DeserializeOwned>(response: reqwest::Result<Response>) -> Result<T> {
+    let response =
+        response.map_err(|e| WorkerError::ExecutionFailed(format!("sidecar call failed: {e}")))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(WorkerError::ExecutionFailed(format!(
+            "sidecar answered {status}: {body}"
+        )));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| WorkerError::ExecutionFailed(format!("invalid sidecar answer: {e}")))
+}
+
+#[cfg(feature = "risc0")]
+struct Risc0Formatter;
+
+#[cfg(feature = "risc0")]
+impl crate::risc0::Risc0ProofFormatter for Risc0Formatter {}
+
+#[cfg(feature = "sp1")]
+struct Sp1Formatter;
+
+#[cfg(feature = "sp1")]
+impl crate::sp1::Sp1ProofFormatter for Sp1Formatter {}
+
+/// Result of `proof`, formatted by the formatter of the system of `params`
+#[cfg_attr(
+    not(any(feature = "risc0", feature = "sp1", feature = "arkworks")),
+    allow(unused_variables)
+)]
+pub fn format_remote_proof<C: CommonProofCommitment>(
+    params: &SystemParams,
+    proof: &RemoteProof,
+    proof_commitment: &C,
+) -> Result<WorkResult> {
+    let (opaque_submission, partial_commitment) = match params {
+        #[cfg(feature = "risc0")]
+        SystemParams::Risc0(params) => {
+            use crate::risc0::Risc0ProofFormatter;
+
+            let image_id = crate::risc0::image_id_for_elf(&params.elf)?;
+            let opaque_submission =
+                Risc0Formatter::encode_submission(&proof.proof, image_id, &proof.public_values);
+            Risc0Formatter::check_submission_layout(
+                &opaque_submission,
+                proof_commitment.extra_data(),
+                image_id,
+                &proof.public_values,
+            )?;
+            let partial_commitment =
+                Risc0Formatter::compute_partial_commitment(proof_commitment, &opaque_submission)?;
+            (opaque_submission, partial_commitment)
+        }
+        #[cfg(feature = "sp1")]
+        SystemParams::Sp1(_) => {
+            use crate::sp1::Sp1ProofFormatter;
+
+            let vkey = proof.verifying_key.ok_or_else(|| {
+                WorkerError::ExecutionFailed("sp1 proof without its verifying key".to_string())
+            })?;
+            let opaque_submission =
+                Sp1Formatter::encode_submission(vkey, &proof.public_values, &proof.proof);
+            Sp1Formatter::check_submission_layout(
+                &opaque_submission,
+                proof_commitment.extra_data(),
+                &proof.public_values,
+            )?;
+            let partial_commitment =
+                Sp1Formatter::compute_partial_commitment(proof_commitment, &opaque_submission)?;
+            (opaque_submission, partial_commitment)
+        }
+        #[cfg(feature = "arkworks")]
+        SystemParams::Arkworks(_) => {
+            use crate::arkworks::ArkworksWorker;
+
+            let opaque_submission =
+                ArkworksWorker::format_raw_submission(&proof.proof, &proof.public_values)?;
+            ArkworksWorker::check_pub_signals_layout(
+                &opaque_submission,
+                proof_commitment.extra_data(),
+                &proof.public_values,
+            )?;
+            let partial_commitment =
+                ArkworksWorker::compute_partial_commitment(proof_commitment, &opaque_submission)?;
+            (opaque_submission, partial_commitment)
+        }
+        #[allow(unreachable_patterns)]
+        params => return Err(not_compiled(params.system_id())),
+    };
+    Ok(WorkResult {
+        opaque_submission,
+        partial_commitment,
+    })
+}
+
+#[async_trait]
+impl<I> ComputeWorker<I> for RemoteWorker
+where
+    I: ComputeIntent<System = SystemParams> + Send + Sync,
+{
+    /// Prove within the whole proving window of `intent`
+    async fn execute(&self, intent: &I) -> taralli_client::error::Result<WorkResult> {
+        let proving_time = Duration::from_secs(intent.proof_commitment().proving_time().into());
+        self.execute_cancellable(intent, proving_time, CancellationToken::new())
+            .await
+    }
+
+    /// Give up on the sidecar's job once cancelled, rather than leaving it to run until its
+    /// timeout, and ask the sidecar to prove within the budget left rather than the whole window
+    async fn execute_cancellable(
+        &self,
+        intent: &I,
+        budget: Duration,
+        cancel: CancellationToken,
+    ) -> taralli_client::error::Result<WorkResult> {
+        tracing::info!("remote worker: execution started");
+
+        let params = intent
+            .system()
+            .system_params()
+            .ok_or_else(|| ClientError::WorkerError("System params not available".into()))?;
+        // formatting the proof needs the system compiled in, fail before it is proved
+        ensure_compiled(params.system_id())?;
+
+        let proof = self.prove(params, budget, cancel).await?;
+        tracing::info!("remote prover execution finished");
+
+        Ok(format_remote_proof(
+            params,
+            &proof,
+            intent.proof_commitment(),
+        )?)
+    }
+
+    fn describe(&self) -> String {
+        format!("remote worker at {}", self.config.endpoint)
+    }
+
+    fn produces_partial_commitment(&self) -> bool {
+        true
+    }
+}
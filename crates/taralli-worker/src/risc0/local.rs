@@ -17,10 +17,10 @@ impl Risc0LocalProver {
 
 #[async_trait]
 impl Risc0Prover for Risc0LocalProver {
-    async fn generate_proof(&self, params: &Risc0ProofParams) -> Result<Receipt> {
+    async fn generate_proof(&self, params: &Risc0ProofParams, inputs: &[u8]) -> Result<Receipt> {
         // setup env
         let env = ExecutorEnv::builder()
-            .write_slice(&params.inputs)
+            .write_slice(inputs)
             .build()
             .map_err(|e| WorkerError::ExecutionFailed(e.to_string()))?;
         let prover = default_prover();
@@ -1,45 +1,117 @@
 pub mod local; // local risc0 prover
 pub mod remote; // bonsai network risc0 prover
+pub mod verify; // local risc0 proof verification
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
 
 use async_trait::async_trait;
-use risc0_zkvm::Receipt;
+use risc0_zkvm::sha::{Impl, Sha256};
+use risc0_zkvm::{compute_image_id, Receipt};
 use taralli_client::error::ClientError;
-use taralli_primitives::intents::ComputeIntent;
+use taralli_primitives::commitment::{check_public_inputs_layout, submitted_partial_commitment};
+use taralli_primitives::intents::{CommonProofCommitment, ComputeIntent};
 use taralli_primitives::systems::risc0::Risc0ProofParams;
 
 use crate::error::{Result, WorkerError};
 use taralli_client::worker::{ComputeWorker, WorkResult};
 use taralli_primitives::alloy::dyn_abi::DynSolValue;
-use taralli_primitives::alloy::primitives::{Bytes, FixedBytes};
-use taralli_primitives::systems::{System, SystemParams};
+use taralli_primitives::alloy::primitives::{keccak256, Bytes, FixedBytes, B256};
+use taralli_primitives::systems::{System, SystemId, SystemParams};
+
+/// Image ids already computed, keyed by the keccak hash of their ELF
+static IMAGE_ID_CACHE: LazyLock<Mutex<HashMap<B256, FixedBytes<32>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Compute the image id of a guest program, reusing the cached value for ELFs seen before.
+pub fn image_id_for_elf(elf: &[u8]) -> Result<FixedBytes<32>> {
+    let elf_hash = keccak256(elf);
+    if let Some(image_id) = IMAGE_ID_CACHE
+        .lock()
+        .map_err(|e| WorkerError::ExecutionFailed(e.to_string()))?
+        .get(&elf_hash)
+    {
+        return Ok(*image_id);
+    }
+
+    let image_id = FixedBytes::from_slice(
+        compute_image_id(elf)
+            .map_err(|e| WorkerError::ExecutionFailed(e.to_string()))?
+            .as_bytes(),
+    );
+    IMAGE_ID_CACHE
+        .lock()
+        .map_err(|e| WorkerError::ExecutionFailed(e.to_string()))?
+        .insert(elf_hash, image_id);
+    Ok(image_id)
+}
+
+/// sha256 digest of a journal, as checked by the risc0 verifier contract
+pub fn journal_digest(journal: &[u8]) -> FixedBytes<32> {
+    FixedBytes::from_slice(Impl::hash_bytes(journal).as_bytes())
+}
 
 // Shared traits & functionality for all RISC0 workers
 pub trait Risc0ProofFormatter {
+    /// abi encode `(bytes seal, bytes32 imageId, bytes32 journalDigest)`, the arguments of
+    /// the risc0 verifier's `verify(...)`
     fn format_opaque_submission(receipt: &Receipt, image_id: FixedBytes<32>) -> Result<Bytes> {
+        let seal = &receipt
+            .inner
+            .groth16()
+            .map_err(|e| WorkerError::ExecutionFailed(e.to_string()))?
+            .seal;
+        Ok(Self::encode_submission(
+            seal,
+            image_id,
+            &receipt.journal.bytes,
+        ))
+    }
+
+    /// Same encoding out of the groth16 seal and the journal it was produced with
+    fn encode_submission(seal: &[u8], image_id: FixedBytes<32>, journal: &[u8]) -> Bytes {
         let proof_input_values = DynSolValue::Tuple(vec![
-            DynSolValue::Bytes(
-                receipt
-                    .inner
-                    .groth16()
-                    .map_err(|e| WorkerError::ExecutionFailed(e.to_string()))?
-                    .seal
-                    .clone(),
-            ),
+            DynSolValue::Bytes(seal.to_vec()),
             DynSolValue::FixedBytes(image_id, 32),
-            DynSolValue::FixedBytes(FixedBytes::from_slice(&receipt.journal.bytes), 32),
+            DynSolValue::FixedBytes(journal_digest(journal), 32),
         ]);
 
-        Ok(Bytes::from(proof_input_values.abi_encode()))
+        Bytes::from(proof_input_values.abi_encode())
     }
 
-    fn compute_partial_commitment(_journal: &[u8]) -> Result<FixedBytes<32>> {
-        Ok(FixedBytes::new([0u8; 32]))
+    /// Check the inputs the verifier details of the intent point at are the image id and journal
+    /// digest of the formatted submission
+    fn check_submission_layout(
+        opaque_submission: &[u8],
+        extra_data: &[u8],
+        image_id: FixedBytes<32>,
+        journal: &[u8],
+    ) -> Result<()> {
+        let public_inputs = [image_id.as_slice(), journal_digest(journal).as_slice()].concat();
+        check_public_inputs_layout(
+            SystemId::Risc0,
+            opaque_submission,
+            extra_data,
+            &public_inputs,
+        )
+        .map_err(|e| WorkerError::SubmissionLayout(e.to_string()))
+    }
+
+    /// Partial commitment the intent's partial commitment result check takes, zero when it
+    /// declares none. A check over the image id and journal digest submits the journal digest.
+    fn compute_partial_commitment<C: CommonProofCommitment>(
+        proof_commitment: &C,
+        opaque_submission: &[u8],
+    ) -> Result<FixedBytes<32>> {
+        submitted_partial_commitment(proof_commitment, opaque_submission)
+            .map_err(|e| WorkerError::SubmissionLayout(e.to_string()))
     }
 }
 
 #[async_trait]
 pub trait Risc0Prover {
-    async fn generate_proof(&self, params: &Risc0ProofParams) -> Result<Receipt>;
+    /// Prove the program of `params` run on `inputs`, the typed inputs of the intent
+    async fn generate_proof(&self, params: &Risc0ProofParams, inputs: &[u8]) -> Result<Receipt>;
 }
 
 pub struct Risc0Worker<P: Risc0Prover> {
@@ -72,17 +144,30 @@ where
             SystemParams::Risc0(params) => params.clone(),
             _ => return Err(ClientError::WorkerError("Expected Risc0 params".into())),
         };
+        // validated as abi encoded words when the params were parsed
+        let inputs = system_params.inputs().into_bytes()?;
 
-        let receipt = self.prover.generate_proof(&params).await?;
+        let receipt = self.prover.generate_proof(&params, &inputs).await?;
         tracing::info!("prover execution finished");
 
-        let image_id = FixedBytes::from_slice(&params.elf[0..32]);
+        let image_id = image_id_for_elf(&params.elf)?;
         let opaque_submission = Self::format_opaque_submission(&receipt, image_id)?;
-        let partial_commitment = Self::compute_partial_commitment(&receipt.journal.bytes)?;
+        Self::check_submission_layout(
+            &opaque_submission,
+            intent.proof_commitment().extra_data(),
+            image_id,
+            &receipt.journal.bytes,
+        )?;
+        let partial_commitment =
+            Self::compute_partial_commitment(intent.proof_commitment(), &opaque_submission)?;
 
         Ok(WorkResult {
             opaque_submission,
             partial_commitment,
         })
     }
+
+    fn produces_partial_commitment(&self) -> bool {
+        true
+    }
 }
@@ -1,29 +1,26 @@
 use std::time::Duration;
 
-use super::Risc0Prover;
+use super::{image_id_for_elf, Risc0Prover};
 use crate::error::{Result, WorkerError};
 use async_trait::async_trait;
 use bonsai_sdk::non_blocking::Client;
-use risc0_zkvm::Receipt;
-use risc0_zkvm::{compute_image_id, serde::to_vec};
+use risc0_zkvm::{serde::to_vec, Receipt};
 use taralli_primitives::systems::risc0::Risc0ProofParams;
 
 pub struct Risc0RemoteProver;
 
 #[async_trait]
 impl Risc0Prover for Risc0RemoteProver {
-    async fn generate_proof(&self, params: &Risc0ProofParams) -> Result<Receipt> {
+    async fn generate_proof(&self, params: &Risc0ProofParams, inputs: &[u8]) -> Result<Receipt> {
         let program = params.elf.clone();
-        let inputs = params.inputs.clone();
+        let inputs = inputs.to_vec();
 
         // Create async client
         let client = Client::from_env(risc0_zkvm::VERSION)
             .map_err(|e| WorkerError::ExecutionFailed(e.to_string()))?;
 
         // Compute and upload image ID
-        let image_id = hex::encode(
-            compute_image_id(&program).map_err(|e| WorkerError::ExecutionFailed(e.to_string()))?,
-        );
+        let image_id = hex::encode(image_id_for_elf(&program)?);
 
         client
             .upload_img(&image_id, program)
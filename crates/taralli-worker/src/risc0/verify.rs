@@ -0,0 +1,67 @@
+use async_trait::async_trait;
+use risc0_zkvm::sha::{Digest, Digestible};
+use risc0_zkvm::{
+    Groth16Receipt, Groth16ReceiptVerifierParameters, MaybePruned, ReceiptClaim, VerifierContext,
+};
+use taralli_client::audit::{ProofVerdict, ProofVerifier};
+use taralli_client::error::ClientError;
+use taralli_client::tracker::payload::DecodedProof;
+use taralli_primitives::systems::SystemParams;
+
+use super::image_id_for_elf;
+
+/// Verifier of risc0 groth16 seals, rebuilding the receipt claimed by the submission. The
+/// journal is only known through its digest, so the claim is verified with a pruned journal.
+#[derive(Default)]
+pub struct Risc0Verifier;
+
+impl Risc0Verifier {
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl ProofVerifier for Risc0Verifier {
+    async fn verify(
+        &self,
+        proof: &DecodedProof,
+        system: Option<&SystemParams>,
+    ) -> taralli_client::error::Result<ProofVerdict> {
+        let DecodedProof::Risc0 {
+            seal,
+            image_id,
+            journal_digest,
+        } = proof
+        else {
+            return Err(ClientError::WorkerError("Expected a Risc0 proof".into()));
+        };
+
+        // the submitted image id has to be the one of the requested program
+        if let Some(SystemParams::Risc0(params)) = system {
+            let expected_image_id = image_id_for_elf(&params.elf)?;
+            if expected_image_id != *image_id {
+                return Ok(ProofVerdict::Invalid(format!(
+                    "image id {image_id} is not the one of the requested elf, {expected_image_id}"
+                )));
+            }
+        }
+
+        let claim = ReceiptClaim::ok(
+            Digest::from_bytes(image_id.0),
+            MaybePruned::Pruned(Digest::from_bytes(journal_digest.0)),
+        );
+        let receipt = Groth16Receipt::new(
+            seal.to_vec(),
+            MaybePruned::Value(claim),
+            Groth16ReceiptVerifierParameters::default().digest(),
+        );
+        Ok(
+            match receipt.verify_integrity_with_context(&VerifierContext::default()) {
+                Ok(()) => ProofVerdict::Valid,
+                Err(e) => ProofVerdict::Invalid(format!("groth16 seal does not verify: {e}")),
+            },
+        )
+    }
+}
@@ -0,0 +1,319 @@
+//! Sandboxed execution of compute workers. Guest programs (circuit wasm, ELFs) are supplied by
+//! requesters, running them in-process lets a malicious one take down the provider with every
+//! job in flight. `Sandboxed` runs the proof generation of each intent in a child process, the
+//! `taralli-sandbox-runner` binary, under resource limits and killed once it runs out of time.
+//!
+//! The runner reads a bincode encoded `SandboxJob` from its stdin and writes a bincode encoded
+//! `SandboxOutcome` to its stdout, logging to its stderr.
+//!
+//! Memory and cpu time limits are set through rlimits, so only on unix targets. Elsewhere the
+//! runner is only bounded by its wall clock timeout.
+
+use std::io;
+#[cfg(unix)]
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::path::PathBuf;
+use std::process::{ExitStatus, Stdio};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use taralli_client::error::ClientError;
+use taralli_client::worker::{ComputeWorker, WorkResult};
+use taralli_primitives::alloy::primitives::{Bytes, FixedBytes};
+use taralli_primitives::intents::offer::ComputeOffer;
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::intents::{CommonProofCommitment, ComputeIntent};
+use taralli_primitives::systems::SystemParams;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::process::{Child, Command};
+use tokio_util::sync::CancellationToken;
+
+use crate::error::{Result, WorkerError};
+use crate::systems::local_worker;
+
+/// Name of the runner binary built along with this crate
+pub const RUNNER_BIN: &str = "taralli-sandbox-runner";
+
+/// Kind of the intent a sandboxed job proves
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SandboxIntentKind {
+    Request,
+    Offer,
+}
+
+/// Intent the runner knows how to decode
+pub trait SandboxIntent: ComputeIntent<System = SystemParams> + 'static {
+    const KIND: SandboxIntentKind;
+}
+
+impl SandboxIntent for ComputeRequest<SystemParams> {
+    const KIND: SandboxIntentKind = SandboxIntentKind::Request;
+}
+
+impl SandboxIntent for ComputeOffer<SystemParams> {
+    const KIND: SandboxIntentKind = SandboxIntentKind::Offer;
+}
+
+/// Job sent to the runner
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SandboxJob {
+    pub kind: SandboxIntentKind,
+    /// JSON encoded intent, its system params holding JSON values bincode can't decode
+    pub intent: Vec<u8>,
+}
+
+/// Result of a job, returned by the runner
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SandboxOutcome {
+    Completed {
+        opaque_submission: Vec<u8>,
+        partial_commitment: [u8; 32],
+    },
+    Failed(String),
+}
+
+/// How the runner of a system is started and limited
+#[derive(Clone, Debug)]
+pub struct SandboxConfig {
+    /// path of the runner binary
+    pub runner: PathBuf,
+    /// most memory the runner may allocate, in bytes. Enforced as `RLIMIT_DATA` rather than
+    /// `RLIMIT_AS`, wasm runtimes reserving far more address space than they ever map.
+    pub max_memory: Option<u64>,
+    /// cpu seconds the runner may use per second of the intent's proving time, cpu time being
+    /// summed over the runner's threads
+    pub cpu_time_factor: u64,
+    /// wall clock time after which the runner is killed, the intent's proving time when unset
+    pub timeout: Option<Duration>,
+}
+
+impl SandboxConfig {
+    /// Runner at `runner` without memory limit, allowed a second of cpu time per core and
+    /// second of proving time
+    pub fn new(runner: impl Into<PathBuf>) -> Self {
+        let cores = std::thread::available_parallelism().map_or(1, |cores| cores.get() as u64);
+        Self {
+            runner: runner.into(),
+            max_memory: None,
+            cpu_time_factor: cores,
+            timeout: None,
+        }
+    }
+
+    pub fn with_max_memory(mut self, bytes: u64) -> Self {
+        self.max_memory = Some(bytes);
+        self
+    }
+
+    pub fn with_cpu_time_factor(mut self, factor: u64) -> Self {
+        self.cpu_time_factor = factor;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+/// Compute worker running the proof generation of each intent in its own runner process
+#[derive(Clone, Debug)]
+pub struct Sandboxed {
+    config: SandboxConfig,
+}
+
+impl Sandboxed {
+    pub fn new(config: SandboxConfig) -> Self {
+        Self { config }
+    }
+
+    async fn run(
+        &self,
+        job: SandboxJob,
+        budget: Duration,
+        cancel: CancellationToken,
+    ) -> Result<WorkResult> {
+        let job = bincode::serialize(&job).map_err(|e| WorkerError::ParamsError(e.to_string()))?;
+        let mut command = Command::new(&self.config.runner);
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true);
+        #[cfg(unix)]
+        {
+            let cpu_seconds = budget
+                .as_secs()
+                .max(1)
+                .saturating_mul(self.config.cpu_time_factor.max(1));
+            let max_memory = self.config.max_memory;
+            // SAFETY: setrlimit is async-signal-safe and the closure doesn't allocate
+            unsafe {
+                command.pre_exec(move || apply_limits(cpu_seconds, max_memory));
+            }
+        }
+        let mut child = command
+            .spawn()
+            .map_err(|e| WorkerError::ExecutionFailed(format!("failed to spawn runner: {e}")))?;
+
+        let timeout = self
+            .config
+            .timeout
+            .map_or(budget, |timeout| timeout.min(budget));
+        let finished = tokio::select! {
+            finished = tokio::time::timeout(timeout, exchange(&mut child, &job)) => Some(finished),
+            () = cancel.cancelled() => None,
+        };
+        let (status, output) = match finished {
+            Some(Ok(finished)) => {
+                finished.map_err(|e| WorkerError::ExecutionFailed(e.to_string()))?
+            }
+            Some(Err(_)) => {
+                let _ = child.kill().await;
+                return Err(WorkerError::ProofTimeout(format!(
+                    "sandboxed worker killed after {}s",
+                    timeout.as_secs()
+                )));
+            }
+            None => {
+                let _ = child.kill().await;
+                return Err(WorkerError::ExecutionFailed(
+                    "sandboxed worker killed on cancellation".to_string(),
+                ));
+            }
+        };
+
+        if !status.success() {
+            return Err(WorkerError::ExecutionFailed(describe_exit(status)));
+        }
+        match bincode::deserialize(&output)
+            .map_err(|e| WorkerError::ExecutionFailed(format!("invalid runner output: {e}")))?
+        {
+            SandboxOutcome::Completed {
+                opaque_submission,
+                partial_commitment,
+            } => Ok(WorkResult {
+                opaque_submission: Bytes::from(opaque_submission),
+                partial_commitment: FixedBytes::from(partial_commitment),
+            }),
+            SandboxOutcome::Failed(reason) => Err(WorkerError::ExecutionFailed(reason)),
+        }
+    }
+}
+
+#[async_trait]
+impl<I: SandboxIntent> ComputeWorker<I> for Sandboxed {
+    /// Run within the whole proving window of `intent`
+    async fn execute(&self, intent: &I) -> taralli_client::error::Result<WorkResult> {
+        let proving_time = Duration::from_secs(intent.proof_commitment().proving_time().into());
+        self.execute_cancellable(intent, proving_time, CancellationToken::new())
+            .await
+    }
+
+    /// Kill the runner and wait for it to exit once cancelled, rather than leaving it to be
+    /// killed whenever the execution is dropped
+    async fn execute_cancellable(
+        &self,
+        intent: &I,
+        budget: Duration,
+        cancel: CancellationToken,
+    ) -> taralli_client::error::Result<WorkResult> {
+        tracing::info!("sandboxed worker: execution started");
+
+        let job = SandboxJob {
+            kind: I::KIND,
+            intent: serde_json::to_vec(intent)
+                .map_err(|e| ClientError::WorkerError(e.to_string()))?,
+        };
+        Ok(self.run(job, budget, cancel).await?)
+    }
+
+    /// The runner proves with the in-process workers, which all produce it
+    fn produces_partial_commitment(&self) -> bool {
+        true
+    }
+}
+
+/// Write the job to the runner's stdin while reading its stdout, so neither side blocks on a
+/// full pipe, then wait for it to exit
+async fn exchange(child: &mut Child, job: &[u8]) -> io::Result<(ExitStatus, Vec<u8>)> {
+    let mut stdin = child.stdin.take().expect("runner stdin is piped");
+    let mut stdout = child.stdout.take().expect("runner stdout is piped");
+    let write = async move {
+        // a runner dying early closes its stdin, its exit status tells why
+        let _ = stdin.write_all(job).await;
+    };
+    let mut output = Vec::new();
+    let (_, read) = tokio::join!(write, stdout.read_to_end(&mut output));
+    read?;
+    Ok((child.wait().await?, output))
+}
+
+#[cfg(unix)]
+fn apply_limits(cpu_seconds: u64, max_memory: Option<u64>) -> io::Result<()> {
+    // SAFETY: the limits are valid rlimits for the duration of the calls
+    check(unsafe { libc::setrlimit(libc::RLIMIT_CPU, &rlimit(cpu_seconds)) })?;
+    if let Some(max_memory) = max_memory {
+        check(unsafe { libc::setrlimit(libc::RLIMIT_DATA, &rlimit(max_memory)) })?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn rlimit(value: u64) -> libc::rlimit {
+    libc::rlimit {
+        rlim_cur: value as libc::rlim_t,
+        rlim_max: value as libc::rlim_t,
+    }
+}
+
+#[cfg(unix)]
+fn check(ret: libc::c_int) -> io::Result<()> {
+    if ret != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn describe_exit(status: ExitStatus) -> String {
+    match status.signal() {
+        Some(libc::SIGXCPU) => "sandboxed worker exceeded its cpu time".to_string(),
+        Some(signal) => format!("sandboxed worker killed by signal {signal}"),
+        None => format!("sandboxed worker failed: {status}"),
+    }
+}
+
+#[cfg(not(unix))]
+fn describe_exit(status: ExitStatus) -> String {
+    format!("sandboxed worker failed: {status}")
+}
+
+/// Run a job read by the runner, proving it with the in-process worker of its system
+pub async fn run_job(job: &[u8]) -> SandboxOutcome {
+    let result = match bincode::deserialize::<SandboxJob>(job) {
+        Ok(job) => match job.kind {
+            SandboxIntentKind::Request => prove::<ComputeRequest<SystemParams>>(&job.intent).await,
+            SandboxIntentKind::Offer => prove::<ComputeOffer<SystemParams>>(&job.intent).await,
+        },
+        Err(e) => Err(ClientError::WorkerError(format!("invalid job: {e}"))),
+    };
+    match result {
+        Ok(result) => SandboxOutcome::Completed {
+            opaque_submission: result.opaque_submission.to_vec(),
+            partial_commitment: result.partial_commitment.0,
+        },
+        Err(e) => SandboxOutcome::Failed(e.to_string()),
+    }
+}
+
+async fn prove<I: SandboxIntent + Send + Sync>(
+    intent: &[u8],
+) -> taralli_client::error::Result<WorkResult> {
+    let intent: I = serde_json::from_slice(intent)
+        .map_err(|e| ClientError::WorkerError(format!("invalid intent: {e}")))?;
+    local_worker::<I>(intent.system_id())?
+        .execute(&intent)
+        .await
+}
@@ -35,9 +35,10 @@ impl Sp1Prover for Sp1LocalProver {
     async fn generate_proof(
         &self,
         params: &Sp1ProofParams,
+        inputs: &[u8],
     ) -> Result<(SP1ProofWithPublicValues, SP1VerifyingKey)> {
         let mut stdin = SP1Stdin::new();
-        stdin.write(&params.inputs);
+        stdin.write(&inputs);
 
         match &self.prover {
             Sp1LocalProverType::Cpu(prover) => {
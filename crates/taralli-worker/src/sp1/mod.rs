@@ -1,9 +1,10 @@
 pub mod local; // local sp1 prover
 pub mod remote; // succint network sp1 prover
+pub mod verify; // local sp1 proof verification
 
 use crate::error::{Result, WorkerError};
 use async_trait::async_trait;
-use sp1_sdk::{HashableKey, SP1ProofWithPublicValues, SP1VerifyingKey};
+use sp1_sdk::{HashableKey, SP1ProofMode, SP1ProofWithPublicValues, SP1VerifyingKey};
 use std::str::FromStr;
 use taralli_client::{
     error::ClientError,
@@ -14,10 +15,22 @@ use taralli_primitives::alloy::{
     primitives::{Bytes, FixedBytes},
 };
 use taralli_primitives::{
-    intents::ComputeIntent,
-    systems::{sp1::Sp1ProofParams, System, SystemParams},
+    commitment::{check_public_inputs_layout, submitted_partial_commitment},
+    intents::{CommonProofCommitment, ComputeIntent},
+    systems::{
+        sp1::{Sp1Mode, Sp1ProofParams},
+        System, SystemId, SystemParams,
+    },
 };
 
+/// sp1 proof type producing a proof verifiable on-chain in the requested mode
+pub fn proof_mode(mode: &Sp1Mode) -> SP1ProofMode {
+    match mode {
+        Sp1Mode::Groth16 => SP1ProofMode::Groth16,
+        Sp1Mode::Plonk => SP1ProofMode::Plonk,
+    }
+}
+
 pub trait Sp1ProofFormatter {
     fn format_opaque_submission(
         sp1_proof: &SP1ProofWithPublicValues,
@@ -35,25 +48,54 @@ pub trait Sp1ProofFormatter {
         let vkey = FixedBytes::from_str(&vk.bytes32())
             .map_err(|e| WorkerError::ExecutionFailed(e.to_string()))?;
 
+        Ok(Self::encode_submission(
+            vkey,
+            sp1_proof.public_values.as_slice(),
+            &proof_bytes,
+        ))
+    }
+
+    /// abi encode `(bytes32 vkey, bytes publicValues, bytes proofBytes)`, the proof bytes being
+    /// the groth16 or plonk proof the sp1 verifier takes, led by its selector
+    fn encode_submission(vkey: FixedBytes<32>, public_values: &[u8], proof_bytes: &[u8]) -> Bytes {
         let proof_input_values = DynSolValue::Tuple(vec![
             DynSolValue::FixedBytes(vkey, 32),
-            DynSolValue::Bytes(sp1_proof.public_values.to_vec()),
-            DynSolValue::Bytes(proof_bytes),
+            DynSolValue::Bytes(public_values.to_vec()),
+            DynSolValue::Bytes(proof_bytes.to_vec()),
         ]);
 
-        Ok(Bytes::from(proof_input_values.abi_encode()))
+        Bytes::from(proof_input_values.abi_encode())
     }
 
-    fn compute_partial_commitment() -> Result<FixedBytes<32>> {
-        Ok(FixedBytes::new([0u8; 32]))
+    /// Check the inputs the verifier details of the intent point at are within the public values
+    /// of the formatted submission
+    fn check_submission_layout(
+        opaque_submission: &[u8],
+        extra_data: &[u8],
+        public_values: &[u8],
+    ) -> Result<()> {
+        check_public_inputs_layout(SystemId::Sp1, opaque_submission, extra_data, public_values)
+            .map_err(|e| WorkerError::SubmissionLayout(e.to_string()))
+    }
+
+    /// Partial commitment the intent's partial commitment result check takes, zero when it
+    /// declares none. The check's result field lies within the public values.
+    fn compute_partial_commitment<C: CommonProofCommitment>(
+        proof_commitment: &C,
+        opaque_submission: &[u8],
+    ) -> Result<FixedBytes<32>> {
+        submitted_partial_commitment(proof_commitment, opaque_submission)
+            .map_err(|e| WorkerError::SubmissionLayout(e.to_string()))
     }
 }
 
 #[async_trait]
 pub trait Sp1Prover {
+    /// Prove the program of `params` run on `inputs`, the typed inputs of the intent
     async fn generate_proof(
         &self,
         params: &Sp1ProofParams,
+        inputs: &[u8],
     ) -> Result<(SP1ProofWithPublicValues, SP1VerifyingKey)>;
 }
 
@@ -88,16 +130,27 @@ where
             SystemParams::Sp1(params) => params.clone(),
             _ => return Err(ClientError::WorkerError("Expected Sp1 params".into())),
         };
+        let inputs = system_params.inputs().into_bytes()?;
 
-        let (sp1_proof, vk) = self.prover.generate_proof(&params).await?;
+        let (sp1_proof, vk) = self.prover.generate_proof(&params, &inputs).await?;
         tracing::info!("prover execution finished");
 
         let opaque_submission = Self::format_opaque_submission(&sp1_proof, &vk)?;
-        let partial_commitment = Self::compute_partial_commitment()?;
+        Self::check_submission_layout(
+            &opaque_submission,
+            intent.proof_commitment().extra_data(),
+            sp1_proof.public_values.as_slice(),
+        )?;
+        let partial_commitment =
+            Self::compute_partial_commitment(intent.proof_commitment(), &opaque_submission)?;
 
         Ok(WorkResult {
             opaque_submission,
             partial_commitment,
         })
     }
+
+    fn produces_partial_commitment(&self) -> bool {
+        true
+    }
 }
@@ -1,44 +1,153 @@
+use std::env;
 use std::time::Duration;
 
-use super::Sp1Prover;
+use super::{proof_mode, Sp1Prover};
 use crate::error::{Result, WorkerError};
+pub use crate::remote::{wait_for_proof, RemoteProofStatus};
 use async_trait::async_trait;
 use sp1_sdk::{
-    network::FulfillmentStrategy, NetworkProver, Prover, ProverClient, SP1ProofMode,
-    SP1ProofWithPublicValues, SP1Stdin, SP1VerifyingKey,
+    network::{
+        proto::network::{ExecutionStatus, FulfillmentStatus},
+        FulfillmentStrategy,
+    },
+    NetworkProver, Prover, ProverClient, SP1ProofWithPublicValues, SP1Stdin, SP1VerifyingKey,
 };
 use taralli_primitives::systems::sp1::Sp1ProofParams;
 
+/// substrings of succinct network errors caused by the account running out of credits or being
+/// rate limited rather than by the proof itself
+const QUOTA_ERROR_MARKERS: [&str; 4] = [
+    "insufficient balance",
+    "resource exhausted",
+    "resourceexhausted",
+    "rate limit",
+];
+
+/// Settings of the succinct prover network
+#[derive(Clone)]
+pub struct Sp1RemoteConfig {
+    /// key of the account paying the network for proofs
+    pub private_key: String,
+    pub rpc_url: String,
+    pub fulfillment_strategy: FulfillmentStrategy,
+    /// skip executing the program locally before requesting the proof
+    pub skip_simulation: bool,
+    /// how long the network has to fulfill a proof request, also the deadline of the worker
+    pub request_timeout: Duration,
+    /// delay between two proof status checks
+    pub poll_interval: Duration,
+    /// cycle limit of the proof request, the network's default when unset
+    pub max_cycles: Option<u64>,
+}
+
+impl Sp1RemoteConfig {
+    pub fn new(private_key: impl Into<String>, rpc_url: impl Into<String>) -> Self {
+        Self {
+            private_key: private_key.into(),
+            rpc_url: rpc_url.into(),
+            fulfillment_strategy: FulfillmentStrategy::Hosted,
+            skip_simulation: false,
+            request_timeout: Duration::from_secs(60 * 60),
+            poll_interval: Duration::from_secs(15),
+            max_cycles: None,
+        }
+    }
+
+    /// Load the config from `NETWORK_PRIVATE_KEY` and `NETWORK_RPC_URL`, with the optional
+    /// `SP1_REQUEST_TIMEOUT_SECS`, `SP1_POLL_INTERVAL_SECS` and `SP1_MAX_CYCLES` overrides
+    pub fn from_env() -> Result<Self> {
+        let required = |name: &str| {
+            env::var(name).map_err(|_| WorkerError::ParamsError(format!("{name} is not set")))
+        };
+        let optional = |name: &str| -> Result<Option<u64>> {
+            env::var(name)
+                .ok()
+                .map(|value| {
+                    value
+                        .parse()
+                        .map_err(|e| WorkerError::ParamsError(format!("invalid {name}: {e}")))
+                })
+                .transpose()
+        };
+
+        let mut config = Self::new(
+            required("NETWORK_PRIVATE_KEY")?,
+            required("NETWORK_RPC_URL")?,
+        );
+        if let Some(secs) = optional("SP1_REQUEST_TIMEOUT_SECS")? {
+            config.request_timeout = Duration::from_secs(secs);
+        }
+        if let Some(secs) = optional("SP1_POLL_INTERVAL_SECS")? {
+            config.poll_interval = Duration::from_secs(secs);
+        }
+        config.max_cycles = optional("SP1_MAX_CYCLES")?;
+        Ok(config)
+    }
+
+    pub fn with_fulfillment_strategy(mut self, fulfillment_strategy: FulfillmentStrategy) -> Self {
+        self.fulfillment_strategy = fulfillment_strategy;
+        self
+    }
+
+    pub fn with_skip_simulation(mut self, skip_simulation: bool) -> Self {
+        self.skip_simulation = skip_simulation;
+        self
+    }
+
+    pub fn with_request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn with_max_cycles(mut self, max_cycles: u64) -> Self {
+        self.max_cycles = Some(max_cycles);
+        self
+    }
+}
+
+/// Tell quota exhaustion apart from other network failures
+pub fn classify_network_error(error: impl std::fmt::Display) -> WorkerError {
+    let message = error.to_string();
+    let lowercase = message.to_lowercase();
+    if QUOTA_ERROR_MARKERS
+        .iter()
+        .any(|marker| lowercase.contains(marker))
+    {
+        WorkerError::QuotaExhausted(message)
+    } else {
+        WorkerError::ExecutionFailed(message)
+    }
+}
+
 pub struct Sp1RemoteProver {
     network_prover: NetworkProver,
-    fulfillment_strategy: FulfillmentStrategy,
-    proof_mode: SP1ProofMode,
-    simulate_locally: bool,
+    config: Sp1RemoteConfig,
 }
 
 impl Sp1RemoteProver {
     #[must_use]
-    pub fn new(
-        private_key: &str,
-        rpc_url: &str,
-        fulfillment_strategy: FulfillmentStrategy,
-        proof_mode: SP1ProofMode,
-        simulate_locally: bool,
-    ) -> Self {
+    pub fn new(config: Sp1RemoteConfig) -> Self {
         // set up succint network prover
         let network_prover = ProverClient::builder()
             .network()
-            .private_key(private_key)
-            .rpc_url(rpc_url)
+            .private_key(&config.private_key)
+            .rpc_url(&config.rpc_url)
             .build();
 
         Self {
             network_prover,
-            fulfillment_strategy,
-            proof_mode,
-            simulate_locally,
+            config,
         }
     }
+
+    pub fn config(&self) -> &Sp1RemoteConfig {
+        &self.config
+    }
 }
 
 #[async_trait]
@@ -46,39 +155,63 @@ impl Sp1Prover for Sp1RemoteProver {
     async fn generate_proof(
         &self,
         params: &Sp1ProofParams,
+        inputs: &[u8],
     ) -> Result<(SP1ProofWithPublicValues, SP1VerifyingKey)> {
         let mut stdin = SP1Stdin::new();
-        stdin.write(&params.inputs);
+        stdin.write(&inputs);
 
         let (pk, vk) = self.network_prover.setup(&params.elf);
 
-        // Request proof and get the proof ID immediately
-        let request_id = self
+        // request the proof type the requester's verifier expects
+        let mut request = self
             .network_prover
             .prove(&pk, &stdin)
-            .groth16()
-            .skip_simulation(true)
+            .mode(proof_mode(&params.config.mode))
+            .strategy(self.config.fulfillment_strategy)
+            .skip_simulation(self.config.skip_simulation)
+            .timeout(self.config.request_timeout);
+        if let Some(max_cycles) = self.config.max_cycles {
+            request = request.cycle_limit(max_cycles);
+        }
+        let request_id = request
             .request_async()
             .await
-            .map_err(|e| WorkerError::ExecutionFailed(e.to_string()))?;
-
-        // Wait for proof complete with a timeout
-        let _proof = self
-            .network_prover
-            .wait_proof(request_id, Some(Duration::from_secs(60 * 60)))
-            .await
-            .map_err(|e| WorkerError::ExecutionFailed(e.to_string()))?;
+            .map_err(classify_network_error)?;
+        tracing::info!("sp1 network proof requested: {request_id}");
 
-        // Request a proof with reserved prover network capacity and wait for it to be fulfilled
-        let proof = self
-            .network_prover
-            .prove(&pk, &stdin)
-            .mode(self.proof_mode)
-            .skip_simulation(self.simulate_locally)
-            .strategy(self.fulfillment_strategy)
-            .run_async()
-            .await
-            .map_err(|e| WorkerError::ExecutionFailed(e.to_string()))?;
+        let proof = wait_for_proof(
+            || async {
+                let (status, proof) = match self.network_prover.get_proof_status(request_id).await {
+                    Ok(status) => status,
+                    // the request keeps running on the network, retry on the next poll
+                    Err(e) => {
+                        tracing::warn!("failed to fetch sp1 proof status: {e}");
+                        return Ok(RemoteProofStatus::Pending);
+                    }
+                };
+                if ExecutionStatus::try_from(status.execution_status)
+                    == Ok(ExecutionStatus::Unexecutable)
+                {
+                    return Ok(RemoteProofStatus::Unfulfillable(
+                        "program is unexecutable".into(),
+                    ));
+                }
+                Ok(
+                    match FulfillmentStatus::try_from(status.fulfillment_status) {
+                        Ok(FulfillmentStatus::Fulfilled) => proof
+                            .map(RemoteProofStatus::Fulfilled)
+                            .unwrap_or(RemoteProofStatus::Pending),
+                        Ok(FulfillmentStatus::Unfulfillable) => RemoteProofStatus::Unfulfillable(
+                            "no prover fulfilled the request".into(),
+                        ),
+                        _ => RemoteProofStatus::Pending,
+                    },
+                )
+            },
+            self.config.poll_interval,
+            self.config.request_timeout,
+        )
+        .await?;
 
         Ok((proof, vk))
     }
@@ -0,0 +1,71 @@
+use async_trait::async_trait;
+use sp1_verifier::{Groth16Verifier, PlonkVerifier, GROTH16_VK_BYTES, PLONK_VK_BYTES};
+use taralli_client::audit::{ProofVerdict, ProofVerifier};
+use taralli_client::error::ClientError;
+use taralli_client::tracker::payload::DecodedProof;
+use taralli_primitives::systems::{sp1::Sp1Mode, SystemParams};
+
+/// Verifier of sp1 groth16 and plonk proofs against the vkey hash they were submitted with.
+/// Without the request's system params the proof is checked in both modes.
+#[derive(Default)]
+pub struct Sp1Verifier;
+
+impl Sp1Verifier {
+    #[must_use]
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn verify_in_mode(
+        mode: &Sp1Mode,
+        proof_bytes: &[u8],
+        public_values: &[u8],
+        vkey_hash: &str,
+    ) -> std::result::Result<(), String> {
+        match mode {
+            Sp1Mode::Groth16 => {
+                Groth16Verifier::verify(proof_bytes, public_values, vkey_hash, *GROTH16_VK_BYTES)
+                    .map_err(|e| e.to_string())
+            }
+            Sp1Mode::Plonk => {
+                PlonkVerifier::verify(proof_bytes, public_values, vkey_hash, *PLONK_VK_BYTES)
+                    .map_err(|e| e.to_string())
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ProofVerifier for Sp1Verifier {
+    async fn verify(
+        &self,
+        proof: &DecodedProof,
+        system: Option<&SystemParams>,
+    ) -> taralli_client::error::Result<ProofVerdict> {
+        let DecodedProof::Sp1 {
+            vkey,
+            public_values,
+            proof_bytes,
+        } = proof
+        else {
+            return Err(ClientError::WorkerError("Expected an Sp1 proof".into()));
+        };
+
+        let modes = match system {
+            Some(SystemParams::Sp1(params)) => vec![params.config.mode.clone()],
+            _ => vec![Sp1Mode::Groth16, Sp1Mode::Plonk],
+        };
+        let vkey_hash = vkey.to_string();
+        let mut failures = Vec::new();
+        for mode in &modes {
+            match Self::verify_in_mode(mode, proof_bytes, public_values, &vkey_hash) {
+                Ok(()) => return Ok(ProofVerdict::Valid),
+                Err(e) => failures.push(e),
+            }
+        }
+        Ok(ProofVerdict::Invalid(format!(
+            "sp1 proof does not verify: {}",
+            failures.join(", ")
+        )))
+    }
+}
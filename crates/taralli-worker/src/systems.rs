@@ -0,0 +1,77 @@
+//! Proving systems whose workers are compiled in, each one being behind the cargo feature of the
+//! same name along with its prover dependencies.
+
+use std::sync::Arc;
+
+use taralli_client::worker::ComputeWorker;
+use taralli_primitives::intents::ComputeIntent;
+use taralli_primitives::systems::{SystemId, SystemParams};
+
+use crate::error::{Result, WorkerError};
+
+/// Cargo feature the workers of `system_id` are compiled behind
+#[must_use]
+pub fn feature(system_id: SystemId) -> &'static str {
+    match system_id {
+        SystemId::Arkworks => "arkworks",
+        SystemId::Risc0 => "risc0",
+        SystemId::Sp1 => "sp1",
+    }
+}
+
+/// Whether the workers of `system_id` are compiled in
+#[must_use]
+pub fn is_compiled(system_id: SystemId) -> bool {
+    match system_id {
+        SystemId::Arkworks => cfg!(feature = "arkworks"),
+        SystemId::Risc0 => cfg!(feature = "risc0"),
+        SystemId::Sp1 => cfg!(feature = "sp1"),
+    }
+}
+
+/// Systems whose workers are compiled in
+#[must_use]
+pub fn compiled_systems() -> Vec<SystemId> {
+    SystemId::all()
+        .into_iter()
+        .filter(|system_id| is_compiled(*system_id))
+        .collect()
+}
+
+/// Fail with `WorkerError::SystemNotCompiled` unless the workers of `system_id` are compiled in
+pub fn ensure_compiled(system_id: SystemId) -> Result<()> {
+    if is_compiled(system_id) {
+        Ok(())
+    } else {
+        Err(not_compiled(system_id))
+    }
+}
+
+pub(crate) fn not_compiled(system_id: SystemId) -> WorkerError {
+    WorkerError::SystemNotCompiled {
+        system_id,
+        feature: feature(system_id),
+    }
+}
+
+/// In-process worker of `system_id` proving locally with groth16 wrapped proofs, e.g. the worker
+/// factory of `ProviderStreamingConfigFile::to_runtime_config`
+pub fn local_worker<I>(system_id: SystemId) -> Result<Arc<dyn ComputeWorker<I> + Send + Sync>>
+where
+    I: ComputeIntent<System = SystemParams> + Send + Sync + 'static,
+{
+    match system_id {
+        #[cfg(feature = "arkworks")]
+        SystemId::Arkworks => Ok(Arc::new(crate::arkworks::ArkworksWorker::new())),
+        #[cfg(feature = "risc0")]
+        SystemId::Risc0 => Ok(Arc::new(crate::risc0::Risc0Worker::new(
+            crate::risc0::local::Risc0LocalProver::new(risc0_zkvm::ProverOpts::groth16()),
+        ))),
+        #[cfg(feature = "sp1")]
+        SystemId::Sp1 => Ok(Arc::new(crate::sp1::Sp1Worker::new(
+            crate::sp1::local::Sp1LocalProver::new(false, sp1_sdk::SP1ProofMode::Groth16),
+        ))),
+        #[allow(unreachable_patterns)]
+        _ => Err(not_compiled(system_id)),
+    }
+}
@@ -0,0 +1,130 @@
+#![cfg(feature = "arkworks")]
+
+use num_bigint::BigInt;
+use rand::Rng;
+use serde_json::{json, Value};
+use taralli_worker::arkworks::circom_inputs;
+
+const SHA256_INPUT: &str =
+    include_str!("../../../contracts/test-proof-data/groth16/sha/input.json");
+
+#[test]
+/// Ensures the sha256 fixture's array signal is converted bit by bit.
+fn should_convert_sha256_array_input() {
+    let input: Value = serde_json::from_str(SHA256_INPUT).unwrap();
+
+    let inputs = circom_inputs(&input).unwrap();
+
+    let expected: Vec<BigInt> = input["in"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|bit| bit.as_str().unwrap().parse().unwrap())
+        .collect();
+    assert_eq!(inputs.len(), 1);
+    assert_eq!(inputs["in"].len(), 512);
+    assert_eq!(inputs["in"], expected);
+}
+
+#[test]
+/// Ensures hex strings, booleans and nested arrays are flattened in row-major order.
+fn should_flatten_nested_signals() {
+    let input = json!({
+        "a": "0x1f",
+        "b": true,
+        "matrix": [[1, "2"], ["0x3", false]],
+    });
+
+    let inputs = circom_inputs(&input).unwrap();
+
+    assert_eq!(inputs["a"], vec![BigInt::from(31)]);
+    assert_eq!(inputs["b"], vec![BigInt::from(1)]);
+    assert_eq!(
+        inputs["matrix"],
+        vec![
+            BigInt::from(1),
+            BigInt::from(2),
+            BigInt::from(3),
+            BigInt::from(0)
+        ]
+    );
+}
+
+#[test]
+/// Ensures errors name the offending signal element.
+fn should_name_invalid_signal() {
+    let input = json!({ "in": ["1", ["0", "not a number"]] });
+
+    let err = circom_inputs(&input).unwrap_err().to_string();
+
+    assert!(err.contains("`in[1][1]`"), "unexpected error: {err}");
+}
+
+#[test]
+/// Ensures randomly generated valid inputs convert back to the values they were encoded from.
+fn should_round_trip_random_inputs() {
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..100 {
+        let mut input = serde_json::Map::new();
+        let mut expected = Vec::new();
+
+        for signal in 0..rng.gen_range(1..5) {
+            let name = format!("signal{signal}");
+            let rows = rng.gen_range(0..4);
+            let mut values = Vec::new();
+            // scalar signals, flat arrays or arrays nested one level
+            let value = match rows {
+                0 => encode(rng.gen(), &mut rng, &mut values),
+                1 => Value::Array(
+                    (0..rng.gen_range(0..8))
+                        .map(|_| encode(rng.gen(), &mut rng, &mut values))
+                        .collect(),
+                ),
+                _ => Value::Array(
+                    (0..rows)
+                        .map(|_| {
+                            Value::Array(
+                                (0..3)
+                                    .map(|_| encode(rng.gen(), &mut rng, &mut values))
+                                    .collect(),
+                            )
+                        })
+                        .collect(),
+                ),
+            };
+            input.insert(name.clone(), value);
+            expected.push((name, values));
+        }
+
+        let inputs = circom_inputs(&Value::Object(input)).unwrap();
+
+        assert_eq!(inputs.len(), expected.len());
+        for (name, values) in expected {
+            assert_eq!(inputs[&name], values, "signal {name}");
+        }
+    }
+}
+
+/// Encode `value` in one of the accepted representations, recording the expected conversion
+fn encode(value: u64, rng: &mut impl Rng, values: &mut Vec<BigInt>) -> Value {
+    match rng.gen_range(0..4) {
+        0 => {
+            values.push(BigInt::from(value));
+            json!(value.to_string())
+        }
+        1 => {
+            values.push(BigInt::from(value));
+            json!(format!("0x{value:x}"))
+        }
+        2 => {
+            values.push(BigInt::from(value));
+            json!(value)
+        }
+        _ => {
+            let bit = value % 2 == 1;
+            values.push(BigInt::from(u8::from(bit)));
+            json!(bit)
+        }
+    }
+}
@@ -0,0 +1,441 @@
+#![cfg(any(feature = "sp1", feature = "risc0", feature = "arkworks"))]
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde_json::json;
+use taralli_client::api::retry::RetryPolicy;
+use taralli_client::error::ClientError;
+use taralli_client::worker::{ComputeWorker, WorkResult};
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta::ProofRequest;
+use taralli_primitives::abi::universal_porchetta::ProofOfferVerifierDetails;
+use taralli_primitives::alloy::dyn_abi::DynSolValue;
+use taralli_primitives::alloy::primitives::{
+    fixed_bytes, Address, Bytes, FixedBytes, PrimitiveSignature, B256, U256,
+};
+use taralli_primitives::alloy::sol_types::SolValue;
+use taralli_primitives::commitment::{ARKWORKS_PUBLIC_INPUTS_OFFSET, ARKWORKS_PUBLIC_INPUT_LENGTH};
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::systems::arkworks::ArkworksProofParams;
+use taralli_primitives::systems::risc0::Risc0ProofParams;
+use taralli_primitives::systems::sp1::{Sp1Config, Sp1Mode, Sp1ProofParams};
+use taralli_primitives::systems::{System, SystemId, SystemParams};
+use taralli_worker::remote::{
+    JobAccepted, JobStatus, ProveJob, RemoteProof, RemoteWorker, RemoteWorkerConfig,
+};
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+use url::Url;
+
+const AUTH_HEADER: (&str, &str) = ("authorization", "Bearer fleet-key");
+const PROVING_TIME: u32 = 60;
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+type Request = ComputeRequest<SystemParams>;
+
+/// Stub sidecar answering `running_polls` status checks with `Running`, then with `outcome`,
+/// running forever when unset
+#[derive(Default)]
+struct Sidecar {
+    jobs: Vec<ProveJob>,
+    /// submissions answered with a 503 before accepting one
+    submit_failures: usize,
+    running_polls: usize,
+    outcome: Option<JobStatus>,
+    cancelled: Vec<String>,
+}
+
+type SharedSidecar = Arc<Mutex<Sidecar>>;
+
+/// Serve `sidecar` under `/prover`, returning its endpoint
+async fn start_sidecar(sidecar: SharedSidecar) -> Url {
+    let app = Router::new()
+        .route("/prover/jobs", post(submit))
+        .route("/prover/jobs/:job_id", get(status).delete(cancel))
+        .with_state(sidecar);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}/prover", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    endpoint.parse().unwrap()
+}
+
+async fn submit(
+    State(sidecar): State<SharedSidecar>,
+    headers: HeaderMap,
+    Json(job): Json<ProveJob>,
+) -> Response {
+    if headers.get(AUTH_HEADER.0).map(|value| value.as_bytes()) != Some(AUTH_HEADER.1.as_bytes()) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    let mut sidecar = sidecar.lock().unwrap();
+    if sidecar.submit_failures > 0 {
+        sidecar.submit_failures -= 1;
+        return StatusCode::SERVICE_UNAVAILABLE.into_response();
+    }
+    sidecar.jobs.push(job);
+    Json(JobAccepted {
+        job_id: format!("job-{}", sidecar.jobs.len()),
+    })
+    .into_response()
+}
+
+async fn status(State(sidecar): State<SharedSidecar>, Path(_job_id): Path<String>) -> Response {
+    let mut sidecar = sidecar.lock().unwrap();
+    if sidecar.running_polls > 0 {
+        sidecar.running_polls -= 1;
+        return Json(JobStatus::Running).into_response();
+    }
+    Json(sidecar.outcome.clone().unwrap_or(JobStatus::Running)).into_response()
+}
+
+async fn cancel(State(sidecar): State<SharedSidecar>, Path(job_id): Path<String>) -> StatusCode {
+    sidecar.lock().unwrap().cancelled.push(job_id);
+    StatusCode::NO_CONTENT
+}
+
+#[cfg(feature = "sp1")]
+fn remote_proof() -> RemoteProof {
+    RemoteProof {
+        proof: Bytes::from(vec![9u8; 260]),
+        public_values: Bytes::from(vec![1u8, 2, 3, 4]),
+        verifying_key: Some(B256::repeat_byte(0x22)),
+    }
+}
+
+/// Sp1 request whose inputs are the 4 bytes of public values of `remote_proof`
+#[cfg(feature = "sp1")]
+fn request_fixture() -> Request {
+    // three head words and the length of the public values precede them
+    request(
+        SystemParams::Sp1(Sp1ProofParams {
+            config: Sp1Config {
+                mode: Sp1Mode::Groth16,
+            },
+            elf: vec![1, 2, 3],
+            inputs: vec![4; 32],
+        }),
+        128,
+        4,
+    )
+}
+
+/// Request proving `system`, whose inputs lie at `inputs_offset..inputs_offset + inputs_length`
+/// of its opaque submission
+fn request(system: SystemParams, inputs_offset: usize, inputs_length: usize) -> Request {
+    let verifier_details = ProofOfferVerifierDetails {
+        verifier: Address::ZERO,
+        selector: fixed_bytes!("deadbeef"),
+        isShaCommitment: true,
+        inputsOffset: U256::from(inputs_offset),
+        inputsLength: U256::from(inputs_length),
+    };
+    ComputeRequest {
+        system_id: system.system_id(),
+        system,
+        proof_request: ProofRequest {
+            signer: Address::ZERO,
+            market: Address::ZERO,
+            nonce: U256::from(1),
+            rewardToken: Address::ZERO,
+            maxRewardAmount: U256::from(200),
+            minRewardAmount: U256::from(100),
+            minimumStake: 0,
+            startAuctionTimestamp: 100,
+            endAuctionTimestamp: 200,
+            provingTime: PROVING_TIME,
+            inputsCommitment: B256::repeat_byte(4),
+            extraData: verifier_details.abi_encode().into(),
+        },
+        signature: PrimitiveSignature::new(U256::from(1), U256::from(2), false),
+    }
+}
+
+fn worker(endpoint: Url) -> RemoteWorker {
+    RemoteWorker::new(
+        RemoteWorkerConfig::new(endpoint)
+            .with_auth_header(AUTH_HEADER.0, AUTH_HEADER.1)
+            .with_poll_interval(POLL_INTERVAL)
+            .with_retry_policy(RetryPolicy {
+                max_retries: 3,
+                initial_backoff: Duration::from_millis(10),
+                max_backoff: Duration::from_millis(50),
+                deadline: Duration::from_secs(5),
+            }),
+    )
+}
+
+#[cfg(feature = "sp1")]
+#[tokio::test]
+/// Ensures the params are handed to the sidecar, its job polled until it succeeds and the proof
+/// formatted as the sp1 worker formats it, a submission failing transiently being retried.
+async fn should_prove_through_sidecar() {
+    let sidecar = Arc::new(Mutex::new(Sidecar {
+        submit_failures: 1,
+        running_polls: 2,
+        outcome: Some(JobStatus::Succeeded(remote_proof())),
+        ..Default::default()
+    }));
+    let worker = worker(start_sidecar(sidecar.clone()).await);
+
+    let result = worker.execute(&request_fixture()).await.unwrap();
+
+    let proof = remote_proof();
+    let opaque_submission = DynSolValue::Tuple(vec![
+        DynSolValue::FixedBytes(proof.verifying_key.unwrap(), 32),
+        DynSolValue::Bytes(proof.public_values.to_vec()),
+        DynSolValue::Bytes(proof.proof.to_vec()),
+    ])
+    .abi_encode();
+    assert_eq!(
+        result,
+        WorkResult {
+            opaque_submission: opaque_submission.into(),
+            partial_commitment: FixedBytes::ZERO,
+        }
+    );
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let sidecar = sidecar.lock().unwrap();
+    assert_eq!(sidecar.jobs.len(), 1);
+    let job = &sidecar.jobs[0];
+    assert_eq!(job.system_id, SystemId::Sp1);
+    assert_eq!(job.params["elf"], json!([1, 2, 3]));
+    assert_eq!(job.params["config"]["mode"], json!("Groth16"));
+    assert_eq!(job.timeout_seconds, u64::from(PROVING_TIME));
+    assert_eq!(sidecar.running_polls, 0);
+    assert!(sidecar.cancelled.is_empty());
+}
+
+#[cfg(feature = "sp1")]
+#[tokio::test]
+/// Ensures a job still running past the timeout, or whose execution is cancelled, is given up on
+/// and cancelled on the sidecar.
+async fn should_cancel_job_given_up_on() {
+    let sidecar = Arc::new(Mutex::new(Sidecar::default()));
+    let endpoint = start_sidecar(sidecar.clone()).await;
+
+    let timing_out = RemoteWorker::new(
+        worker(endpoint.clone())
+            .config()
+            .clone()
+            .with_timeout(Duration::from_millis(100)),
+    );
+    let err = timing_out.execute(&request_fixture()).await.unwrap_err();
+    assert!(matches!(err, ClientError::ProofTimeout(_)), "{err}");
+
+    let cancel = CancellationToken::new();
+    let cancelling = cancel.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        cancelling.cancel();
+    });
+    let err = worker(endpoint)
+        .execute_cancellable(&request_fixture(), Duration::from_secs(60), cancel)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ClientError::WorkerError(_)), "{err}");
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let sidecar = sidecar.lock().unwrap();
+    assert_eq!(sidecar.jobs.len(), 2);
+    assert_eq!(sidecar.cancelled, vec!["job-1", "job-2"]);
+}
+
+#[cfg(feature = "sp1")]
+#[tokio::test]
+/// Ensures jobs the sidecar fails, refuses or answers with an incomplete proof fail the
+/// execution.
+async fn should_fail_jobs_not_proved() {
+    let sidecar = Arc::new(Mutex::new(Sidecar {
+        outcome: Some(JobStatus::Failed {
+            error: "out of gpu memory".to_string(),
+        }),
+        ..Default::default()
+    }));
+    let endpoint = start_sidecar(sidecar.clone()).await;
+
+    let err = worker(endpoint.clone())
+        .execute(&request_fixture())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, ClientError::WorkerError(msg) if msg.contains("out of gpu memory")),
+        "{err}"
+    );
+
+    // without the auth header
+    let unauthenticated = RemoteWorker::new(
+        RemoteWorkerConfig::new(endpoint.clone()).with_poll_interval(POLL_INTERVAL),
+    );
+    let err = unauthenticated
+        .execute(&request_fixture())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, ClientError::WorkerError(msg) if msg.contains("401")),
+        "{err}"
+    );
+
+    // sp1 proofs can't be formatted without their verifying key
+    sidecar.lock().unwrap().outcome = Some(JobStatus::Succeeded(RemoteProof {
+        verifying_key: None,
+        ..remote_proof()
+    }));
+    let err = worker(endpoint)
+        .execute(&request_fixture())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, ClientError::WorkerError(msg) if msg.contains("verifying key")),
+        "{err}"
+    );
+}
+
+#[cfg(feature = "sp1")]
+#[tokio::test]
+/// Ensures the sidecar is asked to prove within the budget left of the proving window, or the
+/// configured timeout when it is shorter.
+async fn should_ask_sidecar_to_prove_within_budget() {
+    let sidecar = Arc::new(Mutex::new(Sidecar {
+        outcome: Some(JobStatus::Succeeded(remote_proof())),
+        ..Default::default()
+    }));
+    let endpoint = start_sidecar(sidecar.clone()).await;
+
+    worker(endpoint.clone())
+        .execute_cancellable(
+            &request_fixture(),
+            Duration::from_secs(20),
+            CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+    let bounded = RemoteWorker::new(
+        worker(endpoint)
+            .config()
+            .clone()
+            .with_timeout(Duration::from_secs(10)),
+    );
+    bounded
+        .execute_cancellable(
+            &request_fixture(),
+            Duration::from_secs(20),
+            CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    let sidecar = sidecar.lock().unwrap();
+    let timeouts: Vec<u64> = sidecar.jobs.iter().map(|job| job.timeout_seconds).collect();
+    assert_eq!(timeouts, vec![20, 10]);
+}
+
+#[cfg(feature = "risc0")]
+#[tokio::test]
+/// Ensures a risc0 proof is formatted as the risc0 worker formats it: the seal along with the
+/// image id of the elf and the digest of the journal, which the inputs have to point at.
+async fn should_format_risc0_proof_from_sidecar() {
+    use taralli_worker::risc0::{image_id_for_elf, journal_digest};
+
+    let elf = std::fs::read(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../../contracts/test-proof-data/risc0/is-even"),
+    )
+    .expect("Couldn't read elf");
+    let proof = RemoteProof {
+        proof: Bytes::from(vec![7u8; 256]),
+        public_values: Bytes::from(vec![1u8, 2, 3, 4]),
+        verifying_key: None,
+    };
+    let sidecar = Arc::new(Mutex::new(Sidecar {
+        outcome: Some(JobStatus::Succeeded(proof.clone())),
+        ..Default::default()
+    }));
+    let worker = worker(start_sidecar(sidecar).await);
+    let system = SystemParams::Risc0(Risc0ProofParams {
+        elf: elf.clone(),
+        inputs: vec![4; 32],
+    });
+
+    // the image id and journal digest follow the offset of the seal
+    let result = worker
+        .execute(&request(system.clone(), 32, 64))
+        .await
+        .unwrap();
+    let opaque_submission = DynSolValue::Tuple(vec![
+        DynSolValue::Bytes(proof.proof.to_vec()),
+        DynSolValue::FixedBytes(image_id_for_elf(&elf).unwrap(), 32),
+        DynSolValue::FixedBytes(journal_digest(&proof.public_values), 32),
+    ])
+    .abi_encode();
+    assert_eq!(result.opaque_submission, Bytes::from(opaque_submission));
+
+    // the seal's offset is no public input
+    let err = worker.execute(&request(system, 0, 32)).await.unwrap_err();
+    assert!(
+        matches!(&err, ClientError::WorkerError(msg) if msg.contains("verifier details")),
+        "{err}"
+    );
+}
+
+#[cfg(feature = "arkworks")]
+#[tokio::test]
+/// Ensures an arkworks proof given as the 8 words of its points is formatted as the arkworks
+/// worker formats it, its public signals at the layout requesters point their inputs at, and that
+/// proofs of any other size are refused.
+async fn should_format_arkworks_proof_from_sidecar() {
+    let word = |value: u64| U256::from(value).to_be_bytes::<32>().to_vec();
+    let proof = RemoteProof {
+        proof: Bytes::from((1..=8).flat_map(word).collect::<Vec<u8>>()),
+        public_values: Bytes::from(word(33)),
+        verifying_key: None,
+    };
+    let sidecar = Arc::new(Mutex::new(Sidecar {
+        outcome: Some(JobStatus::Succeeded(proof.clone())),
+        ..Default::default()
+    }));
+    let worker = worker(start_sidecar(sidecar.clone()).await);
+    let request = request(
+        SystemParams::Arkworks(ArkworksProofParams {
+            r1cs: vec![1, 2, 3],
+            wasm: vec![4, 5, 6],
+            inputs: json!({ "a": "3", "b": "11" }),
+        }),
+        ARKWORKS_PUBLIC_INPUTS_OFFSET,
+        ARKWORKS_PUBLIC_INPUT_LENGTH,
+    );
+
+    let result = worker.execute(&request).await.unwrap();
+    let words = |values: &[u64]| {
+        DynSolValue::Array(
+            values
+                .iter()
+                .map(|value| DynSolValue::Uint(U256::from(*value), 256))
+                .collect(),
+        )
+    };
+    let opaque_submission = DynSolValue::Tuple(vec![
+        words(&[1, 2]),
+        DynSolValue::Array(vec![words(&[3, 4]), words(&[5, 6])]),
+        words(&[7, 8]),
+        words(&[33]),
+    ])
+    .abi_encode();
+    assert_eq!(result.opaque_submission, Bytes::from(opaque_submission));
+
+    sidecar.lock().unwrap().outcome = Some(JobStatus::Succeeded(RemoteProof {
+        proof: proof.proof.slice(..7 * 32),
+        ..proof
+    }));
+    let err = worker.execute(&request).await.unwrap_err();
+    assert!(
+        matches!(&err, ClientError::WorkerError(msg) if msg.contains("8 words")),
+        "{err}"
+    );
+}
@@ -0,0 +1,117 @@
+#![cfg(feature = "risc0")]
+
+use risc0_zkvm::sha::Digest;
+use risc0_zkvm::{Groth16Receipt, InnerReceipt, MaybePruned, Receipt};
+use taralli_client::tracker::payload::{
+    decode_offer_resolve_calldata, decode_request_resolve_calldata, DecodedProof,
+};
+use taralli_primitives::abi::{
+    universal_bombetta::UniversalBombetta, universal_porchetta::UniversalPorchetta,
+};
+use taralli_primitives::alloy::dyn_abi::DynSolValue;
+use taralli_primitives::alloy::primitives::{Bytes, FixedBytes, B256};
+use taralli_primitives::alloy::sol_types::SolCall;
+use taralli_primitives::systems::SystemId;
+use taralli_worker::risc0::{journal_digest, Risc0ProofFormatter};
+
+struct FixtureFormatter;
+
+impl Risc0ProofFormatter for FixtureFormatter {}
+
+fn risc0_receipt_fixture(seal: Vec<u8>, journal: Vec<u8>) -> Receipt {
+    let inner = InnerReceipt::Groth16(Groth16Receipt::new(
+        seal,
+        MaybePruned::Pruned(Digest::ZERO),
+        Digest::ZERO,
+    ));
+    Receipt::new(inner, journal)
+}
+
+#[test]
+/// Ensures a risc0 proof formatted by the worker is recovered from bombetta resolve calldata.
+fn should_decode_risc0_request_resolution() {
+    let seal = vec![7u8; 256];
+    let journal = vec![3u8; 32];
+    let image_id = FixedBytes::<32>::repeat_byte(0x11);
+    let request_id = B256::repeat_byte(0xaa);
+    let partial_commitment = B256::repeat_byte(0xbb);
+
+    let receipt = risc0_receipt_fixture(seal.clone(), journal.clone());
+    let opaque_submission = FixtureFormatter::format_opaque_submission(&receipt, image_id).unwrap();
+
+    // calldata of the fake resolve transaction
+    let calldata = UniversalBombetta::resolveCall {
+        requestId: request_id,
+        opaqueSubmission: opaque_submission.clone(),
+        submittedPartialCommitment: partial_commitment,
+    }
+    .abi_encode();
+
+    let payload = decode_request_resolve_calldata(&calldata, Some(SystemId::Risc0)).unwrap();
+
+    assert_eq!(payload.intent_id, request_id);
+    assert_eq!(payload.opaque_submission, opaque_submission);
+    assert_eq!(
+        payload.submitted_partial_commitment,
+        Some(partial_commitment)
+    );
+    assert_eq!(
+        payload.proof,
+        Some(DecodedProof::Risc0 {
+            seal: Bytes::from(seal),
+            image_id,
+            journal_digest: journal_digest(&journal),
+        })
+    );
+}
+
+#[test]
+/// Ensures an sp1 submission laid out as the sp1 worker formats it is recovered from
+/// porchetta resolve calldata.
+fn should_decode_sp1_offer_resolution() {
+    let vkey = B256::repeat_byte(0x22);
+    let public_values = vec![1u8, 2, 3, 4];
+    let proof_bytes = vec![9u8; 260];
+    let offer_id = B256::repeat_byte(0xcc);
+
+    // (bytes32 vkey, bytes publicValues, bytes proofBytes), see `Sp1ProofFormatter`
+    let opaque_submission = Bytes::from(
+        DynSolValue::Tuple(vec![
+            DynSolValue::FixedBytes(vkey, 32),
+            DynSolValue::Bytes(public_values.clone()),
+            DynSolValue::Bytes(proof_bytes.clone()),
+        ])
+        .abi_encode(),
+    );
+
+    // calldata of the fake resolve transaction
+    let calldata = UniversalPorchetta::resolveCall {
+        offerId: offer_id,
+        opaqueSubmission: opaque_submission.clone(),
+    }
+    .abi_encode();
+
+    let payload = decode_offer_resolve_calldata(&calldata, Some(SystemId::Sp1)).unwrap();
+
+    assert_eq!(payload.intent_id, offer_id);
+    assert_eq!(payload.opaque_submission, opaque_submission);
+    assert_eq!(payload.submitted_partial_commitment, None);
+    assert_eq!(
+        payload.proof,
+        Some(DecodedProof::Sp1 {
+            vkey,
+            public_values: Bytes::from(public_values),
+            proof_bytes: Bytes::from(proof_bytes),
+        })
+    );
+}
+
+#[test]
+/// Ensures an opaque submission of one system is not decoded as another.
+fn should_fail_decoding_mismatched_system() {
+    let receipt = risc0_receipt_fixture(vec![7u8; 256], vec![3u8; 32]);
+    let opaque_submission =
+        FixtureFormatter::format_opaque_submission(&receipt, B256::repeat_byte(0x11)).unwrap();
+
+    assert!(DecodedProof::decode(SystemId::Sp1, &opaque_submission).is_err());
+}
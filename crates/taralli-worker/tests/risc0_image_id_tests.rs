@@ -0,0 +1,89 @@
+#![cfg(feature = "risc0")]
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use risc0_zkvm::sha::Digest;
+use risc0_zkvm::{Groth16Receipt, InnerReceipt, MaybePruned, Receipt};
+use taralli_primitives::alloy::dyn_abi::{DynSolType, DynSolValue};
+use taralli_primitives::alloy::primitives::{Bytes, B256};
+use taralli_worker::risc0::{image_id_for_elf, journal_digest, Risc0ProofFormatter};
+
+struct FixtureFormatter;
+
+impl Risc0ProofFormatter for FixtureFormatter {}
+
+fn risc0_test_data_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../contracts/test-proof-data/risc0")
+}
+
+fn even_number_proof_field(field: &str) -> String {
+    let proof_data: serde_json::Value = serde_json::from_slice(
+        &std::fs::read(risc0_test_data_dir().join("even-number-proof.json"))
+            .expect("Couldn't read proof data"),
+    )
+    .expect("Couldn't parse proof data");
+    proof_data[field]
+        .as_str()
+        .expect("Missing proof data field")
+        .to_string()
+}
+
+#[test]
+/// Ensures the image id of the is-even guest program is computed from the ELF rather
+/// than sliced out of it, and that the cached value is returned on later calls.
+fn should_compute_canonical_is_even_image_id() {
+    let elf = std::fs::read(risc0_test_data_dir().join("is-even")).expect("Couldn't read elf");
+    let expected_image_id = B256::from_str(&even_number_proof_field("image_id")).unwrap();
+
+    let image_id = image_id_for_elf(&elf).unwrap();
+    assert_eq!(image_id, expected_image_id);
+    assert_ne!(image_id, B256::from_slice(&elf[0..32]));
+    assert_eq!(image_id_for_elf(&elf).unwrap(), expected_image_id);
+}
+
+#[test]
+/// Ensures the formatted opaque submission of the is-even fixture matches the arguments
+/// of the risc0 verifier: the canonical image id and the sha256 digest of the journal.
+fn should_format_is_even_submission_for_verifier() {
+    let elf = std::fs::read(risc0_test_data_dir().join("is-even")).expect("Couldn't read elf");
+    let journal = Bytes::from_str(&even_number_proof_field("journal")).unwrap();
+    let expected_image_id = B256::from_str(&even_number_proof_field("image_id")).unwrap();
+    let expected_journal_digest =
+        B256::from_str(&even_number_proof_field("journal_digest")).unwrap();
+    assert_eq!(journal_digest(&journal), expected_journal_digest);
+
+    let receipt = Receipt::new(
+        InnerReceipt::Groth16(Groth16Receipt::new(
+            vec![7u8; 256],
+            MaybePruned::Pruned(Digest::ZERO),
+            Digest::ZERO,
+        )),
+        journal.to_vec(),
+    );
+    let opaque_submission =
+        FixtureFormatter::format_opaque_submission(&receipt, image_id_for_elf(&elf).unwrap())
+            .unwrap();
+
+    // (bytes seal, bytes32 imageId, bytes32 journalDigest)
+    let layout = DynSolType::Tuple(vec![
+        DynSolType::Bytes,
+        DynSolType::FixedBytes(32),
+        DynSolType::FixedBytes(32),
+    ]);
+    let decoded = layout.abi_decode(&opaque_submission).unwrap();
+    let DynSolValue::Tuple(fields) = decoded else {
+        panic!("opaque submission is not a tuple");
+    };
+    assert_eq!(fields[1], DynSolValue::FixedBytes(expected_image_id, 32));
+    assert_eq!(
+        fields[2],
+        DynSolValue::FixedBytes(expected_journal_digest, 32)
+    );
+    // the inputs commitment covers the image id and journal digest words (offset 32, length 64)
+    assert_eq!(&opaque_submission[32..64], expected_image_id.as_slice());
+    assert_eq!(
+        &opaque_submission[64..96],
+        expected_journal_digest.as_slice()
+    );
+}
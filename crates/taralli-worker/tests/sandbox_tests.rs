@@ -0,0 +1,159 @@
+#![cfg(feature = "arkworks")]
+
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use serde_json::json;
+use taralli_client::error::Result as ClientResult;
+use taralli_client::worker::{ComputeWorker, WorkResult};
+use taralli_primitives::abi::universal_bombetta::UniversalBombetta::ProofRequest;
+use taralli_primitives::alloy::primitives::{
+    Address, Bytes, FixedBytes, PrimitiveSignature, B256, U256,
+};
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::systems::arkworks::ArkworksProofParams;
+use taralli_primitives::systems::{SystemId, SystemParams};
+use taralli_worker::sandbox::{SandboxConfig, Sandboxed};
+
+const RUNNER: &str = env!("CARGO_BIN_EXE_taralli-sandbox-runner");
+const MAX_MEMORY: u64 = 256 * 1024 * 1024;
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Circuit wasm whose start function, run as the witness calculator instantiates it, grows its
+/// memory a MiB at a time until denied, then spins forever:
+///
+/// (module (memory 1) (start 0)
+///   (func (loop (br_if 0 (i32.ne (memory.grow (i32.const 16)) (i32.const -1))))
+///         (loop (br 0))))
+const MEMORY_HOG_WASM: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // magic, version
+    0x01, 0x04, 0x01, 0x60, 0x00, 0x00, // type section: () -> ()
+    0x03, 0x02, 0x01, 0x00, // function section
+    0x05, 0x03, 0x01, 0x00, 0x01, // memory section: 1 page, no max
+    0x08, 0x01, 0x00, // start section
+    0x0a, 0x15, 0x01, 0x13, 0x00, // code section, body without locals
+    0x03, 0x40, 0x41, 0x10, 0x40, 0x00, 0x41, 0x7f, 0x47, 0x0d, 0x00, 0x0b, // grow loop
+    0x03, 0x40, 0x0c, 0x00, 0x0b, // spin loop
+    0x0b,
+];
+
+/// Circuit wasm growing its memory a MiB at a time until denied, then trapping when it was
+/// denied before reaching 8192 pages (512 MiB):
+///
+/// (module (memory 1) (start 0)
+///   (func (loop (br_if 0 (i32.ne (memory.grow (i32.const 16)) (i32.const -1))))
+///         (if (i32.lt_u (memory.size) (i32.const 8192)) (then unreachable))))
+const MEMORY_PROBE_WASM: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // magic, version
+    0x01, 0x04, 0x01, 0x60, 0x00, 0x00, // type section: () -> ()
+    0x03, 0x02, 0x01, 0x00, // function section
+    0x05, 0x03, 0x01, 0x00, 0x01, // memory section: 1 page, no max
+    0x08, 0x01, 0x00, // start section
+    0x0a, 0x1b, 0x01, 0x19, 0x00, // code section, body without locals
+    0x03, 0x40, 0x41, 0x10, 0x40, 0x00, 0x41, 0x7f, 0x47, 0x0d, 0x00, 0x0b, // grow loop
+    0x3f, 0x00, 0x41, 0x80, 0xc0, 0x00, 0x49, // memory.size < 8192
+    0x04, 0x40, 0x00, 0x0b, // if, unreachable
+    0x0b,
+];
+
+fn arkworks_request(wasm: Vec<u8>) -> ComputeRequest<SystemParams> {
+    ComputeRequest {
+        system_id: SystemId::Arkworks,
+        system: SystemParams::Arkworks(ArkworksProofParams {
+            r1cs: Vec::new(),
+            wasm,
+            inputs: json!({}),
+        }),
+        proof_request: ProofRequest {
+            signer: Address::ZERO,
+            market: Address::ZERO,
+            nonce: U256::ZERO,
+            rewardToken: Address::ZERO,
+            maxRewardAmount: U256::from(100),
+            minRewardAmount: U256::from(10),
+            minimumStake: 0,
+            startAuctionTimestamp: 0,
+            endAuctionTimestamp: 100,
+            provingTime: 60,
+            inputsCommitment: B256::ZERO,
+            extraData: Bytes::new(),
+        },
+        signature: PrimitiveSignature::new(U256::from(1), U256::from(2), false),
+    }
+}
+
+fn sandboxed() -> Sandboxed {
+    Sandboxed::new(
+        SandboxConfig::new(RUNNER)
+            .with_max_memory(MAX_MEMORY)
+            .with_timeout(TIMEOUT),
+    )
+}
+
+/// In-process worker proving in a second
+struct MockWorker;
+
+#[async_trait]
+impl ComputeWorker<ComputeRequest<SystemParams>> for MockWorker {
+    async fn execute(&self, _intent: &ComputeRequest<SystemParams>) -> ClientResult<WorkResult> {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        Ok(WorkResult {
+            opaque_submission: Bytes::from_static(b"proof"),
+            partial_commitment: FixedBytes::ZERO,
+        })
+    }
+}
+
+#[tokio::test]
+/// Ensures a circuit hogging memory is killed in its runner while a job running alongside it in
+/// the provider's process completes.
+async fn should_kill_memory_hog_without_affecting_other_jobs() {
+    let hog = arkworks_request(MEMORY_HOG_WASM.to_vec());
+    let mock = arkworks_request(Vec::new());
+
+    let started = Instant::now();
+    let (hog_result, mock_result) = tokio::join!(
+        async {
+            let result = sandboxed().execute(&hog).await;
+            (result, started.elapsed())
+        },
+        MockWorker.execute(&mock),
+    );
+
+    let (hog_result, hog_elapsed) = hog_result;
+    assert!(hog_result.is_err(), "memory hog completed");
+    assert!(
+        hog_elapsed < TIMEOUT + Duration::from_secs(5),
+        "{hog_elapsed:?}"
+    );
+    assert_eq!(
+        mock_result.unwrap().opaque_submission,
+        Bytes::from_static(b"proof")
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+/// Ensures the runner is denied memory past its limit: the circuit traps only when its memory
+/// stopped growing short of 512 MiB, it would reach the 4 GiB of a wasm32 memory otherwise.
+async fn should_deny_memory_past_its_limit() {
+    let err = sandboxed()
+        .execute(&arkworks_request(MEMORY_PROBE_WASM.to_vec()))
+        .await
+        .unwrap_err();
+    let err = err.to_string();
+    assert!(err.contains("unreachable"), "{err}");
+    assert!(!err.contains("killed"), "{err}");
+}
+
+#[tokio::test]
+/// Ensures a job failing in the runner reports the worker's error rather than a killed runner.
+async fn should_report_worker_failure_from_runner() {
+    let err = sandboxed()
+        .execute(&arkworks_request(b"not a wasm module".to_vec()))
+        .await
+        .unwrap_err();
+    let err = err.to_string();
+    assert!(!err.contains("killed"), "{err}");
+    assert!(!err.contains("runner"), "{err}");
+}
@@ -0,0 +1,93 @@
+#![cfg(feature = "sp1")]
+
+use std::cell::Cell;
+use std::time::Duration;
+
+use taralli_worker::error::WorkerError;
+use taralli_worker::sp1::remote::{classify_network_error, wait_for_proof, RemoteProofStatus};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Mocked network answering `Pending` until its `pending_polls` are used up, then `last`
+fn mock_network(
+    pending_polls: usize,
+    last: fn() -> RemoteProofStatus<&'static str>,
+) -> (
+    Cell<usize>,
+    impl Fn(&Cell<usize>) -> RemoteProofStatus<&'static str>,
+) {
+    (Cell::new(0), move |polls: &Cell<usize>| {
+        polls.set(polls.get() + 1);
+        if polls.get() <= pending_polls {
+            RemoteProofStatus::Pending
+        } else {
+            last()
+        }
+    })
+}
+
+#[tokio::test]
+/// Ensures the status is polled until the network fulfills the request.
+async fn should_poll_until_fulfilled() {
+    let (polls, network) = mock_network(3, || RemoteProofStatus::Fulfilled("proof"));
+
+    let proof = wait_for_proof(
+        || async { Ok(network(&polls)) },
+        POLL_INTERVAL,
+        Duration::from_secs(5),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(proof, "proof");
+    assert_eq!(polls.get(), 4);
+}
+
+#[tokio::test]
+/// Ensures a request still pending at the deadline fails with a timeout.
+async fn should_time_out_pending_request() {
+    let (polls, network) = mock_network(usize::MAX, || RemoteProofStatus::Pending);
+
+    let err = wait_for_proof(
+        || async { Ok(network(&polls)) },
+        POLL_INTERVAL,
+        Duration::from_millis(100),
+    )
+    .await
+    .unwrap_err();
+
+    assert!(matches!(err, WorkerError::ProofTimeout(_)), "{err}");
+    assert!(polls.get() > 1);
+}
+
+#[tokio::test]
+/// Ensures an unfulfillable request stops polling with a proof failure.
+async fn should_fail_unfulfillable_request() {
+    let (polls, network) = mock_network(1, || {
+        RemoteProofStatus::Unfulfillable("program is unexecutable".into())
+    });
+
+    let err = wait_for_proof(
+        || async { Ok(network(&polls)) },
+        POLL_INTERVAL,
+        Duration::from_secs(5),
+    )
+    .await
+    .unwrap_err();
+
+    assert!(matches!(err, WorkerError::ExecutionFailed(_)), "{err}");
+    assert_eq!(polls.get(), 2);
+}
+
+#[test]
+/// Ensures quota exhaustion is reported apart from proof failures.
+fn should_classify_quota_errors() {
+    assert!(matches!(
+        classify_network_error("status: ResourceExhausted, message: \"Insufficient balance\""),
+        WorkerError::QuotaExhausted(_)
+    ));
+    assert!(matches!(
+        classify_network_error("program execution failed"),
+        WorkerError::ExecutionFailed(_)
+    ));
+}
@@ -0,0 +1,207 @@
+#![cfg(all(feature = "risc0", feature = "sp1"))]
+
+use risc0_zkvm::sha::Digest;
+use risc0_zkvm::{Groth16Receipt, InnerReceipt, MaybePruned, Receipt};
+use taralli_client::error::ClientError;
+use taralli_primitives::abi::universal_bombetta::{
+    ProofRequestVerifierDetails, UniversalBombetta::ProofRequest,
+};
+use taralli_primitives::abi::universal_porchetta::ProofOfferVerifierDetails;
+use taralli_primitives::alloy::dyn_abi::DynSolValue;
+use taralli_primitives::alloy::primitives::{fixed_bytes, Address, FixedBytes, B256, U256};
+use taralli_primitives::alloy::sol_types::SolValue;
+use taralli_primitives::commitment::CommitmentHasher;
+use taralli_primitives::validation::request::validate_request_submission;
+use taralli_worker::error::WorkerError;
+use taralli_worker::risc0::Risc0ProofFormatter;
+use taralli_worker::sp1::Sp1ProofFormatter;
+
+struct FixtureFormatter;
+
+impl Risc0ProofFormatter for FixtureFormatter {}
+impl Sp1ProofFormatter for FixtureFormatter {}
+
+fn risc0_receipt_fixture(journal: Vec<u8>) -> Receipt {
+    let inner = InnerReceipt::Groth16(Groth16Receipt::new(
+        vec![7u8; 256],
+        MaybePruned::Pruned(Digest::ZERO),
+        Digest::ZERO,
+    ));
+    Receipt::new(inner, journal)
+}
+
+/// Request committing to the image id and journal digest of `opaque_submission`, checking a
+/// partial commitment result over them led by `predetermined_partial_commitment`
+fn partial_commitment_request(
+    opaque_submission: &[u8],
+    predetermined_partial_commitment: B256,
+) -> ProofRequest {
+    let verifier_details = ProofRequestVerifierDetails {
+        verifier: Address::ZERO,
+        selector: fixed_bytes!("deadbeef"),
+        isShaCommitment: true,
+        inputsOffset: U256::from(32),
+        inputsLength: U256::from(64),
+        hasPartialCommitmentResultCheck: true,
+        submittedPartialCommitmentResultOffset: U256::from(32),
+        submittedPartialCommitmentResultLength: U256::from(64),
+        predeterminedPartialCommitment: predetermined_partial_commitment,
+    };
+    ProofRequest {
+        signer: Address::ZERO,
+        market: Address::ZERO,
+        nonce: U256::ZERO,
+        rewardToken: Address::ZERO,
+        maxRewardAmount: U256::from(1000),
+        minRewardAmount: U256::from(100),
+        minimumStake: 1,
+        startAuctionTimestamp: 0,
+        endAuctionTimestamp: 60,
+        provingTime: 30,
+        inputsCommitment: CommitmentHasher::Sha256.hash(&opaque_submission[32..96]),
+        extraData: verifier_details.abi_encode().into(),
+    }
+}
+
+/// Verifier details extracting `length` bytes of inputs at `offset`
+fn extra_data(offset: usize, length: usize) -> Vec<u8> {
+    ProofOfferVerifierDetails {
+        verifier: Address::ZERO,
+        selector: fixed_bytes!("deadbeef"),
+        isShaCommitment: true,
+        inputsOffset: U256::from(offset),
+        inputsLength: U256::from(length),
+    }
+    .abi_encode()
+}
+
+#[test]
+/// Ensures the inputs of a risc0 intent are accepted at the image id and journal digest, and
+/// refused with a worker error anywhere else, before the submission is resolved.
+fn should_check_risc0_inputs_layout() {
+    let journal = vec![3u8; 32];
+    let image_id = FixedBytes::<32>::repeat_byte(0x11);
+    let receipt = risc0_receipt_fixture(journal.clone());
+    let opaque_submission = FixtureFormatter::format_opaque_submission(&receipt, image_id).unwrap();
+
+    // `(bytes seal, bytes32 imageId, bytes32 journalDigest)`
+    <FixtureFormatter as Risc0ProofFormatter>::check_submission_layout(
+        &opaque_submission,
+        &extra_data(32, 64),
+        image_id,
+        &journal,
+    )
+    .unwrap();
+    <FixtureFormatter as Risc0ProofFormatter>::check_submission_layout(
+        &opaque_submission,
+        &extra_data(64, 32),
+        image_id,
+        &journal,
+    )
+    .unwrap();
+
+    // the offset of the seal precedes the image id
+    let err = <FixtureFormatter as Risc0ProofFormatter>::check_submission_layout(
+        &opaque_submission,
+        &extra_data(0, 64),
+        image_id,
+        &journal,
+    )
+    .unwrap_err();
+    assert!(matches!(err, WorkerError::SubmissionLayout(_)), "{err}");
+    assert!(err.to_string().contains("offset 0 of length 64"), "{err}");
+    let err = ClientError::from(err);
+    assert_eq!(err.error_code(), "worker");
+
+    // a submission formatted with another image id isn't the proof's
+    let err = <FixtureFormatter as Risc0ProofFormatter>::check_submission_layout(
+        &opaque_submission,
+        &extra_data(32, 64),
+        B256::repeat_byte(0x12),
+        &journal,
+    )
+    .unwrap_err();
+    assert!(matches!(err, WorkerError::SubmissionLayout(_)), "{err}");
+}
+
+#[test]
+/// Ensures a risc0 request checking a partial commitment result over the image id and journal
+/// digest is resolved with the journal digest, passing the market's checks, and that a check
+/// whose predetermined partial commitment isn't the image id is refused before resolving.
+fn should_compute_risc0_partial_commitment() {
+    let image_id = FixedBytes::<32>::repeat_byte(0x11);
+    let receipt = risc0_receipt_fixture(vec![3u8; 32]);
+    let opaque_submission = FixtureFormatter::format_opaque_submission(&receipt, image_id).unwrap();
+    let journal_digest = B256::from_slice(&opaque_submission[64..96]);
+
+    let request = partial_commitment_request(&opaque_submission, image_id);
+    let partial_commitment = <FixtureFormatter as Risc0ProofFormatter>::compute_partial_commitment(
+        &request,
+        &opaque_submission,
+    )
+    .unwrap();
+    assert_eq!(partial_commitment, journal_digest);
+    validate_request_submission(&request, &opaque_submission, partial_commitment).unwrap();
+    // the zero partial commitment workers used to submit fails the market's check
+    validate_request_submission(&request, &opaque_submission, B256::ZERO).unwrap_err();
+
+    let request = partial_commitment_request(&opaque_submission, B256::repeat_byte(0x12));
+    let err = <FixtureFormatter as Risc0ProofFormatter>::compute_partial_commitment(
+        &request,
+        &opaque_submission,
+    )
+    .unwrap_err();
+    assert!(matches!(err, WorkerError::SubmissionLayout(_)), "{err}");
+
+    // requests without the check are resolved with a zero partial commitment
+    let mut request = partial_commitment_request(&opaque_submission, image_id);
+    request.extraData = extra_data(32, 64).into();
+    assert_eq!(
+        <FixtureFormatter as Risc0ProofFormatter>::compute_partial_commitment(
+            &request,
+            &opaque_submission,
+        )
+        .unwrap(),
+        B256::ZERO
+    );
+}
+
+#[test]
+/// Ensures the inputs of an sp1 intent are only accepted within the public values of the
+/// submission, as the inputs of the sp1 examples pointing at the vkey are refused.
+fn should_check_sp1_inputs_layout() {
+    let public_values = vec![1u8, 2, 3, 4];
+    // (bytes32 vkey, bytes publicValues, bytes proofBytes), see `Sp1ProofFormatter`
+    let opaque_submission = DynSolValue::Tuple(vec![
+        DynSolValue::FixedBytes(B256::repeat_byte(0x22), 32),
+        DynSolValue::Bytes(public_values.clone()),
+        DynSolValue::Bytes(vec![9u8; 260]),
+    ])
+    .abi_encode();
+
+    // three head words and the length of the public values precede them
+    <FixtureFormatter as Sp1ProofFormatter>::check_submission_layout(
+        &opaque_submission,
+        &extra_data(128, 4),
+        &public_values,
+    )
+    .unwrap();
+
+    for (offset, length) in [(0, 64), (96, 36), (128, 32)] {
+        let err = <FixtureFormatter as Sp1ProofFormatter>::check_submission_layout(
+            &opaque_submission,
+            &extra_data(offset, length),
+            &public_values,
+        )
+        .unwrap_err();
+        assert!(matches!(err, WorkerError::SubmissionLayout(_)), "{err}");
+    }
+
+    // intents without inputs commitment aren't checked
+    <FixtureFormatter as Sp1ProofFormatter>::check_submission_layout(
+        &opaque_submission,
+        &extra_data(0, 0),
+        &public_values,
+    )
+    .unwrap();
+}
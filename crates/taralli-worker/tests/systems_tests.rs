@@ -0,0 +1,112 @@
+use std::io::Write;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use taralli_client::config::ProviderStreamingConfigFile;
+use taralli_client::error::{ClientError, Result as ClientResult};
+use taralli_client::worker::{ComputeWorker, WorkResult};
+use taralli_primitives::alloy::primitives::{Bytes, FixedBytes};
+use taralli_primitives::intents::request::ComputeRequest;
+use taralli_primitives::systems::{SystemId, SystemParams};
+use taralli_worker::error::WorkerError;
+use taralli_worker::systems::{compiled_systems, ensure_compiled, feature, local_worker};
+
+struct MockWorker;
+
+#[async_trait]
+impl ComputeWorker<ComputeRequest<SystemParams>> for MockWorker {
+    async fn execute(&self, _intent: &ComputeRequest<SystemParams>) -> ClientResult<WorkResult> {
+        Ok(WorkResult {
+            opaque_submission: Bytes::from_static(b"proof"),
+            partial_commitment: FixedBytes::ZERO,
+        })
+    }
+}
+
+/// Features enabled for this build, as cargo sees them
+fn enabled(system_id: SystemId) -> bool {
+    match system_id {
+        SystemId::Arkworks => cfg!(feature = "arkworks"),
+        SystemId::Risc0 => cfg!(feature = "risc0"),
+        SystemId::Sp1 => cfg!(feature = "sp1"),
+    }
+}
+
+#[test]
+/// Ensures the systems reported as compiled in follow the enabled features, and no local worker
+/// is built for the others.
+fn should_report_systems_not_compiled_in() {
+    let expected: Vec<SystemId> = SystemId::all()
+        .into_iter()
+        .filter(|system_id| enabled(*system_id))
+        .collect();
+    assert_eq!(compiled_systems(), expected);
+
+    for system_id in SystemId::all() {
+        assert_eq!(feature(system_id), system_id.as_str());
+        if enabled(system_id) {
+            ensure_compiled(system_id).unwrap();
+            continue;
+        }
+        for err in [
+            ensure_compiled(system_id).unwrap_err(),
+            local_worker::<ComputeRequest<SystemParams>>(system_id)
+                .err()
+                .expect("worker built for a system not compiled in"),
+        ] {
+            assert!(
+                matches!(
+                    err,
+                    WorkerError::SystemNotCompiled { system_id: id, feature }
+                        if id == system_id && feature == system_id.as_str()
+                ),
+                "{err}"
+            );
+        }
+    }
+}
+
+#[test]
+/// Ensures a provider config listing a system whose feature isn't compiled in fails to load with
+/// an error naming the feature.
+fn should_reject_config_of_system_not_compiled_in() {
+    let mut file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    write!(
+        file,
+        r#"
+supported_systems = ["arkworks", "risc0", "sp1"]
+
+[validation_config]
+maximum_allowed_stake = 1000
+
+[validation_config.base]
+minimum_proving_time = 30
+maximum_start_delay = 300
+supported_systems = ["arkworks", "risc0", "sp1"]
+"#
+    )
+    .unwrap();
+    let config = ProviderStreamingConfigFile::from_file(file.path().to_str().unwrap()).unwrap();
+
+    let result = config.to_runtime_config::<ComputeRequest<SystemParams>>(|system_id| {
+        ensure_compiled(system_id)?;
+        Ok(Arc::new(MockWorker))
+    });
+    match SystemId::all()
+        .into_iter()
+        .find(|system_id| !enabled(*system_id))
+    {
+        None => {
+            result.unwrap();
+        }
+        Some(missing) => {
+            let err = result.unwrap_err();
+            assert!(matches!(err, ClientError::ConfigError(_)), "{err}");
+            assert!(
+                err.to_string()
+                    .contains(&format!("with the `{}` feature", missing.as_str())),
+                "{err}"
+            );
+        }
+    }
+}